@@ -38,4 +38,5 @@ mod manager;
 mod ocr_adapter;
 mod whisper;
 
+pub use backend::AnalysisBackend;
 pub use manager::AnalysisManager;
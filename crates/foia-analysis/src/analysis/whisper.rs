@@ -132,11 +132,12 @@ impl AnalysisBackend for WhisperBackend {
         // Create temp directory for output
         let temp_dir = tempfile::TempDir::new()?;
 
-        // Build whisper command
+        // Build whisper command. JSON output keeps segment timestamps so
+        // search hits can be aligned to a playback position.
         let mut cmd = Command::new(self.whisper_binary());
         cmd.arg(file_path)
             .args(["--model", &self.config.model])
-            .args(["--output_format", "txt"])
+            .args(["--output_format", "json"])
             .args(["--output_dir", temp_dir.path().to_str().unwrap()]);
 
         if let Some(ref lang) = self.config.language {
@@ -162,38 +163,30 @@ impl AnalysisBackend for WhisperBackend {
             .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("output");
-        let transcript_file = temp_dir.path().join(format!("{}.txt", input_stem));
+        let transcript_file = temp_dir.path().join(format!("{}.json", input_stem));
 
-        let text = if transcript_file.exists() {
-            std::fs::read_to_string(&transcript_file).map_err(|e| {
-                AnalysisError::AnalysisFailed(format!("Failed to read transcript: {}", e))
-            })?
+        let transcript_file = if transcript_file.exists() {
+            Some(transcript_file)
         } else {
-            // Try to find any .txt file in the output directory
-            let mut found_text = None;
-            if let Ok(entries) = std::fs::read_dir(temp_dir.path()) {
-                for entry in entries.flatten() {
-                    if entry
-                        .path()
-                        .extension()
-                        .map(|e| e == "txt")
-                        .unwrap_or(false)
-                    {
-                        if let Ok(content) = std::fs::read_to_string(entry.path()) {
-                            found_text = Some(content);
-                            break;
-                        }
-                    }
-                }
-            }
-            found_text.ok_or_else(|| {
-                AnalysisError::AnalysisFailed("No transcript file found in output".to_string())
-            })?
+            // Try to find any .json file in the output directory
+            std::fs::read_dir(temp_dir.path())
+                .ok()
+                .into_iter()
+                .flat_map(|entries| entries.flatten())
+                .map(|entry| entry.path())
+                .find(|path| path.extension().is_some_and(|e| e == "json"))
         };
+        let transcript_file = transcript_file.ok_or_else(|| {
+            AnalysisError::AnalysisFailed("No transcript file found in output".to_string())
+        })?;
+        let json = std::fs::read_to_string(&transcript_file).map_err(|e| {
+            AnalysisError::AnalysisFailed(format!("Failed to read transcript: {}", e))
+        })?;
 
-        let metadata = serde_json::json!({
-            "language": self.config.language,
-        });
+        let (text, mut metadata) = parse_transcript(&json)?;
+        if metadata.get("language").is_none() {
+            metadata["language"] = serde_json::json!(self.config.language);
+        }
 
         Ok(AnalysisResult {
             text,
@@ -212,6 +205,48 @@ impl AnalysisBackend for WhisperBackend {
     }
 }
 
+/// Parse whisper's JSON output into the transcript text and result metadata.
+///
+/// Metadata holds the detected `language` and the timed `segments`
+/// (`start`/`end` in seconds, `text`), which the web viewer uses to seek
+/// playback to a search hit.
+fn parse_transcript(json: &str) -> Result<(String, serde_json::Value), AnalysisError> {
+    let output: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| AnalysisError::AnalysisFailed(format!("Invalid transcript JSON: {}", e)))?;
+
+    let segments: Vec<serde_json::Value> = output["segments"]
+        .as_array()
+        .map(|segments| {
+            segments
+                .iter()
+                .filter_map(|seg| {
+                    let text = seg["text"].as_str()?.trim();
+                    if text.is_empty() {
+                        return None;
+                    }
+                    Some(serde_json::json!({
+                        "start": seg["start"].as_f64()?,
+                        "end": seg["end"].as_f64()?,
+                        "text": text,
+                    }))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let text = output["text"]
+        .as_str()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let mut metadata = serde_json::json!({ "segments": segments });
+    if let Some(language) = output["language"].as_str() {
+        metadata["language"] = language.into();
+    }
+    Ok((text, metadata))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,4 +267,27 @@ mod tests {
         let backend = WhisperBackend::new();
         assert_eq!(backend.granularity(), AnalysisGranularity::Document);
     }
+
+    #[test]
+    fn test_parse_transcript_segments() {
+        let json = r#"{
+            "text": " Call to order. Roll call.",
+            "language": "en",
+            "segments": [
+                {"id": 0, "start": 0.0, "end": 2.5, "text": " Call to order.", "tokens": [1]},
+                {"id": 1, "start": 2.5, "end": 4.0, "text": "   "},
+                {"id": 2, "start": 4.0, "end": 6.25, "text": " Roll call."}
+            ]
+        }"#;
+
+        let (text, metadata) = parse_transcript(json).unwrap();
+        assert_eq!(text, "Call to order. Roll call.");
+        assert_eq!(metadata["language"], "en");
+
+        let segments = metadata["segments"].as_array().unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1]["start"], 4.0);
+        assert_eq!(segments[1]["text"], "Roll call.");
+        assert!(segments[0].get("tokens").is_none());
+    }
 }
@@ -0,0 +1,641 @@
+//! Arbitration between OCR backends' readings of one page.
+//!
+//! Each reading is scored by how well it agrees with the others (word-level
+//! similarity) and by a dictionary check, and the best one becomes the
+//! page's final text. With three or more readings, words the others
+//! outvote are corrected in it. When the top two score too close to call,
+//! an LLM can be asked to judge. Every decision carries a rationale that is
+//! recorded with the page.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+
+use foia::config::ArbitrationConfig;
+use foia::llm::LlmClient;
+use foia::models::{PageTextDecision, ReadingScore};
+use foia::repository::models::PageOcrResultRecord;
+
+/// Weight of agreement with the other readings in a reading's score; the
+/// rest is the dictionary check.
+const AGREEMENT_WEIGHT: f32 = 0.6;
+
+/// Largest word alignment computed (words in one reading times words in
+/// the other); larger pages are compared as bags of words.
+const MAX_ALIGNMENT_CELLS: usize = 4_000_000;
+
+/// One backend's reading of a page.
+#[derive(Debug, Clone)]
+pub struct Reading {
+    /// ID of the stored OCR result.
+    pub result_id: i64,
+    pub backend: String,
+    pub text: String,
+}
+
+/// Readings with text among a page's stored OCR results.
+pub fn readings_from_results(results: Vec<PageOcrResultRecord>) -> Vec<Reading> {
+    results
+        .into_iter()
+        .filter(|r| r.error_message.is_none())
+        .filter_map(|r| {
+            let text = r.text.filter(|t| !t.trim().is_empty())?;
+            let backend = match r.model {
+                Some(model) if !model.is_empty() => format!("{} ({})", r.backend, model),
+                _ => r.backend,
+            };
+            Some(Reading {
+                result_id: r.id as i64,
+                backend,
+                text,
+            })
+        })
+        .collect()
+}
+
+/// How the final text was arrived at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Only one backend read the page.
+    Single,
+    /// The best-scoring reading, as is.
+    Agreement,
+    /// The best-scoring reading with words corrected by majority vote.
+    Fused,
+    /// The reading an LLM chose between close contenders.
+    LlmJudge,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Agreement => "agreement",
+            Self::Fused => "fused",
+            Self::LlmJudge => "llm_judge",
+        }
+    }
+}
+
+/// The outcome of arbitrating a page's readings.
+#[derive(Debug, Clone)]
+pub struct Decision {
+    pub text: String,
+    pub method: Method,
+    /// Index of the reading the text is based on.
+    pub chosen: usize,
+    /// Scores of every reading, in input order.
+    pub scores: Vec<ReadingScore>,
+    pub rationale: String,
+}
+
+impl Decision {
+    /// The decision as recorded with the page.
+    pub fn to_record(&self, page_id: i64) -> PageTextDecision {
+        PageTextDecision {
+            page_id,
+            method: self.method.as_str().to_string(),
+            backend: self.scores.get(self.chosen).map(|s| s.backend.clone()),
+            scores: self.scores.clone(),
+            rationale: self.rationale.clone(),
+            decided_at: Utc::now(),
+        }
+    }
+}
+
+/// Chooses a page's final text from its readings.
+#[derive(Default)]
+pub struct Arbiter {
+    config: ArbitrationConfig,
+    dictionary: Option<HashSet<String>>,
+    llm: Option<LlmClient>,
+}
+
+impl Arbiter {
+    /// An arbiter for `config`, loading its word list if it names one.
+    pub fn new(config: ArbitrationConfig) -> std::io::Result<Self> {
+        let dictionary = match &config.dictionary {
+            Some(path) => Some(
+                std::fs::read_to_string(path)?
+                    .lines()
+                    .map(|w| w.trim().to_lowercase())
+                    .filter(|w| !w.is_empty())
+                    .collect(),
+            ),
+            None => None,
+        };
+        Ok(Self {
+            config,
+            dictionary,
+            llm: None,
+        })
+    }
+
+    /// Consult `client` when the top readings are too close to call, if
+    /// the config enables the LLM judge.
+    pub fn with_llm(mut self, client: LlmClient) -> Self {
+        if self.config.llm_judge {
+            self.llm = Some(client);
+        }
+        self
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.config.disabled
+    }
+
+    /// Choose the final text, consulting the LLM judge if needed. `None`
+    /// when there are no readings.
+    pub async fn arbitrate(&self, readings: &[Reading]) -> Option<Decision> {
+        let decision = self.decide(readings)?;
+        match &self.llm {
+            Some(llm) if self.is_close(&decision) => {
+                Some(self.judge(llm, readings, decision).await)
+            }
+            _ => Some(decision),
+        }
+    }
+
+    /// Choose the final text by scores and voting alone.
+    pub fn decide(&self, readings: &[Reading]) -> Option<Decision> {
+        let words: Vec<Vec<&str>> = readings
+            .iter()
+            .map(|r| r.text.split_whitespace().collect())
+            .collect();
+        let keys: Vec<Vec<String>> = words
+            .iter()
+            .map(|w| w.iter().map(|t| word_key(t)).collect())
+            .collect();
+
+        let scores: Vec<ReadingScore> = readings
+            .iter()
+            .enumerate()
+            .map(|(i, reading)| {
+                let dictionary = self.dictionary_score(&words[i]);
+                let agreement = (readings.len() > 1).then(|| {
+                    let total: f32 = (0..readings.len())
+                        .filter(|&j| j != i)
+                        .map(|j| similarity(&keys[i], &keys[j]))
+                        .sum();
+                    total / (readings.len() - 1) as f32
+                });
+                let score = match agreement {
+                    Some(a) => AGREEMENT_WEIGHT * a + (1.0 - AGREEMENT_WEIGHT) * dictionary,
+                    None => dictionary,
+                };
+                ReadingScore {
+                    backend: reading.backend.clone(),
+                    agreement,
+                    dictionary,
+                    score: if words[i].is_empty() { 0.0 } else { score },
+                }
+            })
+            .collect();
+
+        let ranked = ranking(&scores, &words);
+        let chosen = *ranked.first()?;
+        let best = &scores[chosen];
+        if readings.len() == 1 {
+            return Some(Decision {
+                text: readings[chosen].text.clone(),
+                method: Method::Single,
+                chosen,
+                rationale: format!(
+                    "Only {} read the page (dictionary {:.2})",
+                    best.backend, best.dictionary
+                ),
+                scores,
+            });
+        }
+
+        let runner_up = &scores[ranked[1]];
+        let mut rationale = format!(
+            "{} scored {:.2} (agreement {:.2}, dictionary {:.2}); next best {} scored {:.2}",
+            best.backend,
+            best.score,
+            best.agreement.unwrap_or_default(),
+            best.dictionary,
+            runner_up.backend,
+            runner_up.score
+        );
+
+        let mut text = readings[chosen].text.clone();
+        let mut method = Method::Agreement;
+        if readings.len() >= 3 && !self.config.no_fusion {
+            let others: Vec<&[String]> = (0..readings.len())
+                .filter(|&j| j != chosen)
+                .map(|j| keys[j].as_slice())
+                .collect();
+            let others_words: Vec<&[&str]> = (0..readings.len())
+                .filter(|&j| j != chosen)
+                .map(|j| words[j].as_slice())
+                .collect();
+            let (fused, corrected) = fuse(
+                &readings[chosen].text,
+                &keys[chosen],
+                &others,
+                &others_words,
+            );
+            if corrected > 0 {
+                text = fused;
+                method = Method::Fused;
+                rationale.push_str(&format!(
+                    "; {} word(s) corrected by majority vote of {} readings",
+                    corrected,
+                    readings.len()
+                ));
+            }
+        }
+
+        Some(Decision {
+            text,
+            method,
+            chosen,
+            scores,
+            rationale,
+        })
+    }
+
+    /// Whether the top two readings score within the judge margin.
+    fn is_close(&self, decision: &Decision) -> bool {
+        let mut scores: Vec<f32> = decision.scores.iter().map(|s| s.score).collect();
+        if scores.len() < 2 {
+            return false;
+        }
+        scores.sort_by(|a, b| b.total_cmp(a));
+        f64::from(scores[0] - scores[1]) < self.config.judge_margin()
+    }
+
+    async fn judge(&self, llm: &LlmClient, readings: &[Reading], decision: Decision) -> Decision {
+        let texts: Vec<&str> = readings.iter().map(|r| r.text.as_str()).collect();
+        match llm.judge_ocr(&texts).await {
+            Ok((chosen, reason)) => Decision {
+                text: readings[chosen].text.clone(),
+                method: Method::LlmJudge,
+                chosen,
+                rationale: format!(
+                    "Top scores within {:.2} ({}); LLM chose {}: {}",
+                    self.config.judge_margin(),
+                    decision.rationale,
+                    readings[chosen].backend,
+                    reason
+                ),
+                scores: decision.scores,
+            },
+            Err(e) => {
+                tracing::warn!("LLM judge failed, keeping scored choice: {}", e);
+                decision
+            }
+        }
+    }
+
+    /// Share of a reading's words that pass the dictionary check.
+    fn dictionary_score(&self, words: &[&str]) -> f32 {
+        let checked: Vec<(&str, String)> = words
+            .iter()
+            .map(|w| (*w, word_key(w)))
+            .filter(|(_, k)| k.chars().count() >= 2 && k.chars().all(char::is_alphabetic))
+            .collect();
+        if checked.is_empty() {
+            return 0.0;
+        }
+        let passed = match &self.dictionary {
+            Some(dictionary) => checked
+                .iter()
+                .filter(|(_, k)| dictionary.contains(k))
+                .count(),
+            None => checked.iter().filter(|(w, _)| looks_like_word(w)).count(),
+        };
+        passed as f32 / checked.len() as f32
+    }
+}
+
+/// Reading indices, best first: by score, then by length.
+fn ranking(scores: &[ReadingScore], words: &[Vec<&str>]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| {
+        scores[b]
+            .score
+            .total_cmp(&scores[a].score)
+            .then(words[b].len().cmp(&words[a].len()))
+    });
+    order
+}
+
+/// A word as compared: lowercase, without surrounding punctuation.
+fn word_key(token: &str) -> String {
+    let trimmed = token.trim_matches(|c: char| !c.is_alphanumeric());
+    if trimmed.is_empty() {
+        token.to_string()
+    } else {
+        trimmed.to_lowercase()
+    }
+}
+
+/// Whether a token is shaped like a word, for pages without a word list:
+/// a vowel, no long consonant runs or repeated letters, and no capitals
+/// after lowercase letters. Non-Latin words pass.
+fn looks_like_word(token: &str) -> bool {
+    let letters: String = token.chars().filter(|c| c.is_alphabetic()).collect();
+    if !letters.is_ascii() {
+        return true;
+    }
+    let lower = letters.to_ascii_lowercase();
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    if !lower.chars().any(is_vowel) {
+        return false;
+    }
+    let mut consonants = 0;
+    let mut repeats = 0;
+    let mut previous = None;
+    for c in lower.chars() {
+        consonants = if is_vowel(c) { 0 } else { consonants + 1 };
+        repeats = if previous == Some(c) { repeats + 1 } else { 0 };
+        if consonants > 4 || repeats >= 2 {
+            return false;
+        }
+        previous = Some(c);
+    }
+    // "tHe", "reCord": a capital after a lowercase letter
+    !letters
+        .chars()
+        .zip(letters.chars().skip(1))
+        .any(|(a, b)| a.is_lowercase() && b.is_uppercase())
+}
+
+/// Word-level similarity of two readings (0-1): twice the longest common
+/// subsequence over the total length.
+fn similarity(a: &[String], b: &[String]) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let common = if a.len() * b.len() <= MAX_ALIGNMENT_CELLS {
+        lcs_len(a, b)
+    } else {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for w in a {
+            *counts.entry(w.as_str()).or_default() += 1;
+        }
+        b.iter()
+            .filter(|w| match counts.get_mut(w.as_str()) {
+                Some(n) if *n > 0 => {
+                    *n -= 1;
+                    true
+                }
+                _ => false,
+            })
+            .count()
+    };
+    2.0 * common as f32 / (a.len() + b.len()) as f32
+}
+
+fn lcs_len(a: &[String], b: &[String]) -> usize {
+    let mut previous = vec![0usize; b.len() + 1];
+    let mut current = vec![0usize; b.len() + 1];
+    for x in a {
+        for (j, y) in b.iter().enumerate() {
+            current[j + 1] = if x == y {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// For each word of `a`, the aligned word of `b`: matched by the longest
+/// common subsequence, or paired by position between matches where both
+/// sides have the same number of unmatched words.
+fn align(a: &[String], b: &[String]) -> Option<Vec<Option<usize>>> {
+    if a.len() * b.len() > MAX_ALIGNMENT_CELLS {
+        return None;
+    }
+    let width = b.len() + 1;
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i * width + j] = if a[i] == b[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    let mut aligned = vec![None; a.len()];
+    let (mut gap_a, mut gap_b) = (0, 0);
+    for (i, j) in matches
+        .into_iter()
+        .chain(std::iter::once((a.len(), b.len())))
+    {
+        if i - gap_a == j - gap_b {
+            for k in 0..(i - gap_a) {
+                aligned[gap_a + k] = Some(gap_b + k);
+            }
+        }
+        if i < a.len() {
+            aligned[i] = Some(j);
+        }
+        (gap_a, gap_b) = (i + 1, j + 1);
+    }
+    Some(aligned)
+}
+
+/// Correct words of `pivot` that the other readings outvote. Returns the
+/// fused text, with the pivot's spacing and line breaks, and how many
+/// words changed.
+fn fuse(
+    pivot: &str,
+    pivot_keys: &[String],
+    others: &[&[String]],
+    others_words: &[&[&str]],
+) -> (String, usize) {
+    let alignments: Vec<Option<Vec<Option<usize>>>> =
+        others.iter().map(|keys| align(pivot_keys, keys)).collect();
+
+    let mut fused = String::with_capacity(pivot.len());
+    let mut corrected = 0;
+    let mut last = 0;
+    for (i, token) in pivot.split_whitespace().enumerate() {
+        let start = token.as_ptr() as usize - pivot.as_ptr() as usize;
+        fused.push_str(&pivot[last..start]);
+        last = start + token.len();
+
+        // Votes per word: count and a spelling to use
+        let mut votes: HashMap<&str, (usize, &str)> = HashMap::new();
+        votes.insert(&pivot_keys[i], (1, token));
+        for (k, alignment) in alignments.iter().enumerate() {
+            let Some(j) = alignment.as_ref().and_then(|a| a[i]) else {
+                continue;
+            };
+            let entry = votes
+                .entry(&others[k][j])
+                .or_insert((0, others_words[k][j]));
+            entry.0 += 1;
+        }
+        let own = votes[pivot_keys[i].as_str()].0;
+        let winner = votes
+            .iter()
+            .filter(|(key, _)| **key != pivot_keys[i])
+            .max_by_key(|(_, (count, _))| *count)
+            .filter(|(_, (count, _))| *count > own);
+        match winner {
+            Some((_, (_, spelling))) => {
+                fused.push_str(spelling);
+                corrected += 1;
+            }
+            None => fused.push_str(token),
+        }
+    }
+    fused.push_str(&pivot[last..]);
+    (fused, corrected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(id: i64, backend: &str, text: &str) -> Reading {
+        Reading {
+            result_id: id,
+            backend: backend.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_agreement_and_dictionary_pick_the_best_reading() {
+        let arbiter = Arbiter::default();
+        let readings = vec![
+            reading(
+                1,
+                "tesseract",
+                "The memorandum was sent to the field office in March.",
+            ),
+            reading(
+                2,
+                "ocrs",
+                "Tbe rnemorandvm wqs sxnt tx thx fleld offlce ln Mxrch.",
+            ),
+            reading(
+                3,
+                "groq",
+                "The memorandum was sent to the field office in March",
+            ),
+        ];
+        let decision = arbiter.decide(&readings).unwrap();
+        assert_ne!(decision.chosen, 1);
+        assert_eq!(decision.method, Method::Agreement);
+        assert!(decision.scores[1].score < decision.scores[0].score);
+        assert!(decision.scores[1].dictionary < decision.scores[0].dictionary);
+        assert!(decision.rationale.contains("next best"));
+
+        let single = arbiter.decide(&readings[..1]).unwrap();
+        assert_eq!(single.method, Method::Single);
+        assert_eq!(single.scores[0].agreement, None);
+        assert!(arbiter.decide(&[]).is_none());
+    }
+
+    #[test]
+    fn test_majority_vote_corrects_words() {
+        let arbiter = Arbiter::default();
+        let readings = vec![
+            reading(
+                1,
+                "tesseract",
+                "Agent Smith met the inforrnant\non 12 June 1962.",
+            ),
+            reading(2, "ocrs", "Agent Smith met the informant on 12 Juno 1962."),
+            reading(3, "groq", "Agent Smlth met the informant on 12 June 1962."),
+        ];
+        // Each reading has one slip the other two outvote
+        let decision = arbiter.decide(&readings).unwrap();
+        assert_eq!(decision.method, Method::Fused);
+        assert_eq!(decision.chosen, 0);
+        assert_eq!(
+            decision.text,
+            "Agent Smith met the informant\non 12 June 1962."
+        );
+
+        let unfused = Arbiter::new(ArbitrationConfig {
+            no_fusion: true,
+            ..Default::default()
+        })
+        .unwrap()
+        .decide(&readings)
+        .unwrap();
+        assert_eq!(unfused.method, Method::Agreement);
+        assert_eq!(unfused.text, readings[unfused.chosen].text);
+    }
+
+    #[test]
+    fn test_fuse_keeps_pivot_layout() {
+        let keys = |t: &str| t.split_whitespace().map(word_key).collect::<Vec<_>>();
+        let pivot = "SECRET\n\nThe  directive,  dated 1961.";
+        let a = "SECRET The directive, dated 1961.";
+        let b = "SECRFT The directlve, dated 1961.";
+        let (a_keys, b_keys) = (keys(a), keys(b));
+        let a_words: Vec<&str> = a.split_whitespace().collect();
+        let b_words: Vec<&str> = b.split_whitespace().collect();
+        let (fused, corrected) = fuse(
+            pivot,
+            &keys(pivot),
+            &[&a_keys, &b_keys],
+            &[&a_words, &b_words],
+        );
+        assert_eq!(corrected, 0);
+        assert_eq!(fused, pivot);
+
+        let pivot = "SECRFT\nThe directive";
+        let c = "SECRET The directive";
+        let c_keys = keys(c);
+        let c_words: Vec<&str> = c.split_whitespace().collect();
+        let (fused, corrected) = fuse(
+            pivot,
+            &keys(pivot),
+            &[&c_keys, &c_keys],
+            &[&c_words, &c_words],
+        );
+        assert_eq!(corrected, 1);
+        assert_eq!(fused, "SECRET\nThe directive");
+    }
+
+    #[test]
+    fn test_dictionary_check() {
+        assert!(looks_like_word("Memorandum"));
+        assert!(looks_like_word("FBI's"));
+        assert!(!looks_like_word("rnxqt"));
+        assert!(!looks_like_word("tHe"));
+        assert!(!looks_like_word("aaa"));
+        assert!(looks_like_word("Санкт"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("words.txt");
+        std::fs::write(&path, "the\nfield\noffice\n").unwrap();
+        let arbiter = Arbiter::new(ArbitrationConfig {
+            dictionary: Some(path.to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            arbiter.dictionary_score(&["The", "field", "offise"]),
+            2.0 / 3.0
+        );
+    }
+}
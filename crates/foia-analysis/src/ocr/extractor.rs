@@ -7,10 +7,12 @@ use std::process::Command;
 use tempfile::TempDir;
 use thiserror::Error;
 
+use super::legacy::ConvertedContent;
 use super::model_utils::check_binary;
+use super::readability::html_main_text;
 
 /// Handle command output, extracting stdout on success or returning appropriate error.
-fn handle_cmd_output(
+pub(super) fn handle_cmd_output(
     result: std::io::Result<std::process::Output>,
     tool_name: &str,
     error_prefix: &str,
@@ -134,7 +136,7 @@ impl TextExtractor {
             "image/png" | "image/jpeg" | "image/tiff" | "image/gif" | "image/bmp" => {
                 self.extract_image(file_path)
             }
-            "text/plain" | "text/html" => {
+            "text/plain" | "text/csv" | "text/tab-separated-values" => {
                 // Read directly
                 let text = std::fs::read_to_string(file_path)?;
                 Ok(ExtractionResult {
@@ -143,6 +145,56 @@ impl TextExtractor {
                     page_count: None,
                 })
             }
+            "text/html" => {
+                // Keep the main content; the stored HTML is left as fetched
+                let raw = std::fs::read(file_path)?;
+                Ok(ExtractionResult {
+                    text: html_main_text(&String::from_utf8_lossy(&raw)),
+                    method: ExtractionMethod::PdfToText, // Not really, but direct read
+                    page_count: None,
+                })
+            }
+            "multipart/related" | "application/x-mimearchive" => {
+                // MHTML page capture: keep the main document, not its resources
+                let raw = std::fs::read(file_path)?;
+                Ok(ExtractionResult {
+                    text: mhtml_to_text(&raw),
+                    method: ExtractionMethod::PdfToText, // Not really, but direct read
+                    page_count: None,
+                })
+            }
+            "application/json" => {
+                // Flatten to "path: value" lines so field names are searchable
+                let raw = std::fs::read_to_string(file_path)?;
+                let text = match serde_json::from_str::<serde_json::Value>(&raw) {
+                    Ok(value) => json_to_text(&value),
+                    Err(_) => raw,
+                };
+                Ok(ExtractionResult {
+                    text,
+                    method: ExtractionMethod::PdfToText, // Not really, but direct read
+                    page_count: None,
+                })
+            }
+            mime if super::legacy::is_legacy_mimetype(mime) => {
+                let conversion = super::legacy::convert_legacy(file_path, mime)?;
+                match conversion.content {
+                    ConvertedContent::Pdf { ref path, .. } => self.extract_pdf(path),
+                    ConvertedContent::Text(text) => Ok(ExtractionResult {
+                        text,
+                        method: ExtractionMethod::PdfToText, // Not really, but direct read
+                        page_count: None,
+                    }),
+                }
+            }
+            mime if super::office::is_office_mimetype(mime) => {
+                let pages = super::office::extract_office_pages(file_path, mime)?;
+                Ok(ExtractionResult {
+                    page_count: Some(pages.len() as u32),
+                    text: pages.join("\n\n"),
+                    method: ExtractionMethod::PdfToText, // Not really, but direct read
+                })
+            }
             _ => Err(ExtractionError::UnsupportedFileType(mime_type.to_string())),
         }
     }
@@ -575,10 +627,108 @@ impl TextExtractor {
     }
 }
 
+/// Render a JSON document as one `path: value` line per scalar value.
+fn json_to_text(value: &serde_json::Value) -> String {
+    fn walk(value: &serde_json::Value, path: &str, out: &mut String) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map {
+                    let child_path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    walk(child, &child_path, out);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    walk(item, path, out);
+                }
+            }
+            serde_json::Value::Null => {}
+            serde_json::Value::String(s) if s.trim().is_empty() => {}
+            serde_json::Value::String(s) => push_line(out, path, s.trim()),
+            other => push_line(out, path, &other.to_string()),
+        }
+    }
+    fn push_line(out: &mut String, path: &str, value: &str) {
+        if !path.is_empty() {
+            out.push_str(path);
+            out.push_str(": ");
+        }
+        out.push_str(value);
+        out.push('\n');
+    }
+
+    let mut out = String::new();
+    walk(value, "", &mut out);
+    out
+}
+
+/// Text of the main document in an MHTML archive.
+///
+/// The first part is the captured page; images and stylesheets that follow
+/// are ignored. Falls back to the raw bytes if the archive doesn't parse.
+fn mhtml_to_text(raw: &[u8]) -> String {
+    mail_parser::MessageParser::default()
+        .parse(raw)
+        .and_then(|message| message.body_text(0).map(|text| text.into_owned()))
+        .unwrap_or_else(|| String::from_utf8_lossy(raw).into_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_mhtml_to_text() {
+        let raw = concat!(
+            "From: <Saved by Blink>\r\n",
+            "Subject: Case status\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/related; type=\"text/html\"; boundary=\"b\"\r\n",
+            "\r\n",
+            "--b\r\n",
+            "Content-Type: text/html; charset=utf-8\r\n",
+            "Content-Location: https://portal.agency.gov/case/42\r\n",
+            "\r\n",
+            "<html><body><p>Request 42 closed</p></body></html>\r\n",
+            "--b\r\n",
+            "Content-Type: text/css\r\n",
+            "Content-Location: https://portal.agency.gov/site.css\r\n",
+            "\r\n",
+            "body { color: red }\r\n",
+            "--b--\r\n",
+        );
+        let text = mhtml_to_text(raw.as_bytes());
+        assert!(text.contains("Request 42 closed"));
+        assert!(!text.contains("color: red"));
+    }
+
+    #[test]
+    fn test_json_to_text() {
+        let value = serde_json::json!({
+            "title": "Meeting minutes",
+            "agency": {"name": "EPA", "id": 12},
+            "topics": ["water", "air"],
+            "withdrawn": null,
+        });
+        let text = json_to_text(&value);
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines.sort_unstable();
+        assert_eq!(
+            lines,
+            vec![
+                "agency.id: 12",
+                "agency.name: EPA",
+                "title: Meeting minutes",
+                "topics: air",
+                "topics: water",
+            ]
+        );
+    }
+
     #[test]
     fn test_check_tools() {
         let tools = TextExtractor::check_tools();
@@ -636,7 +786,11 @@ mod tests {
         }
 
         let dir = tempfile::TempDir::new().unwrap();
-        let page_contents = ["Page one content here", "Page two content here", "Page three final"];
+        let page_contents = [
+            "Page one content here",
+            "Page two content here",
+            "Page three final",
+        ];
         let pdf_path = create_test_pdf(dir.path(), &page_contents);
 
         let extractor = TextExtractor::new();
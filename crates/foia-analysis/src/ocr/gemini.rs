@@ -90,7 +90,7 @@ impl GeminiBackend {
     pub fn new() -> Self {
         Self {
             config: BackendConfig::new(),
-            api_key: std::env::var("GEMINI_API_KEY").ok(),
+            api_key: foia::secrets::env_var("GEMINI_API_KEY"),
             model: "gemini-1.5-flash".to_string(),
         }
     }
@@ -99,7 +99,7 @@ impl GeminiBackend {
     pub fn with_config(config: OcrConfig) -> Self {
         Self {
             config: BackendConfig::with_config(config),
-            api_key: std::env::var("GEMINI_API_KEY").ok(),
+            api_key: foia::secrets::env_var("GEMINI_API_KEY"),
             model: "gemini-1.5-flash".to_string(),
         }
     }
@@ -108,7 +108,7 @@ impl GeminiBackend {
     pub fn from_backend_config(config: BackendConfig) -> Self {
         Self {
             config,
-            api_key: std::env::var("GEMINI_API_KEY").ok(),
+            api_key: foia::secrets::env_var("GEMINI_API_KEY"),
             model: "gemini-1.5-flash".to_string(),
         }
     }
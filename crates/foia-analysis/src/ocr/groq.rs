@@ -89,7 +89,7 @@ impl GroqBackend {
     pub fn new() -> Self {
         Self {
             config: BackendConfig::new(),
-            api_key: std::env::var("GROQ_API_KEY").ok(),
+            api_key: foia::secrets::env_var("GROQ_API_KEY"),
             model: resolve_model(),
         }
     }
@@ -98,7 +98,7 @@ impl GroqBackend {
     pub fn with_config(config: OcrConfig) -> Self {
         Self {
             config: BackendConfig::with_config(config),
-            api_key: std::env::var("GROQ_API_KEY").ok(),
+            api_key: foia::secrets::env_var("GROQ_API_KEY"),
             model: resolve_model(),
         }
     }
@@ -107,7 +107,7 @@ impl GroqBackend {
     pub fn from_backend_config(config: BackendConfig) -> Self {
         Self {
             config,
-            api_key: std::env::var("GROQ_API_KEY").ok(),
+            api_key: foia::secrets::env_var("GROQ_API_KEY"),
             model: resolve_model(),
         }
     }
@@ -0,0 +1,247 @@
+//! Conversion of legacy word processing formats.
+//!
+//! Word 97-2003 (.doc), WordPerfect (.wpd) and RTF files have no native
+//! parser, so they are converted with external tools. LibreOffice is
+//! preferred and produces a PDF for the standard per-page pipeline; without
+//! it, antiword, wpd2text or unrtf produce plain text.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tempfile::TempDir;
+
+use super::extractor::{handle_cmd_output, ExtractionError};
+use super::model_utils::check_binary;
+
+pub const DOC: &str = "application/msword";
+pub const RTF: &str = "application/rtf";
+pub const WPD: &str = "application/vnd.wordperfect";
+
+/// Alternate MIME types servers send for the same formats.
+const RTF_ALIASES: &[&str] = &["text/rtf", "application/x-rtf"];
+const WPD_ALIASES: &[&str] = &["application/wordperfect", "application/x-wordperfect"];
+
+/// Whether a MIME type is a legacy format converted here.
+pub fn is_legacy_mimetype(mime_type: &str) -> bool {
+    canonical(mime_type).is_some()
+}
+
+fn canonical(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        DOC => Some(DOC),
+        RTF => Some(RTF),
+        WPD => Some(WPD),
+        m if RTF_ALIASES.contains(&m) => Some(RTF),
+        m if WPD_ALIASES.contains(&m) => Some(WPD),
+        _ => None,
+    }
+}
+
+/// External tool used to convert a legacy document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyConverter {
+    /// `soffice --headless --convert-to pdf`, for all legacy formats.
+    LibreOffice,
+    /// antiword, for Word 97-2003 documents.
+    Antiword,
+    /// wpd2text from libwpd-tools, for WordPerfect documents.
+    Wpd2Text,
+    /// unrtf, for RTF documents.
+    Unrtf,
+}
+
+impl LegacyConverter {
+    const ALL: [LegacyConverter; 4] = [
+        LegacyConverter::LibreOffice,
+        LegacyConverter::Antiword,
+        LegacyConverter::Wpd2Text,
+        LegacyConverter::Unrtf,
+    ];
+
+    /// Name recorded in conversion lineage.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LibreOffice => "libreoffice",
+            Self::Antiword => "antiword",
+            Self::Wpd2Text => "wpd2text",
+            Self::Unrtf => "unrtf",
+        }
+    }
+
+    /// Executable looked up on `PATH`.
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Self::LibreOffice => "soffice",
+            Self::Antiword => "antiword",
+            Self::Wpd2Text => "wpd2text",
+            Self::Unrtf => "unrtf",
+        }
+    }
+
+    fn handles(&self, mime_type: &str) -> bool {
+        match self {
+            Self::LibreOffice => true,
+            Self::Antiword => mime_type == DOC,
+            Self::Wpd2Text => mime_type == WPD,
+            Self::Unrtf => mime_type == RTF,
+        }
+    }
+
+    /// First installed converter for a MIME type, LibreOffice preferred.
+    pub fn for_mimetype(mime_type: &str) -> Option<Self> {
+        let mime_type = canonical(mime_type)?;
+        Self::ALL
+            .into_iter()
+            .find(|c| c.handles(mime_type) && check_binary(c.binary()))
+    }
+
+    /// Availability of every converter, for `analyze-check`.
+    pub fn check_tools() -> Vec<(String, bool)> {
+        Self::ALL
+            .iter()
+            .map(|c| (c.binary().to_string(), check_binary(c.binary())))
+            .collect()
+    }
+}
+
+/// Output of a conversion.
+pub enum ConvertedContent {
+    /// A PDF in a temporary directory, removed when this is dropped.
+    Pdf { dir: TempDir, path: PathBuf },
+    /// Plain text.
+    Text(String),
+}
+
+/// A converted legacy document.
+pub struct Conversion {
+    pub converter: LegacyConverter,
+    pub source_mime: &'static str,
+    pub content: ConvertedContent,
+}
+
+impl Conversion {
+    /// Lineage metadata stored with the extraction result.
+    pub fn lineage(&self) -> serde_json::Value {
+        let output = match self.content {
+            ConvertedContent::Pdf { .. } => "application/pdf",
+            ConvertedContent::Text(_) => "text/plain",
+        };
+        serde_json::json!({
+            "converted_from": self.source_mime,
+            "converted_to": output,
+            "converter": self.converter.name(),
+        })
+    }
+}
+
+/// Convert a legacy document with the best available tool.
+pub fn convert_legacy(path: &Path, mime_type: &str) -> Result<Conversion, ExtractionError> {
+    let source_mime = canonical(mime_type)
+        .ok_or_else(|| ExtractionError::UnsupportedFileType(mime_type.to_string()))?;
+    let converter = LegacyConverter::for_mimetype(source_mime).ok_or_else(|| {
+        ExtractionError::ToolNotFound(match source_mime {
+            DOC => "soffice or antiword".to_string(),
+            WPD => "soffice or wpd2text".to_string(),
+            _ => "soffice or unrtf".to_string(),
+        })
+    })?;
+
+    let content = match converter {
+        LegacyConverter::LibreOffice => convert_to_pdf(path, source_mime)?,
+        LegacyConverter::Unrtf => {
+            let output = Command::new("unrtf").arg("--text").arg(path).output();
+            ConvertedContent::Text(strip_unrtf_header(&handle_cmd_output(
+                output,
+                "unrtf",
+                "unrtf failed",
+            )?))
+        }
+        other => {
+            let output = Command::new(other.binary()).arg(path).output();
+            ConvertedContent::Text(handle_cmd_output(
+                output,
+                other.binary(),
+                &format!("{} failed", other.binary()),
+            )?)
+        }
+    };
+
+    Ok(Conversion {
+        converter,
+        source_mime,
+        content,
+    })
+}
+
+fn convert_to_pdf(path: &Path, mime_type: &str) -> Result<ConvertedContent, ExtractionError> {
+    let dir = TempDir::new()?;
+    // LibreOffice picks its import filter by extension, and stored files may
+    // not have one
+    let extension = match mime_type {
+        DOC => "doc",
+        WPD => "wpd",
+        _ => "rtf",
+    };
+    let input = dir.path().join(format!("input.{}", extension));
+    std::fs::copy(path, &input)?;
+
+    // A private profile lets concurrent workers run their own instances
+    let profile = format!(
+        "-env:UserInstallation=file://{}",
+        dir.path().join("profile").display()
+    );
+    let output = Command::new("soffice")
+        .arg(&profile)
+        .args(["--headless", "--convert-to", "pdf", "--outdir"])
+        .arg(dir.path())
+        .arg(&input)
+        .output();
+    handle_cmd_output(output, "soffice", "LibreOffice conversion failed")?;
+
+    let pdf = dir.path().join("input.pdf");
+    if !pdf.exists() {
+        return Err(ExtractionError::ExtractionFailed(
+            "LibreOffice produced no PDF".to_string(),
+        ));
+    }
+    Ok(ConvertedContent::Pdf { dir, path: pdf })
+}
+
+/// Drop the banner unrtf prints before the document text.
+fn strip_unrtf_header(output: &str) -> String {
+    let mut lines = output.lines().peekable();
+    while lines
+        .peek()
+        .is_some_and(|l| l.starts_with("###") || (!l.is_empty() && l.chars().all(|c| c == '-')))
+    {
+        lines.next();
+    }
+    lines.collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_mimetypes() {
+        assert!(is_legacy_mimetype("application/msword"));
+        assert!(is_legacy_mimetype("text/rtf"));
+        assert!(is_legacy_mimetype("application/wordperfect"));
+        assert!(!is_legacy_mimetype("application/pdf"));
+        assert!(LegacyConverter::Antiword.handles(DOC));
+        assert!(!LegacyConverter::Antiword.handles(RTF));
+    }
+
+    #[test]
+    fn test_strip_unrtf_header() {
+        let output = "###  Translation from RTF performed by UnRTF, version 0.21.10\n\
+                      ### font table contains 2 fonts total\n\
+                      -----------------\n\
+                      MEMORANDUM FOR THE RECORD\n\nSubject: Project files\n";
+        assert_eq!(
+            strip_unrtf_header(output),
+            "MEMORANDUM FOR THE RECORD\n\nSubject: Project files"
+        );
+    }
+}
@@ -12,6 +12,12 @@
 //! Also includes URL extraction from extracted text.
 //! And archive handling for processing files within zip archives.
 //! And email parsing for extracting attachments from RFC822 emails.
+//! And native parsing of office documents (docx, xlsx, pptx, OpenDocument).
+//! And conversion of legacy formats (.doc, .wpd, .rtf) with external tools.
+//! And main-content extraction for HTML, dropping navigation and banners.
+//! And sheet-and-row reading of CSV files and spreadsheets for previews.
+//! And arbitration between backends' readings of a page by agreement and voting.
+//! And image cleanup (rotation, deskew, despeckle, contrast) of pages before OCR.
 //!
 //! ## OCR Backends
 //!
@@ -31,6 +37,7 @@
 #![allow(unused_imports)]
 
 mod api_backend;
+mod arbitration;
 mod archive;
 mod backend;
 mod deepseek;
@@ -39,8 +46,13 @@ mod extractor;
 mod fallback;
 mod gemini;
 mod groq;
+mod legacy;
 mod model_utils;
+mod office;
 mod pdf_utils;
+mod preprocess;
+mod readability;
+mod tabular;
 mod tesseract;
 
 #[cfg(feature = "ocr-ocrs")]
@@ -48,10 +60,16 @@ mod ocrs_backend;
 #[cfg(feature = "ocr-paddle")]
 mod paddle_backend;
 
-pub use archive::ArchiveExtractor;
-pub use email::EmailExtractor;
+pub use arbitration::{readings_from_results, Arbiter, Decision, Method, Reading};
+pub use archive::{ArchiveEntry, ArchiveExtractor};
+pub use email::{EmailAttachment, EmailExtractor};
 pub use extractor::TextExtractor;
 pub use foia::utils::UrlFinder;
+pub use legacy::{convert_legacy, is_legacy_mimetype, ConvertedContent, LegacyConverter};
+pub use office::{extract_office_pages, is_office_mimetype};
+pub use preprocess::{Preprocessed, Preprocessor, Step as PreprocessStep};
+pub use readability::html_main_text;
+pub use tabular::{is_tabular_mimetype, parse_delimited, read_sheets, Sheet};
 
 // OCR backend abstraction for A/B testing and per-source backend selection
 pub use backend::{
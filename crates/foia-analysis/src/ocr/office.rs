@@ -0,0 +1,548 @@
+//! Native text extraction for office documents.
+//!
+//! Word, Excel and PowerPoint files (OOXML) and their OpenDocument
+//! counterparts are zip archives of XML parts, so their text is read
+//! directly without external tools. Spreadsheets produce one page per sheet
+//! and presentations one page per slide; word processing documents are
+//! split at explicit page breaks.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+use zip::ZipArchive;
+
+use super::extractor::ExtractionError;
+use super::tabular::Sheet;
+
+pub const DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+pub const XLSX: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+pub const PPTX: &str = "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+pub const ODT: &str = "application/vnd.oasis.opendocument.text";
+pub const ODS: &str = "application/vnd.oasis.opendocument.spreadsheet";
+pub const ODP: &str = "application/vnd.oasis.opendocument.presentation";
+
+const W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const SML: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const DML: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+const PML: &str = "http://schemas.openxmlformats.org/presentationml/2006/main";
+const REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const ODF_TEXT: &str = "urn:oasis:names:tc:opendocument:xmlns:text:1.0";
+const ODF_TABLE: &str = "urn:oasis:names:tc:opendocument:xmlns:table:1.0";
+const ODF_DRAW: &str = "urn:oasis:names:tc:opendocument:xmlns:drawing:1.0";
+
+/// Repeated empty spreadsheet cells beyond this are not expanded.
+const MAX_REPEATED_CELLS: usize = 256;
+
+/// Whether a MIME type is an office format handled here.
+pub fn is_office_mimetype(mime_type: &str) -> bool {
+    matches!(mime_type, DOCX | XLSX | PPTX | ODT | ODS | ODP)
+}
+
+/// Extract an office document's text, one entry per page, sheet or slide.
+pub fn extract_office_pages(path: &Path, mime_type: &str) -> Result<Vec<String>, ExtractionError> {
+    let mut package = Package::open(path)?;
+    let pages = match mime_type {
+        DOCX => docx_pages(&package.read("word/document.xml")?)?,
+        XLSX => xlsx_pages(&mut package)?,
+        PPTX => pptx_pages(&mut package)?,
+        ODT | ODS | ODP => odf_pages(&package.read("content.xml")?, mime_type)?,
+        _ => return Err(ExtractionError::UnsupportedFileType(mime_type.to_string())),
+    };
+    if pages.is_empty() {
+        return Ok(vec![String::new()]);
+    }
+    Ok(pages)
+}
+
+/// The zip container of an office document.
+struct Package {
+    archive: ZipArchive<File>,
+}
+
+impl Package {
+    fn open(path: &Path) -> Result<Self, ExtractionError> {
+        let archive = ZipArchive::new(File::open(path)?).map_err(failed)?;
+        Ok(Self { archive })
+    }
+
+    fn read(&mut self, name: &str) -> Result<String, ExtractionError> {
+        let mut part = self.archive.by_name(name).map_err(failed)?;
+        let mut xml = String::new();
+        part.read_to_string(&mut xml)?;
+        Ok(xml)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.archive.file_names().map(str::to_string).collect()
+    }
+
+    /// Relationship targets of a part, by relationship ID, resolved to
+    /// package paths.
+    fn relationships(
+        &mut self,
+        dir: &str,
+        part: &str,
+    ) -> Result<Vec<(String, String)>, ExtractionError> {
+        let xml = match self.read(&format!("{}/_rels/{}.rels", dir, part)) {
+            Ok(xml) => xml,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let doc = parse(&xml)?;
+        Ok(doc
+            .descendants()
+            .filter(|n| n.tag_name().name() == "Relationship")
+            .filter_map(|n| {
+                let id = n.attribute("Id")?;
+                let target = n.attribute("Target")?;
+                Some((id.to_string(), resolve_target(dir, target)))
+            })
+            .collect())
+    }
+}
+
+fn failed(e: impl std::fmt::Display) -> ExtractionError {
+    ExtractionError::ExtractionFailed(e.to_string())
+}
+
+fn parse(xml: &str) -> Result<Document<'_>, ExtractionError> {
+    Document::parse(xml).map_err(failed)
+}
+
+fn is(node: &Node, ns: &str, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name && node.tag_name().namespace() == Some(ns)
+}
+
+/// Resolve a relationship target against the directory of its source part.
+fn resolve_target(dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            _ => parts.push(segment),
+        }
+    }
+    parts.join("/")
+}
+
+fn docx_pages(xml: &str) -> Result<Vec<String>, ExtractionError> {
+    let doc = parse(xml)?;
+    let mut pages = vec![String::new()];
+    // Paragraphs nested in text boxes are read with their outer paragraph
+    let paragraphs = doc
+        .descendants()
+        .filter(|n| is(n, W, "p") && !n.ancestors().skip(1).any(|a| is(&a, W, "p")));
+    for paragraph in paragraphs {
+        let mut line = String::new();
+        for node in paragraph.descendants() {
+            if is(&node, W, "t") {
+                line.push_str(node.text().unwrap_or(""));
+            } else if is(&node, W, "tab") {
+                line.push('\t');
+            } else if is(&node, W, "br") && node.attribute((W, "type")) == Some("page") {
+                push_line(pages.last_mut().unwrap(), &line);
+                line.clear();
+                pages.push(String::new());
+            } else if is(&node, W, "br") || is(&node, W, "cr") {
+                line.push('\n');
+            }
+        }
+        push_line(pages.last_mut().unwrap(), &line);
+    }
+    Ok(pages
+        .into_iter()
+        .map(|p| p.trim_end().to_string())
+        .filter(|p| !p.is_empty())
+        .collect())
+}
+
+fn push_line(page: &mut String, line: &str) {
+    page.push_str(line);
+    page.push('\n');
+}
+
+fn xlsx_pages(package: &mut Package) -> Result<Vec<String>, ExtractionError> {
+    Ok(xlsx_sheets(package)?.iter().map(Sheet::page_text).collect())
+}
+
+fn xlsx_sheets(package: &mut Package) -> Result<Vec<Sheet>, ExtractionError> {
+    let shared = match package.read("xl/sharedStrings.xml") {
+        Ok(xml) => shared_strings(&xml)?,
+        Err(_) => Vec::new(),
+    };
+    let rels = package.relationships("xl", "workbook.xml")?;
+    let workbook = package.read("xl/workbook.xml")?;
+    let targets: Vec<(String, String)> = parse(&workbook)?
+        .descendants()
+        .filter(|n| is(n, SML, "sheet"))
+        .filter_map(|n| {
+            let name = n.attribute("name").unwrap_or_default().to_string();
+            let id = n.attribute((REL, "id"))?;
+            let target = rels.iter().find(|(rid, _)| rid == id)?.1.clone();
+            Some((name, target))
+        })
+        .collect();
+
+    let mut sheets = Vec::with_capacity(targets.len());
+    for (name, target) in targets {
+        let xml = package.read(&target)?;
+        let rows = sheet_rows(&xml, &shared)?;
+        sheets.push(Sheet { name, rows });
+    }
+    Ok(sheets)
+}
+
+fn shared_strings(xml: &str) -> Result<Vec<String>, ExtractionError> {
+    let doc = parse(xml)?;
+    Ok(doc
+        .root_element()
+        .children()
+        .filter(|n| is(n, SML, "si"))
+        .map(|si| {
+            si.descendants()
+                .filter(|n| is(n, SML, "t") && !n.ancestors().any(|a| is(&a, SML, "rPh")))
+                .filter_map(|t| t.text())
+                .collect()
+        })
+        .collect())
+}
+
+/// Non-empty rows of a worksheet, cells placed by column.
+fn sheet_rows(xml: &str, shared: &[String]) -> Result<Vec<Vec<String>>, ExtractionError> {
+    let doc = parse(xml)?;
+    let mut rows = Vec::new();
+    for row in doc.descendants().filter(|n| is(n, SML, "row")) {
+        let mut cells: Vec<String> = Vec::new();
+        for cell in row.children().filter(|n| is(n, SML, "c")) {
+            let value = cell_value(&cell, shared);
+            let column = cell.attribute("r").map(column_index).unwrap_or(cells.len());
+            if column >= cells.len() {
+                cells.resize(column.min(cells.len() + MAX_REPEATED_CELLS), String::new());
+            }
+            cells.push(value);
+        }
+        while cells.last().is_some_and(|c| c.is_empty()) {
+            cells.pop();
+        }
+        if !cells.is_empty() {
+            rows.push(cells);
+        }
+    }
+    Ok(rows)
+}
+
+fn cell_value(cell: &Node, shared: &[String]) -> String {
+    let value = || {
+        cell.children()
+            .find(|n| is(n, SML, "v"))
+            .and_then(|v| v.text())
+            .unwrap_or("")
+    };
+    match cell.attribute("t") {
+        Some("s") => value()
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| shared.get(i))
+            .cloned()
+            .unwrap_or_default(),
+        Some("inlineStr") => cell
+            .descendants()
+            .filter(|n| is(n, SML, "t"))
+            .filter_map(|t| t.text())
+            .collect(),
+        Some("b") => match value() {
+            "1" => "TRUE".to_string(),
+            _ => "FALSE".to_string(),
+        },
+        _ => value().to_string(),
+    }
+}
+
+/// Zero-based column of a cell reference like `C12`.
+fn column_index(reference: &str) -> usize {
+    reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .fold(0, |acc, c| {
+            acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1)
+        })
+        .saturating_sub(1)
+}
+
+fn pptx_pages(package: &mut Package) -> Result<Vec<String>, ExtractionError> {
+    let rels = package.relationships("ppt", "presentation.xml")?;
+    let presentation = package.read("ppt/presentation.xml")?;
+    let mut slides: Vec<String> = parse(&presentation)?
+        .descendants()
+        .filter(|n| is(n, PML, "sldId"))
+        .filter_map(|n| {
+            let id = n.attribute((REL, "id"))?;
+            rels.iter()
+                .find(|(rid, _)| rid == id)
+                .map(|(_, target)| target.clone())
+        })
+        .collect();
+
+    // Without a usable slide list, fall back to the slide parts in number order
+    if slides.is_empty() {
+        slides = package
+            .names()
+            .into_iter()
+            .filter(|n| n.starts_with("ppt/slides/slide") && n.ends_with(".xml"))
+            .collect();
+        slides.sort_by_key(|n| {
+            n.trim_start_matches("ppt/slides/slide")
+                .trim_end_matches(".xml")
+                .parse::<u32>()
+                .unwrap_or(u32::MAX)
+        });
+    }
+
+    let mut pages = Vec::with_capacity(slides.len());
+    for slide in slides {
+        let xml = package.read(&slide)?;
+        let doc = parse(&xml)?;
+        let mut text = String::new();
+        for paragraph in doc.descendants().filter(|n| is(n, DML, "p")) {
+            let mut line = String::new();
+            for node in paragraph.descendants() {
+                if is(&node, DML, "t") {
+                    line.push_str(node.text().unwrap_or(""));
+                } else if is(&node, DML, "br") {
+                    line.push('\n');
+                }
+            }
+            if !line.trim().is_empty() {
+                push_line(&mut text, &line);
+            }
+        }
+        pages.push(text.trim_end().to_string());
+    }
+    Ok(pages)
+}
+
+fn odf_pages(xml: &str, mime_type: &str) -> Result<Vec<String>, ExtractionError> {
+    let doc = parse(xml)?;
+    let pages = match mime_type {
+        ODS => ods_sheets(&doc).iter().map(Sheet::page_text).collect(),
+        ODP => doc
+            .descendants()
+            .filter(|n| is(n, ODF_DRAW, "page"))
+            .map(|page| odf_paragraphs(&page))
+            .collect(),
+        _ => vec![odf_paragraphs(&doc.root_element())],
+    };
+    Ok(pages)
+}
+
+/// Text of the outermost paragraphs and headings under a node.
+fn odf_paragraphs(root: &Node) -> String {
+    let is_paragraph = |n: &Node| is(n, ODF_TEXT, "p") || is(n, ODF_TEXT, "h");
+    let mut text = String::new();
+    for paragraph in root
+        .descendants()
+        .filter(|n| is_paragraph(n) && !n.ancestors().skip(1).any(|a| is_paragraph(&a)))
+    {
+        let mut line = String::new();
+        odf_inline_text(&paragraph, &mut line);
+        push_line(&mut text, &line);
+    }
+    text.trim_end().to_string()
+}
+
+fn odf_inline_text(node: &Node, out: &mut String) {
+    for child in node.children() {
+        if child.is_text() {
+            out.push_str(child.text().unwrap_or(""));
+        } else if is(&child, ODF_TEXT, "tab") {
+            out.push('\t');
+        } else if is(&child, ODF_TEXT, "line-break") {
+            out.push('\n');
+        } else if is(&child, ODF_TEXT, "s") {
+            let count = child
+                .attribute((ODF_TEXT, "c"))
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(1usize);
+            out.push_str(&" ".repeat(count.min(MAX_REPEATED_CELLS)));
+        } else if child.is_element() {
+            odf_inline_text(&child, out);
+        }
+    }
+}
+
+fn ods_sheets(doc: &Document) -> Vec<Sheet> {
+    doc.descendants()
+        .filter(|n| is(n, ODF_TABLE, "table"))
+        .map(|table| Sheet {
+            name: table
+                .attribute((ODF_TABLE, "name"))
+                .unwrap_or_default()
+                .to_string(),
+            rows: odf_table_rows(&table),
+        })
+        .collect()
+}
+
+fn odf_table_rows(table: &Node) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    for row in table
+        .descendants()
+        .filter(|n| is(n, ODF_TABLE, "table-row"))
+    {
+        let mut cells: Vec<String> = Vec::new();
+        for cell in row
+            .children()
+            .filter(|n| is(n, ODF_TABLE, "table-cell") || is(n, ODF_TABLE, "covered-table-cell"))
+        {
+            let value = odf_paragraphs(&cell).replace('\n', " ");
+            let repeat = cell
+                .attribute((ODF_TABLE, "number-columns-repeated"))
+                .and_then(|r| r.parse().ok())
+                .unwrap_or(1usize)
+                .min(MAX_REPEATED_CELLS);
+            for _ in 0..repeat {
+                cells.push(value.clone());
+            }
+        }
+        while cells.last().is_some_and(|c| c.is_empty()) {
+            cells.pop();
+        }
+        if !cells.is_empty() {
+            rows.push(cells);
+        }
+    }
+    rows
+}
+
+/// Sheets of an xlsx or ods workbook.
+pub(super) fn read_workbook(path: &Path, mime_type: &str) -> Result<Vec<Sheet>, ExtractionError> {
+    let mut package = Package::open(path)?;
+    match mime_type {
+        XLSX => xlsx_sheets(&mut package),
+        ODS => Ok(ods_sheets(&parse(&package.read("content.xml")?)?)),
+        _ => Err(ExtractionError::UnsupportedFileType(mime_type.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn package(parts: &[(&str, &str)]) -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut zip = zip::ZipWriter::new(file.reopen().unwrap());
+        for (name, content) in parts {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_docx_splits_at_page_breaks() {
+        let document = format!(
+            r#"<w:document xmlns:w="{W}"><w:body>
+                <w:p><w:r><w:t>MEMORANDUM</w:t></w:r></w:p>
+                <w:p><w:r><w:t>To:</w:t><w:tab/><w:t>Director</w:t></w:r></w:p>
+                <w:p><w:r><w:br w:type="page"/><w:t>Attachment A</w:t></w:r></w:p>
+            </w:body></w:document>"#
+        );
+        let file = package(&[("word/document.xml", &document)]);
+        let pages = extract_office_pages(file.path(), DOCX).unwrap();
+        assert_eq!(pages, vec!["MEMORANDUM\nTo:\tDirector", "Attachment A"]);
+    }
+
+    #[test]
+    fn test_xlsx_one_page_per_sheet() {
+        let workbook = format!(
+            r#"<workbook xmlns="{SML}" xmlns:r="{REL}"><sheets>
+                <sheet name="Budget" sheetId="1" r:id="rId1"/>
+                <sheet name="Staff" sheetId="2" r:id="rId2"/>
+            </sheets></workbook>"#
+        );
+        let rels = r#"<Relationships>
+            <Relationship Id="rId1" Target="worksheets/sheet1.xml"/>
+            <Relationship Id="rId2" Target="/xl/worksheets/sheet2.xml"/>
+        </Relationships>"#;
+        let shared =
+            format!(r#"<sst xmlns="{SML}"><si><t>Item</t></si><si><t>Cost</t></si></sst>"#);
+        let sheet1 = format!(
+            r#"<worksheet xmlns="{SML}"><sheetData>
+                <row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="s"><v>1</v></c></row>
+                <row r="2"><c r="A2" t="inlineStr"><is><t>Surveillance</t></is></c><c r="C2"><v>1200</v></c></row>
+            </sheetData></worksheet>"#
+        );
+        let sheet2 = format!(r#"<worksheet xmlns="{SML}"><sheetData/></worksheet>"#);
+        let file = package(&[
+            ("xl/workbook.xml", &workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/sharedStrings.xml", &shared),
+            ("xl/worksheets/sheet1.xml", &sheet1),
+            ("xl/worksheets/sheet2.xml", &sheet2),
+        ]);
+        let pages = extract_office_pages(file.path(), XLSX).unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                "Sheet: Budget\n\nItem\t\tCost\nSurveillance\t\t1200",
+                "Sheet: Staff"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pptx_and_odt() {
+        let presentation = format!(
+            r#"<p:presentation xmlns:p="{PML}" xmlns:r="{REL}"><p:sldIdLst>
+                <p:sldId id="257" r:id="rId3"/><p:sldId id="256" r:id="rId2"/>
+            </p:sldIdLst></p:presentation>"#
+        );
+        let rels = r#"<Relationships>
+            <Relationship Id="rId2" Target="slides/slide1.xml"/>
+            <Relationship Id="rId3" Target="slides/slide2.xml"/>
+        </Relationships>"#;
+        let slide = |text: &str| {
+            format!(
+                r#"<p:sld xmlns:p="{PML}" xmlns:a="{DML}"><a:p><a:r><a:t>{text}</a:t></a:r></a:p></p:sld>"#
+            )
+        };
+        let (first, second) = (slide("Overview"), slide("Timeline"));
+        let file = package(&[
+            ("ppt/presentation.xml", &presentation),
+            ("ppt/_rels/presentation.xml.rels", rels),
+            ("ppt/slides/slide1.xml", &first),
+            ("ppt/slides/slide2.xml", &second),
+        ]);
+        // Slide order follows the presentation, not the part names
+        let pages = extract_office_pages(file.path(), PPTX).unwrap();
+        assert_eq!(pages, vec!["Timeline", "Overview"]);
+
+        let content = format!(
+            r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="{ODF_TEXT}">
+                <office:body><office:text>
+                    <text:h>Findings</text:h>
+                    <text:p>Records<text:s text:c="2"/>were <text:span>withheld</text:span>.</text:p>
+                </office:text></office:body></office:document-content>"#
+        );
+        let file = package(&[("content.xml", &content)]);
+        let pages = extract_office_pages(file.path(), ODT).unwrap();
+        assert_eq!(pages, vec!["Findings\nRecords   were withheld."]);
+    }
+
+    #[test]
+    fn test_column_index() {
+        assert_eq!(column_index("A1"), 0);
+        assert_eq!(column_index("C12"), 2);
+        assert_eq!(column_index("AA3"), 26);
+    }
+}
@@ -0,0 +1,291 @@
+//! Image preprocessing of scanned pages before OCR.
+//!
+//! Sideways and skewed scans make every backend produce garbage, so each
+//! page image is cleaned up once before any backend reads it:
+//! - rotate: upright the page using tesseract's orientation detection
+//! - deskew: straighten a slightly tilted scan
+//! - despeckle: remove scanner noise
+//! - normalize: stretch contrast on faded or gray pages
+//!
+//! The image work is done by ImageMagick; without it pages are OCR'd as
+//! rendered.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::Utc;
+
+use foia::config::PreprocessConfig;
+use foia::models::PagePreprocessing;
+
+use super::backend::OcrError;
+use super::model_utils::check_binary;
+use super::pdf_utils::pdf_page_to_image;
+
+/// Least confidence in tesseract's orientation estimate for a page to be
+/// rotated; pages with little text get low scores.
+const MIN_ORIENTATION_CONFIDENCE: f32 = 2.0;
+
+/// ImageMagick's deskew threshold.
+const DESKEW_THRESHOLD: &str = "40%";
+
+/// A preprocessing step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Rotate,
+    Deskew,
+    Despeckle,
+    Normalize,
+}
+
+impl Step {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "rotate" | "orientation" => Some(Self::Rotate),
+            "deskew" => Some(Self::Deskew),
+            "despeckle" | "denoise" => Some(Self::Despeckle),
+            "normalize" | "contrast" => Some(Self::Normalize),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rotate => "rotate",
+            Self::Deskew => "deskew",
+            Self::Despeckle => "despeckle",
+            Self::Normalize => "normalize",
+        }
+    }
+}
+
+/// A preprocessed page image and what was done to it.
+#[derive(Debug, Clone)]
+pub struct Preprocessed {
+    pub image: PathBuf,
+    /// Clockwise rotation applied, in degrees.
+    pub rotation: i32,
+    /// Skew ImageMagick corrected, in degrees.
+    pub skew_angle: Option<f32>,
+    /// Steps that ran.
+    pub steps: Vec<Step>,
+    pub tool: Option<String>,
+}
+
+impl Preprocessed {
+    /// The record kept with the page.
+    pub fn to_record(&self, page_id: i64) -> PagePreprocessing {
+        PagePreprocessing {
+            page_id,
+            rotation: self.rotation,
+            skew_angle: self.skew_angle,
+            steps: self.steps.iter().map(|s| s.as_str().to_string()).collect(),
+            tool: self.tool.clone(),
+            applied_at: Utc::now(),
+        }
+    }
+}
+
+/// Cleans up page images before OCR.
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    steps: Vec<Step>,
+    /// ImageMagick command ("magick" for v7, "convert" for v6).
+    magick: Option<&'static str>,
+    /// ImageMagick version, recorded with each page.
+    version: Option<String>,
+    /// Whether tesseract is there to detect orientation.
+    tesseract: bool,
+}
+
+impl Preprocessor {
+    /// A preprocessor for `config`, using whichever tools are installed.
+    pub fn new(config: &PreprocessConfig) -> Self {
+        let steps: Vec<Step> = config
+            .steps()
+            .into_iter()
+            .filter_map(|name| {
+                let step = Step::from_str(name);
+                if step.is_none() {
+                    tracing::warn!("Unknown preprocessing step '{}' ignored", name);
+                }
+                step
+            })
+            .collect();
+        if steps.is_empty() {
+            return Self::default();
+        }
+
+        let magick = ["magick", "convert"]
+            .into_iter()
+            .find(|bin| check_binary(bin));
+        if magick.is_none() {
+            tracing::debug!("ImageMagick not found; pages are OCR'd without preprocessing");
+        }
+        let version = magick.and_then(magick_version);
+        Self {
+            steps,
+            magick,
+            version,
+            tesseract: check_binary("tesseract"),
+        }
+    }
+
+    /// Whether any step will run.
+    pub fn is_active(&self) -> bool {
+        self.magick.is_some() && !self.steps.is_empty()
+    }
+
+    /// Whether ImageMagick is installed, for `analyze-check`.
+    pub fn is_available() -> bool {
+        check_binary("magick") || check_binary("convert")
+    }
+
+    /// Render a PDF page and preprocess it into `output_dir`.
+    pub fn process_pdf_page(
+        &self,
+        pdf_path: &Path,
+        page: u32,
+        output_dir: &Path,
+    ) -> Result<Preprocessed, OcrError> {
+        let image = pdf_page_to_image(pdf_path, page, output_dir)?;
+        self.process(&image, output_dir)
+    }
+
+    /// Preprocess `image` into `output_dir`.
+    pub fn process(&self, image: &Path, output_dir: &Path) -> Result<Preprocessed, OcrError> {
+        let magick = self.magick.ok_or_else(|| {
+            OcrError::BackendNotAvailable(
+                "ImageMagick not found. Install imagemagick for page preprocessing".to_string(),
+            )
+        })?;
+
+        let rotation = if self.steps.contains(&Step::Rotate) && self.tesseract {
+            detect_rotation(image)
+        } else {
+            0
+        };
+
+        let output = output_dir.join("preprocessed.png");
+        let mut cmd = Command::new(magick);
+        cmd.arg(image).args(["-background", "white"]);
+        let mut applied = Vec::new();
+        for step in &self.steps {
+            match step {
+                Step::Rotate if rotation != 0 => {
+                    cmd.args(["-rotate", &rotation.to_string(), "+repage"]);
+                }
+                Step::Rotate => continue,
+                Step::Deskew => {
+                    cmd.args(["-deskew", DESKEW_THRESHOLD, "+repage"]);
+                }
+                Step::Despeckle => {
+                    cmd.arg("-despeckle");
+                }
+                Step::Normalize => {
+                    cmd.arg("-normalize");
+                }
+            }
+            applied.push(*step);
+        }
+        if applied.contains(&Step::Deskew) {
+            cmd.args(["-print", "%[deskew:angle]\n"]);
+        }
+        let result = cmd.arg(&output).output()?;
+        if !result.status.success() {
+            return Err(OcrError::OcrFailed(format!(
+                "ImageMagick preprocessing failed: {}",
+                String::from_utf8_lossy(&result.stderr).trim()
+            )));
+        }
+
+        let skew_angle = if applied.contains(&Step::Deskew) {
+            String::from_utf8_lossy(&result.stdout).trim().parse().ok()
+        } else {
+            None
+        };
+        Ok(Preprocessed {
+            image: output,
+            rotation,
+            skew_angle,
+            steps: applied,
+            tool: self.version.clone(),
+        })
+    }
+}
+
+/// First line of `magick -version`, e.g. "ImageMagick 7.1.1-15".
+fn magick_version(bin: &str) -> Option<String> {
+    let output = Command::new(bin).arg("-version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?;
+    let version = line.trim_start_matches("Version:").trim();
+    let mut words = version.split_whitespace();
+    Some(format!("{} {}", words.next()?, words.next()?))
+}
+
+/// Clockwise rotation that uprights the page, from tesseract's orientation
+/// and script detection; 0 when unsure.
+fn detect_rotation(image: &Path) -> i32 {
+    let output = Command::new("tesseract")
+        .arg(image)
+        .args(["stdout", "--psm", "0"])
+        .output();
+    match output {
+        Ok(out) if out.status.success() => {
+            parse_orientation(&String::from_utf8_lossy(&out.stdout)).unwrap_or(0)
+        }
+        // Pages with too little text fail detection; leave them as they are
+        _ => 0,
+    }
+}
+
+/// The rotation from tesseract's `--psm 0` output, if it is confident.
+fn parse_orientation(osd: &str) -> Option<i32> {
+    let field = |name: &str| {
+        osd.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|value| value.trim_start_matches(':').trim())
+    };
+    let rotate: i32 = field("Rotate")?.parse().ok()?;
+    let confidence: f32 = field("Orientation confidence")?.parse().ok()?;
+    (confidence >= MIN_ORIENTATION_CONFIDENCE && rotate.rem_euclid(90) == 0)
+        .then_some(rotate.rem_euclid(360))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_orientation() {
+        let osd = "Page number: 0\nOrientation in degrees: 270\nRotate: 90\n\
+                   Orientation confidence: 4.21\nScript: Latin\nScript confidence: 1.67\n";
+        assert_eq!(parse_orientation(osd), Some(90));
+
+        let unsure = osd.replace("4.21", "0.80");
+        assert_eq!(parse_orientation(&unsure), None);
+        assert_eq!(
+            parse_orientation("Too few characters. Skipping this page"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_steps_from_config() {
+        let all = Preprocessor::new(&PreprocessConfig::default());
+        assert_eq!(all.steps.len(), 4);
+
+        let off = Preprocessor::new(&PreprocessConfig {
+            disabled: true,
+            ..Default::default()
+        });
+        assert!(!off.is_active());
+
+        let some = Preprocessor::new(&PreprocessConfig {
+            steps: vec!["deskew".to_string(), "sharpen".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(some.steps, vec![Step::Deskew]);
+    }
+}
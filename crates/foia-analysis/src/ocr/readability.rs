@@ -0,0 +1,246 @@
+//! Main-content extraction for HTML documents.
+//!
+//! Agency pages wrap a few paragraphs of content in navigation menus,
+//! cookie banners and footers. Indexing all of it makes every page from a
+//! site match the same menu terms and feeds the boilerplate to summaries,
+//! so HTML text is reduced to the main content, readability-style: drop
+//! elements that are never content, then keep the block holding the most
+//! paragraph text. The stored HTML file is untouched.
+
+use std::collections::HashMap;
+
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+
+/// Elements never part of the main content.
+const SKIPPED_TAGS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer",
+    "aside", "form", "button", "select", "dialog",
+];
+
+/// ARIA roles of page chrome.
+const SKIPPED_ROLES: &[&str] = &[
+    "navigation",
+    "banner",
+    "contentinfo",
+    "complementary",
+    "search",
+    "dialog",
+    "alertdialog",
+];
+
+/// Elements whose text ends a line.
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "br",
+    "li",
+    "tr",
+    "table",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "pre",
+    "blockquote",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "figure",
+    "figcaption",
+    "address",
+];
+
+/// Paragraphs shorter than this don't count toward a block's score.
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// A block needs this much paragraph text to be picked over the whole page.
+const MIN_CONTENT_SCORE: f64 = 140.0;
+
+fn boilerplate_pattern() -> &'static Regex {
+    static PATTERN: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?i)cookie|consent|gdpr|banner|navbar|\bnav\b|menu|breadcrumb|footer|masthead|sidebar|\bshare|social|related|advert|\bads?\b|promo|popup|modal|newsletter|subscribe|skip-?link",
+        )
+        .expect("valid regex")
+    })
+}
+
+/// Whether an element is page chrome rather than content.
+fn is_boilerplate(element: &ElementRef) -> bool {
+    let value = element.value();
+    if SKIPPED_TAGS.contains(&value.name()) {
+        return true;
+    }
+    if value
+        .attr("role")
+        .is_some_and(|role| SKIPPED_ROLES.contains(&role))
+    {
+        return true;
+    }
+    if value.attr("hidden").is_some() || value.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    // Body and main containers often carry layout classes like "has-sidebar"
+    if matches!(value.name(), "html" | "body" | "main" | "article") {
+        return false;
+    }
+    let pattern = boilerplate_pattern();
+    value.id().is_some_and(|id| pattern.is_match(id))
+        || value.attr("class").is_some_and(|c| pattern.is_match(c))
+}
+
+fn in_boilerplate(element: &ElementRef) -> bool {
+    is_boilerplate(element)
+        || element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| is_boilerplate(&a))
+}
+
+/// Main-content text of an HTML page.
+pub fn html_main_text(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let root = document.root_element();
+    let content = main_block(&document).unwrap_or(root);
+
+    let mut text = String::new();
+    push_text(&content, &mut text);
+    normalize_lines(&text)
+}
+
+/// The block with the most paragraph text, or `None` if no block has enough
+/// to tell content from chrome (listing pages, short notices).
+fn main_block(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraphs = Selector::parse("p, pre, td, li, blockquote").expect("valid selector");
+    let mut scores = HashMap::new();
+
+    for paragraph in document.select(&paragraphs) {
+        if in_boilerplate(&paragraph) {
+            continue;
+        }
+        let text: String = paragraph.text().collect();
+        let length = text.split_whitespace().collect::<Vec<_>>().join(" ").len();
+        if length < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        // Commas are a cheap signal of prose rather than link lists
+        let score = length as f64 * (1.0 - link_density(&paragraph))
+            + text.matches(',').count() as f64 * 10.0;
+
+        let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+        for share in [1.0, 0.5] {
+            let Some(block) = ancestors.next() else {
+                break;
+            };
+            scores.entry(block.id()).or_insert((block, 0.0)).1 += score * share;
+        }
+    }
+
+    scores
+        .into_values()
+        .filter(|(_, score)| *score >= MIN_CONTENT_SCORE)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(block, _)| block)
+}
+
+/// Share of an element's text that sits inside links.
+fn link_density(element: &ElementRef) -> f64 {
+    let links = Selector::parse("a").expect("valid selector");
+    let total: usize = element.text().map(str::len).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = element
+        .select(&links)
+        .flat_map(|a| a.text())
+        .map(str::len)
+        .sum();
+    linked as f64 / total as f64
+}
+
+fn push_text(element: &ElementRef, out: &mut String) {
+    for child in element.children() {
+        if let Some(text) = child.value().as_text() {
+            out.push_str(text);
+        } else if let Some(child) = ElementRef::wrap(child) {
+            if is_boilerplate(&child) {
+                continue;
+            }
+            let block = BLOCK_TAGS.contains(&child.value().name());
+            if block {
+                out.push('\n');
+            }
+            push_text(&child, out);
+            if block {
+                out.push('\n');
+            } else if matches!(child.value().name(), "td" | "th") {
+                out.push(' ');
+            }
+        }
+    }
+}
+
+/// Collapse whitespace within lines and drop empty lines.
+fn normalize_lines(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r#"<html><body class="has-sidebar">
+        <div id="cookie-banner">We use cookies to improve your experience. Accept all cookies?</div>
+        <header><a href="/">Department of Records</a></header>
+        <nav><ul><li><a href="/about">About</a></li><li><a href="/foia">FOIA</a></li></ul></nav>
+        <div class="layout">
+          <div class="sidebar"><p>Related links, press releases, and other announcements from the office.</p></div>
+          <div class="content">
+            <h1>Inspection report</h1>
+            <p>The inspection found that records were stored in an unlocked room, contrary to policy, for several months.</p>
+            <p>Corrective actions included new locks, an access log, and quarterly audits by the records officer.</p>
+          </div>
+        </div>
+        <footer>Contact us | Privacy policy</footer>
+        <script>track();</script>
+    </body></html>"#;
+
+    #[test]
+    fn test_main_text_drops_boilerplate() {
+        let text = html_main_text(ARTICLE);
+        assert!(text.starts_with("Inspection report"));
+        assert!(text.contains("unlocked room"));
+        assert!(text.contains("quarterly audits"));
+        for chrome in [
+            "cookies",
+            "About",
+            "Related links",
+            "Privacy policy",
+            "track()",
+        ] {
+            assert!(!text.contains(chrome), "kept {:?}", chrome);
+        }
+    }
+
+    #[test]
+    fn test_short_pages_keep_all_visible_text() {
+        let html = r#"<html><body><nav>Home</nav><h1>Reading room</h1>
+            <ul><li><a href="/a.pdf">Report A</a></li><li><a href="/b.pdf">Report B</a></li></ul>
+        </body></html>"#;
+        assert_eq!(html_main_text(html), "Reading room\nReport A\nReport B");
+    }
+}
@@ -0,0 +1,163 @@
+//! Tabular documents: CSV and TSV files and xlsx/ods workbooks.
+//!
+//! Data releases are read as sheets of rows so their structure survives:
+//! the web UI previews them as tables, header rows are indexed as column
+//! names, and workbook sheets can be downloaded as CSV.
+
+use std::path::Path;
+
+use super::extractor::ExtractionError;
+use super::office::{self, ODS, XLSX};
+
+pub const CSV: &str = "text/csv";
+pub const TSV: &str = "text/tab-separated-values";
+
+/// One sheet of a tabular document; CSV files have a single sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sheet {
+    pub name: String,
+    /// Non-empty rows; rows may differ in length.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Sheet {
+    /// Column names: the first row, trimmed.
+    pub fn header(&self) -> Vec<String> {
+        self.rows
+            .first()
+            .map(|row| row.iter().map(|c| c.trim().to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// The sheet as RFC 4180 CSV.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(|c| escape_csv(c)).collect();
+            out.push_str(&cells.join(","));
+            out.push_str("\r\n");
+        }
+        out
+    }
+
+    /// Page text: the sheet name, then one tab-separated line per row.
+    pub(super) fn page_text(&self) -> String {
+        let rows: Vec<String> = self.rows.iter().map(|row| row.join("\t")).collect();
+        format!("Sheet: {}\n\n{}", self.name, rows.join("\n"))
+            .trim_end()
+            .to_string()
+    }
+}
+
+/// Whether a MIME type is read as sheets of rows.
+pub fn is_tabular_mimetype(mime_type: &str) -> bool {
+    matches!(mime_type, CSV | TSV | XLSX | ODS)
+}
+
+/// Read every sheet of a tabular document.
+pub fn read_sheets(path: &Path, mime_type: &str) -> Result<Vec<Sheet>, ExtractionError> {
+    match mime_type {
+        CSV | TSV => {
+            let raw = std::fs::read(path)?;
+            let delimiter = if mime_type == TSV { '\t' } else { ',' };
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Ok(vec![Sheet {
+                name,
+                rows: parse_delimited(&String::from_utf8_lossy(&raw), delimiter),
+            }])
+        }
+        _ => office::read_workbook(path, mime_type),
+    }
+}
+
+/// Parse delimited text with RFC 4180 quoting, skipping blank rows.
+pub fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                push_row(&mut rows, std::mem::take(&mut row));
+            }
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        push_row(&mut rows, row);
+    }
+    rows
+}
+
+fn push_row(rows: &mut Vec<Vec<String>>, row: Vec<String>) {
+    if row.iter().any(|c| !c.trim().is_empty()) {
+        rows.push(row);
+    }
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delimited_quoting() {
+        let rows = parse_delimited(
+            "\u{feff}Agency,Amount,Note\r\nFBI,\"1,200\",\"said \"\"no\"\"\"\r\n\r\nDEA,40,\"two\nlines\"\n",
+            ',',
+        );
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Agency", "Amount", "Note"],
+                vec!["FBI", "1,200", "said \"no\""],
+                vec!["DEA", "40", "two\nlines"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sheet_csv_round_trip() {
+        let sheet = Sheet {
+            name: "Budget".to_string(),
+            rows: vec![
+                vec![" Item ".to_string(), "Cost".to_string()],
+                vec!["Radios, handheld".to_string(), "1200".to_string()],
+            ],
+        };
+        assert_eq!(sheet.header(), vec!["Item", "Cost"]);
+        let csv = sheet.to_csv();
+        assert_eq!(csv, " Item ,Cost\r\n\"Radios, handheld\",1200\r\n");
+        assert_eq!(parse_delimited(&csv, ','), sheet.rows);
+    }
+}
@@ -1,6 +1,6 @@
 //! Document analysis service.
 //!
-//! Handles MIME detection, text extraction, and OCR processing.
+//! Handles MIME detection, text extraction, OCR, and audio/video transcription.
 //! Separated from UI concerns - emits events for progress tracking.
 
 mod processing;
@@ -8,15 +8,20 @@ pub mod stages;
 mod types;
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use tokio::sync::mpsc;
 
 use crate::analysis::AnalysisManager;
-use foia::repository::DieselDocumentRepository;
+use crate::ocr::{Arbiter, Preprocessor};
+use foia::repository::diesel_document::Projection;
+use foia::repository::{DieselDocumentRepository, DieselScraperConfigRepository};
 use foia::work_queue::{ExecutionStrategy, PipelineEvent, PipelineRunner};
 
-pub use processing::{extract_document_text_per_page, ocr_document_page_with_config};
-pub use stages::{OcrStage, TextExtractionStage};
+pub use processing::{
+    arbitrate_page, extract_document_text_per_page, ocr_document_page_with_config,
+};
+pub use stages::{OcrStage, TextExtractionStage, TranscriptionStage, VirtualFileTextStage};
 pub use types::{AnalysisEvent, AnalysisResult};
 
 use foia::config::OcrConfig;
@@ -29,20 +34,25 @@ pub struct AnalysisService {
     doc_repo: DieselDocumentRepository,
     analysis_manager: AnalysisManager,
     ocr_config: OcrConfig,
+    arbiter: Arc<Arbiter>,
+    preprocessor: Arc<Preprocessor>,
     documents_dir: PathBuf,
     retry_interval_hours: u32,
+    scraper_configs: Option<DieselScraperConfigRepository>,
 }
 
 impl AnalysisService {
     /// Create a new analysis service with default OCR config.
-    #[allow(dead_code)]
     pub fn new(doc_repo: DieselDocumentRepository, documents_dir: PathBuf) -> Self {
         Self {
             doc_repo,
             analysis_manager: AnalysisManager::with_defaults(),
             ocr_config: OcrConfig::default(),
+            arbiter: Arc::new(Arbiter::default()),
+            preprocessor: Arc::new(Preprocessor::default()),
             documents_dir,
             retry_interval_hours: DEFAULT_RETRY_INTERVAL_HOURS,
+            scraper_configs: None,
         }
     }
 
@@ -56,8 +66,11 @@ impl AnalysisService {
             doc_repo,
             analysis_manager: AnalysisManager::with_defaults(),
             ocr_config,
+            arbiter: Arc::new(Arbiter::default()),
+            preprocessor: Arc::new(Preprocessor::default()),
             documents_dir,
             retry_interval_hours: DEFAULT_RETRY_INTERVAL_HOURS,
+            scraper_configs: None,
         }
     }
 
@@ -67,20 +80,78 @@ impl AnalysisService {
         self
     }
 
+    /// Choose pages' final text among OCR readings with `arbiter`.
+    pub fn with_arbiter(mut self, arbiter: Arbiter) -> Self {
+        self.arbiter = Arc::new(arbiter);
+        self
+    }
+
+    /// Clean up page images with `preprocessor` before OCR.
+    pub fn with_preprocessor(mut self, preprocessor: Preprocessor) -> Self {
+        self.preprocessor = Arc::new(preprocessor);
+        self
+    }
+
+    /// Skip sources whose processing profile excludes OCR.
+    pub fn with_processing_profiles(
+        mut self,
+        scraper_configs: DieselScraperConfigRepository,
+    ) -> Self {
+        self.scraper_configs = Some(scraper_configs);
+        self
+    }
+
+    /// Sources whose processing profile skips `method`.
+    async fn sources_skipping(&self, method: &str) -> anyhow::Result<Vec<String>> {
+        match &self.scraper_configs {
+            Some(configs) => Ok(configs.sources_skipping(method).await?),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Get count of documents needing analysis.
     pub async fn count_needing_processing(
         &self,
         source_id: Option<&str>,
         mime_type: Option<&str>,
     ) -> anyhow::Result<(u64, u64)> {
+        let excluded = self.sources_skipping("ocr").await?;
         let docs = self
             .doc_repo
-            .count_needing_analysis("ocr", source_id, mime_type, self.retry_interval_hours)
+            .count_needing_analysis(
+                "ocr",
+                source_id,
+                mime_type,
+                self.retry_interval_hours,
+                &excluded,
+            )
             .await?;
         let pages = self.doc_repo.count_pages_needing_ocr().await?;
         Ok((docs, pages))
     }
 
+    /// Get count of audio/video documents needing transcription.
+    pub async fn count_needing_transcription(
+        &self,
+        source_id: Option<&str>,
+    ) -> anyhow::Result<u64> {
+        let excluded = self.sources_skipping("whisper").await?;
+        let mut total = 0;
+        for mime in ["audio/*", "video/*"] {
+            total += self
+                .doc_repo
+                .count_needing_analysis(
+                    "whisper",
+                    source_id,
+                    Some(mime),
+                    self.retry_interval_hours,
+                    &excluded,
+                )
+                .await?;
+        }
+        Ok(total)
+    }
+
     /// Analyze documents: detect MIME types, extract text, and run analysis.
     ///
     /// The `methods` parameter specifies which analysis methods to run (e.g., ["ocr", "whisper"]).
@@ -121,6 +192,7 @@ impl AnalysisService {
 
         // Check if any page-level (OCR) methods are requested
         let has_ocr_methods = methods.iter().any(|m| m == "ocr" || m.starts_with("ocr:"));
+        let transcribe = methods.iter().any(|m| m == "whisper");
 
         // Pre-pipeline setup
         tracing::debug!("Finalizing pending documents...");
@@ -129,42 +201,80 @@ impl AnalysisService {
 
         self.migrate_legacy_file_paths().await;
 
-        if !has_ocr_methods {
+        if !has_ocr_methods && !transcribe {
             return Ok(AnalysisResult::default());
         }
 
-        for method in &methods {
+        // Indexed documents haven't necessarily been transcribed, so whisper
+        // completions are never backfilled.
+        for method in methods.iter().filter(|m| *m != "whisper") {
             self.backfill_analysis_completions(method).await;
         }
 
-        // Build pipeline stages
         let effective_chunk = chunk_size.unwrap_or(4096);
 
-        let text_stage = TextExtractionStage::new(
-            self.doc_repo.clone(),
-            self.documents_dir.clone(),
-            source_id,
-            mime_type,
-            self.retry_interval_hours,
-            workers,
-        );
-
-        let ocr_stage = OcrStage::new(
-            self.doc_repo.clone(),
-            self.ocr_config.clone(),
-            self.documents_dir.clone(),
-            workers,
-        );
-
-        let mut runner = PipelineRunner::new(effective_chunk, limit);
-        runner.add_stage(Box::new(text_stage));
-        runner.add_stage(Box::new(ocr_stage));
-
         // Bridge PipelineEvent -> AnalysisEvent
         let (pipe_tx, pipe_rx) = mpsc::channel::<PipelineEvent>(100);
         let bridge = tokio::spawn(bridge_pipeline_to_analysis_events(pipe_rx, event_tx));
 
-        runner.run(strategy, pipe_tx).await?;
+        if has_ocr_methods {
+            let text_stage = TextExtractionStage::new(
+                self.doc_repo.clone(),
+                self.documents_dir.clone(),
+                source_id,
+                mime_type,
+                self.retry_interval_hours,
+                workers,
+            )
+            .with_excluded_sources(self.sources_skipping("ocr").await?);
+
+            let ocr_stage = OcrStage::new(
+                self.doc_repo.clone(),
+                self.ocr_config.clone(),
+                self.documents_dir.clone(),
+                workers,
+            )
+            .with_arbiter(self.arbiter.clone())
+            .with_preprocessor(self.preprocessor.clone());
+
+            // Archive members and attachments go through the same pass
+            let vf_stage = VirtualFileTextStage::new(
+                self.doc_repo.clone(),
+                self.documents_dir.clone(),
+                source_id,
+                workers,
+            );
+
+            let mut runner = PipelineRunner::new(effective_chunk, limit);
+            runner.add_stage(Box::new(text_stage));
+            runner.add_stage(Box::new(ocr_stage));
+            runner.add_stage(Box::new(vf_stage));
+            runner.run(strategy, pipe_tx.clone()).await?;
+        }
+
+        // Transcription runs as its own pass: it doesn't feed the OCR stage
+        match self.analysis_manager.get("whisper") {
+            Some(backend) if transcribe && backend.is_available() => {
+                let stage = TranscriptionStage::new(
+                    self.doc_repo.clone(),
+                    backend,
+                    self.documents_dir.clone(),
+                    source_id,
+                    self.retry_interval_hours,
+                    workers,
+                )
+                .with_excluded_sources(self.sources_skipping("whisper").await?);
+
+                let mut runner = PipelineRunner::new(effective_chunk, limit);
+                runner.add_stage(Box::new(stage));
+                runner.run(ExecutionStrategy::Wide, pipe_tx.clone()).await?;
+            }
+            Some(backend) if transcribe => {
+                tracing::warn!("Skipping transcription: {}", backend.availability_hint());
+            }
+            _ => {}
+        }
+        drop(pipe_tx);
 
         // Wait for bridge to finish
         let result = bridge.await?;
@@ -271,32 +381,41 @@ impl AnalysisService {
         tracing::info!("Done: {checked} checked, {cleared} updated");
     }
 
+    /// Extract text from one document's current version, returning the
+    /// number of pages. Non-PDFs are finalized immediately; PDF pages
+    /// without a text layer are left for OCR.
+    pub async fn extract_text(&self, doc_id: &str) -> anyhow::Result<usize> {
+        let doc = self
+            .doc_repo
+            .get_projected(doc_id, Projection::Metadata)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Document not found: {}", doc_id))?;
+
+        // Run in blocking context for CPU-intensive work
+        let doc_repo = self.doc_repo.clone();
+        let documents_dir = self.documents_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let handle = tokio::runtime::Handle::current();
+            extract_document_text_per_page(&doc, &doc_repo, &handle, &documents_dir)
+        })
+        .await?
+    }
+
     /// Process a single document by ID.
     pub async fn process_single(
         &self,
         doc_id: &str,
         _event_tx: mpsc::Sender<AnalysisEvent>,
     ) -> anyhow::Result<()> {
-        // Get the document
         let doc = self
             .doc_repo
-            .get(doc_id)
+            .get_projected(doc_id, Projection::Metadata)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Document not found: {}", doc_id))?;
 
         println!("  {} Processing: {}", console::style("→").cyan(), doc.title);
 
-        // Extract text per-page (run in blocking context for CPU-intensive work)
-        let doc_repo = self.doc_repo.clone();
-        let doc_clone = doc.clone();
-        let doc_id_owned = doc_id.to_string();
-        let documents_dir = self.documents_dir.clone();
-
-        let pages = tokio::task::spawn_blocking(move || {
-            let handle = tokio::runtime::Handle::current();
-            extract_document_text_per_page(&doc_clone, &doc_repo, &handle, &documents_dir)
-        })
-        .await??;
+        let pages = self.extract_text(doc_id).await?;
 
         println!(
             "  {} Extracted {} pages",
@@ -305,7 +424,7 @@ impl AnalysisService {
         );
 
         // Finalize the document
-        self.doc_repo.finalize_document(&doc_id_owned).await?;
+        self.doc_repo.finalize_document(doc_id).await?;
         println!("  {} Document finalized", console::style("✓").green());
 
         Ok(())
@@ -315,7 +434,8 @@ impl AnalysisService {
 /// Bridge generic `PipelineEvent`s to domain-specific `AnalysisEvent`s.
 ///
 /// Maps stage names ("Text extraction" / "OCR") to the existing phase-based
-/// event variants so the CLI event handler works unchanged.
+/// event variants so the CLI event handler works unchanged. "Transcription"
+/// and "Attachment text" map to their own variants.
 async fn bridge_pipeline_to_analysis_events(
     mut pipe_rx: mpsc::Receiver<PipelineEvent>,
     event_tx: mpsc::Sender<AnalysisEvent>,
@@ -337,6 +457,18 @@ async fn bridge_pipeline_to_analysis_events(
                             total_pages: total_items as usize,
                         })
                         .await;
+                } else if stage == "Transcription" {
+                    let _ = event_tx
+                        .send(AnalysisEvent::TranscriptionStarted {
+                            total_documents: total_items as usize,
+                        })
+                        .await;
+                } else if stage == "Attachment text" {
+                    let _ = event_tx
+                        .send(AnalysisEvent::VirtualFilesStarted {
+                            total_files: total_items as usize,
+                        })
+                        .await;
                 }
             }
             PipelineEvent::ItemStarted { ref stage, ref item_id, ref label } => {
@@ -407,6 +539,26 @@ async fn bridge_pipeline_to_analysis_events(
                             .send(AnalysisEvent::DocumentFinalized { document_id })
                             .await;
                     }
+                } else if stage == "Transcription" {
+                    let segments = detail
+                        .as_deref()
+                        .and_then(|d| d.split(' ').next())
+                        .and_then(|n| n.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    result.transcribed += 1;
+                    let _ = event_tx
+                        .send(AnalysisEvent::DocumentTranscribed {
+                            document_id: item_id.clone(),
+                            segments,
+                        })
+                        .await;
+                } else if stage == "Attachment text" {
+                    result.virtual_files_extracted += 1;
+                    let _ = event_tx
+                        .send(AnalysisEvent::VirtualFileCompleted {
+                            virtual_file_id: item_id.clone(),
+                        })
+                        .await;
                 }
             }
             PipelineEvent::ItemSkipped { ref stage, ref item_id } => {
@@ -465,6 +617,22 @@ async fn bridge_pipeline_to_analysis_events(
                             error: error.clone(),
                         })
                         .await;
+                } else if stage == "Transcription" {
+                    result.transcription_failed += 1;
+                    let _ = event_tx
+                        .send(AnalysisEvent::TranscriptionFailed {
+                            document_id: item_id.clone(),
+                            error: error.clone(),
+                        })
+                        .await;
+                } else if stage == "Attachment text" {
+                    result.virtual_files_failed += 1;
+                    let _ = event_tx
+                        .send(AnalysisEvent::VirtualFileFailed {
+                            virtual_file_id: item_id.clone(),
+                            error: error.clone(),
+                        })
+                        .await;
                 }
             }
             PipelineEvent::StageCompleted { ref stage, succeeded, failed, skipped, .. } => {
@@ -485,6 +653,22 @@ async fn bridge_pipeline_to_analysis_events(
                             failed,
                         })
                         .await;
+                } else if stage == "Transcription" {
+                    let _ = event_tx
+                        .send(AnalysisEvent::TranscriptionComplete {
+                            succeeded,
+                            failed,
+                            skipped,
+                        })
+                        .await;
+                } else if stage == "Attachment text" {
+                    let _ = event_tx
+                        .send(AnalysisEvent::VirtualFilesComplete {
+                            succeeded,
+                            failed,
+                            skipped,
+                        })
+                        .await;
                 }
             }
         }
@@ -3,9 +3,15 @@
 use std::fs::File;
 use std::io::Read;
 
-use crate::ocr::{BackendConfig, FallbackOcrBackend, OcrBackend, TextExtractor};
+use crate::ocr::{
+    convert_legacy, extract_office_pages, is_legacy_mimetype, is_office_mimetype,
+    is_tabular_mimetype, read_sheets, readings_from_results, Arbiter, ArchiveExtractor,
+    BackendConfig, ConvertedContent, Decision, EmailExtractor, FallbackOcrBackend, OcrBackend,
+    Preprocessor, TextExtractor,
+};
 use foia::config::OcrConfig;
-use foia::models::{Document, DocumentPage, PageOcrStatus};
+use foia::models::{Document, DocumentPage, PageOcrStatus, VirtualFile};
+use foia::repository::diesel_document::Projection;
 use foia::repository::DieselDocumentRepository;
 
 use super::types::PageOcrResult;
@@ -13,20 +19,26 @@ use super::types::PageOcrResult;
 /// Detect MIME type from file content and check if it differs from the stored type.
 ///
 /// Returns `Some((detected_mime, old_mime))` if they differ meaningfully, `None` otherwise.
-/// Reads the first 8KB of the file for magic-byte detection.
+/// Reads the first 8KB of the file (decrypted, if encrypted) for magic-byte detection.
 pub fn detect_mime_mismatch(
     path: &std::path::Path,
     stored_mime: &str,
 ) -> Option<(String, String)> {
-    let mut file = File::open(path).ok()?;
-    let mut buffer = [0u8; 8192];
-    let bytes_read = file.read(&mut buffer).ok()?;
+    let mut buffer = vec![0u8; 8192];
+    if foia::storage::is_encrypted_file(path) {
+        buffer = foia::storage::read_content(path).ok()?;
+        buffer.truncate(8192);
+    } else {
+        let mut file = File::open(path).ok()?;
+        let bytes_read = file.read(&mut buffer).ok()?;
+        buffer.truncate(bytes_read);
+    }
 
-    if bytes_read == 0 {
+    if buffer.is_empty() {
         return None;
     }
 
-    let detected = infer::get(&buffer[..bytes_read])?;
+    let detected = infer::get(&buffer)?;
     let detected_mime = detected.mime_type();
 
     let stored_normalized = stored_mime
@@ -68,22 +80,58 @@ pub fn extract_document_text_per_page(
         .current_version()
         .ok_or_else(|| anyhow::anyhow!("Document has no versions"))?;
 
-    let file_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let stored_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let file_path = foia::storage::plaintext_path(&stored_path)?;
 
     // Only process PDFs with per-page extraction
     if version.mime_type != "application/pdf" {
-        // For non-PDFs, use the old extraction method
-        let result = extractor.extract(&file_path, &version.mime_type)?;
-
-        // Create a single "page" for non-PDF documents
-        let mut page = DocumentPage::new(doc.id.clone(), version.id, 1);
-        page.pdf_text = Some(result.text.clone());
-        page.final_text = Some(result.text);
-        page.ocr_status = PageOcrStatus::OcrComplete;
-        handle.block_on(doc_repo.save_page(&page))?;
+        // Office documents keep their sheets and slides as separate pages, and
+        // legacy formats converted to PDF keep the PDF's pages; other non-PDFs
+        // use the old extraction method as a single "page"
+        let mut lineage = None;
+        let texts = if is_office_mimetype(&version.mime_type) {
+            extract_office_pages(&file_path, &version.mime_type)?
+        } else if is_legacy_mimetype(&version.mime_type) {
+            let conversion = convert_legacy(&file_path, &version.mime_type)?;
+            lineage = Some(conversion.lineage());
+            match conversion.content {
+                ConvertedContent::Pdf { ref path, .. } => {
+                    let count = extractor.get_pdf_page_count(path).unwrap_or(1);
+                    extractor.extract_all_pdf_page_texts(path, count)?
+                }
+                ConvertedContent::Text(text) => vec![text],
+            }
+        } else {
+            vec![extractor.extract(&file_path, &version.mime_type)?.text]
+        };
 
-        // Cache page count (1 for non-PDFs)
-        handle.block_on(doc_repo.set_version_page_count(version.id, 1))?;
+        let page_count = texts.len();
+        for (i, text) in texts.into_iter().enumerate() {
+            let mut page = DocumentPage::new(doc.id.clone(), version.id, (i + 1) as u32);
+            page.pdf_text = Some(text.clone());
+            page.final_text = Some(text);
+            page.ocr_status = PageOcrStatus::OcrComplete;
+            handle.block_on(doc_repo.save_page(&page))?;
+        }
+        handle.block_on(doc_repo.set_version_page_count(version.id, page_count as u32))?;
+
+        // Index header rows so data files can be found by column name
+        if is_tabular_mimetype(&version.mime_type) {
+            match read_sheets(&file_path, &version.mime_type) {
+                Ok(sheets) => {
+                    let headers: Vec<(String, Vec<String>)> = sheets
+                        .iter()
+                        .map(|s| (s.name.clone(), s.header()))
+                        .collect();
+                    handle.block_on(doc_repo.replace_document_columns(
+                        &doc.id,
+                        version.id as i32,
+                        &headers,
+                    ))?;
+                }
+                Err(e) => tracing::warn!("Failed to read columns of {}: {}", doc.id, e),
+            }
+        }
 
         // Non-PDFs are complete immediately - finalize the document
         handle.block_on(doc_repo.finalize_document(&doc.id))?;
@@ -99,10 +147,10 @@ pub fn extract_document_text_per_page(
             None,
             None,
             None,
-            None,
+            lineage.as_ref(),
         ));
 
-        return Ok(1);
+        return Ok(page_count);
     }
 
     // Get page count (use cached value if available)
@@ -154,19 +202,78 @@ pub fn extract_document_text_per_page(
         pages.push(page);
     }
 
-    // Bulk insert all pages at once
+    // Save all pages and their text-layer results in one transaction
     if !pages.is_empty() {
         tracing::debug!(
             "Saving {} pages to database for document {}",
             pages.len(),
             doc.id
         );
-        handle.block_on(doc_repo.save_pages_batch(&pages))?;
+        handle.block_on(doc_repo.save_pages(&pages))?;
     }
 
     Ok(pages.len())
 }
 
+/// Arbitrate between a page's stored OCR readings, recording the decision
+/// and each reading's score. Returns `None` if the page has no readings.
+pub async fn arbitrate_page(
+    page_id: i64,
+    doc_repo: &DieselDocumentRepository,
+    arbiter: &Arbiter,
+) -> anyhow::Result<Option<Decision>> {
+    let readings = readings_from_results(doc_repo.get_page_ocr_results(page_id).await?);
+    let Some(decision) = arbiter.arbitrate(&readings).await else {
+        return Ok(None);
+    };
+    for (reading, score) in readings.iter().zip(&decision.scores) {
+        doc_repo
+            .set_ocr_result_quality(reading.result_id, score.score)
+            .await?;
+    }
+    doc_repo
+        .record_page_text_decision(&decision.to_record(page_id))
+        .await?;
+    Ok(Some(decision))
+}
+
+/// Render and preprocess a page's image for OCR, recording what was done.
+/// `None` if preprocessing is off or fails, in which case backends render
+/// the page themselves.
+fn preprocess_page(
+    page: &DocumentPage,
+    file_path: &std::path::Path,
+    dir: &std::path::Path,
+    preprocessor: &Preprocessor,
+    doc_repo: &DieselDocumentRepository,
+    handle: &tokio::runtime::Handle,
+) -> Option<std::path::PathBuf> {
+    if !preprocessor.is_active() {
+        return None;
+    }
+    match preprocessor.process_pdf_page(file_path, page.page_number, dir) {
+        Ok(prepared) => {
+            let record = prepared.to_record(page.id);
+            if let Err(e) = handle.block_on(doc_repo.record_page_preprocessing(&record)) {
+                tracing::warn!(
+                    "Failed to record preprocessing of page {}: {}",
+                    page.page_number,
+                    e
+                );
+            }
+            Some(prepared.image)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Preprocessing page {} failed, running OCR on it as rendered: {}",
+                page.page_number,
+                e
+            );
+            None
+        }
+    }
+}
+
 /// Run OCR on a page and compare with existing text.
 /// If all pages for this document are now complete, the document is finalized
 /// (status set to OcrComplete, combined text saved).
@@ -181,7 +288,15 @@ pub fn ocr_document_page(
     handle: &tokio::runtime::Handle,
     documents_dir: &std::path::Path,
 ) -> anyhow::Result<PageOcrResult> {
-    ocr_document_page_with_config(page, doc_repo, handle, &OcrConfig::default(), documents_dir)
+    ocr_document_page_with_config(
+        page,
+        doc_repo,
+        handle,
+        &OcrConfig::default(),
+        &Arbiter::default(),
+        &Preprocessor::default(),
+        documents_dir,
+    )
 }
 
 /// Run OCR on a page using configured backend entries.
@@ -193,18 +308,25 @@ pub fn ocr_document_page(
 /// Example config: `["tesseract", ["groq", "gemini"]]`
 /// - Runs tesseract, stores as "tesseract"
 /// - Runs groq (falls back to gemini if rate limited), stores as "groq" or "gemini"
+///
+/// Backends read the page image as cleaned up by `preprocessor`. The page's
+/// final text is chosen by `arbiter` from every reading stored for it, or
+/// is the longest reading when arbitration is disabled. Pages a curator
+/// corrected keep their text.
 pub fn ocr_document_page_with_config(
     page: &DocumentPage,
     doc_repo: &DieselDocumentRepository,
     handle: &tokio::runtime::Handle,
     ocr_config: &OcrConfig,
+    arbiter: &Arbiter,
+    preprocessor: &Preprocessor,
     documents_dir: &std::path::Path,
 ) -> anyhow::Result<PageOcrResult> {
     let extractor = TextExtractor::new();
 
     // Get the document to find the file path
     let doc = handle
-        .block_on(doc_repo.get(&page.document_id))?
+        .block_on(doc_repo.get_projected(&page.document_id, Projection::Metadata))?
         .ok_or_else(|| anyhow::anyhow!("Document not found"))?;
 
     let version = doc
@@ -213,13 +335,18 @@ pub fn ocr_document_page_with_config(
         .find(|v| v.id == page.version_id)
         .ok_or_else(|| anyhow::anyhow!("Version not found"))?;
 
-    let file_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let stored_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let file_path = foia::storage::plaintext_path(&stored_path)?;
 
     // Compute image hash once for deduplication across all backends
     let image_hash = extractor
         .get_pdf_page_hash(&file_path, page.page_number)
         .ok();
 
+    // The preprocessed page image, made when the first backend needs it
+    let preprocess_dir = tempfile::TempDir::new()?;
+    let mut page_image: Option<Option<std::path::PathBuf>> = None;
+
     let mut updated_page = page.clone();
     let mut improved = false;
     let mut any_succeeded = false;
@@ -279,8 +406,22 @@ pub fn ocr_document_page_with_config(
         } else {
             // Run OCR with this entry (single backend or fallback chain)
             let fallback = FallbackOcrBackend::from_names(&backend_names, BackendConfig::default());
-
-            match fallback.ocr_pdf_page(&file_path, page.page_number) {
+            let image = page_image.get_or_insert_with(|| {
+                preprocess_page(
+                    page,
+                    &file_path,
+                    preprocess_dir.path(),
+                    preprocessor,
+                    doc_repo,
+                    handle,
+                )
+            });
+            let ocr = match image {
+                Some(image) => fallback.ocr_image(image),
+                None => fallback.ocr_pdf_page(&file_path, page.page_number),
+            };
+
+            match ocr {
                 Ok(result) => {
                     let ocr_text = result.text;
                     let backend_name = result.backend.as_str();
@@ -322,6 +463,26 @@ pub fn ocr_document_page_with_config(
         }
     }
 
+    // A curator's correction stays the page's text; new readings are only
+    // stored alongside it
+    let verified = handle.block_on(doc_repo.is_page_verified(page.id))?;
+
+    // Arbitrate between all stored readings, including earlier runs' backends
+    if any_succeeded && !verified && !arbiter.is_disabled() {
+        match handle.block_on(arbitrate_page(page.id, doc_repo, arbiter)) {
+            Ok(Some(decision)) => {
+                best_char_count = decision.text.chars().filter(|c| !c.is_whitespace()).count();
+                best_text = Some(decision.text);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(
+                "OCR arbitration failed for page {}: {}",
+                page.page_number,
+                e
+            ),
+        }
+    }
+
     // Update page with best result
     if let Some(text) = best_text {
         improved = best_char_count > pdf_chars + (pdf_chars / 5);
@@ -342,6 +503,9 @@ pub fn ocr_document_page_with_config(
         updated_page.final_text = page.pdf_text.clone();
     }
 
+    if verified {
+        updated_page.final_text = page.final_text.clone();
+    }
     handle.block_on(doc_repo.save_page(&updated_page))?;
 
     // Check if all pages for this document are now complete
@@ -366,6 +530,20 @@ pub fn ocr_document_page_with_config(
             None,
         ));
 
+        // Chapters and sections, now that every page has its text
+        if doc
+            .current_version()
+            .is_some_and(|v| v.id == page.version_id)
+        {
+            if let Err(e) = handle.block_on(foia::services::sections::derive_sections(
+                doc_repo,
+                &doc,
+                documents_dir,
+            )) {
+                tracing::warn!("Failed to derive sections of {}: {}", doc.id, e);
+            }
+        }
+
         document_finalized = true;
         tracing::debug!(
             "Document {} finalized after page {} completed",
@@ -379,3 +557,33 @@ pub fn ocr_document_page_with_config(
         document_finalized,
     })
 }
+
+/// Extract the text of an archive member or email attachment.
+///
+/// The member is unpacked from the parent's stored file into a temporary
+/// directory and run through the same extractors as documents.
+pub fn extract_virtual_file_text(
+    parent: &Document,
+    vf: &VirtualFile,
+    documents_dir: &std::path::Path,
+) -> anyhow::Result<String> {
+    let version = parent
+        .versions
+        .iter()
+        .find(|v| v.id == vf.version_id)
+        .ok_or_else(|| {
+            anyhow::anyhow!("Version {} of {} not found", vf.version_id, parent.id)
+        })?;
+    let stored_path = version.resolve_path(documents_dir, &parent.source_url, &parent.title);
+    let container = foia::storage::plaintext_path(&stored_path)?;
+
+    let extractor = TextExtractor::new();
+    let result = if EmailExtractor::is_email(&version.mime_type) {
+        let attachment = EmailExtractor::extract_attachment(&container, &vf.archive_path)?;
+        extractor.extract(&attachment.file_path, &vf.mime_type)?
+    } else {
+        let member = ArchiveExtractor::extract_file(&container, &vf.archive_path)?;
+        extractor.extract(&member.file_path, &vf.mime_type)?
+    };
+    Ok(result.text)
+}
@@ -1,5 +1,7 @@
-//! Pipeline stage implementations for analysis: text extraction and OCR.
+//! Pipeline stage implementations for analysis: text extraction, OCR,
+//! transcription, and text extraction for archive members and attachments.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -8,6 +10,7 @@ use async_trait::async_trait;
 use tokio::sync::{mpsc, Mutex};
 
 use foia::config::OcrConfig;
+use foia::models::{Document, DocumentPage, PageOcrStatus, VirtualFileStatus};
 use foia::repository::DieselDocumentRepository;
 use foia::work_queue::db_analysis::DbAnalysisQueue;
 use foia::work_queue::{
@@ -15,9 +18,11 @@ use foia::work_queue::{
     WorkQueueError,
 };
 
-use crate::ocr::OcrBackendType;
+use crate::analysis::AnalysisBackend;
+use crate::ocr::{Arbiter, OcrBackendType, Preprocessor};
 use super::processing::{
-    detect_mime_mismatch, extract_document_text_per_page, ocr_document_page_with_config,
+    detect_mime_mismatch, extract_document_text_per_page, extract_virtual_file_text,
+    ocr_document_page_with_config,
 };
 
 /// Text extraction stage (Phase 0 MIME check + Phase 1 extraction merged).
@@ -61,6 +66,12 @@ impl TextExtractionStage {
             cursor: Mutex::new(None),
         }
     }
+
+    /// Skip documents from these sources.
+    pub fn with_excluded_sources(mut self, sources: Vec<String>) -> Self {
+        self.filter.exclude_sources = sources;
+        self
+    }
 }
 
 #[async_trait]
@@ -239,6 +250,8 @@ impl PipelineStage for TextExtractionStage {
 pub struct OcrStage {
     doc_repo: DieselDocumentRepository,
     ocr_config: OcrConfig,
+    arbiter: Arc<Arbiter>,
+    preprocessor: Arc<Preprocessor>,
     documents_dir: PathBuf,
     workers: usize,
     deferred: bool,
@@ -267,11 +280,25 @@ impl OcrStage {
         Self {
             doc_repo,
             ocr_config,
+            arbiter: Arc::new(Arbiter::default()),
+            preprocessor: Arc::new(Preprocessor::default()),
             documents_dir,
             workers,
             deferred,
         }
     }
+
+    /// Choose each page's final text with `arbiter`.
+    pub fn with_arbiter(mut self, arbiter: Arc<Arbiter>) -> Self {
+        self.arbiter = arbiter;
+        self
+    }
+
+    /// Clean up page images with `preprocessor` before OCR.
+    pub fn with_preprocessor(mut self, preprocessor: Arc<Preprocessor>) -> Self {
+        self.preprocessor = preprocessor;
+        self
+    }
 }
 
 #[async_trait]
@@ -321,6 +348,8 @@ impl PipelineStage for OcrStage {
         for page in pages {
             let doc_repo = self.doc_repo.clone();
             let ocr_config = self.ocr_config.clone();
+            let arbiter = self.arbiter.clone();
+            let preprocessor = self.preprocessor.clone();
             let documents_dir = self.documents_dir.clone();
             let succeeded = succeeded.clone();
             let failed = failed.clone();
@@ -344,6 +373,8 @@ impl PipelineStage for OcrStage {
                     &doc_repo,
                     &rt_handle,
                     &ocr_config,
+                    &arbiter,
+                    &preprocessor,
                     &documents_dir,
                 ) {
                     Ok(ocr_result) => {
@@ -404,3 +435,480 @@ impl PipelineStage for OcrStage {
         })
     }
 }
+
+/// Transcription stage — runs Whisper on audio and video documents.
+///
+/// The transcript becomes the document's single page of text, and the timed
+/// segments are kept in the `whisper` analysis result metadata for the viewer.
+pub struct TranscriptionStage {
+    queue: DbAnalysisQueue,
+    doc_repo: DieselDocumentRepository,
+    backend: Arc<dyn AnalysisBackend>,
+    documents_dir: PathBuf,
+    /// One filter per media MIME prefix, each paged with its own cursor.
+    filters: Vec<WorkFilter>,
+    cursors: Mutex<Vec<Option<String>>>,
+    workers: usize,
+}
+
+impl TranscriptionStage {
+    pub fn new(
+        doc_repo: DieselDocumentRepository,
+        backend: Arc<dyn AnalysisBackend>,
+        documents_dir: PathBuf,
+        source_id: Option<&str>,
+        retry_interval_hours: u32,
+        workers: usize,
+    ) -> Self {
+        let queue = DbAnalysisQueue::new(doc_repo.clone());
+        let filters: Vec<WorkFilter> = ["audio/*", "video/*"]
+            .into_iter()
+            .map(|mime| WorkFilter {
+                work_type: "whisper".into(),
+                source_id: source_id.map(Into::into),
+                mime_type: Some(mime.into()),
+                retry_interval_hours: Some(retry_interval_hours),
+                ..Default::default()
+            })
+            .collect();
+        let cursors = Mutex::new(vec![None; filters.len()]);
+        Self {
+            queue,
+            doc_repo,
+            backend,
+            documents_dir,
+            filters,
+            cursors,
+            workers,
+        }
+    }
+
+    /// Skip documents from these sources.
+    pub fn with_excluded_sources(mut self, sources: Vec<String>) -> Self {
+        for filter in &mut self.filters {
+            filter.exclude_sources = sources.clone();
+        }
+        self
+    }
+}
+
+#[async_trait]
+impl PipelineStage for TranscriptionStage {
+    fn name(&self) -> &str {
+        "Transcription"
+    }
+
+    fn is_deferred(&self) -> bool {
+        false
+    }
+
+    async fn count(&self) -> Result<u64, PipelineError> {
+        let mut total = 0;
+        for filter in &self.filters {
+            total += self.queue.count(filter).await?;
+        }
+        Ok(total)
+    }
+
+    async fn run_chunk(
+        &self,
+        chunk_size: usize,
+        remaining_limit: usize,
+        event_tx: &mpsc::Sender<PipelineEvent>,
+    ) -> Result<ChunkResult, PipelineError> {
+        let batch_limit = if remaining_limit > 0 {
+            chunk_size.min(remaining_limit)
+        } else {
+            chunk_size
+        };
+
+        // Work through the filters in order, one batch per chunk
+        let mut batch = None;
+        for (i, filter) in self.filters.iter().enumerate() {
+            let cursor = self.cursors.lock().await[i].clone();
+            let docs = self
+                .queue
+                .fetch_batch(filter, batch_limit, cursor.as_deref())
+                .await?;
+            if let Some(last) = docs.last() {
+                self.cursors.lock().await[i] = Some(last.id.clone());
+                let has_more = docs.len() >= batch_limit || i + 1 < self.filters.len();
+                batch = Some((filter, docs, has_more));
+                break;
+            }
+        }
+        let Some((filter, docs, has_more)) = batch else {
+            return Ok(ChunkResult::default());
+        };
+
+        let succeeded = Arc::new(AtomicUsize::new(0));
+        let failed = Arc::new(AtomicUsize::new(0));
+        let skipped = Arc::new(AtomicUsize::new(0));
+
+        let mut handles = Vec::with_capacity(docs.len().min(self.workers));
+        let stage_name = self.name().to_string();
+
+        for doc in &docs {
+            let Some(version) = doc.current_version().cloned() else {
+                continue;
+            };
+            let path = version.resolve_path(&self.documents_dir, &doc.source_url, &doc.title);
+            if !path.exists() {
+                skipped.fetch_add(1, Ordering::Relaxed);
+                let _ = event_tx
+                    .send(PipelineEvent::ItemSkipped {
+                        stage: stage_name.clone(),
+                        item_id: doc.id.clone(),
+                    })
+                    .await;
+                continue;
+            }
+
+            let work_handle = match self.queue.claim(doc, filter).await {
+                Ok(h) => h,
+                Err(WorkQueueError::AlreadyClaimed) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to claim {}: {}", doc.id, e);
+                    continue;
+                }
+            };
+            // The pending claim is replaced when the result is stored below
+            let _ = self.queue.complete(work_handle).await;
+
+            let doc_id = doc.id.clone();
+            let title = doc.title.clone();
+            let doc_repo = self.doc_repo.clone();
+            let backend = self.backend.clone();
+            let succeeded = succeeded.clone();
+            let failed = failed.clone();
+            let event_tx = event_tx.clone();
+            let stage_name = stage_name.clone();
+
+            let handle = tokio::task::spawn_blocking(move || {
+                let _ = futures::executor::block_on(event_tx.send(PipelineEvent::ItemStarted {
+                    stage: stage_name.clone(),
+                    item_id: doc_id.clone(),
+                    label: title.clone(),
+                }));
+
+                let rt_handle = tokio::runtime::Handle::current();
+                let version_id = version.id as i32;
+
+                // Encrypted media is decrypted to a temp file for the backend
+                let analyzed = foia::storage::plaintext_path(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|media| backend.analyze_file(&media).map_err(|e| e.to_string()));
+                let result = match analyzed {
+                    Ok(result) => result,
+                    Err(e) => {
+                        tracing::warn!("Transcription failed for {}: {}", title, e);
+                        let error = e.to_string();
+                        let _ = rt_handle.block_on(doc_repo.store_analysis_result_for_document(
+                            &doc_id,
+                            version_id,
+                            "whisper",
+                            backend.backend_id(),
+                            None,
+                            None,
+                            None,
+                            None,
+                            Some(&error),
+                            None,
+                        ));
+                        failed.fetch_add(1, Ordering::Relaxed);
+                        let _ =
+                            futures::executor::block_on(event_tx.send(PipelineEvent::ItemFailed {
+                                stage: stage_name,
+                                item_id: doc_id,
+                                error,
+                            }));
+                        return;
+                    }
+                };
+
+                let stored = rt_handle.block_on(async {
+                    let mut page = DocumentPage::new(doc_id.clone(), version.id, 1);
+                    page.pdf_text = Some(result.text.clone());
+                    page.final_text = Some(result.text.clone());
+                    page.ocr_status = PageOcrStatus::OcrComplete;
+                    doc_repo.save_page(&page).await?;
+                    doc_repo.set_version_page_count(version.id, 1).await?;
+                    doc_repo.finalize_document(&doc_id).await?;
+                    doc_repo
+                        .store_analysis_result_for_document(
+                            &doc_id,
+                            version_id,
+                            "whisper",
+                            &result.backend,
+                            result.model.as_deref(),
+                            Some(&result.text),
+                            result.confidence,
+                            Some(result.processing_time_ms),
+                            None,
+                            result.metadata.as_ref(),
+                        )
+                        .await
+                });
+
+                match stored {
+                    Ok(_) => {
+                        let segments = result
+                            .metadata
+                            .as_ref()
+                            .and_then(|m| m["segments"].as_array())
+                            .map_or(0, |s| s.len());
+                        succeeded.fetch_add(1, Ordering::Relaxed);
+                        let _ = futures::executor::block_on(event_tx.send(
+                            PipelineEvent::ItemCompleted {
+                                stage: stage_name,
+                                item_id: doc_id,
+                                detail: Some(format!("{} segments", segments)),
+                            },
+                        ));
+                    }
+                    Err(e) => {
+                        failed.fetch_add(1, Ordering::Relaxed);
+                        let _ =
+                            futures::executor::block_on(event_tx.send(PipelineEvent::ItemFailed {
+                                stage: stage_name,
+                                item_id: doc_id,
+                                error: e.to_string(),
+                            }));
+                    }
+                }
+            });
+
+            handles.push(handle);
+
+            if handles.len() >= self.workers {
+                for h in handles.drain(..) {
+                    if let Err(e) = h.await {
+                        tracing::error!("Transcription worker panicked: {}", e);
+                    }
+                }
+            }
+        }
+
+        for h in handles {
+            if let Err(e) = h.await {
+                tracing::error!("Transcription worker panicked: {}", e);
+            }
+        }
+
+        Ok(ChunkResult {
+            succeeded: succeeded.load(Ordering::Relaxed),
+            failed: failed.load(Ordering::Relaxed),
+            skipped: skipped.load(Ordering::Relaxed),
+            has_more,
+        })
+    }
+}
+
+/// Virtual file stage — extracts text from archive members and email
+/// attachments that were catalogued without it.
+///
+/// Each member is unpacked from its parent's stored file; the text lands in
+/// `virtual_files.extracted_text`, where search and annotation pick it up.
+pub struct VirtualFileTextStage {
+    doc_repo: DieselDocumentRepository,
+    documents_dir: PathBuf,
+    source_id: Option<String>,
+    workers: usize,
+    cursor: Mutex<Option<String>>,
+}
+
+impl VirtualFileTextStage {
+    pub fn new(
+        doc_repo: DieselDocumentRepository,
+        documents_dir: PathBuf,
+        source_id: Option<&str>,
+        workers: usize,
+    ) -> Self {
+        Self {
+            doc_repo,
+            documents_dir,
+            source_id: source_id.map(Into::into),
+            workers,
+            cursor: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for VirtualFileTextStage {
+    fn name(&self) -> &str {
+        "Attachment text"
+    }
+
+    fn is_deferred(&self) -> bool {
+        false
+    }
+
+    async fn count(&self) -> Result<u64, PipelineError> {
+        self.doc_repo
+            .count_virtual_files_needing_text(self.source_id.as_deref())
+            .await
+            .map_err(|e| PipelineError::Other(e.into()))
+    }
+
+    async fn run_chunk(
+        &self,
+        chunk_size: usize,
+        remaining_limit: usize,
+        event_tx: &mpsc::Sender<PipelineEvent>,
+    ) -> Result<ChunkResult, PipelineError> {
+        let batch_limit = if remaining_limit > 0 {
+            chunk_size.min(remaining_limit)
+        } else {
+            chunk_size
+        };
+
+        let cursor = self.cursor.lock().await.clone();
+        let files = self
+            .doc_repo
+            .get_virtual_files_needing_text(
+                self.source_id.as_deref(),
+                batch_limit,
+                cursor.as_deref(),
+            )
+            .await
+            .map_err(|e| PipelineError::Other(e.into()))?;
+
+        let Some(last) = files.last() else {
+            return Ok(ChunkResult::default());
+        };
+        *self.cursor.lock().await = Some(last.id.clone());
+        let has_more = files.len() >= batch_limit;
+
+        let succeeded = Arc::new(AtomicUsize::new(0));
+        let failed = Arc::new(AtomicUsize::new(0));
+        let mut skipped = 0usize;
+
+        let mut handles = Vec::with_capacity(files.len().min(self.workers));
+        let stage_name = self.name().to_string();
+        // Members of one archive are usually adjacent; load each parent once
+        let mut parents: HashMap<String, Option<Arc<Document>>> = HashMap::new();
+
+        for vf in files {
+            if vf.is_placeholder() {
+                let _ = self
+                    .doc_repo
+                    .update_virtual_file_text(&vf.id, None, VirtualFileStatus::Unsupported)
+                    .await;
+                skipped += 1;
+                continue;
+            }
+
+            let parent = match parents.get(&vf.document_id) {
+                Some(parent) => parent.clone(),
+                None => {
+                    let parent = self
+                        .doc_repo
+                        .get(&vf.document_id)
+                        .await
+                        .map_err(|e| PipelineError::Other(e.into()))?
+                        .map(Arc::new);
+                    parents.insert(vf.document_id.clone(), parent.clone());
+                    parent
+                }
+            };
+            let Some(parent) = parent else {
+                skipped += 1;
+                let _ = event_tx
+                    .send(PipelineEvent::ItemSkipped {
+                        stage: stage_name.clone(),
+                        item_id: vf.id.clone(),
+                    })
+                    .await;
+                continue;
+            };
+
+            let doc_repo = self.doc_repo.clone();
+            let documents_dir = self.documents_dir.clone();
+            let succeeded = succeeded.clone();
+            let failed = failed.clone();
+            let event_tx = event_tx.clone();
+            let stage_name = stage_name.clone();
+
+            let handle = tokio::task::spawn_blocking(move || {
+                let _ = futures::executor::block_on(event_tx.send(PipelineEvent::ItemStarted {
+                    stage: stage_name.clone(),
+                    item_id: vf.id.clone(),
+                    label: format!("{} / {}", parent.title, vf.archive_path),
+                }));
+
+                let rt_handle = tokio::runtime::Handle::current();
+                let (text, status, error) =
+                    match extract_virtual_file_text(&parent, &vf, &documents_dir) {
+                        Ok(text) => (Some(text), VirtualFileStatus::OcrComplete, None),
+                        Err(e) if e.to_string().contains("Unsupported file type") => {
+                            (None, VirtualFileStatus::Unsupported, None)
+                        }
+                        Err(e) => {
+                            tracing::debug!(
+                                "Text extraction failed for {}: {}",
+                                vf.archive_path,
+                                e
+                            );
+                            (None, VirtualFileStatus::Failed, Some(e.to_string()))
+                        }
+                    };
+
+                let stored = rt_handle.block_on(doc_repo.update_virtual_file_text(
+                    &vf.id,
+                    text.as_deref(),
+                    status,
+                ));
+                let error = match stored {
+                    Ok(()) => error,
+                    Err(e) => Some(e.to_string()),
+                };
+
+                match error {
+                    None => {
+                        succeeded.fetch_add(1, Ordering::Relaxed);
+                        let _ = futures::executor::block_on(event_tx.send(
+                            PipelineEvent::ItemCompleted {
+                                stage: stage_name,
+                                item_id: vf.id,
+                                detail: Some(status.as_str().to_string()),
+                            },
+                        ));
+                    }
+                    Some(error) => {
+                        failed.fetch_add(1, Ordering::Relaxed);
+                        let _ =
+                            futures::executor::block_on(event_tx.send(PipelineEvent::ItemFailed {
+                                stage: stage_name,
+                                item_id: vf.id,
+                                error,
+                            }));
+                    }
+                }
+            });
+
+            handles.push(handle);
+
+            if handles.len() >= self.workers {
+                for h in handles.drain(..) {
+                    if let Err(e) = h.await {
+                        tracing::error!("Attachment text worker panicked: {}", e);
+                    }
+                }
+            }
+        }
+
+        for h in handles {
+            if let Err(e) = h.await {
+                tracing::error!("Attachment text worker panicked: {}", e);
+            }
+        }
+
+        Ok(ChunkResult {
+            succeeded: succeeded.load(Ordering::Relaxed),
+            failed: failed.load(Ordering::Relaxed),
+            skipped,
+            has_more,
+        })
+    }
+}
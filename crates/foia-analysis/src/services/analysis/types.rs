@@ -66,6 +66,38 @@ pub enum AnalysisEvent {
         skipped: usize,
         failed: usize,
     },
+
+    /// Transcription of audio/video documents started
+    TranscriptionStarted { total_documents: usize },
+    /// Document transcribed into timed segments
+    DocumentTranscribed {
+        document_id: String,
+        segments: usize,
+    },
+    /// Document transcription failed
+    TranscriptionFailed { document_id: String, error: String },
+    /// Transcription complete
+    TranscriptionComplete {
+        succeeded: usize,
+        failed: usize,
+        skipped: usize,
+    },
+
+    /// Text extraction for archive members and attachments started
+    VirtualFilesStarted { total_files: usize },
+    /// Archive member or attachment processed
+    VirtualFileCompleted { virtual_file_id: String },
+    /// Archive member or attachment extraction failed
+    VirtualFileFailed {
+        virtual_file_id: String,
+        error: String,
+    },
+    /// Archive member and attachment extraction complete
+    VirtualFilesComplete {
+        succeeded: usize,
+        failed: usize,
+        skipped: usize,
+    },
 }
 
 /// Result of document analysis.
@@ -80,6 +112,10 @@ pub struct AnalysisResult {
     pub phase2_improved: usize,
     pub phase2_skipped: usize,
     pub phase2_failed: usize,
+    pub transcribed: usize,
+    pub transcription_failed: usize,
+    pub virtual_files_extracted: usize,
+    pub virtual_files_failed: usize,
 }
 
 /// Result of OCR on a single page.
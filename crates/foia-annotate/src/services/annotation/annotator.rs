@@ -2,7 +2,7 @@
 
 use async_trait::async_trait;
 
-use foia::models::Document;
+use foia::models::{Document, VirtualFile};
 use foia::repository::DieselDocumentRepository;
 
 use super::types::{AnnotationError, AnnotationOutput};
@@ -50,6 +50,31 @@ pub trait Annotator: Send + Sync {
         doc_repo: &DieselDocumentRepository,
     ) -> Result<AnnotationOutput, AnnotationError>;
 
+    /// Documents handed to each `annotate_batch` call.
+    fn batch_size(&self) -> usize {
+        1
+    }
+
+    /// Batches the annotation stage runs at once.
+    fn concurrency(&self) -> usize {
+        1
+    }
+
+    /// Annotate several documents, returning one result per document in
+    /// order. Backends that can combine documents into fewer requests
+    /// override this; the default annotates them one by one.
+    async fn annotate_batch(
+        &self,
+        docs: &[Document],
+        doc_repo: &DieselDocumentRepository,
+    ) -> Vec<Result<AnnotationOutput, AnnotationError>> {
+        let mut results = Vec::with_capacity(docs.len());
+        for doc in docs {
+            results.push(self.annotate(doc, doc_repo).await);
+        }
+        results
+    }
+
     /// Post-processing hook called after annotation data is recorded.
     /// Used by NerAnnotator to populate the document_entities table.
     /// Default implementation is a no-op.
@@ -61,6 +86,39 @@ pub trait Annotator: Send + Sync {
     ) -> Result<(), AnnotationError> {
         Ok(())
     }
+
+    /// Called instead of `annotate` when a processing profile skips this
+    /// annotation for the document's record type, so annotators whose queue
+    /// isn't driven by recorded annotations can move the document on.
+    /// Default implementation is a no-op.
+    async fn skip_document(
+        &self,
+        _doc: &Document,
+        _doc_repo: &DieselDocumentRepository,
+    ) -> Result<(), AnnotationError> {
+        Ok(())
+    }
+
+    /// Whether this annotator also runs on archive members and attachments.
+    fn annotates_virtual_files(&self) -> bool {
+        false
+    }
+
+    /// Whether virtual files need extracted text before they can be annotated.
+    fn virtual_file_requires_text(&self) -> bool {
+        true
+    }
+
+    /// Annotate an archive member or attachment of `parent`.
+    /// Results are recorded in `virtual_file_annotations`.
+    async fn annotate_virtual_file(
+        &self,
+        _vf: &VirtualFile,
+        _parent: &Document,
+        _doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        Ok(AnnotationOutput::Skipped)
+    }
 }
 
 /// Extract combined page text for a document, returning Err(Skipped) if
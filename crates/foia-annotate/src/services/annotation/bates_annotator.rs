@@ -0,0 +1,108 @@
+//! Bates number annotator — finds page stamps and records each document's
+//! Bates ranges.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use foia::models::Document;
+use foia::repository::DieselDocumentRepository;
+use foia::services::bates::{find_page_stamp, page_ranges, BatesRange};
+
+use super::annotator::Annotator;
+use super::types::{AnnotationError, AnnotationOutput};
+
+/// Annotation data, also written to `document_bates`.
+#[derive(Debug, Serialize, Deserialize)]
+struct BatesData {
+    version_id: i32,
+    ranges: Vec<BatesRange>,
+}
+
+/// Annotator that extracts Bates stamps page by page.
+///
+/// Each page's first and last lines are searched for a stamp; consecutive
+/// stamped pages become ranges, stored in `document_bates` for lookup by
+/// number and gap detection.
+#[derive(Default)]
+pub struct BatesAnnotator;
+
+impl BatesAnnotator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Annotator for BatesAnnotator {
+    fn annotation_type(&self) -> &str {
+        "bates_extraction"
+    }
+
+    fn display_name(&self) -> &str {
+        "Bates Number Extraction"
+    }
+
+    async fn annotate(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        let Some(version) = doc.current_version() else {
+            return Ok(AnnotationOutput::Skipped);
+        };
+        let version_id = version.id as i32;
+        let pages = doc_repo
+            .get_pages(&doc.id, version_id)
+            .await
+            .map_err(|e| AnnotationError::Database(e.to_string()))?;
+        if pages.is_empty() {
+            return Ok(AnnotationOutput::Skipped);
+        }
+
+        let stamps: Vec<_> = pages
+            .iter()
+            .filter_map(|page| {
+                let text = page
+                    .final_text
+                    .as_deref()
+                    .or(page.ocr_text.as_deref())
+                    .or(page.pdf_text.as_deref())?;
+                Some((page.page_number, find_page_stamp(text)?))
+            })
+            .collect();
+        if stamps.is_empty() {
+            return Ok(AnnotationOutput::NoResult);
+        }
+
+        let data = BatesData {
+            version_id,
+            ranges: page_ranges(&stamps),
+        };
+        let data =
+            serde_json::to_string(&data).map_err(|e| AnnotationError::Failed(e.to_string()))?;
+        Ok(AnnotationOutput::Data(data))
+    }
+
+    async fn post_record(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+        output: &AnnotationOutput,
+    ) -> Result<(), AnnotationError> {
+        // A re-extracted version without stamps drops the old ranges
+        let data = match output {
+            AnnotationOutput::Data(d) => serde_json::from_str(d).map_err(|e| {
+                AnnotationError::Failed(format!("Failed to parse Bates ranges: {}", e))
+            })?,
+            AnnotationOutput::NoResult => BatesData {
+                version_id: doc.current_version().map(|v| v.id as i32).unwrap_or(0),
+                ranges: Vec::new(),
+            },
+            AnnotationOutput::Skipped => return Ok(()),
+        };
+        doc_repo
+            .replace_bates_ranges(&doc.id, data.version_id, &data.ranges)
+            .await
+            .map_err(|e| AnnotationError::Database(e.to_string()))
+    }
+}
@@ -0,0 +1,142 @@
+//! Record-type classification annotator — sorts documents into letters,
+//! memos, emails, forms, invoices, reports, photos and maps.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::services::classification::classify;
+use foia::llm::{LlmClient, LlmConfig};
+use foia::models::{Document, RecordType};
+use foia::repository::DieselDocumentRepository;
+
+use super::annotator::{get_document_text, Annotator};
+use super::types::{AnnotationError, AnnotationOutput};
+
+/// Annotation data, also written to `document_classifications`.
+#[derive(Debug, Serialize, Deserialize)]
+struct ClassificationData {
+    record_type: RecordType,
+    confidence: f32,
+    classifier: String,
+}
+
+/// Annotator that assigns each document a record type.
+///
+/// Uses the heuristic classifier by default. With an LLM configured, text
+/// documents are classified by the model instead; images with little text
+/// still go to the heuristic, since the model only sees text.
+pub struct ClassificationAnnotator {
+    llm: Option<(LlmClient, LlmConfig)>,
+}
+
+impl ClassificationAnnotator {
+    pub fn new() -> Self {
+        Self { llm: None }
+    }
+
+    /// Classify text documents with an LLM.
+    pub fn with_llm(config: LlmConfig) -> Self {
+        Self {
+            llm: Some((LlmClient::new(config.clone()), config)),
+        }
+    }
+}
+
+impl Default for ClassificationAnnotator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Annotator for ClassificationAnnotator {
+    fn annotation_type(&self) -> &str {
+        "classification"
+    }
+
+    fn display_name(&self) -> &str {
+        "Record Type Classification"
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.llm.is_some()
+    }
+
+    async fn is_available(&self) -> bool {
+        match &self.llm {
+            Some((client, _)) => client.is_available().await,
+            None => true,
+        }
+    }
+
+    fn availability_hint(&self) -> String {
+        match &self.llm {
+            Some((_, config)) => config.availability_hint(),
+            None => String::new(),
+        }
+    }
+
+    async fn annotate(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        let Some(version) = doc.current_version() else {
+            return Ok(AnnotationOutput::Skipped);
+        };
+        // Photos and maps often have no text at all
+        let text = get_document_text(doc, doc_repo).await.unwrap_or_default();
+
+        let Some(heuristic) = classify(&version.mime_type, &doc.title, &text) else {
+            return Ok(AnnotationOutput::Skipped);
+        };
+        // Images the heuristic judged by file type have nothing for the model
+        let from_text = !matches!(heuristic.record_type, RecordType::Photo | RecordType::Map);
+        let data = match &self.llm {
+            Some((client, config)) if from_text => {
+                let (record_type, confidence) = client
+                    .classify_record_type(&text, &doc.title)
+                    .await
+                    .map_err(|e| {
+                        if e.is_transient() {
+                            AnnotationError::Transient(e.to_string())
+                        } else {
+                            AnnotationError::Failed(e.to_string())
+                        }
+                    })?;
+                ClassificationData {
+                    record_type,
+                    confidence,
+                    classifier: format!("llm:{}", config.model()),
+                }
+            }
+            _ => ClassificationData {
+                record_type: heuristic.record_type,
+                confidence: heuristic.confidence,
+                classifier: "heuristic".to_string(),
+            },
+        };
+
+        let data =
+            serde_json::to_string(&data).map_err(|e| AnnotationError::Failed(e.to_string()))?;
+        Ok(AnnotationOutput::Data(data))
+    }
+
+    async fn post_record(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+        output: &AnnotationOutput,
+    ) -> Result<(), AnnotationError> {
+        let AnnotationOutput::Data(data) = output else {
+            return Ok(());
+        };
+        let data: ClassificationData = serde_json::from_str(data).map_err(|e| {
+            AnnotationError::Failed(format!("Failed to parse classification: {}", e))
+        })?;
+        doc_repo
+            .set_classification(&doc.id, data.record_type, data.confidence, &data.classifier)
+            .await
+            .map_err(|e| AnnotationError::Database(e.to_string()))
+    }
+}
@@ -3,14 +3,18 @@
 use async_trait::async_trait;
 
 use crate::services::date_detection::detect_date;
-use foia::models::Document;
+use foia::models::{Document, VirtualFile};
 use foia::repository::DieselDocumentRepository;
 
 use super::annotator::Annotator;
 use super::types::{AnnotationError, AnnotationOutput};
 
 /// Annotator that estimates document publication dates from metadata signals
-/// (server headers, filename patterns, URL paths).
+/// (recorded publish dates, server headers, filename patterns, URL paths).
+///
+/// Archive members and attachments are dated from their own filename and the
+/// parent's signals; the estimate is kept as the annotation data since virtual
+/// files have no date columns.
 pub struct DateAnnotator {
     dry_run: bool,
 }
@@ -43,6 +47,7 @@ impl Annotator for DateAnnotator {
         let source_url = Some(doc.source_url.clone());
 
         let estimate = detect_date(
+            &doc.metadata,
             server_date,
             acquired_at,
             filename.as_deref(),
@@ -70,4 +75,46 @@ impl Annotator for DateAnnotator {
             None => Ok(AnnotationOutput::NoResult),
         }
     }
+
+    fn annotates_virtual_files(&self) -> bool {
+        // The annotation record is the only place a file's estimate is stored
+        !self.dry_run
+    }
+
+    fn virtual_file_requires_text(&self) -> bool {
+        false
+    }
+
+    async fn annotate_virtual_file(
+        &self,
+        vf: &VirtualFile,
+        parent: &Document,
+        _doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        let version = parent
+            .versions
+            .iter()
+            .find(|v| v.id == vf.version_id)
+            .or_else(|| parent.current_version());
+        let server_date = version.and_then(|v| v.server_date);
+        let acquired_at = version.map(|v| v.acquired_at).unwrap_or(parent.created_at);
+
+        match detect_date(
+            &parent.metadata,
+            server_date,
+            acquired_at,
+            Some(&vf.filename),
+            None,
+        ) {
+            Some(est) => Ok(AnnotationOutput::Data(
+                serde_json::json!({
+                    "date": est.date.to_rfc3339(),
+                    "confidence": est.confidence.as_str(),
+                    "source": est.source.as_str(),
+                })
+                .to_string(),
+            )),
+            None => Ok(AnnotationOutput::NoResult),
+        }
+    }
 }
@@ -0,0 +1,102 @@
+//! FOIA exemption annotator — counts exemption markings such as `(b)(5)`
+//! and `b7C` on each page.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use foia::models::Document;
+use foia::repository::DieselDocumentRepository;
+use foia::services::exemptions::{page_exemptions, PageExemption};
+
+use super::annotator::Annotator;
+use super::types::{AnnotationError, AnnotationOutput};
+
+/// Annotation data, also written to `document_exemptions`.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExemptionData {
+    version_id: i32,
+    exemptions: Vec<PageExemption>,
+}
+
+/// Annotator that records the exemptions cited on each page, for
+/// per-source statistics and the browse filter.
+#[derive(Default)]
+pub struct ExemptionAnnotator;
+
+impl ExemptionAnnotator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Annotator for ExemptionAnnotator {
+    fn annotation_type(&self) -> &str {
+        "exemption_extraction"
+    }
+
+    fn display_name(&self) -> &str {
+        "Exemption Extraction"
+    }
+
+    async fn annotate(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        let Some(version) = doc.current_version() else {
+            return Ok(AnnotationOutput::Skipped);
+        };
+        let version_id = version.id as i32;
+        let pages = doc_repo
+            .get_pages(&doc.id, version_id)
+            .await
+            .map_err(|e| AnnotationError::Database(e.to_string()))?;
+        if pages.is_empty() {
+            return Ok(AnnotationOutput::Skipped);
+        }
+
+        let exemptions = page_exemptions(pages.iter().filter_map(|page| {
+            let text = page
+                .final_text
+                .as_deref()
+                .or(page.ocr_text.as_deref())
+                .or(page.pdf_text.as_deref())?;
+            Some((page.page_number, text))
+        }));
+        if exemptions.is_empty() {
+            return Ok(AnnotationOutput::NoResult);
+        }
+
+        let data = ExemptionData {
+            version_id,
+            exemptions,
+        };
+        let data =
+            serde_json::to_string(&data).map_err(|e| AnnotationError::Failed(e.to_string()))?;
+        Ok(AnnotationOutput::Data(data))
+    }
+
+    async fn post_record(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+        output: &AnnotationOutput,
+    ) -> Result<(), AnnotationError> {
+        // A re-extracted version without markings drops the old ones
+        let data = match output {
+            AnnotationOutput::Data(d) => serde_json::from_str(d).map_err(|e| {
+                AnnotationError::Failed(format!("Failed to parse exemptions: {}", e))
+            })?,
+            AnnotationOutput::NoResult => ExemptionData {
+                version_id: doc.current_version().map(|v| v.id as i32).unwrap_or(0),
+                exemptions: Vec::new(),
+            },
+            AnnotationOutput::Skipped => return Ok(()),
+        };
+        doc_repo
+            .replace_exemptions(&doc.id, data.version_id, &data.exemptions)
+            .await
+            .map_err(|e| AnnotationError::Database(e.to_string()))
+    }
+}
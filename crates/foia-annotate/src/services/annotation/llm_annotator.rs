@@ -2,8 +2,8 @@
 
 use async_trait::async_trait;
 
-use foia::llm::{LlmClient, LlmConfig};
-use foia::models::{Document, DocumentStatus};
+use foia::llm::{BatchItem, LlmClient, LlmConfig, LlmError, SummarizeResult};
+use foia::models::{Document, DocumentStatus, VirtualFile};
 use foia::repository::DieselDocumentRepository;
 
 use super::annotator::{get_document_text, Annotator};
@@ -13,6 +13,10 @@ use super::types::{AnnotationError, AnnotationOutput};
 ///
 /// Unlike simpler annotators, this one also updates the document's
 /// `synopsis`, `tags`, and `status` fields (setting status to `Indexed`).
+/// Archive members and attachments get their own synopsis and tags.
+///
+/// Where the provider allows it, short documents are summarized several to
+/// a request, and requests share the client's concurrency and rate budget.
 pub struct LlmAnnotator {
     llm_client: LlmClient,
     config: LlmConfig,
@@ -28,6 +32,63 @@ impl LlmAnnotator {
     pub fn llm_config(&self) -> &LlmConfig {
         &self.config
     }
+
+    /// Summarize one document's text and save the result.
+    async fn summarize_one(
+        &self,
+        doc: &Document,
+        text: &str,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        let result = self
+            .llm_client
+            .summarize(text, &doc.title)
+            .await
+            .map_err(annotation_error)?;
+        save_summary(doc, result, &self.config, doc_repo).await
+    }
+}
+
+fn annotation_error(e: LlmError) -> AnnotationError {
+    if e.is_transient() {
+        AnnotationError::Transient(e.to_string())
+    } else {
+        AnnotationError::Failed(e.to_string())
+    }
+}
+
+/// Annotation data for a synopsis, recording what produced it so stale
+/// summaries can be found after a model or prompt change.
+fn summary_data(result: &SummarizeResult, config: &LlmConfig) -> AnnotationOutput {
+    let data = serde_json::json!({
+        "synopsis_len": result.synopsis.len(),
+        "tag_count": result.tags.len(),
+        "provider": config.provider_name(),
+        "model": config.model(),
+        "prompt_version": config.prompt_version(),
+    });
+    AnnotationOutput::Data(data.to_string())
+}
+
+/// Update the document with synopsis, tags, and status.
+async fn save_summary(
+    doc: &Document,
+    result: SummarizeResult,
+    config: &LlmConfig,
+    doc_repo: &DieselDocumentRepository,
+) -> Result<AnnotationOutput, AnnotationError> {
+    let mut updated_doc = doc.clone();
+    updated_doc.synopsis = Some(result.synopsis.clone());
+    updated_doc.tags = result.tags.clone();
+    updated_doc.status = DocumentStatus::Indexed;
+    updated_doc.updated_at = chrono::Utc::now();
+
+    doc_repo
+        .save(&updated_doc)
+        .await
+        .map_err(|e| AnnotationError::Database(format!("Save failed: {}", e)))?;
+
+    Ok(summary_data(&result, config))
 }
 
 #[async_trait]
@@ -61,30 +122,120 @@ impl Annotator for LlmAnnotator {
             Ok(t) => t,
             Err(output) => return Ok(output),
         };
+        self.summarize_one(doc, &text, doc_repo).await
+    }
+
+    /// The summarization queue is status-driven, so a skipped document is
+    /// marked indexed without a synopsis.
+    async fn skip_document(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<(), AnnotationError> {
+        doc_repo
+            .update_status(&doc.id, DocumentStatus::Indexed)
+            .await
+            .map_err(|e| AnnotationError::Database(format!("Status update failed: {}", e)))
+    }
+
+    fn batch_size(&self) -> usize {
+        self.config.batch_size()
+    }
+
+    fn concurrency(&self) -> usize {
+        self.config.max_concurrent()
+    }
+
+    async fn annotate_batch(
+        &self,
+        docs: &[Document],
+        doc_repo: &DieselDocumentRepository,
+    ) -> Vec<Result<AnnotationOutput, AnnotationError>> {
+        let mut results: Vec<Option<Result<AnnotationOutput, AnnotationError>>> =
+            (0..docs.len()).map(|_| None).collect();
+        let mut texts: Vec<Option<String>> = Vec::with_capacity(docs.len());
+        for (i, doc) in docs.iter().enumerate() {
+            match get_document_text(doc, doc_repo).await {
+                Ok(t) => texts.push(Some(t)),
+                Err(output) => {
+                    results[i] = Some(Ok(output));
+                    texts.push(None);
+                }
+            }
+        }
+
+        // Short documents share one request; long ones go alone
+        let short: Vec<usize> = (0..docs.len())
+            .filter(|&i| {
+                texts[i]
+                    .as_ref()
+                    .is_some_and(|t| t.len() <= self.config.batch_max_chars())
+            })
+            .collect();
+        if short.len() > 1 {
+            let items: Vec<BatchItem> = short
+                .iter()
+                .map(|&i| BatchItem {
+                    title: &docs[i].title,
+                    text: texts[i].as_deref().unwrap_or_default(),
+                })
+                .collect();
+            match self.llm_client.summarize_batch(&items).await {
+                Ok(summaries) => {
+                    for (&i, summary) in short.iter().zip(summaries) {
+                        if let Some(summary) = summary {
+                            results[i] =
+                                Some(save_summary(&docs[i], summary, &self.config, doc_repo).await);
+                        }
+                    }
+                }
+                Err(e) if e.is_transient() => {
+                    for &i in &short {
+                        results[i] = Some(Err(AnnotationError::Transient(e.to_string())));
+                    }
+                }
+                // Fall back to one request per document below
+                Err(e) => tracing::warn!("Batch summarization failed: {}", e),
+            }
+        }
+
+        let mut out = Vec::with_capacity(docs.len());
+        for (i, result) in results.into_iter().enumerate() {
+            out.push(match (result, texts[i].as_deref()) {
+                (Some(r), _) => r,
+                (None, Some(text)) => self.summarize_one(&docs[i], text, doc_repo).await,
+                (None, None) => Ok(AnnotationOutput::Skipped),
+            });
+        }
+        out
+    }
+
+    fn annotates_virtual_files(&self) -> bool {
+        true
+    }
+
+    async fn annotate_virtual_file(
+        &self,
+        vf: &VirtualFile,
+        _parent: &Document,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        let text = match vf.extracted_text.as_deref() {
+            Some(t) if !t.trim().is_empty() => t,
+            _ => return Ok(AnnotationOutput::NoResult),
+        };
 
         let result = self
             .llm_client
-            .summarize(&text, &doc.title)
+            .summarize(text, &vf.filename)
             .await
-            .map_err(|e| AnnotationError::Failed(e.to_string()))?;
-
-        // Update document with synopsis, tags, and status
-        let mut updated_doc = doc.clone();
-        updated_doc.synopsis = Some(result.synopsis.clone());
-        updated_doc.tags = result.tags.clone();
-        updated_doc.status = DocumentStatus::Indexed;
-        updated_doc.updated_at = chrono::Utc::now();
+            .map_err(annotation_error)?;
 
         doc_repo
-            .save(&updated_doc)
+            .update_virtual_file_summary(&vf.id, &result.synopsis, &result.tags)
             .await
             .map_err(|e| AnnotationError::Database(format!("Save failed: {}", e)))?;
 
-        let data = serde_json::json!({
-            "synopsis_len": result.synopsis.len(),
-            "tag_count": result.tags.len(),
-        });
-
-        Ok(AnnotationOutput::Data(data.to_string()))
+        Ok(summary_data(&result, &self.config))
     }
 }
@@ -1,48 +1,98 @@
 //! Annotation manager — generic batch orchestration for any `Annotator`.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use tokio::sync::mpsc;
 
-use foia::repository::DieselDocumentRepository;
+use foia::repository::{DieselDocumentRepository, DieselScraperConfigRepository};
 use foia::work_queue::db_annotation::DbAnnotationQueue;
 use foia::work_queue::{
     ExecutionStrategy, PipelineEvent, PipelineRunner, WorkFilter, WorkQueue,
 };
 
 use super::annotator::Annotator;
-use super::stage::AnnotationStage;
+use super::stage::{AnnotationStage, VirtualFileAnnotationStage};
 use super::types::{AnnotationEvent, AnnotationOutput, BatchAnnotationResult};
 
 /// Orchestrates batch annotation using a registered `Annotator`.
 pub struct AnnotationManager {
     doc_repo: DieselDocumentRepository,
+    scraper_configs: Option<DieselScraperConfigRepository>,
 }
 
 impl AnnotationManager {
     pub fn new(doc_repo: DieselDocumentRepository) -> Self {
-        Self { doc_repo }
+        Self {
+            doc_repo,
+            scraper_configs: None,
+        }
+    }
+
+    /// Skip documents from sources whose processing profile excludes the
+    /// annotation being run.
+    pub fn with_processing_profiles(
+        mut self,
+        scraper_configs: DieselScraperConfigRepository,
+    ) -> Self {
+        self.scraper_configs = Some(scraper_configs);
+        self
     }
 
     /// Build a WorkFilter from annotator metadata and optional source filter.
-    fn build_filter(annotator: &dyn Annotator, source_id: Option<&str>) -> WorkFilter {
-        WorkFilter {
+    async fn build_filter(
+        &self,
+        annotator: &dyn Annotator,
+        source_id: Option<&str>,
+    ) -> anyhow::Result<WorkFilter> {
+        let exclude_sources = match &self.scraper_configs {
+            Some(configs) => {
+                configs
+                    .sources_skipping(annotator.annotation_type())
+                    .await?
+            }
+            None => Vec::new(),
+        };
+        Ok(WorkFilter {
             work_type: annotator.annotation_type().into(),
             source_id: source_id.map(Into::into),
+            exclude_sources,
             version: Some(annotator.version()),
             ..Default::default()
-        }
+        })
     }
 
-    /// Count documents that still need the given annotation.
+    /// Count documents, and virtual files if the annotator handles them,
+    /// that still need the given annotation.
     pub async fn count_needing(
         &self,
         annotator: &dyn Annotator,
         source_id: Option<&str>,
     ) -> anyhow::Result<u64> {
         let queue = DbAnnotationQueue::new(self.doc_repo.clone());
-        let filter = Self::build_filter(annotator, source_id);
-        Ok(queue.count(&filter).await?)
+        let filter = self.build_filter(annotator, source_id).await?;
+        let documents = queue.count(&filter).await?;
+        Ok(documents + self.count_virtual_files_needing(annotator, &filter).await?)
+    }
+
+    async fn count_virtual_files_needing(
+        &self,
+        annotator: &dyn Annotator,
+        filter: &WorkFilter,
+    ) -> anyhow::Result<u64> {
+        if !annotator.annotates_virtual_files() {
+            return Ok(0);
+        }
+        Ok(self
+            .doc_repo
+            .count_virtual_files_needing_annotation(
+                annotator.annotation_type(),
+                annotator.version(),
+                annotator.virtual_file_requires_text(),
+                filter.source_id.as_deref(),
+                &filter.exclude_sources,
+            )
+            .await?)
     }
 
     /// Run a batch of annotations, emitting events for progress tracking.
@@ -75,9 +125,12 @@ impl AnnotationManager {
         }
 
         let queue = DbAnnotationQueue::new(self.doc_repo.clone());
-        let filter = Self::build_filter(annotator.as_ref(), source_id);
+        let filter = self.build_filter(annotator.as_ref(), source_id).await?;
 
-        let total_count = queue.count(&filter).await?;
+        let total_count = queue.count(&filter).await?
+            + self
+                .count_virtual_files_needing(annotator.as_ref(), &filter)
+                .await?;
 
         if total_count == 0 {
             let _ = event_tx
@@ -98,14 +151,29 @@ impl AnnotationManager {
 
         let effective_chunk = chunk_size.unwrap_or(4096);
 
-        let stage = AnnotationStage::new(
-            self.doc_repo.clone(),
-            annotator.clone(),
-            source_id,
-        );
+        let record_type_skips = match &self.scraper_configs {
+            Some(configs) => {
+                configs
+                    .record_type_skips(annotator.annotation_type())
+                    .await?
+            }
+            None => HashMap::new(),
+        };
+        let stage = AnnotationStage::new(self.doc_repo.clone(), annotator.clone(), source_id)
+            .with_excluded_sources(filter.exclude_sources.clone())
+            .with_record_type_skips(record_type_skips);
 
         let mut runner = PipelineRunner::new(effective_chunk, limit);
         runner.add_stage(Box::new(stage));
+        if annotator.annotates_virtual_files() {
+            let vf_stage = VirtualFileAnnotationStage::new(
+                self.doc_repo.clone(),
+                annotator.clone(),
+                source_id,
+            )
+            .with_excluded_sources(filter.exclude_sources);
+            runner.add_stage(Box::new(vf_stage));
+        }
 
         // Bridge PipelineEvent -> AnnotationEvent
         let (pipe_tx, pipe_rx) = mpsc::channel::<PipelineEvent>(100);
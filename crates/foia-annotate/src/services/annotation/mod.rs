@@ -5,19 +5,29 @@
 //! that works with any annotator.
 
 mod annotator;
+mod bates_annotator;
+mod classification_annotator;
 mod date_annotator;
+mod exemption_annotator;
 mod llm_annotator;
 mod manager;
 mod ner_annotator;
+mod section_annotator;
 pub mod stage;
+mod title_annotator;
 mod types;
 mod url_annotator;
 
 pub use annotator::{get_document_text, Annotator};
+pub use bates_annotator::BatesAnnotator;
+pub use classification_annotator::ClassificationAnnotator;
 pub use date_annotator::DateAnnotator;
+pub use exemption_annotator::ExemptionAnnotator;
 pub use llm_annotator::LlmAnnotator;
 pub use manager::AnnotationManager;
 pub use ner_annotator::NerAnnotator;
+pub use section_annotator::SectionAnnotator;
+pub use title_annotator::TitleAnnotator;
 pub use types::{AnnotationError, AnnotationEvent, AnnotationOutput, BatchAnnotationResult};
-pub use stage::AnnotationStage;
+pub use stage::{AnnotationStage, VirtualFileAnnotationStage};
 pub use url_annotator::UrlAnnotator;
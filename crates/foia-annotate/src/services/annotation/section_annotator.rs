@@ -0,0 +1,92 @@
+//! Section annotator — derives the table of contents of long reports from
+//! PDF bookmarks or headings in the page text.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use foia::models::{Document, DocumentSection};
+use foia::repository::DieselDocumentRepository;
+use foia::services::sections::find_sections;
+
+use super::annotator::Annotator;
+use super::types::{AnnotationError, AnnotationOutput};
+
+/// Annotation data, also written to `document_sections`.
+#[derive(Debug, Serialize, Deserialize)]
+struct SectionData {
+    version_id: i64,
+    sections: Vec<DocumentSection>,
+}
+
+/// Annotator that records each document's chapters and sections, for
+/// the reader's contents and section-scoped search and export.
+///
+/// Needs the documents directory to read PDF bookmarks.
+pub struct SectionAnnotator {
+    documents_dir: PathBuf,
+}
+
+impl SectionAnnotator {
+    pub fn new(documents_dir: PathBuf) -> Self {
+        Self { documents_dir }
+    }
+}
+
+#[async_trait]
+impl Annotator for SectionAnnotator {
+    fn annotation_type(&self) -> &str {
+        "section_extraction"
+    }
+
+    fn display_name(&self) -> &str {
+        "Section Extraction"
+    }
+
+    async fn annotate(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        let Some(version) = doc.current_version() else {
+            return Ok(AnnotationOutput::Skipped);
+        };
+        let sections = find_sections(doc_repo, doc, &self.documents_dir)
+            .await
+            .map_err(|e| AnnotationError::Failed(e.to_string()))?;
+        if sections.is_empty() {
+            return Ok(AnnotationOutput::NoResult);
+        }
+
+        let data = SectionData {
+            version_id: version.id,
+            sections,
+        };
+        let data =
+            serde_json::to_string(&data).map_err(|e| AnnotationError::Failed(e.to_string()))?;
+        Ok(AnnotationOutput::Data(data))
+    }
+
+    async fn post_record(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+        output: &AnnotationOutput,
+    ) -> Result<(), AnnotationError> {
+        // A version that lost its structure drops the old sections
+        let data = match output {
+            AnnotationOutput::Data(d) => serde_json::from_str(d)
+                .map_err(|e| AnnotationError::Failed(format!("Failed to parse sections: {}", e)))?,
+            AnnotationOutput::NoResult => SectionData {
+                version_id: doc.current_version().map(|v| v.id).unwrap_or(0),
+                sections: Vec::new(),
+            },
+            AnnotationOutput::Skipped => return Ok(()),
+        };
+        doc_repo
+            .replace_document_sections(&doc.id, data.version_id, &data.sections)
+            .await
+            .map_err(|e| AnnotationError::Database(e.to_string()))
+    }
+}
@@ -1,19 +1,23 @@
-//! Pipeline stage implementation for annotation.
+//! Pipeline stage implementations for annotation of documents and of their
+//! archive members and attachments.
 
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinSet;
 
+use foia::models::Document;
 use foia::repository::DieselDocumentRepository;
 use foia::work_queue::db_annotation::DbAnnotationQueue;
 use foia::work_queue::{
-    ChunkResult, PipelineError, PipelineEvent, PipelineStage, WorkFilter, WorkQueue,
+    ChunkResult, PipelineError, PipelineEvent, PipelineStage, WorkFilter, WorkHandle, WorkQueue,
     WorkQueueError,
 };
 
 use super::annotator::Annotator;
-use super::types::AnnotationOutput;
+use super::types::{AnnotationError, AnnotationOutput};
 
 /// Annotation pipeline stage — runs a single `Annotator` against documents.
 pub struct AnnotationStage {
@@ -21,6 +25,8 @@ pub struct AnnotationStage {
     doc_repo: DieselDocumentRepository,
     annotator: Arc<dyn Annotator>,
     filter: WorkFilter,
+    /// Per source, record types whose documents skip this annotation.
+    record_type_skips: HashMap<String, Vec<String>>,
     cursor: Mutex<Option<String>>,
 }
 
@@ -42,9 +48,22 @@ impl AnnotationStage {
             doc_repo,
             annotator,
             filter,
+            record_type_skips: HashMap::new(),
             cursor: Mutex::new(None),
         }
     }
+
+    /// Skip documents from these sources.
+    pub fn with_excluded_sources(mut self, sources: Vec<String>) -> Self {
+        self.filter.exclude_sources = sources;
+        self
+    }
+
+    /// Skip documents classified as these record types, keyed by source.
+    pub fn with_record_type_skips(mut self, skips: HashMap<String, Vec<String>>) -> Self {
+        self.record_type_skips = skips;
+        self
+    }
 }
 
 #[async_trait]
@@ -83,126 +102,463 @@ impl PipelineStage for AnnotationStage {
         }
 
         let has_more = docs.len() >= batch_limit;
-        let mut succeeded = 0usize;
-        let mut failed = 0usize;
-        let mut skipped = 0usize;
-        let stage_name = self.name().to_string();
+        let mut result = ChunkResult {
+            has_more,
+            ..Default::default()
+        };
 
+        let mut handles = HashMap::new();
         for doc in &docs {
-            // Claim the document
-            let work_handle = match self.queue.claim(doc, &self.filter).await {
-                Ok(h) => h,
-                Err(WorkQueueError::AlreadyClaimed) => {
-                    skipped += 1;
-                    continue;
+            match self.queue.claim(doc, &self.filter).await {
+                Ok(h) => {
+                    handles.insert(doc.id.clone(), h);
+                }
+                Err(WorkQueueError::AlreadyClaimed) => result.skipped += 1,
+                Err(e) => tracing::warn!("Failed to claim {}: {}", doc.id, e),
+            }
+        }
+        let mut claimed: Vec<Document> = docs
+            .into_iter()
+            .filter(|d| handles.contains_key(&d.id))
+            .collect();
+
+        if !self.record_type_skips.is_empty() {
+            let ids: Vec<String> = claimed.iter().map(|d| d.id.clone()).collect();
+            let record_types = self
+                .doc_repo
+                .get_record_types(&ids)
+                .await
+                .unwrap_or_default();
+            let mut kept = Vec::with_capacity(claimed.len());
+            for doc in claimed {
+                let skipped_type = record_types.get(&doc.id).filter(|t| {
+                    self.record_type_skips
+                        .get(&doc.source_id)
+                        .is_some_and(|types| types.contains(t))
+                });
+                match (skipped_type, handles.remove(&doc.id)) {
+                    (Some(record_type), Some(handle)) => {
+                        self.skip_record_type(&doc, record_type, handle, event_tx)
+                            .await;
+                        result.skipped += 1;
+                    }
+                    (None, Some(handle)) => {
+                        handles.insert(doc.id.clone(), handle);
+                        kept.push(doc);
+                    }
+                    (_, None) => {}
                 }
+            }
+            claimed = kept;
+        }
+
+        // Batches go out `concurrency` at a time; each is recorded as soon
+        // as it returns, so an interrupted run only repeats unfinished ones.
+        let mut batches: VecDeque<Vec<Document>> = claimed
+            .chunks(self.annotator.batch_size().max(1))
+            .map(<[Document]>::to_vec)
+            .collect();
+        let concurrency = self.annotator.concurrency().max(1);
+        let mut running = JoinSet::new();
+
+        loop {
+            while running.len() < concurrency {
+                let Some(batch) = batches.pop_front() else {
+                    break;
+                };
+                for doc in &batch {
+                    let _ = event_tx
+                        .send(PipelineEvent::ItemStarted {
+                            stage: self.name().to_string(),
+                            item_id: doc.id.clone(),
+                            label: doc.title.clone(),
+                        })
+                        .await;
+                }
+                let annotator = self.annotator.clone();
+                let doc_repo = self.doc_repo.clone();
+                running.spawn(async move {
+                    let outputs = annotator.annotate_batch(&batch, &doc_repo).await;
+                    (batch, outputs)
+                });
+            }
+
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let (batch, outputs) = match joined {
+                Ok(done) => done,
                 Err(e) => {
-                    tracing::warn!("Failed to claim {}: {}", doc.id, e);
+                    tracing::warn!("Annotation batch aborted: {}", e);
                     continue;
                 }
             };
+            for (doc, output) in batch.iter().zip(outputs) {
+                let Some(handle) = handles.remove(&doc.id) else {
+                    continue;
+                };
+                match self.finish(doc, handle, output, event_tx).await {
+                    Outcome::Succeeded => result.succeeded += 1,
+                    Outcome::Failed => result.failed += 1,
+                    Outcome::Skipped => result.skipped += 1,
+                }
+            }
+        }
 
-            let _ = event_tx
-                .send(PipelineEvent::ItemStarted {
-                    stage: stage_name.clone(),
-                    item_id: doc.id.clone(),
-                    label: doc.title.clone(),
-                })
-                .await;
+        // Claims whose batch never reported back expire on their own
+        for (_, handle) in handles {
+            let _ = self.queue.fail(handle, "not annotated", true).await;
+        }
 
-            match self.annotator.annotate(doc, &self.doc_repo).await {
-                Ok(output @ AnnotationOutput::Data(_)) => {
-                    let data = match &output {
-                        AnnotationOutput::Data(d) => d.as_str(),
-                        _ => unreachable!(),
-                    };
-                    if let Err(e) = self
-                        .doc_repo
-                        .record_annotation(
-                            &doc.id,
-                            self.annotator.annotation_type(),
-                            self.annotator.version(),
-                            Some(data),
-                            None,
-                        )
-                        .await
-                    {
-                        tracing::warn!("Failed to record annotation for {}: {}", doc.id, e);
-                        let _ = self.queue.fail(work_handle, &e.to_string(), false).await;
-                        let _ = event_tx
-                            .send(PipelineEvent::ItemFailed {
-                                stage: stage_name.clone(),
-                                item_id: doc.id.clone(),
-                                error: e.to_string(),
-                            })
-                            .await;
-                        failed += 1;
-                        continue;
-                    }
-                    if let Err(e) = self.annotator.post_record(doc, &self.doc_repo, &output).await {
-                        tracing::warn!("post_record failed for {}: {}", doc.id, e);
-                    }
-                    let _ = self.queue.complete(work_handle).await;
+        Ok(result)
+    }
+}
+
+/// How a single document's annotation ended.
+enum Outcome {
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+impl AnnotationStage {
+    /// Record that a processing profile skips this annotation for the
+    /// document's record type, so the queue doesn't offer it again.
+    async fn skip_record_type(
+        &self,
+        doc: &Document,
+        record_type: &str,
+        work_handle: WorkHandle<Document>,
+        event_tx: &mpsc::Sender<PipelineEvent>,
+    ) {
+        let _ = self
+            .doc_repo
+            .record_annotation(
+                &doc.id,
+                self.annotator.annotation_type(),
+                self.annotator.version(),
+                Some(&format!("skipped:{}", record_type)),
+                None,
+            )
+            .await;
+        if let Err(e) = self.annotator.skip_document(doc, &self.doc_repo).await {
+            tracing::warn!("skip_document failed for {}: {}", doc.id, e);
+        }
+        let _ = self.queue.complete(work_handle).await;
+        let _ = event_tx
+            .send(PipelineEvent::ItemSkipped {
+                stage: self.name().to_string(),
+                item_id: doc.id.clone(),
+            })
+            .await;
+    }
+
+    /// Record one document's result, release its claim, and report it.
+    async fn finish(
+        &self,
+        doc: &Document,
+        work_handle: WorkHandle<Document>,
+        output: Result<AnnotationOutput, AnnotationError>,
+        event_tx: &mpsc::Sender<PipelineEvent>,
+    ) -> Outcome {
+        let stage_name = self.name().to_string();
+        match output {
+            Ok(output @ AnnotationOutput::Data(_)) => {
+                let data = match &output {
+                    AnnotationOutput::Data(d) => d.as_str(),
+                    _ => unreachable!(),
+                };
+                if let Err(e) = self
+                    .doc_repo
+                    .record_annotation(
+                        &doc.id,
+                        self.annotator.annotation_type(),
+                        self.annotator.version(),
+                        Some(data),
+                        None,
+                    )
+                    .await
+                {
+                    tracing::warn!("Failed to record annotation for {}: {}", doc.id, e);
+                    let _ = self.queue.fail(work_handle, &e.to_string(), false).await;
                     let _ = event_tx
-                        .send(PipelineEvent::ItemCompleted {
-                            stage: stage_name.clone(),
+                        .send(PipelineEvent::ItemFailed {
+                            stage: stage_name,
                             item_id: doc.id.clone(),
-                            detail: None,
+                            error: e.to_string(),
                         })
                         .await;
-                    succeeded += 1;
+                    return Outcome::Failed;
+                }
+                if let Err(e) = self
+                    .annotator
+                    .post_record(doc, &self.doc_repo, &output)
+                    .await
+                {
+                    tracing::warn!("post_record failed for {}: {}", doc.id, e);
+                }
+                let _ = self.queue.complete(work_handle).await;
+                let _ = event_tx
+                    .send(PipelineEvent::ItemCompleted {
+                        stage: stage_name,
+                        item_id: doc.id.clone(),
+                        detail: None,
+                    })
+                    .await;
+                Outcome::Succeeded
+            }
+            Ok(output @ AnnotationOutput::NoResult) => {
+                let _ = self
+                    .doc_repo
+                    .record_annotation(
+                        &doc.id,
+                        self.annotator.annotation_type(),
+                        self.annotator.version(),
+                        Some("no_result"),
+                        None,
+                    )
+                    .await;
+                if let Err(e) = self
+                    .annotator
+                    .post_record(doc, &self.doc_repo, &output)
+                    .await
+                {
+                    tracing::warn!("post_record failed for {}: {}", doc.id, e);
                 }
-                Ok(output @ AnnotationOutput::NoResult) => {
+                let _ = self.queue.complete(work_handle).await;
+                let _ = event_tx
+                    .send(PipelineEvent::ItemCompleted {
+                        stage: stage_name,
+                        item_id: doc.id.clone(),
+                        detail: None,
+                    })
+                    .await;
+                Outcome::Succeeded
+            }
+            Ok(AnnotationOutput::Skipped) => {
+                let _ = self.queue.complete(work_handle).await;
+                let _ = event_tx
+                    .send(PipelineEvent::ItemSkipped {
+                        stage: stage_name,
+                        item_id: doc.id.clone(),
+                    })
+                    .await;
+                Outcome::Skipped
+            }
+            Err(e) => {
+                // Transient failures stay unrecorded so the next run retries them
+                let transient = matches!(e, AnnotationError::Transient(_));
+                if !transient {
                     let _ = self
                         .doc_repo
                         .record_annotation(
                             &doc.id,
                             self.annotator.annotation_type(),
                             self.annotator.version(),
-                            Some("no_result"),
                             None,
+                            Some(&e.to_string()),
                         )
                         .await;
-                    if let Err(e) = self.annotator.post_record(doc, &self.doc_repo, &output).await {
-                        tracing::warn!("post_record failed for {}: {}", doc.id, e);
-                    }
-                    let _ = self.queue.complete(work_handle).await;
-                    let _ = event_tx
-                        .send(PipelineEvent::ItemCompleted {
-                            stage: stage_name.clone(),
-                            item_id: doc.id.clone(),
-                            detail: None,
-                        })
-                        .await;
-                    succeeded += 1;
+                }
+                let _ = self
+                    .queue
+                    .fail(work_handle, &e.to_string(), transient)
+                    .await;
+                let _ = event_tx
+                    .send(PipelineEvent::ItemFailed {
+                        stage: stage_name,
+                        item_id: doc.id.clone(),
+                        error: e.to_string(),
+                    })
+                    .await;
+                Outcome::Failed
+            }
+        }
+    }
+}
+
+/// Annotation stage for virtual files — runs an `Annotator` against archive
+/// members and email attachments, recording results per file.
+pub struct VirtualFileAnnotationStage {
+    doc_repo: DieselDocumentRepository,
+    annotator: Arc<dyn Annotator>,
+    source_id: Option<String>,
+    exclude_sources: Vec<String>,
+    name: String,
+    cursor: Mutex<Option<String>>,
+}
+
+impl VirtualFileAnnotationStage {
+    pub fn new(
+        doc_repo: DieselDocumentRepository,
+        annotator: Arc<dyn Annotator>,
+        source_id: Option<&str>,
+    ) -> Self {
+        let name = format!("{} (attachments)", annotator.display_name());
+        Self {
+            doc_repo,
+            annotator,
+            source_id: source_id.map(Into::into),
+            exclude_sources: Vec::new(),
+            name,
+            cursor: Mutex::new(None),
+        }
+    }
+
+    /// Skip files whose parent document is from one of these sources.
+    pub fn with_excluded_sources(mut self, sources: Vec<String>) -> Self {
+        self.exclude_sources = sources;
+        self
+    }
+
+    async fn record(
+        &self,
+        id: &str,
+        data: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<(), String> {
+        self.doc_repo
+            .record_virtual_file_annotation(
+                id,
+                self.annotator.annotation_type(),
+                self.annotator.version(),
+                data,
+                error,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl PipelineStage for VirtualFileAnnotationStage {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.annotator.is_deferred()
+    }
+
+    async fn count(&self) -> Result<u64, PipelineError> {
+        self.doc_repo
+            .count_virtual_files_needing_annotation(
+                self.annotator.annotation_type(),
+                self.annotator.version(),
+                self.annotator.virtual_file_requires_text(),
+                self.source_id.as_deref(),
+                &self.exclude_sources,
+            )
+            .await
+            .map_err(|e| PipelineError::Other(e.into()))
+    }
+
+    async fn run_chunk(
+        &self,
+        chunk_size: usize,
+        remaining_limit: usize,
+        event_tx: &mpsc::Sender<PipelineEvent>,
+    ) -> Result<ChunkResult, PipelineError> {
+        let batch_limit = if remaining_limit > 0 {
+            chunk_size.min(remaining_limit)
+        } else {
+            chunk_size
+        };
+
+        let cursor = self.cursor.lock().await.clone();
+        let files = self
+            .doc_repo
+            .get_virtual_files_needing_annotation(
+                self.annotator.annotation_type(),
+                self.annotator.version(),
+                self.annotator.virtual_file_requires_text(),
+                self.source_id.as_deref(),
+                &self.exclude_sources,
+                batch_limit,
+                cursor.as_deref(),
+            )
+            .await
+            .map_err(|e| PipelineError::Other(e.into()))?;
+
+        let Some(last) = files.last() else {
+            return Ok(ChunkResult::default());
+        };
+        *self.cursor.lock().await = Some(last.id.clone());
+
+        let has_more = files.len() >= batch_limit;
+        let mut succeeded = 0usize;
+        let mut failed = 0usize;
+        let mut skipped = 0usize;
+        let stage_name = self.name().to_string();
+        let mut parents: HashMap<String, Option<Document>> = HashMap::new();
+
+        for vf in &files {
+            if !parents.contains_key(&vf.document_id) {
+                let parent = self
+                    .doc_repo
+                    .get(&vf.document_id)
+                    .await
+                    .map_err(|e| PipelineError::Other(e.into()))?;
+                parents.insert(vf.document_id.clone(), parent);
+            }
+            let Some(parent) = parents.get(&vf.document_id).and_then(Option::as_ref) else {
+                skipped += 1;
+                continue;
+            };
+
+            let _ = event_tx
+                .send(PipelineEvent::ItemStarted {
+                    stage: stage_name.clone(),
+                    item_id: vf.id.clone(),
+                    label: format!("{} / {}", parent.title, vf.archive_path),
+                })
+                .await;
+
+            let recorded = match self
+                .annotator
+                .annotate_virtual_file(vf, parent, &self.doc_repo)
+                .await
+            {
+                Ok(AnnotationOutput::Data(data)) => self.record(&vf.id, Some(&data), None).await,
+                Ok(AnnotationOutput::NoResult) => {
+                    self.record(&vf.id, Some("no_result"), None).await
                 }
                 Ok(AnnotationOutput::Skipped) => {
-                    let _ = self.queue.complete(work_handle).await;
                     let _ = event_tx
                         .send(PipelineEvent::ItemSkipped {
                             stage: stage_name.clone(),
-                            item_id: doc.id.clone(),
+                            item_id: vf.id.clone(),
                         })
                         .await;
                     skipped += 1;
+                    continue;
                 }
                 Err(e) => {
-                    let _ = self
-                        .doc_repo
-                        .record_annotation(
-                            &doc.id,
-                            self.annotator.annotation_type(),
-                            self.annotator.version(),
-                            None,
-                            Some(&e.to_string()),
-                        )
+                    if !matches!(e, AnnotationError::Transient(_)) {
+                        let _ = self.record(&vf.id, None, Some(&e.to_string())).await;
+                    }
+                    Err(e.to_string())
+                }
+            };
+
+            match recorded {
+                Ok(()) => {
+                    let _ = event_tx
+                        .send(PipelineEvent::ItemCompleted {
+                            stage: stage_name.clone(),
+                            item_id: vf.id.clone(),
+                            detail: None,
+                        })
                         .await;
-                    let _ = self.queue.fail(work_handle, &e.to_string(), false).await;
+                    succeeded += 1;
+                }
+                Err(e) => {
+                    tracing::warn!("Annotation of {} failed: {}", vf.id, e);
                     let _ = event_tx
                         .send(PipelineEvent::ItemFailed {
                             stage: stage_name.clone(),
-                            item_id: doc.id.clone(),
-                            error: e.to_string(),
+                            item_id: vf.id.clone(),
+                            error: e,
                         })
                         .await;
                     failed += 1;
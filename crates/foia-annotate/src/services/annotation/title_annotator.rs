@@ -0,0 +1,152 @@
+//! Title enhancement annotator — suggests descriptive titles for documents
+//! titled after their file name or URL.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use foia::llm::{LlmClient, LlmConfig};
+use foia::models::Document;
+use foia::repository::DieselDocumentRepository;
+use foia::services::titles::{is_generic_title, title_from_text, FIRST_PAGE_METHOD};
+
+use super::annotator::{get_document_text, Annotator};
+use super::types::{AnnotationError, AnnotationOutput};
+
+/// Text the first-page heuristic looks at.
+const FIRST_PAGE_CHARS: usize = 4000;
+
+/// Annotation data, also written to `title_suggestions`.
+#[derive(Debug, Serialize, Deserialize)]
+struct TitleData {
+    title: String,
+    method: String,
+}
+
+/// Annotator that suggests titles for documents with generic ones.
+///
+/// Titles come from the first page, or from an LLM when one is configured.
+/// Suggestions replace the title only for sources whose processing profile
+/// sets `apply_titles`; either way they wait for a curator's review.
+pub struct TitleAnnotator {
+    apply_sources: HashSet<String>,
+    llm: Option<(LlmClient, LlmConfig)>,
+}
+
+impl TitleAnnotator {
+    /// Apply suggestions to documents of `apply_sources`, suggest elsewhere.
+    pub fn new(apply_sources: HashSet<String>) -> Self {
+        Self {
+            apply_sources,
+            llm: None,
+        }
+    }
+
+    /// Ask an LLM for titles instead of reading them off the first page.
+    pub fn with_llm(mut self, config: LlmConfig) -> Self {
+        self.llm = Some((LlmClient::new(config.clone()), config));
+        self
+    }
+}
+
+#[async_trait]
+impl Annotator for TitleAnnotator {
+    fn annotation_type(&self) -> &str {
+        "title_enhancement"
+    }
+
+    fn display_name(&self) -> &str {
+        "Title Enhancement"
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.llm.is_some()
+    }
+
+    async fn is_available(&self) -> bool {
+        match &self.llm {
+            Some((client, _)) => client.is_available().await,
+            None => true,
+        }
+    }
+
+    fn availability_hint(&self) -> String {
+        match &self.llm {
+            Some((_, config)) => config.availability_hint(),
+            None => String::new(),
+        }
+    }
+
+    async fn annotate(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+    ) -> Result<AnnotationOutput, AnnotationError> {
+        if !is_generic_title(&doc.title) {
+            return Ok(AnnotationOutput::NoResult);
+        }
+        let text = match get_document_text(doc, doc_repo).await {
+            Ok(t) => t,
+            Err(output) => return Ok(output),
+        };
+
+        let data = match &self.llm {
+            Some((client, config)) => {
+                let title = client.suggest_title(&text, &doc.title).await.map_err(|e| {
+                    if e.is_transient() {
+                        AnnotationError::Transient(e.to_string())
+                    } else {
+                        AnnotationError::Failed(e.to_string())
+                    }
+                })?;
+                TitleData {
+                    title,
+                    method: format!("llm:{}", config.model()),
+                }
+            }
+            None => {
+                let first_page: String = text.chars().take(FIRST_PAGE_CHARS).collect();
+                let Some(title) = title_from_text(&first_page) else {
+                    return Ok(AnnotationOutput::NoResult);
+                };
+                TitleData {
+                    title,
+                    method: FIRST_PAGE_METHOD.to_string(),
+                }
+            }
+        };
+        // A suggestion no better than the title it replaces
+        if is_generic_title(&data.title) {
+            return Ok(AnnotationOutput::NoResult);
+        }
+
+        let data =
+            serde_json::to_string(&data).map_err(|e| AnnotationError::Failed(e.to_string()))?;
+        Ok(AnnotationOutput::Data(data))
+    }
+
+    async fn post_record(
+        &self,
+        doc: &Document,
+        doc_repo: &DieselDocumentRepository,
+        output: &AnnotationOutput,
+    ) -> Result<(), AnnotationError> {
+        let AnnotationOutput::Data(data) = output else {
+            return Ok(());
+        };
+        let data: TitleData = serde_json::from_str(data)
+            .map_err(|e| AnnotationError::Failed(format!("Failed to parse title: {}", e)))?;
+        doc_repo
+            .record_title_suggestion(
+                &doc.id,
+                &doc.title,
+                &data.title,
+                &data.method,
+                self.apply_sources.contains(&doc.source_id),
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| AnnotationError::Database(e.to_string()))
+    }
+}
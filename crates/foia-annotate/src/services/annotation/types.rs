@@ -73,4 +73,9 @@ pub enum AnnotationError {
 
     #[error("Database error: {0}")]
     Database(String),
+
+    /// Rate limited or the service is down; the document is left for a
+    /// later run instead of being recorded as failed.
+    #[error("Temporarily unavailable: {0}")]
+    Transient(String),
 }
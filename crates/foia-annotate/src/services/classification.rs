@@ -0,0 +1,257 @@
+//! Record-type classification: whether a document is a letter, memo, email,
+//! form, invoice, report, photo or map.
+//!
+//! The heuristic classifier scores layout cues in the text (message headers,
+//! salutations, form fields, invoice terms) along with the MIME type. It
+//! needs no model; an LLM can classify instead via
+//! `LlmClient::classify_record_type`.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use foia::models::RecordType;
+
+/// Characters of text examined for header and layout cues.
+const HEAD_CHARS: usize = 4000;
+
+/// Images with less text than this are treated as photos or maps rather
+/// than scanned paper.
+const IMAGE_TEXT_CHARS: usize = 200;
+
+/// A record type with a confidence between 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Classification {
+    pub record_type: RecordType,
+    pub confidence: f32,
+}
+
+static HEADER_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?im)^\s*(from|to|sent|date|subject|cc|bcc)\s*:\s*\S").unwrap());
+static EMAIL_ADDRESS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\w.+-]+@[\w-]+\.[\w.-]+").unwrap());
+static MEMO_HEADING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(MEMORANDUM|Memorandum)\b|(?i)\bmemorandum (for|to)\b").unwrap()
+});
+static SALUTATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^\s*Dear\s+\S").unwrap());
+static CLOSING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?im)^\s*(sincerely|respectfully|yours truly|very truly yours|best regards)\b")
+        .unwrap()
+});
+static INVOICE_TERMS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(amount due|balance due|total due|bill to|remit to|invoice (no|number|date)|unit price)\b")
+        .unwrap()
+});
+static FORM_FIELD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(☐|☑|☒|\[ ?[xX ]? ?\]|:\s*_{4,})").unwrap());
+static FORM_NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(form\s+[A-Z]{0,4}-?\d{1,5}[A-Z]?\b|omb (no|number|control)|please print)")
+        .unwrap()
+});
+static REPORT_SECTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?im)^\s*(\d+(\.\d+)*\.?\s+)?(table of contents|executive summary|introduction|background|findings|recommendations|conclusions?|appendix|methodology)\s*$",
+    )
+    .unwrap()
+});
+static MAP_TERMS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(map|legend|scale|parcel|quadrangle|survey|plat|zoning|site plan)\b")
+        .unwrap()
+});
+
+/// Classify a document from its MIME type, title and extracted text.
+///
+/// Returns `None` when there is nothing to go on (no text, not an image).
+pub fn classify(mime_type: &str, title: &str, text: &str) -> Option<Classification> {
+    let text = text.trim();
+    if mime_type.starts_with("image/") && text.chars().count() < IMAGE_TEXT_CHARS {
+        let map_cues = MAP_TERMS.find_iter(title).count() * 2 + MAP_TERMS.find_iter(text).count();
+        return Some(if map_cues >= 2 {
+            Classification {
+                record_type: RecordType::Map,
+                confidence: if map_cues >= 4 { 0.8 } else { 0.6 },
+            }
+        } else {
+            Classification {
+                record_type: RecordType::Photo,
+                confidence: if text.is_empty() { 0.9 } else { 0.7 },
+            }
+        });
+    }
+    if text.is_empty() {
+        return None;
+    }
+
+    let head: String = text.chars().take(HEAD_CHARS).collect();
+    let scores = [
+        (RecordType::Email, email_score(&head)),
+        (RecordType::Memo, memo_score(&head)),
+        (RecordType::Letter, letter_score(text, &head)),
+        (RecordType::Invoice, invoice_score(title, &head)),
+        (RecordType::Form, form_score(&head)),
+        (RecordType::Report, report_score(text)),
+    ];
+
+    let total: u32 = scores.iter().map(|(_, s)| s).sum();
+    let (record_type, best) = scores
+        .iter()
+        .copied()
+        .max_by_key(|(_, s)| *s)
+        .unwrap_or((RecordType::Report, 0));
+
+    // Most released records without distinctive cues are reports or
+    // narrative documents
+    if best == 0 {
+        return Some(Classification {
+            record_type: RecordType::Report,
+            confidence: 0.3,
+        });
+    }
+    let confidence = (best as f32 / (total as f32 + 1.0)).clamp(0.3, 0.95);
+    Some(Classification {
+        record_type,
+        confidence,
+    })
+}
+
+fn email_score(head: &str) -> u32 {
+    let mut fields: Vec<String> = HEADER_LINE
+        .captures_iter(head)
+        .map(|c| c[1].to_lowercase())
+        .collect();
+    fields.sort();
+    fields.dedup();
+    let has = |name: &str| fields.iter().any(|f| f == name);
+    if !(has("from") && (has("to") || has("sent")) && has("subject")) {
+        return 0;
+    }
+    let mut score = fields.len() as u32;
+    if has("sent") || EMAIL_ADDRESS.is_match(head) {
+        score += 2;
+    }
+    // Memos carry FROM/TO/SUBJECT headers too
+    if MEMO_HEADING.is_match(head) {
+        score = score.saturating_sub(3);
+    }
+    score
+}
+
+fn memo_score(head: &str) -> u32 {
+    if MEMO_HEADING.is_match(head) {
+        let headers = HEADER_LINE.find_iter(head).count().min(3) as u32;
+        5 + headers
+    } else {
+        0
+    }
+}
+
+fn letter_score(text: &str, head: &str) -> u32 {
+    let mut score = 0;
+    if SALUTATION.is_match(head) {
+        score += 3;
+    }
+    if CLOSING.is_match(text) {
+        score += 2;
+    }
+    score
+}
+
+fn invoice_score(title: &str, head: &str) -> u32 {
+    let mut score = INVOICE_TERMS.find_iter(head).count().min(4) as u32;
+    if title.to_lowercase().contains("invoice") || head.contains("INVOICE") {
+        score += 3;
+    }
+    if score < 2 {
+        0
+    } else {
+        score
+    }
+}
+
+fn form_score(head: &str) -> u32 {
+    let fields = FORM_FIELD.find_iter(head).count() as u32;
+    let mut score = (fields / 3).min(5);
+    if FORM_NUMBER.is_match(head) {
+        score += 2;
+    }
+    if score < 2 {
+        0
+    } else {
+        score
+    }
+}
+
+fn report_score(text: &str) -> u32 {
+    let mut sections: Vec<String> = REPORT_SECTION
+        .captures_iter(text)
+        .map(|c| c[3].to_lowercase())
+        .collect();
+    sections.sort();
+    sections.dedup();
+    let mut score = (sections.len() as u32) * 2;
+    if text.len() > 20_000 {
+        score += 1;
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(mime: &str, title: &str, text: &str) -> RecordType {
+        classify(mime, title, text).unwrap().record_type
+    }
+
+    #[test]
+    fn test_classify_correspondence() {
+        let email = "From: Jane Roe <jroe@agency.gov>\nSent: Monday, March 3, 2021 9:14 AM\n\
+                     To: John Doe <jdoe@agency.gov>\nSubject: RE: budget\n\nSee attached.";
+        assert_eq!(kind("application/pdf", "Emails", email), RecordType::Email);
+
+        let memo = "MEMORANDUM\n\nTO: All Staff\nFROM: Director\nSUBJECT: Travel policy\n\n\
+                    Effective immediately, all travel requires approval.";
+        assert_eq!(kind("application/pdf", "Policy", memo), RecordType::Memo);
+
+        let letter = "March 3, 2021\n\nDear Ms. Roe:\n\nThank you for your request.\n\n\
+                      Sincerely,\n\nJohn Doe";
+        assert_eq!(
+            kind("application/pdf", "Response", letter),
+            RecordType::Letter
+        );
+    }
+
+    #[test]
+    fn test_classify_forms_and_invoices() {
+        let invoice = "INVOICE\nInvoice Number: 4411\nBill To: City of Springfield\n\
+                       Unit Price 40.00\nAmount Due: $1,200.00";
+        assert_eq!(
+            kind("application/pdf", "scan", invoice),
+            RecordType::Invoice
+        );
+
+        let form = "Form SF-86\nOMB No. 3206-0005\nName: ________\nDate of birth: ________\n\
+                    [ ] Yes [X] No\n[ ] Yes [ ] No\nSignature: ________";
+        assert_eq!(kind("application/pdf", "scan", form), RecordType::Form);
+    }
+
+    #[test]
+    fn test_classify_images_and_fallback() {
+        let photo = classify("image/jpeg", "IMG_0412", "").unwrap();
+        assert_eq!(photo.record_type, RecordType::Photo);
+        assert!(photo.confidence > 0.8);
+        assert_eq!(
+            kind("image/tiff", "Zoning map", "LEGEND\nScale 1:24000"),
+            RecordType::Map
+        );
+
+        let report = "Executive Summary\nThe program met its goals.\n\n1. Introduction\n...\n\
+                      2. Findings\n...\n3. Recommendations\n...";
+        assert_eq!(kind("application/pdf", "Audit", report), RecordType::Report);
+
+        let plain = classify("application/pdf", "Notes", "Meeting ran long.").unwrap();
+        assert_eq!(plain.record_type, RecordType::Report);
+        assert!(plain.confidence < 0.5);
+        assert!(classify("application/pdf", "Empty", "  ").is_none());
+    }
+}
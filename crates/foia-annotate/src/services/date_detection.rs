@@ -2,6 +2,7 @@
 #![allow(dead_code)]
 //!
 //! Uses multiple deterministic strategies to estimate dates:
+//! - Publish dates recorded by the source (high confidence)
 //! - Server-provided dates (high confidence)
 //! - Filename patterns (medium confidence)
 //! - PDF metadata (medium-high confidence)
@@ -33,6 +34,7 @@ impl DateConfidence {
 /// Source of the date estimate.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DateSource {
+    Metadata,
     Server,
     Filename,
     PdfMetadata,
@@ -44,6 +46,7 @@ pub enum DateSource {
 impl DateSource {
     pub fn as_str(&self) -> &'static str {
         match self {
+            DateSource::Metadata => "metadata",
             DateSource::Server => "server",
             DateSource::Filename => "filename",
             DateSource::PdfMetadata => "pdf_metadata",
@@ -82,25 +85,63 @@ static DATE_PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
     ]
 });
 
+/// Written dates in document text: "January 5, 1963", "5 JAN 1963",
+/// "1963-01-05", "01/05/1963".
+static TEXT_DATE_PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    const MONTH: &str = r"(jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sept?(?:ember)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)";
+    vec![
+        (
+            Regex::new(&format!(
+                r"(?i)\b{MONTH}\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?,?\s+(\d{{4}})\b"
+            ))
+            .unwrap(),
+            "month_dy",
+        ),
+        (
+            Regex::new(&format!(r"(?i)\b(\d{{1,2}})\s+{MONTH}\.?,?\s+(\d{{4}})\b")).unwrap(),
+            "d_month_y",
+        ),
+        (Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap(), "ymd"),
+        (
+            Regex::new(r"\b(\d{1,2})/(\d{1,2})/(\d{4})\b").unwrap(),
+            "mdy",
+        ),
+    ]
+});
+
+/// Only the start of the text is scanned for dates; letterheads and
+/// date lines come first.
+const TEXT_SCAN_CHARS: usize = 4000;
+
+/// Most dates offered from text.
+const MAX_TEXT_CANDIDATES: usize = 6;
+
 /// Try to detect document date using deterministic strategies.
 ///
 /// Strategies are tried in order of confidence:
-/// 1. Server date (if significantly different from acquired date)
-/// 2. Filename patterns
+/// 1. `published_at` in document metadata (e.g. a video's publish date)
+/// 2. Server date (if significantly different from acquired date)
+/// 3. Filename patterns
 ///
 /// Returns None if no date can be determined.
 pub fn detect_date(
+    metadata: &serde_json::Value,
     server_date: Option<DateTime<Utc>>,
     acquired_at: DateTime<Utc>,
     filename: Option<&str>,
     source_url: Option<&str>,
 ) -> Option<DateEstimate> {
-    // Strategy 1: Server-provided date
+    // Strategy 1: Publish date recorded at download time
+    if let Some(estimate) = check_metadata_date(metadata) {
+        return Some(estimate);
+    }
+
+    // Strategy 2: Server-provided date
     if let Some(estimate) = check_server_date(server_date, acquired_at) {
         return Some(estimate);
     }
 
-    // Strategy 2: Filename patterns
+    // Strategy 3: Filename patterns
     if let Some(estimate) = extract_date_from_filename(filename, source_url) {
         return Some(estimate);
     }
@@ -108,6 +149,118 @@ pub fn detect_date(
     None
 }
 
+/// Every plausible date for a document, most trusted first, one per day.
+///
+/// Unlike [`detect_date`], which stops at the first strategy that succeeds,
+/// this collects the results of all of them plus dates written near the
+/// start of `text`, for a person to choose from.
+pub fn date_candidates(
+    metadata: &serde_json::Value,
+    server_date: Option<DateTime<Utc>>,
+    acquired_at: DateTime<Utc>,
+    filename: Option<&str>,
+    source_url: Option<&str>,
+    text: Option<&str>,
+) -> Vec<DateEstimate> {
+    let mut candidates: Vec<DateEstimate> = [
+        check_metadata_date(metadata),
+        check_server_date(server_date, acquired_at),
+        extract_date_from_filename(filename, source_url),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if let Some(text) = text {
+        candidates.extend(
+            find_dates_in_text(text)
+                .into_iter()
+                .filter_map(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| DateEstimate {
+                    date: d.and_utc(),
+                    confidence: DateConfidence::Low,
+                    source: DateSource::Content,
+                }),
+        );
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    candidates.retain(|c| seen.insert(c.date.date_naive()));
+    candidates
+}
+
+/// Dates written in the first part of `text`, in order of appearance.
+pub fn find_dates_in_text(text: &str) -> Vec<NaiveDate> {
+    let end = text
+        .char_indices()
+        .nth(TEXT_SCAN_CHARS)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+    let text = &text[..end];
+
+    let mut found: Vec<(usize, NaiveDate)> = Vec::new();
+    for (pattern, format) in TEXT_DATE_PATTERNS.iter() {
+        for caps in pattern.captures_iter(text) {
+            let date = match *format {
+                "month_dy" => month_from_name(&caps[1]).and_then(|m| {
+                    NaiveDate::from_ymd_opt(caps[3].parse().ok()?, m, caps[2].parse().ok()?)
+                }),
+                "d_month_y" => month_from_name(&caps[2]).and_then(|m| {
+                    NaiveDate::from_ymd_opt(caps[3].parse().ok()?, m, caps[1].parse().ok()?)
+                }),
+                _ => parse_captured_date(&caps, format),
+            };
+            if let Some(date) = date.filter(|d| is_plausible_year(d.year())) {
+                found.push((caps.get(0).map_or(0, |m| m.start()), date));
+            }
+        }
+    }
+
+    found.sort_by_key(|(pos, _)| *pos);
+    let mut dates: Vec<NaiveDate> = Vec::new();
+    for (_, date) in found {
+        if !dates.contains(&date) {
+            dates.push(date);
+        }
+        if dates.len() == MAX_TEXT_CANDIDATES {
+            break;
+        }
+    }
+    dates
+}
+
+fn month_from_name(name: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let prefix = name.get(..3)?.to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|m| *m == prefix)
+        .map(|i| i as u32 + 1)
+}
+
+/// Between 1900 and next year.
+fn is_plausible_year(year: i32) -> bool {
+    year >= 1900 && year <= Utc::now().year() + 1
+}
+
+/// Read a publish date stored in document metadata.
+///
+/// Unlike server dates this is trusted even when close to the acquisition
+/// time: a video published the day it was crawled still has that date.
+fn check_metadata_date(metadata: &serde_json::Value) -> Option<DateEstimate> {
+    let published = metadata.get("published_at")?.as_str()?;
+    let date = DateTime::parse_from_rfc3339(published)
+        .ok()?
+        .with_timezone(&Utc);
+    Some(DateEstimate {
+        date,
+        confidence: DateConfidence::High,
+        source: DateSource::Metadata,
+    })
+}
+
 /// Check if server date is a valid publication date.
 ///
 /// Returns Some if:
@@ -153,9 +306,7 @@ fn extract_date_from_filename(
         for (pattern, format) in DATE_PATTERNS.iter() {
             if let Some(caps) = pattern.captures(candidate) {
                 if let Some(date) = parse_captured_date(&caps, format) {
-                    // Sanity check: date should be between 1900 and next year
-                    let year = date.year();
-                    if year >= 1900 && year <= Utc::now().year() + 1 {
+                    if is_plausible_year(date.year()) {
                         return Some(DateEstimate {
                             date: date.and_hms_opt(0, 0, 0)?.and_utc(),
                             confidence: DateConfidence::Medium,
@@ -253,6 +404,55 @@ mod tests {
         assert!(result.is_none()); // Same day, likely just crawl date
     }
 
+    #[test]
+    fn test_metadata_published_at() {
+        let acquired = DateTime::parse_from_rfc3339("2024-01-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let metadata = serde_json::json!({ "published_at": "2024-01-01T08:30:00+00:00" });
+
+        let result = detect_date(&metadata, None, acquired, None, None).unwrap();
+        assert_eq!(result.source, DateSource::Metadata);
+        assert_eq!(result.date.format("%Y-%m-%d").to_string(), "2024-01-01");
+
+        let result = detect_date(&serde_json::json!({}), None, acquired, None, None);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_find_dates_in_text() {
+        let text = "MEMORANDUM FOR THE RECORD\nDate: 22 NOV 1963\n\
+                    Subject: Meeting of November 21st, 1963. Filed 1963-12-02; \
+                    cc 12/02/1963 and 13/45/1963. Ref. May 1999.";
+        let dates: Vec<String> = find_dates_in_text(text)
+            .iter()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .collect();
+        assert_eq!(dates, vec!["1963-11-22", "1963-11-21", "1963-12-02"]);
+    }
+
+    #[test]
+    fn test_date_candidates_deduplicated() {
+        let acquired = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let candidates = date_candidates(
+            &serde_json::json!({}),
+            None,
+            acquired,
+            Some("memo-1963-11-22.pdf"),
+            None,
+            Some("Dated November 22, 1963. Received December 1, 1963."),
+        );
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].source, DateSource::Filename);
+        assert_eq!(candidates[1].source, DateSource::Content);
+        assert_eq!(
+            candidates[1].date.format("%Y-%m-%d").to_string(),
+            "1963-12-01"
+        );
+    }
+
     #[test]
     fn test_server_date_epoch() {
         let epoch = DateTime::parse_from_rfc3339("1970-01-01T00:00:00Z")
@@ -1,13 +1,18 @@
 pub mod annotation;
+pub mod classification;
 pub mod date_detection;
 pub mod ner;
 
 #[allow(unused_imports)]
 pub use annotation::{
     AnnotationError, AnnotationEvent, AnnotationManager, AnnotationOutput, Annotator,
-    BatchAnnotationResult, DateAnnotator, LlmAnnotator, NerAnnotator, UrlAnnotator,
+    BatesAnnotator, BatchAnnotationResult, ClassificationAnnotator, DateAnnotator,
+    ExemptionAnnotator, LlmAnnotator, NerAnnotator, SectionAnnotator, TitleAnnotator,
+    UrlAnnotator,
 };
 #[allow(unused_imports)]
-pub use date_detection::{detect_date, DateConfidence, DateEstimate, DateSource};
+pub use date_detection::{
+    date_candidates, detect_date, DateConfidence, DateEstimate, DateSource,
+};
 #[allow(unused_imports)]
 pub use ner::{NerBackend, NerResult, RegexNerBackend};
@@ -0,0 +1,136 @@
+//! Document and source visibility commands.
+
+use chrono::{NaiveDate, Utc};
+use console::style;
+
+use foia::config::Settings;
+use foia::services::access::{AccessScope, Visibility};
+
+use super::helpers::truncate;
+
+fn scope(source: bool) -> AccessScope {
+    if source {
+        AccessScope::Source
+    } else {
+        AccessScope::Document
+    }
+}
+
+/// List visibility rules, sources first.
+pub async fn cmd_access_list(settings: &Settings) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let rules = repos.documents.list_access_rules().await?;
+
+    if rules.is_empty() {
+        println!(
+            "{} No access rules; everything is public. Restrict with 'foia access set'.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let today = Utc::now().date_naive();
+    println!("\n{}", style("Access Rules").bold());
+    println!("{}", "-".repeat(90));
+    println!("{:<9} {:<36} {:<22} Note", "Scope", "Target", "Visibility");
+    println!("{}", "-".repeat(90));
+    for rule in &rules {
+        let restricted = rule.visibility.is_restricted(today);
+        let label = match rule.visibility {
+            Visibility::Embargoed(until) if restricted => format!("embargoed until {}", until),
+            Visibility::Embargoed(until) => format!("public since {}", until),
+            v => v.as_str().to_string(),
+        };
+        let label = format!("{:<22}", label);
+        let label = if restricted {
+            style(label).yellow()
+        } else {
+            style(label).green()
+        };
+        println!(
+            "{:<9} {:<36} {} {}",
+            rule.scope.as_str(),
+            truncate(&rule.target, 36),
+            label,
+            style(rule.note.as_deref().unwrap_or("")).dim()
+        );
+    }
+    Ok(())
+}
+
+/// Set the visibility of a document or, with `source`, a whole source.
+pub async fn cmd_access_set(
+    settings: &Settings,
+    target: &str,
+    visibility: &str,
+    until: Option<&str>,
+    source: bool,
+    note: Option<&str>,
+) -> anyhow::Result<()> {
+    let until = until
+        .map(|d| {
+            NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .map_err(|_| anyhow::anyhow!("Invalid date '{}' (expected YYYY-MM-DD)", d))
+        })
+        .transpose()?;
+    let Some(visibility) = Visibility::parse(visibility, until) else {
+        anyhow::bail!(
+            "Unknown visibility '{}' (expected public, internal, or embargo with --until)",
+            visibility
+        );
+    };
+
+    let repos = settings.repositories()?;
+    let scope = scope(source);
+    let known = match scope {
+        AccessScope::Source => repos.sources.get(target).await?.is_some(),
+        AccessScope::Document => repos.documents.get(target).await?.is_some(),
+    };
+    if !known {
+        anyhow::bail!("No {} with ID '{}'", scope.as_str(), target);
+    }
+
+    repos
+        .documents
+        .set_access_rule(scope, target, visibility, note)
+        .await?;
+    let shown = match visibility {
+        Visibility::Embargoed(until) => format!("embargoed until {}", until),
+        v => v.as_str().to_string(),
+    };
+    println!(
+        "{} {} {} is now {}",
+        style("✓").green(),
+        scope.as_str(),
+        target,
+        shown
+    );
+    Ok(())
+}
+
+/// Remove a rule: a document falls back to its source's visibility, a
+/// source becomes public.
+pub async fn cmd_access_clear(
+    settings: &Settings,
+    target: &str,
+    source: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let scope = scope(source);
+    if repos.documents.clear_access_rule(scope, target).await? {
+        println!(
+            "{} Cleared access rule for {} {}",
+            style("✓").green(),
+            scope.as_str(),
+            target
+        );
+    } else {
+        println!(
+            "{} No access rule for {} {}",
+            style("✗").red(),
+            scope.as_str(),
+            target
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Ad-hoc URL acquisition command.
+
+use std::path::Path;
+
+use console::style;
+
+use foia::config::{Config, Settings};
+use foia::services::acquire::{self, AcquireRequest, CaptureRequest};
+use foia_analysis::services::AnalysisService;
+
+use super::helpers::format_bytes;
+
+/// Fetch a single URL, store it, and extract its text.
+///
+/// With `from_file`, a page saved from the browser is stored under `url`
+/// instead of fetching it.
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_acquire(
+    settings: &Settings,
+    config: &Config,
+    url: &str,
+    source_id: Option<String>,
+    tags: Vec<String>,
+    no_process: bool,
+    from_file: Option<&Path>,
+    submitted_by: Option<String>,
+) -> anyhow::Result<()> {
+    settings.ensure_directories()?;
+    let repos = settings.repositories()?;
+
+    let outcome = match from_file {
+        Some(path) => {
+            let is_mhtml = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("mht" | "mhtml")
+            );
+            let capture = CaptureRequest {
+                url: url.to_string(),
+                content: std::fs::read_to_string(path)?,
+                content_type: is_mhtml.then(|| "multipart/related".to_string()),
+                source_id,
+                tags,
+                submitted_by,
+                ..Default::default()
+            };
+            capture.validate().map_err(anyhow::Error::msg)?;
+
+            println!(
+                "{} Storing capture of {} into source '{}'",
+                style("→").cyan(),
+                capture.url.trim(),
+                capture.source_id()
+            );
+            acquire::store_capture(
+                &repos.documents,
+                &repos.sources,
+                &settings.documents_dir,
+                &capture,
+            )
+            .await?
+        }
+        None => {
+            let request = AcquireRequest {
+                url: url.to_string(),
+                source_id,
+                tags,
+            };
+            request.validate().map_err(anyhow::Error::msg)?;
+
+            println!(
+                "{} Acquiring {} into source '{}'",
+                style("→").cyan(),
+                request.url.trim(),
+                request.source_id()
+            );
+            acquire::fetch_and_store(
+                config,
+                &repos.documents,
+                &repos.sources,
+                &settings.documents_dir,
+                &request,
+            )
+            .await?
+        }
+    };
+    println!(
+        "  {} {} {} ({}, {})",
+        style("✓").green(),
+        if outcome.created {
+            "Saved"
+        } else {
+            "Already archived as"
+        },
+        outcome.document_id,
+        outcome.mime_type,
+        format_bytes(outcome.bytes)
+    );
+
+    if no_process {
+        return Ok(());
+    }
+
+    let analysis = AnalysisService::new(repos.documents.clone(), settings.documents_dir.clone());
+    match analysis.extract_text(&outcome.document_id).await {
+        Ok(pages) => println!(
+            "  {} Extracted text from {} pages",
+            style("✓").green(),
+            pages
+        ),
+        Err(e) if e.to_string().contains("Unsupported file type") => {
+            println!(
+                "  {} No text extractor for {}",
+                style("!").yellow(),
+                outcome.mime_type
+            )
+        }
+        Err(e) => println!("  {} Text extraction failed: {}", style("✗").red(), e),
+    }
+
+    Ok(())
+}
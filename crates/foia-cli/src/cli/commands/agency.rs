@@ -0,0 +1,201 @@
+//! Agency management commands.
+
+use std::collections::HashMap;
+
+use console::style;
+
+use foia::config::Settings;
+use foia::models::{Agency, AgencyStats, AgencyTree};
+use foia::privacy::SourcePrivacyConfig;
+
+use super::helpers::truncate;
+
+/// Request settings given on the command line for `agency add`.
+pub struct AgencyArgs {
+    pub name: Option<String>,
+    pub parent: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_timeout: Option<u64>,
+    pub request_delay_ms: Option<u64>,
+    pub direct: bool,
+}
+
+/// List agencies as a tree with roll-up counts.
+pub async fn cmd_agency_list(settings: &Settings) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let tree = repos.agencies.tree().await?;
+
+    if tree.is_empty() {
+        println!(
+            "{} No agencies configured. Add one with 'foia agency add'.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let source_counts = repos.documents.get_all_source_counts().await?;
+    let stats = tree.rollup(&source_counts);
+
+    println!("\n{}", style("Agencies").bold());
+    println!("{}", "-".repeat(70));
+    println!(
+        "{:<40} {:>8} {:>10}  Sources",
+        "Agency", "Sources", "Documents"
+    );
+    println!("{}", "-".repeat(70));
+
+    for agency in tree.roots() {
+        print_agency(&tree, &stats, agency, 0);
+    }
+
+    Ok(())
+}
+
+fn print_agency(
+    tree: &AgencyTree,
+    stats: &HashMap<String, AgencyStats>,
+    agency: &Agency,
+    depth: usize,
+) {
+    let totals = stats.get(&agency.id).copied().unwrap_or_default();
+    let label = format!("{}{} ({})", "  ".repeat(depth), agency.name, agency.id);
+    println!(
+        "{:<40} {:>8} {:>10}  {}",
+        truncate(&label, 40),
+        totals.sources,
+        totals.documents,
+        style(tree.members_of(&agency.id).join(", ")).dim()
+    );
+
+    for child in tree.children(&agency.id) {
+        print_agency(tree, stats, child, depth + 1);
+    }
+}
+
+/// Add an agency, or update one that already exists.
+pub async fn cmd_agency_add(settings: &Settings, id: &str, args: AgencyArgs) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let agency_repo = repos.agencies;
+    let tree = agency_repo.tree().await?;
+
+    if let Some(ref parent) = args.parent {
+        if tree.get(parent).is_none() {
+            println!("{} Parent agency '{}' not found", style("✗").red(), parent);
+            return Ok(());
+        }
+        if tree.would_cycle(id, parent) {
+            println!(
+                "{} '{}' is below '{}' already; that parent would create a cycle",
+                style("✗").red(),
+                parent,
+                id
+            );
+            return Ok(());
+        }
+    }
+
+    let existing = tree.get(id).cloned();
+    let updating = existing.is_some();
+    let mut agency = match existing {
+        Some(agency) => agency,
+        None => {
+            let Some(ref name) = args.name else {
+                println!("{} New agencies need a --name", style("✗").red());
+                return Ok(());
+            };
+            Agency::new(id.to_string(), name.clone(), None)
+        }
+    };
+
+    if let Some(name) = args.name {
+        agency.name = name;
+    }
+    if args.parent.is_some() {
+        agency.parent_id = args.parent;
+    }
+    if args.user_agent.is_some() {
+        agency.settings.user_agent = args.user_agent;
+    }
+    if args.request_timeout.is_some() {
+        agency.settings.request_timeout = args.request_timeout;
+    }
+    if args.request_delay_ms.is_some() {
+        agency.settings.request_delay_ms = args.request_delay_ms;
+    }
+    if args.direct {
+        agency.settings.privacy = Some(SourcePrivacyConfig {
+            direct: true,
+            ..Default::default()
+        });
+    }
+
+    agency_repo.upsert(&agency).await?;
+
+    println!(
+        "{} {} agency '{}' ({})",
+        style("✓").green(),
+        if updating { "Updated" } else { "Added" },
+        agency.name,
+        agency.id
+    );
+    Ok(())
+}
+
+/// Assign a source to an agency.
+pub async fn cmd_agency_assign(
+    settings: &Settings,
+    source_id: &str,
+    agency_id: &str,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    if repos.sources.get(source_id).await?.is_none() {
+        println!("{} Source '{}' not found", style("✗").red(), source_id);
+        return Ok(());
+    }
+    let Some(agency) = repos.agencies.get(agency_id).await? else {
+        println!("{} Agency '{}' not found", style("✗").red(), agency_id);
+        return Ok(());
+    };
+
+    repos.agencies.assign_source(source_id, agency_id).await?;
+    println!(
+        "{} Assigned '{}' to {}",
+        style("✓").green(),
+        source_id,
+        agency.name
+    );
+    Ok(())
+}
+
+/// Remove a source from its agency.
+pub async fn cmd_agency_unassign(settings: &Settings, source_id: &str) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    if repos.agencies.unassign_source(source_id).await? {
+        println!(
+            "{} '{}' no longer belongs to an agency",
+            style("✓").green(),
+            source_id
+        );
+    } else {
+        println!(
+            "{} '{}' is not assigned to an agency",
+            style("!").yellow(),
+            source_id
+        );
+    }
+    Ok(())
+}
+
+/// Remove an agency. Sub-agencies move up to its parent.
+pub async fn cmd_agency_remove(settings: &Settings, id: &str) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    if repos.agencies.delete(id).await? {
+        println!("{} Removed agency '{}'", style("✓").green(), id);
+    } else {
+        println!("{} Agency '{}' not found", style("✗").red(), id);
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Search alias dictionary commands.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::aliases::AliasKind;
+
+use super::helpers::truncate;
+
+/// List aliases by term.
+pub async fn cmd_alias_list(settings: &Settings) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let aliases = repos.documents.list_aliases().await?;
+
+    if aliases.is_empty() {
+        println!(
+            "{} No aliases defined. Add one with 'foia alias add'.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    println!("\n{}", style("Search Aliases").bold());
+    println!("{}", "-".repeat(80));
+    println!("{:>5}  {:<20} {:<40} Kind", "ID", "Term", "Stands for");
+    println!("{}", "-".repeat(80));
+    for alias in &aliases {
+        println!(
+            "{:>5}  {:<20} {:<40} {}",
+            alias.id,
+            truncate(&alias.term, 20),
+            truncate(&alias.expansion, 40),
+            style(alias.kind.label()).dim()
+        );
+    }
+    Ok(())
+}
+
+/// Add an alias: searches for either side also find the other.
+pub async fn cmd_alias_add(
+    settings: &Settings,
+    term: &str,
+    expansion: &str,
+    kind: &str,
+) -> anyhow::Result<()> {
+    let Some(kind) = AliasKind::from_str(kind) else {
+        anyhow::bail!(
+            "Unknown alias kind '{}' (expected acronym, code_name or person)",
+            kind
+        );
+    };
+    let (term, expansion) = (term.trim(), expansion.trim());
+    if term.is_empty() || expansion.is_empty() || term.eq_ignore_ascii_case(expansion) {
+        anyhow::bail!("An alias needs a term and a different expansion");
+    }
+
+    let repos = settings.repositories()?;
+    let alias = repos.documents.add_alias(term, expansion, kind).await?;
+    println!(
+        "{} [{}] {} ↔ {}",
+        style("✓").green(),
+        alias.id,
+        alias.term,
+        alias.expansion
+    );
+    Ok(())
+}
+
+/// Remove an alias by ID.
+pub async fn cmd_alias_remove(settings: &Settings, id: i32) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    if repos.documents.delete_alias(id).await? {
+        println!("{} Removed alias {}", style("✓").green(), id);
+    } else {
+        println!("{} Alias {} not found", style("✗").red(), id);
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Re-arbitrate pages' final text among their stored OCR readings.
+
+use std::collections::BTreeMap;
+
+use console::style;
+
+use foia::config::{Config, Settings};
+use foia_analysis::ocr::readings_from_results;
+use foia_analysis::services::analysis::arbitrate_page;
+
+use super::process::build_arbiter;
+use crate::cli::output;
+
+/// Choose the final text of pages with several OCR readings again, e.g.
+/// after adding a backend or a dictionary.
+pub async fn cmd_analyze_arbitrate(
+    settings: &Settings,
+    source_id: Option<&str>,
+    doc_id: Option<&str>,
+    limit: usize,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let config = Config::load().await;
+    let arbiter = build_arbiter(&config)?;
+    let doc_repo = settings.repositories()?.documents;
+
+    let pages = doc_repo
+        .get_pages_with_readings(source_id, doc_id, limit)
+        .await?;
+    if pages.is_empty() {
+        println!(
+            "{} No pages have more than one OCR reading",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let mut methods: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut changed = 0usize;
+    for mut page in pages {
+        let decision = if dry_run {
+            let readings = readings_from_results(doc_repo.get_page_ocr_results(page.id).await?);
+            arbiter.arbitrate(&readings).await
+        } else {
+            arbitrate_page(page.id, &doc_repo, &arbiter).await?
+        };
+        let Some(decision) = decision else {
+            continue;
+        };
+        *methods.entry(decision.method.as_str()).or_default() += 1;
+        if page.final_text.as_deref() == Some(decision.text.as_str()) {
+            continue;
+        }
+
+        changed += 1;
+        println!(
+            "  {} {} page {}: {}",
+            style("→").dim(),
+            page.document_id,
+            page.page_number,
+            decision.rationale
+        );
+        if !dry_run {
+            page.ocr_text = Some(decision.text.clone());
+            page.final_text = Some(decision.text);
+            doc_repo.save_page(&page).await?;
+        }
+    }
+
+    output::emit(
+        "result",
+        serde_json::json!({
+            "changed": changed,
+            "methods": methods,
+            "dry_run": dry_run,
+        }),
+    );
+
+    let total: usize = methods.values().sum();
+    let verb = if dry_run { "Would change" } else { "Changed" };
+    println!(
+        "{} {} the text of {} of {} pages",
+        style("✓").green(),
+        verb,
+        changed,
+        total
+    );
+    for (method, count) in &methods {
+        println!("  {:<12} {}", method, count);
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@
 
 use console::style;
 
-use foia_analysis::ocr::TextExtractor;
+use foia_analysis::ocr::{LegacyConverter, Preprocessor, TextExtractor};
 
 /// Check analysis tool availability.
 pub async fn cmd_analyze_check() -> anyhow::Result<()> {
@@ -26,6 +26,27 @@ pub async fn cmd_analyze_check() -> anyhow::Result<()> {
         println!("  {:<15} {}", tool, status);
     }
 
+    // Legacy format converters (LibreOffice covers all of them)
+    println!("\n{}", style("Legacy Formats (.doc, .wpd, .rtf):").cyan());
+    let converters = LegacyConverter::check_tools();
+    for (tool, available) in &converters {
+        let status = if *available {
+            style("✓ found").green()
+        } else {
+            style("○ not found").yellow()
+        };
+        println!("  {:<15} {}", tool, status);
+    }
+
+    // Page cleanup before OCR (rotation, deskew, despeckle, contrast)
+    println!("\n{}", style("Preprocessing:").cyan());
+    let status = if Preprocessor::is_available() {
+        style("✓ found").green()
+    } else {
+        style("○ not found (pages are OCR'd as rendered)").yellow()
+    };
+    println!("  {:<15} {}", "ImageMagick", status);
+
     // Check new backends
     println!("\n{}", style("OCR Backends:").cyan());
 
@@ -136,6 +157,12 @@ pub async fn cmd_analyze_check() -> anyhow::Result<()> {
         println!("  - pdftotext, pdftoppm, pdfinfo: poppler-utils package");
         println!("  - tesseract: tesseract-ocr package");
     }
+    if !converters.iter().any(|(_, found)| *found) {
+        println!(
+            "  {} Install libreoffice (or antiword, libwpd-tools, unrtf) to extract .doc, .wpd and .rtf files",
+            style("!").yellow()
+        );
+    }
 
     Ok(())
 }
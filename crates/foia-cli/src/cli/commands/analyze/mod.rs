@@ -1,9 +1,11 @@
 //! Document analysis commands (MIME detection, text extraction, OCR).
 
+mod arbitrate;
 mod check;
 mod compare;
 mod process;
 
+pub use arbitrate::cmd_analyze_arbitrate;
 pub use check::cmd_analyze_check;
 pub use compare::cmd_analyze_compare;
 pub use process::cmd_analyze;
@@ -3,13 +3,14 @@
 use std::sync::Arc;
 
 use console::style;
-use indicatif::{ProgressBar, ProgressStyle};
 
 use foia::config::{Config, Settings};
+use foia::llm::LlmClient;
 use foia::work_queue::ExecutionStrategy;
-use foia_analysis::ocr::TextExtractor;
+use foia_analysis::ocr::{Arbiter, Preprocessor, TextExtractor};
 
 use crate::cli::commands::daemon::{ConfigWatcher, DaemonAction, ReloadMode};
+use crate::cli::progress::TaskProgress;
 
 /// Analyze documents: detect MIME types, extract text, and run OCR.
 #[allow(clippy::too_many_arguments)]
@@ -84,26 +85,36 @@ pub async fn cmd_analyze(
     }
 
     let repos = settings.repositories()?;
+    if daemon {
+        repos.pool().spawn_wal_checkpoint();
+    }
     let doc_repo = repos.documents;
+    let crawl_repo = repos.crawl;
     let config_history = repos.config_history;
     let scraper_configs = repos.scraper_configs;
 
+    let build_service = |config: &Config| -> anyhow::Result<AnalysisService> {
+        Ok(AnalysisService::with_ocr_config(
+            doc_repo.clone(),
+            config.analysis.ocr.clone(),
+            settings.documents_dir.clone(),
+        )
+        .with_retry_interval(retry_interval)
+        .with_processing_profiles(scraper_configs.clone())
+        .with_arbiter(build_arbiter(config)?)
+        .with_preprocessor(Preprocessor::new(&config.analysis.preprocess)))
+    };
+    let mut service = build_service(&config)?;
+
     let mut config_watcher = ConfigWatcher::new(
         daemon,
         reload,
         config_history,
-        scraper_configs,
-        config.hash(),
+        scraper_configs.clone(),
+        config,
     )
     .await;
 
-    let service = AnalysisService::with_ocr_config(
-        doc_repo,
-        config.analysis.ocr.clone(),
-        settings.documents_dir.clone(),
-    )
-    .with_retry_interval(retry_interval);
-
     // If specific doc_id provided, process just that document (no daemon mode)
     if let Some(id) = doc_id {
         println!("{} Processing single document: {}", style("→").cyan(), id);
@@ -121,11 +132,24 @@ pub async fn cmd_analyze(
     }
 
     loop {
+        // Pick up OCR backend changes between runs
+        if let Some(fresh) = config_watcher.reload_config(&crawl_repo).await {
+            match build_service(&fresh) {
+                Ok(fresh_service) => service = fresh_service,
+                Err(e) => tracing::warn!("Keeping previous analysis config: {}", e),
+            }
+        }
+
         // Check if there's work to do
         let (docs_count, pages_count) = service
             .count_needing_processing(source_id, mime_type)
             .await?;
-        if docs_count == 0 && pages_count == 0 {
+        let media_count = if methods.iter().any(|m| m == "whisper") {
+            service.count_needing_transcription(source_id).await?
+        } else {
+            0
+        };
+        if docs_count == 0 && pages_count == 0 && media_count == 0 {
             if daemon {
                 println!(
                     "{} No documents need OCR processing, sleeping for {}s...",
@@ -144,7 +168,7 @@ pub async fn cmd_analyze(
         let (event_tx, mut event_rx) = mpsc::channel::<AnalysisEvent>(100);
 
         // State for progress bar
-        let pb = Arc::new(tokio::sync::Mutex::new(None::<ProgressBar>));
+        let pb = Arc::new(tokio::sync::Mutex::new(None::<TaskProgress>));
         let pb_clone = pb.clone();
 
         // Spawn event handler for UI
@@ -166,16 +190,11 @@ pub async fn cmd_analyze(
                             style("→").cyan(),
                             total_documents
                         );
-                        let progress = ProgressBar::new(total_documents as u64);
-                        progress.set_style(
-                            ProgressStyle::default_bar()
-                                .template(
-                                    "{spinner:.green} [{bar:30.cyan/blue}] {pos}/{len} {wide_msg}",
-                                )
-                                .unwrap()
-                                .progress_chars("█▓░"),
+                        let progress = TaskProgress::new(
+                            "mime_check",
+                            total_documents as u64,
+                            "Checking MIME types...",
                         );
-                        progress.set_message("Checking MIME types...");
                         *pb_clone.lock().await = Some(progress);
                     }
                     AnalysisEvent::MimeChecked { .. } => {
@@ -207,16 +226,11 @@ pub async fn cmd_analyze(
                             style("→").cyan(),
                             total_documents
                         );
-                        let progress = ProgressBar::new(total_documents as u64);
-                        progress.set_style(
-                            ProgressStyle::default_bar()
-                                .template(
-                                    "{spinner:.green} [{bar:30.cyan/blue}] {pos}/{len} {wide_msg}",
-                                )
-                                .unwrap()
-                                .progress_chars("█▓░"),
+                        let progress = TaskProgress::new(
+                            "text_extraction",
+                            total_documents as u64,
+                            "Extracting text...",
                         );
-                        progress.set_message("Extracting text...");
                         *pb_clone.lock().await = Some(progress);
                     }
                     AnalysisEvent::DocumentCompleted {
@@ -288,16 +302,8 @@ pub async fn cmd_analyze(
                             style("→").cyan(),
                             total_pages
                         );
-                        let progress = ProgressBar::new(total_pages as u64);
-                        progress.set_style(
-                            ProgressStyle::default_bar()
-                                .template(
-                                    "{spinner:.green} [{bar:30.cyan/blue}] {pos}/{len} {wide_msg}",
-                                )
-                                .unwrap()
-                                .progress_chars("█▓░"),
-                        );
-                        progress.set_message("Running OCR...");
+                        let progress =
+                            TaskProgress::new("ocr", total_pages as u64, "Running OCR...");
                         *pb_clone.lock().await = Some(progress);
                     }
                     AnalysisEvent::PageOcrCompleted { improved, .. } => {
@@ -368,6 +374,129 @@ pub async fn cmd_analyze(
                         }
                         println!("{}", msg);
                     }
+                    AnalysisEvent::TranscriptionStarted { total_documents } => {
+                        println!(
+                            "{} Transcribing {} audio/video documents",
+                            style("→").cyan(),
+                            total_documents
+                        );
+                        let progress = TaskProgress::new(
+                            "transcription",
+                            total_documents as u64,
+                            "Transcribing...",
+                        );
+                        *pb_clone.lock().await = Some(progress);
+                    }
+                    AnalysisEvent::DocumentTranscribed { .. } => {
+                        if let Some(ref progress) = *pb_clone.lock().await {
+                            progress.inc(1);
+                        }
+                    }
+                    AnalysisEvent::TranscriptionFailed { document_id, error } => {
+                        if let Some(ref progress) = *pb_clone.lock().await {
+                            progress.suspend(|| {
+                                eprintln!(
+                                    "  {} Transcription of {} failed: {}",
+                                    style("✗").red(),
+                                    document_id,
+                                    error
+                                );
+                            });
+                            progress.inc(1);
+                        } else {
+                            eprintln!(
+                                "  {} Transcription of {} failed: {}",
+                                style("✗").red(),
+                                document_id,
+                                error
+                            );
+                        }
+                    }
+                    AnalysisEvent::TranscriptionComplete {
+                        succeeded,
+                        failed,
+                        skipped,
+                    } => {
+                        if let Some(ref progress) = *pb_clone.lock().await {
+                            progress.finish_and_clear();
+                        }
+                        *pb_clone.lock().await = None;
+                        let mut msg = format!(
+                            "{} Transcription complete: {} documents transcribed",
+                            style("✓").green(),
+                            succeeded
+                        );
+                        if failed > 0 {
+                            msg.push_str(&format!(", {} failed", failed));
+                        }
+                        if skipped > 0 {
+                            msg.push_str(&format!(", {} missing files skipped", skipped));
+                        }
+                        println!("{}", msg);
+                    }
+                    AnalysisEvent::VirtualFilesStarted { total_files } => {
+                        println!(
+                            "{} Extracting text from {} attachments and archive members",
+                            style("→").cyan(),
+                            total_files
+                        );
+                        let progress = TaskProgress::new(
+                            "attachment_text",
+                            total_files as u64,
+                            "Extracting...",
+                        );
+                        *pb_clone.lock().await = Some(progress);
+                    }
+                    AnalysisEvent::VirtualFileCompleted { .. } => {
+                        if let Some(ref progress) = *pb_clone.lock().await {
+                            progress.inc(1);
+                        }
+                    }
+                    AnalysisEvent::VirtualFileFailed {
+                        virtual_file_id,
+                        error,
+                    } => {
+                        if let Some(ref progress) = *pb_clone.lock().await {
+                            progress.suspend(|| {
+                                eprintln!(
+                                    "  {} Attachment {} failed: {}",
+                                    style("✗").red(),
+                                    virtual_file_id,
+                                    error
+                                );
+                            });
+                            progress.inc(1);
+                        } else {
+                            eprintln!(
+                                "  {} Attachment {} failed: {}",
+                                style("✗").red(),
+                                virtual_file_id,
+                                error
+                            );
+                        }
+                    }
+                    AnalysisEvent::VirtualFilesComplete {
+                        succeeded,
+                        failed,
+                        skipped,
+                    } => {
+                        if let Some(ref progress) = *pb_clone.lock().await {
+                            progress.finish_and_clear();
+                        }
+                        *pb_clone.lock().await = None;
+                        let mut msg = format!(
+                            "{} Attachment text complete: {} files processed",
+                            style("✓").green(),
+                            succeeded
+                        );
+                        if failed > 0 {
+                            msg.push_str(&format!(", {} failed", failed));
+                        }
+                        if skipped > 0 {
+                            msg.push_str(&format!(", {} skipped", skipped));
+                        }
+                        println!("{}", msg);
+                    }
                     AnalysisEvent::DocumentStarted { .. }
                     | AnalysisEvent::PageOcrStarted { .. } => {}
                 }
@@ -396,3 +525,21 @@ pub async fn cmd_analyze(
 
     Ok(())
 }
+
+/// The arbiter that chooses pages' final text among OCR readings, with the
+/// LLM judge when it and the LLM are enabled.
+pub(super) fn build_arbiter(config: &Config) -> anyhow::Result<Arbiter> {
+    let arbitration = &config.analysis.arbitration;
+    let arbiter = Arbiter::new(arbitration.clone()).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read arbitration dictionary {}: {}",
+            arbitration.dictionary.as_deref().unwrap_or_default(),
+            e
+        )
+    })?;
+    Ok(if arbitration.llm_judge && config.llm.enabled() {
+        arbiter.with_llm(LlmClient::new(config.llm.clone()))
+    } else {
+        arbiter
+    })
+}
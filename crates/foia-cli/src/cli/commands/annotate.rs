@@ -7,13 +7,16 @@ use indicatif::{ProgressBar, ProgressStyle};
 use tokio::sync::mpsc;
 
 use foia::config::{Config, Settings};
+use foia::repository::diesel_document::SummarySelector;
 use foia::work_queue::ExecutionStrategy;
 use foia_annotate::services::annotation::{
-    AnnotationEvent, AnnotationManager, Annotator, DateAnnotator, LlmAnnotator, NerAnnotator,
+    AnnotationEvent, AnnotationManager, Annotator, BatesAnnotator, ClassificationAnnotator,
+    DateAnnotator, ExemptionAnnotator, LlmAnnotator, NerAnnotator, SectionAnnotator,
+    TitleAnnotator,
 };
 
 use super::daemon::{ConfigWatcher, DaemonAction, ReloadMode};
-use super::helpers::truncate;
+use super::helpers::{parse_date_arg, truncate};
 
 /// Spawn a task that drives a progress bar from annotation events.
 ///
@@ -113,21 +116,18 @@ pub async fn cmd_annotate(
     strategy: ExecutionStrategy,
 ) -> anyhow::Result<()> {
     let repos = settings.repositories()?;
-    let manager = AnnotationManager::new(repos.documents.clone());
+    if daemon {
+        repos.pool().spawn_wal_checkpoint();
+    }
+    let manager = AnnotationManager::new(repos.documents.clone())
+        .with_processing_profiles(repos.scraper_configs.clone());
 
     // Initial config load
     let config = Config::load().await;
+    let crawl_repo = repos.crawl;
     let config_history = repos.config_history;
     let scraper_configs = repos.scraper_configs;
 
-    let mut config_watcher = ConfigWatcher::new(
-        daemon,
-        reload,
-        config_history,
-        scraper_configs,
-        config.hash(),
-    )
-    .await;
     let mut llm_config = config.llm.clone();
     if let Some(ref ep) = endpoint {
         llm_config.set_endpoint(ep.clone());
@@ -145,6 +145,8 @@ pub async fn cmd_annotate(
         return Ok(());
     }
 
+    let mut config_watcher =
+        ConfigWatcher::new(daemon, reload, config_history, scraper_configs, config).await;
     let mut annotator = LlmAnnotator::new(llm_config.clone());
 
     println!(
@@ -154,6 +156,26 @@ pub async fn cmd_annotate(
         llm_config.endpoint(),
         llm_config.model()
     );
+    let rate = llm_config
+        .requests_per_minute()
+        .map(|rpm| format!(", {} per minute", rpm))
+        .unwrap_or_default();
+    if llm_config.batch_size() > 1 {
+        println!(
+            "  {} Up to {} short documents per request, {} requests at a time{}",
+            style("→").dim(),
+            llm_config.batch_size(),
+            llm_config.max_concurrent(),
+            rate
+        );
+    } else {
+        println!(
+            "  {} {} requests at a time{}",
+            style("→").dim(),
+            llm_config.max_concurrent(),
+            rate
+        );
+    }
 
     if !annotator.is_available().await {
         println!(
@@ -181,9 +203,8 @@ pub async fn cmd_annotate(
     }
 
     loop {
-        // Reload config in daemon mode
-        if daemon && matches!(reload, ReloadMode::NextRun | ReloadMode::Inplace) {
-            let fresh_config = Config::load().await;
+        // Reload config between runs in daemon mode
+        if let Some(fresh_config) = config_watcher.reload_config(&crawl_repo).await {
             let mut new_llm_config = fresh_config.llm.clone();
             if let Some(ref ep) = endpoint {
                 new_llm_config.set_endpoint(ep.clone());
@@ -197,8 +218,8 @@ pub async fn cmd_annotate(
                 || new_llm_config.enabled() != llm_config.enabled()
             {
                 println!(
-                    "{} Config reloaded (model: {})",
-                    style("↻").cyan(),
+                    "  {} LLM settings changed (model: {})",
+                    style("→").dim(),
                     new_llm_config.model()
                 );
                 llm_config = new_llm_config;
@@ -274,7 +295,8 @@ pub async fn cmd_detect_dates(
     let repos = settings.repositories()?;
 
     let annotator = DateAnnotator::new(dry_run);
-    let manager = AnnotationManager::new(repos.documents);
+    let manager =
+        AnnotationManager::new(repos.documents).with_processing_profiles(repos.scraper_configs);
 
     let total_count = manager.count_needing(&annotator, source_id).await?;
 
@@ -335,7 +357,8 @@ pub async fn cmd_extract_entities(
     let repos = settings.repositories()?;
 
     let annotator = NerAnnotator::new();
-    let manager = AnnotationManager::new(repos.documents);
+    let manager =
+        AnnotationManager::new(repos.documents).with_processing_profiles(repos.scraper_configs);
 
     let total_count = manager.count_needing(&annotator, source_id).await?;
 
@@ -375,6 +398,295 @@ pub async fn cmd_extract_entities(
     Ok(())
 }
 
+/// Extract Bates stamps from document pages.
+pub async fn cmd_extract_bates(
+    settings: &Settings,
+    source_id: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    let annotator = BatesAnnotator::new();
+    let manager =
+        AnnotationManager::new(repos.documents).with_processing_profiles(repos.scraper_configs);
+
+    let total_count = manager.count_needing(&annotator, source_id).await?;
+
+    if total_count == 0 {
+        println!("{} No documents need Bates extraction", style("!").yellow());
+        println!("  Documents need OCR complete status with page text");
+        return Ok(());
+    }
+
+    let effective_limit = if limit > 0 {
+        limit
+    } else {
+        total_count as usize
+    };
+
+    println!(
+        "{} Extracting Bates numbers from up to {} documents",
+        style("→").cyan(),
+        effective_limit
+    );
+
+    let (event_tx, event_rx) = mpsc::channel::<AnnotationEvent>(100);
+    let event_handler = spawn_progress_handler(event_rx, "Bates extraction");
+
+    let annotator_arc: Arc<dyn Annotator> = Arc::new(annotator);
+    let _result = manager
+        .run_batch(annotator_arc, source_id, limit, None, ExecutionStrategy::Wide, event_tx)
+        .await?;
+
+    if let Err(e) = event_handler.await {
+        tracing::warn!("Event handler task failed: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Extract FOIA exemption markings from document pages.
+pub async fn cmd_extract_exemptions(
+    settings: &Settings,
+    source_id: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    let annotator = ExemptionAnnotator::new();
+    let manager =
+        AnnotationManager::new(repos.documents).with_processing_profiles(repos.scraper_configs);
+
+    let total_count = manager.count_needing(&annotator, source_id).await?;
+
+    if total_count == 0 {
+        println!(
+            "{} No documents need exemption extraction",
+            style("!").yellow()
+        );
+        println!("  Documents need OCR complete status with page text");
+        return Ok(());
+    }
+
+    let effective_limit = if limit > 0 {
+        limit
+    } else {
+        total_count as usize
+    };
+
+    println!(
+        "{} Extracting exemption markings from up to {} documents",
+        style("→").cyan(),
+        effective_limit
+    );
+
+    let (event_tx, event_rx) = mpsc::channel::<AnnotationEvent>(100);
+    let event_handler = spawn_progress_handler(event_rx, "Exemption extraction");
+
+    let annotator_arc: Arc<dyn Annotator> = Arc::new(annotator);
+    let _result = manager
+        .run_batch(annotator_arc, source_id, limit, None, ExecutionStrategy::Wide, event_tx)
+        .await?;
+
+    if let Err(e) = event_handler.await {
+        tracing::warn!("Event handler task failed: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Derive the chapters and sections of long reports.
+pub async fn cmd_extract_sections(
+    settings: &Settings,
+    source_id: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    let annotator = SectionAnnotator::new(settings.documents_dir.clone());
+    let manager =
+        AnnotationManager::new(repos.documents).with_processing_profiles(repos.scraper_configs);
+
+    let total_count = manager.count_needing(&annotator, source_id).await?;
+
+    if total_count == 0 {
+        println!(
+            "{} No documents need section extraction",
+            style("!").yellow()
+        );
+        println!("  Documents need OCR complete status with page text");
+        return Ok(());
+    }
+
+    let effective_limit = if limit > 0 {
+        limit
+    } else {
+        total_count as usize
+    };
+
+    println!(
+        "{} Deriving sections of up to {} documents",
+        style("→").cyan(),
+        effective_limit
+    );
+
+    let (event_tx, event_rx) = mpsc::channel::<AnnotationEvent>(100);
+    let event_handler = spawn_progress_handler(event_rx, "Section extraction");
+
+    let annotator_arc: Arc<dyn Annotator> = Arc::new(annotator);
+    let _result = manager
+        .run_batch(annotator_arc, source_id, limit, None, ExecutionStrategy::Wide, event_tx)
+        .await?;
+
+    if let Err(e) = event_handler.await {
+        tracing::warn!("Event handler task failed: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Classify documents by record type.
+pub async fn cmd_classify(
+    settings: &Settings,
+    source_id: Option<&str>,
+    limit: usize,
+    llm: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    let annotator = if llm {
+        let llm_config = Config::load().await.llm;
+        if !llm_config.enabled() {
+            println!(
+                "{} LLM annotation is disabled in configuration",
+                style("!").yellow()
+            );
+            println!("  Set llm.enabled = true in your foia.json config, or drop --llm");
+            return Ok(());
+        }
+        println!(
+            "{} Classifying with {} ({})",
+            style("→").cyan(),
+            llm_config.provider_name(),
+            llm_config.model()
+        );
+        ClassificationAnnotator::with_llm(llm_config)
+    } else {
+        ClassificationAnnotator::new()
+    };
+    let manager =
+        AnnotationManager::new(repos.documents).with_processing_profiles(repos.scraper_configs);
+
+    let total_count = manager.count_needing(&annotator, source_id).await?;
+
+    if total_count == 0 {
+        println!("{} No documents need classification", style("!").yellow());
+        return Ok(());
+    }
+
+    let effective_limit = if limit > 0 {
+        limit
+    } else {
+        total_count as usize
+    };
+
+    println!(
+        "{} Classifying up to {} documents",
+        style("→").cyan(),
+        effective_limit
+    );
+
+    let (event_tx, event_rx) = mpsc::channel::<AnnotationEvent>(100);
+    let event_handler = spawn_progress_handler(event_rx, "Classification");
+
+    let annotator_arc: Arc<dyn Annotator> = Arc::new(annotator);
+    let _result = manager
+        .run_batch(annotator_arc, source_id, limit, None, ExecutionStrategy::Wide, event_tx)
+        .await?;
+
+    if let Err(e) = event_handler.await {
+        tracing::warn!("Event handler task failed: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Suggest descriptive titles for documents titled after their file name.
+pub async fn cmd_improve_titles(
+    settings: &Settings,
+    source_id: Option<&str>,
+    limit: usize,
+    llm: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let apply_sources = repos
+        .scraper_configs
+        .sources_applying_titles()
+        .await?
+        .into_iter()
+        .collect();
+
+    let annotator = if llm {
+        let llm_config = Config::load().await.llm;
+        if !llm_config.enabled() {
+            println!(
+                "{} LLM annotation is disabled in configuration",
+                style("!").yellow()
+            );
+            println!("  Set llm.enabled = true in your foia.json config, or drop --llm");
+            return Ok(());
+        }
+        println!(
+            "{} Suggesting titles with {} ({})",
+            style("→").cyan(),
+            llm_config.provider_name(),
+            llm_config.model()
+        );
+        TitleAnnotator::new(apply_sources).with_llm(llm_config)
+    } else {
+        TitleAnnotator::new(apply_sources)
+    };
+    let manager =
+        AnnotationManager::new(repos.documents).with_processing_profiles(repos.scraper_configs);
+
+    let total_count = manager.count_needing(&annotator, source_id).await?;
+
+    if total_count == 0 {
+        println!(
+            "{} No documents need title enhancement",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let effective_limit = if limit > 0 {
+        limit
+    } else {
+        total_count as usize
+    };
+
+    println!(
+        "{} Checking titles of up to {} documents",
+        style("→").cyan(),
+        effective_limit
+    );
+
+    let (event_tx, event_rx) = mpsc::channel::<AnnotationEvent>(100);
+    let event_handler = spawn_progress_handler(event_rx, "Title enhancement");
+
+    let annotator_arc: Arc<dyn Annotator> = Arc::new(annotator);
+    let _result = manager
+        .run_batch(annotator_arc, source_id, limit, None, ExecutionStrategy::Wide, event_tx)
+        .await?;
+
+    if let Err(e) = event_handler.await {
+        tracing::warn!("Event handler task failed: {}", e);
+    }
+    println!("  Review suggestions with `foia titles review`");
+
+    Ok(())
+}
+
 /// Reset annotations for documents, allowing them to be re-annotated.
 pub async fn cmd_annotate_reset(
     settings: &Settings,
@@ -429,3 +741,90 @@ pub async fn cmd_annotate_reset(
 
     Ok(())
 }
+
+/// Requeue summaries written before a date or with another prompt version.
+///
+/// The documents keep their current synopsis until the next annotation run
+/// replaces it.
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_annotate_resummarize(
+    settings: &Settings,
+    source_id: Option<&str>,
+    model_older_than: Option<&str>,
+    prompt_version: Option<String>,
+    limit: usize,
+    dry_run: bool,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    if model_older_than.is_none() && prompt_version.is_none() {
+        anyhow::bail!(
+            "Specify --model-older-than and/or --prompt-version \
+             (use `annotate reset` to redo every summary)"
+        );
+    }
+    let selector = SummarySelector {
+        older_than: model_older_than.map(parse_date_arg).transpose()?,
+        prompt_version,
+    };
+
+    let config = Config::load().await;
+    println!(
+        "{} Current: {} model {}, prompt version {}",
+        style("→").cyan(),
+        config.llm.provider_name(),
+        config.llm.model(),
+        config.llm.prompt_version()
+    );
+
+    let repos = settings.repositories()?;
+    let doc_repo = repos.documents;
+    let ids = doc_repo
+        .find_summaries_to_refresh(source_id, &selector, limit)
+        .await?;
+
+    if ids.is_empty() {
+        println!("{} No summaries match", style("!").yellow());
+        return Ok(());
+    }
+
+    let scope = source_id.unwrap_or("all sources");
+    println!(
+        "{} Found {} summaries to regenerate in {}",
+        style("→").cyan(),
+        ids.len(),
+        scope
+    );
+
+    if dry_run {
+        for id in ids.iter().take(20) {
+            println!("  {}", id);
+        }
+        if ids.len() > 20 {
+            println!("  ... and {} more", ids.len() - 20);
+        }
+        return Ok(());
+    }
+
+    if !confirm {
+        print!("Requeue {} documents for summarization? [y/N] ", ids.len());
+        use std::io::Write;
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{} Cancelled", style("!").yellow());
+            return Ok(());
+        }
+    }
+
+    let requeued = doc_repo.requeue_for_summary(&ids).await?;
+    println!(
+        "{} Requeued {} documents - run `foia annotate` to regenerate their summaries",
+        style("✓").green(),
+        requeued
+    );
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! API key commands.
+
+use chrono::{Duration, Utc};
+use console::style;
+
+use foia::config::{Config, Settings};
+use foia::services::api_keys::ApiKeySpec;
+
+use super::helpers::truncate;
+
+/// List keys, active ones first.
+pub async fn cmd_api_key_list(settings: &Settings) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let mut keys = repos.documents.list_api_keys().await?;
+
+    if keys.is_empty() {
+        println!(
+            "{} No API keys. Issue one with 'foia api-key create <name>'.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let default_limit = Config::load().await.access.api_rate_limit();
+    keys.sort_by_key(|k| !k.is_active());
+    println!("\n{}", style("API Keys").bold());
+    println!("{}", "-".repeat(90));
+    println!(
+        "{:>4} {:<20} {:<14} {:<26} {:>8} {:>12}",
+        "ID", "Name", "Key", "Scope", "Per min", "Requests"
+    );
+    println!("{}", "-".repeat(90));
+    for key in &keys {
+        let limit = match key.rate_limit.unwrap_or(default_limit) {
+            0 => "-".to_string(),
+            n => n.to_string(),
+        };
+        let line = format!(
+            "{:>4} {:<20} {:<14} {:<26} {:>8} {:>12}",
+            key.id,
+            truncate(&key.name, 20),
+            format!("{}…", key.prefix),
+            truncate(&key.scope_label(), 26),
+            limit,
+            key.requests
+        );
+        match key.revoked_at {
+            Some(revoked) => println!(
+                "{} {}",
+                style(line).dim(),
+                style(format!("revoked {}", revoked.format("%Y-%m-%d"))).red()
+            ),
+            None => println!("{}", line),
+        }
+    }
+    Ok(())
+}
+
+/// Issue a key and print it once.
+pub async fn cmd_api_key_create(
+    settings: &Settings,
+    name: &str,
+    sources: Vec<String>,
+    read_write: bool,
+    rate_limit: Option<u32>,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    for source in &sources {
+        if repos.sources.get(source).await?.is_none() {
+            anyhow::bail!("No source with ID '{}'", source);
+        }
+    }
+
+    let spec = ApiKeySpec {
+        name: name.to_string(),
+        read_only: !read_write,
+        sources: (!sources.is_empty()).then_some(sources),
+        rate_limit,
+    };
+    let (key, secret) = repos.documents.create_api_key(&spec).await?;
+    println!(
+        "{} Created API key {} for {} ({})",
+        style("✓").green(),
+        key.id,
+        key.name,
+        key.scope_label()
+    );
+    println!("\n    {}\n", style(&secret).bold());
+    println!(
+        "{} Store it now; it cannot be shown again. Clients send it in the X-API-Key header.",
+        style("!").yellow()
+    );
+    Ok(())
+}
+
+/// Revoke a key.
+pub async fn cmd_api_key_revoke(settings: &Settings, id: i32) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    if repos.documents.revoke_api_key(id).await? {
+        println!("{} Revoked API key {}", style("✓").green(), id);
+    } else {
+        println!("{} No active API key with ID {}", style("✗").red(), id);
+    }
+    Ok(())
+}
+
+/// Print requests per day made with a key.
+pub async fn cmd_api_key_usage(settings: &Settings, id: i32, days: u32) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let Some(key) = repos
+        .documents
+        .list_api_keys()
+        .await?
+        .into_iter()
+        .find(|k| k.id == id)
+    else {
+        anyhow::bail!("No API key with ID {}", id);
+    };
+
+    let since = Utc::now().date_naive() - Duration::days(i64::from(days.max(1)) - 1);
+    let usage = repos.documents.api_key_usage(id, since).await?;
+
+    println!(
+        "\n{} {}",
+        style(format!("Usage of API key {}", key.id)).bold(),
+        style(format!("({})", key.name)).dim()
+    );
+    println!("{}", "-".repeat(90));
+    if usage.is_empty() {
+        println!("No requests in the last {} days.", days);
+    } else {
+        let max = usage.iter().map(|u| u.requests).max().unwrap_or(1).max(1);
+        for day in &usage {
+            let bar = "█".repeat(((day.requests * 50) / max).max(1) as usize);
+            println!("{}  {:>10}  {}", day.day, day.requests, style(bar).cyan());
+        }
+        let total: i64 = usage.iter().map(|u| u.requests).sum();
+        println!("{}", "-".repeat(90));
+        println!("{:<10}  {:>10}", "Total", total);
+    }
+    if let Some(last) = key.last_used_at {
+        println!(
+            "\nLast used {}",
+            style(last.format("%Y-%m-%d %H:%M UTC")).dim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Bates number lookup and sequence gap commands.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::bates::{find_gaps, BatesNumber};
+
+/// Find the documents and pages stamped with a Bates number.
+pub async fn cmd_bates_lookup(
+    settings: &Settings,
+    number: &str,
+    source_id: Option<&str>,
+) -> anyhow::Result<()> {
+    let Some(number) = BatesNumber::parse(number) else {
+        anyhow::bail!(
+            "'{}' is not a Bates number (expected a prefix and at least 5 digits, e.g. DOJ-OGR-00012345)",
+            number
+        );
+    };
+    let repos = settings.repositories()?;
+    let hits = repos.documents.find_bates(&number, source_id).await?;
+
+    if hits.is_empty() {
+        println!("{} No document carries {}", style("!").yellow(), number);
+        println!("  Run `foia bates extract` to index Bates stamps");
+        return Ok(());
+    }
+
+    for hit in &hits {
+        println!(
+            "{} {} page {} {}",
+            style("✓").green(),
+            hit.title,
+            hit.page,
+            style(format!("({})", hit.source_id)).dim()
+        );
+        println!(
+            "  {} {} – {} (pages {}–{})",
+            style(&hit.document_id[..8.min(hit.document_id.len())]).dim(),
+            hit.range.first(),
+            hit.range.last(),
+            hit.range.first_page,
+            hit.range.last_page
+        );
+    }
+
+    Ok(())
+}
+
+/// List numbers missing between the Bates ranges of a source.
+pub async fn cmd_bates_gaps(settings: &Settings, source_id: &str) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let doc_repo = repos.documents;
+    let ranges = doc_repo.get_source_bates_ranges(source_id).await?;
+
+    if ranges.is_empty() {
+        println!(
+            "{} No Bates ranges recorded for {}",
+            style("!").yellow(),
+            source_id
+        );
+        println!("  Run `foia bates extract {}` first", source_id);
+        return Ok(());
+    }
+
+    let gaps = find_gaps(&ranges);
+    if gaps.is_empty() {
+        println!(
+            "{} {} ranges in {}, no gaps",
+            style("✓").green(),
+            ranges.len(),
+            source_id
+        );
+        return Ok(());
+    }
+
+    let missing: u64 = gaps.iter().map(|g| g.missing).sum();
+    println!(
+        "{} {} gaps in {} ({} numbers missing)",
+        style("!").yellow(),
+        gaps.len(),
+        source_id,
+        missing
+    );
+    for gap in &gaps {
+        let span = if gap.from == gap.to {
+            gap.from.clone()
+        } else {
+            format!("{} – {}", gap.from, gap.to)
+        };
+        println!("  {} {} missing", style(span).bold(), gap.missing);
+        println!(
+            "    {} after {}, before {}",
+            style("between").dim(),
+            gap.after_document,
+            gap.before_document
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+//! CAPTCHA challenge queue commands.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::models::{ChallengeStatus, CrawlChallenge};
+use foia::repository::DieselCrawlRepository;
+
+/// List detected challenges.
+pub async fn cmd_captcha_list(
+    settings: &Settings,
+    status: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let status = status
+        .map(|s| {
+            ChallengeStatus::from_str(s).ok_or_else(|| {
+                anyhow::anyhow!("Unknown status '{}' (pending, solved, dismissed)", s)
+            })
+        })
+        .transpose()?;
+
+    let repos = settings.repositories()?;
+    let challenges = repos.crawl.list_challenges(status, limit).await?;
+
+    if challenges.is_empty() {
+        println!("{} No challenges found", style("✓").green());
+        return Ok(());
+    }
+
+    println!(
+        "{:<6} {:<10} {:<16} {:<30} {:<12} Detected",
+        "ID", "Status", "Source", "Domain", "Kind"
+    );
+    for c in &challenges {
+        let status = match c.status {
+            ChallengeStatus::Pending => style(c.status.as_str()).yellow(),
+            ChallengeStatus::Solved => style(c.status.as_str()).green(),
+            ChallengeStatus::Dismissed => style(c.status.as_str()).dim(),
+        };
+        println!(
+            "{:<6} {:<10} {:<16} {:<30} {:<12} {}",
+            c.id,
+            status,
+            c.source_id,
+            c.domain,
+            c.kind,
+            c.detected_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    let pending = challenges
+        .iter()
+        .filter(|c| c.status == ChallengeStatus::Pending)
+        .count();
+    if pending > 0 {
+        println!();
+        println!(
+            "{} {} domain(s) paused. Solve with: foia captcha solve <id>",
+            style("!").yellow(),
+            pending
+        );
+    }
+
+    Ok(())
+}
+
+/// Solve a pending challenge, either in a browser window or with cookies
+/// copied from a browser that already passed it.
+pub async fn cmd_captcha_solve(
+    settings: &Settings,
+    id: i64,
+    cookies: Option<String>,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let challenge = require_pending(&repos.crawl, id).await?;
+
+    let cookies = match cookies {
+        Some(c) => c.trim().to_string(),
+        None => solve_in_browser(settings, &challenge).await?,
+    };
+    if cookies.is_empty() {
+        anyhow::bail!("No cookies captured; the challenge is still pending");
+    }
+
+    repos
+        .crawl
+        .resolve_challenge(id, ChallengeStatus::Solved, Some(&cookies))
+        .await?;
+    println!(
+        "{} Challenge {} solved; requests to {} resume with the captured cookies",
+        style("✓").green(),
+        id,
+        challenge.domain
+    );
+    Ok(())
+}
+
+/// Dismiss a pending challenge without solving it.
+pub async fn cmd_captcha_dismiss(settings: &Settings, id: i64) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let challenge = require_pending(&repos.crawl, id).await?;
+
+    repos
+        .crawl
+        .resolve_challenge(id, ChallengeStatus::Dismissed, None)
+        .await?;
+    println!(
+        "{} Challenge {} dismissed; requests to {} resume",
+        style("✓").green(),
+        id,
+        challenge.domain
+    );
+    Ok(())
+}
+
+async fn require_pending(crawl: &DieselCrawlRepository, id: i64) -> anyhow::Result<CrawlChallenge> {
+    match crawl.get_challenge(id).await? {
+        Some(c) if c.status == ChallengeStatus::Pending => Ok(c),
+        Some(c) => anyhow::bail!("Challenge {} is already {}", id, c.status.as_str()),
+        None => anyhow::bail!("Challenge {} not found", id),
+    }
+}
+
+/// Open the challenge URL in a visible browser and wait for the operator.
+#[cfg(feature = "browser")]
+async fn solve_in_browser(
+    settings: &Settings,
+    challenge: &CrawlChallenge,
+) -> anyhow::Result<String> {
+    use foia::browser::{cookie_header, BrowserEngineConfig, BrowserFetcher};
+    use tokio::io::AsyncBufReadExt;
+
+    // Reuse the source's browser settings (proxy, profile) when it has them
+    let repos = settings.repositories()?;
+    let config = repos
+        .scraper_configs
+        .get(&challenge.source_id)
+        .await?
+        .and_then(|c| c.browser)
+        .unwrap_or_default()
+        .with_env_overrides()
+        .with_session_dir(&settings.data_dir, &challenge.source_id);
+    let config = BrowserEngineConfig {
+        headless: false,
+        ..config
+    };
+
+    println!(
+        "{} Opening {} in a browser window",
+        style("→").cyan(),
+        challenge.url
+    );
+    println!("  Complete the challenge, then press Enter here.");
+
+    let mut fetcher = BrowserFetcher::new(config);
+    let cookies = fetcher
+        .solve_challenge(&challenge.url, async {
+            let mut line = String::new();
+            let mut stdin = tokio::io::BufReader::new(tokio::io::stdin());
+            let _ = stdin.read_line(&mut line).await;
+        })
+        .await;
+    fetcher.close().await;
+
+    Ok(cookie_header(&cookies?))
+}
+
+#[cfg(not(feature = "browser"))]
+async fn solve_in_browser(
+    _settings: &Settings,
+    _challenge: &CrawlChallenge,
+) -> anyhow::Result<String> {
+    anyhow::bail!(
+        "Browser support not compiled; pass --cookies with the Cookie header from a browser that passed the challenge"
+    )
+}
@@ -0,0 +1,122 @@
+//! Overlap report between two sources or tagged collections.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::overlap::{compare, fingerprints, DocRef, Match, Production};
+
+use crate::cli::output;
+
+/// Compare two productions: shared files, near-duplicate text, and
+/// documents only one side has.
+pub async fn cmd_compare(
+    settings: &Settings,
+    left: &str,
+    right: &str,
+    threshold: f32,
+    limit: usize,
+) -> anyhow::Result<()> {
+    if !(0.0..=1.0).contains(&threshold) {
+        anyhow::bail!("Threshold must be between 0 and 1");
+    }
+    let repos = settings.repositories()?;
+    let doc_repo = repos.documents;
+
+    let left_prints = fingerprints(&doc_repo, &Production::parse(left)).await?;
+    let right_prints = fingerprints(&doc_repo, &Production::parse(right)).await?;
+    if left_prints.is_empty() || right_prints.is_empty() {
+        let empty = if left_prints.is_empty() { left } else { right };
+        anyhow::bail!(
+            "No documents in '{}' (use a source ID or tag:<name>)",
+            empty
+        );
+    }
+
+    let report = compare(&left_prints, &right_prints, threshold);
+    if output::is_json() {
+        output::emit("result", serde_json::to_value(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "\n{} {} ({} documents) vs {} ({} documents)",
+        style("Overlap:").bold(),
+        left,
+        report.left_total,
+        right,
+        report.right_total
+    );
+    println!("{}", "-".repeat(60));
+    println!("{:<32} {:>8}", "Identical files", report.exact.len());
+    println!(
+        "{:<32} {:>8}",
+        format!("Near-duplicate text (≥{:.0}%)", threshold * 100.0),
+        report.near.len()
+    );
+    println!(
+        "{:<32} {:>8}",
+        format!("Only in {}", left),
+        report.only_left.len()
+    );
+    println!(
+        "{:<32} {:>8}",
+        format!("Only in {}", right),
+        report.only_right.len()
+    );
+
+    print_matches("Near-duplicate text", &report.near, limit);
+    print_docs(&format!("Only in {}", left), &report.only_left, limit);
+    print_docs(&format!("Only in {}", right), &report.only_right, limit);
+
+    Ok(())
+}
+
+fn print_matches(heading: &str, matches: &[Match], limit: usize) {
+    if matches.is_empty() {
+        return;
+    }
+    println!("\n{}", style(heading).bold());
+    for m in matches.iter().take(limit) {
+        println!(
+            "  {:>3.0}%  {} {}",
+            m.similarity * 100.0,
+            style(short_id(&m.left.document_id)).dim(),
+            m.left.title
+        );
+        println!(
+            "        {} {}",
+            style(short_id(&m.right.document_id)).dim(),
+            m.right.title
+        );
+    }
+    print_more(matches.len(), limit);
+}
+
+fn print_docs(heading: &str, docs: &[DocRef], limit: usize) {
+    if docs.is_empty() {
+        return;
+    }
+    println!("\n{}", style(heading).bold());
+    for doc in docs.iter().take(limit) {
+        println!(
+            "  {} {}",
+            style(short_id(&doc.document_id)).dim(),
+            doc.title
+        );
+    }
+    print_more(docs.len(), limit);
+}
+
+fn print_more(total: usize, limit: usize) {
+    if total > limit {
+        println!(
+            "  {} {} more (--limit or --json)",
+            style("…").dim(),
+            total - limit
+        );
+    }
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..8.min(id.len())]
+}
@@ -1,11 +1,16 @@
 //! Configuration management commands.
 
+use std::io::Read;
 use std::path::Path;
 
 use console::style;
 
 use crate::cli::icons::{error, success};
-use foia::config::{Config, ScraperConfig, Settings};
+use crate::cli::output;
+use foia::config::{Config, ScraperConfig, Settings, SourcesConfig};
+use foia::prefer_db::FoiaConfigLoader;
+use foia::repository::util::redact_url_password;
+use foia::services::config_history::{self, short_hash, ConfigDiff};
 
 /// Migrate a config file into the database.
 pub async fn cmd_config_transfer(settings: &Settings, file: Option<&Path>) -> anyhow::Result<()> {
@@ -38,6 +43,7 @@ pub async fn cmd_config_transfer(settings: &Settings, file: Option<&Path>) -> an
 
     // Save each scraper config to scraper_configs table
     let repos = settings.repositories()?;
+    config_history::record(&repos.scraper_configs, &repos.config_history).await?;
     let mut transferred = 0usize;
     for (source_id, scraper_config) in &config.scrapers {
         repos
@@ -46,6 +52,7 @@ pub async fn cmd_config_transfer(settings: &Settings, file: Option<&Path>) -> an
             .await?;
         transferred += 1;
     }
+    config_history::record(&repos.scraper_configs, &repos.config_history).await?;
 
     eprintln!(
         "{} Transferred {} scraper configs to database",
@@ -130,12 +137,16 @@ pub async fn cmd_config_set(settings: &Settings, setting: &str, value: &str) ->
     // Set the value at the sub-path
     set_json_value(&mut json_value, sub_path, new_value)?;
 
-    // Validate by deserializing into ScraperConfig
-    let config: ScraperConfig = serde_json::from_value(json_value)
+    // Validate, then save to DB with a history entry
+    let config = config_history::validate_scraper(source_id, json_value)
         .map_err(|e| anyhow::anyhow!("Invalid config after update: {}", e))?;
-
-    // Save to DB
-    repos.scraper_configs.upsert(source_id, &config).await?;
+    config_history::save_scraper(
+        &repos.scraper_configs,
+        &repos.config_history,
+        source_id,
+        &config,
+    )
+    .await?;
 
     eprintln!("{} Config updated", success());
     eprintln!("  {} {}: {}", style("→").dim(), setting, value);
@@ -143,6 +154,264 @@ pub async fn cmd_config_set(settings: &Settings, setting: &str, value: &str) ->
     Ok(())
 }
 
+/// List saved versions of the scraper configs.
+pub async fn cmd_config_history(settings: &Settings) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let items = config_history::list(&repos.scraper_configs, &repos.config_history).await?;
+    if output::is_json() {
+        output::emit("result", serde_json::json!({ "history": items }));
+        return Ok(());
+    }
+    if items.is_empty() {
+        println!(
+            "{} No config history yet. Changes made with 'foia config' are recorded.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "
+{}",
+        style("Config history").bold()
+    );
+    println!("{}", "-".repeat(50));
+    for item in &items {
+        let sources = item
+            .sources
+            .map(|n| format!("{} sources", n))
+            .unwrap_or_else(|| "unreadable".to_string());
+        println!(
+            "{}  {}  {:<12} {}",
+            short_hash(&item.hash),
+            item.created_at.format("%Y-%m-%d %H:%M:%S"),
+            sources,
+            if item.current {
+                style("(current)").green().to_string()
+            } else {
+                String::new()
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Show what changed between two versions, or between one and the
+/// configs in use now.
+pub async fn cmd_config_diff(
+    settings: &Settings,
+    from: &str,
+    to: Option<&str>,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let old = config_history::parse(&config_history::find(&repos.config_history, from).await?)?;
+    let new = match to {
+        Some(to) => config_history::parse(&config_history::find(&repos.config_history, to).await?)?,
+        None => config_history::current(&repos.scraper_configs).await?,
+    };
+    let diff = ConfigDiff::between(&old, &new);
+    if output::is_json() {
+        output::emit("result", serde_json::to_value(&diff)?);
+        return Ok(());
+    }
+    print_diff(&diff);
+    Ok(())
+}
+
+/// Restore the scraper configs saved in a version.
+pub async fn cmd_config_rollback(settings: &Settings, hash: &str) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let rollback =
+        config_history::rollback(&repos.scraper_configs, &repos.config_history, hash).await?;
+    if output::is_json() {
+        output::emit("result", serde_json::to_value(&rollback)?);
+        return Ok(());
+    }
+    if rollback.diff.is_empty() {
+        println!(
+            "{} Scraper configs already match {}",
+            style("!").yellow(),
+            short_hash(&rollback.hash)
+        );
+        return Ok(());
+    }
+    eprintln!(
+        "{} Rolled back scraper configs to {}",
+        success(),
+        short_hash(&rollback.hash)
+    );
+    print_diff(&rollback.diff);
+    Ok(())
+}
+
+/// Replace a source's scraper config with JSON read from a file or stdin.
+pub async fn cmd_config_edit(
+    settings: &Settings,
+    source_id: &str,
+    file: Option<&Path>,
+) -> anyhow::Result<()> {
+    let text = match file {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?,
+        None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("Invalid JSON: {}", e))?;
+    let config = config_history::validate_scraper(source_id, value)?;
+
+    let repos = settings.repositories()?;
+    match config_history::save_scraper(
+        &repos.scraper_configs,
+        &repos.config_history,
+        source_id,
+        &config,
+    )
+    .await?
+    {
+        Some(hash) => eprintln!(
+            "{} Saved {} as config version {}",
+            success(),
+            source_id,
+            short_hash(&hash)
+        ),
+        None => eprintln!("{} {} is unchanged", style("!").yellow(), source_id),
+    }
+    Ok(())
+}
+
+fn print_diff(diff: &ConfigDiff) {
+    if diff.lines.is_empty() {
+        println!("No differences");
+        return;
+    }
+    for (label, ids) in [
+        ("Added", &diff.added),
+        ("Removed", &diff.removed),
+        ("Changed", &diff.changed),
+    ] {
+        if !ids.is_empty() {
+            println!("{}: {}", style(label).bold(), ids.join(", "));
+        }
+    }
+    println!();
+    for line in diff.lines.lines() {
+        if line.starts_with("@@") {
+            println!("{}", style(line).cyan());
+        } else if line.starts_with('+') {
+            println!("{}", style(line).green());
+        } else if line.starts_with('-') {
+            println!("{}", style(line).red());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Print the loaded config with the selected profile applied.
+///
+/// With `effective`, source settings stored in the database are laid over
+/// it as they are at runtime (under any `--set` and `FOIACQUIRE__`
+/// overrides), and the resolved paths, connections and LLM backend are
+/// added. Secrets are redacted.
+pub async fn cmd_config_show(
+    settings: &Settings,
+    config: &Config,
+    effective: bool,
+) -> anyhow::Result<()> {
+    let mut config = config.clone();
+    let profiles: Vec<String> = {
+        let mut names: Vec<String> = config.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    };
+    config.profiles.clear();
+
+    if effective {
+        let sources = if settings.is_postgres() {
+            let repos = settings.repositories()?;
+            Some(SourcesConfig {
+                scrapers: repos.scraper_configs.get_all().await?.into_iter().collect(),
+                ..SourcesConfig::default()
+            })
+        } else if settings.database_exists() {
+            FoiaConfigLoader::new(settings.database_path())
+                .load_snapshot()
+                .await
+        } else {
+            None
+        };
+        if let Some(sources) = sources {
+            config = config.with_sources(sources);
+        }
+    }
+
+    let mut value = serde_json::to_value(&config)?;
+    if effective {
+        let device = &config.llm.device;
+        value["resolved"] = serde_json::json!({
+            "profile": config.profile,
+            "profiles": profiles,
+            "overrides": config.overrides.iter().map(|o| o.origin()).collect::<Vec<_>>(),
+            "config_file": config.source_path,
+            "data_dir": settings.data_dir,
+            "documents_dir": settings.documents_dir,
+            "database": redact_url_password(&settings.database_url()),
+            "user_agent": settings.user_agent,
+            "request_timeout": settings.request_timeout,
+            "request_delay_ms": settings.request_delay_ms,
+            "rate_limit_backend": settings.rate_limit_backend.as_deref().map(redact_url_password),
+            "broker_url": settings.broker_url.as_deref().map(redact_url_password),
+            "read_only": settings.read_only,
+            "llm_backend": {
+                "provider": device.provider,
+                "endpoint": device.endpoint,
+                "model": device.model,
+                "api_key": device.api_key,
+                "max_concurrent": device.max_concurrent,
+                "requests_per_minute": device.requests_per_minute,
+            },
+        });
+    }
+    redact_secrets(&mut value);
+
+    if output::is_json() {
+        output::emit("result", value);
+        return Ok(());
+    }
+    if let Some(ref profile) = config.profile {
+        eprintln!("{} Profile: {}", style("→").dim(), profile);
+    }
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+/// Replace the values of secret-looking keys, leaving `secret://`
+/// references (which name a secret rather than hold it) readable.
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                let secret = ["token", "password", "api_key", "secret"]
+                    .iter()
+                    .any(|s| key.contains(s));
+                match value {
+                    serde_json::Value::String(s) if secret && !s.starts_with("secret://") => {
+                        *s = "********".to_string();
+                    }
+                    _ => redact_secrets(value),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
 /// Navigate a JSON value by dot-separated path.
 fn navigate_json<'a>(
     value: &'a serde_json::Value,
@@ -313,6 +582,20 @@ mod tests {
         assert_eq!(value, json!({"new": "data"}));
     }
 
+    #[test]
+    fn test_redact_secrets() {
+        let mut value = json!({
+            "sync": {"token": "abc123"},
+            "llm_backend": {"api_key": null, "model": "gpt-4o-mini"},
+            "scrapers": [{"password": "secret://portal"}],
+        });
+        redact_secrets(&mut value);
+        assert_eq!(value["sync"]["token"], json!("********"));
+        assert_eq!(value["llm_backend"]["api_key"], json!(null));
+        assert_eq!(value["llm_backend"]["model"], json!("gpt-4o-mini"));
+        assert_eq!(value["scrapers"][0]["password"], json!("secret://portal"));
+    }
+
     #[test]
     fn test_set_json_value_complex_object() {
         let mut value = json!({});
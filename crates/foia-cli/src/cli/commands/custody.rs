@@ -0,0 +1,106 @@
+//! Chain-of-custody certificate commands.
+
+use std::path::Path;
+
+use console::style;
+
+use foia::config::{Config, Settings};
+use foia::services::custody::{self, FixityStatus, SignedCertificate};
+
+/// Generate a signed chain-of-custody certificate for a document.
+pub async fn cmd_certify(
+    settings: &Settings,
+    config: &Config,
+    doc_id: &str,
+    format: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    if !matches!(format, "json" | "pdf") {
+        anyhow::bail!("Unknown format '{}' (expected json or pdf)", format);
+    }
+
+    let Some(key_path) = config.custody.signing_key.as_deref() else {
+        anyhow::bail!(
+            "No signing key configured. Set custody.signing_key to a file containing a hex-encoded ed25519 seed."
+        );
+    };
+    let base_dir = config
+        .base_dir()
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let key = custody::load_signing_key(&config.resolve_path(key_path, &base_dir))?;
+
+    let repos = settings.repositories()?;
+    let Some(certificate) = custody::build_certificate(
+        &repos.documents,
+        &repos.crawl,
+        &settings.documents_dir,
+        doc_id,
+        config.custody.signer.as_deref(),
+    )
+    .await?
+    else {
+        anyhow::bail!("Document not found: {}", doc_id);
+    };
+
+    for version in &certificate.versions {
+        if version.fixity.status != FixityStatus::Verified {
+            eprintln!(
+                "{} Version {} fixity check: {}",
+                style("!").yellow(),
+                version.id,
+                version.fixity.status.as_str()
+            );
+        }
+    }
+
+    let signed = certificate.sign(&key)?;
+    let bytes = match format {
+        "pdf" => signed.to_pdf(),
+        _ => {
+            let mut json = serde_json::to_vec_pretty(&signed)?;
+            json.push(b'\n');
+            json
+        }
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, &bytes)?;
+            eprintln!(
+                "{} Wrote certificate for {} to {}",
+                style("✓").green(),
+                doc_id,
+                path.display()
+            );
+        }
+        None => {
+            use std::io::Write;
+            std::io::stdout().write_all(&bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify the signature on a JSON chain-of-custody certificate.
+pub fn cmd_verify_certificate(file: &Path) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(file)?;
+    let signed: SignedCertificate = serde_json::from_str(&contents)?;
+
+    let cert = &signed.certificate;
+    println!("{:<18} {}", "Document:", cert.document.id);
+    println!("{:<18} {}", "Title:", cert.document.title);
+    println!("{:<18} {}", "Issued:", cert.issued_at);
+    if let Some(signer) = &cert.signer {
+        println!("{:<18} {}", "Issued by:", signer);
+    }
+    println!("{:<18} {}", "Public key:", signed.signature.public_key);
+
+    if signed.verify()? {
+        println!("{} Signature valid", style("✓").green());
+        Ok(())
+    } else {
+        println!("{} Signature INVALID", style("✗").red());
+        std::process::exit(1);
+    }
+}
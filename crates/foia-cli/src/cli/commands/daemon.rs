@@ -3,7 +3,10 @@
 use console::style;
 use tokio::sync::mpsc;
 
-use foia::repository::{DieselConfigHistoryRepository, DieselScraperConfigRepository};
+use foia::config::{Config, ConfigReloader};
+use foia::repository::{
+    DieselConfigHistoryRepository, DieselCrawlRepository, DieselScraperConfigRepository,
+};
 
 /// Reload mode for daemon operation.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
@@ -33,6 +36,7 @@ pub struct ConfigWatcher {
     config_history: DieselConfigHistoryRepository,
     scraper_configs: DieselScraperConfigRepository,
     current_hash: String,
+    reloader: ConfigReloader,
     reload: ReloadMode,
     daemon: bool,
 }
@@ -48,7 +52,7 @@ impl ConfigWatcher {
         reload: ReloadMode,
         config_history: DieselConfigHistoryRepository,
         scraper_configs: DieselScraperConfigRepository,
-        initial_hash: String,
+        config: Config,
     ) -> Self {
         let watcher = if daemon && matches!(reload, ReloadMode::StopProcess | ReloadMode::Inplace) {
             prefer::watch("foia").await.ok()
@@ -62,19 +66,74 @@ impl ConfigWatcher {
         let current_hash = if let Ok(Some(ts)) = scraper_configs.max_updated_at().await {
             ts
         } else {
-            initial_hash
+            config.hash()
         };
+        let reloader = ConfigReloader::new(config, config_history.clone()).await;
 
         Self {
             watcher,
             config_history,
             scraper_configs,
             current_hash,
+            reloader,
             reload,
             daemon,
         }
     }
 
+    /// Reload config at a run boundary if the config file or the
+    /// configuration history changed since the last check.
+    ///
+    /// Logs which scrapers the config change touched, and which configured
+    /// sources now differ from the config they were last crawled with.
+    /// Returns the new config, or `None` when nothing changed or this is not
+    /// a reloading daemon.
+    pub async fn reload_config(&mut self, crawl_repo: &DieselCrawlRepository) -> Option<Config> {
+        if !self.daemon || self.reload == ReloadMode::StopProcess {
+            return None;
+        }
+        let reloaded = self.reloader.reload().await?;
+
+        println!("{} Config reloaded", style("↻").cyan());
+        if !reloaded.scrapers.is_empty() {
+            println!(
+                "  {} Scrapers {}",
+                style("→").dim(),
+                reloaded.scrapers.summary()
+            );
+        }
+        let stale = self.sources_changed_since_crawl(crawl_repo).await;
+        if !stale.is_empty() {
+            println!(
+                "  {} Changed since last crawl: {}",
+                style("→").dim(),
+                stale.join(", ")
+            );
+        }
+
+        Some(reloaded.config)
+    }
+
+    /// Configured sources whose scraper config hash differs from the one
+    /// stored with their crawl state.
+    async fn sources_changed_since_crawl(&self, crawl_repo: &DieselCrawlRepository) -> Vec<String> {
+        let Ok(configs) = self.scraper_configs.get_all().await else {
+            return Vec::new();
+        };
+        let mut changed = Vec::new();
+        for (source_id, config) in configs {
+            if crawl_repo
+                .check_config_changed(&source_id, &config.config_hash())
+                .await
+                .unwrap_or(false)
+            {
+                changed.push(source_id);
+            }
+        }
+        changed.sort();
+        changed
+    }
+
     /// Update the stored config hash (used when the caller reloads config at
     /// the top of its loop).
     pub fn update_hash(&mut self, hash: String) {
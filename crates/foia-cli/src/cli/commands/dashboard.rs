@@ -0,0 +1,435 @@
+//! Operator dashboard: live crawl state with controls.
+//!
+//! Shows per-source crawl progress, the download, OCR and LLM backlogs with
+//! their throughput and estimated completion, recent fetch errors and
+//! persisted rate-limiter state, refreshed on an interval. Sources can be
+//! paused and resumed, and failed URLs requeued, without leaving the
+//! terminal.
+
+use std::io::{stdout, Stdout};
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::ExecutableCommand;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+
+use foia::config::Settings;
+use foia::models::CrawlUrl;
+use foia::rate_limit::{DieselRateLimitBackend, DomainRateState};
+use foia::repository::Repositories;
+use foia::services::pipeline_eta::{self, StageEta};
+
+use super::helpers::{format_eta, format_number, truncate};
+
+/// Fetch errors listed.
+const RECENT_ERRORS: u32 = 20;
+
+/// One row of the sources table.
+struct SourceRow {
+    id: String,
+    paused: bool,
+    discovered: u64,
+    pending: u64,
+    fetched: u64,
+    failed: u64,
+    last_scraped: Option<String>,
+}
+
+/// Everything the dashboard shows, read in one go.
+struct DashboardData {
+    sources: Vec<SourceRow>,
+    /// Download, OCR and LLM backlogs with recent throughput.
+    stages: Vec<StageEta>,
+    errors: Vec<CrawlUrl>,
+    rate_limits: Vec<DomainRateState>,
+    last_updated: String,
+}
+
+/// Dashboard state between frames.
+struct Dashboard {
+    data: DashboardData,
+    table: TableState,
+    /// Outcome of the last action, shown in the footer.
+    message: Option<String>,
+}
+
+impl Dashboard {
+    fn selected_source(&self) -> Option<&SourceRow> {
+        self.table.selected().and_then(|i| self.data.sources.get(i))
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.data.sources.len();
+        if len == 0 {
+            self.table.select(None);
+            return;
+        }
+        let current = self.table.selected().unwrap_or(0) as isize;
+        let next = (current + offset).clamp(0, len as isize - 1);
+        self.table.select(Some(next as usize));
+    }
+}
+
+/// Run the operator dashboard until the user quits.
+pub async fn cmd_tui(settings: &Settings, interval: u64) -> anyhow::Result<()> {
+    if !settings.database_exists() {
+        anyhow::bail!("System not initialized. Run 'foia init' first.");
+    }
+    let repos = settings.repositories()?;
+    let rate_limits = DieselRateLimitBackend::new(repos.pool().clone(), settings.request_delay_ms);
+
+    let data = fetch_dashboard_data(&repos, &rate_limits).await?;
+    let mut dashboard = Dashboard {
+        data,
+        table: TableState::default(),
+        message: None,
+    };
+    dashboard.select(0);
+
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let result = run_loop(
+        &mut terminal,
+        &repos,
+        &rate_limits,
+        &mut dashboard,
+        interval,
+    )
+    .await;
+
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+
+    result
+}
+
+/// Main event loop: draw, wait for a key or the refresh interval, act.
+async fn run_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    repos: &Repositories,
+    rate_limits: &DieselRateLimitBackend,
+    dashboard: &mut Dashboard,
+    interval: u64,
+) -> anyhow::Result<()> {
+    let refresh_duration = Duration::from_secs(interval.max(1));
+    let poll_duration = Duration::from_millis(100);
+
+    loop {
+        terminal.draw(|frame| draw(frame, dashboard))?;
+
+        let deadline = tokio::time::Instant::now() + refresh_duration;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            if !event::poll(remaining.min(poll_duration))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(())
+                }
+                KeyCode::Up | KeyCode::Char('k') => dashboard.select(-1),
+                KeyCode::Down | KeyCode::Char('j') => dashboard.select(1),
+                KeyCode::Char('p') => {
+                    dashboard.message = Some(toggle_pause(repos, dashboard).await);
+                    break;
+                }
+                KeyCode::Char('f') => {
+                    let source_id = dashboard.selected_source().map(|s| s.id.clone());
+                    dashboard.message = Some(requeue(repos, source_id.as_deref()).await);
+                    break;
+                }
+                KeyCode::Char('F') => {
+                    dashboard.message = Some(requeue(repos, None).await);
+                    break;
+                }
+                KeyCode::Char('r') => break,
+                _ => continue,
+            }
+            terminal.draw(|frame| draw(frame, dashboard))?;
+        }
+
+        // Keep the old data on screen if a refresh fails
+        match fetch_dashboard_data(repos, rate_limits).await {
+            Ok(data) => {
+                dashboard.data = data;
+                let selected = dashboard.table.selected().unwrap_or(0) as isize;
+                dashboard.table.select(None);
+                dashboard.select(selected);
+            }
+            Err(e) => dashboard.message = Some(format!("Refresh failed: {}", e)),
+        }
+    }
+}
+
+/// Pause the selected source, or resume it if paused.
+async fn toggle_pause(repos: &Repositories, dashboard: &Dashboard) -> String {
+    let Some(source) = dashboard.selected_source() else {
+        return "No source selected".to_string();
+    };
+    let pause = !source.paused;
+    match repos.sources.set_paused(&source.id, pause).await {
+        Ok(true) if pause => format!("Paused {}", source.id),
+        Ok(true) => format!("Resumed {}", source.id),
+        Ok(false) => format!("{} is not a registered source", source.id),
+        Err(e) => format!("Failed to update {}: {}", source.id, e),
+    }
+}
+
+/// Put failed URLs of one source, or of all sources, back in the queue.
+async fn requeue(repos: &Repositories, source_id: Option<&str>) -> String {
+    let scope = source_id.unwrap_or("all sources");
+    match repos.crawl.reset_failed_urls(source_id).await {
+        Ok(count) => format!("Requeued {} failed URL(s) of {}", count, scope),
+        Err(e) => format!("Failed to requeue {}: {}", scope, e),
+    }
+}
+
+async fn fetch_dashboard_data(
+    repos: &Repositories,
+    rate_limits: &DieselRateLimitBackend,
+) -> anyhow::Result<DashboardData> {
+    let stats = repos.crawl.get_all_stats().await?;
+    let mut sources: Vec<SourceRow> = repos
+        .sources
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|source| {
+            let crawl = stats.get(&source.id);
+            SourceRow {
+                paused: source.paused,
+                discovered: crawl.map(|s| s.urls_discovered).unwrap_or(0),
+                pending: crawl.map(|s| s.urls_pending).unwrap_or(0),
+                fetched: crawl.map(|s| s.urls_fetched).unwrap_or(0),
+                failed: crawl.map(|s| s.urls_failed).unwrap_or(0),
+                last_scraped: source
+                    .last_scraped
+                    .map(|t| t.with_timezone(&Local).format("%m-%d %H:%M").to_string()),
+                id: source.id,
+            }
+        })
+        .collect();
+    sources.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(DashboardData {
+        sources,
+        stages: pipeline_eta::estimate(
+            &repos.documents,
+            &repos.crawl,
+            None,
+            pipeline_eta::DEFAULT_WINDOW,
+        )
+        .await?,
+        errors: repos.crawl.get_failed_urls(None, RECENT_ERRORS).await?,
+        // Only the database rate-limit backend persists its state
+        rate_limits: rate_limits.list_states().await.unwrap_or_default(),
+        last_updated: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    })
+}
+
+fn section(title: &str) -> Block<'_> {
+    Block::default()
+        .title(format!(" {} ", title))
+        .title_style(Style::default().fg(Color::Cyan).bold())
+        .borders(Borders::TOP)
+}
+
+fn draw(frame: &mut Frame, dashboard: &mut Dashboard) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),      // Header
+            Constraint::Length(4),      // Queues
+            Constraint::Percentage(45), // Sources
+            Constraint::Min(6),         // Errors and rate limits
+            Constraint::Length(1),      // Footer
+        ])
+        .split(frame.area());
+
+    let header = Paragraph::new(format!(
+        "foia dashboard                                       Last updated: {}",
+        dashboard.data.last_updated
+    ))
+    .style(Style::default().bold())
+    .block(Block::default().borders(Borders::BOTTOM));
+    frame.render_widget(header, rows[0]);
+
+    let data = &dashboard.data;
+    let paused = data.sources.iter().filter(|s| s.paused).count();
+    let lines: Vec<String> = data
+        .stages
+        .iter()
+        .enumerate()
+        .map(|(i, stage)| {
+            let line = format!(
+                "  {:<10} {:>10} pending {:>9.1}/h   ETA {:<10}",
+                stage.stage.label(),
+                format_number(stage.pending),
+                stage.per_hour,
+                format_eta(stage.eta_secs),
+            );
+            if i == 0 {
+                format!("{}  Paused sources: {}", line, paused)
+            } else {
+                line
+            }
+        })
+        .collect();
+    let queues = Paragraph::new(lines.join("\n")).block(section("QUEUES"));
+    frame.render_widget(queues, rows[1]);
+
+    draw_sources(frame, rows[2], dashboard);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[3]);
+    draw_errors(frame, bottom[0], &dashboard.data.errors);
+    draw_rate_limits(frame, bottom[1], &dashboard.data.rate_limits);
+
+    let footer_text = match &dashboard.message {
+        Some(message) => message.clone(),
+        None => {
+            "↑/↓ select  p pause/resume  f requeue failed  F requeue all failed  r refresh  q quit"
+                .to_string()
+        }
+    };
+    let footer = Paragraph::new(footer_text).style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(footer, rows[4]);
+}
+
+fn draw_sources(frame: &mut Frame, area: Rect, dashboard: &mut Dashboard) {
+    let header = Row::new(
+        [
+            "Source",
+            "State",
+            "Discovered",
+            "Pending",
+            "Fetched",
+            "Failed",
+            "Last run",
+        ]
+        .iter()
+        .map(|h| Cell::from(*h).style(Style::default().bold())),
+    );
+    let rows = dashboard.data.sources.iter().map(|s| {
+        let state = if s.paused {
+            Cell::from("paused").style(Style::default().fg(Color::Red))
+        } else {
+            Cell::from("active").style(Style::default().fg(Color::Green))
+        };
+        Row::new([
+            Cell::from(truncate(&s.id, 24)),
+            state,
+            Cell::from(format_number(s.discovered)),
+            Cell::from(format_number(s.pending)).style(if s.pending > 0 {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            }),
+            Cell::from(format_number(s.fetched)),
+            Cell::from(format_number(s.failed)).style(if s.failed > 0 {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            }),
+            Cell::from(s.last_scraped.clone().unwrap_or_else(|| "-".to_string())),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(26),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(Style::default().reversed())
+    .block(section("SOURCES"));
+    frame.render_stateful_widget(table, area, &mut dashboard.table);
+}
+
+fn draw_errors(frame: &mut Frame, area: Rect, errors: &[CrawlUrl]) {
+    let now = Utc::now();
+    let rows = errors.iter().map(|url| {
+        let age = url
+            .fetched_at
+            .map(|t| now - t)
+            .map(|age| match age.num_minutes() {
+                m if m < 60 => format!("{}m", m),
+                m if m < 60 * 48 => format!("{}h", m / 60),
+                m => format!("{}d", m / 60 / 24),
+            })
+            .unwrap_or_else(|| "-".to_string());
+        Row::new([
+            Cell::from(age),
+            Cell::from(truncate(&url.source_id, 16)),
+            Cell::from(url.last_error.clone().unwrap_or_default()),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Length(17),
+            Constraint::Min(20),
+        ],
+    )
+    .block(section("RECENT ERRORS"));
+    frame.render_widget(table, area);
+}
+
+fn draw_rate_limits(frame: &mut Frame, area: Rect, states: &[DomainRateState]) {
+    let header = Row::new(
+        ["Domain", "Delay", "Hits", "Requests"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().bold())),
+    );
+    let rows = states.iter().map(|s| {
+        let delay = Cell::from(format!("{}ms", s.current_delay_ms));
+        Row::new([
+            Cell::from(truncate(&s.domain, 24)),
+            if s.in_backoff {
+                delay.style(Style::default().fg(Color::Red))
+            } else {
+                delay
+            },
+            Cell::from(format_number(s.rate_limit_hits)),
+            Cell::from(format_number(s.total_requests)),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(9),
+            Constraint::Length(6),
+            Constraint::Length(9),
+        ],
+    )
+    .header(header)
+    .block(section("RATE LIMITS"));
+    frame.render_widget(table, area);
+}
@@ -0,0 +1,153 @@
+//! Query plan and index diagnostics command.
+
+use std::collections::BTreeMap;
+
+use console::style;
+
+use foia::config::Settings;
+use foia::repository::diagnostics::{self, IndexSpec, QueryPlan, RelationSize};
+
+use super::super::helpers::format_bytes;
+
+/// Number of tables listed in the size report.
+const TOP_TABLES: usize = 15;
+
+/// Explain the hot repository queries, report full scans and table sizes,
+/// and with `apply` create the indexes the scanning queries need.
+pub async fn cmd_db_analyze(settings: &Settings, apply: bool) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let pool = repos.pool();
+    let backend = if pool.is_sqlite() {
+        "SQLite"
+    } else {
+        "PostgreSQL"
+    };
+
+    println!(
+        "{} Query plans for hot queries ({})",
+        style("→").cyan(),
+        backend
+    );
+    let plans = diagnostics::explain_hot_queries(pool).await?;
+    print_plans(&plans);
+
+    let mut missing: Vec<&IndexSpec> = Vec::new();
+    for plan in plans.iter().filter(|p| p.is_full_scan()) {
+        if !missing.contains(&&plan.query.index) {
+            missing.push(&plan.query.index);
+        }
+    }
+
+    if !missing.is_empty() {
+        println!();
+        if apply {
+            println!("{} Creating recommended indexes", style("→").cyan());
+            for index in &missing {
+                diagnostics::create_index(pool, index).await?;
+                println!("  {} {}", style("✓").green(), index.create_sql());
+            }
+
+            println!();
+            println!("{} Query plans after indexing", style("→").cyan());
+            let plans = diagnostics::explain_hot_queries(pool).await?;
+            print_plans(&plans);
+            if plans.iter().any(|p| p.is_full_scan()) && !pool.is_sqlite() {
+                println!(
+                    "  PostgreSQL may still prefer sequential scans on small tables; that is expected."
+                );
+            }
+        } else {
+            println!("{} Recommended indexes:", style("!").yellow());
+            for index in &missing {
+                println!("  {};", index.create_sql());
+            }
+            println!("  Re-run with --apply to create them.");
+        }
+    }
+
+    println!();
+    println!("{} Table sizes", style("→").cyan());
+    let sizes = diagnostics::relation_sizes(pool).await?;
+    print_sizes(&sizes);
+
+    Ok(())
+}
+
+fn print_plans(plans: &[QueryPlan]) {
+    let width = plans.iter().map(|p| p.query.name.len()).max().unwrap_or(0);
+    for plan in plans {
+        if plan.is_full_scan() {
+            println!(
+                "  {} {:<width$}  full scan of {}",
+                style("✗").red(),
+                plan.query.name,
+                plan.full_scans.join(", "),
+            );
+            for line in &plan.lines {
+                println!("      {}", style(line).dim());
+            }
+        } else {
+            let summary = plan.lines.first().map(|l| l.trim()).unwrap_or("");
+            println!(
+                "  {} {:<width$}  {}",
+                style("✓").green(),
+                plan.query.name,
+                style(summary).dim(),
+            );
+        }
+    }
+
+    let scans = plans.iter().filter(|p| p.is_full_scan()).count();
+    if scans == 0 {
+        println!("  All {} queries use indexes.", plans.len());
+    } else {
+        println!("  {} of {} queries scan whole tables.", scans, plans.len());
+    }
+}
+
+fn print_sizes(sizes: &[RelationSize]) {
+    // Index sizes roll up into their table's line
+    let mut index_bytes: BTreeMap<&str, (u64, usize)> = BTreeMap::new();
+    for index in sizes.iter().filter(|s| s.is_index) {
+        let entry = index_bytes.entry(index.table.as_str()).or_default();
+        entry.0 += index.bytes.unwrap_or(0);
+        entry.1 += 1;
+    }
+
+    let mut tables: Vec<&RelationSize> = sizes.iter().filter(|s| !s.is_index).collect();
+    tables.sort_by_key(|t| std::cmp::Reverse((t.bytes, t.rows)));
+
+    let has_bytes = tables.iter().any(|t| t.bytes.is_some());
+    println!(
+        "  {:<28} {:>12} {:>10} {:>10} {:>8}",
+        "Table", "Rows", "Data", "Indexes", "#Idx"
+    );
+    for table in tables.iter().take(TOP_TABLES) {
+        let (idx_bytes, idx_count) = index_bytes
+            .get(table.name.as_str())
+            .copied()
+            .unwrap_or_default();
+        let size = |bytes: Option<u64>| bytes.map(format_bytes).unwrap_or_else(|| "-".into());
+        println!(
+            "  {:<28} {:>12} {:>10} {:>10} {:>8}",
+            table.name,
+            table
+                .rows
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "-".into()),
+            size(table.bytes),
+            size(has_bytes.then_some(idx_bytes)),
+            idx_count,
+        );
+    }
+    if tables.len() > TOP_TABLES {
+        println!("  ... and {} smaller tables", tables.len() - TOP_TABLES);
+    }
+
+    if has_bytes {
+        let total: u64 = sizes.iter().filter_map(|s| s.bytes).sum();
+        println!("  Total: {}", format_bytes(total));
+    } else {
+        println!("  (this SQLite build has no dbstat table, so sizes are unavailable)");
+    }
+}
@@ -0,0 +1,95 @@
+//! Page text compression command.
+
+use std::time::Duration;
+
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+
+use foia::config::Settings;
+
+use super::super::helpers::format_bytes;
+
+/// Compress stored page text written before compression was enabled.
+///
+/// Only `pdf_text` and `ocr_text` on pages that already have `final_text`
+/// are compressed; `final_text` stays plain for search.
+pub async fn cmd_db_compress_text(
+    settings: &Settings,
+    dry_run: bool,
+    batch_size: usize,
+) -> anyhow::Result<()> {
+    println!(
+        "{} Compressing stored page text{}",
+        style("→").cyan(),
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let doc_repo = settings.repositories()?.documents;
+
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("  {spinner:.cyan} {pos} pages scanned ({per_sec}) {msg}")
+            .unwrap(),
+    );
+    pb.enable_steady_tick(Duration::from_millis(100));
+
+    let mut after_id = 0;
+    let mut compressed = 0u64;
+    let mut bytes_before = 0u64;
+    let mut bytes_after = 0u64;
+    loop {
+        let batch = doc_repo
+            .compress_page_text_batch(after_id, batch_size, dry_run)
+            .await?;
+        let Some(last_id) = batch.last_id else {
+            break;
+        };
+        after_id = last_id;
+        compressed += batch.pages_compressed;
+        bytes_before += batch.bytes_before;
+        bytes_after += batch.bytes_after;
+
+        pb.inc(batch.pages_scanned);
+        pb.set_message(format!(
+            "{} compressed, {} saved",
+            compressed,
+            format_bytes(bytes_before - bytes_after)
+        ));
+    }
+    pb.finish_and_clear();
+
+    let saved = bytes_before - bytes_after;
+    let ratio = if bytes_before > 0 {
+        bytes_after as f64 / bytes_before as f64 * 100.0
+    } else {
+        100.0
+    };
+    println!(
+        "  {} -> {} ({:.0}% of original)",
+        format_bytes(bytes_before),
+        format_bytes(bytes_after),
+        ratio
+    );
+
+    if dry_run {
+        println!(
+            "\n{} Dry run complete. {} pages would be compressed, saving {}.",
+            style("✓").green(),
+            compressed,
+            format_bytes(saved)
+        );
+    } else {
+        println!(
+            "\n{} Compressed {} pages, saving {}.",
+            style("✓").green(),
+            compressed,
+            format_bytes(saved)
+        );
+        if saved > 0 {
+            println!("  Run VACUUM (SQLite) or VACUUM FULL (PostgreSQL) to return the space to the filesystem.");
+        }
+    }
+
+    Ok(())
+}
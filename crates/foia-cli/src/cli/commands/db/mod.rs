@@ -1,11 +1,17 @@
 //! Database management commands.
 
+mod analyze;
+mod compress;
 mod copy;
 mod dedup;
 mod migrate;
 mod remap;
+mod stats;
 
+pub use analyze::cmd_db_analyze;
+pub use compress::cmd_db_compress_text;
 pub use copy::cmd_db_copy;
 pub use dedup::cmd_db_dedup;
 pub use migrate::cmd_migrate;
 pub use remap::cmd_db_remap_categories;
+pub use stats::cmd_db_refresh_stats;
@@ -0,0 +1,31 @@
+//! Statistics table maintenance.
+
+use console::style;
+
+use foia::config::Settings;
+
+/// Recompute the trigger-maintained statistics tables.
+///
+/// Only needed after bulk changes that bypass row triggers, such as
+/// `TRUNCATE` or a restore from a dump taken without them.
+pub async fn cmd_db_refresh_stats(settings: &Settings) -> anyhow::Result<()> {
+    println!("{} Rebuilding statistics tables...", style("→").cyan());
+
+    let doc_repo = settings.repositories()?.documents;
+    doc_repo.rebuild_stats().await?;
+
+    let total = doc_repo.count().await?;
+    let sources = doc_repo.get_all_source_counts().await?.len();
+    let tags = doc_repo.get_tag_counts().await?.len();
+    let types = doc_repo.get_type_stats().await?.len();
+    println!(
+        "{} {} documents across {} sources, {} tags, {} MIME types",
+        style("✓").green(),
+        total,
+        sources,
+        tags,
+        types
+    );
+
+    Ok(())
+}
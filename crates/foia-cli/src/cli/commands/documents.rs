@@ -3,19 +3,25 @@
 use std::path::Path;
 
 use console::style;
+use futures::{StreamExt, TryStreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
 
-use foia::config::Settings;
-use foia::models::Document;
+use foia::config::{Config, Settings};
+use foia::llm::LlmClient;
+use foia::models::{Document, DocumentStatus};
+use foia::repository::diesel_document::{Projection, StreamFilter, DEFAULT_STREAM_BATCH};
 use foia::repository::DieselDocumentRepository;
+use foia::services::bundles::{split_bundle, BundleSplitter, Signal, SplitOptions};
 
 use super::helpers::{format_bytes, mime_short, truncate};
+use crate::cli::output;
 
 /// Statistics from processing containers.
 struct ProcessingStats {
     containers_processed: usize,
     files_discovered: usize,
     files_extracted: usize,
+    relations_added: usize,
 }
 
 impl ProcessingStats {
@@ -24,68 +30,173 @@ impl ProcessingStats {
             containers_processed: 0,
             files_discovered: 0,
             files_extracted: 0,
+            relations_added: 0,
         }
     }
+
+    fn add(&mut self, container: ContainerStats) {
+        self.files_discovered += container.discovered;
+        self.files_extracted += container.extracted;
+        self.relations_added += container.linked;
+        self.containers_processed += 1;
+    }
 }
 
-/// Extract and optionally OCR a virtual file from an archive or email.
-fn extract_and_ocr_from_archive(
-    file_path: &Path,
-    entry_path: &str,
-    entry_mime: &str,
+/// Counts from expanding one container.
+#[derive(Default)]
+struct ContainerStats {
+    discovered: usize,
+    extracted: usize,
+    linked: usize,
+}
+
+/// A member pulled out of an archive or email.
+struct ExtractedMember {
+    text: Option<String>,
+    status: foia::models::VirtualFileStatus,
+    /// SHA-256 of the member's bytes, for matching documents already archived.
+    content_hash: Option<String>,
+}
+
+impl ExtractedMember {
+    fn failed() -> Self {
+        Self {
+            text: None,
+            status: foia::models::VirtualFileStatus::Failed,
+            content_hash: None,
+        }
+    }
+}
+
+/// Hash an extracted member and optionally OCR it.
+fn read_member(
+    extracted_path: &Path,
+    name: &str,
+    mime_type: &str,
+    extractable: bool,
     run_ocr: bool,
     text_extractor: &foia_analysis::ocr::TextExtractor,
-) -> (Option<String>, foia::models::VirtualFileStatus) {
-    use foia::models::VirtualFileStatus;
-    use foia_analysis::ocr::ArchiveExtractor;
+) -> ExtractedMember {
+    use foia::models::{DocumentVersion, VirtualFileStatus};
 
-    if !run_ocr {
-        return (None, VirtualFileStatus::Pending);
-    }
+    let content_hash = std::fs::read(extracted_path)
+        .map(|bytes| DocumentVersion::compute_hash(&bytes))
+        .ok();
 
-    match ArchiveExtractor::extract_file(file_path, entry_path) {
-        Ok(extracted) => match text_extractor.extract(&extracted.file_path, entry_mime) {
+    let (text, status) = if !extractable {
+        (None, VirtualFileStatus::Unsupported)
+    } else if !run_ocr {
+        (None, VirtualFileStatus::Pending)
+    } else {
+        match text_extractor.extract(extracted_path, mime_type) {
             Ok(result) => (Some(result.text), VirtualFileStatus::OcrComplete),
             Err(e) => {
-                tracing::debug!("OCR failed for {}: {}", entry_path, e);
+                tracing::debug!("OCR failed for {}: {}", name, e);
                 (None, VirtualFileStatus::Failed)
             }
-        },
+        }
+    };
+
+    ExtractedMember {
+        text,
+        status,
+        content_hash,
+    }
+}
+
+/// Extract a file from an archive, hash it, and optionally OCR it.
+fn extract_from_archive(
+    file_path: &Path,
+    entry: &foia_analysis::ocr::ArchiveEntry,
+    run_ocr: bool,
+    text_extractor: &foia_analysis::ocr::TextExtractor,
+) -> ExtractedMember {
+    use foia_analysis::ocr::ArchiveExtractor;
+
+    match ArchiveExtractor::extract_file(file_path, &entry.path) {
+        Ok(extracted) => read_member(
+            &extracted.file_path,
+            &entry.path,
+            &entry.mime_type,
+            entry.is_extractable(),
+            run_ocr,
+            text_extractor,
+        ),
         Err(e) => {
-            tracing::debug!("Failed to extract {}: {}", entry_path, e);
-            (None, VirtualFileStatus::Failed)
+            tracing::debug!("Failed to extract {}: {}", entry.path, e);
+            ExtractedMember::failed()
         }
     }
 }
 
-/// Extract and optionally OCR an email attachment.
-fn extract_and_ocr_from_email(
+/// Extract an email attachment, hash it, and optionally OCR it.
+fn extract_from_email(
     file_path: &Path,
-    attachment_name: &str,
-    attachment_mime: &str,
+    attachment: &foia_analysis::ocr::EmailAttachment,
     run_ocr: bool,
     text_extractor: &foia_analysis::ocr::TextExtractor,
-) -> (Option<String>, foia::models::VirtualFileStatus) {
-    use foia::models::VirtualFileStatus;
+) -> ExtractedMember {
     use foia_analysis::ocr::EmailExtractor;
 
-    if !run_ocr {
-        return (None, VirtualFileStatus::Pending);
+    match EmailExtractor::extract_attachment(file_path, &attachment.filename) {
+        Ok(extracted) => read_member(
+            &extracted.file_path,
+            &attachment.filename,
+            &attachment.mime_type,
+            attachment.is_extractable(),
+            run_ocr,
+            text_extractor,
+        ),
+        Err(e) => {
+            tracing::debug!("Failed to extract {}: {}", attachment.filename, e);
+            ExtractedMember::failed()
+        }
     }
+}
 
-    match EmailExtractor::extract_attachment(file_path, attachment_name) {
-        Ok(extracted) => match text_extractor.extract(&extracted.file_path, attachment_mime) {
-            Ok(result) => (Some(result.text), VirtualFileStatus::OcrComplete),
-            Err(e) => {
-                tracing::debug!("OCR failed for {}: {}", attachment_name, e);
-                (None, VirtualFileStatus::Failed)
-            }
-        },
+/// Record an `attachment-of` edge from every archived document whose content
+/// matches a member to the container it was found in. Returns edges added.
+async fn link_existing_copies(
+    doc_repo: &DieselDocumentRepository,
+    container: &Document,
+    virtual_file_id: &str,
+    content_hash: Option<&str>,
+) -> usize {
+    use foia::models::RelationType;
+    use foia::repository::models::NewDocumentRelation;
+
+    let Some(hash) = content_hash else {
+        return 0;
+    };
+    let matches = match doc_repo.find_sources_by_hash(hash, None).await {
+        Ok(m) => m,
         Err(e) => {
-            tracing::debug!("Failed to extract {}: {}", attachment_name, e);
-            (None, VirtualFileStatus::Failed)
+            tracing::debug!("Hash lookup failed for {}: {}", virtual_file_id, e);
+            return 0;
+        }
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut linked = 0;
+    for (_, document_id, _) in matches {
+        if document_id == container.id {
+            continue;
+        }
+        let relation = NewDocumentRelation {
+            document_id: &document_id,
+            related_document_id: &container.id,
+            relation_type: RelationType::AttachmentOf.as_str(),
+            virtual_file_id: Some(virtual_file_id),
+            note: None,
+            created_at: &now,
+        };
+        match doc_repo.add_relation(&relation).await {
+            Ok(true) => linked += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to link {} to {}: {}", document_id, container.id, e),
         }
     }
+    linked
 }
 
 /// Process a single archive document.
@@ -95,13 +206,14 @@ async fn process_archive(
     run_ocr: bool,
     text_extractor: &foia_analysis::ocr::TextExtractor,
     documents_dir: &Path,
-) -> Option<(usize, usize)> {
-    use foia::models::{VirtualFile, VirtualFileStatus};
+) -> Option<ContainerStats> {
+    use foia::models::VirtualFile;
     use foia_analysis::ocr::ArchiveExtractor;
 
     let version = doc.current_version()?;
     let version_id = doc_repo.get_current_version_id(&doc.id).await.ok()??;
-    let file_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let stored_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let file_path = foia::storage::plaintext_path(&stored_path).ok()?;
 
     let entries = match ArchiveExtractor::list_zip_contents(&file_path) {
         Ok(e) => e,
@@ -111,25 +223,16 @@ async fn process_archive(
         }
     };
 
-    let files_discovered = entries.len();
-    let mut files_extracted = 0;
+    let mut stats = ContainerStats {
+        discovered: entries.len(),
+        ..Default::default()
+    };
 
     for entry in entries {
-        let (text, status) = if entry.is_extractable() {
-            let result = extract_and_ocr_from_archive(
-                &file_path,
-                &entry.path,
-                &entry.mime_type,
-                run_ocr,
-                text_extractor,
-            );
-            if result.0.is_some() {
-                files_extracted += 1;
-            }
-            result
-        } else {
-            (None, VirtualFileStatus::Unsupported)
-        };
+        let member = extract_from_archive(&file_path, &entry, run_ocr, text_extractor);
+        if member.text.is_some() {
+            stats.extracted += 1;
+        }
 
         let mut vf = VirtualFile::new(
             doc.id.clone(),
@@ -139,15 +242,18 @@ async fn process_archive(
             entry.mime_type.clone(),
             entry.size,
         );
-        vf.extracted_text = text;
-        vf.status = status;
+        vf.extracted_text = member.text;
+        vf.status = member.status;
 
         if let Err(e) = doc_repo.insert_virtual_file(&vf).await {
             tracing::warn!("Failed to save virtual file {}: {}", entry.path, e);
+            continue;
         }
+        stats.linked +=
+            link_existing_copies(doc_repo, doc, &vf.id, member.content_hash.as_deref()).await;
     }
 
-    Some((files_discovered, files_extracted))
+    Some(stats)
 }
 
 /// Process a single email document.
@@ -157,13 +263,14 @@ async fn process_email(
     run_ocr: bool,
     text_extractor: &foia_analysis::ocr::TextExtractor,
     documents_dir: &Path,
-) -> Option<(usize, usize)> {
-    use foia::models::{VirtualFile, VirtualFileStatus};
+) -> Option<ContainerStats> {
+    use foia::models::{VirtualFile, VirtualFileStatus, EMAIL_BODY_PLACEHOLDER};
     use foia_analysis::ocr::EmailExtractor;
 
     let version = doc.current_version()?;
     let version_id = doc_repo.get_current_version_id(&doc.id).await.ok()??;
-    let file_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let stored_path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let file_path = foia::storage::plaintext_path(&stored_path).ok()?;
 
     let parsed = match EmailExtractor::parse_email(&file_path) {
         Ok(p) => p,
@@ -173,25 +280,16 @@ async fn process_email(
         }
     };
 
-    let files_discovered = parsed.attachments.len();
-    let mut files_extracted = 0;
+    let mut stats = ContainerStats {
+        discovered: parsed.attachments.len(),
+        ..Default::default()
+    };
 
     for attachment in &parsed.attachments {
-        let (text, status) = if attachment.is_extractable() {
-            let result = extract_and_ocr_from_email(
-                &file_path,
-                &attachment.filename,
-                &attachment.mime_type,
-                run_ocr,
-                text_extractor,
-            );
-            if result.0.is_some() {
-                files_extracted += 1;
-            }
-            result
-        } else {
-            (None, VirtualFileStatus::Unsupported)
-        };
+        let member = extract_from_email(&file_path, attachment, run_ocr, text_extractor);
+        if member.text.is_some() {
+            stats.extracted += 1;
+        }
 
         let mut vf = VirtualFile::new(
             doc.id.clone(),
@@ -201,21 +299,24 @@ async fn process_email(
             attachment.mime_type.clone(),
             attachment.size,
         );
-        vf.extracted_text = text;
-        vf.status = status;
+        vf.extracted_text = member.text;
+        vf.status = member.status;
 
         if let Err(e) = doc_repo.insert_virtual_file(&vf).await {
             tracing::warn!("Failed to save virtual file {}: {}", attachment.filename, e);
+            continue;
         }
+        stats.linked +=
+            link_existing_copies(doc_repo, doc, &vf.id, member.content_hash.as_deref()).await;
     }
 
     // Mark emails with no attachments as processed
     if parsed.attachments.is_empty() {
-        let placeholder = VirtualFile::new(
+        let mut placeholder = VirtualFile::new(
             doc.id.clone(),
             version_id,
-            "_email_body".to_string(),
-            "_email_body".to_string(),
+            EMAIL_BODY_PLACEHOLDER.to_string(),
+            EMAIL_BODY_PLACEHOLDER.to_string(),
             "text/plain".to_string(),
             parsed
                 .body_text
@@ -223,10 +324,12 @@ async fn process_email(
                 .map(|s| s.len() as u64)
                 .unwrap_or(0),
         );
+        // Nothing to extract, so keep it out of the text extraction queue
+        placeholder.status = VirtualFileStatus::Unsupported;
         let _ = doc_repo.insert_virtual_file(&placeholder).await;
     }
 
-    Some((files_discovered, files_extracted))
+    Some(stats)
 }
 
 /// Process archive/email containers.
@@ -283,7 +386,7 @@ pub async fn cmd_archive(
             .await?
         {
             pb.set_message(truncate(&doc.title, 40));
-            if let Some((discovered, extracted)) = process_archive(
+            if let Some(container) = process_archive(
                 &doc,
                 &doc_repo,
                 run_ocr,
@@ -292,9 +395,7 @@ pub async fn cmd_archive(
             )
             .await
             {
-                stats.files_discovered += discovered;
-                stats.files_extracted += extracted;
-                stats.containers_processed += 1;
+                stats.add(container);
             }
             pb.inc(1);
         }
@@ -308,7 +409,7 @@ pub async fn cmd_archive(
             .await?
         {
             pb.set_message(truncate(&doc.title, 40));
-            if let Some((discovered, extracted)) = process_email(
+            if let Some(container) = process_email(
                 &doc,
                 &doc_repo,
                 run_ocr,
@@ -317,9 +418,7 @@ pub async fn cmd_archive(
             )
             .await
             {
-                stats.files_discovered += discovered;
-                stats.files_extracted += extracted;
-                stats.containers_processed += 1;
+                stats.add(container);
             }
             pb.inc(1);
         }
@@ -333,6 +432,12 @@ pub async fn cmd_archive(
     if run_ocr {
         println!("  {} files extracted and OCR'd", stats.files_extracted);
     }
+    if stats.relations_added > 0 {
+        println!(
+            "  {} already-archived documents linked to their containers",
+            stats.relations_added
+        );
+    }
 
     Ok(())
 }
@@ -358,12 +463,16 @@ pub async fn cmd_ls(
         doc_repo
             .get_by_type_category(type_name, source_id, limit)
             .await?
-    } else if let Some(sid) = source_id {
-        // Filter by source
-        doc_repo.get_by_source(sid).await?
     } else {
-        // Get all
-        doc_repo.get_all().await?
+        // Filter by source (or all), stopping once the limit is reached
+        doc_repo
+            .stream_documents(
+                StreamFilter::source(source_id).with_projection(Projection::Metadata),
+                DEFAULT_STREAM_BATCH,
+            )
+            .take(limit)
+            .try_collect()
+            .await?
     };
 
     // Apply limit
@@ -607,7 +716,7 @@ pub async fn cmd_read(settings: &Settings, doc_id: &str, text_only: bool) -> any
             .ok_or_else(|| anyhow::anyhow!("Document has no file version"))?;
 
         let resolved = version.resolve_path(&settings.documents_dir, &doc.source_url, &doc.title);
-        let content = std::fs::read(&resolved)?;
+        let content = foia::storage::read_content(&resolved)?;
 
         use std::io::Write;
         std::io::stdout().write_all(&content)?;
@@ -616,6 +725,256 @@ pub async fn cmd_read(settings: &Settings, doc_id: &str, text_only: bool) -> any
     Ok(())
 }
 
+/// Whether a document's title, synopsis, tags or text contain the query.
+fn matches_query(doc: &Document, query_lower: &str) -> bool {
+    // Check title
+    if doc.title.to_lowercase().contains(query_lower) {
+        return true;
+    }
+    // Check synopsis
+    if let Some(synopsis) = &doc.synopsis {
+        if synopsis.to_lowercase().contains(query_lower) {
+            return true;
+        }
+    }
+    // Check tags
+    if doc
+        .tags
+        .iter()
+        .any(|t| t.to_lowercase().contains(query_lower))
+    {
+        return true;
+    }
+    // Check extracted text
+    if let Some(text) = &doc.extracted_text {
+        if text.to_lowercase().contains(query_lower) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Parse a document status name, listing the valid names on failure.
+fn parse_status(name: &str) -> anyhow::Result<DocumentStatus> {
+    DocumentStatus::from_str(name).ok_or_else(|| {
+        let valid: Vec<&str> = DocumentStatus::ALL.iter().map(|s| s.as_str()).collect();
+        anyhow::anyhow!(
+            "Unknown document status '{}' (expected one of: {})",
+            name,
+            valid.join(", ")
+        )
+    })
+}
+
+/// Reset documents to an earlier processing status.
+pub async fn cmd_requeue(
+    settings: &Settings,
+    from_status: &str,
+    to_status: &str,
+    source_id: Option<&str>,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    let from = parse_status(from_status)?;
+    let to = parse_status(to_status)?;
+    if !from.can_requeue_to(to) {
+        let targets: Vec<&str> = from
+            .allowed_transitions()
+            .iter()
+            .filter(|next| from.can_requeue_to(**next))
+            .map(|next| next.as_str())
+            .collect();
+        println!(
+            "{} Cannot requeue {} documents to {}",
+            style("✗").red(),
+            from.as_str(),
+            to.as_str()
+        );
+        if targets.is_empty() {
+            println!("  {} documents have no earlier status", from.as_str());
+        } else {
+            println!("  Allowed targets: {}", targets.join(", "));
+        }
+        return Ok(());
+    }
+
+    let doc_repo = settings.repositories()?.documents;
+    let matching = doc_repo
+        .count_by_status(source_id)
+        .await?
+        .get(from.as_str())
+        .copied()
+        .unwrap_or(0);
+    let scope = source_id
+        .map(|s| format!(" in '{}'", s))
+        .unwrap_or_default();
+
+    if matching == 0 {
+        println!(
+            "{} No {} documents{}",
+            style("!").yellow(),
+            from.as_str(),
+            scope
+        );
+        return Ok(());
+    }
+
+    if !confirm {
+        println!(
+            "{} This will move {} {} documents{} to {}.",
+            style("!").yellow(),
+            matching,
+            from.as_str(),
+            scope,
+            to.as_str()
+        );
+        println!("  Synopses and tags on these documents will be cleared.");
+        println!("  Use --confirm to proceed.");
+        return Ok(());
+    }
+
+    let moved = doc_repo.requeue(from, to, source_id).await?;
+    println!(
+        "{} Requeued {} documents{} from {} to {}",
+        style("✓").green(),
+        moved,
+        scope,
+        from.as_str(),
+        to.as_str()
+    );
+
+    Ok(())
+}
+
+/// Split a multi-document bundle into one document per record it holds.
+pub async fn cmd_split(
+    settings: &Settings,
+    doc_id: &str,
+    signals: &[String],
+    min_pages: u32,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    let mut options = SplitOptions {
+        min_pages: min_pages.max(1),
+        ..Default::default()
+    };
+    if !signals.is_empty() {
+        options.signals = signals
+            .iter()
+            .map(|name| {
+                Signal::from_str(name).ok_or_else(|| {
+                    let known: Vec<&str> = Signal::ALL.iter().map(Signal::as_str).collect();
+                    anyhow::anyhow!(
+                        "Unknown signal '{}' (expected one of: {})",
+                        name,
+                        known.join(", ")
+                    )
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+    }
+    let mut splitter = BundleSplitter::new(options.clone());
+    if options.signals.contains(&Signal::Llm) {
+        let config = Config::load().await;
+        if !config.llm.enabled() {
+            anyhow::bail!(
+                "The llm signal needs an LLM: {}",
+                config.llm.availability_hint()
+            );
+        }
+        splitter = splitter.with_llm(LlmClient::new(config.llm));
+    }
+
+    let doc_repo = settings.repositories()?.documents;
+    let doc = doc_repo
+        .get(doc_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Document not found: {}", doc_id))?;
+    let existing = doc_repo.get_document_splits(doc_id).await?;
+    if !existing.is_empty() {
+        println!(
+            "{} {} was already split into {} documents",
+            style("!").yellow(),
+            doc_id,
+            existing.len()
+        );
+        return Ok(());
+    }
+    let version = doc
+        .current_version()
+        .ok_or_else(|| anyhow::anyhow!("Document {} has no versions", doc_id))?;
+    let pages = doc_repo.get_pages(doc_id, version.id as i32).await?;
+    if pages.is_empty() {
+        anyhow::bail!(
+            "{} has no page text yet; run `analyze` on it before splitting",
+            doc_id
+        );
+    }
+
+    let texts: Vec<String> = pages
+        .iter()
+        .map(|p| {
+            p.final_text
+                .clone()
+                .or_else(|| p.ocr_text.clone())
+                .or_else(|| p.pdf_text.clone())
+                .unwrap_or_default()
+        })
+        .collect();
+    let segments = splitter.segments(&texts).await;
+    if segments.len() < 2 {
+        println!(
+            "{} Found no document boundaries in {} ({} pages)",
+            style("!").yellow(),
+            doc_id,
+            pages.len()
+        );
+        return Ok(());
+    }
+
+    println!("{:<12} {:<11} Starts with", "Pages", "Signal");
+    println!("{}", "-".repeat(80));
+    for segment in &segments {
+        let opening = texts[segment.first_page as usize - 1]
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .unwrap_or_default();
+        println!(
+            "{:<12} {:<11} {}",
+            format!("{}-{}", segment.first_page, segment.last_page),
+            segment.boundary.map_or("-", |s| s.as_str()),
+            truncate(opening, 55)
+        );
+    }
+
+    if !confirm {
+        println!(
+            "\n{} This will split {} into {} documents.",
+            style("!").yellow(),
+            doc_id,
+            segments.len()
+        );
+        println!("  Use --confirm to proceed.");
+        return Ok(());
+    }
+
+    let split = split_bundle(&doc_repo, &doc, &segments, &settings.documents_dir).await?;
+    output::emit(
+        "result",
+        serde_json::json!({
+            "document_id": doc_id,
+            "documents": split,
+        }),
+    );
+    println!(
+        "\n{} Split {} into {} documents",
+        style("✓").green(),
+        doc_id,
+        split.len()
+    );
+    Ok(())
+}
+
 /// Search documents by content or metadata.
 pub async fn cmd_search(
     settings: &Settings,
@@ -626,47 +985,28 @@ pub async fn cmd_search(
     let repos = settings.repositories()?;
     let doc_repo = repos.documents;
 
-    let query_lower = query.to_lowercase();
-
-    // Get all documents and filter
-    let documents: Vec<Document> = if let Some(sid) = source_id {
-        doc_repo.get_by_source(sid).await?
-    } else {
-        doc_repo.get_all().await?
-    };
+    // The query and its alias expansions ("OIG" and "Office of Inspector General")
+    let terms: Vec<String> = doc_repo
+        .expand_search_query(query)
+        .await?
+        .iter()
+        .map(|t| t.to_lowercase())
+        .collect();
+    if terms.len() > 1 {
+        println!(
+            "{} Also searching: {}",
+            style("→").dim(),
+            terms[1..].join(", ")
+        );
+    }
 
-    // Search in title, synopsis, tags, and extracted text
-    let matches: Vec<_> = documents
-        .into_iter()
-        .filter(|doc| {
-            // Check title
-            if doc.title.to_lowercase().contains(&query_lower) {
-                return true;
-            }
-            // Check synopsis
-            if let Some(synopsis) = &doc.synopsis {
-                if synopsis.to_lowercase().contains(&query_lower) {
-                    return true;
-                }
-            }
-            // Check tags
-            if doc
-                .tags
-                .iter()
-                .any(|t| t.to_lowercase().contains(&query_lower))
-            {
-                return true;
-            }
-            // Check extracted text
-            if let Some(text) = &doc.extracted_text {
-                if text.to_lowercase().contains(&query_lower) {
-                    return true;
-                }
-            }
-            false
-        })
+    // Stream documents so only matches are held in memory
+    let matches: Vec<Document> = doc_repo
+        .stream_documents(StreamFilter::source(source_id), DEFAULT_STREAM_BATCH)
+        .try_filter(|doc| futures::future::ready(terms.iter().any(|t| matches_query(doc, t))))
         .take(limit)
-        .collect();
+        .try_collect()
+        .await?;
 
     if matches.is_empty() {
         println!(
@@ -680,6 +1020,10 @@ pub async fn cmd_search(
     println!("\n{} results for '{}'\n", matches.len(), query);
 
     for doc in &matches {
+        let query_lower = terms
+            .iter()
+            .find(|t| matches_query(doc, t))
+            .map_or(terms[0].as_str(), String::as_str);
         let version = doc.current_version();
         let mime = version.map(|v| mime_short(&v.mime_type)).unwrap_or("???");
 
@@ -692,7 +1036,7 @@ pub async fn cmd_search(
 
         // Show context of match
         if let Some(synopsis) = &doc.synopsis {
-            if synopsis.to_lowercase().contains(&query_lower) {
+            if synopsis.to_lowercase().contains(query_lower) {
                 println!("  Synopsis: {}", truncate(synopsis, 80));
             }
         }
@@ -701,7 +1045,7 @@ pub async fn cmd_search(
             let matching_tags: Vec<_> = doc
                 .tags
                 .iter()
-                .filter(|t| t.to_lowercase().contains(&query_lower))
+                .filter(|t| t.to_lowercase().contains(query_lower))
                 .collect();
             if !matching_tags.is_empty() {
                 println!(
@@ -717,9 +1061,9 @@ pub async fn cmd_search(
 
         // Show snippet from extracted text if match found there
         if let Some(text) = &doc.extracted_text {
-            if let Some(pos) = text.to_lowercase().find(&query_lower) {
+            if let Some(pos) = text.to_lowercase().find(query_lower) {
                 let start = pos.saturating_sub(40);
-                let end = (pos + query.len() + 40).min(text.len());
+                let end = (pos + query_lower.len() + 40).min(text.len());
                 let snippet: String = text[start..end].chars().collect();
                 let snippet = snippet.replace('\n', " ");
                 println!("  ...{}...", truncate(&snippet, 80));
@@ -0,0 +1,154 @@
+//! Document encryption key generation and at-rest migration commands.
+
+use std::path::{Path, PathBuf};
+
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+
+use foia::config::Settings;
+use foia::encryption::{self, ContentKey};
+use foia::storage;
+
+/// Generate a new document encryption key into a key file or keychain entry.
+pub fn cmd_keygen(key_file: Option<&Path>, keychain: Option<&str>) -> anyhow::Result<()> {
+    let key = ContentKey::generate();
+
+    match (key_file, keychain) {
+        (Some(path), None) => {
+            if path.exists() {
+                anyhow::bail!(
+                    "{} already exists; refusing to overwrite a key",
+                    path.display()
+                );
+            }
+            write_key_file(path, &key)?;
+            println!(
+                "{} Wrote encryption key to {}",
+                style("✓").green(),
+                path.display()
+            );
+            println!("  Set encryption.key_file to this path in your config.");
+        }
+        (None, Some(entry)) => {
+            key.store_keychain(entry)?;
+            println!(
+                "{} Stored encryption key in keychain entry '{}'",
+                style("✓").green(),
+                entry
+            );
+            println!("  Set encryption.keychain to this entry name in your config.");
+        }
+        _ => anyhow::bail!("Specify exactly one of --key-file or --keychain"),
+    }
+
+    println!(
+        "  {} Back the key up: encrypted documents cannot be recovered without it.",
+        style("!").yellow()
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_key_file(path: &Path, key: &ContentKey) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    writeln!(file, "{}", key.to_hex())
+}
+
+#[cfg(not(unix))]
+fn write_key_file(path: &Path, key: &ContentKey) -> std::io::Result<()> {
+    std::fs::write(path, format!("{}\n", key.to_hex()))
+}
+
+/// Encrypt or decrypt every file in the documents directory in place.
+pub fn cmd_migrate(settings: &Settings, encrypt: bool, confirm: bool) -> anyhow::Result<()> {
+    let Some(key) = encryption::content_key() else {
+        anyhow::bail!(
+            "No encryption key configured. Set encryption.key_file or encryption.keychain (see 'foia docs keygen')."
+        );
+    };
+
+    let mut files = Vec::new();
+    collect_files(&settings.documents_dir, &mut files)?;
+    let pending: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|path| storage::is_encrypted_file(path) != encrypt)
+        .collect();
+    let action = if encrypt { "encrypt" } else { "decrypt" };
+
+    if pending.is_empty() {
+        println!(
+            "{} No files to {} in {}",
+            style("!").yellow(),
+            action,
+            settings.documents_dir.display()
+        );
+        return Ok(());
+    }
+
+    if !confirm {
+        println!(
+            "{} This will {} {} files in {}.",
+            style("!").yellow(),
+            action,
+            pending.len(),
+            settings.documents_dir.display()
+        );
+        println!("  Run again with --confirm to proceed.");
+        return Ok(());
+    }
+
+    let pb = ProgressBar::new(pending.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:30.cyan/blue}] {pos}/{len} {wide_msg}")
+            .unwrap()
+            .progress_chars("█▓░"),
+    );
+
+    let mut migrated = 0usize;
+    let mut failed = 0usize;
+    for path in &pending {
+        match storage::migrate_file(path, key, encrypt) {
+            Ok(true) => migrated += 1,
+            Ok(false) => {}
+            Err(e) => {
+                failed += 1;
+                pb.suspend(|| {
+                    eprintln!("  {} {}: {}", style("✗").red(), path.display(), e);
+                });
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    println!(
+        "{} {} {} files ({} failed)",
+        style("✓").green(),
+        if encrypt { "Encrypted" } else { "Decrypted" },
+        migrated,
+        failed
+    );
+    Ok(())
+}
+
+/// Recursively collect regular files under `dir`.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
@@ -5,7 +5,7 @@ use indicatif::{ProgressBar, ProgressStyle};
 
 use foia::config::Settings;
 use foia::repository::diesel_document::entities::EntityFilter;
-use foia::repository::diesel_document::DocIdRow;
+use foia::repository::diesel_document::{DocIdRow, Projection};
 use foia::repository::models::NewDocumentEntity;
 #[cfg(feature = "gis")]
 use foia::services::geolookup;
@@ -84,7 +84,10 @@ pub async fn cmd_backfill_entities(
     let mut failed = 0usize;
 
     for row in &doc_ids {
-        let doc = match doc_repo.get(&row.id).await? {
+        let doc = match doc_repo
+            .get_projected(&row.id, Projection::Metadata)
+            .await?
+        {
             Some(d) => d,
             None => {
                 pb.inc(1);
@@ -243,7 +246,7 @@ pub async fn cmd_search_entities(
         );
 
         for id in &doc_ids {
-            if let Ok(Some(doc)) = doc_repo.get(id).await {
+            if let Ok(Some(doc)) = doc_repo.get_projected(id, Projection::Metadata).await {
                 println!(
                     "  {} {}",
                     style(&doc.id[..8.min(doc.id.len())]).dim(),
@@ -285,7 +288,7 @@ pub async fn cmd_search_entities(
     let entities_map = doc_repo.get_entities_batch(&doc_ids).await?;
 
     for id in &doc_ids {
-        if let Ok(Some(doc)) = doc_repo.get(id).await {
+        if let Ok(Some(doc)) = doc_repo.get_projected(id, Projection::Metadata).await {
             let entities = entities_map.get(id);
             let entity_summary = entities
                 .map(|es| {
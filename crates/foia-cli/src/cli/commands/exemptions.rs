@@ -0,0 +1,99 @@
+//! FOIA exemption statistics commands.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::repository::diesel_document::StatsInterval;
+use foia::services::exemptions::normalize_code;
+
+/// Show how often each exemption is cited, optionally over time.
+pub async fn cmd_exemption_stats(
+    settings: &Settings,
+    source_id: Option<&str>,
+    code: Option<&str>,
+    by: Option<&str>,
+) -> anyhow::Result<()> {
+    let code = code
+        .map(|c| {
+            normalize_code(c).ok_or_else(|| {
+                anyhow::anyhow!("'{}' is not an exemption code (e.g. b5, (b)(7)(C))", c)
+            })
+        })
+        .transpose()?;
+    let interval = by
+        .map(|b| {
+            StatsInterval::from_str(b)
+                .ok_or_else(|| anyhow::anyhow!("Invalid interval '{}' (expected year or month)", b))
+        })
+        .transpose()?;
+
+    let repos = settings.repositories()?;
+    let doc_repo = repos.documents;
+    let scope = source_id.unwrap_or("all sources");
+
+    if let Some(interval) = interval {
+        let periods = doc_repo
+            .get_exemption_timeline(source_id, code.as_deref(), interval)
+            .await?;
+        if periods.is_empty() {
+            println!(
+                "{} No dated exemption citations in {}",
+                style("!").yellow(),
+                scope
+            );
+            println!("  Run `foia exemptions extract` and `foia detect-dates` first");
+            return Ok(());
+        }
+
+        println!(
+            "\n{}",
+            style(format!("Exemptions over time ({})", scope)).bold()
+        );
+        println!("{}", "-".repeat(50));
+        println!(
+            "{:<10} {:<12} {:>10} {:>12}",
+            "Period", "Exemption", "Documents", "Markings"
+        );
+        println!("{}", "-".repeat(50));
+        for p in &periods {
+            println!(
+                "{:<10} {:<12} {:>10} {:>12}",
+                p.period, p.code, p.documents, p.markings
+            );
+        }
+        return Ok(());
+    }
+
+    let mut stats = doc_repo.get_exemption_stats(source_id).await?;
+    if let Some(code) = &code {
+        stats.retain(|s| &s.code == code);
+    }
+    if stats.is_empty() {
+        println!(
+            "{} No exemption citations in {}",
+            style("!").yellow(),
+            scope
+        );
+        println!("  Run `foia exemptions extract` to scan page text");
+        return Ok(());
+    }
+
+    println!(
+        "\n{}",
+        style(format!("Exemptions cited ({})", scope)).bold()
+    );
+    println!("{}", "-".repeat(50));
+    println!(
+        "{:<12} {:>10} {:>10} {:>12}",
+        "Exemption", "Documents", "Pages", "Markings"
+    );
+    println!("{}", "-".repeat(50));
+    for s in &stats {
+        println!(
+            "{:<12} {:>10} {:>10} {:>12}",
+            s.code, s.documents, s.pages, s.markings
+        );
+    }
+
+    Ok(())
+}
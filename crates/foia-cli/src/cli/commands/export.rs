@@ -0,0 +1,347 @@
+//! Scheduled, production and analytical export commands.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use console::style;
+
+use foia::config::{Config, Settings};
+use foia::services::duckdb_export;
+use foia::services::exports::{
+    self, ExportJob, ExportLayout, ExportProgress, ExportRun, ExportRunner, RunStatus,
+};
+
+use super::helpers::{format_bytes, format_number, truncate};
+use crate::cli::output;
+use crate::cli::progress::TaskProgress;
+
+/// Parse the configured export jobs, sorted by name.
+fn configured_jobs(config: &Config) -> anyhow::Result<Vec<ExportJob>> {
+    let mut jobs = config
+        .exports
+        .jobs
+        .iter()
+        .map(|(name, job)| ExportJob::from_config(name, job))
+        .collect::<Result<Vec<_>, _>>()?;
+    jobs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(jobs)
+}
+
+fn http_client(config: &Config) -> anyhow::Result<reqwest::Client> {
+    // ALLOWED: Uploads go to our own storage and alert endpoints, not a
+    // scraped source. Still routed through the configured proxy.
+    #[allow(clippy::disallowed_methods)]
+    let mut builder = reqwest::Client::builder().timeout(std::time::Duration::from_secs(3600));
+    if let Some(proxy) = config.privacy.effective_proxy_url() {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    #[allow(clippy::disallowed_methods)]
+    let client = builder.build()?;
+    Ok(client)
+}
+
+fn print_run(run: &ExportRun) {
+    match run.status {
+        RunStatus::Succeeded => println!(
+            "{} {}: {} document(s), {} → {}",
+            style("✓").green(),
+            run.job,
+            run.documents,
+            format_bytes(run.bytes),
+            run.artifact.as_deref().unwrap_or(&run.destination)
+        ),
+        _ => println!(
+            "{} {}: {}",
+            style("✗").red(),
+            run.job,
+            run.error.as_deref().unwrap_or("failed")
+        ),
+    }
+}
+
+/// Run export jobs: one job now, or every job that is due.
+///
+/// In daemon mode due jobs are checked every `interval` seconds.
+pub async fn cmd_export_run(
+    settings: &Settings,
+    config: &Config,
+    job: Option<&str>,
+    daemon: bool,
+    interval: u64,
+) -> anyhow::Result<()> {
+    let jobs = configured_jobs(config)?;
+    if jobs.is_empty() {
+        anyhow::bail!("No export jobs configured. Add them under exports.jobs.");
+    }
+    let named = job.is_some();
+    let selected: Vec<&ExportJob> = match job {
+        Some(name) => vec![jobs
+            .iter()
+            .find(|j| j.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown export job '{}'", name))?],
+        None => jobs.iter().collect(),
+    };
+
+    let repos = settings.repositories()?;
+    let progress: Arc<Mutex<Option<TaskProgress>>> = Arc::default();
+    let bar = progress.clone();
+    let runner = ExportRunner::new(
+        &repos.documents,
+        &settings.documents_dir,
+        &settings.data_dir,
+        http_client(config)?,
+        config.exports.alert_webhook.clone(),
+    )
+    .with_progress(Arc::new(move |event| {
+        let Ok(mut bar) = bar.lock() else {
+            return;
+        };
+        match event {
+            ExportProgress::Started { total } => {
+                *bar = Some(TaskProgress::new("export", total, "Exporting..."));
+            }
+            ExportProgress::Exported => {
+                if let Some(ref bar) = *bar {
+                    bar.inc(1);
+                }
+            }
+        }
+    }));
+
+    if daemon {
+        println!(
+            "{} Running {} export job(s) when due (interval: {}s)",
+            style("→").cyan(),
+            selected.len(),
+            interval
+        );
+    }
+
+    loop {
+        let mut failed = 0;
+        for job in &selected {
+            // A named job runs now, unless daemonized
+            let forced = named && !daemon;
+            if !forced && !runner.is_due(job).await? {
+                continue;
+            }
+            if !daemon {
+                println!(
+                    "{} Exporting {} ({}) to {}",
+                    style("→").cyan(),
+                    job.name,
+                    job.format.as_str(),
+                    job.destination
+                );
+            }
+            let run = runner.run(job).await?;
+            if let Some(bar) = progress.lock().ok().and_then(|mut p| p.take()) {
+                bar.finish_and_clear();
+            }
+            print_run(&run);
+            output::emit("result", serde_json::to_value(&run)?);
+            if run.status == RunStatus::Failed {
+                failed += 1;
+            }
+        }
+
+        if !daemon {
+            if failed > 0 {
+                anyhow::bail!("{} export job(s) failed", failed);
+            }
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+    }
+}
+
+/// List configured export jobs with their last run.
+pub async fn cmd_export_list(settings: &Settings, config: &Config) -> anyhow::Result<()> {
+    let jobs = configured_jobs(config)?;
+    if jobs.is_empty() {
+        println!(
+            "{} No export jobs configured. Add them under exports.jobs.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let repos = settings.repositories()?;
+    println!("\n{}", style("Export Jobs").bold());
+    println!("{}", "-".repeat(90));
+    println!(
+        "{:<16} {:<6} {:<8} {:<36} Last run",
+        "Job", "Format", "Schedule", "Destination"
+    );
+    println!("{}", "-".repeat(90));
+    for job in &jobs {
+        let last = repos.documents.last_export_run(&job.name, None).await?;
+        let last = match last {
+            Some(run) => format!(
+                "{} ({})",
+                run.started_at.format("%Y-%m-%d %H:%M"),
+                run.status.as_str()
+            ),
+            None => "never".to_string(),
+        };
+        println!(
+            "{:<16} {:<6} {:<8} {:<36} {}",
+            truncate(&job.name, 16),
+            job.format.as_str(),
+            job.schedule.as_str(),
+            truncate(&job.destination.to_string(), 36),
+            last
+        );
+    }
+    Ok(())
+}
+
+/// Show export run history, newest first.
+pub async fn cmd_export_history(
+    settings: &Settings,
+    job: Option<&str>,
+    limit: i64,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let runs = repos.documents.list_export_runs(job, limit).await?;
+    if runs.is_empty() {
+        println!("{} No export runs recorded", style("!").yellow());
+        return Ok(());
+    }
+
+    println!("\n{}", style("Export Runs").bold());
+    println!("{}", "-".repeat(90));
+    println!(
+        "{:<17} {:<16} {:<10} {:>6} {:>10}  Artifact / error",
+        "Started", "Job", "Status", "Docs", "Size"
+    );
+    println!("{}", "-".repeat(90));
+    for run in &runs {
+        let status = match run.status {
+            RunStatus::Succeeded => style(run.status.as_str()).green(),
+            RunStatus::Failed => style(run.status.as_str()).red(),
+            RunStatus::Running => style(run.status.as_str()).yellow(),
+        };
+        let detail = run
+            .error
+            .as_deref()
+            .or(run.artifact.as_deref())
+            .unwrap_or("");
+        println!(
+            "{:<17} {:<16} {:<10} {:>6} {:>10}  {}",
+            run.started_at.format("%Y-%m-%d %H:%M"),
+            truncate(&run.job, 16),
+            status,
+            run.documents,
+            format_bytes(run.bytes),
+            truncate(detail, 40)
+        );
+    }
+    Ok(())
+}
+
+/// Write a source's files into a directory under an export layout.
+pub async fn cmd_export_production(
+    settings: &Settings,
+    source_id: &str,
+    dir: &Path,
+    layout: &str,
+    include_restricted: bool,
+) -> anyhow::Result<()> {
+    let layout = ExportLayout::from_str(layout)
+        .ok_or_else(|| anyhow::anyhow!("Unknown layout '{}' (original, bates, hash)", layout))?;
+    let repos = settings.repositories()?;
+    let summary = exports::write_directory(
+        &repos.documents,
+        &settings.documents_dir,
+        source_id,
+        layout,
+        include_restricted,
+        dir,
+    )
+    .await?;
+
+    if output::is_json() {
+        let mut value = serde_json::to_value(&summary)?;
+        value["dir"] = serde_json::json!(dir);
+        value["layout"] = serde_json::json!(layout.as_str());
+        output::emit("result", value);
+        return Ok(());
+    }
+    println!(
+        "{} Wrote {} file(s) of {} document(s), {} → {}",
+        style("✓").green(),
+        summary.files,
+        summary.documents,
+        format_bytes(summary.bytes),
+        dir.display()
+    );
+    println!("  File map: {}", dir.join(exports::FILE_MAP).display());
+    if summary.unnamed > 0 {
+        println!(
+            "{} {} file(s) had no {} name and were named by content hash",
+            style("!").yellow(),
+            summary.unnamed,
+            layout.as_str()
+        );
+    }
+    Ok(())
+}
+
+/// Write the archive as typed tables for DuckDB.
+pub async fn cmd_export_duckdb(
+    settings: &Settings,
+    dir: &Path,
+    source_id: Option<&str>,
+    parquet: bool,
+    include_restricted: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let summary = duckdb_export::write_duckdb(
+        &repos.documents,
+        &repos.crawl,
+        source_id,
+        include_restricted,
+        parquet,
+        dir,
+    )
+    .await?;
+
+    if output::is_json() {
+        let mut value = serde_json::to_value(&summary)?;
+        value["dir"] = serde_json::json!(dir);
+        output::emit("result", value);
+        return Ok(());
+    }
+    println!(
+        "{} Exported {} documents, {} versions, {} pages, {} requests, {} entities → {}",
+        style("✓").green(),
+        format_number(summary.documents),
+        format_number(summary.versions),
+        format_number(summary.pages),
+        format_number(summary.requests),
+        format_number(summary.entities),
+        dir.display()
+    );
+    match &summary.database {
+        Some(database) => {
+            println!("  Database: {}", database.display());
+            if summary.parquet {
+                println!("  Parquet:  {}", dir.join("parquet").display());
+            }
+        }
+        None => {
+            println!(
+                "{} duckdb not found; build the database with:",
+                style("!").yellow()
+            );
+            println!(
+                "  cd {} && duckdb {} < {}",
+                dir.display(),
+                duckdb_export::DATABASE,
+                duckdb_export::LOAD_SCRIPT
+            );
+        }
+    }
+    Ok(())
+}
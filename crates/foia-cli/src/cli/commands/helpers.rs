@@ -1,5 +1,8 @@
 //! Helper utilities for CLI commands.
 
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use console::style;
+
 /// Truncate a string to a maximum length, adding "..." if truncated.
 pub fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
@@ -9,6 +12,17 @@ pub fn truncate(s: &str, max: usize) -> String {
     }
 }
 
+/// Format a number with thousand separators.
+pub fn format_number(n: u64) -> String {
+    let s = n.to_string();
+    let bytes: Vec<_> = s.bytes().rev().collect();
+    let chunks: Vec<_> = bytes
+        .chunks(3)
+        .map(|chunk| chunk.iter().rev().map(|&b| b as char).collect::<String>())
+        .collect();
+    chunks.into_iter().rev().collect::<Vec<_>>().join(",")
+}
+
 /// Format bytes as human-readable size.
 pub fn format_bytes(bytes: u64) -> String {
     if bytes >= 1_000_000_000 {
@@ -22,6 +36,20 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// Format an estimated time remaining, e.g. `3h 20m` or `2d 4h`.
+pub fn format_eta(eta_secs: Option<u64>) -> String {
+    let Some(secs) = eta_secs else {
+        return "stalled".to_string();
+    };
+    let minutes = secs.div_ceil(60);
+    match minutes {
+        0 => "done".to_string(),
+        m if m < 60 => format!("{}m", m),
+        m if m < 60 * 24 => format!("{}h {}m", m / 60, m % 60),
+        m => format!("{}d {}h", m / (60 * 24), m / 60 % 24),
+    }
+}
+
 /// Convert MIME type to short form for display.
 pub fn mime_short(mime: &str) -> &'static str {
     match mime {
@@ -34,3 +62,33 @@ pub fn mime_short(mime: &str) -> &'static str {
         _ => "other",
     }
 }
+
+/// Notice printed when a paused source is skipped.
+pub fn paused_notice(source_id: &str) -> String {
+    format!(
+        "{} Source '{}' is paused (resume with 'foia source resume {}')",
+        style("⏸").yellow(),
+        source_id,
+        source_id
+    )
+}
+
+/// Notice printed when a source is skipped outside its crawl window.
+pub fn window_notice(source_id: &str, opens: DateTime<Utc>) -> String {
+    format!(
+        "{} Source '{}' is outside its crawl window (opens {})",
+        style("⏾").yellow(),
+        source_id,
+        opens.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+/// Parse a date argument given as YYYY-MM-DD (midnight UTC) or RFC 3339.
+pub fn parse_date_arg(value: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| anyhow::anyhow!("Invalid date '{}' (expected YYYY-MM-DD or RFC 3339)", value))
+}
@@ -1,5 +1,6 @@
-//! Import commands for WARC files, URL lists, and stdin content.
+//! Import commands for WARC files, URL lists, stdin content, and intake folders.
 
+use std::collections::HashSet;
 use std::io::Read;
 use std::path::PathBuf;
 
@@ -259,7 +260,7 @@ pub async fn cmd_import_stdin(
 
     use foia::models::{Document, DocumentVersion, Source, SourceType};
     use foia::repository::extract_filename_parts;
-    use foia::storage::compute_storage_path_with_dedup;
+    use foia::storage::{compute_storage_path_with_dedup, write_content};
 
     settings.ensure_directories()?;
     let repos = settings.repositories()?;
@@ -316,6 +317,7 @@ pub async fn cmd_import_stdin(
                 metadata: serde_json::json!({}),
                 created_at: Utc::now(),
                 last_scraped: None,
+                paused: false,
             };
             source_repo.save(&new_source).await?;
             new_source
@@ -341,7 +343,7 @@ pub async fn cmd_import_stdin(
     if let Some(parent) = content_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    std::fs::write(&content_path, &content)?;
+    write_content(&content_path, &content)?;
 
     // Create document version
     let mut version = DocumentVersion::new_with_metadata(
@@ -480,3 +482,80 @@ pub async fn cmd_import_concordance(
 
     Ok(())
 }
+
+/// Import scanned files dropped into an intake directory.
+///
+/// In daemon mode the directory is re-scanned every `interval` seconds.
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_import_intake(
+    settings: &Settings,
+    dir: &std::path::Path,
+    source_id: &str,
+    pattern: &str,
+    tags: &[String],
+    dry_run: bool,
+    daemon: bool,
+    interval: u64,
+) -> anyhow::Result<()> {
+    use foia_import::{FilenamePattern, IntakeImportSource};
+
+    settings.ensure_directories()?;
+
+    let pattern = FilenamePattern::parse(pattern)?;
+    let mut source = IntakeImportSource::new(dir.to_path_buf(), pattern.clone(), settings.clone())?;
+
+    let runner = ImportRunner::new(settings);
+    let storage_mode = ImportRunner::detect_storage_mode(dir, &settings.documents_dir);
+    let mut config = runner
+        .create_config(Some(source_id.to_string()), 0, dry_run, false, storage_mode)
+        .await?;
+    config.verify = false;
+    config.tags = tags.to_vec();
+
+    if daemon {
+        println!(
+            "{} Watching {} for files matching {} (interval: {}s)",
+            style("→").cyan(),
+            dir.display(),
+            pattern.as_str(),
+            interval
+        );
+    }
+
+    // Unmatched files are reported once, not on every scan
+    let mut reported: HashSet<String> = HashSet::new();
+
+    loop {
+        if !daemon || source.has_pending() {
+            let stats = runner.run(&mut source, &config).await?;
+
+            let new_unmatched: Vec<&String> = source
+                .unmatched()
+                .iter()
+                .filter(|name| !reported.contains(*name))
+                .collect();
+            if !new_unmatched.is_empty() {
+                println!(
+                    "{} {} file(s) don't match {} - rename them to import:",
+                    style("!").yellow(),
+                    new_unmatched.len(),
+                    pattern.as_str()
+                );
+                for name in new_unmatched {
+                    println!("  - {}", name);
+                    reported.insert(name.clone());
+                }
+            }
+            reported.retain(|name| source.unmatched().contains(name));
+
+            if !daemon && stats.errors > 0 {
+                anyhow::bail!("{} error(s) during import", stats.errors);
+            }
+        }
+
+        if !daemon {
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+    }
+}
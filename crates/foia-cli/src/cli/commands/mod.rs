@@ -2,32 +2,58 @@
 //!
 //! This module contains the CLI parser and dispatches to command-specific modules.
 
+mod access;
+mod acquire;
+mod agency;
+mod alias;
 mod analyze;
 mod annotate;
+mod api_key;
+mod bates;
+mod captcha;
+mod compare;
 mod config_cmd;
+mod custody;
 mod daemon;
+mod dashboard;
 mod db;
 mod discover;
 mod documents;
+mod encryption;
 mod entities;
+mod exemptions;
+mod export;
 mod helpers;
 mod import;
 mod init;
 mod llm;
+mod pipeline;
 #[cfg(feature = "gis")]
 mod regions;
+mod repair;
+mod report;
+mod retention;
 mod scrape;
+mod search_index;
+mod secrets;
+mod sections;
 mod serve;
 mod source;
 mod state;
+mod storage;
+mod sync;
+mod titles;
+mod transcription;
 
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 
-use foia::config::{load_settings_with_options, LoadOptions};
+use foia::config::{load_settings_with_options, LoadOptions, SearchBackend};
 use foia::work_queue::ExecutionStrategy;
 
+use crate::cli::output;
+
 // Re-export ReloadMode for use by other modules
 pub use daemon::ReloadMode;
 
@@ -62,6 +88,15 @@ pub struct Cli {
     #[arg(long, global = true)]
     cwd: bool,
 
+    /// Config profile to apply (e.g. dev, staging, production)
+    #[arg(long, global = true, env = "FOIA_PROFILE")]
+    profile: Option<String>,
+
+    /// Override a setting, e.g. `--set llm.model=llama3.2` (repeatable;
+    /// wins over FOIACQUIRE__ environment variables and the config file)
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    overrides: Vec<String>,
+
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     pub verbose: bool,
@@ -86,6 +121,10 @@ pub struct Cli {
     #[arg(long, global = true)]
     no_tor_warning: bool,
 
+    /// Report as newline-delimited JSON events on stdout (human output goes to stderr)
+    #[arg(long, global = true)]
+    json: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -106,6 +145,54 @@ enum Commands {
         command: SourceCommands,
     },
 
+    /// Group sources into agencies and sub-agencies
+    Agency {
+        #[command(subcommand)]
+        command: AgencyCommands,
+    },
+
+    /// Manage search aliases (acronyms, code names, alternate names)
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommands,
+    },
+
+    /// Restrict documents or sources to internal use or embargo them
+    Access {
+        #[command(subcommand)]
+        command: AccessCommands,
+    },
+
+    /// Issue and revoke API keys for partner access to the JSON API
+    ApiKey {
+        #[command(subcommand)]
+        command: ApiKeyCommands,
+    },
+
+    /// Package unreadable pages for volunteers to transcribe
+    Transcribe {
+        #[command(subcommand)]
+        command: TranscribeCommands,
+    },
+
+    /// Build and sync the search index (tantivy, Meilisearch or OpenSearch)
+    SearchIndex {
+        #[command(subcommand)]
+        command: SearchIndexCommands,
+    },
+
+    /// Exchange documents and crawl state with another foia instance
+    Sync {
+        #[command(subcommand)]
+        command: SyncCommands,
+    },
+
+    /// Scheduled exports to a local path, S3 or SFTP (configured under exports.jobs)
+    Export {
+        #[command(subcommand)]
+        command: ExportCommands,
+    },
+
     /// Discover document URLs from a source (does not download)
     Crawl {
         /// Source ID to crawl
@@ -113,6 +200,9 @@ enum Commands {
         /// Limit number of pages to crawl (0 = unlimited)
         #[arg(short, long, default_value = "0")]
         limit: usize,
+        /// Outside the source's crawl window, wait for it to open instead of exiting
+        #[arg(long)]
+        wait: bool,
     },
 
     /// Download pending documents from queue
@@ -136,18 +226,48 @@ enum Commands {
         command: StateCommands,
     },
 
+    /// Review and solve CAPTCHA challenges that paused crawling
+    Captcha {
+        #[command(subcommand)]
+        command: CaptchaCommands,
+    },
+
     /// Configuration management
     Config {
         #[command(subcommand)]
         command: ConfigCommands,
     },
 
+    /// Manage secrets referenced from config as secret://name
+    Secrets {
+        #[command(subcommand)]
+        command: SecretsCommands,
+    },
+
     /// Database management (copy between SQLite/Postgres)
     Db {
         #[command(subcommand)]
         command: DbCommands,
     },
 
+    /// Repair document storage (re-fetch missing files)
+    Repair {
+        #[command(subcommand)]
+        command: RepairCommands,
+    },
+
+    /// Periodic archive statistics reports (HTML, OpenDocument or PDF)
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
+    },
+
+    /// Storage usage by source and type, and cleanup of reclaimable space
+    Storage {
+        #[command(subcommand)]
+        command: StorageCommands,
+    },
+
     /// Scrape documents from one or more sources (crawl + download combined)
     Scrape {
         /// Source IDs to scrape (can specify multiple, or use --all)
@@ -178,6 +298,28 @@ enum Commands {
         rate_limit_backend: RateLimitBackendType,
     },
 
+    /// Run the full pipeline for a source: crawl, download, text extraction
+    /// and OCR, summarization, then date and entity extraction
+    Run {
+        /// Source ID to process
+        source_id: String,
+        /// Start at this stage, skipping earlier ones
+        #[arg(long, value_enum, default_value = "crawl")]
+        from: pipeline::RunStage,
+        /// Stages to skip (comma-separated)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        skip: Vec<pipeline::RunStage>,
+        /// Number of download workers (default: 4)
+        #[arg(short, long, default_value = "4")]
+        workers: usize,
+        /// Number of analysis workers (default: 2)
+        #[arg(long, default_value = "2")]
+        analysis_workers: usize,
+        /// Limit number of documents per stage (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+    },
+
     /// Show system status
     Status {
         /// Server URL to fetch status from (e.g., http://localhost:3030).
@@ -195,10 +337,15 @@ enum Commands {
         /// Refresh interval in seconds
         #[arg(long, default_value = "5")]
         interval: u64,
+    },
 
-        /// Output as JSON
-        #[arg(long)]
-        json: bool,
+    /// Interactive operator dashboard: per-source crawl state, backlogs,
+    /// recent errors and rate limits, with keys to pause sources and
+    /// requeue failures
+    Tui {
+        /// Refresh interval in seconds
+        #[arg(long, default_value = "5")]
+        interval: u64,
     },
 
     /// Analyze documents: detect content types, extract text, and run OCR
@@ -265,6 +412,21 @@ enum Commands {
         deepseek_path: Option<std::path::PathBuf>,
     },
 
+    /// Choose pages' final text again among their stored OCR readings
+    AnalyzeArbitrate {
+        /// Source ID (optional, all sources if not specified)
+        source_id: Option<String>,
+        /// Only pages of this document
+        #[arg(long)]
+        doc_id: Option<String>,
+        /// Maximum pages to arbitrate (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+        /// Show which pages would change without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Start web server to browse documents (as Tor hidden service by default)
     Serve {
         /// Address to bind to: PORT, HOST, or HOST:PORT (default: 127.0.0.1:3030)
@@ -283,6 +445,11 @@ enum Commands {
         /// (requires allow_potentially_insecure_circuits in config)
         #[arg(long)]
         use_arti: bool,
+
+        /// Serve a replicated read-only database: reject writes and skip
+        /// migrations (auto-detected when the database is not writable)
+        #[arg(long)]
+        read_only: bool,
     },
 
     /// Refresh metadata for existing documents (server date, original filename)
@@ -361,6 +528,25 @@ enum Commands {
         limit: usize,
     },
 
+    /// Classify documents by record type (letter, memo, email, form, invoice, report, photo, map)
+    Classify {
+        /// Source ID (optional, processes all sources if not specified)
+        source_id: Option<String>,
+        /// Limit number of documents to process (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+        /// Classify text documents with the configured LLM instead of heuristics
+        #[arg(long)]
+        llm: bool,
+    },
+
+    /// Suggest and review descriptive titles for documents titled after
+    /// their file name or URL
+    Titles {
+        #[command(subcommand)]
+        command: TitlesCommands,
+    },
+
     /// Backfill the document_entities table from existing NER annotations
     BackfillEntities {
         /// Source ID (optional, processes all sources if not specified)
@@ -388,6 +574,38 @@ enum Commands {
         limit: usize,
     },
 
+    /// Extract Bates numbers, look documents up by them, and find gaps
+    Bates {
+        #[command(subcommand)]
+        command: BatesCommands,
+    },
+
+    /// Derive and show the chapters and sections of long reports
+    Sections {
+        #[command(subcommand)]
+        command: SectionCommands,
+    },
+
+    /// Extract FOIA exemption markings and show how often they're cited
+    Exemptions {
+        #[command(subcommand)]
+        command: ExemptionCommands,
+    },
+
+    /// Compare two sources or tagged collections for shared and missing documents
+    Compare {
+        /// Source ID, or tag:<name> for documents with a tag
+        left: String,
+        /// Source ID, or tag:<name> for documents with a tag
+        right: String,
+        /// Text similarity (0-1) above which documents count as near-duplicates
+        #[arg(long, default_value = "0.8")]
+        threshold: f32,
+        /// Documents listed per section
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
     /// List available LLM models
     LlmModels,
 
@@ -422,12 +640,36 @@ enum Commands {
         format: String,
     },
 
+    /// Manage document processing status
+    Docs {
+        #[command(subcommand)]
+        command: DocsCommands,
+    },
+
     /// Show document metadata and info
     Info {
         /// Document ID or search term
         doc_id: String,
     },
 
+    /// Generate a signed chain-of-custody certificate for a document
+    Certify {
+        /// Document ID
+        doc_id: String,
+        /// Output format (json, pdf)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        /// Write to file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Verify the signature on a JSON chain-of-custody certificate
+    VerifyCertificate {
+        /// Certificate file
+        file: PathBuf,
+    },
+
     /// Output document content to stdout
     Read {
         /// Document ID
@@ -449,6 +691,27 @@ enum Commands {
         limit: usize,
     },
 
+    /// Fetch a single URL now, store it, and extract its text
+    Acquire {
+        /// URL to fetch, or the address of the page saved with --from-file
+        url: String,
+        /// Source ID to file the document under (default: adhoc)
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Comma-separated tags to add to the document
+        #[arg(long, value_delimiter = ',')]
+        tag: Vec<String>,
+        /// Store the document without extracting its text
+        #[arg(long)]
+        no_process: bool,
+        /// Store a page saved from the browser (HTML or MHTML) instead of fetching
+        #[arg(long, value_name = "PATH")]
+        from_file: Option<PathBuf>,
+        /// Who captured the page (recorded with --from-file)
+        #[arg(long, requires = "from_file")]
+        submitted_by: Option<String>,
+    },
+
     /// Import documents or URLs from various sources
     Import {
         #[command(subcommand)]
@@ -510,6 +773,364 @@ enum SourceCommands {
         #[arg(long)]
         confirm: bool,
     },
+    /// Copy a source's scraper config to a new source
+    Clone {
+        /// Source to copy
+        existing_id: String,
+        /// New source ID
+        new_id: String,
+        /// Display name for the new source
+        #[arg(long)]
+        name: Option<String>,
+        /// Point the copy at a different site (replaces the old base URL throughout)
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Add a source from a built-in template
+    Add {
+        /// New source ID
+        id: String,
+        /// Template name (see 'foia source templates')
+        #[arg(short, long)]
+        template: String,
+        /// Base URL of the site
+        #[arg(long)]
+        base_url: String,
+        /// Display name for the new source
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// List built-in source templates
+    Templates,
+    /// Pause a source: stop crawling and downloading it until resumed
+    Pause {
+        /// Source ID
+        source_id: String,
+    },
+    /// Resume a paused source
+    Resume {
+        /// Source ID
+        source_id: String,
+    },
+    /// Check sources' listing pages against the expectations in their config's `tests`
+    Test {
+        /// Source ID (default: every source with tests)
+        source_id: Option<String>,
+        /// Use listing pages stored by earlier crawls instead of the live site
+        #[arg(long, conflicts_with = "record")]
+        recorded: bool,
+        /// Store the pages fetched as listing snapshots for later --recorded runs
+        #[arg(long)]
+        record: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AgencyCommands {
+    /// Show the agency tree with source and document totals
+    List,
+    /// Add an agency, or update an existing one
+    Add {
+        /// Agency ID (e.g. fbi)
+        id: String,
+        /// Display name (required for new agencies)
+        #[arg(long)]
+        name: Option<String>,
+        /// Parent agency ID
+        #[arg(long)]
+        parent: Option<String>,
+        /// User agent inherited by member sources
+        #[arg(long)]
+        user_agent: Option<String>,
+        /// Request timeout in seconds inherited by member sources
+        #[arg(long)]
+        request_timeout: Option<u64>,
+        /// Delay between requests in milliseconds inherited by member sources
+        #[arg(long)]
+        request_delay_ms: Option<u64>,
+        /// Member sources skip Tor
+        #[arg(long)]
+        direct: bool,
+    },
+    /// Assign a source to an agency
+    Assign {
+        /// Source ID
+        source_id: String,
+        /// Agency ID
+        agency_id: String,
+    },
+    /// Remove a source from its agency
+    Unassign {
+        /// Source ID
+        source_id: String,
+    },
+    /// Remove an agency (sub-agencies move up to its parent)
+    Remove {
+        /// Agency ID
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasCommands {
+    /// List aliases
+    List,
+    /// Add an alias; searching either side also finds the other
+    Add {
+        /// Term as it appears in documents (e.g. OIG)
+        term: String,
+        /// What it stands for (e.g. "Office of Inspector General")
+        expansion: String,
+        /// acronym, code_name, or person
+        #[arg(short, long, default_value = "acronym")]
+        kind: String,
+    },
+    /// Remove an alias
+    Remove {
+        /// Alias ID (see `foia alias list`)
+        id: i32,
+    },
+}
+
+#[derive(Subcommand)]
+enum AccessCommands {
+    /// List visibility rules
+    List,
+    /// Set who may see a document (or, with --source, a whole source)
+    Set {
+        /// Document ID, or source ID with --source
+        target: String,
+        /// public, internal, or embargo (requires --until)
+        visibility: String,
+        /// Embargo end date (YYYY-MM-DD); public from that day on
+        #[arg(short, long)]
+        until: Option<String>,
+        /// Apply to every document of a source
+        #[arg(short, long)]
+        source: bool,
+        /// Why it is restricted
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    /// Remove a rule; a document falls back to its source's visibility
+    Clear {
+        /// Document ID, or source ID with --source
+        target: String,
+        /// Clear the rule of a source
+        #[arg(short, long)]
+        source: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ApiKeyCommands {
+    /// List keys with their scope and usage
+    List,
+    /// Issue a key; it is shown only once
+    Create {
+        /// Who the key is for
+        name: String,
+        /// Only let the key see this source (repeatable)
+        #[arg(short, long = "source")]
+        sources: Vec<String>,
+        /// Also allow requests that change data (keys are read-only by default)
+        #[arg(long)]
+        read_write: bool,
+        /// Requests per minute (default: access.api_rate_limit; 0 = no limit)
+        #[arg(short, long)]
+        rate_limit: Option<u32>,
+    },
+    /// Revoke a key; requests with it are refused from then on
+    Revoke {
+        /// Key ID (see `foia api-key list`)
+        id: i32,
+    },
+    /// Requests per day made with a key
+    Usage {
+        /// Key ID (see `foia api-key list`)
+        id: i32,
+        /// Number of days to show
+        #[arg(long, default_value = "30")]
+        days: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum TranscribeCommands {
+    /// Queue pages with no text or low OCR quality for transcription
+    Queue {
+        /// Only pages of this source
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Queue pages whose best OCR quality is below this (0-1)
+        #[arg(long, default_value_t = foia::services::transcription::DEFAULT_MAX_QUALITY)]
+        below: f64,
+        /// Most pages to queue
+        #[arg(short, long, default_value = "500")]
+        limit: u32,
+    },
+    /// Tasks by status, and those waiting for a curator
+    Status,
+}
+
+#[derive(Subcommand)]
+enum SearchIndexCommands {
+    /// Index every document from scratch
+    Rebuild,
+    /// Index documents changed since the last update and drop deleted ones
+    Update,
+    /// Show the index location, size and last update
+    Status,
+}
+
+#[derive(Subcommand)]
+enum BatesCommands {
+    /// Find Bates stamps on document pages and record their ranges
+    Extract {
+        /// Source ID (optional, processes all sources if not specified)
+        source_id: Option<String>,
+        /// Limit number of documents to process (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+    },
+    /// Find the document and page carrying a Bates number
+    Lookup {
+        /// Bates number (e.g. DOJ-OGR-00012345)
+        number: String,
+        /// Filter by source ID
+        #[arg(short, long)]
+        source: Option<String>,
+    },
+    /// List numbers missing between a source's Bates ranges
+    Gaps {
+        /// Source ID
+        source_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SectionCommands {
+    /// Find chapters and sections from PDF bookmarks or page headings
+    Extract {
+        /// Source ID (optional, processes all sources if not specified)
+        source_id: Option<String>,
+        /// Limit number of documents to process (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+    },
+    /// Show a document's table of contents
+    Show {
+        /// Document ID
+        doc_id: String,
+        /// Derive the sections again before showing them
+        #[arg(long)]
+        refresh: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExemptionCommands {
+    /// Find exemption markings ((b)(5), b7C, ...) on document pages
+    Extract {
+        /// Source ID (optional, processes all sources if not specified)
+        source_id: Option<String>,
+        /// Limit number of documents to process (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+    },
+    /// Show how many documents and pages cite each exemption
+    Stats {
+        /// Filter by source ID
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Only this exemption (e.g. b5, "(b)(7)(C)")
+        #[arg(long)]
+        code: Option<String>,
+        /// Break down by publication date: year or month
+        #[arg(long)]
+        by: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommands {
+    /// Run export jobs that are due, or one job now
+    Run {
+        /// Job name (default: every job that is due)
+        job: Option<String>,
+        /// Run continuously, exporting jobs as they come due
+        #[arg(long)]
+        daemon: bool,
+        /// Seconds to wait between checks in daemon mode
+        #[arg(long, default_value = "300")]
+        interval: u64,
+    },
+    /// List configured export jobs and their last run
+    List,
+    /// Show export run history, newest first
+    History {
+        /// Only this job
+        job: Option<String>,
+        /// Maximum runs to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+    /// Write a source's files into a directory, rebuilding the folder
+    /// structure of the production they were imported from
+    Production {
+        /// Source ID
+        source_id: String,
+        /// Directory to write (must be empty or not exist)
+        dir: PathBuf,
+        /// File names: original (paths from the import), bates (by Bates
+        /// range) or hash (content hash)
+        #[arg(long, default_value = "original")]
+        layout: String,
+        /// Also export internal and embargoed documents
+        #[arg(long)]
+        include_restricted: bool,
+    },
+    /// Write documents, versions, pages, requests and entities as typed
+    /// tables for DuckDB, building the database if duckdb is installed
+    Duckdb {
+        /// Directory to write (must be empty or not exist)
+        dir: PathBuf,
+        /// Only this source (default: the whole archive)
+        #[arg(long)]
+        source: Option<String>,
+        /// Also write each table as a Parquet file
+        #[arg(long)]
+        parquet: bool,
+        /// Also export internal and embargoed documents
+        #[arg(long)]
+        include_restricted: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SyncCommands {
+    /// Send local changes to a remote instance
+    Push {
+        /// Remote name (from sync.remotes) or server URL
+        remote: String,
+        /// Sync token (overrides the remote's configured token)
+        #[arg(long, env = "FOIA_SYNC_TOKEN")]
+        token: Option<String>,
+        /// Show what would be transferred without sending anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Fetch changes from a remote instance
+    Pull {
+        /// Remote name (from sync.remotes) or server URL
+        remote: String,
+        /// Sync token (overrides the remote's configured token)
+        #[arg(long, env = "FOIA_SYNC_TOKEN")]
+        token: Option<String>,
+        /// Show what would be transferred without fetching anything
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -532,6 +1153,57 @@ enum ConfigCommands {
         /// Value to set (JSON for complex types)
         value: String,
     },
+    /// List saved versions of the scraper configs, most recent first
+    History,
+    /// Show what changed between two versions of the scraper configs
+    Diff {
+        /// Hash (or prefix) of the older version
+        from: String,
+        /// Hash (or prefix) of the newer version (default: the configs in use now)
+        to: Option<String>,
+    },
+    /// Restore the scraper configs saved in a version
+    Rollback {
+        /// Hash (or prefix) of the version to restore
+        hash: String,
+    },
+    /// Replace a source's scraper config with JSON from a file or stdin
+    Edit {
+        /// Source ID
+        source_id: String,
+        /// JSON file with the new config (default: read stdin)
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+    /// Print the loaded config, with the selected profile applied
+    Show {
+        /// Also apply the source settings stored in the database and show
+        /// the resolved paths and connections
+        #[arg(long)]
+        effective: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretsCommands {
+    /// Store a secret (the value is prompted for, or read from stdin)
+    Set {
+        /// Secret name, referenced as secret://<name>
+        name: String,
+        /// Store in the OS keychain instead of the secrets file
+        #[arg(long)]
+        keychain: bool,
+    },
+    /// List secret names (values are never shown)
+    List,
+    /// Remove a secret
+    Remove {
+        /// Secret name
+        name: String,
+        /// Remove from the OS keychain instead of the secrets file
+        #[arg(long)]
+        keychain: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -549,6 +1221,185 @@ enum StateCommands {
         #[arg(long)]
         confirm: bool,
     },
+    /// Export every logged request to a domain as CSV, with traffic totals
+    Report {
+        /// Domain to report on (subdomains are included)
+        domain: String,
+        /// Only include requests on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only include requests before this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Write the CSV to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Show fingerprinted API response shapes and unacknowledged drift
+    Schema {
+        /// Source ID (optional, shows all if not specified)
+        source_id: Option<String>,
+        /// Acknowledge drift for the source, accepting the current shape
+        #[arg(long, requires = "source_id")]
+        ack: bool,
+        /// Only acknowledge drift on this endpoint
+        #[arg(long, requires = "ack")]
+        endpoint: Option<String>,
+    },
+    /// Replay stored listing pages through a config offline and show what it would discover
+    Simulate {
+        /// Source ID
+        source_id: String,
+        /// Candidate scraper config (JSON); defaults to the current config
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Maximum URLs to list per section
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// Delete archived listing pages past their retention (the latest of each page is kept)
+    PruneSnapshots {
+        /// Source ID (optional, prunes all sources if not specified)
+        source_id: Option<String>,
+        /// Delete snapshots not seen for this many days (default: each source's snapshot_ttl_days)
+        #[arg(long)]
+        days: Option<u64>,
+    },
+    /// Show each stage's backlog, recent throughput and estimated completion
+    Eta {
+        /// Source ID (optional, covers all sources if not specified)
+        source_id: Option<String>,
+        /// Minutes of recent activity to measure throughput over
+        #[arg(short, long, default_value = "60")]
+        window: u64,
+    },
+    /// List domains paused after blocking too many requests (403/429/451)
+    Incidents {
+        /// Maximum number of incidents to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// End a domain's pause before its cool-down runs out
+    ResumeDomain {
+        /// Domain to resume (e.g. vault.fbi.gov)
+        domain: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CaptchaCommands {
+    /// List detected challenges, newest first
+    List {
+        /// Only show challenges with this status (pending, solved, dismissed)
+        #[arg(long)]
+        status: Option<String>,
+        /// Maximum number of challenges to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Solve a pending challenge in a browser window and resume its domain
+    Solve {
+        /// Challenge ID
+        id: i64,
+        /// Use this Cookie header (e.g. "cf_clearance=...") instead of opening a browser
+        #[arg(long)]
+        cookies: Option<String>,
+    },
+    /// Dismiss a pending challenge and resume its domain without cookies
+    Dismiss {
+        /// Challenge ID
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum DocsCommands {
+    /// Reset documents to an earlier processing status so they are reprocessed
+    Requeue {
+        /// Status of the documents to requeue
+        #[arg(long)]
+        from_status: String,
+        /// Status to reset them to
+        #[arg(long, default_value = "downloaded")]
+        to_status: String,
+        /// Only requeue documents from this source
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Confirm the reset (otherwise only shows what would change)
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Generate a document encryption key
+    Keygen {
+        /// Write the hex-encoded key to this file (must not exist)
+        #[arg(long, conflicts_with = "keychain")]
+        key_file: Option<PathBuf>,
+        /// Store the key in this OS keychain entry instead
+        #[arg(long)]
+        keychain: Option<String>,
+    },
+    /// Encrypt existing document files in place with the configured key
+    Encrypt {
+        /// Confirm the migration (otherwise only shows what would change)
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Decrypt document files in place with the configured key
+    Decrypt {
+        /// Confirm the migration (otherwise only shows what would change)
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Delete originals in text-only retention sources once their text is verified
+    Prune {
+        /// Only prune this source
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Delete the files (otherwise only reports what would be reclaimed)
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Delete near-identical versions of re-fetched pages in sources with keep_versions set
+    PruneVersions {
+        /// Only prune this source
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Delete the versions (otherwise only reports what would be reclaimed)
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Place a document under legal hold so pruning never deletes it
+    Hold {
+        /// Document ID
+        doc_id: String,
+        /// Why the document is held (e.g. a case or request number)
+        #[arg(long, required_unless_present = "release")]
+        reason: Option<String>,
+        /// Release the hold instead
+        #[arg(long, conflicts_with = "reason")]
+        release: bool,
+    },
+    /// List documents under legal hold
+    Holds {
+        /// Only list holds in this source
+        #[arg(short, long)]
+        source: Option<String>,
+    },
+    /// Split a multi-document PDF bundle into one document per record
+    Split {
+        /// Document ID of the bundle
+        doc_id: String,
+        /// Boundary signals to use: bates, numbering, blank, letterhead, llm
+        /// (default: all but llm)
+        #[arg(long, value_delimiter = ',')]
+        by: Vec<String>,
+        /// Fold documents shorter than this into the one before
+        #[arg(long, default_value = "1")]
+        min_pages: u32,
+        /// Create the documents (otherwise only shows where the bundle would split)
+        #[arg(long)]
+        confirm: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -658,6 +1509,28 @@ enum AnnotateCommands {
         #[arg(long)]
         confirm: bool,
     },
+    /// Regenerate summaries from an older model or prompt version
+    Resummarize {
+        /// Source ID (optional, all sources if not specified)
+        #[arg(long)]
+        source_id: Option<String>,
+        /// Summaries generated before this date (YYYY-MM-DD or RFC 3339),
+        /// e.g. when the model was changed
+        #[arg(long, value_name = "DATE")]
+        model_older_than: Option<String>,
+        /// Summaries not generated with this prompt version
+        #[arg(long, value_name = "VERSION")]
+        prompt_version: Option<String>,
+        /// Limit number of documents to requeue (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+        /// Show matching documents without requeueing them
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip confirmation prompt
+        #[arg(long)]
+        confirm: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -745,13 +1618,146 @@ enum ImportCommands {
         dry_run: bool,
         /// Disable resume support
         #[arg(long)]
-        no_resume: bool,
-        /// Move files instead of copying (deletes originals after import)
-        #[arg(long, conflicts_with = "link")]
-        r#move: bool,
-        /// Use hard links instead of copying (saves disk space)
-        #[arg(long, conflicts_with = "r#move")]
-        link: bool,
+        no_resume: bool,
+        /// Move files instead of copying (deletes originals after import)
+        #[arg(long, conflicts_with = "link")]
+        r#move: bool,
+        /// Use hard links instead of copying (saves disk space)
+        #[arg(long, conflicts_with = "r#move")]
+        link: bool,
+    },
+
+    /// Import scanned files dropped into an intake (watch) folder
+    Intake {
+        /// Intake directory; imported files are moved to its imported/ subfolder
+        dir: PathBuf,
+        /// Source ID to associate imported documents with (required)
+        #[arg(short, long)]
+        source: String,
+        /// Filename pattern mapping name parts to metadata ({title} and {date} are special)
+        #[arg(short, long, default_value = "{title}")]
+        pattern: String,
+        /// Comma-separated tags to apply to all imported documents
+        #[arg(long, value_delimiter = ',')]
+        tag: Vec<String>,
+        /// Dry run - show what would be imported without saving
+        #[arg(long)]
+        dry_run: bool,
+        /// Keep watching the folder for new files
+        #[arg(long)]
+        daemon: bool,
+        /// Seconds to wait between scans in daemon mode (default: 30)
+        #[arg(long, default_value = "30")]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum RepairCommands {
+    /// Re-fetch versions whose files are missing, marking unrecoverable ones lost
+    Redownload {
+        /// Only repair this source
+        source_id: Option<String>,
+        /// Maximum number of missing files to process (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+        /// Delay between requests in milliseconds
+        #[arg(long, default_value = "2000")]
+        delay_ms: u64,
+        /// List missing files without fetching anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Also retry versions already marked lost
+        #[arg(long)]
+        retry_lost: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportCommands {
+    /// Report documents acquired, growth by source, bytes, OCR coverage and
+    /// notable new records over a period
+    Generate {
+        /// Start of the period (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        from: String,
+        /// End of the period, exclusive (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        to: String,
+        /// Output file (default: report-FROM-TO.FORMAT)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Output format: html, odt or pdf (default: from the output
+        /// extension, else html). odt and pdf need LibreOffice
+        #[arg(short, long)]
+        format: Option<String>,
+        /// HTML template with {{placeholder}} fields (see 'foia report template')
+        #[arg(long)]
+        template: Option<PathBuf>,
+        /// Report title
+        #[arg(long)]
+        title: Option<String>,
+        /// Number of notable new records to list
+        #[arg(long, default_value_t = foia::services::report::DEFAULT_NOTABLE)]
+        notable: u32,
+    },
+    /// Print the default report template
+    Template,
+}
+
+#[derive(Subcommand)]
+enum TitlesCommands {
+    /// Suggest titles from first-page text (or an LLM) for documents with
+    /// generic titles. Sources with processing.apply_titles get them applied
+    Improve {
+        /// Source ID (optional, processes all sources if not specified)
+        source_id: Option<String>,
+        /// Limit number of documents to process (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        limit: usize,
+        /// Ask the configured LLM for titles instead of reading the first page
+        #[arg(long)]
+        llm: bool,
+    },
+    /// List suggestions awaiting review (suggested or applied)
+    Review {
+        /// Only suggestions for documents of this source
+        source_id: Option<String>,
+        /// List suggestions with this status instead: suggested, applied,
+        /// accepted or rejected
+        #[arg(long)]
+        status: Option<String>,
+        /// Maximum suggestions to list
+        #[arg(short, long, default_value = "50")]
+        limit: u32,
+    },
+    /// Accept a suggestion, giving the document its suggested title
+    Accept {
+        /// Document ID
+        doc_id: String,
+    },
+    /// Reject a suggestion, restoring the document's original title
+    Reject {
+        /// Document ID
+        doc_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageCommands {
+    /// Break down storage usage and list what can be reclaimed
+    Report {
+        /// Also list every orphaned file
+        #[arg(long)]
+        orphans: bool,
+    },
+    /// Reclaim one category of waste: failed or orphans
+    Clean {
+        /// Category to reclaim (failed, orphans)
+        category: String,
+        /// Delete (otherwise only reports what would be reclaimed)
+        #[arg(long)]
+        confirm: bool,
     },
 }
 
@@ -811,6 +1817,26 @@ enum DbCommands {
         batch_size: usize,
     },
 
+    /// Compress stored page text and report the space saved
+    CompressText {
+        /// Only report the savings, don't rewrite any rows
+        #[arg(long)]
+        dry_run: bool,
+        /// Pages per batch (default: 1000)
+        #[arg(long, default_value = "1000")]
+        batch_size: usize,
+    },
+
+    /// Rebuild the document, tag and type count tables from scratch
+    RefreshStats,
+
+    /// Explain hot queries, report full table scans and table sizes
+    Analyze {
+        /// Create the recommended indexes for queries that scan whole tables
+        #[arg(long)]
+        apply: bool,
+    },
+
     /// Deduplicate documents by content hash
     Deduplicate {
         /// Only show what would be deleted, don't actually delete
@@ -838,14 +1864,23 @@ enum DbCommands {
 
 /// Run the CLI.
 pub async fn run() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    if cli.json {
+        output::enable_json()?;
+    }
+    let command = command_path(&matches);
 
     let options = LoadOptions {
         config_path: cli.config,
         use_cwd: cli.cwd,
         data: cli.data,
+        profile: cli.profile,
+        overrides: cli.overrides,
     };
-    let (mut settings, mut config) = load_settings_with_options(options).await;
+    let (mut settings, mut config) = load_settings_with_options(options)
+        .await
+        .map_err(anyhow::Error::msg)?;
 
     if cli.no_tls {
         settings.no_tls = true;
@@ -859,6 +1894,24 @@ pub async fn run() -> anyhow::Result<()> {
         cli.no_tor_warning,
     );
 
+    // Install the document encryption key before any command touches storage,
+    // and the secret store that resolves secret:// references
+    let generating_key = matches!(
+        cli.command,
+        Commands::Docs {
+            command: DocsCommands::Keygen { .. }
+        }
+    );
+    if !generating_key {
+        if let Some(key) = foia::encryption::ContentKey::from_config(&config)? {
+            foia::encryption::install_key(key);
+        }
+        foia::secrets::install_store(foia::secrets::SecretStore::from_config(&config)?);
+    }
+
+    // Transparency mode: identify the crawler on every request
+    foia::http_client::install_identification(&config.transparency);
+
     // Show Tor legality warning (can be disabled)
     config.privacy.show_tor_legal_warning();
 
@@ -867,10 +1920,47 @@ pub async fn run() -> anyhow::Result<()> {
         cli.command,
         Commands::Init
             | Commands::Source { .. }
+            | Commands::Agency { .. }
+            | Commands::Alias { .. }
+            | Commands::Access { .. }
+            | Commands::ApiKey { .. }
+            | Commands::Transcribe { .. }
+            | Commands::SearchIndex { .. }
+            | Commands::Storage { .. }
+            | Commands::Report { .. }
+            | Commands::Export {
+                command: ExportCommands::Production { .. } | ExportCommands::Duckdb { .. }
+            }
+            | Commands::Titles {
+                command: TitlesCommands::Review { .. }
+                    | TitlesCommands::Accept { .. }
+                    | TitlesCommands::Reject { .. }
+            }
             | Commands::Config { .. }
+            | Commands::Secrets { .. }
             | Commands::Serve { .. }
             | Commands::BackfillEntities { .. }
             | Commands::SearchEntities { .. }
+            | Commands::Bates { .. }
+            | Commands::Sections { .. }
+            | Commands::Exemptions { .. }
+            | Commands::Compare { .. }
+            | Commands::Tui { .. }
+            | Commands::Certify { .. }
+            | Commands::VerifyCertificate { .. }
+            | Commands::Docs {
+                command: DocsCommands::PruneVersions { .. }
+                    | DocsCommands::Hold { .. }
+                    | DocsCommands::Holds { .. }
+            }
+    ) || matches!(
+        cli.command,
+        Commands::Source {
+            command: SourceCommands::Test {
+                recorded: false,
+                ..
+            }
+        }
     );
     if needs_tor {
         if let Err(e) = config.privacy.check_tor_availability() {
@@ -883,7 +1973,9 @@ pub async fn run() -> anyhow::Result<()> {
         config.privacy.enforce_security_warning().await;
     }
 
-    match cli.command {
+    output::emit("started", serde_json::json!({ "command": command }));
+    let started = std::time::Instant::now();
+    let result = match cli.command {
         Commands::Init => init::cmd_init(&settings).await,
         Commands::Source { command } => match command {
             SourceCommands::List => source::cmd_source_list(&settings).await,
@@ -892,10 +1984,191 @@ pub async fn run() -> anyhow::Result<()> {
                 new_id,
                 confirm,
             } => source::cmd_source_rename(&settings, &old_id, &new_id, confirm).await,
+            SourceCommands::Clone {
+                existing_id,
+                new_id,
+                name,
+                base_url,
+            } => source::cmd_source_clone(&settings, &existing_id, &new_id, name, base_url).await,
+            SourceCommands::Add {
+                id,
+                template,
+                base_url,
+                name,
+            } => source::cmd_source_add(&settings, &id, &template, &base_url, name).await,
+            SourceCommands::Templates => source::cmd_source_templates(),
+            SourceCommands::Pause { source_id } => {
+                source::cmd_source_pause(&settings, &source_id, true).await
+            }
+            SourceCommands::Resume { source_id } => {
+                source::cmd_source_pause(&settings, &source_id, false).await
+            }
+            SourceCommands::Test {
+                source_id,
+                recorded,
+                record,
+            } => {
+                source::cmd_source_test(&settings, &config, source_id.as_deref(), recorded, record)
+                    .await
+            }
         },
-        Commands::Crawl { source_id, limit } => {
-            state::cmd_crawl(&settings, &source_id, limit).await
-        }
+        Commands::Agency { command } => match command {
+            AgencyCommands::List => agency::cmd_agency_list(&settings).await,
+            AgencyCommands::Add {
+                id,
+                name,
+                parent,
+                user_agent,
+                request_timeout,
+                request_delay_ms,
+                direct,
+            } => {
+                let args = agency::AgencyArgs {
+                    name,
+                    parent,
+                    user_agent,
+                    request_timeout,
+                    request_delay_ms,
+                    direct,
+                };
+                agency::cmd_agency_add(&settings, &id, args).await
+            }
+            AgencyCommands::Assign {
+                source_id,
+                agency_id,
+            } => agency::cmd_agency_assign(&settings, &source_id, &agency_id).await,
+            AgencyCommands::Unassign { source_id } => {
+                agency::cmd_agency_unassign(&settings, &source_id).await
+            }
+            AgencyCommands::Remove { id } => agency::cmd_agency_remove(&settings, &id).await,
+        },
+        Commands::Alias { command } => match command {
+            AliasCommands::List => alias::cmd_alias_list(&settings).await,
+            AliasCommands::Add {
+                term,
+                expansion,
+                kind,
+            } => alias::cmd_alias_add(&settings, &term, &expansion, &kind).await,
+            AliasCommands::Remove { id } => alias::cmd_alias_remove(&settings, id).await,
+        },
+        Commands::Access { command } => match command {
+            AccessCommands::List => access::cmd_access_list(&settings).await,
+            AccessCommands::Set {
+                target,
+                visibility,
+                until,
+                source,
+                note,
+            } => {
+                access::cmd_access_set(
+                    &settings,
+                    &target,
+                    &visibility,
+                    until.as_deref(),
+                    source,
+                    note.as_deref(),
+                )
+                .await
+            }
+            AccessCommands::Clear { target, source } => {
+                access::cmd_access_clear(&settings, &target, source).await
+            }
+        },
+        Commands::ApiKey { command } => match command {
+            ApiKeyCommands::List => api_key::cmd_api_key_list(&settings).await,
+            ApiKeyCommands::Create {
+                name,
+                sources,
+                read_write,
+                rate_limit,
+            } => {
+                api_key::cmd_api_key_create(&settings, &name, sources, read_write, rate_limit).await
+            }
+            ApiKeyCommands::Revoke { id } => api_key::cmd_api_key_revoke(&settings, id).await,
+            ApiKeyCommands::Usage { id, days } => {
+                api_key::cmd_api_key_usage(&settings, id, days).await
+            }
+        },
+        Commands::Transcribe { command } => match command {
+            TranscribeCommands::Queue {
+                source,
+                below,
+                limit,
+            } => {
+                transcription::cmd_transcribe_queue(&settings, source.as_deref(), below, limit)
+                    .await
+            }
+            TranscribeCommands::Status => transcription::cmd_transcribe_status(&settings).await,
+        },
+        Commands::SearchIndex { command } => match command {
+            SearchIndexCommands::Rebuild => {
+                search_index::cmd_search_index_rebuild(&settings, &config).await
+            }
+            SearchIndexCommands::Update => {
+                search_index::cmd_search_index_update(&settings, &config).await
+            }
+            SearchIndexCommands::Status => {
+                search_index::cmd_search_index_status(&settings, &config).await
+            }
+        },
+        Commands::Sync { command } => match command {
+            SyncCommands::Push {
+                remote,
+                token,
+                dry_run,
+            } => sync::cmd_sync_push(&settings, &config, &remote, token, dry_run).await,
+            SyncCommands::Pull {
+                remote,
+                token,
+                dry_run,
+            } => sync::cmd_sync_pull(&settings, &config, &remote, token, dry_run).await,
+        },
+        Commands::Export { command } => match command {
+            ExportCommands::Run {
+                job,
+                daemon,
+                interval,
+            } => export::cmd_export_run(&settings, &config, job.as_deref(), daemon, interval).await,
+            ExportCommands::List => export::cmd_export_list(&settings, &config).await,
+            ExportCommands::History { job, limit } => {
+                export::cmd_export_history(&settings, job.as_deref(), limit).await
+            }
+            ExportCommands::Production {
+                source_id,
+                dir,
+                layout,
+                include_restricted,
+            } => {
+                export::cmd_export_production(
+                    &settings,
+                    &source_id,
+                    &dir,
+                    &layout,
+                    include_restricted,
+                )
+                .await
+            }
+            ExportCommands::Duckdb {
+                dir,
+                source,
+                parquet,
+                include_restricted,
+            } => {
+                export::cmd_export_duckdb(
+                    &settings,
+                    &dir,
+                    source.as_deref(),
+                    parquet,
+                    include_restricted,
+                )
+                .await
+            }
+        },
+        Commands::Crawl {
+            source_id,
+            limit,
+            wait,
+        } => state::cmd_crawl(&settings, &source_id, limit, wait).await,
         Commands::Download {
             source_id,
             workers,
@@ -919,6 +2192,53 @@ pub async fn run() -> anyhow::Result<()> {
             StateCommands::Clear { source_id, confirm } => {
                 state::cmd_crawl_clear(&settings, &source_id, confirm).await
             }
+            StateCommands::Report {
+                domain,
+                since,
+                until,
+                output,
+            } => {
+                state::cmd_request_report(
+                    &settings,
+                    &domain,
+                    since.as_deref(),
+                    until.as_deref(),
+                    output.as_deref(),
+                )
+                .await
+            }
+            StateCommands::Schema {
+                source_id,
+                ack,
+                endpoint,
+            } => {
+                state::cmd_api_schema(&settings, source_id.as_deref(), ack, endpoint.as_deref())
+                    .await
+            }
+            StateCommands::Simulate {
+                source_id,
+                config,
+                limit,
+            } => state::cmd_crawl_simulate(&settings, &source_id, config.as_deref(), limit).await,
+            StateCommands::PruneSnapshots { source_id, days } => {
+                state::cmd_prune_snapshots(&settings, source_id.as_deref(), days).await
+            }
+            StateCommands::Eta { source_id, window } => {
+                state::cmd_eta(&settings, source_id.as_deref(), window).await
+            }
+            StateCommands::Incidents { limit } => state::cmd_incidents(&settings, limit).await,
+            StateCommands::ResumeDomain { domain } => {
+                state::cmd_resume_domain(&settings, &domain).await
+            }
+        },
+        Commands::Captcha { command } => match command {
+            CaptchaCommands::List { status, limit } => {
+                captcha::cmd_captcha_list(&settings, status.as_deref(), limit).await
+            }
+            CaptchaCommands::Solve { id, cookies } => {
+                captcha::cmd_captcha_solve(&settings, id, cookies).await
+            }
+            CaptchaCommands::Dismiss { id } => captcha::cmd_captcha_dismiss(&settings, id).await,
         },
         Commands::Config { command } => match command {
             ConfigCommands::Transfer { file } => {
@@ -930,6 +2250,28 @@ pub async fn run() -> anyhow::Result<()> {
             ConfigCommands::Set { setting, value } => {
                 config_cmd::cmd_config_set(&settings, &setting, &value).await
             }
+            ConfigCommands::History => config_cmd::cmd_config_history(&settings).await,
+            ConfigCommands::Diff { from, to } => {
+                config_cmd::cmd_config_diff(&settings, &from, to.as_deref()).await
+            }
+            ConfigCommands::Rollback { hash } => {
+                config_cmd::cmd_config_rollback(&settings, &hash).await
+            }
+            ConfigCommands::Edit { source_id, file } => {
+                config_cmd::cmd_config_edit(&settings, &source_id, file.as_deref()).await
+            }
+            ConfigCommands::Show { effective } => {
+                config_cmd::cmd_config_show(&settings, &config, effective).await
+            }
+        },
+        Commands::Secrets { command } => match command {
+            SecretsCommands::Set { name, keychain } => {
+                secrets::cmd_secrets_set(&config, &name, keychain)
+            }
+            SecretsCommands::List => secrets::cmd_secrets_list(&config),
+            SecretsCommands::Remove { name, keychain } => {
+                secrets::cmd_secrets_remove(&config, &name, keychain)
+            }
         },
         Commands::Db { command } => match command {
             DbCommands::Migrate { check, force } => db::cmd_migrate(&settings, check, force).await,
@@ -962,6 +2304,12 @@ pub async fn run() -> anyhow::Result<()> {
                 dry_run,
                 batch_size,
             } => db::cmd_db_remap_categories(&settings, dry_run, batch_size).await,
+            DbCommands::CompressText {
+                dry_run,
+                batch_size,
+            } => db::cmd_db_compress_text(&settings, dry_run, batch_size).await,
+            DbCommands::RefreshStats => db::cmd_db_refresh_stats(&settings).await,
+            DbCommands::Analyze { apply } => db::cmd_db_analyze(&settings, apply).await,
             DbCommands::Deduplicate {
                 dry_run,
                 keep,
@@ -973,6 +2321,57 @@ pub async fn run() -> anyhow::Result<()> {
                 regions::cmd_load_regions(&settings, file.as_deref()).await
             }
         },
+        Commands::Repair { command } => match command {
+            RepairCommands::Redownload {
+                source_id,
+                limit,
+                delay_ms,
+                dry_run,
+                retry_lost,
+            } => {
+                repair::cmd_repair_redownload(
+                    &settings,
+                    source_id.as_deref(),
+                    limit,
+                    delay_ms,
+                    dry_run,
+                    retry_lost,
+                    &config.privacy,
+                )
+                .await
+            }
+        },
+        Commands::Report { command } => match command {
+            ReportCommands::Generate {
+                from,
+                to,
+                output,
+                format,
+                template,
+                title,
+                notable,
+            } => {
+                let options = report::GenerateOptions {
+                    from: &from,
+                    to: &to,
+                    output,
+                    format: format.as_deref(),
+                    template,
+                    title,
+                    notable,
+                };
+                report::cmd_report_generate(&settings, options).await
+            }
+            ReportCommands::Template => report::cmd_report_template(),
+        },
+        Commands::Storage { command } => match command {
+            StorageCommands::Report { orphans } => {
+                storage::cmd_storage_report(&settings, orphans).await
+            }
+            StorageCommands::Clean { category, confirm } => {
+                storage::cmd_storage_clean(&settings, &category, confirm).await
+            }
+        },
         Commands::Scrape {
             source_ids,
             all,
@@ -999,13 +2398,30 @@ pub async fn run() -> anyhow::Result<()> {
             )
             .await
         }
+        Commands::Run {
+            source_id,
+            from,
+            skip,
+            workers,
+            analysis_workers,
+            limit,
+        } => {
+            let options = pipeline::RunOptions {
+                from,
+                skip: &skip,
+                workers,
+                analysis_workers,
+                limit,
+            };
+            pipeline::cmd_run(&settings, &source_id, options, &config.privacy).await
+        }
         Commands::Status {
             url,
             source_id,
             live,
             interval,
-            json,
-        } => scrape::cmd_status(&settings, url, source_id, live, interval, json).await,
+        } => scrape::cmd_status(&settings, url, source_id, live, interval).await,
+        Commands::Tui { interval } => dashboard::cmd_tui(&settings, interval).await,
         Commands::Analyze {
             source_id,
             doc_id,
@@ -1051,11 +2467,27 @@ pub async fn run() -> anyhow::Result<()> {
             backends,
             deepseek_path,
         } => analyze::cmd_analyze_compare(&file, pages.as_deref(), &backends, deepseek_path).await,
+        Commands::AnalyzeArbitrate {
+            source_id,
+            doc_id,
+            limit,
+            dry_run,
+        } => {
+            analyze::cmd_analyze_arbitrate(
+                &settings,
+                source_id.as_deref(),
+                doc_id.as_deref(),
+                limit,
+                dry_run,
+            )
+            .await
+        }
         Commands::Serve {
             bind,
             no_migrate,
             no_hidden_service,
             use_arti,
+            read_only,
         } => {
             serve::cmd_serve(
                 &settings,
@@ -1064,6 +2496,7 @@ pub async fn run() -> anyhow::Result<()> {
                 no_migrate,
                 no_hidden_service,
                 use_arti,
+                read_only,
             )
             .await
         }
@@ -1100,6 +2533,25 @@ pub async fn run() -> anyhow::Result<()> {
             Some(AnnotateCommands::Reset { source_id, confirm }) => {
                 annotate::cmd_annotate_reset(&settings, source_id.as_deref(), confirm).await
             }
+            Some(AnnotateCommands::Resummarize {
+                source_id,
+                model_older_than,
+                prompt_version,
+                limit,
+                dry_run,
+                confirm,
+            }) => {
+                annotate::cmd_annotate_resummarize(
+                    &settings,
+                    source_id.as_deref(),
+                    model_older_than.as_deref(),
+                    prompt_version,
+                    limit,
+                    dry_run,
+                    confirm,
+                )
+                .await
+            }
             None => {
                 let strategy = if deep {
                     ExecutionStrategy::Deep
@@ -1130,6 +2582,32 @@ pub async fn run() -> anyhow::Result<()> {
         Commands::ExtractEntities { source_id, limit } => {
             annotate::cmd_extract_entities(&settings, source_id.as_deref(), limit).await
         }
+        Commands::Classify {
+            source_id,
+            limit,
+            llm,
+        } => annotate::cmd_classify(&settings, source_id.as_deref(), limit, llm).await,
+        Commands::Titles { command } => match command {
+            TitlesCommands::Improve {
+                source_id,
+                limit,
+                llm,
+            } => annotate::cmd_improve_titles(&settings, source_id.as_deref(), limit, llm).await,
+            TitlesCommands::Review {
+                source_id,
+                status,
+                limit,
+            } => {
+                titles::cmd_titles_review(&settings, source_id.as_deref(), status.as_deref(), limit)
+                    .await
+            }
+            TitlesCommands::Accept { doc_id } => {
+                titles::cmd_titles_decide(&settings, &doc_id, true).await
+            }
+            TitlesCommands::Reject { doc_id } => {
+                titles::cmd_titles_decide(&settings, &doc_id, false).await
+            }
+        },
         Commands::BackfillEntities { source_id, limit } => {
             entities::cmd_backfill_entities(&settings, source_id.as_deref(), limit).await
         }
@@ -1150,6 +2628,43 @@ pub async fn run() -> anyhow::Result<()> {
             )
             .await
         }
+        Commands::Bates { command } => match command {
+            BatesCommands::Extract { source_id, limit } => {
+                annotate::cmd_extract_bates(&settings, source_id.as_deref(), limit).await
+            }
+            BatesCommands::Lookup { number, source } => {
+                bates::cmd_bates_lookup(&settings, &number, source.as_deref()).await
+            }
+            BatesCommands::Gaps { source_id } => bates::cmd_bates_gaps(&settings, &source_id).await,
+        },
+        Commands::Sections { command } => match command {
+            SectionCommands::Extract { source_id, limit } => {
+                annotate::cmd_extract_sections(&settings, source_id.as_deref(), limit).await
+            }
+            SectionCommands::Show { doc_id, refresh } => {
+                sections::cmd_sections_show(&settings, &doc_id, refresh).await
+            }
+        },
+        Commands::Exemptions { command } => match command {
+            ExemptionCommands::Extract { source_id, limit } => {
+                annotate::cmd_extract_exemptions(&settings, source_id.as_deref(), limit).await
+            }
+            ExemptionCommands::Stats { source, code, by } => {
+                exemptions::cmd_exemption_stats(
+                    &settings,
+                    source.as_deref(),
+                    code.as_deref(),
+                    by.as_deref(),
+                )
+                .await
+            }
+        },
+        Commands::Compare {
+            left,
+            right,
+            threshold,
+            limit,
+        } => compare::cmd_compare(&settings, &left, &right, threshold, limit).await,
         Commands::LlmModels => llm::cmd_llm_models(&settings).await,
         Commands::Archive {
             source_id,
@@ -1173,13 +2688,96 @@ pub async fn run() -> anyhow::Result<()> {
             )
             .await
         }
+        Commands::Docs { command } => match command {
+            DocsCommands::Requeue {
+                from_status,
+                to_status,
+                source,
+                confirm,
+            } => {
+                documents::cmd_requeue(
+                    &settings,
+                    &from_status,
+                    &to_status,
+                    source.as_deref(),
+                    confirm,
+                )
+                .await
+            }
+            DocsCommands::Keygen { key_file, keychain } => {
+                encryption::cmd_keygen(key_file.as_deref(), keychain.as_deref())
+            }
+            DocsCommands::Encrypt { confirm } => encryption::cmd_migrate(&settings, true, confirm),
+            DocsCommands::Decrypt { confirm } => encryption::cmd_migrate(&settings, false, confirm),
+            DocsCommands::Prune { source, confirm } => {
+                retention::cmd_prune(&settings, source.as_deref(), confirm).await
+            }
+            DocsCommands::PruneVersions { source, confirm } => {
+                retention::cmd_prune_versions(&settings, source.as_deref(), confirm).await
+            }
+            DocsCommands::Hold {
+                doc_id,
+                reason,
+                release,
+            } => retention::cmd_hold(&settings, &doc_id, reason.as_deref(), release).await,
+            DocsCommands::Holds { source } => {
+                retention::cmd_holds(&settings, source.as_deref()).await
+            }
+            DocsCommands::Split {
+                doc_id,
+                by,
+                min_pages,
+                confirm,
+            } => documents::cmd_split(&settings, &doc_id, &by, min_pages, confirm).await,
+        },
         Commands::Info { doc_id } => documents::cmd_info(&settings, &doc_id).await,
+        Commands::Certify {
+            doc_id,
+            format,
+            output,
+        } => {
+            custody::cmd_certify(&settings, &config, &doc_id, &format, output.as_deref()).await
+        }
+        Commands::VerifyCertificate { file } => custody::cmd_verify_certificate(&file),
         Commands::Read { doc_id, text } => documents::cmd_read(&settings, &doc_id, text).await,
         Commands::Search {
             query,
             source,
             limit,
-        } => documents::cmd_search(&settings, &query, source.as_deref(), limit).await,
+        } => {
+            if config.search.backend != SearchBackend::Database {
+                search_index::cmd_search_indexed(
+                    &settings,
+                    &config,
+                    &query,
+                    source.as_deref(),
+                    limit,
+                )
+                .await
+            } else {
+                documents::cmd_search(&settings, &query, source.as_deref(), limit).await
+            }
+        }
+        Commands::Acquire {
+            url,
+            source,
+            tag,
+            no_process,
+            from_file,
+            submitted_by,
+        } => {
+            acquire::cmd_acquire(
+                &settings,
+                &config,
+                &url,
+                source,
+                tag,
+                no_process,
+                from_file.as_deref(),
+                submitted_by,
+            )
+            .await
+        }
         Commands::Import { command } => match command {
             ImportCommands::Warc {
                 files,
@@ -1252,6 +2850,20 @@ pub async fn run() -> anyhow::Result<()> {
                 )
                 .await
             }
+            ImportCommands::Intake {
+                dir,
+                source,
+                pattern,
+                tag,
+                dry_run,
+                daemon,
+                interval,
+            } => {
+                import::cmd_import_intake(
+                    &settings, &dir, &source, &pattern, &tag, dry_run, daemon, interval,
+                )
+                .await
+            }
         },
         Commands::Discover { command } => match command {
             DiscoverCommands::Pattern {
@@ -1347,5 +2959,18 @@ pub async fn run() -> anyhow::Result<()> {
             )
             .await
         }
+    };
+    output::emit_finished(&command, started.elapsed(), &result);
+    result
+}
+
+/// The subcommand being run, e.g. "export run".
+fn command_path(matches: &clap::ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
     }
+    names.join(" ")
 }
@@ -0,0 +1,248 @@
+//! End-to-end acquisition pipeline command.
+//!
+//! `foia run` chains the stages users would otherwise invoke one by one.
+//! Every stage works from persisted state (crawl queue, document status,
+//! annotation markers), so a failed or interrupted run picks up where it
+//! left off when re-run, and `--from` skips stages already known to be done.
+
+use std::time::{Duration, Instant};
+
+use console::style;
+
+use foia::config::Settings;
+use foia::privacy::PrivacyConfig;
+use foia::work_queue::ExecutionStrategy;
+
+use super::daemon::ReloadMode;
+use super::{analyze, annotate, scrape, state};
+
+/// Hours before failed analyses are retried (matches `foia analyze`).
+const ANALYSIS_RETRY_HOURS: u32 = 12;
+
+/// A stage of `foia run`, in execution order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum RunStage {
+    /// Discover document URLs
+    Crawl,
+    /// Download queued documents
+    Download,
+    /// Extract text, OCR scanned pages and classify record types
+    Analyze,
+    /// Generate LLM synopses and tags
+    Summarize,
+    /// Estimate publication dates, extract entities, Bates numbers and
+    /// exemption markings
+    Finalize,
+}
+
+impl RunStage {
+    const ALL: [RunStage; 5] = [
+        RunStage::Crawl,
+        RunStage::Download,
+        RunStage::Analyze,
+        RunStage::Summarize,
+        RunStage::Finalize,
+    ];
+
+    /// Name as accepted by `--from` and `--skip`.
+    fn name(self) -> &'static str {
+        match self {
+            RunStage::Crawl => "crawl",
+            RunStage::Download => "download",
+            RunStage::Analyze => "analyze",
+            RunStage::Summarize => "summarize",
+            RunStage::Finalize => "finalize",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            RunStage::Crawl => "Crawl",
+            RunStage::Download => "Download",
+            RunStage::Analyze => "Text extraction, OCR & classification",
+            RunStage::Summarize => "Summarization",
+            RunStage::Finalize => "Dates, entities, Bates numbers & exemptions",
+        }
+    }
+}
+
+/// Pipeline tuning shared by all stages.
+pub struct RunOptions<'a> {
+    pub from: RunStage,
+    pub skip: &'a [RunStage],
+    pub workers: usize,
+    pub analysis_workers: usize,
+    pub limit: usize,
+}
+
+enum StageOutcome {
+    Completed(Duration),
+    Failed(Duration, String),
+    Skipped,
+}
+
+/// Run every pipeline stage for a source, continuing past failed stages.
+pub async fn cmd_run(
+    settings: &Settings,
+    source_id: &str,
+    options: RunOptions<'_>,
+    privacy_config: &PrivacyConfig,
+) -> anyhow::Result<()> {
+    settings.ensure_directories()?;
+
+    let repos = settings.repositories()?;
+    if repos.scraper_configs.get(source_id).await?.is_none() {
+        let available = repos.scraper_configs.list_source_ids().await?;
+        println!(
+            "{} No scraper configured for '{}'",
+            style("✗").red(),
+            source_id
+        );
+        println!("Available sources: {}", available.join(", "));
+        return Ok(());
+    }
+    drop(repos);
+
+    println!(
+        "{} Running pipeline for {}",
+        style("→").cyan(),
+        style(source_id).bold()
+    );
+
+    let total = RunStage::ALL.len();
+    let mut outcomes = Vec::with_capacity(total);
+    for (i, stage) in RunStage::ALL.into_iter().enumerate() {
+        if stage < options.from || options.skip.contains(&stage) {
+            outcomes.push((stage, StageOutcome::Skipped));
+            continue;
+        }
+
+        println!();
+        println!(
+            "{} [{}/{}] {}",
+            style("▶").cyan().bold(),
+            i + 1,
+            total,
+            style(stage.label()).bold()
+        );
+
+        let started = Instant::now();
+        let result = run_stage(stage, settings, source_id, &options, privacy_config).await;
+        let elapsed = started.elapsed();
+        match result {
+            Ok(()) => outcomes.push((stage, StageOutcome::Completed(elapsed))),
+            Err(e) => {
+                // Later stages still have work from earlier runs, so keep going.
+                println!("{} {} failed: {}", style("✗").red(), stage.label(), e);
+                outcomes.push((stage, StageOutcome::Failed(elapsed, e.to_string())));
+            }
+        }
+    }
+
+    println!();
+    println!("{}", style("Pipeline summary").bold());
+    for (stage, outcome) in &outcomes {
+        match outcome {
+            StageOutcome::Completed(elapsed) => println!(
+                "  {} {:<24} {:.1}s",
+                style("✓").green(),
+                stage.label(),
+                elapsed.as_secs_f64()
+            ),
+            StageOutcome::Failed(elapsed, error) => println!(
+                "  {} {:<24} {:.1}s  {}",
+                style("✗").red(),
+                stage.label(),
+                elapsed.as_secs_f64(),
+                style(error).dim()
+            ),
+            StageOutcome::Skipped => {
+                println!("  {} {:<24} skipped", style("-").dim(), stage.label())
+            }
+        }
+    }
+
+    let failed: Vec<RunStage> = outcomes
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, StageOutcome::Failed(..)))
+        .map(|(stage, _)| *stage)
+        .collect();
+    if let Some(first) = failed.first() {
+        println!();
+        println!(
+            "  {} Resume with: foia run {} --from {}",
+            style("→").dim(),
+            source_id,
+            first.name()
+        );
+        anyhow::bail!("{} pipeline stage(s) failed", failed.len());
+    }
+
+    Ok(())
+}
+
+async fn run_stage(
+    stage: RunStage,
+    settings: &Settings,
+    source_id: &str,
+    options: &RunOptions<'_>,
+    privacy_config: &PrivacyConfig,
+) -> anyhow::Result<()> {
+    let source = Some(source_id);
+    match stage {
+        RunStage::Crawl => state::cmd_crawl(settings, source_id, options.limit, false).await,
+        RunStage::Download => {
+            scrape::cmd_download(
+                settings,
+                source,
+                options.workers,
+                options.limit,
+                false,
+                privacy_config,
+            )
+            .await
+        }
+        RunStage::Analyze => {
+            analyze::cmd_analyze(
+                settings,
+                source,
+                None,
+                None,
+                options.analysis_workers,
+                options.limit,
+                None,
+                false,
+                0,
+                ANALYSIS_RETRY_HOURS,
+                None,
+                ReloadMode::default(),
+                ExecutionStrategy::Wide,
+            )
+            .await?;
+            // Before summarization, so record-type processing rules apply
+            annotate::cmd_classify(settings, source, options.limit, false).await
+        }
+        RunStage::Summarize => {
+            annotate::cmd_annotate(
+                settings,
+                source,
+                None,
+                options.limit,
+                None,
+                None,
+                None,
+                false,
+                0,
+                ReloadMode::default(),
+                ExecutionStrategy::Wide,
+            )
+            .await
+        }
+        RunStage::Finalize => {
+            annotate::cmd_detect_dates(settings, source, options.limit, false).await?;
+            annotate::cmd_extract_entities(settings, source, options.limit).await?;
+            annotate::cmd_extract_bates(settings, source, options.limit).await?;
+            annotate::cmd_extract_exemptions(settings, source, options.limit).await
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Storage repair commands.
+
+use std::time::Duration;
+
+use console::style;
+use futures::StreamExt;
+
+use foia::config::{Config, Settings};
+use foia::models::DocumentVersion;
+use foia::privacy::PrivacyConfig;
+use foia::repository::diesel_document::{Projection, StreamFilter, DEFAULT_STREAM_BATCH};
+
+use super::helpers::truncate;
+
+/// A version whose content file is missing from storage.
+struct MissingFile {
+    document_id: String,
+    title: String,
+    version_id: i64,
+    url: String,
+    content_hash: String,
+    path: std::path::PathBuf,
+}
+
+/// Outcome of trying to re-fetch one missing file.
+enum Refetch {
+    Restored,
+    /// Permanently gone; the reason is recorded on the version.
+    Lost(String),
+    /// Worth retrying on a later run (rate limit, server error, network).
+    Transient(String),
+}
+
+/// Re-fetch versions whose files are missing from storage.
+///
+/// Content is only written back when its hash matches the recorded version;
+/// versions whose URL is gone or now serves different content are marked
+/// lost so their extracted text stays available without the original file.
+pub async fn cmd_repair_redownload(
+    settings: &Settings,
+    source_id: Option<&str>,
+    limit: usize,
+    delay_ms: u64,
+    dry_run: bool,
+    retry_lost: bool,
+    privacy_config: &PrivacyConfig,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let doc_repo = repos.documents;
+
+    let known_lost = if retry_lost {
+        Default::default()
+    } else {
+        doc_repo.get_lost_version_ids(source_id).await?
+    };
+
+    println!("{} Scanning for missing files...", style("→").cyan());
+
+    let mut missing = Vec::new();
+    let mut skipped_lost = 0usize;
+    let mut docs = doc_repo.stream_documents(
+        StreamFilter::source(source_id).with_projection(Projection::Metadata),
+        DEFAULT_STREAM_BATCH,
+    );
+    while let Some(doc) = docs.next().await {
+        let doc = doc?;
+        for version in &doc.versions {
+            let path = version.resolve_path(&settings.documents_dir, &doc.source_url, &doc.title);
+            if path.exists() {
+                continue;
+            }
+            if known_lost.contains(&version.id) {
+                skipped_lost += 1;
+                continue;
+            }
+            missing.push(MissingFile {
+                document_id: doc.id.clone(),
+                title: doc.title.clone(),
+                version_id: version.id,
+                url: version
+                    .source_url
+                    .clone()
+                    .unwrap_or_else(|| doc.source_url.clone()),
+                content_hash: version.content_hash.clone(),
+                path,
+            });
+        }
+        if limit > 0 && missing.len() >= limit {
+            missing.truncate(limit);
+            break;
+        }
+    }
+    drop(docs);
+
+    if missing.is_empty() {
+        println!("{} No missing files found", style("✓").green());
+        if skipped_lost > 0 {
+            println!(
+                "  {} version(s) already marked lost (use --retry-lost to try again)",
+                skipped_lost
+            );
+        }
+        return Ok(());
+    }
+
+    println!(
+        "{} {} version(s) with missing files",
+        style("!").yellow(),
+        missing.len()
+    );
+
+    if dry_run {
+        for m in &missing {
+            println!(
+                "  {} {} {}",
+                style(&m.document_id[..8.min(m.document_id.len())]).cyan(),
+                truncate(&m.title, 50),
+                style(&m.url).dim()
+            );
+        }
+        println!("\n{} Dry run, nothing fetched", style("!").yellow());
+        return Ok(());
+    }
+
+    let config = Config::load().await;
+    let client = foia::http_client::HttpClient::builder(
+        "repair",
+        Duration::from_secs(30),
+        Duration::from_millis(delay_ms),
+    )
+    .privacy(privacy_config)
+    .build()?;
+    let client = if !config.via.is_empty() {
+        client.with_via_config(config.via, config.via_mode)
+    } else {
+        client
+    };
+
+    let mut restored = 0usize;
+    let mut lost = 0usize;
+    let mut transient = 0usize;
+
+    for m in &missing {
+        match refetch(&client, m).await {
+            Refetch::Restored => {
+                doc_repo.clear_version_lost(m.version_id).await?;
+                restored += 1;
+                println!(
+                    "  {} {} restored",
+                    style("✓").green(),
+                    truncate(&m.title, 50)
+                );
+            }
+            Refetch::Lost(reason) => {
+                doc_repo
+                    .mark_version_lost(m.version_id, &m.document_id, &reason)
+                    .await?;
+                lost += 1;
+                println!(
+                    "  {} {} lost: {}",
+                    style("✗").red(),
+                    truncate(&m.title, 50),
+                    reason
+                );
+            }
+            Refetch::Transient(reason) => {
+                transient += 1;
+                println!(
+                    "  {} {} skipped: {}",
+                    style("!").yellow(),
+                    truncate(&m.title, 50),
+                    reason
+                );
+            }
+        }
+    }
+
+    println!();
+    println!("{} {} file(s) restored", style("✓").green(), restored);
+    if lost > 0 {
+        println!(
+            "{} {} version(s) marked lost (extracted text is kept)",
+            style("✗").red(),
+            lost
+        );
+    }
+    if transient > 0 {
+        println!(
+            "{} {} version(s) could not be fetched right now; run again later",
+            style("!").yellow(),
+            transient
+        );
+    }
+
+    Ok(())
+}
+
+async fn refetch(client: &foia::http_client::HttpClient, m: &MissingFile) -> Refetch {
+    let response = match client.get(&m.url, None, None).await {
+        Ok(r) => r,
+        Err(e) => return Refetch::Transient(e.to_string()),
+    };
+
+    let status = response.status.as_u16();
+    if status == 404 || status == 410 {
+        return Refetch::Lost(format!("HTTP {}", status));
+    }
+    if !response.is_success() {
+        return Refetch::Transient(format!("HTTP {}", status));
+    }
+
+    let bytes = match response.bytes().await {
+        Ok(b) => b,
+        Err(e) => return Refetch::Transient(e.to_string()),
+    };
+    if DocumentVersion::compute_hash(&bytes) != m.content_hash {
+        return Refetch::Lost("content changed at source".to_string());
+    }
+
+    if let Some(parent) = m.path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            return Refetch::Transient(format!("cannot create {}: {}", parent.display(), e));
+        }
+    }
+    match foia::storage::write_content(&m.path, &bytes) {
+        Ok(()) => Refetch::Restored,
+        Err(e) => Refetch::Transient(format!("cannot write {}: {}", m.path.display(), e)),
+    }
+}
@@ -0,0 +1,109 @@
+//! Archive statistics reports for funders.
+
+use std::path::{Path, PathBuf};
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::report::{self, ArchiveReport, ReportFormat};
+
+use super::helpers::{format_bytes, format_number, parse_date_arg};
+use crate::cli::output;
+
+/// Options of `foia report generate`.
+pub struct GenerateOptions<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub output: Option<PathBuf>,
+    pub format: Option<&'a str>,
+    pub template: Option<PathBuf>,
+    pub title: Option<String>,
+    pub notable: u32,
+}
+
+/// Generate a report of archive growth over a period.
+pub async fn cmd_report_generate(
+    settings: &Settings,
+    opts: GenerateOptions<'_>,
+) -> anyhow::Result<()> {
+    let from = parse_date_arg(opts.from)?;
+    let to = parse_date_arg(opts.to)?;
+    let format = resolve_format(opts.format, opts.output.as_deref())?;
+    let template = match &opts.template {
+        Some(path) => Some(
+            tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| anyhow::anyhow!("Cannot read template {}: {}", path.display(), e))?,
+        ),
+        None => None,
+    };
+
+    let repos = settings.repositories()?;
+    let report = ArchiveReport::build(&repos.documents, from, to, opts.notable).await?;
+    let title = opts.title.unwrap_or_else(|| {
+        format!(
+            "Archive report, {} to {}",
+            from.format("%Y-%m-%d"),
+            to.format("%Y-%m-%d")
+        )
+    });
+    let html = report.render_html(&title, template.as_deref());
+    let output_path = opts.output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "report-{}-{}.{}",
+            from.format("%Y%m%d"),
+            to.format("%Y%m%d"),
+            format.extension()
+        ))
+    });
+    report::write_report(&html, format, &output_path).await?;
+
+    if output::is_json() {
+        let mut value = serde_json::to_value(&report)?;
+        value["output"] = serde_json::json!(output_path);
+        value["format"] = serde_json::to_value(format)?;
+        output::emit("result", value);
+        return Ok(());
+    }
+
+    let totals = report.totals();
+    println!(
+        "{} Wrote {}",
+        style("✓").green(),
+        style(output_path.display()).cyan()
+    );
+    println!(
+        "  {} documents acquired ({} versions, {}) across {} source(s)",
+        format_number(totals.documents_added),
+        format_number(totals.versions_added),
+        format_bytes(totals.bytes_added),
+        report
+            .sources
+            .iter()
+            .filter(|s| s.documents_added > 0 || s.versions_added > 0)
+            .count()
+    );
+    if let Some(coverage) = totals.ocr_coverage() {
+        println!("  {:.1}% of pages have OCR coverage", coverage);
+    }
+    Ok(())
+}
+
+/// Print the default template, as a starting point for a custom one.
+pub fn cmd_report_template() -> anyhow::Result<()> {
+    print!("{}", report::DEFAULT_TEMPLATE);
+    Ok(())
+}
+
+/// The format asked for, else the one the output file's extension names,
+/// else HTML.
+fn resolve_format(format: Option<&str>, output: Option<&Path>) -> anyhow::Result<ReportFormat> {
+    if let Some(format) = format {
+        return ReportFormat::from_str(format)
+            .ok_or_else(|| anyhow::anyhow!("Unknown report format '{}' (html, odt, pdf)", format));
+    }
+    Ok(output
+        .and_then(|p| p.extension())
+        .and_then(|ext| ReportFormat::from_str(&ext.to_string_lossy().to_lowercase()))
+        .unwrap_or_default())
+}
@@ -0,0 +1,452 @@
+//! Retention: delete originals whose text is preserved, thin out the
+//! versions of frequently re-fetched pages, and manage the legal holds
+//! that exempt documents from both.
+
+use std::collections::{BTreeMap, HashSet};
+
+use console::style;
+use futures::StreamExt;
+
+use foia::config::{RetentionConfig, Settings};
+use foia::models::{Document, DocumentVersion};
+use foia::repository::diesel_document::{StreamFilter, DEFAULT_STREAM_BATCH};
+use foia::repository::DieselDocumentRepository;
+use foia::services::overlap::Signature;
+use foia::services::retention::{check_prunable, select_versions_to_prune, VersionText};
+use foia::storage;
+
+use super::helpers::{format_bytes, truncate};
+
+/// Kept-reason for documents under legal hold.
+const HELD: &str = "legal hold";
+
+/// Reason recorded on versions whose original was deleted.
+const PRUNED_REASON: &str = "text-only retention";
+
+/// Per-source outcome of a prune run.
+#[derive(Default)]
+struct PruneReport {
+    pruned: usize,
+    reclaimed: u64,
+    kept: BTreeMap<&'static str, usize>,
+}
+
+/// Delete the originals of documents in text-only sources once their text
+/// is verified, recording each so the UI and `repair redownload` know.
+pub async fn cmd_prune(
+    settings: &Settings,
+    source_id: Option<&str>,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let sources: Vec<(String, RetentionConfig)> = repos
+        .scraper_configs
+        .get_all()
+        .await?
+        .into_iter()
+        .filter(|(id, _)| source_id.is_none() || source_id == Some(id.as_str()))
+        .filter(|(_, config)| config.retention.text_only)
+        .map(|(id, config)| (id, config.retention))
+        .collect();
+
+    if sources.is_empty() {
+        println!(
+            "{} No sources with text-only retention{}",
+            style("!").yellow(),
+            source_id
+                .map(|s| format!(" matching '{}'", s))
+                .unwrap_or_default()
+        );
+        println!("  Set \"retention\": {{\"text_only\": true}} in a source's scraper config.");
+        return Ok(());
+    }
+
+    let doc_repo = repos.documents;
+    let already_gone = doc_repo.get_lost_version_ids(None).await?;
+    let held = doc_repo.get_held_document_ids(None).await?;
+
+    let mut total_pruned = 0usize;
+    let mut total_reclaimed = 0u64;
+    for (source_id, retention) in &sources {
+        let report = prune_source(
+            settings,
+            &doc_repo,
+            source_id,
+            retention,
+            &already_gone,
+            &held,
+            confirm,
+        )
+        .await?;
+
+        let verb = if confirm { "deleted" } else { "would delete" };
+        println!(
+            "{} {}: {} {} original(s), {}",
+            style("→").cyan(),
+            source_id,
+            verb,
+            report.pruned,
+            format_bytes(report.reclaimed)
+        );
+        for (reason, count) in &report.kept {
+            println!("    kept {} ({})", count, reason);
+        }
+        total_pruned += report.pruned;
+        total_reclaimed += report.reclaimed;
+    }
+
+    println!();
+    if confirm {
+        println!(
+            "{} Deleted {} original(s), reclaimed {}",
+            style("✓").green(),
+            total_pruned,
+            format_bytes(total_reclaimed)
+        );
+        if total_pruned > 0 {
+            println!("  Restore with: foia repair redownload --retry-lost");
+        }
+    } else {
+        println!(
+            "{} Would delete {} original(s), reclaiming {}. Re-run with --confirm.",
+            style("!").yellow(),
+            total_pruned,
+            format_bytes(total_reclaimed)
+        );
+    }
+
+    Ok(())
+}
+
+async fn prune_source(
+    settings: &Settings,
+    doc_repo: &DieselDocumentRepository,
+    source_id: &str,
+    retention: &RetentionConfig,
+    already_gone: &HashSet<i64>,
+    held: &HashSet<String>,
+    confirm: bool,
+) -> anyhow::Result<PruneReport> {
+    let mut report = PruneReport::default();
+    let now = chrono::Utc::now();
+
+    let mut docs =
+        doc_repo.stream_documents(StreamFilter::source(Some(source_id)), DEFAULT_STREAM_BATCH);
+    while let Some(doc) = docs.next().await {
+        let doc = doc?;
+        let Some(version) = doc.current_version() else {
+            continue;
+        };
+        if already_gone.contains(&version.id) {
+            continue;
+        }
+        if held.contains(&doc.id) {
+            *report.kept.entry(HELD).or_default() += 1;
+            continue;
+        }
+        if let Err(reason) = check_prunable(&doc, version, retention, now) {
+            *report.kept.entry(reason.as_str()).or_default() += 1;
+            continue;
+        }
+        match verify_file(settings, doc_repo, &doc, version).await? {
+            Ok(size) => {
+                if confirm {
+                    // Record first: a leftover file is harmless, an
+                    // unrecorded deletion is not
+                    doc_repo
+                        .mark_version_lost(version.id, &doc.id, PRUNED_REASON)
+                        .await?;
+                    let path =
+                        version.resolve_path(&settings.documents_dir, &doc.source_url, &doc.title);
+                    std::fs::remove_file(&path)?;
+                }
+                report.pruned += 1;
+                report.reclaimed += size;
+            }
+            Err(reason) => *report.kept.entry(reason).or_default() += 1,
+        }
+    }
+
+    Ok(report)
+}
+
+/// Check the original on disk before deletion, returning its size.
+///
+/// The file must match the recorded hash (so the preserved text came from
+/// it), every page must be stored, and no other version may share the file.
+async fn verify_file(
+    settings: &Settings,
+    doc_repo: &DieselDocumentRepository,
+    doc: &Document,
+    version: &DocumentVersion,
+) -> anyhow::Result<Result<u64, &'static str>> {
+    if let Some(pages) = version.page_count.filter(|n| *n > 0) {
+        if doc_repo.count_pages(&doc.id, version.id as i32).await? < pages {
+            return Ok(Err("pages incomplete"));
+        }
+    }
+
+    let path = version.resolve_path(&settings.documents_dir, &doc.source_url, &doc.title);
+    let size = match std::fs::metadata(&path) {
+        Ok(meta) => meta.len(),
+        Err(_) => return Ok(Err("file missing")),
+    };
+    let content = storage::read_content(&path)?;
+    if DocumentVersion::compute_hash(&content) != version.content_hash {
+        return Ok(Err("hash mismatch"));
+    }
+
+    if doc_repo
+        .count_versions_needing_file(&version.content_hash, version.id)
+        .await?
+        > 0
+    {
+        return Ok(Err("shared with another document"));
+    }
+
+    Ok(Ok(size))
+}
+
+/// Per-source outcome of a version prune run.
+#[derive(Default)]
+struct VersionPruneReport {
+    documents: usize,
+    versions: usize,
+    reclaimed: u64,
+    held: usize,
+}
+
+/// Delete the versions of frequently re-fetched pages that add nothing
+/// over the ones kept around them, in sources with `keep_versions` set.
+pub async fn cmd_prune_versions(
+    settings: &Settings,
+    source_id: Option<&str>,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let sources: Vec<(String, RetentionConfig)> = repos
+        .scraper_configs
+        .get_all()
+        .await?
+        .into_iter()
+        .filter(|(id, _)| source_id.is_none() || source_id == Some(id.as_str()))
+        .filter(|(_, config)| config.retention.keep_versions.is_some())
+        .map(|(id, config)| (id, config.retention))
+        .collect();
+
+    if sources.is_empty() {
+        println!(
+            "{} No sources with a version retention policy{}",
+            style("!").yellow(),
+            source_id
+                .map(|s| format!(" matching '{}'", s))
+                .unwrap_or_default()
+        );
+        println!("  Set \"retention\": {{\"keep_versions\": 5}} in a source's scraper config.");
+        return Ok(());
+    }
+
+    let doc_repo = repos.documents;
+    let held = doc_repo.get_held_document_ids(None).await?;
+
+    let mut total_versions = 0usize;
+    let mut total_reclaimed = 0u64;
+    for (source_id, retention) in &sources {
+        let report =
+            prune_source_versions(settings, &doc_repo, source_id, retention, &held, confirm)
+                .await?;
+
+        let verb = if confirm { "deleted" } else { "would delete" };
+        println!(
+            "{} {}: {} {} version(s) of {} document(s), {}",
+            style("→").cyan(),
+            source_id,
+            verb,
+            report.versions,
+            report.documents,
+            format_bytes(report.reclaimed)
+        );
+        if report.held > 0 {
+            println!("    kept {} ({})", report.held, HELD);
+        }
+        total_versions += report.versions;
+        total_reclaimed += report.reclaimed;
+    }
+
+    println!();
+    if confirm {
+        println!(
+            "{} Deleted {} version(s), reclaimed {}",
+            style("✓").green(),
+            total_versions,
+            format_bytes(total_reclaimed)
+        );
+    } else {
+        println!(
+            "{} Would delete {} version(s), reclaiming {}. Re-run with --confirm.",
+            style("!").yellow(),
+            total_versions,
+            format_bytes(total_reclaimed)
+        );
+    }
+
+    Ok(())
+}
+
+async fn prune_source_versions(
+    settings: &Settings,
+    doc_repo: &DieselDocumentRepository,
+    source_id: &str,
+    retention: &RetentionConfig,
+    held: &HashSet<String>,
+    confirm: bool,
+) -> anyhow::Result<VersionPruneReport> {
+    let mut report = VersionPruneReport::default();
+    let keep = retention.keep_versions.unwrap_or(usize::MAX);
+    let already_gone = doc_repo.get_lost_version_ids(Some(source_id)).await?;
+
+    let mut docs =
+        doc_repo.stream_documents(StreamFilter::source(Some(source_id)), DEFAULT_STREAM_BATCH);
+    while let Some(doc) = docs.next().await {
+        let mut doc = doc?;
+        if doc.versions.len() <= 2 {
+            continue;
+        }
+        if held.contains(&doc.id) {
+            report.held += 1;
+            continue;
+        }
+
+        doc.versions.sort_by_key(|v| (v.acquired_at, v.id));
+        let mut texts = Vec::with_capacity(doc.versions.len());
+        for version in &doc.versions {
+            let text = version_text(settings, doc_repo, &doc, version, &already_gone).await?;
+            texts.push(VersionText {
+                version_id: version.id,
+                signature: text.as_deref().and_then(Signature::of),
+            });
+        }
+        let prune = select_versions_to_prune(&texts, keep, retention.version_similarity());
+        if prune.is_empty() {
+            continue;
+        }
+
+        report.documents += 1;
+        for version in doc.versions.iter().filter(|v| prune.contains(&v.id)) {
+            let path = version.resolve_path(&settings.documents_dir, &doc.source_url, &doc.title);
+            // Versions of one page often share a file when it flips back
+            let size = if already_gone.contains(&version.id)
+                || doc_repo
+                    .count_versions_needing_file(&version.content_hash, version.id)
+                    .await?
+                    > 0
+            {
+                None
+            } else {
+                std::fs::metadata(&path).ok().map(|meta| meta.len())
+            };
+
+            if confirm {
+                // Delete the row first: a leftover file is harmless, a
+                // version pointing at a deleted file is not
+                doc_repo.delete_version(&doc.id, version.id).await?;
+                if size.is_some() {
+                    std::fs::remove_file(&path)?;
+                }
+            }
+            report.versions += 1;
+            report.reclaimed += size.unwrap_or(0);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Text of a version to compare: its page text, or the main text of its
+/// file for HTML and plain-text captures.
+async fn version_text(
+    settings: &Settings,
+    doc_repo: &DieselDocumentRepository,
+    doc: &Document,
+    version: &DocumentVersion,
+    already_gone: &HashSet<i64>,
+) -> anyhow::Result<Option<String>> {
+    if let Some(text) = doc_repo
+        .get_combined_page_text(&doc.id, version.id as i32)
+        .await?
+    {
+        return Ok(Some(text));
+    }
+    let html = version.mime_type.contains("html");
+    if already_gone.contains(&version.id) || !(html || version.mime_type.starts_with("text/")) {
+        return Ok(None);
+    }
+    let path = version.resolve_path(&settings.documents_dir, &doc.source_url, &doc.title);
+    let Ok(content) = storage::read_content(&path) else {
+        return Ok(None);
+    };
+    let content = String::from_utf8_lossy(&content);
+    Ok(Some(if html {
+        foia_analysis::ocr::html_main_text(&content)
+    } else {
+        content.into_owned()
+    }))
+}
+
+/// Place a document under legal hold, or release it.
+pub async fn cmd_hold(
+    settings: &Settings,
+    doc_id: &str,
+    reason: Option<&str>,
+    release: bool,
+) -> anyhow::Result<()> {
+    let doc_repo = settings.repositories()?.documents;
+    if release {
+        if doc_repo.release_legal_hold(doc_id).await? {
+            println!("{} Released legal hold on {}", style("✓").green(), doc_id);
+        } else {
+            println!("{} {} is not under legal hold", style("!").yellow(), doc_id);
+        }
+        return Ok(());
+    }
+
+    if doc_repo.get(doc_id).await?.is_none() {
+        anyhow::bail!("Document not found: {}", doc_id);
+    }
+    let reason = reason.unwrap_or_default();
+    doc_repo.place_legal_hold(doc_id, reason).await?;
+    println!(
+        "{} Placed {} under legal hold: {}",
+        style("✓").green(),
+        doc_id,
+        reason
+    );
+    println!("  Pruning will not delete its files or versions until released.");
+    Ok(())
+}
+
+/// List documents under legal hold.
+pub async fn cmd_holds(settings: &Settings, source_id: Option<&str>) -> anyhow::Result<()> {
+    let holds = settings
+        .repositories()?
+        .documents
+        .get_legal_holds(source_id)
+        .await?;
+    if holds.is_empty() {
+        println!("{} No documents under legal hold", style("!").yellow());
+        return Ok(());
+    }
+
+    println!("{:<40} {:<12} Reason", "Document", "Since");
+    println!("{}", "-".repeat(80));
+    for hold in &holds {
+        println!(
+            "{:<40} {:<12} {}",
+            truncate(&hold.document_id, 40),
+            hold.placed_at.format("%Y-%m-%d"),
+            hold.reason
+        );
+    }
+    println!("\n{} document(s) under legal hold", holds.len());
+    Ok(())
+}
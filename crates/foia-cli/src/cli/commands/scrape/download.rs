@@ -9,6 +9,8 @@ use foia::config::{Config, Settings};
 use foia::privacy::PrivacyConfig;
 use foia::repository::DieselCrawlRepository;
 
+use crate::cli::output;
+
 /// Download pending documents from the queue.
 pub async fn cmd_download(
     settings: &Settings,
@@ -29,6 +31,19 @@ pub async fn cmd_download(
     // Check for pending work
     let initial_pending = get_pending_count(&repos.crawl, source_id).await?;
 
+    // Per-source credentials and content checks, for sources that set them
+    let scrapers = repos.scraper_configs.get_all().await?;
+    let auth = scrapers
+        .iter()
+        .filter(|(_, scraper)| !scraper.auth.is_default())
+        .map(|(id, scraper)| (id.clone(), scraper.auth.clone()))
+        .collect();
+    let validation = scrapers
+        .into_iter()
+        .filter(|(_, scraper)| !scraper.validation.is_default())
+        .map(|(id, scraper)| (id, scraper.validation))
+        .collect();
+
     let doc_repo = Arc::new(repos.documents);
     let crawl_repo = Arc::new(repos.crawl);
 
@@ -51,7 +66,7 @@ pub async fn cmd_download(
         initial_pending
     );
 
-    // Load config for via mappings
+    // Load config for via mappings and media settings
     let config = Config::load().await;
 
     // Create service
@@ -65,14 +80,19 @@ pub async fn cmd_download(
             privacy: privacy_config.clone(),
             via: config.via,
             via_mode: config.via_mode,
+            media: config.media,
+            auth,
+            circuit_breaker: config.circuit_breaker.settings(),
+            validation,
+            dedup_window: config.fetch_dedup.window(),
         },
     );
 
     // Event channel for progress updates
     let (event_tx, mut event_rx) = mpsc::channel::<DownloadEvent>(100);
 
-    // Set up progress display (UI concern)
-    let progress_display = if show_progress {
+    // Set up progress display (UI concern); JSON mode reports it as events
+    let progress_display = if show_progress || output::is_json() {
         Some(Arc::new(DownloadProgress::new(workers, initial_pending)))
     } else {
         None
@@ -122,7 +142,8 @@ pub async fn cmd_download(
                         progress.finish_download(worker_id, true).await;
                     }
                 }
-                DownloadEvent::Unchanged { worker_id, .. } => {
+                DownloadEvent::Unchanged { worker_id, .. }
+                | DownloadEvent::Duplicate { worker_id, .. } => {
                     skipped += 1;
                     if let Some(ref progress) = progress_clone {
                         progress.set_summary(downloaded, skipped);
@@ -171,6 +192,15 @@ pub async fn cmd_download(
         progress.finish().await;
     }
 
+    output::emit(
+        "result",
+        serde_json::json!({
+            "downloaded": result.downloaded,
+            "skipped": result.skipped,
+            "remaining": result.remaining,
+        }),
+    );
+
     // Print results (UI layer)
     println!(
         "{} Downloaded {} documents",
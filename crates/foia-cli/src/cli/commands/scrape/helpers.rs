@@ -3,7 +3,7 @@
 use std::path::Path;
 
 use foia::models::{Document, DocumentVersion};
-use foia::storage::{compute_storage_path_with_dedup, mime_to_extension};
+use foia::storage::{compute_storage_path_with_dedup, mime_to_extension, write_content};
 
 /// Parse server date from Last-Modified header.
 pub fn parse_server_date(last_modified: Option<&str>) -> Option<chrono::DateTime<chrono::Utc>> {
@@ -57,7 +57,7 @@ pub fn save_new_version(
     if let Some(parent) = abs_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    std::fs::write(&abs_path, content)?;
+    write_content(&abs_path, content)?;
 
     let mut new_version = DocumentVersion::new_with_metadata(
         content,
@@ -4,6 +4,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use console::style;
+use futures::{StreamExt, TryStreamExt};
 use indicatif::ProgressBar;
 
 use super::helpers::{process_get_response_for_refresh, RefreshResult};
@@ -11,6 +12,7 @@ use crate::cli::commands::helpers::truncate;
 use foia::config::{Config, Settings};
 use foia::models::Document;
 use foia::privacy::PrivacyConfig;
+use foia::repository::diesel_document::{Projection, StreamFilter, DEFAULT_STREAM_BATCH};
 use foia::repository::DieselDocumentRepository;
 
 /// Shared GET request handling for refresh.
@@ -96,34 +98,28 @@ pub async fn cmd_refresh(
 
     let repos = settings.repositories()?;
     let doc_repo = Arc::new(repos.documents);
-
-    // Get documents that need metadata refresh
-    let documents = if let Some(sid) = source_id {
-        doc_repo.get_by_source(sid).await?
-    } else {
-        doc_repo.get_all().await?
-    };
-
-    // Filter to documents needing refresh (missing original_filename or server_date)
-    let docs_needing_refresh: Vec<_> = documents
-        .into_iter()
-        .filter(|doc| {
-            if force {
-                return true;
-            }
-            if let Some(version) = doc.current_version() {
-                version.original_filename.is_none() || version.server_date.is_none()
-            } else {
-                false
-            }
+    let paused = repos.sources.paused_ids().await?;
+
+    // Stream documents, keeping only those needing refresh
+    // (missing original_filename or server_date) from sources not paused
+    let docs_needing_refresh: Vec<_> = doc_repo
+        .stream_documents(
+            StreamFilter::source(source_id).with_projection(Projection::Metadata),
+            DEFAULT_STREAM_BATCH,
+        )
+        .try_filter(|doc| {
+            let needs = !paused.contains(&doc.source_id)
+                && (force
+                    || doc.current_version().is_some_and(|version| {
+                        version.original_filename.is_none() || version.server_date.is_none()
+                    }));
+            futures::future::ready(needs)
         })
-        .collect();
+        .take(if limit > 0 { limit } else { usize::MAX })
+        .try_collect()
+        .await?;
 
-    let total = if limit > 0 {
-        std::cmp::min(limit, docs_needing_refresh.len())
-    } else {
-        docs_needing_refresh.len()
-    };
+    let total = docs_needing_refresh.len();
 
     if total == 0 {
         println!("{} All documents already have metadata", style("✓").green());
@@ -6,6 +6,7 @@ use std::time::Duration;
 use console::style;
 
 use crate::cli::commands::daemon::{ConfigWatcher, DaemonAction, ReloadMode};
+use crate::cli::commands::helpers::window_notice;
 use crate::cli::commands::RateLimitBackendType;
 use foia::config::{Config, Settings};
 use foia::models::{ScraperStats, ServiceStatus};
@@ -88,10 +89,15 @@ pub async fn cmd_scrape(
     };
 
     let repos = settings.repositories()?;
+    if daemon {
+        repos.pool().spawn_wal_checkpoint();
+    }
+    let crawl_repo = repos.crawl;
+    let source_repo = repos.sources;
     let config_history = repos.config_history;
     let scraper_configs = repos.scraper_configs;
 
-    // Initial config load — file config hash used only as fallback for daemon detection
+    // Initial config load — watched for changes between daemon runs
     let config = Config::load().await;
 
     let mut config_watcher = ConfigWatcher::new(
@@ -99,7 +105,7 @@ pub async fn cmd_scrape(
         reload,
         config_history,
         scraper_configs.clone(),
-        config.hash(),
+        config,
     )
     .await;
 
@@ -128,12 +134,16 @@ pub async fn cmd_scrape(
     }
 
     loop {
+        // Each source run re-reads its own config; this only reports what
+        // changed since the previous run.
+        config_watcher.reload_config(&crawl_repo).await;
+
         // For next-run and inplace modes, reload source list from DB
         if daemon && all && matches!(reload, ReloadMode::NextRun | ReloadMode::Inplace) {
             if let Ok(new_sources) = scraper_configs.list_source_ids().await {
                 if new_sources != sources_to_scrape {
                     println!(
-                        "{} Config reloaded ({} sources)",
+                        "{} Source list updated ({} sources)",
                         style("↻").cyan(),
                         new_sources.len()
                     );
@@ -141,8 +151,55 @@ pub async fn cmd_scrape(
                 }
             }
         }
+        // Paused sources are left out of this run; checked again every run,
+        // so resuming one picks it up without a restart
+        let paused = match source_repo.paused_ids().await {
+            Ok(paused) => paused,
+            Err(e) => {
+                tracing::warn!("Failed to load paused sources: {}", e);
+                Vec::new()
+            }
+        };
+        let (skipped, runnable): (Vec<String>, Vec<String>) = sources_to_scrape
+            .iter()
+            .cloned()
+            .partition(|id| paused.contains(id));
+        if !skipped.is_empty() {
+            println!(
+                "{} Skipping paused source{}: {}",
+                style("⏸").yellow(),
+                if skipped.len() == 1 { "" } else { "s" },
+                skipped.join(", ")
+            );
+        }
+
+        // Sources outside their crawl window wait for a later run
+        let now = chrono::Utc::now();
+        let mut in_window = Vec::with_capacity(runnable.len());
+        for source_id in runnable {
+            let opens = match scraper_configs.get(&source_id).await {
+                Ok(Some(config)) => config.crawl_window.reopens_at(now),
+                _ => None,
+            };
+            match opens {
+                Some(opens) => println!("{}", window_notice(&source_id, opens)),
+                None => in_window.push(source_id),
+            }
+        }
+        let runnable = in_window;
+
+        if runnable.is_empty() {
+            if !daemon {
+                break;
+            }
+            match config_watcher.sleep_or_reload(interval, "reloading").await {
+                DaemonAction::Exit => return Ok(()),
+                DaemonAction::Continue | DaemonAction::Reload => continue,
+            }
+        }
+
         // Initialize TUI with fixed status pane at top (1 header + 1 line per source)
-        let num_status_lines = (sources_to_scrape.len() + 1).min(10) as u16; // Cap at 10 lines
+        let num_status_lines = (runnable.len() + 1).min(10) as u16; // Cap at 10 lines
         let tui_guard = crate::cli::tui::TuiGuard::new(num_status_lines)?;
 
         // Set header
@@ -151,17 +208,13 @@ pub async fn cmd_scrape(
             &format!(
                 "{} Scraping {} source{}...",
                 style("→").cyan(),
-                sources_to_scrape.len(),
-                if sources_to_scrape.len() == 1 {
-                    ""
-                } else {
-                    "s"
-                }
+                runnable.len(),
+                if runnable.len() == 1 { "" } else { "s" }
             ),
         );
 
         // Initialize status lines for each source
-        let source_lines: std::collections::HashMap<String, u16> = sources_to_scrape
+        let source_lines: std::collections::HashMap<String, u16> = runnable
             .iter()
             .enumerate()
             .take(9) // Only show first 9 sources in status (line 0 is header)
@@ -175,9 +228,9 @@ pub async fn cmd_scrape(
             );
         }
 
-        if sources_to_scrape.len() == 1 {
+        if runnable.len() == 1 {
             // Single source - run directly but catch errors in daemon mode
-            let source_id = &sources_to_scrape[0];
+            let source_id = &runnable[0];
             let line = source_lines.get(source_id).copied();
             let result = cmd_scrape_single_tui(
                 settings,
@@ -219,7 +272,7 @@ pub async fn cmd_scrape(
         } else {
             // Multiple sources - run in parallel
             let mut handles = Vec::new();
-            for source_id in &sources_to_scrape {
+            for source_id in &runnable {
                 let settings = settings.clone();
                 let source_id_clone = source_id.clone();
                 let line = source_lines.get(source_id).copied();
@@ -5,13 +5,18 @@ use std::time::Duration;
 
 use console::style;
 
-use foia::config::{Config, Settings, DEFAULT_REFRESH_TTL_DAYS};
+use foia::config::{Config, QuotaConfig, QuotaLevel, Settings, DEFAULT_REFRESH_TTL_DAYS};
 use foia::llm::LlmClient;
 use foia::models::{ScraperStats, ServiceStatus, Source, SourceType};
 use foia::privacy::PrivacyConfig;
 use foia_scrape::{ConfigurableScraper, RateLimiter};
 
 use super::scrape_cmd::maybe_update_heartbeat;
+use crate::cli::commands::helpers::{format_bytes, paused_notice, window_notice};
+
+/// How often a running scrape checks whether its source was paused or its
+/// crawl window closed.
+const PAUSE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Scrape a single source with TUI status updates.
 #[allow(clippy::too_many_arguments)]
@@ -57,6 +62,39 @@ pub(super) async fn cmd_scrape_single_tui(
         }
     };
 
+    if repos.sources.is_paused(source_id).await? {
+        log_msg(&paused_notice(source_id));
+        if let Some(line) = status_line {
+            let _ = crate::cli::tui::set_status(
+                line,
+                &format!("  {} {} paused", style("⏸").yellow(), source_id),
+            );
+        }
+        return Ok(());
+    }
+
+    let crawl_window = scraper_config.crawl_window.clone();
+    if let Some(opens) = crawl_window.reopens_at(chrono::Utc::now()) {
+        log_msg(&window_notice(source_id, opens));
+        if let Some(line) = status_line {
+            let _ = crate::cli::tui::set_status(
+                line,
+                &format!("  {} {} outside window", style("⏾").yellow(), source_id),
+            );
+        }
+        return Ok(());
+    }
+
+    // Hash the configured (pre-expansion) scraper config so crawl state
+    // tracks changes to the config itself, not to LLM-expanded terms.
+    let config_hash = scraper_config.config_hash();
+
+    // Settings the source leaves unset come from its agency chain
+    repos
+        .agencies
+        .apply_inherited(source_id, &mut scraper_config)
+        .await?;
+
     // Load file config for device-specific settings (LLM, privacy, etc.)
     let config = Config::load().await;
 
@@ -91,7 +129,12 @@ pub(super) async fn cmd_scrape_single_tui(
 
     let source_repo = repos.sources;
     let doc_repo = repos.documents;
-    let crawl_repo = Arc::new(repos.crawl);
+    // A source sharing its host's frontier queues what it finds into it
+    let crawl_repo = match repos.scraper_configs.shared_frontier(source_id).await? {
+        Some(frontier) => repos.crawl.with_frontier(frontier),
+        None => repos.crawl,
+    };
+    let crawl_repo = Arc::new(crawl_repo);
     let service_status_repo = repos.service_status;
 
     // Run external discovery if enabled
@@ -151,15 +194,6 @@ pub(super) async fn cmd_scrape_single_tui(
 
     // Check crawl state and update config hash
     {
-        let config_hash = {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-            let json = serde_json::to_string(&scraper_config).unwrap_or_default();
-            let mut hasher = DefaultHasher::new();
-            json.hash(&mut hasher);
-            format!("{:x}", hasher.finish())
-        };
-
         let config_changed = crawl_repo
             .check_config_changed(source_id, &config_hash)
             .await?;
@@ -179,6 +213,34 @@ pub(super) async fn cmd_scrape_single_tui(
         tracing::warn!("Failed to register service status: {}", e);
     }
 
+    // Quota usage is loaded once and tracked locally as documents are saved
+    let quota = scraper_config.quota.clone();
+    let (mut quota_docs, mut quota_bytes) = if quota.is_enabled() {
+        (
+            doc_repo.count_by_source(source_id).await?,
+            doc_repo.storage_bytes_by_source(source_id).await?,
+        )
+    } else {
+        (0, 0)
+    };
+    let mut quota_level = QuotaLevel::Ok;
+    if quota.is_enabled() {
+        quota_level = quota.level(quota_docs, quota_bytes);
+        if quota_level != QuotaLevel::Ok {
+            let msg = quota_message(source_id, &quota, quota_level, quota_docs, quota_bytes);
+            log_msg(&msg.0);
+            tracing::warn!("{}", msg.1);
+            if quota_level == QuotaLevel::Exceeded && quota.enforce {
+                service_status.record_error(&msg.1);
+                service_status.set_stopped();
+                if let Err(e) = service_status_repo.upsert(&service_status).await {
+                    tracing::warn!("Failed to update service status: {}", e);
+                }
+                return Ok(());
+            }
+        }
+    }
+
     // Create scraper and start streaming
     let refresh_ttl_days = scraper_config
         .refresh_ttl_days
@@ -195,7 +257,10 @@ pub(super) async fn cmd_scrape_single_tui(
         limiter_opt,
         Some(privacy_config),
     )
-    .map_err(|e| anyhow::anyhow!("Failed to create scraper: {}", e))?;
+    .map_err(|e| anyhow::anyhow!("Failed to create scraper: {}", e))?
+    .with_browser_profiles(&settings.data_dir)
+    .with_circuit_breaker(config.circuit_breaker.settings())
+    .with_dedup_window(config.fetch_dedup.window());
 
     // Apply per-source via mappings for caching proxy support if configured
     let scraper = if !scraper_config.via.is_empty() {
@@ -205,6 +270,7 @@ pub(super) async fn cmd_scrape_single_tui(
         scraper
     };
 
+    let hooks = scraper.hooks();
     let stream = match scraper.scrape_stream(workers).await {
         Ok(s) => s,
         Err(e) => {
@@ -223,8 +289,25 @@ pub(super) async fn cmd_scrape_single_tui(
     let mut errors_this_session = 0u64;
     let mut last_heartbeat = std::time::Instant::now();
     let heartbeat_interval = std::time::Duration::from_secs(15);
+    let mut last_pause_check = std::time::Instant::now();
+
+    while let Some(mut result) = rx.recv().await {
+        // Stop promptly if the source is paused or its crawl window closes
+        // mid-run; dropping the receiver winds down the discovery and
+        // download workers
+        if last_pause_check.elapsed() >= PAUSE_CHECK_INTERVAL {
+            last_pause_check = std::time::Instant::now();
+            if source_repo.is_paused(source_id).await? {
+                log_msg(&paused_notice(source_id));
+                break;
+            }
+            // Pending URLs stay queued for the next window
+            if let Some(opens) = crawl_window.reopens_at(chrono::Utc::now()) {
+                log_msg(&window_notice(source_id, opens));
+                break;
+            }
+        }
 
-    while let Some(result) = rx.recv().await {
         if result.not_modified {
             count += 1;
             update_status(&format!("{} {} processed", source_id, count));
@@ -244,13 +327,20 @@ pub(super) async fn cmd_scrape_single_tui(
             continue;
         }
 
+        if let Some(hooks) = &hooks {
+            if !hooks.before_save(&mut result) {
+                tracing::debug!("before_save hook dropped {}", result.url);
+                continue;
+            }
+        }
+
         let content = match &result.content {
             Some(c) => c,
             None => continue,
         };
 
         // Save document using helper
-        if let Err(e) = crate::cli::helpers::save_scraped_document_async(
+        let created = match crate::cli::helpers::save_scraped_document_async(
             &doc_repo,
             content,
             &result,
@@ -259,14 +349,17 @@ pub(super) async fn cmd_scrape_single_tui(
         )
         .await
         {
-            tracing::warn!("Failed to save document: {}", e);
-            errors_this_session += 1;
-            service_status.record_error(&e.to_string());
-            if let Err(e) = service_status_repo.upsert(&service_status).await {
-                tracing::warn!("Failed to update service status on error: {}", e);
+            Ok(created) => created,
+            Err(e) => {
+                tracing::warn!("Failed to save document: {}", e);
+                errors_this_session += 1;
+                service_status.record_error(&e.to_string());
+                if let Err(e) = service_status_repo.upsert(&service_status).await {
+                    tracing::warn!("Failed to update service status on error: {}", e);
+                }
+                continue;
             }
-            continue;
-        }
+        };
 
         count += 1;
         new_this_session += 1;
@@ -291,6 +384,22 @@ pub(super) async fn cmd_scrape_single_tui(
         if limit > 0 && new_this_session as usize >= limit {
             break;
         }
+
+        if quota.is_enabled() {
+            quota_docs += created as u64;
+            quota_bytes += content.len() as u64;
+            let level = quota.level(quota_docs, quota_bytes);
+            if level > quota_level {
+                quota_level = level;
+                let msg = quota_message(source_id, &quota, level, quota_docs, quota_bytes);
+                log_msg(&msg.0);
+                tracing::warn!("{}", msg.1);
+                if level == QuotaLevel::Exceeded && quota.enforce {
+                    service_status.record_error(&msg.1);
+                    break;
+                }
+            }
+        }
     }
 
     // Update last scraped
@@ -322,3 +431,37 @@ pub(super) async fn cmd_scrape_single_tui(
 
     Ok(())
 }
+
+/// Build the console and log lines for a quota warning or stop.
+fn quota_message(
+    source_id: &str,
+    quota: &QuotaConfig,
+    level: QuotaLevel,
+    documents: u64,
+    bytes: u64,
+) -> (String, String) {
+    let mut usage = Vec::new();
+    if let Some(max) = quota.max_documents {
+        usage.push(format!("{}/{} documents", documents, max));
+    }
+    if let Some(max) = quota.max_bytes {
+        usage.push(format!("{}/{}", format_bytes(bytes), format_bytes(max)));
+    }
+    let usage = usage.join(", ");
+
+    let (icon, text) = match level {
+        QuotaLevel::Exceeded if quota.enforce => (
+            style("✗").red(),
+            format!("{} quota reached ({}); scraping paused", source_id, usage),
+        ),
+        QuotaLevel::Exceeded => (
+            style("!").yellow(),
+            format!("{} over quota ({})", source_id, usage),
+        ),
+        _ => (
+            style("!").yellow(),
+            format!("{} nearing quota ({})", source_id, usage),
+        ),
+    };
+    (format!("  {} {}", icon, text), text)
+}
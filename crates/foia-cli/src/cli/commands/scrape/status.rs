@@ -18,6 +18,9 @@ use foia::config::Settings;
 use foia::models::{DocumentStatus, ServiceStatus};
 use foia::repository::util::redact_url_password;
 
+use super::super::helpers::format_number;
+use crate::cli::output;
+
 /// Show overall system status.
 pub async fn cmd_status(
     settings: &Settings,
@@ -25,8 +28,8 @@ pub async fn cmd_status(
     source_id: Option<String>,
     live: bool,
     interval: u64,
-    json: bool,
 ) -> anyhow::Result<()> {
+    let json = output::is_json();
     // If URL is provided (via --url or FOIA_API_URL), fetch from API
     if let Some(base_url) = url {
         return fetch_and_display_api_status(&base_url, source_id.as_deref(), json).await;
@@ -89,7 +92,7 @@ async fn fetch_and_display_api_status(
         .map_err(|e| anyhow::anyhow!("Failed to parse response: {}", e))?;
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&data)?);
+        output::emit("result", data);
     } else {
         display_api_status(&data, base_url);
     }
@@ -193,7 +196,7 @@ fn display_api_status(data: &serde_json::Value, url: &str) {
     println!("{}", separator);
 }
 
-/// Display status as JSON from local database.
+/// Report status from the local database as a `result` event.
 async fn display_status_json(settings: &Settings, _source_id: Option<&str>) -> anyhow::Result<()> {
     let data = fetch_status_data(settings).await?;
 
@@ -216,7 +219,7 @@ async fn display_status_json(settings: &Settings, _source_id: Option<&str>) -> a
         "data_dir": data.data_dir,
     });
 
-    println!("{}", serde_json::to_string_pretty(&json)?);
+    output::emit("result", json);
     Ok(())
 }
 
@@ -316,13 +319,7 @@ async fn display_status_simple(settings: &Settings) -> anyhow::Result<()> {
     println!("{}", style("DOCUMENTS").cyan().bold());
     println!("  {:<20} {:>10}", "Total:", format_number(data.total_docs));
 
-    for status in [
-        DocumentStatus::Pending,
-        DocumentStatus::Downloaded,
-        DocumentStatus::OcrComplete,
-        DocumentStatus::Indexed,
-        DocumentStatus::Failed,
-    ] {
+    for status in DocumentStatus::ALL {
         if let Some(&count) = data.status_counts.get(status.as_str()) {
             println!(
                 "  {:<20} {:>10}",
@@ -675,17 +672,6 @@ fn draw_status(frame: &mut Frame, data: &StatusData) {
     frame.render_widget(footer, chunks[6]);
 }
 
-/// Format a number with thousand separators.
-fn format_number(n: u64) -> String {
-    let s = n.to_string();
-    let bytes: Vec<_> = s.bytes().rev().collect();
-    let chunks: Vec<_> = bytes
-        .chunks(3)
-        .map(|chunk| chunk.iter().rev().map(|&b| b as char).collect::<String>())
-        .collect();
-    chunks.into_iter().rev().collect::<Vec<_>>().join(",")
-}
-
 /// Truncate a string to max length with ellipsis.
 fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
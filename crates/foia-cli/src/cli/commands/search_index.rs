@@ -0,0 +1,200 @@
+//! Search index commands.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use console::style;
+
+use foia::config::{Config, SearchBackend, Settings};
+use foia::services::search::{
+    self, BoxedSearchIndex, FacetCount, SearchError, SearchRequest, TantivyIndex,
+};
+
+fn open_index(settings: &Settings, config: &Config) -> anyhow::Result<BoxedSearchIndex> {
+    Ok(search::open_index(&config.search, &settings.data_dir)?)
+}
+
+/// Index every document, replacing the index's contents.
+pub async fn cmd_search_index_rebuild(settings: &Settings, config: &Config) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let index: BoxedSearchIndex = match search::open_index(&config.search, &settings.data_dir) {
+        Err(SearchError::SchemaMismatch(_)) => {
+            println!(
+                "{} Index was built by another version; recreating it",
+                style("!").yellow()
+            );
+            Arc::new(TantivyIndex::recreate(
+                &config.search.index_dir(&settings.data_dir),
+            )?)
+        }
+        other => other?,
+    };
+
+    println!("Indexing documents into {}...", index.location());
+    let started = Instant::now();
+    let stats = index.update(&repos.documents, true).await?;
+    println!(
+        "{} Indexed {} documents in {:.1}s",
+        style("✓").green(),
+        stats.total,
+        started.elapsed().as_secs_f64()
+    );
+    if config.search.backend == SearchBackend::Database {
+        println!(
+            "{} Set search.backend = \"tantivy\" in the config to search with it",
+            style("!").yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Index documents changed since the last update and drop deleted ones.
+pub async fn cmd_search_index_update(settings: &Settings, config: &Config) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let index = open_index(settings, config)?;
+    let stats = index.update(&repos.documents, false).await?;
+    println!(
+        "{} Updated {} documents, removed {} deleted ({} in index)",
+        style("✓").green(),
+        stats.indexed,
+        stats.deleted,
+        stats.total
+    );
+    Ok(())
+}
+
+/// Show where the index is and how current it is.
+pub async fn cmd_search_index_status(settings: &Settings, config: &Config) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    println!("\n{}", style("Search Index").bold());
+    println!("{}", "-".repeat(90));
+    println!("{:<20} {}", "Backend:", config.search.backend.as_str());
+    if !config.search.backend.is_external() {
+        let dir = config.search.index_dir(&settings.data_dir);
+        if !dir.join("meta.json").exists() {
+            println!("{:<20} {}", "Location:", dir.display());
+            println!(
+                "\n{} No index yet. Build one with 'foia search-index rebuild'.",
+                style("!").yellow()
+            );
+            return Ok(());
+        }
+    }
+
+    let index = open_index(settings, config)?;
+    println!("{:<20} {}", "Location:", index.location());
+    let status = index.status().await?;
+    let documents = repos.documents.count().await?;
+    println!(
+        "{:<20} {} of {} documents",
+        "Indexed:", status.documents, documents
+    );
+    match status.indexed_through {
+        Some(through) => println!(
+            "{:<20} {}",
+            "Last update:",
+            through.format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => println!("{:<20} never", "Last update:"),
+    }
+    Ok(())
+}
+
+fn facet_line(counts: &[FacetCount]) -> String {
+    counts
+        .iter()
+        .take(8)
+        .map(|f| format!("{} ({})", f.value, f.count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Snippet with `<b>` highlights shown in bold.
+fn render_snippet(snippet: &str) -> String {
+    let snippet = snippet.replace('\n', " ");
+    snippet
+        .split("<b>")
+        .enumerate()
+        .map(|(i, part)| match (i, part.split_once("</b>")) {
+            (0, _) | (_, None) => part.to_string(),
+            (_, Some((hit, rest))) => format!("{}{}", style(hit).bold(), rest),
+        })
+        .collect()
+}
+
+/// `foia search` answered by the index.
+pub async fn cmd_search_indexed(
+    settings: &Settings,
+    config: &Config,
+    query: &str,
+    source_id: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let index = open_index(settings, config)?;
+    let expansions: Vec<String> = repos
+        .documents
+        .expand_search_query(query)
+        .await?
+        .into_iter()
+        .skip(1)
+        .collect();
+    if !expansions.is_empty() {
+        println!(
+            "{} Also searching: {}",
+            style("→").dim(),
+            expansions.join(", ")
+        );
+    }
+    let results = index
+        .search(&SearchRequest {
+            query: query.to_string(),
+            expansions,
+            source_id: source_id.map(str::to_string),
+            limit,
+            ..Default::default()
+        })
+        .await?;
+
+    if results.hits.is_empty() {
+        println!(
+            "{} No documents found matching '{}'",
+            style("!").yellow(),
+            query
+        );
+        return Ok(());
+    }
+
+    println!(
+        "\n{} results for '{}' (showing {})\n",
+        results.total,
+        query,
+        results.hits.len()
+    );
+    for hit in &results.hits {
+        println!(
+            "{} {} {}",
+            style(hit.document_id.get(..8).unwrap_or(&hit.document_id)).cyan(),
+            style(&hit.title).bold(),
+            style(format!("[{}]", hit.source_id)).dim()
+        );
+        if let Some(snippet) = &hit.snippet {
+            println!("  ...{}...", render_snippet(snippet));
+        }
+        println!();
+    }
+
+    let facets = &results.facets;
+    for (label, counts) in [
+        ("Sources", &facets.sources),
+        ("Tags", &facets.tags),
+        ("Years", &facets.years),
+        ("Entities", &facets.entity_types),
+    ] {
+        if !counts.is_empty() {
+            println!("{:<10} {}", style(label).dim(), facet_line(counts));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Secrets store management commands.
+
+use std::collections::BTreeMap;
+
+use console::style;
+
+use foia::config::Config;
+use foia::secrets::{self, SecretsFile, SECRET_SCHEME};
+
+/// Store a secret in the secrets file or the OS keychain.
+pub fn cmd_secrets_set(config: &Config, name: &str, keychain: bool) -> anyhow::Result<()> {
+    secrets::validate_name(name)?;
+    let value = read_secret_value(name)?;
+
+    if keychain {
+        secrets::keychain_set(name, &value)?;
+        println!(
+            "{} Stored secret '{}' in the OS keychain",
+            style("✓").green(),
+            name
+        );
+        if !config.secrets.keychain {
+            println!("  Set secrets.keychain to true in your config to use it.");
+        }
+    } else {
+        let file = require_file(config)?;
+        let mut stored = file.load()?;
+        stored.insert(name.to_string(), value);
+        file.save(&stored)?;
+        println!(
+            "{} Stored secret '{}' in {}",
+            style("✓").green(),
+            name,
+            file.path().display()
+        );
+    }
+    println!("  Reference it in config as {}{}", SECRET_SCHEME, name);
+    Ok(())
+}
+
+/// List secret names (never values) from the secrets file and environment.
+pub fn cmd_secrets_list(config: &Config) -> anyhow::Result<()> {
+    let stored = match SecretsFile::from_config(config)? {
+        Some(file) => file.load()?,
+        None => BTreeMap::new(),
+    };
+    let env: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("FOIA_SECRET_").map(str::to_string))
+        .collect();
+
+    if stored.is_empty() && env.is_empty() {
+        println!("{} No secrets stored", style("!").yellow());
+        return Ok(());
+    }
+    for name in stored.keys() {
+        println!("  {:<30} {}", name, style("file").dim());
+    }
+    for name in env {
+        println!(
+            "  {:<30} {}",
+            name.to_lowercase(),
+            style(format!("env FOIA_SECRET_{}", name)).dim()
+        );
+    }
+    if config.secrets.keychain {
+        println!("  {} Keychain entries are not listed", style("→").dim());
+    }
+    Ok(())
+}
+
+/// Remove a secret from the secrets file or the OS keychain.
+pub fn cmd_secrets_remove(config: &Config, name: &str, keychain: bool) -> anyhow::Result<()> {
+    let removed = if keychain {
+        secrets::keychain_remove(name)?
+    } else {
+        let file = require_file(config)?;
+        let mut stored = file.load()?;
+        let removed = stored.remove(name).is_some();
+        if removed {
+            file.save(&stored)?;
+        }
+        removed
+    };
+
+    if removed {
+        println!("{} Removed secret '{}'", style("✓").green(), name);
+    } else {
+        println!("{} Secret '{}' not found", style("!").yellow(), name);
+    }
+    Ok(())
+}
+
+fn require_file(config: &Config) -> anyhow::Result<SecretsFile> {
+    SecretsFile::from_config(config)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "No secrets file configured. Set secrets.file in your config, or use --keychain."
+        )
+    })
+}
+
+/// Read the secret value without echoing it on a terminal, or from stdin
+/// when piped.
+fn read_secret_value(name: &str) -> anyhow::Result<String> {
+    use std::io::IsTerminal;
+
+    let value = if std::io::stdin().is_terminal() {
+        let term = console::Term::stderr();
+        term.write_str(&format!("Value for '{}': ", name))?;
+        term.read_secure_line()?
+    } else {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        line
+    };
+
+    let value = value.trim_end_matches(['\r', '\n']).to_string();
+    if value.is_empty() {
+        anyhow::bail!("Empty secret value");
+    }
+    Ok(value)
+}
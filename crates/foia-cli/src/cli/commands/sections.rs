@@ -0,0 +1,60 @@
+//! Table of contents commands.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::sections::derive_sections;
+
+/// Show the chapters and sections of a document.
+pub async fn cmd_sections_show(
+    settings: &Settings,
+    doc_id: &str,
+    refresh: bool,
+) -> anyhow::Result<()> {
+    let doc_repo = settings.repositories()?.documents;
+    let doc = doc_repo
+        .get(doc_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Document not found: {}", doc_id))?;
+    let version = doc
+        .current_version()
+        .ok_or_else(|| anyhow::anyhow!("Document {} has no versions", doc_id))?;
+
+    let sections = if refresh {
+        derive_sections(&doc_repo, &doc, &settings.documents_dir).await?
+    } else {
+        doc_repo.get_document_sections(doc_id, version.id).await?
+    };
+
+    if sections.is_empty() {
+        println!("{} {} has no sections", style("!").yellow(), doc.title);
+        if !refresh {
+            println!("  Run `foia sections extract` or pass --refresh to derive them");
+        }
+        return Ok(());
+    }
+
+    println!(
+        "{} {} {}",
+        style("✓").green(),
+        doc.title,
+        style(format!("(from {})", sections[0].origin)).dim()
+    );
+    for section in &sections {
+        let indent = "  ".repeat(section.level as usize);
+        let pages = if section.first_page == section.last_page {
+            format!("p. {}", section.first_page)
+        } else {
+            format!("pp. {}–{}", section.first_page, section.last_page)
+        };
+        println!(
+            "{}{} {} {}",
+            indent,
+            section.title,
+            style(pages).dim(),
+            style(format!("#{}", section.id)).dim()
+        );
+    }
+
+    Ok(())
+}
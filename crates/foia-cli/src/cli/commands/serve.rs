@@ -16,12 +16,35 @@ pub async fn cmd_serve(
     no_migrate: bool,
     no_hidden_service: bool,
     use_arti: bool,
+    read_only: bool,
 ) -> anyhow::Result<()> {
     let (host, port) = parse_bind_address(bind)?;
 
+    let mut settings = settings.clone();
+    let detected = !read_only
+        && settings
+            .create_db_context()?
+            .pool()
+            .detect_read_only()
+            .await
+            .unwrap_or(false);
+    if read_only || detected {
+        settings.read_only = true;
+        println!(
+            "{} Read-only replica mode{}: writes disabled",
+            style("→").cyan(),
+            if detected {
+                " (database not writable)"
+            } else {
+                ""
+            }
+        );
+    }
+    let settings = &settings;
+
     let repos = settings.repositories()?;
 
-    if no_migrate {
+    if no_migrate || settings.read_only {
         // Check schema version but don't migrate
         match repos.schema_version().await {
             Ok(Some(version)) => {
@@ -1,10 +1,16 @@
 //! Source management commands.
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use console::style;
 
-use foia::config::Settings;
+use foia::config::{Config, Settings, SourceTemplate, DEFAULT_REFRESH_TTL_DAYS};
+use foia::models::{Source, SourceType};
+use foia_scrape::ConfigurableScraper;
 
 use super::helpers::truncate;
+use crate::cli::output;
 
 /// List configured sources.
 pub async fn cmd_source_list(settings: &Settings) -> anyhow::Result<()> {
@@ -32,11 +38,16 @@ pub async fn cmd_source_list(settings: &Settings) -> anyhow::Result<()> {
             .unwrap_or_else(|| "Never".to_string());
 
         println!(
-            "{:<15} {:<25} {:<10} {}",
+            "{:<15} {:<25} {:<10} {}{}",
             source.id,
             truncate(&source.name, 24),
             source.source_type.as_str(),
-            last_scraped
+            last_scraped,
+            if source.paused {
+                style(" (paused)").yellow().to_string()
+            } else {
+                String::new()
+            }
         );
     }
 
@@ -56,6 +67,7 @@ pub async fn cmd_source_rename(
     let source_repo = repos.sources;
     let doc_repo = repos.documents;
     let crawl_repo = repos.crawl;
+    let agency_repo = repos.agencies;
 
     // Check old source exists
     let old_source = source_repo.get(old_id).await?;
@@ -102,6 +114,12 @@ pub async fn cmd_source_rename(
     // Perform the rename using the repository (handles both SQLite and PostgreSQL)
     let (docs_updated, crawls_updated) = source_repo.rename(old_id, new_id).await?;
 
+    // Keep the agency assignment with the renamed source
+    if let Some(agency_id) = agency_repo.memberships().await?.remove(old_id) {
+        agency_repo.unassign_source(old_id).await?;
+        agency_repo.assign_source(new_id, &agency_id).await?;
+    }
+
     println!(
         "\n{} Renamed '{}' → '{}'",
         style("✓").green(),
@@ -113,3 +131,273 @@ pub async fn cmd_source_rename(
 
     Ok(())
 }
+
+/// Copy a source's scraper config to a new source.
+pub async fn cmd_source_clone(
+    settings: &Settings,
+    existing_id: &str,
+    new_id: &str,
+    name: Option<String>,
+    base_url: Option<String>,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    let Some(existing) = repos.sources.get(existing_id).await? else {
+        println!("{} Source '{}' not found", style("✗").red(), existing_id);
+        return Ok(());
+    };
+    if repos.sources.exists(new_id).await? {
+        println!("{} Source '{}' already exists", style("✗").red(), new_id);
+        return Ok(());
+    }
+
+    let mut config = repos
+        .scraper_configs
+        .get(existing_id)
+        .await?
+        .unwrap_or_default();
+    let old_base = config.base_url_or(&existing.base_url);
+    if let Some(ref base_url) = base_url {
+        config = config.rebased(&old_base, base_url)?;
+    }
+    let name = name.unwrap_or_else(|| new_id.to_string());
+    config.name = Some(name.clone());
+
+    let source = Source::new(
+        new_id.to_string(),
+        existing.source_type,
+        name,
+        base_url.unwrap_or(old_base),
+    );
+    repos.sources.save(&source).await?;
+    repos.scraper_configs.upsert(new_id, &config).await?;
+
+    // The copy joins the same agency as the original
+    if let Some(agency_id) = repos.agencies.memberships().await?.remove(existing_id) {
+        repos.agencies.assign_source(new_id, &agency_id).await?;
+    }
+
+    println!(
+        "{} Cloned '{}' → '{}' ({})",
+        style("✓").green(),
+        existing_id,
+        new_id,
+        source.base_url
+    );
+    Ok(())
+}
+
+/// Add a source from a built-in template.
+pub async fn cmd_source_add(
+    settings: &Settings,
+    id: &str,
+    template: &str,
+    base_url: &str,
+    name: Option<String>,
+) -> anyhow::Result<()> {
+    let Some(template) = SourceTemplate::find(template) else {
+        println!(
+            "{} Unknown template '{}'. Run 'foia source templates' to list them.",
+            style("✗").red(),
+            template
+        );
+        return Ok(());
+    };
+
+    let repos = settings.repositories()?;
+    if repos.sources.exists(id).await? {
+        println!("{} Source '{}' already exists", style("✗").red(), id);
+        return Ok(());
+    }
+
+    let mut config = template.instantiate(base_url)?;
+    let name = name.unwrap_or_else(|| id.to_string());
+    config.name = Some(name.clone());
+
+    let source = Source::new(
+        id.to_string(),
+        SourceType::Custom,
+        name,
+        config.base_url_or(base_url),
+    );
+    repos.sources.save(&source).await?;
+    repos.scraper_configs.upsert(id, &config).await?;
+
+    println!(
+        "{} Added '{}' from template {} ({})",
+        style("✓").green(),
+        id,
+        template.name,
+        source.base_url
+    );
+    Ok(())
+}
+
+/// Pause or resume a source. Crawling, downloading and the daemon skip a
+/// paused source until it is resumed.
+pub async fn cmd_source_pause(settings: &Settings, id: &str, paused: bool) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    // Sources are registered on their first scrape; register one that has
+    // only a config so it can be paused before it is ever crawled
+    if !repos.sources.exists(id).await? {
+        let Some(config) = repos.scraper_configs.get(id).await? else {
+            println!("{} Source '{}' not found", style("✗").red(), id);
+            return Ok(());
+        };
+        let source = Source::new(
+            id.to_string(),
+            SourceType::Custom,
+            config.name_or(id),
+            config.base_url_or(""),
+        );
+        repos.sources.save(&source).await?;
+    }
+
+    repos.sources.set_paused(id, paused).await?;
+    if paused {
+        println!(
+            "{} Paused '{}'. Resume with 'foia source resume {}'",
+            style("✓").green(),
+            id,
+            id
+        );
+    } else {
+        println!("{} Resumed '{}'", style("✓").green(), id);
+    }
+    Ok(())
+}
+
+/// List built-in source templates.
+pub fn cmd_source_templates() -> anyhow::Result<()> {
+    println!("\n{}", style("Source Templates").bold());
+    println!("{}", "-".repeat(60));
+    for template in SourceTemplate::all() {
+        println!("{:<22} {}", template.name, template.description);
+    }
+    println!("\nUse: foia source add <id> --template <name> --base-url <url>");
+    Ok(())
+}
+
+/// Check sources' listing pages against the expectations in their `tests`,
+/// live or from stored listing snapshots. Fails if any test fails.
+pub async fn cmd_source_test(
+    settings: &Settings,
+    config: &Config,
+    source_id: Option<&str>,
+    recorded: bool,
+    record: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    let mut configs: Vec<_> = match source_id {
+        Some(id) => match repos.scraper_configs.get(id).await? {
+            Some(scraper_config) => vec![(id.to_string(), scraper_config)],
+            None => anyhow::bail!("No scraper config for source '{}'", id),
+        },
+        None => repos.scraper_configs.get_all().await?,
+    };
+    configs.retain(|(_, scraper_config)| !scraper_config.tests.is_empty());
+    configs.sort_by(|a, b| a.0.cmp(&b.0));
+    if configs.is_empty() {
+        println!(
+            "{} No source tests configured. Add a \"tests\" list to the scraper config.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let mut report = Vec::new();
+    let (mut total, mut failed) = (0, 0);
+    for (id, mut scraper_config) in configs {
+        // Settings the source leaves unset come from its agency chain
+        repos
+            .agencies
+            .apply_inherited(&id, &mut scraper_config)
+            .await?;
+        let source = match repos.sources.get(&id).await? {
+            Some(source) => source,
+            None => Source::new(
+                id.clone(),
+                SourceType::Custom,
+                scraper_config.name_or(&id),
+                scraper_config.base_url_or(""),
+            ),
+        };
+        let refresh_ttl_days = scraper_config
+            .refresh_ttl_days
+            .or(config.default_refresh_ttl_days)
+            .unwrap_or(DEFAULT_REFRESH_TTL_DAYS);
+        let via = scraper_config.via.clone();
+        let via_mode = scraper_config.via_mode.unwrap_or_default();
+        let scraper = ConfigurableScraper::with_rate_limiter_and_privacy(
+            source,
+            scraper_config,
+            Some(Arc::new(repos.crawl.clone())),
+            Duration::from_millis(settings.request_delay_ms),
+            refresh_ttl_days,
+            None,
+            // Recorded runs make no requests, so don't need Tor
+            (!recorded).then_some(&config.privacy),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to create scraper: {}", e))?;
+        let scraper = if via.is_empty() {
+            scraper
+        } else {
+            scraper.with_via_config(via, via_mode)
+        };
+
+        let pages = if recorded {
+            Some(repos.crawl.latest_listing_contents(&id).await?)
+        } else {
+            None
+        };
+        let results = scraper.run_source_tests(pages.as_ref(), record).await;
+
+        println!("\n{} {}", style("→").cyan(), style(&id).bold());
+        for result in results {
+            total += 1;
+            if result.passed() {
+                println!(
+                    "  {} {} ({} documents, {} pages)",
+                    style("✓").green(),
+                    result.name,
+                    result.matching,
+                    result.pages
+                );
+            } else {
+                failed += 1;
+                println!("  {} {}", style("✗").red(), result.name);
+                for failure in &result.failures {
+                    println!("      {}", failure);
+                }
+            }
+            report.push(serde_json::json!({
+                "source_id": id,
+                "name": result.name,
+                "page": result.page,
+                "passed": result.passed(),
+                "documents": result.documents,
+                "matching": result.matching,
+                "pages": result.pages,
+                "failures": result.failures,
+            }));
+        }
+    }
+
+    output::emit(
+        "result",
+        serde_json::json!({ "tests": report, "total": total, "failed": failed }),
+    );
+    println!();
+    if failed > 0 {
+        anyhow::bail!("{} of {} source test(s) failed", failed, total);
+    }
+    println!(
+        "{} {} source test(s) passed{}",
+        style("✓").green(),
+        total,
+        if recorded { " (recorded pages)" } else { "" }
+    );
+    Ok(())
+}
@@ -1,16 +1,23 @@
 //! Crawl state management commands.
 
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
 
-use foia::config::{Config, Settings, DEFAULT_REFRESH_TTL_DAYS};
+use foia::config::{Config, ScraperConfig, Settings, DEFAULT_REFRESH_TTL_DAYS};
 use foia::models::{Source, SourceType};
+use foia::services::pipeline_eta;
+use foia::services::politeness::{self, PolitenessTotals};
+use foia_scrape::configurable::simulate_html_crawl;
 use foia_scrape::ConfigurableScraper;
 
-use super::helpers::format_bytes;
+use super::helpers::{
+    format_bytes, format_eta, format_number, parse_date_arg, paused_notice, window_notice,
+};
+use crate::cli::output;
 
 /// Show crawl status for sources.
 pub async fn cmd_crawl_status(
@@ -44,6 +51,8 @@ pub async fn cmd_crawl_status(
         )
     };
 
+    let api_schemas = crawl_repo.list_api_schemas(source_id.as_deref()).await?;
+
     for source in sources {
         // Use bulk-loaded data when available, otherwise fetch individually
         let crawl_stats = if source_id.is_none() {
@@ -65,7 +74,9 @@ pub async fn cmd_crawl_status(
         );
         println!("{}", "-".repeat(40));
 
-        let status_str = if state.is_complete() {
+        let status_str = if source.paused {
+            style("Paused").yellow().to_string()
+        } else if state.is_complete() {
             style("Complete").green().to_string()
         } else if state.needs_resume() {
             style("Needs Resume").yellow().to_string()
@@ -87,6 +98,19 @@ pub async fn cmd_crawl_status(
         println!("{:<20} {}", "URLs Pending:", state.urls_pending);
         println!("{:<20} {}", "URLs Failed:", state.urls_failed);
 
+        let drifted = api_schemas
+            .iter()
+            .filter(|s| s.source_id == source.id && s.drift.is_some())
+            .count();
+        if drifted > 0 {
+            println!(
+                "{:<20} {} (see: foia state schema {})",
+                "API Schema Drift:",
+                style(format!("{} endpoint(s)", drifted)).red(),
+                source.id
+            );
+        }
+
         if stats.total_requests > 0 {
             println!();
             println!("{:<20} {}", "Total Requests:", stats.total_requests);
@@ -108,6 +132,353 @@ pub async fn cmd_crawl_status(
     Ok(())
 }
 
+/// Show API response schemas, or acknowledge drift for a source.
+pub async fn cmd_api_schema(
+    settings: &Settings,
+    source_id: Option<&str>,
+    ack: bool,
+    endpoint: Option<&str>,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    if ack {
+        let source_id = source_id.ok_or_else(|| anyhow::anyhow!("--ack needs a source ID"))?;
+        let cleared = repos
+            .crawl
+            .acknowledge_api_drift(source_id, endpoint)
+            .await?;
+        println!(
+            "{} Acknowledged drift on {} endpoint(s) for '{}'",
+            style("✓").green(),
+            cleared,
+            source_id
+        );
+        return Ok(());
+    }
+
+    let schemas = repos.crawl.list_api_schemas(source_id).await?;
+    if schemas.is_empty() {
+        println!(
+            "{} No API responses fingerprinted yet (run a crawl of an API source)",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:<16} {:<40} {:<18} {:>6} Last Seen",
+        "Source", "Endpoint", "Fingerprint", "Paths"
+    );
+    for schema in &schemas {
+        let fingerprint = if schema.fingerprint.is_empty() {
+            "-"
+        } else {
+            schema.fingerprint.as_str()
+        };
+        println!(
+            "{:<16} {:<40} {:<18} {:>6} {}",
+            schema.source_id,
+            schema.endpoint,
+            fingerprint,
+            schema.paths.len(),
+            schema.last_seen_at.format("%Y-%m-%d %H:%M")
+        );
+        if let Some(ref drift) = schema.drift {
+            let at = schema
+                .drift_at
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            println!(
+                "  {} {} {}",
+                style("drift").red().bold(),
+                style(at).dim(),
+                drift
+            );
+        }
+    }
+
+    let drifted = schemas.iter().filter(|s| s.drift.is_some()).count();
+    if drifted > 0 {
+        println!();
+        println!(
+            "{} {} endpoint(s) drifted. Update the source's results_path/url_extraction, then: foia state schema <source> --ack",
+            style("!").yellow(),
+            drifted
+        );
+    }
+
+    Ok(())
+}
+
+/// Replay a source's stored listing pages through a candidate config and
+/// compare what it finds with the current config.
+pub async fn cmd_crawl_simulate(
+    settings: &Settings,
+    source_id: &str,
+    config_path: Option<&Path>,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    let Some(current) = repos.scraper_configs.get(source_id).await? else {
+        println!("{} No config for source '{}'", style("✗").red(), source_id);
+        return Ok(());
+    };
+    let candidate: ScraperConfig = match config_path {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow::anyhow!("Invalid config in {}: {}", path.display(), e))?,
+        None => current.clone(),
+    };
+    if candidate.discovery.discovery_type != "html_crawl" {
+        println!(
+            "{} Simulation replays HTML crawls only; '{}' uses {}",
+            style("!").yellow(),
+            source_id,
+            candidate.discovery.discovery_type
+        );
+        return Ok(());
+    }
+
+    let pages = repos.crawl.latest_listing_contents(source_id).await?;
+    if pages.is_empty() {
+        println!(
+            "{} No stored listing pages for '{}'. Set discovery.snapshot_listings and crawl once.",
+            style("!").yellow(),
+            source_id
+        );
+        return Ok(());
+    }
+    let requested = repos.crawl.requested_urls(source_id).await?;
+
+    let before = simulate_html_crawl(&current, &pages, &requested);
+    let after = simulate_html_crawl(&candidate, &pages, &requested);
+
+    println!(
+        "\n{} Simulated crawl of '{}' over {} stored listing page(s)",
+        style("→").cyan(),
+        source_id,
+        pages.len()
+    );
+    println!("  Pages replayed:       {}", after.pages_replayed);
+    println!("  Documents discovered: {}", after.documents.len());
+    if config_path.is_some() {
+        println!("  Current config finds: {}", before.documents.len());
+    }
+
+    let gained: Vec<&String> = after.documents.difference(&before.documents).collect();
+    let lost: Vec<&String> = before.documents.difference(&after.documents).collect();
+    print_url_list("New documents", style("+").green(), &gained, limit);
+    print_url_list("No longer found", style("-").red(), &lost, limit);
+
+    let missing: Vec<&String> = after.missing_bodies.iter().collect();
+    let unfetched: Vec<&String> = after.unfetched.iter().collect();
+    print_url_list(
+        "Fetched before but not stored (results may be incomplete)",
+        style("?").yellow(),
+        &missing,
+        limit,
+    );
+    print_url_list(
+        "Never fetched (a live crawl would request these)",
+        style("→").cyan(),
+        &unfetched,
+        limit,
+    );
+
+    Ok(())
+}
+
+/// Delete listing snapshots past retention.
+pub async fn cmd_prune_snapshots(
+    settings: &Settings,
+    source_id: Option<&str>,
+    days: Option<u64>,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+
+    // An explicit --days applies to the selection as a whole; otherwise
+    // each source uses its own snapshot_ttl_days
+    let targets: Vec<(Option<String>, u64)> = match days {
+        Some(days) => vec![(source_id.map(str::to_string), days)],
+        None => repos
+            .scraper_configs
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|(id, _)| source_id.is_none_or(|s| s == id))
+            .filter_map(|(id, config)| config.discovery.snapshot_ttl_days.map(|d| (Some(id), d)))
+            .collect(),
+    };
+
+    if targets.is_empty() {
+        println!(
+            "{} No snapshot retention configured. Set discovery.snapshot_ttl_days or pass --days.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    let mut total = 0;
+    for (source, days) in targets {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        let pruned = repos
+            .crawl
+            .prune_listing_snapshots(source.as_deref(), cutoff)
+            .await?;
+        println!(
+            "{} {}: {} snapshot(s) older than {} days",
+            style("→").cyan(),
+            source.as_deref().unwrap_or("all sources"),
+            pruned,
+            days
+        );
+        total += pruned;
+    }
+    println!(
+        "{} Pruned {} listing snapshot(s)",
+        style("✓").green(),
+        total
+    );
+
+    Ok(())
+}
+
+/// Show each pipeline stage's backlog, throughput and estimated completion.
+pub async fn cmd_eta(
+    settings: &Settings,
+    source_id: Option<&str>,
+    window_minutes: u64,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let window = Duration::from_secs(window_minutes.max(1) * 60);
+    let stages = pipeline_eta::estimate(&repos.documents, &repos.crawl, source_id, window).await?;
+
+    if output::is_json() {
+        output::emit("result", serde_json::to_value(&stages)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<12} {:>10} {:>12} {:>10}  Completes",
+        "Stage", "Pending", "Per hour", "ETA"
+    );
+    for stage in &stages {
+        let completes = stage
+            .completes_at
+            .filter(|_| stage.pending > 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<12} {:>10} {:>12.1} {:>10}  {}",
+            stage.stage.label(),
+            format_number(stage.pending),
+            stage.per_hour,
+            format_eta(stage.eta_secs),
+            completes
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        style(format!(
+            "Throughput measured over the last {} minute(s)",
+            window_minutes.max(1)
+        ))
+        .dim()
+    );
+
+    Ok(())
+}
+
+/// List domains the circuit breaker paused.
+pub async fn cmd_incidents(settings: &Settings, limit: usize) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let incidents = repos.crawl.list_incidents(limit).await?;
+
+    if incidents.is_empty() {
+        println!("{} No domains have been paused", style("✓").green());
+        return Ok(());
+    }
+
+    println!(
+        "{:<6} {:<8} {:<16} {:<30} {:<9} {:<24} Paused until",
+        "ID", "Status", "Source", "Domain", "Blocked", "Statuses"
+    );
+    for incident in &incidents {
+        let status = if incident.is_active() {
+            style("paused").yellow()
+        } else {
+            style("resumed").dim()
+        };
+        let statuses = incident
+            .statuses
+            .iter()
+            .map(|(code, count)| format!("{}×{}", code, count))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let blocked = format!("{}/{}", incident.blocked, incident.responses);
+        println!(
+            "{:<6} {:<8} {:<16} {:<30} {:<9} {:<24} {}",
+            incident.id,
+            status,
+            incident.source_id,
+            incident.domain,
+            blocked,
+            statuses,
+            incident.paused_until.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    let active = incidents.iter().filter(|i| i.is_active()).count();
+    if active > 0 {
+        println!();
+        println!(
+            "{} {} domain(s) paused. Resume early with: foia state resume-domain <domain>",
+            style("!").yellow(),
+            active
+        );
+    }
+
+    Ok(())
+}
+
+/// End a circuit breaker pause before its cool-down runs out.
+pub async fn cmd_resume_domain(settings: &Settings, domain: &str) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    if repos.crawl.resume_domain(domain).await? {
+        println!(
+            "{} Resumed {}; requests start again at a cautious pace",
+            style("✓").green(),
+            domain
+        );
+    } else {
+        println!("{} {} is not paused", style("!").yellow(), domain);
+    }
+    Ok(())
+}
+
+fn print_url_list(
+    title: &str,
+    marker: console::StyledObject<&str>,
+    urls: &[&String],
+    limit: usize,
+) {
+    if urls.is_empty() {
+        return;
+    }
+    println!("\n{} ({}):", style(title).bold(), urls.len());
+    for url in urls.iter().take(limit) {
+        println!("  {} {}", marker, url);
+    }
+    if urls.len() > limit {
+        println!("  ... and {} more", urls.len() - limit);
+    }
+}
+
 /// Clear crawl state for a source.
 pub async fn cmd_crawl_clear(
     settings: &Settings,
@@ -138,13 +509,80 @@ pub async fn cmd_crawl_clear(
     Ok(())
 }
 
+/// Export logged requests to a domain as CSV and print traffic totals.
+pub async fn cmd_request_report(
+    settings: &Settings,
+    domain: &str,
+    since: Option<&str>,
+    until: Option<&str>,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let since = since.map(parse_date_arg).transpose()?;
+    let until = until.map(parse_date_arg).transpose()?;
+
+    let repos = settings.repositories()?;
+    let requests = repos
+        .crawl
+        .get_requests_for_domain(domain, since, until)
+        .await?;
+    let csv = politeness::requests_to_csv(&requests);
+
+    match output {
+        Some(path) => std::fs::write(path, csv)?,
+        None => {
+            use std::io::Write;
+            std::io::stdout().write_all(csv.as_bytes())?;
+        }
+    }
+
+    // Totals go to stderr so stdout stays valid CSV
+    let totals = PolitenessTotals::from_requests(&requests);
+    eprintln!("\n{}", style(format!("Request Report: {}", domain)).bold());
+    eprintln!("{}", "-".repeat(40));
+    eprintln!("{:<20} {}", "Requests:", totals.requests);
+    eprintln!("{:<20} {}", "Downloaded:", format_bytes(totals.bytes));
+    if let (Some(first), Some(last)) = (totals.first_request, totals.last_request) {
+        eprintln!("{:<20} {}", "First Request:", first.to_rfc3339());
+        eprintln!("{:<20} {}", "Last Request:", last.to_rfc3339());
+    }
+    if let Some(avg) = totals.avg_interval_secs {
+        eprintln!("{:<20} {:.1}s", "Avg Interval:", avg);
+    }
+    if let Some(min) = totals.min_interval_secs {
+        eprintln!("{:<20} {:.1}s", "Min Interval:", min);
+    }
+    eprintln!("{:<20} {}", "Identified:", totals.identified);
+    eprintln!("{:<20} {}", "No Response:", totals.failed);
+    for (status, count) in &totals.error_statuses {
+        eprintln!("{:<20} {}", format!("  HTTP {}:", status), count);
+    }
+    if let Some(path) = output {
+        eprintln!(
+            "{} Wrote {} requests to {}",
+            style("✓").green(),
+            totals.requests,
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
 /// Discover document URLs from a source (does not download).
-pub async fn cmd_crawl(settings: &Settings, source_id: &str, _limit: usize) -> anyhow::Result<()> {
+///
+/// Outside the source's crawl window this exits, or with `wait` sleeps
+/// until the window opens.
+pub async fn cmd_crawl(
+    settings: &Settings,
+    source_id: &str,
+    _limit: usize,
+    wait: bool,
+) -> anyhow::Result<()> {
     settings.ensure_directories()?;
 
     // Load scraper config from database (server config)
     let repos = settings.repositories()?;
-    let scraper_config = match repos.scraper_configs.get(source_id).await? {
+    let mut scraper_config = match repos.scraper_configs.get(source_id).await? {
         Some(c) => c,
         None => {
             println!(
@@ -160,7 +598,12 @@ pub async fn cmd_crawl(settings: &Settings, source_id: &str, _limit: usize) -> a
     let config = Config::load().await;
 
     let source_repo = repos.sources;
-    let crawl_repo = Arc::new(repos.crawl);
+    // A source sharing its host's frontier queues what it finds into it
+    let crawl_repo = match repos.scraper_configs.shared_frontier(source_id).await? {
+        Some(frontier) => repos.crawl.with_frontier(frontier),
+        None => repos.crawl,
+    };
+    let crawl_repo = Arc::new(crawl_repo);
 
     // Auto-register source
     let source = match source_repo.get(source_id).await? {
@@ -181,17 +624,23 @@ pub async fn cmd_crawl(settings: &Settings, source_id: &str, _limit: usize) -> a
             new_source
         }
     };
+    if source.paused {
+        println!("{}", paused_notice(source_id));
+        return Ok(());
+    }
+    if let Some(opens) = scraper_config.crawl_window.reopens_at(chrono::Utc::now()) {
+        println!("{}", window_notice(source_id, opens));
+        if !wait {
+            println!("  Run again then, or pass --wait to sleep until it opens");
+            return Ok(());
+        }
+        let delay = (opens - chrono::Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(delay).await;
+    }
 
     // Check crawl state and update config hash
     {
-        let config_hash = {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-            let json = serde_json::to_string(&scraper_config).unwrap_or_default();
-            let mut hasher = DefaultHasher::new();
-            json.hash(&mut hasher);
-            format!("{:x}", hasher.finish())
-        };
+        let config_hash = scraper_config.config_hash();
 
         let config_changed = crawl_repo
             .check_config_changed(source_id, &config_hash)
@@ -215,6 +664,12 @@ pub async fn cmd_crawl(settings: &Settings, source_id: &str, _limit: usize) -> a
         let _ = config_changed;
     }
 
+    // Settings the source leaves unset come from its agency chain
+    repos
+        .agencies
+        .apply_inherited(source_id, &mut scraper_config)
+        .await?;
+
     // Create scraper for discovery
     let refresh_ttl_days = scraper_config
         .refresh_ttl_days
@@ -226,7 +681,10 @@ pub async fn cmd_crawl(settings: &Settings, source_id: &str, _limit: usize) -> a
         Some(crawl_repo.clone()),
         Duration::from_millis(settings.request_delay_ms),
         refresh_ttl_days,
-    );
+    )
+    .with_browser_profiles(&settings.data_dir)
+    .with_circuit_breaker(config.circuit_breaker.settings())
+    .with_dedup_window(config.fetch_dedup.window());
 
     // Apply per-source via mappings for caching proxy support if configured
     let scraper = if !scraper_config.via.is_empty() {
@@ -248,6 +706,14 @@ pub async fn cmd_crawl(settings: &Settings, source_id: &str, _limit: usize) -> a
     pb.finish_and_clear();
 
     let state = crawl_repo.get_crawl_state(source_id).await?;
+    output::emit(
+        "result",
+        serde_json::json!({
+            "source_id": source_id,
+            "discovered": urls.len(),
+            "pending": state.urls_pending,
+        }),
+    );
 
     println!(
         "{} Discovered {} URLs from {} ({} pending)",
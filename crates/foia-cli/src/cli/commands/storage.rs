@@ -0,0 +1,179 @@
+//! Storage usage report and guided cleanup.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::storage_report::{self, Reclaim, StorageReport, Usage};
+
+use super::helpers::{format_bytes, truncate};
+
+/// Print one usage table with each row's share of `total`.
+fn print_usage(title: &str, rows: &[Usage], total: u64) {
+    println!("\n{}", style(title).bold());
+    println!("{}", "-".repeat(90));
+    println!("{:<40} {:>12} {:>14} {:>8}", "", "Files", "Size", "Share");
+    for row in rows {
+        let share = if total > 0 {
+            row.bytes as f64 * 100.0 / total as f64
+        } else {
+            0.0
+        };
+        println!(
+            "{:<40} {:>12} {:>14} {:>7.1}%",
+            truncate(&row.key, 40),
+            row.files,
+            format_bytes(row.bytes),
+            share
+        );
+    }
+}
+
+/// Break storage down by source, type and artifact, and list what can be
+/// reclaimed with the command for each.
+pub async fn cmd_storage_report(settings: &Settings, show_orphans: bool) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    println!("{} Measuring storage...", style("→").cyan());
+    let report = StorageReport::build(&repos.documents, &settings.documents_dir).await?;
+
+    let total = report.artifacts.originals;
+    print_usage("By Source", &report.by_source, total);
+    print_usage("By Type", &report.by_category, total);
+
+    println!("\n{}", style("By Artifact").bold());
+    println!("{}", "-".repeat(90));
+    println!(
+        "{:<40} {:>14}",
+        "Originals (files on disk)",
+        format_bytes(report.artifacts.originals)
+    );
+    println!(
+        "{:<40} {:>14}",
+        "Derived (page OCR, analysis output)",
+        format_bytes(report.artifacts.derived)
+    );
+    println!(
+        "{:<40} {:>14}",
+        "Text (extracted and final page text)",
+        format_bytes(report.artifacts.text)
+    );
+    if report.artifacts.derived > 0 {
+        println!(
+            "  {}",
+            style("Compress intermediate page text with: foia db compress-text").dim()
+        );
+    }
+
+    println!("\n{}", style("Reclaimable").bold());
+    println!("{}", "-".repeat(90));
+    if report.reclaimable.is_empty() {
+        println!("{} Nothing to reclaim", style("✓").green());
+        return Ok(());
+    }
+    for item in &report.reclaimable {
+        println!(
+            "{} {:<12} {:>8} file(s) {:>14}  {}",
+            style("!").yellow(),
+            item.kind.as_str(),
+            item.files,
+            format_bytes(item.bytes),
+            item.advice
+        );
+        println!("  {}", style(item.command).cyan());
+    }
+    println!(
+        "\n  Up to {} reclaimable",
+        style(format_bytes(report.reclaimable_bytes())).bold()
+    );
+
+    if show_orphans {
+        println!("\n{}", style("Orphaned Files").bold());
+        println!("{}", "-".repeat(90));
+        for orphan in &report.orphans {
+            println!(
+                "{:>12}  {}",
+                format_bytes(orphan.bytes),
+                orphan.path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Reclaim one category of waste. Without `confirm` only reports what would
+/// be deleted.
+pub async fn cmd_storage_clean(
+    settings: &Settings,
+    category: &str,
+    confirm: bool,
+) -> anyhow::Result<()> {
+    let Some(kind) = Reclaim::from_str(category) else {
+        anyhow::bail!(
+            "Unknown category '{}' (expected failed or orphans)",
+            category
+        );
+    };
+    let repos = settings.repositories()?;
+    let doc_repo = repos.documents;
+
+    let (count, bytes) = match kind {
+        Reclaim::Duplicates => {
+            println!(
+                "{} Duplicates are merged, not deleted: run '{}'",
+                style("!").yellow(),
+                kind.command()
+            );
+            return Ok(());
+        }
+        Reclaim::Orphans => {
+            let known = storage_report::stored_paths(&doc_repo, &settings.documents_dir).await?;
+            let orphans = storage_report::find_orphans(&settings.documents_dir, &known)?;
+            let bytes = if confirm {
+                storage_report::remove_orphans(&orphans)?
+            } else {
+                orphans.iter().map(|o| o.bytes).sum()
+            };
+            (orphans.len(), bytes)
+        }
+        Reclaim::Failed => {
+            let ids = doc_repo.failed_document_ids().await?;
+            let mut bytes = 0;
+            if confirm {
+                for id in &ids {
+                    bytes += storage_report::remove_failed_document(
+                        &doc_repo,
+                        &settings.documents_dir,
+                        id,
+                    )
+                    .await?;
+                }
+            } else {
+                bytes = doc_repo.failed_document_storage().await?.bytes;
+            }
+            (ids.len(), bytes)
+        }
+    };
+
+    let noun = match kind {
+        Reclaim::Failed => "failed document(s)",
+        _ => "orphaned file(s)",
+    };
+    if confirm {
+        println!(
+            "{} Deleted {} {}, freed {}",
+            style("✓").green(),
+            count,
+            noun,
+            format_bytes(bytes)
+        );
+    } else {
+        println!(
+            "{} Would delete {} {}, freeing {}. Re-run with --confirm.",
+            style("!").yellow(),
+            count,
+            noun,
+            format_bytes(bytes)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,291 @@
+//! Sync commands for exchanging data with another foia instance.
+
+use console::style;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use foia::config::{Config, Settings};
+use foia::services::sync::{
+    diff_manifests, ApplyReport, BlobRef, CrawlApplyReport, SyncBatch, SyncCrawlState,
+    SyncManifest, SyncService, SYNC_PROTOCOL_VERSION,
+};
+
+/// Documents sent per request.
+const BATCH_SIZE: usize = 50;
+
+/// HTTP client for a remote instance's `/api/sync` endpoints.
+struct RemoteClient {
+    client: reqwest::Client,
+    base: url::Url,
+    token: Option<String>,
+}
+
+impl RemoteClient {
+    fn new(config: &Config, remote: &str, token: Option<String>) -> anyhow::Result<Self> {
+        let resolved = config.sync.resolve_remote(remote).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown remote '{}'. Add it under sync.remotes or pass a URL.",
+                remote
+            )
+        })?;
+
+        // ALLOWED: Sync talks to our own instance, not a scraped source.
+        // Still routed through the configured proxy so onion remotes work.
+        #[allow(clippy::disallowed_methods)]
+        let mut builder = reqwest::Client::builder().timeout(std::time::Duration::from_secs(300));
+        if let Some(proxy) = config.privacy.effective_proxy_url() {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            base: url::Url::parse(&resolved.url)?,
+            token: token.or(resolved.token),
+        })
+    }
+
+    fn url(&self, segments: &[&str]) -> anyhow::Result<url::Url> {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Remote URL cannot be a base: {}", self.base))?
+            .pop_if_empty()
+            .extend(["api", "sync"])
+            .extend(segments);
+        Ok(url)
+    }
+
+    fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::RequestBuilder {
+        let req = self.client.request(method, url);
+        match &self.token {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        }
+    }
+
+    async fn check(response: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("Remote returned {}: {}", status, body.trim())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, segments: &[&str]) -> anyhow::Result<T> {
+        let response = self
+            .request(reqwest::Method::GET, self.url(segments)?)
+            .send()
+            .await?;
+        Ok(Self::check(response).await?.json().await?)
+    }
+
+    async fn post_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        segments: &[&str],
+        body: &B,
+    ) -> anyhow::Result<T> {
+        let response = self
+            .request(reqwest::Method::POST, self.url(segments)?)
+            .json(body)
+            .send()
+            .await?;
+        Ok(Self::check(response).await?.json().await?)
+    }
+
+    /// Download a file; `None` if the remote doesn't have it on disk.
+    async fn get_blob(&self, blob: &BlobRef) -> anyhow::Result<Option<Vec<u8>>> {
+        let url = self.url(&["blobs", &blob.document_id, &blob.content_hash])?;
+        let response = self.request(reqwest::Method::GET, url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(Self::check(response).await?.bytes().await?.to_vec()))
+    }
+
+    async fn put_blob(&self, blob: &BlobRef, content: Vec<u8>) -> anyhow::Result<()> {
+        let url = self.url(&["blobs", &blob.document_id, &blob.content_hash])?;
+        let response = self
+            .request(reqwest::Method::PUT, url)
+            .body(content)
+            .send()
+            .await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    async fn manifest(&self) -> anyhow::Result<SyncManifest> {
+        let manifest: SyncManifest = self.get_json(&["manifest"]).await?;
+        if manifest.protocol != SYNC_PROTOCOL_VERSION {
+            anyhow::bail!(
+                "Remote uses sync protocol {}, this build uses {}",
+                manifest.protocol,
+                SYNC_PROTOCOL_VERSION
+            );
+        }
+        Ok(manifest)
+    }
+}
+
+#[derive(Default)]
+struct Totals {
+    docs: ApplyReport,
+    crawl: CrawlApplyReport,
+    files: usize,
+    files_unavailable: usize,
+}
+
+impl Totals {
+    fn add(&mut self, report: &ApplyReport) {
+        self.docs.documents_created += report.documents_created;
+        self.docs.documents_updated += report.documents_updated;
+        self.docs.versions_added += report.versions_added;
+        self.docs.pages_written += report.pages_written;
+    }
+
+    fn print(&self, direction: &str) {
+        println!("\n{} Sync {} complete", style("✓").green(), direction);
+        println!("{:<18} {}", "Documents added:", self.docs.documents_created);
+        println!("{:<18} {}", "Documents updated:", self.docs.documents_updated);
+        println!("{:<18} {}", "Versions added:", self.docs.versions_added);
+        println!("{:<18} {}", "Pages written:", self.docs.pages_written);
+        println!("{:<18} {}", "Files copied:", self.files);
+        if self.files_unavailable > 0 {
+            println!(
+                "{:<18} {}",
+                "Files missing:",
+                style(self.files_unavailable).yellow()
+            );
+        }
+        println!(
+            "{:<18} {} added, {} updated",
+            "Crawl URLs:", self.crawl.added, self.crawl.updated
+        );
+    }
+}
+
+fn print_plan(documents: usize, sources: usize, remote: &str, direction: &str) {
+    println!(
+        "{} {} {} document(s) and crawl state for {} source(s) {} {}",
+        style("→").cyan(),
+        if direction == "push" { "Pushing" } else { "Pulling" },
+        documents,
+        sources,
+        if direction == "push" { "to" } else { "from" },
+        remote
+    );
+}
+
+/// Send local changes to a remote instance.
+pub async fn cmd_sync_push(
+    settings: &Settings,
+    config: &Config,
+    remote: &str,
+    token: Option<String>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let client = RemoteClient::new(config, remote, token)?;
+    let repos = settings.repositories()?;
+    let service = SyncService::new(
+        &repos.documents,
+        &repos.sources,
+        &repos.crawl,
+        &settings.documents_dir,
+    );
+
+    let local = service.manifest().await?;
+    let remote_manifest = client.manifest().await?;
+    let diff = diff_manifests(&local, &remote_manifest);
+
+    print_plan(diff.documents.len(), diff.sources.len(), remote, "push");
+    if dry_run || diff.is_empty() {
+        return Ok(());
+    }
+
+    let mut totals = Totals::default();
+    for chunk in diff.documents.chunks(BATCH_SIZE) {
+        let batch = service.export(chunk).await?;
+        let report: ApplyReport = client.post_json(&["apply"], &batch).await?;
+        totals.add(&report);
+
+        for blob in &report.missing_blobs {
+            let path = service
+                .blob_path(&blob.document_id, &blob.content_hash)
+                .await?;
+            match path.map(|p| foia::storage::read_content(&p)) {
+                Some(Ok(content)) => {
+                    client.put_blob(blob, content).await?;
+                    totals.files += 1;
+                }
+                _ => totals.files_unavailable += 1,
+            }
+        }
+    }
+
+    for source_id in &diff.sources {
+        let state = service.export_crawl(source_id).await?;
+        let report: CrawlApplyReport = client.post_json(&["crawl"], &state).await?;
+        totals.crawl.added += report.added;
+        totals.crawl.updated += report.updated;
+    }
+
+    totals.print("push");
+    Ok(())
+}
+
+/// Fetch remote changes into the local instance.
+pub async fn cmd_sync_pull(
+    settings: &Settings,
+    config: &Config,
+    remote: &str,
+    token: Option<String>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let client = RemoteClient::new(config, remote, token)?;
+    let repos = settings.repositories()?;
+    let service = SyncService::new(
+        &repos.documents,
+        &repos.sources,
+        &repos.crawl,
+        &settings.documents_dir,
+    );
+
+    let remote_manifest = client.manifest().await?;
+    let local = service.manifest().await?;
+    let diff = diff_manifests(&remote_manifest, &local);
+
+    print_plan(diff.documents.len(), diff.sources.len(), remote, "pull");
+    if dry_run || diff.is_empty() {
+        return Ok(());
+    }
+
+    let mut totals = Totals::default();
+    for chunk in diff.documents.chunks(BATCH_SIZE) {
+        let batch: SyncBatch = client
+            .post_json(&["export"], &serde_json::json!({ "document_ids": chunk }))
+            .await?;
+        let report = service.apply(&batch).await?;
+        totals.add(&report);
+
+        for blob in &report.missing_blobs {
+            match client.get_blob(blob).await? {
+                Some(content) => {
+                    service
+                        .store_blob(&blob.document_id, &blob.content_hash, &content)
+                        .await?;
+                    totals.files += 1;
+                }
+                None => totals.files_unavailable += 1,
+            }
+        }
+    }
+
+    for source_id in &diff.sources {
+        let state: SyncCrawlState = client.get_json(&["crawl", source_id]).await?;
+        let report = service.apply_crawl(&state).await?;
+        totals.crawl.added += report.added;
+        totals.crawl.updated += report.updated;
+    }
+
+    totals.print("pull");
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Curator review of suggested document titles.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::titles::TitleStatus;
+
+use crate::cli::output;
+
+/// List title suggestions, those awaiting review unless a status is given.
+pub async fn cmd_titles_review(
+    settings: &Settings,
+    source_id: Option<&str>,
+    status: Option<&str>,
+    limit: u32,
+) -> anyhow::Result<()> {
+    let status = match status {
+        Some(s) => Some(TitleStatus::from_str(s).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown status '{}' (suggested, applied, accepted, rejected)",
+                s
+            )
+        })?),
+        None => None,
+    };
+    let repos = settings.repositories()?;
+    let suggestions = repos
+        .documents
+        .list_title_suggestions(source_id, status, limit)
+        .await?
+        .into_iter()
+        .filter(|s| status.is_some() || s.status.needs_review())
+        .collect::<Vec<_>>();
+
+    if output::is_json() {
+        output::emit("result", serde_json::to_value(&suggestions)?);
+        return Ok(());
+    }
+    if suggestions.is_empty() {
+        println!("{} No title suggestions to review", style("!").yellow());
+        return Ok(());
+    }
+
+    for s in &suggestions {
+        let status = match s.status {
+            TitleStatus::Applied => style(s.status.as_str()).yellow(),
+            TitleStatus::Accepted => style(s.status.as_str()).green(),
+            TitleStatus::Rejected => style(s.status.as_str()).red(),
+            TitleStatus::Suggested => style(s.status.as_str()).cyan(),
+        };
+        println!(
+            "{} [{}] {}",
+            style(&s.document_id).dim(),
+            status,
+            style(&s.suggested_title).bold()
+        );
+        println!(
+            "  was {} {}",
+            s.original_title,
+            style(format!("({}, {})", s.source_id, s.method)).dim()
+        );
+    }
+    println!();
+    println!(
+        "  Accept with `foia titles accept <DOC_ID>`, reject with `foia titles reject <DOC_ID>`"
+    );
+    Ok(())
+}
+
+/// Accept or reject the title suggested for a document.
+pub async fn cmd_titles_decide(
+    settings: &Settings,
+    doc_id: &str,
+    accept: bool,
+) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let Some(suggestion) = repos
+        .documents
+        .review_title_suggestion(doc_id, accept)
+        .await?
+    else {
+        anyhow::bail!("No title suggestion for document {}", doc_id);
+    };
+
+    if output::is_json() {
+        output::emit("result", serde_json::to_value(&suggestion)?);
+        return Ok(());
+    }
+    let title = if accept {
+        &suggestion.suggested_title
+    } else {
+        &suggestion.original_title
+    };
+    println!(
+        "{} {} {}: {}",
+        style("✓").green(),
+        if accept { "Accepted" } else { "Rejected" },
+        style(doc_id).dim(),
+        title
+    );
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! Crowdsourced transcription commands.
+
+use console::style;
+
+use foia::config::Settings;
+use foia::services::transcription::{self, TaskStatus};
+
+use super::helpers::{format_number, truncate};
+
+/// Package pages OCR couldn't read into transcription tasks.
+pub async fn cmd_transcribe_queue(
+    settings: &Settings,
+    source_id: Option<&str>,
+    below: f64,
+    limit: u32,
+) -> anyhow::Result<()> {
+    if !(0.0..=1.0).contains(&below) {
+        anyhow::bail!("--below must be between 0 and 1");
+    }
+    let repos = settings.repositories()?;
+    let queued = transcription::queue_pages(&repos.documents, source_id, below, limit).await?;
+    if queued == 0 {
+        println!("{} No pages need transcription", style("!").yellow());
+    } else {
+        println!(
+            "{} Queued {} pages for transcription",
+            style("✓").green(),
+            format_number(queued as u64)
+        );
+    }
+    Ok(())
+}
+
+/// Tasks by status, then the tasks waiting for a curator.
+pub async fn cmd_transcribe_status(settings: &Settings) -> anyhow::Result<()> {
+    let repos = settings.repositories()?;
+    let counts = repos.documents.count_transcription_tasks().await?;
+    if counts.is_empty() {
+        println!(
+            "{} No transcription tasks. Queue pages with 'foia transcribe queue'.",
+            style("!").yellow()
+        );
+        return Ok(());
+    }
+
+    println!("\n{}", style("Transcription").bold());
+    println!("{}", "-".repeat(40));
+    for status in [
+        TaskStatus::Open,
+        TaskStatus::Disputed,
+        TaskStatus::Review,
+        TaskStatus::Accepted,
+    ] {
+        let n = counts
+            .iter()
+            .find(|(s, _)| *s == status)
+            .map(|(_, n)| *n)
+            .unwrap_or(0);
+        println!("{:<12} {:>10}", status.as_str(), format_number(n));
+    }
+
+    let review = repos
+        .documents
+        .list_transcription_tasks(Some(TaskStatus::Review), 50)
+        .await?;
+    if !review.is_empty() {
+        println!("\n{}", style("Waiting for a curator").bold());
+        println!("{}", "-".repeat(60));
+        for task in &review {
+            println!(
+                "{:>6}  {:<40} page {}",
+                task.id,
+                truncate(&task.document_id, 40),
+                task.page_number
+            );
+        }
+        println!(
+            "\nSettle them with POST /api/transcription/tasks/{{id}}/resolve, or see each volunteer's text with GET /api/transcription/tasks/{{id}}."
+        );
+    }
+    Ok(())
+}
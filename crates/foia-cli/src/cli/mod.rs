@@ -3,6 +3,7 @@
 mod commands;
 pub mod helpers;
 pub mod icons;
+pub mod output;
 pub mod progress;
 pub mod tui;
 
@@ -0,0 +1,130 @@
+//! Machine-readable output mode.
+//!
+//! With `--json`, a command reports what it does as newline-delimited JSON
+//! events on stdout: `started` and `finished` around the command,
+//! `task_started`, `progress` and `task_finished` from long operations, and
+//! any results the command has. Each event carries its name and the time
+//! it was emitted. Human-readable output goes to stderr instead, so stdout
+//! carries nothing a wrapper can't parse.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use serde_json::{Map, Value};
+
+static JSON_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Where events are written: the original stdout.
+static EVENTS: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+/// Check if JSON output is requested (for early logging setup).
+pub fn is_json_requested() -> bool {
+    std::env::args().any(|arg| arg == "--json")
+}
+
+/// Whether commands are reporting as JSON events.
+pub fn is_json() -> bool {
+    JSON_MODE.load(Ordering::Relaxed)
+}
+
+/// Switch to JSON output. Events keep stdout; everything else printed from
+/// here on, progress bars and logs included, goes to stderr.
+pub fn enable_json() -> io::Result<()> {
+    if is_json() {
+        return Ok(());
+    }
+    io::stdout().flush()?;
+    let _ = EVENTS.set(Mutex::new(events_writer()?));
+    JSON_MODE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn events_writer() -> io::Result<Box<dyn Write + Send>> {
+    use std::os::fd::FromRawFd;
+
+    // Keep a handle on the real stdout for events, then point stdout at
+    // stderr so println! output can't end up among them
+    let fd = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+        let err = io::Error::last_os_error();
+        unsafe { libc::close(fd) };
+        return Err(err);
+    }
+    Ok(Box::new(unsafe { std::fs::File::from_raw_fd(fd) }))
+}
+
+#[cfg(not(unix))]
+fn events_writer() -> io::Result<Box<dyn Write + Send>> {
+    Ok(Box::new(io::stdout()))
+}
+
+/// Emit an event with the given fields (a JSON object). Does nothing
+/// outside JSON mode, so callers needn't check.
+pub fn emit(event: &str, fields: Value) {
+    if !is_json() {
+        return;
+    }
+    let Some(events) = EVENTS.get() else {
+        return;
+    };
+    let line = event_line(event, fields);
+    if let Ok(mut out) = events.lock() {
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+}
+
+/// Emit the `finished` event for a command that ran for `elapsed`.
+pub fn emit_finished(command: &str, elapsed: std::time::Duration, result: &anyhow::Result<()>) {
+    let mut fields = serde_json::json!({
+        "command": command,
+        "status": if result.is_ok() { "ok" } else { "error" },
+        "elapsed_secs": elapsed.as_secs_f64(),
+    });
+    if let Err(e) = result {
+        fields["error"] = Value::String(format!("{:#}", e));
+    }
+    emit("finished", fields);
+}
+
+fn event_line(event: &str, fields: Value) -> String {
+    let mut object = Map::new();
+    object.insert("event".into(), Value::String(event.to_string()));
+    object.insert(
+        "time".into(),
+        Value::String(chrono::Utc::now().to_rfc3339()),
+    );
+    match fields {
+        Value::Object(fields) => object.extend(fields),
+        Value::Null => {}
+        other => {
+            object.insert("data".into(), other);
+        }
+    }
+    Value::Object(object).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_event_line() {
+        let line = event_line("progress", json!({"task": "export", "done": 3}));
+        assert!(!line.contains('\n'));
+        let parsed: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["event"], "progress");
+        assert_eq!(parsed["task"], "export");
+        assert_eq!(parsed["done"], 3);
+        assert!(parsed["time"].is_string());
+
+        let parsed: Value = serde_json::from_str(&event_line("result", json!([1, 2]))).unwrap();
+        assert_eq!(parsed["data"], json!([1, 2]));
+    }
+}
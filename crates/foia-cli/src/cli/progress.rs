@@ -1,15 +1,20 @@
-//! Multi-progress display for concurrent downloads.
+//! Multi-progress display for concurrent downloads, and progress of long
+//! operations as bars or `--json` events.
 //!
 //! Also provides global progress context for coordinating output from
 //! any part of the application during progress display.
 
 #![allow(dead_code)]
 
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::json;
 use std::sync::RwLock;
 use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+use super::output;
+
 /// Global reference to active progress display for coordinating output.
 static ACTIVE_PROGRESS: OnceLock<RwLock<Option<MultiProgress>>> = OnceLock::new();
 
@@ -48,7 +53,7 @@ macro_rules! progress_println {
 pub struct DownloadProgress {
     multi: MultiProgress,
     slots: Arc<Mutex<Vec<ProgressBarSlot>>>,
-    summary_bar: ProgressBar,
+    summary: TaskProgress,
 }
 
 struct ProgressBarSlot {
@@ -59,17 +64,19 @@ struct ProgressBarSlot {
 impl DownloadProgress {
     /// Create a new download progress display with the given number of worker slots.
     pub fn new(num_workers: usize, total_pending: u64) -> Self {
-        let multi = MultiProgress::new();
+        // In JSON mode only the summary is reported, as events
+        let multi = if output::is_json() {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        } else {
+            MultiProgress::new()
+        };
 
         // Summary bar at the top
-        let summary_bar = multi.add(ProgressBar::new(total_pending));
-        summary_bar.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} {msg} [{bar:30.cyan/blue}] {pos}/{len}")
-                .unwrap()
-                .progress_chars("█▓░"),
+        let summary = TaskProgress::start(
+            "download",
+            multi.add(ProgressBar::new(total_pending)),
+            "Downloading",
         );
-        summary_bar.set_message("Downloading");
 
         // Create slots for each worker
         let mut slots = Vec::with_capacity(num_workers);
@@ -93,7 +100,7 @@ impl DownloadProgress {
         Self {
             multi,
             slots: Arc::new(Mutex::new(slots)),
-            summary_bar,
+            summary,
         }
     }
 
@@ -170,13 +177,13 @@ impl DownloadProgress {
 
         // Update summary
         if success {
-            self.summary_bar.inc(1);
+            self.summary.inc(1);
         }
     }
 
     /// Update the summary message.
     pub fn set_summary(&self, downloaded: usize, skipped: usize) {
-        self.summary_bar
+        self.summary
             .set_message(format!("Downloaded: {} | Skipped: {}", downloaded, skipped));
     }
 
@@ -186,7 +193,7 @@ impl DownloadProgress {
         for slot in slots.iter() {
             slot.bar.finish_and_clear();
         }
-        self.summary_bar.finish_and_clear();
+        self.summary.finish_and_clear();
 
         // Unregister active progress display
         set_active_progress(None);
@@ -208,6 +215,118 @@ impl DownloadProgress {
     }
 }
 
+/// How often a [`TaskProgress`] emits `progress` events in JSON mode.
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Progress of one long operation over a known number of items.
+///
+/// Shown as a bar with throughput and time remaining, or in JSON mode
+/// reported as `task_started`, throttled `progress` and `task_finished`
+/// events carrying the same figures.
+pub struct TaskProgress {
+    task: String,
+    bar: ProgressBar,
+    last_event: std::sync::Mutex<Instant>,
+}
+
+impl TaskProgress {
+    /// Start tracking `task` over `total` items, typically a count from the
+    /// repository of what is left to do.
+    pub fn new(task: &str, total: u64, message: &str) -> Self {
+        let bar = if output::is_json() {
+            ProgressBar::hidden()
+        } else {
+            ProgressBar::new(total)
+        };
+        bar.set_length(total);
+        Self::start(task, bar, message)
+    }
+
+    /// Start tracking `task` on a bar already sized to its total, such as
+    /// one in a [`MultiProgress`].
+    fn start(task: &str, bar: ProgressBar, message: &str) -> Self {
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{bar:30.cyan/blue}] {pos}/{len} {per_sec} ETA {eta} {wide_msg}",
+                )
+                .unwrap()
+                .progress_chars("█▓░"),
+        );
+        bar.set_message(message.to_string());
+        output::emit(
+            "task_started",
+            json!({ "task": task, "total": bar.length() }),
+        );
+        Self {
+            task: task.to_string(),
+            bar,
+            last_event: std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Count `n` more items done.
+    pub fn inc(&self, n: u64) {
+        self.bar.inc(n);
+        if !output::is_json() {
+            return;
+        }
+        if let Ok(mut last) = self.last_event.lock() {
+            if last.elapsed() < PROGRESS_EVENT_INTERVAL {
+                return;
+            }
+            *last = Instant::now();
+        }
+        output::emit("progress", self.figures());
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        self.bar.set_message(message.into());
+    }
+
+    /// Run a closure that prints, with the bar hidden meanwhile.
+    pub fn suspend<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.bar.suspend(f)
+    }
+
+    /// Print a line above the bar.
+    pub fn println(&self, message: &str) {
+        self.bar.println(message);
+    }
+
+    /// Items done so far.
+    pub fn position(&self) -> u64 {
+        self.bar.position()
+    }
+
+    /// Stop tracking and clear the bar.
+    pub fn finish_and_clear(&self) {
+        self.bar.finish_and_clear();
+        let mut figures = self.figures();
+        figures["elapsed_secs"] = json!(self.bar.elapsed().as_secs_f64());
+        output::emit("task_finished", figures);
+    }
+
+    fn figures(&self) -> serde_json::Value {
+        let done = self.bar.position();
+        let total = self.bar.length();
+        let eta = match total {
+            Some(total) if done > 0 && done < total => Some(self.bar.eta().as_secs()),
+            _ => None,
+        };
+        json!({
+            "task": self.task,
+            "done": done,
+            "total": total,
+            "per_sec": self.bar.per_sec(),
+            "eta_secs": eta,
+        })
+    }
+}
+
 /// Truncate a filename for display, keeping the extension visible.
 fn truncate_filename(name: &str, max_len: usize) -> String {
     if name.len() <= max_len {
@@ -240,4 +359,17 @@ mod tests {
         );
         assert_eq!(truncate_filename("no_extension", 8), "no_ex...");
     }
+
+    #[test]
+    fn test_task_progress_figures() {
+        let progress = TaskProgress::new("export", 10, "Exporting...");
+        progress.inc(4);
+        let figures = progress.figures();
+        assert_eq!(figures["task"], "export");
+        assert_eq!(figures["done"], 4);
+        assert_eq!(figures["total"], 10);
+        progress.inc(6);
+        assert!(progress.figures()["eta_secs"].is_null());
+        progress.finish_and_clear();
+    }
 }
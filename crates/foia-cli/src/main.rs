@@ -12,6 +12,11 @@ async fn main() -> anyhow::Result<()> {
     // Load .env file if present (before anything else)
     let _ = dotenvy::dotenv();
 
+    // With --json, claim stdout for events before anything else prints
+    if cli::output::is_json_requested() {
+        cli::output::enable_json()?;
+    }
+
     // Initialize logging based on verbosity
     let default_filter = if cli::is_verbose() {
         "foia=info"
@@ -27,7 +27,8 @@ use crate::{
 };
 use foia::models::{Document, DocumentVersion};
 use foia::repository::extract_filename_parts;
-use foia::storage::compute_storage_path_with_dedup;
+use foia::services::exports::normalize_original_path;
+use foia::storage::{compute_storage_path_with_dedup, write_content};
 
 /// Concordance DAT field delimiter (þ, thorn character).
 /// In UTF-8 this is encoded as 0xC3 0xBE.
@@ -583,10 +584,13 @@ impl ImportSource for ConcordanceImportSource {
                 }
             }
 
-            // Perform the storage-mode-specific file operation
+            // Perform the storage-mode-specific file operation. Renames and
+            // links would store the plaintext original, so encrypted storage
+            // always writes the content.
+            let encrypted = foia::encryption::content_key().is_some();
             let file_op_failed = match config.storage_mode {
                 FileStorageMode::Copy => {
-                    if let Err(e) = std::fs::write(&dest_path, &content) {
+                    if let Err(e) = write_content(&dest_path, &content) {
                         tracing::warn!("Failed to write {}: {}", dest_path.display(), e);
                         true
                     } else {
@@ -594,7 +598,13 @@ impl ImportSource for ConcordanceImportSource {
                     }
                 }
                 FileStorageMode::Move => {
-                    if let Err(e) = std::fs::rename(&file_path, &dest_path) {
+                    let moved = if encrypted {
+                        write_content(&dest_path, &content)
+                            .and_then(|_| std::fs::remove_file(&file_path))
+                    } else {
+                        std::fs::rename(&file_path, &dest_path)
+                    };
+                    if let Err(e) = moved {
                         tracing::warn!("Failed to move {}: {}", file_path.display(), e);
                         true
                     } else {
@@ -602,14 +612,19 @@ impl ImportSource for ConcordanceImportSource {
                     }
                 }
                 FileStorageMode::HardLink => {
-                    if let Err(e) = std::fs::hard_link(&file_path, &dest_path) {
-                        tracing::debug!("Hard link failed ({}), falling back to copy", e);
-                        if let Err(e) = std::fs::copy(&file_path, &dest_path) {
-                            tracing::warn!("Failed to copy {}: {}", file_path.display(), e);
-                            true
-                        } else {
-                            false
-                        }
+                    let linked = !encrypted
+                        && match std::fs::hard_link(&file_path, &dest_path) {
+                            Ok(()) => true,
+                            Err(e) => {
+                                tracing::debug!("Hard link failed ({}), falling back to copy", e);
+                                false
+                            }
+                        };
+                    if linked {
+                        false
+                    } else if let Err(e) = write_content(&dest_path, &content) {
+                        tracing::warn!("Failed to copy {}: {}", file_path.display(), e);
+                        true
                     } else {
                         false
                     }
@@ -634,13 +649,15 @@ impl ImportSource for ConcordanceImportSource {
                 None,
             );
             version.dedup_index = dedup_index;
+            let version_hash = version.content_hash.clone();
 
             let save_result: anyhow::Result<bool> = {
                 let existing = doc_repo.get_by_url(&url).await?;
-                if let Some(mut doc) = existing.into_iter().next() {
+                let doc_id = if let Some(mut doc) = existing.into_iter().next() {
                     if doc.add_version(version) {
                         doc_repo.save_with_versions(&doc).await?;
                     }
+                    doc.id
                 } else {
                     let mut doc = Document::new(
                         uuid::Uuid::new_v4().to_string(),
@@ -652,6 +669,13 @@ impl ImportSource for ConcordanceImportSource {
                     );
                     doc.tags = config.tags.clone();
                     doc_repo.save_with_versions(&doc).await?;
+                    doc.id
+                };
+                // Kept so exports can rebuild the production's folders
+                if let Some(path) = normalize_original_path(&opt_page.image_path) {
+                    doc_repo
+                        .record_original_path(&doc_id, &version_hash, &path)
+                        .await?;
                 }
                 Ok(true)
             };
@@ -0,0 +1,404 @@
+//! Watch-folder intake for scanned paper productions.
+//!
+//! Files dropped into an intake directory are matched against a filename
+//! pattern such as `{agency}_{date}_{title}.pdf`. The captured fields become
+//! document metadata (`{title}` the title, `{date}` the document date), and
+//! imported files are moved to `imported/` inside the intake directory so the
+//! next scan only sees new drops. Files that don't match are left in place
+//! and reported, so they can be renamed and picked up on the next scan.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use console::style;
+use regex::Regex;
+
+use crate::{
+    guess_mime_type, runner::FileStorageMode, ImportConfig, ImportProgress, ImportSource,
+    ImportStats,
+};
+use foia::models::{Document, DocumentVersion};
+use foia::repository::{extract_filename_parts, DieselDocumentRepository};
+use foia::storage::{compute_storage_path_with_dedup, write_content};
+
+/// Subdirectory of the intake directory that imported files are moved to.
+pub const IMPORTED_DIR: &str = "imported";
+
+/// Files modified more recently than this may still be written by the scanner.
+const SETTLE_TIME: Duration = Duration::from_secs(10);
+
+/// Filename pattern with `{field}` placeholders.
+///
+/// `{date}` matches `YYYY-MM-DD` or `YYYYMMDD`; other fields match any text.
+/// Literal parts match case-insensitively.
+#[derive(Debug, Clone)]
+pub struct FilenamePattern {
+    pattern: String,
+    regex: Regex,
+    fields: Vec<String>,
+}
+
+impl FilenamePattern {
+    /// Compile a pattern like `{agency}_{date}_{title}.pdf`.
+    pub fn parse(pattern: &str) -> anyhow::Result<Self> {
+        let mut regex = String::from("(?i)^");
+        let mut fields: Vec<String> = Vec::new();
+        let mut rest = pattern;
+
+        while let Some(open) = rest.find('{') {
+            regex.push_str(&regex::escape(&rest[..open]));
+            let close = rest[open..]
+                .find('}')
+                .map(|i| open + i)
+                .ok_or_else(|| anyhow::anyhow!("Unclosed '{{' in pattern '{}'", pattern))?;
+            let name = &rest[open + 1..close];
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                anyhow::bail!("Invalid field name '{{{}}}' in pattern '{}'", name, pattern);
+            }
+            if fields.iter().any(|f| f == name) {
+                anyhow::bail!(
+                    "Field '{{{}}}' appears twice in pattern '{}'",
+                    name,
+                    pattern
+                );
+            }
+            if name == "date" {
+                regex.push_str(r"(?P<date>\d{4}-?\d{2}-?\d{2})");
+            } else {
+                regex.push_str(&format!("(?P<{}>.+?)", name));
+            }
+            fields.push(name.to_string());
+            rest = &rest[close + 1..];
+        }
+        regex.push_str(&regex::escape(rest));
+        regex.push('$');
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex: Regex::new(&regex)?,
+            fields,
+        })
+    }
+
+    /// The pattern as written.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Captured fields if `filename` matches the pattern.
+    pub fn capture(&self, filename: &str) -> Option<HashMap<String, String>> {
+        let caps = self.regex.captures(filename)?;
+        Some(
+            self.fields
+                .iter()
+                .filter_map(|name| Some((name.clone(), caps.name(name)?.as_str().to_string())))
+                .collect(),
+        )
+    }
+}
+
+/// Parse a `{date}` capture (`YYYY-MM-DD` or `YYYYMMDD`).
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d"))
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+/// Intake directory import source.
+pub struct IntakeImportSource {
+    /// Intake directory being watched.
+    dir: PathBuf,
+    /// Filename-to-metadata pattern.
+    pattern: FilenamePattern,
+    /// Settings for database access.
+    settings: foia::config::Settings,
+    /// Files that didn't match the pattern on the last scan.
+    unmatched: Vec<String>,
+}
+
+impl IntakeImportSource {
+    /// Create an intake source for `dir`.
+    pub fn new(
+        dir: PathBuf,
+        pattern: FilenamePattern,
+        settings: foia::config::Settings,
+    ) -> anyhow::Result<Self> {
+        if !dir.is_dir() {
+            anyhow::bail!("Intake directory not found: {}", dir.display());
+        }
+        Ok(Self {
+            dir,
+            pattern,
+            settings,
+            unmatched: Vec::new(),
+        })
+    }
+
+    /// Files left in place on the last scan because they didn't match.
+    pub fn unmatched(&self) -> &[String] {
+        &self.unmatched
+    }
+
+    /// Whether any settled files are waiting in the intake directory.
+    pub fn has_pending(&self) -> bool {
+        self.pending_files().is_ok_and(|files| !files.is_empty())
+    }
+
+    /// Settled, visible files directly inside the intake directory, by name.
+    fn pending_files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let now = SystemTime::now();
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                !name.starts_with('.') && !name.ends_with(".progress")
+            })
+            .filter(|entry| {
+                entry.metadata().is_ok_and(|m| {
+                    m.is_file()
+                        && m.modified()
+                            .ok()
+                            .and_then(|t| now.duration_since(t).ok())
+                            .is_some_and(|age| age >= SETTLE_TIME)
+                })
+            })
+            .map(|entry| entry.path())
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Move an imported file out of the intake directory.
+    fn archive(&self, file_path: &Path) -> std::io::Result<()> {
+        let imported_dir = self.dir.join(IMPORTED_DIR);
+        std::fs::create_dir_all(&imported_dir)?;
+        let name = file_path.file_name().unwrap_or_default();
+        let mut dest = imported_dir.join(name);
+        if dest.exists() {
+            dest = imported_dir.join(format!(
+                "{}-{}",
+                Utc::now().format("%Y%m%dT%H%M%S"),
+                name.to_string_lossy()
+            ));
+        }
+        std::fs::rename(file_path, dest)
+    }
+
+    /// Store one file in the documents directory and save its document.
+    async fn import_file(
+        &self,
+        config: &ImportConfig,
+        doc_repo: &DieselDocumentRepository,
+        source_id: &str,
+        file_path: &Path,
+        filename: &str,
+        fields: HashMap<String, String>,
+    ) -> anyhow::Result<String> {
+        let url = format!("intake://{}/{}", source_id, filename);
+        let content = std::fs::read(file_path)?;
+
+        let title = fields
+            .get("title")
+            .map(|t| t.replace('_', " ").trim().to_string())
+            .filter(|t| !t.is_empty())
+            .or_else(|| {
+                file_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.to_string())
+            })
+            .unwrap_or_else(|| filename.to_string());
+        let date = fields.get("date").and_then(|d| parse_date(d));
+
+        let mime_type = infer::get(&content)
+            .map(|t| t.mime_type().to_string())
+            .unwrap_or_else(|| guess_mime_type(file_path));
+        let content_hash = DocumentVersion::compute_hash(&content);
+        let (basename, extension) = extract_filename_parts(&url, &title, &mime_type);
+        let (relative_path, dedup_index) = compute_storage_path_with_dedup(
+            &config.documents_dir,
+            &content_hash,
+            &basename,
+            &extension,
+            &content,
+        );
+        let dest_path = config.documents_dir.join(&relative_path);
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // The original is archived after import, so a move just stores it
+        // directly. Links would expose the plaintext original when encrypting.
+        match config.storage_mode {
+            FileStorageMode::HardLink
+                if foia::encryption::content_key().is_none()
+                    && std::fs::hard_link(file_path, &dest_path).is_ok() => {}
+            _ => write_content(&dest_path, &content)?,
+        }
+
+        let mut version = DocumentVersion::new_with_metadata(
+            &content,
+            mime_type,
+            Some(url.clone()),
+            Some(filename.to_string()),
+            date,
+        );
+        version.dedup_index = dedup_index;
+
+        let metadata = serde_json::json!({
+            "import_source": "intake",
+            "intake_pattern": self.pattern.as_str(),
+            "fields": fields,
+        });
+
+        let existing = doc_repo.get_by_url(&url).await?;
+        if let Some(mut doc) = existing.into_iter().next() {
+            if doc.add_version(version) {
+                doc_repo.save_with_versions(&doc).await?;
+            }
+        } else {
+            let mut doc = Document::new(
+                uuid::Uuid::new_v4().to_string(),
+                source_id.to_string(),
+                title,
+                url.clone(),
+                version,
+                metadata,
+            );
+            doc.tags = config.tags.clone();
+            doc_repo.save_with_versions(&doc).await?;
+        }
+
+        Ok(url)
+    }
+}
+
+#[async_trait::async_trait]
+impl ImportSource for IntakeImportSource {
+    fn format_id(&self) -> &'static str {
+        "intake"
+    }
+
+    fn display_name(&self) -> &str {
+        "Intake folder"
+    }
+
+    fn source_path(&self) -> &Path {
+        &self.dir
+    }
+
+    fn supports_resume(&self) -> bool {
+        // Imported files leave the directory, so every scan starts fresh
+        false
+    }
+
+    async fn run_import(
+        &mut self,
+        config: &ImportConfig,
+        _start_position: u64,
+    ) -> anyhow::Result<(ImportProgress, ImportStats)> {
+        let mut stats = ImportStats::default();
+        let source_id = config
+            .source_id
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Source ID is required for intake import"))?;
+
+        let ctx = self.settings.create_db_context()?;
+        let doc_repo = ctx.documents();
+        self.unmatched.clear();
+
+        for file_path in self.pending_files()? {
+            if config.limit > 0 && stats.imported >= config.limit {
+                break;
+            }
+            stats.scanned += 1;
+
+            let filename = file_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let Some(fields) = self.pattern.capture(&filename) else {
+                stats.filtered += 1;
+                self.unmatched.push(filename);
+                continue;
+            };
+
+            if config.dry_run {
+                println!(
+                    "  {} [{}] {} {:?}",
+                    style("+").green(),
+                    source_id,
+                    filename,
+                    fields
+                );
+                stats.imported += 1;
+                continue;
+            }
+
+            match self
+                .import_file(config, &doc_repo, source_id, &file_path, &filename, fields)
+                .await
+            {
+                Ok(url) => {
+                    stats.imported += 1;
+                    stats.imported_urls.push(url);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to import {}: {}", file_path.display(), e);
+                    stats.errors += 1;
+                    continue;
+                }
+            }
+
+            if let Err(e) = self.archive(&file_path) {
+                tracing::warn!("Failed to archive {}: {}", file_path.display(), e);
+            }
+        }
+
+        let progress = ImportProgress {
+            position: stats.scanned as u64,
+            done: true,
+            error: None,
+        };
+        Ok((progress, stats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filename_pattern_captures_fields() {
+        let pattern = FilenamePattern::parse("{agency}_{date}_{title}.pdf").unwrap();
+
+        let fields = pattern
+            .capture("FBI_2023-04-15_Field_office_memo.PDF")
+            .unwrap();
+        assert_eq!(fields["agency"], "FBI");
+        assert_eq!(fields["date"], "2023-04-15");
+        assert_eq!(fields["title"], "Field_office_memo");
+
+        let fields = pattern.capture("DOJ_20230415_Letter.pdf").unwrap();
+        assert_eq!(
+            parse_date(&fields["date"]).unwrap().to_rfc3339(),
+            "2023-04-15T00:00:00+00:00"
+        );
+
+        assert!(pattern.capture("FBI_April_memo.pdf").is_none());
+        assert!(pattern.capture("FBI_2023-04-15_memo.tif").is_none());
+    }
+
+    #[test]
+    fn test_filename_pattern_rejects_invalid() {
+        assert!(FilenamePattern::parse("{agency_{title}.pdf").is_err());
+        assert!(FilenamePattern::parse("{title}_{title}.pdf").is_err());
+        assert!(FilenamePattern::parse("{}.pdf").is_err());
+        assert!(FilenamePattern::parse("{title").is_err());
+    }
+}
@@ -1,7 +1,9 @@
 //! Import source implementations.
 
 pub mod concordance;
+pub mod intake;
 pub mod warc;
 
 pub use concordance::{ConcordanceImportSource, MultiPageMode};
+pub use intake::{FilenamePattern, IntakeImportSource};
 pub use warc::WarcImportSource;
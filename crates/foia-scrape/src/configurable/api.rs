@@ -4,9 +4,11 @@ use std::sync::Arc;
 use tracing::{debug, info, warn};
 
 use super::extract::{extract_path, extract_url, extract_urls};
+use super::records::record_result;
+use super::schema_watch::SchemaWatch;
 use super::ConfigurableScraper;
 use crate::config::ScraperConfig;
-use crate::HttpClient;
+use crate::{HttpClient, ScraperResult};
 use foia::models::{CrawlUrl, DiscoveryMethod};
 use foia::repository::DieselCrawlRepository;
 
@@ -18,6 +20,7 @@ impl ConfigurableScraper {
         source_id: &str,
         crawl_repo: &Option<Arc<DieselCrawlRepository>>,
         url_tx: &tokio::sync::mpsc::Sender<String>,
+        result_tx: &tokio::sync::mpsc::Sender<ScraperResult>,
     ) {
         let api = match &config.discovery.api {
             Some(api) => api,
@@ -38,6 +41,7 @@ impl ConfigurableScraper {
         let mut total_urls = 0;
         let mut rate_limited = false;
         let mut last_error: Option<String> = None;
+        let mut watch = SchemaWatch::new(crawl_repo, source_id, &api.endpoint);
 
         loop {
             let mut params: Vec<(String, String)> = Vec::new();
@@ -98,6 +102,13 @@ impl ConfigurableScraper {
             };
 
             let results = extract_path(&data, &api.pagination.results_path);
+            watch
+                .observe(
+                    &data,
+                    &api.pagination.results_path,
+                    results.as_array().map(Vec::as_slice),
+                )
+                .await;
             let results = match results.as_array() {
                 Some(arr) => arr,
                 None => {
@@ -115,7 +126,21 @@ impl ConfigurableScraper {
             }
 
             let mut page_urls = 0;
+            let mut page_records = 0;
             for item in results {
+                if let Some(ref records) = api.records {
+                    if result_tx
+                        .send(record_result(item, &api_url, records))
+                        .await
+                        .is_err()
+                    {
+                        return; // Receiver dropped
+                    }
+                    page_records += 1;
+                    if !records.follow_urls {
+                        continue;
+                    }
+                }
                 for url in extract_urls(item, &api.url_extraction) {
                     // Track URL in database
                     if let Some(repo) = crawl_repo {
@@ -138,12 +163,15 @@ impl ConfigurableScraper {
                 }
             }
 
+            watch.record_extraction(results.len(), page_urls + page_records);
+
             info!(
-                "Page {}: found {} items, extracted {} URLs (total: {})",
+                "Page {}: found {} items, extracted {} URLs (total: {}), stored {} records",
                 page,
                 results.len(),
                 page_urls,
-                total_urls
+                total_urls,
+                page_records
             );
 
             if results.len() < api.pagination.page_size as usize {
@@ -153,6 +181,8 @@ impl ConfigurableScraper {
             page += 1;
         }
 
+        watch.finish().await;
+
         // Report results with appropriate log level
         if rate_limited {
             tracing::error!(
@@ -184,6 +214,7 @@ impl ConfigurableScraper {
         source_id: &str,
         crawl_repo: &Option<Arc<DieselCrawlRepository>>,
         url_tx: &tokio::sync::mpsc::Sender<String>,
+        result_tx: &tokio::sync::mpsc::Sender<ScraperResult>,
     ) {
         let api = match &config.discovery.api {
             Some(api) => api,
@@ -214,6 +245,7 @@ impl ConfigurableScraper {
         let mut total_urls = 0;
         let mut rate_limited = false;
         let mut last_error: Option<String> = None;
+        let mut watch = SchemaWatch::new(crawl_repo, source_id, &api.endpoint);
 
         for query in queries {
             let mut cursor: Option<String> = None;
@@ -271,6 +303,13 @@ impl ConfigurableScraper {
                 };
 
                 let results = extract_path(&data, &api.pagination.results_path);
+                watch
+                    .observe(
+                        &data,
+                        &api.pagination.results_path,
+                        results.as_array().map(Vec::as_slice),
+                    )
+                    .await;
                 let results = match results.as_array() {
                     Some(arr) => arr,
                     None => break,
@@ -280,7 +319,22 @@ impl ConfigurableScraper {
                     break;
                 }
 
+                let before = total_urls;
+                let mut page_records = 0;
                 for item in results {
+                    if let Some(ref records) = api.records {
+                        if result_tx
+                            .send(record_result(item, &api_url, records))
+                            .await
+                            .is_err()
+                        {
+                            return;
+                        }
+                        page_records += 1;
+                        if !records.follow_urls {
+                            continue;
+                        }
+                    }
                     for doc_url in extract_urls(item, &api.url_extraction) {
                         if let Some(repo) = crawl_repo {
                             let crawl_url = CrawlUrl::new(
@@ -300,6 +354,8 @@ impl ConfigurableScraper {
                     }
                 }
 
+                watch.record_extraction(results.len(), total_urls - before + page_records);
+
                 cursor = extract_path(&data, cursor_path)
                     .as_str()
                     .map(|s| s.to_string());
@@ -315,6 +371,8 @@ impl ConfigurableScraper {
             }
         }
 
+        watch.finish().await;
+
         // Report results with appropriate log level
         if rate_limited {
             tracing::error!(
@@ -357,6 +415,7 @@ impl ConfigurableScraper {
 
         info!("Starting API paginated discovery from {}", api_url);
 
+        let mut watch = SchemaWatch::new(&self.crawl_repo, &self.source.id, &api.endpoint);
         let mut page = 1u32;
         loop {
             let mut params: Vec<(String, String)> = Vec::new();
@@ -396,6 +455,13 @@ impl ConfigurableScraper {
             };
 
             let results = extract_path(&data, &api.pagination.results_path);
+            watch
+                .observe(
+                    &data,
+                    &api.pagination.results_path,
+                    results.as_array().map(Vec::as_slice),
+                )
+                .await;
             let results = match results.as_array() {
                 Some(arr) => arr,
                 None => {
@@ -428,6 +494,8 @@ impl ConfigurableScraper {
                 }
             }
 
+            watch.record_extraction(results.len(), page_urls);
+
             info!(
                 "Page {}: found {} items, extracted {} URLs (total: {})",
                 page,
@@ -443,6 +511,8 @@ impl ConfigurableScraper {
             page += 1;
         }
 
+        watch.finish().await;
+
         urls
     }
 
@@ -476,6 +546,8 @@ impl ConfigurableScraper {
             .as_deref()
             .unwrap_or("next_cursor");
 
+        let mut watch = SchemaWatch::new(&self.crawl_repo, &self.source.id, &api.endpoint);
+
         for query in queries {
             let mut cursor: Option<String> = None;
 
@@ -514,6 +586,13 @@ impl ConfigurableScraper {
                 };
 
                 let results = extract_path(&data, &api.pagination.results_path);
+                watch
+                    .observe(
+                        &data,
+                        &api.pagination.results_path,
+                        results.as_array().map(Vec::as_slice),
+                    )
+                    .await;
                 let results = match results.as_array() {
                     Some(arr) => arr,
                     None => break,
@@ -523,6 +602,7 @@ impl ConfigurableScraper {
                     break;
                 }
 
+                let before = urls.len();
                 for item in results {
                     if let Some(url) = extract_url(item, &api.url_extraction) {
                         let crawl_url = CrawlUrl::new(
@@ -537,6 +617,8 @@ impl ConfigurableScraper {
                     }
                 }
 
+                watch.record_extraction(results.len(), urls.len() - before);
+
                 cursor = extract_path(&data, cursor_path)
                     .as_str()
                     .map(|s| s.to_string());
@@ -547,6 +629,8 @@ impl ConfigurableScraper {
             }
         }
 
+        watch.finish().await;
+
         urls
     }
 
@@ -577,6 +661,11 @@ impl ConfigurableScraper {
             .unwrap_or(&default_base);
         let parent_url = format!("{}{}", base_url, parent.endpoint);
 
+        let mut parent_watch =
+            SchemaWatch::new(&self.crawl_repo, &self.source.id, &parent.endpoint);
+        let mut child_watch =
+            SchemaWatch::new(&self.crawl_repo, &self.source.id, &child.endpoint_template);
+
         let mut page = 1u32;
         loop {
             let url_with_params =
@@ -593,6 +682,13 @@ impl ConfigurableScraper {
             };
 
             let results = extract_path(&data, &parent.results_path);
+            parent_watch
+                .observe(
+                    &data,
+                    &parent.results_path,
+                    results.as_array().map(Vec::as_slice),
+                )
+                .await;
             let results = match results.as_array() {
                 Some(arr) => arr,
                 None => break,
@@ -627,6 +723,13 @@ impl ConfigurableScraper {
                 };
 
                 let child_results = extract_path(&child_data, &child.results_path);
+                child_watch
+                    .observe(
+                        &child_data,
+                        &child.results_path,
+                        child_results.as_array().map(Vec::as_slice),
+                    )
+                    .await;
                 let mut items: Vec<&serde_json::Value> = match child_results.as_array() {
                     Some(arr) => arr.iter().collect(),
                     None => continue,
@@ -644,6 +747,7 @@ impl ConfigurableScraper {
                     items = nested_items;
                 }
 
+                let (item_count, before) = (items.len(), urls.len());
                 for item in items {
                     if let Some(url) = extract_url(item, &child.url_extraction) {
                         let crawl_url = CrawlUrl::new(
@@ -657,6 +761,7 @@ impl ConfigurableScraper {
                         urls.push(url);
                     }
                 }
+                child_watch.record_extraction(item_count, urls.len() - before);
             }
 
             if results.len() < parent.pagination.page_size as usize {
@@ -666,6 +771,9 @@ impl ConfigurableScraper {
             page += 1;
         }
 
+        parent_watch.finish().await;
+        child_watch.finish().await;
+
         urls
     }
 }
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use super::ConfigurableScraper;
 use crate::config::ScraperConfig;
-use crate::HttpClient;
+use crate::{HttpClient, ScraperResult};
 #[cfg(feature = "browser")]
 use foia::browser::BrowserEngineConfig;
 use foia::repository::DieselCrawlRepository;
@@ -18,6 +18,7 @@ impl ConfigurableScraper {
         source_id: &str,
         crawl_repo: &Option<Arc<DieselCrawlRepository>>,
         url_tx: &tokio::sync::mpsc::Sender<String>,
+        result_tx: &tokio::sync::mpsc::Sender<ScraperResult>,
         browser_config: &Option<BrowserEngineConfig>,
     ) {
         match config.discovery.discovery_type.as_str() {
@@ -34,13 +35,21 @@ impl ConfigurableScraper {
             }
             "api_paginated" => {
                 Self::discover_api_paginated_streaming(
-                    config, client, source_id, crawl_repo, url_tx,
+                    config, client, source_id, crawl_repo, url_tx, result_tx,
                 )
                 .await;
             }
             "api_cursor" => {
-                Self::discover_api_cursor_streaming(config, client, source_id, crawl_repo, url_tx)
-                    .await;
+                Self::discover_api_cursor_streaming(
+                    config, client, source_id, crawl_repo, url_tx, result_tx,
+                )
+                .await;
+            }
+            "youtube" => {
+                Self::discover_youtube_streaming(config, source_id, crawl_repo, url_tx).await;
+            }
+            "rss" => {
+                Self::discover_feed_streaming(config, client, source_id, crawl_repo, url_tx).await;
             }
             _ => {}
         }
@@ -54,6 +63,7 @@ impl ConfigurableScraper {
         source_id: &str,
         crawl_repo: &Option<Arc<DieselCrawlRepository>>,
         url_tx: &tokio::sync::mpsc::Sender<String>,
+        result_tx: &tokio::sync::mpsc::Sender<ScraperResult>,
     ) {
         match config.discovery.discovery_type.as_str() {
             "html_crawl" => {
@@ -64,13 +74,21 @@ impl ConfigurableScraper {
             }
             "api_paginated" => {
                 Self::discover_api_paginated_streaming(
-                    config, client, source_id, crawl_repo, url_tx,
+                    config, client, source_id, crawl_repo, url_tx, result_tx,
                 )
                 .await;
             }
             "api_cursor" => {
-                Self::discover_api_cursor_streaming(config, client, source_id, crawl_repo, url_tx)
-                    .await;
+                Self::discover_api_cursor_streaming(
+                    config, client, source_id, crawl_repo, url_tx, result_tx,
+                )
+                .await;
+            }
+            "youtube" => {
+                Self::discover_youtube_streaming(config, source_id, crawl_repo, url_tx).await;
+            }
+            "rss" => {
+                Self::discover_feed_streaming(config, client, source_id, crawl_repo, url_tx).await;
             }
             _ => {}
         }
@@ -83,6 +101,8 @@ impl ConfigurableScraper {
             "api_paginated" => self.discover_api_paginated().await,
             "api_cursor" => self.discover_api_cursor().await,
             "api_nested" => self.discover_api_nested().await,
+            "youtube" => self.discover_youtube().await,
+            "rss" => self.discover_feed().await,
             _ => Vec::new(),
         }
     }
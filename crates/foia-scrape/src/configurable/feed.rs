@@ -0,0 +1,317 @@
+//! RSS and Atom feed discovery (agency podcasts, meeting audio).
+//!
+//! Each entry in `discovery.start_paths` is a feed URL. Item enclosures are
+//! queued with the episode title and publish date in the discovery context,
+//! and enclosures already in the crawl queue are skipped, so re-running a
+//! crawl only picks up new episodes.
+
+use std::sync::{Arc, LazyLock};
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use tracing::{info, warn};
+
+use super::extract::resolve_url;
+use super::ConfigurableScraper;
+use crate::config::ScraperConfig;
+use crate::HttpClient;
+use foia::models::{CrawlUrl, DiscoveryMethod};
+use foia::repository::DieselCrawlRepository;
+
+static ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<(?:item|entry)\b[^>]*>(.*?)</(?:item|entry)>").unwrap());
+static ENCLOSURE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<(?:enclosure|link)\b([^>]*)>").unwrap());
+static ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// A downloadable enclosure from one feed item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedEnclosure {
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub title: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl FeedEnclosure {
+    /// Crawl queue entry carrying the episode details for the downloader.
+    fn to_crawl_url(&self, source_id: &str, feed_url: &str) -> CrawlUrl {
+        let mut crawl_url = CrawlUrl::new(
+            self.url.clone(),
+            source_id.to_string(),
+            DiscoveryMethod::Feed,
+            Some(feed_url.to_string()),
+            1,
+        );
+        let context = &mut crawl_url.discovery_context;
+        if let Some(title) = &self.title {
+            context.insert("title".to_string(), title.clone().into());
+        }
+        if let Some(published) = self.published_at {
+            context.insert("published_at".to_string(), published.to_rfc3339().into());
+        }
+        if let Some(mime) = &self.mime_type {
+            context.insert("mime_type".to_string(), mime.clone().into());
+        }
+        crawl_url
+    }
+}
+
+impl ConfigurableScraper {
+    /// Streaming feed discovery.
+    pub(crate) async fn discover_feed_streaming(
+        config: &ScraperConfig,
+        client: &HttpClient,
+        source_id: &str,
+        crawl_repo: &Option<Arc<DieselCrawlRepository>>,
+        url_tx: &tokio::sync::mpsc::Sender<String>,
+    ) {
+        let base_url = config.base_url_or("");
+        let mut total_urls = 0;
+
+        for start in &config.discovery.start_paths {
+            let feed_url = resolve_url(&base_url, start);
+            for url in feed_new_enclosures(&feed_url, client, source_id, crawl_repo).await {
+                if url_tx.send(url).await.is_err() {
+                    return;
+                }
+                total_urls += 1;
+            }
+        }
+
+        info!(
+            "[{}] Feed discovery complete: {} new enclosures",
+            source_id, total_urls
+        );
+    }
+
+    /// Legacy feed discovery (non-streaming).
+    pub(crate) async fn discover_feed(&self) -> Vec<String> {
+        let base_url = self.config.base_url_or("");
+        let mut urls = Vec::new();
+        for start in &self.config.discovery.start_paths {
+            let feed_url = resolve_url(&base_url, start);
+            urls.extend(
+                feed_new_enclosures(&feed_url, &self.client, &self.source.id, &self.crawl_repo)
+                    .await,
+            );
+        }
+        urls
+    }
+}
+
+/// Fetch one feed and queue the enclosures that aren't already known.
+async fn feed_new_enclosures(
+    feed_url: &str,
+    client: &HttpClient,
+    source_id: &str,
+    crawl_repo: &Option<Arc<DieselCrawlRepository>>,
+) -> Vec<String> {
+    let response = match client.get(feed_url, None, None).await {
+        Ok(r) if r.is_success() => r,
+        Ok(r) => {
+            warn!(
+                "[{}] Feed request failed (HTTP {}) - {}",
+                source_id, r.status, feed_url
+            );
+            return Vec::new();
+        }
+        Err(e) => {
+            warn!("[{}] Feed request error: {} - {}", source_id, e, feed_url);
+            return Vec::new();
+        }
+    };
+    let xml = match response.text().await {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("[{}] Failed to read feed {}: {}", source_id, feed_url, e);
+            return Vec::new();
+        }
+    };
+
+    let enclosures = parse_feed(&xml, feed_url);
+    let mut new_urls = Vec::new();
+    for enclosure in &enclosures {
+        let is_new = match crawl_repo {
+            Some(repo) => repo
+                .add_url(&enclosure.to_crawl_url(source_id, feed_url))
+                .await
+                .unwrap_or(false),
+            None => true,
+        };
+        if is_new {
+            new_urls.push(enclosure.url.clone());
+        }
+    }
+
+    info!(
+        "[{}] {} new of {} enclosures in {}",
+        source_id,
+        new_urls.len(),
+        enclosures.len(),
+        feed_url
+    );
+    new_urls
+}
+
+/// Extract item enclosures from an RSS 2.0 or Atom feed.
+///
+/// RSS uses `<enclosure url=... type=...>`, Atom `<link rel="enclosure" href=...>`.
+/// Relative enclosure URLs are resolved against the feed URL.
+pub fn parse_feed(xml: &str, feed_url: &str) -> Vec<FeedEnclosure> {
+    let mut enclosures = Vec::new();
+
+    for item in ITEM.captures_iter(xml) {
+        let body = &item[1];
+        let title = element_text(body, "title");
+        let published_at = ["pubDate", "published", "updated", "dc:date"]
+            .iter()
+            .find_map(|tag| element_text(body, tag))
+            .and_then(|date| parse_feed_date(&date));
+
+        for tag in ENCLOSURE.captures_iter(body) {
+            let attrs = &tag[1];
+            let is_enclosure = tag[0][1..].to_lowercase().starts_with("enclosure");
+            let url = if is_enclosure {
+                attribute(attrs, "url")
+            } else if attribute(attrs, "rel").as_deref() == Some("enclosure") {
+                attribute(attrs, "href")
+            } else {
+                None
+            };
+            let Some(url) = url.filter(|u| !u.is_empty()) else {
+                continue;
+            };
+
+            enclosures.push(FeedEnclosure {
+                url: resolve_url(feed_url, &url),
+                mime_type: attribute(attrs, "type"),
+                title: title.clone(),
+                published_at,
+            });
+        }
+    }
+
+    enclosures
+}
+
+/// Text content of the first `<tag>` element, with CDATA and entities decoded.
+fn element_text(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut search = 0;
+    while let Some(found) = xml[search..].find(&open) {
+        let start = search + found + open.len();
+        search = start;
+        // Skip longer tag names sharing the prefix (<title> vs <titleType>)
+        match xml[start..].chars().next() {
+            Some('>') | Some(' ') | Some('\t') | Some('\n') | Some('\r') => {}
+            _ => continue,
+        }
+        let content_start = start + xml[start..].find('>')? + 1;
+        let content_end = content_start + xml[content_start..].find(&close)?;
+        let text = xml[content_start..content_end].trim();
+        let text = text
+            .strip_prefix("<![CDATA[")
+            .and_then(|t| t.strip_suffix("]]>"))
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| unescape(text));
+        let text = text.trim();
+        return (!text.is_empty()).then(|| text.to_string());
+    }
+    None
+}
+
+/// Value of attribute `name` within a tag's attribute list.
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    ATTRIBUTE
+        .captures_iter(attrs)
+        .find(|c| c[1].eq_ignore_ascii_case(name))
+        .and_then(|c| c.get(2).or_else(|| c.get(3)))
+        .map(|m| unescape(m.as_str()))
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parse RSS (RFC 2822) or Atom (RFC 3339) dates.
+fn parse_feed_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss_enclosures() {
+        let xml = r#"<rss><channel><title>City Council</title>
+            <item>
+              <title><![CDATA[Regular Meeting & Budget Hearing]]></title>
+              <pubDate>Tue, 02 Jan 2024 19:00:00 -0500</pubDate>
+              <enclosure url="https://city.gov/audio/2024-01-02.mp3?a=1&amp;b=2" length="1" type="audio/mpeg"/>
+            </item>
+            <item>
+              <title>Notes only</title>
+              <link>https://city.gov/notes</link>
+            </item>
+            <item>
+              <title>Work session</title>
+              <enclosure type='audio/mp4' url='/audio/ws.m4a' />
+            </item>
+        </channel></rss>"#;
+
+        let items = parse_feed(xml, "https://city.gov/feeds/council.xml");
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0].url,
+            "https://city.gov/audio/2024-01-02.mp3?a=1&b=2"
+        );
+        assert_eq!(items[0].mime_type.as_deref(), Some("audio/mpeg"));
+        assert_eq!(
+            items[0].title.as_deref(),
+            Some("Regular Meeting & Budget Hearing")
+        );
+        assert_eq!(
+            items[0].published_at.unwrap().to_rfc3339(),
+            "2024-01-03T00:00:00+00:00"
+        );
+        assert_eq!(items[1].url, "https://city.gov/audio/ws.m4a");
+        assert!(items[1].published_at.is_none());
+    }
+
+    #[test]
+    fn test_parse_atom_enclosures() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+            <entry>
+              <title type="text">Episode 12</title>
+              <published>2024-03-01T12:00:00Z</published>
+              <link rel="alternate" href="https://agency.gov/ep12"/>
+              <link rel="enclosure" type="audio/mpeg" href="https://cdn.agency.gov/ep12.mp3"/>
+            </entry>
+        </feed>"#;
+
+        let items = parse_feed(xml, "https://agency.gov/feed");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].url, "https://cdn.agency.gov/ep12.mp3");
+        assert_eq!(items[0].title.as_deref(), Some("Episode 12"));
+
+        let crawl_url = items[0].to_crawl_url("agency", "https://agency.gov/feed");
+        assert_eq!(crawl_url.discovery_method, DiscoveryMethod::Feed);
+        assert_eq!(crawl_url.discovery_context["title"], "Episode 12");
+        assert_eq!(
+            crawl_url.discovery_context["published_at"],
+            "2024-03-01T12:00:00+00:00"
+        );
+    }
+}
@@ -310,6 +310,11 @@ impl ConfigurableScraper {
             }
         };
 
+        if let Err(rejection) = self.guard.check(url, &content) {
+            self.client.mark_failed(url, &rejection.to_string()).await;
+            return None;
+        }
+
         let content_hash = foia::models::DocumentVersion::compute_hash(&content);
 
         self.client
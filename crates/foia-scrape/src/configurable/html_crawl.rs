@@ -9,8 +9,9 @@ use tracing::{info, warn};
 use url::Url;
 
 use super::extract::resolve_url;
+use super::pagination::{infer_pagination, PaginationRun};
 use super::ConfigurableScraper;
-use crate::config::{PaginationConfig, ScraperConfig};
+use crate::config::{PaginationConfig, PaginationInferenceConfig, ScraperConfig};
 use crate::google_drive::{
     extract_file_id, file_download_url, is_google_drive_file_url, is_google_drive_folder_url,
     DriveFolder,
@@ -37,17 +38,19 @@ fn normalize_url_for_detection(url: &str, via_mappings: &HashMap<String, String>
 }
 
 /// Configuration for the BFS HTML crawler, parsed from ScraperConfig.
-struct CrawlerConfig {
-    base_url: String,
-    allowed_domain: String,
-    document_patterns: Vec<Regex>,
+pub(super) struct CrawlerConfig {
+    pub(super) base_url: String,
+    pub(super) allowed_domain: String,
+    pub(super) document_patterns: Vec<Regex>,
     use_browser: bool,
-    max_depth: u32,
+    pub(super) max_depth: u32,
+    snapshot_listings: bool,
+    pub(super) infer_pagination: PaginationInferenceConfig,
 }
 
 impl CrawlerConfig {
     /// Build crawler configuration from ScraperConfig.
-    fn from_scraper_config(config: &ScraperConfig) -> Self {
+    pub(super) fn from_scraper_config(config: &ScraperConfig) -> Self {
         let default_base = String::new();
         let base_url = config
             .discovery
@@ -109,12 +112,14 @@ impl CrawlerConfig {
             document_patterns,
             use_browser,
             max_depth,
+            snapshot_listings: config.discovery.snapshot_listings,
+            infer_pagination: config.discovery.infer_pagination.clone(),
         }
     }
 }
 
 /// Initialize the BFS frontier with seed URLs.
-fn seed_frontier(
+pub(super) fn seed_frontier(
     config: &ScraperConfig,
     base_url: &str,
     visited: &mut HashSet<String>,
@@ -150,6 +155,108 @@ fn seed_frontier(
     frontier
 }
 
+/// Inferred pagination sequences for a crawl, keyed by the generated page
+/// URLs still waiting in the frontier.
+#[derive(Default)]
+pub(super) struct PaginationTracker {
+    runs: Vec<PaginationRun>,
+    pending: HashMap<String, usize>,
+}
+
+impl PaginationTracker {
+    fn is_generated(&self, url: &str) -> bool {
+        self.pending.contains_key(url)
+    }
+
+    /// After a page is fetched, continue its sequence (or start one from a
+    /// seed page) and return the next page URL to crawl, if any.
+    pub(super) fn next_page(
+        &mut self,
+        config: &PaginationInferenceConfig,
+        url: &str,
+        depth: u32,
+        links: &[String],
+        visited: &HashSet<String>,
+    ) -> Option<String> {
+        let idx = match self.pending.remove(url) {
+            Some(idx) => idx,
+            None if depth == 0 => {
+                let pattern = infer_pagination(url, links, &config.params)?;
+                info!(
+                    "Inferred pagination on {}: {} (step {})",
+                    url, pattern.param, pattern.step
+                );
+                self.runs.push(PaginationRun::new(pattern));
+                self.runs.len() - 1
+            }
+            None => return None,
+        };
+
+        let run = &mut self.runs[idx];
+        let content_links: Vec<String> = links
+            .iter()
+            .filter(|l| !run.pattern.is_sequence_url(l))
+            .cloned()
+            .collect();
+        let new_links = content_links
+            .iter()
+            .filter(|l| !visited.contains(*l))
+            .count();
+
+        match run.advance(config, &content_links, new_links) {
+            Ok(next) => {
+                self.pending.insert(next.clone(), idx);
+                Some(next)
+            }
+            Err(reason) => {
+                info!(
+                    "Pagination on {} stopped at {}: {:?}",
+                    run.pattern.param, url, reason
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Archive a fetched listing page as a snapshot (new rows only on change).
+async fn snapshot_listing(
+    crawl_repo: &Option<Arc<DieselCrawlRepository>>,
+    source_id: &str,
+    url: &str,
+    html: &str,
+    link_count: usize,
+) {
+    let Some(repo) = crawl_repo else {
+        return;
+    };
+    match repo
+        .record_listing_snapshot(source_id, url, html, link_count as u32)
+        .await
+    {
+        Ok(true) => info!("Listing page changed, stored new snapshot: {}", url),
+        Ok(false) => {}
+        Err(e) => warn!("Failed to store listing snapshot for {}: {}", url, e),
+    }
+}
+
+/// Drop listing snapshots older than the source's snapshot TTL, once per crawl.
+async fn prune_expired_snapshots(
+    config: &ScraperConfig,
+    crawl_repo: &Option<Arc<DieselCrawlRepository>>,
+    source_id: &str,
+) {
+    let (Some(repo), Some(days)) = (crawl_repo, config.discovery.snapshot_ttl_days) else {
+        return;
+    };
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    match repo.prune_listing_snapshots(Some(source_id), cutoff).await {
+        Ok(0) => {}
+        Ok(n) => info!("Pruned {} listing snapshot(s) older than {} days", n, days),
+        Err(e) => warn!("Failed to prune listing snapshots for {}: {}", source_id, e),
+    }
+}
+
 /// Fetch a page using browser or HTTP client.
 #[cfg(feature = "browser")]
 async fn fetch_page_html(
@@ -225,7 +332,7 @@ async fn enumerate_google_drive_folder(
 }
 
 /// Convert Google Drive file URLs to proper download URLs.
-fn convert_google_drive_file_url(url: String) -> String {
+pub(super) fn convert_google_drive_file_url(url: String) -> String {
     if is_google_drive_file_url(&url) {
         if let Some(file_id) = extract_file_id(&url) {
             return file_download_url(&file_id);
@@ -351,6 +458,7 @@ impl ConfigurableScraper {
     ) {
         let crawler_config = CrawlerConfig::from_scraper_config(config);
         let page_link_selector = "a".to_string();
+        prune_expired_snapshots(config, crawl_repo, source_id).await;
 
         // Create browser fetcher if configured
         let mut browser_fetcher = browser_config
@@ -370,6 +478,7 @@ impl ConfigurableScraper {
         let mut docs_found = 0u64;
         let mut failure_stats = (0u64, 0u64); // (consecutive, total)
         let initial_frontier_size = frontier.len();
+        let mut pagination = PaginationTracker::default();
 
         while let Some((current_url, depth)) = frontier.pop_front() {
             if depth > crawler_config.max_depth {
@@ -380,7 +489,9 @@ impl ConfigurableScraper {
             let crawl_url = CrawlUrl::new(
                 current_url.clone(),
                 source_id.to_string(),
-                if depth == 0 {
+                if pagination.is_generated(&current_url) {
+                    DiscoveryMethod::Pagination
+                } else if depth == 0 {
                     DiscoveryMethod::Seed
                 } else {
                     DiscoveryMethod::HtmlLink
@@ -424,6 +535,34 @@ impl ConfigurableScraper {
                 &page_link_selector,
             );
 
+            // Generate the next page of an inferred pagination sequence
+            // before this page's links are marked visited
+            if crawler_config.infer_pagination.enabled {
+                let links: Vec<String> = doc_urls.iter().chain(&page_urls).cloned().collect();
+                if let Some(next) = pagination.next_page(
+                    &crawler_config.infer_pagination,
+                    &current_url,
+                    depth,
+                    &links,
+                    &visited,
+                ) {
+                    if visited.insert(next.clone()) {
+                        frontier.push_back((next, depth));
+                    }
+                }
+            }
+
+            if crawler_config.snapshot_listings {
+                snapshot_listing(
+                    crawl_repo,
+                    source_id,
+                    &current_url,
+                    &html,
+                    doc_urls.len() + page_urls.len(),
+                )
+                .await;
+            }
+
             // Process Google Drive folders and filter them from page URLs
             let (gdrive_doc_urls, page_urls) =
                 process_google_drive_folders(page_urls, client, client.via_mappings()).await;
@@ -506,10 +645,11 @@ impl ConfigurableScraper {
     pub(crate) async fn discover_html_crawl_streaming_no_browser(
         config: &ScraperConfig,
         client: &HttpClient,
-        _source_id: &str,
-        _crawl_repo: &Option<Arc<DieselCrawlRepository>>,
+        source_id: &str,
+        crawl_repo: &Option<Arc<DieselCrawlRepository>>,
         url_tx: &tokio::sync::mpsc::Sender<String>,
     ) {
+        prune_expired_snapshots(config, crawl_repo, source_id).await;
         let default_base = String::new();
         let base_url = config
             .discovery
@@ -550,6 +690,10 @@ impl ConfigurableScraper {
                 urls
             };
 
+            if config.discovery.snapshot_listings {
+                snapshot_listing(crawl_repo, source_id, &start_url, &html, found_urls.len()).await;
+            }
+
             for full_url in found_urls {
                 if url_tx.send(full_url).await.is_err() {
                     return;
@@ -665,6 +809,17 @@ impl ConfigurableScraper {
                 }
             }
 
+            if self.config.discovery.snapshot_listings {
+                snapshot_listing(
+                    &self.crawl_repo,
+                    &self.source.id,
+                    url,
+                    &html,
+                    links_to_process.len(),
+                )
+                .await;
+            }
+
             for (full_url, matches_doc) in links_to_process {
                 let crawl_url = CrawlUrl::new(
                     full_url.clone(),
@@ -740,7 +895,7 @@ impl ConfigurableScraper {
 }
 
 /// Extract document and page links from HTML content.
-fn extract_links_from_html(
+pub(super) fn extract_links_from_html(
     html: &str,
     current_url: &str,
     base_url: &str,
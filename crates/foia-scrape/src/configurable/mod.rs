@@ -7,25 +7,39 @@
 #![allow(dead_code)]
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
 use super::config::{ScraperConfig, ViaMode};
+use super::hooks::ScriptHooks;
 use super::HttpClient;
 #[cfg(feature = "browser")]
 use foia::config::BrowserEngineConfig;
+use foia::http_client::{BreakerSettings, CircuitBreaker, InflightRegistry, DEFAULT_DEDUP_WINDOW};
 use foia::models::Source;
 #[allow(unused_imports)]
 use foia::privacy::PrivacyConfig;
 use foia::rate_limit::RateLimiter;
 use foia::repository::DieselCrawlRepository;
+use foia::services::content_guard::ContentGuard;
 
 mod api;
 mod discovery;
 mod extract;
+mod feed;
 mod fetch;
 mod html_crawl;
+mod pagination;
+mod records;
+mod schema_watch;
+mod simulate;
+mod source_test;
 mod stream;
+mod youtube;
+
+pub use simulate::{simulate_html_crawl, CrawlSimulation};
+pub use source_test::{check_page, test_page_url, SourceTestResult};
 
 /// Configurable scraper driven by JSON configuration.
 pub struct ConfigurableScraper {
@@ -35,6 +49,12 @@ pub struct ConfigurableScraper {
     pub(crate) crawl_repo: Option<Arc<DieselCrawlRepository>>,
     /// Refresh TTL in days - URLs older than this will be re-checked.
     pub(crate) refresh_ttl_days: u64,
+    /// Compiled scripting hooks, if the source configures any.
+    pub(crate) hooks: Option<Arc<ScriptHooks>>,
+    /// Checks that fetched content is the document, not an error page.
+    pub(crate) guard: Arc<ContentGuard>,
+    /// Claims on URLs and content being fetched, shared by the workers.
+    pub(crate) inflight: Arc<InflightRegistry>,
     /// Browser fetcher for anti-bot protected sites (created lazily when needed).
     #[cfg(feature = "browser")]
     pub(crate) browser_config: Option<BrowserEngineConfig>,
@@ -69,7 +89,7 @@ impl ConfigurableScraper {
         refresh_ttl_days: u64,
         rate_limiter: Option<RateLimiter>,
     ) -> Self {
-        // None privacy config = Direct mode, which never fails
+        // None privacy config = Direct mode, which only fails on bad hooks or patterns
         Self::with_rate_limiter_and_privacy(
             source,
             config,
@@ -79,7 +99,7 @@ impl ConfigurableScraper {
             rate_limiter,
             None,
         )
-        .expect("Direct mode scraper creation should never fail")
+        .expect("Direct mode scraper creation should only fail on invalid hooks or patterns")
     }
 
     /// Create a new configurable scraper with a shared rate limiter and privacy config.
@@ -89,7 +109,8 @@ impl ConfigurableScraper {
     /// 2. Applying per-source overrides from scraper config's `privacy` field
     ///
     /// # Errors
-    /// Returns an error if Tor mode is requested but Tor is not available.
+    /// Returns an error if Tor mode is requested but Tor is not available,
+    /// or if a configured hook script or validation pattern does not compile.
     pub fn with_rate_limiter_and_privacy(
         source: Source,
         config: ScraperConfig,
@@ -102,10 +123,24 @@ impl ConfigurableScraper {
         // Apply per-source privacy overrides to global config
         let effective_privacy = privacy_config.map(|global| config.privacy.apply_to(global));
 
-        let mut builder = HttpClient::builder(&source.id, Duration::from_secs(30), request_delay);
+        // Per-source (or agency-inherited) timing overrides the global values
+        let request_delay = config
+            .request_delay_ms
+            .map(Duration::from_millis)
+            .unwrap_or(request_delay);
+        let timeout = Duration::from_secs(config.request_timeout.unwrap_or(30));
+
+        let mut builder = HttpClient::builder(&source.id, timeout, request_delay);
         if let Some(ua) = config.user_agent.as_deref() {
             builder = builder.user_agent(ua);
         }
+        if !config.auth.is_default() {
+            let headers = config
+                .auth
+                .to_headers()
+                .map_err(|e| format!("Source {} auth: {}", source.id, e))?;
+            builder = builder.default_headers(headers);
+        }
         if let Some(privacy) = effective_privacy.as_ref() {
             builder = builder.privacy(privacy);
         }
@@ -117,6 +152,16 @@ impl ConfigurableScraper {
         }
         let client = builder.build()?;
 
+        let hooks = ScriptHooks::from_config(&config.hooks)
+            .map_err(|e| format!("Source {} {}", source.id, e))?
+            .map(Arc::new);
+        let guard = ContentGuard::from_config(&config.validation)
+            .map_err(|e| format!("Source {} validation: {}", source.id, e))?;
+        let mut inflight = InflightRegistry::new(DEFAULT_DEDUP_WINDOW);
+        if let Some(repo) = crawl_repo.clone() {
+            inflight = inflight.with_repo(repo);
+        }
+
         #[cfg(feature = "browser")]
         let browser_config = config
             .browser
@@ -131,6 +176,9 @@ impl ConfigurableScraper {
             client,
             crawl_repo,
             refresh_ttl_days,
+            hooks,
+            guard: Arc::new(guard),
+            inflight: Arc::new(inflight),
             #[cfg(feature = "browser")]
             browser_config,
         })
@@ -148,6 +196,11 @@ impl ConfigurableScraper {
         }
     }
 
+    /// Scripting hooks for this source, for callers that run `before_save`.
+    pub fn hooks(&self) -> Option<Arc<ScriptHooks>> {
+        self.hooks.clone()
+    }
+
     /// Configure URL rewriting for caching proxies with mode.
     ///
     /// The via mappings allow routing requests through a CDN (like Cloudflare)
@@ -160,6 +213,47 @@ impl ConfigurableScraper {
         self
     }
 
+    /// Pause domains that keep blocking requests, with these settings
+    /// instead of the defaults.
+    pub fn with_circuit_breaker(mut self, settings: BreakerSettings) -> Self {
+        let mut breaker = CircuitBreaker::new(settings);
+        if let Some(repo) = self.crawl_repo.clone() {
+            breaker = breaker.with_repo(repo);
+        }
+        self.client = self.client.with_circuit_breaker(Arc::new(breaker));
+        self
+    }
+
+    /// Suppress duplicate fetches of a URL or its content for `window`
+    /// instead of the default; zero disables suppression.
+    pub fn with_dedup_window(mut self, window: Duration) -> Self {
+        let mut inflight = InflightRegistry::new(window);
+        if let Some(repo) = self.crawl_repo.clone() {
+            inflight = inflight.with_repo(repo);
+        }
+        self.inflight = Arc::new(inflight);
+        self
+    }
+
+    /// Store browser session profiles for this source under `data_dir`
+    /// (only takes effect when the browser config has `persist_session`).
+    pub fn with_browser_profiles(self, data_dir: &Path) -> Self {
+        #[cfg(feature = "browser")]
+        {
+            let mut this = self;
+            this.browser_config = this
+                .browser_config
+                .take()
+                .map(|c| c.with_session_dir(data_dir, &this.source.id));
+            this
+        }
+        #[cfg(not(feature = "browser"))]
+        {
+            let _ = data_dir;
+            self
+        }
+    }
+
     /// Configure URL rewriting for caching proxies (uses Strict mode).
     #[deprecated(note = "Use with_via_config instead to also set via_mode")]
     pub fn with_via_mappings(mut self, via: HashMap<String, String>) -> Self {
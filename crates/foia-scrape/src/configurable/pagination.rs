@@ -0,0 +1,339 @@
+//! Pagination parameter inference for HTML listings.
+//!
+//! Many portals paginate with a numeric query parameter (`?page=2`,
+//! `&offset=40`) but only link a handful of pages at a time. Rather than
+//! requiring a hand-written pagination template, we look at the seed page's
+//! links for a numeric parameter on the same path, then generate the page
+//! sequence one page at a time until a stop condition is hit.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+use url::Url;
+
+use crate::config::PaginationInferenceConfig;
+
+/// Parameter names tried when the config does not list any, most common first.
+const DEFAULT_PARAMS: &[&str] = &[
+    "page",
+    "p",
+    "pg",
+    "pagenum",
+    "page_number",
+    "pageno",
+    "offset",
+    "start",
+    "from",
+    "skip",
+];
+
+/// An inferred numeric pagination parameter on a listing URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PaginationPattern {
+    /// Listing URL the pattern was inferred from.
+    base: Url,
+    pub param: String,
+    /// Value of the parameter on the seed page.
+    pub current: u64,
+    /// Difference between consecutive pages (1 for page numbers, the page
+    /// size for offsets).
+    pub step: u64,
+}
+
+impl PaginationPattern {
+    /// Whether a URL is on the paginated listing's path (pager links change
+    /// from page to page, so they are left out of empty/duplicate checks).
+    pub fn is_sequence_url(&self, url: &str) -> bool {
+        Url::parse(url)
+            .map(|u| u.host_str() == self.base.host_str() && u.path() == self.base.path())
+            .unwrap_or(false)
+    }
+
+    /// URL of the listing page with the parameter set to `value`, keeping
+    /// every other query parameter in place.
+    pub fn url_for(&self, value: u64) -> String {
+        let mut url = self.base.clone();
+        let mut replaced = false;
+        let pairs: Vec<(String, String)> = self
+            .base
+            .query_pairs()
+            .map(|(k, v)| {
+                if k == self.param.as_str() {
+                    replaced = true;
+                    (k.into_owned(), value.to_string())
+                } else {
+                    (k.into_owned(), v.into_owned())
+                }
+            })
+            .collect();
+        {
+            let mut query = url.query_pairs_mut();
+            query.clear();
+            for (k, v) in &pairs {
+                query.append_pair(k, v);
+            }
+            if !replaced {
+                query.append_pair(&self.param, &value.to_string());
+            }
+        }
+        url.to_string()
+    }
+}
+
+/// Look for a numeric pagination parameter among a page's links.
+///
+/// A candidate link must point at the same host and path as the page and
+/// differ from it only in the parameter's value.
+pub(crate) fn infer_pagination(
+    page_url: &str,
+    links: &[String],
+    params: &[String],
+) -> Option<PaginationPattern> {
+    let base = Url::parse(page_url).ok()?;
+    let candidates: Vec<&str> = if params.is_empty() {
+        DEFAULT_PARAMS.to_vec()
+    } else {
+        params.iter().map(String::as_str).collect()
+    };
+    let base_query: BTreeMap<String, String> = base.query_pairs().into_owned().collect();
+
+    // Values seen per candidate parameter
+    let mut seen: HashMap<&str, Vec<u64>> = HashMap::new();
+    for link in links {
+        let Ok(url) = Url::parse(link) else {
+            continue;
+        };
+        if url.host_str() != base.host_str() || url.path() != base.path() {
+            continue;
+        }
+        let query: BTreeMap<String, String> = url.query_pairs().into_owned().collect();
+        for param in &candidates {
+            let Some(value) = query.get(*param).and_then(|v| v.parse::<u64>().ok()) else {
+                continue;
+            };
+            let mut rest = query.clone();
+            rest.remove(*param);
+            let mut base_rest = base_query.clone();
+            base_rest.remove(*param);
+            if rest == base_rest {
+                seen.entry(*param).or_default().push(value);
+            }
+        }
+    }
+
+    // Prefer the earliest-listed candidate that appears at all
+    let (param, mut values) = candidates
+        .iter()
+        .find_map(|p| seen.remove(p).map(|v| (*p, v)))?;
+    values.sort_unstable();
+    values.dedup();
+
+    let current = base_query.get(param).and_then(|v| v.parse::<u64>().ok());
+
+    // Offsets advance by the page size: the smallest gap between the values
+    // we know about, including the current page
+    let mut known = values.clone();
+    if let Some(c) = current {
+        known.push(c);
+        known.sort_unstable();
+        known.dedup();
+    }
+    let step = known
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .min()
+        .filter(|s| *s > 1 && known.iter().all(|v| v % s == 0))
+        .unwrap_or(1);
+
+    // Without the parameter on the seed page, it is the page before the
+    // first linked one (page=2 means we are on 1, offset=20 means 0)
+    let current = current.unwrap_or_else(|| values[0].saturating_sub(step));
+    if values.iter().all(|v| *v <= current) {
+        return None;
+    }
+
+    Some(PaginationPattern {
+        base,
+        param: param.to_string(),
+        current,
+        step,
+    })
+}
+
+/// Why a generated page sequence ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StopReason {
+    /// The page had no links that earlier pages did not already have.
+    Empty,
+    /// The page repeated the previous page's links (out-of-range pages
+    /// often redisplay the last real page).
+    Duplicate,
+    /// `max_pages` pages were generated.
+    MaxPages,
+}
+
+/// Progress through one inferred page sequence.
+#[derive(Debug)]
+pub(crate) struct PaginationRun {
+    pub pattern: PaginationPattern,
+    /// Parameter value of the most recently generated page.
+    value: u64,
+    pages: u32,
+    last_fingerprint: Option<u64>,
+}
+
+impl PaginationRun {
+    pub fn new(pattern: PaginationPattern) -> Self {
+        Self {
+            value: pattern.current,
+            pattern,
+            pages: 0,
+            last_fingerprint: None,
+        }
+    }
+
+    /// Record the links of the last fetched page and decide whether to go on.
+    ///
+    /// `links` are the page's links other than pager links; `new_links` is
+    /// how many of them had not been seen before. Returns the next URL to
+    /// fetch, or why the sequence stops.
+    pub fn advance(
+        &mut self,
+        config: &PaginationInferenceConfig,
+        links: &[String],
+        new_links: usize,
+    ) -> Result<String, StopReason> {
+        let fingerprint = fingerprint(links);
+        if config.stop_on_duplicate && self.last_fingerprint == Some(fingerprint) {
+            return Err(StopReason::Duplicate);
+        }
+        // The seed page itself is never "empty"; only generated pages are
+        if config.stop_on_empty && self.pages > 0 && new_links == 0 {
+            return Err(StopReason::Empty);
+        }
+        if self.pages >= config.max_pages() {
+            return Err(StopReason::MaxPages);
+        }
+
+        self.last_fingerprint = Some(fingerprint);
+        self.pages += 1;
+        self.value += self.pattern.step;
+        Ok(self.pattern.url_for(self.value))
+    }
+}
+
+/// Order-independent hash of a page's links.
+fn fingerprint(links: &[String]) -> u64 {
+    let mut sorted: Vec<&String> = links.iter().collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut hasher = DefaultHasher::new();
+    sorted.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(urls: &[&str]) -> Vec<String> {
+        urls.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infer_page_number() {
+        let pattern = infer_pagination(
+            "https://agency.gov/foia/library?type=memo",
+            &links(&[
+                "https://agency.gov/foia/library?type=memo&page=2",
+                "https://agency.gov/foia/library?type=memo&page=3",
+                "https://agency.gov/foia/library?type=report&page=2",
+                "https://agency.gov/other?page=9",
+            ]),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(pattern.param, "page");
+        assert_eq!(pattern.current, 1);
+        assert_eq!(pattern.step, 1);
+        assert_eq!(
+            pattern.url_for(4),
+            "https://agency.gov/foia/library?type=memo&page=4"
+        );
+    }
+
+    #[test]
+    fn test_infer_offset_step() {
+        let pattern = infer_pagination(
+            "https://agency.gov/docs?offset=0",
+            &links(&[
+                "https://agency.gov/docs?offset=20",
+                "https://agency.gov/docs?offset=40",
+            ]),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(pattern.param, "offset");
+        assert_eq!(pattern.current, 0);
+        assert_eq!(pattern.step, 20);
+        assert_eq!(pattern.url_for(60), "https://agency.gov/docs?offset=60");
+    }
+
+    #[test]
+    fn test_no_pattern_without_forward_links() {
+        assert!(infer_pagination(
+            "https://agency.gov/docs?page=5",
+            &links(&["https://agency.gov/docs?page=4"]),
+            &[],
+        )
+        .is_none());
+        assert!(infer_pagination(
+            "https://agency.gov/docs",
+            &links(&["https://agency.gov/docs/a.pdf"]),
+            &[],
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_run_stop_conditions() {
+        let pattern = infer_pagination(
+            "https://agency.gov/docs",
+            &links(&["https://agency.gov/docs?page=2"]),
+            &[],
+        )
+        .unwrap();
+        let config = PaginationInferenceConfig {
+            enabled: true,
+            max_pages: Some(3),
+            ..Default::default()
+        };
+
+        let mut run = PaginationRun::new(pattern.clone());
+        let page1 = links(&["a"]);
+        assert_eq!(
+            run.advance(&config, &page1, 1).unwrap(),
+            "https://agency.gov/docs?page=2"
+        );
+        // Same links as the previous page
+        assert_eq!(run.advance(&config, &page1, 0), Err(StopReason::Duplicate));
+
+        let mut run = PaginationRun::new(pattern.clone());
+        run.advance(&config, &links(&["a"]), 1).unwrap();
+        assert_eq!(
+            run.advance(&config, &links(&["b"]), 0),
+            Err(StopReason::Empty)
+        );
+
+        let mut run = PaginationRun::new(pattern);
+        for (i, link) in ["a", "b", "c"].iter().enumerate() {
+            let next = run.advance(&config, &links(&[link]), 1).unwrap();
+            assert!(next.ends_with(&format!("page={}", i + 2)));
+        }
+        assert_eq!(
+            run.advance(&config, &links(&["d"]), 1),
+            Err(StopReason::MaxPages)
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Raw API records stored as JSON documents.
+
+use serde_json::{Map, Value};
+
+use super::extract::extract_path;
+use crate::config::ApiRecordConfig;
+use crate::ScraperResult;
+use foia::models::DocumentVersion;
+
+/// Build a JSON document for one API result item.
+///
+/// The document URL is `{api_url}#record={id}`, which keeps re-crawled
+/// records on the same document so edits become new versions.
+pub(crate) fn record_result(
+    item: &Value,
+    api_url: &str,
+    config: &ApiRecordConfig,
+) -> ScraperResult {
+    let content = serde_json::to_vec_pretty(item).unwrap_or_default();
+
+    let id = config
+        .id_path
+        .as_deref()
+        .and_then(|path| scalar_string(extract_path(item, path)))
+        .unwrap_or_else(|| DocumentVersion::compute_hash(&content)[..16].to_string());
+    let title = config
+        .title_path
+        .as_deref()
+        .and_then(|path| scalar_string(extract_path(item, path)))
+        .unwrap_or_else(|| format!("Record {}", id));
+
+    let mut fields = Map::new();
+    for (name, path) in &config.fields {
+        let value = extract_path(item, path);
+        if !value.is_null() {
+            fields.insert(name.clone(), value.clone());
+        }
+    }
+
+    let url = format!("{}#record={}", api_url, urlencoding::encode(&id));
+    let mut result = ScraperResult::new(url, title, content, "application/json".to_string());
+    result.metadata = serde_json::json!({
+        "api_record": {
+            "endpoint": api_url,
+            "id": id,
+        },
+        "fields": fields,
+    });
+    result
+}
+
+/// Render a scalar JSON value as a trimmed, non-empty string.
+fn scalar_string(value: &Value) -> Option<String> {
+    let s = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return None,
+    };
+    (!s.is_empty()).then_some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_record_result_extracts_fields() {
+        let item = json!({
+            "docket": {"id": 4521},
+            "attributes": {"title": " Meeting minutes ", "agency": "EPA", "withdrawn": null},
+        });
+        let config = ApiRecordConfig {
+            id_path: Some("docket.id".to_string()),
+            title_path: Some("attributes.title".to_string()),
+            fields: HashMap::from([
+                ("agency".to_string(), "attributes.agency".to_string()),
+                ("withdrawn".to_string(), "attributes.withdrawn".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let result = record_result(&item, "https://api.example.gov/v4/documents", &config);
+        assert_eq!(
+            result.url,
+            "https://api.example.gov/v4/documents#record=4521"
+        );
+        assert_eq!(result.title, "Meeting minutes");
+        assert_eq!(result.mime_type, "application/json");
+        assert_eq!(result.metadata["api_record"]["id"], "4521");
+        assert_eq!(result.metadata["fields"], json!({"agency": "EPA"}));
+
+        let stored: Value = serde_json::from_slice(result.content.as_ref().unwrap()).unwrap();
+        assert_eq!(stored, item);
+    }
+
+    #[test]
+    fn test_record_id_falls_back_to_content_hash() {
+        let config = ApiRecordConfig::default();
+        let a = record_result(&json!({"n": 1}), "https://api.example.gov/items", &config);
+        let b = record_result(&json!({"n": 1}), "https://api.example.gov/items", &config);
+        let c = record_result(&json!({"n": 2}), "https://api.example.gov/items", &config);
+        assert_eq!(a.url, b.url);
+        assert_ne!(a.url, c.url);
+        assert!(a.title.starts_with("Record "));
+    }
+}
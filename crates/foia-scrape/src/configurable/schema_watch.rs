@@ -0,0 +1,141 @@
+//! API response schema drift detection.
+//!
+//! Agencies reshape their JSON APIs without notice, and the symptom is a
+//! crawl that succeeds but discovers nothing. Each API discovery run
+//! fingerprints the first page of results against the previous run and
+//! raises an alert when the shape changes, when `results_path` no longer
+//! resolves to an array, or when items come back but no URLs extract.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use tracing::{debug, error};
+
+use foia::repository::DieselCrawlRepository;
+use foia::services::schema_drift::{diff_paths, fingerprint, json_paths};
+
+/// Watches one API endpoint's responses during a discovery run.
+pub(crate) struct SchemaWatch<'a> {
+    repo: Option<&'a DieselCrawlRepository>,
+    source_id: &'a str,
+    endpoint: &'a str,
+    /// Whether this run already fingerprinted a response.
+    fingerprinted: bool,
+    /// Whether this run already raised an alert.
+    alerted: bool,
+    items: usize,
+    urls: usize,
+}
+
+impl<'a> SchemaWatch<'a> {
+    pub fn new(
+        repo: &'a Option<Arc<DieselCrawlRepository>>,
+        source_id: &'a str,
+        endpoint: &'a str,
+    ) -> Self {
+        Self {
+            repo: repo.as_deref(),
+            source_id,
+            endpoint,
+            fingerprinted: false,
+            alerted: false,
+            items: 0,
+            urls: 0,
+        }
+    }
+
+    /// Check a parsed response. `results` is what `results_path` resolved
+    /// to, or `None` when it did not resolve to an array.
+    pub async fn observe(&mut self, data: &Value, results_path: &str, results: Option<&[Value]>) {
+        let Some(results) = results else {
+            // A missing array after earlier pages had results is usually
+            // just the end of the listing
+            if self.fingerprinted {
+                return;
+            }
+            let mut message = format!("results_path '{}' no longer matches an array", results_path);
+            if let Some(summary) = self.diff_against_stored(data).await {
+                message = format!("{}; {}", message, summary);
+            }
+            self.alert(&message).await;
+            return;
+        };
+        // Empty pages carry no item shape; wait for one with results
+        if results.is_empty() || self.fingerprinted {
+            return;
+        }
+        self.fingerprinted = true;
+
+        let paths = json_paths(data);
+        let current = fingerprint(&paths);
+        let Some(repo) = self.repo else {
+            return;
+        };
+        match repo.get_api_schema(self.source_id, self.endpoint).await {
+            Ok(Some(stored)) if !stored.fingerprint.is_empty() && stored.fingerprint != current => {
+                let diff = diff_paths(&stored.paths, &paths);
+                self.alert(&diff.summary()).await;
+            }
+            Ok(_) => {}
+            Err(e) => debug!("[{}] Failed to load API schema: {}", self.source_id, e),
+        }
+
+        let paths: Vec<String> = paths.into_iter().collect();
+        if let Err(e) = repo
+            .save_api_schema(self.source_id, self.endpoint, &current, &paths)
+            .await
+        {
+            debug!("[{}] Failed to save API schema: {}", self.source_id, e);
+        }
+    }
+
+    /// Count the items on a page and the URLs extracted from them.
+    pub fn record_extraction(&mut self, items: usize, urls: usize) {
+        self.items += items;
+        self.urls += urls;
+    }
+
+    /// Alert if the run returned items but none of them yielded a URL.
+    pub async fn finish(mut self) {
+        if self.items > 0 && self.urls == 0 {
+            let message = format!(
+                "{} result items matched but URL extraction found no URLs",
+                self.items
+            );
+            self.alert(&message).await;
+        }
+    }
+
+    async fn diff_against_stored(&self, data: &Value) -> Option<String> {
+        let stored = self
+            .repo?
+            .get_api_schema(self.source_id, self.endpoint)
+            .await
+            .ok()
+            .flatten()?;
+        if stored.fingerprint.is_empty() {
+            return None;
+        }
+        let diff = diff_paths(&stored.paths, &json_paths(data));
+        (!diff.is_empty()).then(|| diff.summary())
+    }
+
+    async fn alert(&mut self, message: &str) {
+        if self.alerted {
+            return;
+        }
+        self.alerted = true;
+        error!(
+            "[{}] API schema drift on {}: {}",
+            self.source_id, self.endpoint, message
+        );
+        if let Some(repo) = self.repo {
+            if let Err(e) = repo
+                .record_api_drift(self.source_id, self.endpoint, message)
+                .await
+            {
+                debug!("[{}] Failed to record API drift: {}", self.source_id, e);
+            }
+        }
+    }
+}
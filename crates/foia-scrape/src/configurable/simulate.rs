@@ -0,0 +1,158 @@
+//! Offline crawl simulation.
+//!
+//! Replays an HTML crawl against listing pages archived by earlier crawls
+//! (`discovery.snapshot_listings`) instead of the live site, so changes to
+//! start paths, document patterns or depth can be checked before a real
+//! crawl. Pages the simulated crawl reaches but has no stored body for are
+//! reported rather than fetched.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::html_crawl::{
+    convert_google_drive_file_url, extract_links_from_html, seed_frontier, CrawlerConfig,
+    PaginationTracker,
+};
+use crate::config::ScraperConfig;
+
+/// What a config would have discovered from stored listing pages.
+#[derive(Debug, Default)]
+pub struct CrawlSimulation {
+    /// Listing pages replayed from storage.
+    pub pages_replayed: usize,
+    /// Pages fetched by earlier crawls whose bodies weren't stored.
+    pub missing_bodies: BTreeSet<String>,
+    /// Pages earlier crawls never requested; a live crawl would fetch them.
+    pub unfetched: BTreeSet<String>,
+    /// Document URLs discovered.
+    pub documents: BTreeSet<String>,
+}
+
+/// Run the HTML crawl for `config` over stored pages.
+///
+/// `pages` maps listing URLs to their archived HTML; `requested` holds
+/// every URL in the source's request log.
+pub fn simulate_html_crawl(
+    config: &ScraperConfig,
+    pages: &HashMap<String, String>,
+    requested: &HashSet<String>,
+) -> CrawlSimulation {
+    let crawler_config = CrawlerConfig::from_scraper_config(config);
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut frontier = seed_frontier(config, &crawler_config.base_url, &mut visited);
+    let mut pagination = PaginationTracker::default();
+    let mut result = CrawlSimulation::default();
+
+    while let Some((current_url, depth)) = frontier.pop_front() {
+        if depth > crawler_config.max_depth {
+            continue;
+        }
+
+        let Some(html) = pages.get(&current_url) else {
+            if requested.contains(&current_url) {
+                result.missing_bodies.insert(current_url);
+            } else {
+                result.unfetched.insert(current_url);
+            }
+            continue;
+        };
+        result.pages_replayed += 1;
+
+        let (doc_urls, page_urls) = extract_links_from_html(
+            html,
+            &current_url,
+            &crawler_config.base_url,
+            &crawler_config.allowed_domain,
+            &crawler_config.document_patterns,
+            "a",
+        );
+
+        if crawler_config.infer_pagination.enabled {
+            let links: Vec<String> = doc_urls.iter().chain(&page_urls).cloned().collect();
+            if let Some(next) = pagination.next_page(
+                &crawler_config.infer_pagination,
+                &current_url,
+                depth,
+                &links,
+                &visited,
+            ) {
+                if visited.insert(next.clone()) {
+                    frontier.push_back((next, depth));
+                }
+            }
+        }
+
+        for url in doc_urls.into_iter().map(convert_google_drive_file_url) {
+            if visited.insert(url.clone()) {
+                result.documents.insert(url);
+            }
+        }
+        for page_url in page_urls {
+            if visited.insert(page_url.clone()) {
+                frontier.push_back((page_url, depth + 1));
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(patterns: &[&str]) -> ScraperConfig {
+        let mut config = ScraperConfig::default();
+        config.discovery.base_url = Some("https://records.example.gov".to_string());
+        config.discovery.start_paths = vec!["/library".to_string()];
+        config.discovery.document_patterns = patterns.iter().map(|p| p.to_string()).collect();
+        config
+    }
+
+    fn pages() -> HashMap<String, String> {
+        [
+            (
+                "https://records.example.gov/library",
+                r#"<a href="/files/a.pdf">A</a> <a href="/library/2024">2024</a>
+                   <a href="/library/2023">2023</a> <a href="/files/memo.docx">Memo</a>"#,
+            ),
+            (
+                "https://records.example.gov/library/2024",
+                r#"<a href="/files/b.pdf">B</a>"#,
+            ),
+        ]
+        .into_iter()
+        .map(|(url, html)| (url.to_string(), html.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_simulation_replays_stored_pages() {
+        let requested = HashSet::from(["https://records.example.gov/library/2023".to_string()]);
+        let result = simulate_html_crawl(&config(&[r"\.pdf$"]), &pages(), &requested);
+
+        assert_eq!(result.pages_replayed, 2);
+        assert_eq!(
+            result.documents.into_iter().collect::<Vec<_>>(),
+            vec![
+                "https://records.example.gov/files/a.pdf",
+                "https://records.example.gov/files/b.pdf",
+            ]
+        );
+        assert!(result
+            .missing_bodies
+            .contains("https://records.example.gov/library/2023"));
+        assert!(result.unfetched.is_empty());
+    }
+
+    #[test]
+    fn test_simulation_reflects_new_patterns() {
+        let result =
+            simulate_html_crawl(&config(&[r"\.pdf$", r"\.docx$"]), &pages(), &HashSet::new());
+        assert!(result
+            .documents
+            .contains("https://records.example.gov/files/memo.docx"));
+        assert!(result
+            .unfetched
+            .contains("https://records.example.gov/library/2023"));
+    }
+}
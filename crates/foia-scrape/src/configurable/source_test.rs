@@ -0,0 +1,291 @@
+//! Source onboarding tests.
+//!
+//! A source's `tests` state what its listing pages must yield: at least N
+//! document links matching a pattern, links to further pages, particular
+//! documents. Each page is fetched live or taken from the listing
+//! snapshots stored by earlier crawls and run through the same link
+//! extraction the HTML crawl uses, so a redesign that breaks extraction
+//! fails the test instead of a crawl quietly finding nothing.
+
+use std::collections::{BTreeSet, HashMap};
+
+use regex::Regex;
+
+use super::extract::resolve_url;
+use super::html_crawl::{convert_google_drive_file_url, extract_links_from_html, CrawlerConfig};
+use super::ConfigurableScraper;
+use crate::config::{ScraperConfig, SourceTestConfig};
+
+/// How a source test went.
+#[derive(Debug, Clone, Default)]
+pub struct SourceTestResult {
+    /// The test's name, or the page checked.
+    pub name: String,
+    /// Page checked.
+    pub page: String,
+    /// Document links the page yielded.
+    pub documents: usize,
+    /// Document links matching the test's pattern.
+    pub matching: usize,
+    /// Links to further pages the page yielded.
+    pub pages: usize,
+    /// Expectations the page didn't meet, or why it couldn't be checked.
+    pub failures: Vec<String>,
+}
+
+impl SourceTestResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn failed(test: &SourceTestConfig, page: String, reason: String) -> Self {
+        Self {
+            name: test.name.clone().unwrap_or_else(|| page.clone()),
+            page,
+            failures: vec![reason],
+            ..Self::default()
+        }
+    }
+}
+
+/// The page a test checks.
+pub fn test_page_url(config: &ScraperConfig, test: &SourceTestConfig) -> String {
+    let base_url = CrawlerConfig::from_scraper_config(config).base_url;
+    let path = test
+        .page
+        .as_deref()
+        .or(config.discovery.start_paths.first().map(String::as_str))
+        .unwrap_or("/");
+    resolve_url(&base_url, path)
+}
+
+/// Check one test against the HTML of its page.
+pub fn check_page(
+    config: &ScraperConfig,
+    test: &SourceTestConfig,
+    page: &str,
+    html: &str,
+) -> SourceTestResult {
+    let pattern = match test.pattern.as_deref().map(Regex::new).transpose() {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            return SourceTestResult::failed(
+                test,
+                page.to_string(),
+                format!("invalid pattern: {}", e),
+            )
+        }
+    };
+
+    let crawler_config = CrawlerConfig::from_scraper_config(config);
+    let (doc_urls, page_urls) = extract_links_from_html(
+        html,
+        page,
+        &crawler_config.base_url,
+        &crawler_config.allowed_domain,
+        &crawler_config.document_patterns,
+        "a",
+    );
+    let documents: BTreeSet<String> = doc_urls
+        .into_iter()
+        .map(convert_google_drive_file_url)
+        .collect();
+    let pages: BTreeSet<String> = page_urls.into_iter().collect();
+    let matching = documents
+        .iter()
+        .filter(|url| pattern.as_ref().is_none_or(|p| p.is_match(url)))
+        .count();
+
+    let mut failures = Vec::new();
+    let min_documents = match test.min_documents {
+        Some(min) => Some(min),
+        None if !test.has_expectations() => Some(1),
+        None => None,
+    };
+    if let Some(min) = min_documents {
+        if matching < min {
+            failures.push(match test.pattern {
+                Some(ref p) => format!(
+                    "expected at least {} document link(s) matching {}, found {}",
+                    min, p, matching
+                ),
+                None => format!(
+                    "expected at least {} document link(s), found {}",
+                    min, matching
+                ),
+            });
+        }
+    }
+    if let Some(min) = test.min_pages {
+        if pages.len() < min {
+            failures.push(format!(
+                "expected at least {} page link(s), found {}",
+                min,
+                pages.len()
+            ));
+        }
+    }
+    for expected in &test.expect {
+        let url = resolve_url(&crawler_config.base_url, expected);
+        if !documents.contains(&url) {
+            failures.push(format!("expected document link {} not found", url));
+        }
+    }
+
+    SourceTestResult {
+        name: test.name.clone().unwrap_or_else(|| page.to_string()),
+        page: page.to_string(),
+        documents: documents.len(),
+        matching,
+        pages: pages.len(),
+        failures,
+    }
+}
+
+impl ConfigurableScraper {
+    /// Run the source's tests.
+    ///
+    /// With `recorded`, pages come from the listing snapshots it holds
+    /// (page URL to HTML) instead of the live site. With `record`, pages
+    /// fetched live are stored as listing snapshots for later recorded
+    /// runs.
+    pub async fn run_source_tests(
+        &self,
+        recorded: Option<&HashMap<String, String>>,
+        record: bool,
+    ) -> Vec<SourceTestResult> {
+        let mut results = Vec::new();
+        if self.config.discovery.discovery_type != "html_crawl" {
+            for test in &self.config.tests {
+                let page = test_page_url(&self.config, test);
+                results.push(SourceTestResult::failed(
+                    test,
+                    page,
+                    format!(
+                        "source tests check HTML listing pages; this source uses {} discovery",
+                        self.config.discovery.discovery_type
+                    ),
+                ));
+            }
+            return results;
+        }
+
+        for test in &self.config.tests {
+            let page = test_page_url(&self.config, test);
+            let html = match recorded {
+                Some(pages) => match pages.get(&page) {
+                    Some(html) => html.clone(),
+                    None => {
+                        results.push(SourceTestResult::failed(
+                            test,
+                            page,
+                            "no recorded copy; run the test live with --record first".to_string(),
+                        ));
+                        continue;
+                    }
+                },
+                None => match self.fetch_test_page(&page).await {
+                    Ok(html) => html,
+                    Err(e) => {
+                        results.push(SourceTestResult::failed(test, page, e));
+                        continue;
+                    }
+                },
+            };
+
+            let result = check_page(&self.config, test, &page, &html);
+            if record && recorded.is_none() {
+                if let Some(ref repo) = self.crawl_repo {
+                    let links = (result.documents + result.pages) as u32;
+                    if let Err(e) = repo
+                        .record_listing_snapshot(&self.source.id, &page, &html, links)
+                        .await
+                    {
+                        tracing::warn!("Failed to record {}: {}", page, e);
+                    }
+                }
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    async fn fetch_test_page(&self, url: &str) -> Result<String, String> {
+        let response = self
+            .client
+            .get(url, None, None)
+            .await
+            .map_err(|e| format!("fetch failed: {}", e))?;
+        if !response.is_success() {
+            return Err(format!("fetch failed: HTTP {}", response.status));
+        }
+        response
+            .text()
+            .await
+            .map_err(|e| format!("fetch failed: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ScraperConfig {
+        let mut config = ScraperConfig::default();
+        config.discovery.base_url = Some("https://records.example.gov".to_string());
+        config.discovery.start_paths = vec!["/library".to_string()];
+        config.discovery.document_patterns = vec![r"\.pdf$".to_string()];
+        config
+    }
+
+    const LIBRARY: &str = r#"<a href="/files/a.pdf">A</a> <a href="/files/memo-2.pdf">Memo</a>
+        <a href="/library?page=2">Next</a> <a href="/about">About</a>"#;
+
+    #[test]
+    fn test_check_page_passes() {
+        let test = SourceTestConfig {
+            min_documents: Some(1),
+            pattern: Some(r"memo-\d+\.pdf$".to_string()),
+            min_pages: Some(2),
+            expect: vec!["/files/a.pdf".to_string()],
+            ..Default::default()
+        };
+        let page = test_page_url(&config(), &test);
+        assert_eq!(page, "https://records.example.gov/library");
+
+        let result = check_page(&config(), &test, &page, LIBRARY);
+        assert!(result.passed(), "{:?}", result.failures);
+        assert_eq!(result.documents, 2);
+        assert_eq!(result.matching, 1);
+        assert_eq!(result.pages, 2);
+    }
+
+    #[test]
+    fn test_check_page_fails_after_redesign() {
+        let redesigned = r#"<button data-href="/files/a.pdf">A</button>"#;
+        let result = check_page(
+            &config(),
+            &SourceTestConfig::default(),
+            "https://records.example.gov/library",
+            redesigned,
+        );
+        assert_eq!(
+            result.failures,
+            vec!["expected at least 1 document link(s), found 0"]
+        );
+
+        let test = SourceTestConfig {
+            min_documents: Some(3),
+            expect: vec!["https://records.example.gov/files/b.pdf".to_string()],
+            ..Default::default()
+        };
+        let result = check_page(
+            &config(),
+            &test,
+            "https://records.example.gov/library",
+            LIBRARY,
+        );
+        assert_eq!(result.failures.len(), 2);
+        assert!(result.failures[1].contains("files/b.pdf"));
+    }
+}
@@ -6,9 +6,11 @@ use tracing::debug;
 #[cfg(feature = "browser")]
 use super::fetch::FetchError;
 use super::ConfigurableScraper;
-use crate::{ScrapeStream, ScraperResult};
+use crate::hooks::LinkAction;
+use crate::{create_crawl_url, ScrapeStream, ScraperResult};
 #[cfg(feature = "browser")]
 use foia::browser::BrowserFetcher;
+use foia::models::{DiscoveryMethod, DocumentVersion};
 
 /// Default number of concurrent downloads.
 pub const DEFAULT_CONCURRENCY: usize = 4;
@@ -51,8 +53,9 @@ impl ConfigurableScraper {
             .spawn_download_workers(concurrency, url_rx, result_tx.clone())
             .await;
 
-        // Spawn discovery task
-        let discovery_handle = self.spawn_discovery_task(url_tx).await;
+        // Spawn discovery task (API sources storing raw records send those
+        // straight to the result channel)
+        let discovery_handle = self.spawn_discovery_task(url_tx, result_tx).await;
 
         // Spawn coordinator to clean up when done
         tokio::spawn(async move {
@@ -146,6 +149,11 @@ impl ConfigurableScraper {
             let url_rx = url_rx.clone();
             let result_tx = result_tx.clone();
             let client = self.client.clone();
+            let hooks = self.hooks.clone();
+            let guard = self.guard.clone();
+            let inflight = self.inflight.clone();
+            let source_id = self.source.id.clone();
+            let crawl_repo = self.crawl_repo.clone();
             #[cfg(feature = "browser")]
             let browser_config = browser_config.clone();
             #[cfg(feature = "browser")]
@@ -168,10 +176,55 @@ impl ConfigurableScraper {
                         None => break,
                     };
 
+                    let url = match hooks.as_deref().map(|h| h.on_link(&url)) {
+                        Some(LinkAction::Skip) => {
+                            client.mark_skipped(&url, "skipped by on_link hook").await;
+                            continue;
+                        }
+                        Some(LinkAction::Rewrite(rewritten)) => {
+                            // Track the new URL so its fetch state is recorded
+                            client
+                                .mark_skipped(&url, &format!("rewritten to {}", rewritten))
+                                .await;
+                            client
+                                .track_url(&create_crawl_url(
+                                    &rewritten,
+                                    &source_id,
+                                    DiscoveryMethod::Redirect,
+                                    Some(&url),
+                                    0,
+                                ))
+                                .await;
+                            rewritten
+                        }
+                        _ => url,
+                    };
+
+                    // Another source sharing the frontier fetches this one
+                    if let Some(repo) = &crawl_repo {
+                        if repo
+                            .queued_elsewhere(&source_id, &url)
+                            .await
+                            .unwrap_or(false)
+                        {
+                            continue;
+                        }
+                    }
+
                     if client.is_fetched(&url).await {
                         continue;
                     }
 
+                    // Found along another path and already being fetched;
+                    // released on any early exit so failures can be retried
+                    let url_claim = match inflight.claim_url(&source_id, &url).await {
+                        Ok(claim) => claim,
+                        Err(holder) => {
+                            debug!("{} is a duplicate of {}", url, holder);
+                            continue;
+                        }
+                    };
+
                     client.mark_fetching(&url).await;
 
                     #[cfg(feature = "browser")]
@@ -208,7 +261,45 @@ impl ConfigurableScraper {
                     let fetch_result = Self::fetch_url(&client, &url).await;
 
                     match fetch_result {
-                        Some(result) => {
+                        Some(mut result) => {
+                            // Error and login pages served with 200 OK aren't documents
+                            let rejection = result
+                                .content
+                                .as_deref()
+                                .filter(|_| !result.not_modified)
+                                .and_then(|content| guard.check(&url, content).err());
+                            if let Some(rejection) = rejection {
+                                debug!("{}: {}", url, rejection);
+                                client.mark_failed(&url, &rejection.to_string()).await;
+                                continue;
+                            }
+                            if let Some(hooks) = hooks.as_deref() {
+                                if !result.not_modified && !hooks.after_fetch(&mut result) {
+                                    client
+                                        .mark_skipped(&url, "dropped by after_fetch hook")
+                                        .await;
+                                    continue;
+                                }
+                            }
+                            // Another URL's fetch got the same content moments ago
+                            let content_hash = result
+                                .content
+                                .as_deref()
+                                .filter(|_| !result.not_modified)
+                                .map(DocumentVersion::compute_hash);
+                            let content_claim = match content_hash {
+                                Some(hash) => {
+                                    match inflight.claim_content(&source_id, &hash, &url).await {
+                                        Ok(claim) => Some(claim),
+                                        Err(holder) => {
+                                            let reason = format!("duplicate of {}", holder);
+                                            client.mark_skipped(&url, &reason).await;
+                                            continue;
+                                        }
+                                    }
+                                }
+                                None => None,
+                            };
                             client
                                 .mark_fetched(
                                     &url,
@@ -218,6 +309,10 @@ impl ConfigurableScraper {
                                     result.last_modified.clone(),
                                 )
                                 .await;
+                            url_claim.land();
+                            if let Some(claim) = content_claim {
+                                claim.land();
+                            }
                             if result_tx.send(result).await.is_err() {
                                 break;
                             }
@@ -244,6 +339,7 @@ impl ConfigurableScraper {
     pub(crate) async fn spawn_discovery_task(
         &self,
         url_tx: tokio::sync::mpsc::Sender<String>,
+        result_tx: tokio::sync::mpsc::Sender<ScraperResult>,
     ) -> tokio::task::JoinHandle<()> {
         let source_id = self.source.id.clone();
         let config = self.config.clone();
@@ -314,11 +410,20 @@ impl ConfigurableScraper {
                 &source_id,
                 &crawl_repo,
                 &url_tx,
+                &result_tx,
                 &browser_config,
             )
             .await;
             #[cfg(not(feature = "browser"))]
-            Self::discover_streaming(&config, &client, &source_id, &crawl_repo, &url_tx).await;
+            Self::discover_streaming(
+                &config,
+                &client,
+                &source_id,
+                &crawl_repo,
+                &url_tx,
+                &result_tx,
+            )
+            .await;
         })
     }
 
@@ -0,0 +1,121 @@
+//! YouTube channel and playlist discovery.
+//!
+//! Each entry in `discovery.start_paths` is a channel, playlist or single
+//! video URL. Collections are listed with yt-dlp and only videos not yet in
+//! the crawl queue are reported, so re-running a crawl is an incremental sync.
+
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::ConfigurableScraper;
+use crate::config::ScraperConfig;
+use crate::services::youtube;
+use foia::models::{CrawlUrl, DiscoveryMethod};
+use foia::repository::DieselCrawlRepository;
+
+/// Consecutive already-queued videos after which a channel listing stops.
+/// Channels list newest first, so this bounds an incremental sync to roughly
+/// the videos published since the previous crawl.
+const KNOWN_VIDEO_STREAK: usize = 30;
+
+impl ConfigurableScraper {
+    /// Streaming YouTube discovery.
+    pub(crate) async fn discover_youtube_streaming(
+        config: &ScraperConfig,
+        source_id: &str,
+        crawl_repo: &Option<Arc<DieselCrawlRepository>>,
+        url_tx: &tokio::sync::mpsc::Sender<String>,
+    ) {
+        let mut total_urls = 0;
+
+        for start in &config.discovery.start_paths {
+            for video_url in youtube_new_videos(start, source_id, crawl_repo).await {
+                if url_tx.send(video_url).await.is_err() {
+                    return;
+                }
+                total_urls += 1;
+            }
+        }
+
+        info!(
+            "[{}] YouTube discovery complete: {} new videos",
+            source_id, total_urls
+        );
+    }
+
+    /// Legacy YouTube discovery (non-streaming).
+    pub(crate) async fn discover_youtube(&self) -> Vec<String> {
+        let mut urls = Vec::new();
+        for start in &self.config.discovery.start_paths {
+            urls.extend(youtube_new_videos(start, &self.source.id, &self.crawl_repo).await);
+        }
+        urls
+    }
+}
+
+/// Queue the videos behind one start URL that aren't already known.
+async fn youtube_new_videos(
+    start: &str,
+    source_id: &str,
+    crawl_repo: &Option<Arc<DieselCrawlRepository>>,
+) -> Vec<String> {
+    let is_known = |url: String| {
+        let repo = crawl_repo.clone();
+        let source_id = source_id.to_string();
+        async move {
+            match repo {
+                Some(repo) => repo.url_exists(&source_id, &url).await.unwrap_or(false),
+                None => false,
+            }
+        }
+    };
+
+    if youtube::is_youtube_url(start) {
+        if is_known(start.to_string()).await {
+            return Vec::new();
+        }
+        if let Some(repo) = crawl_repo {
+            let crawl_url = CrawlUrl::new(
+                start.to_string(),
+                source_id.to_string(),
+                DiscoveryMethod::Seed,
+                None,
+                0,
+            );
+            let _ = repo.add_url(&crawl_url).await;
+        }
+        return vec![start.to_string()];
+    }
+
+    if !youtube::is_youtube_collection_url(start) {
+        warn!(
+            "[{}] Not a YouTube channel, playlist or video URL: {}",
+            source_id, start
+        );
+        return Vec::new();
+    }
+
+    let videos = match youtube::list_new_videos(start, None, KNOWN_VIDEO_STREAK, is_known).await {
+        Ok(videos) => videos,
+        Err(e) => {
+            warn!("[{}] Failed to list {}: {}", source_id, start, e);
+            return Vec::new();
+        }
+    };
+
+    if let Some(repo) = crawl_repo {
+        for video_url in &videos {
+            let crawl_url = CrawlUrl::new(
+                video_url.clone(),
+                source_id.to_string(),
+                DiscoveryMethod::VideoPlaylist,
+                Some(start.to_string()),
+                1,
+            );
+            let _ = repo.add_url(&crawl_url).await;
+        }
+    }
+
+    info!("[{}] {} new videos in {}", source_id, videos.len(), start);
+    videos
+}
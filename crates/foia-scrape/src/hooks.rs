@@ -0,0 +1,427 @@
+//! Rhai scripting hooks for the scrape pipeline.
+//!
+//! Sources can run small scripts at three points without recompiling:
+//!
+//! - `on_link` gets `url` for each discovered URL before it is fetched.
+//!   Returning `false` skips it; returning a string (or assigning to `url`)
+//!   fetches that URL instead.
+//! - `after_fetch` and `before_save` get an `item` map with `url`, `title`,
+//!   `mime_type`, `filename`, `metadata`, `size` and `text` (the content as
+//!   text for textual types, otherwise empty). Changes to `url`, `title`,
+//!   `mime_type`, `filename` and `metadata` are kept; returning `false`
+//!   drops the item.
+//!
+//! Scripts cannot import modules or touch the filesystem or network, and
+//! every invocation is bounded by an operation budget, a wall-clock limit
+//! and a cap on string, array and map sizes. A script that fails or hits a
+//! limit is logged and the item passes through unchanged, so a broken hook
+//! never loses documents.
+
+#[cfg(not(feature = "scripting"))]
+use crate::ScraperResult;
+#[cfg(not(feature = "scripting"))]
+use foia::config::HooksConfig;
+
+/// What the `on_link` hook decided for a URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkAction {
+    Keep,
+    Skip,
+    Rewrite(String),
+}
+
+/// Load a hook script: a path ending in `.rhai` is read from disk,
+/// anything else is the script itself.
+fn load_script(name: &str, script: &str) -> Result<String, String> {
+    let trimmed = script.trim();
+    if trimmed.ends_with(".rhai") && !trimmed.contains('\n') {
+        std::fs::read_to_string(trimmed)
+            .map_err(|e| format!("hook {}: cannot read {}: {}", name, trimmed, e))
+    } else {
+        Ok(script.to_string())
+    }
+}
+
+/// Whether content of this MIME type is handed to scripts as text.
+fn is_textual(mime_type: &str) -> bool {
+    let mime = mime_type.to_ascii_lowercase();
+    mime.starts_with("text/")
+        || mime.contains("json")
+        || mime.contains("xml")
+        || mime.contains("javascript")
+}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use std::time::{Duration, Instant};
+
+    use rhai::module_resolvers::DummyModuleResolver;
+    use rhai::{Dynamic, Engine, Map, Scope, AST};
+
+    use super::{is_textual, load_script, LinkAction};
+    use crate::ScraperResult;
+    use foia::config::HooksConfig;
+
+    /// Nesting limits; generous for hook-sized scripts.
+    const MAX_CALL_LEVELS: usize = 32;
+    const MAX_EXPR_DEPTH: usize = 64;
+    const MAX_FN_EXPR_DEPTH: usize = 32;
+
+    /// Operations between wall-clock checks.
+    const CLOCK_CHECK_INTERVAL: u64 = 1024;
+
+    /// Compiled hook scripts for one source.
+    pub struct ScriptHooks {
+        on_link: Option<AST>,
+        after_fetch: Option<AST>,
+        before_save: Option<AST>,
+        max_operations: u64,
+        timeout: Duration,
+        max_size: usize,
+    }
+
+    impl std::fmt::Debug for ScriptHooks {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ScriptHooks")
+                .field("on_link", &self.on_link.is_some())
+                .field("after_fetch", &self.after_fetch.is_some())
+                .field("before_save", &self.before_save.is_some())
+                .finish()
+        }
+    }
+
+    impl ScriptHooks {
+        /// Compile the configured hooks. Returns `None` when no hook is set.
+        pub fn from_config(config: &HooksConfig) -> Result<Option<Self>, String> {
+            if !config.is_enabled() {
+                return Ok(None);
+            }
+            let mut hooks = Self {
+                on_link: None,
+                after_fetch: None,
+                before_save: None,
+                max_operations: config.max_operations(),
+                timeout: Duration::from_millis(config.timeout_ms()),
+                max_size: config.max_size(),
+            };
+            let engine = hooks.engine();
+            let compile = |name: &str, script: &Option<String>| -> Result<Option<AST>, String> {
+                let Some(script) = script else {
+                    return Ok(None);
+                };
+                let source = load_script(name, script)?;
+                engine
+                    .compile(&source)
+                    .map(Some)
+                    .map_err(|e| format!("hook {}: {}", name, e))
+            };
+            hooks.on_link = compile("on_link", &config.on_link)?;
+            hooks.after_fetch = compile("after_fetch", &config.after_fetch)?;
+            hooks.before_save = compile("before_save", &config.before_save)?;
+            Ok(Some(hooks))
+        }
+
+        /// Sandboxed engine whose clock starts now.
+        fn engine(&self) -> Engine {
+            let mut engine = Engine::new();
+            engine.set_module_resolver(DummyModuleResolver::new());
+            engine.set_max_operations(self.max_operations);
+            engine.set_max_string_size(self.max_size);
+            engine.set_max_array_size(self.max_size);
+            engine.set_max_map_size(self.max_size);
+            engine.set_max_call_levels(MAX_CALL_LEVELS);
+            engine.set_max_expr_depths(MAX_EXPR_DEPTH, MAX_FN_EXPR_DEPTH);
+
+            let deadline = Instant::now() + self.timeout;
+            engine.on_progress(move |ops| {
+                if ops % CLOCK_CHECK_INTERVAL == 0 && Instant::now() > deadline {
+                    Some("time limit exceeded".into())
+                } else {
+                    None
+                }
+            });
+            engine.on_print(|text| tracing::info!("hook: {}", text));
+            engine.on_debug(|text, _, _| tracing::debug!("hook: {}", text));
+            engine
+        }
+
+        /// Run a script, logging failures. `None` means the script failed.
+        fn run(&self, name: &str, ast: &AST, scope: &mut Scope) -> Option<Dynamic> {
+            match self.engine().eval_ast_with_scope::<Dynamic>(scope, ast) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::warn!("Hook {} failed, passing item through: {}", name, e);
+                    None
+                }
+            }
+        }
+
+        /// Decide whether to fetch a discovered URL.
+        pub fn on_link(&self, url: &str) -> LinkAction {
+            let Some(ast) = &self.on_link else {
+                return LinkAction::Keep;
+            };
+            let mut scope = Scope::new();
+            scope.push("url", url.to_string());
+            let Some(value) = self.run("on_link", ast, &mut scope) else {
+                return LinkAction::Keep;
+            };
+
+            if value.as_bool() == Ok(false) {
+                return LinkAction::Skip;
+            }
+            let rewritten = if value.is_string() {
+                value.into_string().ok()
+            } else {
+                scope.get_value::<String>("url")
+            };
+            match rewritten {
+                Some(new_url) if new_url.trim().is_empty() => LinkAction::Skip,
+                Some(new_url) if new_url != url => LinkAction::Rewrite(new_url),
+                _ => LinkAction::Keep,
+            }
+        }
+
+        /// Run `after_fetch` on a downloaded item. Returns false to drop it.
+        pub fn after_fetch(&self, result: &mut ScraperResult) -> bool {
+            self.run_item("after_fetch", self.after_fetch.as_ref(), result)
+        }
+
+        /// Run `before_save` on an item about to be stored. Returns false to
+        /// drop it.
+        pub fn before_save(&self, result: &mut ScraperResult) -> bool {
+            self.run_item("before_save", self.before_save.as_ref(), result)
+        }
+
+        fn run_item(&self, name: &str, ast: Option<&AST>, result: &mut ScraperResult) -> bool {
+            let Some(ast) = ast else {
+                return true;
+            };
+            let mut scope = Scope::new();
+            scope.push("item", self.item_map(result));
+            let Some(value) = self.run(name, ast, &mut scope) else {
+                return true;
+            };
+            if value.as_bool() == Ok(false) {
+                return false;
+            }
+            if let Some(item) = scope.get_value::<Map>("item") {
+                apply_item(name, &item, result);
+            }
+            true
+        }
+
+        fn item_map(&self, result: &ScraperResult) -> Map {
+            let content = result.content.as_deref().unwrap_or_default();
+            let text = if is_textual(&result.mime_type) {
+                let end = content.len().min(self.max_size);
+                String::from_utf8_lossy(&content[..end]).into_owned()
+            } else {
+                String::new()
+            };
+
+            let mut item = Map::new();
+            item.insert("url".into(), result.url.clone().into());
+            item.insert("title".into(), result.title.clone().into());
+            item.insert("mime_type".into(), result.mime_type.clone().into());
+            item.insert(
+                "filename".into(),
+                result
+                    .original_filename
+                    .clone()
+                    .map(Dynamic::from)
+                    .unwrap_or(Dynamic::UNIT),
+            );
+            item.insert(
+                "metadata".into(),
+                rhai::serde::to_dynamic(&result.metadata).unwrap_or(Dynamic::UNIT),
+            );
+            item.insert("size".into(), (content.len() as rhai::INT).into());
+            item.insert("text".into(), text.into());
+            item
+        }
+    }
+
+    /// Copy the writable fields of a script's `item` back onto the result.
+    fn apply_item(name: &str, item: &Map, result: &mut ScraperResult) {
+        let string = |key: &str| {
+            item.get(key)
+                .filter(|v| v.is_string())
+                .and_then(|v| v.clone().into_string().ok())
+        };
+        if let Some(url) = string("url").filter(|u| !u.trim().is_empty()) {
+            result.url = url;
+        }
+        if let Some(title) = string("title") {
+            result.title = title;
+        }
+        if let Some(mime_type) = string("mime_type").filter(|m| !m.trim().is_empty()) {
+            result.mime_type = mime_type;
+        }
+        match item.get("filename") {
+            Some(v) if v.is_unit() => result.original_filename = None,
+            Some(_) => {
+                if let Some(filename) = string("filename") {
+                    result.original_filename = Some(filename);
+                }
+            }
+            None => {}
+        }
+        if let Some(metadata) = item.get("metadata") {
+            match rhai::serde::from_dynamic::<serde_json::Value>(metadata) {
+                Ok(value) if value.is_object() => result.metadata = value,
+                Ok(_) => tracing::warn!("Hook {} set metadata to a non-map; ignored", name),
+                Err(e) => tracing::warn!("Hook {} produced invalid metadata: {}", name, e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+pub use engine::ScriptHooks;
+
+/// Stand-in when built without the `scripting` feature: configuring a hook
+/// is an error rather than being silently ignored.
+#[cfg(not(feature = "scripting"))]
+#[derive(Debug)]
+pub struct ScriptHooks {
+    _private: (),
+}
+
+#[cfg(not(feature = "scripting"))]
+impl ScriptHooks {
+    pub fn from_config(config: &HooksConfig) -> Result<Option<Self>, String> {
+        if config.is_enabled() {
+            Err(
+                "scripting hooks are configured but this build lacks the `scripting` feature"
+                    .to_string(),
+            )
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn on_link(&self, _url: &str) -> LinkAction {
+        LinkAction::Keep
+    }
+
+    pub fn after_fetch(&self, _result: &mut ScraperResult) -> bool {
+        true
+    }
+
+    pub fn before_save(&self, _result: &mut ScraperResult) -> bool {
+        true
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+    use crate::ScraperResult;
+    use foia::config::HooksConfig;
+
+    fn compile_hooks(config: HooksConfig) -> ScriptHooks {
+        ScriptHooks::from_config(&config).unwrap().unwrap()
+    }
+
+    fn html_result(body: &str) -> ScraperResult {
+        ScraperResult::new(
+            "https://agency.gov/doc/1".to_string(),
+            "1".to_string(),
+            body.as_bytes().to_vec(),
+            "text/html".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_no_hooks_configured() {
+        assert!(ScriptHooks::from_config(&HooksConfig::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_compile_error_is_reported() {
+        let err = ScriptHooks::from_config(&HooksConfig {
+            on_link: Some("if url {".to_string()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err.starts_with("hook on_link"));
+    }
+
+    #[test]
+    fn test_on_link_skip_and_rewrite() {
+        let hooks = compile_hooks(HooksConfig {
+            on_link: Some(
+                r#"
+                if url.contains("/calendar/") { return false; }
+                if url.starts_with("http://") { url.replace("http://", "https://"); }
+                url
+                "#
+                .to_string(),
+            ),
+            ..Default::default()
+        });
+        assert_eq!(
+            hooks.on_link("https://agency.gov/calendar/2024"),
+            LinkAction::Skip
+        );
+        assert_eq!(
+            hooks.on_link("http://agency.gov/a.pdf"),
+            LinkAction::Rewrite("https://agency.gov/a.pdf".to_string())
+        );
+        assert_eq!(hooks.on_link("https://agency.gov/a.pdf"), LinkAction::Keep);
+    }
+
+    #[test]
+    fn test_item_hooks_edit_and_drop() {
+        let hooks = compile_hooks(HooksConfig {
+            after_fetch: Some(r#"!item.text.contains("Page not found")"#.to_string()),
+            before_save: Some(
+                r#"
+                let start = item.text.index_of("<h1>");
+                if start >= 0 {
+                    let rest = item.text.sub_string(start + 4);
+                    item.title = rest.sub_string(0, rest.index_of("</h1>"));
+                }
+                item.metadata.hooked = true;
+                "#
+                .to_string(),
+            ),
+            ..Default::default()
+        });
+
+        let mut missing = html_result("<h1>Page not found</h1>");
+        assert!(!hooks.after_fetch(&mut missing));
+
+        let mut result = html_result("<html><h1>Memo on Budget</h1></html>");
+        assert!(hooks.after_fetch(&mut result));
+        assert!(hooks.before_save(&mut result));
+        assert_eq!(result.title, "Memo on Budget");
+        assert_eq!(result.metadata["hooked"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_runaway_script_passes_item_through() {
+        let hooks = compile_hooks(HooksConfig {
+            before_save: Some("item.title = \"changed\"; loop {}".to_string()),
+            max_operations: Some(10_000),
+            ..Default::default()
+        });
+        let mut result = html_result("body");
+        assert!(hooks.before_save(&mut result));
+        assert_eq!(result.title, "1");
+
+        // Unlimited operations: only the time limit stops it
+        let timed = compile_hooks(HooksConfig {
+            before_save: Some("item.title = \"changed\"; loop {}".to_string()),
+            max_operations: Some(0),
+            timeout_ms: Some(20),
+            ..Default::default()
+        });
+        let mut result = html_result("body");
+        assert!(timed.before_save(&mut result));
+        assert_eq!(result.title, "1");
+    }
+}
@@ -8,6 +8,7 @@ pub mod config;
 pub mod configurable;
 pub mod discovery;
 pub mod google_drive;
+pub mod hooks;
 pub mod services;
 #[allow(unused_imports)]
 pub use archive::{ArchiveError, ArchiveRegistry, ArchiveSource, SnapshotInfo, WaybackSource};
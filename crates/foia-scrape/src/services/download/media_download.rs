@@ -0,0 +1,198 @@
+//! Embedded media download handler.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::services::media::MediaDownloader;
+use foia::models::{CrawlUrl, DocumentVersion, UrlStatus};
+use foia::repository::{DieselCrawlRepository, DieselDocumentRepository};
+use foia::storage::{compute_storage_path_with_dedup, write_content_async};
+
+use super::types::{
+    handle_download_failure, save_or_update_document, send_failure_event, DownloadEvent,
+};
+
+/// Staging directory (under the documents dir) yt-dlp writes into.
+const STAGING_DIR: &str = ".media-staging";
+
+/// Download embedded media with yt-dlp and store it as a document version.
+/// Failures are recorded against the crawl URL, so this always handles the URL.
+#[allow(clippy::too_many_arguments)]
+pub async fn download_media(
+    downloader: &MediaDownloader,
+    url: &str,
+    crawl_url: &CrawlUrl,
+    documents_dir: &Path,
+    doc_repo: &Arc<DieselDocumentRepository>,
+    crawl_repo: &Arc<DieselCrawlRepository>,
+    worker_id: usize,
+    event_tx: &mpsc::Sender<DownloadEvent>,
+    downloaded: &Arc<AtomicUsize>,
+    deduplicated: &Arc<AtomicUsize>,
+    failed: &Arc<AtomicUsize>,
+    proxy_url: Option<&str>,
+) {
+    debug!("Attempting media download: {}", url);
+
+    let staging_dir = documents_dir.join(STAGING_DIR);
+    let media = match downloader.download(url, &staging_dir, proxy_url).await {
+        Ok(m) => m,
+        Err(e) => {
+            warn!("Media download failed for {}: {}", url, e);
+            handle_download_failure(
+                crawl_url,
+                crawl_repo,
+                failed,
+                event_tx,
+                worker_id,
+                &format!("yt-dlp: {}", e),
+                true,
+            )
+            .await;
+            return;
+        }
+    };
+
+    let content = tokio::fs::read(&media.path).await;
+    let _ = tokio::fs::remove_file(&media.path).await;
+    let content = match content {
+        Ok(c) => c,
+        Err(e) => {
+            send_failure_event(
+                url,
+                failed,
+                event_tx,
+                worker_id,
+                &format!("Failed to read media: {}", e),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let _ = event_tx
+        .send(DownloadEvent::Progress {
+            worker_id,
+            bytes: content.len() as u64,
+            total: Some(content.len() as u64),
+        })
+        .await;
+
+    let hashes = DocumentVersion::compute_dual_hashes(&content);
+    let file_size = content.len() as i64;
+    let extension = media
+        .path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("bin")
+        .to_lowercase();
+
+    // Reuse an identical file if we already have one
+    let (dedup_index, was_deduplicated) = match doc_repo
+        .find_existing_file(&hashes.sha256, &hashes.blake3, file_size)
+        .await
+    {
+        Ok(Some(existing_path)) => {
+            deduplicated.fetch_add(1, Ordering::Relaxed);
+            let _ = event_tx
+                .send(DownloadEvent::Deduplicated {
+                    worker_id,
+                    url: url.to_string(),
+                    existing_path,
+                })
+                .await;
+            (None, true)
+        }
+        Ok(None) | Err(_) => {
+            let (relative_path, dedup_idx) = compute_storage_path_with_dedup(
+                documents_dir,
+                &hashes.sha256,
+                &media.title,
+                &extension,
+                &content,
+            );
+            let new_path = documents_dir.join(&relative_path);
+
+            let written = match new_path.parent() {
+                Some(parent) => match tokio::fs::create_dir_all(parent).await {
+                    Ok(()) => write_content_async(&new_path, &content).await,
+                    Err(e) => Err(e),
+                },
+                None => Err(std::io::Error::other(
+                    "storage path has no parent directory",
+                )),
+            };
+            if let Err(e) = written {
+                send_failure_event(url, failed, event_tx, worker_id, &e.to_string()).await;
+                return;
+            }
+            (dedup_idx, false)
+        }
+    };
+
+    let mut version = DocumentVersion::with_precomputed_hashes(
+        hashes.clone(),
+        file_size as u64,
+        media.mime_type.clone(),
+        Some(url.to_string()),
+        Some(format!("{}.{}", media.title, extension)),
+        media.published_at,
+    );
+    version.dedup_index = dedup_index;
+
+    let mut metadata = serde_json::json!({ "media": media.metadata });
+    if let Some(published) = media.published_at {
+        metadata["published_at"] = serde_json::Value::String(published.to_rfc3339());
+    }
+
+    let new_document = match save_or_update_document(
+        doc_repo,
+        url,
+        &crawl_url.source_id,
+        media.title.clone(),
+        version,
+        metadata,
+        "media",
+    )
+    .await
+    {
+        Ok(new_doc) => new_doc,
+        Err(e) => {
+            handle_download_failure(
+                crawl_url,
+                crawl_repo,
+                failed,
+                event_tx,
+                worker_id,
+                &format!("Failed to save document: {}", e),
+                false,
+            )
+            .await;
+            return;
+        }
+    };
+
+    // Mark URL as fetched
+    let mut fetched_url = crawl_url.clone();
+    fetched_url.status = UrlStatus::Fetched;
+    fetched_url.fetched_at = Some(chrono::Utc::now());
+    fetched_url.content_hash = Some(hashes.sha256.clone());
+    if let Err(e) = crawl_repo.update_url(&fetched_url).await {
+        warn!("Failed to update crawl URL status for {}: {}", url, e);
+    }
+
+    if !was_deduplicated {
+        downloaded.fetch_add(1, Ordering::Relaxed);
+        let _ = event_tx
+            .send(DownloadEvent::Completed {
+                worker_id,
+                url: url.to_string(),
+                new_document,
+            })
+            .await;
+    }
+}
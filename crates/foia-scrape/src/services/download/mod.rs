@@ -3,9 +3,11 @@
 //! Handles downloading pending documents from the crawl queue.
 //! Separated from UI concerns - emits events for progress tracking.
 
+mod media_download;
 mod types;
 mod youtube_download;
 
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -13,14 +15,19 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::warn;
 
+use crate::services::media::MediaDownloader;
 use crate::services::youtube;
 use crate::{extract_title_from_url, HttpClient};
+use foia::http_client::{CircuitBreaker, InflightRegistry};
 use foia::models::{DocumentVersion, UrlStatus};
 use foia::repository::{extract_filename_parts, DieselCrawlRepository, DieselDocumentRepository};
-use foia::storage::compute_storage_path_with_dedup;
+use foia::services::content_guard::ContentGuard;
+use foia::storage::{compute_storage_path_with_dedup, write_content_async};
 
+use media_download::download_media;
 use types::{
-    handle_download_failure, handle_unchanged, save_or_update_document, send_failure_event,
+    handle_download_failure, handle_duplicate, handle_rejected_content, handle_unchanged,
+    save_or_update_document, send_failure_event, FeedEpisode,
 };
 pub use types::{DownloadConfig, DownloadEvent, DownloadResult};
 use youtube_download::download_youtube_video;
@@ -62,6 +69,24 @@ impl DownloadService {
         let skipped = Arc::new(AtomicUsize::new(0));
         let failed = Arc::new(AtomicUsize::new(0));
 
+        let media = MediaDownloader::from_config(&self.config.media);
+        let mut guards = HashMap::new();
+        for (id, validation) in &self.config.validation {
+            let guard = ContentGuard::from_config(validation)
+                .map_err(|e| anyhow::anyhow!("Source {} validation: {}", id, e))?;
+            guards.insert(id.clone(), guard);
+        }
+        let guards = Arc::new(guards);
+        let default_guard = Arc::new(ContentGuard::default());
+        // One breaker for all workers, so their responses count together
+        let breaker = Arc::new(
+            CircuitBreaker::new(self.config.circuit_breaker.clone())
+                .with_repo(self.crawl_repo.clone()),
+        );
+        // One registry for all workers, so a URL found twice is fetched once
+        let inflight = Arc::new(
+            InflightRegistry::new(self.config.dedup_window).with_repo(self.crawl_repo.clone()),
+        );
         let mut handles = Vec::with_capacity(workers);
 
         for worker_id in 0..workers {
@@ -73,6 +98,12 @@ impl DownloadService {
             let privacy = self.config.privacy.clone();
             let via = self.config.via.clone();
             let via_mode = self.config.via_mode;
+            let auth = self.config.auth.clone();
+            let media = media.clone();
+            let breaker = breaker.clone();
+            let guards = guards.clone();
+            let default_guard = default_guard.clone();
+            let inflight = inflight.clone();
             let source_id = source_id.map(|s| s.to_string());
             let downloaded = downloaded.clone();
             let deduplicated = deduplicated.clone();
@@ -81,10 +112,22 @@ impl DownloadService {
             let event_tx = event_tx.clone();
 
             let handle = tokio::spawn(async move {
-                let client = match HttpClient::builder("download", timeout, delay)
-                    .privacy(&privacy)
-                    .build()
-                {
+                let build_client = |headers: HashMap<String, String>| {
+                    let client = HttpClient::builder("download", timeout, delay)
+                        .privacy(&privacy)
+                        .default_headers(headers)
+                        .circuit_breaker(breaker.clone())
+                        .build()?;
+
+                    // Apply via mappings for caching proxy support
+                    Ok::<_, String>(if !via.is_empty() {
+                        client.with_via_config(via.clone(), via_mode)
+                    } else {
+                        client
+                    })
+                };
+
+                let default_client = match build_client(HashMap::new()) {
                     Ok(c) => c,
                     Err(e) => {
                         tracing::error!("Failed to create HTTP client: {}", e);
@@ -92,12 +135,20 @@ impl DownloadService {
                     }
                 };
 
-                // Apply via mappings for caching proxy support
-                let client = if !via.is_empty() {
-                    client.with_via_config(via, via_mode)
-                } else {
-                    client
-                };
+                // Sources with credentials get their own client
+                let mut source_clients = HashMap::new();
+                for (id, source_auth) in &auth {
+                    let client = source_auth
+                        .to_headers()
+                        .map_err(|e| e.to_string())
+                        .and_then(&build_client);
+                    match client {
+                        Ok(client) => {
+                            source_clients.insert(id.clone(), client);
+                        }
+                        Err(e) => tracing::error!("Source {} auth: {}", id, e),
+                    }
+                }
 
                 loop {
                     // Check limit
@@ -124,6 +175,23 @@ impl DownloadService {
                     };
 
                     let url = crawl_url.url.clone();
+
+                    // Released on any early exit, so a failed fetch can be retried
+                    let url_claim = match inflight.claim_url(&crawl_url.source_id, &url).await {
+                        Ok(claim) => claim,
+                        Err(holder) => {
+                            handle_duplicate(
+                                &crawl_url,
+                                &crawl_repo,
+                                &skipped,
+                                &event_tx,
+                                worker_id,
+                                &holder,
+                            )
+                            .await;
+                            continue;
+                        }
+                    };
                     let filename = extract_title_from_url(&url);
 
                     let _ = event_tx
@@ -152,12 +220,37 @@ impl DownloadService {
                         .await;
 
                         if yt_result {
+                            url_claim.land();
                             continue;
                         }
                         // If YouTube download failed, continue to try regular HTTP
                     }
 
+                    // Embedded players that plain HTTP can't fetch
+                    if let Some(media) = media.as_ref().filter(|m| m.matches(&url)) {
+                        let proxy_url = privacy.effective_proxy_url();
+                        download_media(
+                            media,
+                            &url,
+                            &crawl_url,
+                            &documents_dir,
+                            &doc_repo,
+                            &crawl_repo,
+                            worker_id,
+                            &event_tx,
+                            &downloaded,
+                            &deduplicated,
+                            &failed,
+                            proxy_url.as_deref(),
+                        )
+                        .await;
+                        continue;
+                    }
+
                     // Fetch the URL
+                    let client = source_clients
+                        .get(&crawl_url.source_id)
+                        .unwrap_or(&default_client);
                     let response = match client
                         .get(
                             &url,
@@ -188,6 +281,36 @@ impl DownloadService {
                         continue;
                     }
 
+                    // Paused for an operator-assisted challenge; don't burn a retry
+                    if response.is_challenge_paused() {
+                        handle_download_failure(
+                            &crawl_url,
+                            &crawl_repo,
+                            &failed,
+                            &event_tx,
+                            worker_id,
+                            "Paused: challenge pending for domain",
+                            false,
+                        )
+                        .await;
+                        continue;
+                    }
+
+                    // Paused by the circuit breaker; likewise
+                    if response.is_domain_paused() {
+                        handle_download_failure(
+                            &crawl_url,
+                            &crawl_repo,
+                            &failed,
+                            &event_tx,
+                            worker_id,
+                            "Paused: domain is blocking requests",
+                            false,
+                        )
+                        .await;
+                        continue;
+                    }
+
                     if !response.is_success() {
                         handle_download_failure(
                             &crawl_url,
@@ -203,9 +326,12 @@ impl DownloadService {
                     }
 
                     // Extract metadata before consuming response
+                    let episode = FeedEpisode::from_crawl_url(&crawl_url);
                     let disposition_filename = response.content_disposition_filename();
-                    let title = disposition_filename
-                        .clone()
+                    let title = episode
+                        .as_ref()
+                        .and_then(|e| e.title.clone())
+                        .or_else(|| disposition_filename.clone())
                         .unwrap_or_else(|| extract_title_from_url(&url));
                     let mime_type = response
                         .content_type()
@@ -218,6 +344,11 @@ impl DownloadService {
                             .ok()
                             .map(|dt| dt.with_timezone(&chrono::Utc))
                     });
+                    // A feed's publish date is more meaningful than the CDN's Last-Modified
+                    let server_date = episode
+                        .as_ref()
+                        .and_then(|e| e.published_at)
+                        .or(server_date);
 
                     let content = match response.bytes().await {
                         Ok(b) => b,
@@ -244,10 +375,45 @@ impl DownloadService {
                         })
                         .await;
 
+                    // Error and login pages served with 200 OK aren't documents
+                    let guard = guards.get(&crawl_url.source_id).unwrap_or(&*default_guard);
+                    if let Err(rejection) = guard.check(&url, &content) {
+                        handle_rejected_content(
+                            &crawl_url,
+                            &crawl_repo,
+                            &failed,
+                            &event_tx,
+                            worker_id,
+                            &rejection.to_string(),
+                        )
+                        .await;
+                        continue;
+                    }
+
                     // Compute dual hashes for deduplication
                     let hashes = DocumentVersion::compute_dual_hashes(&content);
                     let file_size = content.len() as i64;
 
+                    // Another URL's fetch got the same content moments ago
+                    let content_claim = match inflight
+                        .claim_content(&crawl_url.source_id, &hashes.sha256, &url)
+                        .await
+                    {
+                        Ok(claim) => claim,
+                        Err(holder) => {
+                            handle_duplicate(
+                                &crawl_url,
+                                &crawl_repo,
+                                &skipped,
+                                &event_tx,
+                                worker_id,
+                                &holder,
+                            )
+                            .await;
+                            continue;
+                        }
+                    };
+
                     // Check for existing file with same content
                     let (dedup_index, was_deduplicated) = match doc_repo
                         .find_existing_file(&hashes.sha256, &hashes.blake3, file_size)
@@ -301,7 +467,7 @@ impl DownloadService {
                                 continue;
                             }
 
-                            if let Err(e) = tokio::fs::write(&new_path, &content).await {
+                            if let Err(e) = write_content_async(&new_path, &content).await {
                                 send_failure_event(
                                     &url,
                                     &failed,
@@ -333,8 +499,10 @@ impl DownloadService {
                         &crawl_url.source_id,
                         title,
                         version,
-                        serde_json::json!({}),
-                        "crawl",
+                        episode
+                            .as_ref()
+                            .map_or_else(|| serde_json::json!({}), FeedEpisode::metadata),
+                        if episode.is_some() { "feed" } else { "crawl" },
                     )
                     .await
                     {
@@ -364,6 +532,8 @@ impl DownloadService {
                     if let Err(e) = crawl_repo.update_url(&fetched_url).await {
                         warn!("Failed to update crawl URL status for {}: {}", url, e);
                     }
+                    url_claim.land();
+                    content_claim.land();
 
                     // Only count as downloaded if we actually wrote a new file
                     if !was_deduplicated {
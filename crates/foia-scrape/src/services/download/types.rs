@@ -11,10 +11,15 @@ use tokio::sync::mpsc;
 use tracing::warn;
 
 use crate::config::ViaMode;
-use foia::models::{CrawlUrl, Document, DocumentVersion, UrlStatus};
+use foia::config::{MediaConfig, SourceAuthConfig, ValidationConfig};
+use foia::http_client::BreakerSettings;
+use foia::models::{CrawlUrl, DiscoveryMethod, Document, DocumentVersion, UrlStatus};
 use foia::privacy::PrivacyConfig;
 use foia::repository::{DieselCrawlRepository, DieselDocumentRepository};
 
+/// Attempts before a URL whose content keeps failing validation is exhausted.
+const MAX_RETRIES: u32 = 3;
+
 /// Events emitted during download operations.
 /// Fields are populated when events are created, even if consumers don't read all of them.
 #[derive(Debug, Clone)]
@@ -46,6 +51,12 @@ pub enum DownloadEvent {
     },
     /// Document unchanged (304 Not Modified)
     Unchanged { worker_id: usize, url: String },
+    /// Another fetch of the same URL or content is in flight
+    Duplicate {
+        worker_id: usize,
+        url: String,
+        duplicate_of: String,
+    },
     /// Download failed
     Failed {
         worker_id: usize,
@@ -77,6 +88,56 @@ pub struct DownloadConfig {
     pub via: HashMap<String, String>,
     /// Via mode controlling when via mappings are used.
     pub via_mode: ViaMode,
+    /// yt-dlp settings for embedded media URLs.
+    pub media: MediaConfig,
+    /// Per-source HTTP credentials, keyed by source ID.
+    pub auth: HashMap<String, SourceAuthConfig>,
+    /// When to pause domains that keep blocking downloads.
+    pub circuit_breaker: BreakerSettings,
+    /// Per-source content validation, keyed by source ID. Other sources get
+    /// the default checks.
+    pub validation: HashMap<String, ValidationConfig>,
+    /// How long duplicate fetches of a URL or its content are suppressed.
+    pub dedup_window: Duration,
+}
+
+/// Episode details recorded in the crawl queue by feed discovery.
+#[derive(Debug, Clone, Default)]
+pub struct FeedEpisode {
+    pub feed_url: Option<String>,
+    pub title: Option<String>,
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl FeedEpisode {
+    /// Episode details for a feed enclosure, or `None` for other URLs.
+    pub fn from_crawl_url(crawl_url: &CrawlUrl) -> Option<Self> {
+        if crawl_url.discovery_method != DiscoveryMethod::Feed {
+            return None;
+        }
+        let context = &crawl_url.discovery_context;
+        Some(Self {
+            feed_url: crawl_url.parent_url.clone(),
+            title: context
+                .get("title")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            published_at: context
+                .get("published_at")
+                .and_then(|v| v.as_str())
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&chrono::Utc)),
+        })
+    }
+
+    /// Document metadata for the downloaded enclosure.
+    pub fn metadata(&self) -> serde_json::Value {
+        let mut metadata = serde_json::json!({ "feed_url": self.feed_url });
+        if let Some(published) = self.published_at {
+            metadata["published_at"] = serde_json::Value::String(published.to_rfc3339());
+        }
+        metadata
+    }
 }
 
 /// Handle a download failure: update status, increment counter, send event.
@@ -111,6 +172,54 @@ pub async fn handle_download_failure(
         .await;
 }
 
+/// Handle content that failed validation: mark the URL failed with a
+/// backoff so it is retried later, and send a failure event.
+pub async fn handle_rejected_content(
+    crawl_url: &CrawlUrl,
+    crawl_repo: &Arc<DieselCrawlRepository>,
+    failed: &Arc<AtomicUsize>,
+    event_tx: &mpsc::Sender<DownloadEvent>,
+    worker_id: usize,
+    reason: &str,
+) {
+    let mut failed_url = crawl_url.clone();
+    failed_url.mark_failed(reason, MAX_RETRIES);
+    if let Err(e) = crawl_repo.update_url(&failed_url).await {
+        warn!(
+            "Failed to update crawl URL status for {}: {}",
+            crawl_url.url, e
+        );
+    }
+    send_failure_event(&crawl_url.url, failed, event_tx, worker_id, reason).await;
+}
+
+/// Skip a URL whose normalized form or content another fetch already has.
+pub async fn handle_duplicate(
+    crawl_url: &CrawlUrl,
+    crawl_repo: &Arc<DieselCrawlRepository>,
+    skipped: &Arc<AtomicUsize>,
+    event_tx: &mpsc::Sender<DownloadEvent>,
+    worker_id: usize,
+    duplicate_of: &str,
+) {
+    let mut skipped_url = crawl_url.clone();
+    skipped_url.mark_skipped(&format!("duplicate of {}", duplicate_of));
+    if let Err(e) = crawl_repo.update_url(&skipped_url).await {
+        warn!(
+            "Failed to update crawl URL status for {}: {}",
+            crawl_url.url, e
+        );
+    }
+    skipped.fetch_add(1, Ordering::Relaxed);
+    let _ = event_tx
+        .send(DownloadEvent::Duplicate {
+            worker_id,
+            url: crawl_url.url.clone(),
+            duplicate_of: duplicate_of.to_string(),
+        })
+        .await;
+}
+
 /// Send a failure event without updating crawl status (for local errors like IO).
 pub async fn send_failure_event(
     url: &str,
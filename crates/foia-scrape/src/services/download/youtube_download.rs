@@ -7,12 +7,16 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, warn};
 
-use crate::services::youtube;
-use foia::models::{CrawlUrl, DocumentVersion, UrlStatus};
-use foia::repository::{DieselCrawlRepository, DieselDocumentRepository};
+use crate::services::youtube::{self, Captions};
+use foia::models::{CrawlUrl, DocumentPage, DocumentVersion, PageOcrStatus, UrlStatus};
+use foia::repository::{DieselCrawlRepository, DieselDocumentRepository, DieselError};
+use foia::storage::write_content_async;
 
 use super::types::{handle_download_failure, save_or_update_document, DownloadEvent};
 
+/// Backend name recorded for analysis results satisfied by official captions.
+const CAPTIONS_BACKEND: &str = "youtube_captions";
+
 /// Download a YouTube video and store it as a document.
 /// Returns true if handled (success or failure), false if should fall back to HTTP.
 #[allow(clippy::too_many_arguments)]
@@ -54,6 +58,13 @@ pub async fn download_youtube_video(
                 }
             };
 
+            // yt-dlp writes plaintext straight into the documents dir
+            if foia::encryption::content_key().is_some() {
+                if let Err(e) = write_content_async(&yt_result.video_path, &content).await {
+                    warn!("Failed to encrypt downloaded video: {}", e);
+                }
+            }
+
             let _ = event_tx
                 .send(DownloadEvent::Progress {
                     worker_id,
@@ -65,33 +76,45 @@ pub async fn download_youtube_video(
             // Create document version
             let content_hash = DocumentVersion::compute_hash(&content);
 
-            // Parse upload date if available
-            let server_date = yt_result.metadata.upload_date.as_ref().and_then(|d| {
-                chrono::NaiveDate::parse_from_str(d, "%Y%m%d")
-                    .ok()
-                    .map(|nd| nd.and_hms_opt(0, 0, 0).unwrap().and_utc())
-            });
+            let published_at = yt_result.metadata.published_at();
 
             let version = DocumentVersion::new_with_metadata(
                 &content,
                 "video/mp4".to_string(),
                 Some(url.to_string()),
                 Some(format!("{}.mp4", yt_result.metadata.title)),
-                server_date,
+                published_at,
             );
 
             // Build metadata
             let mut metadata = serde_json::json!({
                 "youtube_id": yt_result.metadata.id,
                 "uploader": yt_result.metadata.uploader,
+                "channel": yt_result.metadata.channel,
+                "channel_id": yt_result.metadata.channel_id,
                 "duration": yt_result.metadata.duration,
                 "view_count": yt_result.metadata.view_count,
             });
 
+            if let Some(published) = published_at {
+                metadata["published_at"] = serde_json::Value::String(published.to_rfc3339());
+            }
             if let Some(desc) = &yt_result.metadata.description {
                 metadata["description"] = serde_json::Value::String(desc.clone());
             }
 
+            // Official captions are a better text source than transcribing
+            let captions =
+                youtube::fetch_captions(url, &yt_result.metadata, documents_dir, proxy_url)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to fetch captions for {}: {}", url, e);
+                        None
+                    });
+            if let Some(captions) = &captions {
+                metadata["captions_language"] = captions.language.clone().into();
+            }
+
             // Save or update document
             let new_document = match save_or_update_document(
                 doc_repo,
@@ -120,6 +143,12 @@ pub async fn download_youtube_video(
                 }
             };
 
+            if let Some(captions) = &captions {
+                if let Err(e) = store_captions(doc_repo, url, captions).await {
+                    warn!("Failed to store captions for {}: {}", url, e);
+                }
+            }
+
             // Mark URL as fetched
             let mut fetched_url = crawl_url.clone();
             fetched_url.status = UrlStatus::Fetched;
@@ -156,3 +185,48 @@ pub async fn download_youtube_video(
         }
     }
 }
+
+/// Store official captions as the video's extracted text.
+///
+/// Mirrors how text extraction finalizes single-page documents, and records
+/// completed `ocr` and `whisper` results so neither re-processes the video.
+async fn store_captions(
+    doc_repo: &DieselDocumentRepository,
+    url: &str,
+    captions: &Captions,
+) -> Result<(), DieselError> {
+    let Some(doc) = doc_repo.get_by_url(url).await?.into_iter().next() else {
+        return Ok(());
+    };
+    let Some(version) = doc.current_version() else {
+        return Ok(());
+    };
+
+    let mut page = DocumentPage::new(doc.id.clone(), version.id, 1);
+    page.pdf_text = Some(captions.text.clone());
+    page.final_text = Some(captions.text.clone());
+    page.ocr_status = PageOcrStatus::OcrComplete;
+    doc_repo.save_page(&page).await?;
+    doc_repo.set_version_page_count(version.id, 1).await?;
+    doc_repo.finalize_document(&doc.id).await?;
+
+    let metadata = serde_json::json!({ "language": captions.language });
+    for (analysis_type, text) in [("ocr", None), ("whisper", Some(captions.text.as_str()))] {
+        doc_repo
+            .store_analysis_result_for_document(
+                &doc.id,
+                version.id as i32,
+                analysis_type,
+                CAPTIONS_BACKEND,
+                None,
+                text,
+                None,
+                None,
+                None,
+                Some(&metadata),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
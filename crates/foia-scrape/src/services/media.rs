@@ -0,0 +1,272 @@
+//! Embedded media download service using yt-dlp.
+//!
+//! Handles players that plain HTTP fetching can't (HLS streams, Vimeo and
+//! Granicus embeds, agency video portals). YouTube has its own handler in
+//! [`super::youtube`]; this one is driven entirely by [`MediaConfig`].
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use foia::config::MediaConfig;
+use foia::models::DocumentVersion;
+
+/// Fields of yt-dlp's info JSON kept as extraction metadata.
+const RECORDED_FIELDS: &[&str] = &[
+    "id",
+    "extractor",
+    "extractor_key",
+    "webpage_url",
+    "format_id",
+    "format",
+    "ext",
+    "duration",
+    "width",
+    "height",
+    "vcodec",
+    "acodec",
+    "uploader",
+    "description",
+    "upload_date",
+];
+
+/// A downloaded media file.
+#[derive(Debug)]
+pub struct MediaDownload {
+    /// Where yt-dlp wrote the file (inside the staging directory).
+    pub path: PathBuf,
+    pub mime_type: String,
+    pub title: String,
+    pub published_at: Option<DateTime<Utc>>,
+    /// Extraction details from yt-dlp (extractor, format, duration, ...).
+    pub metadata: serde_json::Value,
+}
+
+/// yt-dlp backed downloader for URLs matching the configured patterns.
+#[derive(Debug, Clone)]
+pub struct MediaDownloader {
+    binary: String,
+    format: String,
+    extra_args: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl MediaDownloader {
+    /// Build a downloader from config, or `None` if no URL patterns are set.
+    /// Invalid patterns are logged and ignored.
+    pub fn from_config(config: &MediaConfig) -> Option<Self> {
+        let patterns: Vec<Regex> = config
+            .url_patterns
+            .iter()
+            .filter_map(|p| match Regex::new(p) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    warn!("Ignoring invalid media URL pattern '{}': {}", p, e);
+                    None
+                }
+            })
+            .collect();
+
+        if patterns.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            binary: config.binary().to_string(),
+            format: config.format().to_string(),
+            extra_args: config.extra_args.clone(),
+            patterns,
+        })
+    }
+
+    /// Whether a URL should be fetched with yt-dlp.
+    pub fn matches(&self, url: &str) -> bool {
+        self.patterns.iter().any(|re| re.is_match(url))
+    }
+
+    /// Download the media behind `url` into `staging_dir`.
+    ///
+    /// If `proxy_url` is not provided, checks the SOCKS_PROXY environment
+    /// variable, matching the YouTube handler.
+    pub async fn download(
+        &self,
+        url: &str,
+        staging_dir: &Path,
+        proxy_url: Option<&str>,
+    ) -> Result<MediaDownload> {
+        info!("Downloading media with {}: {}", self.binary, url);
+        tokio::fs::create_dir_all(staging_dir).await?;
+
+        // Name files after the URL hash: extractor IDs aren't always filename-safe
+        let url_hash = DocumentVersion::compute_hash(url.as_bytes());
+        let prefix = format!("media-{}", &url_hash[..16]);
+        let output_template = staging_dir
+            .join(format!("{}.%(ext)s", prefix))
+            .to_string_lossy()
+            .to_string();
+
+        let mut cmd = Command::new(&self.binary);
+        cmd.args([
+            "--no-playlist",
+            "--no-simulate",
+            "--dump-json",
+            "--format",
+            &self.format,
+            "--output",
+            &output_template,
+            "--no-progress",
+        ]);
+
+        let env_proxy = foia::privacy::socks_proxy_from_env();
+        let effective_proxy = proxy_url.or(env_proxy.as_deref()).filter(|s| !s.is_empty());
+        if let Some(proxy) = effective_proxy {
+            debug!("Using proxy for {}: {}", self.binary, proxy);
+            cmd.args(["--proxy", proxy]);
+        }
+
+        cmd.args(&self.extra_args);
+        cmd.arg(url);
+
+        let output = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .with_context(|| format!("Failed to execute {}", self.binary))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("{} failed: {}", self.binary, stderr.trim());
+        }
+
+        // --dump-json prints one line per downloaded item; --no-playlist keeps it to one
+        let info: serde_json::Value = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str(line).ok())
+            .context("No info JSON in yt-dlp output")?;
+
+        let path = find_output(staging_dir, &prefix)
+            .await?
+            .with_context(|| format!("Downloaded file not found for {}", url))?;
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        Ok(MediaDownload {
+            mime_type: mime_for_extension(&ext).to_string(),
+            title: info["title"]
+                .as_str()
+                .filter(|t| !t.trim().is_empty())
+                .unwrap_or(url)
+                .to_string(),
+            published_at: published_at(&info),
+            metadata: extraction_metadata(&info),
+            path,
+        })
+    }
+}
+
+/// Find the finished file yt-dlp wrote for `prefix`, skipping partial downloads.
+async fn find_output(dir: &Path, prefix: &str) -> Result<Option<PathBuf>> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(prefix) && !name.ends_with(".part") && !name.ends_with(".ytdl") {
+            return Ok(Some(entry.path()));
+        }
+    }
+    Ok(None)
+}
+
+/// Publish time from yt-dlp's `timestamp` or `upload_date` fields.
+fn published_at(info: &serde_json::Value) -> Option<DateTime<Utc>> {
+    if let Some(ts) = info["timestamp"].as_i64() {
+        return DateTime::from_timestamp(ts, 0);
+    }
+    let date = NaiveDate::parse_from_str(info["upload_date"].as_str()?, "%Y%m%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+/// Keep the extraction details worth recording from yt-dlp's info JSON.
+fn extraction_metadata(info: &serde_json::Value) -> serde_json::Value {
+    let mut media = serde_json::Map::new();
+    for field in RECORDED_FIELDS {
+        if let Some(value) = info.get(*field).filter(|v| !v.is_null()) {
+            media.insert(field.to_string(), value.clone());
+        }
+    }
+    if let Some(version) = info.pointer("/_version/version") {
+        media.insert("yt_dlp_version".to_string(), version.clone());
+    }
+    serde_json::Value::Object(media)
+}
+
+/// MIME type for a media file extension written by yt-dlp.
+pub fn mime_for_extension(ext: &str) -> &'static str {
+    match ext {
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "mov" => "video/quicktime",
+        "avi" => "video/avi",
+        "flv" => "video/x-flv",
+        "ts" => "video/mp2t",
+        "m4a" => "audio/m4a",
+        "mp3" => "audio/mpeg",
+        "ogg" | "opus" => "audio/ogg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "aac" => "audio/aac",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config_requires_patterns() {
+        assert!(MediaDownloader::from_config(&MediaConfig::default()).is_none());
+
+        let config = MediaConfig {
+            url_patterns: vec!["(".to_string(), r"vimeo\.com/\d+".to_string()],
+            ..Default::default()
+        };
+        let downloader = MediaDownloader::from_config(&config).unwrap();
+        assert!(downloader.matches("https://vimeo.com/123456"));
+        assert!(!downloader.matches("https://example.gov/report.pdf"));
+        assert_eq!(downloader.binary, "yt-dlp");
+    }
+
+    #[test]
+    fn test_extraction_metadata() {
+        let info = serde_json::json!({
+            "id": "42",
+            "title": "Council meeting",
+            "extractor_key": "Granicus",
+            "duration": 3600.5,
+            "vcodec": null,
+            "timestamp": 1704067200,
+            "_version": { "version": "2024.12.13" },
+            "formats": [{ "format_id": "hls-720" }],
+        });
+
+        let metadata = extraction_metadata(&info);
+        assert_eq!(metadata["extractor_key"], "Granicus");
+        assert_eq!(metadata["yt_dlp_version"], "2024.12.13");
+        assert!(metadata.get("vcodec").is_none());
+        assert!(metadata.get("formats").is_none());
+
+        let published = published_at(&info).unwrap();
+        assert_eq!(published.format("%Y-%m-%d").to_string(), "2024-01-01");
+    }
+}
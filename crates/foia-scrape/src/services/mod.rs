@@ -1,4 +1,5 @@
 //! Scrape-related services.
 
 pub mod download;
+pub mod media;
 pub mod youtube;
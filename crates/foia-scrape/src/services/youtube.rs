@@ -1,13 +1,19 @@
 //! YouTube video download service using yt-dlp.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use tracing::{debug, info, warn};
 
+/// Channel tabs that list videos (anything else is treated as the channel root).
+const CHANNEL_TABS: &[&str] = &["videos", "streams", "shorts", "playlists", "podcasts"];
+
 /// Check if a URL is a YouTube video URL.
 pub fn is_youtube_url(url: &str) -> bool {
     url.contains("youtube.com/watch")
@@ -16,6 +22,40 @@ pub fn is_youtube_url(url: &str) -> bool {
         || url.contains("youtube.com/v/")
 }
 
+/// Check if a URL is a YouTube channel or playlist rather than a single video.
+pub fn is_youtube_collection_url(url: &str) -> bool {
+    if !url.contains("youtube.com/") || is_youtube_url(url) {
+        return false;
+    }
+    url.contains("/playlist?")
+        || url.contains("/@")
+        || url.contains("/channel/")
+        || url.contains("/c/")
+        || url.contains("/user/")
+}
+
+/// Canonical watch URL for a video ID.
+pub fn video_watch_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", video_id)
+}
+
+/// Normalize a collection URL for listing.
+///
+/// A bare channel URL lists its tabs rather than its videos, so it is
+/// pointed at the uploads tab. Playlists and explicit tabs are kept as-is.
+fn collection_listing_url(url: &str) -> String {
+    if url.contains("/playlist?") {
+        return url.to_string();
+    }
+    let trimmed = url.trim_end_matches('/');
+    let last = trimmed.rsplit('/').next().unwrap_or_default();
+    if CHANNEL_TABS.contains(&last) {
+        trimmed.to_string()
+    } else {
+        format!("{}/videos", trimmed)
+    }
+}
+
 /// Metadata returned by yt-dlp.
 #[derive(Debug, Clone, Deserialize)]
 pub struct VideoMetadata {
@@ -25,12 +65,66 @@ pub struct VideoMetadata {
     pub description: Option<String>,
     #[serde(default)]
     pub upload_date: Option<String>,
+    /// Publish time as a Unix timestamp (more precise than `upload_date`).
+    #[serde(default)]
+    pub timestamp: Option<i64>,
     #[serde(default)]
     pub uploader: Option<String>,
     #[serde(default)]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    #[serde(default)]
     pub duration: Option<f64>,
     #[serde(default)]
     pub view_count: Option<u64>,
+    /// Uploader-provided caption tracks keyed by language.
+    /// Auto-generated captions are listed separately by yt-dlp and ignored.
+    #[serde(default)]
+    pub subtitles: HashMap<String, serde_json::Value>,
+}
+
+impl VideoMetadata {
+    /// When the video was published, if yt-dlp reported it.
+    pub fn published_at(&self) -> Option<DateTime<Utc>> {
+        if let Some(ts) = self.timestamp {
+            if let Some(dt) = DateTime::from_timestamp(ts, 0) {
+                return Some(dt);
+            }
+        }
+        self.upload_date.as_ref().and_then(|d| {
+            NaiveDate::parse_from_str(d, "%Y%m%d")
+                .ok()
+                .and_then(|nd| nd.and_hms_opt(0, 0, 0))
+                .map(|ndt| ndt.and_utc())
+        })
+    }
+
+    /// Language of the official caption track to download.
+    ///
+    /// Prefers English, otherwise the first language alphabetically so the
+    /// choice is stable across runs. Live chat replays are not captions.
+    pub fn caption_language(&self) -> Option<&str> {
+        let mut languages: Vec<&str> = self
+            .subtitles
+            .keys()
+            .map(|k| k.as_str())
+            .filter(|k| *k != "live_chat")
+            .collect();
+        languages.sort_unstable();
+        languages
+            .iter()
+            .find(|l| **l == "en" || l.starts_with("en-"))
+            .or_else(|| languages.first())
+            .copied()
+    }
+}
+
+/// Official captions for a video, converted to plain text.
+#[derive(Debug, Clone)]
+pub struct Captions {
+    pub language: String,
+    pub text: String,
 }
 
 /// Result of a YouTube download.
@@ -166,6 +260,212 @@ pub async fn fetch_metadata(url: &str, proxy_url: Option<&str>) -> Result<VideoM
     Ok(metadata)
 }
 
+/// Download a video's official captions as plain text.
+///
+/// Returns `None` when the video has no uploader-provided captions.
+/// The caption file is written next to the video and removed once read.
+pub async fn fetch_captions(
+    url: &str,
+    metadata: &VideoMetadata,
+    output_dir: &Path,
+    proxy_url: Option<&str>,
+) -> Result<Option<Captions>> {
+    let language = match metadata.caption_language() {
+        Some(lang) => lang.to_string(),
+        None => return Ok(None),
+    };
+
+    let prefix = format!("{}.captions", metadata.id);
+    let output_template = output_dir
+        .join(format!("{}.%(ext)s", prefix))
+        .to_string_lossy()
+        .to_string();
+
+    let mut cmd = Command::new("yt-dlp");
+    cmd.args([
+        "--no-playlist",
+        "--skip-download",
+        "--write-subs",
+        "--sub-format",
+        "vtt",
+        "--sub-langs",
+        &language,
+        "--output",
+        &output_template,
+        "--no-progress",
+    ]);
+
+    let env_proxy = foia::privacy::socks_proxy_from_env();
+    let effective_proxy = proxy_url.or(env_proxy.as_deref()).filter(|s| !s.is_empty());
+    if let Some(proxy) = effective_proxy {
+        cmd.args(["--proxy", proxy]);
+    }
+
+    cmd.arg(url);
+
+    let output = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to execute yt-dlp for captions")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("yt-dlp caption download failed: {}", stderr);
+    }
+
+    // yt-dlp names the file <prefix>.<lang>.vtt
+    let mut entries = tokio::fs::read_dir(output_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(&prefix) && name.ends_with(".vtt") {
+            let vtt = tokio::fs::read_to_string(entry.path()).await?;
+            let _ = tokio::fs::remove_file(entry.path()).await;
+            let text = vtt_to_text(&vtt);
+            if text.is_empty() {
+                return Ok(None);
+            }
+            return Ok(Some(Captions { language, text }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Convert a WebVTT caption file to plain text.
+///
+/// Drops the header, cue timings, cue identifiers and inline markup, and
+/// collapses the repeated lines that rolling captions produce.
+pub fn vtt_to_text(vtt: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_header = true;
+    let mut in_note = false;
+
+    for raw in vtt.lines() {
+        let line = raw.trim();
+        if line.is_empty() {
+            in_header = false;
+            in_note = false;
+            continue;
+        }
+        if in_header || in_note {
+            continue;
+        }
+        if line.starts_with("NOTE") || line == "STYLE" || line == "REGION" {
+            in_note = true;
+            continue;
+        }
+        if line.contains("-->") || line.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let text = strip_tags(line);
+        let text = text.trim();
+        if text.is_empty() || lines.last().is_some_and(|prev| prev == text) {
+            continue;
+        }
+        lines.push(text.to_string());
+    }
+
+    lines.join("\n")
+}
+
+/// Remove `<...>` markup (voice spans, timestamps, styling) from a cue line.
+fn strip_tags(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+}
+
+/// List the videos in a channel or playlist, skipping ones already known.
+///
+/// Channel uploads are listed newest first, so listing stops after
+/// `stop_after_known` consecutive known videos; an incremental sync then
+/// only pages through what was published since the last run. Playlists have
+/// no such ordering and are always listed in full. Returns watch URLs of the
+/// new videos in listing order.
+pub async fn list_new_videos<F, Fut>(
+    url: &str,
+    proxy_url: Option<&str>,
+    stop_after_known: usize,
+    mut is_known: F,
+) -> Result<Vec<String>>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let listing_url = collection_listing_url(url);
+    let newest_first = !listing_url.contains("/playlist?");
+
+    let mut cmd = Command::new("yt-dlp");
+    cmd.args(["--flat-playlist", "--lazy-playlist", "--print", "id"]);
+
+    let env_proxy = foia::privacy::socks_proxy_from_env();
+    let effective_proxy = proxy_url.or(env_proxy.as_deref()).filter(|s| !s.is_empty());
+    if let Some(proxy) = effective_proxy {
+        cmd.args(["--proxy", proxy]);
+    }
+
+    cmd.arg(&listing_url);
+
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to execute yt-dlp for playlist listing")?;
+    let stdout = child.stdout.take().context("yt-dlp stdout not captured")?;
+    let mut reader = BufReader::new(stdout).lines();
+
+    let mut new_videos = Vec::new();
+    let mut known_streak = 0;
+    let mut stopped_early = false;
+    while let Some(line) = reader.next_line().await? {
+        let id = line.trim();
+        if id.is_empty() {
+            continue;
+        }
+        let watch_url = video_watch_url(id);
+        if is_known(watch_url.clone()).await {
+            known_streak += 1;
+            if newest_first && stop_after_known > 0 && known_streak >= stop_after_known {
+                stopped_early = true;
+                break;
+            }
+        } else {
+            known_streak = 0;
+            new_videos.push(watch_url);
+        }
+    }
+
+    if stopped_early {
+        let _ = child.kill().await;
+        debug!(
+            "Stopped listing {} after {} known videos",
+            listing_url, stop_after_known
+        );
+    } else {
+        let status = child.wait().await?;
+        if !status.success() && new_videos.is_empty() {
+            anyhow::bail!("yt-dlp listing failed for {}", listing_url);
+        }
+    }
+
+    Ok(new_videos)
+}
+
 /// Sanitize a string for use as a filename.
 fn sanitize_filename(name: &str) -> String {
     name.chars()
@@ -192,4 +492,64 @@ mod tests {
         assert!(!is_youtube_url("https://example.com/video.mp4"));
         assert!(!is_youtube_url("https://vimeo.com/123456"));
     }
+
+    #[test]
+    fn test_is_youtube_collection_url() {
+        assert!(is_youtube_collection_url(
+            "https://www.youtube.com/playlist?list=PL123"
+        ));
+        assert!(is_youtube_collection_url("https://www.youtube.com/@fbi"));
+        assert!(is_youtube_collection_url(
+            "https://www.youtube.com/channel/UC123/videos"
+        ));
+        assert!(!is_youtube_collection_url(
+            "https://www.youtube.com/watch?v=abc123&list=PL123"
+        ));
+        assert!(!is_youtube_collection_url("https://example.com/@user"));
+    }
+
+    #[test]
+    fn test_collection_listing_url() {
+        assert_eq!(
+            collection_listing_url("https://www.youtube.com/@fbi/"),
+            "https://www.youtube.com/@fbi/videos"
+        );
+        assert_eq!(
+            collection_listing_url("https://www.youtube.com/@fbi/streams"),
+            "https://www.youtube.com/@fbi/streams"
+        );
+        assert_eq!(
+            collection_listing_url("https://www.youtube.com/playlist?list=PL123"),
+            "https://www.youtube.com/playlist?list=PL123"
+        );
+    }
+
+    #[test]
+    fn test_vtt_to_text() {
+        let vtt = "WEBVTT\nKind: captions\nLanguage: en\n\n\
+                   NOTE produced by hand\n\n\
+                   1\n00:00:00.000 --> 00:00:02.000\n<v Speaker>Good morning &amp; welcome.</v>\n\n\
+                   2\n00:00:02.000 --> 00:00:04.000 align:start\nGood morning &amp; welcome.\n\n\
+                   3\n00:00:04.000 --> 00:00:06.000\nToday's <c>briefing</c> covers\nthe release.\n";
+        assert_eq!(
+            vtt_to_text(vtt),
+            "Good morning & welcome.\nToday's briefing covers\nthe release."
+        );
+    }
+
+    #[test]
+    fn test_caption_language_prefers_english() {
+        let mut metadata: VideoMetadata =
+            serde_json::from_str(r#"{"id": "abc", "title": "t"}"#).unwrap();
+        assert_eq!(metadata.caption_language(), None);
+
+        let track = serde_json::Value::Null;
+        for lang in ["live_chat", "fr"] {
+            metadata.subtitles.insert(lang.to_string(), track.clone());
+        }
+        assert_eq!(metadata.caption_language(), Some("fr"));
+
+        metadata.subtitles.insert("en-US".to_string(), track);
+        assert_eq!(metadata.caption_language(), Some("en-US"));
+    }
 }
@@ -0,0 +1,216 @@
+# English messages for the web interface. The fallback for every other
+# catalog, so every message must be defined here.
+
+## Navigation
+
+nav-tags = tags
+nav-snapshots = snapshots
+nav-sources = sources
+nav-challenges = challenges
+nav-dates = dates
+nav-coverage = coverage
+nav-aliases = aliases
+nav-transcribe = transcribe
+nav-about = about
+language-label = Language
+
+## Page titles and errors
+
+title-browse = Browse
+title-tags = Tags
+title-tag = Tag: { $tag }
+title-types = Document Types
+title-type = Type: { $type }
+title-coverage = OCR Coverage
+title-coverage-source = OCR Coverage: { $source }
+title-about = About
+title-error = Error
+title-not-found = Not Found
+title-takedown = Request Removal
+title-tombstone = Document Withheld
+title-transcribe = Transcribe
+title-config = Configuration
+error-document-not-found = Document not found.
+error-version-not-found = This version of the document does not exist.
+error-page-not-found = This page of the document does not exist.
+error-no-about = No about page.
+
+## Document listings
+
+filter-source = Source:
+filter-all-sources = All Sources
+filter-agency = Agency:
+filter-all-agencies = All Agencies
+filter-record-type = Record type:
+filter-all-records = All Records
+filter-citing = Citing:
+filter-any-exemption = Any Exemption
+filter-search = Search:
+filter-search-placeholder = Titles, synopses, attachment text...
+filter-tags = Tags:
+filter-tag-placeholder = Add tag...
+filter-types = Types:
+result-count = { $count ->
+    [one] { $count } result
+   *[other] { $count } results
+}
+document-count = { $count ->
+    [one] { $count } document
+   *[other] { $count } documents
+}
+tag-document-count = { $count ->
+    [one] { $count } document with tag "{ $tag }"
+   *[other] { $count } documents with tag "{ $tag }"
+}
+of-total = of { $total }
+page-previous = Previous
+page-next = Next
+page-number = page { $page }
+bates = Bates
+attachment-in = in
+col-document = Document
+col-source = Source
+col-type = Type
+col-size = Size
+col-acquired = Acquired
+col-category = Category
+col-mime-type = MIME Type
+col-count = Count
+col-file = File
+col-status = Status
+
+## Tags and types
+
+breadcrumb-tags = Tags
+breadcrumb-types = Types
+tags-intro = Click a tag to view all documents with that tag:
+tags-empty = No tags found. Run 'foia summarize' to generate tags for your documents.
+types-breakdown = MIME Type Breakdown
+
+## Document page
+
+breadcrumb-browse = Browse
+doc-also-in = Also in:
+doc-versions = Versions:
+version-lost = lost
+version-lost-reason = File lost, text preserved: { $reason }
+doc-lost-notice = File lost, text preserved. The original file is no longer in storage; its extracted text is kept below.
+sheet-rows = { $count ->
+    [one] { $count } row
+   *[other] { $count } rows
+}
+sheet-download = Download CSV
+sheet-truncated = Showing the first { $shown } of { $total } rows.
+pages-loading = Loading pages...
+pages-end = { $count ->
+    [one] End of document (1 page)
+   *[other] End of document ({ $count } pages)
+}
+reocr-run = Run DeepSeek OCR
+page-ocr-run = OCR this page now
+page-ocr-running = Running OCR...
+page-ocr-failed = OCR failed
+page-text-edit = Edit text
+page-text-save = Save
+page-text-cancel = Cancel
+page-text-save-failed = Could not save the text
+page-text-corrected = Corrected
+page-text-verified = Verified
+archive-contents = { $count ->
+    [one] Archive Contents (1 file)
+   *[other] Archive Contents ({ $count } files)
+}
+related-records = Related Records ({ $count })
+doc-request-removal = Request removal
+
+## Reader
+
+reader-link = Accessible text view
+reader-original = Original document
+reader-skip = Skip to text
+reader-text-size = Text size
+reader-smaller = Smaller text
+reader-reset = Default size
+reader-larger = Larger text
+reader-note = Text extracted from the original file by software. It may contain recognition errors.
+reader-pages = Pages
+reader-page = Page { $page }
+reader-empty-page = No text on this page.
+reader-no-text = No text has been extracted from this document yet.
+reader-cite-page = Link to this page
+reader-cite-selection = Copy link to selection
+reader-cite-copied = Link copied.
+reader-contents = Contents
+reader-section-pages = Pages { $first }–{ $last }
+reader-section-text = Text
+reader-section-download-text = Download the text of { $title }
+reader-section-download-pdf = Download the pages of { $title } as PDF
+
+## Removal requests
+
+takedown-intro = If a document here exposes your personal information or should not be public for another reason, ask for it to be taken down. Requests are reviewed by the archive's operators, who may withhold the document while they decide.
+takedown-read-only = This archive does not accept requests online. Contact its operators instead.
+takedown-name = Your name
+takedown-contact = Email address or other contact
+takedown-documents = Documents (IDs or links, one per line)
+takedown-reason = Why should they be taken down?
+takedown-submit = Send request
+takedown-received = Request received. Your case number is
+tombstone-suppressed = This document is withheld while a removal request is reviewed.
+tombstone-removed = This document was removed following a removal request.
+tombstone-case = Case #{ $case }, { $date }
+
+## Configuration
+
+config-intro = Scraper configs are saved as a new version each time they change. Compare an earlier version with the configs in use, restore it, or edit one source's config. Crawlers and this server pick changes up at their next config reload.
+config-token-required = Managing the configuration requires the access token.
+config-read-only = This server is read-only; configs can be viewed but not changed.
+config-history = Versions
+config-history-empty = No versions saved yet.
+config-version = Version
+config-saved = Saved
+config-sources = Sources
+config-current = in use
+config-compare = Compare
+config-rollback = Roll back
+config-rollback-confirm = Restore the scraper configs of version
+config-rolled-back = Rolled back to version
+config-no-diff = No differences from the configs in use.
+config-edit = Edit a source
+config-source = Source ID
+config-save = Validate and save
+config-saved-version = Saved as version
+config-unchanged = No changes to save.
+config-invalid-json = Not valid JSON:
+config-failed = Could not reach the server. Try again.
+
+## Transcription
+
+transcribe-intro = Some pages are handwritten or too faint for OCR. Type what you read on each page, line by line. Every page is transcribed by two volunteers working separately; when they agree, the text is added to the archive.
+transcribe-read-only = This archive does not accept transcriptions online.
+transcribe-key = Your volunteer key
+transcribe-name = Your name
+transcribe-start = Start transcribing
+transcribe-submit = Submit and continue
+transcribe-none = No pages are waiting for you. Thank you!
+transcribe-saved = Saved.
+transcribe-failed = Could not reach the archive. Try again.
+transcribe-page = Page
+
+## Dates
+
+# $month is one of the month names below
+date = { $month } { $day }, { $year }
+datetime = { $date }, { $time }
+month-1 = January
+month-2 = February
+month-3 = March
+month-4 = April
+month-5 = May
+month-6 = June
+month-7 = July
+month-8 = August
+month-9 = September
+month-10 = October
+month-11 = November
+month-12 = December
@@ -0,0 +1,214 @@
+# Mensajes en español de la interfaz web.
+
+## Navigation
+
+nav-tags = etiquetas
+nav-snapshots = capturas
+nav-sources = fuentes
+nav-challenges = verificaciones
+nav-dates = fechas
+nav-coverage = cobertura
+nav-aliases = alias
+nav-transcribe = transcribir
+nav-about = acerca de
+language-label = Idioma
+
+## Page titles and errors
+
+title-browse = Explorar
+title-tags = Etiquetas
+title-tag = Etiqueta: { $tag }
+title-types = Tipos de documento
+title-type = Tipo: { $type }
+title-coverage = Cobertura OCR
+title-coverage-source = Cobertura OCR: { $source }
+title-about = Acerca de
+title-error = Error
+title-not-found = No encontrado
+title-takedown = Solicitar retirada
+title-tombstone = Documento retirado
+title-transcribe = Transcribir
+title-config = Configuración
+error-document-not-found = Documento no encontrado.
+error-version-not-found = Esta versión del documento no existe.
+error-page-not-found = Esta página del documento no existe.
+error-no-about = No hay página de información.
+
+## Document listings
+
+filter-source = Fuente:
+filter-all-sources = Todas las fuentes
+filter-agency = Organismo:
+filter-all-agencies = Todos los organismos
+filter-record-type = Tipo de registro:
+filter-all-records = Todos los registros
+filter-citing = Cita:
+filter-any-exemption = Cualquier excepción
+filter-search = Buscar:
+filter-search-placeholder = Títulos, resúmenes, texto de adjuntos...
+filter-tags = Etiquetas:
+filter-tag-placeholder = Añadir etiqueta...
+filter-types = Tipos:
+result-count = { $count ->
+    [one] { $count } resultado
+   *[other] { $count } resultados
+}
+document-count = { $count ->
+    [one] { $count } documento
+   *[other] { $count } documentos
+}
+tag-document-count = { $count ->
+    [one] { $count } documento con la etiqueta «{ $tag }»
+   *[other] { $count } documentos con la etiqueta «{ $tag }»
+}
+of-total = de { $total }
+page-previous = Anterior
+page-next = Siguiente
+page-number = página { $page }
+bates = Bates
+attachment-in = en
+col-document = Documento
+col-source = Fuente
+col-type = Tipo
+col-size = Tamaño
+col-acquired = Obtenido
+col-category = Categoría
+col-mime-type = Tipo MIME
+col-count = Cantidad
+col-file = Archivo
+col-status = Estado
+
+## Tags and types
+
+breadcrumb-tags = Etiquetas
+breadcrumb-types = Tipos
+tags-intro = Haga clic en una etiqueta para ver todos los documentos que la tienen:
+tags-empty = No hay etiquetas. Ejecute 'foia summarize' para generar etiquetas para sus documentos.
+types-breakdown = Desglose por tipo MIME
+
+## Document page
+
+breadcrumb-browse = Explorar
+doc-also-in = También en:
+doc-versions = Versiones:
+version-lost = perdido
+version-lost-reason = Archivo perdido, texto conservado: { $reason }
+doc-lost-notice = Archivo perdido, texto conservado. El archivo original ya no está almacenado; su texto extraído se conserva a continuación.
+sheet-rows = { $count ->
+    [one] { $count } fila
+   *[other] { $count } filas
+}
+sheet-download = Descargar CSV
+sheet-truncated = Se muestran las primeras { $shown } de { $total } filas.
+pages-loading = Cargando páginas...
+pages-end = { $count ->
+    [one] Fin del documento (1 página)
+   *[other] Fin del documento ({ $count } páginas)
+}
+reocr-run = Ejecutar OCR con DeepSeek
+page-ocr-run = Ejecutar OCR en esta página
+page-ocr-running = Ejecutando OCR...
+page-ocr-failed = Falló el OCR
+page-text-edit = Corregir texto
+page-text-save = Guardar
+page-text-cancel = Cancelar
+page-text-save-failed = No se pudo guardar el texto
+page-text-corrected = Corregido
+page-text-verified = Verificado
+archive-contents = { $count ->
+    [one] Contenido del archivo comprimido (1 archivo)
+   *[other] Contenido del archivo comprimido ({ $count } archivos)
+}
+related-records = Registros relacionados ({ $count })
+doc-request-removal = Solicitar retirada
+
+## Reader
+
+reader-link = Vista de texto accesible
+reader-original = Documento original
+reader-skip = Saltar al texto
+reader-text-size = Tamaño del texto
+reader-smaller = Texto más pequeño
+reader-reset = Tamaño predeterminado
+reader-larger = Texto más grande
+reader-note = Texto extraído automáticamente del archivo original. Puede contener errores de reconocimiento.
+reader-pages = Páginas
+reader-page = Página { $page }
+reader-empty-page = Esta página no tiene texto.
+reader-no-text = Todavía no se ha extraído texto de este documento.
+reader-cite-page = Enlace a esta página
+reader-cite-selection = Copiar enlace a la selección
+reader-cite-copied = Enlace copiado.
+reader-contents = Índice
+reader-section-pages = Páginas { $first }–{ $last }
+reader-section-text = Texto
+reader-section-download-text = Descargar el texto de { $title }
+reader-section-download-pdf = Descargar las páginas de { $title } en PDF
+
+## Removal requests
+
+takedown-intro = Si un documento publicado aquí expone sus datos personales o no debería ser público por otro motivo, solicite su retirada. Los responsables del archivo revisan las solicitudes y pueden ocultar el documento mientras deciden.
+takedown-read-only = Este archivo no acepta solicitudes en línea. Contacte con sus responsables.
+takedown-name = Su nombre
+takedown-contact = Correo electrónico u otro contacto
+takedown-documents = Documentos (identificadores o enlaces, uno por línea)
+takedown-reason = ¿Por qué deberían retirarse?
+takedown-submit = Enviar solicitud
+takedown-received = Solicitud recibida. Su número de caso es el
+tombstone-suppressed = Este documento está oculto mientras se revisa una solicitud de retirada.
+tombstone-removed = Este documento se retiró tras una solicitud de retirada.
+tombstone-case = Caso n.º { $case }, { $date }
+
+## Configuration
+
+config-intro = Las configuraciones de los scrapers se guardan como una nueva versión cada vez que cambian. Compare una versión anterior con la configuración en uso, restáurela o edite la configuración de una fuente. Los rastreadores y este servidor aplican los cambios en su próxima recarga de configuración.
+config-token-required = Gestionar la configuración requiere el token de acceso.
+config-read-only = Este servidor es de solo lectura; la configuración se puede ver pero no cambiar.
+config-history = Versiones
+config-history-empty = Aún no hay versiones guardadas.
+config-version = Versión
+config-saved = Guardada
+config-sources = Fuentes
+config-current = en uso
+config-compare = Comparar
+config-rollback = Restaurar
+config-rollback-confirm = Restaurar la configuración de los scrapers de la versión
+config-rolled-back = Restaurada la versión
+config-no-diff = No hay diferencias con la configuración en uso.
+config-edit = Editar una fuente
+config-source = ID de la fuente
+config-save = Validar y guardar
+config-saved-version = Guardada como versión
+config-unchanged = No hay cambios que guardar.
+config-invalid-json = JSON no válido:
+config-failed = No se pudo contactar con el servidor. Inténtelo de nuevo.
+
+## Transcription
+
+transcribe-intro = Algunas páginas están escritas a mano o son demasiado tenues para el OCR. Escriba lo que lee en cada página, línea por línea. Cada página la transcriben dos voluntarios por separado; cuando coinciden, el texto se añade al archivo.
+transcribe-read-only = Este archivo no acepta transcripciones en línea.
+transcribe-key = Su clave de voluntario
+transcribe-name = Su nombre
+transcribe-start = Empezar a transcribir
+transcribe-submit = Enviar y continuar
+transcribe-none = No hay páginas esperándole. ¡Gracias!
+transcribe-saved = Guardado.
+transcribe-failed = No se pudo contactar con el archivo. Inténtelo de nuevo.
+transcribe-page = Página
+
+## Dates
+
+date = { $day } de { $month } de { $year }
+datetime = { $date }, { $time }
+month-1 = enero
+month-2 = febrero
+month-3 = marzo
+month-4 = abril
+month-5 = mayo
+month-6 = junio
+month-7 = julio
+month-8 = agosto
+month-9 = septiembre
+month-10 = octubre
+month-11 = noviembre
+month-12 = diciembre
@@ -0,0 +1,214 @@
+# Messages en français de l'interface web.
+
+## Navigation
+
+nav-tags = étiquettes
+nav-snapshots = captures
+nav-sources = sources
+nav-challenges = vérifications
+nav-dates = dates
+nav-coverage = couverture
+nav-aliases = alias
+nav-transcribe = transcrire
+nav-about = à propos
+language-label = Langue
+
+## Page titles and errors
+
+title-browse = Parcourir
+title-tags = Étiquettes
+title-tag = Étiquette : { $tag }
+title-types = Types de documents
+title-type = Type : { $type }
+title-coverage = Couverture OCR
+title-coverage-source = Couverture OCR : { $source }
+title-about = À propos
+title-error = Erreur
+title-not-found = Introuvable
+title-takedown = Demande de retrait
+title-tombstone = Document retiré
+title-transcribe = Transcrire
+title-config = Configuration
+error-document-not-found = Document introuvable.
+error-version-not-found = Cette version du document n'existe pas.
+error-page-not-found = Cette page du document n'existe pas.
+error-no-about = Aucune page « à propos ».
+
+## Document listings
+
+filter-source = Source :
+filter-all-sources = Toutes les sources
+filter-agency = Organisme :
+filter-all-agencies = Tous les organismes
+filter-record-type = Type de dossier :
+filter-all-records = Tous les dossiers
+filter-citing = Invoquant :
+filter-any-exemption = Toute exemption
+filter-search = Rechercher :
+filter-search-placeholder = Titres, résumés, texte des pièces jointes...
+filter-tags = Étiquettes :
+filter-tag-placeholder = Ajouter une étiquette...
+filter-types = Types :
+result-count = { $count ->
+    [one] { $count } résultat
+   *[other] { $count } résultats
+}
+document-count = { $count ->
+    [one] { $count } document
+   *[other] { $count } documents
+}
+tag-document-count = { $count ->
+    [one] { $count } document avec l'étiquette « { $tag } »
+   *[other] { $count } documents avec l'étiquette « { $tag } »
+}
+of-total = sur { $total }
+page-previous = Précédent
+page-next = Suivant
+page-number = page { $page }
+bates = Bates
+attachment-in = dans
+col-document = Document
+col-source = Source
+col-type = Type
+col-size = Taille
+col-acquired = Obtenu le
+col-category = Catégorie
+col-mime-type = Type MIME
+col-count = Nombre
+col-file = Fichier
+col-status = État
+
+## Tags and types
+
+breadcrumb-tags = Étiquettes
+breadcrumb-types = Types
+tags-intro = Cliquez sur une étiquette pour voir tous les documents qui la portent :
+tags-empty = Aucune étiquette. Lancez 'foia summarize' pour étiqueter vos documents.
+types-breakdown = Répartition par type MIME
+
+## Document page
+
+breadcrumb-browse = Parcourir
+doc-also-in = Aussi dans :
+doc-versions = Versions :
+version-lost = perdu
+version-lost-reason = Fichier perdu, texte conservé : { $reason }
+doc-lost-notice = Fichier perdu, texte conservé. Le fichier original n'est plus stocké ; son texte extrait est conservé ci-dessous.
+sheet-rows = { $count ->
+    [one] { $count } ligne
+   *[other] { $count } lignes
+}
+sheet-download = Télécharger en CSV
+sheet-truncated = Affichage des { $shown } premières lignes sur { $total }.
+pages-loading = Chargement des pages...
+pages-end = { $count ->
+    [one] Fin du document (1 page)
+   *[other] Fin du document ({ $count } pages)
+}
+reocr-run = Lancer l'OCR DeepSeek
+page-ocr-run = Lancer l'OCR de cette page
+page-ocr-running = OCR en cours...
+page-ocr-failed = Échec de l'OCR
+page-text-edit = Corriger le texte
+page-text-save = Enregistrer
+page-text-cancel = Annuler
+page-text-save-failed = Impossible d'enregistrer le texte
+page-text-corrected = Corrigé
+page-text-verified = Vérifié
+archive-contents = { $count ->
+    [one] Contenu de l'archive (1 fichier)
+   *[other] Contenu de l'archive ({ $count } fichiers)
+}
+related-records = Dossiers liés ({ $count })
+doc-request-removal = Demander le retrait
+
+## Reader
+
+reader-link = Version texte accessible
+reader-original = Document original
+reader-skip = Aller au texte
+reader-text-size = Taille du texte
+reader-smaller = Texte plus petit
+reader-reset = Taille par défaut
+reader-larger = Texte plus grand
+reader-note = Texte extrait automatiquement du fichier original. Il peut contenir des erreurs de reconnaissance.
+reader-pages = Pages
+reader-page = Page { $page }
+reader-empty-page = Aucun texte sur cette page.
+reader-no-text = Aucun texte n'a encore été extrait de ce document.
+reader-cite-page = Lien vers cette page
+reader-cite-selection = Copier le lien vers la sélection
+reader-cite-copied = Lien copié.
+reader-contents = Sommaire
+reader-section-pages = Pages { $first } à { $last }
+reader-section-text = Texte
+reader-section-download-text = Télécharger le texte de { $title }
+reader-section-download-pdf = Télécharger les pages de { $title } en PDF
+
+## Removal requests
+
+takedown-intro = Si un document publié ici expose vos données personnelles ou ne devrait pas être public pour une autre raison, demandez son retrait. Les demandes sont examinées par les responsables de l'archive, qui peuvent masquer le document le temps de décider.
+takedown-read-only = Cette archive n'accepte pas de demandes en ligne. Contactez plutôt ses responsables.
+takedown-name = Votre nom
+takedown-contact = Adresse e-mail ou autre contact
+takedown-documents = Documents (identifiants ou liens, un par ligne)
+takedown-reason = Pourquoi faut-il les retirer ?
+takedown-submit = Envoyer la demande
+takedown-received = Demande reçue. Votre numéro de dossier est le
+tombstone-suppressed = Ce document est masqué pendant l'examen d'une demande de retrait.
+tombstone-removed = Ce document a été retiré à la suite d'une demande de retrait.
+tombstone-case = Dossier n° { $case }, { $date }
+
+## Configuration
+
+config-intro = Les configurations des scrapers sont enregistrées comme nouvelle version à chaque modification. Comparez une version antérieure à la configuration en service, restaurez-la ou modifiez la configuration d'une source. Les robots d'exploration et ce serveur appliquent les changements à leur prochain rechargement de la configuration.
+config-token-required = Gérer la configuration nécessite le jeton d'accès.
+config-read-only = Ce serveur est en lecture seule ; la configuration peut être consultée mais pas modifiée.
+config-history = Versions
+config-history-empty = Aucune version enregistrée pour l'instant.
+config-version = Version
+config-saved = Enregistrée
+config-sources = Sources
+config-current = en service
+config-compare = Comparer
+config-rollback = Restaurer
+config-rollback-confirm = Restaurer la configuration des scrapers de la version
+config-rolled-back = Version restaurée :
+config-no-diff = Aucune différence avec la configuration en service.
+config-edit = Modifier une source
+config-source = ID de la source
+config-save = Valider et enregistrer
+config-saved-version = Enregistrée comme version
+config-unchanged = Aucune modification à enregistrer.
+config-invalid-json = JSON invalide :
+config-failed = Impossible de joindre le serveur. Réessayez.
+
+## Transcription
+
+transcribe-intro = Certaines pages sont manuscrites ou trop pâles pour l'OCR. Tapez ce que vous lisez sur chaque page, ligne par ligne. Chaque page est transcrite par deux bénévoles séparément ; quand leurs textes concordent, il est ajouté aux archives.
+transcribe-read-only = Ces archives n'acceptent pas de transcriptions en ligne.
+transcribe-key = Votre clé de bénévole
+transcribe-name = Votre nom
+transcribe-start = Commencer à transcrire
+transcribe-submit = Envoyer et continuer
+transcribe-none = Aucune page ne vous attend. Merci !
+transcribe-saved = Enregistré.
+transcribe-failed = Impossible de joindre les archives. Réessayez.
+transcribe-page = Page
+
+## Dates
+
+date = { $day } { $month } { $year }
+datetime = { $date } à { $time }
+month-1 = janvier
+month-2 = février
+month-3 = mars
+month-4 = avril
+month-5 = mai
+month-6 = juin
+month-7 = juillet
+month-8 = août
+month-9 = septembre
+month-10 = octobre
+month-11 = novembre
+month-12 = décembre
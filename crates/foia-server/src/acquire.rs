@@ -0,0 +1,296 @@
+//! Background jobs for ad-hoc URL acquisition.
+//!
+//! Each submitted URL runs as a task that fetches, stores, and extracts
+//! text from it; browser captures skip the fetch. Job state is kept in
+//! memory for polling; the documents themselves are in the database, so a
+//! restart only loses job history.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tokio::sync::{RwLock, Semaphore};
+use utoipa::ToSchema;
+
+use foia::services::acquire::{self, AcquireOutcome, AcquireRequest, CaptureRequest};
+use foia_analysis::services::AnalysisService;
+
+use super::AppState;
+
+/// Acquisitions running at once; further jobs wait in `queued`.
+const MAX_CONCURRENT: usize = 4;
+
+/// Finished jobs are forgotten after this long.
+const FINISHED_JOB_TTL_HOURS: i64 = 24;
+
+/// Upper bound on remembered jobs; the oldest finished ones go first.
+const MAX_JOBS: usize = 1000;
+
+/// Stage of an acquisition job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AcquireStatus {
+    Queued,
+    Fetching,
+    Processing,
+    Completed,
+    Failed,
+}
+
+/// An ad-hoc acquisition job, as returned by the API.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AcquireJob {
+    pub id: String,
+    pub url: String,
+    pub source_id: String,
+    pub tags: Vec<String>,
+    pub status: AcquireStatus,
+    pub document_id: Option<String>,
+    /// False when the URL was already archived.
+    pub created: Option<bool>,
+    pub mime_type: Option<String>,
+    pub bytes: Option<u64>,
+    /// Pages with extracted text.
+    pub pages: Option<usize>,
+    /// Why the job failed, or why text extraction did not run.
+    pub error: Option<String>,
+    pub submitted_at: String,
+    pub finished_at: Option<String>,
+    #[serde(skip)]
+    finished: Option<DateTime<Utc>>,
+}
+
+/// What a job stores.
+enum Work {
+    Fetch(AcquireRequest),
+    Capture(CaptureRequest),
+}
+
+/// In-memory registry of acquisition jobs.
+pub struct AcquireJobs {
+    jobs: RwLock<HashMap<String, AcquireJob>>,
+    slots: Semaphore,
+}
+
+impl AcquireJobs {
+    pub fn new() -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            slots: Semaphore::new(MAX_CONCURRENT),
+        }
+    }
+
+    /// Queue `request` and start working on it in the background.
+    pub async fn submit(self: &Arc<Self>, state: AppState, request: AcquireRequest) -> AcquireJob {
+        let job = AcquireJob::queued(&request.url, request.source_id(), &request.tags);
+        self.spawn(job, state, Work::Fetch(request)).await
+    }
+
+    /// Queue a browser capture for storage and text extraction.
+    pub async fn submit_capture(
+        self: &Arc<Self>,
+        state: AppState,
+        capture: CaptureRequest,
+    ) -> AcquireJob {
+        let job = AcquireJob::queued(&capture.url, capture.source_id(), &capture.tags);
+        self.spawn(job, state, Work::Capture(capture)).await
+    }
+
+    async fn spawn(self: &Arc<Self>, job: AcquireJob, state: AppState, work: Work) -> AcquireJob {
+        {
+            let mut jobs = self.jobs.write().await;
+            prune(&mut jobs, Utc::now());
+            jobs.insert(job.id.clone(), job.clone());
+        }
+
+        let jobs = self.clone();
+        let id = job.id.clone();
+        tokio::spawn(async move {
+            let Ok(_permit) = jobs.slots.acquire().await else {
+                return;
+            };
+            jobs.run(&id, &state, &work).await;
+        });
+
+        job
+    }
+
+    pub async fn get(&self, id: &str) -> Option<AcquireJob> {
+        self.jobs.read().await.get(id).cloned()
+    }
+
+    async fn update(&self, id: &str, f: impl FnOnce(&mut AcquireJob)) {
+        if let Some(job) = self.jobs.write().await.get_mut(id) {
+            f(job);
+        }
+    }
+
+    async fn finish(&self, id: &str, status: AcquireStatus, error: Option<String>) {
+        let now = Utc::now();
+        self.update(id, |job| {
+            job.status = status;
+            job.error = error;
+            job.finished = Some(now);
+            job.finished_at = Some(now.to_rfc3339());
+        })
+        .await;
+    }
+
+    async fn run(&self, id: &str, state: &AppState, work: &Work) {
+        let result: anyhow::Result<AcquireOutcome> = match work {
+            Work::Fetch(request) => {
+                self.update(id, |job| job.status = AcquireStatus::Fetching)
+                    .await;
+                let config = state.config.read().await.clone();
+                acquire::fetch_and_store(
+                    &config,
+                    &state.doc_repo,
+                    &state.source_repo,
+                    &state.documents_dir,
+                    request,
+                )
+                .await
+            }
+            Work::Capture(capture) => {
+                acquire::store_capture(
+                    &state.doc_repo,
+                    &state.source_repo,
+                    &state.documents_dir,
+                    capture,
+                )
+                .await
+            }
+        };
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                tracing::warn!("Acquiring job {} failed: {}", id, e);
+                self.finish(id, AcquireStatus::Failed, Some(e.to_string()))
+                    .await;
+                return;
+            }
+        };
+        state.stats_cache.clear();
+        state
+            .read_cache
+            .invalidate_document(&outcome.document_id)
+            .await;
+        state.read_cache.invalidate_sources();
+
+        self.update(id, |job| {
+            job.status = AcquireStatus::Processing;
+            job.document_id = Some(outcome.document_id.clone());
+            job.created = Some(outcome.created);
+            job.mime_type = Some(outcome.mime_type.clone());
+            job.bytes = Some(outcome.bytes);
+        })
+        .await;
+
+        // Extraction problems don't undo the download; report them on the job
+        let analysis = AnalysisService::new((*state.doc_repo).clone(), state.documents_dir.clone());
+        let error = match analysis.extract_text(&outcome.document_id).await {
+            Ok(pages) => {
+                self.update(id, |job| job.pages = Some(pages)).await;
+                None
+            }
+            Err(e) if e.to_string().contains("Unsupported file type") => None,
+            Err(e) => Some(format!("text extraction failed: {}", e)),
+        };
+        self.finish(id, AcquireStatus::Completed, error).await;
+    }
+}
+
+impl AcquireJob {
+    fn queued(url: &str, source_id: &str, tags: &[String]) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: url.trim().to_string(),
+            source_id: source_id.to_string(),
+            tags: tags.to_vec(),
+            status: AcquireStatus::Queued,
+            document_id: None,
+            created: None,
+            mime_type: None,
+            bytes: None,
+            pages: None,
+            error: None,
+            submitted_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+            finished: None,
+        }
+    }
+}
+
+impl Default for AcquireJobs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drop expired finished jobs, then the oldest finished ones over the cap.
+fn prune(jobs: &mut HashMap<String, AcquireJob>, now: DateTime<Utc>) {
+    let cutoff = now - Duration::hours(FINISHED_JOB_TTL_HOURS);
+    jobs.retain(|_, job| !matches!(job.finished, Some(at) if at <= cutoff));
+
+    if jobs.len() >= MAX_JOBS {
+        let mut finished: Vec<(DateTime<Utc>, String)> = jobs
+            .values()
+            .filter_map(|job| job.finished.map(|at| (at, job.id.clone())))
+            .collect();
+        finished.sort();
+        let excess = jobs.len() + 1 - MAX_JOBS;
+        for (_, id) in finished.into_iter().take(excess) {
+            jobs.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, finished: Option<DateTime<Utc>>) -> AcquireJob {
+        AcquireJob {
+            id: id.to_string(),
+            url: "https://agency.gov/a.pdf".to_string(),
+            source_id: "adhoc".to_string(),
+            tags: Vec::new(),
+            status: if finished.is_some() {
+                AcquireStatus::Completed
+            } else {
+                AcquireStatus::Fetching
+            },
+            document_id: None,
+            created: None,
+            mime_type: None,
+            bytes: None,
+            pages: None,
+            error: None,
+            submitted_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+            finished,
+        }
+    }
+
+    #[test]
+    fn test_prune_drops_expired_and_keeps_running() {
+        let now = Utc::now();
+        let mut jobs = HashMap::new();
+        for j in [
+            job(
+                "old",
+                Some(now - Duration::hours(FINISHED_JOB_TTL_HOURS + 1)),
+            ),
+            job("recent", Some(now - Duration::minutes(5))),
+            job("running", None),
+        ] {
+            jobs.insert(j.id.clone(), j);
+        }
+
+        prune(&mut jobs, now);
+        assert!(!jobs.contains_key("old"));
+        assert!(jobs.contains_key("recent"));
+        assert!(jobs.contains_key("running"));
+    }
+}
@@ -1,17 +1,38 @@
-//! In-memory cache for expensive stats queries.
+//! In-memory caches for dashboard stats and hot repository reads.
 //!
-//! Provides TTL-based caching to avoid recomputing stats on every page load.
-//! Stats change infrequently (only when documents are added/modified),
-//! so a 5-minute TTL is reasonable.
+//! The counts themselves come from trigger-maintained aggregate tables
+//! (`source_status_counts`, `tag_counts`, `file_categories.doc_count`), so a
+//! miss is a small indexed read rather than a table scan. The cache only
+//! saves those round trips on busy pages, which allows a short TTL.
+//!
+//! [`ReadCache`] holds objects nearly every page view reads again: document
+//! summaries, the source list and prev/next navigation. The server drops
+//! entries when it writes them; the TTL bounds how long changes made by
+//! other processes, such as a crawler sharing the database, take to show.
 
 use std::collections::HashMap;
 use std::sync::RwLock;
 use std::time::{Duration, Instant};
 
-/// Default TTL for cached stats (5 minutes).
-/// Stats change infrequently (only when documents are added/modified),
-/// so a longer TTL significantly improves performance.
-const DEFAULT_TTL: Duration = Duration::from_secs(300);
+use moka::future::Cache;
+
+use foia::models::{Document, Source};
+use foia::repository::diesel_document::Projection;
+use foia::repository::{
+    DieselDocumentRepository, DieselError, DieselSourceRepository, DocumentNavigation,
+};
+
+/// Default TTL for cached stats (30 seconds).
+const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+/// TTL for cached repository reads.
+const READ_TTL: Duration = Duration::from_secs(120);
+
+/// Document summaries kept at once; the least used go first.
+const MAX_DOCUMENTS: u64 = 10_000;
+
+/// Navigation results kept at once.
+const MAX_NAVIGATION: u64 = 10_000;
 
 /// A cached value with expiration time.
 struct CacheEntry<T> {
@@ -108,6 +129,19 @@ impl StatsCache {
             *guard = Some(CacheEntry::new(stats, self.ttl));
         }
     }
+
+    /// Drop all cached entries.
+    pub fn clear(&self) {
+        if let Ok(mut guard) = self.all_tags.write() {
+            *guard = None;
+        }
+        if let Ok(mut guard) = self.source_counts.write() {
+            *guard = None;
+        }
+        if let Ok(mut guard) = self.category_stats.write() {
+            *guard = None;
+        }
+    }
 }
 
 impl Default for StatsCache {
@@ -115,3 +149,98 @@ impl Default for StatsCache {
         Self::new()
     }
 }
+
+/// Read-through cache of hot repository reads.
+///
+/// Misses are not cached, so a document shows up as soon as it is stored.
+pub struct ReadCache {
+    /// Documents without `extracted_text`, by ID.
+    documents: Cache<String, Document>,
+    sources: Cache<(), Vec<Source>>,
+    /// Navigation by (document ID, source ID).
+    navigation: Cache<(String, String), DocumentNavigation>,
+}
+
+impl ReadCache {
+    pub fn new() -> Self {
+        Self {
+            documents: Cache::builder()
+                .max_capacity(MAX_DOCUMENTS)
+                .time_to_live(READ_TTL)
+                .build(),
+            sources: Cache::builder().time_to_live(READ_TTL).build(),
+            navigation: Cache::builder()
+                .max_capacity(MAX_NAVIGATION)
+                .time_to_live(READ_TTL)
+                .build(),
+        }
+    }
+
+    /// A document's metadata, as `get_projected(id, Projection::Metadata)`.
+    pub async fn document(
+        &self,
+        repo: &DieselDocumentRepository,
+        id: &str,
+    ) -> Result<Option<Document>, DieselError> {
+        if let Some(doc) = self.documents.get(id).await {
+            return Ok(Some(doc));
+        }
+        let doc = repo.get_projected(id, Projection::Metadata).await?;
+        if let Some(doc) = &doc {
+            self.documents.insert(id.to_string(), doc.clone()).await;
+        }
+        Ok(doc)
+    }
+
+    /// All sources, as `DieselSourceRepository::get_all`.
+    pub async fn sources(&self, repo: &DieselSourceRepository) -> Result<Vec<Source>, DieselError> {
+        if let Some(sources) = self.sources.get(&()).await {
+            return Ok(sources);
+        }
+        let sources = repo.get_all().await?;
+        self.sources.insert((), sources.clone()).await;
+        Ok(sources)
+    }
+
+    /// Previous and next documents of a source around `document_id`.
+    pub async fn navigation(
+        &self,
+        repo: &DieselDocumentRepository,
+        document_id: &str,
+        source_id: &str,
+    ) -> Result<DocumentNavigation, DieselError> {
+        let key = (document_id.to_string(), source_id.to_string());
+        if let Some(nav) = self.navigation.get(&key).await {
+            return Ok(nav);
+        }
+        let nav = repo.get_document_navigation(document_id, source_id).await?;
+        self.navigation.insert(key, nav.clone()).await;
+        Ok(nav)
+    }
+
+    /// Forget a document after writing it. Navigation around it is dropped
+    /// wholesale, since its neighbours' prev/next links and every position
+    /// in its source may have changed.
+    pub async fn invalidate_document(&self, id: &str) {
+        self.documents.invalidate(id).await;
+        self.navigation.invalidate_all();
+    }
+
+    /// Forget the source list after adding or changing a source.
+    pub fn invalidate_sources(&self) {
+        self.sources.invalidate_all();
+    }
+
+    /// Drop everything, e.g. after a sync or a replica refresh.
+    pub fn clear(&self) {
+        self.documents.invalidate_all();
+        self.sources.invalidate_all();
+        self.navigation.invalidate_all();
+    }
+}
+
+impl Default for ReadCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,346 @@
+//! GraphQL schema over the document repository.
+//!
+//! Complements the REST API for clients that want nested data in one
+//! round trip: documents with their versions, pages, entities and related
+//! documents. Read-only. Queries are bounded in depth and complexity, and
+//! every document passes the same access checks as the REST API.
+
+use std::sync::LazyLock;
+
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Result, Schema, SimpleObject,
+};
+
+use foia::models::{Document, DocumentPage, DocumentVersion};
+use foia::repository::diesel_document::{BrowseParams, Projection};
+
+use super::handlers::Viewer;
+use super::AppState;
+
+/// Deepest nesting a query may have.
+const MAX_DEPTH: usize = 10;
+
+/// Highest complexity (roughly, fields resolved) a query may have.
+const MAX_COMPLEXITY: usize = 1000;
+
+/// Most documents a list returns.
+const MAX_PAGE_SIZE: i32 = 100;
+
+pub type FoiaSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+static SCHEMA: LazyLock<FoiaSchema> = LazyLock::new(|| {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// The schema. Requests carry the [`AppState`] and [`Viewer`] as data.
+pub fn schema() -> &'static FoiaSchema {
+    &SCHEMA
+}
+
+fn request_data<'a>(ctx: &Context<'a>) -> Result<(&'a AppState, &'a Viewer)> {
+    Ok((ctx.data::<AppState>()?, ctx.data::<Viewer>()?))
+}
+
+/// A document the viewer may see, by ID.
+async fn visible_document(ctx: &Context<'_>, id: &str) -> Result<Option<DocumentNode>> {
+    let (state, viewer) = request_data(ctx)?;
+    let doc = state
+        .doc_repo
+        .get_projected(id, Projection::Metadata)
+        .await?;
+    Ok(doc
+        .filter(|d| viewer.allows(&d.id, &d.source_id))
+        .map(DocumentNode))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A document by ID.
+    async fn document(&self, ctx: &Context<'_>, id: String) -> Result<Option<DocumentNode>> {
+        visible_document(ctx, &id).await
+    }
+
+    /// Documents, most recently updated first.
+    #[graphql(complexity = "limit.clamp(1, MAX_PAGE_SIZE) as usize * child_complexity")]
+    async fn documents(
+        &self,
+        ctx: &Context<'_>,
+        source: Option<String>,
+        tag: Option<String>,
+        search: Option<String>,
+        #[graphql(default = 20)] limit: i32,
+        #[graphql(default = 0)] offset: i32,
+    ) -> Result<Vec<DocumentNode>> {
+        let (state, viewer) = request_data(ctx)?;
+        let tags: Vec<String> = tag.into_iter().collect();
+        let docs = state
+            .doc_repo
+            .browse(BrowseParams {
+                source_id: source.as_deref(),
+                tags: &tags,
+                search_query: search.as_deref(),
+                limit: limit.clamp(1, MAX_PAGE_SIZE) as u32,
+                offset: offset.max(0) as u32,
+                projection: Projection::Metadata,
+                access: viewer.filter(),
+                ..Default::default()
+            })
+            .await?;
+        Ok(docs.into_iter().map(DocumentNode).collect())
+    }
+}
+
+/// A document with its versions.
+pub struct DocumentNode(Document);
+
+#[Object(name = "Document")]
+impl DocumentNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn source_id(&self) -> &str {
+        &self.0.source_id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn source_url(&self) -> &str {
+        &self.0.source_url
+    }
+
+    async fn synopsis(&self) -> Option<&str> {
+        self.0.synopsis.as_deref()
+    }
+
+    async fn tags(&self) -> &[String] {
+        &self.0.tags
+    }
+
+    async fn status(&self) -> &str {
+        self.0.status.as_str()
+    }
+
+    /// Source-specific metadata, as a JSON string.
+    async fn metadata(&self) -> String {
+        self.0.metadata.to_string()
+    }
+
+    /// RFC 3339 timestamp.
+    async fn created_at(&self) -> String {
+        self.0.created_at.to_rfc3339()
+    }
+
+    /// RFC 3339 timestamp.
+    async fn updated_at(&self) -> String {
+        self.0.updated_at.to_rfc3339()
+    }
+
+    /// Versions, newest first.
+    async fn versions(&self) -> Vec<VersionNode> {
+        self.0
+            .versions
+            .iter()
+            .map(|v| VersionNode::new(&self.0.id, v))
+            .collect()
+    }
+
+    async fn current_version(&self) -> Option<VersionNode> {
+        self.0
+            .current_version()
+            .map(|v| VersionNode::new(&self.0.id, v))
+    }
+
+    /// Named entities found in the text.
+    async fn entities(
+        &self,
+        ctx: &Context<'_>,
+        entity_type: Option<String>,
+    ) -> Result<Vec<EntityNode>> {
+        let (state, _) = request_data(ctx)?;
+        let entities = state.doc_repo.get_document_entities(&self.0.id).await?;
+        Ok(entities
+            .into_iter()
+            .filter(|e| entity_type.as_ref().is_none_or(|t| *t == e.entity_type))
+            .map(|e| EntityNode {
+                entity_type: e.entity_type,
+                text: e.entity_text,
+                latitude: e.latitude,
+                longitude: e.longitude,
+            })
+            .collect())
+    }
+
+    /// Links to other documents (attachments, exhibits, ...), leaving out
+    /// documents the viewer may not see.
+    async fn relations(&self, ctx: &Context<'_>) -> Result<Vec<RelationNode>> {
+        let (state, viewer) = request_data(ctx)?;
+        let related = state.doc_repo.get_relations(&self.0.id).await?;
+        let mut nodes = Vec::with_capacity(related.len());
+        for r in related {
+            let visible = match viewer.filter() {
+                None => true,
+                Some(filter) => state
+                    .doc_repo
+                    .document_owner(&r.document_id)
+                    .await?
+                    .is_some_and(|(doc, source)| filter.allows(&doc, &source)),
+            };
+            if visible {
+                nodes.push(RelationNode {
+                    relation_type: r.relation_type.as_str().to_string(),
+                    outgoing: r.outgoing,
+                    document_id: r.document_id,
+                    title: r.title,
+                    note: r.note,
+                });
+            }
+        }
+        Ok(nodes)
+    }
+}
+
+/// One stored version of a document.
+#[derive(SimpleObject)]
+#[graphql(name = "Version", complex)]
+pub struct VersionNode {
+    id: i64,
+    content_hash: String,
+    file_size: u64,
+    mime_type: String,
+    /// RFC 3339 timestamp.
+    acquired_at: String,
+    original_filename: Option<String>,
+    page_count: Option<u32>,
+    #[graphql(skip)]
+    document_id: String,
+}
+
+impl VersionNode {
+    fn new(document_id: &str, v: &DocumentVersion) -> Self {
+        Self {
+            id: v.id,
+            content_hash: v.content_hash.clone(),
+            file_size: v.file_size,
+            mime_type: v.mime_type.clone(),
+            acquired_at: v.acquired_at.to_rfc3339(),
+            original_filename: v.original_filename.clone(),
+            page_count: v.page_count,
+            document_id: document_id.to_string(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl VersionNode {
+    /// Pages with their text, in order.
+    async fn pages(&self, ctx: &Context<'_>) -> Result<Vec<PageNode>> {
+        let (state, _) = request_data(ctx)?;
+        let pages = state
+            .doc_repo
+            .get_pages(&self.document_id, self.id as i32)
+            .await?;
+        Ok(pages.into_iter().map(PageNode::from).collect())
+    }
+}
+
+/// A page of a version.
+#[derive(SimpleObject)]
+#[graphql(name = "Page")]
+pub struct PageNode {
+    page_number: u32,
+    /// Best available text: the chosen text, else OCR, else the PDF text
+    /// layer.
+    text: Option<String>,
+    pdf_text: Option<String>,
+    ocr_text: Option<String>,
+    ocr_status: String,
+}
+
+impl From<DocumentPage> for PageNode {
+    fn from(page: DocumentPage) -> Self {
+        let text = [&page.final_text, &page.ocr_text, &page.pdf_text]
+            .into_iter()
+            .flatten()
+            .find(|t| !t.trim().is_empty())
+            .cloned();
+        Self {
+            page_number: page.page_number,
+            text,
+            ocr_status: page.ocr_status.as_str().to_string(),
+            pdf_text: page.pdf_text,
+            ocr_text: page.ocr_text,
+        }
+    }
+}
+
+/// A named entity (person, organization, location, ...).
+#[derive(SimpleObject)]
+#[graphql(name = "Entity")]
+pub struct EntityNode {
+    entity_type: String,
+    text: String,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+/// A link from one document to another.
+#[derive(SimpleObject)]
+#[graphql(name = "Relation", complex)]
+pub struct RelationNode {
+    /// attachment-of, exhibit-to, supersedes, references, or part-of
+    relation_type: String,
+    /// Whether the link starts at this document.
+    outgoing: bool,
+    document_id: String,
+    title: String,
+    note: Option<String>,
+}
+
+#[ComplexObject]
+impl RelationNode {
+    /// The document on the other end.
+    async fn document(&self, ctx: &Context<'_>) -> Result<Option<DocumentNode>> {
+        visible_document(ctx, &self.document_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_types() {
+        let sdl = schema().sdl();
+        for ty in [
+            "type Document",
+            "type Version",
+            "type Page",
+            "type Entity",
+            "type Relation",
+        ] {
+            assert!(sdl.contains(ty), "schema lacks {}", ty);
+        }
+        assert!(!sdl.contains("type Mutation"));
+    }
+
+    #[tokio::test]
+    async fn test_depth_limit() {
+        // Rejected during validation, before any resolver needs data
+        let mut query = "id".to_string();
+        for _ in 0..MAX_DEPTH {
+            query = format!("relations {{ document {{ {} }} }}", query);
+        }
+        let query = format!("{{ document(id: \"doc-1\") {{ {} }} }}", query);
+        let response = schema().execute(query.as_str()).await;
+        assert!(!response.errors.is_empty());
+        assert!(response.errors[0].message.contains("nested too deep"));
+    }
+}
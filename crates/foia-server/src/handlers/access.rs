@@ -0,0 +1,257 @@
+//! Withholding internal and embargoed documents from the public.
+//!
+//! Requests carrying the configured `access.token` (bearer header or
+//! `foia_access` cookie) see everything. Everyone else gets a 404 for
+//! restricted documents and their files, and listings leave them out.
+//! Documents withheld by a takedown case get a 451 tombstone instead.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+
+use super::super::i18n::I18n;
+use super::super::AppState;
+use super::api_keys::ApiClient;
+use super::helpers::{constant_time_eq, cookie, internal_error, not_found};
+use super::takedowns::tombstone_response;
+use foia::repository::DieselError;
+use foia::services::access::AccessFilter;
+use foia::services::takedowns;
+
+/// Cookie that carries the access token for browsers.
+pub(super) const ACCESS_COOKIE: &str = "foia_access";
+
+/// What the current request may see. Set on every request by
+/// [`access_guard`].
+#[derive(Debug, Clone, Default)]
+pub struct Viewer {
+    /// Documents to withhold; `None` when nothing is.
+    restricted: Option<Arc<AccessFilter>>,
+    /// Whether the request has the access token.
+    internal: bool,
+}
+
+impl Viewer {
+    /// Filter for queries, `None` when the viewer may see everything.
+    pub fn filter(&self) -> Option<&AccessFilter> {
+        self.restricted.as_deref()
+    }
+
+    /// Owned filter, for streams that outlive the request.
+    pub fn owned_filter(&self) -> Option<AccessFilter> {
+        self.restricted.as_deref().cloned()
+    }
+
+    /// Whether the viewer is an operator presenting the access token.
+    pub fn is_internal(&self) -> bool {
+        self.internal
+    }
+
+    /// Whether the viewer may see a document.
+    pub fn allows(&self, doc_id: &str, source_id: &str) -> bool {
+        self.filter().is_none_or(|f| f.allows(doc_id, source_id))
+    }
+}
+
+/// What a request path addresses.
+enum Target {
+    /// A document, or an attachment, by ID.
+    Document(String),
+    /// A stored file, by content hash prefix.
+    Content(String),
+    /// A source's status or timeline.
+    Source(String),
+}
+
+/// The document or stored file a path refers to, if any.
+fn target(path: &str) -> Option<Target> {
+    let decode = |s: &str| {
+        urlencoding::decode(s)
+            .map(|c| c.into_owned())
+            .unwrap_or_else(|_| s.to_string())
+    };
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["documents", id, ..] | ["doc", id, ..] | ["api", "documents", id, ..]
+            if *id != "reocr" =>
+        {
+            Some(Target::Document(decode(id)))
+        }
+        ["api", "annotations", id] if *id != "stats" => Some(Target::Document(decode(id))),
+        ["api", "versions", "hash", hash] => Some(Target::Content(decode(hash))),
+        ["api", "status" | "timeline", id] => Some(Target::Source(decode(id))),
+        ["files", .., name] => file_hash_prefix(name).map(Target::Content),
+        _ => None,
+    }
+}
+
+/// Hash prefix of a stored file name: `{hash8}.{ext}` or
+/// `{name}-{hash8}.{ext}`.
+fn file_hash_prefix(name: &str) -> Option<String> {
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    let prefix = stem.rsplit('-').next()?;
+    (prefix.len() == 8 && prefix.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| prefix.to_ascii_lowercase())
+}
+
+/// Whether the request presents the access token.
+pub(super) fn has_token(headers: &HeaderMap, expected: &str) -> bool {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let cookie = cookie(headers, ACCESS_COOKIE);
+    [bearer, cookie]
+        .into_iter()
+        .flatten()
+        .any(|t| constant_time_eq(t.as_bytes(), expected.as_bytes()))
+}
+
+/// Whether the document or file a path addresses is withheld.
+async fn is_withheld(
+    state: &AppState,
+    filter: &AccessFilter,
+    target: Target,
+) -> Result<bool, DieselError> {
+    match target {
+        Target::Document(id) => Ok(state
+            .doc_repo
+            .document_owner(&id)
+            .await?
+            .is_some_and(|(doc, source)| !filter.allows(&doc, &source))),
+        Target::Content(hash) => {
+            // Shared content stays available through a visible copy
+            let owners = state.doc_repo.documents_with_hash_prefix(&hash).await?;
+            Ok(!owners.is_empty() && owners.iter().all(|(doc, src)| !filter.allows(doc, src)))
+        }
+        Target::Source(id) => Ok(!filter.shows_source(&id)),
+    }
+}
+
+/// Response for a withheld document: a tombstone when a takedown case
+/// withholds it, otherwise a plain 404 that doesn't reveal it exists.
+async fn withheld(state: &AppState, req: &Request, document: Option<&str>) -> Response {
+    if let Some(id) = document {
+        match takedowns::tombstone(&state.doc_repo, id).await {
+            Ok(Some(tombstone)) => {
+                let theme = state.theme().await;
+                return tombstone_response(
+                    &tombstone,
+                    req.uri().path(),
+                    req.extensions().get::<I18n>(),
+                    &theme,
+                );
+            }
+            Ok(None) => {}
+            Err(e) => return internal_error(e).into_response(),
+        }
+    }
+    not_found("Document not found").into_response()
+}
+
+/// Decide what the request may see, and refuse restricted documents and
+/// files outright.
+pub async fn access_guard(State(state): State<AppState>, mut req: Request, next: Next) -> Response {
+    let token = state.config.read().await.access.token.clone();
+    let internal = token
+        .as_deref()
+        .and_then(foia::secrets::resolve_or_warn)
+        .is_some_and(|t| !t.is_empty() && has_token(req.headers(), &t));
+
+    let mut filter = AccessFilter::default();
+    if !internal {
+        // Fail closed: without the rules nothing can be shown safely
+        filter = match state.doc_repo.access_policy().await {
+            Ok(policy) => policy.filter(Utc::now().date_naive()),
+            Err(e) => return internal_error(e).into_response(),
+        };
+    }
+    // API keys scoped to sources see only those, even with the token
+    if let Some(sources) = req
+        .extensions()
+        .get::<ApiClient>()
+        .and_then(|c| c.sources.as_deref())
+    {
+        filter.limit_to_sources(sources);
+    }
+
+    let mut viewer = Viewer {
+        internal,
+        ..Viewer::default()
+    };
+    if !filter.is_empty() {
+        if let Some(addressed) = target(req.uri().path()) {
+            let document = match &addressed {
+                Target::Document(id) => Some(id.clone()),
+                _ => None,
+            };
+            match is_withheld(&state, &filter, addressed).await {
+                Ok(false) => {}
+                Ok(true) => return withheld(&state, &req, document.as_deref()).await,
+                Err(e) => return internal_error(e).into_response(),
+            }
+        }
+        viewer.restricted = Some(Arc::new(filter));
+    }
+
+    req.extensions_mut().insert(viewer);
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        assert!(matches!(
+            target("/documents/doc-1/versions"),
+            Some(Target::Document(id)) if id == "doc-1"
+        ));
+        assert!(matches!(
+            target("/api/documents/a%2Fb"),
+            Some(Target::Document(id)) if id == "a/b"
+        ));
+        assert!(matches!(
+            target("/doc/doc-1/v/3/p/2"),
+            Some(Target::Document(id)) if id == "doc-1"
+        ));
+        assert!(target("/api/documents/reocr/status").is_none());
+        assert!(target("/api/annotations/stats").is_none());
+        assert!(matches!(
+            target("/files/ab/report-ABCDEF12.pdf"),
+            Some(Target::Content(h)) if h == "abcdef12"
+        ));
+        assert!(matches!(
+            target("/files/ab/abcdef12.pdf"),
+            Some(Target::Content(h)) if h == "abcdef12"
+        ));
+        assert!(matches!(
+            target("/api/status/fbi"),
+            Some(Target::Source(id)) if id == "fbi"
+        ));
+        assert!(target("/browse").is_none());
+    }
+
+    #[test]
+    fn test_has_token() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            "theme=dark; foia_access=s3cret".parse().unwrap(),
+        );
+        assert!(has_token(&headers, "s3cret"));
+        assert!(!has_token(&headers, "other"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert!(has_token(&headers, "s3cret"));
+        assert!(!has_token(&HeaderMap::new(), "s3cret"));
+    }
+}
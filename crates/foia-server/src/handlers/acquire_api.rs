@@ -0,0 +1,153 @@
+//! Ad-hoc URL acquisition and browser capture API endpoints.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::super::acquire::AcquireJob;
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, not_found};
+use foia::services::acquire::{AcquireRequest, CaptureRequest};
+
+/// Request body for `POST /api/acquire`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AcquireBody {
+    /// URL to fetch.
+    pub url: String,
+    /// Source to file the document under (default: `adhoc`).
+    #[serde(default)]
+    pub source_id: Option<String>,
+    /// Tags to add to the document.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Submit a URL to fetch, store, and extract text from in the background.
+#[utoipa::path(
+    post,
+    path = "/api/acquire",
+    request_body = AcquireBody,
+    responses(
+        (status = 202, description = "Job queued; poll /api/acquire/{job_id}", body = AcquireJob),
+        (status = 400, description = "Invalid URL")
+    ),
+    tag = "Acquire"
+)]
+pub async fn submit_acquire(
+    State(state): State<AppState>,
+    Json(body): Json<AcquireBody>,
+) -> impl IntoResponse {
+    let request = AcquireRequest {
+        url: body.url,
+        source_id: body.source_id,
+        tags: body.tags,
+    };
+    if let Err(msg) = request.validate() {
+        return bad_request(&msg).into_response();
+    }
+
+    let jobs = state.acquire_jobs.clone();
+    let job = jobs.submit(state, request).await;
+    (StatusCode::ACCEPTED, ApiResponse::ok(job)).into_response()
+}
+
+/// Header naming the submitter when the capture body doesn't.
+const SUBMITTED_BY_HEADER: &str = "x-submitted-by";
+
+/// Request body for `POST /api/acquire/capture`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CaptureBody {
+    /// Address of the captured page.
+    pub url: String,
+    /// Page content as HTML or MHTML.
+    pub content: String,
+    /// `text/html` or `multipart/related` (MHTML); sniffed when omitted.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Page title as seen in the browser.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Source to file the document under (default: `adhoc`).
+    #[serde(default)]
+    pub source_id: Option<String>,
+    /// Tags to add to the document.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Who captured the page; falls back to the `X-Submitted-By` header.
+    #[serde(default)]
+    pub submitted_by: Option<String>,
+    /// When the page was captured (default: time of submission).
+    #[serde(default)]
+    pub captured_at: Option<DateTime<Utc>>,
+}
+
+/// Submit a page captured in the browser, e.g. from a login-gated portal the
+/// server can't fetch. Stored as a new version if the URL is already archived.
+#[utoipa::path(
+    post,
+    path = "/api/acquire/capture",
+    request_body = CaptureBody,
+    params(("X-Submitted-By" = Option<String>, Header, description = "Submitter, if not in the body")),
+    responses(
+        (status = 202, description = "Job queued; poll /api/acquire/{job_id}", body = AcquireJob),
+        (status = 400, description = "Invalid URL or content")
+    ),
+    tag = "Acquire"
+)]
+pub async fn submit_capture(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CaptureBody>,
+) -> impl IntoResponse {
+    let submitted_by = body.submitted_by.or_else(|| {
+        headers
+            .get(SUBMITTED_BY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    });
+    let capture = CaptureRequest {
+        url: body.url,
+        content: body.content,
+        content_type: body.content_type,
+        title: body.title,
+        source_id: body.source_id,
+        tags: body.tags,
+        submitted_by,
+        captured_at: body.captured_at,
+    };
+    if let Err(msg) = capture.validate() {
+        return bad_request(&msg).into_response();
+    }
+
+    let jobs = state.acquire_jobs.clone();
+    let job = jobs.submit_capture(state, capture).await;
+    (StatusCode::ACCEPTED, ApiResponse::ok(job)).into_response()
+}
+
+/// Get the status of an acquisition job.
+#[utoipa::path(
+    get,
+    path = "/api/acquire/{job_id}",
+    params(("job_id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job status", body = AcquireJob),
+        (status = 404, description = "Unknown or expired job")
+    ),
+    tag = "Acquire"
+)]
+pub async fn get_acquire_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    match state.acquire_jobs.get(&job_id).await {
+        Some(job) => ApiResponse::ok(job).into_response(),
+        None => not_found("Job not found").into_response(),
+    }
+}
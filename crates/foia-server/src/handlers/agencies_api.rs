@@ -0,0 +1,74 @@
+//! Agencies API: the source hierarchy with roll-up statistics.
+
+use std::collections::{HashSet, VecDeque};
+
+use axum::{extract::State, response::IntoResponse};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::internal_error;
+
+/// An agency with totals that include its sub-agencies.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgencySummary {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    /// Sources assigned directly to this agency
+    pub sources: Vec<String>,
+    /// Sources here and in every sub-agency
+    pub total_sources: u64,
+    /// Documents from those sources
+    pub total_documents: u64,
+    /// Inherited by member sources that don't set their own
+    pub user_agent: Option<String>,
+    pub request_timeout: Option<u64>,
+    pub request_delay_ms: Option<u64>,
+}
+
+/// List agencies, parents before their sub-agencies.
+#[utoipa::path(
+    get,
+    path = "/api/agencies",
+    responses(
+        (status = 200, description = "Agency hierarchy with roll-up counts", body = Vec<AgencySummary>)
+    ),
+    tag = "Agencies"
+)]
+pub async fn list_agencies(State(state): State<AppState>) -> impl IntoResponse {
+    let tree = match state.agency_repo.tree().await {
+        Ok(tree) => tree,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let source_counts = match state.doc_repo.get_all_source_counts().await {
+        Ok(counts) => counts,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let stats = tree.rollup(&source_counts);
+
+    let mut agencies = Vec::new();
+    let mut queue: VecDeque<_> = tree.roots().into();
+    let mut seen = HashSet::new();
+    while let Some(agency) = queue.pop_front() {
+        if !seen.insert(agency.id.as_str()) {
+            continue;
+        }
+        queue.extend(tree.children(&agency.id));
+        let totals = stats.get(&agency.id).copied().unwrap_or_default();
+        agencies.push(AgencySummary {
+            id: agency.id.clone(),
+            name: agency.name.clone(),
+            parent_id: agency.parent_id.clone(),
+            sources: tree.members_of(&agency.id),
+            total_sources: totals.sources,
+            total_documents: totals.documents,
+            user_agent: agency.settings.user_agent.clone(),
+            request_timeout: agency.settings.request_timeout,
+            request_delay_ms: agency.settings.request_delay_ms,
+        });
+    }
+
+    ApiResponse::ok(agencies).into_response()
+}
@@ -0,0 +1,47 @@
+//! Search alias dictionary page.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Extension,
+};
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{AliasRow, AliasesTemplate, ErrorTemplate};
+use super::super::AppState;
+
+/// List aliases with a form to add more.
+pub async fn aliases_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let aliases = match state.doc_repo.list_aliases().await {
+        Ok(a) => a,
+        Err(e) => {
+            let msg = format!("Failed to load aliases: {}", e);
+            let template = ErrorTemplate {
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
+                message: &msg,
+            };
+            return Html(template.render().unwrap_or(msg));
+        }
+    };
+
+    let template = AliasesTemplate {
+        title: "Search Aliases",
+        theme: &theme,
+        i18n: &i18n,
+        aliases: aliases.into_iter().map(AliasRow::from).collect(),
+        read_only: state.read_only,
+    };
+
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
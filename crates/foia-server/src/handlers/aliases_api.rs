@@ -0,0 +1,129 @@
+//! Search alias API endpoints: the dictionary used to expand search queries.
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::services::aliases::{AliasKind, SearchAlias};
+
+/// A term and what it stands for; searching either finds both.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AliasResponse {
+    pub id: i32,
+    pub term: String,
+    pub expansion: String,
+    /// acronym, code_name, or person
+    pub kind: String,
+}
+
+impl From<SearchAlias> for AliasResponse {
+    fn from(a: SearchAlias) -> Self {
+        Self {
+            id: a.id,
+            term: a.term,
+            expansion: a.expansion,
+            kind: a.kind.as_str().to_string(),
+        }
+    }
+}
+
+/// Create alias request.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateAliasRequest {
+    /// Short form or alternate name, e.g. "OIG"
+    pub term: String,
+    /// What it stands for, e.g. "Office of Inspector General"
+    pub expansion: String,
+    /// acronym (default), code_name, or person
+    pub kind: Option<String>,
+}
+
+/// List search aliases.
+#[utoipa::path(
+    get,
+    path = "/api/aliases",
+    responses(
+        (status = 200, description = "Aliases by term", body = Vec<AliasResponse>)
+    ),
+    tag = "Aliases"
+)]
+pub async fn list_aliases(State(state): State<AppState>) -> impl IntoResponse {
+    match state.doc_repo.list_aliases().await {
+        Ok(aliases) => ApiResponse::ok(
+            aliases
+                .into_iter()
+                .map(AliasResponse::from)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Add a search alias.
+#[utoipa::path(
+    post,
+    path = "/api/aliases",
+    request_body = CreateAliasRequest,
+    responses(
+        (status = 200, description = "Created (or existing) alias", body = AliasResponse),
+        (status = 400, description = "Empty term or unknown kind")
+    ),
+    tag = "Aliases"
+)]
+pub async fn create_alias(
+    State(state): State<AppState>,
+    Json(body): Json<CreateAliasRequest>,
+) -> impl IntoResponse {
+    let term = body.term.trim();
+    let expansion = body.expansion.trim();
+    if term.is_empty() || expansion.is_empty() {
+        return bad_request("Both term and expansion are required").into_response();
+    }
+    if term.eq_ignore_ascii_case(expansion) {
+        return bad_request("A term cannot be an alias of itself").into_response();
+    }
+    let kind = body
+        .kind
+        .as_deref()
+        .map(str::trim)
+        .filter(|k| !k.is_empty());
+    let kind = match kind {
+        None => AliasKind::Acronym,
+        Some(k) => match AliasKind::from_str(k) {
+            Some(kind) => kind,
+            None => return bad_request(&format!("Unknown alias kind: {}", k)).into_response(),
+        },
+    };
+
+    match state.doc_repo.add_alias(term, expansion, kind).await {
+        Ok(alias) => ApiResponse::ok(AliasResponse::from(alias)).into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Delete a search alias.
+#[utoipa::path(
+    delete,
+    path = "/api/aliases/{id}",
+    params(("id" = i32, Path, description = "Alias ID")),
+    responses(
+        (status = 200, description = "Alias deleted"),
+        (status = 404, description = "Alias not found")
+    ),
+    tag = "Aliases"
+)]
+pub async fn delete_alias(State(state): State<AppState>, Path(id): Path<i32>) -> impl IntoResponse {
+    match state.doc_repo.delete_alias(id).await {
+        Ok(true) => ApiResponse::ok(id).into_response(),
+        Ok(false) => not_found("Alias not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
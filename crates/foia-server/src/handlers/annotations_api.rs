@@ -3,17 +3,18 @@
 use axum::{
     extract::{Path, Query, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
 use super::super::AppState;
+use super::access::Viewer;
 use super::api_types::{
     AnnotationListStats, AnnotationsListResponse, ApiResponse, UpdateAnnotationResponse,
 };
 use super::helpers::{internal_error, not_found};
-use foia::repository::diesel_document::BrowseParams;
+use foia::repository::diesel_document::{BrowseParams, Projection};
 
 /// Query params for annotations listing.
 #[derive(Debug, Deserialize, IntoParams)]
@@ -57,6 +58,7 @@ pub struct UpdateAnnotationRequest {
 )]
 pub async fn list_annotations(
     State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<AnnotationsQuery>,
 ) -> impl IntoResponse {
     let per_page = params.per_page.unwrap_or(50).clamp(1, 200);
@@ -65,9 +67,12 @@ pub async fn list_annotations(
     let documents = if params.needs_annotation.unwrap_or(false) {
         state
             .doc_repo
-            .get_needing_summarization(per_page)
+            .get_needing_summarization(params.source.as_deref(), &[], per_page)
             .await
             .unwrap_or_default()
+            .into_iter()
+            .filter(|d| viewer.allows(&d.id, &d.source_id))
+            .collect()
     } else {
         let offset = page.saturating_sub(1) * per_page;
         state
@@ -76,6 +81,8 @@ pub async fn list_annotations(
                 source_id: params.source.as_deref(),
                 limit: per_page as u32,
                 offset: offset as u32,
+                projection: Projection::Metadata,
+                access: viewer.filter(),
                 ..Default::default()
             })
             .await
@@ -100,7 +107,7 @@ pub async fn list_annotations(
         .unwrap_or(0);
     let total_needing = state
         .doc_repo
-        .count_needing_summarization(params.source.as_deref())
+        .count_needing_summarization(params.source.as_deref(), &[])
         .await
         .unwrap_or(0);
 
@@ -131,7 +138,11 @@ pub async fn get_annotation(
     State(state): State<AppState>,
     Path(doc_id): Path<String>,
 ) -> impl IntoResponse {
-    match state.doc_repo.get(&doc_id).await {
+    match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
         Ok(Some(doc)) => ApiResponse::ok(AnnotationResponse {
             document_id: doc.id,
             title: doc.title,
@@ -162,7 +173,11 @@ pub async fn update_annotation(
     Path(doc_id): Path<String>,
     Json(body): Json<UpdateAnnotationRequest>,
 ) -> impl IntoResponse {
-    let doc = match state.doc_repo.get(&doc_id).await {
+    let doc = match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
         Ok(Some(d)) => d,
         Ok(None) => return not_found("Document not found").into_response(),
         Err(e) => return internal_error(e).into_response(),
@@ -178,6 +193,9 @@ pub async fn update_annotation(
     {
         return internal_error(e).into_response();
     }
+    // Tag counts change along with the document's tags
+    state.read_cache.invalidate_document(&doc_id).await;
+    state.stats_cache.clear();
 
     ApiResponse::ok(UpdateAnnotationResponse {
         document_id: doc_id,
@@ -218,7 +236,7 @@ pub async fn annotation_stats(State(state): State<AppState>) -> impl IntoRespons
     let annotated = state.doc_repo.count_annotated(None).await.unwrap_or(0);
     let needing = state
         .doc_repo
-        .count_needing_summarization(None)
+        .count_needing_summarization(None, &[])
         .await
         .unwrap_or(0);
 
@@ -237,7 +255,7 @@ pub async fn annotation_stats(State(state): State<AppState>) -> impl IntoRespons
             .unwrap_or(0);
         let needing = state
             .doc_repo
-            .count_needing_summarization(Some(&source_id))
+            .count_needing_summarization(Some(&source_id), &[])
             .await
             .unwrap_or(0);
         by_source.push(SourceAnnotationStats {
@@ -2,18 +2,22 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::IntoResponse,
+    Extension,
 };
 use serde::Deserialize;
 use utoipa::IntoParams;
 
 use super::super::AppState;
+use super::access::Viewer;
 use super::api_types::{
     ApiResponse, CategoryStat, CrawlState, CrawlStats, DocumentStats, FailedUrl, MimeTypeStat,
     RecentDocument, RecentUrl, RequestStats, SourceCrawlStat, SourceInfo, SourceStatusResponse,
     StatusResponse, TagCount,
 };
+use foia::repository::diesel_document::Projection;
+use foia::services::pipeline_eta;
 
 /// Health check endpoint for container orchestration.
 #[utoipa::path(
@@ -28,6 +32,33 @@ pub async fn health() -> impl IntoResponse {
     StatusCode::OK
 }
 
+/// Pipeline backlog, throughput and ETA per stage, for Prometheus.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", body = String)
+    ),
+    tag = "Health"
+)]
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    match pipeline_eta::estimate(
+        &state.doc_repo,
+        &state.crawl_repo,
+        None,
+        pipeline_eta::DEFAULT_WINDOW,
+    )
+    .await
+    {
+        Ok(stages) => (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            pipeline_eta::to_prometheus(&stages),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
 /// Parameters for recent documents.
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct RecentParams {
@@ -103,12 +134,12 @@ pub async fn api_status(State(state): State<AppState>) -> impl IntoResponse {
     let doc_count = state.doc_repo.count().await.unwrap_or(0);
     let needing_ocr = state
         .doc_repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap_or(0);
     let needing_summary = state
         .doc_repo
-        .count_needing_summarization(None)
+        .count_needing_summarization(None, &[])
         .await
         .unwrap_or(0);
 
@@ -210,12 +241,12 @@ pub async fn api_source_status(
         .unwrap_or(0);
     let needing_ocr = state
         .doc_repo
-        .count_needing_analysis("ocr", Some(&source_id), None, 12)
+        .count_needing_analysis("ocr", Some(&source_id), None, 12, &[])
         .await
         .unwrap_or(0);
     let needing_summary = state
         .doc_repo
-        .count_needing_summarization(Some(&source_id))
+        .count_needing_summarization(Some(&source_id), &[])
         .await
         .unwrap_or(0);
 
@@ -302,16 +333,22 @@ pub async fn api_source_status(
 )]
 pub async fn api_recent_docs(
     State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<RecentParams>,
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(20).min(100);
     let source_id = params.source.as_deref();
 
-    match state.doc_repo.get_recent(limit as u32).await {
+    match state
+        .doc_repo
+        .get_recent(limit as u32, Projection::Metadata)
+        .await
+    {
         Ok(docs) => {
             let doc_list: Vec<RecentDocument> = docs
                 .into_iter()
                 .filter(|d| source_id.is_none() || Some(d.source_id.as_str()) == source_id)
+                .filter(|d| viewer.allows(&d.id, &d.source_id))
                 .map(|d| {
                     let mime_type = d.current_version().map(|v| v.mime_type.clone());
                     let file_size = d.current_version().map(|v| v.file_size);
@@ -0,0 +1,180 @@
+//! API keys on the JSON API: authentication, scopes and rate limits.
+//!
+//! A key is sent in the `X-API-Key` header or the `api_key` query
+//! parameter. Requests without one are served as before unless
+//! `access.require_api_key` is set. Keys never unlock restricted
+//! documents; a key scoped to sources sees only their documents.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::internal_error;
+use super::read_only::is_read_request;
+
+/// Header carrying an API key.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// The API key a request was made with.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    /// Who the key was issued to.
+    pub name: String,
+    /// Sources the key may see; `None` for all.
+    pub sources: Option<Vec<String>>,
+}
+
+/// Whether a path is part of the JSON API that keys apply to. The sync
+/// API has its own token, and the API description stays public.
+fn is_keyed_path(path: &str) -> bool {
+    path.starts_with("/api/") && path != "/api/openapi.json" && !path.starts_with("/api/sync/")
+}
+
+/// The key a request presents, if any.
+fn presented_key(req: &Request) -> Option<String> {
+    let header = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string());
+    header.or_else(|| {
+        req.uri().query()?.split('&').find_map(|pair| {
+            let value = pair.strip_prefix("api_key=")?;
+            urlencoding::decode(value).ok().map(|v| v.into_owned())
+        })
+    })
+}
+
+/// Whether the request comes from the site's own pages. Browsers set
+/// `Sec-Fetch-Site` themselves; other clients can imitate it, so this only
+/// keeps the web interface working when keys are required.
+fn is_same_origin(headers: &HeaderMap) -> bool {
+    headers
+        .get("sec-fetch-site")
+        .is_some_and(|v| v.as_bytes() == b"same-origin")
+}
+
+/// Whether the request has the operator's access token.
+fn has_access_token(headers: &HeaderMap, token: Option<&str>) -> bool {
+    token
+        .and_then(foia::secrets::resolve_or_warn)
+        .is_some_and(|t| !t.is_empty() && super::access::has_token(headers, &t))
+}
+
+fn unauthorized(message: &str) -> Response {
+    let mut response = ApiResponse::error(StatusCode::UNAUTHORIZED, message).into_response();
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        HeaderValue::from_static("ApiKey header=\"X-API-Key\""),
+    );
+    response
+}
+
+/// Authenticate API keys, enforce their method scope and rate limit, and
+/// count their requests. Source scopes are applied by
+/// [`access_guard`](super::access_guard), which runs next.
+pub async fn api_key_guard(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if !is_keyed_path(req.uri().path()) {
+        return next.run(req).await;
+    }
+    let (require, default_limit, token) = {
+        let config = state.config.read().await;
+        (
+            config.access.require_api_key,
+            config.access.api_rate_limit(),
+            config.access.token.clone(),
+        )
+    };
+
+    let Some(presented) = presented_key(&req) else {
+        let exempt = !require
+            || is_same_origin(req.headers())
+            || has_access_token(req.headers(), token.as_deref());
+        if !exempt {
+            return unauthorized("API key required");
+        }
+        return next.run(req).await;
+    };
+
+    let key = match state.doc_repo.find_api_key(&presented).await {
+        Ok(Some(key)) => key,
+        Ok(None) => return unauthorized("Invalid or revoked API key"),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    if key.read_only && !is_read_request(&req) {
+        return ApiResponse::error(StatusCode::FORBIDDEN, "API key is read-only").into_response();
+    }
+
+    let limit = key.rate_limit.unwrap_or(default_limit);
+    let remaining = match state.api_limiter.check(key.id, limit) {
+        Ok(remaining) => remaining,
+        Err(retry_after) => {
+            let mut response =
+                ApiResponse::error(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded")
+                    .into_response();
+            let seconds = retry_after.as_secs().max(1).to_string();
+            if let Ok(value) = HeaderValue::from_str(&seconds) {
+                response.headers_mut().insert(header::RETRY_AFTER, value);
+            }
+            return response;
+        }
+    };
+
+    req.extensions_mut().insert(ApiClient {
+        name: key.name,
+        sources: key.sources,
+    });
+    let mut response = next.run(req).await;
+    if limit > 0 {
+        let headers = response.headers_mut();
+        headers.insert("x-ratelimit-limit", HeaderValue::from(limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    #[test]
+    fn test_keyed_paths() {
+        assert!(is_keyed_path("/api/documents"));
+        assert!(is_keyed_path("/api/search"));
+        assert!(!is_keyed_path("/api/openapi.json"));
+        assert!(!is_keyed_path("/api/sync/manifest"));
+        assert!(!is_keyed_path("/documents/doc-1"));
+    }
+
+    #[test]
+    fn test_presented_key() {
+        let req = Request::builder()
+            .uri("/api/documents")
+            .header("X-API-Key", " foia_abc ")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(presented_key(&req).as_deref(), Some("foia_abc"));
+
+        let req = Request::builder()
+            .uri("/api/documents?limit=5&api_key=foia_def")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(presented_key(&req).as_deref(), Some("foia_def"));
+
+        let req = Request::builder()
+            .uri("/api/documents")
+            .body(Body::empty())
+            .unwrap();
+        assert!(presented_key(&req).is_none());
+    }
+}
@@ -233,6 +233,8 @@ pub struct ScraperInfo {
     pub last_scraped: Option<String>,
     pub document_count: u64,
     pub crawl_stats: Option<ScraperCrawlStats>,
+    /// Whether crawling and downloading are paused for this source
+    pub paused: bool,
 }
 
 /// Crawl stats within a scraper info entry.
@@ -251,6 +253,7 @@ pub struct ScraperStatusResponse {
     pub source_id: String,
     pub name: String,
     pub last_scraped: Option<String>,
+    pub paused: bool,
     pub crawl_state: Option<CrawlState>,
     pub request_stats: Option<RequestStats>,
     pub recent_downloads: Vec<RecentUrl>,
@@ -283,6 +286,13 @@ pub struct RetryResponse {
     pub message: String,
 }
 
+/// Result of `POST /api/scrapers/:source_id/pause` or `/resume`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PauseResponse {
+    pub source_id: String,
+    pub paused: bool,
+}
+
 /// Versions listing response from `GET /api/documents/:id/versions`.
 #[derive(Debug, Serialize, ToSchema)]
 pub struct VersionsListResponse {
@@ -0,0 +1,157 @@
+//! Bates number API endpoints: lookup by number and sequence gaps.
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::helpers::{bad_request, internal_error};
+use foia::repository::diesel_document::BatesHit;
+use foia::services::bates::{find_gaps, BatesGap, BatesNumber};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct BatesLookupQuery {
+    /// Filter by source
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatesMatch {
+    pub document_id: String,
+    pub title: String,
+    pub source_id: String,
+    /// Page of the document stamped with the number.
+    pub page: u32,
+    /// First number of the stamped range containing it.
+    pub range_start: String,
+    /// Last number of the stamped range containing it.
+    pub range_end: String,
+    /// Document page, scrolled to the stamped page.
+    pub document_url: String,
+}
+
+impl From<BatesHit> for BatesMatch {
+    fn from(hit: BatesHit) -> Self {
+        Self {
+            document_url: format!("/documents/{}#page-{}", hit.document_id, hit.page),
+            range_start: hit.range.first().to_string(),
+            range_end: hit.range.last().to_string(),
+            document_id: hit.document_id,
+            title: hit.title,
+            source_id: hit.source_id,
+            page: hit.page,
+        }
+    }
+}
+
+/// Find the documents and pages stamped with a Bates number.
+///
+/// Prefix separators and case are ignored, so `doj_ogr_12345` finds
+/// `DOJ-OGR-00012345`. Ranges are recorded by `foia bates extract`.
+#[utoipa::path(
+    get,
+    path = "/api/bates/lookup/{number}",
+    params(
+        ("number" = String, Path, description = "Bates number, e.g. DOJ-OGR-00012345"),
+        BatesLookupQuery
+    ),
+    responses(
+        (status = 200, description = "Stamped pages", body = Vec<BatesMatch>),
+        (status = 400, description = "Not a Bates number")
+    ),
+    tag = "Bates"
+)]
+pub async fn lookup_bates(
+    State(state): State<AppState>,
+    Path(number): Path<String>,
+    Query(params): Query<BatesLookupQuery>,
+) -> impl IntoResponse {
+    let Some(number) = BatesNumber::parse(&number) else {
+        return bad_request("Not a Bates number (expected a prefix and at least 5 digits)")
+            .into_response();
+    };
+
+    match state
+        .doc_repo
+        .find_bates(&number, params.source.as_deref())
+        .await
+    {
+        Ok(hits) => {
+            let items: Vec<BatesMatch> = hits.into_iter().map(BatesMatch::from).collect();
+            Json(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatesGapItem {
+    /// First missing number.
+    pub from: String,
+    /// Last missing number.
+    pub to: String,
+    /// Count of missing numbers (pages).
+    pub missing: u64,
+    /// Document whose range ends just before the gap.
+    pub after_document: String,
+    /// Document whose range starts just after the gap.
+    pub before_document: String,
+}
+
+impl From<BatesGap> for BatesGapItem {
+    fn from(gap: BatesGap) -> Self {
+        Self {
+            from: gap.from,
+            to: gap.to,
+            missing: gap.missing,
+            after_document: gap.after_document,
+            before_document: gap.before_document,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatesGapsResponse {
+    pub source_id: String,
+    /// Stamped ranges recorded for the source.
+    pub range_count: usize,
+    /// Total numbers missing across all gaps.
+    pub missing: u64,
+    pub gaps: Vec<BatesGapItem>,
+}
+
+/// Numbers missing between the Bates ranges of a source.
+///
+/// Each gap names the documents on either side of it. A gap suggests pages
+/// or documents that were withheld from the release or not yet collected.
+#[utoipa::path(
+    get,
+    path = "/api/bates/gaps/{source_id}",
+    params(("source_id" = String, Path, description = "Source ID")),
+    responses(
+        (status = 200, description = "Gaps in the source's Bates sequences", body = BatesGapsResponse)
+    ),
+    tag = "Bates"
+)]
+pub async fn bates_gaps(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> impl IntoResponse {
+    match state.doc_repo.get_source_bates_ranges(&source_id).await {
+        Ok(ranges) => {
+            let gaps = find_gaps(&ranges);
+            Json(BatesGapsResponse {
+                source_id,
+                range_count: ranges.len(),
+                missing: gaps.iter().map(|g| g.missing).sum(),
+                gaps: gaps.into_iter().map(BatesGapItem::from).collect(),
+            })
+            .into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
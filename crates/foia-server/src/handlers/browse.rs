@@ -1,20 +1,33 @@
 //! Browse page handler.
 
+use std::collections::{HashMap, HashSet};
+
 use askama::Template;
 use axum::{
     extract::{Query, State},
     response::{Html, IntoResponse},
+    Extension,
 };
 use serde::Deserialize;
 
-use foia::utils::MimeCategory;
+use foia::models::{Agency, AgencyTree, RecordType};
+use foia::repository::diesel_document::{Keyset, SourceScope};
+use foia::services::bates::BatesNumber;
+use foia::services::exemptions::normalize_code;
+use foia::utils::{MimeCategory, ATTACHMENTS_CATEGORY};
 
+use super::super::i18n::I18n;
 use super::super::template_structs::{
-    ActiveTagDisplay, BrowseTemplate, CategoryWithCount, DocumentRow, ErrorTemplate, SourceOption,
-    TagWithCount,
+    ActiveTagDisplay, AgencyOption, BatesMatchRow, BrowseTemplate, CategoryWithCount, DocumentRow,
+    ErrorTemplate, ExemptionOption, RecordTypeOption, SourceOption, TagWithCount,
 };
 use super::super::AppState;
-use super::helpers::{paginate, parse_csv_param_limit};
+use super::access::Viewer;
+use super::helpers::{decode_cursor, paginate, parse_csv_param_limit, trim_extra_row};
+
+/// Pages up to this number link by page number; "Next" from the last of
+/// them switches to keyset cursors so deep pages avoid large OFFSETs.
+const PAGE_NUMBER_LIMIT: usize = 10;
 
 /// Query params for the unified browse page.
 #[derive(Debug, Clone, Deserialize)]
@@ -22,104 +35,211 @@ pub struct BrowseParams {
     pub types: Option<String>,
     pub tags: Option<String>,
     pub source: Option<String>,
+    /// Agency ID: documents from its sources and its sub-agencies' sources.
+    pub agency: Option<String>,
+    /// Record type assigned by classification (letter, memo, photo, ...).
+    pub record_type: Option<String>,
+    /// Documents citing a FOIA exemption, e.g. `(b)(5)` or `b7c`.
+    pub exemption: Option<String>,
     pub q: Option<String>,
     pub page: Option<usize>,
     pub per_page: Option<usize>,
+    /// Keyset cursor: show the page after this position.
+    pub after: Option<String>,
+    /// Keyset cursor: show the page before this position.
+    pub before: Option<String>,
 }
 
 /// Unified document browse page with filters.
 pub async fn browse_documents(
     State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<BrowseParams>,
 ) -> impl IntoResponse {
+    let theme = state.theme().await;
     let (page, per_page, _offset) = paginate(params.page, params.per_page);
     let types = parse_csv_param_limit(params.types.as_ref(), Some(20));
     let tags = parse_csv_param_limit(params.tags.as_ref(), Some(50));
+    let record_type = params.record_type.as_deref().and_then(RecordType::from_str);
+    let record_types: Vec<String> = record_type
+        .map(|t| t.as_str().to_string())
+        .into_iter()
+        .collect();
+    let exemption = params.exemption.as_deref().and_then(normalize_code);
+    let exemptions: Vec<String> = exemption.clone().into_iter().collect();
+
+    // A malformed cursor falls back to page-number navigation
+    let cursor = decode_cursor(params.after.as_deref(), params.before.as_deref())
+        .ok()
+        .flatten();
+    let backward = cursor.as_ref().is_some_and(|(_, b)| *b);
+    let keyset = cursor.as_ref().map(|(c, backward)| {
+        if *backward {
+            Keyset::Before(c)
+        } else {
+            Keyset::After(c)
+        }
+    });
 
     let offset = page.saturating_sub(1) * per_page;
-    let (browse_result, count_result, category_stats, source_counts, sources, all_tags) =
-        tokio::join!(
-            state.doc_repo.browse_fast(
-                params.source.as_deref(),
-                None,
-                &types,
-                &tags,
-                per_page as u32,
-                offset as u32,
-            ),
-            state.doc_repo.browse_count(
-                params.source.as_deref(),
-                None,
-                &types,
-                &tags,
-                params.q.as_deref(),
-            ),
-            async {
-                match state.stats_cache.get_category_stats() {
-                    Some(cached) => cached,
-                    None => {
-                        let stats = state
-                            .doc_repo
-                            .get_category_stats(None)
-                            .await
-                            .unwrap_or_default();
-                        state.stats_cache.set_category_stats(stats.clone());
-                        stats
-                    }
+    let search_query = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+
+    // A source filter narrows within an agency; an unknown agency matches nothing
+    let agencies = state.agency_repo.tree().await.unwrap_or_default();
+    let agency = params.agency.as_deref().filter(|a| !a.is_empty());
+    let agency_sources = agency.map(|a| agencies.sources_under(a));
+    let scope = match (params.source.as_deref(), &agency_sources) {
+        (Some(source), _) => SourceScope::One(source),
+        (None, Some(ids)) => SourceScope::Any(ids),
+        (None, None) => SourceScope::All,
+    };
+    let (
+        browse_result,
+        count_result,
+        category_stats,
+        source_counts,
+        sources,
+        all_tags,
+        attachment_count,
+        record_type_counts,
+        exemption_counts,
+    ) = tokio::join!(
+        state.doc_repo.browse_fast(
+            scope,
+            None,
+            &types,
+            &tags,
+            &record_types,
+            &exemptions,
+            search_query,
+            per_page as u32 + 1,
+            offset as u32,
+            keyset,
+            viewer.filter(),
+        ),
+        state.doc_repo.browse_count(
+            scope,
+            None,
+            &types,
+            &tags,
+            &record_types,
+            &exemptions,
+            search_query,
+            viewer.filter(),
+        ),
+        async {
+            match state.stats_cache.get_category_stats() {
+                Some(cached) => cached,
+                None => {
+                    let stats = state
+                        .doc_repo
+                        .get_category_stats(None)
+                        .await
+                        .unwrap_or_default();
+                    state.stats_cache.set_category_stats(stats.clone());
+                    stats
                 }
-            },
-            async {
-                match state.stats_cache.get_source_counts() {
-                    Some(cached) => cached,
-                    None => {
-                        let counts = state
-                            .doc_repo
-                            .get_all_source_counts()
-                            .await
-                            .unwrap_or_default();
-                        state.stats_cache.set_source_counts(counts.clone());
-                        counts
-                    }
+            }
+        },
+        async {
+            match state.stats_cache.get_source_counts() {
+                Some(cached) => cached,
+                None => {
+                    let counts = state
+                        .doc_repo
+                        .get_all_source_counts()
+                        .await
+                        .unwrap_or_default();
+                    state.stats_cache.set_source_counts(counts.clone());
+                    counts
                 }
-            },
-            state.source_repo.get_all(),
-            async {
-                match state.stats_cache.get_all_tags() {
-                    Some(cached) => cached,
-                    None => {
-                        let raw = state.doc_repo.get_all_tags().await.unwrap_or_default();
-                        let with_counts: Vec<(String, usize)> =
-                            raw.into_iter().map(|t| (t, 0)).collect();
-                        state.stats_cache.set_all_tags(with_counts.clone());
-                        with_counts
-                    }
+            }
+        },
+        state.read_cache.sources(&state.source_repo),
+        async {
+            match state.stats_cache.get_all_tags() {
+                Some(cached) => cached,
+                None => {
+                    let with_counts: Vec<(String, usize)> = state
+                        .doc_repo
+                        .get_tag_counts()
+                        .await
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(t, c)| (t, c as usize))
+                        .collect();
+                    state.stats_cache.set_all_tags(with_counts.clone());
+                    with_counts
                 }
-            },
-        );
+            }
+        },
+        state
+            .doc_repo
+            .count_browse_virtual_files(SourceScope::All, &[], None, viewer.filter()),
+        state.doc_repo.get_record_type_counts(),
+        state.doc_repo.get_exemption_counts(),
+    );
 
-    let browse_rows = match browse_result {
+    let mut browse_rows = match browse_result {
         Ok(result) => result,
         Err(e) => {
             let template = ErrorTemplate {
-                title: "Error",
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
                 message: &format!("Failed to load documents: {}", e),
             };
             return Html(template.render().unwrap_or_else(|_| e.to_string()));
         }
     };
 
+    let has_more = trim_extra_row(&mut browse_rows, per_page, backward);
+
     let total = match count_result {
         Ok(count) => count,
         Err(_) => browse_rows.len() as u64,
     };
 
+    // Calculate pagination links (query fragments applied by goToPage)
+    let first_cursor = browse_rows.first().map(|r| r.cursor().encode());
+    let last_cursor = browse_rows.last().map(|r| r.cursor().encode());
+    let (prev_cursor, next_cursor) = if cursor.is_some() {
+        let has_prev = !backward || has_more;
+        let has_next = backward || has_more;
+        (
+            first_cursor
+                .filter(|_| has_prev)
+                .map(|c| format!("before={}", c)),
+            last_cursor
+                .filter(|_| has_next)
+                .map(|c| format!("after={}", c)),
+        )
+    } else {
+        let prev = (page > 1).then(|| format!("page={}", page - 1));
+        let next = if !has_more {
+            None
+        } else if page < PAGE_NUMBER_LIMIT {
+            Some(format!("page={}", page + 1))
+        } else {
+            last_cursor.map(|c| format!("after={}", c))
+        };
+        (prev, next)
+    };
+    let has_prev = prev_cursor.is_some();
+    let has_next = next_cursor.is_some();
+
+    // Position is only known when paging by number
+    let start_position = if cursor.is_some() { 0 } else { offset as u64 };
+
     let doc_rows: Vec<DocumentRow> = browse_rows
         .into_iter()
         .map(DocumentRow::from_browse_row)
         .collect();
 
-    // Build category filter checkboxes
-    let categories: Vec<CategoryWithCount> = MimeCategory::all()
+    // Build category filter checkboxes; attachments are opt-in, not part of
+    // the default "all types" view
+    let mut categories: Vec<CategoryWithCount> = MimeCategory::all()
         .iter()
         .filter_map(|(id, name)| {
             let count = category_stats.get(*id).copied().unwrap_or(0);
@@ -136,6 +256,17 @@ pub async fn browse_documents(
             })
         })
         .collect();
+    let attachment_count = attachment_count.unwrap_or(0);
+    if attachment_count > 0 {
+        let checked = types.iter().any(|t| t == ATTACHMENTS_CATEGORY);
+        categories.push(CategoryWithCount {
+            id: ATTACHMENTS_CATEGORY.to_string(),
+            name: "Attachments".to_string(),
+            count: attachment_count,
+            active: checked,
+            checked,
+        });
+    }
 
     // Build source dropdown options
     let source_options: Vec<SourceOption> = sources
@@ -153,27 +284,38 @@ pub async fn browse_documents(
         })
         .collect();
 
+    let agency_options = agency_options(&agencies, &source_counts, agency);
+
+    let record_type_options: Vec<RecordTypeOption> = record_type_counts
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(id, count)| {
+            let kind = RecordType::from_str(&id)?;
+            Some(RecordTypeOption {
+                id,
+                label: kind.label().to_string(),
+                count,
+                selected: record_type == Some(kind),
+            })
+        })
+        .collect();
+
+    let exemption_options: Vec<ExemptionOption> = exemption_counts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(code, count)| ExemptionOption {
+            selected: exemption.as_deref() == Some(code.as_str()),
+            code,
+            count,
+        })
+        .collect();
+
     // Build tag datalist
     let tag_list: Vec<TagWithCount> = all_tags
         .into_iter()
         .map(|(name, count)| TagWithCount::new(name, count))
         .collect();
 
-    // Calculate pagination cursors
-    let start_position = offset as u64;
-    let has_prev = page > 1;
-    let has_next = start_position + (per_page as u64) < total;
-    let prev_cursor = if has_prev {
-        Some(format!("{}", page - 1))
-    } else {
-        None
-    };
-    let next_cursor = if has_next {
-        Some(format!("{}", page + 1))
-    } else {
-        None
-    };
-
     // Build query string for document links
     let nav_query_string = {
         let mut qs_parts = Vec::new();
@@ -186,6 +328,18 @@ pub async fn browse_documents(
         if let Some(source) = params.source.as_deref() {
             qs_parts.push(format!("source={}", urlencoding::encode(source)));
         }
+        if let Some(agency) = agency {
+            qs_parts.push(format!("agency={}", urlencoding::encode(agency)));
+        }
+        if let Some(kind) = record_type {
+            qs_parts.push(format!("record_type={}", kind.as_str()));
+        }
+        if let Some(code) = exemption.as_deref() {
+            qs_parts.push(format!("exemption={}", urlencoding::encode(code)));
+        }
+        if let Some(q) = search_query {
+            qs_parts.push(format!("q={}", urlencoding::encode(q)));
+        }
         if qs_parts.is_empty() {
             String::new()
         } else {
@@ -208,11 +362,35 @@ pub async fn browse_documents(
 
     let end_position = start_position + doc_rows.len() as u64;
 
+    // A Bates number finds the stamped page, which full-text search can't
+    let bates_matches = match search_query.and_then(BatesNumber::parse) {
+        Some(number) if cursor.is_none() && page == 1 => state
+            .doc_repo
+            .find_bates(&number, params.source.as_deref())
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|hit| BatesMatchRow {
+                range: format!("{} – {}", hit.range.first(), hit.range.last()),
+                document_id: hit.document_id,
+                title: hit.title,
+                source_id: hit.source_id,
+                page: hit.page,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
     let template = BrowseTemplate {
-        title: "Browse",
+        title: &i18n.t("title-browse"),
+        theme: &theme,
+        i18n: &i18n,
         documents: doc_rows,
         categories,
         sources: source_options,
+        agencies: agency_options,
+        record_types: record_type_options,
+        exemptions: exemption_options,
         all_tags: tag_list,
         active_tags_display,
         has_prev_cursor: prev_cursor.is_some(),
@@ -226,6 +404,8 @@ pub async fn browse_documents(
         has_pagination: has_prev || has_next,
         nav_query_string,
         active_tags_json,
+        search_query: search_query.unwrap_or_default().to_string(),
+        bates_matches,
     };
 
     Html(
@@ -234,3 +414,34 @@ pub async fn browse_documents(
             .unwrap_or_else(|e| format!("Template error: {}", e)),
     )
 }
+
+/// Agencies depth-first with sub-agencies indented under their parent,
+/// counting documents across each subtree.
+fn agency_options(
+    tree: &AgencyTree,
+    source_counts: &HashMap<String, u64>,
+    selected: Option<&str>,
+) -> Vec<AgencyOption> {
+    let stats = tree.rollup(source_counts);
+    let mut options = Vec::new();
+    let mut stack: Vec<(usize, &Agency)> = tree.roots().into_iter().rev().map(|a| (0, a)).collect();
+    let mut seen = HashSet::new();
+    while let Some((depth, agency)) = stack.pop() {
+        if !seen.insert(agency.id.as_str()) {
+            continue;
+        }
+        options.push(AgencyOption {
+            id: agency.id.clone(),
+            label: format!("{}{}", "\u{2003}".repeat(depth), agency.name),
+            count: stats.get(&agency.id).map(|s| s.documents).unwrap_or(0),
+            selected: selected == Some(agency.id.as_str()),
+        });
+        stack.extend(
+            tree.children(&agency.id)
+                .into_iter()
+                .rev()
+                .map(|child| (depth + 1, child)),
+        );
+    }
+    options
+}
@@ -0,0 +1,56 @@
+//! CAPTCHA challenge queue page.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Extension,
+};
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{ChallengeRow, ChallengesTemplate, ErrorTemplate};
+use super::super::AppState;
+
+/// Number of recent challenges shown on the page.
+const PAGE_LIMIT: usize = 200;
+
+/// List pending and recently resolved challenges.
+pub async fn list_challenges_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let challenges = match state.crawl_repo.list_challenges(None, PAGE_LIMIT).await {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = format!("Failed to load challenges: {}", e);
+            let template = ErrorTemplate {
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
+                message: &msg,
+            };
+            return Html(template.render().unwrap_or(msg));
+        }
+    };
+
+    let (pending, resolved): (Vec<_>, Vec<_>) = challenges
+        .into_iter()
+        .map(ChallengeRow::from)
+        .partition(|c| c.status == "pending");
+
+    let template = ChallengesTemplate {
+        title: "Challenges",
+        theme: &theme,
+        i18n: &i18n,
+        pending,
+        resolved,
+        read_only: state.read_only,
+    };
+
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
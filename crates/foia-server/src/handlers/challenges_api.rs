@@ -0,0 +1,180 @@
+//! CAPTCHA challenge queue endpoints.
+//!
+//! Detected challenges pause their domain until an operator solves or
+//! dismisses them. Solving records the `Cookie` header captured from a
+//! browser that passed the challenge; the crawler sends it on later requests.
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::models::{ChallengeStatus, CrawlChallenge};
+
+/// Default number of challenges returned by the list endpoint.
+const DEFAULT_LIMIT: usize = 100;
+
+/// A detected CAPTCHA or anti-bot challenge.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChallengeResponse {
+    pub id: i64,
+    pub source_id: String,
+    pub domain: String,
+    /// URL that returned the challenge page
+    pub url: String,
+    /// Detector that matched (e.g. "cloudflare", "recaptcha")
+    pub kind: String,
+    /// pending, solved, or dismissed
+    pub status: String,
+    pub detected_at: String,
+    pub resolved_at: Option<String>,
+    /// Whether cookies were captured when solving
+    pub has_cookies: bool,
+}
+
+impl From<CrawlChallenge> for ChallengeResponse {
+    fn from(c: CrawlChallenge) -> Self {
+        Self {
+            id: c.id,
+            source_id: c.source_id,
+            domain: c.domain,
+            url: c.url,
+            kind: c.kind,
+            status: c.status.as_str().to_string(),
+            detected_at: c.detected_at.to_rfc3339(),
+            resolved_at: c.resolved_at.map(|t| t.to_rfc3339()),
+            has_cookies: c.cookies.is_some(),
+        }
+    }
+}
+
+/// Query parameters for listing challenges.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ChallengeListParams {
+    /// Filter by status (pending, solved, dismissed)
+    pub status: Option<String>,
+    /// Maximum results (default 100)
+    pub limit: Option<usize>,
+}
+
+/// Solve challenge request.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SolveChallengeRequest {
+    /// `Cookie` header value from a browser that passed the challenge
+    /// (e.g. "cf_clearance=abc; session=xyz")
+    pub cookies: String,
+}
+
+/// List detected challenges, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/challenges",
+    params(ChallengeListParams),
+    responses(
+        (status = 200, description = "Challenges", body = Vec<ChallengeResponse>),
+        (status = 400, description = "Unknown status")
+    ),
+    tag = "Challenges"
+)]
+pub async fn list_challenges(
+    State(state): State<AppState>,
+    Query(params): Query<ChallengeListParams>,
+) -> impl IntoResponse {
+    let status = match params.status.as_deref() {
+        None => None,
+        Some(s) => match ChallengeStatus::from_str(s) {
+            Some(status) => Some(status),
+            None => {
+                return bad_request("status must be pending, solved, or dismissed").into_response()
+            }
+        },
+    };
+
+    match state
+        .crawl_repo
+        .list_challenges(status, params.limit.unwrap_or(DEFAULT_LIMIT))
+        .await
+    {
+        Ok(challenges) => {
+            let items: Vec<ChallengeResponse> = challenges.into_iter().map(Into::into).collect();
+            ApiResponse::ok(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Mark a pending challenge solved, resuming its domain with the given cookies.
+#[utoipa::path(
+    post,
+    path = "/api/challenges/{id}/solve",
+    params(("id" = i64, Path, description = "Challenge ID")),
+    request_body = SolveChallengeRequest,
+    responses(
+        (status = 200, description = "Solved challenge", body = ChallengeResponse),
+        (status = 400, description = "Empty cookies"),
+        (status = 404, description = "No pending challenge with this ID")
+    ),
+    tag = "Challenges"
+)]
+pub async fn solve_challenge(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<SolveChallengeRequest>,
+) -> impl IntoResponse {
+    let cookies = body.cookies.trim();
+    if cookies.is_empty() {
+        return bad_request("cookies must not be empty").into_response();
+    }
+    resolve(&state, id, ChallengeStatus::Solved, Some(cookies))
+        .await
+        .into_response()
+}
+
+/// Dismiss a pending challenge, resuming its domain without cookies.
+#[utoipa::path(
+    post,
+    path = "/api/challenges/{id}/dismiss",
+    params(("id" = i64, Path, description = "Challenge ID")),
+    responses(
+        (status = 200, description = "Dismissed challenge", body = ChallengeResponse),
+        (status = 404, description = "No pending challenge with this ID")
+    ),
+    tag = "Challenges"
+)]
+pub async fn dismiss_challenge(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    resolve(&state, id, ChallengeStatus::Dismissed, None)
+        .await
+        .into_response()
+}
+
+async fn resolve(
+    state: &AppState,
+    id: i64,
+    status: ChallengeStatus,
+    cookies: Option<&str>,
+) -> axum::response::Response {
+    match state
+        .crawl_repo
+        .resolve_challenge(id, status, cookies)
+        .await
+    {
+        Ok(false) => return not_found("No pending challenge with this ID").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+        Ok(true) => {}
+    }
+
+    match state.crawl_repo.get_challenge(id).await {
+        Ok(Some(challenge)) => ApiResponse::ok(ChallengeResponse::from(challenge)).into_response(),
+        Ok(None) => not_found("Challenge not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
@@ -0,0 +1,35 @@
+//! Config admin page: saved versions of the scraper configs, with diff,
+//! rollback and editing.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Extension,
+};
+
+use super::super::i18n::I18n;
+use super::super::template_structs::ConfigTemplate;
+use super::super::AppState;
+use super::access::Viewer;
+
+/// Page where operators review and change the scraper configs.
+pub async fn config_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let template = ConfigTemplate {
+        title: &i18n.t("title-config"),
+        theme: &theme,
+        i18n: &i18n,
+        is_internal: viewer.is_internal(),
+        read_only: state.read_only,
+    };
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
@@ -0,0 +1,370 @@
+//! Config API: saved versions of the scraper configs, diffs between
+//! them, rollback, and validated edits of one source's config.
+//!
+//! Every endpoint takes the access token. Changes are recorded as new
+//! versions, and the server and daemons pick them up at their next config
+//! reload.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::access::Viewer;
+use super::api_types::ApiResponse;
+use super::helpers::{internal_error, not_found};
+use foia::services::config_history::{self, ConfigDiff, ConfigHistoryError, HistoryItem};
+
+/// A saved version of the scraper configs.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigVersionItem {
+    pub hash: String,
+    pub created_at: String,
+    /// Scraper configs in the version (`null` if it can't be read).
+    pub sources: Option<usize>,
+    /// Whether the version matches the configs in use now.
+    pub current: bool,
+}
+
+impl From<HistoryItem> for ConfigVersionItem {
+    fn from(item: HistoryItem) -> Self {
+        Self {
+            hash: item.hash,
+            created_at: item.created_at.to_rfc3339(),
+            sources: item.sources,
+            current: item.current,
+        }
+    }
+}
+
+/// What changed between two versions.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigDiffItem {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    /// Line diff of the two versions: `@@ -old +new @@` hunk headers,
+    /// then `-` removed and `+` added lines. Empty when they match.
+    pub lines: String,
+}
+
+impl From<ConfigDiff> for ConfigDiffItem {
+    fn from(diff: ConfigDiff) -> Self {
+        Self {
+            added: diff.added,
+            removed: diff.removed,
+            changed: diff.changed,
+            lines: diff.lines,
+        }
+    }
+}
+
+/// One version with its scraper configs and how it differs from another.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigVersionDetail {
+    pub hash: String,
+    pub created_at: String,
+    /// Scraper configs by source ID.
+    #[schema(value_type = Object)]
+    pub scrapers: serde_json::Value,
+    /// Changes from this version to the one compared against.
+    pub diff: ConfigDiffItem,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ConfigVersionParams {
+    /// Hash (or prefix) of the version to compare with (default: the
+    /// configs in use now)
+    pub against: Option<String>,
+}
+
+/// Request body for `POST /api/config/rollback`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConfigRollbackBody {
+    /// Hash (or prefix of at least 4 hex digits) of the version to restore.
+    pub hash: String,
+}
+
+/// Result of a rollback.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigRollbackItem {
+    /// Version restored.
+    pub hash: String,
+    /// What the rollback changed.
+    pub diff: ConfigDiffItem,
+}
+
+/// Scraper configs in use now.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScraperConfigsItem {
+    /// Scraper configs by source ID.
+    #[schema(value_type = Object)]
+    pub scrapers: serde_json::Value,
+}
+
+/// One source's scraper config.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScraperConfigItem {
+    pub source_id: String,
+    #[schema(value_type = Object)]
+    pub config: serde_json::Value,
+}
+
+/// Request body for `PUT /api/config/scrapers/{source_id}`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScraperConfigBody {
+    /// The new scraper config, as `foia config get <source_id>` shows it.
+    #[schema(value_type = Object)]
+    pub config: serde_json::Value,
+}
+
+/// Result of saving a scraper config.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScraperSaveItem {
+    pub source_id: String,
+    /// Hash of the version recorded; `null` when nothing changed.
+    pub hash: Option<String>,
+}
+
+/// Saved versions of the scraper configs, most recent first.
+#[utoipa::path(
+    get,
+    path = "/api/config/history",
+    responses(
+        (status = 200, description = "Versions", body = Vec<ConfigVersionItem>),
+        (status = 403, description = "Access token required")
+    ),
+    tag = "Config"
+)]
+pub async fn list_config_history(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return operators_only().into_response();
+    }
+    match config_history::list(&state.scraper_configs, &state.config_history).await {
+        Ok(items) => {
+            let items: Vec<ConfigVersionItem> = items.into_iter().map(Into::into).collect();
+            ApiResponse::ok(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// One version's scraper configs, with a diff against the configs in use
+/// now or another version.
+#[utoipa::path(
+    get,
+    path = "/api/config/history/{hash}",
+    params(
+        ("hash" = String, Path, description = "Hash, or a prefix of at least 4 hex digits"),
+        ConfigVersionParams
+    ),
+    responses(
+        (status = 200, description = "Version", body = ConfigVersionDetail),
+        (status = 400, description = "Not a hash, ambiguous, or unreadable"),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Version not found")
+    ),
+    tag = "Config"
+)]
+pub async fn get_config_version(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(hash): Path<String>,
+    Query(params): Query<ConfigVersionParams>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return operators_only().into_response();
+    }
+    let entry = match config_history::find(&state.config_history, &hash).await {
+        Ok(entry) => entry,
+        Err(e) => return history_error(e).into_response(),
+    };
+    let scrapers = match config_history::parse(&entry) {
+        Ok(scrapers) => scrapers,
+        Err(e) => return history_error(e).into_response(),
+    };
+    let other = match params.against.as_deref() {
+        Some(against) => match config_history::find(&state.config_history, against).await {
+            Ok(other) => config_history::parse(&other),
+            Err(e) => Err(e),
+        },
+        None => config_history::current(&state.scraper_configs)
+            .await
+            .map_err(Into::into),
+    };
+    let other = match other {
+        Ok(other) => other,
+        Err(e) => return history_error(e).into_response(),
+    };
+
+    ApiResponse::ok(ConfigVersionDetail {
+        diff: ConfigDiff::between(&scrapers, &other).into(),
+        hash: entry.hash,
+        created_at: entry.created_at.to_rfc3339(),
+        scrapers: serde_json::to_value(&scrapers).unwrap_or_default(),
+    })
+    .into_response()
+}
+
+/// Restore the scraper configs of a saved version. Sources added since
+/// are removed. The rollback is recorded as a new version.
+#[utoipa::path(
+    post,
+    path = "/api/config/rollback",
+    request_body = ConfigRollbackBody,
+    responses(
+        (status = 200, description = "Rolled back", body = ConfigRollbackItem),
+        (status = 400, description = "Not a hash, ambiguous, or unreadable"),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Version not found")
+    ),
+    tag = "Config"
+)]
+pub async fn rollback_config(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Json(body): Json<ConfigRollbackBody>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return operators_only().into_response();
+    }
+    let result =
+        config_history::rollback(&state.scraper_configs, &state.config_history, &body.hash).await;
+    match result {
+        Ok(rollback) => {
+            tracing::info!(
+                "Config rolled back to {}",
+                config_history::short_hash(&rollback.hash)
+            );
+            ApiResponse::ok(ConfigRollbackItem {
+                hash: rollback.hash,
+                diff: rollback.diff.into(),
+            })
+            .into_response()
+        }
+        Err(e) => history_error(e).into_response(),
+    }
+}
+
+/// Scraper configs in use now, by source ID.
+#[utoipa::path(
+    get,
+    path = "/api/config/scrapers",
+    responses(
+        (status = 200, description = "Scraper configs", body = ScraperConfigsItem),
+        (status = 403, description = "Access token required")
+    ),
+    tag = "Config"
+)]
+pub async fn list_scraper_configs(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return operators_only().into_response();
+    }
+    match config_history::current(&state.scraper_configs).await {
+        Ok(scrapers) => ApiResponse::ok(ScraperConfigsItem {
+            scrapers: serde_json::to_value(&scrapers).unwrap_or_default(),
+        })
+        .into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// One source's scraper config.
+#[utoipa::path(
+    get,
+    path = "/api/config/scrapers/{source_id}",
+    params(("source_id" = String, Path, description = "Source ID")),
+    responses(
+        (status = 200, description = "Scraper config", body = ScraperConfigItem),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "No config for this source")
+    ),
+    tag = "Config"
+)]
+pub async fn get_scraper_config(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(source_id): Path<String>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return operators_only().into_response();
+    }
+    match state.scraper_configs.get(&source_id).await {
+        Ok(Some(config)) => ApiResponse::ok(ScraperConfigItem {
+            config: serde_json::to_value(&config).unwrap_or_default(),
+            source_id,
+        })
+        .into_response(),
+        Ok(None) => not_found("No scraper config for this source").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Replace a source's scraper config, or add one. The config is checked
+/// before it is saved, and the change is recorded as a new version.
+#[utoipa::path(
+    put,
+    path = "/api/config/scrapers/{source_id}",
+    params(("source_id" = String, Path, description = "Source ID")),
+    request_body = ScraperConfigBody,
+    responses(
+        (status = 200, description = "Saved", body = ScraperSaveItem),
+        (status = 400, description = "Invalid source ID or config"),
+        (status = 403, description = "Access token required")
+    ),
+    tag = "Config"
+)]
+pub async fn update_scraper_config(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(source_id): Path<String>,
+    Json(body): Json<ScraperConfigBody>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return operators_only().into_response();
+    }
+    let config = match config_history::validate_scraper(&source_id, body.config) {
+        Ok(config) => config,
+        Err(e) => return history_error(e).into_response(),
+    };
+    match config_history::save_scraper(
+        &state.scraper_configs,
+        &state.config_history,
+        &source_id,
+        &config,
+    )
+    .await
+    {
+        Ok(hash) => ApiResponse::ok(ScraperSaveItem { source_id, hash }).into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+fn history_error(e: ConfigHistoryError) -> impl IntoResponse {
+    let status = match e {
+        ConfigHistoryError::NotFound(_) => StatusCode::NOT_FOUND,
+        ConfigHistoryError::Ambiguous(_) | ConfigHistoryError::Invalid(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        ConfigHistoryError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    ApiResponse::error(status, e.to_string())
+}
+
+fn operators_only() -> impl IntoResponse {
+    ApiResponse::error(
+        StatusCode::FORBIDDEN,
+        "Viewing and changing the config requires the access token",
+    )
+}
@@ -0,0 +1,144 @@
+//! OCR coverage and quality pages.
+
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse},
+    Extension,
+};
+use serde::Deserialize;
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{
+    CoverageDocRow, CoverageRow, CoverageSourceTemplate, CoverageTab, CoverageTemplate,
+    ErrorTemplate,
+};
+use super::super::AppState;
+use super::access::Viewer;
+use foia::repository::diesel_document::CoverageIssue;
+
+/// Documents listed in a drill-down.
+const DRILL_DOWN_LIMIT: u32 = 200;
+
+/// Per-source text coverage, OCR quality, failures and backends.
+pub async fn coverage_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let coverage = match state.doc_repo.ocr_coverage().await {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = format!("Failed to load coverage: {}", e);
+            let template = ErrorTemplate {
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
+                message: &msg,
+            };
+            return Html(template.render().unwrap_or(msg));
+        }
+    };
+
+    let sources = coverage
+        .into_iter()
+        .filter(|c| viewer.filter().is_none_or(|f| f.shows_source(&c.source_id)))
+        .map(CoverageRow::from)
+        .collect();
+
+    let template = CoverageTemplate {
+        title: &i18n.t("title-coverage"),
+        theme: &theme,
+        i18n: &i18n,
+        sources,
+    };
+
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
+
+/// Query parameters for a coverage drill-down.
+#[derive(Debug, Deserialize)]
+pub struct CoverageSourceParams {
+    /// `no_text` (default), `failed` or `low_quality`
+    pub issue: Option<String>,
+}
+
+/// A source's documents with no text, failed pages or the lowest OCR quality.
+pub async fn coverage_source_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
+    Path(source_id): Path<String>,
+    Query(params): Query<CoverageSourceParams>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let issue = params
+        .issue
+        .as_deref()
+        .and_then(CoverageIssue::from_str)
+        .unwrap_or(CoverageIssue::NoText);
+
+    let documents = if viewer.filter().is_none_or(|f| f.shows_source(&source_id)) {
+        state
+            .doc_repo
+            .coverage_documents(&source_id, issue, DRILL_DOWN_LIMIT)
+            .await
+    } else {
+        Ok(Vec::new())
+    };
+    let documents = match documents {
+        Ok(d) => d,
+        Err(e) => {
+            let msg = format!("Failed to load documents: {}", e);
+            let template = ErrorTemplate {
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
+                message: &msg,
+            };
+            return Html(template.render().unwrap_or(msg));
+        }
+    };
+
+    let tabs = CoverageIssue::ALL
+        .iter()
+        .map(|&i| CoverageTab {
+            issue: i.as_str(),
+            label: match i {
+                CoverageIssue::NoText => "No text",
+                CoverageIssue::Failed => "Failed pages",
+                CoverageIssue::LowQuality => "Lowest quality",
+            },
+            selected: i == issue,
+        })
+        .collect();
+    let truncated = documents.len() as u32 == DRILL_DOWN_LIMIT;
+    let documents = documents
+        .into_iter()
+        .filter(|d| viewer.allows(&d.id, &source_id))
+        .map(CoverageDocRow::from)
+        .collect();
+
+    let title = i18n.t1("title-coverage-source", "source", &source_id);
+    let template = CoverageSourceTemplate {
+        title: &title,
+        theme: &theme,
+        i18n: &i18n,
+        source_id,
+        tabs,
+        documents,
+        limit: DRILL_DOWN_LIMIT,
+        truncated,
+    };
+
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
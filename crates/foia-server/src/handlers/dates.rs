@@ -0,0 +1,77 @@
+//! Date review queue page.
+
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    response::{Html, IntoResponse},
+    Extension,
+};
+use serde::Deserialize;
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{DateQueueTemplate, ErrorTemplate, SourceOption};
+use super::super::AppState;
+use foia::repository::DieselError;
+
+/// Query parameters for the date review page.
+#[derive(Debug, Deserialize)]
+pub struct DateQueuePageParams {
+    pub source: Option<String>,
+}
+
+/// Keyboard-driven review of documents with missing or uncertain dates.
+pub async fn date_queue_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Query(params): Query<DateQueuePageParams>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let source = params.source.unwrap_or_default();
+    let filter = Some(source.as_str()).filter(|s| !s.is_empty());
+
+    let loaded = async {
+        let total = state.doc_repo.count_date_review_queue(filter).await?;
+        let sources = state.read_cache.sources(&state.source_repo).await?;
+        Ok::<_, DieselError>((total, sources))
+    }
+    .await;
+    let (total, sources) = match loaded {
+        Ok(r) => r,
+        Err(e) => {
+            let msg = format!("Failed to load date queue: {}", e);
+            let template = ErrorTemplate {
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
+                message: &msg,
+            };
+            return Html(template.render().unwrap_or(msg));
+        }
+    };
+
+    let sources = sources
+        .into_iter()
+        .map(|s| SourceOption {
+            selected: s.id == source,
+            id: s.id,
+            name: s.name,
+            count: 0,
+        })
+        .collect();
+
+    let template = DateQueueTemplate {
+        title: "Date Review",
+        theme: &theme,
+        i18n: &i18n,
+        sources,
+        source,
+        total,
+        read_only: state.read_only,
+    };
+
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
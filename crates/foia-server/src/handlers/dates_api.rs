@@ -0,0 +1,257 @@
+//! Date review API: the queue of documents with uncertain dates and manual
+//! date entry.
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::models::Document;
+use foia::repository::diesel_document::Projection;
+use foia_annotate::services::date_candidates;
+
+/// Default and maximum documents per queue request.
+const DEFAULT_QUEUE_LIMIT: usize = 20;
+const MAX_QUEUE_LIMIT: usize = 100;
+
+/// A date a reviewer can pick with one keystroke.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DateCandidate {
+    /// YYYY-MM-DD
+    pub date: String,
+    /// metadata, server, filename, or content
+    pub source: String,
+    /// high, medium, or low
+    pub confidence: String,
+}
+
+/// A document awaiting date review.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DateQueueItem {
+    pub id: String,
+    pub title: String,
+    pub source_id: String,
+    pub source_url: String,
+    pub mime_type: Option<String>,
+    /// Stored file, for showing images that have no rendered pages
+    pub file_url: Option<String>,
+    /// Current automatic estimate, if any
+    pub estimated_date: Option<String>,
+    pub estimated_confidence: Option<String>,
+    /// Most trusted first
+    pub candidates: Vec<DateCandidate>,
+}
+
+/// One page of the date review queue.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DateQueueResponse {
+    pub items: Vec<DateQueueItem>,
+    /// Documents awaiting review in total
+    pub total: u64,
+    /// Pass as `after` to fetch the next page; absent on the last page
+    pub next_after: Option<String>,
+}
+
+/// Query parameters for the date review queue.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DateQueueParams {
+    /// Only documents from this source
+    pub source: Option<String>,
+    /// Continue after this document ID
+    pub after: Option<String>,
+    /// Documents to return (default 20, max 100)
+    pub limit: Option<usize>,
+}
+
+/// Set manual date request.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetDateRequest {
+    /// YYYY-MM-DD, or null when the document has no determinable date
+    pub date: Option<String>,
+}
+
+/// Manual date after an update.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ManualDateResponse {
+    pub document_id: String,
+    pub manual_date: Option<String>,
+}
+
+/// Build a queue item with candidate dates from the version, metadata and
+/// the first page's text.
+async fn queue_item(state: &AppState, doc: Document) -> DateQueueItem {
+    let version = doc.current_version();
+    let text = match version {
+        Some(v) => state
+            .doc_repo
+            .get_page(&doc.id, v.id as i32, 1)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|p| p.final_text.or(p.ocr_text).or(p.pdf_text)),
+        None => None,
+    };
+    let candidates = date_candidates(
+        &doc.metadata,
+        version.and_then(|v| v.server_date),
+        version.map(|v| v.acquired_at).unwrap_or(doc.created_at),
+        version.and_then(|v| v.original_filename.as_deref()),
+        Some(&doc.source_url),
+        text.as_deref(),
+    )
+    .into_iter()
+    .map(|c| DateCandidate {
+        date: c.date.format("%Y-%m-%d").to_string(),
+        source: c.source.as_str().to_string(),
+        confidence: c.confidence.as_str().to_string(),
+    })
+    .collect();
+
+    let estimate = &doc.metadata["estimated_date"];
+    DateQueueItem {
+        mime_type: version.map(|v| v.mime_type.clone()),
+        file_url: version.map(|v| v.file_url(&doc.source_url, &doc.title)),
+        estimated_date: estimate["date"]
+            .as_str()
+            .map(|d| d.get(..10).unwrap_or(d).to_string()),
+        estimated_confidence: estimate["confidence"].as_str().map(str::to_string),
+        candidates,
+        id: doc.id,
+        title: doc.title,
+        source_id: doc.source_id,
+        source_url: doc.source_url,
+    }
+}
+
+/// List documents whose date is missing or low-confidence and not yet reviewed.
+#[utoipa::path(
+    get,
+    path = "/api/dates/queue",
+    params(DateQueueParams),
+    responses(
+        (status = 200, description = "Documents awaiting date review", body = DateQueueResponse)
+    ),
+    tag = "Dates"
+)]
+pub async fn date_queue(
+    State(state): State<AppState>,
+    Query(params): Query<DateQueueParams>,
+) -> impl IntoResponse {
+    let source = params.source.as_deref().filter(|s| !s.is_empty());
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_QUEUE_LIMIT)
+        .clamp(1, MAX_QUEUE_LIMIT);
+
+    let ids = match state
+        .doc_repo
+        .get_date_review_queue(source, params.after.as_deref(), limit)
+        .await
+    {
+        Ok(ids) => ids,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let total = match state.doc_repo.count_date_review_queue(source).await {
+        Ok(n) => n,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let next_after = (ids.len() == limit).then(|| ids.last().cloned()).flatten();
+
+    let docs = match state
+        .doc_repo
+        .get_batch_projected(&ids, Projection::Metadata)
+        .await
+    {
+        Ok(docs) => docs,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let mut items = Vec::with_capacity(docs.len());
+    for doc in docs {
+        items.push(queue_item(&state, doc).await);
+    }
+    items.sort_by(|a, b| a.id.cmp(&b.id));
+
+    ApiResponse::ok(DateQueueResponse {
+        items,
+        total,
+        next_after,
+    })
+    .into_response()
+}
+
+/// Set a document's date by hand, or record that it has none.
+#[utoipa::path(
+    put,
+    path = "/api/documents/{doc_id}/date",
+    params(("doc_id" = String, Path, description = "Document ID")),
+    request_body = SetDateRequest,
+    responses(
+        (status = 200, description = "Date recorded", body = ManualDateResponse),
+        (status = 400, description = "Invalid date"),
+        (status = 404, description = "Document not found")
+    ),
+    tag = "Dates"
+)]
+pub async fn set_document_date(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+    Json(body): Json<SetDateRequest>,
+) -> impl IntoResponse {
+    let date = match body.date.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(s) => match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(d) => Some(d),
+            Err(_) => {
+                return bad_request(&format!("Invalid date '{}' (expected YYYY-MM-DD)", s))
+                    .into_response()
+            }
+        },
+    };
+
+    let result = state.doc_repo.set_manual_date(&doc_id, date).await;
+    state.read_cache.invalidate_document(&doc_id).await;
+    match result {
+        Ok(true) => ApiResponse::ok(ManualDateResponse {
+            document_id: doc_id,
+            manual_date: date.map(|d| d.format("%Y-%m-%d").to_string()),
+        })
+        .into_response(),
+        Ok(false) => not_found("Document not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Remove a manual date, returning the document to the review queue.
+#[utoipa::path(
+    delete,
+    path = "/api/documents/{doc_id}/date",
+    params(("doc_id" = String, Path, description = "Document ID")),
+    responses(
+        (status = 200, description = "Manual date cleared", body = ManualDateResponse),
+        (status = 404, description = "Document not found")
+    ),
+    tag = "Dates"
+)]
+pub async fn clear_document_date(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+) -> impl IntoResponse {
+    let result = state.doc_repo.clear_manual_date(&doc_id).await;
+    state.read_cache.invalidate_document(&doc_id).await;
+    match result {
+        Ok(true) => ApiResponse::ok(ManualDateResponse {
+            document_id: doc_id,
+            manual_date: None,
+        })
+        .into_response(),
+        Ok(false) => not_found("Document not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
@@ -5,14 +5,20 @@ use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     response::{Html, IntoResponse},
+    Extension,
 };
 use serde::Deserialize;
 
+use super::super::i18n::I18n;
 use super::super::template_structs::{
-    DocumentDetailTemplate, ErrorTemplate, VersionItem, VirtualFileRow,
+    DocumentDetailTemplate, ErrorTemplate, RelatedRow, TranscriptSegment, VersionItem,
+    VirtualFileRow,
 };
 use super::super::AppState;
+use super::access::Viewer;
 use super::helpers::{find_sources_with_hash, VersionInfo};
+use super::sheets::{load_sheets, sheet_previews};
+use foia::repository::diesel_document::Projection;
 use foia::utils::format_size;
 
 /// Query params for document detail navigation context.
@@ -27,15 +33,20 @@ pub struct DocumentDetailParams {
 /// Document detail page.
 pub async fn document_detail(
     State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
     Path(doc_id): Path<String>,
     Query(params): Query<DocumentDetailParams>,
 ) -> impl IntoResponse {
+    let theme = state.theme().await;
     let doc = match state.doc_repo.get(&doc_id).await {
         Ok(Some(d)) => d,
         Ok(None) => {
             let template = ErrorTemplate {
-                title: "Not Found",
-                message: "Document not found.",
+                title: &i18n.t("title-not-found"),
+                theme: &theme,
+                i18n: &i18n,
+                message: &i18n.t("error-document-not-found"),
             };
             return Html(
                 template
@@ -46,7 +57,9 @@ pub async fn document_detail(
         Err(e) => {
             let msg = format!("Failed to load document: {}", e);
             let template = ErrorTemplate {
-                title: "Error",
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
                 message: &msg,
             };
             return Html(template.render().unwrap_or(msg));
@@ -55,8 +68,8 @@ pub async fn document_detail(
 
     let source_for_nav = params.source.as_deref().unwrap_or("");
     let navigation = state
-        .doc_repo
-        .get_document_navigation(&doc_id, source_for_nav)
+        .read_cache
+        .navigation(&state.doc_repo, &doc_id, source_for_nav)
         .await
         .ok();
 
@@ -81,6 +94,12 @@ pub async fn document_detail(
         }
     };
 
+    let lost_files = state
+        .doc_repo
+        .get_lost_files(&doc_id)
+        .await
+        .unwrap_or_default();
+
     let versions: Vec<VersionItem> = doc
         .versions
         .iter()
@@ -92,19 +111,23 @@ pub async fn document_detail(
 
             let date_str = v
                 .server_date
-                .map(|dt| dt.format("%Y-%m-%d").to_string())
-                .unwrap_or_else(|| v.acquired_at.format("%Y-%m-%d").to_string());
+                .map(|dt| i18n.format_date(&dt))
+                .unwrap_or_else(|| i18n.format_date(&v.acquired_at));
 
             let filename = v
                 .original_filename
                 .clone()
                 .unwrap_or_else(|| "unknown".to_string());
 
+            let lost = lost_files.iter().find(|l| l.version_id == v.id);
+
             VersionItem {
                 path: relative_path,
                 filename,
                 size_str: format_size(v.file_size),
                 date_str,
+                lost: lost.is_some(),
+                lost_reason: lost.map(|l| l.reason.clone()).unwrap_or_default(),
             }
         })
         .collect();
@@ -131,11 +154,35 @@ pub async fn document_detail(
         vec![]
     };
 
+    let related: Vec<RelatedRow> = state
+        .doc_repo
+        .get_relations(&doc_id)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(RelatedRow::from_related)
+        .collect();
+
     let page_count: Option<u32> = match current_version_id {
         Some(vid) => state.doc_repo.count_pages(&doc_id, vid as i32).await.ok(),
         None => None,
     };
 
+    // Audio/video documents get a player, plus the timed transcript if transcribed
+    let media = current_version
+        .filter(|v| v.mime_type.starts_with("audio/") || v.mime_type.starts_with("video/"));
+    let segments = match media {
+        Some(v) => load_transcript_segments(&state, &doc_id, v.id).await,
+        None => vec![],
+    };
+
+    // CSV files and spreadsheets get a table preview
+    let sheets = if versions.first().is_some_and(|v| v.lost) {
+        vec![]
+    } else {
+        sheet_previews(&load_sheets(&state, &doc).await)
+    };
+
     // Navigation helpers
     let (has_prev, prev_id_val, prev_title_val, prev_title_truncated) =
         if let Some(ref nav) = navigation {
@@ -173,9 +220,12 @@ pub async fn document_detail(
 
     let template = DocumentDetailTemplate {
         title: &doc.title,
+        theme: &theme,
+        i18n: &i18n,
         doc_id: &doc.id,
         source_id: &doc.source_id,
         source_url: &doc.source_url,
+        current_lost: versions.first().is_some_and(|v| v.lost),
         versions,
         has_versions: !doc.versions.is_empty(),
         other_sources,
@@ -193,6 +243,7 @@ pub async fn document_detail(
         virtual_files: virtual_files.clone(),
         has_virtual_files: !virtual_files.is_empty(),
         virtual_files_count: virtual_files.len(),
+        related,
         has_prev,
         prev_id_val,
         prev_title_val,
@@ -207,6 +258,21 @@ pub async fn document_detail(
         has_pages: page_count.is_some() && page_count.unwrap() > 0,
         page_count_val: page_count.unwrap_or(0),
         version_id_val: current_version_id.unwrap_or(0),
+        can_edit_text: viewer.is_internal() && !state.read_only,
+        has_media: media.is_some(),
+        media_is_video: media.is_some_and(|v| v.mime_type.starts_with("video/")),
+        media_path: media
+            .map(|v| {
+                v.compute_storage_path(&doc.source_url, &doc.title)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .unwrap_or_default(),
+        media_mime: media.map(|v| v.mime_type.clone()).unwrap_or_default(),
+        has_segments: !segments.is_empty(),
+        segments,
+        search_query: params.q.clone().unwrap_or_default(),
+        sheets,
     };
 
     Html(
@@ -216,12 +282,60 @@ pub async fn document_detail(
     )
 }
 
+/// Timed segments from the latest successful Whisper transcription.
+async fn load_transcript_segments(
+    state: &AppState,
+    doc_id: &str,
+    version_id: i64,
+) -> Vec<TranscriptSegment> {
+    let results = state
+        .doc_repo
+        .get_analysis_results_by_type(doc_id, version_id as i32, "whisper")
+        .await
+        .unwrap_or_default();
+
+    results
+        .iter()
+        .filter(|r| r.error.is_none())
+        .find_map(|r| r.metadata.as_ref()?["segments"].as_array())
+        .map(|segments| {
+            segments
+                .iter()
+                .filter_map(|seg| {
+                    let start = seg["start"].as_f64()?;
+                    let text = seg["text"].as_str()?.trim();
+                    (!text.is_empty()).then(|| TranscriptSegment {
+                        start_secs: start,
+                        timestamp: format_timestamp(start),
+                        text: text.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Format a media offset as `m:ss`, or `h:mm:ss` past the hour.
+fn format_timestamp(secs: f64) -> String {
+    let total = secs.max(0.0) as u64;
+    let (h, m, s) = (total / 3600, (total / 60) % 60, total % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
 /// Get document versions as JSON.
 pub async fn document_versions(
     State(state): State<AppState>,
     Path(doc_id): Path<String>,
 ) -> impl IntoResponse {
-    let doc = match state.doc_repo.get(&doc_id).await {
+    let doc = match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
         Ok(Some(d)) => d,
         Ok(None) => {
             return (StatusCode::NOT_FOUND, "Document not found").into_response();
@@ -3,24 +3,26 @@
 use axum::{
     extract::{Path, Query, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
 use super::super::AppState;
+use super::access::Viewer;
 use super::api_types::ApiResponse;
 use super::helpers::{
-    internal_error, not_found, paginate, parse_csv_param, DocumentSummary, PaginatedResponse,
+    bad_request, decode_cursor, internal_error, not_found, paginate, parse_csv_param,
+    trim_extra_row, DocumentSummary, PaginatedResponse,
 };
-use foia::repository::diesel_document::BrowseParams;
+use foia::repository::diesel_document::{BrowseCursor, BrowseParams, Keyset, Projection};
 
 /// Query parameters for document search/listing.
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct DocumentsQuery {
     /// Filter by source ID
     pub source: Option<String>,
-    /// Filter by document status (pending, downloaded, ocr_complete, indexed, failed)
+    /// Filter by document status (pending, downloaded, text_extracted, ocr_complete, indexed, failed)
     pub status: Option<String>,
     /// Filter by MIME type categories (comma-separated: documents,spreadsheets,images)
     pub types: Option<String>,
@@ -36,6 +38,11 @@ pub struct DocumentsQuery {
     pub sort: Option<String>,
     /// Sort order (asc, desc)
     pub order: Option<String>,
+    /// Cursor from `next_cursor`: return the page after it (replaces `page`,
+    /// updated_at sort only)
+    pub after: Option<String>,
+    /// Cursor from `prev_cursor`: return the page before it
+    pub before: Option<String>,
 }
 
 /// List/search documents with filters and pagination.
@@ -50,13 +57,32 @@ pub struct DocumentsQuery {
 )]
 pub async fn list_documents(
     State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<DocumentsQuery>,
 ) -> impl IntoResponse {
     let (page, per_page, offset) = paginate(params.page, params.per_page);
     let types = parse_csv_param(params.types.as_ref());
     let tags = parse_csv_param(params.tags.as_ref());
 
-    let documents = match state
+    let cursor = match decode_cursor(params.after.as_deref(), params.before.as_deref()) {
+        Ok(cursor) => cursor,
+        Err(msg) => return bad_request(msg).into_response(),
+    };
+    let keyset_sort = matches!(params.sort.as_deref(), None | Some("updated_at"));
+    if cursor.is_some() && !keyset_sort {
+        return bad_request("Cursors require the updated_at sort").into_response();
+    }
+    let backward = cursor.as_ref().is_some_and(|(_, b)| *b);
+    let keyset = cursor.as_ref().map(|(c, backward)| {
+        if *backward {
+            Keyset::Before(c)
+        } else {
+            Keyset::After(c)
+        }
+    });
+
+    // One extra row tells us whether another page follows
+    let mut documents = match state
         .doc_repo
         .browse(BrowseParams {
             source_id: params.source.as_deref(),
@@ -66,30 +92,58 @@ pub async fn list_documents(
             search_query: params.q.as_deref(),
             sort_field: params.sort.as_deref(),
             sort_order: params.order.as_deref(),
-            limit: per_page as u32,
+            limit: per_page as u32 + 1,
             offset: offset as u32,
+            keyset,
+            projection: Projection::Metadata,
+            access: viewer.filter(),
         })
         .await
     {
         Ok(docs) => docs,
         Err(e) => return internal_error(e).into_response(),
     };
+    let has_more = trim_extra_row(&mut documents, per_page, backward);
+
+    let (prev_cursor, next_cursor) = if keyset_sort {
+        let first = documents
+            .first()
+            .map(|d| BrowseCursor::for_document(d).encode());
+        let last = documents
+            .last()
+            .map(|d| BrowseCursor::for_document(d).encode());
+        let has_prev = if cursor.is_some() {
+            !backward || has_more
+        } else {
+            page > 1
+        };
+        let has_next = backward || has_more;
+        (first.filter(|_| has_prev), last.filter(|_| has_next))
+    } else {
+        (None, None)
+    };
 
     let total = state
         .doc_repo
         .browse_count(
-            params.source.as_deref(),
+            params.source.as_deref().into(),
             params.status.as_deref(),
             &types,
             &tags,
+            &[],
+            &[],
             params.q.as_deref(),
+            viewer.filter(),
         )
         .await
         .unwrap_or(documents.len() as u64);
 
     let items: Vec<DocumentSummary> = documents.into_iter().map(DocumentSummary::from).collect();
 
-    Json(PaginatedResponse::new(items, page, per_page, total)).into_response()
+    Json(
+        PaginatedResponse::new(items, page, per_page, total).with_cursors(prev_cursor, next_cursor),
+    )
+    .into_response()
 }
 
 /// Get a single document by ID.
@@ -107,7 +161,7 @@ pub async fn get_document(
     State(state): State<AppState>,
     Path(doc_id): Path<String>,
 ) -> impl IntoResponse {
-    match state.doc_repo.get(&doc_id).await {
+    match state.read_cache.document(&state.doc_repo, &doc_id).await {
         Ok(Some(doc)) => ApiResponse::ok(DocumentSummary::from(doc)).into_response(),
         Ok(None) => not_found("Document not found").into_response(),
         Err(e) => internal_error(e).into_response(),
@@ -4,22 +4,30 @@ use askama::Template;
 use axum::{
     extract::State,
     response::{Html, IntoResponse},
+    Extension,
 };
 use std::collections::HashMap;
 
+use super::super::i18n::I18n;
 use super::super::template_structs::{
     DuplicateDoc, DuplicateGroup, DuplicatesTemplate, ErrorTemplate,
 };
 use super::super::AppState;
 
 /// List documents that exist in multiple sources.
-pub async fn list_duplicates(State(state): State<AppState>) -> impl IntoResponse {
+pub async fn list_duplicates(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
     let hashes = match state.doc_repo.get_content_hashes().await {
         Ok(h) => h,
         Err(e) => {
             let msg = format!("Failed to load documents: {}", e);
             let template = ErrorTemplate {
-                title: "Error",
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
                 message: &msg,
             };
             return Html(template.render().unwrap_or(msg));
@@ -57,6 +65,8 @@ pub async fn list_duplicates(State(state): State<AppState>) -> impl IntoResponse
 
     let template = DuplicatesTemplate {
         title: "Cross-Source Duplicates",
+        theme: &theme,
+        i18n: &i18n,
         has_duplicates: !duplicates.is_empty(),
         duplicates,
     };
@@ -3,15 +3,17 @@
 use axum::{
     extract::{Path, Query, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
 use super::super::AppState;
+use super::access::Viewer;
 use super::api_types::ApiResponse;
 use super::helpers::{bad_request, internal_error, not_found, paginate, PaginatedResponse};
 use foia::repository::diesel_document::entities::EntityFilter;
+use foia::repository::diesel_document::Projection;
 #[cfg(feature = "gis")]
 use foia::services::geolookup;
 
@@ -108,16 +110,17 @@ pub struct GeocodedLocation {
 )]
 pub async fn search_entities(
     State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<EntitySearchQuery>,
 ) -> impl IntoResponse {
     if let Some(near_str) = &params.near {
-        return handle_near_query(&state, near_str, &params).await;
+        return handle_near_query(&state, &viewer, near_str, &params).await;
     }
 
     if let Some(near_loc) = &params.near_location {
         #[cfg(feature = "gis")]
         {
-            return handle_near_location_query(&state, near_loc, &params).await;
+            return handle_near_location_query(&state, &viewer, near_loc, &params).await;
         }
         #[cfg(not(feature = "gis"))]
         {
@@ -187,7 +190,7 @@ pub async fn search_entities(
         Err(e) => return internal_error(e).into_response(),
     };
 
-    let items = match build_search_results(&state, &doc_ids).await {
+    let items = match build_search_results(&state, &viewer, &doc_ids).await {
         Ok(items) => items,
         Err(e) => return internal_error(e).into_response(),
     };
@@ -264,7 +267,11 @@ pub async fn document_entities(
     State(state): State<AppState>,
     Path(doc_id): Path<String>,
 ) -> impl IntoResponse {
-    match state.doc_repo.get(&doc_id).await {
+    match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
         Ok(None) => return not_found("Document not found").into_response(),
         Err(e) => return internal_error(e).into_response(),
         Ok(Some(_)) => {}
@@ -328,6 +335,7 @@ pub async fn entity_locations(
 
 async fn handle_near_query(
     state: &AppState,
+    viewer: &Viewer,
     near_str: &str,
     params: &EntitySearchQuery,
 ) -> axum::response::Response {
@@ -369,7 +377,7 @@ async fn handle_near_query(
         Err(e) => return internal_error(e).into_response(),
     };
 
-    let items = match build_search_results(state, &doc_ids).await {
+    let items = match build_search_results(state, viewer, &doc_ids).await {
         Ok(items) => items,
         Err(e) => return internal_error(e).into_response(),
     };
@@ -380,6 +388,7 @@ async fn handle_near_query(
 #[cfg(feature = "gis")]
 async fn handle_near_location_query(
     state: &AppState,
+    viewer: &Viewer,
     near_loc: &str,
     params: &EntitySearchQuery,
 ) -> axum::response::Response {
@@ -430,7 +439,7 @@ async fn handle_near_location_query(
         Err(e) => return internal_error(e).into_response(),
     };
 
-    let items = match build_search_results(state, &doc_ids).await {
+    let items = match build_search_results(state, viewer, &doc_ids).await {
         Ok(items) => items,
         Err(e) => return internal_error(e).into_response(),
     };
@@ -440,6 +449,7 @@ async fn handle_near_location_query(
 
 async fn build_search_results(
     state: &AppState,
+    viewer: &Viewer,
     doc_ids: &[String],
 ) -> Result<Vec<EntitySearchResult>, Box<dyn std::error::Error + Send + Sync>> {
     if doc_ids.is_empty() {
@@ -454,10 +464,14 @@ async fn build_search_results(
 
     let mut results = Vec::with_capacity(doc_ids.len());
     for id in doc_ids {
-        let (title, source_id) = match state.doc_repo.get(id).await {
+        let (title, source_id) = match state.doc_repo.get_projected(id, Projection::Metadata).await
+        {
             Ok(Some(doc)) => (doc.title, doc.source_id),
             _ => (id.clone(), String::new()),
         };
+        if !viewer.allows(id, &source_id) {
+            continue;
+        }
 
         let matched_entities = entities_map
             .get(id)
@@ -0,0 +1,178 @@
+//! FOIA exemption API endpoints: citation statistics and per-page markings.
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::helpers::{bad_request, internal_error};
+use foia::repository::diesel_document::StatsInterval;
+use foia::services::exemptions::normalize_code;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExemptionStatsQuery {
+    /// Filter by source
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExemptionCount {
+    /// Normalized exemption code, e.g. `(b)(7)(C)`
+    pub code: String,
+    /// Documents citing it at least once
+    pub documents: u64,
+    /// Pages citing it at least once
+    pub pages: u64,
+    /// Total markings
+    pub markings: u64,
+}
+
+/// Citation counts per exemption, most cited first.
+///
+/// Markings are counted by `foia exemptions extract`.
+#[utoipa::path(
+    get,
+    path = "/api/exemptions/stats",
+    params(ExemptionStatsQuery),
+    responses(
+        (status = 200, description = "Citations per exemption", body = Vec<ExemptionCount>)
+    ),
+    tag = "Exemptions"
+)]
+pub async fn exemption_stats(
+    State(state): State<AppState>,
+    Query(params): Query<ExemptionStatsQuery>,
+) -> impl IntoResponse {
+    match state
+        .doc_repo
+        .get_exemption_stats(params.source.as_deref())
+        .await
+    {
+        Ok(stats) => {
+            let items: Vec<ExemptionCount> = stats
+                .into_iter()
+                .map(|s| ExemptionCount {
+                    code: s.code,
+                    documents: s.documents,
+                    pages: s.pages,
+                    markings: s.markings,
+                })
+                .collect();
+            Json(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExemptionTimelineQuery {
+    /// Filter by source
+    pub source: Option<String>,
+    /// Only this exemption (e.g. `b5`, `(b)(7)(C)`)
+    pub code: Option<String>,
+    /// Bucket size: `year` (default) or `month`
+    pub interval: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExemptionPeriodCount {
+    /// `1998` or `1998-04`
+    pub period: String,
+    pub code: String,
+    pub documents: u64,
+    pub markings: u64,
+}
+
+/// Exemption citations over time, by document publication date.
+///
+/// Documents without a publication date are left out.
+#[utoipa::path(
+    get,
+    path = "/api/exemptions/timeline",
+    params(ExemptionTimelineQuery),
+    responses(
+        (status = 200, description = "Citations per period and exemption", body = Vec<ExemptionPeriodCount>),
+        (status = 400, description = "Invalid exemption code or interval")
+    ),
+    tag = "Exemptions"
+)]
+pub async fn exemption_timeline(
+    State(state): State<AppState>,
+    Query(params): Query<ExemptionTimelineQuery>,
+) -> impl IntoResponse {
+    let code = match params.code.as_deref() {
+        Some(c) => match normalize_code(c) {
+            Some(code) => Some(code),
+            None => return bad_request("Invalid exemption code").into_response(),
+        },
+        None => None,
+    };
+    let interval = match params.interval.as_deref() {
+        Some(i) => match StatsInterval::from_str(i) {
+            Some(interval) => interval,
+            None => return bad_request("interval must be 'year' or 'month'").into_response(),
+        },
+        None => StatsInterval::default(),
+    };
+
+    match state
+        .doc_repo
+        .get_exemption_timeline(params.source.as_deref(), code.as_deref(), interval)
+        .await
+    {
+        Ok(periods) => {
+            let items: Vec<ExemptionPeriodCount> = periods
+                .into_iter()
+                .map(|p| ExemptionPeriodCount {
+                    period: p.period,
+                    code: p.code,
+                    documents: p.documents,
+                    markings: p.markings,
+                })
+                .collect();
+            Json(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PageExemptionResponse {
+    pub page_number: u32,
+    pub code: String,
+    pub count: u32,
+}
+
+/// Exemption markings on each page of a document.
+#[utoipa::path(
+    get,
+    path = "/api/documents/{doc_id}/exemptions",
+    params(("doc_id" = String, Path, description = "Document ID")),
+    responses(
+        (status = 200, description = "Markings per page", body = Vec<PageExemptionResponse>)
+    ),
+    tag = "Exemptions"
+)]
+pub async fn document_exemptions(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+) -> impl IntoResponse {
+    match state.doc_repo.get_exemptions(&doc_id).await {
+        Ok(exemptions) => {
+            let items: Vec<PageExemptionResponse> = exemptions
+                .into_iter()
+                .map(|e| PageExemptionResponse {
+                    page_number: e.page_number,
+                    code: e.code,
+                    count: e.count,
+                })
+                .collect();
+            Json(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
@@ -1,19 +1,36 @@
 //! Export API endpoints for bulk data export.
 
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
 use axum::{
-    body::Body,
+    body::{Body, Bytes},
     extract::{Query, State},
     http::{header, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
 };
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use std::io::Write;
 use utoipa::{IntoParams, ToSchema};
 
 use super::super::AppState;
+use super::access::Viewer;
 use super::api_types::{AnnotationExport, ApiResponse, ExportStatsResponse};
+use super::arrow_ipc::{self, ArrowEncoder, ARROW_STREAM};
 use super::helpers::{internal_error, parse_csv_param};
-use foia::repository::diesel_document::BrowseParams;
+use foia::models::Document;
+use foia::repository::diesel_document::{Projection, StreamFilter};
+use foia::repository::{DieselDocumentRepository, DieselError};
+use foia::services::exports::ExportRun;
+
+/// Documents loaded and encoded per export chunk.
+const EXPORT_CHUNK: usize = 500;
+
+/// Documents whose pages go into one record batch of a page export.
+const PAGE_EXPORT_CHUNK: usize = 50;
+
+const CSV_HEADER: &str = "id,source_id,title,source_url,status,synopsis,tags,created_at,updated_at,mime_type,file_size,page_count,content_hash\n";
 
 /// Export format options.
 #[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, ToSchema)]
@@ -23,12 +40,14 @@ pub enum ExportFormat {
     Json,
     Jsonl,
     Csv,
+    /// Arrow IPC stream, one record batch per chunk of documents
+    Arrow,
 }
 
 /// Query params for export.
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct ExportQuery {
-    /// Export format (json, jsonl, csv)
+    /// Export format (json, jsonl, csv, arrow)
     #[serde(default)]
     pub format: ExportFormat,
     /// Filter by source ID
@@ -40,6 +59,12 @@ pub struct ExportQuery {
     /// Include full text content
     #[serde(default)]
     pub include_text: bool,
+    /// Include user highlights with citation context (json/jsonl only)
+    #[serde(default)]
+    pub include_highlights: bool,
+    /// Only documents changed after this change journal sequence number
+    /// (see `/api/export/changes`)
+    pub changed_since: Option<i64>,
     /// Maximum documents to export (default: 10000)
     pub limit: Option<usize>,
 }
@@ -62,6 +87,21 @@ pub struct ExportDocument {
     pub content_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub extracted_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<Vec<ExportHighlight>>,
+}
+
+/// Highlight export record with enough context to cite the passage.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExportHighlight {
+    pub page_number: i32,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    pub quote: String,
+    pub comment: Option<String>,
+    pub label: Option<String>,
+    /// Human-readable citation, e.g. `"Title", p. 3, https://...`
+    pub citation: String,
 }
 
 /// Export documents in various formats.
@@ -76,30 +116,126 @@ pub struct ExportDocument {
 )]
 pub async fn export_documents(
     State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<ExportQuery>,
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(10_000).min(100_000);
-    let types = parse_csv_param(params.types.as_ref());
-    let tags = parse_csv_param(params.tags.as_ref());
+    let filter = StreamFilter {
+        source_id: params.source.clone(),
+        categories: parse_csv_param(params.types.as_ref()),
+        tags: parse_csv_param(params.tags.as_ref()),
+        projection: if params.include_text {
+            Projection::Full
+        } else {
+            Projection::Metadata
+        },
+        changed_since: params.changed_since,
+        access: viewer.owned_filter(),
+        ..Default::default()
+    };
+    let format = params.format;
+    let include_text = params.include_text;
+    let include_highlights = params.include_highlights;
+    let repo = state.doc_repo.clone();
+
+    if format == ExportFormat::Arrow {
+        let schema = arrow_ipc::documents_schema(include_text);
+        let batch_schema = schema.clone();
+        let batches = state
+            .doc_repo
+            .stream_documents(filter, EXPORT_CHUNK)
+            .take(limit)
+            .chunks(EXPORT_CHUNK)
+            .map(move |chunk| {
+                let documents = chunk
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(std::io::Error::other)?;
+                arrow_ipc::documents_batch(&batch_schema, &documents, include_text)
+                    .map_err(std::io::Error::other)
+            });
+        return arrow_response(schema, batches, "documents.arrows");
+    }
 
-    let documents = match state
+    // Documents are streamed from the database in chunks and encoded as
+    // they arrive, so memory stays bounded regardless of `limit`.
+    let body = state
         .doc_repo
-        .browse(BrowseParams {
-            source_id: params.source.as_deref(),
-            categories: &types,
-            tags: &tags,
-            limit: limit as u32,
-            ..Default::default()
-        })
-        .await
-    {
-        Ok(docs) => docs,
-        Err(e) => return internal_error(e).into_response(),
+        .stream_documents(filter, EXPORT_CHUNK)
+        .take(limit)
+        .chunks(EXPORT_CHUNK)
+        .enumerate()
+        .then(move |(index, chunk)| {
+            let repo = repo.clone();
+            async move {
+                let documents = chunk.into_iter().collect::<Result<Vec<_>, _>>()?;
+                let export_docs =
+                    to_export_documents(&repo, documents, include_text, include_highlights).await?;
+                Ok::<_, DieselError>(encode_chunk(format, &export_docs, index == 0))
+            }
+        });
+
+    let (content_type, filename, prefix, suffix) = match format {
+        ExportFormat::Json => ("application/json", "documents.json", "[", "\n]\n"),
+        ExportFormat::Jsonl => ("application/x-ndjson", "documents.jsonl", "", ""),
+        ExportFormat::Csv => ("text/csv", "documents.csv", CSV_HEADER, ""),
+        ExportFormat::Arrow => unreachable!("Arrow exports are streamed by arrow_response"),
+    };
+    let stream = stream::once(async move { Ok(Bytes::from_static(prefix.as_bytes())) })
+        .chain(body)
+        .chain(stream::once(async move {
+            Ok(Bytes::from_static(suffix.as_bytes()))
+        }));
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(Body::from_stream(stream))
+        .unwrap()
+        .into_response()
+}
+
+/// Convert a chunk of documents to export records, loading highlights for
+/// the whole chunk in one query.
+async fn to_export_documents(
+    repo: &DieselDocumentRepository,
+    documents: Vec<Document>,
+    include_text: bool,
+    include_highlights: bool,
+) -> Result<Vec<ExportDocument>, DieselError> {
+    let mut highlights_map = if include_highlights {
+        let doc_ids: Vec<String> = documents.iter().map(|d| d.id.clone()).collect();
+        repo.get_highlights_batch(&doc_ids).await?
+    } else {
+        Default::default()
     };
 
-    let export_docs: Vec<ExportDocument> = documents
+    Ok(documents
         .into_iter()
         .map(|doc| {
+            let highlights = include_highlights.then(|| {
+                highlights_map
+                    .remove(&doc.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|h| ExportHighlight {
+                        citation: format!(
+                            "\"{}\", p. {}, {}",
+                            doc.title, h.page_number, doc.source_url
+                        ),
+                        page_number: h.page_number,
+                        start_offset: h.start_offset,
+                        end_offset: h.end_offset,
+                        quote: h.selected_text,
+                        comment: h.comment,
+                        label: h.label,
+                    })
+                    .collect()
+            });
             let (mime_type, file_size, page_count, content_hash) =
                 if let Some(v) = doc.current_version() {
                     (
@@ -125,56 +261,40 @@ pub async fn export_documents(
                 file_size,
                 page_count,
                 content_hash,
-                extracted_text: if params.include_text {
+                extracted_text: if include_text {
                     doc.extracted_text
                 } else {
                     None
                 },
+                highlights,
             }
         })
-        .collect();
+        .collect())
+}
 
-    match params.format {
+/// Encode a chunk of export records. `first` marks the first chunk, which
+/// matters for JSON array separators.
+fn encode_chunk(format: ExportFormat, docs: &[ExportDocument], first: bool) -> Bytes {
+    let mut output = Vec::new();
+    match format {
         ExportFormat::Json => {
-            let json = serde_json::to_string_pretty(&export_docs).unwrap_or_default();
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "application/json")
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    "attachment; filename=\"documents.json\"",
-                )
-                .body(Body::from(json))
-                .unwrap()
-                .into_response()
+            for (i, doc) in docs.iter().enumerate() {
+                let separator = if first && i == 0 { "\n" } else { ",\n" };
+                if let Ok(json) = serde_json::to_string_pretty(doc) {
+                    write!(output, "{}{}", separator, json).ok();
+                }
+            }
         }
         ExportFormat::Jsonl => {
-            let mut output = Vec::new();
-            for doc in &export_docs {
+            for doc in docs {
                 if let Ok(line) = serde_json::to_string(doc) {
                     writeln!(output, "{}", line).ok();
                 }
             }
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "application/x-ndjson")
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    "attachment; filename=\"documents.jsonl\"",
-                )
-                .body(Body::from(output))
-                .unwrap()
-                .into_response()
         }
+        ExportFormat::Arrow => unreachable!("Arrow exports are streamed by arrow_response"),
         ExportFormat::Csv => {
-            let mut output = Vec::new();
-            writeln!(
-                output,
-                "id,source_id,title,source_url,status,synopsis,tags,created_at,updated_at,mime_type,file_size,page_count,content_hash"
-            )
-            .ok();
-
-            for doc in &export_docs {
+            for doc in docs {
                 let tags_str = doc.tags.join(";");
                 let synopsis_escaped = doc
                     .synopsis
@@ -202,19 +322,9 @@ pub async fn export_documents(
                 )
                 .ok();
             }
-
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "text/csv")
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    "attachment; filename=\"documents.csv\"",
-                )
-                .body(Body::from(output))
-                .unwrap()
-                .into_response()
         }
     }
+    Bytes::from(output)
 }
 
 fn escape_csv(s: &str) -> String {
@@ -225,6 +335,116 @@ fn escape_csv(s: &str) -> String {
     }
 }
 
+/// Query params for page export.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PageExportQuery {
+    /// Filter by source ID
+    pub source: Option<String>,
+    /// Filter by tags (comma-separated)
+    pub tags: Option<String>,
+    /// Filter by types (comma-separated)
+    pub types: Option<String>,
+    /// Maximum documents whose pages are exported (default: 10000)
+    pub limit: Option<usize>,
+}
+
+/// Export the page text of documents' current versions as an Arrow IPC
+/// stream, one row per page, for reading straight into pandas or polars.
+#[utoipa::path(
+    get,
+    path = "/api/export/pages",
+    params(PageExportQuery),
+    responses(
+        (status = 200, description = "Pages as an Arrow IPC stream", content_type = "application/vnd.apache.arrow.stream")
+    ),
+    tag = "Export"
+)]
+pub async fn export_pages(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(params): Query<PageExportQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(10_000).min(100_000);
+    let filter = StreamFilter {
+        source_id: params.source.clone(),
+        categories: parse_csv_param(params.types.as_ref()),
+        tags: parse_csv_param(params.tags.as_ref()),
+        projection: Projection::Metadata,
+        access: viewer.owned_filter(),
+        ..Default::default()
+    };
+    let schema = arrow_ipc::pages_schema();
+    let batch_schema = schema.clone();
+    let repo = state.doc_repo.clone();
+
+    let batches = state
+        .doc_repo
+        .stream_documents(filter, EXPORT_CHUNK)
+        .take(limit)
+        .chunks(PAGE_EXPORT_CHUNK)
+        .then(move |chunk| {
+            let repo = repo.clone();
+            let schema = batch_schema.clone();
+            async move {
+                let mut rows = Vec::new();
+                for doc in chunk {
+                    let doc = doc.map_err(std::io::Error::other)?;
+                    let Some(version) = doc.current_version() else {
+                        continue;
+                    };
+                    let pages = repo
+                        .get_pages(&doc.id, version.id as i32)
+                        .await
+                        .map_err(std::io::Error::other)?;
+                    rows.extend(pages.into_iter().map(|p| (doc.source_id.clone(), p)));
+                }
+                arrow_ipc::pages_batch(&schema, &rows).map_err(std::io::Error::other)
+            }
+        });
+    arrow_response(schema, batches, "pages.arrows")
+}
+
+/// Stream record batches as an Arrow IPC stream download. A failure part
+/// way through ends the response early, leaving the stream without its
+/// end marker so readers report it instead of returning partial data.
+fn arrow_response<S>(schema: SchemaRef, batches: S, filename: &str) -> Response
+where
+    S: Stream<Item = Result<RecordBatch, std::io::Error>> + Send + 'static,
+{
+    let encoder = match ArrowEncoder::new(&schema) {
+        Ok(encoder) => encoder,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let body = batches
+        .map(Some)
+        .chain(stream::once(async { None }))
+        .scan(encoder, |encoder, batch| {
+            futures::future::ready(Some(encode_batch(encoder, batch)))
+        });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, ARROW_STREAM)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(Body::from_stream(body))
+        .unwrap()
+}
+
+/// Encode the next batch, or end the stream after the last (`None`).
+fn encode_batch(
+    encoder: &mut ArrowEncoder,
+    batch: Option<Result<RecordBatch, std::io::Error>>,
+) -> Result<Bytes, std::io::Error> {
+    match batch {
+        Some(Ok(batch)) => encoder.batch(&batch).map_err(std::io::Error::other),
+        Some(Err(e)) => Err(e),
+        None => encoder.finish().map_err(std::io::Error::other),
+    }
+}
+
 /// Export metadata statistics.
 #[utoipa::path(
     get,
@@ -269,67 +489,208 @@ pub async fn export_stats(State(state): State<AppState>) -> impl IntoResponse {
 )]
 pub async fn export_annotations(
     State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<ExportQuery>,
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(10_000).min(100_000);
+    let jsonl = params.format == ExportFormat::Jsonl;
 
-    let documents = match state
+    let body = state
         .doc_repo
-        .browse(BrowseParams {
-            source_id: params.source.as_deref(),
-            categories: &[],
-            tags: &[],
-            limit: limit as u32,
-            ..Default::default()
-        })
-        .await
-    {
-        Ok(docs) => docs,
-        Err(e) => return internal_error(e).into_response(),
-    };
-
-    let annotations: Vec<AnnotationExport> = documents
-        .into_iter()
-        .filter(|d| d.synopsis.is_some() || !d.tags.is_empty())
-        .map(|d| AnnotationExport {
-            id: d.id,
-            source_url: d.source_url,
-            synopsis: d.synopsis,
-            tags: d.tags,
-        })
-        .collect();
-
-    match params.format {
-        ExportFormat::Jsonl => {
+        .stream_documents(
+            StreamFilter::source(params.source.as_deref())
+                .with_projection(Projection::Metadata)
+                .with_access(viewer.owned_filter()),
+            EXPORT_CHUNK,
+        )
+        .take(limit)
+        .try_filter(|d| futures::future::ready(d.synopsis.is_some() || !d.tags.is_empty()))
+        .enumerate()
+        .map(move |(index, doc)| {
+            let d = doc?;
+            let ann = AnnotationExport {
+                id: d.id,
+                source_url: d.source_url,
+                synopsis: d.synopsis,
+                tags: d.tags,
+            };
             let mut output = Vec::new();
-            for ann in &annotations {
-                if let Ok(line) = serde_json::to_string(ann) {
+            if jsonl {
+                if let Ok(line) = serde_json::to_string(&ann) {
                     writeln!(output, "{}", line).ok();
                 }
+            } else if let Ok(json) = serde_json::to_string_pretty(&ann) {
+                let separator = if index == 0 { "\n" } else { ",\n" };
+                write!(output, "{}{}", separator, json).ok();
             }
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "application/x-ndjson")
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    "attachment; filename=\"annotations.jsonl\"",
-                )
-                .body(Body::from(output))
-                .unwrap()
-                .into_response()
+            Ok::<_, DieselError>(Bytes::from(output))
+        });
+
+    let (content_type, filename, prefix, suffix) = if jsonl {
+        ("application/x-ndjson", "annotations.jsonl", "", "")
+    } else {
+        ("application/json", "annotations.json", "[", "\n]\n")
+    };
+    let stream = stream::once(async move { Ok(Bytes::from_static(prefix.as_bytes())) })
+        .chain(body)
+        .chain(stream::once(async move {
+            Ok(Bytes::from_static(suffix.as_bytes()))
+        }));
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(Body::from_stream(stream))
+        .unwrap()
+        .into_response()
+}
+
+/// Query params for export run history.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExportRunsQuery {
+    /// Only runs of this job
+    pub job: Option<String>,
+    /// Maximum runs to return (default: 50)
+    pub limit: Option<i64>,
+}
+
+/// A run of a scheduled export job.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExportRunResponse {
+    pub id: i32,
+    pub job: String,
+    /// jsonl, warc, or bag
+    pub format: String,
+    pub destination: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// running, succeeded, or failed
+    pub status: String,
+    /// Where the export was written
+    pub artifact: Option<String>,
+    pub documents: u64,
+    pub bytes: u64,
+    pub error: Option<String>,
+    /// Latest change journal entry when the run started
+    pub change_seq: Option<i64>,
+}
+
+impl From<ExportRun> for ExportRunResponse {
+    fn from(run: ExportRun) -> Self {
+        Self {
+            id: run.id,
+            job: run.job,
+            format: run.format,
+            destination: run.destination,
+            started_at: run.started_at.to_rfc3339(),
+            finished_at: run.finished_at.map(|t| t.to_rfc3339()),
+            status: run.status.as_str().to_string(),
+            artifact: run.artifact,
+            documents: run.documents,
+            bytes: run.bytes,
+            error: run.error,
+            change_seq: run.change_seq,
         }
-        _ => {
-            let json = serde_json::to_string_pretty(&annotations).unwrap_or_default();
-            Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "application/json")
-                .header(
-                    header::CONTENT_DISPOSITION,
-                    "attachment; filename=\"annotations.json\"",
-                )
-                .body(Body::from(json))
-                .unwrap()
-                .into_response()
+    }
+}
+
+/// History of scheduled export jobs (`foia export run`), newest first.
+#[utoipa::path(
+    get,
+    path = "/api/export/runs",
+    params(ExportRunsQuery),
+    responses(
+        (status = 200, description = "Export runs", body = Vec<ExportRunResponse>)
+    ),
+    tag = "Export"
+)]
+pub async fn export_runs(
+    State(state): State<AppState>,
+    Query(params): Query<ExportRunsQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(50).clamp(1, 1000);
+    match state
+        .doc_repo
+        .list_export_runs(params.job.as_deref(), limit)
+        .await
+    {
+        Ok(runs) => ApiResponse::ok(
+            runs.into_iter()
+                .map(ExportRunResponse::from)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Query params for the change journal.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ChangesQuery {
+    /// Only changes after this sequence number (default: 0, every change)
+    #[serde(default)]
+    pub after: i64,
+    /// Maximum changes to return (default: 1000)
+    pub limit: Option<i64>,
+}
+
+/// One entry in the change journal.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChangeResponse {
+    pub seq: i64,
+    pub document_id: String,
+    pub source_id: String,
+    /// created, updated, or deleted
+    pub change_type: String,
+    pub changed_at: String,
+}
+
+/// A page of the change journal.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChangesResponse {
+    pub changes: Vec<ChangeResponse>,
+    /// Sequence number to pass as `after` for the next page
+    pub next: i64,
+}
+
+/// Document changes after a sequence number, oldest first, so mirrors can
+/// sync incrementally, deletions included: fetch pages until `changes` is
+/// empty, then re-export documents with `changed_since` and drop deleted ones.
+#[utoipa::path(
+    get,
+    path = "/api/export/changes",
+    params(ChangesQuery),
+    responses(
+        (status = 200, description = "Change journal entries", body = ChangesResponse)
+    ),
+    tag = "Export"
+)]
+pub async fn export_changes(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(params): Query<ChangesQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(1000).clamp(1, 10_000);
+    match state.doc_repo.get_changes_since(params.after, limit).await {
+        Ok(changes) => {
+            let next = changes.last().map_or(params.after, |c| c.seq);
+            let changes = changes
+                .into_iter()
+                .filter(|c| viewer.allows(&c.document_id, &c.source_id))
+                .map(|c| ChangeResponse {
+                    seq: c.seq,
+                    document_id: c.document_id,
+                    source_id: c.source_id,
+                    change_type: c.change_type.as_str().to_string(),
+                    changed_at: c.changed_at.to_rfc3339(),
+                })
+                .collect();
+            ApiResponse::ok(ChangesResponse { changes, next }).into_response()
         }
+        Err(e) => internal_error(e).into_response(),
     }
 }
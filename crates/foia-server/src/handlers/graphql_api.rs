@@ -0,0 +1,28 @@
+//! GraphQL endpoint and its in-browser IDE.
+
+use async_graphql::http::GraphiQLSource;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Extension, Json,
+};
+
+use super::super::graphql::schema;
+use super::super::AppState;
+use super::access::Viewer;
+
+/// Run a GraphQL query. Errors, including queries over the depth or
+/// complexity limit, are reported in the response body per the GraphQL spec.
+pub async fn graphql_query(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Json(request): Json<async_graphql::Request>,
+) -> impl IntoResponse {
+    let request = request.data(state).data(viewer);
+    Json(schema().execute(request).await)
+}
+
+/// GraphiQL, for exploring the schema.
+pub async fn graphql_ide() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/api/graphql").finish())
+}
@@ -1,12 +1,16 @@
 //! Helper types and utility functions for handlers.
 
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 use super::super::AppState;
 use super::api_types::ApiResponse;
 use foia::models::{Document, DocumentVersion};
+use foia::repository::diesel_document::BrowseCursor;
 
 /// Create an internal server error response.
 pub fn internal_error(e: impl std::fmt::Display) -> impl IntoResponse {
@@ -24,6 +28,21 @@ pub fn bad_request(message: &str) -> impl IntoResponse + use<'_> {
     ApiResponse::error(StatusCode::BAD_REQUEST, message.to_string())
 }
 
+/// Compare secrets without leaking where they differ through timing.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Value of a request cookie.
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|c| c.trim().strip_prefix(name)?.strip_prefix('='))
+}
+
 /// Version summary for API responses.
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct VersionSummary {
@@ -98,6 +117,12 @@ pub struct PaginatedResponse<T: Serialize> {
     pub per_page: usize,
     pub total: u64,
     pub total_pages: u64,
+    /// Opaque cursor for the previous page (pass as `before`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_cursor: Option<String>,
+    /// Opaque cursor for the next page (pass as `after`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl<T: Serialize> PaginatedResponse<T> {
@@ -109,8 +134,47 @@ impl<T: Serialize> PaginatedResponse<T> {
             per_page,
             total,
             total_pages,
+            prev_cursor: None,
+            next_cursor: None,
         }
     }
+
+    /// Attach keyset cursors for deep pagination.
+    pub fn with_cursors(mut self, prev: Option<String>, next: Option<String>) -> Self {
+        self.prev_cursor = prev;
+        self.next_cursor = next;
+        self
+    }
+}
+
+/// Resolve `after`/`before` cursor tokens into a cursor and direction
+/// (`true` = backward).
+pub fn decode_cursor(
+    after: Option<&str>,
+    before: Option<&str>,
+) -> Result<Option<(BrowseCursor, bool)>, &'static str> {
+    let (token, backward) = match (after, before) {
+        (Some(token), _) => (token, false),
+        (None, Some(token)) => (token, true),
+        (None, None) => return Ok(None),
+    };
+    BrowseCursor::decode(token)
+        .map(|c| Some((c, backward)))
+        .ok_or("Invalid pagination cursor")
+}
+
+/// Trim a page fetched with one extra row and report whether more rows
+/// exist in the direction of travel.
+pub fn trim_extra_row<T>(rows: &mut Vec<T>, per_page: usize, backward: bool) -> bool {
+    if rows.len() <= per_page {
+        return false;
+    }
+    if backward {
+        rows.remove(0);
+    } else {
+        rows.truncate(per_page);
+    }
+    true
 }
 
 /// Parse a comma-separated query parameter into a Vec of trimmed, non-empty strings.
@@ -0,0 +1,201 @@
+//! Page highlight API endpoints for user-selected text ranges.
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::repository::diesel_document::Projection;
+use foia::repository::models::{NewPageHighlight, PageHighlightRecord};
+
+/// Maximum length of a single highlighted selection, in characters.
+const MAX_SELECTION_CHARS: usize = 10_000;
+
+/// A highlighted text range on a document page.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HighlightResponse {
+    pub id: i32,
+    pub document_id: String,
+    pub version_id: Option<i32>,
+    pub page_number: i32,
+    /// Character offset of the selection start within the page text
+    pub start_offset: i32,
+    /// Character offset one past the selection end
+    pub end_offset: i32,
+    pub selected_text: String,
+    pub comment: Option<String>,
+    pub label: Option<String>,
+    pub created_at: String,
+}
+
+impl From<PageHighlightRecord> for HighlightResponse {
+    fn from(r: PageHighlightRecord) -> Self {
+        Self {
+            id: r.id,
+            document_id: r.document_id,
+            version_id: r.version_id,
+            page_number: r.page_number,
+            start_offset: r.start_offset,
+            end_offset: r.end_offset,
+            selected_text: r.selected_text,
+            comment: r.comment,
+            label: r.label,
+            created_at: r.created_at,
+        }
+    }
+}
+
+/// Create highlight request.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateHighlightRequest {
+    /// Version the page text belongs to (defaults to the current version)
+    pub version_id: Option<i32>,
+    pub page_number: i32,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    pub selected_text: String,
+    pub comment: Option<String>,
+    pub label: Option<String>,
+}
+
+/// List highlights for a document.
+#[utoipa::path(
+    get,
+    path = "/api/documents/{doc_id}/highlights",
+    params(("doc_id" = String, Path, description = "Document ID")),
+    responses(
+        (status = 200, description = "Highlights ordered by page and offset", body = Vec<HighlightResponse>),
+        (status = 404, description = "Document not found")
+    ),
+    tag = "Highlights"
+)]
+pub async fn list_highlights(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+) -> impl IntoResponse {
+    match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
+        Ok(None) => return not_found("Document not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+        Ok(Some(_)) => {}
+    }
+
+    match state.doc_repo.get_highlights(&doc_id).await {
+        Ok(records) => {
+            let items: Vec<HighlightResponse> = records.into_iter().map(Into::into).collect();
+            ApiResponse::ok(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Create a highlight on a document page.
+#[utoipa::path(
+    post,
+    path = "/api/documents/{doc_id}/highlights",
+    params(("doc_id" = String, Path, description = "Document ID")),
+    request_body = CreateHighlightRequest,
+    responses(
+        (status = 200, description = "Created highlight", body = HighlightResponse),
+        (status = 400, description = "Invalid range or selection"),
+        (status = 404, description = "Document not found")
+    ),
+    tag = "Highlights"
+)]
+pub async fn create_highlight(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+    Json(body): Json<CreateHighlightRequest>,
+) -> impl IntoResponse {
+    if body.page_number < 1 {
+        return bad_request("page_number must be >= 1").into_response();
+    }
+    if body.start_offset < 0 || body.end_offset <= body.start_offset {
+        return bad_request("end_offset must be greater than start_offset").into_response();
+    }
+    let selected_len = body.selected_text.chars().count();
+    if selected_len == 0 || selected_len > MAX_SELECTION_CHARS {
+        return bad_request("selected_text must be between 1 and 10000 characters")
+            .into_response();
+    }
+
+    let doc = match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
+        Ok(Some(d)) => d,
+        Ok(None) => return not_found("Document not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+
+    let version_id = body
+        .version_id
+        .or_else(|| doc.current_version().map(|v| v.id as i32));
+    let comment = body.comment.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    let label = body.label.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    let new = NewPageHighlight {
+        document_id: &doc_id,
+        version_id,
+        page_number: body.page_number,
+        start_offset: body.start_offset,
+        end_offset: body.end_offset,
+        selected_text: &body.selected_text,
+        comment,
+        label,
+        created_at: &created_at,
+    };
+
+    match state.doc_repo.save_highlight(&new).await {
+        Ok(id) => ApiResponse::ok(HighlightResponse {
+            id: id as i32,
+            document_id: doc_id.clone(),
+            version_id,
+            page_number: body.page_number,
+            start_offset: body.start_offset,
+            end_offset: body.end_offset,
+            selected_text: body.selected_text.clone(),
+            comment: comment.map(String::from),
+            label: label.map(String::from),
+            created_at,
+        })
+        .into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Delete a highlight.
+#[utoipa::path(
+    delete,
+    path = "/api/documents/{doc_id}/highlights/{highlight_id}",
+    params(
+        ("doc_id" = String, Path, description = "Document ID"),
+        ("highlight_id" = i32, Path, description = "Highlight ID"),
+    ),
+    responses(
+        (status = 200, description = "Highlight deleted"),
+        (status = 404, description = "Highlight not found")
+    ),
+    tag = "Highlights"
+)]
+pub async fn delete_highlight(
+    State(state): State<AppState>,
+    Path((doc_id, highlight_id)): Path<(String, i32)>,
+) -> impl IntoResponse {
+    match state.doc_repo.delete_highlight(&doc_id, highlight_id).await {
+        Ok(true) => ApiResponse::ok(highlight_id).into_response(),
+        Ok(false) => not_found("Highlight not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
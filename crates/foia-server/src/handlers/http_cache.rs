@@ -0,0 +1,176 @@
+//! HTTP caching headers, so browsers and CDNs can revalidate pages cheaply.
+//!
+//! HTML and JSON responses get a weak `ETag` hashed from their body and
+//! `Cache-Control: no-cache`, so a cache keeps them but asks again each
+//! time and gets a `304 Not Modified` when nothing changed. Stored files
+//! set their own `ETag` from their content-addressed path. Responses to
+//! requests carrying credentials are marked `private`, since they may show
+//! documents withheld from the public.
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::access::ACCESS_COOKIE;
+use super::helpers::cookie;
+
+/// Largest body hashed for an `ETag`; bigger responses pass through.
+const MAX_HASHED_BODY: u64 = 8 * 1024 * 1024;
+
+/// `public` unless the request carries an access token or API key.
+pub(super) fn cache_visibility(headers: &HeaderMap, query: Option<&str>) -> &'static str {
+    let credentialed = headers.contains_key(header::AUTHORIZATION)
+        || headers.contains_key("x-api-key")
+        || cookie(headers, ACCESS_COOKIE).is_some()
+        || query.is_some_and(|q| q.split('&').any(|p| p.starts_with("api_key=")));
+    if credentialed {
+        "private"
+    } else {
+        "public"
+    }
+}
+
+/// Whether `If-None-Match` lists `etag`. Weak comparison, as for GET.
+pub(super) fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let bare = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = bare(etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|tag| tag.trim() == "*" || bare(tag) == etag)
+}
+
+/// A `304 Not Modified` carrying the validators of the full response.
+pub(super) fn not_modified(etag: &str, cache_control: &str) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    let headers = response.headers_mut();
+    if let Ok(v) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, v);
+    }
+    if let Ok(v) = HeaderValue::from_str(cache_control) {
+        headers.insert(header::CACHE_CONTROL, v);
+    }
+    response
+}
+
+/// Add `ETag` and `Cache-Control` to HTML and JSON responses, answering
+/// `If-None-Match` with `304 Not Modified`.
+pub async fn conditional_get(req: Request, next: Next) -> Response {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return next.run(req).await;
+    }
+    let visibility = cache_visibility(req.headers(), req.uri().query());
+    let request_headers = req.headers().clone();
+    let response = next.run(req).await;
+
+    if response.status() != StatusCode::OK || response.headers().contains_key(header::ETAG) {
+        return response;
+    }
+    let hashable = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("text/html") || ct.starts_with("application/json"));
+    let small = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len <= MAX_HASHED_BODY);
+    if !hashable || !small {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_HASHED_BODY as usize).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    // A Set-Cookie is meant for this client only
+    let visibility = if parts.headers.contains_key(header::SET_COOKIE) {
+        "private"
+    } else {
+        visibility
+    };
+    let etag = body_etag(&bytes);
+    let cache_control = parts
+        .headers
+        .get(header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}, no-cache", visibility));
+
+    if etag_matches(&request_headers, &etag) {
+        let mut response = not_modified(&etag, &cache_control);
+        if let Some(vary) = parts.headers.get(header::VARY) {
+            response.headers_mut().insert(header::VARY, vary.clone());
+        }
+        return response;
+    }
+
+    if let Ok(v) = HeaderValue::from_str(&etag) {
+        parts.headers.insert(header::ETAG, v);
+    }
+    if let Ok(v) = HeaderValue::from_str(&cache_control) {
+        parts.headers.insert(header::CACHE_CONTROL, v);
+    }
+    // Pages are rendered in the negotiated language
+    parts.headers.append(
+        header::VARY,
+        HeaderValue::from_static("Accept-Language, Cookie"),
+    );
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Weak validator: equal bodies are equivalent, but compression may
+/// change their bytes on the wire.
+fn body_etag(body: &[u8]) -> String {
+    let hash = blake3::hash(body).to_hex();
+    format!("W/\"{}\"", &hash[..32])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_etag_matches_weak_and_lists() {
+        let etag = body_etag(b"<html>page</html>");
+        assert!(etag_matches(&if_none_match(&etag), &etag));
+        let strong = etag.trim_start_matches("W/");
+        assert!(etag_matches(&if_none_match(strong), &etag));
+        let listed = format!("\"other\", {}", etag);
+        assert!(etag_matches(&if_none_match(&listed), &etag));
+        assert!(etag_matches(&if_none_match("*"), &etag));
+        assert!(!etag_matches(&if_none_match("W/\"other\""), &etag));
+        assert!(!etag_matches(&HeaderMap::new(), &etag));
+    }
+
+    #[test]
+    fn test_cache_visibility() {
+        assert_eq!(cache_visibility(&HeaderMap::new(), None), "public");
+        assert_eq!(
+            cache_visibility(&HeaderMap::new(), Some("page=2&api_key=foia_abc")),
+            "private"
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_static("foia_lang=fr"));
+        assert_eq!(cache_visibility(&headers, None), "public");
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("foia_lang=fr; foia_access=secret"),
+        );
+        assert_eq!(cache_visibility(&headers, None), "private");
+    }
+}
@@ -0,0 +1,91 @@
+//! Interface language: negotiation on every request and the switcher.
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+
+use super::super::i18n::I18n;
+use super::super::AppState;
+use super::helpers::{cookie, not_found};
+
+/// Cookie that remembers the language picked in the switcher.
+const LOCALE_COOKIE: &str = "foia_lang";
+
+/// How long the language choice is remembered.
+const LOCALE_COOKIE_MAX_AGE: u64 = 365 * 24 * 60 * 60;
+
+/// Pick the interface language and make it available to handlers as an
+/// [`I18n`] extension.
+pub async fn negotiate_locale(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let default = state.config.read().await.theme.locale.clone();
+    let chosen = req
+        .uri()
+        .query()
+        .and_then(|q| {
+            q.split('&')
+                .find_map(|pair| pair.strip_prefix("lang="))
+                .map(str::to_string)
+        })
+        .or_else(|| cookie(req.headers(), LOCALE_COOKIE).map(str::to_string));
+    let accept_language = req
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok());
+
+    let i18n = I18n::negotiate(chosen.as_deref(), accept_language, default.as_deref());
+    req.extensions_mut().insert(i18n);
+    let mut response = next.run(req).await;
+    // Pages differ by language, so shared caches must not mix them up
+    response.headers_mut().append(
+        header::VARY,
+        HeaderValue::from_static("Accept-Language, Cookie"),
+    );
+    response
+}
+
+/// Remember a language choice and go back to the page it was made on.
+pub async fn set_locale(Path(code): Path<String>, headers: HeaderMap) -> Response {
+    if !I18n::is_supported(&code) {
+        return not_found("Unknown language").into_response();
+    }
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(local_path)
+        .unwrap_or("/");
+    let cookie = format!(
+        "{}={}; Path=/; Max-Age={}; SameSite=Lax",
+        LOCALE_COOKIE, code, LOCALE_COOKIE_MAX_AGE
+    );
+    ([(header::SET_COOKIE, cookie)], Redirect::to(back)).into_response()
+}
+
+/// Path and query of a URL, so redirects never leave the site.
+fn local_path(url: &str) -> Option<&str> {
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = &after_scheme[after_scheme.find('/')?..];
+    (!path.starts_with("//")).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_path() {
+        assert_eq!(
+            local_path("https://archive.example.org/documents/abc?q=x"),
+            Some("/documents/abc?q=x")
+        );
+        assert_eq!(local_path("http://localhost:3030"), None);
+        assert_eq!(local_path("/tags"), Some("/tags"));
+        assert_eq!(local_path("https://evil.example//x"), None);
+    }
+}
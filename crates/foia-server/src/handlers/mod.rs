@@ -1,48 +1,134 @@
 //! HTTP request handlers for the web server.
 
+mod access;
+mod acquire_api;
+mod agencies_api;
+mod aliases;
+mod aliases_api;
 mod annotations_api;
 mod api;
+mod api_keys;
 pub mod api_types;
+mod arrow_ipc;
+mod bates_api;
 mod browse;
+mod challenges;
+mod challenges_api;
+mod config;
+mod config_api;
+mod coverage;
+mod dates;
+mod dates_api;
 mod documents;
 mod documents_api;
 mod duplicates;
 mod entities_api;
+mod exemptions_api;
 mod export_api;
+mod graphql_api;
 mod helpers;
+mod highlights_api;
+mod http_cache;
+mod locale;
 mod ocr;
 pub mod openapi;
+mod page_text_api;
 mod pages;
+mod read_only;
+mod reader;
+mod relations_api;
 mod scrape_api;
 mod search_api;
+mod sections_api;
+mod sheets;
+mod snapshots;
+mod sources;
 mod static_files;
+mod storage_api;
+mod sync_api;
 mod tags;
+mod takedowns;
+mod takedowns_api;
+mod theme;
 mod timeline;
+mod transcription;
+mod transcription_api;
 mod types;
+mod uploads_api;
 mod versions_api;
 
 // Re-export handlers for use by the router
+pub use access::{access_guard, Viewer};
+pub use acquire_api::{get_acquire_job, submit_acquire, submit_capture};
+pub use agencies_api::list_agencies;
+pub use aliases::aliases_page;
+pub use aliases_api::{create_alias, delete_alias, list_aliases};
 pub use annotations_api::{annotation_stats, get_annotation, list_annotations, update_annotation};
 pub use api::{
     api_recent_docs, api_search_tags, api_source_status, api_sources, api_status, api_type_stats,
-    health,
+    health, metrics,
 };
+pub use api_keys::api_key_guard;
+pub use bates_api::{bates_gaps, lookup_bates};
 pub use browse::browse_documents;
+pub use challenges::list_challenges_page;
+pub use challenges_api::{dismiss_challenge, list_challenges, solve_challenge};
+pub use config::config_page;
+pub use config_api::{
+    get_config_version, get_scraper_config, list_config_history, list_scraper_configs,
+    rollback_config, update_scraper_config,
+};
+pub use coverage::{coverage_page, coverage_source_page};
+pub use dates::date_queue_page;
+pub use dates_api::{clear_document_date, date_queue, set_document_date};
 pub use documents::{document_detail, document_versions};
 pub use documents_api::{get_document, get_document_content, list_documents};
 pub use duplicates::list_duplicates;
 pub use entities_api::{
     document_entities, entity_locations, entity_types, search_entities, top_entities,
 };
-pub use export_api::{export_annotations, export_documents, export_stats};
-pub use ocr::{api_reocr_document, api_reocr_status};
+pub use exemptions_api::{document_exemptions, exemption_stats, exemption_timeline};
+pub use export_api::{
+    export_annotations, export_changes, export_documents, export_pages, export_runs, export_stats,
+};
+pub use graphql_api::{graphql_ide, graphql_query};
+pub use highlights_api::{create_highlight, delete_highlight, list_highlights};
+pub use http_cache::conditional_get;
+pub use locale::{negotiate_locale, set_locale};
+pub use ocr::{api_ocr_page, api_page_ocr_status, api_reocr_document, api_reocr_status};
+pub use page_text_api::{list_page_text_revisions, update_page_text};
 pub use pages::api_document_pages;
-pub use scrape_api::{get_scrape_status, list_queue, list_scrapers, retry_failed};
-pub use search_api::search_content;
+pub use read_only::read_only_guard;
+pub use reader::{citation_permalink, document_reader};
+pub use relations_api::{create_relation, delete_relation, list_relations};
+pub use scrape_api::{
+    get_scrape_status, list_queue, list_scrapers, pause_source, resume_source, retry_failed,
+};
+pub use search_api::{search_columns, search_content, search_documents};
+pub use sections_api::{export_section, list_sections};
+pub use sheets::download_sheet;
+pub use snapshots::{list_snapshots, snapshot_detail, snapshot_history, snapshot_raw};
+pub use sources::sources_page;
 pub use static_files::{serve_css, serve_file, serve_js};
+pub use storage_api::storage_report;
+pub use sync_api::{
+    sync_apply, sync_blob_get, sync_blob_put, sync_crawl_apply, sync_crawl_export, sync_export,
+    sync_manifest,
+};
 pub use tags::{api_tags, list_tag_documents, list_tags};
+pub use takedowns::takedown_page;
+pub use takedowns_api::{
+    decide_takedown, get_takedown, list_takedowns, submit_takedown, suppress_takedown,
+};
+pub use theme::{about_page, serve_logo};
 pub use timeline::{timeline_aggregate, timeline_source};
+pub use transcription::transcribe_page;
+pub use transcription_api::{
+    get_transcription_task, list_transcription_tasks, next_transcription_task,
+    resolve_transcription, submit_transcription,
+};
 pub use types::{list_by_type, list_types};
+pub use uploads_api::{approve_upload, get_upload, list_uploads, reject_upload, submit_upload};
 pub use versions_api::{find_by_hash, get_version, list_versions};
 
 pub use openapi::openapi_spec;
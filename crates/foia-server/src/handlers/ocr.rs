@@ -1,14 +1,19 @@
-//! Re-OCR API handlers.
+//! Re-OCR and on-demand page OCR API handlers.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
+use super::super::page_ocr::PageOcrJob;
 use super::super::{AppState, DeepSeekJobStatus};
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::models::PageOcrStatus;
+use foia::repository::diesel_document::Projection;
 
 /// Request body for re-OCR API.
 #[derive(Debug, Deserialize, ToSchema)]
@@ -90,7 +95,11 @@ pub async fn api_reocr_document(
         }
     }
 
-    let doc = match state.doc_repo.get(&document_id).await {
+    let doc = match state
+        .doc_repo
+        .get_projected(&document_id, Projection::Metadata)
+        .await
+    {
         Ok(Some(d)) => d,
         Ok(None) => {
             return (
@@ -149,7 +158,12 @@ pub async fn api_reocr_document(
         .into_response();
     }
 
-    let pdf_path = version.resolve_path(&state.documents_dir, &doc.source_url, &doc.title);
+    // Encrypted documents are decrypted once to a temp file shared by all pages
+    let stored_path = version.resolve_path(&state.documents_dir, &doc.source_url, &doc.title);
+    let pdf_path = std::sync::Arc::new(match foia::storage::plaintext_path(&stored_path) {
+        Ok(path) => path,
+        Err(_) => foia::storage::PlaintextPath::Original(stored_path),
+    });
 
     let config = OcrConfig {
         use_gpu: true,
@@ -341,3 +355,116 @@ pub async fn api_reocr_status(State(state): State<AppState>) -> impl IntoRespons
         message: job_status.error.clone(),
     })
 }
+
+/// Which version's page to OCR; the current version by default.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PageOcrParams {
+    pub version: Option<i64>,
+}
+
+/// OCR one page now, ahead of the batch queue, through the configured
+/// backends. Poll the GET of the same path until the job finishes, then
+/// reload the page.
+#[utoipa::path(
+    post,
+    path = "/api/documents/{doc_id}/pages/{page_number}/ocr",
+    params(
+        ("doc_id" = String, Path, description = "Document ID"),
+        ("page_number" = u32, Path, description = "Page number"),
+        PageOcrParams
+    ),
+    responses(
+        (status = 200, description = "Page already has OCR text", body = PageOcrJob),
+        (status = 202, description = "Job queued, or already queued or running", body = PageOcrJob),
+        (status = 400, description = "Document is not a PDF"),
+        (status = 404, description = "Document or page not found")
+    ),
+    tag = "OCR"
+)]
+pub async fn api_ocr_page(
+    State(state): State<AppState>,
+    Path((doc_id, page_number)): Path<(String, u32)>,
+    Query(params): Query<PageOcrParams>,
+) -> impl IntoResponse {
+    let doc = match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
+        Ok(Some(d)) => d,
+        Ok(None) => return not_found("Document not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let version = match params.version {
+        Some(id) => doc.versions.iter().find(|v| v.id == id),
+        None => doc.current_version(),
+    };
+    let Some(version) = version else {
+        return not_found("Version not found").into_response();
+    };
+    if version.mime_type != "application/pdf" {
+        return bad_request("Only pages of PDFs can be OCRed").into_response();
+    }
+
+    let page = match state
+        .doc_repo
+        .get_page(&doc.id, version.id as i32, page_number)
+        .await
+    {
+        Ok(Some(p)) => p,
+        Ok(None) => return not_found("Page not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    if page.ocr_status == PageOcrStatus::OcrComplete {
+        return ApiResponse::ok(PageOcrJob::already_done(&page)).into_response();
+    }
+
+    let jobs = state.page_ocr_jobs.clone();
+    let job = jobs.submit(state, page).await;
+    (StatusCode::ACCEPTED, ApiResponse::ok(job)).into_response()
+}
+
+/// Status of a page's on-demand OCR job.
+#[utoipa::path(
+    get,
+    path = "/api/documents/{doc_id}/pages/{page_number}/ocr",
+    params(
+        ("doc_id" = String, Path, description = "Document ID"),
+        ("page_number" = u32, Path, description = "Page number"),
+        PageOcrParams
+    ),
+    responses(
+        (status = 200, description = "Job status", body = PageOcrJob),
+        (status = 404, description = "No recent job for this page")
+    ),
+    tag = "OCR"
+)]
+pub async fn api_page_ocr_status(
+    State(state): State<AppState>,
+    Path((doc_id, page_number)): Path<(String, u32)>,
+    Query(params): Query<PageOcrParams>,
+) -> impl IntoResponse {
+    let version_id = match params.version {
+        Some(id) => id,
+        None => match state
+            .doc_repo
+            .get_projected(&doc_id, Projection::Metadata)
+            .await
+        {
+            Ok(Some(doc)) => match doc.current_version() {
+                Some(v) => v.id,
+                None => return not_found("Version not found").into_response(),
+            },
+            Ok(None) => return not_found("Document not found").into_response(),
+            Err(e) => return internal_error(e).into_response(),
+        },
+    };
+    match state
+        .page_ocr_jobs
+        .get(&doc_id, version_id, page_number)
+        .await
+    {
+        Some(job) => ApiResponse::ok(job).into_response(),
+        None => not_found("No OCR job for this page").into_response(),
+    }
+}
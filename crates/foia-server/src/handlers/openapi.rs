@@ -1,20 +1,40 @@
 //! OpenAPI spec generation and serving.
 
 use axum::{http::StatusCode, response::IntoResponse};
-use utoipa::OpenApi;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 
+use super::super::acquire;
+use super::super::page_ocr;
+use super::acquire_api;
+use super::agencies_api;
+use super::aliases_api;
 use super::annotations_api;
 use super::api;
 use super::api_types;
+use super::bates_api;
+use super::challenges_api;
+use super::config_api;
+use super::dates_api;
 use super::documents_api;
 use super::entities_api;
+use super::exemptions_api;
 use super::export_api;
 use super::helpers;
+use super::highlights_api;
 use super::ocr;
+use super::page_text_api;
 use super::pages;
+use super::relations_api;
 use super::scrape_api;
+use super::search_api;
+use super::sections_api;
+use super::storage_api;
 use super::tags;
+use super::takedowns_api;
 use super::timeline;
+use super::transcription_api;
+use super::uploads_api;
 use super::versions_api;
 
 #[derive(OpenApi)]
@@ -27,15 +47,22 @@ use super::versions_api;
     paths(
         // Health
         api::health,
+        api::metrics,
         // Documents
         documents_api::list_documents,
         documents_api::get_document,
         documents_api::get_document_content,
+        sections_api::list_sections,
+        sections_api::export_section,
         // Pages
         pages::api_document_pages,
+        page_text_api::update_page_text,
+        page_text_api::list_page_text_revisions,
         // OCR
         ocr::api_reocr_document,
         ocr::api_reocr_status,
+        ocr::api_ocr_page,
+        ocr::api_page_ocr_status,
         // Versions
         versions_api::list_versions,
         versions_api::get_version,
@@ -50,16 +77,83 @@ use super::versions_api;
         scrape_api::get_scrape_status,
         scrape_api::list_queue,
         scrape_api::retry_failed,
+        scrape_api::pause_source,
+        scrape_api::resume_source,
+        // Acquire
+        acquire_api::submit_acquire,
+        acquire_api::submit_capture,
+        acquire_api::get_acquire_job,
+        // Uploads
+        uploads_api::submit_upload,
+        uploads_api::list_uploads,
+        uploads_api::get_upload,
+        uploads_api::approve_upload,
+        uploads_api::reject_upload,
+        // Takedowns
+        takedowns_api::submit_takedown,
+        takedowns_api::list_takedowns,
+        takedowns_api::get_takedown,
+        takedowns_api::suppress_takedown,
+        takedowns_api::decide_takedown,
+        // Transcription
+        config_api::list_config_history,
+        config_api::get_config_version,
+        config_api::rollback_config,
+        config_api::list_scraper_configs,
+        config_api::get_scraper_config,
+        config_api::update_scraper_config,
+        transcription_api::next_transcription_task,
+        transcription_api::submit_transcription,
+        transcription_api::list_transcription_tasks,
+        transcription_api::get_transcription_task,
+        transcription_api::resolve_transcription,
+        // Challenges
+        challenges_api::list_challenges,
+        challenges_api::solve_challenge,
+        challenges_api::dismiss_challenge,
         // Export
         export_api::export_documents,
+        export_api::export_pages,
         export_api::export_annotations,
         export_api::export_stats,
+        export_api::export_runs,
+        export_api::export_changes,
+        // Storage
+        storage_api::storage_report,
+        // Search
+        search_api::search_columns,
+        search_api::search_documents,
+        // Aliases
+        aliases_api::list_aliases,
+        aliases_api::create_alias,
+        aliases_api::delete_alias,
+        // Bates
+        bates_api::lookup_bates,
+        bates_api::bates_gaps,
+        // Exemptions
+        exemptions_api::exemption_stats,
+        exemptions_api::exemption_timeline,
+        exemptions_api::document_exemptions,
         // Entities
         entities_api::search_entities,
         entities_api::entity_types,
         entities_api::top_entities,
         entities_api::entity_locations,
         entities_api::document_entities,
+        // Highlights
+        highlights_api::list_highlights,
+        highlights_api::create_highlight,
+        highlights_api::delete_highlight,
+        // Relations
+        relations_api::list_relations,
+        relations_api::create_relation,
+        relations_api::delete_relation,
+        // Dates
+        dates_api::date_queue,
+        dates_api::set_document_date,
+        dates_api::clear_document_date,
+        // Agencies
+        agencies_api::list_agencies,
         // Timeline
         timeline::timeline_aggregate,
         timeline::timeline_source,
@@ -108,25 +202,104 @@ use super::versions_api;
         api_types::QueueItem,
         api_types::QueueResponse,
         api_types::RetryResponse,
+        api_types::PauseResponse,
         api_types::RecentUrl,
         api_types::FailedUrl,
+        // Acquire API types
+        acquire_api::AcquireBody,
+        acquire_api::CaptureBody,
+        acquire::AcquireJob,
+        acquire::AcquireStatus,
+        // Upload API types
+        uploads_api::UploadBody,
+        uploads_api::UploadItem,
+        uploads_api::ReviewBody,
+        // Takedown API types
+        takedowns_api::TakedownBody,
+        takedowns_api::TakedownReceipt,
+        takedowns_api::TakedownCaseItem,
+        takedowns_api::TakedownEventItem,
+        takedowns_api::TakedownActionBody,
+        // Transcription API types
+        config_api::ConfigVersionItem,
+        config_api::ConfigDiffItem,
+        config_api::ConfigVersionDetail,
+        config_api::ConfigRollbackBody,
+        config_api::ConfigRollbackItem,
+        config_api::ScraperConfigsItem,
+        config_api::ScraperConfigItem,
+        config_api::ScraperConfigBody,
+        config_api::ScraperSaveItem,
+        transcription_api::TranscriptionTaskItem,
+        transcription_api::TranscriptionEntryItem,
+        transcription_api::NextTaskBody,
+        transcription_api::TranscriptionBody,
         // Export API types
         export_api::ExportFormat,
         export_api::ExportDocument,
+        export_api::ExportHighlight,
+        export_api::ExportRunResponse,
+        export_api::ChangeResponse,
+        export_api::ChangesResponse,
         api_types::ExportStatsResponse,
         api_types::AnnotationExport,
+        // Storage API types
+        storage_api::StorageUsage,
+        storage_api::ReclaimableStorage,
+        storage_api::StorageReportResponse,
+        // Search API types
+        search_api::ColumnSearchResult,
+        search_api::DocumentSearchHit,
+        search_api::FacetValue,
+        search_api::DocumentSearchFacets,
+        search_api::DocumentSearchResponse,
+        sections_api::SectionItem,
+        // Alias API types
+        aliases_api::AliasResponse,
+        aliases_api::CreateAliasRequest,
+        // Bates API types
+        bates_api::BatesMatch,
+        bates_api::BatesGapItem,
+        bates_api::BatesGapsResponse,
+        // Exemption API types
+        exemptions_api::ExemptionCount,
+        exemptions_api::ExemptionPeriodCount,
+        exemptions_api::PageExemptionResponse,
         // Entity API types
         entities_api::MatchedEntity,
         entities_api::EntitySearchResult,
         entities_api::EntityTypeStats,
         entities_api::TopEntity,
         entities_api::GeocodedLocation,
+        // Highlight API types
+        highlights_api::HighlightResponse,
+        highlights_api::CreateHighlightRequest,
+        // Relation API types
+        relations_api::RelationResponse,
+        relations_api::RelationNode,
+        relations_api::RelationGraphResponse,
+        relations_api::CreateRelationRequest,
+        // Date review types
+        dates_api::DateCandidate,
+        dates_api::DateQueueItem,
+        dates_api::DateQueueResponse,
+        dates_api::SetDateRequest,
+        dates_api::ManualDateResponse,
+        // Agency types
+        agencies_api::AgencySummary,
         // OCR types
         ocr::ReOcrRequest,
         ocr::ReOcrResponse,
+        page_ocr::PageOcrJob,
+        page_ocr::PageOcrJobStatus,
+        // Challenge types
+        challenges_api::ChallengeResponse,
+        challenges_api::SolveChallengeRequest,
         // Page types
         pages::PageData,
         pages::PagesResponse,
+        page_text_api::PageTextBody,
+        page_text_api::PageTextRevisionItem,
         // Status types
         api_types::SourceInfo,
         api_types::CategoryStat,
@@ -143,18 +316,48 @@ use super::versions_api;
         (name = "Health", description = "Health check"),
         (name = "Documents", description = "Document search, filter, and details"),
         (name = "Versions", description = "Document version history"),
-        (name = "Pages", description = "Document page content and OCR"),
+        (name = "Pages", description = "Document page content, OCR and curators' text corrections"),
         (name = "OCR", description = "Re-OCR document processing"),
         (name = "Annotations", description = "LLM-generated metadata and tags"),
         (name = "Scrapers", description = "Scraper control and monitoring"),
+        (name = "Acquire", description = "On-demand acquisition of single URLs"),
+        (name = "Uploads", description = "Documents contributed by users, and their moderation"),
+        (name = "Takedowns", description = "Removal requests, suppression pending review, and decisions"),
+        (name = "Config", description = "Saved versions of the scraper configs, rollback, and validated edits"),
+        (name = "Transcription", description = "Volunteer transcription of pages OCR can't read, with double entry"),
+        (name = "Challenges", description = "CAPTCHA challenges awaiting an operator"),
         (name = "Export", description = "Bulk data export"),
+        (name = "Storage", description = "Disk usage by source and type, and reclaimable space"),
+        (name = "Search", description = "Column names of CSV files and spreadsheets"),
+        (name = "Aliases", description = "Acronyms, code names and alternate names that expand searches"),
+        (name = "Bates", description = "Bates number lookup and sequence gaps"),
+        (name = "Exemptions", description = "FOIA exemption citations per page, source and period"),
         (name = "Entities", description = "NER-extracted entity search"),
+        (name = "Highlights", description = "User highlights and comments on page text"),
+        (name = "Relations", description = "Exhibits, attachments, and other links between documents"),
+        (name = "Dates", description = "Review queue for missing or uncertain publication dates"),
+        (name = "Agencies", description = "Source hierarchy with roll-up document counts"),
         (name = "Timeline", description = "Document timeline visualization"),
         (name = "Status", description = "System status, sources, types, and tags"),
-    )
+    ),
+    modifiers(&ApiKeyAuth),
+    security((), ("api_key" = []))
 )]
 struct ApiDoc;
 
+/// Declares the `X-API-Key` header (see `foia api-key`).
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
 /// Serve the OpenAPI spec as JSON.
 pub async fn openapi_spec() -> impl IntoResponse {
     let mut doc = ApiDoc::openapi();
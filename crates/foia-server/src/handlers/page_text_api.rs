@@ -0,0 +1,196 @@
+//! Page text corrections API: curators fix OCR errors page by page, and
+//! anyone can read the history of a page's corrections.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::access::Viewer;
+use super::api_keys::ApiClient;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::models::{DocumentPage, PageTextRevision};
+use foia::repository::diesel_document::Projection;
+use foia::services::page_text;
+
+/// Editor recorded for corrections made with the access token when none
+/// is given.
+const OPERATOR: &str = "operator";
+
+/// Which version's page; the current version by default.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PageTextParams {
+    pub version: Option<i64>,
+}
+
+/// Request body for `PUT /api/documents/{doc_id}/pages/{page_number}/text`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PageTextBody {
+    /// Corrected text of the page. Sending the current text confirms it.
+    pub text: String,
+    /// Editor name for corrections made with the access token;
+    /// corrections made with an API key are credited to the key.
+    #[serde(default)]
+    pub editor: Option<String>,
+}
+
+/// One correction of a page's text.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PageTextRevisionItem {
+    pub id: i64,
+    pub editor: String,
+    pub previous_text: Option<String>,
+    pub text: String,
+    /// Changed lines: `-` removed, `+` added, under `@@ -old +new @@`
+    /// line markers. Empty when the text was confirmed unchanged.
+    pub diff: String,
+    pub created_at: String,
+}
+
+impl From<PageTextRevision> for PageTextRevisionItem {
+    fn from(revision: PageTextRevision) -> Self {
+        Self {
+            id: revision.id,
+            editor: revision.editor,
+            previous_text: revision.previous_text,
+            text: revision.text,
+            diff: revision.diff,
+            created_at: revision.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Correct the text of a page, marking it human-verified. The document's
+/// text and search are updated with the correction, and later OCR runs
+/// leave it alone.
+#[utoipa::path(
+    put,
+    path = "/api/documents/{doc_id}/pages/{page_number}/text",
+    params(
+        ("doc_id" = String, Path, description = "Document ID"),
+        ("page_number" = u32, Path, description = "Page number"),
+        PageTextParams
+    ),
+    request_body = PageTextBody,
+    responses(
+        (status = 200, description = "Saved revision", body = PageTextRevisionItem),
+        (status = 400, description = "Text too long"),
+        (status = 401, description = "No API key or access token"),
+        (status = 404, description = "Document or page not found")
+    ),
+    tag = "Pages"
+)]
+pub async fn update_page_text(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    client: Option<Extension<ApiClient>>,
+    Path((doc_id, page_number)): Path<(String, u32)>,
+    Query(params): Query<PageTextParams>,
+    Json(body): Json<PageTextBody>,
+) -> impl IntoResponse {
+    let editor = match (client, viewer.is_internal()) {
+        (Some(Extension(client)), _) => client.name,
+        (None, true) => body
+            .editor
+            .filter(|e| !e.trim().is_empty())
+            .unwrap_or_else(|| OPERATOR.to_string()),
+        (None, false) => {
+            return ApiResponse::error(
+                StatusCode::UNAUTHORIZED,
+                "Correcting page text requires an API key or the access token",
+            )
+            .into_response();
+        }
+    };
+
+    let page = match find_page(&state, &doc_id, page_number, params.version).await {
+        Ok(page) => page,
+        Err(response) => return response,
+    };
+    match page_text::edit_page_text(&state.doc_repo, &page, &body.text, &editor).await {
+        Ok(revision) => {
+            state.read_cache.invalidate_document(&doc_id).await;
+            ApiResponse::ok(PageTextRevisionItem::from(revision)).into_response()
+        }
+        Err(e) if e.to_string().starts_with("text is longer") => {
+            bad_request(&e.to_string()).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Corrections of a page's text, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/documents/{doc_id}/pages/{page_number}/revisions",
+    params(
+        ("doc_id" = String, Path, description = "Document ID"),
+        ("page_number" = u32, Path, description = "Page number"),
+        PageTextParams
+    ),
+    responses(
+        (status = 200, description = "Revisions", body = Vec<PageTextRevisionItem>),
+        (status = 404, description = "Document or page not found")
+    ),
+    tag = "Pages"
+)]
+pub async fn list_page_text_revisions(
+    State(state): State<AppState>,
+    Path((doc_id, page_number)): Path<(String, u32)>,
+    Query(params): Query<PageTextParams>,
+) -> impl IntoResponse {
+    let page = match find_page(&state, &doc_id, page_number, params.version).await {
+        Ok(page) => page,
+        Err(response) => return response,
+    };
+    match state.doc_repo.get_page_text_revisions(page.id).await {
+        Ok(revisions) => {
+            let items: Vec<PageTextRevisionItem> = revisions
+                .into_iter()
+                .map(PageTextRevisionItem::from)
+                .collect();
+            ApiResponse::ok(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// A page of `version` (default: current) of a document.
+async fn find_page(
+    state: &AppState,
+    doc_id: &str,
+    page_number: u32,
+    version: Option<i64>,
+) -> Result<DocumentPage, axum::response::Response> {
+    let doc = match state
+        .doc_repo
+        .get_projected(doc_id, Projection::Metadata)
+        .await
+    {
+        Ok(Some(d)) => d,
+        Ok(None) => return Err(not_found("Document not found").into_response()),
+        Err(e) => return Err(internal_error(e).into_response()),
+    };
+    let version = match version {
+        Some(id) => doc.versions.iter().find(|v| v.id == id),
+        None => doc.current_version(),
+    };
+    let Some(version) = version else {
+        return Err(not_found("Version not found").into_response());
+    };
+    match state
+        .doc_repo
+        .get_page(&doc.id, version.id as i32, page_number)
+        .await
+    {
+        Ok(Some(page)) => Ok(page),
+        Ok(None) => Err(not_found("Page not found").into_response()),
+        Err(e) => Err(internal_error(e).into_response()),
+    }
+}
@@ -28,6 +28,8 @@ pub struct PageData {
     pub image_base64: Option<String>,
     pub ocr_status: String,
     pub deepseek_text: Option<String>,
+    /// A curator corrected or confirmed `final_text`.
+    pub verified: bool,
 }
 
 /// Pages API response.
@@ -59,7 +61,7 @@ pub async fn api_document_pages(
     Path(doc_id): Path<String>,
     Query(params): Query<PagesParams>,
 ) -> impl IntoResponse {
-    let doc = match state.doc_repo.get(&doc_id).await {
+    let doc = match state.read_cache.document(&state.doc_repo, &doc_id).await {
         Ok(Some(d)) => d,
         Ok(None) => {
             return (StatusCode::NOT_FOUND, "Document not found").into_response();
@@ -113,6 +115,12 @@ pub async fn api_document_pages(
         .await
         .unwrap_or_default();
 
+    let verified_ids = state
+        .doc_repo
+        .verified_page_ids(&page_ids)
+        .await
+        .unwrap_or_default();
+
     let mut deepseek_map: std::collections::HashMap<i64, Option<String>> =
         std::collections::HashMap::new();
     for (page_id, ocr_results) in all_ocr_results {
@@ -127,7 +135,12 @@ pub async fn api_document_pages(
     }
 
     let is_pdf = version.mime_type.contains("pdf");
-    let pdf_path = version.resolve_path(&state.documents_dir, &doc.source_url, &doc.title);
+    // Encrypted documents are decrypted once to a temp file shared by all pages
+    let stored_path = version.resolve_path(&state.documents_dir, &doc.source_url, &doc.title);
+    let pdf_path = std::sync::Arc::new(match foia::storage::plaintext_path(&stored_path) {
+        Ok(path) => path,
+        Err(_) => foia::storage::PlaintextPath::Original(stored_path),
+    });
 
     let page_data_list: Vec<PageData> = if is_pdf {
         let mut handles = Vec::new();
@@ -140,6 +153,7 @@ pub async fn api_document_pages(
             let final_text = page.final_text;
             let ocr_status = page.ocr_status.as_str().to_string();
             let deepseek_text = deepseek_map.get(&page_id).cloned().flatten();
+            let verified = verified_ids.contains(&page_id);
 
             let handle = tokio::task::spawn_blocking(move || {
                 let image_base64 = render_pdf_page_to_base64(&path, page_num);
@@ -151,6 +165,7 @@ pub async fn api_document_pages(
                     image_base64,
                     ocr_status,
                     deepseek_text,
+                    verified,
                 }
             });
             handles.push(handle);
@@ -177,6 +192,7 @@ pub async fn api_document_pages(
                     image_base64: None,
                     ocr_status: page.ocr_status.as_str().to_string(),
                     deepseek_text,
+                    verified: verified_ids.contains(&page.id),
                 }
             })
             .collect()
@@ -0,0 +1,33 @@
+//! Write protection for read-only replica mode.
+
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+
+/// POST endpoints that only read data.
+const READ_ONLY_POSTS: &[&str] = &["/api/sync/export", "/api/graphql"];
+
+/// Whether a request only reads data.
+pub(super) fn is_read_request(req: &Request) -> bool {
+    let method = req.method();
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || (*method == Method::POST && READ_ONLY_POSTS.contains(&req.uri().path()))
+}
+
+/// Reject mutating requests when the server is attached to a read-only replica.
+pub async fn read_only_guard(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if state.read_only && !is_read_request(&req) {
+        return ApiResponse::error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Server is running in read-only replica mode",
+        )
+        .into_response();
+    }
+    next.run(req).await
+}
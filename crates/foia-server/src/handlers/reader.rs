@@ -0,0 +1,296 @@
+//! Text-first document reader.
+//!
+//! Serves the text layer of a document (final page text, or the extracted
+//! text) as headings and paragraphs with page anchors, for screen readers
+//! and anyone who can't use the page images. Citation permalinks
+//! (`/doc/{id}/v/{version}/p/{page}`) open it at one version and page.
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    Extension,
+};
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{ErrorTemplate, ReaderBlock, ReaderPage, ReaderTemplate};
+use super::super::AppState;
+use foia::models::DocumentPage;
+
+/// Longest line treated as a heading.
+const MAX_HEADING_CHARS: usize = 80;
+
+/// Document reader page, for the current version.
+pub async fn document_reader(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Path(doc_id): Path<String>,
+) -> Response {
+    render_reader(&state, &i18n, &doc_id, None, None).await
+}
+
+/// Citation permalink: the reader for one version, scrolled to a page.
+/// A `#start-end` fragment highlights that stretch of the page text.
+pub async fn citation_permalink(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Path((doc_id, version_id, page)): Path<(String, i64, u32)>,
+) -> Response {
+    render_reader(&state, &i18n, &doc_id, Some(version_id), Some(page)).await
+}
+
+async fn render_reader(
+    state: &AppState,
+    i18n: &I18n,
+    doc_id: &str,
+    version_id: Option<i64>,
+    target_page: Option<u32>,
+) -> Response {
+    let theme = state.theme().await;
+    let not_found = |message: &str| {
+        let template = ErrorTemplate {
+            title: &i18n.t("title-not-found"),
+            theme: &theme,
+            i18n,
+            message,
+        };
+        let html = template
+            .render()
+            .unwrap_or_else(|_| "Not found".to_string());
+        (StatusCode::NOT_FOUND, Html(html)).into_response()
+    };
+
+    let doc = match state.doc_repo.get(doc_id).await {
+        Ok(Some(d)) => d,
+        Ok(None) => return not_found(&i18n.t("error-document-not-found")),
+        Err(e) => {
+            let msg = format!("Failed to load document: {}", e);
+            let template = ErrorTemplate {
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n,
+                message: &msg,
+            };
+            return Html(template.render().unwrap_or(msg)).into_response();
+        }
+    };
+
+    let current_id = doc.current_version().map(|v| v.id);
+    let version_id = match version_id {
+        Some(id) if doc.versions.iter().any(|v| v.id == id) => Some(id),
+        Some(_) => return not_found(&i18n.t("error-version-not-found")),
+        None => current_id,
+    };
+
+    let page_records = match version_id {
+        Some(id) => state
+            .doc_repo
+            .get_pages(&doc.id, id as i32)
+            .await
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let extracted = doc.extracted_text.as_deref().unwrap_or_default();
+    let (pages, paged) = if !page_records.is_empty() {
+        (page_records.iter().map(page_from_record).collect(), true)
+    } else if version_id == current_id {
+        // Extracted text belongs to the current version only
+        (split_pages(extracted), extracted.contains('\u{c}'))
+    } else {
+        (Vec::new(), false)
+    };
+    if target_page.is_some_and(|n| !pages.iter().any(|p| p.number == n)) {
+        return not_found(&i18n.t("error-page-not-found"));
+    }
+    let sections = match version_id {
+        Some(id) if paged => state
+            .doc_repo
+            .get_document_sections(&doc.id, id)
+            .await
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let sections_pdf = doc
+        .versions
+        .iter()
+        .any(|v| Some(v.id) == version_id && v.mime_type == "application/pdf");
+
+    let template = ReaderTemplate {
+        title: &doc.title,
+        theme: &theme,
+        i18n,
+        doc_id: &doc.id,
+        version_id,
+        target_page,
+        pages,
+        paged,
+        sections,
+        sections_pdf,
+    };
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+    .into_response()
+}
+
+/// Reader page from a page record: the chosen text, else OCR, else the
+/// PDF text layer.
+fn page_from_record(page: &DocumentPage) -> ReaderPage {
+    let text = [&page.final_text, &page.ocr_text, &page.pdf_text]
+        .into_iter()
+        .flatten()
+        .find(|t| !t.trim().is_empty())
+        .map(String::as_str)
+        .unwrap_or_default();
+    ReaderPage {
+        number: page.page_number,
+        blocks: text_blocks(text),
+    }
+}
+
+/// Reader pages from extracted text. pdftotext separates pages with form
+/// feeds; text without them is all page 1.
+fn split_pages(text: &str) -> Vec<ReaderPage> {
+    let mut pages: Vec<ReaderPage> = text
+        .split('\u{c}')
+        .enumerate()
+        .map(|(i, page)| ReaderPage {
+            number: i as u32 + 1,
+            blocks: text_blocks(page),
+        })
+        .collect();
+    // pdftotext ends the last page with a form feed too
+    if pages.last().is_some_and(|p| p.blocks.is_empty()) {
+        pages.pop();
+    }
+    pages
+}
+
+/// Split page text into headings and paragraphs.
+///
+/// Paragraphs are separated by blank lines; their lines are rejoined so
+/// screen readers don't pause at every line break, and words hyphenated
+/// across lines are mended. A short capitalized line at the start of a
+/// paragraph is a heading.
+fn text_blocks(text: &str) -> Vec<ReaderBlock> {
+    let mut blocks = Vec::new();
+    let mut paragraph = String::new();
+
+    let flush = |blocks: &mut Vec<ReaderBlock>, paragraph: &mut String| {
+        if !paragraph.is_empty() {
+            blocks.push(ReaderBlock {
+                heading: false,
+                text: std::mem::take(paragraph),
+            });
+        }
+    };
+
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            flush(&mut blocks, &mut paragraph);
+        } else if paragraph.is_empty() && is_heading(&line) {
+            blocks.push(ReaderBlock {
+                heading: true,
+                text: line,
+            });
+        } else if paragraph.is_empty() {
+            paragraph = line;
+        } else if let Some(stem) = hyphenated(&paragraph, &line) {
+            paragraph.truncate(stem);
+            paragraph.push_str(&line);
+        } else {
+            paragraph.push(' ');
+            paragraph.push_str(&line);
+        }
+    }
+    flush(&mut blocks, &mut paragraph);
+    blocks
+}
+
+/// Whether a line reads as a heading: short, mostly letters, in capitals,
+/// and not ending like a sentence.
+fn is_heading(line: &str) -> bool {
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    let visible = line.chars().filter(|c| !c.is_whitespace()).count();
+    let upper = letters.iter().filter(|c| c.is_uppercase()).count();
+    line.chars().count() <= MAX_HEADING_CHARS
+        && letters.len() >= 3
+        && letters.len() * 2 >= visible
+        && upper * 10 >= letters.len() * 8
+        && !line.ends_with(['.', ',', ';'])
+}
+
+/// Where to cut `paragraph` to join `next` onto a word hyphenated across
+/// the line break (`"exam-"` + `"ple"`), if it was.
+fn hyphenated(paragraph: &str, next: &str) -> Option<usize> {
+    let stem = paragraph.strip_suffix('-')?;
+    let broken = stem.chars().last().is_some_and(char::is_alphabetic)
+        && next.chars().next().is_some_and(char::is_lowercase);
+    broken.then_some(stem.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(blocks: &[ReaderBlock]) -> Vec<String> {
+        blocks
+            .iter()
+            .map(|b| {
+                if b.heading {
+                    format!("# {}", b.text)
+                } else {
+                    b.text.clone()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_blocks() {
+        let text = "MEMORANDUM FOR THE RECORD\n\
+                    SUBJECT: Budget review\n\n\
+                    The committee met on   Tuesday to dis-\n\
+                    cuss the proposed budget. Self-\n\
+                    Funded programs were\n\
+                    not reviewed.\n\n\n\
+                    1. BACKGROUND\n\
+                    Funding fell in 2019.\n";
+        assert_eq!(
+            render(&text_blocks(text)),
+            vec![
+                "# MEMORANDUM FOR THE RECORD",
+                "SUBJECT: Budget review",
+                "The committee met on Tuesday to discuss the proposed budget. \
+                 Self- Funded programs were not reviewed.",
+                "# 1. BACKGROUND",
+                "Funding fell in 2019.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heading_needs_capitals_and_no_sentence_end() {
+        assert!(is_heading("EXECUTIVE SUMMARY"));
+        assert!(!is_heading("Executive summary"));
+        assert!(!is_heading("THE REQUEST WAS DENIED."));
+        assert!(!is_heading("12-0034"));
+        assert!(!is_heading(&"A".repeat(MAX_HEADING_CHARS + 1)));
+    }
+
+    #[test]
+    fn test_split_pages_on_form_feeds() {
+        let pages = split_pages("First page\n\u{c}Second page\n\u{c}");
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].number, 2);
+        assert_eq!(pages[1].blocks[0].text, "Second page");
+
+        let single = split_pages("No page breaks");
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].number, 1);
+    }
+}
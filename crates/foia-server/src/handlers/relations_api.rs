@@ -0,0 +1,232 @@
+//! Document relation API endpoints: typed links and graph traversal.
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::models::RelationType;
+use foia::repository::diesel_document::{Projection, MAX_RELATION_DEPTH};
+use foia::repository::models::{DocumentRelationRecord, NewDocumentRelation};
+
+/// A typed edge: `document_id` is `relation_type` of `related_document_id`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelationResponse {
+    pub id: i32,
+    pub document_id: String,
+    pub related_document_id: String,
+    /// attachment-of, exhibit-to, supersedes, references, or part-of
+    pub relation_type: String,
+    /// Archive member or email attachment the edge was found through
+    pub virtual_file_id: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+impl From<DocumentRelationRecord> for RelationResponse {
+    fn from(r: DocumentRelationRecord) -> Self {
+        Self {
+            id: r.id,
+            document_id: r.document_id,
+            related_document_id: r.related_document_id,
+            relation_type: r.relation_type,
+            virtual_file_id: r.virtual_file_id,
+            note: r.note,
+            created_at: r.created_at,
+        }
+    }
+}
+
+/// A document in a relation graph.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelationNode {
+    pub id: String,
+    pub title: String,
+}
+
+/// Documents reachable from the requested one, and the edges between them.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RelationGraphResponse {
+    /// Starting document first, then in order of discovery
+    pub nodes: Vec<RelationNode>,
+    pub edges: Vec<RelationResponse>,
+    /// True if the traversal stopped early at the node limit
+    pub truncated: bool,
+}
+
+/// Query parameters for relation traversal.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RelationGraphParams {
+    /// Hops to follow from the document (default 1, max 5)
+    pub depth: Option<u32>,
+    /// Comma-separated relation types to follow (default: all)
+    pub types: Option<String>,
+}
+
+/// Create relation request.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRelationRequest {
+    /// Document on the other end of the edge
+    pub related_document_id: String,
+    /// attachment-of, exhibit-to, supersedes, references, or part-of
+    pub relation_type: String,
+    pub note: Option<String>,
+}
+
+fn parse_types(types: Option<&str>) -> Result<Vec<RelationType>, String> {
+    types
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| RelationType::from_str(s).ok_or_else(|| format!("Unknown relation type: {}", s)))
+        .collect()
+}
+
+/// Traverse relations from a document.
+#[utoipa::path(
+    get,
+    path = "/api/documents/{doc_id}/relations",
+    params(("doc_id" = String, Path, description = "Document ID"), RelationGraphParams),
+    responses(
+        (status = 200, description = "Related documents and edges", body = RelationGraphResponse),
+        (status = 400, description = "Unknown relation type"),
+        (status = 404, description = "Document not found")
+    ),
+    tag = "Relations"
+)]
+pub async fn list_relations(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+    Query(params): Query<RelationGraphParams>,
+) -> impl IntoResponse {
+    let types = match parse_types(params.types.as_deref()) {
+        Ok(t) => t,
+        Err(msg) => return bad_request(&msg).into_response(),
+    };
+    let depth = params.depth.unwrap_or(1).clamp(1, MAX_RELATION_DEPTH);
+
+    match state
+        .doc_repo
+        .get_projected(&doc_id, Projection::Metadata)
+        .await
+    {
+        Ok(None) => return not_found("Document not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+        Ok(Some(_)) => {}
+    }
+
+    match state
+        .doc_repo
+        .get_relation_graph(&doc_id, depth, &types)
+        .await
+    {
+        Ok(graph) => ApiResponse::ok(RelationGraphResponse {
+            nodes: graph
+                .nodes
+                .into_iter()
+                .map(|(id, title)| RelationNode { id, title })
+                .collect(),
+            edges: graph.edges.into_iter().map(Into::into).collect(),
+            truncated: graph.truncated,
+        })
+        .into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Link a document to another one.
+#[utoipa::path(
+    post,
+    path = "/api/documents/{doc_id}/relations",
+    params(("doc_id" = String, Path, description = "Document ID")),
+    request_body = CreateRelationRequest,
+    responses(
+        (status = 200, description = "Created (or existing) relation", body = RelationResponse),
+        (status = 400, description = "Unknown relation type or self-link"),
+        (status = 404, description = "Document not found")
+    ),
+    tag = "Relations"
+)]
+pub async fn create_relation(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+    Json(body): Json<CreateRelationRequest>,
+) -> impl IntoResponse {
+    let Some(relation_type) = RelationType::from_str(body.relation_type.trim()) else {
+        return bad_request(&format!("Unknown relation type: {}", body.relation_type))
+            .into_response();
+    };
+    let related_id = body.related_document_id.trim();
+    if related_id == doc_id {
+        return bad_request("A document cannot be related to itself").into_response();
+    }
+
+    for id in [doc_id.as_str(), related_id] {
+        match state.doc_repo.get_projected(id, Projection::Metadata).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return not_found("Document not found").into_response(),
+            Err(e) => return internal_error(e).into_response(),
+        }
+    }
+
+    let note = body
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let new = NewDocumentRelation {
+        document_id: &doc_id,
+        related_document_id: related_id,
+        relation_type: relation_type.as_str(),
+        virtual_file_id: None,
+        note,
+        created_at: &created_at,
+    };
+    if let Err(e) = state.doc_repo.add_relation(&new).await {
+        return internal_error(e).into_response();
+    }
+
+    // Return the stored edge, which may predate this request
+    match state
+        .doc_repo
+        .find_relation(&doc_id, related_id, relation_type)
+        .await
+    {
+        Ok(Some(record)) => ApiResponse::ok(RelationResponse::from(record)).into_response(),
+        Ok(None) => internal_error("Relation was not stored").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Delete a relation from either of its documents.
+#[utoipa::path(
+    delete,
+    path = "/api/documents/{doc_id}/relations/{relation_id}",
+    params(
+        ("doc_id" = String, Path, description = "Document ID"),
+        ("relation_id" = i32, Path, description = "Relation ID"),
+    ),
+    responses(
+        (status = 200, description = "Relation deleted"),
+        (status = 404, description = "Relation not found")
+    ),
+    tag = "Relations"
+)]
+pub async fn delete_relation(
+    State(state): State<AppState>,
+    Path((doc_id, relation_id)): Path<(String, i32)>,
+) -> impl IntoResponse {
+    match state.doc_repo.delete_relation(&doc_id, relation_id).await {
+        Ok(true) => ApiResponse::ok(relation_id).into_response(),
+        Ok(false) => not_found("Relation not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
@@ -10,8 +10,8 @@ use utoipa::{IntoParams, ToSchema};
 
 use super::super::AppState;
 use super::api_types::{
-    ApiResponse, CrawlState, FailedUrl, QueueItem, QueueResponse, RecentUrl, RequestStats,
-    RetryResponse, ScraperCrawlStats, ScraperInfo, ScraperStatusResponse,
+    ApiResponse, CrawlState, FailedUrl, PauseResponse, QueueItem, QueueResponse, RecentUrl,
+    RequestStats, RetryResponse, ScraperCrawlStats, ScraperInfo, ScraperStatusResponse,
 };
 use super::helpers::{internal_error, not_found};
 
@@ -25,7 +25,11 @@ use super::helpers::{internal_error, not_found};
     tag = "Scrapers"
 )]
 pub async fn list_scrapers(State(state): State<AppState>) -> impl IntoResponse {
-    let sources = state.source_repo.get_all().await.unwrap_or_default();
+    let sources = state
+        .read_cache
+        .sources(&state.source_repo)
+        .await
+        .unwrap_or_default();
     let source_counts = state
         .doc_repo
         .get_all_source_counts()
@@ -52,6 +56,7 @@ pub async fn list_scrapers(State(state): State<AppState>) -> impl IntoResponse {
                     urls_failed: st.urls_failed,
                     has_pending: st.crawl_state.has_pending_urls,
                 }),
+                paused: s.paused,
             }
         })
         .collect();
@@ -115,6 +120,7 @@ pub async fn get_scrape_status(
         source_id,
         name: source.name,
         last_scraped: source.last_scraped.map(|d| d.to_rfc3339()),
+        paused: source.paused,
         crawl_state: crawl_state.map(|s| CrawlState {
             discovered: s.urls_discovered,
             fetched: s.urls_fetched,
@@ -221,3 +227,50 @@ pub async fn retry_failed(
         Err(e) => internal_error(e).into_response(),
     }
 }
+
+/// Pause a source: no more URLs are crawled or downloaded for it until it
+/// is resumed. Requests already in flight finish.
+#[utoipa::path(
+    post,
+    path = "/api/scrapers/{source_id}/pause",
+    params(("source_id" = String, Path, description = "Source ID")),
+    responses(
+        (status = 200, description = "Source paused", body = PauseResponse),
+        (status = 404, description = "Source not found")
+    ),
+    tag = "Scrapers"
+)]
+pub async fn pause_source(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> impl IntoResponse {
+    set_paused(&state, source_id, true).await.into_response()
+}
+
+/// Resume a paused source.
+#[utoipa::path(
+    post,
+    path = "/api/scrapers/{source_id}/resume",
+    params(("source_id" = String, Path, description = "Source ID")),
+    responses(
+        (status = 200, description = "Source resumed", body = PauseResponse),
+        (status = 404, description = "Source not found")
+    ),
+    tag = "Scrapers"
+)]
+pub async fn resume_source(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+) -> impl IntoResponse {
+    set_paused(&state, source_id, false).await.into_response()
+}
+
+async fn set_paused(state: &AppState, source_id: String, paused: bool) -> axum::response::Response {
+    let result = state.source_repo.set_paused(&source_id, paused).await;
+    state.read_cache.invalidate_sources();
+    match result {
+        Ok(true) => ApiResponse::ok(PauseResponse { source_id, paused }).into_response(),
+        Ok(false) => not_found("Source not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
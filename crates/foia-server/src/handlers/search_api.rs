@@ -1,16 +1,22 @@
-//! Full-text search API endpoint for page content.
+//! Search API endpoints for page content, documents and data file column
+//! names.
 
 use axum::{
     extract::{Query, State},
-    response::IntoResponse,
-    Json,
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
 use super::super::AppState;
-use super::helpers::{bad_request, internal_error, paginate, PaginatedResponse};
+use super::access::Viewer;
+use super::helpers::{bad_request, internal_error, not_found, paginate, PaginatedResponse};
 use foia::models::DocumentVersion;
+use foia::repository::diesel_document::{BrowseParams, Projection, SourceScope};
+use foia::services::search::{FacetCount, SearchRequest};
+use foia::services::sections::search_section;
 
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct SearchQuery {
@@ -20,6 +26,8 @@ pub struct SearchQuery {
     pub source: Option<String>,
     /// Filter to a single document
     pub document_id: Option<String>,
+    /// Filter to one section of `document_id` (see `/api/documents/{doc_id}/sections`)
+    pub section: Option<i64>,
     /// Page number (1-indexed)
     pub page: Option<usize>,
     /// Items per page (default: 50, max: 200)
@@ -34,25 +42,37 @@ pub struct SearchResult {
     pub page_number: i32,
     pub headline: String,
     pub file_url: String,
+    /// Set when the match is in an archive member or email attachment;
+    /// `document_id` and `file_url` then point at the parent document.
+    pub virtual_file_id: Option<String>,
+    /// Path of the matching member within the parent document.
+    pub archive_path: Option<String>,
 }
 
 /// Search document page content.
 ///
 /// Uses Postgres full-text search (tsvector/tsquery) with headline snippets,
 /// or LIKE fallback on SQLite. Returns page-level matches — a document can
-/// appear multiple times with different page numbers and snippets.
+/// appear multiple times with different page numbers and snippets. Matches in
+/// archive members and attachments are reported against their parent
+/// document with page number 0. Queries are expanded with the alias
+/// dictionary, so "OIG" also finds "Office of Inspector General".
+/// With `section`, only the pages of that section of `document_id` are
+/// searched, ignoring case.
 #[utoipa::path(
     get,
     path = "/api/search",
     params(SearchQuery),
     responses(
         (status = 200, description = "Paginated search results", body = PaginatedResponse<SearchResult>),
-        (status = 400, description = "Missing or empty search query")
+        (status = 400, description = "Missing or empty search query"),
+        (status = 404, description = "Section not found")
     ),
     tag = "Search"
 )]
 pub async fn search_content(
     State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
     Query(params): Query<SearchQuery>,
 ) -> impl IntoResponse {
     let q = params.q.trim();
@@ -62,9 +82,22 @@ pub async fn search_content(
 
     let (page, per_page, offset) = paginate(params.page, params.per_page);
 
+    let terms = match state.doc_repo.expand_search_query(q).await {
+        Ok(t) => t,
+        Err(e) => return internal_error(e).into_response(),
+    };
+
+    if let Some(section_id) = params.section {
+        return search_in_section(&state, &viewer, &params, section_id, &terms).await;
+    }
+
     let total = match state
         .doc_repo
-        .count_page_content_matches(q, params.source.as_deref(), params.document_id.as_deref())
+        .count_page_content_matches(
+            &terms,
+            params.source.as_deref(),
+            params.document_id.as_deref(),
+        )
         .await
     {
         Ok(c) => c,
@@ -74,7 +107,7 @@ pub async fn search_content(
     let rows = match state
         .doc_repo
         .search_page_content(
-            q,
+            &terms,
             params.source.as_deref(),
             params.document_id.as_deref(),
             per_page,
@@ -88,6 +121,7 @@ pub async fn search_content(
 
     let items: Vec<SearchResult> = rows
         .into_iter()
+        .filter(|r| viewer.allows(&r.document_id, &r.source_id))
         .map(|r| {
             let file_url = DocumentVersion::build_file_url(
                 &r.content_hash,
@@ -104,9 +138,423 @@ pub async fn search_content(
                 page_number: r.page_number,
                 headline: r.headline,
                 file_url,
+                virtual_file_id: r.virtual_file_id,
+                archive_path: r.archive_path,
             }
         })
         .collect();
 
     Json(PaginatedResponse::new(items, page, per_page, total)).into_response()
 }
+
+/// Page matches within one section of a document.
+async fn search_in_section(
+    state: &AppState,
+    viewer: &Viewer,
+    params: &SearchQuery,
+    section_id: i64,
+    terms: &[String],
+) -> Response {
+    let Some(doc_id) = params.document_id.as_deref() else {
+        return bad_request("'section' needs 'document_id'").into_response();
+    };
+    let section = match state
+        .doc_repo
+        .get_document_section(doc_id, section_id)
+        .await
+    {
+        Ok(Some(s)) => s,
+        Ok(None) => return not_found("Section not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let doc = match state.doc_repo.get(doc_id).await {
+        Ok(Some(d)) if viewer.allows(&d.id, &d.source_id) => d,
+        Ok(_) => return not_found("Document not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let Some(version) = doc.versions.iter().find(|v| v.id == section.version_id) else {
+        return not_found("Section version not found").into_response();
+    };
+    let pages = match state
+        .doc_repo
+        .get_pages(doc_id, section.version_id as i32)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => return internal_error(e).into_response(),
+    };
+
+    let (page, per_page, offset) = paginate(params.page, params.per_page);
+    let matches = search_section(&pages, &section, terms);
+    let total = matches.len() as u64;
+    let file_url = version.file_url(&doc.source_url, &doc.title);
+    let items: Vec<SearchResult> = matches
+        .into_iter()
+        .skip(offset)
+        .take(per_page)
+        .map(|m| SearchResult {
+            document_id: doc.id.clone(),
+            title: doc.title.clone(),
+            source_id: doc.source_id.clone(),
+            page_number: m.page_number as i32,
+            headline: m.snippet,
+            file_url: file_url.clone(),
+            virtual_file_id: None,
+            archive_path: None,
+        })
+        .collect();
+
+    Json(PaginatedResponse::new(items, page, per_page, total)).into_response()
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ColumnSearchQuery {
+    /// Text to find in column names (case-insensitive)
+    pub q: String,
+    /// Filter by source
+    pub source: Option<String>,
+    /// Maximum results (default: 100, max: 500)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ColumnSearchResult {
+    pub document_id: String,
+    pub title: String,
+    pub source_id: String,
+    /// Sheet name; the file name for CSV files.
+    pub sheet: String,
+    /// Zero-based column position within the sheet.
+    pub position: i32,
+    pub name: String,
+    /// Document page, which previews the sheet and links its CSV download.
+    pub document_url: String,
+}
+
+/// Search column names of CSV files and spreadsheets.
+///
+/// Header rows are indexed when data files are analyzed, so a search for
+/// "badge number" finds every release with such a column.
+#[utoipa::path(
+    get,
+    path = "/api/search/columns",
+    params(ColumnSearchQuery),
+    responses(
+        (status = 200, description = "Matching columns", body = Vec<ColumnSearchResult>),
+        (status = 400, description = "Missing or empty search query")
+    ),
+    tag = "Search"
+)]
+pub async fn search_columns(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(params): Query<ColumnSearchQuery>,
+) -> impl IntoResponse {
+    let q = params.q.trim();
+    if q.is_empty() {
+        return bad_request("Search query 'q' cannot be empty").into_response();
+    }
+    let limit = params.limit.unwrap_or(100).clamp(1, 500);
+
+    match state
+        .doc_repo
+        .search_columns(q, params.source.as_deref(), limit)
+        .await
+    {
+        Ok(rows) => {
+            let items: Vec<ColumnSearchResult> = rows
+                .into_iter()
+                .filter(|c| viewer.allows(&c.document_id, &c.source_id))
+                .map(|c| ColumnSearchResult {
+                    document_url: format!("/documents/{}", c.document_id),
+                    document_id: c.document_id,
+                    title: c.title,
+                    source_id: c.source_id,
+                    sheet: c.sheet,
+                    position: c.position,
+                    name: c.name,
+                })
+                .collect();
+            Json(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DocumentSearchQuery {
+    /// Query text. With the search index, may be empty to count facets
+    /// over all documents, and supports `"phrases"`, `+required`,
+    /// `-excluded` and `field:term` (title, body, tags, entities).
+    pub q: Option<String>,
+    /// Filter by source
+    pub source: Option<String>,
+    /// Comma-separated tags (all of)
+    pub tags: Option<String>,
+    /// Earliest publication date (YYYY-MM-DD); needs the search index
+    pub from: Option<String>,
+    /// Latest publication date (YYYY-MM-DD); needs the search index
+    pub to: Option<String>,
+    /// Page number (1-indexed)
+    pub page: Option<usize>,
+    /// Items per page (default: 50, max: 200)
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentSearchHit {
+    pub document_id: String,
+    pub title: String,
+    pub source_id: String,
+    /// Relevance score (search index only)
+    pub score: Option<f32>,
+    /// Matching text with terms in `<b>` (search index only)
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FacetValue {
+    pub value: String,
+    pub count: u64,
+}
+
+impl From<FacetCount> for FacetValue {
+    fn from(f: FacetCount) -> Self {
+        Self {
+            value: f.value,
+            count: f.count,
+        }
+    }
+}
+
+/// Counts over all matching documents; empty without the search index.
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct DocumentSearchFacets {
+    pub sources: Vec<FacetValue>,
+    pub tags: Vec<FacetValue>,
+    pub years: Vec<FacetValue>,
+    pub entity_types: Vec<FacetValue>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentSearchResponse {
+    /// `database`, `tantivy`, `meilisearch` or `opensearch`
+    pub backend: String,
+    pub items: Vec<DocumentSearchHit>,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: u64,
+    pub facets: DocumentSearchFacets,
+}
+
+fn parse_date_param(name: &str, value: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                .map_err(|_| format!("'{}' must be a date (YYYY-MM-DD)", name))
+        })
+        .transpose()
+}
+
+/// Search documents, ranked by relevance, with facet counts.
+///
+/// Answered by the search index when `search.backend` selects one
+/// (tantivy, Meilisearch or OpenSearch): hits are ranked over title, text,
+/// tags and entities, and the response counts
+/// matches per source, tag, year and entity type. Otherwise the database
+/// answers, newest first and without facets. Queries are expanded with the
+/// alias dictionary either way.
+#[utoipa::path(
+    get,
+    path = "/api/search/documents",
+    params(DocumentSearchQuery),
+    responses(
+        (status = 200, description = "Matching documents", body = DocumentSearchResponse),
+        (status = 400, description = "Invalid query")
+    ),
+    tag = "Search"
+)]
+pub async fn search_documents(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(params): Query<DocumentSearchQuery>,
+) -> impl IntoResponse {
+    let q = params.q.as_deref().unwrap_or_default().trim().to_string();
+    let tags: Vec<String> = params
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect();
+    let (date_from, date_to) = match (
+        parse_date_param("from", params.from.as_deref()),
+        parse_date_param("to", params.to.as_deref()),
+    ) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return bad_request(&e).into_response(),
+    };
+    let (page, per_page, offset) = paginate(params.page, params.per_page);
+
+    let Some(index) = state.search_index.clone() else {
+        if q.is_empty() {
+            return bad_request("Search query 'q' cannot be empty").into_response();
+        }
+        if date_from.is_some() || date_to.is_some() {
+            return bad_request("Date filters need a search index (search.backend)")
+                .into_response();
+        }
+        return search_documents_in_database(
+            &state,
+            &viewer,
+            &q,
+            params.source.as_deref(),
+            &tags,
+            page,
+            per_page,
+            offset,
+        )
+        .await;
+    };
+
+    let expansions = if q.is_empty() {
+        Vec::new()
+    } else {
+        match state.doc_repo.expand_search_query(&q).await {
+            Ok(terms) => terms.into_iter().skip(1).collect(),
+            Err(e) => return internal_error(e).into_response(),
+        }
+    };
+    let request = SearchRequest {
+        query: q,
+        expansions,
+        source_id: params.source.clone(),
+        tags,
+        date_from,
+        date_to,
+        limit: per_page,
+        offset,
+        access: viewer.owned_filter(),
+    };
+    let results = match index.search(&request).await {
+        Ok(results) => results,
+        Err(e) => return internal_error(e).into_response(),
+    };
+
+    // The index may lag behind deletions and title changes
+    let ids: Vec<String> = results.hits.iter().map(|h| h.document_id.clone()).collect();
+    let docs = match state
+        .doc_repo
+        .get_batch_projected(&ids, Projection::Metadata)
+        .await
+    {
+        Ok(docs) => docs,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let items = results
+        .hits
+        .into_iter()
+        .filter_map(|hit| {
+            let doc = docs.iter().find(|d| d.id == hit.document_id)?;
+            viewer
+                .allows(&doc.id, &doc.source_id)
+                .then(|| DocumentSearchHit {
+                    document_id: hit.document_id,
+                    title: doc.title.clone(),
+                    source_id: doc.source_id.clone(),
+                    score: Some(hit.score),
+                    snippet: hit.snippet,
+                })
+        })
+        .collect();
+
+    let facets = results.facets;
+    Json(DocumentSearchResponse {
+        backend: index.backend().as_str().to_string(),
+        items,
+        page,
+        per_page,
+        total: results.total,
+        facets: DocumentSearchFacets {
+            sources: facets.sources.into_iter().map(FacetValue::from).collect(),
+            tags: facets.tags.into_iter().map(FacetValue::from).collect(),
+            years: facets.years.into_iter().map(FacetValue::from).collect(),
+            entity_types: facets
+                .entity_types
+                .into_iter()
+                .map(FacetValue::from)
+                .collect(),
+        },
+    })
+    .into_response()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn search_documents_in_database(
+    state: &AppState,
+    viewer: &Viewer,
+    q: &str,
+    source: Option<&str>,
+    tags: &[String],
+    page: usize,
+    per_page: usize,
+    offset: usize,
+) -> axum::response::Response {
+    let total = match state
+        .doc_repo
+        .browse_count(
+            SourceScope::from(source),
+            None,
+            &[],
+            tags,
+            &[],
+            &[],
+            Some(q),
+            viewer.filter(),
+        )
+        .await
+    {
+        Ok(total) => total,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let docs = match state
+        .doc_repo
+        .browse(BrowseParams {
+            source_id: source,
+            tags,
+            search_query: Some(q),
+            limit: per_page as u32,
+            offset: offset as u32,
+            projection: Projection::Metadata,
+            access: viewer.filter(),
+            ..Default::default()
+        })
+        .await
+    {
+        Ok(docs) => docs,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let items = docs
+        .into_iter()
+        .map(|d| DocumentSearchHit {
+            document_id: d.id,
+            title: d.title,
+            source_id: d.source_id,
+            score: None,
+            snippet: None,
+        })
+        .collect();
+    Json(DocumentSearchResponse {
+        backend: "database".to_string(),
+        items,
+        page,
+        per_page,
+        total,
+        facets: DocumentSearchFacets::default(),
+    })
+    .into_response()
+}
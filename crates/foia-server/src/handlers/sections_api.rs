@@ -0,0 +1,178 @@
+//! Section API endpoints: a document's table of contents and exports of
+//! single sections.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::models::DocumentSection;
+use foia::services::bundles::extract_pages;
+use foia::services::sections::section_text;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SectionItem {
+    pub id: i64,
+    /// Nesting depth, 1 for top-level chapters.
+    pub level: u32,
+    pub title: String,
+    pub first_page: u32,
+    pub last_page: u32,
+    /// `bookmarks` (PDF outline) or `headings` (detected in the text).
+    pub origin: String,
+    /// Reader page, scrolled to the section's first page.
+    pub reader_url: String,
+}
+
+impl From<DocumentSection> for SectionItem {
+    fn from(section: DocumentSection) -> Self {
+        Self {
+            reader_url: format!(
+                "/documents/{}/read#page-{}",
+                section.document_id, section.first_page
+            ),
+            id: section.id,
+            level: section.level,
+            title: section.title,
+            first_page: section.first_page,
+            last_page: section.last_page,
+            origin: section.origin,
+        }
+    }
+}
+
+/// Chapters and sections of a document's current version, in reading
+/// order. Empty until `foia sections extract` or OCR has derived them.
+#[utoipa::path(
+    get,
+    path = "/api/documents/{doc_id}/sections",
+    params(("doc_id" = String, Path, description = "Document ID")),
+    responses(
+        (status = 200, description = "Table of contents", body = Vec<SectionItem>),
+        (status = 404, description = "Document not found")
+    ),
+    tag = "Documents"
+)]
+pub async fn list_sections(
+    State(state): State<AppState>,
+    Path(doc_id): Path<String>,
+) -> impl IntoResponse {
+    let doc = match state.doc_repo.get(&doc_id).await {
+        Ok(Some(d)) => d,
+        Ok(None) => return not_found("Document not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let Some(version) = doc.current_version() else {
+        return Json(Vec::<SectionItem>::new()).into_response();
+    };
+    match state
+        .doc_repo
+        .get_document_sections(&doc.id, version.id)
+        .await
+    {
+        Ok(sections) => {
+            let items: Vec<SectionItem> = sections.into_iter().map(SectionItem::from).collect();
+            Json(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SectionExportQuery {
+    /// `txt` (page text, default) or `pdf` (the section's pages of the PDF)
+    pub format: Option<String>,
+}
+
+/// Download one section as text or as a PDF of its pages.
+#[utoipa::path(
+    get,
+    path = "/api/documents/{doc_id}/sections/{section_id}/export",
+    params(
+        ("doc_id" = String, Path, description = "Document ID"),
+        ("section_id" = i64, Path, description = "Section ID"),
+        SectionExportQuery
+    ),
+    responses(
+        (status = 200, description = "Section text or PDF"),
+        (status = 400, description = "Unknown format, or a PDF of a document that isn't one"),
+        (status = 404, description = "Section not found")
+    ),
+    tag = "Documents"
+)]
+pub async fn export_section(
+    State(state): State<AppState>,
+    Path((doc_id, section_id)): Path<(String, i64)>,
+    Query(params): Query<SectionExportQuery>,
+) -> impl IntoResponse {
+    let section = match state
+        .doc_repo
+        .get_document_section(&doc_id, section_id)
+        .await
+    {
+        Ok(Some(s)) => s,
+        Ok(None) => return not_found("Section not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let doc = match state.doc_repo.get(&doc_id).await {
+        Ok(Some(d)) => d,
+        Ok(None) => return not_found("Document not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let Some(version) = doc.versions.iter().find(|v| v.id == section.version_id) else {
+        return not_found("Section version not found").into_response();
+    };
+
+    let stem: String = format!("{}-{}", doc.title, section.title)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .take(80)
+        .collect();
+    let (content_type, filename, body) = match params.format.as_deref().unwrap_or("txt") {
+        "txt" => {
+            let pages = match state.doc_repo.get_pages(&doc.id, version.id as i32).await {
+                Ok(p) => p,
+                Err(e) => return internal_error(e).into_response(),
+            };
+            (
+                "text/plain; charset=utf-8",
+                format!("{}.txt", stem),
+                section_text(&pages, &section).into_bytes(),
+            )
+        }
+        "pdf" => {
+            if version.mime_type != "application/pdf" {
+                return bad_request("Only sections of PDFs can be exported as PDF").into_response();
+            }
+            let stored = version.resolve_path(&state.documents_dir, &doc.source_url, &doc.title);
+            let pdf = match foia::storage::plaintext_path(&stored) {
+                Ok(path) => path,
+                Err(e) => return internal_error(e).into_response(),
+            };
+            match extract_pages(pdf.path(), section.first_page, section.last_page).await {
+                Ok(bytes) => ("application/pdf", format!("{}.pdf", stem), bytes),
+                Err(e) => return internal_error(e).into_response(),
+            }
+        }
+        _ => return bad_request("format must be txt or pdf").into_response(),
+    };
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
@@ -0,0 +1,103 @@
+//! Table previews and CSV downloads for CSV files and spreadsheets.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+
+use super::super::template_structs::SheetPreview;
+use super::super::AppState;
+use foia::models::Document;
+use foia_analysis::ocr::{is_tabular_mimetype, read_sheets, Sheet};
+
+/// Rows shown per sheet on the document page, after the header.
+const PREVIEW_ROWS: usize = 50;
+
+/// Sheets of a document's current version, or empty if it isn't tabular or
+/// the file can't be read.
+pub(super) async fn load_sheets(state: &AppState, doc: &Document) -> Vec<Sheet> {
+    let Some(version) = doc.current_version() else {
+        return vec![];
+    };
+    if !is_tabular_mimetype(&version.mime_type) {
+        return vec![];
+    }
+    let stored_path = version.resolve_path(&state.documents_dir, &doc.source_url, &doc.title);
+    let mime_type = version.mime_type.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let path = foia::storage::plaintext_path(&stored_path).ok()?;
+        read_sheets(&path, &mime_type).ok()
+    })
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+/// Previews of the first rows of each sheet.
+pub(super) fn sheet_previews(sheets: &[Sheet]) -> Vec<SheetPreview> {
+    sheets
+        .iter()
+        .enumerate()
+        .filter(|(_, sheet)| !sheet.rows.is_empty())
+        .map(|(index, sheet)| {
+            let body = &sheet.rows[1..];
+            SheetPreview {
+                index,
+                name: sheet.name.clone(),
+                header: sheet.header(),
+                rows: body.iter().take(PREVIEW_ROWS).cloned().collect(),
+                total_rows: body.len(),
+                truncated: body.len() > PREVIEW_ROWS,
+            }
+        })
+        .collect()
+}
+
+/// Download one sheet of a CSV file or spreadsheet as CSV.
+pub async fn download_sheet(
+    State(state): State<AppState>,
+    Path((doc_id, index)): Path<(String, usize)>,
+) -> impl IntoResponse {
+    let doc = match state.doc_repo.get(&doc_id).await {
+        Ok(Some(d)) => d,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Document not found").into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let sheets = load_sheets(&state, &doc).await;
+    let Some(sheet) = sheets.get(index) else {
+        return (StatusCode::NOT_FOUND, "Sheet not found").into_response();
+    };
+
+    let filename = if sheets.len() > 1 {
+        format!("{}-{}.csv", doc.title, sheet.name)
+    } else {
+        format!("{}.csv", doc.title)
+    };
+    let filename: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '/' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        sheet.to_csv(),
+    )
+        .into_response()
+}
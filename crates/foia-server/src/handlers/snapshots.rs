@@ -0,0 +1,192 @@
+//! Listing page snapshot viewer handlers.
+
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse},
+    Extension,
+};
+use serde::Deserialize;
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{
+    ErrorTemplate, SnapshotDetailTemplate, SnapshotHistoryTemplate, SnapshotRow, SnapshotUrlRow,
+    SnapshotsTemplate,
+};
+use super::super::theme::Theme;
+use super::super::AppState;
+use foia::models::ListingSnapshot;
+use foia::services::listing_diff::diff_listings;
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Query params for the snapshots overview.
+#[derive(Debug, Deserialize)]
+pub struct SnapshotsParams {
+    pub source: Option<String>,
+}
+
+/// Query params for one listing page's history.
+#[derive(Debug, Deserialize)]
+pub struct SnapshotHistoryParams {
+    pub source: String,
+    pub url: String,
+}
+
+impl From<&ListingSnapshot> for SnapshotRow {
+    fn from(s: &ListingSnapshot) -> Self {
+        Self {
+            id: s.id,
+            first_seen_str: s.first_seen_at.format(DATE_FORMAT).to_string(),
+            last_seen_str: s.last_seen_at.format(DATE_FORMAT).to_string(),
+            fetch_count: s.fetch_count,
+            link_count: s.link_count,
+            hash_prefix: s.content_hash.chars().take(12).collect(),
+        }
+    }
+}
+
+fn error_page(theme: &Theme, i18n: &I18n, message: &str) -> Html<String> {
+    let template = ErrorTemplate {
+        title: &i18n.t("title-error"),
+        theme,
+        i18n,
+        message,
+    };
+    Html(template.render().unwrap_or_else(|_| message.to_string()))
+}
+
+fn render(template: impl Template) -> Html<String> {
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
+
+/// List archived listing pages with their snapshot counts.
+pub async fn list_snapshots(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Query(params): Query<SnapshotsParams>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let summaries = match state
+        .crawl_repo
+        .list_snapshot_urls(params.source.as_deref())
+        .await
+    {
+        Ok(s) => s,
+        Err(e) => return error_page(&theme, &i18n, &format!("Failed to load snapshots: {}", e)),
+    };
+
+    let fmt = |t: Option<chrono::DateTime<chrono::Utc>>| {
+        t.map(|t| t.format(DATE_FORMAT).to_string())
+            .unwrap_or_default()
+    };
+    let urls = summaries
+        .into_iter()
+        .map(|s| SnapshotUrlRow {
+            encoded_url: urlencoding::encode(&s.url).into_owned(),
+            last_changed_str: fmt(s.last_changed_at),
+            last_seen_str: fmt(s.last_seen_at),
+            source_id: s.source_id,
+            url: s.url,
+            snapshots: s.snapshots,
+        })
+        .collect();
+
+    render(SnapshotsTemplate {
+        title: "Listing Snapshots",
+        theme: &theme,
+        i18n: &i18n,
+        urls,
+    })
+}
+
+/// Show every snapshot of one listing page.
+pub async fn snapshot_history(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Query(params): Query<SnapshotHistoryParams>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let snapshots = match state
+        .crawl_repo
+        .list_listing_snapshots(&params.source, &params.url)
+        .await
+    {
+        Ok(s) if s.is_empty() => return error_page(&theme, &i18n, "No snapshots for this page"),
+        Ok(s) => s,
+        Err(e) => return error_page(&theme, &i18n, &format!("Failed to load snapshots: {}", e)),
+    };
+
+    render(SnapshotHistoryTemplate {
+        title: "Snapshot History",
+        theme: &theme,
+        i18n: &i18n,
+        source_id: params.source,
+        url: params.url,
+        snapshots: snapshots.iter().map(SnapshotRow::from).collect(),
+    })
+}
+
+/// Show a snapshot and the links that changed since the previous one.
+pub async fn snapshot_detail(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let (snapshot, content) = match state.crawl_repo.get_listing_snapshot(id).await {
+        Ok(Some(s)) => s,
+        Ok(None) => return error_page(&theme, &i18n, "Snapshot not found"),
+        Err(e) => return error_page(&theme, &i18n, &format!("Failed to load snapshot: {}", e)),
+    };
+    let previous = match state.crawl_repo.previous_listing_snapshot(&snapshot).await {
+        Ok(p) => p,
+        Err(e) => return error_page(&theme, &i18n, &format!("Failed to load snapshot: {}", e)),
+    };
+
+    let (previous_id, diff) = match previous {
+        Some((prev, prev_content)) => (
+            Some(prev.id),
+            diff_listings(&prev_content, &content, &snapshot.url),
+        ),
+        None => (None, Default::default()),
+    };
+
+    render(SnapshotDetailTemplate {
+        title: "Listing Snapshot",
+        theme: &theme,
+        i18n: &i18n,
+        snapshot: SnapshotRow::from(&snapshot),
+        encoded_url: urlencoding::encode(&snapshot.url).into_owned(),
+        source_id: snapshot.source_id,
+        url: snapshot.url,
+        previous_id,
+        added: diff.added,
+        removed: diff.removed,
+        unchanged: diff.unchanged,
+    })
+}
+
+/// Serve the archived HTML of a snapshot.
+///
+/// Sandboxed by CSP so the agency page's scripts cannot run on our origin.
+pub async fn snapshot_raw(State(state): State<AppState>, Path(id): Path<i64>) -> impl IntoResponse {
+    match state.crawl_repo.get_listing_snapshot(id).await {
+        Ok(Some((_, content))) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+                (header::CONTENT_SECURITY_POLICY, "sandbox"),
+            ],
+            content,
+        )
+            .into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Snapshot not found").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,67 @@
+//! Sources page: crawl progress per source, with pause and resume.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Extension,
+};
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{ErrorTemplate, SourceRow, SourcesTemplate};
+use super::super::AppState;
+
+/// List sources with their crawl state.
+pub async fn sources_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let sources = match state.read_cache.sources(&state.source_repo).await {
+        Ok(s) => s,
+        Err(e) => {
+            let msg = format!("Failed to load sources: {}", e);
+            let template = ErrorTemplate {
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
+                message: &msg,
+            };
+            return Html(template.render().unwrap_or(msg));
+        }
+    };
+    let stats = state.crawl_repo.get_all_stats().await.unwrap_or_default();
+
+    let sources = sources
+        .into_iter()
+        .map(|source| {
+            let crawl = stats.get(&source.id);
+            SourceRow {
+                pending: crawl.map(|s| s.urls_pending).unwrap_or(0),
+                fetched: crawl.map(|s| s.urls_fetched).unwrap_or(0),
+                failed: crawl.map(|s| s.urls_failed).unwrap_or(0),
+                last_scraped_str: source
+                    .last_scraped
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default(),
+                id: source.id,
+                name: source.name,
+                paused: source.paused,
+            }
+        })
+        .collect();
+
+    let template = SourcesTemplate {
+        title: "Sources",
+        theme: &theme,
+        i18n: &i18n,
+        sources,
+        read_only: state.read_only,
+    };
+
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
@@ -2,13 +2,18 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode, Uri},
     response::{IntoResponse, Response},
 };
 use serde::Deserialize;
 
 use super::super::assets;
 use super::super::AppState;
+use super::http_cache::{cache_visibility, etag_matches, not_modified};
+
+/// How long caches may reuse a stored file without asking again. Paths are
+/// content-addressed, so a path's content never changes.
+const FILE_MAX_AGE_SECS: u64 = 24 * 60 * 60;
 
 #[derive(Debug, Deserialize)]
 pub struct FileQuery {
@@ -20,10 +25,16 @@ pub struct FileQuery {
 /// When a `filename` query parameter is provided, the response includes a
 /// `Content-Disposition` header so browsers use the original filename for
 /// downloads instead of the content-addressable storage name.
+///
+/// Single `Range` requests get a `206 Partial Content` response so audio and
+/// video players can seek. The `ETag` is derived from the path, which
+/// embeds the content hash, so revalidation never reads the file.
 pub async fn serve_file(
     State(state): State<AppState>,
     Path(path): Path<String>,
     Query(params): Query<FileQuery>,
+    headers: HeaderMap,
+    uri: Uri,
 ) -> Response {
     let canonical_docs_dir = match state.documents_dir.canonicalize() {
         Ok(p) => p,
@@ -53,7 +64,17 @@ pub async fn serve_file(
         return (StatusCode::NOT_FOUND, "File not found").into_response();
     }
 
-    let content = match tokio::fs::read(&canonical_file).await {
+    let etag = format!("\"{}\"", &blake3::hash(path.as_bytes()).to_hex()[..32]);
+    let cache_control = format!(
+        "{}, max-age={}",
+        cache_visibility(&headers, uri.query()),
+        FILE_MAX_AGE_SECS
+    );
+    if etag_matches(&headers, &etag) {
+        return not_modified(&etag, &cache_control);
+    }
+
+    let content = match foia::storage::read_content_async(&canonical_file).await {
         Ok(c) => c,
         Err(_) => {
             return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file").into_response();
@@ -79,14 +100,70 @@ pub async fn serve_file(
         None => "inline".to_string(),
     };
 
-    (
-        [
-            (header::CONTENT_TYPE, mime),
-            (header::CONTENT_DISPOSITION, disposition),
-        ],
-        content,
-    )
-        .into_response()
+    let total = content.len() as u64;
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| parse_range(v, total));
+
+    match range {
+        Some(Some((start, end))) => (
+            StatusCode::PARTIAL_CONTENT,
+            [
+                (header::CONTENT_TYPE, mime),
+                (header::CONTENT_DISPOSITION, disposition),
+                (header::ACCEPT_RANGES, "bytes".to_string()),
+                (header::ETAG, etag),
+                (header::CACHE_CONTROL, cache_control),
+                (
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", start, end, total),
+                ),
+            ],
+            content[start as usize..=end as usize].to_vec(),
+        )
+            .into_response(),
+        Some(None) => (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{}", total))],
+        )
+            .into_response(),
+        None => (
+            [
+                (header::CONTENT_TYPE, mime),
+                (header::CONTENT_DISPOSITION, disposition),
+                (header::ACCEPT_RANGES, "bytes".to_string()),
+                (header::ETAG, etag),
+                (header::CACHE_CONTROL, cache_control),
+            ],
+            content,
+        )
+            .into_response(),
+    }
+}
+
+/// Parse a single-range `Range` header into inclusive byte offsets.
+///
+/// Returns `None` when the range can't be satisfied for a body of `len` bytes.
+/// Multi-range requests are answered with the first range only.
+fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.strip_prefix("bytes=")?.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (len.saturating_sub(suffix), len.checked_sub(1)?)
+        }
+        (start, "") => (start.parse().ok()?, len.checked_sub(1)?),
+        (start, end) => {
+            let end: u64 = end.parse().ok()?;
+            (start.parse().ok()?, end.min(len.checked_sub(1)?))
+        }
+    };
+    (start <= end && start < len).then_some((start, end))
 }
 
 /// Serve CSS.
@@ -101,3 +178,21 @@ pub async fn serve_js() -> impl IntoResponse {
         assets::JS,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=500-", 1000), Some((500, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=0-1, 5-6", 1000), Some((0, 1)));
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=5-2", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=0-", 0), None);
+    }
+}
@@ -0,0 +1,108 @@
+//! Storage usage API: where disk space goes and what can be reclaimed.
+
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::super::AppState;
+use super::access::Viewer;
+use super::helpers::internal_error;
+use foia::services::storage_report::{StorageReport, Usage};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StorageUsage {
+    /// Source ID or MIME category
+    pub key: String,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+impl From<Usage> for StorageUsage {
+    fn from(u: Usage) -> Self {
+        Self {
+            key: u.key,
+            files: u.files,
+            bytes: u.bytes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReclaimableStorage {
+    /// `duplicates`, `failed` or `orphans`
+    pub kind: String,
+    pub files: u64,
+    pub bytes: u64,
+    /// CLI command that reclaims the space
+    pub command: String,
+    /// What the command does
+    pub advice: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StorageReportResponse {
+    pub by_source: Vec<StorageUsage>,
+    /// By MIME category
+    pub by_category: Vec<StorageUsage>,
+    /// Downloaded files on disk
+    pub originals_bytes: u64,
+    /// Page OCR text, alternative OCR results and analysis output
+    pub derived_bytes: u64,
+    /// Extracted document text and final page text
+    pub text_bytes: u64,
+    /// Largest first
+    pub reclaimable: Vec<ReclaimableStorage>,
+}
+
+/// Storage usage by source, type and artifact, with reclaimable space.
+///
+/// Scans the documents directory for orphaned files, so this can take a
+/// while on large collections.
+#[utoipa::path(
+    get,
+    path = "/api/storage",
+    responses(
+        (status = 200, description = "Storage usage report", body = StorageReportResponse)
+    ),
+    tag = "Storage"
+)]
+pub async fn storage_report(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+) -> impl IntoResponse {
+    let report = match StorageReport::build(&state.doc_repo, &state.documents_dir).await {
+        Ok(report) => report,
+        Err(e) => return internal_error(e).into_response(),
+    };
+
+    // Don't name withheld sources
+    let shown = |source: &str| viewer.filter().is_none_or(|f| f.shows_source(source));
+    Json(StorageReportResponse {
+        by_source: report
+            .by_source
+            .into_iter()
+            .filter(|u| shown(&u.key))
+            .map(StorageUsage::from)
+            .collect(),
+        by_category: report
+            .by_category
+            .into_iter()
+            .map(StorageUsage::from)
+            .collect(),
+        originals_bytes: report.artifacts.originals,
+        derived_bytes: report.artifacts.derived,
+        text_bytes: report.artifacts.text,
+        reclaimable: report
+            .reclaimable
+            .into_iter()
+            .map(|r| ReclaimableStorage {
+                kind: r.kind.as_str().to_string(),
+                files: r.files,
+                bytes: r.bytes,
+                command: r.command.to_string(),
+                advice: r.advice.to_string(),
+            })
+            .collect(),
+    })
+    .into_response()
+}
@@ -0,0 +1,172 @@
+//! Instance-to-instance sync endpoints used by `foia sync push/pull`.
+//!
+//! All endpoints require `Authorization: Bearer <sync.token>` and are
+//! disabled (404) when the server has no sync token configured.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+
+use super::super::AppState;
+use super::helpers::constant_time_eq;
+use foia::services::sync::{SyncBatch, SyncCrawlState, SyncError, SyncService};
+
+/// Request body for exporting documents.
+#[derive(Debug, Deserialize)]
+pub struct SyncExportRequest {
+    pub document_ids: Vec<String>,
+}
+
+fn service(state: &AppState) -> SyncService<'_> {
+    SyncService::new(
+        &state.doc_repo,
+        &state.source_repo,
+        &state.crawl_repo,
+        &state.documents_dir,
+    )
+}
+
+/// Check the bearer token against the configured sync token.
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), Response> {
+    let Some(expected) = state.sync_token.as_deref() else {
+        return Err((StatusCode::NOT_FOUND, "Sync is not enabled").into_response());
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+    if constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err((StatusCode::UNAUTHORIZED, "Invalid sync token").into_response())
+    }
+}
+
+fn sync_error(e: SyncError) -> Response {
+    let status = match e {
+        SyncError::HashMismatch { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        SyncError::UnknownVersion { .. } => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string()).into_response()
+}
+
+/// Document and crawl digests for change detection.
+pub async fn sync_manifest(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(r) = authorize(&state, &headers) {
+        return r;
+    }
+    match service(&state).manifest().await {
+        Ok(manifest) => Json(manifest).into_response(),
+        Err(e) => sync_error(e),
+    }
+}
+
+/// Export documents with their versions, pages and sources.
+pub async fn sync_export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<SyncExportRequest>,
+) -> Response {
+    if let Err(r) = authorize(&state, &headers) {
+        return r;
+    }
+    match service(&state).export(&body.document_ids).await {
+        Ok(batch) => Json(batch).into_response(),
+        Err(e) => sync_error(e),
+    }
+}
+
+/// Merge documents pushed by a remote instance.
+pub async fn sync_apply(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(batch): Json<SyncBatch>,
+) -> Response {
+    if let Err(r) = authorize(&state, &headers) {
+        return r;
+    }
+    let result = service(&state).apply(&batch).await;
+    state.read_cache.clear();
+    state.stats_cache.clear();
+    match result {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => sync_error(e),
+    }
+}
+
+/// Export a source's crawl state.
+pub async fn sync_crawl_export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(source_id): Path<String>,
+) -> Response {
+    if let Err(r) = authorize(&state, &headers) {
+        return r;
+    }
+    match service(&state).export_crawl(&source_id).await {
+        Ok(crawl) => Json(crawl).into_response(),
+        Err(e) => sync_error(e),
+    }
+}
+
+/// Merge crawl state pushed by a remote instance.
+pub async fn sync_crawl_apply(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(crawl): Json<SyncCrawlState>,
+) -> Response {
+    if let Err(r) = authorize(&state, &headers) {
+        return r;
+    }
+    match service(&state).apply_crawl(&crawl).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => sync_error(e),
+    }
+}
+
+/// Download a version's file.
+pub async fn sync_blob_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((doc_id, hash)): Path<(String, String)>,
+) -> Response {
+    if let Err(r) = authorize(&state, &headers) {
+        return r;
+    }
+    let path = match service(&state).blob_path(&doc_id, &hash).await {
+        Ok(Some(path)) => path,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Unknown version").into_response(),
+        Err(e) => return sync_error(e),
+    };
+    match foia::storage::read_content_async(&path).await {
+        Ok(content) => (
+            [(header::CONTENT_TYPE, "application/octet-stream")],
+            content,
+        )
+            .into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, "File not present").into_response(),
+    }
+}
+
+/// Upload a version's file. The content must match the version's hash.
+pub async fn sync_blob_put(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((doc_id, hash)): Path<(String, String)>,
+    body: Bytes,
+) -> Response {
+    if let Err(r) = authorize(&state, &headers) {
+        return r;
+    }
+    match service(&state).store_blob(&doc_id, &hash, &body).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => sync_error(e),
+    }
+}
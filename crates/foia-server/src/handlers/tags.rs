@@ -4,33 +4,46 @@ use askama::Template;
 use axum::{
     extract::{Path, State},
     response::{Html, IntoResponse},
+    Extension,
 };
 
+use super::super::i18n::I18n;
 use super::super::template_structs::{
     DocumentRow, ErrorTemplate, TagDocumentsTemplate, TagWithCount, TagsTemplate,
 };
 use super::super::AppState;
+use super::access::Viewer;
 use super::api_types::{ApiResponse, TagCount};
 
 /// List all tags with document counts.
-pub async fn list_tags(State(state): State<AppState>) -> impl IntoResponse {
-    let tags = match state.doc_repo.get_all_tags().await {
+pub async fn list_tags(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let tags = match state.doc_repo.get_tag_counts().await {
         Ok(t) => t,
         Err(e) => {
             let msg = format!("Failed to load tags: {}", e);
             let template = ErrorTemplate {
-                title: "Error",
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
                 message: &msg,
             };
             return Html(template.render().unwrap_or(msg));
         }
     };
 
-    let tags_with_counts: Vec<TagWithCount> =
-        tags.into_iter().map(|t| TagWithCount::new(t, 0)).collect();
+    let tags_with_counts: Vec<TagWithCount> = tags
+        .into_iter()
+        .map(|(t, c)| TagWithCount::new(t, c as usize))
+        .collect();
 
     let template = TagsTemplate {
-        title: "Tags",
+        title: &i18n.t("title-tags"),
+        theme: &theme,
+        i18n: &i18n,
         has_tags: !tags_with_counts.is_empty(),
         tags: tags_with_counts,
     };
@@ -45,8 +58,11 @@ pub async fn list_tags(State(state): State<AppState>) -> impl IntoResponse {
 /// List documents with a specific tag.
 pub async fn list_tag_documents(
     State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
     Path(tag): Path<String>,
 ) -> impl IntoResponse {
+    let theme = state.theme().await;
     let tag = urlencoding::decode(&tag)
         .unwrap_or(std::borrow::Cow::Borrowed(&tag))
         .to_string();
@@ -56,7 +72,9 @@ pub async fn list_tag_documents(
         Err(e) => {
             let msg = format!("Failed to load documents: {}", e);
             let template = ErrorTemplate {
-                title: "Error",
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
                 message: &msg,
             };
             return Html(template.render().unwrap_or(msg));
@@ -65,12 +83,15 @@ pub async fn list_tag_documents(
 
     let doc_rows: Vec<DocumentRow> = documents
         .iter()
+        .filter(|doc| viewer.allows(&doc.id, &doc.source_id))
         .filter_map(|doc| DocumentRow::from_document(doc).map(|row| row.with_other_tags(&tag)))
         .collect();
 
-    let title = format!("Tag: {}", tag);
+    let title = i18n.t1("title-tag", "tag", &tag);
     let template = TagDocumentsTemplate {
         title: &title,
+        theme: &theme,
+        i18n: &i18n,
         tag: &tag,
         document_count: doc_rows.len(),
         documents: doc_rows,
@@ -96,9 +117,14 @@ pub async fn api_tags(State(state): State<AppState>) -> impl IntoResponse {
     let tags: Vec<(String, usize)> = match state.stats_cache.get_all_tags() {
         Some(cached) => cached,
         None => {
-            let raw_tags = state.doc_repo.get_all_tags().await.unwrap_or_default();
-            let tags_with_counts: Vec<(String, usize)> =
-                raw_tags.into_iter().map(|t| (t, 0)).collect();
+            let tags_with_counts: Vec<(String, usize)> = state
+                .doc_repo
+                .get_tag_counts()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(t, c)| (t, c as usize))
+                .collect();
             state.stats_cache.set_all_tags(tags_with_counts.clone());
             tags_with_counts
         }
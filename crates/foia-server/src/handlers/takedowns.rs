@@ -0,0 +1,80 @@
+//! Removal request form and the tombstones of withheld documents.
+
+use askama::Template;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    Extension,
+};
+use serde::Deserialize;
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{TakedownTemplate, TombstoneTemplate};
+use super::super::AppState;
+use super::api_types::ApiResponse;
+use foia::services::takedowns::{TakedownStatus, Tombstone};
+
+#[derive(Debug, Deserialize)]
+pub struct TakedownPageParams {
+    /// Document the request is about, when linked from its page.
+    pub document: Option<String>,
+}
+
+/// Public form for asking to take documents down.
+pub async fn takedown_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Query(params): Query<TakedownPageParams>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let template = TakedownTemplate {
+        title: &i18n.t("title-takedown"),
+        theme: &theme,
+        i18n: &i18n,
+        document: params.document.as_deref().unwrap_or(""),
+        read_only: state.read_only,
+    };
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
+
+/// `451 Unavailable For Legal Reasons` explaining why a document is
+/// withheld: a page for browsers, JSON for the API.
+pub(super) fn tombstone_response(
+    tombstone: &Tombstone,
+    path: &str,
+    i18n: Option<&I18n>,
+    theme: &super::super::theme::Theme,
+) -> Response {
+    let status = StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS;
+    let i18n = i18n.copied().unwrap_or_default();
+    let message_id = match tombstone.status {
+        TakedownStatus::Upheld => "tombstone-removed",
+        _ => "tombstone-suppressed",
+    };
+    let message = i18n.t(message_id);
+    if path.starts_with("/api/") {
+        return ApiResponse::error(status, message).into_response();
+    }
+
+    let case_label = i18n.t2(
+        "tombstone-case",
+        "case",
+        tombstone.case_id,
+        "date",
+        i18n.format_date(&tombstone.since),
+    );
+    let template = TombstoneTemplate {
+        title: &i18n.t("title-tombstone"),
+        theme,
+        i18n: &i18n,
+        message: &message,
+        case_label: &case_label,
+    };
+    let body = template.render().unwrap_or(message);
+    (status, Html(body)).into_response()
+}
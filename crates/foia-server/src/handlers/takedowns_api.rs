@@ -0,0 +1,365 @@
+//! Takedown API endpoints: removal requests from the public and their
+//! review.
+//!
+//! Anyone may submit a request. Listing, suppressing and deciding cases
+//! takes the access token, as requests carry the requester's contact
+//! details.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::access::Viewer;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::services::takedowns::{
+    self, TakedownCase, TakedownEvent, TakedownRequest, TakedownStatus,
+};
+
+/// Reviewer recorded when none is given.
+const OPERATOR: &str = "operator";
+
+/// Request body for `POST /api/takedowns`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TakedownBody {
+    /// Requester's name.
+    pub name: String,
+    /// How to reach the requester, usually an email address.
+    pub contact: String,
+    /// Why the documents should be taken down.
+    pub reason: String,
+    /// Document IDs or links to their pages.
+    pub documents: Vec<String>,
+}
+
+/// What a requester is told about a new case.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TakedownReceipt {
+    pub id: i32,
+    pub status: String,
+    pub document_ids: Vec<String>,
+}
+
+/// Ask for documents to be taken down. Creates a case for review; the
+/// documents stay up until an operator suppresses them.
+#[utoipa::path(
+    post,
+    path = "/api/takedowns",
+    request_body = TakedownBody,
+    responses(
+        (status = 201, description = "Case opened", body = TakedownReceipt),
+        (status = 400, description = "Missing fields or unknown documents")
+    ),
+    tag = "Takedowns"
+)]
+pub async fn submit_takedown(
+    State(state): State<AppState>,
+    Json(body): Json<TakedownBody>,
+) -> impl IntoResponse {
+    let request = TakedownRequest {
+        requester_name: body.name,
+        requester_contact: body.contact,
+        reason: body.reason,
+        documents: body.documents,
+    };
+    if let Err(msg) = request.validate() {
+        return bad_request(&msg).into_response();
+    }
+    match takedowns::open_case(&state.doc_repo, &request).await {
+        Ok(case) => {
+            let receipt = TakedownReceipt {
+                id: case.id,
+                status: case.status.as_str().to_string(),
+                document_ids: case.document_ids,
+            };
+            (StatusCode::CREATED, ApiResponse::ok(receipt)).into_response()
+        }
+        Err(e) if e.to_string().starts_with("Unknown document") => {
+            bad_request(&e.to_string()).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// A takedown case, for reviewers.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TakedownCaseItem {
+    pub id: i32,
+    pub requester_name: String,
+    pub requester_contact: String,
+    pub reason: String,
+    /// `open`, `suppressed`, `upheld` or `rejected`.
+    pub status: String,
+    pub document_ids: Vec<String>,
+    pub submitted_at: String,
+    pub updated_at: String,
+    /// Steps taken, oldest first; only on single cases.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<TakedownEventItem>,
+}
+
+/// One step of a case.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TakedownEventItem {
+    /// `received`, `suppressed`, `upheld` or `rejected`.
+    pub action: String,
+    pub actor: String,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+impl TakedownCaseItem {
+    fn new(case: TakedownCase, events: Vec<TakedownEvent>) -> Self {
+        Self {
+            id: case.id,
+            requester_name: case.requester_name,
+            requester_contact: case.requester_contact,
+            reason: case.reason,
+            status: case.status.as_str().to_string(),
+            document_ids: case.document_ids,
+            submitted_at: case.submitted_at.to_rfc3339(),
+            updated_at: case.updated_at.to_rfc3339(),
+            events: events
+                .into_iter()
+                .map(|e| TakedownEventItem {
+                    action: e.action,
+                    actor: e.actor,
+                    note: e.note,
+                    created_at: e.created_at.to_rfc3339(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TakedownListParams {
+    /// `open`, `suppressed`, `upheld`, `rejected`, or `all` (default)
+    pub status: Option<String>,
+}
+
+/// Takedown cases, oldest first.
+#[utoipa::path(
+    get,
+    path = "/api/takedowns",
+    params(TakedownListParams),
+    responses(
+        (status = 200, description = "Cases", body = Vec<TakedownCaseItem>),
+        (status = 400, description = "Unknown status"),
+        (status = 403, description = "Access token required")
+    ),
+    tag = "Takedowns"
+)]
+pub async fn list_takedowns(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(params): Query<TakedownListParams>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return reviewers_only().into_response();
+    }
+    let status = match params.status.as_deref().unwrap_or("all") {
+        "all" => None,
+        s => match TakedownStatus::from_str(s) {
+            Some(status) => Some(status),
+            None => {
+                return bad_request("status must be open, suppressed, upheld, rejected or all")
+                    .into_response()
+            }
+        },
+    };
+    match state.doc_repo.list_takedown_cases(status).await {
+        Ok(cases) => {
+            let items: Vec<TakedownCaseItem> = cases
+                .into_iter()
+                .map(|case| TakedownCaseItem::new(case, Vec::new()))
+                .collect();
+            ApiResponse::ok(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// One case with its history.
+#[utoipa::path(
+    get,
+    path = "/api/takedowns/{id}",
+    params(("id" = i32, Path, description = "Case ID")),
+    responses(
+        (status = 200, description = "Case", body = TakedownCaseItem),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Case not found")
+    ),
+    tag = "Takedowns"
+)]
+pub async fn get_takedown(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return reviewers_only().into_response();
+    }
+    case_response(&state, id).await
+}
+
+/// Request body for suppressing or deciding a case.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct TakedownActionBody {
+    /// `uphold` or `reject`; only for decisions.
+    #[serde(default)]
+    pub decision: Option<String>,
+    /// Reason for the step; required for decisions.
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Who took the step (default: `operator`).
+    #[serde(default)]
+    pub actor: Option<String>,
+}
+
+impl TakedownActionBody {
+    fn actor(&self) -> String {
+        self.actor
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .unwrap_or(OPERATOR)
+            .to_string()
+    }
+}
+
+/// Withhold a case's documents while it is reviewed. Their pages show a
+/// notice instead.
+#[utoipa::path(
+    post,
+    path = "/api/takedowns/{id}/suppress",
+    params(("id" = i32, Path, description = "Case ID")),
+    request_body = TakedownActionBody,
+    responses(
+        (status = 200, description = "Suppressed case", body = TakedownCaseItem),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Case not found"),
+        (status = 409, description = "Case is not open")
+    ),
+    tag = "Takedowns"
+)]
+pub async fn suppress_takedown(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(id): Path<i32>,
+    body: Option<Json<TakedownActionBody>>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return reviewers_only().into_response();
+    }
+    let Json(body) = body.unwrap_or_default();
+    if let Some(response) = check_case(&state, id, |s| s == TakedownStatus::Open).await {
+        return response;
+    }
+    let result =
+        takedowns::suppress_case(&state.doc_repo, id, &body.actor(), body.note.as_deref()).await;
+    finish(&state, id, result).await
+}
+
+/// Decide a case: uphold it, keeping its documents withheld, or reject
+/// it, restoring them.
+#[utoipa::path(
+    post,
+    path = "/api/takedowns/{id}/decide",
+    params(("id" = i32, Path, description = "Case ID")),
+    request_body = TakedownActionBody,
+    responses(
+        (status = 200, description = "Decided case", body = TakedownCaseItem),
+        (status = 400, description = "Missing decision or reason"),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Case not found"),
+        (status = 409, description = "Case was already decided")
+    ),
+    tag = "Takedowns"
+)]
+pub async fn decide_takedown(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(id): Path<i32>,
+    Json(body): Json<TakedownActionBody>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return reviewers_only().into_response();
+    }
+    let decision = match body.decision.as_deref() {
+        Some("uphold") => TakedownStatus::Upheld,
+        Some("reject") => TakedownStatus::Rejected,
+        _ => return bad_request("decision must be uphold or reject").into_response(),
+    };
+    let Some(reason) = body.note.as_deref().filter(|n| !n.trim().is_empty()) else {
+        return bad_request("a decision needs a reason in note").into_response();
+    };
+    if let Some(response) = check_case(&state, id, |s| !s.is_closed()).await {
+        return response;
+    }
+    let result = takedowns::decide_case(&state.doc_repo, id, decision, &body.actor(), reason).await;
+    finish(&state, id, result).await
+}
+
+/// A 404 or 409 response when the case is missing or not in a state
+/// `allowed` accepts.
+async fn check_case(
+    state: &AppState,
+    id: i32,
+    allowed: impl Fn(TakedownStatus) -> bool,
+) -> Option<axum::response::Response> {
+    match state.doc_repo.get_takedown_case(id).await {
+        Ok(Some(case)) if allowed(case.status) => None,
+        Ok(Some(case)) => Some(
+            ApiResponse::error(
+                StatusCode::CONFLICT,
+                format!("Case is already {}", case.status.as_str()),
+            )
+            .into_response(),
+        ),
+        Ok(None) => Some(not_found("Case not found").into_response()),
+        Err(e) => Some(internal_error(e).into_response()),
+    }
+}
+
+/// Drop cached copies of the case's documents and return the case.
+async fn finish(
+    state: &AppState,
+    id: i32,
+    result: anyhow::Result<TakedownCase>,
+) -> axum::response::Response {
+    let case = match result {
+        Ok(case) => case,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    for doc_id in &case.document_ids {
+        state.read_cache.invalidate_document(doc_id).await;
+    }
+    state.stats_cache.clear();
+    case_response(state, id).await
+}
+
+async fn case_response(state: &AppState, id: i32) -> axum::response::Response {
+    let case = match state.doc_repo.get_takedown_case(id).await {
+        Ok(Some(case)) => case,
+        Ok(None) => return not_found("Case not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    match state.doc_repo.takedown_events(id).await {
+        Ok(events) => ApiResponse::ok(TakedownCaseItem::new(case, events)).into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+fn reviewers_only() -> impl IntoResponse {
+    ApiResponse::error(
+        StatusCode::FORBIDDEN,
+        "Reviewing takedown requests requires the access token",
+    )
+}
@@ -0,0 +1,86 @@
+//! Theme assets: the configured logo and about page.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Extension,
+};
+
+use super::super::i18n::I18n;
+use super::super::template_structs::{AboutTemplate, ErrorTemplate};
+use super::super::theme::render_markdown;
+use super::super::AppState;
+
+/// Serve the logo file named by `theme.logo`.
+pub async fn serve_logo(State(state): State<AppState>) -> Response {
+    let path = state.config.read().await.theme.logo_path(&state.data_dir);
+    let Some(path) = path else {
+        return (StatusCode::NOT_FOUND, "No logo configured").into_response();
+    };
+    match tokio::fs::read(&path).await {
+        Ok(content) => {
+            let mime = mime_guess::from_path(&path)
+                .first_or_octet_stream()
+                .to_string();
+            (
+                [
+                    (header::CONTENT_TYPE, mime),
+                    (header::CACHE_CONTROL, "public, max-age=300".to_string()),
+                    // SVG logos may carry scripts; never run them
+                    (
+                        header::CONTENT_SECURITY_POLICY,
+                        "default-src 'none'; style-src 'unsafe-inline'".to_string(),
+                    ),
+                ],
+                content,
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::warn!("Cannot read logo {}: {}", path.display(), e);
+            (StatusCode::NOT_FOUND, "Logo not found").into_response()
+        }
+    }
+}
+
+/// Render the markdown file named by `theme.about`.
+pub async fn about_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+) -> Response {
+    let theme = state.theme().await;
+    let path = state.config.read().await.theme.about_path(&state.data_dir);
+    let markdown = match path {
+        Some(path) => tokio::fs::read_to_string(&path).await.map_err(|e| {
+            tracing::warn!("Cannot read about page {}: {}", path.display(), e);
+        }),
+        None => Err(()),
+    };
+    let Ok(markdown) = markdown else {
+        let template = ErrorTemplate {
+            title: &i18n.t("title-not-found"),
+            theme: &theme,
+            i18n: &i18n,
+            message: &i18n.t("error-no-about"),
+        };
+        let html = template
+            .render()
+            .unwrap_or_else(|_| "Not found".to_string());
+        return (StatusCode::NOT_FOUND, Html(html)).into_response();
+    };
+
+    let template = AboutTemplate {
+        title: &i18n.t("title-about"),
+        theme: &theme,
+        i18n: &i18n,
+        body_html: render_markdown(&markdown),
+    };
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+    .into_response()
+}
@@ -0,0 +1,34 @@
+//! Volunteer transcription page.
+
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+    Extension,
+};
+
+use super::super::i18n::I18n;
+use super::super::template_structs::TranscribeTemplate;
+use super::super::AppState;
+use super::access::Viewer;
+
+/// Page where volunteers transcribe pages one at a time.
+pub async fn transcribe_page(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
+    let template = TranscribeTemplate {
+        title: &i18n.t("title-transcribe"),
+        theme: &theme,
+        i18n: &i18n,
+        is_internal: viewer.is_internal(),
+        read_only: state.read_only,
+    };
+    Html(
+        template
+            .render()
+            .unwrap_or_else(|e| format!("Template error: {}", e)),
+    )
+}
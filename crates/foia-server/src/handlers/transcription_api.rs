@@ -0,0 +1,379 @@
+//! Transcription API: volunteers take pages OCR couldn't read and type
+//! them up, and curators settle the pages they disagree on.
+//!
+//! Volunteers work with their own API key, so that two transcriptions of
+//! a page always come from two people. Listing tasks with everyone's
+//! transcriptions and resolving disputes takes the access token.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::access::Viewer;
+use super::api_keys::ApiClient;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::services::transcription::{self, TaskStatus, TranscriptionEntry, TranscriptionTask};
+
+/// Curator recorded for resolutions made with the access token when none
+/// is given.
+const OPERATOR: &str = "operator";
+
+/// Most tasks listed at once.
+const MAX_LIST: u32 = 500;
+
+/// A transcription task.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TranscriptionTaskItem {
+    pub id: i32,
+    pub document_id: String,
+    pub version_id: i64,
+    pub page_number: u32,
+    /// `open`, `disputed`, `review` or `accepted`.
+    pub status: String,
+    /// The page image, for pages of image files. PDF pages are rendered
+    /// by `GET /api/documents/{doc_id}/pages`.
+    pub image_url: Option<String>,
+    pub accepted_text: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Volunteers' transcriptions, in the order they were assigned; only
+    /// shown to curators.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<TranscriptionEntryItem>,
+}
+
+/// One volunteer's transcription of a task.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TranscriptionEntryItem {
+    pub volunteer: String,
+    /// `None` while the volunteer is still working on it.
+    pub text: Option<String>,
+    pub assigned_at: String,
+    pub submitted_at: Option<String>,
+}
+
+impl From<TranscriptionEntry> for TranscriptionEntryItem {
+    fn from(entry: TranscriptionEntry) -> Self {
+        Self {
+            volunteer: entry.volunteer,
+            text: entry.text,
+            assigned_at: entry.assigned_at.to_rfc3339(),
+            submitted_at: entry.submitted_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+/// Request body for `POST /api/transcription/next`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct NextTaskBody {
+    /// Volunteer name when using the access token; requests with an API
+    /// key are made as the key.
+    #[serde(default)]
+    pub volunteer: Option<String>,
+}
+
+/// Request body for submitting or resolving a task.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TranscriptionBody {
+    /// Text of the page as read from the image.
+    pub text: String,
+    /// Volunteer or curator name when using the access token.
+    #[serde(default)]
+    pub volunteer: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TranscriptionListParams {
+    /// `open`, `disputed`, `review`, `accepted`, or `all` (default)
+    pub status: Option<String>,
+    /// Most tasks returned (default 100)
+    pub limit: Option<u32>,
+}
+
+/// Take the next page to transcribe. Returns the page already assigned to
+/// the volunteer if they have one, or `null` when nothing is left for them.
+#[utoipa::path(
+    post,
+    path = "/api/transcription/next",
+    request_body = NextTaskBody,
+    responses(
+        (status = 200, description = "Assigned task, or null when none is left", body = TranscriptionTaskItem),
+        (status = 401, description = "No API key or access token")
+    ),
+    tag = "Transcription"
+)]
+pub async fn next_transcription_task(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    client: Option<Extension<ApiClient>>,
+    body: Option<Json<NextTaskBody>>,
+) -> impl IntoResponse {
+    let Json(body) = body.unwrap_or_default();
+    let Some(volunteer) = volunteer_name(client, &viewer, body.volunteer) else {
+        return volunteers_only().into_response();
+    };
+    match transcription::next_task(&state.doc_repo, &volunteer).await {
+        Ok(Some(task)) => {
+            let item = task_item(&state, task, Vec::new()).await;
+            ApiResponse::ok(Some(item)).into_response()
+        }
+        Ok(None) => ApiResponse::ok(None::<TranscriptionTaskItem>).into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Submit a transcription of a task. When it matches another volunteer's,
+/// the text becomes the page's text and the task is accepted.
+#[utoipa::path(
+    post,
+    path = "/api/transcription/tasks/{id}",
+    params(("id" = i32, Path, description = "Task ID")),
+    request_body = TranscriptionBody,
+    responses(
+        (status = 200, description = "Task after the submission", body = TranscriptionTaskItem),
+        (status = 400, description = "Empty or too long text"),
+        (status = 401, description = "No API key or access token"),
+        (status = 404, description = "Task not found"),
+        (status = 409, description = "Already transcribed by this volunteer, or no longer open")
+    ),
+    tag = "Transcription"
+)]
+pub async fn submit_transcription(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    client: Option<Extension<ApiClient>>,
+    Path(id): Path<i32>,
+    Json(body): Json<TranscriptionBody>,
+) -> impl IntoResponse {
+    let Some(volunteer) = volunteer_name(client, &viewer, body.volunteer) else {
+        return volunteers_only().into_response();
+    };
+    let result = transcription::submit(&state.doc_repo, id, &volunteer, &body.text).await;
+    finish(&state, result, false).await
+}
+
+/// Transcription tasks, oldest first, with each volunteer's transcription.
+#[utoipa::path(
+    get,
+    path = "/api/transcription/tasks",
+    params(TranscriptionListParams),
+    responses(
+        (status = 200, description = "Tasks", body = Vec<TranscriptionTaskItem>),
+        (status = 400, description = "Unknown status"),
+        (status = 403, description = "Access token required")
+    ),
+    tag = "Transcription"
+)]
+pub async fn list_transcription_tasks(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(params): Query<TranscriptionListParams>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return curators_only().into_response();
+    }
+    let status = match params.status.as_deref().unwrap_or("all") {
+        "all" => None,
+        s => match TaskStatus::from_str(s) {
+            Some(status) => Some(status),
+            None => return bad_request(&format!("Unknown status: {}", s)).into_response(),
+        },
+    };
+    let limit = params.limit.unwrap_or(100).clamp(1, MAX_LIST);
+    let tasks = match state.doc_repo.list_transcription_tasks(status, limit).await {
+        Ok(tasks) => tasks,
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let mut items = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let entries = match state.doc_repo.get_transcription_entries(task.id).await {
+            Ok(entries) => entries,
+            Err(e) => return internal_error(e).into_response(),
+        };
+        items.push(task_item(&state, task, entries).await);
+    }
+    ApiResponse::ok(items).into_response()
+}
+
+/// A transcription task with each volunteer's transcription.
+#[utoipa::path(
+    get,
+    path = "/api/transcription/tasks/{id}",
+    params(("id" = i32, Path, description = "Task ID")),
+    responses(
+        (status = 200, description = "Task", body = TranscriptionTaskItem),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Task not found")
+    ),
+    tag = "Transcription"
+)]
+pub async fn get_transcription_task(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return curators_only().into_response();
+    }
+    match state.doc_repo.get_transcription_task(id).await {
+        Ok(Some(task)) => task_response(&state, task, true).await,
+        Ok(None) => not_found("Task not found").into_response(),
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// Settle a task with a curator's text, typically one the volunteers
+/// couldn't agree on.
+#[utoipa::path(
+    post,
+    path = "/api/transcription/tasks/{id}/resolve",
+    params(("id" = i32, Path, description = "Task ID")),
+    request_body = TranscriptionBody,
+    responses(
+        (status = 200, description = "Accepted task", body = TranscriptionTaskItem),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Task not found"),
+        (status = 409, description = "Task already accepted")
+    ),
+    tag = "Transcription"
+)]
+pub async fn resolve_transcription(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(id): Path<i32>,
+    Json(body): Json<TranscriptionBody>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return curators_only().into_response();
+    }
+    let curator = body
+        .volunteer
+        .filter(|c| !c.trim().is_empty())
+        .unwrap_or_else(|| OPERATOR.to_string());
+    let result = transcription::resolve(&state.doc_repo, id, &body.text, &curator).await;
+    finish(&state, result, true).await
+}
+
+/// Who is transcribing: the API key's holder, or with the access token the
+/// name given.
+fn volunteer_name(
+    client: Option<Extension<ApiClient>>,
+    viewer: &Viewer,
+    given: Option<String>,
+) -> Option<String> {
+    match client {
+        Some(Extension(client)) => Some(client.name),
+        None if viewer.is_internal() => Some(
+            given
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| OPERATOR.to_string()),
+        ),
+        None => None,
+    }
+}
+
+/// Respond with the task, dropping cached copies of its document once its
+/// text was accepted.
+async fn finish(
+    state: &AppState,
+    result: anyhow::Result<TranscriptionTask>,
+    with_entries: bool,
+) -> axum::response::Response {
+    match result {
+        Ok(task) => {
+            if task.status == TaskStatus::Accepted {
+                state
+                    .read_cache
+                    .invalidate_document(&task.document_id)
+                    .await;
+            }
+            task_response(state, task, with_entries).await
+        }
+        Err(e) => {
+            let message = e.to_string();
+            if message.ends_with("not found") {
+                not_found(&message).into_response()
+            } else if message.ends_with("required") || message.starts_with("text is longer") {
+                bad_request(&message).into_response()
+            } else if message.contains("already") || message.contains("doesn't need") {
+                ApiResponse::error(StatusCode::CONFLICT, message).into_response()
+            } else {
+                internal_error(e).into_response()
+            }
+        }
+    }
+}
+
+async fn task_response(
+    state: &AppState,
+    task: TranscriptionTask,
+    with_entries: bool,
+) -> axum::response::Response {
+    let entries = if with_entries {
+        match state.doc_repo.get_transcription_entries(task.id).await {
+            Ok(entries) => entries,
+            Err(e) => return internal_error(e).into_response(),
+        }
+    } else {
+        Vec::new()
+    };
+    ApiResponse::ok(task_item(state, task, entries).await).into_response()
+}
+
+async fn task_item(
+    state: &AppState,
+    task: TranscriptionTask,
+    entries: Vec<TranscriptionEntry>,
+) -> TranscriptionTaskItem {
+    let image_url = match state
+        .read_cache
+        .document(&state.doc_repo, &task.document_id)
+        .await
+    {
+        Ok(Some(doc)) => doc
+            .versions
+            .iter()
+            .find(|v| v.id == task.version_id && v.mime_type.starts_with("image/"))
+            .map(|v| {
+                format!(
+                    "/files/{}",
+                    v.compute_storage_path(&doc.source_url, &doc.title)
+                        .to_string_lossy()
+                )
+            }),
+        _ => None,
+    };
+    TranscriptionTaskItem {
+        id: task.id,
+        document_id: task.document_id,
+        version_id: task.version_id,
+        page_number: task.page_number,
+        status: task.status.as_str().to_string(),
+        image_url,
+        accepted_text: task.accepted_text,
+        created_at: task.created_at.to_rfc3339(),
+        updated_at: task.updated_at.to_rfc3339(),
+        entries: entries.into_iter().map(Into::into).collect(),
+    }
+}
+
+fn volunteers_only() -> impl IntoResponse {
+    ApiResponse::error(
+        StatusCode::UNAUTHORIZED,
+        "Transcribing requires an API key or the access token",
+    )
+}
+
+fn curators_only() -> impl IntoResponse {
+    ApiResponse::error(
+        StatusCode::FORBIDDEN,
+        "Reviewing transcriptions requires the access token",
+    )
+}
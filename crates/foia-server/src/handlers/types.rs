@@ -4,13 +4,16 @@ use askama::Template;
 use axum::{
     extract::{Path, Query, State},
     response::{Html, IntoResponse},
+    Extension,
 };
 use serde::Deserialize;
 
+use super::super::i18n::I18n;
 use super::super::template_structs::{
     CategoryWithCount, DocumentRow, ErrorTemplate, TypeDocumentsTemplate, TypeStat, TypesTemplate,
 };
 use super::super::AppState;
+use super::access::Viewer;
 use foia::utils::{mime_to_category, MimeCategory};
 
 /// Filter parameters for type listing.
@@ -21,13 +24,19 @@ pub struct TypeFilterParams {
 }
 
 /// List all type categories.
-pub async fn list_types(State(state): State<AppState>) -> impl IntoResponse {
+pub async fn list_types(
+    State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+) -> impl IntoResponse {
+    let theme = state.theme().await;
     let type_stats = match state.doc_repo.get_type_stats().await {
         Ok(stats) => stats,
         Err(e) => {
             let msg = format!("Failed to load type stats: {}", e);
             let template = ErrorTemplate {
-                title: "Error",
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
                 message: &msg,
             };
             return Html(template.render().unwrap_or(msg));
@@ -69,7 +78,9 @@ pub async fn list_types(State(state): State<AppState>) -> impl IntoResponse {
         .collect();
 
     let template = TypesTemplate {
-        title: "Document Types",
+        title: &i18n.t("title-types"),
+        theme: &theme,
+        i18n: &i18n,
         categories,
         type_stats: stats_with_category,
     };
@@ -84,9 +95,12 @@ pub async fn list_types(State(state): State<AppState>) -> impl IntoResponse {
 /// List documents filtered by type.
 pub async fn list_by_type(
     State(state): State<AppState>,
+    Extension(i18n): Extension<I18n>,
+    Extension(viewer): Extension<Viewer>,
     Path(type_name): Path<String>,
     Query(params): Query<TypeFilterParams>,
 ) -> impl IntoResponse {
+    let theme = state.theme().await;
     let limit = params.limit.unwrap_or(500).clamp(1, 1000);
     let source_id = params.source.as_deref();
 
@@ -99,7 +113,9 @@ pub async fn list_by_type(
         Err(e) => {
             let msg = format!("Failed to load documents: {}", e);
             let template = ErrorTemplate {
-                title: "Error",
+                title: &i18n.t("title-error"),
+                theme: &theme,
+                i18n: &i18n,
                 message: &msg,
             };
             return Html(template.render().unwrap_or(msg));
@@ -139,12 +155,15 @@ pub async fn list_by_type(
 
     let doc_rows: Vec<DocumentRow> = documents
         .iter()
+        .filter(|doc| viewer.allows(&doc.id, &doc.source_id))
         .filter_map(DocumentRow::from_document)
         .collect();
 
-    let title = format!("Type: {}", type_name);
+    let title = i18n.t1("title-type", "type", &type_name);
     let template = TypeDocumentsTemplate {
         title: &title,
+        theme: &theme,
+        i18n: &i18n,
         type_name: &type_name,
         document_count: doc_rows.len(),
         tabs: tabs.clone(),
@@ -0,0 +1,393 @@
+//! Upload API endpoints: document submissions from contributors and the
+//! moderation queue.
+//!
+//! Uploading takes a read-write API key (the key's name is recorded as the
+//! contributor) or the access token. Moderation takes the access token.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::super::AppState;
+use super::access::Viewer;
+use super::api_keys::ApiClient;
+use super::api_types::ApiResponse;
+use super::helpers::{bad_request, internal_error, not_found};
+use foia::services::uploads::{self, Upload, UploadRequest, UploadStatus};
+use foia_analysis::services::AnalysisService;
+
+/// Contributor recorded for uploads made with the access token.
+const OPERATOR: &str = "operator";
+
+/// An upload and its moderation state.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadItem {
+    pub id: i32,
+    pub document_id: String,
+    pub contributor: String,
+    pub description: Option<String>,
+    pub attribution: Option<String>,
+    /// `pending`, `approved` or `rejected`.
+    pub status: String,
+    pub submitted_at: String,
+    pub reviewed_at: Option<String>,
+    pub reviewed_by: Option<String>,
+    pub review_note: Option<String>,
+}
+
+impl From<Upload> for UploadItem {
+    fn from(upload: Upload) -> Self {
+        Self {
+            id: upload.id,
+            document_id: upload.document_id,
+            contributor: upload.contributor,
+            description: upload.description,
+            attribution: upload.attribution,
+            status: upload.status.as_str().to_string(),
+            submitted_at: upload.submitted_at.to_rfc3339(),
+            reviewed_at: upload.reviewed_at.map(|t| t.to_rfc3339()),
+            reviewed_by: upload.reviewed_by,
+            review_note: upload.review_note,
+        }
+    }
+}
+
+/// Request body for `POST /api/uploads`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UploadBody {
+    /// File name, used for the title and type when they aren't given.
+    pub filename: String,
+    /// File content, base64-encoded.
+    pub content: String,
+    /// MIME type of the file; sniffed from the content when possible.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Document title (default: the file name).
+    #[serde(default)]
+    pub title: Option<String>,
+    /// What the document is.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Where it came from, e.g. the request it was released under.
+    #[serde(default)]
+    pub attribution: Option<String>,
+    /// Source to file the document under (default: `uploads`).
+    #[serde(default)]
+    pub source_id: Option<String>,
+    /// Tags to add to the document.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Contributor name for uploads made with the access token; uploads
+    /// made with an API key are credited to the key.
+    #[serde(default)]
+    pub contributor: Option<String>,
+}
+
+/// Submit a document for moderation. It is stored and its text extracted
+/// right away, but stays internal until a moderator approves it.
+#[utoipa::path(
+    post,
+    path = "/api/uploads",
+    request_body = UploadBody,
+    responses(
+        (status = 201, description = "Upload awaiting moderation", body = UploadItem),
+        (status = 400, description = "Invalid file or content encoding"),
+        (status = 401, description = "No API key or access token"),
+        (status = 403, description = "API key may not file into this source"),
+        (status = 409, description = "The same file was already uploaded")
+    ),
+    tag = "Uploads"
+)]
+pub async fn submit_upload(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    client: Option<Extension<ApiClient>>,
+    Json(body): Json<UploadBody>,
+) -> impl IntoResponse {
+    let contributor = match (client, viewer.is_internal()) {
+        (Some(Extension(client)), _) => {
+            let source_id = body.source_id.as_deref().map(str::trim).unwrap_or("");
+            let source_id = if source_id.is_empty() {
+                uploads::UPLOADS_SOURCE_ID
+            } else {
+                source_id
+            };
+            if client
+                .sources
+                .as_ref()
+                .is_some_and(|sources| !sources.iter().any(|s| s == source_id))
+            {
+                return ApiResponse::error(
+                    StatusCode::FORBIDDEN,
+                    format!("API key may not upload to source {}", source_id),
+                )
+                .into_response();
+            }
+            client.name
+        }
+        (None, true) => body
+            .contributor
+            .filter(|c| !c.trim().is_empty())
+            .unwrap_or_else(|| OPERATOR.to_string()),
+        (None, false) => {
+            return ApiResponse::error(
+                StatusCode::UNAUTHORIZED,
+                "Uploading requires an API key or the access token",
+            )
+            .into_response();
+        }
+    };
+
+    let content = match base64::engine::general_purpose::STANDARD.decode(body.content.trim()) {
+        Ok(content) => content,
+        Err(e) => {
+            return bad_request(&format!("content is not valid base64: {}", e)).into_response()
+        }
+    };
+    let request = UploadRequest {
+        filename: body.filename,
+        content,
+        content_type: body.content_type,
+        title: body.title,
+        description: body.description,
+        attribution: body.attribution,
+        source_id: body.source_id,
+        tags: body.tags,
+        contributor,
+    };
+    if let Err(msg) = request.validate() {
+        return bad_request(&msg).into_response();
+    }
+
+    let upload = match uploads::store_upload(
+        &state.doc_repo,
+        &state.source_repo,
+        &state.documents_dir,
+        &request,
+    )
+    .await
+    {
+        Ok(upload) => upload,
+        Err(e) if e.to_string().contains("already uploaded") => {
+            return ApiResponse::error(StatusCode::CONFLICT, e.to_string()).into_response();
+        }
+        Err(e) => return internal_error(e).into_response(),
+    };
+    state.stats_cache.clear();
+    state.read_cache.invalidate_sources();
+
+    // Extraction runs while the upload waits for review
+    let analysis = AnalysisService::new((*state.doc_repo).clone(), state.documents_dir.clone());
+    let document_id = upload.document_id.clone();
+    tokio::spawn(async move {
+        match analysis.extract_text(&document_id).await {
+            Ok(_) => {}
+            Err(e) if e.to_string().contains("Unsupported file type") => {}
+            Err(e) => tracing::warn!("Text extraction of upload {} failed: {}", document_id, e),
+        }
+    });
+
+    (
+        StatusCode::CREATED,
+        ApiResponse::ok(UploadItem::from(upload)),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UploadListParams {
+    /// `pending` (default), `approved`, `rejected` or `all`
+    pub status: Option<String>,
+    /// Maximum uploads returned (default 100)
+    pub limit: Option<i64>,
+}
+
+/// The moderation queue: uploads by status, oldest first.
+#[utoipa::path(
+    get,
+    path = "/api/uploads",
+    params(UploadListParams),
+    responses(
+        (status = 200, description = "Uploads", body = Vec<UploadItem>),
+        (status = 400, description = "Unknown status"),
+        (status = 403, description = "Access token required")
+    ),
+    tag = "Uploads"
+)]
+pub async fn list_uploads(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(params): Query<UploadListParams>,
+) -> impl IntoResponse {
+    if !viewer.is_internal() {
+        return moderators_only().into_response();
+    }
+    let status = match params.status.as_deref().unwrap_or("pending") {
+        "all" => None,
+        s => match UploadStatus::from_str(s) {
+            Some(status) => Some(status),
+            None => {
+                return bad_request("status must be pending, approved, rejected or all")
+                    .into_response()
+            }
+        },
+    };
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    match state.doc_repo.list_uploads(status, limit).await {
+        Ok(list) => {
+            let items: Vec<UploadItem> = list.into_iter().map(UploadItem::from).collect();
+            ApiResponse::ok(items).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+/// One upload. Contributors can follow their own uploads with the key they
+/// submitted them with.
+#[utoipa::path(
+    get,
+    path = "/api/uploads/{id}",
+    params(("id" = i32, Path, description = "Upload ID")),
+    responses(
+        (status = 200, description = "Upload", body = UploadItem),
+        (status = 404, description = "Upload not found")
+    ),
+    tag = "Uploads"
+)]
+pub async fn get_upload(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    client: Option<Extension<ApiClient>>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    let upload = match state.doc_repo.get_upload(id).await {
+        Ok(Some(upload)) => upload,
+        Ok(None) => return not_found("Upload not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    };
+    let own = client.is_some_and(|Extension(c)| c.name == upload.contributor);
+    if !viewer.is_internal() && !own {
+        return not_found("Upload not found").into_response();
+    }
+    ApiResponse::ok(UploadItem::from(upload)).into_response()
+}
+
+/// Request body for approving or rejecting an upload.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ReviewBody {
+    /// Reason for the decision, shown to the contributor.
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Who reviewed the upload (default: `operator`).
+    #[serde(default)]
+    pub reviewer: Option<String>,
+}
+
+/// Approve a pending upload, making its document public (or whatever its
+/// source's visibility is).
+#[utoipa::path(
+    post,
+    path = "/api/uploads/{id}/approve",
+    params(("id" = i32, Path, description = "Upload ID")),
+    request_body = ReviewBody,
+    responses(
+        (status = 200, description = "Approved upload", body = UploadItem),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Upload not found"),
+        (status = 409, description = "Upload was already reviewed")
+    ),
+    tag = "Uploads"
+)]
+pub async fn approve_upload(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(id): Path<i32>,
+    body: Option<Json<ReviewBody>>,
+) -> impl IntoResponse {
+    review(state, viewer, id, UploadStatus::Approved, body).await
+}
+
+/// Reject a pending upload. Its document stays internal.
+#[utoipa::path(
+    post,
+    path = "/api/uploads/{id}/reject",
+    params(("id" = i32, Path, description = "Upload ID")),
+    request_body = ReviewBody,
+    responses(
+        (status = 200, description = "Rejected upload", body = UploadItem),
+        (status = 403, description = "Access token required"),
+        (status = 404, description = "Upload not found"),
+        (status = 409, description = "Upload was already reviewed")
+    ),
+    tag = "Uploads"
+)]
+pub async fn reject_upload(
+    State(state): State<AppState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(id): Path<i32>,
+    body: Option<Json<ReviewBody>>,
+) -> impl IntoResponse {
+    review(state, viewer, id, UploadStatus::Rejected, body).await
+}
+
+async fn review(
+    state: AppState,
+    viewer: Viewer,
+    id: i32,
+    decision: UploadStatus,
+    body: Option<Json<ReviewBody>>,
+) -> axum::response::Response {
+    if !viewer.is_internal() {
+        return moderators_only().into_response();
+    }
+    let Json(body) = body.unwrap_or_default();
+    let reviewer = body
+        .reviewer
+        .filter(|r| !r.trim().is_empty())
+        .unwrap_or_else(|| OPERATOR.to_string());
+
+    match state.doc_repo.get_upload(id).await {
+        Ok(Some(upload)) if upload.status != UploadStatus::Pending => {
+            return ApiResponse::error(
+                StatusCode::CONFLICT,
+                format!("Upload was already {}", upload.status.as_str()),
+            )
+            .into_response();
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found("Upload not found").into_response(),
+        Err(e) => return internal_error(e).into_response(),
+    }
+    match uploads::review_upload(
+        &state.doc_repo,
+        id,
+        decision,
+        &reviewer,
+        body.note.as_deref(),
+    )
+    .await
+    {
+        Ok(upload) => {
+            state.stats_cache.clear();
+            state
+                .read_cache
+                .invalidate_document(&upload.document_id)
+                .await;
+            ApiResponse::ok(UploadItem::from(upload)).into_response()
+        }
+        Err(e) => internal_error(e).into_response(),
+    }
+}
+
+fn moderators_only() -> impl IntoResponse {
+    ApiResponse::error(
+        StatusCode::FORBIDDEN,
+        "Moderating uploads requires the access token",
+    )
+}
@@ -65,7 +65,7 @@ pub async fn list_versions(
     State(state): State<AppState>,
     Path(doc_id): Path<String>,
 ) -> impl IntoResponse {
-    match state.doc_repo.get(&doc_id).await {
+    match state.read_cache.document(&state.doc_repo, &doc_id).await {
         Ok(Some(doc)) => {
             let source_url = &doc.source_url;
             let title = &doc.title;
@@ -105,7 +105,7 @@ pub async fn get_version(
     State(state): State<AppState>,
     Path((doc_id, version_id)): Path<(String, i64)>,
 ) -> impl IntoResponse {
-    match state.doc_repo.get(&doc_id).await {
+    match state.read_cache.document(&state.doc_repo, &doc_id).await {
         Ok(Some(doc)) => {
             if let Some(version) = doc.versions.into_iter().find(|v| v.id == version_id) {
                 ApiResponse::ok(VersionResponse::from_version(version, &doc.source_url, &doc.title)).into_response()
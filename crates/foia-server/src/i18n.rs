@@ -0,0 +1,308 @@
+//! Translations of the web interface.
+//!
+//! Message catalogs are Fluent (`.ftl`) files under `locales/`, built into
+//! the binary. Each request gets the catalog negotiated from an explicit
+//! choice (`?lang=` or the `foia_lang` cookie), then the browser's
+//! `Accept-Language`, then `theme.locale`, then English. Messages missing
+//! from a catalog fall back to English.
+
+use std::fmt::{self, Display};
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Datelike, Utc};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+/// Built-in catalogs: code, native name, Fluent source. English first, as
+/// the fallback.
+const SOURCES: &[(&str, &str, &str)] = &[
+    ("en", "English", include_str!("../locales/en.ftl")),
+    ("es", "Español", include_str!("../locales/es.ftl")),
+    ("fr", "Français", include_str!("../locales/fr.ftl")),
+];
+
+static CATALOGS: LazyLock<Vec<Catalog>> = LazyLock::new(|| {
+    SOURCES
+        .iter()
+        .map(|(code, name, source)| Catalog::new(code, name, source))
+        .collect()
+});
+
+/// Messages of one language.
+struct Catalog {
+    code: &'static str,
+    name: &'static str,
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl Catalog {
+    fn new(code: &'static str, name: &'static str, source: &str) -> Self {
+        let langid: LanguageIdentifier = code.parse().expect("valid language code");
+        let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+        // Unicode isolation marks would end up inside HTML attributes
+        bundle.set_use_isolating(false);
+        let resource =
+            FluentResource::try_new(source.to_string()).unwrap_or_else(|(res, errors)| {
+                tracing::warn!("Syntax errors in {} catalog: {:?}", code, errors);
+                res
+            });
+        if let Err(errors) = bundle.add_resource(resource) {
+            tracing::warn!("Duplicate messages in {} catalog: {:?}", code, errors);
+        }
+        Self { code, name, bundle }
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        let pattern = self.bundle.get_message(id)?.value()?;
+        let mut errors = Vec::new();
+        let text = self.bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            tracing::debug!("Formatting {} in {}: {:?}", id, self.code, errors);
+        }
+        Some(text.into_owned())
+    }
+}
+
+/// The catalog for a language tag such as `es`, `es-MX` or `FR_ca`.
+fn find(tag: &str) -> Option<&'static Catalog> {
+    let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    CATALOGS.iter().find(|c| c.code == primary)
+}
+
+/// Language tags of an `Accept-Language` header, most preferred first.
+/// Tags with `q=0` and the `*` wildcard are dropped.
+fn preferences(accept_language: &str) -> Vec<&str> {
+    let mut tags: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|part| {
+            let mut fields = part.split(';');
+            let tag = fields.next()?.trim();
+            let q = fields
+                .find_map(|f| f.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && q > 0.0).then_some((tag, q))
+        })
+        .collect();
+    // Stable, so equal weights keep header order
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// A message variable. Numbers stay numbers so they select plural forms.
+fn arg(value: impl Display) -> FluentValue<'static> {
+    let text = value.to_string();
+    if let Ok(n) = text.parse::<i64>() {
+        n.into()
+    } else if let Ok(n) = text.parse::<f64>() {
+        n.into()
+    } else {
+        text.into()
+    }
+}
+
+/// A language offered by the switcher.
+pub struct Language {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub current: bool,
+}
+
+/// Translator for one request, passed to templates.
+#[derive(Clone, Copy)]
+pub struct I18n {
+    catalog: &'static Catalog,
+}
+
+impl fmt::Debug for I18n {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("I18n").field(&self.catalog.code).finish()
+    }
+}
+
+impl Default for I18n {
+    fn default() -> Self {
+        Self {
+            catalog: &CATALOGS[0],
+        }
+    }
+}
+
+impl I18n {
+    /// Pick the language for a request. `chosen` is an explicit choice,
+    /// `default` the configured `theme.locale`.
+    pub fn negotiate(
+        chosen: Option<&str>,
+        accept_language: Option<&str>,
+        default: Option<&str>,
+    ) -> Self {
+        let browser = accept_language.map(preferences).unwrap_or_default();
+        chosen
+            .into_iter()
+            .chain(browser)
+            .chain(default)
+            .find_map(find)
+            .map(|catalog| Self { catalog })
+            .unwrap_or_default()
+    }
+
+    /// Whether a language code has a catalog.
+    pub fn is_supported(code: &str) -> bool {
+        find(code).is_some_and(|c| c.code == code)
+    }
+
+    /// Language code, for `<html lang>`.
+    pub fn lang(&self) -> &'static str {
+        self.catalog.code
+    }
+
+    /// All languages, for the switcher.
+    pub fn languages(&self) -> Vec<Language> {
+        CATALOGS
+            .iter()
+            .map(|c| Language {
+                code: c.code,
+                name: c.name,
+                current: c.code == self.catalog.code,
+            })
+            .collect()
+    }
+
+    /// Translate a message.
+    pub fn t(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    /// Translate a message with one variable. Numeric values select plural
+    /// forms.
+    pub fn t1(&self, id: &str, name: &str, value: impl Display) -> String {
+        let mut args = FluentArgs::new();
+        args.set(name, arg(value));
+        self.format(id, Some(&args))
+    }
+
+    /// Translate a message with two variables.
+    pub fn t2(
+        &self,
+        id: &str,
+        name1: &str,
+        value1: impl Display,
+        name2: &str,
+        value2: impl Display,
+    ) -> String {
+        let mut args = FluentArgs::new();
+        args.set(name1, arg(value1));
+        args.set(name2, arg(value2));
+        self.format(id, Some(&args))
+    }
+
+    /// Localized date of a Unix timestamp, e.g. "16 de octubre de 2026".
+    pub fn date(&self, timestamp: &i64) -> String {
+        DateTime::from_timestamp(*timestamp, 0)
+            .map(|dt| self.format_date(&dt))
+            .unwrap_or_default()
+    }
+
+    /// Localized date and UTC time of a Unix timestamp.
+    pub fn datetime(&self, timestamp: &i64) -> String {
+        DateTime::from_timestamp(*timestamp, 0)
+            .map(|dt| {
+                let mut args = FluentArgs::new();
+                args.set("date", self.format_date(&dt));
+                args.set("time", dt.format("%H:%M").to_string());
+                self.format("datetime", Some(&args))
+            })
+            .unwrap_or_default()
+    }
+
+    /// Localized date, e.g. "October 16, 2026".
+    pub fn format_date(&self, dt: &DateTime<Utc>) -> String {
+        let mut args = FluentArgs::new();
+        args.set("day", dt.day().to_string());
+        args.set("month", self.t(&format!("month-{}", dt.month())));
+        args.set("year", dt.year().to_string());
+        self.format("date", Some(&args))
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        self.catalog
+            .format(id, args)
+            .or_else(|| CATALOGS[0].format(id, args))
+            .unwrap_or_else(|| {
+                tracing::debug!("Missing message {}", id);
+                id.to_string()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Message IDs defined in a catalog.
+    fn message_ids(source: &str) -> Vec<&str> {
+        source
+            .lines()
+            .filter(|l| l.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|l| l.split_once(" =").map(|(id, _)| id.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_are_complete() {
+        let english = message_ids(SOURCES[0].2);
+        assert!(english.contains(&"nav-tags"));
+        for (code, _, source) in &SOURCES[1..] {
+            let ids = message_ids(source);
+            let missing: Vec<_> = english.iter().filter(|id| !ids.contains(id)).collect();
+            assert!(missing.is_empty(), "{} catalog lacks {:?}", code, missing);
+            // Every message must parse
+            let catalog = find(code).unwrap();
+            for id in &english {
+                assert!(
+                    catalog.bundle.has_message(id),
+                    "{} cannot parse {}",
+                    code,
+                    id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let lang = |chosen, accept, default| I18n::negotiate(chosen, accept, default).lang();
+        assert_eq!(lang(None, None, None), "en");
+        assert_eq!(lang(None, None, Some("fr")), "fr");
+        assert_eq!(lang(None, Some("es-MX,es;q=0.9,en;q=0.8"), None), "es");
+        assert_eq!(lang(None, Some("de-DE, fr;q=0.5, en;q=0.7"), None), "en");
+        assert_eq!(lang(None, Some("de, ja"), Some("es")), "es");
+        assert_eq!(lang(None, Some("fr;q=0, *"), None), "en");
+        assert_eq!(lang(Some("fr"), Some("es"), None), "fr");
+        assert_eq!(lang(Some("xx"), Some("es"), None), "es");
+        assert!(I18n::is_supported("es"));
+        assert!(!I18n::is_supported("es-MX"));
+    }
+
+    #[test]
+    fn test_translate_with_plurals() {
+        let es = I18n::negotiate(Some("es"), None, None);
+        assert_eq!(es.t("nav-tags"), "etiquetas");
+        assert_eq!(es.t1("result-count", "count", 1), "1 resultado");
+        assert_eq!(es.t1("result-count", "count", 12), "12 resultados");
+        assert_eq!(es.t("no-such-message"), "no-such-message");
+    }
+
+    #[test]
+    fn test_dates() {
+        let dt = Utc.with_ymd_and_hms(2026, 10, 16, 14, 5, 0).unwrap();
+        let ts = dt.timestamp();
+        let date = |code| I18n::negotiate(Some(code), None, None).date(&ts);
+        assert_eq!(date("en"), "October 16, 2026");
+        assert_eq!(date("es"), "16 de octubre de 2026");
+        assert_eq!(date("fr"), "16 octobre 2026");
+        let fr = I18n::negotiate(Some("fr"), None, None);
+        assert_eq!(fr.datetime(&ts), "16 octobre 2026 à 14:05");
+    }
+}
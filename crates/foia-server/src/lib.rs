@@ -6,23 +6,47 @@
 //! - Cross-source deduplication display
 //! - Document version history
 
+mod acquire;
 mod assets;
 mod cache;
+mod graphql;
 mod handlers;
+mod i18n;
+mod page_ocr;
+mod rate_limit;
 mod routes;
 mod template_structs;
+mod theme;
 
 pub use routes::create_router;
 
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
-use foia::config::Settings;
-use foia::repository::{DieselCrawlRepository, DieselDocumentRepository, DieselSourceRepository};
+use foia::config::{Config, ConfigReloader, SearchBackend, Settings};
+use foia::repository::{
+    DieselAgencyRepository, DieselConfigHistoryRepository, DieselCrawlRepository,
+    DieselDocumentRepository, DieselScraperConfigRepository, DieselSourceRepository,
+};
+use foia::services::search::{self, BoxedSearchIndex};
 
-use cache::StatsCache;
+use acquire::AcquireJobs;
+use cache::{ReadCache, StatsCache};
+use page_ocr::PageOcrJobs;
+use rate_limit::ApiKeyLimiter;
+use theme::Theme;
+
+/// How often a read-only replica server refreshes connections and caches.
+const REPLICA_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the server checks the config file and history for changes.
+const CONFIG_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often API key usage counts are written to the database.
+const API_USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Status of a DeepSeek OCR job.
 #[derive(Clone, Debug, Default)]
@@ -45,30 +69,79 @@ pub struct AppState {
     pub doc_repo: Arc<DieselDocumentRepository>,
     pub source_repo: Arc<DieselSourceRepository>,
     pub crawl_repo: Arc<DieselCrawlRepository>,
+    pub agency_repo: Arc<DieselAgencyRepository>,
+    /// Per-source scraper configs and their saved versions.
+    pub scraper_configs: Arc<DieselScraperConfigRepository>,
+    pub config_history: Arc<DieselConfigHistoryRepository>,
     pub documents_dir: PathBuf,
+    /// Data directory, for theme files.
+    pub data_dir: PathBuf,
     pub stats_cache: Arc<StatsCache>,
+    /// Hot documents, sources and navigation, dropped when the server writes them.
+    pub read_cache: Arc<ReadCache>,
     /// DeepSeek OCR job status (only one can run at a time).
     pub deepseek_job: Arc<RwLock<DeepSeekJobStatus>>,
+    /// Token required by the sync API (None = sync endpoints disabled).
+    pub sync_token: Option<String>,
+    /// Serving a read-only replica: mutating requests are rejected.
+    pub read_only: bool,
+    /// Active config, swapped in place when the config changes.
+    pub config: Arc<RwLock<Config>>,
+    /// Ad-hoc URL acquisitions submitted through the API.
+    pub acquire_jobs: Arc<AcquireJobs>,
+    /// Single pages OCRed on demand from the document viewer.
+    pub page_ocr_jobs: Arc<PageOcrJobs>,
+    /// Rate limits and usage counts of API keys.
+    pub api_limiter: Arc<ApiKeyLimiter>,
+    /// Search index, when `search.backend` selects one.
+    pub search_index: Option<BoxedSearchIndex>,
 }
 
 impl AppState {
     pub async fn new(settings: &Settings) -> anyhow::Result<Self> {
         let ctx = settings.create_db_context()?;
+        let config = Config::load().await;
+        let search_index = open_search_index(&config, settings).await;
 
         Ok(Self {
             doc_repo: Arc::new(ctx.documents()),
             source_repo: Arc::new(ctx.sources()),
             crawl_repo: Arc::new(ctx.crawl()),
+            agency_repo: Arc::new(ctx.agencies()),
+            scraper_configs: Arc::new(ctx.scraper_configs()),
+            config_history: Arc::new(ctx.config_history()),
             documents_dir: settings.documents_dir.clone(),
+            data_dir: settings.data_dir.clone(),
             stats_cache: Arc::new(StatsCache::new()),
+            read_cache: Arc::new(ReadCache::new()),
             deepseek_job: Arc::new(RwLock::new(DeepSeekJobStatus::default())),
+            sync_token: settings.sync_token.clone(),
+            read_only: settings.read_only,
+            config: Arc::new(RwLock::new(config)),
+            acquire_jobs: Arc::new(AcquireJobs::new()),
+            page_ocr_jobs: Arc::new(PageOcrJobs::new()),
+            api_limiter: Arc::new(ApiKeyLimiter::new()),
+            search_index,
         })
     }
+
+    /// Branding from the current config.
+    pub async fn theme(&self) -> Theme {
+        Theme::from_config(&self.config.read().await.theme, &self.data_dir)
+    }
 }
 
 /// Start the web server.
 pub async fn serve(settings: &Settings, host: &str, port: u16) -> anyhow::Result<()> {
     let state = AppState::new(settings).await?;
+    if settings.read_only {
+        spawn_replica_refresh(settings, &state)?;
+    } else {
+        settings.create_db_context()?.pool().spawn_wal_checkpoint();
+        spawn_api_usage_flush(&state);
+    }
+    spawn_config_reload(settings, &state).await?;
+    spawn_search_index_refresh(&state).await;
     let app = create_router(state);
 
     let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
@@ -79,3 +152,123 @@ pub async fn serve(settings: &Settings, host: &str, port: u16) -> anyhow::Result
 
     Ok(())
 }
+
+/// Periodically recycle pooled connections and drop cached stats and reads
+/// so a continuously replicated database's changes show up.
+fn spawn_replica_refresh(settings: &Settings, state: &AppState) -> anyhow::Result<()> {
+    let ctx = settings.create_db_context()?;
+    let stats_cache = state.stats_cache.clone();
+    let read_cache = state.read_cache.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REPLICA_REFRESH_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let recycled = ctx.pool().refresh_connections(REPLICA_REFRESH_INTERVAL);
+            stats_cache.clear();
+            read_cache.clear();
+            tracing::debug!("Replica refresh: recycled {} connection(s)", recycled);
+        }
+    });
+    Ok(())
+}
+
+/// Periodically write API key usage counts. Replicas can't write, so
+/// their usage is not recorded.
+fn spawn_api_usage_flush(state: &AppState) {
+    let limiter = state.api_limiter.clone();
+    let doc_repo = state.doc_repo.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(API_USAGE_FLUSH_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let usage = limiter.take_usage();
+            if usage.is_empty() {
+                continue;
+            }
+            if let Err(e) = doc_repo.record_api_key_usage(&usage).await {
+                tracing::warn!("Failed to record API key usage: {}", e);
+            }
+        }
+    });
+}
+
+/// Open the search index if the config selects one. Searches fall back to
+/// the database when it can't be opened.
+async fn open_search_index(config: &Config, settings: &Settings) -> Option<BoxedSearchIndex> {
+    if config.search.backend == SearchBackend::Database {
+        return None;
+    }
+    let index = match search::open_index(&config.search, &settings.data_dir) {
+        Ok(index) => index,
+        Err(e) => {
+            tracing::warn!("Search index unavailable, searching the database: {}", e);
+            return None;
+        }
+    };
+    // A search service that is down now may be back by the first search
+    match index.status().await {
+        Ok(status) => tracing::info!(
+            "Search index at {} ({} documents)",
+            index.location(),
+            status.documents
+        ),
+        Err(e) => tracing::warn!("Search index at {} not answering: {}", index.location(), e),
+    }
+    Some(index)
+}
+
+/// Periodically index documents changed since the last update.
+async fn spawn_search_index_refresh(state: &AppState) {
+    let Some(index) = state.search_index.clone() else {
+        return;
+    };
+    let period = Duration::from_secs(state.config.read().await.search.refresh_interval());
+    let doc_repo = state.doc_repo.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match index.update(&doc_repo, false).await {
+                Ok(stats) if stats.indexed > 0 || stats.deleted > 0 => {
+                    tracing::debug!(
+                        "Search index: {} document(s) updated, {} deleted",
+                        stats.indexed,
+                        stats.deleted
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to update search index: {}", e),
+            }
+        }
+    });
+}
+
+/// Periodically reload the config, logging which scrapers changed and
+/// dropping cached stats that may depend on it.
+async fn spawn_config_reload(settings: &Settings, state: &AppState) -> anyhow::Result<()> {
+    let ctx = settings.create_db_context()?;
+    let config = state.config.read().await.clone();
+    let mut reloader = ConfigReloader::new(config, ctx.config_history()).await;
+    let shared = state.config.clone();
+    let stats_cache = state.stats_cache.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CONFIG_RELOAD_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(reloaded) = reloader.reload().await else {
+                continue;
+            };
+            if reloaded.scrapers.is_empty() {
+                tracing::info!("Config reloaded");
+            } else {
+                tracing::info!("Config reloaded, scrapers {}", reloaded.scrapers.summary());
+            }
+            *shared.write().await = reloaded.config;
+            stats_cache.clear();
+        }
+    });
+    Ok(())
+}
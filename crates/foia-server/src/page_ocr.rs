@@ -0,0 +1,268 @@
+//! On-demand OCR of single pages opened in the document viewer.
+//!
+//! A reviewer looking at a page without OCR text can have it run right away
+//! instead of waiting for the batch queue to reach it. The page goes through
+//! the configured backend chain, preprocessing and arbitration like any
+//! other; these jobs have their own slots, so they never wait behind
+//! `foia analyze`. Job state is kept in memory for polling, as for
+//! acquisitions.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tokio::sync::{RwLock, Semaphore};
+use utoipa::ToSchema;
+
+use foia::config::Config;
+use foia::llm::LlmClient;
+use foia::models::DocumentPage;
+use foia_analysis::ocr::{Arbiter, FallbackOcrBackend, Preprocessor};
+use foia_analysis::services::analysis::ocr_document_page_with_config;
+
+use super::AppState;
+
+/// Pages OCRed at once; further requests wait in `queued`.
+const MAX_CONCURRENT: usize = 2;
+
+/// Finished jobs are forgotten after this long.
+const FINISHED_JOB_TTL_MINUTES: i64 = 60;
+
+/// Stage of a page OCR job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PageOcrJobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// An on-demand OCR job for one page, as returned by the API.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PageOcrJob {
+    pub document_id: String,
+    pub version_id: i64,
+    pub page_number: u32,
+    pub status: PageOcrJobStatus,
+    /// Whether OCR gave the page better text than it had.
+    pub improved: Option<bool>,
+    /// Why the job failed.
+    pub error: Option<String>,
+    pub submitted_at: String,
+    pub finished_at: Option<String>,
+    #[serde(skip)]
+    finished: Option<DateTime<Utc>>,
+}
+
+impl PageOcrJob {
+    fn queued(page: &DocumentPage) -> Self {
+        Self {
+            document_id: page.document_id.clone(),
+            version_id: page.version_id,
+            page_number: page.page_number,
+            status: PageOcrJobStatus::Queued,
+            improved: None,
+            error: None,
+            submitted_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+            finished: None,
+        }
+    }
+
+    /// A job for a page that already has OCR text, so nothing runs.
+    pub fn already_done(page: &DocumentPage) -> Self {
+        let now = Utc::now();
+        Self {
+            status: PageOcrJobStatus::Completed,
+            improved: Some(false),
+            finished_at: Some(now.to_rfc3339()),
+            finished: Some(now),
+            ..Self::queued(page)
+        }
+    }
+}
+
+/// In-memory registry of page OCR jobs, one per page.
+pub struct PageOcrJobs {
+    jobs: RwLock<HashMap<String, PageOcrJob>>,
+    slots: Semaphore,
+}
+
+impl PageOcrJobs {
+    pub fn new() -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            slots: Semaphore::new(MAX_CONCURRENT),
+        }
+    }
+
+    /// Queue OCR of `page` and start it in the background. A page already
+    /// queued or running returns its existing job.
+    pub async fn submit(self: &Arc<Self>, state: AppState, page: DocumentPage) -> PageOcrJob {
+        let key = job_key(&page.document_id, page.version_id, page.page_number);
+        let job = {
+            let mut jobs = self.jobs.write().await;
+            prune(&mut jobs, Utc::now());
+            if let Some(job) = jobs.get(&key).filter(|job| job.finished.is_none()) {
+                return job.clone();
+            }
+            let job = PageOcrJob::queued(&page);
+            jobs.insert(key.clone(), job.clone());
+            job
+        };
+
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            let Ok(_permit) = jobs.slots.acquire().await else {
+                return;
+            };
+            jobs.run(&key, &state, page).await;
+        });
+
+        job
+    }
+
+    pub async fn get(
+        &self,
+        document_id: &str,
+        version_id: i64,
+        page_number: u32,
+    ) -> Option<PageOcrJob> {
+        self.jobs
+            .read()
+            .await
+            .get(&job_key(document_id, version_id, page_number))
+            .cloned()
+    }
+
+    async fn update(&self, key: &str, f: impl FnOnce(&mut PageOcrJob)) {
+        if let Some(job) = self.jobs.write().await.get_mut(key) {
+            f(job);
+        }
+    }
+
+    async fn run(&self, key: &str, state: &AppState, page: DocumentPage) {
+        self.update(key, |job| job.status = PageOcrJobStatus::Running)
+            .await;
+        let config = state.config.read().await.clone();
+        let document_id = page.document_id.clone();
+        let page_number = page.page_number;
+        let result = ocr_page(state, &config, page).await;
+        // Finishing the last page changes the document's status
+        state.read_cache.invalidate_document(&document_id).await;
+
+        let now = Utc::now();
+        self.update(key, |job| {
+            match result {
+                Ok(improved) => {
+                    job.status = PageOcrJobStatus::Completed;
+                    job.improved = Some(improved);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "On-demand OCR of {} page {} failed: {}",
+                        document_id,
+                        page_number,
+                        e
+                    );
+                    job.status = PageOcrJobStatus::Failed;
+                    job.error = Some(e.to_string());
+                }
+            }
+            job.finished = Some(now);
+            job.finished_at = Some(now.to_rfc3339());
+        })
+        .await;
+    }
+}
+
+impl Default for PageOcrJobs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run `page` through the configured OCR backends; true when its text
+/// improved.
+async fn ocr_page(state: &AppState, config: &Config, page: DocumentPage) -> anyhow::Result<bool> {
+    let ocr_config = config.analysis.ocr.clone();
+    let available = ocr_config
+        .backends
+        .iter()
+        .flat_map(|entry| entry.backends())
+        .any(FallbackOcrBackend::check_backend_available);
+    if !available {
+        anyhow::bail!("No configured OCR backend is available on the server");
+    }
+
+    let arbitration = &config.analysis.arbitration;
+    let mut arbiter = Arbiter::new(arbitration.clone()).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read arbitration dictionary {}: {}",
+            arbitration.dictionary.as_deref().unwrap_or_default(),
+            e
+        )
+    })?;
+    if arbitration.llm_judge && config.llm.enabled() {
+        arbiter = arbiter.with_llm(LlmClient::new(config.llm.clone()));
+    }
+    let preprocessor = Preprocessor::new(&config.analysis.preprocess);
+
+    let doc_repo = state.doc_repo.clone();
+    let documents_dir = state.documents_dir.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let handle = tokio::runtime::Handle::current();
+        ocr_document_page_with_config(
+            &page,
+            &doc_repo,
+            &handle,
+            &ocr_config,
+            &arbiter,
+            &preprocessor,
+            &documents_dir,
+        )
+    })
+    .await??;
+    Ok(result.improved)
+}
+
+fn job_key(document_id: &str, version_id: i64, page_number: u32) -> String {
+    format!("{}:v{}:p{}", document_id, version_id, page_number)
+}
+
+/// Drop finished jobs older than the TTL; queued and running jobs stay.
+fn prune(jobs: &mut HashMap<String, PageOcrJob>, now: DateTime<Utc>) {
+    let cutoff = now - Duration::minutes(FINISHED_JOB_TTL_MINUTES);
+    jobs.retain(|_, job| !matches!(job.finished, Some(at) if at <= cutoff));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(number: u32) -> DocumentPage {
+        DocumentPage::new("doc".to_string(), 1, number)
+    }
+
+    #[test]
+    fn test_prune_keeps_unfinished_and_recent_jobs() {
+        let now = Utc::now();
+        let mut old = PageOcrJob::already_done(&page(1));
+        old.finished = Some(now - Duration::minutes(FINISHED_JOB_TTL_MINUTES + 1));
+        let recent = PageOcrJob::already_done(&page(2));
+        let running = PageOcrJob::queued(&page(3));
+
+        let mut jobs = HashMap::new();
+        for job in [old, recent, running] {
+            let key = job_key(&job.document_id, job.version_id, job.page_number);
+            jobs.insert(key, job);
+        }
+
+        prune(&mut jobs, now);
+        assert!(!jobs.contains_key(&job_key("doc", 1, 1)));
+        assert!(jobs.contains_key(&job_key("doc", 1, 2)));
+        assert!(jobs.contains_key(&job_key("doc", 1, 3)));
+    }
+}
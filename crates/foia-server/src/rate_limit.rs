@@ -0,0 +1,108 @@
+//! Per-key rate limits and usage counts for API keys.
+//!
+//! Limits are requests per minute, counted in fixed one-minute windows per
+//! key. Usage is counted in memory and written to the database
+//! periodically, so answering a request never waits on a write.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Length of a rate limit window.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Requests a key has made in its current window.
+struct KeyWindow {
+    started: Instant,
+    requests: u32,
+}
+
+/// Rate limiter and usage counter shared by all requests.
+#[derive(Default)]
+pub struct ApiKeyLimiter {
+    windows: Mutex<HashMap<i32, KeyWindow>>,
+    /// Accepted requests per key since the last [`take_usage`](Self::take_usage).
+    usage: Mutex<HashMap<i32, u64>>,
+}
+
+impl ApiKeyLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a request against a key's limit of `limit` per minute (0 for
+    /// none). Returns the requests left in the window, or how long to wait
+    /// when the limit is reached.
+    pub fn check(&self, key_id: i32, limit: u32) -> Result<u32, Duration> {
+        self.check_at(key_id, limit, Instant::now())
+    }
+
+    fn check_at(&self, key_id: i32, limit: u32, now: Instant) -> Result<u32, Duration> {
+        let remaining = if limit == 0 {
+            u32::MAX
+        } else {
+            let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+            let window = windows.entry(key_id).or_insert(KeyWindow {
+                started: now,
+                requests: 0,
+            });
+            if now.duration_since(window.started) >= WINDOW {
+                window.started = now;
+                window.requests = 0;
+            }
+            if window.requests >= limit {
+                return Err(WINDOW.saturating_sub(now.duration_since(window.started)));
+            }
+            window.requests += 1;
+            limit - window.requests
+        };
+        *self
+            .usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key_id)
+            .or_default() += 1;
+        Ok(remaining)
+    }
+
+    /// Accepted requests per key since the last call.
+    pub fn take_usage(&self) -> Vec<(i32, u64)> {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *usage).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_per_window() {
+        let limiter = ApiKeyLimiter::new();
+        let start = Instant::now();
+        assert_eq!(limiter.check_at(1, 2, start), Ok(1));
+        assert_eq!(limiter.check_at(1, 2, start), Ok(0));
+        let wait = limiter
+            .check_at(1, 2, start + Duration::from_secs(20))
+            .unwrap_err();
+        assert_eq!(wait, Duration::from_secs(40));
+        // Other keys have their own window
+        assert_eq!(limiter.check_at(2, 2, start), Ok(1));
+        // A new window starts after a minute
+        assert_eq!(limiter.check_at(1, 2, start + WINDOW), Ok(1));
+    }
+
+    #[test]
+    fn test_usage_counts_accepted_requests() {
+        let limiter = ApiKeyLimiter::new();
+        let now = Instant::now();
+        for _ in 0..3 {
+            let _ = limiter.check_at(1, 2, now);
+        }
+        let _ = limiter.check_at(2, 0, now);
+        let mut usage = limiter.take_usage();
+        usage.sort();
+        assert_eq!(usage, [(1, 2), (2, 1)]);
+        assert!(limiter.take_usage().is_empty());
+    }
+}
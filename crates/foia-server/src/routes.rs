@@ -1,19 +1,35 @@
 //! Router configuration for the web server.
 
 use axum::{
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    http::{header, Extensions, HeaderMap, StatusCode, Version},
+    middleware,
+    routing::{delete, get, post, put},
     Router,
 };
+use tower_http::compression::predicate::{DefaultPredicate, Predicate};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 
 use super::handlers;
 use super::AppState;
 
+/// Request body limit for sync uploads (document files and batches).
+const SYNC_BODY_LIMIT: usize = 1024 * 1024 * 1024;
+
+/// Request body limit for browser page captures (HTML or MHTML).
+const CAPTURE_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Request body limit for contributed uploads (base64-encoded files).
+const UPLOAD_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
 /// Create the main router with all routes.
 pub fn create_router(state: AppState) -> Router {
     Router::new()
         // Health check for container orchestration
         .route("/health", get(handlers::health))
+        // Pipeline backlog and ETA gauges for Prometheus
+        .route("/metrics", get(handlers::metrics))
         // Root and /browse are the unified browse page
         .route("/", get(handlers::browse_documents))
         .route("/browse", get(handlers::browse_documents))
@@ -23,6 +39,15 @@ pub fn create_router(state: AppState) -> Router {
             "/documents/:doc_id/versions",
             get(handlers::document_versions),
         )
+        .route("/documents/:doc_id/read", get(handlers::document_reader))
+        .route(
+            "/doc/:doc_id/v/:version/p/:page",
+            get(handlers::citation_permalink),
+        )
+        .route(
+            "/documents/:doc_id/sheets/:index",
+            get(handlers::download_sheet),
+        )
         .route("/files/*path", get(handlers::serve_file))
         // Tags (HTML views)
         .route("/tags", get(handlers::list_tags))
@@ -30,9 +55,36 @@ pub fn create_router(state: AppState) -> Router {
         // Type filtering (HTML views)
         .route("/types", get(handlers::list_types))
         .route("/types/:type_name", get(handlers::list_by_type))
+        // CAPTCHA challenge queue (HTML view)
+        .route("/challenges", get(handlers::list_challenges_page))
+        // Sources with pause/resume (HTML view)
+        .route("/sources", get(handlers::sources_page))
+        // Date review queue (HTML view)
+        .route("/dates", get(handlers::date_queue_page))
+        // OCR coverage and quality (HTML views)
+        .route("/coverage", get(handlers::coverage_page))
+        .route("/coverage/:source_id", get(handlers::coverage_source_page))
+        // Search alias dictionary (HTML view)
+        .route("/aliases", get(handlers::aliases_page))
+        // Public removal request form (HTML view)
+        .route("/takedown", get(handlers::takedown_page))
+        // Volunteer transcription (HTML view)
+        .route("/transcribe", get(handlers::transcribe_page))
+        // Scraper config history, rollback and editing (HTML view)
+        .route("/config", get(handlers::config_page))
+        // Listing page snapshots (HTML views)
+        .route("/snapshots", get(handlers::list_snapshots))
+        .route("/snapshots/history", get(handlers::snapshot_history))
+        .route("/snapshots/:id", get(handlers::snapshot_detail))
+        .route("/snapshots/:id/raw", get(handlers::snapshot_raw))
         // Static assets (CSS/JS)
         .route("/static/style.css", get(handlers::serve_css))
         .route("/static/timeline.js", get(handlers::serve_js))
+        // Branding (theme config)
+        .route("/about", get(handlers::about_page))
+        .route("/theme/logo", get(handlers::serve_logo))
+        // Interface language switcher
+        .route("/lang/:code", get(handlers::set_locale))
         // ===========================================
         // JSON API Endpoints
         // ===========================================
@@ -47,6 +99,14 @@ pub fn create_router(state: AppState) -> Router {
             "/api/documents/:doc_id/pages",
             get(handlers::api_document_pages),
         )
+        .route(
+            "/api/documents/:doc_id/sections",
+            get(handlers::list_sections),
+        )
+        .route(
+            "/api/documents/:doc_id/sections/:section_id/export",
+            get(handlers::export_section),
+        )
         .route(
             "/api/documents/:doc_id/reocr",
             post(handlers::api_reocr_document),
@@ -55,6 +115,19 @@ pub fn create_router(state: AppState) -> Router {
             "/api/documents/reocr/status",
             get(handlers::api_reocr_status),
         )
+        .route(
+            "/api/documents/:doc_id/pages/:page_number/ocr",
+            get(handlers::api_page_ocr_status).post(handlers::api_ocr_page),
+        )
+        // Curators' corrections of page text
+        .route(
+            "/api/documents/:doc_id/pages/:page_number/text",
+            put(handlers::update_page_text),
+        )
+        .route(
+            "/api/documents/:doc_id/pages/:page_number/revisions",
+            get(handlers::list_page_text_revisions),
+        )
         // Versions API - document version history
         .route(
             "/api/documents/:doc_id/versions",
@@ -77,12 +150,110 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/scrapers/:source_id", get(handlers::get_scrape_status))
         .route("/api/scrapers/queue", get(handlers::list_queue))
         .route("/api/scrapers/retry", post(handlers::retry_failed))
+        .route(
+            "/api/scrapers/:source_id/pause",
+            post(handlers::pause_source),
+        )
+        .route(
+            "/api/scrapers/:source_id/resume",
+            post(handlers::resume_source),
+        )
+        // Ad-hoc acquisition
+        .route("/api/acquire", post(handlers::submit_acquire))
+        .route(
+            "/api/acquire/capture",
+            post(handlers::submit_capture).layer(DefaultBodyLimit::max(CAPTURE_BODY_LIMIT)),
+        )
+        .route("/api/acquire/:job_id", get(handlers::get_acquire_job))
+        // Contributed uploads and their moderation
+        .route(
+            "/api/uploads",
+            get(handlers::list_uploads)
+                .post(handlers::submit_upload)
+                .layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)),
+        )
+        .route("/api/uploads/:id", get(handlers::get_upload))
+        .route("/api/uploads/:id/approve", post(handlers::approve_upload))
+        .route("/api/uploads/:id/reject", post(handlers::reject_upload))
+        // Removal requests and their review
+        .route(
+            "/api/takedowns",
+            get(handlers::list_takedowns).post(handlers::submit_takedown),
+        )
+        .route("/api/takedowns/:id", get(handlers::get_takedown))
+        .route(
+            "/api/takedowns/:id/suppress",
+            post(handlers::suppress_takedown),
+        )
+        .route("/api/takedowns/:id/decide", post(handlers::decide_takedown))
+        // Scraper config history, rollback and editing
+        .route("/api/config/history", get(handlers::list_config_history))
+        .route(
+            "/api/config/history/:hash",
+            get(handlers::get_config_version),
+        )
+        .route("/api/config/rollback", post(handlers::rollback_config))
+        .route("/api/config/scrapers", get(handlers::list_scraper_configs))
+        .route(
+            "/api/config/scrapers/:source_id",
+            get(handlers::get_scraper_config).put(handlers::update_scraper_config),
+        )
+        // Crowdsourced transcription and its review
+        .route(
+            "/api/transcription/next",
+            post(handlers::next_transcription_task),
+        )
+        .route(
+            "/api/transcription/tasks",
+            get(handlers::list_transcription_tasks),
+        )
+        .route(
+            "/api/transcription/tasks/:id",
+            get(handlers::get_transcription_task).post(handlers::submit_transcription),
+        )
+        .route(
+            "/api/transcription/tasks/:id/resolve",
+            post(handlers::resolve_transcription),
+        )
+        // Challenges API - CAPTCHA operator queue
+        .route("/api/challenges", get(handlers::list_challenges))
+        .route("/api/challenges/:id/solve", post(handlers::solve_challenge))
+        .route(
+            "/api/challenges/:id/dismiss",
+            post(handlers::dismiss_challenge),
+        )
         // Export API - bulk data export
         .route("/api/export/documents", get(handlers::export_documents))
+        .route("/api/export/pages", get(handlers::export_pages))
         .route("/api/export/annotations", get(handlers::export_annotations))
         .route("/api/export/stats", get(handlers::export_stats))
+        .route("/api/export/runs", get(handlers::export_runs))
+        .route("/api/export/changes", get(handlers::export_changes))
+        // Storage API - usage breakdown and reclaimable space
+        .route("/api/storage", get(handlers::storage_report))
         // Search API - full-text page content search
         .route("/api/search", get(handlers::search_content))
+        .route("/api/search/columns", get(handlers::search_columns))
+        .route("/api/search/documents", get(handlers::search_documents))
+        // Bates API - stamped page ranges
+        .route("/api/bates/lookup/:number", get(handlers::lookup_bates))
+        .route("/api/bates/gaps/:source_id", get(handlers::bates_gaps))
+        // Exemptions API - FOIA exemption citations
+        .route("/api/exemptions/stats", get(handlers::exemption_stats))
+        .route(
+            "/api/exemptions/timeline",
+            get(handlers::exemption_timeline),
+        )
+        .route(
+            "/api/documents/:doc_id/exemptions",
+            get(handlers::document_exemptions),
+        )
+        // Aliases API - search query expansion dictionary
+        .route(
+            "/api/aliases",
+            get(handlers::list_aliases).post(handlers::create_alias),
+        )
+        .route("/api/aliases/:id", delete(handlers::delete_alias))
         // Entities API - NER-extracted entity search
         .route("/api/entities/search", get(handlers::search_entities))
         .route("/api/entities/types", get(handlers::entity_types))
@@ -92,6 +263,50 @@ pub fn create_router(state: AppState) -> Router {
             "/api/documents/:doc_id/entities",
             get(handlers::document_entities),
         )
+        // Highlights API - user-selected page text ranges
+        .route(
+            "/api/documents/:doc_id/highlights",
+            get(handlers::list_highlights).post(handlers::create_highlight),
+        )
+        .route(
+            "/api/documents/:doc_id/highlights/:highlight_id",
+            delete(handlers::delete_highlight),
+        )
+        // Relations API - typed links between documents
+        .route(
+            "/api/documents/:doc_id/relations",
+            get(handlers::list_relations).post(handlers::create_relation),
+        )
+        .route(
+            "/api/documents/:doc_id/relations/:relation_id",
+            delete(handlers::delete_relation),
+        )
+        // Dates API - review queue and manual publication dates
+        .route("/api/dates/queue", get(handlers::date_queue))
+        .route(
+            "/api/documents/:doc_id/date",
+            put(handlers::set_document_date).delete(handlers::clear_document_date),
+        )
+        // Agencies API - source hierarchy with roll-up counts
+        .route("/api/agencies", get(handlers::list_agencies))
+        // Sync API - instance-to-instance replication (token protected)
+        .route("/api/sync/manifest", get(handlers::sync_manifest))
+        .route("/api/sync/export", post(handlers::sync_export))
+        .route(
+            "/api/sync/apply",
+            post(handlers::sync_apply).layer(DefaultBodyLimit::max(SYNC_BODY_LIMIT)),
+        )
+        .route("/api/sync/crawl", post(handlers::sync_crawl_apply))
+        .route(
+            "/api/sync/crawl/:source_id",
+            get(handlers::sync_crawl_export),
+        )
+        .route(
+            "/api/sync/blobs/:doc_id/:hash",
+            get(handlers::sync_blob_get)
+                .put(handlers::sync_blob_put)
+                .layer(DefaultBodyLimit::max(SYNC_BODY_LIMIT)),
+        )
         // Legacy/existing API endpoints
         .route("/api/timeline", get(handlers::timeline_aggregate))
         .route("/api/timeline/:source_id", get(handlers::timeline_source))
@@ -104,8 +319,40 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/types", get(handlers::api_type_stats))
         .route("/api/sources", get(handlers::api_sources))
         // OpenAPI spec
-        .route("/api", get(handlers::openapi_spec).options(handlers::openapi_spec))
+        .route(
+            "/api",
+            get(handlers::openapi_spec).options(handlers::openapi_spec),
+        )
         .route("/api/openapi.json", get(handlers::openapi_spec))
+        // GraphQL
+        .route(
+            "/api/graphql",
+            get(handlers::graphql_ide).post(handlers::graphql_query),
+        )
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            handlers::read_only_guard,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            handlers::access_guard,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            handlers::api_key_guard,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            handlers::negotiate_locale,
+        ))
+        .layer(middleware::from_fn(handlers::conditional_get))
+        .layer(CompressionLayer::new().compress_when(DefaultPredicate::new().and(not_ranged)))
         .layer(CorsLayer::permissive())
         .with_state(state)
 }
+
+/// Leave stored files uncompressed: they are served in byte ranges, which
+/// must count bytes of the file itself, and are mostly compressed already.
+fn not_ranged(_: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions) -> bool {
+    !headers.contains_key(header::ACCEPT_RANGES)
+}
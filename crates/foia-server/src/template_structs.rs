@@ -5,11 +5,18 @@
 
 use askama::Template;
 
-use foia::models::{Document, VirtualFile, VirtualFileStatus};
-use foia::repository::diesel_document::BrowseRow;
+use foia::models::{CrawlChallenge, Document, DocumentSection, VirtualFile, VirtualFileStatus};
+use foia::repository::diesel_document::{
+    BrowseRow, CoverageDocument, RelatedDocument, SourceCoverage,
+};
 use foia::repository::parse_datetime;
+use foia::services::aliases::SearchAlias;
+use foia::services::listing_diff::ListingLink;
 use foia::utils::{format_size, mime_icon};
 
+use super::i18n::I18n;
+use super::theme::Theme;
+
 /// Helper struct for document rows in listings.
 pub struct DocumentRow {
     pub id: String,
@@ -17,13 +24,16 @@ pub struct DocumentRow {
     pub icon: String,
     pub mime_type: String,
     pub size_str: String,
-    pub date_str: String,
+    /// Acquisition time, formatted per request language by the template.
     pub timestamp: i64,
     pub source_id: String,
     pub has_synopsis: bool,
     pub synopsis_preview: String,
     pub tags: Vec<TagRef>,
     pub other_tags: Vec<TagRef>,
+    /// Parent document of an archive member or attachment; empty for documents.
+    pub parent_id: String,
+    pub parent_title: String,
 }
 
 /// Helper struct for tag references.
@@ -51,6 +61,30 @@ pub struct VersionItem {
     pub filename: String,
     pub size_str: String,
     pub date_str: String,
+    /// File is gone from storage and could not be re-fetched.
+    pub lost: bool,
+    pub lost_reason: String,
+}
+
+/// Timed transcript segment for audio/video playback.
+pub struct TranscriptSegment {
+    /// Start offset in seconds, used to seek the player.
+    pub start_secs: f64,
+    /// Start offset as `h:mm:ss` / `m:ss`.
+    pub timestamp: String,
+    pub text: String,
+}
+
+/// First rows of one sheet of a CSV file or spreadsheet.
+pub struct SheetPreview {
+    /// Position in the workbook, used by the CSV download link.
+    pub index: usize,
+    pub name: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Rows after the header, including those not previewed.
+    pub total_rows: usize,
+    pub truncated: bool,
 }
 
 /// Helper struct for virtual file display.
@@ -62,6 +96,30 @@ pub struct VirtualFileRow {
     pub mime_type: String,
     pub size_str: String,
     pub status_badge: String,
+    pub has_synopsis: bool,
+    pub synopsis: String,
+}
+
+/// Helper struct for a related record on the detail page.
+#[derive(Clone)]
+pub struct RelatedRow {
+    pub doc_id: String,
+    pub title: String,
+    pub label: String,
+    pub has_note: bool,
+    pub note: String,
+}
+
+impl RelatedRow {
+    pub fn from_related(related: &RelatedDocument) -> Self {
+        Self {
+            doc_id: related.document_id.clone(),
+            title: related.title.clone(),
+            label: related.label().to_string(),
+            has_note: related.note.is_some(),
+            note: related.note.clone().unwrap_or_default(),
+        }
+    }
 }
 
 /// Helper struct for type statistics.
@@ -89,6 +147,41 @@ pub struct SourceOption {
     pub selected: bool,
 }
 
+/// Agency in the browse filter dropdown, indented under its parent.
+pub struct AgencyOption {
+    pub id: String,
+    pub label: String,
+    /// Documents from the agency's sources and its sub-agencies'.
+    pub count: u64,
+    pub selected: bool,
+}
+
+/// Record type option for the browse filter.
+pub struct RecordTypeOption {
+    pub id: String,
+    pub label: String,
+    pub count: u64,
+    pub selected: bool,
+}
+
+/// Exemption option for the browse filter.
+pub struct ExemptionOption {
+    /// Normalized code, e.g. `(b)(5)`.
+    pub code: String,
+    pub count: u64,
+    pub selected: bool,
+}
+
+/// A page stamped with the Bates number searched for on the browse page.
+pub struct BatesMatchRow {
+    pub document_id: String,
+    pub title: String,
+    pub source_id: String,
+    pub page: u32,
+    /// Stamped range containing the number, e.g. `ABC000100 – ABC000142`.
+    pub range: String,
+}
+
 /// Helper struct for duplicate groups.
 pub struct DuplicateGroup {
     pub hash_prefix: String,
@@ -107,15 +200,283 @@ pub struct DuplicateDoc {
 #[template(path = "duplicates.html")]
 pub struct DuplicatesTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub duplicates: Vec<DuplicateGroup>,
     pub has_duplicates: bool,
 }
 
+/// Helper struct for rows on the challenges page.
+pub struct ChallengeRow {
+    pub id: i64,
+    pub source_id: String,
+    pub domain: String,
+    pub url: String,
+    pub kind: String,
+    pub status: String,
+    pub detected_str: String,
+    pub resolved_str: String,
+}
+
+impl From<CrawlChallenge> for ChallengeRow {
+    fn from(c: CrawlChallenge) -> Self {
+        Self {
+            id: c.id,
+            source_id: c.source_id,
+            domain: c.domain,
+            url: c.url,
+            kind: c.kind,
+            status: c.status.as_str().to_string(),
+            detected_str: c.detected_at.format("%Y-%m-%d %H:%M").to_string(),
+            resolved_str: c
+                .resolved_at
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// CAPTCHA challenge queue page.
+#[derive(Template)]
+#[template(path = "challenges.html")]
+pub struct ChallengesTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub pending: Vec<ChallengeRow>,
+    pub resolved: Vec<ChallengeRow>,
+    pub read_only: bool,
+}
+
+/// Helper struct for rows on the sources page.
+pub struct SourceRow {
+    pub id: String,
+    pub name: String,
+    pub paused: bool,
+    pub pending: u64,
+    pub fetched: u64,
+    pub failed: u64,
+    pub last_scraped_str: String,
+}
+
+/// Sources page.
+#[derive(Template)]
+#[template(path = "sources.html")]
+pub struct SourcesTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub sources: Vec<SourceRow>,
+    pub read_only: bool,
+}
+
+/// Row on the OCR coverage page.
+pub struct CoverageRow {
+    pub source_id: String,
+    pub documents: u64,
+    pub documents_with_text: u64,
+    pub text_pct_str: String,
+    pub pages: u64,
+    pub pages_failed: u64,
+    pub pages_pending: u64,
+    pub quality_str: String,
+    /// Backends with their page counts, e.g. `tesseract (1200)`.
+    pub backends_str: String,
+}
+
+impl From<SourceCoverage> for CoverageRow {
+    fn from(c: SourceCoverage) -> Self {
+        Self {
+            text_pct_str: percent_str(c.text_fraction()),
+            quality_str: percent_str(c.quality),
+            backends_str: c
+                .backends
+                .iter()
+                .map(|b| format!("{} ({})", b.backend, b.pages))
+                .collect::<Vec<_>>()
+                .join(", "),
+            source_id: c.source_id,
+            documents: c.documents,
+            documents_with_text: c.documents_with_text,
+            pages: c.pages,
+            pages_failed: c.pages_failed,
+            pages_pending: c.pages_pending,
+        }
+    }
+}
+
+/// OCR coverage and quality page.
+#[derive(Template)]
+#[template(path = "coverage.html")]
+pub struct CoverageTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub sources: Vec<CoverageRow>,
+}
+
+/// Document row in an OCR coverage drill-down.
+pub struct CoverageDocRow {
+    pub id: String,
+    pub title: String,
+    pub pages: i64,
+    pub failed_pages: i64,
+    pub text_pages: i64,
+    pub quality_str: String,
+}
+
+impl From<CoverageDocument> for CoverageDocRow {
+    fn from(d: CoverageDocument) -> Self {
+        Self {
+            quality_str: percent_str(d.quality),
+            id: d.id,
+            title: d.title,
+            pages: d.pages,
+            failed_pages: d.failed_pages,
+            text_pages: d.text_pages,
+        }
+    }
+}
+
+/// Drill-down tab on the OCR coverage page.
+pub struct CoverageTab {
+    pub issue: &'static str,
+    pub label: &'static str,
+    pub selected: bool,
+}
+
+/// Documents of one source with an OCR coverage issue.
+#[derive(Template)]
+#[template(path = "coverage_source.html")]
+pub struct CoverageSourceTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub source_id: String,
+    pub tabs: Vec<CoverageTab>,
+    pub documents: Vec<CoverageDocRow>,
+    pub limit: u32,
+    /// Whether the list stopped at `limit`.
+    pub truncated: bool,
+}
+
+/// A 0-1 fraction as a percentage, or `-` when unknown.
+fn percent_str(fraction: Option<f64>) -> String {
+    fraction
+        .map(|f| format!("{:.1}%", f * 100.0))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Row on the search aliases page.
+pub struct AliasRow {
+    pub id: i32,
+    pub term: String,
+    pub expansion: String,
+    pub kind: &'static str,
+}
+
+impl From<SearchAlias> for AliasRow {
+    fn from(a: SearchAlias) -> Self {
+        Self {
+            id: a.id,
+            term: a.term,
+            expansion: a.expansion,
+            kind: a.kind.label(),
+        }
+    }
+}
+
+/// Search alias dictionary page.
+#[derive(Template)]
+#[template(path = "aliases.html")]
+pub struct AliasesTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub aliases: Vec<AliasRow>,
+    pub read_only: bool,
+}
+
+/// Date review queue page.
+#[derive(Template)]
+#[template(path = "date_queue.html")]
+pub struct DateQueueTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub sources: Vec<SourceOption>,
+    /// Selected source, empty for all.
+    pub source: String,
+    pub total: u64,
+    pub read_only: bool,
+}
+
+/// Helper struct for listing URLs on the snapshots page.
+pub struct SnapshotUrlRow {
+    pub source_id: String,
+    pub url: String,
+    pub encoded_url: String,
+    pub snapshots: u64,
+    pub last_changed_str: String,
+    pub last_seen_str: String,
+}
+
+/// Listing page snapshots overview.
+#[derive(Template)]
+#[template(path = "snapshots.html")]
+pub struct SnapshotsTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub urls: Vec<SnapshotUrlRow>,
+}
+
+/// Helper struct for one snapshot in a listing page's history.
+pub struct SnapshotRow {
+    pub id: i64,
+    pub first_seen_str: String,
+    pub last_seen_str: String,
+    pub fetch_count: u32,
+    pub link_count: u32,
+    pub hash_prefix: String,
+}
+
+/// Snapshot history of one listing page.
+#[derive(Template)]
+#[template(path = "snapshot_history.html")]
+pub struct SnapshotHistoryTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub source_id: String,
+    pub url: String,
+    pub snapshots: Vec<SnapshotRow>,
+}
+
+/// One snapshot compared with the snapshot before it.
+#[derive(Template)]
+#[template(path = "snapshot_detail.html")]
+pub struct SnapshotDetailTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub snapshot: SnapshotRow,
+    pub source_id: String,
+    pub url: String,
+    pub encoded_url: String,
+    pub previous_id: Option<i64>,
+    pub added: Vec<ListingLink>,
+    pub removed: Vec<ListingLink>,
+    pub unchanged: usize,
+}
+
 /// Tags list page.
 #[derive(Template)]
 #[template(path = "tags.html")]
 pub struct TagsTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub tags: Vec<TagWithCount>,
     pub has_tags: bool,
 }
@@ -125,6 +486,8 @@ pub struct TagsTemplate<'a> {
 #[template(path = "tag_documents.html")]
 pub struct TagDocumentsTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub tag: &'a str,
     pub document_count: usize,
     pub documents: Vec<DocumentRow>,
@@ -135,6 +498,8 @@ pub struct TagDocumentsTemplate<'a> {
 #[template(path = "types.html")]
 pub struct TypesTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub categories: Vec<CategoryWithCount>,
     pub type_stats: Vec<TypeStat>,
 }
@@ -144,6 +509,8 @@ pub struct TypesTemplate<'a> {
 #[template(path = "type_documents.html")]
 pub struct TypeDocumentsTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub type_name: &'a str,
     pub document_count: usize,
     pub tabs: Vec<CategoryWithCount>,
@@ -156,11 +523,15 @@ pub struct TypeDocumentsTemplate<'a> {
 #[template(path = "document_detail.html")]
 pub struct DocumentDetailTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub doc_id: &'a str,
     pub source_id: &'a str,
     pub source_url: &'a str,
     pub versions: Vec<VersionItem>,
     pub has_versions: bool,
+    /// Current version's file is lost; only the extracted text remains.
+    pub current_lost: bool,
     pub other_sources: Vec<String>,
     pub has_other_sources: bool,
     pub has_extracted_text: bool,
@@ -168,6 +539,7 @@ pub struct DocumentDetailTemplate<'a> {
     pub virtual_files: Vec<VirtualFileRow>,
     pub has_virtual_files: bool,
     pub virtual_files_count: usize,
+    pub related: Vec<RelatedRow>,
     pub has_prev: bool,
     pub prev_id_val: String,
     pub prev_title_val: String,
@@ -182,6 +554,50 @@ pub struct DocumentDetailTemplate<'a> {
     pub has_pages: bool,
     pub page_count_val: u32,
     pub version_id_val: i64,
+    /// Viewer may correct page text from the page viewer.
+    pub can_edit_text: bool,
+    pub has_media: bool,
+    pub media_is_video: bool,
+    pub media_path: String,
+    pub media_mime: String,
+    pub segments: Vec<TranscriptSegment>,
+    pub has_segments: bool,
+    pub search_query: String,
+    pub sheets: Vec<SheetPreview>,
+}
+
+/// A run of text in the reader: a paragraph or a heading.
+pub struct ReaderBlock {
+    pub heading: bool,
+    pub text: String,
+}
+
+/// One page of the reader.
+pub struct ReaderPage {
+    pub number: u32,
+    pub blocks: Vec<ReaderBlock>,
+}
+
+/// Text-first reader: the document's text as headings and paragraphs.
+#[derive(Template)]
+#[template(path = "reader.html")]
+pub struct ReaderTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub doc_id: &'a str,
+    /// Version the text belongs to; citation links point at it.
+    pub version_id: Option<i64>,
+    /// Page a citation permalink opened.
+    pub target_page: Option<u32>,
+    /// Pages in order. Text without page breaks is all page 1.
+    pub pages: Vec<ReaderPage>,
+    /// Whether the text has real page breaks to show.
+    pub paged: bool,
+    /// Chapters and sections of the version, in reading order.
+    pub sections: Vec<DocumentSection>,
+    /// Whether sections can be downloaded as PDFs of their pages.
+    pub sections_pdf: bool,
 }
 
 /// Main browse page with filters.
@@ -189,9 +605,14 @@ pub struct DocumentDetailTemplate<'a> {
 #[template(path = "browse.html")]
 pub struct BrowseTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub documents: Vec<DocumentRow>,
     pub categories: Vec<CategoryWithCount>,
     pub sources: Vec<SourceOption>,
+    pub agencies: Vec<AgencyOption>,
+    pub record_types: Vec<RecordTypeOption>,
+    pub exemptions: Vec<ExemptionOption>,
     pub all_tags: Vec<TagWithCount>,
     pub active_tags_display: Vec<ActiveTagDisplay>,
     pub has_prev_cursor: bool,
@@ -205,6 +626,9 @@ pub struct BrowseTemplate<'a> {
     pub has_pagination: bool,
     pub nav_query_string: String,
     pub active_tags_json: String,
+    pub search_query: String,
+    /// Pages stamped with the search query, when it is a Bates number.
+    pub bates_matches: Vec<BatesMatchRow>,
 }
 
 /// Error page template.
@@ -212,9 +636,67 @@ pub struct BrowseTemplate<'a> {
 #[template(path = "error.html")]
 pub struct ErrorTemplate<'a> {
     pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
     pub message: &'a str,
 }
 
+/// Public form for requesting the removal of documents.
+#[derive(Template)]
+#[template(path = "takedown.html")]
+pub struct TakedownTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    /// Document to prefill, when linked from its page.
+    pub document: &'a str,
+    pub read_only: bool,
+}
+
+/// Volunteer transcription page.
+#[derive(Template)]
+#[template(path = "transcribe.html")]
+pub struct TranscribeTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    /// Operators transcribe under a name instead of a key.
+    pub is_internal: bool,
+    pub read_only: bool,
+}
+
+#[derive(Template)]
+#[template(path = "config.html")]
+pub struct ConfigTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    /// The page works only with the access token.
+    pub is_internal: bool,
+    pub read_only: bool,
+}
+
+/// Page shown in place of a document withheld by a takedown case.
+#[derive(Template)]
+#[template(path = "tombstone.html")]
+pub struct TombstoneTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub message: &'a str,
+    pub case_label: &'a str,
+}
+
+/// About page, rendered from the configured markdown file.
+#[derive(Template)]
+#[template(path = "about.html")]
+pub struct AboutTemplate<'a> {
+    pub title: &'a str,
+    pub theme: &'a Theme,
+    pub i18n: &'a I18n,
+    pub body_html: String,
+}
+
 // Helper implementations for converting data to template structs
 
 impl TagRef {
@@ -250,6 +732,8 @@ impl VirtualFileRow {
             mime_type: vf.mime_type.clone(),
             size_str: format_size(vf.file_size),
             status_badge: status_badge.to_string(),
+            has_synopsis: vf.synopsis.is_some(),
+            synopsis: vf.synopsis.clone().unwrap_or_default(),
         }
     }
 }
@@ -285,16 +769,22 @@ impl DocumentRow {
             icon: mime_icon(&mime_type).to_string(),
             mime_type,
             size_str: format_size(size),
-            date_str: acquired_at.format("%Y-%m-%d %H:%M").to_string(),
             timestamp: acquired_at.timestamp(),
             source_id,
             has_synopsis: synopsis.is_some(),
             synopsis_preview,
             tags: tags.iter().map(|t| TagRef::new(t.clone())).collect(),
             other_tags: Vec::new(),
+            parent_id: String::new(),
+            parent_title: String::new(),
         }
     }
 
+    /// Whether the row is an archive member or attachment of another document.
+    pub fn is_attachment(&self) -> bool {
+        !self.parent_id.is_empty()
+    }
+
     /// Create with other_tags for tag document pages (excludes the current tag).
     pub fn with_other_tags(mut self, current_tag: &str) -> Self {
         self.other_tags = self
@@ -309,6 +799,10 @@ impl DocumentRow {
 
     /// Create from an optimized BrowseRow (used for fast browse queries).
     pub fn from_browse_row(row: BrowseRow) -> Self {
+        let (parent_id, parent_title) = match row.parent_id.clone() {
+            Some(parent_id) if row.is_attachment() => (parent_id, row.title.clone()),
+            _ => (String::new(), String::new()),
+        };
         let display_name = row.original_filename.unwrap_or(row.title);
         let tags: Vec<String> = row
             .tags
@@ -335,13 +829,14 @@ impl DocumentRow {
             icon: mime_icon(&row.mime_type).to_string(),
             mime_type: row.mime_type,
             size_str: format_size(row.file_size as u64),
-            date_str: acquired_at.format("%Y-%m-%d %H:%M").to_string(),
             timestamp: acquired_at.timestamp(),
             source_id: row.source_id,
             has_synopsis: row.synopsis.is_some(),
             synopsis_preview,
             tags: tags.iter().map(|t| TagRef::new(t.clone())).collect(),
             other_tags: Vec::new(),
+            parent_id,
+            parent_title,
         }
     }
 
@@ -0,0 +1,216 @@
+//! Site branding applied to every page.
+//!
+//! Built from the `theme` config section on each request, so edits show
+//! up with the next config reload.
+
+use std::path::Path;
+
+use foia::config::ThemeConfig;
+
+/// Default site name.
+const DEFAULT_SITE_NAME: &str = "foia";
+
+/// Branding passed to templates.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub site_name: String,
+    /// Logo image URL; empty for a text-only header.
+    pub logo_url: String,
+    /// CSS variable declarations overriding the stylesheet's `:root`.
+    pub css_variables: String,
+    pub footer: String,
+    /// Whether an about page is configured.
+    pub has_about: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::from_config(&ThemeConfig::default(), Path::new(""))
+    }
+}
+
+impl Theme {
+    pub fn from_config(config: &ThemeConfig, data_dir: &Path) -> Self {
+        let logo_url = if config.logo_is_url() {
+            config.logo.clone().unwrap_or_default()
+        } else if config.logo_path(data_dir).is_some() {
+            "/theme/logo".to_string()
+        } else {
+            String::new()
+        };
+        Self {
+            site_name: config
+                .site_name
+                .clone()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_SITE_NAME.to_string()),
+            logo_url,
+            css_variables: config.css_variables(),
+            footer: config.footer.clone().unwrap_or_default(),
+            has_about: config.about.is_some(),
+        }
+    }
+}
+
+/// Render the markdown of an about page to HTML.
+///
+/// Covers what an about page needs: headings, paragraphs, bullet lists,
+/// links, emphasis and inline code. All text is escaped, so raw HTML in
+/// the file shows as text.
+pub fn render_markdown(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_list = false;
+
+    let flush = |html: &mut String, paragraph: &mut Vec<&str>| {
+        if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "));
+        if item.is_none() && in_list {
+            html.push_str("</ul>\n");
+            in_list = false;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut html, &mut paragraph);
+        } else if let Some(item) = item {
+            flush(&mut html, &mut paragraph);
+            if !in_list {
+                html.push_str("<ul>\n");
+                in_list = true;
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline(item)));
+        } else if let Some((level, heading)) = heading(trimmed) {
+            flush(&mut html, &mut paragraph);
+            html.push_str(&format!("<h{0}>{1}</h{0}>\n", level, inline(heading)));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    flush(&mut html, &mut paragraph);
+    if in_list {
+        html.push_str("</ul>\n");
+    }
+    html
+}
+
+/// `# Title` to `(level, "Title")`. Levels start at 2: the page title is
+/// the only `<h1>`.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    let text = line[hashes..].strip_prefix(' ')?;
+    (1..=5)
+        .contains(&hashes)
+        .then_some((hashes + 1, text.trim()))
+}
+
+/// Escape text and apply links, `**bold**`, `*emphasis*` and `` `code` ``.
+fn inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some((label, url, after)) = link(rest) {
+                out.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape(url),
+                    inline(label)
+                ));
+                rest = after;
+                continue;
+            }
+        }
+        let spans = [("`", "code"), ("**", "strong"), ("*", "em")];
+        if let Some((tag, inner, after)) = spans.iter().find_map(|(marker, tag)| {
+            let body = rest.strip_prefix(marker)?;
+            let end = body.find(marker).filter(|e| *e > 0)?;
+            Some((*tag, &body[..end], &body[end + marker.len()..]))
+        }) {
+            let inner = if tag == "code" {
+                escape(inner)
+            } else {
+                inline(inner)
+            };
+            out.push_str(&format!("<{0}>{1}</{0}>", tag, inner));
+            rest = after;
+            continue;
+        }
+        out.push_str(&escape(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Split `[label](url)rest`. Only http(s), mailto and site-relative URLs
+/// are linked.
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let close = text.find("](")?;
+    let label = &text[1..close];
+    let after_label = &text[close + 2..];
+    let end = after_label.find(')')?;
+    let url = &after_label[..end];
+    let allowed = ["http://", "https://", "mailto:", "/"]
+        .iter()
+        .any(|p| url.starts_with(p));
+    (allowed && !url.starts_with("//")).then(|| (label, url, &after_label[end + 1..]))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let html = render_markdown(
+            "# About us\n\nWe publish **public** records\nfrom *many* agencies.\n\n\
+             - Contact: [email](mailto:tips@example.org)\n- Source: `foia`\n",
+        );
+        assert_eq!(
+            html,
+            "<h2>About us</h2>\n\
+             <p>We publish <strong>public</strong> records from <em>many</em> agencies.</p>\n\
+             <ul>\n<li>Contact: <a href=\"mailto:tips@example.org\">email</a></li>\n\
+             <li>Source: <code>foia</code></li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_escapes_html() {
+        assert_eq!(
+            render_markdown("<script>alert(1)</script> [x](javascript:alert(1))"),
+            "<p>&lt;script&gt;alert(1)&lt;/script&gt; [x](javascript:alert(1))</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_theme_defaults() {
+        let theme = Theme::default();
+        assert_eq!(theme.site_name, "foia");
+        assert!(theme.logo_url.is_empty());
+        assert!(!theme.has_about);
+
+        let config = ThemeConfig {
+            site_name: Some("City Records".to_string()),
+            logo: Some("logo.png".to_string()),
+            ..Default::default()
+        };
+        let theme = Theme::from_config(&config, Path::new("/data"));
+        assert_eq!(theme.site_name, "City Records");
+        assert_eq!(theme.logo_url, "/theme/logo");
+    }
+}
@@ -2,6 +2,8 @@
 
 #![allow(dead_code)]
 
+#[cfg(feature = "browser")]
+use std::collections::HashMap;
 #[cfg(feature = "browser")]
 use std::time::Duration;
 
@@ -11,7 +13,9 @@ use anyhow::Result;
 use tracing::{debug, warn};
 
 #[cfg(feature = "browser")]
-use chromiumoxide::cdp::browser_protocol::network::{GetCookiesParams, SetUserAgentOverrideParams};
+use chromiumoxide::cdp::browser_protocol::network::{
+    CookieParam, GetCookiesParams, SetUserAgentOverrideParams,
+};
 #[cfg(feature = "browser")]
 use chromiumoxide::cdp::browser_protocol::page::NavigateParams;
 #[cfg(feature = "browser")]
@@ -20,6 +24,8 @@ use chromiumoxide::Page;
 #[cfg(feature = "browser")]
 use super::config::BrowserEngineType;
 #[cfg(feature = "browser")]
+use super::session::BrowserSession;
+#[cfg(feature = "browser")]
 use super::stealth::STEALTH_SCRIPTS;
 #[cfg(feature = "browser")]
 use super::types::{BrowserCookie, BrowserFetchResponse};
@@ -44,6 +50,18 @@ const WAIT_FOR_READY_SCRIPT: &str = r#"
     })
 "#;
 
+/// JavaScript returning `[origin, {key: value}]` for the page's localStorage.
+#[cfg(feature = "browser")]
+const LOCAL_STORAGE_SCRIPT: &str = r#"
+    (() => {
+        try {
+            return [location.origin, Object.fromEntries(Object.entries(localStorage))];
+        } catch (e) {
+            return [location.origin, {}];
+        }
+    })()
+"#;
+
 /// Wait for the page to reach a ready state.
 #[cfg(feature = "browser")]
 async fn wait_for_page_ready(page: &Page, timeout_secs: u64) {
@@ -122,6 +140,55 @@ async fn extract_browser_cookies(page: &Page, url: &str) -> Vec<BrowserCookie> {
         .collect()
 }
 
+/// Apply a saved session's cookies and localStorage to a fresh page.
+#[cfg(feature = "browser")]
+async fn restore_session(page: &Page, session: &BrowserSession) {
+    for cookie in &session.cookies {
+        let param = CookieParam::builder()
+            .name(cookie.name.clone())
+            .value(cookie.value.clone())
+            .domain(cookie.domain.clone())
+            .path(cookie.path.clone())
+            .secure(cookie.secure)
+            .http_only(cookie.http_only)
+            .build();
+        match param {
+            Ok(param) => {
+                if let Err(e) = page.set_cookie(param).await {
+                    debug!("Failed to restore cookie {}: {}", cookie.name, e);
+                }
+            }
+            Err(e) => debug!("Failed to build cookie {}: {}", cookie.name, e),
+        }
+    }
+
+    if let Some(script) = session.local_storage_script() {
+        if let Err(e) = page.evaluate_on_new_document(script).await {
+            debug!("Failed to install localStorage restore script: {}", e);
+        }
+    }
+
+    debug!(
+        "Restored browser session ({} cookies)",
+        session.cookies.len()
+    );
+}
+
+/// Read the page's origin and localStorage entries.
+#[cfg(feature = "browser")]
+async fn extract_local_storage(page: &Page) -> Option<(String, HashMap<String, String>)> {
+    let result = page
+        .evaluate(LOCAL_STORAGE_SCRIPT.to_string())
+        .await
+        .map_err(|e| debug!("Could not read localStorage: {}", e))
+        .ok()?;
+    let (origin, entries): (String, HashMap<String, String>) = result.into_value().ok()?;
+    if origin == "null" || entries.is_empty() {
+        return None;
+    }
+    Some((origin, entries))
+}
+
 /// Check if page content indicates blocking.
 #[cfg(feature = "browser")]
 fn check_for_block_indicators(content: &str, url: &str) {
@@ -173,6 +240,14 @@ impl BrowserFetcher {
             }
         }
 
+        // Restore the saved session so solved challenges carry over
+        let mut session = self.config.session_dir.as_ref().map(|dir| {
+            BrowserSession::load(dir, self.config.session_ttl_hours).unwrap_or_default()
+        });
+        if let Some(ref session) = session {
+            restore_session(page, session).await;
+        }
+
         // Navigate to URL with timeout
         self.navigate_to_url(page, url).await?;
 
@@ -208,6 +283,16 @@ impl BrowserFetcher {
         let cookies = extract_browser_cookies(page, &final_url).await;
         check_for_block_indicators(&content, url);
 
+        if let (Some(session), Some(dir)) = (session.as_mut(), self.config.session_dir.as_ref()) {
+            session.merge_cookies(cookies.clone());
+            if let Some((origin, entries)) = extract_local_storage(page).await {
+                session.local_storage.insert(origin, entries);
+            }
+            if let Err(e) = session.save(dir) {
+                warn!("Failed to save browser session to {:?}: {}", dir, e);
+            }
+        }
+
         Ok(BrowserFetchResponse {
             url: url.to_string(),
             final_url,
@@ -218,6 +303,60 @@ impl BrowserFetcher {
         })
     }
 
+    /// Open a URL for an operator to solve a challenge by hand.
+    ///
+    /// The page stays open until `done` resolves (e.g. the operator presses
+    /// Enter), then the cookies it earned are returned and, when a session
+    /// directory is configured, saved to the source's profile.
+    pub async fn solve_challenge<F>(&mut self, url: &str, done: F) -> Result<Vec<BrowserCookie>>
+    where
+        F: std::future::Future<Output = ()>,
+    {
+        self.ensure_browser().await?;
+
+        let browser = self
+            .browser
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("browser not initialized after ensure_browser"))?
+            .lock()
+            .await;
+        let page = browser.new_page("about:blank").await?;
+
+        let result = async {
+            page.execute(SetUserAgentOverrideParams::new(
+                BROWSER_USER_AGENT.to_string(),
+            ))
+            .await?;
+            self.navigate_to_url(&page, url).await?;
+            done.await;
+
+            let final_url = page
+                .url()
+                .await?
+                .map(|u| u.to_string())
+                .unwrap_or_else(|| url.to_string());
+            let cookies = extract_browser_cookies(&page, &final_url).await;
+
+            if let Some(dir) = self.config.session_dir.as_ref() {
+                let mut session =
+                    BrowserSession::load(dir, self.config.session_ttl_hours).unwrap_or_default();
+                session.merge_cookies(cookies.clone());
+                if let Some((origin, entries)) = extract_local_storage(&page).await {
+                    session.local_storage.insert(origin, entries);
+                }
+                if let Err(e) = session.save(dir) {
+                    warn!("Failed to save browser session to {:?}: {}", dir, e);
+                }
+            }
+
+            Ok(cookies)
+        }
+        .await;
+
+        let _ = page.close().await;
+        result
+    }
+
     /// Navigate to a URL with timeout handling.
     async fn navigate_to_url(&self, page: &Page, url: &str) -> Result<()> {
         tracing::info!("Navigating to {}", url);
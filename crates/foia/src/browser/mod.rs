@@ -11,6 +11,7 @@ mod cookies;
 mod fetch;
 mod pool;
 mod selection;
+mod session;
 mod stealth;
 mod types;
 
@@ -18,8 +19,9 @@ pub use config::{BrowserEngineConfig, BrowserEngineType};
 #[cfg(feature = "browser")]
 pub use pool::{BrowserPool, BrowserPoolConfig};
 pub use selection::SelectionStrategyType;
+pub use session::BrowserSession;
 #[allow(unused_imports)]
-pub use types::{BinaryFetchResponse, BrowserCookie, BrowserFetchResponse};
+pub use types::{cookie_header, BinaryFetchResponse, BrowserCookie, BrowserFetchResponse};
 
 #[cfg(not(feature = "browser"))]
 use std::path::PathBuf;
@@ -336,6 +338,15 @@ impl BrowserFetcher {
         ))
     }
 
+    pub async fn solve_challenge<F>(&mut self, _url: &str, _done: F) -> Result<Vec<BrowserCookie>>
+    where
+        F: std::future::Future<Output = ()>,
+    {
+        Err(anyhow::anyhow!(
+            "Browser support not compiled. Rebuild with: cargo build --features browser"
+        ))
+    }
+
     pub async fn close(&mut self) {}
 }
 
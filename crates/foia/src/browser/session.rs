@@ -0,0 +1,177 @@
+//! Persistent browser sessions (cookies and localStorage) per source.
+//!
+//! Anti-bot portals hand out clearance cookies and localStorage tokens once a
+//! challenge is solved. Saving them between fetches means later pages reuse
+//! the solved session instead of triggering a new challenge each time.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::types::BrowserCookie;
+
+/// File name of the saved session inside a profile directory.
+const SESSION_FILE: &str = "session.json";
+
+/// Saved browser state for one source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserSession {
+    pub saved_at: DateTime<Utc>,
+    pub cookies: Vec<BrowserCookie>,
+    /// localStorage entries keyed by origin (e.g. "https://www.cia.gov").
+    #[serde(default)]
+    pub local_storage: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for BrowserSession {
+    fn default() -> Self {
+        Self {
+            saved_at: Utc::now(),
+            cookies: Vec::new(),
+            local_storage: HashMap::new(),
+        }
+    }
+}
+
+impl BrowserSession {
+    /// Load the session in `dir`, discarding it if older than `ttl_hours`
+    /// (0 = never expires). Missing or unreadable sessions yield `None`.
+    pub fn load(dir: &Path, ttl_hours: u64) -> Option<Self> {
+        let path = dir.join(SESSION_FILE);
+        let content = std::fs::read_to_string(&path).ok()?;
+        let session: Self = match serde_json::from_str(&content) {
+            Ok(s) => s,
+            Err(e) => {
+                debug!("Ignoring unreadable browser session {:?}: {}", path, e);
+                return None;
+            }
+        };
+
+        if session.is_expired(ttl_hours, Utc::now()) {
+            debug!("Browser session {:?} expired, discarding", path);
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+
+        Some(session)
+    }
+
+    /// Write the session to `dir`, stamping it with the current time.
+    pub fn save(&mut self, dir: &Path) -> std::io::Result<()> {
+        self.saved_at = Utc::now();
+        std::fs::create_dir_all(dir)?;
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(dir.join(SESSION_FILE), json)
+    }
+
+    /// Whether the session was saved more than `ttl_hours` before `now`.
+    pub fn is_expired(&self, ttl_hours: u64, now: DateTime<Utc>) -> bool {
+        ttl_hours > 0 && now - self.saved_at > Duration::hours(ttl_hours as i64)
+    }
+
+    /// Replace cookies with the same name, domain, and path; add the rest.
+    pub fn merge_cookies(&mut self, cookies: Vec<BrowserCookie>) {
+        for cookie in cookies {
+            self.cookies.retain(|c| {
+                !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+            });
+            self.cookies.push(cookie);
+        }
+    }
+
+    /// Script that restores saved localStorage for the current origin before
+    /// any page script runs.
+    pub fn local_storage_script(&self) -> Option<String> {
+        if self.local_storage.is_empty() {
+            return None;
+        }
+        let data = serde_json::to_string(&self.local_storage).ok()?;
+        Some(format!(
+            r#"(() => {{
+                const saved = {}[location.origin];
+                if (!saved) return;
+                for (const [k, v] of Object.entries(saved)) {{
+                    try {{ localStorage.setItem(k, v); }} catch (e) {{}}
+                }}
+            }})();"#,
+            data
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie(name: &str, value: &str) -> BrowserCookie {
+        BrowserCookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: ".cia.gov".to_string(),
+            path: "/".to_string(),
+            secure: true,
+            http_only: true,
+        }
+    }
+
+    #[test]
+    fn test_expiry() {
+        let session = BrowserSession::default();
+        let later = session.saved_at + Duration::hours(25);
+        assert!(session.is_expired(24, later));
+        assert!(!session.is_expired(48, later));
+        assert!(!session.is_expired(0, later));
+    }
+
+    #[test]
+    fn test_merge_cookies_replaces_same_name() {
+        let mut session = BrowserSession::default();
+        session.merge_cookies(vec![cookie("cf_clearance", "old"), cookie("a", "1")]);
+        session.merge_cookies(vec![cookie("cf_clearance", "new")]);
+        assert_eq!(session.cookies.len(), 2);
+        let clearance = session
+            .cookies
+            .iter()
+            .find(|c| c.name == "cf_clearance")
+            .unwrap();
+        assert_eq!(clearance.value, "new");
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = BrowserSession::default();
+        session.merge_cookies(vec![cookie("a", "1")]);
+        session.local_storage.insert(
+            "https://www.cia.gov".to_string(),
+            HashMap::from([("token".to_string(), "abc".to_string())]),
+        );
+        session.save(dir.path()).unwrap();
+
+        let loaded = BrowserSession::load(dir.path(), 24).unwrap();
+        assert_eq!(loaded.cookies.len(), 1);
+        assert!(loaded.local_storage_script().unwrap().contains("abc"));
+    }
+
+    #[test]
+    fn test_load_discards_expired() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = BrowserSession {
+            saved_at: Utc::now() - Duration::hours(48),
+            ..Default::default()
+        };
+        std::fs::write(
+            dir.path().join(SESSION_FILE),
+            serde_json::to_string(&session).unwrap(),
+        )
+        .unwrap();
+
+        assert!(BrowserSession::load(dir.path(), 24).is_none());
+        assert!(!dir.path().join(SESSION_FILE).exists());
+    }
+}
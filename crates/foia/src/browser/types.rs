@@ -23,6 +23,15 @@ pub struct BrowserCookie {
     pub http_only: bool,
 }
 
+/// Format cookies as a `Cookie` request header value.
+pub fn cookie_header(cookies: &[BrowserCookie]) -> String {
+    cookies
+        .iter()
+        .map(|c| format!("{}={}", c.name, c.value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// Response from binary fetch (PDF, images, etc).
 #[derive(Debug, Clone)]
 pub struct BinaryFetchResponse {
@@ -0,0 +1,44 @@
+//! Access restriction configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Default requests per minute for an API key without its own limit.
+const DEFAULT_API_RATE_LIMIT: u32 = 60;
+
+/// Settings for serving restricted documents. Which documents are
+/// restricted is stored in the database (`foia access set`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct AccessConfig {
+    /// Token that unlocks internal and embargoed documents on the web
+    /// server, sent as `Authorization: Bearer <token>` or in the
+    /// `foia_access` cookie. Without it, restricted documents are never
+    /// served. May be a `secret://name` reference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub token: Option<String>,
+
+    /// Refuse JSON API requests that carry neither an API key
+    /// (`foia api-key create`) nor the access token. Requests made by the
+    /// site's own pages are still answered.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[prefer(default)]
+    pub require_api_key: bool,
+
+    /// Requests per minute for API keys created without `--rate-limit`
+    /// (default: 60; 0 for no limit).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub api_rate_limit: Option<u32>,
+}
+
+impl AccessConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Requests per minute for API keys without their own limit.
+    pub fn api_rate_limit(&self) -> u32 {
+        self.api_rate_limit.unwrap_or(DEFAULT_API_RATE_LIMIT)
+    }
+}
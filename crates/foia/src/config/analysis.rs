@@ -169,12 +169,108 @@ pub struct AnalysisConfig {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[prefer(default)]
     pub default_methods: Vec<String>,
+    /// How a page's final text is chosen when several backends read it.
+    #[serde(default, skip_serializing_if = "ArbitrationConfig::is_default")]
+    #[prefer(default)]
+    pub arbitration: ArbitrationConfig,
+    /// Image cleanup of scanned pages before OCR.
+    #[serde(default, skip_serializing_if = "PreprocessConfig::is_default")]
+    #[prefer(default)]
+    pub preprocess: PreprocessConfig,
 }
 
 impl AnalysisConfig {
     /// Check if this is the default (empty) config.
     pub fn is_default(&self) -> bool {
-        self.methods.is_empty() && self.default_methods.is_empty()
+        self.methods.is_empty()
+            && self.default_methods.is_empty()
+            && self.arbitration.is_default()
+            && self.preprocess.is_default()
+    }
+}
+
+/// Default score gap under which the LLM judge is consulted.
+const DEFAULT_JUDGE_MARGIN: f64 = 0.05;
+
+/// OCR result arbitration.
+///
+/// When several backends have read a page, their outputs are scored by how
+/// well they agree with each other and how many of their words are real,
+/// and the best is taken as the page's final text. With three or more
+/// readings, words the others outvote are corrected in it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct ArbitrationConfig {
+    /// Keep the old behavior: the longest reading wins.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[prefer(default)]
+    pub disabled: bool,
+    /// Don't correct words by majority vote; only pick a reading.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[prefer(default)]
+    pub no_fusion: bool,
+    /// Word list (one word per line) for the dictionary check. Without one,
+    /// words are checked for looking like words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub dictionary: Option<String>,
+    /// Ask the configured LLM to choose when the top readings score within
+    /// `judge_margin` of each other.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[prefer(default)]
+    pub llm_judge: bool,
+    /// Score gap under which the LLM judge decides (default 0.05).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub judge_margin: Option<f64>,
+}
+
+impl ArbitrationConfig {
+    /// Check if the config equals the default (for skip_serializing_if).
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn judge_margin(&self) -> f64 {
+        self.judge_margin.unwrap_or(DEFAULT_JUDGE_MARGIN)
+    }
+}
+
+/// Preprocessing steps run when none are configured, in order.
+pub const DEFAULT_PREPROCESS_STEPS: &[&str] = &["rotate", "deskew", "despeckle", "normalize"];
+
+/// Image preprocessing of scanned pages before OCR.
+///
+/// Each page is rendered once, uprighted, deskewed, despeckled and
+/// contrast-normalized, and every backend reads the cleaned image. Steps
+/// need ImageMagick; orientation detection also needs tesseract.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct PreprocessConfig {
+    /// OCR pages as rendered, without preprocessing.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[prefer(default)]
+    pub disabled: bool,
+    /// Steps to run, in order: "rotate", "deskew", "despeckle" and
+    /// "normalize" (default: all of them).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[prefer(default)]
+    pub steps: Vec<String>,
+}
+
+impl PreprocessConfig {
+    /// Check if the config equals the default (for skip_serializing_if).
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The steps to run, or none if preprocessing is disabled.
+    pub fn steps(&self) -> Vec<&str> {
+        if self.disabled {
+            Vec::new()
+        } else if self.steps.is_empty() {
+            DEFAULT_PREPROCESS_STEPS.to_vec()
+        } else {
+            self.steps.iter().map(String::as_str).collect()
+        }
     }
 }
 
@@ -4,7 +4,7 @@
 //! They live here (always compiled) rather than behind `#[cfg(feature = "browser")]`
 //! so that config parsing and serialization work without the browser feature.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -123,6 +123,19 @@ pub struct BrowserEngineConfig {
     /// Options: round-robin (default), random, per-domain.
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub selection: SelectionStrategyType,
+
+    /// Keep cookies and localStorage between fetches in a per-source
+    /// profile under the data directory, so solved challenges are reused.
+    #[serde(default)]
+    pub persist_session: bool,
+
+    /// Hours before a saved browser profile is discarded (0 = never).
+    #[serde(default = "default_session_ttl_hours")]
+    pub session_ttl_hours: u64,
+
+    /// Resolved profile directory for this source (set at runtime).
+    #[serde(skip)]
+    pub session_dir: Option<PathBuf>,
 }
 
 fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
@@ -172,6 +185,15 @@ impl BrowserEngineConfig {
         self
     }
 
+    /// Point the session profile at `<data_dir>/browser_profiles/<source_id>`
+    /// when `persist_session` is enabled.
+    pub fn with_session_dir(mut self, data_dir: &Path, source_id: &str) -> Self {
+        if self.persist_session {
+            self.session_dir = Some(data_dir.join("browser_profiles").join(source_id));
+        }
+        self
+    }
+
     /// Check if multiple browsers are configured.
     pub fn has_multiple_browsers(&self) -> bool {
         !self.urls.is_empty()
@@ -210,6 +232,10 @@ pub fn default_timeout() -> u64 {
     30
 }
 
+pub fn default_session_ttl_hours() -> u64 {
+    24
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -302,6 +328,8 @@ mod tests {
         assert!(config.remote_url.is_none());
         assert!(config.urls.is_empty());
         assert_eq!(config.selection, SelectionStrategyType::RoundRobin);
+        assert!(!config.persist_session);
+        assert!(config.session_dir.is_none());
     }
 
     #[test]
@@ -310,6 +338,24 @@ mod tests {
         assert!(!config.enabled);
         assert!(config.headless); // serde default = true via default_headless
         assert_eq!(config.timeout, 30); // serde default via default_timeout
+        assert_eq!(config.session_ttl_hours, 24);
+    }
+
+    #[test]
+    fn test_with_session_dir() {
+        let data_dir = Path::new("/data");
+        let off = BrowserEngineConfig::default().with_session_dir(data_dir, "cia");
+        assert!(off.session_dir.is_none());
+
+        let on = BrowserEngineConfig {
+            persist_session: true,
+            ..Default::default()
+        }
+        .with_session_dir(data_dir, "cia");
+        assert_eq!(
+            on.session_dir,
+            Some(PathBuf::from("/data/browser_profiles/cia"))
+        );
     }
 
     #[test]
@@ -0,0 +1,76 @@
+//! Circuit breaker configuration.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::http_client::BreakerSettings;
+
+/// When to pause a domain that keeps answering 403, 429 or 451, and how
+/// to bring it back.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct CircuitBreakerConfig {
+    /// Set to false to never pause domains (default true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub enabled: Option<bool>,
+    /// Seconds of responses counted per domain (default 300).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub window_secs: Option<u64>,
+    /// Fewest responses in the window before a domain can be paused
+    /// (default 10).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub min_responses: Option<usize>,
+    /// Share of responses in the window that must be blocked (default 0.8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub trip_ratio: Option<f64>,
+    /// Seconds a tripped domain stays paused (default 1800).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub cooldown_secs: Option<u64>,
+    /// Milliseconds between requests when a domain resumes, eased back to
+    /// the normal delay as requests succeed (default 30000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub ramp_up_delay_ms: Option<u64>,
+    /// URL that receives a JSON `POST` when a domain is paused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub alert_webhook: Option<String>,
+}
+
+impl CircuitBreakerConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Breaker settings, with defaults for whatever isn't set here.
+    pub fn settings(&self) -> BreakerSettings {
+        let defaults = BreakerSettings::default();
+        BreakerSettings {
+            enabled: self.enabled.unwrap_or(defaults.enabled),
+            window: self
+                .window_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.window),
+            min_responses: self.min_responses.unwrap_or(defaults.min_responses),
+            trip_ratio: self
+                .trip_ratio
+                .map(|r| r.clamp(0.0, 1.0))
+                .unwrap_or(defaults.trip_ratio),
+            cooldown: self
+                .cooldown_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.cooldown),
+            ramp_up_delay: self
+                .ramp_up_delay_ms
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.ramp_up_delay),
+            alert_webhook: self.alert_webhook.clone(),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Chain-of-custody certificate configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for signing chain-of-custody certificates.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, prefer::FromValue)]
+pub struct CustodyConfig {
+    /// Path to an ed25519 signing key: a file containing the 32-byte seed
+    /// hex-encoded (e.g. generated with `openssl rand -hex 32`).
+    /// Relative paths are resolved against the config file directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub signing_key: Option<String>,
+    /// Name of the person or organization issuing certificates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub signer: Option<String>,
+}
+
+impl CustodyConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        self.signing_key.is_none() && self.signer.is_none()
+    }
+}
@@ -0,0 +1,34 @@
+//! At-rest document encryption configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for encrypting document files on disk.
+///
+/// Encryption is enabled when either key source is set. The key is a
+/// hex-encoded 32-byte value (e.g. generated with `foia docs keygen` or
+/// `openssl rand -hex 32`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct EncryptionConfig {
+    /// Path to a file containing the hex-encoded key.
+    /// Relative paths are resolved against the config file directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub key_file: Option<String>,
+    /// Name of an OS keychain entry (service `foia`) holding the hex-encoded
+    /// key. Requires a build with the `keychain` feature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub keychain: Option<String>,
+}
+
+impl EncryptionConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether document files should be encrypted.
+    pub fn is_enabled(&self) -> bool {
+        self.key_file.is_some() || self.keychain.is_some()
+    }
+}
@@ -0,0 +1,87 @@
+//! Scheduled export configuration.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Export jobs run by `foia export run` to feed downstream pipelines.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct ExportsConfig {
+    /// URL that receives a JSON `POST` when an export job fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub alert_webhook: Option<String>,
+    /// Named export jobs.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[prefer(default)]
+    pub jobs: HashMap<String, ExportJobConfig>,
+}
+
+/// One scheduled export.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct ExportJobConfig {
+    /// `jsonl` (documents changed since the last successful run),
+    /// `warc` (every stored file) or `bag` (BagIt zip of every stored file).
+    pub format: String,
+    /// `hourly`, `daily` or `weekly`.
+    pub schedule: String,
+    /// Local directory, `s3://bucket/prefix` or `sftp://user@host/path`.
+    pub destination: String,
+    /// Only export this source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub source: Option<String>,
+    /// How `bag` exports name files: `hash` (default), `original` (the
+    /// path in the production they were imported from) or `bates`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub layout: Option<String>,
+    /// Also export internal and embargoed documents (see `foia access`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[prefer(default)]
+    pub include_restricted: bool,
+    /// Read every document from one database snapshot taken when the run
+    /// starts, so documents crawled meanwhile are left for the next run.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[prefer(default)]
+    pub snapshot: bool,
+    /// S3 credentials, for `s3://` destinations.
+    #[serde(default, skip_serializing_if = "S3Config::is_default")]
+    #[prefer(default)]
+    pub s3: S3Config,
+}
+
+/// S3 (or S3-compatible) upload settings. Keys may be `secret://name`
+/// references.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct S3Config {
+    /// Region (default `us-east-1`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub region: Option<String>,
+    /// Endpoint of an S3-compatible store, e.g. `https://minio.example.org`.
+    /// Defaults to AWS. Buckets are addressed path-style.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub access_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub secret_key: Option<String>,
+}
+
+impl ExportsConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl S3Config {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
@@ -0,0 +1,30 @@
+//! Duplicate fetch suppression configuration.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::http_client::DEFAULT_DEDUP_WINDOW;
+
+/// How long a URL (by its normalized form) or fetched content is claimed,
+/// suppressing duplicate fetches of it by other workers and processes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct FetchDedupConfig {
+    /// Seconds a claim lasts (default 600; 0 disables suppression).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub window_secs: Option<u64>,
+}
+
+impl FetchDedupConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_DEDUP_WINDOW)
+    }
+}
@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 
 use crate::repository::util::{is_postgres_url, validate_database_url};
 
-use super::{Config, ResolvedData, Settings};
+use super::{Config, Override, ResolvedData, Settings};
 
 /// Options for loading settings.
 #[derive(Debug, Clone, Default)]
@@ -16,6 +16,10 @@ pub struct LoadOptions {
     /// Data directory or database file (--data flag).
     /// Can be a directory containing foia.db or a .db file directly.
     pub data: Option<PathBuf>,
+    /// Named profile to layer over the config file (--profile flag).
+    pub profile: Option<String>,
+    /// `key=value` overrides of single settings (--set flags).
+    pub overrides: Vec<String>,
 }
 
 /// Look for a config file next to the database.
@@ -122,8 +126,17 @@ async fn load_file_config(options: &LoadOptions, data_dir_override: Option<&Path
 }
 
 /// Load settings with explicit options.
-/// Returns (Settings, Config) tuple.
-pub async fn load_settings_with_options(options: LoadOptions) -> (Settings, Config) {
+///
+/// Precedence, highest first: `--set` flags, `FOIACQUIRE__` environment
+/// variables, the older single-purpose environment variables
+/// (`DATABASE_URL`, `RATE_LIMIT_BACKEND`, `BROKER_URL`, `FOIA_NO_TLS`),
+/// the profile, the config file, defaults. Source settings stored in the
+/// database rank below the overrides wherever they are laid over the
+/// config. Returns (Settings, Config) tuple, or an error naming an
+/// unknown profile or an invalid override.
+pub async fn load_settings_with_options(
+    options: LoadOptions,
+) -> Result<(Settings, Config), String> {
     let db_env = DatabaseUrlEnv::from_env();
 
     let data_dir_override = options.data.as_ref().map(|d| resolve_data_path_to_dir(d));
@@ -135,9 +148,22 @@ pub async fn load_settings_with_options(options: LoadOptions) -> (Settings, Conf
         None
     };
 
-    let config =
+    let mut config =
         load_config_from_sources(&options, data_dir_override.as_ref(), resolved_data.as_ref())
             .await;
+    if let Some(ref profile) = options.profile {
+        config = config.with_profile(profile)?;
+    }
+    let overrides = Override::collect(&options.overrides)?;
+    if !overrides.is_empty() {
+        config = config.with_overrides(overrides)?;
+    }
+    let overridden = |keys: &[&str]| {
+        config
+            .overrides
+            .iter()
+            .any(|o| keys.contains(&o.key.as_str()))
+    };
 
     let mut settings = Settings::default();
 
@@ -163,8 +189,11 @@ pub async fn load_settings_with_options(options: LoadOptions) -> (Settings, Conf
         settings.database_filename = resolved.database_filename;
     }
 
-    // DATABASE_URL environment variable takes highest precedence
-    if let Some(database_url) = db_env.url {
+    // DATABASE_URL environment variable takes precedence over config
+    if let Some(database_url) = db_env
+        .url
+        .filter(|_| !overridden(&["database", "database_url"]))
+    {
         tracing::debug!(
             "Using DATABASE_URL from environment: {}",
             crate::repository::util::redact_url_password(&database_url)
@@ -175,7 +204,7 @@ pub async fn load_settings_with_options(options: LoadOptions) -> (Settings, Conf
     // RATE_LIMIT_BACKEND environment variable takes precedence over config
     if let Some(backend) = std::env::var("RATE_LIMIT_BACKEND")
         .ok()
-        .filter(|s| !s.is_empty())
+        .filter(|s| !s.is_empty() && !overridden(&["rate_limit_backend"]))
     {
         tracing::debug!(
             "Using RATE_LIMIT_BACKEND from environment: {}",
@@ -185,7 +214,10 @@ pub async fn load_settings_with_options(options: LoadOptions) -> (Settings, Conf
     }
 
     // BROKER_URL environment variable takes precedence over config
-    if let Some(broker) = std::env::var("BROKER_URL").ok().filter(|s| !s.is_empty()) {
+    if let Some(broker) = std::env::var("BROKER_URL")
+        .ok()
+        .filter(|s| !s.is_empty() && !overridden(&["broker_url"]))
+    {
         tracing::debug!(
             "Using BROKER_URL from environment: {}",
             crate::repository::util::redact_url_password(&broker)
@@ -199,5 +231,28 @@ pub async fn load_settings_with_options(options: LoadOptions) -> (Settings, Conf
         settings.no_tls = true;
     }
 
-    (settings, config)
+    apply_settings_overrides(&config, &mut settings, &base_dir)?;
+
+    Ok((settings, config))
+}
+
+/// Apply the overrides of settings that aren't in the config file.
+fn apply_settings_overrides(
+    config: &Config,
+    settings: &mut Settings,
+    base_dir: &Path,
+) -> Result<(), String> {
+    for o in config.overrides.iter().filter(|o| o.is_settings_key()) {
+        match o.key.as_str() {
+            "database_url" => {
+                validate_database_url(&o.value).map_err(|e| format!("{}: {}", o.origin(), e))?;
+                settings.database_url = Some(o.value.clone());
+            }
+            "documents_dir" => settings.documents_dir = config.resolve_path(&o.value, base_dir),
+            "no_tls" => settings.no_tls = o.bool_value()?,
+            "read_only" => settings.read_only = o.bool_value()?,
+            _ => {}
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,50 @@
+//! Embedded media (yt-dlp) download configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Default yt-dlp binary name, resolved on `PATH`.
+pub const DEFAULT_MEDIA_BINARY: &str = "yt-dlp";
+
+/// Default yt-dlp format selector: a single mp4 when offered, otherwise the
+/// best available streams.
+pub const DEFAULT_MEDIA_FORMAT: &str = "best[ext=mp4]/bestvideo+bestaudio/best";
+
+/// Settings for fetching embedded video and audio (bodycam footage, meeting
+/// recordings) through yt-dlp instead of plain HTTP.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct MediaConfig {
+    /// Regex patterns for page or player URLs handed to yt-dlp.
+    /// Media downloads are disabled while this is empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[prefer(default)]
+    pub url_patterns: Vec<String>,
+    /// Path to the yt-dlp binary (default: `yt-dlp` on `PATH`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub binary: Option<String>,
+    /// yt-dlp format selector (`--format`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub format: Option<String>,
+    /// Extra command-line arguments passed to yt-dlp.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[prefer(default)]
+    pub extra_args: Vec<String>,
+}
+
+impl MediaConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Binary to execute.
+    pub fn binary(&self) -> &str {
+        self.binary.as_deref().unwrap_or(DEFAULT_MEDIA_BINARY)
+    }
+
+    /// Format selector to request.
+    pub fn format(&self) -> &str {
+        self.format.as_deref().unwrap_or(DEFAULT_MEDIA_FORMAT)
+    }
+}
@@ -1,11 +1,27 @@
 //! Configuration management for foia using the prefer crate.
 
+mod access;
 mod analysis;
 pub mod browser;
+mod circuit_breaker;
+mod custody;
 pub mod discovery;
+mod encryption;
+mod exports;
+mod fetch_dedup;
 mod loader;
+mod media;
+mod overrides;
+mod pool;
+mod reload;
 pub mod scraper;
+mod search;
+mod secrets;
 mod settings;
+mod source_template;
+mod sync;
+mod theme;
+mod transparency;
 
 use std::collections::HashMap;
 use std::fs;
@@ -14,15 +30,36 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::llm::LlmConfig;
+use crate::llm::{LlmBackendProfile, LlmConfig};
 use crate::privacy::PrivacyConfig;
 use crate::repository::util::validate_database_url;
 
-pub use analysis::{AnalysisConfig, AnalysisMethodConfig, OcrConfig};
+pub use access::AccessConfig;
+pub use analysis::{
+    AnalysisConfig, AnalysisMethodConfig, ArbitrationConfig, OcrConfig, PreprocessConfig,
+};
 pub use browser::{BrowserEngineConfig, BrowserEngineType, SelectionStrategyType};
+pub use circuit_breaker::CircuitBreakerConfig;
+pub use custody::CustodyConfig;
+pub use encryption::EncryptionConfig;
+pub use exports::{ExportJobConfig, ExportsConfig, S3Config};
+pub use fetch_dedup::FetchDedupConfig;
 pub use loader::{load_settings_with_options, LoadOptions};
-pub use scraper::{ScraperConfig, ViaMode};
+pub use media::MediaConfig;
+pub use overrides::{Override, OverrideSource};
+pub use pool::PoolConfig;
+pub use reload::{ConfigReload, ConfigReloader, ScraperDiff};
+pub use scraper::{
+    CrawlWindowConfig, FrontierConfig, HooksConfig, ProcessingConfig, QuotaConfig, QuotaLevel,
+    RetentionConfig, ScraperConfig, SourceAuthConfig, SourceTestConfig, ValidationConfig, ViaMode,
+};
+pub use search::{SearchBackend, SearchConfig};
+pub use secrets::SecretsConfig;
 pub use settings::Settings;
+pub use source_template::SourceTemplate;
+pub use sync::{SyncConfig, SyncRemote};
+pub use theme::ThemeConfig;
+pub use transparency::{TransparencyConfig, CRAWL_POLICY_HEADER};
 
 /// Default refresh TTL in days (14 days).
 pub const DEFAULT_REFRESH_TTL_DAYS: u64 = 14;
@@ -78,6 +115,58 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "PrivacyConfig::is_default")]
     #[prefer(default)]
     pub privacy: PrivacyConfig,
+    /// Chain-of-custody certificate signing.
+    #[serde(default, skip_serializing_if = "CustodyConfig::is_default")]
+    #[prefer(default)]
+    pub custody: CustodyConfig,
+    /// At-rest encryption of document files.
+    #[serde(default, skip_serializing_if = "EncryptionConfig::is_default")]
+    #[prefer(default)]
+    pub encryption: EncryptionConfig,
+    /// Secret stores for `secret://` references.
+    #[serde(default, skip_serializing_if = "SecretsConfig::is_default")]
+    #[prefer(default)]
+    pub secrets: SecretsConfig,
+    /// Sync with other foia instances.
+    #[serde(default, skip_serializing_if = "SyncConfig::is_default")]
+    #[prefer(default)]
+    pub sync: SyncConfig,
+    /// Scheduled exports to external destinations.
+    #[serde(default, skip_serializing_if = "ExportsConfig::is_default")]
+    #[prefer(default)]
+    pub exports: ExportsConfig,
+    /// Serving of internal and embargoed documents.
+    #[serde(default, skip_serializing_if = "AccessConfig::is_default")]
+    #[prefer(default)]
+    pub access: AccessConfig,
+    /// Branding of the web interface.
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_default")]
+    #[prefer(default)]
+    pub theme: ThemeConfig,
+    /// Search backend (database or tantivy index).
+    #[serde(default, skip_serializing_if = "SearchConfig::is_default")]
+    #[prefer(default)]
+    pub search: SearchConfig,
+    /// Database connection pool tuning.
+    #[serde(default, skip_serializing_if = "PoolConfig::is_default")]
+    #[prefer(default)]
+    pub pool: PoolConfig,
+    /// Embedded video/audio downloads via yt-dlp.
+    #[serde(default, skip_serializing_if = "MediaConfig::is_default")]
+    #[prefer(default)]
+    pub media: MediaConfig,
+    /// Pausing of domains that keep blocking requests.
+    #[serde(default, skip_serializing_if = "CircuitBreakerConfig::is_default")]
+    #[prefer(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Suppression of duplicate fetches of the same URL or content.
+    #[serde(default, skip_serializing_if = "FetchDedupConfig::is_default")]
+    #[prefer(default)]
+    pub fetch_dedup: FetchDedupConfig,
+    /// Identification sent with every request.
+    #[serde(default, skip_serializing_if = "TransparencyConfig::is_default")]
+    #[prefer(default)]
+    pub transparency: TransparencyConfig,
     /// URL rewriting for caching proxies (CDN bypass).
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     #[prefer(default)]
@@ -86,6 +175,20 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "is_via_mode_default")]
     #[prefer(default)]
     pub via_mode: ViaMode,
+    /// Named overlays of any of the settings above (e.g. `dev`, `staging`,
+    /// `production`), selected with `--profile` or `FOIA_PROFILE`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[prefer(skip)]
+    pub profiles: HashMap<String, serde_json::Value>,
+    /// Profile laid over this config, if any (not serialized).
+    #[serde(skip)]
+    #[prefer(skip)]
+    pub profile: Option<String>,
+    /// Overrides from the environment and `--set` flags applied to this
+    /// config (not serialized).
+    #[serde(skip)]
+    #[prefer(skip)]
+    pub overrides: Vec<Override>,
     /// Path to the config file this was loaded from (not serialized).
     #[serde(skip)]
     #[prefer(skip)]
@@ -233,6 +336,119 @@ impl Config {
         Ok(config)
     }
 
+    /// Lay the named profile over this config. Tables in the profile are
+    /// merged key by key into the base settings; any other value replaces
+    /// the base value.
+    pub fn with_profile(self, name: &str) -> Result<Self, String> {
+        let Some(overlay) = self.profiles.get(name).cloned() else {
+            let mut names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            names.sort();
+            return Err(if names.is_empty() {
+                format!("Unknown config profile '{}': no profiles defined", name)
+            } else {
+                format!(
+                    "Unknown config profile '{}' (defined: {})",
+                    name,
+                    names.join(", ")
+                )
+            });
+        };
+        let serde_json::Value::Object(mut overlay) = overlay else {
+            return Err(format!("Config profile '{}' must be a table", name));
+        };
+        if let Some(target) = overlay.remove("target") {
+            overlay.insert("data_dir".to_string(), target);
+        }
+
+        let mut config = self
+            .overlay(overlay)
+            .map_err(|e| format!("Invalid config profile '{}': {}", name, e))?;
+        config.profile = Some(name.to_string());
+        Ok(config)
+    }
+
+    /// Apply overrides from `FOIACQUIRE__` environment variables and
+    /// `--set` flags, later ones winning. They are kept with the config so
+    /// they still win when database settings are laid over it or the
+    /// config is reloaded.
+    pub fn with_overrides(mut self, overrides: Vec<Override>) -> Result<Self, String> {
+        self.overrides = overrides;
+        self.apply_overrides()
+    }
+
+    /// Re-apply the overrides kept with the config.
+    fn apply_overrides(self) -> Result<Self, String> {
+        let overrides = self.overrides.clone();
+        let mut config = self;
+        for o in overrides.iter().filter(|o| !o.is_settings_key()) {
+            // A value that parses as JSON may still be meant as a string
+            config = match config.clone().overlay(o.overlay(false)) {
+                Ok(config) => config,
+                Err(e) => config
+                    .overlay(o.overlay(true))
+                    .map_err(|_| format!("Invalid {}: {}", o.origin(), e))?,
+            };
+        }
+        Ok(config)
+    }
+
+    /// Merge a table of settings into this config. Tables are merged key
+    /// by key; any other value replaces the base value.
+    fn overlay(
+        self,
+        mut overlay: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Self, String> {
+        overlay.remove("profiles");
+        // LLM backend settings aren't part of the serialized config
+        let backend: LlmBackendProfile = match overlay.get("llm") {
+            Some(llm) => serde_json::from_value(llm.clone()).map_err(|e| e.to_string())?,
+            None => LlmBackendProfile::default(),
+        };
+
+        let mut merged = serde_json::to_value(&self).map_err(|e| e.to_string())?;
+        merge_json(&mut merged, serde_json::Value::Object(overlay));
+        let mut config: Config = serde_json::from_value(merged).map_err(|e| e.to_string())?;
+        config.llm.device = self.llm.device;
+        config.llm.device.apply_profile(backend)?;
+        config.profile = self.profile;
+        config.overrides = self.overrides;
+        config.source_path = self.source_path;
+        config.privacy = config.privacy.with_env_overrides();
+        Ok(config)
+    }
+
+    /// Lay source settings stored in the database over this config, as
+    /// they take precedence at runtime.
+    pub fn with_sources(mut self, sources: SourcesConfig) -> Self {
+        if sources.user_agent.is_some() {
+            self.user_agent = sources.user_agent;
+        }
+        if sources.request_timeout.is_some() {
+            self.request_timeout = sources.request_timeout;
+        }
+        if sources.request_delay_ms.is_some() {
+            self.request_delay_ms = sources.request_delay_ms;
+        }
+        if sources.default_refresh_ttl_days.is_some() {
+            self.default_refresh_ttl_days = sources.default_refresh_ttl_days;
+        }
+        self.scrapers.extend(sources.scrapers);
+        self.via.extend(sources.via);
+        if sources.via_mode != ViaMode::default() {
+            self.via_mode = sources.via_mode;
+        }
+        if self.overrides.is_empty() {
+            return self;
+        }
+        match self.clone().apply_overrides() {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Not applying overrides over database settings: {}", e);
+                self
+            }
+        }
+    }
+
     /// Get the base directory for resolving relative paths.
     /// Returns the config file's parent directory if available, otherwise None.
     pub fn base_dir(&self) -> Option<PathBuf> {
@@ -289,6 +505,10 @@ impl Config {
         if let Some(ref broker) = self.broker_url {
             settings.broker_url = Some(broker.clone());
         }
+        if let Some(ref token) = self.sync.token {
+            settings.sync_token = Some(token.clone());
+        }
+        self.pool.apply(&mut settings.db_pool);
     }
 
     /// Get the effective refresh TTL in days for a scraper.
@@ -355,6 +575,23 @@ impl Config {
     }
 }
 
+/// Merge `overlay` into `base`: objects key by key, anything else replaced.
+fn merge_json(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,6 +609,9 @@ mod tests {
             rate_limit_backend: None,
             broker_url: None,
             no_tls: false,
+            sync_token: None,
+            read_only: false,
+            db_pool: Default::default(),
         }
     }
 
@@ -445,6 +685,88 @@ mod tests {
         assert!(json.contains("sqlite:///absolute/path/to/db"));
     }
 
+    #[test]
+    fn with_profile_overlays_settings() {
+        let config: Config = toml::from_str(
+            r#"
+            data_dir = "./data"
+            request_delay_ms = 2000
+            user_agent = "foia"
+
+            [profiles.dev]
+            target = "./dev-data"
+            request_delay_ms = 0
+
+            [profiles.dev.llm]
+            model = "llama3.2"
+            "#,
+        )
+        .unwrap();
+
+        let dev = config.clone().with_profile("dev").unwrap();
+        assert_eq!(dev.profile.as_deref(), Some("dev"));
+        assert_eq!(dev.data_dir.as_deref(), Some("./dev-data"));
+        assert_eq!(dev.request_delay_ms, Some(0));
+        assert_eq!(dev.user_agent.as_deref(), Some("foia"));
+        assert_eq!(dev.llm.model(), "llama3.2");
+
+        let err = config.with_profile("production").unwrap_err();
+        assert!(err.contains("defined: dev"));
+    }
+
+    #[test]
+    fn with_overrides_wins_over_profile_and_database() {
+        let config: Config = toml::from_str(
+            r#"
+            request_delay_ms = 2000
+            user_agent = "foia"
+
+            [scrapers.fbi]
+            base_url = "https://vault.fbi.gov"
+
+            [profiles.dev]
+            request_delay_ms = 500
+            "#,
+        )
+        .unwrap();
+        let overrides = vec![
+            Override::from_env_var("FOIACQUIRE__REQUEST_DELAY_MS", "100").unwrap(),
+            Override::from_env_var("FOIACQUIRE__USER_AGENT", "123").unwrap(),
+            Override::parse_flag("request_delay_ms=0").unwrap(),
+            Override::parse_flag("scrapers.fbi.request_delay_ms=250").unwrap(),
+            Override::parse_flag("llm.model=llama3.2").unwrap(),
+            Override::parse_flag("read_only=true").unwrap(),
+        ];
+
+        let config = config
+            .with_profile("dev")
+            .unwrap()
+            .with_overrides(overrides)
+            .unwrap();
+        assert_eq!(config.request_delay_ms, Some(0));
+        assert_eq!(config.user_agent.as_deref(), Some("123"));
+        assert_eq!(config.scrapers["fbi"].request_delay_ms, Some(250));
+        assert_eq!(config.llm.model(), "llama3.2");
+        assert_eq!(config.profile.as_deref(), Some("dev"));
+
+        let fbi = ScraperConfig {
+            request_delay_ms: Some(5000),
+            ..Default::default()
+        };
+        let sources = SourcesConfig {
+            request_delay_ms: Some(5000),
+            scrapers: HashMap::from([("fbi".to_string(), fbi)]),
+            ..SourcesConfig::default()
+        };
+        let config = config.with_sources(sources);
+        assert_eq!(config.request_delay_ms, Some(0));
+        assert_eq!(config.scrapers["fbi"].request_delay_ms, Some(250));
+
+        let bad = vec![Override::parse_flag("request_timeout=soon").unwrap()];
+        let err = Config::default().with_overrides(bad).unwrap_err();
+        assert!(err.contains("--set request_timeout"));
+    }
+
     #[test]
     fn apply_no_database_leaves_defaults() {
         let config = Config::default();
@@ -0,0 +1,197 @@
+//! Overrides of single settings from the environment and the command line.
+//!
+//! Any config setting can be set with a `FOIACQUIRE__` environment variable
+//! or a `--set key=value` flag. Keys are dotted paths into the config
+//! (`request_delay_ms`, `llm.model`, `privacy.obfuscation`); in variable
+//! names the dots are written as double underscores and the key is
+//! lowercased (`FOIACQUIRE__LLM__MODEL`). A value is read as JSON when it parses as
+//! JSON and the setting accepts it, otherwise as a string.
+//!
+//! Precedence, highest first: `--set` flags, environment variables, source
+//! settings stored in the database, the config file and its profile,
+//! defaults.
+
+use serde_json::{Map, Value};
+
+/// Prefix of override environment variables.
+pub const ENV_PREFIX: &str = "FOIACQUIRE__";
+
+/// Settings that aren't in the config file. They are applied to
+/// [`Settings`](super::Settings) after it is built from the config.
+pub const SETTINGS_KEYS: &[&str] = &["database_url", "documents_dir", "no_tls", "read_only"];
+
+/// Where an override was given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideSource {
+    Env,
+    Flag,
+}
+
+/// One setting overridden from the environment or the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    /// Dotted path of the setting.
+    pub key: String,
+    /// Value as given.
+    pub value: String,
+    pub source: OverrideSource,
+}
+
+impl Override {
+    /// Parse a `--set key=value` flag.
+    pub fn parse_flag(arg: &str) -> Result<Self, String> {
+        let Some((key, value)) = arg.split_once('=') else {
+            return Err(format!("--set {}: expected KEY=VALUE", arg));
+        };
+        let key = normalize_key(key.trim())
+            .ok_or_else(|| format!("--set {}: invalid key '{}'", arg, key.trim()))?;
+        Ok(Self {
+            key,
+            value: value.to_string(),
+            source: OverrideSource::Flag,
+        })
+    }
+
+    /// Read an override from an environment variable, if it is one.
+    pub fn from_env_var(name: &str, value: &str) -> Option<Self> {
+        let path = name.strip_prefix(ENV_PREFIX)?;
+        let key = normalize_key(&path.to_lowercase().replace("__", "."))?;
+        Some(Self {
+            key,
+            value: value.to_string(),
+            source: OverrideSource::Env,
+        })
+    }
+
+    /// Overrides set in the environment, sorted by key.
+    pub fn from_env() -> Vec<Self> {
+        let mut overrides: Vec<Self> = std::env::vars()
+            .filter_map(|(name, value)| {
+                let parsed = Self::from_env_var(&name, &value);
+                if parsed.is_none() && name.starts_with(ENV_PREFIX) {
+                    tracing::warn!("Ignoring {}: not a setting path", name);
+                }
+                parsed
+            })
+            .collect();
+        overrides.sort_by(|a, b| a.key.cmp(&b.key));
+        overrides
+    }
+
+    /// Environment overrides followed by `--set` flags, so that later ones
+    /// win.
+    pub fn collect(flags: &[String]) -> Result<Vec<Self>, String> {
+        let mut overrides = Self::from_env();
+        for flag in flags {
+            overrides.push(Self::parse_flag(flag)?);
+        }
+        Ok(overrides)
+    }
+
+    /// Whether the override is of a setting that isn't in the config file.
+    pub fn is_settings_key(&self) -> bool {
+        SETTINGS_KEYS.contains(&self.key.as_str())
+    }
+
+    /// How the override was given, for messages.
+    pub fn origin(&self) -> String {
+        match self.source {
+            OverrideSource::Env => {
+                format!(
+                    "{}{}",
+                    ENV_PREFIX,
+                    self.key.to_uppercase().replace('.', "__")
+                )
+            }
+            OverrideSource::Flag => format!("--set {}", self.key),
+        }
+    }
+
+    /// The value as JSON, or as a string if it isn't JSON or `as_string`.
+    pub fn json_value(&self, as_string: bool) -> Value {
+        if !as_string {
+            if let Ok(value) = serde_json::from_str(&self.value) {
+                return value;
+            }
+        }
+        Value::String(self.value.clone())
+    }
+
+    /// The override as a table to lay over the serialized config.
+    pub fn overlay(&self, as_string: bool) -> Map<String, Value> {
+        let mut value = self.json_value(as_string);
+        for segment in self.key.rsplit('.') {
+            let mut table = Map::new();
+            table.insert(segment.to_string(), value);
+            value = Value::Object(table);
+        }
+        match value {
+            Value::Object(table) => table,
+            _ => Map::new(),
+        }
+    }
+
+    /// The value as a boolean (`true`/`false`, `1`/`0`, `yes`/`no`).
+    pub fn bool_value(&self) -> Result<bool, String> {
+        match self.value.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err(format!("{}: expected true or false", self.origin())),
+        }
+    }
+}
+
+/// Check a dotted key, and spell the `target` alias as `data_dir`.
+fn normalize_key(key: &str) -> Option<String> {
+    let valid = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+    };
+    if !key.split('.').all(valid) {
+        return None;
+    }
+    Some(match key.strip_prefix("target") {
+        Some(rest) if rest.is_empty() || rest.starts_with('.') => format!("data_dir{}", rest),
+        _ => key.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_overrides() {
+        let flag = Override::parse_flag("llm.model=llama3.2:3b").unwrap();
+        assert_eq!(flag.key, "llm.model");
+        assert_eq!(flag.value, "llama3.2:3b");
+        assert_eq!(flag.origin(), "--set llm.model");
+        assert!(Override::parse_flag("llm.model").is_err());
+        assert!(Override::parse_flag("llm..model=x").is_err());
+        assert_eq!(Override::parse_flag("target=/srv").unwrap().key, "data_dir");
+
+        let env = Override::from_env_var("FOIACQUIRE__PRIVACY__OBFUSCATION", "false").unwrap();
+        assert_eq!(env.key, "privacy.obfuscation");
+        assert_eq!(env.origin(), "FOIACQUIRE__PRIVACY__OBFUSCATION");
+        assert!(!env.bool_value().unwrap());
+        assert!(Override::from_env_var("FOIA_PROFILE", "dev").is_none());
+    }
+
+    #[test]
+    fn test_override_overlay() {
+        let delay = Override::parse_flag("scrapers.fbi.request_delay_ms=0").unwrap();
+        assert_eq!(
+            Value::Object(delay.overlay(false)),
+            json!({"scrapers": {"fbi": {"request_delay_ms": 0}}})
+        );
+        assert_eq!(
+            Value::Object(delay.overlay(true)),
+            json!({"scrapers": {"fbi": {"request_delay_ms": "0"}}})
+        );
+        let agent = Override::parse_flag("user_agent=foia research").unwrap();
+        assert_eq!(agent.json_value(false), json!("foia research"));
+    }
+}
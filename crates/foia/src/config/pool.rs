@@ -0,0 +1,57 @@
+//! Database connection pool configuration.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::repository::PoolOptions;
+
+/// Connection pool tuning for deployments running crawlers, OCR workers
+/// and the web server against one database at the same time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, prefer::FromValue)]
+pub struct PoolConfig {
+    /// Maximum PostgreSQL connections per process (default 10).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub max_connections: Option<usize>,
+    /// Milliseconds a SQLite connection waits for a lock before giving up
+    /// (default 5000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub busy_timeout_ms: Option<u64>,
+    /// WAL size in pages that triggers an automatic checkpoint (default 1000).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub wal_autocheckpoint: Option<u32>,
+    /// Seconds between WAL truncations in long-running processes
+    /// (default 300, 0 disables).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub checkpoint_interval_secs: Option<u64>,
+}
+
+impl PoolConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        self.max_connections.is_none()
+            && self.busy_timeout_ms.is_none()
+            && self.wal_autocheckpoint.is_none()
+            && self.checkpoint_interval_secs.is_none()
+    }
+
+    /// Override the fields of `options` that are set here.
+    pub fn apply(&self, options: &mut PoolOptions) {
+        if let Some(max) = self.max_connections {
+            options.max_connections = max.max(1);
+        }
+        if let Some(ms) = self.busy_timeout_ms {
+            options.busy_timeout = Duration::from_millis(ms);
+        }
+        if let Some(pages) = self.wal_autocheckpoint {
+            options.wal_autocheckpoint = pages;
+        }
+        if let Some(secs) = self.checkpoint_interval_secs {
+            options.checkpoint_interval = (secs > 0).then(|| Duration::from_secs(secs));
+        }
+    }
+}
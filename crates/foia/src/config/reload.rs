@@ -0,0 +1,192 @@
+//! Config hot-reload for long-running processes.
+//!
+//! [`ConfigReloader`] notices when the config file's mtime or the latest
+//! `configuration_history` hash moves, and re-reads the config. Callers
+//! invoke [`ConfigReloader::reload`] at run boundaries so a run never sees
+//! settings change underneath it.
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use super::{Config, ScraperConfig};
+use crate::repository::DieselConfigHistoryRepository;
+
+/// Scraper sources that differ between two configs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScraperDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ScraperDiff {
+    /// Compare two scraper maps by source ID.
+    pub fn between(
+        old: &HashMap<String, ScraperConfig>,
+        new: &HashMap<String, ScraperConfig>,
+    ) -> Self {
+        let mut diff = Self::default();
+        for (id, config) in new {
+            match old.get(id) {
+                None => diff.added.push(id.clone()),
+                Some(previous) if previous != config => diff.changed.push(id.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|id| !new.contains_key(*id))
+            .cloned()
+            .collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// One-line summary, e.g. "added: a; changed: b, c".
+    pub fn summary(&self) -> String {
+        [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("changed", &self.changed),
+        ]
+        .iter()
+        .filter(|(_, ids)| !ids.is_empty())
+        .map(|(label, ids)| format!("{}: {}", label, ids.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ")
+    }
+}
+
+/// A config that was reloaded because its source changed.
+#[derive(Debug, Clone)]
+pub struct ConfigReload {
+    pub config: Config,
+    pub scrapers: ScraperDiff,
+}
+
+/// Tracks the active config and reloads it when its sources change.
+pub struct ConfigReloader {
+    config: Config,
+    modified: Option<SystemTime>,
+    history_hash: Option<String>,
+    config_history: DieselConfigHistoryRepository,
+}
+
+impl ConfigReloader {
+    pub async fn new(config: Config, config_history: DieselConfigHistoryRepository) -> Self {
+        let modified = file_modified(&config);
+        let history_hash = config_history.get_latest_hash().await.ok().flatten();
+        Self {
+            config,
+            modified,
+            history_hash,
+            config_history,
+        }
+    }
+
+    /// The currently active config.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Re-read the config if the file or config history changed.
+    ///
+    /// Returns `None` when nothing changed. A config file that fails to
+    /// parse is logged and ignored, keeping the previous config active.
+    pub async fn reload(&mut self) -> Option<ConfigReload> {
+        let modified = file_modified(&self.config);
+        let history_hash = self.config_history.get_latest_hash().await.ok().flatten();
+        if modified == self.modified && history_hash == self.history_hash {
+            return None;
+        }
+        self.modified = modified;
+        self.history_hash = history_hash;
+
+        let config = match self.config.source_path.clone() {
+            Some(path) => match Config::load_from_path(&path).await {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::warn!("Keeping previous config, {}: {}", path.display(), e);
+                    return None;
+                }
+            },
+            None => Config::load().await,
+        };
+        let config = match self.config.profile.clone() {
+            Some(profile) => match config.with_profile(&profile) {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::warn!("Keeping previous config: {}", e);
+                    return None;
+                }
+            },
+            None => config,
+        };
+        let config = match config.with_overrides(self.config.overrides.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Keeping previous config: {}", e);
+                return None;
+            }
+        };
+        if config.hash() == self.config.hash() {
+            return None;
+        }
+
+        let scrapers = ScraperDiff::between(&self.config.scrapers, &config.scrapers);
+        self.config = config.clone();
+        Some(ConfigReload { config, scrapers })
+    }
+}
+
+fn file_modified(config: &Config) -> Option<SystemTime> {
+    config
+        .source_path
+        .as_ref()?
+        .metadata()
+        .ok()?
+        .modified()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scraper(base_url: &str) -> ScraperConfig {
+        ScraperConfig {
+            base_url: Some(base_url.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_scraper_diff() {
+        let old = HashMap::from([
+            ("kept".to_string(), scraper("https://a.example")),
+            ("edited".to_string(), scraper("https://b.example")),
+            ("dropped".to_string(), scraper("https://c.example")),
+        ]);
+        let new = HashMap::from([
+            ("kept".to_string(), scraper("https://a.example")),
+            ("edited".to_string(), scraper("https://b2.example")),
+            ("fresh".to_string(), scraper("https://d.example")),
+        ]);
+
+        let diff = ScraperDiff::between(&old, &new);
+        assert_eq!(diff.added, vec!["fresh"]);
+        assert_eq!(diff.removed, vec!["dropped"]);
+        assert_eq!(diff.changed, vec!["edited"]);
+        assert_eq!(
+            diff.summary(),
+            "added: fresh; removed: dropped; changed: edited"
+        );
+        assert!(ScraperDiff::between(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+//! Search backend configuration.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Index directory, relative to the data directory, when none is set.
+const DEFAULT_INDEX_DIR: &str = "search-index";
+
+/// Name of the index on a search service when none is set.
+const DEFAULT_INDEX_NAME: &str = "foia-documents";
+
+/// Engine that answers document searches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchBackend {
+    /// Query the database directly (full-text search on PostgreSQL, LIKE
+    /// matching on SQLite).
+    #[default]
+    Database,
+    /// An embedded tantivy index kept next to the database, with relevance
+    /// ranking and faceted counts.
+    Tantivy,
+    /// An index on a Meilisearch server.
+    Meilisearch,
+    /// An index on an OpenSearch (or Elasticsearch-compatible) cluster.
+    #[serde(rename = "opensearch")]
+    OpenSearch,
+}
+
+impl SearchBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Database => "database",
+            Self::Tantivy => "tantivy",
+            Self::Meilisearch => "meilisearch",
+            Self::OpenSearch => "opensearch",
+        }
+    }
+
+    /// Whether the index lives on a separate search service.
+    pub fn is_external(&self) -> bool {
+        matches!(self, Self::Meilisearch | Self::OpenSearch)
+    }
+}
+
+impl prefer::FromValue for SearchBackend {
+    fn from_value(value: &prefer::ConfigValue) -> prefer::Result<Self> {
+        match value.as_str() {
+            Some("database") => Ok(SearchBackend::Database),
+            Some("tantivy") => Ok(SearchBackend::Tantivy),
+            Some("meilisearch") => Ok(SearchBackend::Meilisearch),
+            Some("opensearch") => Ok(SearchBackend::OpenSearch),
+            Some(other) => Err(prefer::Error::ConversionError {
+                key: String::new(),
+                type_name: "SearchBackend".to_string(),
+                source: format!("unknown search backend: {}", other).into(),
+            }),
+            None => Err(prefer::Error::ConversionError {
+                key: String::new(),
+                type_name: "SearchBackend".to_string(),
+                source: "expected string".into(),
+            }),
+        }
+    }
+}
+
+/// Which engine answers searches, and where its index lives.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct SearchConfig {
+    /// `database` (default), `tantivy`, `meilisearch` or `opensearch`.
+    /// Build the index with `foia search-index rebuild` before switching
+    /// to it.
+    #[serde(default, skip_serializing_if = "is_backend_default")]
+    #[prefer(default)]
+    pub backend: SearchBackend,
+    /// Directory of the tantivy index (default: `search-index` in the data
+    /// directory). Relative paths are resolved against the data directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub index_dir: Option<String>,
+    /// Seconds between index updates while the web server runs
+    /// (default: 60).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub refresh_interval: Option<u64>,
+    /// Base URL of the Meilisearch server or OpenSearch cluster, e.g.
+    /// `http://localhost:7700`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub url: Option<String>,
+    /// Index on the search service (default: `foia-documents`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub index: Option<String>,
+    /// Meilisearch API key. May be a `secret://name` reference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub api_key: Option<String>,
+    /// OpenSearch user for basic authentication.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub username: Option<String>,
+    /// OpenSearch password. May be a `secret://name` reference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub password: Option<String>,
+}
+
+fn is_backend_default(backend: &SearchBackend) -> bool {
+    *backend == SearchBackend::default()
+}
+
+impl SearchConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Directory of the tantivy index.
+    pub fn index_dir(&self, data_dir: &Path) -> PathBuf {
+        let dir = Path::new(self.index_dir.as_deref().unwrap_or(DEFAULT_INDEX_DIR));
+        if dir.is_absolute() {
+            dir.to_path_buf()
+        } else {
+            data_dir.join(dir)
+        }
+    }
+
+    /// Seconds between index updates while the web server runs.
+    pub fn refresh_interval(&self) -> u64 {
+        self.refresh_interval.unwrap_or(60).max(1)
+    }
+
+    /// Name of the index on the search service.
+    pub fn index_name(&self) -> &str {
+        self.index.as_deref().unwrap_or(DEFAULT_INDEX_NAME)
+    }
+}
@@ -0,0 +1,31 @@
+//! Secrets store configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Where `secret://name` references are resolved from.
+///
+/// Environment variables (`FOIA_SECRET_<NAME>`) are always consulted first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct SecretsConfig {
+    /// Path to the encrypted secrets file.
+    /// Relative paths are resolved against the config file directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub file: Option<String>,
+    /// Key file for the secrets file. Defaults to the `encryption` key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub key_file: Option<String>,
+    /// Look up secrets in the OS keychain (service `foia-secrets`).
+    /// Requires a build with the `keychain` feature.
+    #[serde(default)]
+    #[prefer(default)]
+    pub keychain: bool,
+}
+
+impl SecretsConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
@@ -7,7 +7,7 @@ use std::path::PathBuf;
 
 use crate::repository::diesel_context::DieselDbContext;
 use crate::repository::util::is_postgres_url;
-use crate::repository::Repositories;
+use crate::repository::{DbPool, PoolOptions, Repositories};
 
 use super::DEFAULT_DATABASE_FILENAME;
 
@@ -39,6 +39,12 @@ pub struct Settings {
     pub broker_url: Option<String>,
     /// Disable TLS for PostgreSQL connections.
     pub no_tls: bool,
+    /// Token required by the server's sync endpoints (None = sync disabled).
+    pub sync_token: Option<String>,
+    /// Open the database read-only (e.g. serving a replicated copy).
+    pub read_only: bool,
+    /// Connection pool tuning (max connections, busy timeout, WAL checkpoints).
+    pub db_pool: PoolOptions,
 }
 
 impl Default for Settings {
@@ -61,6 +67,9 @@ impl Default for Settings {
             rate_limit_backend: None, // In-memory by default
             broker_url: None,         // Local DB by default
             no_tls: false,
+            sync_token: None,
+            read_only: false,
+            db_pool: PoolOptions::default(),
         }
     }
 }
@@ -200,7 +209,11 @@ impl Settings {
     /// This is the preferred way to get a DieselDbContext from settings.
     /// Returns an error if the database URL is invalid.
     pub fn create_db_context(&self) -> Result<DieselDbContext, diesel::result::Error> {
-        DieselDbContext::from_url(&self.database_url(), self.no_tls)
+        let pool = DbPool::from_url_with_options(&self.database_url(), self.no_tls, &self.db_pool)?;
+        if self.read_only {
+            return Ok(DieselDbContext::with_pool(pool.into_read_only()));
+        }
+        Ok(DieselDbContext::with_pool(pool))
     }
 
     /// Create bundled repositories for all database operations.
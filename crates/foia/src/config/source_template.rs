@@ -0,0 +1,174 @@
+//! Built-in source templates.
+//!
+//! Many agencies publish records through the same hosted portal software,
+//! so their scraper configs differ only by host. A template is a scraper
+//! config with `{base_url}` placeholders, filled in when a source is added.
+
+use serde_json::Value;
+
+use super::ScraperConfig;
+
+/// Placeholder replaced with the new source's base URL.
+const BASE_URL: &str = "{base_url}";
+
+/// A named scraper config parameterized by base URL.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    config: &'static str,
+}
+
+const TEMPLATES: &[SourceTemplate] = &[
+    SourceTemplate {
+        name: "govqa",
+        description: "GovQA public records portal (request archive)",
+        config: r#"{
+            "discovery": {
+                "type": "html_crawl",
+                "base_url": "{base_url}",
+                "start_paths": ["/WEBAPP/_rs/RequestArchive.aspx"],
+                "use_browser": true,
+                "document_links": ["a[href*='RequestArchiveDetails']", "a[href*='DownloadAttachment']"],
+                "document_patterns": ["DownloadAttachment", "\\.pdf$"],
+                "infer_pagination": {"enabled": true}
+            },
+            "fetch": {"use_browser": true},
+            "request_delay_ms": 2000
+        }"#,
+    },
+    SourceTemplate {
+        name: "nextrequest",
+        description: "NextRequest portal (released documents)",
+        config: r#"{
+            "discovery": {
+                "type": "html_crawl",
+                "base_url": "{base_url}",
+                "start_paths": ["/documents", "/requests"],
+                "use_browser": true,
+                "document_links": ["a[href*='/documents/']", "a[href*='/requests/']"],
+                "document_patterns": ["/documents/\\d+", "\\.pdf$"],
+                "infer_pagination": {"enabled": true}
+            },
+            "fetch": {"use_browser": true},
+            "request_delay_ms": 1500
+        }"#,
+    },
+    SourceTemplate {
+        name: "documentcloud-embed",
+        description: "Site embedding or linking DocumentCloud documents",
+        config: r#"{
+            "discovery": {
+                "type": "html_crawl",
+                "base_url": "{base_url}",
+                "start_paths": ["/"],
+                "max_depth": 3,
+                "document_links": ["a[href*='documentcloud.org/documents/']", "a[href$='.pdf']"],
+                "document_patterns": ["documentcloud\\.org/documents/", "\\.pdf$"]
+            }
+        }"#,
+    },
+    SourceTemplate {
+        name: "reading-room",
+        description: "Static FOIA reading room listing PDF links",
+        config: r#"{
+            "discovery": {
+                "type": "html_crawl",
+                "base_url": "{base_url}",
+                "start_paths": ["/"],
+                "max_depth": 4,
+                "document_links": ["a[href$='.pdf']", "a[href*='/files/']"],
+                "document_patterns": ["\\.pdf$"],
+                "infer_pagination": {"enabled": true}
+            }
+        }"#,
+    },
+];
+
+impl SourceTemplate {
+    /// All built-in templates.
+    pub fn all() -> &'static [SourceTemplate] {
+        TEMPLATES
+    }
+
+    /// Look up a template by name.
+    pub fn find(name: &str) -> Option<&'static SourceTemplate> {
+        TEMPLATES.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Build a scraper config for a source at `base_url`.
+    pub fn instantiate(&self, base_url: &str) -> Result<ScraperConfig, serde_json::Error> {
+        let mut value: Value = serde_json::from_str(self.config)?;
+        replace_in_strings(&mut value, BASE_URL, base_url.trim_end_matches('/'));
+        serde_json::from_value(value)
+    }
+}
+
+impl ScraperConfig {
+    /// Copy of this config pointed at a different site: every occurrence of
+    /// `old_base` in string values becomes `new_base`.
+    pub fn rebased(&self, old_base: &str, new_base: &str) -> Result<Self, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        replace_in_strings(
+            &mut value,
+            old_base.trim_end_matches('/'),
+            new_base.trim_end_matches('/'),
+        );
+        serde_json::from_value(value)
+    }
+}
+
+fn replace_in_strings(value: &mut Value, from: &str, to: &str) {
+    if from.is_empty() {
+        return;
+    }
+    match value {
+        Value::String(s) if s.contains(from) => *s = s.replace(from, to),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| replace_in_strings(item, from, to)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|item| replace_in_strings(item, from, to)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_instantiate() {
+        for template in SourceTemplate::all() {
+            let config = template
+                .instantiate("https://example.govqa.us/")
+                .unwrap_or_else(|e| panic!("{}: {}", template.name, e));
+            assert_eq!(
+                config.discovery.base_url.as_deref(),
+                Some("https://example.govqa.us")
+            );
+        }
+        assert!(SourceTemplate::find("GovQA").is_some());
+        assert!(SourceTemplate::find("missing").is_none());
+    }
+
+    #[test]
+    fn test_rebased() {
+        let config = SourceTemplate::find("govqa")
+            .unwrap()
+            .instantiate("https://springfield.govqa.us")
+            .unwrap();
+        let clone = config
+            .rebased(
+                "https://springfield.govqa.us",
+                "https://shelbyville.govqa.us/",
+            )
+            .unwrap();
+        assert_eq!(
+            clone.discovery.base_url.as_deref(),
+            Some("https://shelbyville.govqa.us")
+        );
+        assert_eq!(clone.discovery.start_paths, config.discovery.start_paths);
+    }
+}
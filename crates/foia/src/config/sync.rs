@@ -0,0 +1,51 @@
+//! Instance-to-instance sync configuration.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for `foia sync` and the server's `/api/sync` endpoints.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, prefer::FromValue)]
+pub struct SyncConfig {
+    /// Bearer token remote instances must present to use this server's sync
+    /// endpoints. The endpoints are disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub token: Option<String>,
+    /// Named remotes usable as `foia sync push <name>`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[prefer(default)]
+    pub remotes: HashMap<String, SyncRemote>,
+}
+
+/// A remote foia instance to sync with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, prefer::FromValue)]
+pub struct SyncRemote {
+    /// Base URL of the remote server (e.g. "https://vps.example.org:3030").
+    pub url: String,
+    /// Token matching the remote's `sync.token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub token: Option<String>,
+}
+
+impl SyncConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        self.token.is_none() && self.remotes.is_empty()
+    }
+
+    /// Resolve a remote by name, or treat the argument as a URL.
+    pub fn resolve_remote(&self, name_or_url: &str) -> Option<SyncRemote> {
+        if let Some(remote) = self.remotes.get(name_or_url) {
+            return Some(remote.clone());
+        }
+        if name_or_url.starts_with("http://") || name_or_url.starts_with("https://") {
+            return Some(SyncRemote {
+                url: name_or_url.to_string(),
+                token: None,
+            });
+        }
+        None
+    }
+}
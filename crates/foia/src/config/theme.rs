@@ -0,0 +1,158 @@
+//! Branding of the public web interface.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Site name, logo, colors, footer, about page and default language of
+/// the web UI.
+///
+/// File paths are relative to the data directory unless absolute.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct ThemeConfig {
+    /// Shown in the header and page titles instead of "foia".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub site_name: Option<String>,
+    /// Logo image: an `http(s)://` URL, or a file path served at
+    /// `/theme/logo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub logo: Option<String>,
+    /// Stylesheet variables to override, without the leading `--`
+    /// (e.g. `link = "#b00020"`). See `:root` in the built-in stylesheet.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[prefer(default)]
+    pub colors: HashMap<String, String>,
+    /// Plain-text footer on every page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub footer: Option<String>,
+    /// Markdown file rendered at `/about` and linked from the header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub about: Option<String>,
+    /// Interface language (`en`, `es` or `fr`) for visitors whose browser
+    /// asks for none of those.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub locale: Option<String>,
+}
+
+impl ThemeConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the logo is a remote URL rather than a local file.
+    pub fn logo_is_url(&self) -> bool {
+        self.logo
+            .as_deref()
+            .is_some_and(|l| l.starts_with("http://") || l.starts_with("https://"))
+    }
+
+    /// Local logo file, if the logo is one.
+    pub fn logo_path(&self, data_dir: &Path) -> Option<PathBuf> {
+        if self.logo_is_url() {
+            return None;
+        }
+        self.logo.as_deref().map(|l| resolve(data_dir, l))
+    }
+
+    /// About page markdown file, if configured.
+    pub fn about_path(&self, data_dir: &Path) -> Option<PathBuf> {
+        self.about.as_deref().map(|a| resolve(data_dir, a))
+    }
+
+    /// Color overrides as CSS declarations, sorted by name. Entries whose
+    /// name or value could break out of the declaration are dropped.
+    pub fn css_variables(&self) -> String {
+        let mut colors: Vec<(&String, &String)> = self
+            .colors
+            .iter()
+            .filter(|(name, value)| is_css_name(name) && is_css_value(value))
+            .collect();
+        colors.sort();
+        colors
+            .into_iter()
+            .map(|(name, value)| format!("--{}: {};", name, value.trim()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn resolve(data_dir: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        data_dir.join(path)
+    }
+}
+
+fn is_css_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_css_value(value: &str) -> bool {
+    !value.trim().is_empty()
+        && !value
+            .chars()
+            .any(|c| matches!(c, ';' | '{' | '}' | '<' | '>' | '"' | '\'' | '\\'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_variables_drop_unsafe_entries() {
+        let theme = ThemeConfig {
+            colors: HashMap::from([
+                ("link".to_string(), "#b00020".to_string()),
+                ("bg".to_string(), " rgb(250, 250, 250) ".to_string()),
+                (
+                    "text".to_string(),
+                    "red; } body { display: none".to_string(),
+                ),
+                ("bad name".to_string(), "#000".to_string()),
+                ("border".to_string(), "</style>".to_string()),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            theme.css_variables(),
+            "--bg: rgb(250, 250, 250); --link: #b00020;"
+        );
+    }
+
+    #[test]
+    fn test_logo_url_or_path() {
+        let data_dir = Path::new("/data");
+        let remote = ThemeConfig {
+            logo: Some("https://example.org/logo.png".to_string()),
+            ..Default::default()
+        };
+        assert!(remote.logo_is_url());
+        assert_eq!(remote.logo_path(data_dir), None);
+
+        let local = ThemeConfig {
+            logo: Some("theme/logo.svg".to_string()),
+            about: Some("/srv/about.md".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            local.logo_path(data_dir),
+            Some(PathBuf::from("/data/theme/logo.svg"))
+        );
+        assert_eq!(
+            local.about_path(data_dir),
+            Some(PathBuf::from("/srv/about.md"))
+        );
+    }
+}
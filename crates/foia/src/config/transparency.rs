@@ -0,0 +1,92 @@
+//! Transparency mode: identifying the crawler on every request.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Header naming the crawl policy page.
+pub const CRAWL_POLICY_HEADER: &str = "X-Crawl-Policy";
+
+/// Contact details sent with every request, for agencies that only admit
+/// identifiable (e.g. academic) crawlers. Off while `contact` is unset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, prefer::FromValue)]
+pub struct TransparencyConfig {
+    /// Email address (or URL) of whoever runs the crawl, sent as
+    /// `X-Contact`, and as `From` when it is an email address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub contact: Option<String>,
+    /// Page describing the crawl and how to opt out, sent as
+    /// `X-Crawl-Policy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[prefer(default)]
+    pub policy_url: Option<String>,
+}
+
+impl TransparencyConfig {
+    /// Check if this is the default (empty) config.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether requests are identified.
+    pub fn is_enabled(&self) -> bool {
+        self.contact
+            .as_deref()
+            .is_some_and(|c| !c.trim().is_empty())
+    }
+
+    /// Headers identifying the crawler; empty when transparency mode is off.
+    pub fn headers(&self) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        if !self.is_enabled() {
+            return headers;
+        }
+        let contact = self.contact.as_deref().unwrap_or_default().trim();
+        let email = contact.trim_start_matches("mailto:");
+        if email.contains('@') && !email.contains('/') {
+            headers.insert("From".to_string(), email.to_string());
+        }
+        headers.insert("X-Contact".to_string(), contact.to_string());
+        if let Some(url) = self.policy_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            headers.insert(CRAWL_POLICY_HEADER.to_string(), url.trim().to_string());
+        }
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers() {
+        assert!(TransparencyConfig::default().headers().is_empty());
+        // A policy page alone doesn't identify anyone
+        let config = TransparencyConfig {
+            contact: None,
+            policy_url: Some("https://lab.example.edu/crawl".to_string()),
+        };
+        assert!(config.headers().is_empty());
+
+        let config = TransparencyConfig {
+            contact: Some("crawler@lab.example.edu".to_string()),
+            policy_url: Some("https://lab.example.edu/crawl".to_string()),
+        };
+        let headers = config.headers();
+        assert_eq!(headers["From"], "crawler@lab.example.edu");
+        assert_eq!(headers["X-Contact"], "crawler@lab.example.edu");
+        assert_eq!(
+            headers[CRAWL_POLICY_HEADER],
+            "https://lab.example.edu/crawl"
+        );
+
+        let config = TransparencyConfig {
+            contact: Some("https://lab.example.edu/contact".to_string()),
+            policy_url: None,
+        };
+        let headers = config.headers();
+        assert!(!headers.contains_key("From"));
+        assert_eq!(headers["X-Contact"], "https://lab.example.edu/contact");
+    }
+}
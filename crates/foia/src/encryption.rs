@@ -0,0 +1,224 @@
+//! Optional at-rest encryption for stored document files.
+//!
+//! Encrypted files are laid out as `MAGIC || nonce || ciphertext`, sealed
+//! with XChaCha20-Poly1305 under a single 32-byte content key. Each file gets
+//! a random 24-byte nonce, so one key can safely cover the whole documents
+//! directory.
+//!
+//! The key is loaded once at startup (from a key file or the OS keychain)
+//! and installed process-wide with [`install_key`]. The storage helpers in
+//! [`crate::storage`] then encrypt on write and decrypt on read, so callers
+//! that only know the documents directory need no changes.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use crate::config::Config;
+
+/// Header identifying an encrypted document file.
+pub const MAGIC: &[u8; 8] = b"FOIAENC1";
+
+/// XChaCha20 nonce length.
+const NONCE_LEN: usize = 24;
+
+/// Keychain service name for stored keys.
+#[cfg_attr(not(feature = "keychain"), allow(dead_code))]
+const KEYCHAIN_SERVICE: &str = "foia";
+
+/// Key used by the storage helpers for this process.
+static CONTENT_KEY: OnceLock<ContentKey> = OnceLock::new();
+
+/// Errors from loading keys or decrypting document content.
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("failed to read encryption key {path}: {source}")]
+    KeyRead {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid encryption key: expected 32-byte hex-encoded key")]
+    InvalidKey,
+    #[error("keychain error: {0}")]
+    Keychain(String),
+    #[error("{0} is encrypted but no encryption key is configured")]
+    NoKey(String),
+    #[error("decryption failed: wrong key or corrupted file")]
+    Decrypt,
+}
+
+/// A 32-byte XChaCha20-Poly1305 key for document content.
+#[derive(Clone)]
+pub struct ContentKey([u8; 32]);
+
+impl std::fmt::Debug for ContentKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ContentKey(..)")
+    }
+}
+
+impl ContentKey {
+    /// Generate a new random key.
+    pub fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    /// Parse a hex-encoded 32-byte key.
+    pub fn parse(hex_key: &str) -> Result<Self, EncryptionError> {
+        let bytes = hex::decode(hex_key).map_err(|_| EncryptionError::InvalidKey)?;
+        let key: [u8; 32] = bytes.try_into().map_err(|_| EncryptionError::InvalidKey)?;
+        Ok(Self(key))
+    }
+
+    /// Hex encoding, as stored in key files and keychain entries.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Load a key from a file containing the hex-encoded key.
+    pub fn load_file(path: &Path) -> Result<Self, EncryptionError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|source| EncryptionError::KeyRead {
+                path: path.display().to_string(),
+                source,
+            })?;
+        Self::parse(contents.trim())
+    }
+
+    /// Load a key from an OS keychain entry.
+    #[cfg(feature = "keychain")]
+    pub fn load_keychain(entry: &str) -> Result<Self, EncryptionError> {
+        let secret = keyring::Entry::new(KEYCHAIN_SERVICE, entry)
+            .and_then(|e| e.get_password())
+            .map_err(|e| EncryptionError::Keychain(e.to_string()))?;
+        Self::parse(secret.trim())
+    }
+
+    /// Load a key from an OS keychain entry.
+    #[cfg(not(feature = "keychain"))]
+    pub fn load_keychain(_entry: &str) -> Result<Self, EncryptionError> {
+        Err(keychain_unsupported())
+    }
+
+    /// Store this key in an OS keychain entry.
+    #[cfg(feature = "keychain")]
+    pub fn store_keychain(&self, entry: &str) -> Result<(), EncryptionError> {
+        keyring::Entry::new(KEYCHAIN_SERVICE, entry)
+            .and_then(|e| e.set_password(&self.to_hex()))
+            .map_err(|e| EncryptionError::Keychain(e.to_string()))
+    }
+
+    /// Store this key in an OS keychain entry.
+    #[cfg(not(feature = "keychain"))]
+    pub fn store_keychain(&self, _entry: &str) -> Result<(), EncryptionError> {
+        Err(keychain_unsupported())
+    }
+
+    /// Load the key configured under `encryption`, if any.
+    ///
+    /// A key file takes precedence over a keychain entry.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, EncryptionError> {
+        if let Some(ref key_file) = config.encryption.key_file {
+            let base_dir = config
+                .base_dir()
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+            return Self::load_file(&config.resolve_path(key_file, &base_dir)).map(Some);
+        }
+        if let Some(ref entry) = config.encryption.keychain {
+            return Self::load_keychain(entry).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.0.into())
+    }
+
+    /// Encrypt `plaintext` into the on-disk format.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()
+            .encrypt(&nonce, plaintext)
+            .expect("XChaCha20-Poly1305 encryption cannot fail for in-memory buffers");
+
+        let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        out
+    }
+
+    /// Decrypt data in the on-disk format.
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        if !is_encrypted(data) || data.len() < MAGIC.len() + NONCE_LEN {
+            return Err(EncryptionError::Decrypt);
+        }
+        let (nonce, ciphertext) = data[MAGIC.len()..].split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| EncryptionError::Decrypt)
+    }
+}
+
+#[cfg(not(feature = "keychain"))]
+fn keychain_unsupported() -> EncryptionError {
+    EncryptionError::Keychain(
+        "built without keychain support (enable the `keychain` feature)".into(),
+    )
+}
+
+/// Install the process-wide content key. New document files are encrypted
+/// from then on. Returns `false` if a key was already installed.
+pub fn install_key(key: ContentKey) -> bool {
+    CONTENT_KEY.set(key).is_ok()
+}
+
+/// The installed content key, if encryption is enabled.
+pub fn content_key() -> Option<&'static ContentKey> {
+    CONTENT_KEY.get()
+}
+
+/// Check whether data is in the encrypted on-disk format.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = ContentKey::generate();
+        let sealed = key.encrypt(b"pre-publication draft");
+
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed
+            .windows(b"draft".len())
+            .any(|w| w == b"draft".as_slice()));
+        assert_eq!(key.decrypt(&sealed).unwrap(), b"pre-publication draft");
+    }
+
+    #[test]
+    fn test_wrong_key_or_tampering_fails() {
+        let key = ContentKey::generate();
+        let mut sealed = key.encrypt(b"content");
+
+        assert!(ContentKey::generate().decrypt(&sealed).is_err());
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(key.decrypt(&sealed).is_err());
+        assert!(key.decrypt(b"plain bytes").is_err());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let key = ContentKey::parse(&"07".repeat(32)).unwrap();
+        assert_eq!(key.to_hex(), "07".repeat(32));
+        assert!(ContentKey::parse("not hex").is_err());
+        assert!(ContentKey::parse("abcd").is_err());
+    }
+}
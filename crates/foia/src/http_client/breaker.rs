@@ -0,0 +1,257 @@
+//! Per-domain circuit breaker.
+//!
+//! The rate limiter slows down when a domain pushes back; this stops
+//! altogether. When most of a domain's recent responses are 403, 429 or
+//! 451, the breaker trips: the domain is paused for a cool-down period and
+//! an incident is recorded, so every process sharing the database stops
+//! requesting it. Once the cool-down ends, requests resume at a cautious
+//! delay that the rate limiter eases back to normal as they succeed.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::repository::DieselCrawlRepository;
+
+/// Status codes that count against a domain.
+pub const BLOCKING_STATUSES: [u16; 3] = [403, 429, 451];
+
+/// Thresholds and timings for the circuit breaker.
+#[derive(Debug, Clone)]
+pub struct BreakerSettings {
+    /// Whether the breaker trips at all.
+    pub enabled: bool,
+    /// How far back responses are counted.
+    pub window: Duration,
+    /// Fewest responses in the window before the breaker may trip.
+    pub min_responses: usize,
+    /// Share of responses in the window that must be blocking (0.0-1.0).
+    pub trip_ratio: f64,
+    /// How long a tripped domain stays paused.
+    pub cooldown: Duration,
+    /// Delay between requests when the domain resumes.
+    pub ramp_up_delay: Duration,
+    /// URL that receives a JSON `POST` when a domain is paused.
+    pub alert_webhook: Option<String>,
+}
+
+impl Default for BreakerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: Duration::from_secs(300),
+            min_responses: 10,
+            trip_ratio: 0.8,
+            cooldown: Duration::from_secs(1800),
+            ramp_up_delay: Duration::from_secs(30),
+            alert_webhook: None,
+        }
+    }
+}
+
+/// What the breaker saw when it tripped.
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerTrip {
+    pub domain: String,
+    /// Responses in the window.
+    pub responses: u32,
+    /// How many of them were blocking statuses.
+    pub blocked: u32,
+    /// Count of each blocking status code.
+    pub statuses: BTreeMap<u16, u32>,
+    pub paused_until: DateTime<Utc>,
+}
+
+/// Recent responses from one domain.
+#[derive(Default)]
+struct DomainWindow {
+    responses: VecDeque<(Instant, u16)>,
+    paused_until: Option<DateTime<Utc>>,
+}
+
+/// Tracks blocking responses per domain and pauses domains that get too
+/// many. Share one between clients so their responses count together.
+pub struct CircuitBreaker {
+    settings: BreakerSettings,
+    repo: Option<Arc<DieselCrawlRepository>>,
+    domains: Mutex<HashMap<String, DomainWindow>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(settings: BreakerSettings) -> Self {
+        Self {
+            settings,
+            repo: None,
+            domains: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record incidents in the database, and honor pauses opened by other
+    /// processes.
+    pub fn with_repo(mut self, repo: Arc<DieselCrawlRepository>) -> Self {
+        self.repo = Some(repo);
+        self
+    }
+
+    pub fn settings(&self) -> &BreakerSettings {
+        &self.settings
+    }
+
+    /// When the domain's pause ends, if it is paused now.
+    ///
+    /// With a database, its incidents decide, so a pause ended early by an
+    /// operator takes effect everywhere.
+    pub async fn paused_until(&self, domain: &str) -> Option<DateTime<Utc>> {
+        if !self.settings.enabled {
+            return None;
+        }
+        let now = Utc::now();
+        if let Some(repo) = &self.repo {
+            match repo.latest_incident(domain).await {
+                Ok(incident) => {
+                    return incident
+                        .map(|i| i.paused_until)
+                        .filter(|until| *until > now)
+                }
+                Err(e) => tracing::warn!("Failed to check incidents for {}: {}", domain, e),
+            }
+        }
+        let domains = self.domains.lock().ok()?;
+        domains
+            .get(domain)
+            .and_then(|w| w.paused_until)
+            .filter(|until| *until > now)
+    }
+
+    /// Count a response from `domain`. Returns the trip if it paused the
+    /// domain, or `None` if the domain was already paused elsewhere.
+    pub async fn observe(&self, source_id: &str, domain: &str, status: u16) -> Option<BreakerTrip> {
+        if !self.settings.enabled {
+            return None;
+        }
+        let trip = self.record(domain, status, Instant::now())?;
+        if let Some(repo) = &self.repo {
+            match repo.open_incident(source_id, &trip).await {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => tracing::warn!("Failed to record incident for {}: {}", domain, e),
+            }
+        }
+        Some(trip)
+    }
+
+    /// Add a response to the domain's window, tripping the breaker when
+    /// the window holds enough responses and enough of them are blocking.
+    fn record(&self, domain: &str, status: u16, now: Instant) -> Option<BreakerTrip> {
+        let mut domains = self.domains.lock().ok()?;
+        let window = domains.entry(domain.to_string()).or_default();
+
+        window.responses.push_back((now, status));
+        while let Some(&(at, _)) = window.responses.front() {
+            if now.duration_since(at) <= self.settings.window {
+                break;
+            }
+            window.responses.pop_front();
+        }
+
+        let responses = window.responses.len();
+        let mut statuses = BTreeMap::new();
+        for (_, status) in &window.responses {
+            if BLOCKING_STATUSES.contains(status) {
+                *statuses.entry(*status).or_insert(0u32) += 1;
+            }
+        }
+        let blocked: u32 = statuses.values().sum();
+        if responses < self.settings.min_responses.max(1)
+            || (blocked as f64) < responses as f64 * self.settings.trip_ratio
+        {
+            return None;
+        }
+
+        // Start over after the pause rather than tripping on stale responses
+        window.responses.clear();
+        let cooldown =
+            chrono::Duration::from_std(self.settings.cooldown).unwrap_or(chrono::Duration::zero());
+        let paused_until = Utc::now() + cooldown;
+        window.paused_until = Some(paused_until);
+
+        Some(BreakerTrip {
+            domain: domain.to_string(),
+            responses: responses as u32,
+            blocked,
+            statuses,
+            paused_until,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(BreakerSettings {
+            window: Duration::from_secs(60),
+            min_responses: 4,
+            trip_ratio: 0.75,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_trips_on_sustained_blocking() {
+        let breaker = breaker();
+        let start = Instant::now();
+
+        assert!(breaker.record("agency.gov", 200, start).is_none());
+        assert!(breaker.record("agency.gov", 429, start).is_none());
+        assert!(breaker.record("agency.gov", 429, start).is_none());
+        // 2 of 4 blocked: under the ratio
+        assert!(breaker.record("agency.gov", 200, start).is_none());
+        assert!(breaker.record("agency.gov", 451, start).is_none());
+        assert!(breaker.record("agency.gov", 403, start).is_none());
+        assert!(breaker.record("agency.gov", 429, start).is_none());
+
+        // 6 of 8 blocked
+        let trip = breaker.record("agency.gov", 429, start).unwrap();
+        assert_eq!(trip.responses, 8);
+        assert_eq!(trip.blocked, 6);
+        assert_eq!(
+            trip.statuses,
+            BTreeMap::from([(403, 1), (429, 4), (451, 1)])
+        );
+        assert!(trip.paused_until > Utc::now());
+
+        // The window starts over after a trip
+        assert!(breaker.record("agency.gov", 429, start).is_none());
+        // Other domains are unaffected
+        assert!(breaker.record("other.gov", 429, start).is_none());
+    }
+
+    #[test]
+    fn test_old_responses_leave_the_window() {
+        let breaker = breaker();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(breaker.record("agency.gov", 429, start).is_none());
+        }
+        // The first three have aged out, so one more is not enough
+        let later = start + Duration::from_secs(120);
+        assert!(breaker.record("agency.gov", 429, later).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_breaker_never_trips() {
+        let breaker = CircuitBreaker::new(BreakerSettings {
+            enabled: false,
+            min_responses: 1,
+            ..Default::default()
+        });
+        assert!(breaker.observe("fbi", "agency.gov", 429).await.is_none());
+        assert!(breaker.paused_until("agency.gov").await.is_none());
+    }
+}
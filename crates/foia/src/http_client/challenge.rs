@@ -0,0 +1,117 @@
+//! CAPTCHA and anti-bot challenge page detection.
+//!
+//! Challenge pages come back as ordinary responses (often 403/429/503, but
+//! sometimes 200 from a browser), so without detection the crawler just
+//! records them as failures or, worse, as documents. These heuristics look at
+//! vendor headers and well-known markup markers.
+
+use std::collections::HashMap;
+
+/// Response header Cloudflare sets on interstitial challenge pages.
+const CF_MITIGATED_HEADER: &str = "cf-mitigated";
+
+/// Body markers, checked in order; the first match names the challenge kind.
+const BODY_MARKERS: &[(&str, &str)] = &[
+    ("cloudflare", "challenge-platform"),
+    ("cloudflare", "cf-chl-"),
+    ("cloudflare", "<title>Just a moment...</title>"),
+    ("cloudflare", "Attention Required! | Cloudflare"),
+    ("turnstile", "challenges.cloudflare.com/turnstile"),
+    ("hcaptcha", "hcaptcha.com/1/api.js"),
+    ("hcaptcha", "class=\"h-captcha\""),
+    ("recaptcha", "www.google.com/recaptcha/"),
+    ("recaptcha", "class=\"g-recaptcha\""),
+    ("akamai", "sec-if-cpt-container"),
+    ("akamai", "_sec/cp_challenge"),
+    ("datadome", "captcha-delivery.com"),
+    ("perimeterx", "px-captcha"),
+];
+
+/// Only this much of a body is scanned; challenge markers appear early.
+const MAX_SCAN_BYTES: usize = 64 * 1024;
+
+/// Identify a challenge page from its status, headers, and body.
+///
+/// Returns the challenge kind (e.g. "cloudflare", "recaptcha") or `None` for
+/// ordinary responses.
+pub fn detect_challenge(
+    status: u16,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> Option<&'static str> {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    };
+
+    if header(CF_MITIGATED_HEADER).is_some_and(|v| v.eq_ignore_ascii_case("challenge")) {
+        return Some("cloudflare");
+    }
+
+    // Only HTML can carry an interactive challenge
+    if header("content-type").is_some_and(|ct| !ct.contains("html")) {
+        return None;
+    }
+
+    let body = String::from_utf8_lossy(&body[..body.len().min(MAX_SCAN_BYTES)]);
+    if let Some((kind, _)) = BODY_MARKERS
+        .iter()
+        .find(|(_, marker)| body.contains(marker))
+    {
+        return Some(*kind);
+    }
+
+    // A blocked status whose page talks about a captcha is almost certainly one
+    if matches!(status, 403 | 429 | 503) && body.to_lowercase().contains("captcha") {
+        return Some("generic");
+    }
+
+    None
+}
+
+/// Whether a response should have its body buffered for challenge detection.
+pub fn may_be_challenge(status: u16, headers: &HashMap<String, String>) -> bool {
+    matches!(status, 403 | 429 | 503)
+        || headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case(CF_MITIGATED_HEADER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html() -> HashMap<String, String> {
+        HashMap::from([("content-type".to_string(), "text/html".to_string())])
+    }
+
+    #[test]
+    fn test_cloudflare_header() {
+        let headers = HashMap::from([("cf-mitigated".to_string(), "challenge".to_string())]);
+        assert_eq!(detect_challenge(403, &headers, b""), Some("cloudflare"));
+    }
+
+    #[test]
+    fn test_body_markers() {
+        let body = b"<div class=\"g-recaptcha\" data-sitekey=\"x\"></div>";
+        assert_eq!(detect_challenge(200, &html(), body), Some("recaptcha"));
+        let body = b"<html><head><title>Just a moment...</title></head></html>";
+        assert_eq!(detect_challenge(503, &html(), body), Some("cloudflare"));
+    }
+
+    #[test]
+    fn test_generic_captcha_only_on_blocked_status() {
+        let body = b"<p>Please complete the CAPTCHA below.</p>";
+        assert_eq!(detect_challenge(403, &html(), body), Some("generic"));
+        // A 200 page merely mentioning captchas is not a challenge
+        assert_eq!(detect_challenge(200, &html(), body), None);
+    }
+
+    #[test]
+    fn test_non_html_ignored() {
+        let headers = HashMap::from([("content-type".to_string(), "application/pdf".to_string())]);
+        assert_eq!(detect_challenge(403, &headers, b"g-recaptcha"), None);
+    }
+}
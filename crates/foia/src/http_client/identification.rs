@@ -0,0 +1,24 @@
+//! Transparency mode identification, installed once per process.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::config::TransparencyConfig;
+
+static IDENTIFICATION: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Send the transparency config's identification headers with every
+/// request from clients built after this. Returns `false` if transparency
+/// mode is off or identification was already installed.
+pub fn install_identification(config: &TransparencyConfig) -> bool {
+    let headers = config.headers();
+    if headers.is_empty() {
+        return false;
+    }
+    IDENTIFICATION.set(headers).is_ok()
+}
+
+/// Installed identification headers, if transparency mode is on.
+pub(crate) fn identification_headers() -> Option<&'static HashMap<String, String>> {
+    IDENTIFICATION.get()
+}
@@ -0,0 +1,272 @@
+//! In-flight fetch registry.
+//!
+//! Discovery can find the same URL along several paths in one run, and
+//! workers may pick up both copies before the first fetch is recorded.
+//! Workers claim a URL, by its normalized form, before fetching it, and
+//! the content they fetched before saving it; a second claim on either
+//! within the window is suppressed. Claims live in memory, and in the
+//! database when there is one, so separate processes see each other's.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::repository::DieselCrawlRepository;
+
+/// Default time a claim suppresses duplicates.
+pub const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(600);
+
+/// Memory claims by `(source_id, key)`: the claiming URL and expiry.
+type Claims = HashMap<(String, String), (String, Instant)>;
+
+/// Registry of URLs and content being fetched. Share one between workers.
+pub struct InflightRegistry {
+    window: Duration,
+    repo: Option<Arc<DieselCrawlRepository>>,
+    claims: Arc<Mutex<Claims>>,
+}
+
+/// A held claim. Dropping it without [`FetchClaim::land`] releases it, so
+/// a failed fetch can be retried within the window.
+pub struct FetchClaim {
+    source_id: String,
+    key: String,
+    landed: bool,
+    repo: Option<Arc<DieselCrawlRepository>>,
+    claims: Arc<Mutex<Claims>>,
+}
+
+impl FetchClaim {
+    /// Keep the claim for the rest of the window: the fetch succeeded.
+    pub fn land(mut self) {
+        self.landed = true;
+    }
+}
+
+impl Drop for FetchClaim {
+    fn drop(&mut self) {
+        if self.landed || self.key.is_empty() {
+            return;
+        }
+        if let Ok(mut claims) = self.claims.lock() {
+            claims.remove(&(self.source_id.clone(), self.key.clone()));
+        }
+        if let Some(repo) = self.repo.clone() {
+            let (source_id, key) = (self.source_id.clone(), self.key.clone());
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                handle.spawn(async move {
+                    if let Err(e) = repo.release_fetch(&source_id, &key).await {
+                        tracing::warn!("Failed to release fetch claim {}: {}", key, e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl InflightRegistry {
+    /// A registry suppressing duplicates for `window`; zero disables it.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            repo: None,
+            claims: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Record claims in the database, and honor those of other processes.
+    pub fn with_repo(mut self, repo: Arc<DieselCrawlRepository>) -> Self {
+        self.repo = Some(repo);
+        self
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Claim `url` before fetching it. Returns the URL already being
+    /// fetched in its place if it is a duplicate.
+    pub async fn claim_url(&self, source_id: &str, url: &str) -> Result<FetchClaim, String> {
+        self.claim(source_id, &format!("url:{}", normalize_url(url)), url)
+            .await
+    }
+
+    /// Claim fetched content by its hash before saving it. Returns the URL
+    /// whose fetch got the same content if it is a duplicate.
+    pub async fn claim_content(
+        &self,
+        source_id: &str,
+        content_hash: &str,
+        url: &str,
+    ) -> Result<FetchClaim, String> {
+        self.claim(source_id, &format!("sha256:{}", content_hash), url)
+            .await
+    }
+
+    async fn claim(&self, source_id: &str, key: &str, url: &str) -> Result<FetchClaim, String> {
+        let claim = |key: &str| FetchClaim {
+            source_id: source_id.to_string(),
+            key: key.to_string(),
+            landed: false,
+            repo: self.repo.clone(),
+            claims: self.claims.clone(),
+        };
+        if self.window.is_zero() {
+            // Nothing to release
+            return Ok(claim(""));
+        }
+
+        self.claim_local(source_id, key, url, Instant::now())?;
+        if let Some(repo) = &self.repo {
+            let window = chrono::Duration::from_std(self.window)
+                .unwrap_or_else(|_| chrono::Duration::days(365));
+            match repo.claim_fetch(source_id, key, url, window).await {
+                Ok(None) => {}
+                Ok(Some(holder)) => {
+                    // Held by another process: forget the local claim
+                    if let Ok(mut claims) = self.claims.lock() {
+                        claims.remove(&(source_id.to_string(), key.to_string()));
+                    }
+                    return Err(holder);
+                }
+                Err(e) => tracing::warn!("Failed to record fetch claim for {}: {}", url, e),
+            }
+        }
+        Ok(claim(key))
+    }
+
+    /// Take a claim in memory, or return the URL holding it.
+    fn claim_local(
+        &self,
+        source_id: &str,
+        key: &str,
+        url: &str,
+        now: Instant,
+    ) -> Result<(), String> {
+        let Ok(mut claims) = self.claims.lock() else {
+            return Ok(());
+        };
+        claims.retain(|_, (_, expires)| *expires > now);
+        let entry = (source_id.to_string(), key.to_string());
+        if let Some((holder, _)) = claims.get(&entry) {
+            return Err(holder.clone());
+        }
+        claims.insert(entry, (url.to_string(), now + self.window));
+        Ok(())
+    }
+}
+
+/// Normalize a URL for duplicate detection: lowercase scheme and host,
+/// no default port, fragment or trailing slash, and sorted query
+/// parameters. Unparseable URLs are compared as given.
+pub fn normalize_url(url: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+    parsed.set_fragment(None);
+    let mut pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if pairs.is_empty() {
+        parsed.set_query(None);
+    } else {
+        pairs.sort();
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    let mut normalized = parsed.to_string();
+    if parsed.query().is_none() && normalized.ends_with('/') && parsed.path() != "/" {
+        normalized.pop();
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("HTTPS://Agency.GOV:443/files/report.pdf#page=2"),
+            "https://agency.gov/files/report.pdf"
+        );
+        assert_eq!(
+            normalize_url("https://agency.gov/view?id=7&doc=a"),
+            normalize_url("https://agency.gov/view?doc=a&id=7")
+        );
+        assert_eq!(
+            normalize_url("https://agency.gov/foia/"),
+            "https://agency.gov/foia"
+        );
+        assert_eq!(normalize_url("https://agency.gov"), "https://agency.gov/");
+        assert_eq!(normalize_url("not a url"), "not a url");
+    }
+
+    #[tokio::test]
+    async fn test_claims_suppress_duplicates() {
+        let registry = InflightRegistry::new(Duration::from_secs(60));
+
+        let first = registry
+            .claim_url("fbi", "https://fbi.gov/vault/a.pdf")
+            .await
+            .unwrap();
+        assert_eq!(
+            registry
+                .claim_url("fbi", "https://FBI.gov/vault/a.pdf#p1")
+                .await
+                .err()
+                .as_deref(),
+            Some("https://fbi.gov/vault/a.pdf")
+        );
+        // Another source may fetch it
+        assert!(registry
+            .claim_url("cia", "https://fbi.gov/vault/a.pdf")
+            .await
+            .is_ok());
+
+        // A failed fetch drops its claim; a landed one keeps it
+        drop(first);
+        let again = registry
+            .claim_url("fbi", "https://fbi.gov/vault/a.pdf")
+            .await
+            .unwrap();
+        again.land();
+        assert!(registry
+            .claim_url("fbi", "https://fbi.gov/vault/a.pdf")
+            .await
+            .is_err());
+
+        registry
+            .claim_content("fbi", "abc", "https://fbi.gov/vault/a.pdf")
+            .await
+            .unwrap()
+            .land();
+        assert_eq!(
+            registry
+                .claim_content("fbi", "abc", "https://fbi.gov/mirror/a.pdf")
+                .await
+                .err()
+                .as_deref(),
+            Some("https://fbi.gov/vault/a.pdf")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_claims_expire() {
+        let registry = InflightRegistry::new(Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(registry.claim_local("fbi", "url:a", "a", start).is_ok());
+        assert!(registry
+            .claim_local("fbi", "url:a", "b", start + Duration::from_secs(30))
+            .is_err());
+        assert!(registry
+            .claim_local("fbi", "url:a", "b", start + Duration::from_secs(61))
+            .is_ok());
+
+        // A zero window never suppresses
+        let off = InflightRegistry::new(Duration::ZERO);
+        let _held = off.claim_url("fbi", "https://a.gov/x").await.unwrap();
+        assert!(off.claim_url("fbi", "https://a.gov/x").await.is_ok());
+    }
+}
@@ -12,11 +12,22 @@
 // This module is the privacy wrapper - it's allowed to use reqwest directly
 #![allow(clippy::disallowed_methods)]
 
+mod breaker;
+pub mod challenge;
+mod identification;
+mod inflight;
 mod response;
 mod user_agent;
 
 #[allow(unused_imports)]
-pub use response::{parse_content_disposition_filename, HeadResponse, HttpResponse};
+pub use breaker::{BreakerSettings, BreakerTrip, CircuitBreaker, BLOCKING_STATUSES};
+pub use identification::install_identification;
+pub use inflight::{normalize_url, FetchClaim, InflightRegistry, DEFAULT_DEDUP_WINDOW};
+#[allow(unused_imports)]
+pub use response::{
+    parse_content_disposition_filename, HeadResponse, HttpResponse, CHALLENGE_PAUSED_HEADER,
+    DOMAIN_PAUSED_HEADER,
+};
 #[allow(unused_imports)]
 pub use user_agent::{resolve_user_agent, IMPERSONATE_USER_AGENTS, USER_AGENT};
 
@@ -25,12 +36,12 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use chrono::Utc;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{Client, Proxy, Response, StatusCode};
-#[cfg(feature = "browser")]
 use tracing::debug;
 
 use crate::config::scraper::ViaMode;
-use crate::models::{CrawlRequest, CrawlUrl, UrlStatus};
+use crate::models::{ChallengeStatus, CrawlChallenge, CrawlRequest, CrawlUrl, UrlStatus};
 use crate::privacy::{PrivacyConfig, PrivacyMode};
 use crate::rate_limit::{InMemoryRateLimitBackend, RateLimiter};
 use crate::repository::DieselCrawlRepository;
@@ -67,6 +78,12 @@ pub struct HttpClient {
     via_mappings: Arc<HashMap<String, String>>,
     /// Via mode controlling when via mappings are used for requests.
     via_mode: ViaMode,
+    /// Pauses domains that keep blocking requests.
+    breaker: Option<Arc<CircuitBreaker>>,
+    /// Client for breaker alerts, without the source's default headers.
+    alert_client: Client,
+    /// Whether requests carry transparency mode's identification headers.
+    identified: bool,
     #[cfg(feature = "browser")]
     browser_pool: Option<Arc<BrowserPool>>,
 }
@@ -84,6 +101,17 @@ fn extract_response_headers(response: &Response) -> HashMap<String, String> {
         .collect()
 }
 
+/// Mask the password in a proxy URL for error messages.
+fn redact_proxy_url(proxy_url: &str) -> String {
+    match url::Url::parse(proxy_url) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some("****"));
+            url.to_string()
+        }
+        _ => proxy_url.to_string(),
+    }
+}
+
 /// Builder for constructing `HttpClient` with optional configuration.
 ///
 /// Required parameters (source_id, timeout, request_delay) are provided
@@ -99,7 +127,9 @@ pub struct HttpClientBuilder {
     via_mappings: Option<HashMap<String, String>>,
     via_mode: Option<ViaMode>,
     crawl_repo: Option<Arc<DieselCrawlRepository>>,
+    breaker: Option<Arc<CircuitBreaker>>,
     referer: Option<String>,
+    headers: HashMap<String, String>,
 }
 
 impl HttpClientBuilder {
@@ -139,12 +169,26 @@ impl HttpClientBuilder {
         self
     }
 
+    /// Set a shared circuit breaker.
+    /// Without this, a client with a crawl repository gets its own breaker
+    /// with default settings; one without gets none.
+    pub fn circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
     /// Set the Referer header for requests.
     pub fn referer(mut self, referer: String) -> Self {
         self.referer = Some(referer);
         self
     }
 
+    /// Set headers sent with every request (e.g. source credentials).
+    pub fn default_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
     /// Build the `HttpClient`.
     ///
     /// # Errors
@@ -157,8 +201,31 @@ impl HttpClientBuilder {
             .privacy
             .unwrap_or_else(|| PrivacyConfig::default().with_env_overrides());
 
+        // Transparency mode identification, unless the source overrides it
+        let identification = identification::identification_headers();
+        let mut headers = identification.cloned().unwrap_or_default();
+        headers.extend(self.headers.clone());
+
         let (client, privacy_mode) =
-            HttpClient::build_client(&user_agent, self.timeout, Some(&privacy_config))?;
+            HttpClient::build_client(&user_agent, self.timeout, Some(&privacy_config), &headers)?;
+        // Source credentials must not reach the alert webhook
+        let alert_client = if self.headers.is_empty() {
+            client.clone()
+        } else {
+            HttpClient::build_client(
+                &user_agent,
+                self.timeout,
+                Some(&privacy_config),
+                &HashMap::new(),
+            )?
+            .0
+        };
+
+        let breaker = self.breaker.or_else(|| {
+            self.crawl_repo.clone().map(|repo| {
+                Arc::new(CircuitBreaker::new(BreakerSettings::default()).with_repo(repo))
+            })
+        });
 
         let rate_limiter = self.rate_limiter.unwrap_or_else(|| {
             let backend = Arc::new(InMemoryRateLimitBackend::new(
@@ -191,6 +258,9 @@ impl HttpClientBuilder {
             privacy_mode,
             via_mappings: Arc::new(via_mappings),
             via_mode,
+            breaker,
+            alert_client,
+            identified: identification.is_some(),
             #[cfg(feature = "browser")]
             browser_pool: HttpClient::create_browser_pool(),
         })
@@ -219,7 +289,9 @@ impl HttpClient {
             via_mappings: None,
             via_mode: None,
             crawl_repo: None,
+            breaker: None,
             referer: None,
+            headers: HashMap::new(),
         }
     }
 
@@ -239,6 +311,14 @@ impl HttpClient {
         (url.to_string(), false)
     }
 
+    /// Log entry for a request sent directly (not through the browser).
+    fn request_log(&self, url: &str, method: &str) -> CrawlRequest {
+        let mut log =
+            CrawlRequest::new(self.source_id.clone(), url.to_string(), method.to_string());
+        log.identified = self.identified;
+        log
+    }
+
     /// Create browser pool from BROWSER_URL env var.
     /// Supports comma-separated URLs for multiple browsers.
     #[cfg(feature = "browser")]
@@ -262,6 +342,7 @@ impl HttpClient {
         user_agent: &str,
         timeout: Duration,
         privacy_config: Option<&PrivacyConfig>,
+        headers: &HashMap<String, String>,
     ) -> Result<(Client, PrivacyMode), String> {
         let mut builder = Client::builder()
             .user_agent(user_agent)
@@ -269,6 +350,19 @@ impl HttpClient {
             .gzip(true)
             .brotli(true);
 
+        if !headers.is_empty() {
+            let mut header_map = HeaderMap::new();
+            for (name, value) in headers {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| format!("Invalid header name '{}': {}", name, e))?;
+                let mut value = HeaderValue::from_str(value)
+                    .map_err(|_| format!("Invalid value for header '{}'", name))?;
+                value.set_sensitive(true);
+                header_map.insert(name, value);
+            }
+            builder = builder.default_headers(header_map);
+        }
+
         let mode = privacy_config
             .map(|c| c.mode())
             .unwrap_or(PrivacyMode::Direct);
@@ -279,17 +373,19 @@ impl HttpClient {
                 // Use external SOCKS proxy from SOCKS_PROXY env var
                 if let Some(config) = privacy_config {
                     if let Some(proxy_url) = config.proxy_url() {
+                        // Proxy URLs may carry credentials (e.g. from a secret)
+                        let redacted = redact_proxy_url(&proxy_url);
                         // Validate SOCKS URL scheme
                         if !proxy_url.starts_with("socks5://")
                             && !proxy_url.starts_with("socks5h://")
                         {
                             return Err(format!(
                                 "Invalid SOCKS proxy URL: '{}'. Must start with socks5:// or socks5h://",
-                                proxy_url
+                                redacted
                             ));
                         }
-                        let proxy = Proxy::all(proxy_url).map_err(|e| {
-                            format!("Invalid SOCKS proxy URL '{}': {}", proxy_url, e)
+                        let proxy = Proxy::all(&proxy_url).map_err(|e| {
+                            format!("Invalid SOCKS proxy URL '{}': {}", redacted, e)
                         })?;
                         builder = builder.proxy(proxy);
                     } else {
@@ -375,6 +471,12 @@ impl HttpClient {
         self
     }
 
+    /// Set the circuit breaker, replacing any the client was built with.
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
     /// Set the Referer header for requests.
     pub fn with_referer(mut self, referer: String) -> Self {
         self.referer = Some(referer);
@@ -424,27 +526,143 @@ impl HttpClient {
             self.rate_limiter
                 .report_response_status(domain, status_code, url, response_headers)
                 .await;
+
+            if let Some(breaker) = &self.breaker {
+                if let Some(trip) = breaker.observe(&self.source_id, domain, status_code).await {
+                    self.report_trip(breaker.settings(), &trip).await;
+                }
+            }
         }
 
         tokio::time::sleep(self.request_delay).await;
     }
 
+    /// Synthetic response to return instead of requesting `url` while the
+    /// circuit breaker has its domain paused.
+    async fn domain_pause(&self, url: &str) -> Option<HttpResponse> {
+        let breaker = self.breaker.as_ref()?;
+        let domain = RateLimiter::extract_domain(url)?;
+        let until = breaker.paused_until(&domain).await?;
+        debug!(
+            "Skipping {} while {} is paused until {}",
+            url, domain, until
+        );
+        // Whenever the pause ends, start again slowly
+        self.rate_limiter
+            .ramp_up(&domain, breaker.settings().ramp_up_delay)
+            .await;
+        Some(HttpResponse::domain_paused(until))
+    }
+
+    /// Log a circuit breaker trip, slow the domain down for when it
+    /// resumes, and post the alert webhook if one is configured.
+    async fn report_trip(&self, settings: &BreakerSettings, trip: &BreakerTrip) {
+        tracing::warn!(
+            "{} blocked {} of the last {} requests; pausing it until {}",
+            trip.domain,
+            trip.blocked,
+            trip.responses,
+            trip.paused_until
+        );
+        self.rate_limiter
+            .ramp_up(&trip.domain, settings.ramp_up_delay)
+            .await;
+
+        let Some(ref url) = settings.alert_webhook else {
+            return;
+        };
+        let Some(url) = crate::secrets::resolve_or_warn(url) else {
+            return;
+        };
+        let payload = serde_json::json!({
+            "event": "domain_paused",
+            "source_id": self.source_id,
+            "domain": trip.domain,
+            "responses": trip.responses,
+            "blocked": trip.blocked,
+            "statuses": trip.statuses,
+            "paused_until": trip.paused_until,
+        });
+        let result = self
+            .alert_client
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!("Failed to deliver alert for {}: {}", trip.domain, e);
+        }
+    }
+
+    /// Latest CAPTCHA/anti-bot challenge recorded for a URL's domain.
+    async fn latest_challenge(&self, url: &str) -> Option<CrawlChallenge> {
+        let repo = self.crawl_repo.as_ref()?;
+        let domain = RateLimiter::extract_domain(url)?;
+        match repo.latest_challenge(&domain).await {
+            Ok(challenge) => challenge,
+            Err(e) => {
+                tracing::warn!("Failed to check challenges for {}: {}", domain, e);
+                None
+            }
+        }
+    }
+
+    /// Open an operator task for a challenge page, pausing its domain.
+    async fn report_challenge(&self, url: &str, kind: &str) {
+        let (Some(repo), Some(domain)) = (&self.crawl_repo, RateLimiter::extract_domain(url))
+        else {
+            return;
+        };
+        match repo
+            .open_challenge(&self.source_id, &domain, url, kind)
+            .await
+        {
+            Ok(true) => tracing::warn!(
+                "{} challenge at {}; pausing {} until an operator solves it",
+                kind,
+                url,
+                domain
+            ),
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to record challenge for {}: {}", domain, e),
+        }
+    }
+
     /// Make a GET request with optional conditional headers.
     /// Uses adaptive rate limiting per domain.
     /// When BROWSER_URL is configured, routes through browser pool.
+    ///
+    /// While the domain has a pending challenge, no request is made and a
+    /// 503 response carrying [`CHALLENGE_PAUSED_HEADER`] is returned instead;
+    /// likewise with [`DOMAIN_PAUSED_HEADER`] while the circuit breaker has
+    /// it paused.
     pub async fn get(
         &self,
         url: &str,
         etag: Option<&str>,
         last_modified: Option<&str>,
     ) -> Result<HttpResponse, reqwest::Error> {
+        if let Some(paused) = self.domain_pause(url).await {
+            return Ok(paused);
+        }
+        let challenge = self.latest_challenge(url).await;
+        if let Some(ref c) = challenge {
+            if c.status == ChallengeStatus::Pending {
+                debug!("Skipping {} while challenge {} is pending", url, c.id);
+                return Ok(HttpResponse::challenge_paused(c.id));
+            }
+        }
+        let cookies = challenge.and_then(|c| c.cookies);
+
         // Check if browser mode is enabled
         #[cfg(feature = "browser")]
         if let Some(ref pool) = self.browser_pool {
             return self.get_via_browser_pool(pool, url).await;
         }
 
-        self.get_via_reqwest(url, etag, last_modified).await
+        self.get_via_reqwest(url, etag, last_modified, cookies.as_deref())
+            .await
     }
 
     /// Fetch via browser pool (with load balancing and failover).
@@ -497,7 +715,7 @@ impl HttpClient {
                     return Ok(retry_response);
                 }
                 // Browser retry failed, fall back to reqwest
-                return self.get_via_reqwest(url, None, None).await;
+                return self.get_via_reqwest(url, None, None, None).await;
             }
 
             return Ok(response);
@@ -508,7 +726,7 @@ impl HttpClient {
             "Browser pool exhausted, falling back to reqwest for {}",
             url
         );
-        self.get_via_reqwest(url, None, None).await
+        self.get_via_reqwest(url, None, None, None).await
     }
 
     /// Internal: perform a single browser fetch and handle logging/rate limiting.
@@ -539,6 +757,14 @@ impl HttpClient {
                 let mut headers = HashMap::new();
                 headers.insert("content-type".to_string(), browser_response.content_type);
 
+                if let Some(kind) = challenge::detect_challenge(
+                    status_code,
+                    &headers,
+                    browser_response.content.as_bytes(),
+                ) {
+                    self.report_challenge(original_url, kind).await;
+                }
+
                 self.finalize_request(
                     &mut request_log,
                     original_url,
@@ -574,6 +800,7 @@ impl HttpClient {
         url: &str,
         etag: Option<&str>,
         last_modified: Option<&str>,
+        cookies: Option<&str>,
     ) -> Result<HttpResponse, reqwest::Error> {
         let (via_url, has_via) = self.apply_via_rewrite(url);
 
@@ -592,7 +819,7 @@ impl HttpClient {
 
         // Make first request
         let result = self
-            .do_get_reqwest(&initial_url, url, etag, last_modified)
+            .do_get_reqwest(&initial_url, url, etag, last_modified, cookies)
             .await?;
 
         // Check if we should retry with alternate URL
@@ -617,7 +844,7 @@ impl HttpClient {
             tokio::time::sleep(self.request_delay).await;
 
             return self
-                .do_get_reqwest(alternate_url, url, etag, last_modified)
+                .do_get_reqwest(alternate_url, url, etag, last_modified, cookies)
                 .await;
         }
 
@@ -631,6 +858,7 @@ impl HttpClient {
         original_url: &str,
         etag: Option<&str>,
         last_modified: Option<&str>,
+        cookies: Option<&str>,
     ) -> Result<HttpResponse, reqwest::Error> {
         // Wait for rate limiter before making request (use original URL for rate limiting)
         let domain = self.rate_limiter.acquire(original_url).await;
@@ -639,6 +867,11 @@ impl HttpClient {
 
         let mut headers = HashMap::new();
 
+        // Cookies captured when an operator solved this domain's challenge
+        if let Some(cookies) = cookies {
+            request = request.header("Cookie", cookies);
+        }
+
         // Add conditional request headers
         if let Some(etag) = etag {
             request = request.header("If-None-Match", etag);
@@ -652,11 +885,7 @@ impl HttpClient {
         let was_conditional = etag.is_some() || last_modified.is_some();
 
         // Create request log (always log original URL for accurate records)
-        let mut request_log = CrawlRequest::new(
-            self.source_id.clone(),
-            original_url.to_string(),
-            "GET".to_string(),
-        );
+        let mut request_log = self.request_log(original_url, "GET");
         request_log.request_headers = headers;
         request_log.was_conditional = was_conditional;
 
@@ -678,6 +907,16 @@ impl HttpClient {
         )
         .await;
 
+        // Buffer blocked responses so challenge pages can be recognized
+        if challenge::may_be_challenge(status_code, &response_headers) {
+            let status = response.status();
+            let body = response.bytes().await?.to_vec();
+            if let Some(kind) = challenge::detect_challenge(status_code, &response_headers, &body) {
+                self.report_challenge(original_url, kind).await;
+            }
+            return Ok(HttpResponse::from_bytes(status, response_headers, body));
+        }
+
         Ok(HttpResponse::from_reqwest(
             response.status(),
             response_headers,
@@ -697,6 +936,10 @@ impl HttpClient {
         url: &str,
         headers: HashMap<String, String>,
     ) -> Result<HttpResponse, reqwest::Error> {
+        if let Some(paused) = self.domain_pause(url).await {
+            return Ok(paused);
+        }
+
         // Apply via rewriting if configured (fetch via caching proxy)
         let (fetch_url, _via_rewritten) = self.apply_via_rewrite(url);
 
@@ -709,8 +952,7 @@ impl HttpClient {
         }
 
         // Create request log
-        let mut request_log =
-            CrawlRequest::new(self.source_id.clone(), url.to_string(), "GET".to_string());
+        let mut request_log = self.request_log(url, "GET");
         request_log.request_headers = headers.clone();
 
         let start = Instant::now();
@@ -745,6 +987,9 @@ impl HttpClient {
         url: &str,
         form: &T,
     ) -> Result<HttpResponse, reqwest::Error> {
+        if let Some(paused) = self.domain_pause(url).await {
+            return Ok(paused);
+        }
         self.post_via_reqwest(url, form).await
     }
 
@@ -756,6 +1001,9 @@ impl HttpClient {
         url: &str,
         json: &T,
     ) -> Result<HttpResponse, reqwest::Error> {
+        if let Some(paused) = self.domain_pause(url).await {
+            return Ok(paused);
+        }
         self.post_json_via_reqwest(url, json).await
     }
 
@@ -766,6 +1014,10 @@ impl HttpClient {
         json: &T,
         headers: HashMap<String, String>,
     ) -> Result<HttpResponse, reqwest::Error> {
+        if let Some(paused) = self.domain_pause(url).await {
+            return Ok(paused);
+        }
+
         // Apply via rewriting if configured (fetch via caching proxy)
         let (fetch_url, _via_rewritten) = self.apply_via_rewrite(url);
 
@@ -778,8 +1030,7 @@ impl HttpClient {
         }
 
         // Create request log (log original URL, not the via-rewritten one)
-        let mut request_log =
-            CrawlRequest::new(self.source_id.clone(), url.to_string(), "POST".to_string());
+        let mut request_log = self.request_log(url, "POST");
         request_log.request_headers = headers.clone();
 
         let start = Instant::now();
@@ -821,8 +1072,7 @@ impl HttpClient {
         let request = self.client.post(&fetch_url).form(form);
 
         // Create request log
-        let mut request_log =
-            CrawlRequest::new(self.source_id.clone(), url.to_string(), "POST".to_string());
+        let mut request_log = self.request_log(url, "POST");
 
         let start = Instant::now();
         let response = request.send().await?;
@@ -863,8 +1113,7 @@ impl HttpClient {
         let request = self.client.post(&fetch_url).json(json);
 
         // Create request log
-        let mut request_log =
-            CrawlRequest::new(self.source_id.clone(), url.to_string(), "POST".to_string());
+        let mut request_log = self.request_log(url, "POST");
 
         let start = Instant::now();
         let response = request.send().await?;
@@ -921,8 +1170,7 @@ impl HttpClient {
         let was_conditional = etag.is_some() || last_modified.is_some();
 
         // Create request log
-        let mut request_log =
-            CrawlRequest::new(self.source_id.clone(), url.to_string(), "HEAD".to_string());
+        let mut request_log = self.request_log(url, "HEAD");
         request_log.request_headers = headers;
         request_log.was_conditional = was_conditional;
 
@@ -1071,7 +1319,8 @@ mod tests {
         let config = tor_direct_config();
         assert_eq!(config.mode(), PrivacyMode::TorDirect);
 
-        let result = HttpClient::build_client("test-agent", test_timeout(), Some(&config));
+        let result =
+            HttpClient::build_client("test-agent", test_timeout(), Some(&config), &HashMap::new());
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(
@@ -1086,7 +1335,8 @@ mod tests {
         let config = tor_obfuscated_config();
         assert!(matches!(config.mode(), PrivacyMode::TorObfuscated(_)));
 
-        let result = HttpClient::build_client("test-agent", test_timeout(), Some(&config));
+        let result =
+            HttpClient::build_client("test-agent", test_timeout(), Some(&config), &HashMap::new());
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(
@@ -1100,7 +1350,8 @@ mod tests {
     fn test_build_client_external_proxy_fails_without_url() {
         let config = external_proxy_no_url_config();
 
-        let result = HttpClient::build_client("test-agent", test_timeout(), Some(&config));
+        let result =
+            HttpClient::build_client("test-agent", test_timeout(), Some(&config), &HashMap::new());
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(
@@ -1115,7 +1366,8 @@ mod tests {
         let config = direct_config();
         assert_eq!(config.mode(), PrivacyMode::Direct);
 
-        let result = HttpClient::build_client("test-agent", test_timeout(), Some(&config));
+        let result =
+            HttpClient::build_client("test-agent", test_timeout(), Some(&config), &HashMap::new());
         assert!(result.is_ok());
         let (_, mode) = result.unwrap();
         assert_eq!(mode, PrivacyMode::Direct);
@@ -2,8 +2,17 @@
 
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use reqwest::{Response, StatusCode};
 
+/// Header set on the synthetic response returned while a domain is paused
+/// for a pending challenge; its value is the challenge ID.
+pub const CHALLENGE_PAUSED_HEADER: &str = "x-foia-challenge-paused";
+
+/// Header set on the synthetic response returned while the circuit breaker
+/// has a domain paused; its value is when the pause ends (RFC 3339).
+pub const DOMAIN_PAUSED_HEADER: &str = "x-foia-domain-paused";
+
 /// Response body source - either pending (reqwest) or already fetched (browser).
 pub(crate) enum ResponseBody {
     /// Pending response from reqwest.
@@ -46,6 +55,30 @@ impl HttpResponse {
         }
     }
 
+    /// Synthetic 503 returned instead of fetching while challenge `id` is pending.
+    pub(crate) fn challenge_paused(id: i64) -> Self {
+        let headers = HashMap::from([(CHALLENGE_PAUSED_HEADER.to_string(), id.to_string())]);
+        Self::from_bytes(StatusCode::SERVICE_UNAVAILABLE, headers, Vec::new())
+    }
+
+    /// Check if no request was made because the domain is paused for a challenge.
+    pub fn is_challenge_paused(&self) -> bool {
+        self.headers.contains_key(CHALLENGE_PAUSED_HEADER)
+    }
+
+    /// Synthetic 503 returned instead of fetching while the circuit breaker
+    /// has the domain paused.
+    pub(crate) fn domain_paused(until: DateTime<Utc>) -> Self {
+        let headers = HashMap::from([(DOMAIN_PAUSED_HEADER.to_string(), until.to_rfc3339())]);
+        Self::from_bytes(StatusCode::SERVICE_UNAVAILABLE, headers, Vec::new())
+    }
+
+    /// Check if no request was made because the circuit breaker has the
+    /// domain paused.
+    pub fn is_domain_paused(&self) -> bool {
+        self.headers.contains_key(DOMAIN_PAUSED_HEADER)
+    }
+
     /// Check if the response is 304 Not Modified.
     pub fn is_not_modified(&self) -> bool {
         self.status == StatusCode::NOT_MODIFIED
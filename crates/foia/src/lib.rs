@@ -9,6 +9,7 @@
 #[cfg(feature = "browser")]
 pub mod browser;
 pub mod config;
+pub mod encryption;
 #[cfg(feature = "gis")]
 pub mod gis_data;
 pub mod http_client;
@@ -19,6 +20,7 @@ pub mod prefer_db;
 pub mod privacy;
 pub mod rate_limit;
 pub mod repository;
+pub mod secrets;
 pub mod schema;
 pub mod services;
 pub mod storage;
@@ -0,0 +1,85 @@
+//! Request budget for LLM providers: concurrency, rate, and retry backoff.
+
+use std::time::Duration;
+
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// First retry delay; doubles with each further attempt.
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Upper bound on a single retry delay.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Limits requests in flight and spaces them to a per-minute rate.
+///
+/// Shared by every call through one `LlmClient`, so concurrent annotation
+/// batches draw from the same provider allowance.
+pub struct RequestBudget {
+    slots: Semaphore,
+    interval: Option<Duration>,
+    next_start: Mutex<Instant>,
+}
+
+impl RequestBudget {
+    pub fn new(max_concurrent: usize, requests_per_minute: Option<u32>) -> Self {
+        Self {
+            slots: Semaphore::new(max_concurrent.max(1)),
+            interval: requests_per_minute
+                .filter(|&rpm| rpm > 0)
+                .map(|rpm| Duration::from_secs(60) / rpm),
+            next_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for a free slot and the next start time under the rate limit.
+    /// The slot is held until the returned permit is dropped.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self
+            .slots
+            .acquire()
+            .await
+            .expect("request budget semaphore is never closed");
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next = self.next_start.lock().await;
+                let start = (*next).max(Instant::now());
+                *next = start + interval;
+                start
+            };
+            tokio::time::sleep_until(start).await;
+        }
+        permit
+    }
+}
+
+/// Delay before retry number `attempt` (starting at 1).
+pub fn backoff(attempt: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(2));
+        assert_eq!(backoff(2), Duration::from_secs(4));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_rate_spaces_requests() {
+        // 1200/min is one request every 50ms
+        let budget = RequestBudget::new(4, Some(1200));
+        let started = Instant::now();
+        for _ in 0..3 {
+            drop(budget.acquire().await);
+        }
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -5,13 +5,15 @@
 //! - `LlmDeviceConfig`: From env vars, device-specific (provider, endpoint, model, api_key)
 //!
 //! Env vars: ANNOTATE_PROVIDER, ANNOTATE_MODEL, ANNOTATE_ENDPOINT, ANNOTATE_API_KEY
-//! (legacy LLM_* names also accepted as fallback)
+//! (legacy LLM_* names also accepted as fallback). API keys may be
+//! `secret://name` references, resolved when the key is used.
 
 #![allow(dead_code)]
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use super::prompts::{DEFAULT_SYNOPSIS_PROMPT, DEFAULT_TAGS_PROMPT};
+use super::prompts::{DEFAULT_SYNOPSIS_PROMPT, DEFAULT_TAGS_PROMPT, PROMPT_VERSION};
 
 /// LLM provider type.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -83,6 +85,18 @@ pub struct LlmAppConfig {
     #[serde(default = "default_max_content_chars")]
     #[prefer(default)]
     pub max_content_chars: usize,
+    /// Short documents summarized together in one request (1 disables batching)
+    #[serde(default = "default_batch_size")]
+    #[prefer(default)]
+    pub batch_size: usize,
+    /// Documents with more text than this are always summarized on their own
+    #[serde(default = "default_batch_max_chars")]
+    #[prefer(default)]
+    pub batch_max_chars: usize,
+    /// Retries for rate-limited or failed requests, with exponential backoff
+    #[serde(default = "default_max_retries")]
+    #[prefer(default)]
+    pub max_retries: u32,
 }
 
 /// Device-level LLM config (from env vars, varies per device).
@@ -97,6 +111,22 @@ pub struct LlmDeviceConfig {
     pub model: String,
     /// API key for OpenAI-compatible providers
     pub api_key: Option<String>,
+    /// Requests in flight at once
+    pub max_concurrent: usize,
+    /// Request rate ceiling (None for unlimited)
+    pub requests_per_minute: Option<u32>,
+}
+
+/// Backend settings a config profile may give in its `llm` table, in
+/// place of the environment's.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct LlmBackendProfile {
+    pub provider: Option<String>,
+    pub endpoint: Option<String>,
+    pub model: Option<String>,
+    pub api_key: Option<String>,
+    pub max_concurrent: Option<usize>,
+    pub requests_per_minute: Option<u32>,
 }
 
 /// Combined LLM configuration (runtime).
@@ -166,6 +196,18 @@ fn default_max_content_chars() -> usize {
     12000
 }
 
+fn default_batch_size() -> usize {
+    8
+}
+
+fn default_batch_max_chars() -> usize {
+    3000
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
 // === LlmAppConfig implementations ===
 
 impl Default for LlmAppConfig {
@@ -177,6 +219,9 @@ impl Default for LlmAppConfig {
             synopsis_prompt: None,
             tags_prompt: None,
             max_content_chars: default_max_content_chars(),
+            batch_size: default_batch_size(),
+            batch_max_chars: default_batch_max_chars(),
+            max_retries: default_max_retries(),
         }
     }
 }
@@ -198,6 +243,25 @@ impl LlmAppConfig {
     pub fn get_tags_prompt(&self) -> &str {
         self.tags_prompt.as_deref().unwrap_or(DEFAULT_TAGS_PROMPT)
     }
+
+    /// Identifies the prompts in effect: [`PROMPT_VERSION`] for the
+    /// built-in prompts, or `custom-` and a short hash of the custom ones.
+    pub fn prompt_version(&self) -> String {
+        if self.synopsis_prompt.is_none() && self.tags_prompt.is_none() {
+            return PROMPT_VERSION.to_string();
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(self.get_synopsis_prompt());
+        hasher.update([0u8]);
+        hasher.update(self.get_tags_prompt());
+        format!("custom-{}", &hex::encode(hasher.finalize())[..8])
+    }
+
+    /// Whether short documents may share a request. Custom prompts
+    /// can't be merged into the combined prompt, so they turn batching off.
+    pub fn batching_enabled(&self) -> bool {
+        self.batch_size > 1 && self.synopsis_prompt.is_none() && self.tags_prompt.is_none()
+    }
 }
 
 // === LlmDeviceConfig implementations ===
@@ -216,12 +280,16 @@ impl LlmDeviceConfig {
     /// - ANNOTATE_MODEL / LLM_MODEL: model ID
     /// - ANNOTATE_ENDPOINT / LLM_ENDPOINT: API base URL
     /// - ANNOTATE_API_KEY / LLM_API_KEY: API key
+    /// - ANNOTATE_CONCURRENCY: requests in flight at once
+    /// - ANNOTATE_RPM: requests per minute (0 for unlimited)
     pub fn from_env() -> Self {
         let mut config = Self {
             provider: LlmProvider::default(),
             endpoint: default_endpoint(),
             model: default_model(),
             api_key: None,
+            max_concurrent: 1,
+            requests_per_minute: None,
         };
 
         // Check if provider is explicitly set
@@ -319,9 +387,85 @@ impl LlmDeviceConfig {
             config.model = model;
         }
 
+        (config.max_concurrent, config.requests_per_minute) = config.default_budget();
+        if let Some(n) = std::env::var("ANNOTATE_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+        {
+            config.max_concurrent = n.max(1);
+        }
+        if let Some(rpm) = std::env::var("ANNOTATE_RPM")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+        {
+            config.requests_per_minute = (rpm > 0).then_some(rpm);
+        }
+
         config
     }
 
+    /// Switch to the backend a config profile names. Naming only a
+    /// provider brings that provider's default endpoint, model, budget and
+    /// API key variable.
+    pub fn apply_profile(&mut self, profile: LlmBackendProfile) -> Result<(), String> {
+        let budget_changed = profile.provider.is_some() || profile.endpoint.is_some();
+        if let Some(name) = profile.provider {
+            self.provider = LlmProvider::from_str(&name)
+                .ok_or_else(|| format!("unknown LLM provider '{}'", name))?;
+            (self.endpoint, self.model) = match name.to_lowercase().as_str() {
+                "groq" => (
+                    "https://api.groq.com/openai".to_string(),
+                    "llama-3.3-70b-versatile".to_string(),
+                ),
+                "openai" => (
+                    "https://api.openai.com".to_string(),
+                    "gpt-4o-mini".to_string(),
+                ),
+                "together" => (
+                    "https://api.together.xyz".to_string(),
+                    "meta-llama/Meta-Llama-3.1-70B-Instruct-Turbo".to_string(),
+                ),
+                _ => (default_endpoint(), default_model()),
+            };
+            self.api_key = match name.to_lowercase().as_str() {
+                "groq" => std::env::var("GROQ_API_KEY").ok(),
+                "openai" => std::env::var("OPENAI_API_KEY").ok(),
+                _ => None,
+            };
+        }
+        if let Some(endpoint) = profile.endpoint {
+            self.endpoint = endpoint;
+        }
+        if let Some(model) = profile.model {
+            self.model = model;
+        }
+        if profile.api_key.is_some() {
+            self.api_key = profile.api_key;
+        }
+        if budget_changed {
+            (self.max_concurrent, self.requests_per_minute) = self.default_budget();
+        }
+        if let Some(n) = profile.max_concurrent {
+            self.max_concurrent = n.max(1);
+        }
+        if let Some(rpm) = profile.requests_per_minute {
+            self.requests_per_minute = (rpm > 0).then_some(rpm);
+        }
+        Ok(())
+    }
+
+    /// Provider-specific `(max_concurrent, requests_per_minute)`.
+    ///
+    /// A local Ollama instance works through one prompt at a time; Groq's
+    /// free tier allows 30 requests a minute; paid APIs take a few at once.
+    pub fn default_budget(&self) -> (usize, Option<u32>) {
+        match self.provider {
+            LlmProvider::Ollama => (1, None),
+            LlmProvider::OpenAI if self.endpoint.contains("groq.com") => (2, Some(30)),
+            LlmProvider::OpenAI => (4, None),
+        }
+    }
+
     /// Get the provider name for display.
     pub fn provider_name(&self) -> &'static str {
         match self.provider {
@@ -394,8 +538,12 @@ impl LlmConfig {
         &self.device.model
     }
 
-    pub fn api_key(&self) -> Option<&str> {
-        self.device.api_key.as_deref()
+    /// API key, with `secret://` references resolved.
+    pub fn api_key(&self) -> Option<String> {
+        self.device
+            .api_key
+            .as_deref()
+            .and_then(crate::secrets::resolve_or_warn)
     }
 
     pub fn max_tokens(&self) -> u32 {
@@ -410,6 +558,35 @@ impl LlmConfig {
         self.app.max_content_chars
     }
 
+    pub fn max_retries(&self) -> u32 {
+        self.app.max_retries
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.device.max_concurrent.max(1)
+    }
+
+    pub fn requests_per_minute(&self) -> Option<u32> {
+        self.device.requests_per_minute
+    }
+
+    /// Documents per combined summarization request; 1 when the provider
+    /// or configuration doesn't allow batching.
+    ///
+    /// Only OpenAI-compatible chat models batch: small local models lose
+    /// track of which summary belongs to which document.
+    pub fn batch_size(&self) -> usize {
+        if self.app.batching_enabled() && matches!(self.device.provider, LlmProvider::OpenAI) {
+            self.app.batch_size
+        } else {
+            1
+        }
+    }
+
+    pub fn batch_max_chars(&self) -> usize {
+        self.app.batch_max_chars
+    }
+
     pub fn get_synopsis_prompt(&self) -> &str {
         self.app.get_synopsis_prompt()
     }
@@ -418,6 +595,10 @@ impl LlmConfig {
         self.app.get_tags_prompt()
     }
 
+    pub fn prompt_version(&self) -> String {
+        self.app.prompt_version()
+    }
+
     pub fn provider_name(&self) -> &'static str {
         self.device.provider_name()
     }
@@ -472,6 +653,7 @@ impl LlmConfigLegacy {
             synopsis_prompt: self.synopsis_prompt,
             tags_prompt: self.tags_prompt,
             max_content_chars: self.max_content_chars,
+            ..LlmAppConfig::default()
         };
         // Device config always comes from env, ignoring legacy provider/endpoint/model/key
         let device = LlmDeviceConfig::from_env();
@@ -487,6 +669,7 @@ impl LlmConfigLegacy {
             synopsis_prompt: self.synopsis_prompt.clone(),
             tags_prompt: self.tags_prompt.clone(),
             max_content_chars: self.max_content_chars,
+            ..LlmAppConfig::default()
         }
     }
 }
@@ -4,18 +4,24 @@
 
 #![allow(dead_code)]
 
+mod budget;
 mod config;
 mod prompts;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+use budget::{backoff, RequestBudget};
 
 use crate::http_client::HttpClient;
+use crate::models::RecordType;
 use crate::privacy::PrivacyConfig;
+use crate::services::titles::clean_title;
 
-pub use config::{LlmConfig, LlmProvider};
+pub use config::{LlmBackendProfile, LlmConfig, LlmProvider};
 
 /// Result of summarizing a document.
 #[derive(Debug, Clone)]
@@ -26,10 +32,18 @@ pub struct SummarizeResult {
     pub tags: Vec<String>,
 }
 
+/// A document to summarize as part of a combined request.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchItem<'a> {
+    pub title: &'a str,
+    pub text: &'a str,
+}
+
 /// LLM client for document processing.
 pub struct LlmClient {
     config: LlmConfig,
     privacy: Option<PrivacyConfig>,
+    budget: Arc<RequestBudget>,
 }
 
 // ============================================================================
@@ -99,6 +113,7 @@ impl LlmClient {
     /// via the HttpClient defaults.
     pub fn new(config: LlmConfig) -> Self {
         Self {
+            budget: Self::budget_for(&config),
             config,
             privacy: None,
         }
@@ -110,11 +125,19 @@ impl LlmClient {
     /// Local Ollama instances are not affected by privacy settings.
     pub fn with_privacy(config: LlmConfig, privacy: PrivacyConfig) -> Self {
         Self {
+            budget: Self::budget_for(&config),
             config,
             privacy: Some(privacy),
         }
     }
 
+    fn budget_for(config: &LlmConfig) -> Arc<RequestBudget> {
+        Arc::new(RequestBudget::new(
+            config.max_concurrent(),
+            config.requests_per_minute(),
+        ))
+    }
+
     /// Get the config.
     pub fn config(&self) -> &LlmConfig {
         &self.config
@@ -295,6 +318,156 @@ impl LlmClient {
         Ok(SummarizeResult { synopsis, tags })
     }
 
+    /// Summarize several short documents with one request.
+    ///
+    /// Returns one entry per item, in order; `None` where the response had
+    /// no usable summary for that document, so the caller can retry it alone.
+    pub async fn summarize_batch(
+        &self,
+        items: &[BatchItem<'_>],
+    ) -> Result<Vec<Option<SummarizeResult>>, LlmError> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let documents: String = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                format!(
+                    "=== Document {} ===\nTitle: {}\n\n{}\n\n",
+                    i + 1,
+                    item.title,
+                    self.truncate_content(item.text)
+                )
+            })
+            .collect();
+        let prompt = prompts::DEFAULT_BATCH_PROMPT
+            .replace("{count}", &items.len().to_string())
+            .replace("{documents}", &documents);
+
+        info!("Summarizing {} documents in one request", items.len());
+        let response = self.call_llm(&prompt).await?;
+        Ok(self.parse_batch_response(&response, items.len()))
+    }
+
+    /// Classify a document's record type, with the model's confidence.
+    pub async fn classify_record_type(
+        &self,
+        text: &str,
+        title: &str,
+    ) -> Result<(RecordType, f32), LlmError> {
+        let prompt = prompts::DEFAULT_CLASSIFY_PROMPT
+            .replace("{title}", title)
+            .replace("{content}", self.truncate_content(text));
+
+        debug!("Classifying record type for: {}", title);
+        let response = self.call_llm(&prompt).await?;
+        parse_classification(&response).ok_or_else(|| {
+            LlmError::Parse(format!(
+                "No record type in response: {}",
+                response.chars().take(200).collect::<String>()
+            ))
+        })
+    }
+
+    /// Suggest a descriptive title for a document titled after its file name.
+    pub async fn suggest_title(&self, text: &str, title: &str) -> Result<String, LlmError> {
+        let prompt = prompts::DEFAULT_TITLE_PROMPT
+            .replace("{title}", title)
+            .replace("{content}", self.truncate_content(text));
+
+        debug!("Suggesting title for: {}", title);
+        let response = self.call_llm(&prompt).await?;
+        let line = response
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .unwrap_or_default();
+        let line = line
+            .strip_prefix("Title:")
+            .or_else(|| line.strip_prefix("title:"))
+            .unwrap_or(line);
+        let suggested = clean_title(line);
+        if suggested.is_empty() {
+            return Err(LlmError::Parse("Empty title response".to_string()));
+        }
+        Ok(suggested)
+    }
+
+    /// Choose the most faithful of several OCR readings of one page.
+    ///
+    /// Returns the index of the chosen reading and the model's reason.
+    pub async fn judge_ocr(&self, readings: &[&str]) -> Result<(usize, String), LlmError> {
+        let per_reading = self.config.max_content_chars() / readings.len().max(1);
+        let numbered = readings
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let end = text
+                    .char_indices()
+                    .nth(per_reading)
+                    .map_or(text.len(), |(end, _)| end);
+                format!("Reading {}:\n{}", i + 1, &text[..end])
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let prompt = prompts::DEFAULT_OCR_JUDGE_PROMPT.replace("{readings}", &numbered);
+
+        debug!("Judging {} OCR readings", readings.len());
+        let response = self.call_llm(&prompt).await?;
+        let mut lines = response.lines().map(str::trim).filter(|l| !l.is_empty());
+        let choice = lines
+            .next()
+            .and_then(|l| {
+                l.split(|c: char| !c.is_ascii_digit())
+                    .find(|n| !n.is_empty())
+                    .and_then(|n| n.parse::<usize>().ok())
+            })
+            .filter(|n| (1..=readings.len()).contains(n))
+            .ok_or_else(|| {
+                LlmError::Parse(format!(
+                    "No reading number in response: {}",
+                    response.chars().take(200).collect::<String>()
+                ))
+            })?;
+        let reason = lines.collect::<Vec<_>>().join(" ");
+        Ok((choice - 1, reason))
+    }
+
+    /// Whether `page` begins a new record rather than continuing the one
+    /// that `previous` ends, for splitting multi-document bundles.
+    pub async fn starts_new_document(&self, previous: &str, page: &str) -> Result<bool, LlmError> {
+        let half = self.config.max_content_chars() / 2;
+        let tail_start = previous
+            .char_indices()
+            .rev()
+            .nth(half)
+            .map_or(0, |(start, _)| start);
+        let head_end = page
+            .char_indices()
+            .nth(half)
+            .map_or(page.len(), |(end, _)| end);
+        let prompt = prompts::DEFAULT_BOUNDARY_PROMPT
+            .replace("{previous}", &previous[tail_start..])
+            .replace("{page}", &page[..head_end]);
+
+        debug!("Asking whether a bundle page starts a new document");
+        let response = self.call_llm(&prompt).await?;
+        let answer = response
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .find(|w| !w.is_empty())
+            .map(str::to_ascii_uppercase);
+        match answer.as_deref() {
+            Some("YES") => Ok(true),
+            Some("NO") => Ok(false),
+            _ => Err(LlmError::Parse(format!(
+                "No YES/NO in response: {}",
+                response.chars().take(200).collect::<String>()
+            ))),
+        }
+    }
+
     /// Expand search terms using LLM to generate related terms.
     /// Takes seed terms and a domain description, returns expanded list.
     pub async fn expand_search_terms(
@@ -358,10 +531,31 @@ Focus on terms specifically relevant to {domain}. Return ONLY a comma-separated
     }
 
     /// Call LLM API with a prompt (provider-aware).
+    ///
+    /// Each attempt waits for the request budget; rate limits, server errors
+    /// and dropped connections are retried with exponential backoff.
     async fn call_llm(&self, prompt: &str) -> Result<String, LlmError> {
-        match self.config.provider() {
-            LlmProvider::Ollama => self.call_ollama(prompt).await,
-            LlmProvider::OpenAI => self.call_openai(prompt).await,
+        let mut attempt = 0;
+        loop {
+            let result = {
+                let _permit = self.budget.acquire().await;
+                match self.config.provider() {
+                    LlmProvider::Ollama => self.call_ollama(prompt).await,
+                    LlmProvider::OpenAI => self.call_openai(prompt).await,
+                }
+            };
+            match result {
+                Err(e) if e.is_transient() && attempt < self.config.max_retries() => {
+                    attempt += 1;
+                    let delay = backoff(attempt);
+                    warn!(
+                        "LLM request failed ({}), retry {} in {:?}",
+                        e, attempt, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                other => return other,
+            }
         }
     }
 
@@ -390,7 +584,7 @@ Focus on terms specifically relevant to {domain}. Return ONLY a comma-separated
         if !resp.status.is_success() {
             let status = resp.status;
             let body = resp.text().await.unwrap_or_default();
-            return Err(LlmError::Api(format!("HTTP {}: {}", status, body)));
+            return Err(LlmError::from_status(status, body));
         }
 
         let ollama_resp: OllamaResponse = resp
@@ -431,7 +625,7 @@ Focus on terms specifically relevant to {domain}. Return ONLY a comma-separated
         if !resp.status.is_success() {
             let status = resp.status;
             let body = resp.text().await.unwrap_or_default();
-            return Err(LlmError::Api(format!("HTTP {}: {}", status, body)));
+            return Err(LlmError::from_status(status, body));
         }
 
         let openai_resp: OpenAIResponse = resp
@@ -447,6 +641,49 @@ Focus on terms specifically relevant to {domain}. Return ONLY a comma-separated
             .ok_or_else(|| LlmError::Parse("No response choices".to_string()))
     }
 
+    /// Parse a combined summarization response: a JSON array of
+    /// `{"id", "synopsis", "tags"}` objects, possibly wrapped in prose or a
+    /// code fence.
+    fn parse_batch_response(&self, response: &str, count: usize) -> Vec<Option<SummarizeResult>> {
+        #[derive(Deserialize)]
+        struct Entry {
+            id: usize,
+            synopsis: String,
+            #[serde(default)]
+            tags: serde_json::Value,
+        }
+
+        let mut results = vec![None; count];
+        let json = match (response.find('['), response.rfind(']')) {
+            (Some(start), Some(end)) if start < end => &response[start..=end],
+            _ => return results,
+        };
+        let entries: Vec<serde_json::Value> = serde_json::from_str(json).unwrap_or_default();
+        for value in entries {
+            let Ok(entry) = serde_json::from_value::<Entry>(value) else {
+                continue;
+            };
+            let synopsis = entry.synopsis.trim().to_string();
+            if entry.id == 0 || entry.id > count || synopsis.is_empty() {
+                continue;
+            }
+            // Tags come back as a list or as a comma-separated string
+            let tags = match entry.tags {
+                serde_json::Value::Array(items) => self.parse_tags(
+                    &items
+                        .iter()
+                        .filter_map(|t| t.as_str())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                ),
+                serde_json::Value::String(s) => self.parse_tags(&s),
+                _ => Vec::new(),
+            };
+            results[entry.id - 1] = Some(SummarizeResult { synopsis, tags });
+        }
+        results
+    }
+
     /// Parse tags from LLM response.
     fn parse_tags(&self, response: &str) -> Vec<String> {
         // Remove common prefixes/formatting
@@ -475,6 +712,30 @@ Focus on terms specifically relevant to {domain}. Return ONLY a comma-separated
     }
 }
 
+/// Parse a `{"type", "confidence"}` classification, possibly wrapped in prose
+/// or a code fence. A bare type name is accepted with middling confidence.
+fn parse_classification(response: &str) -> Option<(RecordType, f32)> {
+    #[derive(Deserialize)]
+    struct Answer {
+        #[serde(rename = "type")]
+        record_type: String,
+        #[serde(default)]
+        confidence: Option<f32>,
+    }
+
+    if let (Some(start), Some(end)) = (response.find('{'), response.rfind('}')) {
+        if start < end {
+            if let Ok(answer) = serde_json::from_str::<Answer>(&response[start..=end]) {
+                let record_type = RecordType::from_str(&answer.record_type)?;
+                let confidence = answer.confidence.unwrap_or(0.5).clamp(0.0, 1.0);
+                return Some((record_type, confidence));
+            }
+        }
+    }
+    let word = response.trim().trim_matches(|c: char| !c.is_alphanumeric());
+    RecordType::from_str(word).map(|t| (t, 0.5))
+}
+
 /// Errors that can occur during LLM operations.
 #[derive(Debug, thiserror::Error)]
 pub enum LlmError {
@@ -488,6 +749,26 @@ pub enum LlmError {
     ModelNotFound(String),
     #[error("LLM is disabled")]
     Disabled,
+    /// Rate limited or a server-side failure; worth retrying later.
+    #[error("Temporary API error: {0}")]
+    Transient(String),
+}
+
+impl LlmError {
+    /// Classify a non-success HTTP response.
+    fn from_status(status: reqwest::StatusCode, body: String) -> Self {
+        let message = format!("HTTP {}: {}", status, body);
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            Self::Transient(message)
+        } else {
+            Self::Api(message)
+        }
+    }
+
+    /// Whether the request may succeed if tried again later.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Connection(_) | Self::Transient(_))
+    }
 }
 
 #[cfg(test)]
@@ -527,6 +808,51 @@ mod tests {
         assert_eq!(tags, vec!["cia", "mkultra", "cold-war", "memo"]);
     }
 
+    #[test]
+    fn test_parse_batch_response() {
+        let client = LlmClient::new(LlmConfig::default());
+        let response = r#"Here you go:
+```json
+[
+  {"id": 2, "synopsis": "A cable on embassy security.", "tags": ["state-dept", "Cable"]},
+  {"id": 1, "synopsis": "FBI memo about a wiretap.", "tags": "fbi, surveillance"},
+  {"id": 7, "synopsis": "Out of range", "tags": []}
+]
+```"#;
+        let results = client.parse_batch_response(response, 3);
+        assert_eq!(results.len(), 3);
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.synopsis, "FBI memo about a wiretap.");
+        assert_eq!(first.tags, vec!["fbi", "surveillance"]);
+        assert_eq!(
+            results[1].as_ref().unwrap().tags,
+            vec!["state-dept", "cable"]
+        );
+        assert!(results[2].is_none());
+
+        assert!(client
+            .parse_batch_response("not json", 2)
+            .iter()
+            .all(Option::is_none));
+    }
+
+    #[test]
+    fn test_parse_classification() {
+        assert_eq!(
+            parse_classification("```json\n{\"type\": \"Memo\", \"confidence\": 0.85}\n```"),
+            Some((RecordType::Memo, 0.85))
+        );
+        assert_eq!(
+            parse_classification("{\"type\": \"photo\", \"confidence\": 3}"),
+            Some((RecordType::Photo, 1.0))
+        );
+        assert_eq!(
+            parse_classification("invoice."),
+            Some((RecordType::Invoice, 0.5))
+        );
+        assert_eq!(parse_classification("{\"type\": \"spreadsheet\"}"), None);
+    }
+
     #[test]
     fn test_default_config() {
         let config = LlmConfig::default();
@@ -1,5 +1,11 @@
 //! Default LLM prompts for document analysis.
 
+/// Version of the built-in prompts, recorded with each synopsis.
+///
+/// Bump when any default prompt below changes so summaries written with the
+/// old wording can be selected by `annotate resummarize --prompt-version`.
+pub const PROMPT_VERSION: &str = "2";
+
 /// Default prompt for generating document synopsis.
 pub const DEFAULT_SYNOPSIS_PROMPT: &str = r#"You are analyzing a FOIA (Freedom of Information Act) document. Read the ENTIRE content and identify the MAIN SUBJECT and KEY FINDINGS - not just what's in the introduction.
 
@@ -41,3 +47,68 @@ Document Content:
 {content}
 
 Respond with ONLY 3-5 comma-separated lowercase tags. Example: cia, mind-control, mkultra, memo, cold-war"#;
+
+/// Prompt for summarizing several short documents in one request.
+/// `{count}` and `{documents}` are filled in by the client.
+pub const DEFAULT_BATCH_PROMPT: &str = r#"You are analyzing {count} short FOIA (Freedom of Information Act) documents. Summarize EACH document on its own; do not mix up facts between documents.
+
+For each document write:
+- "synopsis": 2-3 sentences on the document's main subject and key facts (dates, names, actions, decisions)
+- "tags": 3-5 specific lowercase search tags, hyphenated for multiple words (agencies, programs, subjects, document type), e.g. "cia", "cold-war", "memo"
+
+{documents}Respond with ONLY a JSON array containing one object per document, in order, with no other text:
+[{"id": 1, "synopsis": "...", "tags": ["...", "..."]}]"#;
+
+/// Prompt for classifying a document's record type.
+pub const DEFAULT_CLASSIFY_PROMPT: &str = r#"You are sorting FOIA (Freedom of Information Act) records by the KIND of record they are, not their subject.
+
+Choose exactly one type:
+- letter: correspondence with a salutation and signature
+- memo: internal memorandum with TO/FROM/SUBJECT headers
+- email: email message or printed email thread
+- form: a fill-in form, application or questionnaire
+- invoice: invoice, bill, receipt or purchase order
+- report: report, study, briefing, minutes or other narrative document
+- photo: photograph or image with little or no text
+- map: map, plat, site plan or drawing
+
+Document Title: {title}
+
+Document Content:
+{content}
+
+Respond with ONLY a JSON object and no other text: {"type": "memo", "confidence": 0.8}"#;
+
+/// Prompt for suggesting a title for a document whose title is a file name.
+pub const DEFAULT_TITLE_PROMPT: &str = r#"You are cataloguing FOIA (Freedom of Information Act) records. This document's title came from its file name or URL and says nothing about it.
+
+Write a short, descriptive title for the document (at most 12 words), the way an archivist would: name the kind of record, who produced it and its subject. Do not invent facts not in the text.
+
+Current Title: {title}
+
+Document Content:
+{content}
+
+Respond with ONLY the title and no other text."#;
+
+/// Prompt for choosing between OCR readings of one page.
+pub const DEFAULT_OCR_JUDGE_PROMPT: &str = r#"Several OCR engines read the same scanned page of a FOIA (Freedom of Information Act) record. Their readings follow, numbered.
+
+Pick the reading that most faithfully transcribes the page: real words, sensible sentences, names and numbers that are consistent, and nothing missing or invented. Ignore differences in line breaks and spacing.
+
+{readings}
+
+Respond with the number of the best reading on the first line, then one sentence saying why."#;
+
+/// Prompt for deciding whether a page of a bundle starts a new document.
+pub const DEFAULT_BOUNDARY_PROMPT: &str = r#"A FOIA (Freedom of Information Act) release bundles many separate records (letters, memos, forms, emails) into one PDF. Below are the end of one page and the start of the page after it.
+
+Does the second page begin a new record, or does it continue the record on the first page? New records usually start with a letterhead, a memo or email header, a form title, or a new date and addressee; continuations pick up mid-sentence, repeat a running header, or carry on a numbered list or page count.
+
+End of the first page:
+{previous}
+
+Start of the second page:
+{page}
+
+Respond with YES if the second page begins a new record or NO if it continues the first, on the first line, then one sentence saying why."#;
@@ -4,4 +4,4 @@
 
 mod client;
 
-pub use client::{LlmClient, LlmConfig};
+pub use client::{BatchItem, LlmBackendProfile, LlmClient, LlmConfig, LlmError, SummarizeResult};
@@ -0,0 +1,52 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0015_page_highlights")
+        .depends_on(&["0014_search_indexes"])
+        // User-selected text ranges within a page, with optional comment/label
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS page_highlights (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER,
+    page_number INTEGER NOT NULL,
+    start_offset INTEGER NOT NULL,
+    end_offset INTEGER NOT NULL,
+    selected_text TEXT NOT NULL,
+    comment TEXT,
+    label TEXT,
+    created_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS page_highlights (
+    id SERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER,
+    page_number INTEGER NOT NULL,
+    start_offset INTEGER NOT NULL,
+    end_offset INTEGER NOT NULL,
+    selected_text TEXT NOT NULL,
+    comment TEXT,
+    label TEXT,
+    created_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        // Highlights are always fetched per document, ordered by page
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_page_highlights_doc_page ON page_highlights(document_id, page_number)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_page_highlights_doc_page ON page_highlights(document_id, page_number)",
+                ),
+        )
+}
@@ -0,0 +1,12 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0016_version_lookup_index")
+        .depends_on(&["0001_initial_schema"])
+        .operation(AddIndex::new(
+            "document_versions",
+            Index::new("idx_versions_document_acquired")
+                .column("document_id")
+                .column("acquired_at"),
+        ))
+}
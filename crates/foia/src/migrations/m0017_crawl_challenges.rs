@@ -0,0 +1,51 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0017_crawl_challenges")
+        .depends_on(&["0016_version_lookup_index"])
+        // CAPTCHA/anti-bot challenges awaiting an operator; a pending row
+        // pauses requests to its domain until it is solved or dismissed
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS crawl_challenges (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_id TEXT NOT NULL,
+    domain TEXT NOT NULL,
+    url TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    detected_at TEXT NOT NULL,
+    resolved_at TEXT,
+    cookies TEXT
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS crawl_challenges (
+    id SERIAL PRIMARY KEY,
+    source_id TEXT NOT NULL,
+    domain TEXT NOT NULL,
+    url TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    detected_at TEXT NOT NULL,
+    resolved_at TEXT,
+    cookies TEXT
+)"#,
+                ),
+        )
+        // Every request checks the latest challenge for its domain
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_crawl_challenges_domain ON crawl_challenges(domain, id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_crawl_challenges_domain ON crawl_challenges(domain, id)",
+                ),
+        )
+}
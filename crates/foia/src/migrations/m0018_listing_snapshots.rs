@@ -0,0 +1,51 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0018_listing_snapshots")
+        .depends_on(&["0017_crawl_challenges"])
+        // Archived HTML of listing/index pages; a new row is stored only when
+        // the page content changes, otherwise last_seen_at is bumped
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS listing_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    content TEXT NOT NULL,
+    link_count INTEGER NOT NULL DEFAULT 0,
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    fetch_count INTEGER NOT NULL DEFAULT 1
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS listing_snapshots (
+    id SERIAL PRIMARY KEY,
+    source_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    content TEXT NOT NULL,
+    link_count INTEGER NOT NULL DEFAULT 0,
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    fetch_count INTEGER NOT NULL DEFAULT 1
+)"#,
+                ),
+        )
+        // Each fetch compares against the latest snapshot of its URL
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_listing_snapshots_url ON listing_snapshots(source_id, url, id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_listing_snapshots_url ON listing_snapshots(source_id, url, id)",
+                ),
+        )
+}
@@ -0,0 +1,40 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0019_api_schemas")
+        .depends_on(&["0018_listing_snapshots"])
+        // Last seen JSON shape of each API endpoint a source discovers from;
+        // drift is set when the shape changes or extraction stops matching
+        // and stays until an operator acknowledges it
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS api_schemas (
+    source_id TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    paths TEXT NOT NULL DEFAULT '[]',
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    drift TEXT,
+    drift_at TEXT,
+    PRIMARY KEY (source_id, endpoint)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS api_schemas (
+    source_id TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    paths TEXT NOT NULL DEFAULT '[]',
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    drift TEXT,
+    drift_at TEXT,
+    PRIMARY KEY (source_id, endpoint)
+)"#,
+                ),
+        )
+}
@@ -0,0 +1,41 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0020_lost_files")
+        .depends_on(&["0019_api_schemas"])
+        // Versions whose stored file is gone and could not be re-fetched
+        // (source removed it or now serves different bytes); their
+        // extracted text and pages are kept
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS lost_files (
+    version_id INTEGER PRIMARY KEY,
+    document_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    lost_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS lost_files (
+    version_id INTEGER PRIMARY KEY,
+    document_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    lost_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_lost_files_document ON lost_files(document_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_lost_files_document ON lost_files(document_id)",
+                ),
+        )
+}
@@ -0,0 +1,480 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0021_stats_tables")
+        .depends_on(&["0020_lost_files"])
+        // Aggregates maintained by triggers so dashboards read a handful of
+        // rows instead of scanning documents on every load
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS source_status_counts (
+    source_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (source_id, status)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS source_status_counts (
+    source_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (source_id, status)
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS tag_counts (
+    tag TEXT PRIMARY KEY,
+    count INTEGER NOT NULL DEFAULT 0
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS tag_counts (
+    tag TEXT PRIMARY KEY,
+    count BIGINT NOT NULL DEFAULT 0
+)"#,
+                ),
+        )
+        // Documents by the MIME type of their newest version
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS mime_type_counts (
+    mime_type TEXT PRIMARY KEY,
+    count INTEGER NOT NULL DEFAULT 0
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS mime_type_counts (
+    mime_type TEXT PRIMARY KEY,
+    count BIGINT NOT NULL DEFAULT 0
+)"#,
+                ),
+        )
+        // Backfill from existing documents
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"INSERT INTO source_status_counts (source_id, status, count)
+SELECT source_id, status, COUNT(*) FROM documents WHERE true
+GROUP BY source_id, status
+ON CONFLICT(source_id, status) DO UPDATE SET count = excluded.count"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"INSERT INTO source_status_counts (source_id, status, count)
+SELECT source_id, status, COUNT(*) FROM documents
+GROUP BY source_id, status
+ON CONFLICT(source_id, status) DO UPDATE SET count = EXCLUDED.count"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"INSERT INTO tag_counts (tag, count)
+SELECT value, COUNT(DISTINCT documents.id)
+FROM documents, json_each(CASE WHEN json_valid(documents.tags) THEN documents.tags ELSE '[]' END)
+WHERE true
+GROUP BY value
+ON CONFLICT(tag) DO UPDATE SET count = excluded.count"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"INSERT INTO tag_counts (tag, count)
+SELECT tag, COUNT(DISTINCT documents.id)
+FROM documents, jsonb_array_elements_text(documents.tags::jsonb) AS tag
+WHERE documents.tags IS NOT NULL AND documents.tags != '[]'
+GROUP BY tag
+ON CONFLICT(tag) DO UPDATE SET count = EXCLUDED.count"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"INSERT INTO mime_type_counts (mime_type, count)
+SELECT dv.mime_type, COUNT(*)
+FROM document_versions dv
+INNER JOIN (
+    SELECT document_id, MAX(id) AS max_id
+    FROM document_versions
+    GROUP BY document_id
+) latest ON dv.id = latest.max_id
+WHERE true
+GROUP BY dv.mime_type
+ON CONFLICT(mime_type) DO UPDATE SET count = excluded.count"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"INSERT INTO mime_type_counts (mime_type, count)
+SELECT dv.mime_type, COUNT(*)
+FROM document_versions dv
+INNER JOIN (
+    SELECT document_id, MAX(id) AS max_id
+    FROM document_versions
+    GROUP BY document_id
+) latest ON dv.id = latest.max_id
+GROUP BY dv.mime_type
+ON CONFLICT(mime_type) DO UPDATE SET count = EXCLUDED.count"#,
+                ),
+        )
+        // Per-source status and tag counts follow document rows
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_stats_documents_insert
+AFTER INSERT ON documents
+BEGIN
+    INSERT INTO source_status_counts (source_id, status, count)
+    VALUES (NEW.source_id, NEW.status, 1)
+    ON CONFLICT(source_id, status) DO UPDATE SET count = count + 1;
+    INSERT INTO tag_counts (tag, count)
+    SELECT DISTINCT value, 1
+    FROM json_each(CASE WHEN json_valid(NEW.tags) THEN NEW.tags ELSE '[]' END)
+    WHERE true
+    ON CONFLICT(tag) DO UPDATE SET count = count + 1;
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION update_stats_documents_insert()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO source_status_counts (source_id, status, count)
+    VALUES (NEW.source_id, NEW.status, 1)
+    ON CONFLICT(source_id, status) DO UPDATE SET count = source_status_counts.count + 1;
+    IF NEW.tags IS NOT NULL AND NEW.tags != '[]' THEN
+        INSERT INTO tag_counts (tag, count)
+        SELECT DISTINCT tag, 1 FROM jsonb_array_elements_text(NEW.tags::jsonb) AS tag
+        ON CONFLICT(tag) DO UPDATE SET count = tag_counts.count + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_stats_documents_delete
+AFTER DELETE ON documents
+BEGIN
+    UPDATE source_status_counts SET count = count - 1
+    WHERE source_id = OLD.source_id AND status = OLD.status;
+    UPDATE tag_counts SET count = count - 1
+    WHERE tag IN (SELECT value FROM json_each(CASE WHEN json_valid(OLD.tags) THEN OLD.tags ELSE '[]' END));
+    DELETE FROM tag_counts WHERE count <= 0;
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION update_stats_documents_delete()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE source_status_counts SET count = count - 1
+    WHERE source_id = OLD.source_id AND status = OLD.status;
+    IF OLD.tags IS NOT NULL AND OLD.tags != '[]' THEN
+        UPDATE tag_counts SET count = count - 1
+        WHERE tag IN (SELECT jsonb_array_elements_text(OLD.tags::jsonb));
+        DELETE FROM tag_counts WHERE count <= 0;
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_stats_documents_status
+AFTER UPDATE OF source_id, status ON documents
+WHEN OLD.source_id IS NOT NEW.source_id OR OLD.status IS NOT NEW.status
+BEGIN
+    UPDATE source_status_counts SET count = count - 1
+    WHERE source_id = OLD.source_id AND status = OLD.status;
+    INSERT INTO source_status_counts (source_id, status, count)
+    VALUES (NEW.source_id, NEW.status, 1)
+    ON CONFLICT(source_id, status) DO UPDATE SET count = count + 1;
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION update_stats_documents_status()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.source_id IS DISTINCT FROM NEW.source_id OR OLD.status IS DISTINCT FROM NEW.status THEN
+        UPDATE source_status_counts SET count = count - 1
+        WHERE source_id = OLD.source_id AND status = OLD.status;
+        INSERT INTO source_status_counts (source_id, status, count)
+        VALUES (NEW.source_id, NEW.status, 1)
+        ON CONFLICT(source_id, status) DO UPDATE SET count = source_status_counts.count + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_stats_documents_tags
+AFTER UPDATE OF tags ON documents
+WHEN OLD.tags IS NOT NEW.tags
+BEGIN
+    UPDATE tag_counts SET count = count - 1
+    WHERE tag IN (SELECT value FROM json_each(CASE WHEN json_valid(OLD.tags) THEN OLD.tags ELSE '[]' END));
+    INSERT INTO tag_counts (tag, count)
+    SELECT DISTINCT value, 1
+    FROM json_each(CASE WHEN json_valid(NEW.tags) THEN NEW.tags ELSE '[]' END)
+    WHERE true
+    ON CONFLICT(tag) DO UPDATE SET count = count + 1;
+    DELETE FROM tag_counts WHERE count <= 0;
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION update_stats_documents_tags()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.tags IS DISTINCT FROM NEW.tags THEN
+        IF OLD.tags IS NOT NULL AND OLD.tags != '[]' THEN
+            UPDATE tag_counts SET count = count - 1
+            WHERE tag IN (SELECT jsonb_array_elements_text(OLD.tags::jsonb));
+        END IF;
+        IF NEW.tags IS NOT NULL AND NEW.tags != '[]' THEN
+            INSERT INTO tag_counts (tag, count)
+            SELECT DISTINCT tag, 1 FROM jsonb_array_elements_text(NEW.tags::jsonb) AS tag
+            ON CONFLICT(tag) DO UPDATE SET count = tag_counts.count + 1;
+        END IF;
+        DELETE FROM tag_counts WHERE count <= 0;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        // MIME type counts follow each document's newest version. SQLite
+        // fires row triggers as each row changes; Postgres fires AFTER row
+        // triggers once the statement is done, so it uses statement triggers
+        // over the transition tables instead.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_stats_versions_insert
+AFTER INSERT ON document_versions
+WHEN NOT EXISTS (SELECT 1 FROM document_versions WHERE document_id = NEW.document_id AND id > NEW.id)
+BEGIN
+    UPDATE mime_type_counts SET count = count - 1
+    WHERE mime_type = (SELECT mime_type FROM document_versions
+                       WHERE document_id = NEW.document_id AND id < NEW.id
+                       ORDER BY id DESC LIMIT 1);
+    INSERT INTO mime_type_counts (mime_type, count)
+    VALUES (NEW.mime_type, 1)
+    ON CONFLICT(mime_type) DO UPDATE SET count = count + 1;
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION update_stats_versions_insert()
+RETURNS TRIGGER AS $$
+BEGIN
+    WITH affected AS (
+        SELECT DISTINCT document_id FROM new_versions
+    ),
+    newest AS (
+        SELECT DISTINCT ON (dv.document_id) dv.document_id, dv.id, dv.mime_type
+        FROM document_versions dv JOIN affected a ON a.document_id = dv.document_id
+        ORDER BY dv.document_id, dv.id DESC
+    ),
+    previous AS (
+        SELECT DISTINCT ON (dv.document_id) dv.document_id, dv.mime_type
+        FROM document_versions dv JOIN affected a ON a.document_id = dv.document_id
+        WHERE dv.id NOT IN (SELECT id FROM new_versions)
+        ORDER BY dv.document_id, dv.id DESC
+    ),
+    replaced AS (
+        SELECT n.document_id, n.mime_type FROM newest n
+        WHERE n.id IN (SELECT id FROM new_versions)
+    )
+    INSERT INTO mime_type_counts (mime_type, count)
+    SELECT mime_type, SUM(delta) FROM (
+        SELECT mime_type, 1 AS delta FROM replaced
+        UNION ALL
+        SELECT p.mime_type, -1 FROM previous p JOIN replaced r ON r.document_id = p.document_id
+    ) deltas
+    GROUP BY mime_type
+    ON CONFLICT(mime_type) DO UPDATE SET count = mime_type_counts.count + EXCLUDED.count;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_stats_versions_delete
+AFTER DELETE ON document_versions
+WHEN NOT EXISTS (SELECT 1 FROM document_versions WHERE document_id = OLD.document_id AND id > OLD.id)
+BEGIN
+    UPDATE mime_type_counts SET count = count - 1
+    WHERE mime_type = OLD.mime_type;
+    INSERT INTO mime_type_counts (mime_type, count)
+    SELECT mime_type, 1 FROM document_versions
+    WHERE document_id = OLD.document_id
+    ORDER BY id DESC LIMIT 1
+    ON CONFLICT(mime_type) DO UPDATE SET count = count + 1;
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION update_stats_versions_delete()
+RETURNS TRIGGER AS $$
+BEGIN
+    WITH affected AS (
+        SELECT document_id, MAX(id) AS max_deleted FROM old_versions GROUP BY document_id
+    ),
+    remaining AS (
+        SELECT DISTINCT ON (dv.document_id) dv.document_id, dv.id, dv.mime_type
+        FROM document_versions dv JOIN affected a ON a.document_id = dv.document_id
+        ORDER BY dv.document_id, dv.id DESC
+    ),
+    replaced AS (
+        SELECT a.document_id, o.mime_type
+        FROM affected a
+        JOIN old_versions o ON o.id = a.max_deleted
+        LEFT JOIN remaining r ON r.document_id = a.document_id
+        WHERE r.id IS NULL OR r.id < a.max_deleted
+    )
+    INSERT INTO mime_type_counts (mime_type, count)
+    SELECT mime_type, SUM(delta) FROM (
+        SELECT mime_type, -1 AS delta FROM replaced
+        UNION ALL
+        SELECT r.mime_type, 1 FROM remaining r JOIN replaced x ON x.document_id = r.document_id
+    ) deltas
+    GROUP BY mime_type
+    ON CONFLICT(mime_type) DO UPDATE SET count = mime_type_counts.count + EXCLUDED.count;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_stats_versions_mime
+AFTER UPDATE OF mime_type ON document_versions
+WHEN OLD.mime_type IS NOT NEW.mime_type
+    AND NOT EXISTS (SELECT 1 FROM document_versions WHERE document_id = NEW.document_id AND id > NEW.id)
+BEGIN
+    UPDATE mime_type_counts SET count = count - 1
+    WHERE mime_type = OLD.mime_type;
+    INSERT INTO mime_type_counts (mime_type, count)
+    VALUES (NEW.mime_type, 1)
+    ON CONFLICT(mime_type) DO UPDATE SET count = count + 1;
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION update_stats_versions_mime()
+RETURNS TRIGGER AS $$
+BEGIN
+    WITH changed AS (
+        SELECT o.mime_type AS old_mime, n.mime_type AS new_mime
+        FROM old_versions o JOIN new_versions n ON n.id = o.id
+        WHERE o.mime_type IS DISTINCT FROM n.mime_type
+          AND NOT EXISTS (
+              SELECT 1 FROM document_versions l
+              WHERE l.document_id = n.document_id AND l.id > n.id
+          )
+    )
+    INSERT INTO mime_type_counts (mime_type, count)
+    SELECT mime_type, SUM(delta) FROM (
+        SELECT old_mime AS mime_type, -1 AS delta FROM changed
+        UNION ALL
+        SELECT new_mime, 1 FROM changed
+    ) deltas
+    GROUP BY mime_type
+    ON CONFLICT(mime_type) DO UPDATE SET count = mime_type_counts.count + EXCLUDED.count;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        // PostgreSQL trigger creation
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_stats_documents_insert ON documents")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_stats_documents_delete ON documents")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_stats_documents_status ON documents")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_stats_documents_tags ON documents")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_stats_versions_insert ON document_versions")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_stats_versions_delete ON document_versions")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_stats_versions_mime ON document_versions")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_stats_documents_insert AFTER INSERT ON documents FOR EACH ROW EXECUTE FUNCTION update_stats_documents_insert()")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_stats_documents_delete AFTER DELETE ON documents FOR EACH ROW EXECUTE FUNCTION update_stats_documents_delete()")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_stats_documents_status AFTER UPDATE OF source_id, status ON documents FOR EACH ROW EXECUTE FUNCTION update_stats_documents_status()")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_stats_documents_tags AFTER UPDATE OF tags ON documents FOR EACH ROW EXECUTE FUNCTION update_stats_documents_tags()")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_stats_versions_insert AFTER INSERT ON document_versions REFERENCING NEW TABLE AS new_versions FOR EACH STATEMENT EXECUTE FUNCTION update_stats_versions_insert()")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_stats_versions_delete AFTER DELETE ON document_versions REFERENCING OLD TABLE AS old_versions FOR EACH STATEMENT EXECUTE FUNCTION update_stats_versions_delete()")
+                .only_for(&["postgres"]),
+        )
+        // Transition tables can't be combined with a column list
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_stats_versions_mime AFTER UPDATE ON document_versions REFERENCING OLD TABLE AS old_versions NEW TABLE AS new_versions FOR EACH STATEMENT EXECUTE FUNCTION update_stats_versions_mime()")
+                .only_for(&["postgres"]),
+        )
+}
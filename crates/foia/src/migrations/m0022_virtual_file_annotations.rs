@@ -0,0 +1,47 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0022_virtual_file_annotations")
+        .depends_on(&["0021_stats_tables"])
+        // Per-annotation results for archive members and attachments, the
+        // counterpart of `metadata.annotations` on documents
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS virtual_file_annotations (
+    virtual_file_id TEXT NOT NULL REFERENCES virtual_files(id) ON DELETE CASCADE,
+    annotation_type TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    data TEXT,
+    error TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (virtual_file_id, annotation_type)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS virtual_file_annotations (
+    virtual_file_id TEXT NOT NULL REFERENCES virtual_files(id) ON DELETE CASCADE,
+    annotation_type TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    data TEXT,
+    error TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (virtual_file_id, annotation_type)
+)"#,
+                ),
+        )
+        // Work selection filters on status
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_virtual_files_status ON virtual_files(status)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_virtual_files_status ON virtual_files(status)",
+                ),
+        )
+}
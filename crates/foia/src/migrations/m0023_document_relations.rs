@@ -0,0 +1,59 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0023_document_relations")
+        .depends_on(&["0022_virtual_file_annotations"])
+        // Typed edges between documents: `document_id` is <relation_type> of
+        // `related_document_id` (e.g. an exhibit to a filing). Edges found
+        // through an archive member or attachment record it in virtual_file_id.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS document_relations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    related_document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    relation_type TEXT NOT NULL,
+    virtual_file_id TEXT REFERENCES virtual_files(id) ON DELETE SET NULL,
+    note TEXT,
+    created_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS document_relations (
+    id SERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    related_document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    relation_type TEXT NOT NULL,
+    virtual_file_id TEXT REFERENCES virtual_files(id) ON DELETE SET NULL,
+    note TEXT,
+    created_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        // One edge of each type per pair; also serves lookups from document_id
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_document_relations_edge ON document_relations(document_id, related_document_id, relation_type)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_document_relations_edge ON document_relations(document_id, related_document_id, relation_type)",
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_relations_related ON document_relations(related_document_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_relations_related ON document_relations(related_document_id)",
+                ),
+        )
+}
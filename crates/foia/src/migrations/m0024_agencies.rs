@@ -0,0 +1,63 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0024_agencies")
+        .depends_on(&["0023_document_relations"])
+        // Agencies group sources into a hierarchy (DOJ → FBI → field office).
+        // `config` holds request settings inherited by member sources and
+        // sub-agencies that don't set their own.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS agencies (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    parent_id TEXT REFERENCES agencies(id) ON DELETE SET NULL,
+    config TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS agencies (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    parent_id TEXT REFERENCES agencies(id) ON DELETE SET NULL,
+    config TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        // A source belongs to at most one agency
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS agency_sources (
+    source_id TEXT PRIMARY KEY,
+    agency_id TEXT NOT NULL REFERENCES agencies(id) ON DELETE CASCADE
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS agency_sources (
+    source_id TEXT PRIMARY KEY,
+    agency_id TEXT NOT NULL REFERENCES agencies(id) ON DELETE CASCADE
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_agency_sources_agency ON agency_sources(agency_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_agency_sources_agency ON agency_sources(agency_id)",
+                ),
+        )
+}
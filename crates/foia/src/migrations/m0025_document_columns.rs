@@ -0,0 +1,58 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0025_document_columns")
+        .depends_on(&["0024_agencies"])
+        // Column names from the header rows of CSV files and spreadsheets,
+        // one row per column, so data releases can be found by field name.
+        // `name_key` is the lowercased, whitespace-collapsed name for matching.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS document_columns (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER NOT NULL,
+    sheet TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    name_key TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS document_columns (
+    id SERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER NOT NULL,
+    sheet TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    name_key TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_columns_document ON document_columns(document_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_columns_document ON document_columns(document_id)",
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_columns_name_key ON document_columns(name_key)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_columns_name_key ON document_columns(name_key)",
+                ),
+        )
+}
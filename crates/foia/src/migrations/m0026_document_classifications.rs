@@ -0,0 +1,43 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0026_document_classifications")
+        .depends_on(&["0025_document_columns"])
+        // Record type of each document (letter, memo, photo, ...) with the
+        // classifier's confidence. One row per document; reclassifying
+        // replaces it.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS document_classifications (
+    document_id TEXT PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    record_type TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    classifier TEXT NOT NULL,
+    classified_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS document_classifications (
+    document_id TEXT PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    record_type TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    classifier TEXT NOT NULL,
+    classified_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_classifications_type ON document_classifications(record_type)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_classifications_type ON document_classifications(record_type)",
+                ),
+        )
+}
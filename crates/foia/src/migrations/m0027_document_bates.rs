@@ -0,0 +1,64 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0027_document_bates")
+        .depends_on(&["0026_document_classifications"])
+        // Bates-stamped page ranges of each document: one row per run of
+        // consecutively numbered pages. `prefix_key` is the prefix with
+        // separators dropped, for lookup by number and gap detection.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS document_bates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER NOT NULL,
+    prefix TEXT NOT NULL,
+    prefix_key TEXT NOT NULL,
+    start_number INTEGER NOT NULL,
+    end_number INTEGER NOT NULL,
+    digits INTEGER NOT NULL,
+    first_page INTEGER NOT NULL,
+    last_page INTEGER NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS document_bates (
+    id SERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER NOT NULL,
+    prefix TEXT NOT NULL,
+    prefix_key TEXT NOT NULL,
+    start_number BIGINT NOT NULL,
+    end_number BIGINT NOT NULL,
+    digits INTEGER NOT NULL,
+    first_page INTEGER NOT NULL,
+    last_page INTEGER NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_bates_document ON document_bates(document_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_bates_document ON document_bates(document_id)",
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_bates_number ON document_bates(prefix_key, start_number)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_bates_number ON document_bates(prefix_key, start_number)",
+                ),
+        )
+}
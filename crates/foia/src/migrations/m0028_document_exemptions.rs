@@ -0,0 +1,55 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0028_document_exemptions")
+        .depends_on(&["0027_document_bates"])
+        // FOIA exemption markings per page: one row per page and normalized
+        // code ("(b)(5)", "(b)(7)(C)") with the number of markings.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS document_exemptions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER NOT NULL,
+    page_number INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    count INTEGER NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS document_exemptions (
+    id SERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    version_id INTEGER NOT NULL,
+    page_number INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    count INTEGER NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_exemptions_document ON document_exemptions(document_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_exemptions_document ON document_exemptions(document_id)",
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_exemptions_code ON document_exemptions(code, document_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_exemptions_code ON document_exemptions(code, document_id)",
+                ),
+        )
+}
@@ -0,0 +1,42 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0029_search_aliases")
+        .depends_on(&["0028_document_exemptions"])
+        // Alias dictionary for search expansion: a term ("OIG") and what it
+        // stands for ("Office of Inspector General"), matched both ways.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS search_aliases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    term TEXT NOT NULL,
+    expansion TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    created_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS search_aliases (
+    id SERIAL PRIMARY KEY,
+    term TEXT NOT NULL,
+    expansion TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    created_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_search_aliases_pair ON search_aliases(term, expansion)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_search_aliases_pair ON search_aliases(term, expansion)",
+                ),
+        )
+}
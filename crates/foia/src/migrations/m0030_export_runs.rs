@@ -0,0 +1,54 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0030_export_runs")
+        .depends_on(&["0029_search_aliases"])
+        // History of scheduled export jobs; the last successful run of a
+        // job is where its next incremental export starts.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS export_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job TEXT NOT NULL,
+    format TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT,
+    status TEXT NOT NULL,
+    artifact TEXT,
+    documents INTEGER NOT NULL DEFAULT 0,
+    bytes BIGINT NOT NULL DEFAULT 0,
+    error TEXT
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS export_runs (
+    id SERIAL PRIMARY KEY,
+    job TEXT NOT NULL,
+    format TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT,
+    status TEXT NOT NULL,
+    artifact TEXT,
+    documents INTEGER NOT NULL DEFAULT 0,
+    bytes BIGINT NOT NULL DEFAULT 0,
+    error TEXT
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_export_runs_job ON export_runs(job, started_at)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_export_runs_job ON export_runs(job, started_at)",
+                ),
+        )
+}
@@ -0,0 +1,35 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0031_access_rules")
+        .depends_on(&["0030_export_runs"])
+        // Visibility of documents and whole sources: public, internal-only,
+        // or embargoed until a date. A document rule overrides its source's.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS access_rules (
+    scope TEXT NOT NULL,
+    target TEXT NOT NULL,
+    visibility TEXT NOT NULL,
+    embargo_until TEXT,
+    note TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (scope, target)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS access_rules (
+    scope TEXT NOT NULL,
+    target TEXT NOT NULL,
+    visibility TEXT NOT NULL,
+    embargo_until TEXT,
+    note TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (scope, target)
+)"#,
+                ),
+        )
+}
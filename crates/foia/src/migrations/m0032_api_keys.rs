@@ -0,0 +1,65 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0032_api_keys")
+        .depends_on(&["0031_access_rules"])
+        // Keys for partner access to the JSON API. Only a hash of the key is
+        // stored; the prefix identifies it in listings.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    read_only INTEGER NOT NULL DEFAULT 1,
+    sources TEXT,
+    rate_limit INTEGER,
+    requests BIGINT NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    last_used_at TEXT,
+    revoked_at TEXT
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS api_keys (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    key_prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    read_only INTEGER NOT NULL DEFAULT 1,
+    sources TEXT,
+    rate_limit INTEGER,
+    requests BIGINT NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    last_used_at TEXT,
+    revoked_at TEXT
+)"#,
+                ),
+        )
+        // Requests per key and day
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS api_key_usage (
+    key_id INTEGER NOT NULL,
+    day TEXT NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (key_id, day)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS api_key_usage (
+    key_id INTEGER NOT NULL,
+    day TEXT NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (key_id, day)
+)"#,
+                ),
+        )
+}
@@ -0,0 +1,75 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0033_document_tombstones")
+        .depends_on(&["0032_api_keys"])
+        // Deleted documents, so external search indexes can drop them
+        // without a full rebuild. Filled by a trigger to catch every
+        // deletion path.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS document_tombstones (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    deleted_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS document_tombstones (
+    id SERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    deleted_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_tombstones_deleted_at ON document_tombstones(deleted_at)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_tombstones_deleted_at ON document_tombstones(deleted_at)",
+                ),
+        )
+        // Timestamps in the RFC 3339 form the application writes, so they
+        // compare as strings with `updated_at`.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TRIGGER IF NOT EXISTS tr_documents_tombstone
+AFTER DELETE ON documents
+BEGIN
+    INSERT INTO document_tombstones (document_id, source_id, deleted_at)
+    VALUES (OLD.id, OLD.source_id, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+END"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE OR REPLACE FUNCTION record_document_tombstone()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO document_tombstones (document_id, source_id, deleted_at)
+    VALUES (OLD.id, OLD.source_id,
+            to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US"+00:00"'));
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql"#,
+                ),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_documents_tombstone ON documents")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_documents_tombstone AFTER DELETE ON documents FOR EACH ROW EXECUTE FUNCTION record_document_tombstone()")
+                .only_for(&["postgres"]),
+        )
+}
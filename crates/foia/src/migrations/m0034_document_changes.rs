@@ -0,0 +1,188 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0034_document_changes")
+        .depends_on(&["0033_document_tombstones"])
+        // Journal of document changes, numbered in the order they happened,
+        // so exports and search indexes can pick up "everything after
+        // change N". Filled by triggers to catch every write path,
+        // including deletions that leave no row behind.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS document_changes (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    change_type TEXT NOT NULL,
+    changed_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS document_changes (
+    seq BIGSERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    change_type TEXT NOT NULL,
+    changed_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_document_changes_document ON document_changes(document_id, seq)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_document_changes_document ON document_changes(document_id, seq)",
+                ),
+        )
+        // Deletions recorded so far become the start of the journal
+        .operation(RunSql::new(
+            r#"INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+SELECT document_id, source_id, 'deleted', deleted_at FROM document_tombstones ORDER BY id"#,
+        ))
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_documents_tombstone")
+                .only_for(&["sqlite"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_documents_tombstone ON documents")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP FUNCTION IF EXISTS record_document_tombstone()")
+                .only_for(&["postgres"]),
+        )
+        .operation(RunSql::new("DROP TABLE IF EXISTS document_tombstones"))
+        // Timestamps in the RFC 3339 form the application writes, so they
+        // compare as strings with `updated_at`. New versions and attachment
+        // text count as updates of their document.
+        .operation(
+            RunSql::new(
+                r#"CREATE TRIGGER IF NOT EXISTS tr_documents_change_insert
+AFTER INSERT ON documents
+BEGIN
+    INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+    VALUES (NEW.id, NEW.source_id, 'created', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+END"#,
+            )
+            .only_for(&["sqlite"]),
+        )
+        .operation(
+            RunSql::new(
+                r#"CREATE TRIGGER IF NOT EXISTS tr_documents_change_update
+AFTER UPDATE ON documents
+BEGIN
+    INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+    VALUES (NEW.id, NEW.source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+END"#,
+            )
+            .only_for(&["sqlite"]),
+        )
+        .operation(
+            RunSql::new(
+                r#"CREATE TRIGGER IF NOT EXISTS tr_documents_change_delete
+AFTER DELETE ON documents
+BEGIN
+    INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+    VALUES (OLD.id, OLD.source_id, 'deleted', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+END"#,
+            )
+            .only_for(&["sqlite"]),
+        )
+        .operation(
+            RunSql::new(
+                r#"CREATE TRIGGER IF NOT EXISTS tr_document_versions_change
+AFTER INSERT ON document_versions
+BEGIN
+    INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+    SELECT id, source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+    FROM documents WHERE id = NEW.document_id;
+END"#,
+            )
+            .only_for(&["sqlite"]),
+        )
+        .operation(
+            RunSql::new(
+                r#"CREATE TRIGGER IF NOT EXISTS tr_virtual_files_change_insert
+AFTER INSERT ON virtual_files
+BEGIN
+    INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+    SELECT id, source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+    FROM documents WHERE id = NEW.document_id;
+END"#,
+            )
+            .only_for(&["sqlite"]),
+        )
+        .operation(
+            RunSql::new(
+                r#"CREATE TRIGGER IF NOT EXISTS tr_virtual_files_change_update
+AFTER UPDATE OF extracted_text ON virtual_files
+BEGIN
+    INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+    SELECT id, source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+    FROM documents WHERE id = NEW.document_id;
+END"#,
+            )
+            .only_for(&["sqlite"]),
+        )
+        .operation(
+            RunSql::new(
+                r#"CREATE OR REPLACE FUNCTION record_document_change()
+RETURNS TRIGGER AS $$
+DECLARE
+    stamp TEXT := to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US"+00:00"');
+BEGIN
+    IF TG_TABLE_NAME = 'documents' THEN
+        IF TG_OP = 'DELETE' THEN
+            INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+            VALUES (OLD.id, OLD.source_id, 'deleted', stamp);
+            RETURN OLD;
+        END IF;
+        INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+        VALUES (NEW.id, NEW.source_id,
+                CASE WHEN TG_OP = 'INSERT' THEN 'created' ELSE 'updated' END, stamp);
+        RETURN NEW;
+    END IF;
+    INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+    SELECT id, source_id, 'updated', stamp FROM documents WHERE id = NEW.document_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql"#,
+            )
+            .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_documents_change ON documents")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_documents_change AFTER INSERT OR UPDATE OR DELETE ON documents FOR EACH ROW EXECUTE FUNCTION record_document_change()")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_document_versions_change ON document_versions")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_document_versions_change AFTER INSERT ON document_versions FOR EACH ROW EXECUTE FUNCTION record_document_change()")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("DROP TRIGGER IF EXISTS tr_virtual_files_change ON virtual_files")
+                .only_for(&["postgres"]),
+        )
+        .operation(
+            RunSql::new("CREATE TRIGGER tr_virtual_files_change AFTER INSERT OR UPDATE OF extracted_text ON virtual_files FOR EACH ROW EXECUTE FUNCTION record_document_change()")
+                .only_for(&["postgres"]),
+        )
+        // The change each export run picked up through
+        .operation(RunSql::new(
+            "ALTER TABLE export_runs ADD COLUMN change_seq BIGINT",
+        ))
+}
@@ -0,0 +1,11 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0035_source_paused")
+        .depends_on(&["0034_document_changes"])
+        // Operators pause a source to stop all traffic to it at once,
+        // without editing its scraper config
+        .operation(RunSql::new(
+            "ALTER TABLE sources ADD COLUMN paused INTEGER NOT NULL DEFAULT 0",
+        ))
+}
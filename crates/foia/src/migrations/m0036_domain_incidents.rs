@@ -0,0 +1,49 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0036_domain_incidents")
+        .depends_on(&["0035_source_paused"])
+        // Circuit breaker trips: a domain that kept answering 403/429/451 is
+        // paused until `paused_until`, for every process crawling it
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS domain_incidents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_id TEXT NOT NULL,
+    domain TEXT NOT NULL,
+    responses INTEGER NOT NULL,
+    blocked INTEGER NOT NULL,
+    statuses TEXT NOT NULL DEFAULT '{}',
+    opened_at TEXT NOT NULL,
+    paused_until TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS domain_incidents (
+    id SERIAL PRIMARY KEY,
+    source_id TEXT NOT NULL,
+    domain TEXT NOT NULL,
+    responses INTEGER NOT NULL,
+    blocked INTEGER NOT NULL,
+    statuses TEXT NOT NULL DEFAULT '{}',
+    opened_at TEXT NOT NULL,
+    paused_until TEXT NOT NULL
+)"#,
+                ),
+        )
+        // Every request checks the latest incident for its domain
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_domain_incidents_domain ON domain_incidents(domain, id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_domain_incidents_domain ON domain_incidents(domain, id)",
+                ),
+        )
+}
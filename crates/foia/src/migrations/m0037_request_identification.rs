@@ -0,0 +1,11 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0037_request_identification")
+        .depends_on(&["0036_domain_incidents"])
+        // Whether transparency mode identified the crawler on the request,
+        // for agencies that only admit identifiable crawlers
+        .operation(RunSql::new(
+            "ALTER TABLE crawl_requests ADD COLUMN identified INTEGER NOT NULL DEFAULT 0",
+        ))
+}
@@ -0,0 +1,47 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0038_title_suggestions")
+        .depends_on(&["0037_request_identification"])
+        // Titles derived from document content for documents whose title came
+        // from their URL. The original title is kept here so an applied
+        // suggestion can be reverted; `status` tracks curator review.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS title_suggestions (
+    document_id TEXT PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    original_title TEXT NOT NULL,
+    suggested_title TEXT NOT NULL,
+    method TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    reviewed_at TEXT
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS title_suggestions (
+    document_id TEXT PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    original_title TEXT NOT NULL,
+    suggested_title TEXT NOT NULL,
+    method TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    reviewed_at TEXT
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_title_suggestions_status ON title_suggestions(status)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_title_suggestions_status ON title_suggestions(status)",
+                ),
+        )
+}
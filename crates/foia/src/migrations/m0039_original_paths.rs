@@ -0,0 +1,30 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0039_original_paths")
+        .depends_on(&["0038_title_suggestions"])
+        // Where each imported file sat in the production it came from,
+        // relative to the load file, so exports can rebuild the original
+        // folder structure instead of the content-addressed layout.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS original_paths (
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    content_hash TEXT NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY (document_id, content_hash)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS original_paths (
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    content_hash TEXT NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY (document_id, content_hash)
+)"#,
+                ),
+        )
+}
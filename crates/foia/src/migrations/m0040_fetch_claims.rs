@@ -0,0 +1,34 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0040_fetch_claims")
+        .depends_on(&["0039_original_paths"])
+        // URLs (by normalized form) and content hashes being fetched right
+        // now, so a URL found twice in one run, or by two processes, is only
+        // fetched once. Rows expire after the suppression window.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS fetch_claims (
+    source_id TEXT NOT NULL,
+    claim_key TEXT NOT NULL,
+    url TEXT NOT NULL,
+    claimed_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    PRIMARY KEY (source_id, claim_key)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS fetch_claims (
+    source_id TEXT NOT NULL,
+    claim_key TEXT NOT NULL,
+    url TEXT NOT NULL,
+    claimed_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    PRIMARY KEY (source_id, claim_key)
+)"#,
+                ),
+        )
+}
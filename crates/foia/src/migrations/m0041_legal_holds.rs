@@ -0,0 +1,27 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0041_legal_holds")
+        .depends_on(&["0040_fetch_claims"])
+        // Documents under legal hold: retention and pruning never delete
+        // their files or versions until the hold is released.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS legal_holds (
+    document_id TEXT PRIMARY KEY NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    placed_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS legal_holds (
+    document_id TEXT PRIMARY KEY NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    placed_at TEXT NOT NULL
+)"#,
+                ),
+        )
+}
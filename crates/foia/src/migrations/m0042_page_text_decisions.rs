@@ -0,0 +1,34 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0042_page_text_decisions")
+        .depends_on(&["0041_legal_holds"])
+        // Why a page's final text is what it is when several OCR backends
+        // read it: the arbitration method, the reading it came from, each
+        // reading's scores (JSON) and a human-readable rationale.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS page_text_decisions (
+    page_id INTEGER PRIMARY KEY NOT NULL REFERENCES document_pages(id) ON DELETE CASCADE,
+    method TEXT NOT NULL,
+    backend TEXT,
+    scores TEXT NOT NULL,
+    rationale TEXT NOT NULL,
+    decided_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS page_text_decisions (
+    page_id INTEGER PRIMARY KEY NOT NULL REFERENCES document_pages(id) ON DELETE CASCADE,
+    method TEXT NOT NULL,
+    backend TEXT,
+    scores TEXT NOT NULL,
+    rationale TEXT NOT NULL,
+    decided_at TEXT NOT NULL
+)"#,
+                ),
+        )
+}
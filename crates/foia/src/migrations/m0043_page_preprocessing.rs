@@ -0,0 +1,33 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0043_page_preprocessing")
+        .depends_on(&["0042_page_text_decisions"])
+        // What was done to a page's image before OCR: the rotation applied,
+        // the measured skew, the steps run (JSON) and the tool that ran them.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS page_preprocessing (
+    page_id INTEGER PRIMARY KEY NOT NULL REFERENCES document_pages(id) ON DELETE CASCADE,
+    rotation INTEGER NOT NULL DEFAULT 0,
+    skew_angle REAL,
+    steps TEXT NOT NULL,
+    tool TEXT,
+    applied_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS page_preprocessing (
+    page_id INTEGER PRIMARY KEY NOT NULL REFERENCES document_pages(id) ON DELETE CASCADE,
+    rotation INTEGER NOT NULL DEFAULT 0,
+    skew_angle REAL,
+    steps TEXT NOT NULL,
+    tool TEXT,
+    applied_at TEXT NOT NULL
+)"#,
+                ),
+        )
+}
@@ -0,0 +1,54 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0046_uploads")
+        .depends_on(&["0045_document_sections"])
+        // Documents submitted by contributors through the upload API, and
+        // their moderation. `status` is `pending` until a moderator
+        // approves or rejects the upload; the document stays internal
+        // until it is approved.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS uploads (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    contributor TEXT NOT NULL,
+    description TEXT,
+    attribution TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    submitted_at TEXT NOT NULL,
+    reviewed_at TEXT,
+    reviewed_by TEXT,
+    review_note TEXT
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS uploads (
+    id SERIAL PRIMARY KEY,
+    document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    contributor TEXT NOT NULL,
+    description TEXT,
+    attribution TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    submitted_at TEXT NOT NULL,
+    reviewed_at TEXT,
+    reviewed_by TEXT,
+    review_note TEXT
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_uploads_status ON uploads(status, submitted_at)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_uploads_status ON uploads(status, submitted_at)",
+                ),
+        )
+}
@@ -0,0 +1,93 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0047_takedowns")
+        .depends_on(&["0046_uploads"])
+        // Removal requests from the public. A case names the documents it
+        // asks to take down; `status` moves from `open` through
+        // `suppressed` (hidden pending review) to `upheld` or `rejected`.
+        // Each step, with who took it and why, is kept in
+        // takedown_events.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS takedown_cases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    requester_name TEXT NOT NULL,
+    requester_contact TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open',
+    submitted_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS takedown_cases (
+    id SERIAL PRIMARY KEY,
+    requester_name TEXT NOT NULL,
+    requester_contact TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open',
+    submitted_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS takedown_documents (
+    case_id INTEGER NOT NULL REFERENCES takedown_cases(id) ON DELETE CASCADE,
+    document_id TEXT NOT NULL,
+    PRIMARY KEY (case_id, document_id)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS takedown_documents (
+    case_id INTEGER NOT NULL REFERENCES takedown_cases(id) ON DELETE CASCADE,
+    document_id TEXT NOT NULL,
+    PRIMARY KEY (case_id, document_id)
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_takedown_documents_doc ON takedown_documents(document_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_takedown_documents_doc ON takedown_documents(document_id)",
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS takedown_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    case_id INTEGER NOT NULL REFERENCES takedown_cases(id) ON DELETE CASCADE,
+    action TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    note TEXT,
+    created_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS takedown_events (
+    id SERIAL PRIMARY KEY,
+    case_id INTEGER NOT NULL REFERENCES takedown_cases(id) ON DELETE CASCADE,
+    action TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    note TEXT,
+    created_at TEXT NOT NULL
+)"#,
+                ),
+        )
+}
@@ -0,0 +1,48 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0048_page_text_revisions")
+        .depends_on(&["0047_takedowns"])
+        // Corrections of a page's text by curators. Each revision keeps the
+        // text it replaced and a line diff, so edits can be reviewed and
+        // undone. A page with a revision is human-verified: OCR and
+        // arbitration leave its final text alone.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS page_text_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    page_id INTEGER NOT NULL REFERENCES document_pages(id) ON DELETE CASCADE,
+    editor TEXT NOT NULL,
+    previous_text TEXT,
+    text TEXT NOT NULL,
+    diff TEXT NOT NULL,
+    created_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS page_text_revisions (
+    id SERIAL PRIMARY KEY,
+    page_id INTEGER NOT NULL REFERENCES document_pages(id) ON DELETE CASCADE,
+    editor TEXT NOT NULL,
+    previous_text TEXT,
+    text TEXT NOT NULL,
+    diff TEXT NOT NULL,
+    created_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_page_text_revisions_page ON page_text_revisions(page_id)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_page_text_revisions_page ON page_text_revisions(page_id)",
+                ),
+        )
+}
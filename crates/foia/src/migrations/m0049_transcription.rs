@@ -0,0 +1,76 @@
+use cetane::prelude::*;
+
+pub fn migration() -> Migration {
+    Migration::new("0049_transcription")
+        .depends_on(&["0048_page_text_revisions"])
+        // Pages packaged for volunteers to transcribe by hand. `status`
+        // moves from `open` to `accepted` once two transcriptions agree;
+        // a disagreement makes the task `disputed` until a third volunteer
+        // breaks the tie, and `review` when none of them agree.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS transcription_tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    page_id INTEGER NOT NULL UNIQUE REFERENCES document_pages(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'open',
+    accepted_text TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS transcription_tasks (
+    id SERIAL PRIMARY KEY,
+    page_id INTEGER NOT NULL UNIQUE REFERENCES document_pages(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'open',
+    accepted_text TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+)"#,
+                ),
+        )
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    "CREATE INDEX IF NOT EXISTS idx_transcription_tasks_status ON transcription_tasks(status)",
+                )
+                .for_backend(
+                    "postgres",
+                    "CREATE INDEX IF NOT EXISTS idx_transcription_tasks_status ON transcription_tasks(status)",
+                ),
+        )
+        // One row per volunteer working on a task: assigned when handed
+        // out, with `text` and `submitted_at` set once transcribed.
+        // Assignments not submitted in time are released.
+        .operation(
+            RunSql::portable()
+                .for_backend(
+                    "sqlite",
+                    r#"CREATE TABLE IF NOT EXISTS transcription_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL REFERENCES transcription_tasks(id) ON DELETE CASCADE,
+    volunteer TEXT NOT NULL,
+    text TEXT,
+    assigned_at TEXT NOT NULL,
+    submitted_at TEXT,
+    UNIQUE (task_id, volunteer)
+)"#,
+                )
+                .for_backend(
+                    "postgres",
+                    r#"CREATE TABLE IF NOT EXISTS transcription_entries (
+    id SERIAL PRIMARY KEY,
+    task_id INTEGER NOT NULL REFERENCES transcription_tasks(id) ON DELETE CASCADE,
+    volunteer TEXT NOT NULL,
+    text TEXT,
+    assigned_at TEXT NOT NULL,
+    submitted_at TEXT,
+    UNIQUE (task_id, volunteer)
+)"#,
+                ),
+        )
+}
@@ -12,6 +12,41 @@ mod m0011_constraints;
 mod m0012_scraper_configs;
 mod m0013_analysis_lookup_index;
 mod m0014_search_indexes;
+mod m0015_page_highlights;
+mod m0016_version_lookup_index;
+mod m0017_crawl_challenges;
+mod m0018_listing_snapshots;
+mod m0019_api_schemas;
+mod m0020_lost_files;
+mod m0021_stats_tables;
+mod m0022_virtual_file_annotations;
+mod m0023_document_relations;
+mod m0024_agencies;
+mod m0025_document_columns;
+mod m0026_document_classifications;
+mod m0027_document_bates;
+mod m0028_document_exemptions;
+mod m0029_search_aliases;
+mod m0030_export_runs;
+mod m0031_access_rules;
+mod m0032_api_keys;
+mod m0033_document_tombstones;
+mod m0034_document_changes;
+mod m0035_source_paused;
+mod m0036_domain_incidents;
+mod m0037_request_identification;
+mod m0038_title_suggestions;
+mod m0039_original_paths;
+mod m0040_fetch_claims;
+mod m0041_legal_holds;
+mod m0042_page_text_decisions;
+mod m0043_page_preprocessing;
+mod m0044_document_splits;
+mod m0045_document_sections;
+mod m0046_uploads;
+mod m0047_takedowns;
+mod m0048_page_text_revisions;
+mod m0049_transcription;
 
 use cetane::prelude::MigrationRegistry;
 
@@ -31,5 +66,40 @@ pub fn registry() -> MigrationRegistry {
     reg.register(m0012_scraper_configs::migration());
     reg.register(m0013_analysis_lookup_index::migration());
     reg.register(m0014_search_indexes::migration());
+    reg.register(m0015_page_highlights::migration());
+    reg.register(m0016_version_lookup_index::migration());
+    reg.register(m0017_crawl_challenges::migration());
+    reg.register(m0018_listing_snapshots::migration());
+    reg.register(m0019_api_schemas::migration());
+    reg.register(m0020_lost_files::migration());
+    reg.register(m0021_stats_tables::migration());
+    reg.register(m0022_virtual_file_annotations::migration());
+    reg.register(m0023_document_relations::migration());
+    reg.register(m0024_agencies::migration());
+    reg.register(m0025_document_columns::migration());
+    reg.register(m0026_document_classifications::migration());
+    reg.register(m0027_document_bates::migration());
+    reg.register(m0028_document_exemptions::migration());
+    reg.register(m0029_search_aliases::migration());
+    reg.register(m0030_export_runs::migration());
+    reg.register(m0031_access_rules::migration());
+    reg.register(m0032_api_keys::migration());
+    reg.register(m0033_document_tombstones::migration());
+    reg.register(m0034_document_changes::migration());
+    reg.register(m0035_source_paused::migration());
+    reg.register(m0036_domain_incidents::migration());
+    reg.register(m0037_request_identification::migration());
+    reg.register(m0038_title_suggestions::migration());
+    reg.register(m0039_original_paths::migration());
+    reg.register(m0040_fetch_claims::migration());
+    reg.register(m0041_legal_holds::migration());
+    reg.register(m0042_page_text_decisions::migration());
+    reg.register(m0043_page_preprocessing::migration());
+    reg.register(m0044_document_splits::migration());
+    reg.register(m0045_document_sections::migration());
+    reg.register(m0046_uploads::migration());
+    reg.register(m0047_takedowns::migration());
+    reg.register(m0048_page_text_revisions::migration());
+    reg.register(m0049_transcription::migration());
     reg
 }
@@ -0,0 +1,322 @@
+//! Agencies: a hierarchy for grouping sources (DOJ → FBI → field office
+//! reading rooms), with request settings inherited down the tree.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ScraperConfig;
+use crate::privacy::SourcePrivacyConfig;
+
+/// Request settings an agency passes down to its sources and sub-agencies.
+///
+/// A source keeps any value its own scraper config sets; otherwise the
+/// nearest agency up the tree that sets the value wins.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgencySettings {
+    /// User agent string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Request timeout in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout: Option<u64>,
+    /// Delay between requests in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_delay_ms: Option<u64>,
+    /// Tor and proxy routing for member sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<SourcePrivacyConfig>,
+}
+
+impl AgencySettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A node in the agency hierarchy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Agency {
+    /// Unique identifier, e.g. `fbi`.
+    pub id: String,
+    /// Human-readable name.
+    pub name: String,
+    /// Parent agency, `None` for a top-level agency.
+    pub parent_id: Option<String>,
+    /// Settings inherited by member sources and sub-agencies.
+    pub settings: AgencySettings,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Agency {
+    pub fn new(id: String, name: String, parent_id: Option<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id,
+            name,
+            parent_id,
+            settings: AgencySettings::default(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Document and source totals for an agency, including its sub-agencies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AgencyStats {
+    pub sources: u64,
+    pub documents: u64,
+}
+
+/// All agencies with source membership, for walking the hierarchy.
+#[derive(Debug, Clone, Default)]
+pub struct AgencyTree {
+    agencies: HashMap<String, Agency>,
+    /// source_id → agency_id
+    members: HashMap<String, String>,
+}
+
+impl AgencyTree {
+    pub fn new(agencies: Vec<Agency>, members: HashMap<String, String>) -> Self {
+        Self {
+            agencies: agencies.into_iter().map(|a| (a.id.clone(), a)).collect(),
+            members,
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Agency> {
+        self.agencies.get(id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.agencies.is_empty()
+    }
+
+    /// Agency a source is assigned to.
+    pub fn agency_of(&self, source_id: &str) -> Option<&Agency> {
+        self.members.get(source_id).and_then(|id| self.get(id))
+    }
+
+    /// Top-level agencies, and agencies whose parent no longer exists,
+    /// sorted by name.
+    pub fn roots(&self) -> Vec<&Agency> {
+        self.sorted(self.agencies.values().filter(|a| {
+            a.parent_id
+                .as_deref()
+                .is_none_or(|p| !self.agencies.contains_key(p))
+        }))
+    }
+
+    /// Direct sub-agencies, sorted by name.
+    pub fn children(&self, id: &str) -> Vec<&Agency> {
+        self.sorted(
+            self.agencies
+                .values()
+                .filter(|a| a.parent_id.as_deref() == Some(id)),
+        )
+    }
+
+    fn sorted<'a>(&self, agencies: impl Iterator<Item = &'a Agency>) -> Vec<&'a Agency> {
+        let mut agencies: Vec<&Agency> = agencies.collect();
+        agencies.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        agencies
+    }
+
+    /// The agency and its ancestors, nearest first. Stops at a cycle.
+    pub fn ancestors(&self, id: &str) -> Vec<&Agency> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = self.get(id);
+        while let Some(agency) = next {
+            if !seen.insert(agency.id.as_str()) {
+                break;
+            }
+            chain.push(agency);
+            next = agency.parent_id.as_deref().and_then(|p| self.get(p));
+        }
+        chain
+    }
+
+    /// IDs of the agency and everything below it.
+    pub fn descendants(&self, id: &str) -> HashSet<&str> {
+        let mut found: HashSet<&str> = HashSet::new();
+        let Some(root) = self.get(id) else {
+            return found;
+        };
+        let mut stack = vec![root.id.as_str()];
+        while let Some(current) = stack.pop() {
+            if found.insert(current) {
+                stack.extend(self.children(current).into_iter().map(|a| a.id.as_str()));
+            }
+        }
+        found
+    }
+
+    /// Whether making `parent_id` the parent of `id` would create a cycle.
+    pub fn would_cycle(&self, id: &str, parent_id: &str) -> bool {
+        id == parent_id || self.ancestors(parent_id).iter().any(|a| a.id == id)
+    }
+
+    /// Sources assigned directly to the agency, sorted.
+    pub fn members_of(&self, id: &str) -> Vec<String> {
+        let mut sources: Vec<String> = self
+            .members
+            .iter()
+            .filter(|(_, agency)| agency.as_str() == id)
+            .map(|(source, _)| source.clone())
+            .collect();
+        sources.sort();
+        sources
+    }
+
+    /// Sources assigned to the agency or any of its sub-agencies, sorted.
+    pub fn sources_under(&self, id: &str) -> Vec<String> {
+        let agencies = self.descendants(id);
+        let mut sources: Vec<String> = self
+            .members
+            .iter()
+            .filter(|(_, agency)| agencies.contains(agency.as_str()))
+            .map(|(source, _)| source.clone())
+            .collect();
+        sources.sort();
+        sources
+    }
+
+    /// Fill settings the source's own config leaves unset from its agency
+    /// chain, nearest agency first.
+    pub fn apply(&self, source_id: &str, config: &mut ScraperConfig) {
+        let Some(agency) = self.agency_of(source_id) else {
+            return;
+        };
+        for agency in self.ancestors(&agency.id) {
+            let settings = &agency.settings;
+            if config.user_agent.is_none() {
+                config.user_agent = settings.user_agent.clone();
+            }
+            if config.request_timeout.is_none() {
+                config.request_timeout = settings.request_timeout;
+            }
+            if config.request_delay_ms.is_none() {
+                config.request_delay_ms = settings.request_delay_ms;
+            }
+            if config.privacy.is_default() {
+                if let Some(ref privacy) = settings.privacy {
+                    config.privacy = privacy.clone();
+                }
+            }
+        }
+    }
+
+    /// Roll per-source document counts up the hierarchy. Each agency's
+    /// totals include every sub-agency.
+    pub fn rollup(&self, source_counts: &HashMap<String, u64>) -> HashMap<String, AgencyStats> {
+        let mut stats: HashMap<String, AgencyStats> = self
+            .agencies
+            .keys()
+            .map(|id| (id.clone(), AgencyStats::default()))
+            .collect();
+        for (source_id, agency_id) in &self.members {
+            let documents = source_counts.get(source_id).copied().unwrap_or(0);
+            for agency in self.ancestors(agency_id) {
+                if let Some(entry) = stats.get_mut(&agency.id) {
+                    entry.sources += 1;
+                    entry.documents += documents;
+                }
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agency(id: &str, parent: Option<&str>) -> Agency {
+        Agency::new(
+            id.to_string(),
+            id.to_uppercase(),
+            parent.map(str::to_string),
+        )
+    }
+
+    fn tree() -> AgencyTree {
+        let mut doj = agency("doj", None);
+        doj.settings.user_agent = Some("doj-bot".to_string());
+        doj.settings.request_delay_ms = Some(2000);
+        let mut fbi = agency("fbi", Some("doj"));
+        fbi.settings.request_delay_ms = Some(5000);
+        let members = [("vault", "fbi"), ("fbi-boston", "boston"), ("oip", "doj")]
+            .into_iter()
+            .map(|(s, a)| (s.to_string(), a.to_string()))
+            .collect();
+        AgencyTree::new(
+            vec![doj, fbi, agency("boston", Some("fbi")), agency("cia", None)],
+            members,
+        )
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let tree = tree();
+        let roots: Vec<&str> = tree.roots().iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(roots, vec!["cia", "doj"]);
+        let chain: Vec<&str> = tree
+            .ancestors("boston")
+            .iter()
+            .map(|a| a.id.as_str())
+            .collect();
+        assert_eq!(chain, vec!["boston", "fbi", "doj"]);
+        assert_eq!(
+            tree.sources_under("fbi"),
+            vec!["fbi-boston".to_string(), "vault".to_string()]
+        );
+        assert_eq!(tree.sources_under("doj").len(), 3);
+        assert!(tree.would_cycle("doj", "boston"));
+        assert!(!tree.would_cycle("cia", "boston"));
+    }
+
+    #[test]
+    fn test_settings_inherit_nearest_first() {
+        let tree = tree();
+        let mut config = ScraperConfig::default();
+        tree.apply("fbi-boston", &mut config);
+        assert_eq!(config.request_delay_ms, Some(5000));
+        assert_eq!(config.user_agent.as_deref(), Some("doj-bot"));
+
+        // A source's own settings win
+        let mut config = ScraperConfig {
+            request_delay_ms: Some(100),
+            ..Default::default()
+        };
+        tree.apply("vault", &mut config);
+        assert_eq!(config.request_delay_ms, Some(100));
+
+        let mut config = ScraperConfig::default();
+        tree.apply("unassigned", &mut config);
+        assert_eq!(config, ScraperConfig::default());
+    }
+
+    #[test]
+    fn test_rollup() {
+        let tree = tree();
+        let counts = [("vault", 10), ("fbi-boston", 5), ("oip", 1)]
+            .into_iter()
+            .map(|(s, n)| (s.to_string(), n))
+            .collect();
+        let stats = tree.rollup(&counts);
+        assert_eq!(
+            stats["doj"],
+            AgencyStats {
+                sources: 3,
+                documents: 16
+            }
+        );
+        assert_eq!(stats["fbi"].documents, 15);
+        assert_eq!(stats["boston"].documents, 5);
+        assert_eq!(stats["cia"], AgencyStats::default());
+    }
+}
@@ -7,7 +7,7 @@
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Status of a discovered URL in the crawl.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -86,6 +86,10 @@ pub enum DiscoveryMethod {
     Manual,
     /// Imported from Concordance DAT/OPT load files, queued for verification.
     ConcordanceImport,
+    /// Found by listing a video channel or playlist.
+    VideoPlaylist,
+    /// Enclosure of an RSS or Atom feed item (podcasts, meeting audio).
+    Feed,
 }
 
 impl DiscoveryMethod {
@@ -106,6 +110,8 @@ impl DiscoveryMethod {
             Self::CommonPath => "common_path",
             Self::Manual => "manual",
             Self::ConcordanceImport => "concordance_import",
+            Self::VideoPlaylist => "video_playlist",
+            Self::Feed => "feed",
         }
     }
 
@@ -126,6 +132,8 @@ impl DiscoveryMethod {
             "common_path" => Some(Self::CommonPath),
             "manual" => Some(Self::Manual),
             "concordance_import" => Some(Self::ConcordanceImport),
+            "video_playlist" => Some(Self::VideoPlaylist),
+            "feed" => Some(Self::Feed),
             _ => None,
         }
     }
@@ -278,6 +286,10 @@ pub struct CrawlRequest {
     pub was_conditional: bool,
     /// Did we get 304 Not Modified?
     pub was_not_modified: bool,
+
+    /// Did we send transparency mode's identification headers?
+    #[serde(default)]
+    pub identified: bool,
 }
 
 impl CrawlRequest {
@@ -298,8 +310,133 @@ impl CrawlRequest {
             error: None,
             was_conditional: false,
             was_not_modified: false,
+            identified: false,
+        }
+    }
+}
+
+/// Operator status of a detected anti-bot challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeStatus {
+    /// Waiting for an operator; requests to the domain are paused.
+    Pending,
+    /// Solved; requests resume with the captured cookies.
+    Solved,
+    /// Dismissed without solving; requests resume as before.
+    Dismissed,
+}
+
+impl ChallengeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Solved => "solved",
+            Self::Dismissed => "dismissed",
         }
     }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "solved" => Some(Self::Solved),
+            "dismissed" => Some(Self::Dismissed),
+            _ => None,
+        }
+    }
+}
+
+/// A CAPTCHA or anti-bot challenge page hit while crawling.
+///
+/// Serves as the operator task: while pending, requests to `domain` are
+/// paused; once solved, the captured cookies are sent with later requests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlChallenge {
+    pub id: i64,
+    pub source_id: String,
+    pub domain: String,
+    /// URL that returned the challenge page.
+    pub url: String,
+    /// Detector that matched (e.g. "cloudflare", "recaptcha").
+    pub kind: String,
+    pub status: ChallengeStatus,
+    pub detected_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// `Cookie` header value captured when the challenge was solved.
+    pub cookies: Option<String>,
+}
+
+/// A domain paused by the circuit breaker after answering too many
+/// requests with 403, 429 or 451.
+///
+/// Requests to `domain` are refused until `paused_until`, then resume at a
+/// cautious delay that eases back to normal as requests succeed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainIncident {
+    pub id: i64,
+    pub source_id: String,
+    pub domain: String,
+    /// Responses in the window that tripped the breaker.
+    pub responses: u32,
+    /// How many of them were blocking statuses.
+    pub blocked: u32,
+    /// Count of each blocking status code seen.
+    pub statuses: BTreeMap<u16, u32>,
+    pub opened_at: DateTime<Utc>,
+    pub paused_until: DateTime<Utc>,
+}
+
+impl DomainIncident {
+    /// Whether the domain is still paused.
+    pub fn is_active(&self) -> bool {
+        self.paused_until > Utc::now()
+    }
+}
+
+/// An archived version of a listing/index page.
+///
+/// A new snapshot is stored only when the page content changes; refetching
+/// identical content just extends `last_seen_at`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingSnapshot {
+    pub id: i64,
+    pub source_id: String,
+    pub url: String,
+    pub content_hash: String,
+    /// Number of links on the page when captured.
+    pub link_count: u32,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// How many fetches returned this exact content.
+    pub fetch_count: u32,
+}
+
+/// Snapshot history summary for one listing page URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingSnapshotSummary {
+    pub source_id: String,
+    pub url: String,
+    pub snapshots: u64,
+    pub last_changed_at: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+/// Last observed JSON shape of an API endpoint a source discovers from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSchemaState {
+    pub source_id: String,
+    /// Endpoint path from the scraper config (parent and child endpoints are
+    /// tracked separately).
+    pub endpoint: String,
+    /// Hash of `paths`; empty until a response with results has been seen.
+    pub fingerprint: String,
+    /// JSON paths present in the last fingerprinted response.
+    pub paths: Vec<String>,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Unacknowledged drift: what changed or stopped matching.
+    pub drift: Option<String>,
+    pub drift_at: Option<DateTime<Utc>>,
 }
 
 /// Aggregate state of a crawl for a source.
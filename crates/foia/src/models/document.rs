@@ -18,21 +18,38 @@ pub struct ContentHashes {
 }
 
 /// Processing status of a document.
+///
+/// Statuses form a state machine: documents move forward through
+/// `Pending → Downloaded → TextExtracted → OcrComplete → Indexed`, may fail
+/// from any active stage, and can be requeued back to an earlier stage.
+/// See [`DocumentStatus::can_transition_to`] for the allowed edges.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum DocumentStatus {
     Pending,
     Downloaded,
+    TextExtracted,
     OcrComplete,
     Indexed,
     Failed,
 }
 
 impl DocumentStatus {
+    /// All statuses in pipeline order.
+    pub const ALL: [DocumentStatus; 6] = [
+        Self::Pending,
+        Self::Downloaded,
+        Self::TextExtracted,
+        Self::OcrComplete,
+        Self::Indexed,
+        Self::Failed,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Pending => "pending",
             Self::Downloaded => "downloaded",
+            Self::TextExtracted => "text_extracted",
             Self::OcrComplete => "ocr_complete",
             Self::Indexed => "indexed",
             Self::Failed => "failed",
@@ -43,12 +60,176 @@ impl DocumentStatus {
         match s {
             "pending" => Some(Self::Pending),
             "downloaded" => Some(Self::Downloaded),
+            "text_extracted" => Some(Self::TextExtracted),
             "ocr_complete" => Some(Self::OcrComplete),
             "indexed" => Some(Self::Indexed),
             "failed" => Some(Self::Failed),
             _ => None,
         }
     }
+
+    /// Statuses a document in this status may move to.
+    ///
+    /// Forward edges may skip stages (a text-layer PDF goes straight from
+    /// downloaded to indexed); backward edges are requeues.
+    pub fn allowed_transitions(&self) -> &'static [DocumentStatus] {
+        match self {
+            Self::Pending => &[Self::Downloaded, Self::Failed],
+            Self::Downloaded => &[
+                Self::TextExtracted,
+                Self::OcrComplete,
+                Self::Indexed,
+                Self::Failed,
+                Self::Pending,
+            ],
+            Self::TextExtracted => &[
+                Self::OcrComplete,
+                Self::Indexed,
+                Self::Failed,
+                Self::Downloaded,
+                Self::Pending,
+            ],
+            Self::OcrComplete => &[Self::Indexed, Self::Failed, Self::Downloaded, Self::Pending],
+            Self::Indexed => &[
+                Self::OcrComplete,
+                Self::Failed,
+                Self::Downloaded,
+                Self::Pending,
+            ],
+            Self::Failed => &[Self::Pending, Self::Downloaded],
+        }
+    }
+
+    /// Whether moving from this status to `next` is allowed.
+    /// Re-setting the current status is always allowed.
+    pub fn can_transition_to(&self, next: DocumentStatus) -> bool {
+        *self == next || self.allowed_transitions().contains(&next)
+    }
+
+    /// Statuses from which a document may move to `self`, including `self`.
+    pub fn predecessors(&self) -> Vec<DocumentStatus> {
+        Self::ALL
+            .into_iter()
+            .filter(|from| from.can_transition_to(*self))
+            .collect()
+    }
+
+    /// Whether `next` is an earlier pipeline stage reachable from here,
+    /// i.e. a valid target for requeueing documents.
+    pub fn can_requeue_to(&self, next: DocumentStatus) -> bool {
+        if *self == next || next == Self::Failed || !self.can_transition_to(next) {
+            return false;
+        }
+        // Failed documents may be requeued to any stage they can re-enter.
+        *self == Self::Failed || next.stage() < self.stage()
+    }
+
+    /// Position in the forward pipeline (`Failed` sorts last).
+    fn stage(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|s| s == self)
+            .unwrap_or(usize::MAX)
+    }
+}
+
+/// A version whose stored file is missing and could not be re-fetched.
+///
+/// The version row, its extracted text, and its pages are kept; only the
+/// original bytes are gone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LostFile {
+    pub version_id: i64,
+    pub document_id: String,
+    /// Why the file could not be restored (e.g. "HTTP 404").
+    pub reason: String,
+    pub lost_at: DateTime<Utc>,
+}
+
+/// A document under legal hold: nothing may delete its files or versions
+/// until the hold is released.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHold {
+    pub document_id: String,
+    /// Why the document is held (e.g. a case or request number).
+    pub reason: String,
+    pub placed_at: DateTime<Utc>,
+}
+
+/// A document cut out of a multi-document bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSplit {
+    pub document_id: String,
+    /// The bundle it was cut from.
+    pub parent_id: String,
+    /// Version of the bundle the page range refers to.
+    pub version_id: i64,
+    pub first_page: u32,
+    pub last_page: u32,
+    /// Signal that marked where the document starts, e.g. "bates"; `None`
+    /// for the first document of the bundle.
+    pub boundary: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A chapter or section in a document's table of contents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSection {
+    /// Row id; 0 until stored.
+    pub id: i64,
+    pub document_id: String,
+    pub version_id: i64,
+    /// Nesting depth, 1 for top-level chapters.
+    pub level: u32,
+    pub title: String,
+    pub first_page: u32,
+    pub last_page: u32,
+    /// "bookmarks" when read from the PDF outline, "headings" when
+    /// detected in the page text.
+    pub origin: String,
+}
+
+/// What happened to a document in a [`DocumentChange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeType {
+    Created,
+    /// The document, one of its versions or its attachment text changed.
+    Updated,
+    Deleted,
+}
+
+impl ChangeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Deleted => "deleted",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "created" => Some(Self::Created),
+            "updated" => Some(Self::Updated),
+            "deleted" => Some(Self::Deleted),
+            _ => None,
+        }
+    }
+}
+
+/// An entry in the change journal, written by database triggers on every
+/// document mutation.
+///
+/// Sequence numbers only grow, so a consumer that remembers the last one
+/// it processed can ask for everything after it, deletions included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChange {
+    pub seq: i64,
+    pub document_id: String,
+    pub source_id: String,
+    pub change_type: ChangeType,
+    pub changed_at: DateTime<Utc>,
 }
 
 /// A specific version of a document's content.
@@ -476,6 +657,50 @@ impl DocumentDisplay {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_status_transitions() {
+        use DocumentStatus::*;
+
+        assert!(Pending.can_transition_to(Downloaded));
+        assert!(Downloaded.can_transition_to(TextExtracted));
+        assert!(TextExtracted.can_transition_to(OcrComplete));
+        assert!(OcrComplete.can_transition_to(Indexed));
+        assert!(Downloaded.can_transition_to(Indexed));
+        assert!(Indexed.can_transition_to(Indexed));
+
+        assert!(!Pending.can_transition_to(Indexed));
+        assert!(!Pending.can_transition_to(OcrComplete));
+        assert!(!Failed.can_transition_to(Indexed));
+
+        assert!(Pending.predecessors().contains(&Failed));
+        assert_eq!(
+            TextExtracted.predecessors(),
+            vec![Downloaded, TextExtracted]
+        );
+    }
+
+    #[test]
+    fn test_status_requeue_targets() {
+        use DocumentStatus::*;
+
+        assert!(Indexed.can_requeue_to(OcrComplete));
+        assert!(Indexed.can_requeue_to(Downloaded));
+        assert!(Failed.can_requeue_to(Pending));
+        assert!(Failed.can_requeue_to(Downloaded));
+
+        assert!(!Downloaded.can_requeue_to(Indexed));
+        assert!(!Indexed.can_requeue_to(Indexed));
+        assert!(!Indexed.can_requeue_to(Failed));
+        assert!(!Indexed.can_requeue_to(TextExtracted));
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in DocumentStatus::ALL {
+            assert_eq!(DocumentStatus::from_str(status.as_str()), Some(status));
+        }
+    }
+
     #[test]
     fn test_compute_hash() {
         let content = b"Hello, World!";
@@ -44,6 +44,64 @@ impl PageOcrStatus {
     }
 }
 
+/// How one OCR reading of a page scored in arbitration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadingScore {
+    pub backend: String,
+    /// Mean word-level similarity to the other readings (0-1); `None`
+    /// when it was the only one.
+    pub agreement: Option<f32>,
+    /// Share of its words that passed the dictionary check (0-1).
+    pub dictionary: f32,
+    /// Combined score the readings were ranked by (0-1).
+    pub score: f32,
+}
+
+/// How a page's final text was chosen from its OCR readings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageTextDecision {
+    pub page_id: i64,
+    /// "single", "agreement", "fused", "llm_judge", or "human" when a
+    /// curator corrected the text.
+    pub method: String,
+    /// Backend of the reading the final text is based on.
+    pub backend: Option<String>,
+    pub scores: Vec<ReadingScore>,
+    /// Why the reading was chosen, for reviewers.
+    pub rationale: String,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// A curator's correction of a page's text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageTextRevision {
+    pub id: i64,
+    pub page_id: i64,
+    pub editor: String,
+    /// Final text before the edit; `None` if the page had none.
+    pub previous_text: Option<String>,
+    pub text: String,
+    /// Changed lines, `-` removed and `+` added, under `@@` line markers.
+    pub diff: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What was done to a page's image before OCR, so the result can be
+/// reproduced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PagePreprocessing {
+    pub page_id: i64,
+    /// Clockwise rotation applied to upright the page (0, 90, 180 or 270).
+    pub rotation: i32,
+    /// Skew corrected by deskewing, in degrees.
+    pub skew_angle: Option<f32>,
+    /// Steps run, in order ("rotate", "deskew", "despeckle", "normalize").
+    pub steps: Vec<String>,
+    /// Tool and version that ran them.
+    pub tool: Option<String>,
+    pub applied_at: DateTime<Utc>,
+}
+
 /// A single page of a document with its extracted text.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentPage {
@@ -0,0 +1,166 @@
+//! Crawl frontiers shared by the sources on one host.
+//!
+//! Two sources on the same site otherwise each discover and fetch the same
+//! URLs and compete for the same rate limit. Sources that opt in with
+//! `frontier.shared` queue into one frontier instead: the crawl URLs of
+//! every member are checked before a URL is queued, and each URL is queued
+//! under the member it belongs to.
+
+use regex::Regex;
+use url::Url;
+
+use crate::config::ScraperConfig;
+
+/// One source in a shared frontier.
+#[derive(Debug, Clone)]
+struct FrontierMember {
+    source_id: String,
+    patterns: Vec<Regex>,
+}
+
+/// The sources sharing a host's frontier.
+#[derive(Debug, Clone)]
+pub struct SharedFrontier {
+    host: String,
+    members: Vec<FrontierMember>,
+}
+
+impl SharedFrontier {
+    /// The frontier `source_id` shares, if it opted in and another source
+    /// on its host did too. Members keep the order of `configs`, which
+    /// decides who gets a URL more than one member's patterns match.
+    pub fn for_source(source_id: &str, configs: &[(String, ScraperConfig)]) -> Option<Self> {
+        let (_, config) = configs.iter().find(|(id, _)| id == source_id)?;
+        if !config.frontier.shared {
+            return None;
+        }
+        let host = frontier_host(config)?;
+
+        let members: Vec<FrontierMember> = configs
+            .iter()
+            .filter(|(_, c)| {
+                c.frontier.shared && frontier_host(c).as_deref() == Some(host.as_str())
+            })
+            .map(|(id, c)| FrontierMember {
+                source_id: id.clone(),
+                patterns: c
+                    .frontier
+                    .patterns
+                    .iter()
+                    .filter_map(|p| match Regex::new(p) {
+                        Ok(re) => Some(re),
+                        Err(e) => {
+                            tracing::warn!("Ignoring frontier pattern '{}' of {}: {}", p, id, e);
+                            None
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        if members.len() < 2 {
+            return None;
+        }
+        Some(Self { host, members })
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// IDs of every member source.
+    pub fn source_ids(&self) -> Vec<String> {
+        self.members.iter().map(|m| m.source_id.clone()).collect()
+    }
+
+    /// Whether `source_id` is a member.
+    pub fn contains(&self, source_id: &str) -> bool {
+        self.members.iter().any(|m| m.source_id == source_id)
+    }
+
+    /// The member a URL found by `discovered_by` belongs to: the first
+    /// whose patterns match it, or the discoverer when none do.
+    pub fn owner<'a>(&'a self, discovered_by: &'a str, url: &str) -> &'a str {
+        self.members
+            .iter()
+            .find(|m| m.patterns.iter().any(|re| re.is_match(url)))
+            .map(|m| m.source_id.as_str())
+            .unwrap_or(discovered_by)
+    }
+}
+
+/// Host a source is crawled on, ignoring a leading `www.`.
+fn frontier_host(config: &ScraperConfig) -> Option<String> {
+    let url = Url::parse(&config.base_url_or("")).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    Some(
+        host.strip_prefix("www.")
+            .map(str::to_string)
+            .unwrap_or(host),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FrontierConfig;
+
+    fn source(
+        id: &str,
+        base_url: &str,
+        shared: bool,
+        patterns: &[&str],
+    ) -> (String, ScraperConfig) {
+        let config = ScraperConfig {
+            base_url: Some(base_url.to_string()),
+            frontier: FrontierConfig {
+                shared,
+                patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            },
+            ..Default::default()
+        };
+        (id.to_string(), config)
+    }
+
+    #[test]
+    fn test_frontier_membership_and_owner() {
+        let configs = vec![
+            source(
+                "city_police",
+                "https://www.city.gov/police",
+                true,
+                &["/police/"],
+            ),
+            source(
+                "city_council",
+                "https://city.gov/council",
+                true,
+                &["/council/", r"\.minutes\.pdf$"],
+            ),
+            source("city_clerk", "https://city.gov/clerk", false, &[]),
+            source("county", "https://county.gov", true, &[]),
+        ];
+
+        let frontier = SharedFrontier::for_source("city_police", &configs).unwrap();
+        assert_eq!(frontier.host(), "city.gov");
+        assert_eq!(frontier.source_ids(), vec!["city_police", "city_council"]);
+        assert!(!frontier.contains("city_clerk"));
+
+        assert_eq!(
+            frontier.owner("city_police", "https://city.gov/council/2024.pdf"),
+            "city_council"
+        );
+        assert_eq!(
+            frontier.owner("city_council", "https://city.gov/police/report.pdf"),
+            "city_police"
+        );
+        // Unclaimed URLs stay with whoever found them
+        assert_eq!(
+            frontier.owner("city_council", "https://city.gov/files/x.pdf"),
+            "city_council"
+        );
+
+        // Not opted in, or alone on its host
+        assert!(SharedFrontier::for_source("city_clerk", &configs).is_none());
+        assert!(SharedFrontier::for_source("county", &configs).is_none());
+    }
+}
@@ -1,17 +1,34 @@
 //! Data models for foia.
 
+mod agency;
 mod archive;
 mod crawl;
 mod document;
 mod document_page;
+mod frontier;
+mod record_type;
+mod relation;
 mod service_status;
 mod source;
 mod virtual_file;
 
+pub use agency::{Agency, AgencySettings, AgencyStats, AgencyTree};
 pub use archive::ArchiveService;
-pub use crawl::{CrawlRequest, CrawlUrl, DiscoveryMethod, UrlStatus};
-pub use document::{Document, DocumentStatus, DocumentVersion};
-pub use document_page::{DocumentPage, PageOcrStatus};
+pub use crawl::{
+    ApiSchemaState, ChallengeStatus, CrawlChallenge, CrawlRequest, CrawlUrl, DiscoveryMethod,
+    DomainIncident, ListingSnapshot, ListingSnapshotSummary, UrlStatus,
+};
+pub use document::{
+    ChangeType, Document, DocumentChange, DocumentSection, DocumentSplit, DocumentStatus,
+    DocumentVersion, LegalHold, LostFile,
+};
+pub use document_page::{
+    DocumentPage, PageOcrStatus, PagePreprocessing, PageTextDecision, PageTextRevision,
+    ReadingScore,
+};
+pub use frontier::SharedFrontier;
+pub use record_type::RecordType;
+pub use relation::RelationType;
 pub use service_status::{ScraperStats, ServiceState, ServiceStatus, ServiceType};
 pub use source::{Source, SourceType};
-pub use virtual_file::{VirtualFile, VirtualFileStatus};
+pub use virtual_file::{VirtualFile, VirtualFileStatus, EMAIL_BODY_PLACEHOLDER};
@@ -0,0 +1,63 @@
+//! Record types assigned by document classification.
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of record a document is, as opposed to its file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordType {
+    Letter,
+    Memo,
+    Email,
+    Form,
+    Invoice,
+    Report,
+    Photo,
+    Map,
+}
+
+impl RecordType {
+    pub const ALL: [RecordType; 8] = [
+        Self::Letter,
+        Self::Memo,
+        Self::Email,
+        Self::Form,
+        Self::Invoice,
+        Self::Report,
+        Self::Photo,
+        Self::Map,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Letter => "letter",
+            Self::Memo => "memo",
+            Self::Email => "email",
+            Self::Form => "form",
+            Self::Invoice => "invoice",
+            Self::Report => "report",
+            Self::Photo => "photo",
+            Self::Map => "map",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str().eq_ignore_ascii_case(s.trim()))
+    }
+
+    /// Display name for facets and badges.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Letter => "Letter",
+            Self::Memo => "Memo",
+            Self::Email => "Email",
+            Self::Form => "Form",
+            Self::Invoice => "Invoice",
+            Self::Report => "Report",
+            Self::Photo => "Photo",
+            Self::Map => "Map",
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Typed relationships between documents.
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of link between two documents.
+///
+/// Edges read "document <type> related document": an `AttachmentOf` edge
+/// from A to B means A was found attached to (or inside) B.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RelationType {
+    /// Enclosed in an email or archive.
+    AttachmentOf,
+    /// Filed as an exhibit to a brief, report, or response letter.
+    ExhibitTo,
+    /// A later release or revision replacing the related document.
+    Supersedes,
+    /// Cites or mentions the related document.
+    References,
+    /// Cut out of a multi-document bundle (see `docs split`).
+    PartOf,
+}
+
+impl RelationType {
+    pub const ALL: [RelationType; 5] = [
+        Self::AttachmentOf,
+        Self::ExhibitTo,
+        Self::Supersedes,
+        Self::References,
+        Self::PartOf,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AttachmentOf => "attachment-of",
+            Self::ExhibitTo => "exhibit-to",
+            Self::Supersedes => "supersedes",
+            Self::References => "references",
+            Self::PartOf => "part-of",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "attachment-of" => Some(Self::AttachmentOf),
+            "exhibit-to" => Some(Self::ExhibitTo),
+            "supersedes" => Some(Self::Supersedes),
+            "references" => Some(Self::References),
+            "part-of" => Some(Self::PartOf),
+            _ => None,
+        }
+    }
+
+    /// Label for the edge seen from the document it points at.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::AttachmentOf => "Attachment of",
+            Self::ExhibitTo => "Exhibit to",
+            Self::Supersedes => "Supersedes",
+            Self::References => "References",
+            Self::PartOf => "Part of",
+        }
+    }
+
+    /// Label for the edge seen from the related document's side.
+    pub fn inverse_label(&self) -> &'static str {
+        match self {
+            Self::AttachmentOf => "Has attachment",
+            Self::ExhibitTo => "Has exhibit",
+            Self::Supersedes => "Superseded by",
+            Self::References => "Referenced by",
+            Self::PartOf => "Has part",
+        }
+    }
+}
@@ -59,6 +59,11 @@ pub struct Source {
     pub created_at: DateTime<Utc>,
     /// When the source was last scraped.
     pub last_scraped: Option<DateTime<Utc>>,
+    /// Whether an operator paused all traffic to the source. Changed only
+    /// through `DieselSourceRepository::set_paused`; saving a source
+    /// leaves it as it is.
+    #[serde(default)]
+    pub paused: bool,
 }
 
 impl Source {
@@ -72,6 +77,7 @@ impl Source {
             metadata: serde_json::json!({}),
             created_at: Utc::now(),
             last_scraped: None,
+            paused: false,
         }
     }
 }
@@ -8,6 +8,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Archive path of the row recorded for an email without attachments, so the
+/// email is not scanned again. It has no content of its own.
+pub const EMAIL_BODY_PLACEHOLDER: &str = "_email_body";
+
 /// A file contained within an archive that is not stored on disk.
 ///
 /// Virtual files track their location within the parent archive and store
@@ -104,4 +108,9 @@ impl VirtualFile {
             updated_at: now,
         }
     }
+
+    /// Whether this row only marks an email without attachments as scanned.
+    pub fn is_placeholder(&self) -> bool {
+        self.archive_path == EMAIL_BODY_PLACEHOLDER
+    }
 }
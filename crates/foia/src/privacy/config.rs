@@ -5,7 +5,23 @@ use std::env;
 
 /// Read SOCKS proxy URL from environment.
 pub fn socks_proxy_from_env() -> Option<String> {
-    env::var("SOCKS_PROXY").ok().filter(|s| !s.is_empty())
+    env::var("SOCKS_PROXY")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(|s| resolve_proxy(&s))
+}
+
+/// Resolve a `secret://` proxy reference (for proxies with credentials).
+/// An unresolvable reference is returned as-is so the connection fails
+/// instead of silently going direct.
+fn resolve_proxy(proxy: &str) -> String {
+    match crate::secrets::resolve(proxy) {
+        Ok(url) => url,
+        Err(e) => {
+            tracing::error!("Cannot resolve SOCKS proxy: {}", e);
+            proxy.to_string()
+        }
+    }
 }
 
 #[cfg(not(feature = "embedded-tor"))]
@@ -225,7 +241,8 @@ pub struct PrivacyConfig {
     pub obfuscation: bool,
 
     /// External SOCKS5 proxy URL (bypasses embedded Arti).
-    /// Set via `SOCKS_PROXY` environment variable.
+    /// Set via `SOCKS_PROXY` environment variable. May be a `secret://`
+    /// reference when the URL carries credentials.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[prefer(default)]
     pub socks_proxy: Option<String>,
@@ -381,8 +398,8 @@ impl PrivacyConfig {
     }
 
     /// Get the SOCKS proxy URL if configured (external proxy only).
-    pub fn proxy_url(&self) -> Option<&str> {
-        self.socks_proxy.as_deref()
+    pub fn proxy_url(&self) -> Option<String> {
+        self.socks_proxy.as_deref().map(resolve_proxy)
     }
 
     /// Get the effective proxy URL for external commands (like yt-dlp).
@@ -399,7 +416,7 @@ impl PrivacyConfig {
 
         // External proxy takes precedence
         if let Some(ref proxy) = self.socks_proxy {
-            return Some(proxy.clone());
+            return Some(resolve_proxy(proxy));
         }
 
         // Try embedded Arti if available
@@ -491,7 +508,7 @@ See https://rustsec.org/advisories/RUSTSEC-2023-0071 for details."#
     pub fn get_socks_proxy_url(&self) -> Option<String> {
         // Explicit proxy configured
         if let Some(ref proxy) = self.socks_proxy {
-            return Some(proxy.clone());
+            return Some(resolve_proxy(proxy));
         }
 
         // Direct mode - no proxy
@@ -230,6 +230,36 @@ impl RateLimiter {
         }
     }
 
+    /// Resume a domain after a circuit breaker pause at no faster than
+    /// `delay`. The usual recovery then eases it back to the base delay as
+    /// requests succeed.
+    pub async fn ramp_up(&self, domain: &str, delay: Duration) {
+        let base_delay_ms = self.config.base_delay.as_millis() as u64;
+
+        let mut state = match self
+            .backend
+            .get_or_create_domain(domain, base_delay_ms)
+            .await
+        {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Failed to get domain state for {}: {}", domain, e);
+                return;
+            }
+        };
+
+        state.consecutive_successes = 0;
+        state.in_backoff = true;
+        state.current_delay_ms = state
+            .current_delay_ms
+            .max(delay.as_millis() as u64)
+            .min(self.config.max_delay.as_millis() as u64);
+
+        if let Err(e) = self.backend.update_domain(&state).await {
+            warn!("Failed to update domain state for {}: {}", domain, e);
+        }
+    }
+
     /// Report a client error (4xx other than 429) - no delay change.
     pub async fn report_client_error(&self, domain: &str) {
         let base_delay_ms = self.config.base_delay.as_millis() as u64;
@@ -506,6 +536,24 @@ mod tests {
         assert!(!state.in_backoff);
     }
 
+    #[tokio::test]
+    async fn test_ramp_up_sets_cautious_delay() {
+        let limiter = create_test_limiter();
+        limiter.acquire("https://example.com/doc").await;
+
+        limiter
+            .ramp_up("example.com", Duration::from_millis(5_000))
+            .await;
+
+        let state = limiter
+            .backend
+            .get_or_create_domain("example.com", 100)
+            .await
+            .unwrap();
+        assert!(state.in_backoff);
+        assert_eq!(state.current_delay_ms, 5_000);
+    }
+
     #[tokio::test]
     async fn test_is_definite_rate_limit() {
         assert!(RateLimiter::is_definite_rate_limit(429));
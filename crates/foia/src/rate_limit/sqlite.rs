@@ -130,6 +130,28 @@ impl DieselRateLimitBackend {
 
         Ok(result.map(Self::record_to_state))
     }
+
+    /// Persisted state of every domain, slowest first.
+    pub async fn list_states(&self) -> RateLimitResult<Vec<DomainRateState>> {
+        let records: Vec<RateLimitStateRecord> = with_conn_split!(self.pool,
+            sqlite: conn => {
+                rate_limit_state::table
+                    .order(rate_limit_state::current_delay_ms.desc())
+                    .load::<RateLimitStateRecord>(&mut conn)
+                    .await
+                    .map_err(|e| RateLimitError::Database(e.to_string()))?
+            },
+            postgres: conn => {
+                rate_limit_state::table
+                    .order(rate_limit_state::current_delay_ms.desc())
+                    .load::<RateLimitStateRecord>(&mut conn)
+                    .await
+                    .map_err(|e| RateLimitError::Database(e.to_string()))?
+            }
+        );
+
+        Ok(records.into_iter().map(Self::record_to_state).collect())
+    }
 }
 
 #[async_trait]
@@ -293,5 +315,14 @@ mod tests {
 
         assert_eq!(s1.current_delay_ms, 100);
         assert_eq!(s2.current_delay_ms, 200);
+
+        let domains: Vec<String> = backend
+            .list_states()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.domain)
+            .collect();
+        assert_eq!(domains, vec!["test.org", "example.com"]);
     }
 }
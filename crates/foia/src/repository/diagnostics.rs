@@ -0,0 +1,444 @@
+//! Query plan and storage diagnostics.
+//!
+//! Runs `EXPLAIN` on representative versions of the repository's hot
+//! queries against the live database, so operators can see which ones have
+//! fallen back to full table scans (missing or dropped indexes, databases
+//! created by older tooling) and which tables take up the space.
+
+use diesel_async::RunQueryDsl;
+
+use super::pool::{DbPool, DieselError};
+use crate::with_conn_split;
+
+/// An index a hot query relies on. Names match the migrations, so creating
+/// one on a database that already has it is a no-op.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexSpec {
+    pub name: &'static str,
+    pub table: &'static str,
+    pub columns: &'static str,
+}
+
+impl IndexSpec {
+    /// Portable `CREATE INDEX` statement for this index.
+    pub fn create_sql(&self) -> String {
+        format!(
+            "CREATE INDEX IF NOT EXISTS {} ON {}({})",
+            self.name, self.table, self.columns
+        )
+    }
+}
+
+/// A representative query from a hot repository path.
+#[derive(Debug, Clone, Copy)]
+pub struct HotQuery {
+    pub name: &'static str,
+    pub sql: &'static str,
+    pub index: IndexSpec,
+}
+
+/// Queries run on every page load, crawl step or processing batch, with
+/// placeholder literals in place of bind parameters.
+pub const HOT_QUERIES: &[HotQuery] = &[
+    HotQuery {
+        name: "browse: recent documents",
+        sql: "SELECT id FROM documents ORDER BY updated_at DESC LIMIT 50",
+        index: IndexSpec {
+            name: "idx_documents_updated_at",
+            table: "documents",
+            columns: "updated_at",
+        },
+    },
+    HotQuery {
+        name: "browse: documents in a source",
+        sql: "SELECT id FROM documents WHERE source_id = 'example' ORDER BY updated_at DESC LIMIT 50",
+        index: IndexSpec {
+            name: "idx_documents_source_updated",
+            table: "documents",
+            columns: "source_id, updated_at",
+        },
+    },
+    HotQuery {
+        name: "status counts for a source",
+        sql: "SELECT status, COUNT(*) FROM documents WHERE source_id = 'example' GROUP BY status",
+        index: IndexSpec {
+            name: "idx_documents_source_status",
+            table: "documents",
+            columns: "source_id, status",
+        },
+    },
+    HotQuery {
+        name: "document by URL",
+        sql: "SELECT id FROM documents WHERE source_url = 'https://example.com/doc.pdf'",
+        index: IndexSpec {
+            name: "idx_documents_url",
+            table: "documents",
+            columns: "source_url",
+        },
+    },
+    HotQuery {
+        name: "versions of a document",
+        sql: "SELECT id, mime_type FROM document_versions WHERE document_id = 'example' ORDER BY id DESC",
+        index: IndexSpec {
+            name: "idx_versions_document",
+            table: "document_versions",
+            columns: "document_id",
+        },
+    },
+    HotQuery {
+        name: "duplicate check by content hash",
+        sql: "SELECT document_id FROM document_versions WHERE content_hash = 'example'",
+        index: IndexSpec {
+            name: "idx_versions_hash",
+            table: "document_versions",
+            columns: "content_hash",
+        },
+    },
+    HotQuery {
+        name: "pages of a version",
+        sql: "SELECT page_number FROM document_pages WHERE document_id = 'example' AND version_id = 1",
+        index: IndexSpec {
+            name: "idx_pages_doc_version",
+            table: "document_pages",
+            columns: "document_id, version_id",
+        },
+    },
+    HotQuery {
+        name: "pages awaiting OCR",
+        sql: "SELECT id FROM document_pages WHERE ocr_status = 'pending' LIMIT 100",
+        index: IndexSpec {
+            name: "idx_document_pages_ocr_status",
+            table: "document_pages",
+            columns: "ocr_status",
+        },
+    },
+    HotQuery {
+        name: "analysis results for a version",
+        sql: "SELECT analysis_type, status FROM document_analysis_results WHERE document_id = 'example' AND version_id = 1",
+        index: IndexSpec {
+            name: "idx_dar_doc_version_type_status",
+            table: "document_analysis_results",
+            columns: "document_id, version_id, analysis_type, status",
+        },
+    },
+    HotQuery {
+        name: "OCR cache by page image",
+        sql: "SELECT id FROM page_ocr_results WHERE image_hash = 'example' AND backend = 'tesseract'",
+        index: IndexSpec {
+            name: "idx_page_ocr_results_hash_backend",
+            table: "page_ocr_results",
+            columns: "image_hash, backend",
+        },
+    },
+    HotQuery {
+        name: "crawl queue",
+        sql: "SELECT url FROM crawl_urls WHERE source_id = 'example' AND status = 'discovered' ORDER BY depth, discovered_at LIMIT 100",
+        index: IndexSpec {
+            name: "idx_crawl_urls_source_status",
+            table: "crawl_urls",
+            columns: "source_id, status",
+        },
+    },
+    HotQuery {
+        name: "recent crawl requests",
+        sql: "SELECT url FROM crawl_requests WHERE source_id = 'example' ORDER BY request_at DESC LIMIT 100",
+        index: IndexSpec {
+            name: "idx_crawl_requests_source",
+            table: "crawl_requests",
+            columns: "source_id, request_at",
+        },
+    },
+    HotQuery {
+        name: "files inside an archive",
+        sql: "SELECT id FROM virtual_files WHERE document_id = 'example'",
+        index: IndexSpec {
+            name: "idx_virtual_files_document",
+            table: "virtual_files",
+            columns: "document_id",
+        },
+    },
+];
+
+/// The plan the database chose for a hot query.
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    pub query: &'static HotQuery,
+    /// Plan lines as reported by `EXPLAIN`.
+    pub lines: Vec<String>,
+    /// Tables read in full.
+    pub full_scans: Vec<String>,
+}
+
+impl QueryPlan {
+    pub fn is_full_scan(&self) -> bool {
+        !self.full_scans.is_empty()
+    }
+}
+
+/// Tables read in full according to SQLite `EXPLAIN QUERY PLAN` details.
+///
+/// `SCAN t` reads every row; `SCAN t USING INDEX` walks an index in order
+/// (to satisfy ORDER BY under a LIMIT) and `SEARCH` is a keyed lookup.
+pub fn sqlite_full_scans(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("SCAN ")?;
+            if rest.contains(" USING ") {
+                return None;
+            }
+            rest.split_whitespace().next().map(str::to_string)
+        })
+        .collect()
+}
+
+/// Tables read in full according to Postgres `EXPLAIN` output.
+pub fn postgres_full_scans(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once("Seq Scan on ")?;
+            rest.split_whitespace().next().map(str::to_string)
+        })
+        .collect()
+}
+
+/// Size of a table or index.
+#[derive(Debug, Clone)]
+pub struct RelationSize {
+    pub name: String,
+    /// Table the relation belongs to (itself, for tables).
+    pub table: String,
+    pub is_index: bool,
+    /// On-disk size; `None` when the SQLite build lacks `dbstat`.
+    pub bytes: Option<u64>,
+    /// Row count for tables (an estimate on Postgres).
+    pub rows: Option<u64>,
+}
+
+#[derive(diesel::QueryableByName)]
+struct SqlitePlanRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    detail: String,
+}
+
+#[cfg(feature = "postgres")]
+#[derive(diesel::QueryableByName)]
+struct PostgresPlanRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    #[diesel(column_name = "QUERY PLAN")]
+    line: String,
+}
+
+#[derive(diesel::QueryableByName)]
+struct SizeRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    table_name: String,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    is_index: bool,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    bytes: Option<i64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    row_count: Option<i64>,
+}
+
+#[derive(diesel::QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    count: i64,
+}
+
+/// Explain one query, returning the plan lines.
+pub async fn explain(pool: &DbPool, sql: &str) -> Result<Vec<String>, DieselError> {
+    with_conn_split!(pool,
+        sqlite: conn => {
+            let rows: Vec<SqlitePlanRow> =
+                diesel::sql_query(format!("EXPLAIN QUERY PLAN {}", sql))
+                    .load(&mut conn)
+                    .await?;
+            Ok(rows.into_iter().map(|r| r.detail).collect())
+        },
+        postgres: conn => {
+            let rows: Vec<PostgresPlanRow> = diesel::sql_query(format!("EXPLAIN {}", sql))
+                .load(&mut conn)
+                .await?;
+            Ok(rows.into_iter().map(|r| r.line).collect())
+        }
+    )
+}
+
+/// Explain every query in [`HOT_QUERIES`].
+pub async fn explain_hot_queries(pool: &DbPool) -> Result<Vec<QueryPlan>, DieselError> {
+    let mut plans = Vec::with_capacity(HOT_QUERIES.len());
+    for query in HOT_QUERIES {
+        let lines = explain(pool, query.sql).await?;
+        let full_scans = if pool.is_sqlite() {
+            sqlite_full_scans(&lines)
+        } else {
+            postgres_full_scans(&lines)
+        };
+        plans.push(QueryPlan {
+            query,
+            lines,
+            full_scans,
+        });
+    }
+    Ok(plans)
+}
+
+/// Create an index and refresh the planner statistics for its table.
+pub async fn create_index(pool: &DbPool, index: &IndexSpec) -> Result<(), DieselError> {
+    let create = index.create_sql();
+    let analyze = format!("ANALYZE {}", index.table);
+    crate::with_conn!(pool, conn, {
+        diesel::sql_query(&create).execute(&mut conn).await?;
+        diesel::sql_query(&analyze).execute(&mut conn).await?;
+        Ok(())
+    })
+}
+
+/// Sizes of all tables and indexes, largest first.
+pub async fn relation_sizes(pool: &DbPool) -> Result<Vec<RelationSize>, DieselError> {
+    let rows: Vec<SizeRow> = with_conn_split!(pool,
+        sqlite: conn => {
+            // dbstat is optional in SQLite builds; without it only row
+            // counts are reported
+            let sized: Result<Vec<SizeRow>, _> = diesel::sql_query(
+                r#"SELECT s.name AS name, m.tbl_name AS table_name, m.type = 'index' AS is_index,
+                          SUM(s.pgsize) AS bytes, NULL AS row_count
+                   FROM dbstat s JOIN sqlite_master m ON m.name = s.name
+                   WHERE s.name NOT LIKE 'sqlite_%' OR m.type = 'index'
+                   GROUP BY s.name
+                   ORDER BY bytes DESC"#,
+            )
+            .load(&mut conn)
+            .await;
+            let mut rows = match sized {
+                Ok(rows) => rows,
+                Err(_) => {
+                    diesel::sql_query(
+                        r#"SELECT name, tbl_name AS table_name, type = 'index' AS is_index,
+                                  NULL AS bytes, NULL AS row_count
+                           FROM sqlite_master
+                           WHERE type IN ('table', 'index') AND name NOT LIKE 'sqlite_stat%'
+                           ORDER BY name"#,
+                    )
+                    .load(&mut conn)
+                    .await?
+                }
+            };
+            for row in rows.iter_mut().filter(|r| !r.is_index) {
+                let count: CountRow =
+                    diesel::sql_query(format!("SELECT COUNT(*) AS count FROM \"{}\"", row.name))
+                        .get_result(&mut conn)
+                        .await?;
+                row.row_count = Some(count.count);
+            }
+            Ok::<_, DieselError>(rows)
+        },
+        postgres: conn => {
+            diesel::sql_query(
+                r#"SELECT c.relname::TEXT AS name,
+                          COALESCE(t.relname, c.relname)::TEXT AS table_name,
+                          c.relkind = 'i' AS is_index,
+                          CASE WHEN c.relkind = 'i' THEN pg_relation_size(c.oid)
+                               ELSE pg_table_size(c.oid) END AS bytes,
+                          CASE WHEN c.relkind = 'i' OR c.reltuples < 0 THEN NULL
+                               ELSE c.reltuples::BIGINT END AS row_count
+                   FROM pg_class c
+                   JOIN pg_namespace n ON n.oid = c.relnamespace
+                   LEFT JOIN pg_index i ON i.indexrelid = c.oid
+                   LEFT JOIN pg_class t ON t.oid = i.indrelid
+                   WHERE n.nspname = current_schema() AND c.relkind IN ('r', 'i')
+                   ORDER BY bytes DESC"#,
+            )
+            .load(&mut conn)
+            .await
+        }
+    )?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| RelationSize {
+            name: r.name,
+            table: r.table_name,
+            is_index: r.is_index,
+            bytes: r.bytes.map(|b| b as u64),
+            rows: r.row_count.map(|n| n as u64),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::migrations;
+
+    fn lines(plan: &[&str]) -> Vec<String> {
+        plan.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sqlite_full_scans() {
+        let plan = lines(&[
+            "SEARCH documents USING INDEX idx_documents_url (source_url=?)",
+            "SCAN documents USING INDEX idx_documents_updated_at",
+            "SCAN crawl_urls",
+            "USE TEMP B-TREE FOR ORDER BY",
+        ]);
+        assert_eq!(sqlite_full_scans(&plan), vec!["crawl_urls"]);
+    }
+
+    #[test]
+    fn test_postgres_full_scans() {
+        let plan = lines(&[
+            "Limit  (cost=0.00..4.12 rows=50 width=32)",
+            "  ->  Seq Scan on documents  (cost=0.00..1210.00 rows=14700 width=32)",
+            "        Filter: (source_url = 'x'::text)",
+            "  ->  Index Scan using idx_versions_document on document_versions dv",
+        ]);
+        assert_eq!(postgres_full_scans(&plan), vec!["documents"]);
+    }
+
+    #[tokio::test]
+    async fn test_hot_queries_use_migrated_indexes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        migrations::run_migrations(&format!("sqlite:{}", db_path.display()), false)
+            .await
+            .unwrap();
+        let pool = DbPool::sqlite_from_path(&db_path);
+
+        let plans = explain_hot_queries(&pool).await.unwrap();
+        let scanning: Vec<_> = plans
+            .iter()
+            .filter(|p| p.is_full_scan())
+            .map(|p| p.query.name)
+            .collect();
+        assert!(scanning.is_empty(), "full scans: {:?}", scanning);
+
+        crate::with_conn!(pool, conn, {
+            diesel::sql_query("DROP INDEX idx_documents_url")
+                .execute(&mut conn)
+                .await
+        })
+        .unwrap();
+        let plans = explain_hot_queries(&pool).await.unwrap();
+        let by_url = plans
+            .iter()
+            .find(|p| p.query.index.name == "idx_documents_url")
+            .unwrap();
+        assert_eq!(by_url.full_scans, vec!["documents"]);
+
+        create_index(&pool, &by_url.query.index).await.unwrap();
+        let plans = explain_hot_queries(&pool).await.unwrap();
+        assert!(plans.iter().all(|p| !p.is_full_scan()));
+
+        let sizes = relation_sizes(&pool).await.unwrap();
+        let documents = sizes.iter().find(|s| s.name == "documents").unwrap();
+        assert!(!documents.is_index);
+        assert_eq!(documents.rows, Some(0));
+    }
+}
@@ -0,0 +1,279 @@
+//! Diesel-based agency repository.
+//!
+//! Stores the agency hierarchy in the `agencies` table and source
+//! membership in `agency_sources`. Works with both SQLite and PostgreSQL.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::models::{AgencyRecord, NewAgency};
+use super::parse_datetime;
+use super::pool::{DbPool, DieselError};
+use crate::config::ScraperConfig;
+use crate::models::{Agency, AgencyTree};
+use crate::schema::{agencies, agency_sources};
+use crate::{with_conn, with_conn_split};
+
+impl TryFrom<AgencyRecord> for Agency {
+    type Error = DieselError;
+
+    fn try_from(record: AgencyRecord) -> Result<Self, Self::Error> {
+        let settings = serde_json::from_str(&record.config)
+            .map_err(|e| DieselError::DeserializationError(Box::new(e)))?;
+        Ok(Agency {
+            id: record.id,
+            name: record.name,
+            parent_id: record.parent_id,
+            settings,
+            created_at: parse_datetime(&record.created_at),
+            updated_at: parse_datetime(&record.updated_at),
+        })
+    }
+}
+
+/// Diesel-based agency repository.
+#[derive(Clone)]
+pub struct DieselAgencyRepository {
+    pool: DbPool,
+}
+
+impl DieselAgencyRepository {
+    /// Create a new agency repository.
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Get an agency by ID.
+    pub async fn get(&self, id: &str) -> Result<Option<Agency>, DieselError> {
+        let record: Option<AgencyRecord> = with_conn!(self.pool, conn, {
+            agencies::table
+                .find(id)
+                .first::<AgencyRecord>(&mut conn)
+                .await
+                .optional()?
+        });
+        record.map(Agency::try_from).transpose()
+    }
+
+    /// Get all agencies.
+    pub async fn get_all(&self) -> Result<Vec<Agency>, DieselError> {
+        let records: Vec<AgencyRecord> = with_conn!(self.pool, conn, {
+            agencies::table
+                .order(agencies::name.asc())
+                .load::<AgencyRecord>(&mut conn)
+                .await?
+        });
+        records.into_iter().map(Agency::try_from).collect()
+    }
+
+    /// Map of source ID to the agency it is assigned to.
+    pub async fn memberships(&self) -> Result<HashMap<String, String>, DieselError> {
+        let rows: Vec<(String, String)> = with_conn!(self.pool, conn, {
+            agency_sources::table
+                .select((agency_sources::source_id, agency_sources::agency_id))
+                .load(&mut conn)
+                .await?
+        });
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Load the whole hierarchy with source membership.
+    pub async fn tree(&self) -> Result<AgencyTree, DieselError> {
+        Ok(AgencyTree::new(
+            self.get_all().await?,
+            self.memberships().await?,
+        ))
+    }
+
+    /// Fill settings a source's scraper config leaves unset from its agency
+    /// chain. Sources outside any agency are left unchanged.
+    pub async fn apply_inherited(
+        &self,
+        source_id: &str,
+        config: &mut ScraperConfig,
+    ) -> Result<(), DieselError> {
+        self.tree().await?.apply(source_id, config);
+        Ok(())
+    }
+
+    /// Insert or update an agency.
+    pub async fn upsert(&self, agency: &Agency) -> Result<(), DieselError> {
+        let config = serde_json::to_string(&agency.settings)
+            .map_err(|e| DieselError::SerializationError(Box::new(e)))?;
+        let created_at = agency.created_at.to_rfc3339();
+        let now = Utc::now().to_rfc3339();
+        let new = NewAgency {
+            id: &agency.id,
+            name: &agency.name,
+            parent_id: agency.parent_id.as_deref(),
+            config: &config,
+            created_at: &created_at,
+            updated_at: &now,
+        };
+
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(agencies::table)
+                    .values(&new)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(agencies::table)
+                    .values(&new)
+                    .on_conflict(agencies::id)
+                    .do_update()
+                    .set((
+                        agencies::name.eq(&agency.name),
+                        agencies::parent_id.eq(agency.parent_id.as_deref()),
+                        agencies::config.eq(&config),
+                        agencies::updated_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// Delete an agency. Its sub-agencies move up to its parent and its
+    /// sources become unassigned. Returns false if it didn't exist.
+    pub async fn delete(&self, id: &str) -> Result<bool, DieselError> {
+        let Some(agency) = self.get(id).await? else {
+            return Ok(false);
+        };
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            diesel::update(agencies::table.filter(agencies::parent_id.eq(id)))
+                .set((
+                    agencies::parent_id.eq(agency.parent_id.as_deref()),
+                    agencies::updated_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            diesel::delete(agency_sources::table.filter(agency_sources::agency_id.eq(id)))
+                .execute(&mut conn)
+                .await?;
+            let rows = diesel::delete(agencies::table.find(id))
+                .execute(&mut conn)
+                .await?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Assign a source to an agency, replacing any earlier assignment.
+    pub async fn assign_source(&self, source_id: &str, agency_id: &str) -> Result<(), DieselError> {
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(agency_sources::table)
+                    .values((
+                        agency_sources::source_id.eq(source_id),
+                        agency_sources::agency_id.eq(agency_id),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(agency_sources::table)
+                    .values((
+                        agency_sources::source_id.eq(source_id),
+                        agency_sources::agency_id.eq(agency_id),
+                    ))
+                    .on_conflict(agency_sources::source_id)
+                    .do_update()
+                    .set(agency_sources::agency_id.eq(agency_id))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// Remove a source from its agency. Returns false if it had none.
+    pub async fn unassign_source(&self, source_id: &str) -> Result<bool, DieselError> {
+        let rows = with_conn!(self.pool, conn, {
+            diesel::delete(agency_sources::table.find(source_id))
+                .execute(&mut conn)
+                .await?
+        });
+        Ok(rows > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::pool::SqlitePool;
+    use diesel_async::SimpleAsyncConnection;
+    use tempfile::tempdir;
+
+    async fn setup_test_db() -> (DbPool, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let sqlite_pool = SqlitePool::from_path(&db_path);
+        let mut conn = sqlite_pool.get().await.unwrap();
+
+        conn.batch_execute(
+            r#"CREATE TABLE IF NOT EXISTS agencies (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                parent_id TEXT,
+                config TEXT NOT NULL DEFAULT '{}',
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS agency_sources (
+                source_id TEXT PRIMARY KEY,
+                agency_id TEXT NOT NULL
+            );"#,
+        )
+        .await
+        .unwrap();
+
+        (DbPool::Sqlite(sqlite_pool), dir)
+    }
+
+    #[tokio::test]
+    async fn test_agency_crud_and_membership() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselAgencyRepository::new(pool);
+
+        let mut doj = Agency::new("doj".to_string(), "Justice".to_string(), None);
+        doj.settings.request_delay_ms = Some(3000);
+        repo.upsert(&doj).await.unwrap();
+        let fbi = Agency::new(
+            "fbi".to_string(),
+            "FBI".to_string(),
+            Some("doj".to_string()),
+        );
+        repo.upsert(&fbi).await.unwrap();
+        repo.assign_source("vault", "fbi").await.unwrap();
+
+        let loaded = repo.get("doj").await.unwrap().unwrap();
+        assert_eq!(loaded.settings.request_delay_ms, Some(3000));
+        assert_eq!(repo.get_all().await.unwrap().len(), 2);
+
+        let mut config = ScraperConfig::default();
+        repo.apply_inherited("vault", &mut config).await.unwrap();
+        assert_eq!(config.request_delay_ms, Some(3000));
+
+        // Reassignment replaces the earlier agency
+        repo.assign_source("vault", "doj").await.unwrap();
+        assert_eq!(repo.memberships().await.unwrap()["vault"], "doj");
+
+        // Deleting an agency moves its children up and drops its sources
+        repo.assign_source("vault", "fbi").await.unwrap();
+        assert!(repo.delete("doj").await.unwrap());
+        assert_eq!(repo.get("fbi").await.unwrap().unwrap().parent_id, None);
+        assert!(repo.delete("fbi").await.unwrap());
+        assert!(repo.memberships().await.unwrap().is_empty());
+        assert!(!repo.unassign_source("vault").await.unwrap());
+        assert!(!repo.delete("fbi").await.unwrap());
+    }
+}
@@ -20,12 +20,9 @@ const MAX_HISTORY_ENTRIES: i64 = 16;
 pub struct DieselConfigHistoryEntry {
     #[allow(dead_code)]
     pub uuid: String,
-    #[allow(dead_code)]
     pub created_at: DateTime<Utc>,
     pub data: String,
-    #[allow(dead_code)]
     pub format: String,
-    #[allow(dead_code)]
     pub hash: String,
 }
 
@@ -103,6 +100,63 @@ impl DieselConfigHistoryRepository {
         Ok(true)
     }
 
+    /// Insert a configuration entry unless it matches the most recent one.
+    /// Unlike [`insert_if_new`](Self::insert_if_new), a config seen before
+    /// is recorded again, so rolling back becomes the latest entry.
+    /// Returns true if inserted.
+    pub async fn insert_if_changed(
+        &self,
+        data: &str,
+        format: &str,
+        hash: &str,
+    ) -> Result<bool, DieselError> {
+        if self.get_latest_hash().await?.as_deref() == Some(hash) {
+            return Ok(false);
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let uuid = uuid::Uuid::new_v4().to_string();
+        let new_entry = NewConfigHistory {
+            uuid: &uuid,
+            created_at: &now,
+            data,
+            format,
+            hash,
+        };
+
+        with_conn!(self.pool, conn, {
+            diesel::insert_into(configuration_history::table)
+                .values(&new_entry)
+                .execute(&mut conn)
+                .await?;
+            Ok::<(), DieselError>(())
+        })?;
+
+        self.prune_old_entries().await?;
+        Ok(true)
+    }
+
+    /// Entries whose hash starts with `prefix` (hex digits), most recent first.
+    pub async fn find_by_hash_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<DieselConfigHistoryEntry>, DieselError> {
+        let pattern = format!("{}%", prefix);
+        with_conn!(self.pool, conn, {
+            configuration_history::table
+                .filter(configuration_history::hash.like(pattern))
+                .order(configuration_history::created_at.desc())
+                .load::<ConfigHistoryRecord>(&mut conn)
+                .await
+                .map(|records| {
+                    records
+                        .into_iter()
+                        .map(DieselConfigHistoryEntry::from)
+                        .collect()
+                })
+        })
+    }
+
     /// Get the most recent configuration entry.
     pub async fn get_latest(&self) -> Result<Option<DieselConfigHistoryEntry>, DieselError> {
         with_conn!(self.pool, conn, {
@@ -116,7 +170,6 @@ impl DieselConfigHistoryRepository {
     }
 
     /// Get all configuration history entries (most recent first).
-    #[allow(dead_code)]
     pub async fn get_all(&self) -> Result<Vec<DieselConfigHistoryEntry>, DieselError> {
         with_conn!(self.pool, conn, {
             configuration_history::table
@@ -239,5 +292,17 @@ mod tests {
         // Get all
         let all = repo.get_all().await.unwrap();
         assert_eq!(all.len(), 2);
+
+        // A config seen before is recorded again once something else is latest
+        assert!(!repo
+            .insert_if_changed("{\"key\": \"value2\"}", "json", "hash2")
+            .await
+            .unwrap());
+        assert!(repo
+            .insert_if_changed("{\"key\": \"value1\"}", "json", "hash1")
+            .await
+            .unwrap());
+        assert_eq!(repo.get_latest_hash().await.unwrap().unwrap(), "hash1");
+        assert_eq!(repo.find_by_hash_prefix("hash1").await.unwrap().len(), 2);
     }
 }
@@ -5,6 +5,7 @@
 
 use std::path::Path;
 
+use super::diesel_agency::DieselAgencyRepository;
 use super::diesel_config_history::DieselConfigHistoryRepository;
 use super::diesel_crawl::DieselCrawlRepository;
 use super::diesel_document::DieselDocumentRepository;
@@ -98,6 +99,11 @@ impl DieselDbContext {
         DieselScraperConfigRepository::new(self.pool.clone())
     }
 
+    /// Get an agency repository.
+    pub fn agencies(&self) -> DieselAgencyRepository {
+        DieselAgencyRepository::new(self.pool.clone())
+    }
+
     /// Get a service status repository.
     pub fn service_status(&self) -> DieselServiceStatusRepository {
         DieselServiceStatusRepository::new(self.pool.clone())
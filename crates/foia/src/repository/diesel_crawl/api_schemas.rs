@@ -0,0 +1,181 @@
+//! API response schema tracking for the crawl repository.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselCrawlRepository;
+use crate::models::ApiSchemaState;
+use crate::repository::models::ApiSchemaRecord;
+use crate::repository::pool::DieselError;
+use crate::repository::{parse_datetime, parse_datetime_opt};
+use crate::schema::api_schemas;
+use crate::with_conn;
+
+impl From<ApiSchemaRecord> for ApiSchemaState {
+    fn from(record: ApiSchemaRecord) -> Self {
+        Self {
+            source_id: record.source_id,
+            endpoint: record.endpoint,
+            fingerprint: record.fingerprint,
+            paths: serde_json::from_str(&record.paths).unwrap_or_default(),
+            first_seen_at: parse_datetime(&record.first_seen_at),
+            last_seen_at: parse_datetime(&record.last_seen_at),
+            drift: record.drift,
+            drift_at: parse_datetime_opt(record.drift_at),
+        }
+    }
+}
+
+impl DieselCrawlRepository {
+    /// Get the stored schema for an API endpoint.
+    pub async fn get_api_schema(
+        &self,
+        source_id: &str,
+        endpoint: &str,
+    ) -> Result<Option<ApiSchemaState>, DieselError> {
+        with_conn!(self.pool, conn, {
+            api_schemas::table
+                .find((source_id, endpoint))
+                .select(ApiSchemaRecord::as_select())
+                .first(&mut conn)
+                .await
+                .optional()
+                .map(|r| r.map(ApiSchemaState::from))
+        })
+    }
+
+    /// Store the latest fingerprint and paths for an API endpoint.
+    pub async fn save_api_schema(
+        &self,
+        source_id: &str,
+        endpoint: &str,
+        fingerprint: &str,
+        paths: &[String],
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let paths_json = serde_json::to_string(paths).unwrap_or_else(|_| "[]".to_string());
+
+        with_conn!(self.pool, conn, {
+            // Try to update first
+            let updated = diesel::update(api_schemas::table.find((source_id, endpoint)))
+                .set((
+                    api_schemas::fingerprint.eq(fingerprint),
+                    api_schemas::paths.eq(&paths_json),
+                    api_schemas::last_seen_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+
+            // If no row was updated, insert
+            if updated == 0 {
+                diesel::insert_into(api_schemas::table)
+                    .values((
+                        api_schemas::source_id.eq(source_id),
+                        api_schemas::endpoint.eq(endpoint),
+                        api_schemas::fingerprint.eq(fingerprint),
+                        api_schemas::paths.eq(&paths_json),
+                        api_schemas::first_seen_at.eq(&now),
+                        api_schemas::last_seen_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Flag drift on an API endpoint. Replaces any earlier unacknowledged
+    /// message, so the latest problem is the one shown.
+    pub async fn record_api_drift(
+        &self,
+        source_id: &str,
+        endpoint: &str,
+        message: &str,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+
+        with_conn!(self.pool, conn, {
+            let updated = diesel::update(api_schemas::table.find((source_id, endpoint)))
+                .set((
+                    api_schemas::drift.eq(Some(message)),
+                    api_schemas::drift_at.eq(Some(now.as_str())),
+                    api_schemas::last_seen_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+
+            // Extraction can fail on the very first crawl, before any
+            // response was fingerprinted
+            if updated == 0 {
+                diesel::insert_into(api_schemas::table)
+                    .values((
+                        api_schemas::source_id.eq(source_id),
+                        api_schemas::endpoint.eq(endpoint),
+                        api_schemas::fingerprint.eq(""),
+                        api_schemas::paths.eq("[]"),
+                        api_schemas::first_seen_at.eq(&now),
+                        api_schemas::last_seen_at.eq(&now),
+                        api_schemas::drift.eq(Some(message)),
+                        api_schemas::drift_at.eq(Some(now.as_str())),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// List tracked API endpoints, optionally for one source.
+    pub async fn list_api_schemas(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<Vec<ApiSchemaState>, DieselError> {
+        with_conn!(self.pool, conn, {
+            let mut query = api_schemas::table
+                .select(ApiSchemaRecord::as_select())
+                .order((api_schemas::source_id.asc(), api_schemas::endpoint.asc()))
+                .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(api_schemas::source_id.eq(source_id));
+            }
+            let records: Vec<ApiSchemaRecord> = query.load(&mut conn).await?;
+            Ok(records.into_iter().map(ApiSchemaState::from).collect())
+        })
+    }
+
+    /// Clear drift for a source's endpoints (or one endpoint), accepting the
+    /// current shape. Returns the number of endpoints cleared.
+    pub async fn acknowledge_api_drift(
+        &self,
+        source_id: &str,
+        endpoint: Option<&str>,
+    ) -> Result<usize, DieselError> {
+        let cleared = (
+            api_schemas::drift.eq(None::<String>),
+            api_schemas::drift_at.eq(None::<String>),
+        );
+
+        with_conn!(self.pool, conn, {
+            let pending = api_schemas::table
+                .filter(api_schemas::source_id.eq(source_id))
+                .filter(api_schemas::drift.is_not_null());
+            match endpoint {
+                Some(endpoint) => {
+                    diesel::update(pending.filter(api_schemas::endpoint.eq(endpoint)))
+                        .set(cleared)
+                        .execute(&mut conn)
+                        .await
+                }
+                None => {
+                    diesel::update(pending)
+                        .set(cleared)
+                        .execute(&mut conn)
+                        .await
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,143 @@
+//! CAPTCHA/anti-bot challenge operations for the crawl repository.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselCrawlRepository;
+use crate::models::{ChallengeStatus, CrawlChallenge};
+use crate::repository::models::CrawlChallengeRecord;
+use crate::repository::pool::DieselError;
+use crate::repository::{parse_datetime, parse_datetime_opt};
+use crate::schema::crawl_challenges;
+use crate::with_conn;
+
+impl From<CrawlChallengeRecord> for CrawlChallenge {
+    fn from(record: CrawlChallengeRecord) -> Self {
+        Self {
+            id: record.id as i64,
+            source_id: record.source_id,
+            domain: record.domain,
+            url: record.url,
+            kind: record.kind,
+            status: ChallengeStatus::from_str(&record.status).unwrap_or(ChallengeStatus::Pending),
+            detected_at: parse_datetime(&record.detected_at),
+            resolved_at: parse_datetime_opt(record.resolved_at),
+            cookies: record.cookies,
+        }
+    }
+}
+
+impl DieselCrawlRepository {
+    /// Record a challenge for a domain, unless one is already pending.
+    /// Returns true if a new challenge was opened.
+    pub async fn open_challenge(
+        &self,
+        source_id: &str,
+        domain: &str,
+        url: &str,
+        kind: &str,
+    ) -> Result<bool, DieselError> {
+        let now = Utc::now().to_rfc3339();
+
+        with_conn!(self.pool, conn, {
+            let pending: i64 = crawl_challenges::table
+                .filter(crawl_challenges::domain.eq(domain))
+                .filter(crawl_challenges::status.eq(ChallengeStatus::Pending.as_str()))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if pending > 0 {
+                return Ok(false);
+            }
+
+            diesel::insert_into(crawl_challenges::table)
+                .values((
+                    crawl_challenges::source_id.eq(source_id),
+                    crawl_challenges::domain.eq(domain),
+                    crawl_challenges::url.eq(url),
+                    crawl_challenges::kind.eq(kind),
+                    crawl_challenges::status.eq(ChallengeStatus::Pending.as_str()),
+                    crawl_challenges::detected_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(true)
+        })
+    }
+
+    /// Get the most recent challenge for a domain.
+    pub async fn latest_challenge(
+        &self,
+        domain: &str,
+    ) -> Result<Option<CrawlChallenge>, DieselError> {
+        let record: Option<CrawlChallengeRecord> = with_conn!(self.pool, conn, {
+            crawl_challenges::table
+                .filter(crawl_challenges::domain.eq(domain))
+                .order(crawl_challenges::id.desc())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(record.map(CrawlChallenge::from))
+    }
+
+    /// Get a challenge by ID.
+    pub async fn get_challenge(&self, id: i64) -> Result<Option<CrawlChallenge>, DieselError> {
+        let record: Option<CrawlChallengeRecord> = with_conn!(self.pool, conn, {
+            crawl_challenges::table
+                .find(id as i32)
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(record.map(CrawlChallenge::from))
+    }
+
+    /// List challenges, newest first, optionally filtered by status.
+    pub async fn list_challenges(
+        &self,
+        status: Option<ChallengeStatus>,
+        limit: usize,
+    ) -> Result<Vec<CrawlChallenge>, DieselError> {
+        let records: Vec<CrawlChallengeRecord> = with_conn!(self.pool, conn, {
+            let mut query = crawl_challenges::table.into_boxed();
+            if let Some(status) = status {
+                query = query.filter(crawl_challenges::status.eq(status.as_str()));
+            }
+            query
+                .order(crawl_challenges::id.desc())
+                .limit(limit as i64)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(CrawlChallenge::from).collect())
+    }
+
+    /// Close a pending challenge as solved (with captured cookies) or
+    /// dismissed. Returns false if no pending challenge has this ID.
+    pub async fn resolve_challenge(
+        &self,
+        id: i64,
+        status: ChallengeStatus,
+        cookies: Option<&str>,
+    ) -> Result<bool, DieselError> {
+        let now = Utc::now().to_rfc3339();
+
+        with_conn!(self.pool, conn, {
+            let updated = diesel::update(
+                crawl_challenges::table
+                    .filter(crawl_challenges::id.eq(id as i32))
+                    .filter(crawl_challenges::status.eq(ChallengeStatus::Pending.as_str())),
+            )
+            .set((
+                crawl_challenges::status.eq(status.as_str()),
+                crawl_challenges::resolved_at.eq(&now),
+                crawl_challenges::cookies.eq(cookies),
+            ))
+            .execute(&mut conn)
+            .await?;
+            Ok(updated > 0)
+        })
+    }
+}
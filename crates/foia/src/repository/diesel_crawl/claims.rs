@@ -0,0 +1,95 @@
+//! In-flight fetch claims for the crawl repository.
+
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselCrawlRepository;
+use crate::repository::pool::DieselError;
+use crate::schema::fetch_claims;
+use crate::{with_conn, with_conn_split};
+
+impl DieselCrawlRepository {
+    /// Claim `key` for a fetch of `url` for `window`.
+    ///
+    /// Returns `None` if the claim was taken, or the URL holding an
+    /// unexpired claim on the same key.
+    pub async fn claim_fetch(
+        &self,
+        source_id: &str,
+        key: &str,
+        url: &str,
+        window: Duration,
+    ) -> Result<Option<String>, DieselError> {
+        let now = Utc::now();
+        let claimed_at = now.to_rfc3339();
+        let expires_at = (now + window).to_rfc3339();
+        let values = (
+            fetch_claims::source_id.eq(source_id),
+            fetch_claims::claim_key.eq(key),
+            fetch_claims::url.eq(url),
+            fetch_claims::claimed_at.eq(&claimed_at),
+            fetch_claims::expires_at.eq(&expires_at),
+        );
+        let expired = fetch_claims::table
+            .filter(fetch_claims::source_id.eq(source_id))
+            .filter(fetch_claims::claim_key.eq(key))
+            .filter(fetch_claims::expires_at.le(&claimed_at));
+
+        let inserted = with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::delete(expired).execute(&mut conn).await?;
+                diesel::insert_or_ignore_into(fetch_claims::table)
+                    .values(values)
+                    .execute(&mut conn)
+                    .await?
+            },
+            postgres: conn => {
+                diesel::delete(expired).execute(&mut conn).await?;
+                diesel::insert_into(fetch_claims::table)
+                    .values(values)
+                    .on_conflict_do_nothing()
+                    .execute(&mut conn)
+                    .await?
+            }
+        );
+        if inserted > 0 {
+            return Ok(None);
+        }
+
+        // None here means the holder released it after the insert; go ahead
+        with_conn!(self.pool, conn, {
+            fetch_claims::table
+                .filter(fetch_claims::source_id.eq(source_id))
+                .filter(fetch_claims::claim_key.eq(key))
+                .select(fetch_claims::url)
+                .first(&mut conn)
+                .await
+                .optional()
+        })
+    }
+
+    /// Give up a claim, so the key can be fetched again right away.
+    pub async fn release_fetch(&self, source_id: &str, key: &str) -> Result<(), DieselError> {
+        with_conn!(self.pool, conn, {
+            diesel::delete(
+                fetch_claims::table
+                    .filter(fetch_claims::source_id.eq(source_id))
+                    .filter(fetch_claims::claim_key.eq(key)),
+            )
+            .execute(&mut conn)
+            .await?;
+            Ok(())
+        })
+    }
+
+    /// Delete expired claims. Returns how many were removed.
+    pub async fn prune_fetch_claims(&self) -> Result<usize, DieselError> {
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            diesel::delete(fetch_claims::table.filter(fetch_claims::expires_at.le(&now)))
+                .execute(&mut conn)
+                .await
+        })
+    }
+}
@@ -5,7 +5,7 @@ use diesel_async::RunQueryDsl;
 
 use super::DieselCrawlRepository;
 use crate::repository::pool::DieselError;
-use crate::schema::{crawl_config, crawl_requests, crawl_urls};
+use crate::schema::{crawl_config, crawl_requests, crawl_urls, fetch_claims};
 use crate::with_conn;
 
 impl DieselCrawlRepository {
@@ -30,6 +30,10 @@ impl DieselCrawlRepository {
                 .execute(&mut conn)
                 .await?;
 
+            diesel::delete(fetch_claims::table.filter(fetch_claims::source_id.eq(source_id)))
+                .execute(&mut conn)
+                .await?;
+
             Ok(())
         })
     }
@@ -45,6 +49,10 @@ impl DieselCrawlRepository {
                 .execute(&mut conn)
                 .await?;
 
+            diesel::delete(fetch_claims::table.filter(fetch_claims::source_id.eq(source_id)))
+                .execute(&mut conn)
+                .await?;
+
             diesel::delete(crawl_config::table.filter(crawl_config::source_id.eq(source_id)))
                 .execute(&mut conn)
                 .await?;
@@ -0,0 +1,113 @@
+//! Circuit breaker incident operations for the crawl repository.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselCrawlRepository;
+use crate::http_client::BreakerTrip;
+use crate::models::DomainIncident;
+use crate::repository::models::DomainIncidentRecord;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::domain_incidents;
+use crate::with_conn;
+
+impl From<DomainIncidentRecord> for DomainIncident {
+    fn from(record: DomainIncidentRecord) -> Self {
+        Self {
+            id: record.id as i64,
+            source_id: record.source_id,
+            domain: record.domain,
+            responses: record.responses as u32,
+            blocked: record.blocked as u32,
+            statuses: serde_json::from_str(&record.statuses).unwrap_or_default(),
+            opened_at: parse_datetime(&record.opened_at),
+            paused_until: parse_datetime(&record.paused_until),
+        }
+    }
+}
+
+impl DieselCrawlRepository {
+    /// Record a circuit breaker trip, unless the domain is already paused.
+    /// Returns true if a new incident was opened.
+    pub async fn open_incident(
+        &self,
+        source_id: &str,
+        trip: &BreakerTrip,
+    ) -> Result<bool, DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let statuses = serde_json::to_string(&trip.statuses).unwrap_or_else(|_| "{}".to_string());
+
+        with_conn!(self.pool, conn, {
+            let active: i64 = domain_incidents::table
+                .filter(domain_incidents::domain.eq(&trip.domain))
+                .filter(domain_incidents::paused_until.gt(&now))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if active > 0 {
+                return Ok(false);
+            }
+
+            diesel::insert_into(domain_incidents::table)
+                .values((
+                    domain_incidents::source_id.eq(source_id),
+                    domain_incidents::domain.eq(&trip.domain),
+                    domain_incidents::responses.eq(trip.responses as i32),
+                    domain_incidents::blocked.eq(trip.blocked as i32),
+                    domain_incidents::statuses.eq(&statuses),
+                    domain_incidents::opened_at.eq(&now),
+                    domain_incidents::paused_until.eq(trip.paused_until.to_rfc3339()),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(true)
+        })
+    }
+
+    /// Get the most recent incident for a domain.
+    pub async fn latest_incident(
+        &self,
+        domain: &str,
+    ) -> Result<Option<DomainIncident>, DieselError> {
+        let record: Option<DomainIncidentRecord> = with_conn!(self.pool, conn, {
+            domain_incidents::table
+                .filter(domain_incidents::domain.eq(domain))
+                .order(domain_incidents::id.desc())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(record.map(DomainIncident::from))
+    }
+
+    /// List incidents, newest first.
+    pub async fn list_incidents(&self, limit: usize) -> Result<Vec<DomainIncident>, DieselError> {
+        let records: Vec<DomainIncidentRecord> = with_conn!(self.pool, conn, {
+            domain_incidents::table
+                .order(domain_incidents::id.desc())
+                .limit(limit as i64)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(DomainIncident::from).collect())
+    }
+
+    /// End a domain's pause early. Returns false if it wasn't paused.
+    pub async fn resume_domain(&self, domain: &str) -> Result<bool, DieselError> {
+        let now = Utc::now().to_rfc3339();
+
+        with_conn!(self.pool, conn, {
+            let updated = diesel::update(
+                domain_incidents::table
+                    .filter(domain_incidents::domain.eq(domain))
+                    .filter(domain_incidents::paused_until.gt(&now)),
+            )
+            .set(domain_incidents::paused_until.eq(&now))
+            .execute(&mut conn)
+            .await?;
+            Ok(updated > 0)
+        })
+    }
+}
@@ -4,29 +4,40 @@
 //!
 //! Split into submodules:
 //! - `mod.rs` (this file): Main struct, From impls, types
-//! - `urls.rs`: URL CRUD operations
+//! - `urls.rs`: URL CRUD operations, routed through a shared frontier
 //! - `queue.rs`: Queue/claiming operations
 //! - `requests.rs`: Request logging
 //! - `stats.rs`: Statistics and analytics
 //! - `config.rs`: Config hash management
 //! - `cleanup.rs`: Cleanup operations
-
+//! - `challenges.rs`: CAPTCHA/anti-bot challenges awaiting an operator
+//! - `snapshots.rs`: Archived listing page snapshots
+//! - `api_schemas.rs`: API response shapes and drift alerts
+//! - `incidents.rs`: Domains paused by the circuit breaker
+//! - `claims.rs`: In-flight fetch claims that suppress duplicate fetches
+
+mod api_schemas;
+mod challenges;
+mod claims;
 mod cleanup;
 mod config;
+mod incidents;
 mod queue;
 mod requests;
+mod snapshots;
 mod stats;
 mod urls;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use diesel::prelude::*;
 
 use super::models::{CrawlRequestRecord, CrawlUrlRecord};
 use super::pool::DbPool;
 use super::{parse_datetime, parse_datetime_opt};
-use crate::models::{CrawlRequest, CrawlUrl, DiscoveryMethod, UrlStatus};
+use crate::models::{CrawlRequest, CrawlUrl, DiscoveryMethod, SharedFrontier, UrlStatus};
 
 /// Common fields for crawl URL database records.
 trait CrawlUrlFields {
@@ -159,6 +170,7 @@ impl TryFrom<CrawlRequestRecord> for CrawlRequest {
             error: record.error,
             was_conditional: record.was_conditional != 0,
             was_not_modified: record.was_not_modified != 0,
+            identified: record.identified != 0,
         })
     }
 }
@@ -167,12 +179,35 @@ impl TryFrom<CrawlRequestRecord> for CrawlRequest {
 #[derive(Clone)]
 pub struct DieselCrawlRepository {
     pool: DbPool,
+    /// Frontier that discovered URLs are queued into, if the crawling
+    /// source shares one.
+    frontier: Option<Arc<SharedFrontier>>,
 }
 
 impl DieselCrawlRepository {
     /// Create a new Diesel crawl repository.
     pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            frontier: None,
+        }
+    }
+
+    /// Queue URLs discovered by the frontier's members into the frontier:
+    /// skipped if any member already has them, and attributed to the
+    /// member they belong to.
+    pub fn with_frontier(mut self, frontier: SharedFrontier) -> Self {
+        self.frontier = Some(Arc::new(frontier));
+        self
+    }
+
+    pub fn frontier(&self) -> Option<&SharedFrontier> {
+        self.frontier.as_deref()
+    }
+
+    /// The frontier a source's URLs are queued into, if it is a member.
+    fn frontier_of(&self, source_id: &str) -> Option<&SharedFrontier> {
+        self.frontier().filter(|f| f.contains(source_id))
     }
 }
 
@@ -349,7 +384,9 @@ impl TryFrom<CrawlUrlRecordRaw> for CrawlUrl {
 mod tests {
     use super::super::pool::SqlitePool;
     use super::*;
+    use crate::http_client::BreakerTrip;
     use diesel_async::SimpleAsyncConnection;
+    use std::collections::BTreeMap;
     use tempfile::tempdir;
 
     async fn setup_test_db() -> (DbPool, tempfile::TempDir) {
@@ -361,6 +398,17 @@ mod tests {
 
         conn.batch_execute(
             r#"
+            CREATE TABLE IF NOT EXISTS sources (
+                id TEXT PRIMARY KEY,
+                source_type TEXT NOT NULL,
+                name TEXT NOT NULL,
+                base_url TEXT NOT NULL,
+                metadata TEXT NOT NULL DEFAULT '{}',
+                created_at TEXT NOT NULL,
+                last_scraped TEXT,
+                paused INTEGER NOT NULL DEFAULT 0
+            );
+
             CREATE TABLE IF NOT EXISTS crawl_urls (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 url TEXT NOT NULL,
@@ -396,7 +444,8 @@ mod tests {
                 duration_ms INTEGER,
                 error TEXT,
                 was_conditional INTEGER NOT NULL DEFAULT 0,
-                was_not_modified INTEGER NOT NULL DEFAULT 0
+                was_not_modified INTEGER NOT NULL DEFAULT 0,
+                identified INTEGER NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS crawl_config (
@@ -404,6 +453,50 @@ mod tests {
                 config_hash TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             );
+
+            CREATE TABLE IF NOT EXISTS listing_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_id TEXT NOT NULL,
+                url TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                content TEXT NOT NULL,
+                link_count INTEGER NOT NULL DEFAULT 0,
+                first_seen_at TEXT NOT NULL,
+                last_seen_at TEXT NOT NULL,
+                fetch_count INTEGER NOT NULL DEFAULT 1
+            );
+
+            CREATE TABLE IF NOT EXISTS api_schemas (
+                source_id TEXT NOT NULL,
+                endpoint TEXT NOT NULL,
+                fingerprint TEXT NOT NULL,
+                paths TEXT NOT NULL DEFAULT '[]',
+                first_seen_at TEXT NOT NULL,
+                last_seen_at TEXT NOT NULL,
+                drift TEXT,
+                drift_at TEXT,
+                PRIMARY KEY (source_id, endpoint)
+            );
+
+            CREATE TABLE IF NOT EXISTS domain_incidents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_id TEXT NOT NULL,
+                domain TEXT NOT NULL,
+                responses INTEGER NOT NULL,
+                blocked INTEGER NOT NULL,
+                statuses TEXT NOT NULL DEFAULT '{}',
+                opened_at TEXT NOT NULL,
+                paused_until TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS fetch_claims (
+                source_id TEXT NOT NULL,
+                claim_key TEXT NOT NULL,
+                url TEXT NOT NULL,
+                claimed_at TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                PRIMARY KEY (source_id, claim_key)
+            );
             "#,
         )
         .await
@@ -487,6 +580,102 @@ mod tests {
         assert!(pending.is_none());
     }
 
+    #[tokio::test]
+    async fn test_claim_skips_paused_sources() {
+        let (pool, _dir) = setup_test_db().await;
+        let sources = crate::repository::DieselSourceRepository::new(pool.clone());
+        let repo = DieselCrawlRepository::new(pool);
+
+        sources
+            .save(&crate::models::Source::new(
+                "paused-source".to_string(),
+                crate::models::SourceType::Custom,
+                "Paused".to_string(),
+                "https://example.com".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert!(sources.set_paused("paused-source", true).await.unwrap());
+        repo.add_url(&CrawlUrl::new(
+            "https://example.com/paused".to_string(),
+            "paused-source".to_string(),
+            DiscoveryMethod::Seed,
+            None,
+            0,
+        ))
+        .await
+        .unwrap();
+
+        assert!(repo.claim_pending_url(None).await.unwrap().is_none());
+
+        sources.set_paused("paused-source", false).await.unwrap();
+        let claimed = repo.claim_pending_url(None).await.unwrap().unwrap();
+        assert_eq!(claimed.url, "https://example.com/paused");
+    }
+
+    #[tokio::test]
+    async fn test_shared_frontier_dedups_and_attributes() {
+        let (pool, _dir) = setup_test_db().await;
+        let member = |id: &str, pattern: &str| {
+            let mut config = crate::config::ScraperConfig {
+                base_url: Some("https://city.gov".to_string()),
+                ..Default::default()
+            };
+            config.frontier.shared = true;
+            config.frontier.patterns = vec![pattern.to_string()];
+            (id.to_string(), config)
+        };
+        let configs = vec![member("police", "/police/"), member("council", "/council/")];
+        let frontier = SharedFrontier::for_source("police", &configs).unwrap();
+        let repo = DieselCrawlRepository::new(pool).with_frontier(frontier);
+        let url = |source: &str, url: &str| {
+            CrawlUrl::new(
+                url.to_string(),
+                source.to_string(),
+                DiscoveryMethod::HtmlLink,
+                None,
+                1,
+            )
+        };
+
+        // Found by police, but it's the council's
+        assert!(repo
+            .add_url(&url("police", "https://city.gov/council/minutes.pdf"))
+            .await
+            .unwrap());
+        let queued = repo
+            .get_url("council", "https://city.gov/council/minutes.pdf")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(queued.discovery_context["discovered_by"], "police");
+        assert!(repo
+            .queued_elsewhere("police", "https://city.gov/council/minutes.pdf")
+            .await
+            .unwrap());
+        assert!(!repo
+            .queued_elsewhere("council", "https://city.gov/council/minutes.pdf")
+            .await
+            .unwrap());
+
+        // The council finding it again doesn't queue it twice
+        assert!(!repo
+            .add_url(&url("council", "https://city.gov/council/minutes.pdf"))
+            .await
+            .unwrap());
+        // Nor does police finding something council already found
+        assert!(repo
+            .add_url(&url("council", "https://city.gov/files/budget.pdf"))
+            .await
+            .unwrap());
+        assert!(!repo
+            .add_url(&url("police", "https://city.gov/files/budget.pdf"))
+            .await
+            .unwrap());
+        assert_eq!(repo.count_by_source("police").await.unwrap(), 0);
+        assert_eq!(repo.count_by_source("council").await.unwrap(), 2);
+    }
+
     #[tokio::test]
     async fn test_config_hash() {
         let (pool, _dir) = setup_test_db().await;
@@ -519,6 +708,46 @@ mod tests {
         assert!(changed);
     }
 
+    #[tokio::test]
+    async fn test_get_requests_for_urls() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselCrawlRepository::new(pool);
+
+        let mut first = CrawlRequest::new(
+            "test-source".to_string(),
+            "https://example.com/a.pdf".to_string(),
+            "GET".to_string(),
+        );
+        first.response_status = Some(200);
+        repo.log_request(&first).await.unwrap();
+
+        let other = CrawlRequest::new(
+            "test-source".to_string(),
+            "https://example.com/other".to_string(),
+            "GET".to_string(),
+        );
+        repo.log_request(&other).await.unwrap();
+
+        let mut second = first.clone();
+        second.request_at = first.request_at + chrono::Duration::seconds(60);
+        second.response_status = Some(304);
+        second.was_not_modified = true;
+        second.identified = true;
+        repo.log_request(&second).await.unwrap();
+
+        let requests = repo
+            .get_requests_for_urls(&["https://example.com/a.pdf".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].response_status, Some(200));
+        assert!(requests[1].was_not_modified);
+        assert!(!requests[0].identified);
+        assert!(requests[1].identified);
+
+        assert!(repo.get_requests_for_urls(&[]).await.unwrap().is_empty());
+    }
+
     async fn insert_raw_crawl(pool: &DbPool, sql: &str) {
         match pool {
             DbPool::Sqlite(ref sqlite_pool) => {
@@ -530,6 +759,193 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_listing_snapshots_dedup_by_hash() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselCrawlRepository::new(pool);
+        let url = "https://example.com/reading-room";
+
+        assert!(repo
+            .record_listing_snapshot("src", url, "<a href=\"/a.pdf\">A</a>", 1)
+            .await
+            .unwrap());
+        // Identical content only bumps the existing snapshot
+        assert!(!repo
+            .record_listing_snapshot("src", url, "<a href=\"/a.pdf\">A</a>", 1)
+            .await
+            .unwrap());
+        assert!(repo
+            .record_listing_snapshot("src", url, "<a href=\"/b.pdf\">B</a>", 1)
+            .await
+            .unwrap());
+
+        let snapshots = repo.list_listing_snapshots("src", url).await.unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].fetch_count, 2);
+
+        let (previous, content) = repo
+            .previous_listing_snapshot(&snapshots[0])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(previous.id, snapshots[1].id);
+        assert!(content.contains("a.pdf"));
+
+        let summaries = repo.list_snapshot_urls(Some("src")).await.unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].snapshots, 2);
+    }
+
+    #[tokio::test]
+    async fn test_listing_snapshots_compressed_and_pruned() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselCrawlRepository::new(pool);
+        let url = "https://example.com/library";
+        let old = "<a href=\"/a.pdf\">A</a>".repeat(200);
+        let new = "<a href=\"/b.pdf\">B</a>".repeat(200);
+
+        repo.record_listing_snapshot("src", url, &old, 200)
+            .await
+            .unwrap();
+        repo.record_listing_snapshot("src", url, &new, 200)
+            .await
+            .unwrap();
+
+        let contents = repo.latest_listing_contents("src").await.unwrap();
+        assert_eq!(contents[url], new);
+        let snapshots = repo.list_listing_snapshots("src", url).await.unwrap();
+        let (_, content) = repo
+            .get_listing_snapshot(snapshots[1].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(content, old);
+
+        // Everything is past a future cutoff, but the latest snapshot stays
+        let cutoff = chrono::Utc::now() + chrono::Duration::days(1);
+        assert_eq!(
+            repo.prune_listing_snapshots(Some("src"), cutoff)
+                .await
+                .unwrap(),
+            1
+        );
+        let remaining = repo.list_listing_snapshots("src", url).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, snapshots[0].id);
+    }
+
+    #[tokio::test]
+    async fn test_api_schema_drift_lifecycle() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselCrawlRepository::new(pool);
+
+        // Drift before any fingerprint creates the row
+        repo.record_api_drift("src", "/api/items", "no URLs extracted")
+            .await
+            .unwrap();
+        let state = repo
+            .get_api_schema("src", "/api/items")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.fingerprint, "");
+        assert_eq!(state.drift.as_deref(), Some("no URLs extracted"));
+
+        let paths = vec!["results".to_string(), "results[]".to_string()];
+        repo.save_api_schema("src", "/api/items", "abc", &paths)
+            .await
+            .unwrap();
+        let state = repo
+            .get_api_schema("src", "/api/items")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.fingerprint, "abc");
+        assert_eq!(state.paths, paths);
+        // Saving a fingerprint does not clear unacknowledged drift
+        assert!(state.drift.is_some());
+
+        assert_eq!(repo.acknowledge_api_drift("src", None).await.unwrap(), 1);
+        assert_eq!(repo.acknowledge_api_drift("src", None).await.unwrap(), 0);
+        let states = repo.list_api_schemas(Some("src")).await.unwrap();
+        assert_eq!(states.len(), 1);
+        assert!(states[0].drift.is_none());
+        assert!(states[0].drift_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_domain_incident_lifecycle() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselCrawlRepository::new(pool);
+
+        let trip = BreakerTrip {
+            domain: "agency.gov".to_string(),
+            responses: 12,
+            blocked: 11,
+            statuses: BTreeMap::from([(429, 9), (451, 2)]),
+            paused_until: chrono::Utc::now() + chrono::Duration::minutes(30),
+        };
+        assert!(repo.open_incident("fbi", &trip).await.unwrap());
+        // One incident per pause
+        assert!(!repo.open_incident("fbi", &trip).await.unwrap());
+
+        let incident = repo.latest_incident("agency.gov").await.unwrap().unwrap();
+        assert!(incident.is_active());
+        assert_eq!(incident.statuses, trip.statuses);
+        assert!(repo.latest_incident("other.gov").await.unwrap().is_none());
+
+        assert!(repo.resume_domain("agency.gov").await.unwrap());
+        assert!(!repo.resume_domain("agency.gov").await.unwrap());
+        let incident = repo.latest_incident("agency.gov").await.unwrap().unwrap();
+        assert!(!incident.is_active());
+
+        // A resumed domain can trip again
+        assert!(repo.open_incident("fbi", &trip).await.unwrap());
+        assert_eq!(repo.list_incidents(10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_claims() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselCrawlRepository::new(pool);
+        let window = chrono::Duration::minutes(10);
+
+        let held = repo
+            .claim_fetch("src", "url:a", "https://a.gov/x", window)
+            .await
+            .unwrap();
+        assert!(held.is_none());
+        let held = repo
+            .claim_fetch("src", "url:a", "https://A.gov/x#top", window)
+            .await
+            .unwrap();
+        assert_eq!(held.as_deref(), Some("https://a.gov/x"));
+        // Claims are per source
+        assert!(repo
+            .claim_fetch("other", "url:a", "https://a.gov/x", window)
+            .await
+            .unwrap()
+            .is_none());
+
+        repo.release_fetch("src", "url:a").await.unwrap();
+        assert!(repo
+            .claim_fetch("src", "url:a", "https://a.gov/x", window)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Expired claims are taken over, and pruned
+        let expired = chrono::Duration::seconds(-1);
+        for url in ["https://a.gov/y", "https://a.gov/z"] {
+            assert!(repo
+                .claim_fetch("src", "sha256:b", url, expired)
+                .await
+                .unwrap()
+                .is_none());
+        }
+        assert_eq!(repo.prune_fetch_claims().await.unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn test_invalid_discovery_context_json_returns_error() {
         let (pool, _dir) = setup_test_db().await;
@@ -7,8 +7,8 @@ use diesel_async::{AsyncConnection, RunQueryDsl};
 use super::DieselCrawlRepository;
 use crate::models::{CrawlUrl, UrlStatus};
 use crate::repository::models::CrawlUrlRecord;
-use crate::repository::pool::DieselError;
-use crate::schema::crawl_urls;
+use crate::repository::pool::{retry_on_busy, DieselError};
+use crate::schema::{crawl_urls, sources};
 use crate::with_conn;
 
 impl DieselCrawlRepository {
@@ -35,49 +35,60 @@ impl DieselCrawlRepository {
         })
     }
 
-    /// Atomically claim a pending URL for processing.
+    /// Atomically claim a pending URL for processing. URLs of paused
+    /// sources are left alone.
     pub async fn claim_pending_url(
         &self,
         source_id: Option<&str>,
     ) -> Result<Option<CrawlUrl>, DieselError> {
         let source_id = source_id.map(|s| s.to_string());
 
-        with_conn!(self.pool, conn, {
-            conn.transaction(|conn| {
-                let source_id = source_id.clone();
-                Box::pin(async move {
-                    let mut query = crawl_urls::table
-                        .filter(crawl_urls::status.eq("discovered"))
-                        .order((crawl_urls::depth.asc(), crawl_urls::discovered_at.asc()))
-                        .limit(1)
-                        .into_boxed();
-
-                    if let Some(ref sid) = source_id {
-                        query = query.filter(crawl_urls::source_id.eq(sid));
-                    }
-
-                    let record: Option<CrawlUrlRecord> = query.first(conn).await.optional()?;
-
-                    if let Some(record) = record {
-                        diesel::update(
-                            crawl_urls::table
-                                .filter(crawl_urls::source_id.eq(&record.source_id))
-                                .filter(crawl_urls::url.eq(&record.url)),
-                        )
-                        .set(crawl_urls::status.eq("fetching"))
-                        .execute(conn)
-                        .await?;
-
-                        let mut crawl_url = CrawlUrl::try_from(record)?;
-                        crawl_url.status = UrlStatus::Fetching;
-                        Ok(Some(crawl_url))
-                    } else {
-                        Ok(None)
-                    }
+        retry_on_busy(|| async {
+            with_conn!(self.pool, conn, {
+                conn.transaction(|conn| {
+                    let source_id = source_id.clone();
+                    Box::pin(async move {
+                        let mut query = crawl_urls::table
+                            .filter(crawl_urls::status.eq("discovered"))
+                            .filter(diesel::dsl::not(
+                                crawl_urls::source_id.eq_any(
+                                    sources::table
+                                        .filter(sources::paused.ne(0))
+                                        .select(sources::id),
+                                ),
+                            ))
+                            .order((crawl_urls::depth.asc(), crawl_urls::discovered_at.asc()))
+                            .limit(1)
+                            .into_boxed();
+
+                        if let Some(ref sid) = source_id {
+                            query = query.filter(crawl_urls::source_id.eq(sid));
+                        }
+
+                        let record: Option<CrawlUrlRecord> = query.first(conn).await.optional()?;
+
+                        if let Some(record) = record {
+                            diesel::update(
+                                crawl_urls::table
+                                    .filter(crawl_urls::source_id.eq(&record.source_id))
+                                    .filter(crawl_urls::url.eq(&record.url)),
+                            )
+                            .set(crawl_urls::status.eq("fetching"))
+                            .execute(conn)
+                            .await?;
+
+                            let mut crawl_url = CrawlUrl::try_from(record)?;
+                            crawl_url.status = UrlStatus::Fetching;
+                            Ok(Some(crawl_url))
+                        } else {
+                            Ok(None)
+                        }
+                    })
                 })
+                .await
             })
-            .await
         })
+        .await
     }
 
     /// Get failed URLs that are ready for retry.
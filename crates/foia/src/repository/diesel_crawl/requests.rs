@@ -1,5 +1,8 @@
 //! Request logging operations for the crawl repository.
 
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
@@ -7,6 +10,7 @@ use diesel_async::RunQueryDsl;
 use super::LastInsertId;
 use super::{DieselCrawlRepository, LastInsertRowId};
 use crate::models::CrawlRequest;
+use crate::repository::models::CrawlRequestRecord;
 use crate::repository::pool::{DbPool, DieselError};
 use crate::schema::crawl_requests;
 use crate::with_conn;
@@ -25,6 +29,7 @@ impl DieselCrawlRepository {
         let duration_ms = request.duration_ms.map(|d| d as i32);
         let was_conditional = if request.was_conditional { 1i32 } else { 0 };
         let was_not_modified = if request.was_not_modified { 1i32 } else { 0 };
+        let identified = if request.identified { 1i32 } else { 0 };
 
         with_conn!(self.pool, conn, {
             diesel::insert_into(crawl_requests::table)
@@ -42,6 +47,7 @@ impl DieselCrawlRepository {
                     crawl_requests::error.eq(&request.error),
                     crawl_requests::was_conditional.eq(was_conditional),
                     crawl_requests::was_not_modified.eq(was_not_modified),
+                    crawl_requests::identified.eq(identified),
                 ))
                 .execute(&mut conn)
                 .await?;
@@ -66,4 +72,126 @@ impl DieselCrawlRepository {
             Ok(id)
         })
     }
+
+    /// Every URL in a source's request log.
+    pub async fn requested_urls(&self, source_id: &str) -> Result<HashSet<String>, DieselError> {
+        let urls: Vec<String> = with_conn!(self.pool, conn, {
+            crawl_requests::table
+                .filter(crawl_requests::source_id.eq(source_id))
+                .select(crawl_requests::url)
+                .distinct()
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(urls.into_iter().collect())
+    }
+
+    /// Logged requests with an ID above `after`, in ID order, for walking
+    /// the whole log in batches.
+    pub async fn get_requests_after(
+        &self,
+        source_id: Option<&str>,
+        after: i64,
+        limit: i64,
+    ) -> Result<Vec<CrawlRequest>, DieselError> {
+        let records: Vec<CrawlRequestRecord> = with_conn!(self.pool, conn, {
+            let mut query = crawl_requests::table
+                .filter(crawl_requests::id.gt(after as i32))
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(crawl_requests::source_id.eq(sid));
+            }
+            query
+                .order(crawl_requests::id.asc())
+                .limit(limit)
+                .load(&mut conn)
+                .await
+        })?;
+
+        records.into_iter().map(CrawlRequest::try_from).collect()
+    }
+
+    /// Get the logged requests for a set of URLs, oldest first.
+    pub async fn get_requests_for_urls(
+        &self,
+        urls: &[String],
+    ) -> Result<Vec<CrawlRequest>, DieselError> {
+        if urls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let records: Vec<CrawlRequestRecord> = with_conn!(self.pool, conn, {
+            crawl_requests::table
+                .filter(crawl_requests::url.eq_any(urls))
+                .order(crawl_requests::request_at.asc())
+                .load(&mut conn)
+                .await
+        })?;
+
+        records.into_iter().map(CrawlRequest::try_from).collect()
+    }
+
+    /// Get the logged requests to a host (or its subdomains) within an
+    /// optional time range, oldest first.
+    pub async fn get_requests_for_domain(
+        &self,
+        domain: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<CrawlRequest>, DieselError> {
+        let since = since.map(|dt| dt.to_rfc3339());
+        let until = until.map(|dt| dt.to_rfc3339());
+        let pattern = format!("%{}%", domain);
+
+        let records: Vec<CrawlRequestRecord> = with_conn!(self.pool, conn, {
+            let mut query = crawl_requests::table
+                .filter(crawl_requests::url.like(&pattern))
+                .into_boxed();
+            if let Some(ref since) = since {
+                query = query.filter(crawl_requests::request_at.ge(since));
+            }
+            if let Some(ref until) = until {
+                query = query.filter(crawl_requests::request_at.lt(until));
+            }
+            query
+                .order(crawl_requests::request_at.asc())
+                .load(&mut conn)
+                .await
+        })?;
+
+        // LIKE is only a prefilter; match the parsed host exactly
+        records
+            .into_iter()
+            .filter(|record| url_matches_domain(&record.url, domain))
+            .map(CrawlRequest::try_from)
+            .collect()
+    }
+}
+
+/// Whether `url`'s host is `domain` or a subdomain of it.
+fn url_matches_domain(url: &str, domain: &str) -> bool {
+    let Some(host) = url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_lowercase))
+    else {
+        return false;
+    };
+    let domain = domain.trim_start_matches('.').to_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_matches_domain() {
+        assert!(url_matches_domain("https://vault.fbi.gov/a.pdf", "fbi.gov"));
+        assert!(url_matches_domain("https://FBI.gov/", "fbi.gov"));
+        assert!(!url_matches_domain("https://notfbi.gov/", "fbi.gov"));
+        assert!(!url_matches_domain(
+            "https://example.com/?ref=fbi.gov",
+            "fbi.gov"
+        ));
+    }
 }
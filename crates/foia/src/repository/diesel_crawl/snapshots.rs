@@ -0,0 +1,234 @@
+//! Listing page snapshot operations for the crawl repository.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use sha2::{Digest, Sha256};
+
+use super::DieselCrawlRepository;
+use crate::models::{ListingSnapshot, ListingSnapshotSummary};
+use crate::repository::models::ListingSnapshotRecord;
+use crate::repository::pool::DieselError;
+use crate::repository::text_codec;
+use crate::repository::{parse_datetime, parse_datetime_opt};
+use crate::schema::listing_snapshots;
+use crate::with_conn;
+
+impl From<ListingSnapshotRecord> for ListingSnapshot {
+    fn from(record: ListingSnapshotRecord) -> Self {
+        Self {
+            id: record.id as i64,
+            source_id: record.source_id,
+            url: record.url,
+            content_hash: record.content_hash,
+            link_count: record.link_count.max(0) as u32,
+            first_seen_at: parse_datetime(&record.first_seen_at),
+            last_seen_at: parse_datetime(&record.last_seen_at),
+            fetch_count: record.fetch_count.max(0) as u32,
+        }
+    }
+}
+
+impl DieselCrawlRepository {
+    /// Archive a fetched listing page. Stores a new snapshot when the content
+    /// differs from the latest one for this URL, otherwise only records that
+    /// it was seen again. Returns true if a new snapshot was stored.
+    ///
+    /// Content is hashed as fetched and stored compressed.
+    pub async fn record_listing_snapshot(
+        &self,
+        source_id: &str,
+        url: &str,
+        content: &str,
+        link_count: u32,
+    ) -> Result<bool, DieselError> {
+        let hash = hex::encode(Sha256::digest(content.as_bytes()));
+        let stored = text_codec::compress(content);
+        let now = Utc::now().to_rfc3339();
+
+        with_conn!(self.pool, conn, {
+            let latest: Option<(i32, String)> = listing_snapshots::table
+                .filter(listing_snapshots::source_id.eq(source_id))
+                .filter(listing_snapshots::url.eq(url))
+                .order(listing_snapshots::id.desc())
+                .select((listing_snapshots::id, listing_snapshots::content_hash))
+                .first(&mut conn)
+                .await
+                .optional()?;
+
+            if let Some((id, latest_hash)) = latest {
+                if latest_hash == hash {
+                    diesel::update(listing_snapshots::table.find(id))
+                        .set((
+                            listing_snapshots::last_seen_at.eq(&now),
+                            listing_snapshots::fetch_count.eq(listing_snapshots::fetch_count + 1),
+                        ))
+                        .execute(&mut conn)
+                        .await?;
+                    return Ok(false);
+                }
+            }
+
+            diesel::insert_into(listing_snapshots::table)
+                .values((
+                    listing_snapshots::source_id.eq(source_id),
+                    listing_snapshots::url.eq(url),
+                    listing_snapshots::content_hash.eq(&hash),
+                    listing_snapshots::content.eq(stored.as_ref()),
+                    listing_snapshots::link_count.eq(link_count as i32),
+                    listing_snapshots::first_seen_at.eq(&now),
+                    listing_snapshots::last_seen_at.eq(&now),
+                    listing_snapshots::fetch_count.eq(1),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(true)
+        })
+    }
+
+    /// Summarize snapshot history per listing URL, optionally for one source.
+    pub async fn list_snapshot_urls(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<Vec<ListingSnapshotSummary>, DieselError> {
+        let rows: Vec<(String, String, String, String)> = with_conn!(self.pool, conn, {
+            let mut query = listing_snapshots::table
+                .select((
+                    listing_snapshots::source_id,
+                    listing_snapshots::url,
+                    listing_snapshots::first_seen_at,
+                    listing_snapshots::last_seen_at,
+                ))
+                .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(listing_snapshots::source_id.eq(source_id));
+            }
+            query
+                .order(listing_snapshots::id.asc())
+                .load(&mut conn)
+                .await
+        })?;
+
+        // Rows are oldest first, so the last row per URL is its latest change
+        let mut summaries: BTreeMap<(String, String), ListingSnapshotSummary> = BTreeMap::new();
+        for (source_id, url, first_seen, last_seen) in rows {
+            let summary = summaries
+                .entry((source_id.clone(), url.clone()))
+                .or_insert_with(|| ListingSnapshotSummary {
+                    source_id,
+                    url,
+                    snapshots: 0,
+                    last_changed_at: None,
+                    last_seen_at: None,
+                });
+            summary.snapshots += 1;
+            summary.last_changed_at = parse_datetime_opt(Some(first_seen));
+            summary.last_seen_at = parse_datetime_opt(Some(last_seen));
+        }
+        Ok(summaries.into_values().collect())
+    }
+
+    /// List snapshots of one listing URL, newest first.
+    pub async fn list_listing_snapshots(
+        &self,
+        source_id: &str,
+        url: &str,
+    ) -> Result<Vec<ListingSnapshot>, DieselError> {
+        let records: Vec<ListingSnapshotRecord> = with_conn!(self.pool, conn, {
+            listing_snapshots::table
+                .filter(listing_snapshots::source_id.eq(source_id))
+                .filter(listing_snapshots::url.eq(url))
+                .order(listing_snapshots::id.desc())
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(ListingSnapshot::from).collect())
+    }
+
+    /// Get a snapshot and its archived HTML.
+    pub async fn get_listing_snapshot(
+        &self,
+        id: i64,
+    ) -> Result<Option<(ListingSnapshot, String)>, DieselError> {
+        let record: Option<ListingSnapshotRecord> = with_conn!(self.pool, conn, {
+            listing_snapshots::table
+                .find(id as i32)
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(record.map(|r| {
+            let content = text_codec::decompress(r.content.clone());
+            (ListingSnapshot::from(r), content)
+        }))
+    }
+
+    /// Get the snapshot of the same URL captured just before this one.
+    pub async fn previous_listing_snapshot(
+        &self,
+        snapshot: &ListingSnapshot,
+    ) -> Result<Option<(ListingSnapshot, String)>, DieselError> {
+        let record: Option<ListingSnapshotRecord> = with_conn!(self.pool, conn, {
+            listing_snapshots::table
+                .filter(listing_snapshots::source_id.eq(&snapshot.source_id))
+                .filter(listing_snapshots::url.eq(&snapshot.url))
+                .filter(listing_snapshots::id.lt(snapshot.id as i32))
+                .order(listing_snapshots::id.desc())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(record.map(|r| {
+            let content = text_codec::decompress(r.content.clone());
+            (ListingSnapshot::from(r), content)
+        }))
+    }
+
+    /// Latest archived HTML of every listing page of a source, by URL.
+    pub async fn latest_listing_contents(
+        &self,
+        source_id: &str,
+    ) -> Result<HashMap<String, String>, DieselError> {
+        let rows: Vec<(String, String)> = with_conn!(self.pool, conn, {
+            listing_snapshots::table
+                .filter(listing_snapshots::source_id.eq(source_id))
+                .order(listing_snapshots::id.asc())
+                .select((listing_snapshots::url, listing_snapshots::content))
+                .load(&mut conn)
+                .await
+        })?;
+        // Oldest first, so later snapshots replace earlier ones
+        Ok(rows
+            .into_iter()
+            .map(|(url, content)| (url, text_codec::decompress(content)))
+            .collect())
+    }
+
+    /// Delete snapshots last seen before `cutoff`, optionally for one source.
+    /// The latest snapshot of each URL is always kept. Returns the number
+    /// deleted.
+    pub async fn prune_listing_snapshots(
+        &self,
+        source_id: Option<&str>,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, DieselError> {
+        let cutoff = cutoff.to_rfc3339();
+        with_conn!(self.pool, conn, {
+            let latest = listing_snapshots::table
+                .group_by((listing_snapshots::source_id, listing_snapshots::url))
+                .select(diesel::dsl::max(listing_snapshots::id));
+            let mut query = diesel::delete(listing_snapshots::table)
+                .filter(listing_snapshots::last_seen_at.lt(&cutoff))
+                .filter(diesel::dsl::not(
+                    listing_snapshots::id.nullable().eq_any(latest),
+                ))
+                .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(listing_snapshots::source_id.eq(source_id));
+            }
+            query.execute(&mut conn).await
+        })
+    }
+}
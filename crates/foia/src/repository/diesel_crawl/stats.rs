@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
@@ -69,6 +70,26 @@ impl DieselCrawlRepository {
         })
     }
 
+    /// Count URLs fetched since `since`, for one source or all of them.
+    pub async fn count_fetched_since(
+        &self,
+        source_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<u64, DieselError> {
+        let since = since.to_rfc3339();
+        with_conn!(self.pool, conn, {
+            let mut query = crawl_urls::table
+                .filter(crawl_urls::status.eq("fetched"))
+                .filter(crawl_urls::fetched_at.ge(&since))
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(crawl_urls::source_id.eq(sid));
+            }
+            let count: i64 = query.count().get_result(&mut conn).await?;
+            Ok(count as u64)
+        })
+    }
+
     /// Get overall crawl state for a source.
     pub async fn get_crawl_state(&self, source_id: &str) -> Result<CrawlState, DieselError> {
         let counts = self.count_by_status(source_id).await?;
@@ -6,17 +6,38 @@ use diesel_async::RunQueryDsl;
 use super::DieselCrawlRepository;
 use crate::models::CrawlUrl;
 use crate::repository::models::CrawlUrlRecord;
-use crate::repository::pool::DieselError;
+use crate::repository::pool::{retry_on_busy, DieselError};
 use crate::schema::crawl_urls;
 use crate::with_conn;
 
 impl DieselCrawlRepository {
     /// Add a discovered URL if not already known.
+    ///
+    /// In a shared frontier, a URL already known to any member is skipped,
+    /// and a new one is queued under the member it belongs to, recording
+    /// the discoverer in its discovery context.
     pub async fn add_url(&self, crawl_url: &CrawlUrl) -> Result<bool, DieselError> {
+        let frontier = self.frontier_of(&crawl_url.source_id);
+        let known_to = match frontier {
+            Some(f) => f.source_ids(),
+            None => vec![crawl_url.source_id.clone()],
+        };
+        let source_id = frontier
+            .map(|f| f.owner(&crawl_url.source_id, &crawl_url.url))
+            .unwrap_or(&crawl_url.source_id)
+            .to_string();
+        let mut discovery_context = crawl_url.discovery_context.clone();
+        if source_id != crawl_url.source_id {
+            discovery_context.insert(
+                "discovered_by".to_string(),
+                serde_json::Value::String(crawl_url.source_id.clone()),
+            );
+        }
+
         let status = crawl_url.status.as_str().to_string();
         let discovery_method = crawl_url.discovery_method.as_str().to_string();
-        let discovery_context = serde_json::to_string(&crawl_url.discovery_context)
-            .unwrap_or_else(|_| "{}".to_string());
+        let discovery_context =
+            serde_json::to_string(&discovery_context).unwrap_or_else(|_| "{}".to_string());
         let depth = crawl_url.depth as i32;
         let discovered_at = crawl_url.discovered_at.to_rfc3339();
         let retry_count = crawl_url.retry_count as i32;
@@ -24,42 +45,45 @@ impl DieselCrawlRepository {
         let next_retry_at = crawl_url.next_retry_at.map(|dt| dt.to_rfc3339());
 
         use diesel::dsl::count_star;
-        with_conn!(self.pool, conn, {
-            let exists: i64 = crawl_urls::table
-                .filter(crawl_urls::source_id.eq(&crawl_url.source_id))
-                .filter(crawl_urls::url.eq(&crawl_url.url))
-                .select(count_star())
-                .first(&mut conn)
-                .await?;
+        retry_on_busy(|| async {
+            with_conn!(self.pool, conn, {
+                let exists: i64 = crawl_urls::table
+                    .filter(crawl_urls::source_id.eq_any(&known_to))
+                    .filter(crawl_urls::url.eq(&crawl_url.url))
+                    .select(count_star())
+                    .first(&mut conn)
+                    .await?;
 
-            if exists > 0 {
-                return Ok(false);
-            }
+                if exists > 0 {
+                    return Ok(false);
+                }
 
-            diesel::insert_into(crawl_urls::table)
-                .values((
-                    crawl_urls::url.eq(&crawl_url.url),
-                    crawl_urls::source_id.eq(&crawl_url.source_id),
-                    crawl_urls::status.eq(&status),
-                    crawl_urls::discovery_method.eq(&discovery_method),
-                    crawl_urls::parent_url.eq(&crawl_url.parent_url),
-                    crawl_urls::discovery_context.eq(&discovery_context),
-                    crawl_urls::depth.eq(depth),
-                    crawl_urls::discovered_at.eq(&discovered_at),
-                    crawl_urls::fetched_at.eq(&fetched_at),
-                    crawl_urls::retry_count.eq(retry_count),
-                    crawl_urls::last_error.eq(&crawl_url.last_error),
-                    crawl_urls::next_retry_at.eq(&next_retry_at),
-                    crawl_urls::etag.eq(&crawl_url.etag),
-                    crawl_urls::last_modified.eq(&crawl_url.last_modified),
-                    crawl_urls::content_hash.eq(&crawl_url.content_hash),
-                    crawl_urls::document_id.eq(&crawl_url.document_id),
-                ))
-                .execute(&mut conn)
-                .await?;
+                diesel::insert_into(crawl_urls::table)
+                    .values((
+                        crawl_urls::url.eq(&crawl_url.url),
+                        crawl_urls::source_id.eq(&source_id),
+                        crawl_urls::status.eq(&status),
+                        crawl_urls::discovery_method.eq(&discovery_method),
+                        crawl_urls::parent_url.eq(&crawl_url.parent_url),
+                        crawl_urls::discovery_context.eq(&discovery_context),
+                        crawl_urls::depth.eq(depth),
+                        crawl_urls::discovered_at.eq(&discovered_at),
+                        crawl_urls::fetched_at.eq(&fetched_at),
+                        crawl_urls::retry_count.eq(retry_count),
+                        crawl_urls::last_error.eq(&crawl_url.last_error),
+                        crawl_urls::next_retry_at.eq(&next_retry_at),
+                        crawl_urls::etag.eq(&crawl_url.etag),
+                        crawl_urls::last_modified.eq(&crawl_url.last_modified),
+                        crawl_urls::content_hash.eq(&crawl_url.content_hash),
+                        crawl_urls::document_id.eq(&crawl_url.document_id),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
 
-            Ok(true)
+                Ok(true)
+            })
         })
+        .await
     }
 
     /// Get a URL by source and URL string.
@@ -79,6 +103,24 @@ impl DieselCrawlRepository {
         })
     }
 
+    /// Whether another member of the source's shared frontier has the URL
+    /// and the source itself doesn't, so the source should leave it alone.
+    pub async fn queued_elsewhere(&self, source_id: &str, url: &str) -> Result<bool, DieselError> {
+        let Some(frontier) = self.frontier_of(source_id) else {
+            return Ok(false);
+        };
+        let members = frontier.source_ids();
+        let owners: Vec<String> = with_conn!(self.pool, conn, {
+            crawl_urls::table
+                .filter(crawl_urls::source_id.eq_any(&members))
+                .filter(crawl_urls::url.eq(url))
+                .select(crawl_urls::source_id)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(!owners.is_empty() && !owners.iter().any(|owner| owner == source_id))
+    }
+
     /// Check if a URL exists.
     #[allow(dead_code)]
     pub async fn url_exists(&self, source_id: &str, url: &str) -> Result<bool, DieselError> {
@@ -101,28 +143,31 @@ impl DieselCrawlRepository {
         let next_retry_at = crawl_url.next_retry_at.map(|dt| dt.to_rfc3339());
         let retry_count = crawl_url.retry_count as i32;
 
-        with_conn!(self.pool, conn, {
-            diesel::update(
-                crawl_urls::table
-                    .filter(crawl_urls::source_id.eq(&crawl_url.source_id))
-                    .filter(crawl_urls::url.eq(&crawl_url.url)),
-            )
-            .set((
-                crawl_urls::status.eq(&status),
-                crawl_urls::fetched_at.eq(&fetched_at),
-                crawl_urls::retry_count.eq(retry_count),
-                crawl_urls::last_error.eq(&crawl_url.last_error),
-                crawl_urls::next_retry_at.eq(&next_retry_at),
-                crawl_urls::etag.eq(&crawl_url.etag),
-                crawl_urls::last_modified.eq(&crawl_url.last_modified),
-                crawl_urls::content_hash.eq(&crawl_url.content_hash),
-                crawl_urls::document_id.eq(&crawl_url.document_id),
-            ))
-            .execute(&mut conn)
-            .await?;
+        retry_on_busy(|| async {
+            with_conn!(self.pool, conn, {
+                diesel::update(
+                    crawl_urls::table
+                        .filter(crawl_urls::source_id.eq(&crawl_url.source_id))
+                        .filter(crawl_urls::url.eq(&crawl_url.url)),
+                )
+                .set((
+                    crawl_urls::status.eq(&status),
+                    crawl_urls::fetched_at.eq(&fetched_at),
+                    crawl_urls::retry_count.eq(retry_count),
+                    crawl_urls::last_error.eq(&crawl_url.last_error),
+                    crawl_urls::next_retry_at.eq(&next_retry_at),
+                    crawl_urls::etag.eq(&crawl_url.etag),
+                    crawl_urls::last_modified.eq(&crawl_url.last_modified),
+                    crawl_urls::content_hash.eq(&crawl_url.content_hash),
+                    crawl_urls::document_id.eq(&crawl_url.document_id),
+                ))
+                .execute(&mut conn)
+                .await?;
 
-            Ok(())
+                Ok(())
+            })
         })
+        .await
     }
 
     /// Get URLs needing refresh (older than cutoff date).
@@ -197,6 +242,18 @@ impl DieselCrawlRepository {
         })
     }
 
+    /// Get all URLs for a source, ordered by URL.
+    pub async fn get_urls_by_source(&self, source_id: &str) -> Result<Vec<CrawlUrl>, DieselError> {
+        with_conn!(self.pool, conn, {
+            crawl_urls::table
+                .filter(crawl_urls::source_id.eq(source_id))
+                .order(crawl_urls::url.asc())
+                .load::<CrawlUrlRecord>(&mut conn)
+                .await
+                .and_then(|records| records.into_iter().map(CrawlUrl::try_from).collect())
+        })
+    }
+
     /// Count URLs for a source.
     pub async fn count_by_source(&self, source_id: &str) -> Result<u64, DieselError> {
         use diesel::dsl::count_star;
@@ -0,0 +1,296 @@
+//! Visibility rules for documents and sources.
+
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{AccessRuleRecord, NewAccessRule};
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{access_rules, document_versions, documents, virtual_files};
+use crate::services::access::{AccessPolicy, AccessRule, AccessScope, Visibility};
+use crate::{with_conn, with_conn_split};
+
+impl TryFrom<AccessRuleRecord> for AccessRule {
+    type Error = DieselError;
+
+    fn try_from(record: AccessRuleRecord) -> Result<Self, Self::Error> {
+        let invalid = || {
+            diesel::result::Error::DeserializationError(
+                format!("invalid access rule for {} {}", record.scope, record.target).into(),
+            )
+        };
+        let scope = AccessScope::from_str(&record.scope).ok_or_else(invalid)?;
+        let until = record
+            .embargo_until
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let visibility = Visibility::parse(&record.visibility, until).ok_or_else(invalid)?;
+        Ok(Self {
+            scope,
+            target: record.target,
+            visibility,
+            note: record.note,
+            updated_at: parse_datetime(&record.updated_at),
+        })
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Set the visibility of a document or source, replacing any earlier rule.
+    pub async fn set_access_rule(
+        &self,
+        scope: AccessScope,
+        target: &str,
+        visibility: Visibility,
+        note: Option<&str>,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let until = visibility.until().map(|d| d.format("%Y-%m-%d").to_string());
+        let new = NewAccessRule {
+            scope: scope.as_str(),
+            target,
+            visibility: visibility.as_str(),
+            embargo_until: until.as_deref(),
+            note,
+            updated_at: &now,
+        };
+
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(access_rules::table)
+                    .values(&new)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(access_rules::table)
+                    .values(&new)
+                    .on_conflict((access_rules::scope, access_rules::target))
+                    .do_update()
+                    .set((
+                        access_rules::visibility.eq(visibility.as_str()),
+                        access_rules::embargo_until.eq(until.as_deref()),
+                        access_rules::note.eq(note),
+                        access_rules::updated_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// Remove a rule, making the target inherit (documents) or public
+    /// (sources) again. Returns whether a rule existed.
+    pub async fn clear_access_rule(
+        &self,
+        scope: AccessScope,
+        target: &str,
+    ) -> Result<bool, DieselError> {
+        with_conn!(self.pool, conn, {
+            let rows = diesel::delete(
+                access_rules::table
+                    .filter(access_rules::scope.eq(scope.as_str()))
+                    .filter(access_rules::target.eq(target)),
+            )
+            .execute(&mut conn)
+            .await?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// The rule set on one document or source, if any.
+    pub async fn get_access_rule(
+        &self,
+        scope: AccessScope,
+        target: &str,
+    ) -> Result<Option<AccessRule>, DieselError> {
+        let record: Option<AccessRuleRecord> = with_conn!(self.pool, conn, {
+            access_rules::table
+                .filter(access_rules::scope.eq(scope.as_str()))
+                .filter(access_rules::target.eq(target))
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        record.map(AccessRule::try_from).transpose()
+    }
+
+    /// All rules, sources first, then by target.
+    pub async fn list_access_rules(&self) -> Result<Vec<AccessRule>, DieselError> {
+        let records: Vec<AccessRuleRecord> = with_conn!(self.pool, conn, {
+            access_rules::table
+                .order((access_rules::scope.desc(), access_rules::target.asc()))
+                .load(&mut conn)
+                .await
+        })?;
+        records.into_iter().map(AccessRule::try_from).collect()
+    }
+
+    /// Every rule, resolved for visibility checks.
+    pub async fn access_policy(&self) -> Result<AccessPolicy, DieselError> {
+        Ok(AccessPolicy::new(self.list_access_rules().await?))
+    }
+
+    /// The document an ID refers to, as `(document_id, source_id)`.
+    ///
+    /// Attachment IDs resolve to their parent document.
+    pub async fn document_owner(&self, id: &str) -> Result<Option<(String, String)>, DieselError> {
+        with_conn!(self.pool, conn, {
+            let doc: Option<(String, String)> = documents::table
+                .find(id)
+                .select((documents::id, documents::source_id))
+                .first(&mut conn)
+                .await
+                .optional()?;
+            if doc.is_some() {
+                return Ok(doc);
+            }
+            virtual_files::table
+                .inner_join(documents::table)
+                .filter(virtual_files::id.eq(id))
+                .select((documents::id, documents::source_id))
+                .first(&mut conn)
+                .await
+                .optional()
+        })
+    }
+
+    /// Documents with a version whose content hash starts with `prefix`,
+    /// as `(document_id, source_id)`. Stored file names carry such a prefix.
+    pub async fn documents_with_hash_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, String)>, DieselError> {
+        let pattern = format!("{}%", prefix);
+        with_conn!(self.pool, conn, {
+            document_versions::table
+                .inner_join(documents::table)
+                .filter(document_versions::content_hash.like(&pattern))
+                .select((documents::id, documents::source_id))
+                .distinct()
+                .load(&mut conn)
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use crate::repository::diesel_document::BrowseParams;
+
+    fn doc(id: &str, source_id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: id.to_string(),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_access_rules() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        let until = NaiveDate::from_ymd_opt(2027, 1, 1).unwrap();
+
+        repo.set_access_rule(AccessScope::Source, "fbi", Visibility::Internal, None)
+            .await
+            .unwrap();
+        repo.set_access_rule(
+            AccessScope::Document,
+            "doc-1",
+            Visibility::Embargoed(until),
+            Some("publication date"),
+        )
+        .await
+        .unwrap();
+        repo.set_access_rule(AccessScope::Document, "doc-2", Visibility::Internal, None)
+            .await
+            .unwrap();
+        // Replaces the earlier rule
+        repo.set_access_rule(AccessScope::Document, "doc-2", Visibility::Public, None)
+            .await
+            .unwrap();
+
+        let rules = repo.list_access_rules().await.unwrap();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].scope, AccessScope::Source);
+        assert_eq!(rules[1].visibility, Visibility::Embargoed(until));
+        assert_eq!(rules[1].note.as_deref(), Some("publication date"));
+        assert_eq!(rules[2].visibility, Visibility::Public);
+
+        let policy = repo.access_policy().await.unwrap();
+        assert_eq!(policy.visibility("doc-2", "fbi"), Visibility::Public);
+        assert_eq!(policy.visibility("doc-3", "fbi"), Visibility::Internal);
+
+        assert!(repo
+            .clear_access_rule(AccessScope::Source, "fbi")
+            .await
+            .unwrap());
+        assert!(!repo
+            .clear_access_rule(AccessScope::Source, "fbi")
+            .await
+            .unwrap());
+        let policy = repo.access_policy().await.unwrap();
+        assert_eq!(policy.visibility("doc-3", "fbi"), Visibility::Public);
+    }
+
+    #[tokio::test]
+    async fn test_browse_withholds_restricted() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        for (id, source) in [("a", "fbi"), ("b", "fbi"), ("c", "cia"), ("d", "cia")] {
+            repo.save(&doc(id, source)).await.unwrap();
+        }
+        repo.set_access_rule(AccessScope::Source, "fbi", Visibility::Internal, None)
+            .await
+            .unwrap();
+        repo.set_access_rule(AccessScope::Document, "b", Visibility::Public, None)
+            .await
+            .unwrap();
+        repo.set_access_rule(AccessScope::Document, "d", Visibility::Internal, None)
+            .await
+            .unwrap();
+
+        let today = Utc::now().date_naive();
+        let filter = repo.access_policy().await.unwrap().filter(today);
+        let mut ids: Vec<String> = repo
+            .browse(BrowseParams {
+                limit: 10,
+                access: Some(&filter),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["b", "c"]);
+
+        let all = repo
+            .browse(BrowseParams {
+                limit: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 4);
+    }
+}
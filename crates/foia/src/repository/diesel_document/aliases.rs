@@ -0,0 +1,135 @@
+//! Alias dictionary used to expand search queries.
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{NewSearchAlias, SearchAliasRecord};
+use crate::repository::pool::DieselError;
+use crate::schema::search_aliases;
+use crate::services::aliases::{expand_query, AliasKind, SearchAlias};
+use crate::{with_conn, with_conn_split};
+
+impl From<SearchAliasRecord> for SearchAlias {
+    fn from(record: SearchAliasRecord) -> Self {
+        Self {
+            id: record.id,
+            kind: AliasKind::from_str(&record.kind).unwrap_or(AliasKind::Acronym),
+            term: record.term,
+            expansion: record.expansion,
+        }
+    }
+}
+
+impl DieselDocumentRepository {
+    /// All aliases, by term.
+    pub async fn list_aliases(&self) -> Result<Vec<SearchAlias>, DieselError> {
+        let records: Vec<SearchAliasRecord> = with_conn!(self.pool, conn, {
+            search_aliases::table
+                .order((search_aliases::term.asc(), search_aliases::expansion.asc()))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(SearchAlias::from).collect())
+    }
+
+    /// Add an alias, or return the existing one for the same pair.
+    pub async fn add_alias(
+        &self,
+        term: &str,
+        expansion: &str,
+        kind: AliasKind,
+    ) -> Result<SearchAlias, DieselError> {
+        let created_at = chrono::Utc::now().to_rfc3339();
+        let new = NewSearchAlias {
+            term,
+            expansion,
+            kind: kind.as_str(),
+            created_at: &created_at,
+        };
+        let record: SearchAliasRecord = with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::insert_or_ignore_into(search_aliases::table)
+                    .values(&new)
+                    .execute(&mut conn)
+                    .await?;
+                search_aliases::table
+                    .filter(search_aliases::term.eq(term))
+                    .filter(search_aliases::expansion.eq(expansion))
+                    .first(&mut conn)
+                    .await
+            },
+            postgres: conn => {
+                diesel::insert_into(search_aliases::table)
+                    .values(&new)
+                    .on_conflict_do_nothing()
+                    .execute(&mut conn)
+                    .await?;
+                search_aliases::table
+                    .filter(search_aliases::term.eq(term))
+                    .filter(search_aliases::expansion.eq(expansion))
+                    .first(&mut conn)
+                    .await
+            }
+        )?;
+        Ok(record.into())
+    }
+
+    /// Delete an alias. Returns true if a row was removed.
+    pub async fn delete_alias(&self, id: i32) -> Result<bool, DieselError> {
+        with_conn!(self.pool, conn, {
+            let deleted = diesel::delete(search_aliases::table.filter(search_aliases::id.eq(id)))
+                .execute(&mut conn)
+                .await?;
+            Ok(deleted > 0)
+        })
+    }
+
+    /// A search query followed by its alias expansions.
+    pub async fn expand_search_query(&self, query: &str) -> Result<Vec<String>, DieselError> {
+        let aliases = self.list_aliases().await?;
+        Ok(expand_query(query, &aliases))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_alias_crud_and_expansion() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        let oig = repo
+            .add_alias("OIG", "Office of Inspector General", AliasKind::Acronym)
+            .await
+            .unwrap();
+        let again = repo
+            .add_alias("OIG", "Office of Inspector General", AliasKind::Acronym)
+            .await
+            .unwrap();
+        assert_eq!(oig.id, again.id);
+        repo.add_alias("ARTICHOKE", "Project Bluebird", AliasKind::CodeName)
+            .await
+            .unwrap();
+
+        let aliases = repo.list_aliases().await.unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases[0].term, "ARTICHOKE");
+        assert_eq!(aliases[0].kind, AliasKind::CodeName);
+
+        assert_eq!(
+            repo.expand_search_query("OIG audit").await.unwrap(),
+            vec!["OIG audit", "Office of Inspector General audit"]
+        );
+
+        assert!(repo.delete_alias(oig.id).await.unwrap());
+        assert!(!repo.delete_alias(oig.id).await.unwrap());
+        assert_eq!(
+            repo.expand_search_query("OIG audit").await.unwrap(),
+            vec!["OIG audit"]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Partner API keys and their usage.
+
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{ApiKeyRecord, NewApiKey};
+use crate::repository::pool::DieselError;
+use crate::repository::{parse_datetime, parse_datetime_opt};
+use crate::schema::{api_key_usage, api_keys};
+use crate::services::api_keys::{
+    display_prefix, generate_key, hash_key, ApiKey, ApiKeySpec, KeyUsage,
+};
+use crate::with_conn;
+
+impl From<ApiKeyRecord> for ApiKey {
+    fn from(record: ApiKeyRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            prefix: record.key_prefix,
+            read_only: record.read_only != 0,
+            sources: record
+                .sources
+                .as_deref()
+                .and_then(|s| serde_json::from_str(s).ok()),
+            rate_limit: record.rate_limit.and_then(|r| u32::try_from(r).ok()),
+            requests: record.requests,
+            created_at: parse_datetime(&record.created_at),
+            last_used_at: parse_datetime_opt(record.last_used_at),
+            revoked_at: parse_datetime_opt(record.revoked_at),
+        }
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Issue a new key. Returns the key record and the key itself, which
+    /// is not stored and cannot be shown again.
+    pub async fn create_api_key(&self, spec: &ApiKeySpec) -> Result<(ApiKey, String), DieselError> {
+        let key = generate_key();
+        let hash = hash_key(&key);
+        let prefix = display_prefix(&key);
+        let sources = spec
+            .sources
+            .as_ref()
+            .map(|s| serde_json::to_string(s).unwrap_or_default());
+        let now = Utc::now().to_rfc3339();
+        let new = NewApiKey {
+            name: &spec.name,
+            key_prefix: &prefix,
+            key_hash: &hash,
+            read_only: spec.read_only as i32,
+            sources: sources.as_deref(),
+            rate_limit: spec.rate_limit.map(|r| r.min(i32::MAX as u32) as i32),
+            created_at: &now,
+        };
+
+        let record: ApiKeyRecord = with_conn!(self.pool, conn, {
+            diesel::insert_into(api_keys::table)
+                .values(&new)
+                .execute(&mut conn)
+                .await?;
+            api_keys::table
+                .filter(api_keys::key_hash.eq(&hash))
+                .first(&mut conn)
+                .await
+        })?;
+        Ok((record.into(), key))
+    }
+
+    /// The active key a client presented, if any.
+    pub async fn find_api_key(&self, key: &str) -> Result<Option<ApiKey>, DieselError> {
+        let hash = hash_key(key);
+        let record: Option<ApiKeyRecord> = with_conn!(self.pool, conn, {
+            api_keys::table
+                .filter(api_keys::key_hash.eq(&hash))
+                .filter(api_keys::revoked_at.is_null())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(record.map(ApiKey::from))
+    }
+
+    /// All keys, including revoked ones, oldest first.
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKey>, DieselError> {
+        let records: Vec<ApiKeyRecord> = with_conn!(self.pool, conn, {
+            api_keys::table
+                .order(api_keys::id.asc())
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(ApiKey::from).collect())
+    }
+
+    /// Revoke a key. Returns whether an active key was revoked.
+    pub async fn revoke_api_key(&self, id: i32) -> Result<bool, DieselError> {
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            let rows = diesel::update(
+                api_keys::table
+                    .filter(api_keys::id.eq(id))
+                    .filter(api_keys::revoked_at.is_null()),
+            )
+            .set(api_keys::revoked_at.eq(&now))
+            .execute(&mut conn)
+            .await?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Add requests made with keys today to their counters.
+    pub async fn record_api_key_usage(&self, usage: &[(i32, u64)]) -> Result<(), DieselError> {
+        let now = Utc::now();
+        let day = now.format("%Y-%m-%d").to_string();
+        let now = now.to_rfc3339();
+        with_conn!(self.pool, conn, {
+            for &(key_id, requests) in usage {
+                let requests = requests.min(i64::MAX as u64) as i64;
+                diesel::update(api_keys::table.filter(api_keys::id.eq(key_id)))
+                    .set((
+                        api_keys::requests.eq(api_keys::requests + requests),
+                        api_keys::last_used_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                let updated = diesel::update(
+                    api_key_usage::table
+                        .filter(api_key_usage::key_id.eq(key_id))
+                        .filter(api_key_usage::day.eq(&day)),
+                )
+                .set(api_key_usage::requests.eq(api_key_usage::requests + requests))
+                .execute(&mut conn)
+                .await?;
+                if updated == 0 {
+                    diesel::insert_into(api_key_usage::table)
+                        .values((
+                            api_key_usage::key_id.eq(key_id),
+                            api_key_usage::day.eq(&day),
+                            api_key_usage::requests.eq(requests),
+                        ))
+                        .execute(&mut conn)
+                        .await?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Requests per day made with a key since `since`, oldest first.
+    pub async fn api_key_usage(
+        &self,
+        key_id: i32,
+        since: NaiveDate,
+    ) -> Result<Vec<KeyUsage>, DieselError> {
+        let since = since.format("%Y-%m-%d").to_string();
+        let rows: Vec<(String, i64)> = with_conn!(self.pool, conn, {
+            api_key_usage::table
+                .filter(api_key_usage::key_id.eq(key_id))
+                .filter(api_key_usage::day.ge(&since))
+                .order(api_key_usage::day.asc())
+                .select((api_key_usage::day, api_key_usage::requests))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(day, requests)| {
+                let day = NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()?;
+                Some(KeyUsage { day, requests })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_api_key_lifecycle() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        let spec = ApiKeySpec {
+            name: "Newsroom".to_string(),
+            read_only: true,
+            sources: Some(vec!["fbi".to_string()]),
+            rate_limit: Some(30),
+        };
+        let (created, key) = repo.create_api_key(&spec).await.unwrap();
+        assert!(key.starts_with(&created.prefix));
+        assert_eq!(created.sources.as_deref(), Some(&["fbi".to_string()][..]));
+        assert_eq!(created.rate_limit, Some(30));
+
+        let found = repo.find_api_key(&key).await.unwrap().unwrap();
+        assert_eq!(found.id, created.id);
+        assert!(found.read_only);
+        assert!(repo.find_api_key("foia_wrong").await.unwrap().is_none());
+
+        repo.record_api_key_usage(&[(created.id, 5)]).await.unwrap();
+        repo.record_api_key_usage(&[(created.id, 2)]).await.unwrap();
+        let today = Utc::now().date_naive();
+        let usage = repo.api_key_usage(created.id, today).await.unwrap();
+        assert_eq!(
+            usage,
+            [KeyUsage {
+                day: today,
+                requests: 7
+            }]
+        );
+        let listed = repo.list_api_keys().await.unwrap();
+        assert_eq!(listed[0].requests, 7);
+        assert!(listed[0].last_used_at.is_some());
+
+        assert!(repo.revoke_api_key(created.id).await.unwrap());
+        assert!(!repo.revoke_api_key(created.id).await.unwrap());
+        assert!(repo.find_api_key(&key).await.unwrap().is_none());
+        assert!(!repo.list_api_keys().await.unwrap()[0].is_active());
+    }
+}
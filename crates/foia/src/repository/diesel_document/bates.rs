@@ -0,0 +1,229 @@
+//! Bates-stamped page ranges of documents.
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{DocumentBatesRecord, NewDocumentBates};
+use crate::repository::pool::DieselError;
+use crate::schema::{document_bates, documents};
+use crate::services::bates::{BatesNumber, BatesRange};
+use crate::with_conn;
+
+/// A document page carrying a looked-up Bates number.
+#[derive(Debug, Clone)]
+pub struct BatesHit {
+    pub document_id: String,
+    pub title: String,
+    pub source_id: String,
+    pub page: u32,
+    /// The stamped range the number falls in.
+    pub range: BatesRange,
+}
+
+impl From<DocumentBatesRecord> for BatesRange {
+    fn from(record: DocumentBatesRecord) -> Self {
+        Self {
+            prefix: record.prefix,
+            start: record.start_number as u64,
+            end: record.end_number as u64,
+            digits: record.digits as usize,
+            first_page: record.first_page as u32,
+            last_page: record.last_page as u32,
+        }
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Replace a document's Bates ranges with those found in `version_id`.
+    pub async fn replace_bates_ranges(
+        &self,
+        doc_id: &str,
+        version_id: i32,
+        ranges: &[BatesRange],
+    ) -> Result<(), DieselError> {
+        let rows: Vec<NewDocumentBates> = ranges
+            .iter()
+            .map(|r| NewDocumentBates {
+                document_id: doc_id,
+                version_id,
+                prefix: &r.prefix,
+                prefix_key: r.key(),
+                start_number: r.start as i64,
+                end_number: r.end as i64,
+                digits: r.digits as i32,
+                first_page: r.first_page as i32,
+                last_page: r.last_page as i32,
+            })
+            .collect();
+
+        with_conn!(self.pool, conn, {
+            diesel::delete(document_bates::table.filter(document_bates::document_id.eq(doc_id)))
+                .execute(&mut conn)
+                .await?;
+            for chunk in rows.chunks(500) {
+                diesel::insert_into(document_bates::table)
+                    .values(chunk)
+                    .execute(&mut conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Bates ranges of a document, in page order.
+    pub async fn get_bates_ranges(&self, doc_id: &str) -> Result<Vec<BatesRange>, DieselError> {
+        let records: Vec<DocumentBatesRecord> = with_conn!(self.pool, conn, {
+            document_bates::table
+                .filter(document_bates::document_id.eq(doc_id))
+                .order(document_bates::first_page.asc())
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(BatesRange::from).collect())
+    }
+
+    /// Documents and pages stamped with `number`.
+    pub async fn find_bates(
+        &self,
+        number: &BatesNumber,
+        source_id: Option<&str>,
+    ) -> Result<Vec<BatesHit>, DieselError> {
+        let key = number.key();
+        let n = number.number as i64;
+        let rows: Vec<(DocumentBatesRecord, String, String)> = with_conn!(self.pool, conn, {
+            let mut q = document_bates::table
+                .inner_join(documents::table)
+                .filter(document_bates::prefix_key.eq(&key))
+                .filter(document_bates::start_number.le(n))
+                .filter(document_bates::end_number.ge(n))
+                .into_boxed();
+            if let Some(sid) = source_id {
+                q = q.filter(documents::source_id.eq(sid));
+            }
+            q.select((
+                DocumentBatesRecord::as_select(),
+                documents::title,
+                documents::source_id,
+            ))
+            .order(documents::title.asc())
+            .load(&mut conn)
+            .await
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(record, title, source_id)| {
+                let document_id = record.document_id.clone();
+                let range = BatesRange::from(record);
+                Some(BatesHit {
+                    document_id,
+                    title,
+                    source_id,
+                    page: range.page_of(number.number)?,
+                    range,
+                })
+            })
+            .collect())
+    }
+
+    /// Bates ranges of every document in a source, as `(document_id, range)`.
+    pub async fn get_source_bates_ranges(
+        &self,
+        source_id: &str,
+    ) -> Result<Vec<(String, BatesRange)>, DieselError> {
+        let records: Vec<DocumentBatesRecord> = with_conn!(self.pool, conn, {
+            document_bates::table
+                .inner_join(documents::table)
+                .filter(documents::source_id.eq(source_id))
+                .select(DocumentBatesRecord::as_select())
+                .order((
+                    document_bates::prefix_key.asc(),
+                    document_bates::start_number.asc(),
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records
+            .into_iter()
+            .map(|r| (r.document_id.clone(), BatesRange::from(r)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use crate::services::bates::{find_gaps, page_ranges};
+    use chrono::Utc;
+
+    fn doc(id: &str, source_id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: format!("Production {}", id),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    fn ranges_for(prefix: &str, first: u64, pages: u32) -> Vec<BatesRange> {
+        let stamps: Vec<(u32, BatesNumber)> = (0..pages)
+            .map(|i| {
+                let n = format!("{}{:06}", prefix, first + i as u64);
+                (i + 1, BatesNumber::parse(&n).unwrap())
+            })
+            .collect();
+        page_ranges(&stamps)
+    }
+
+    #[tokio::test]
+    async fn test_bates_lookup_and_gaps() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        repo.save(&doc("a", "doj")).await.unwrap();
+        repo.save(&doc("b", "doj")).await.unwrap();
+        repo.save(&doc("c", "fbi")).await.unwrap();
+
+        repo.replace_bates_ranges("a", 1, &ranges_for("DOJ-OGR-", 100, 5))
+            .await
+            .unwrap();
+        repo.replace_bates_ranges("b", 1, &ranges_for("DOJ-OGR-", 120, 3))
+            .await
+            .unwrap();
+        repo.replace_bates_ranges("c", 1, &ranges_for("FBI", 100, 2))
+            .await
+            .unwrap();
+
+        let hits = repo
+            .find_bates(&BatesNumber::parse("doj-ogr-000103").unwrap(), None)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].document_id, "a");
+        assert_eq!(hits[0].page, 4);
+        let miss = BatesNumber::parse("DOJ-OGR-000110").unwrap();
+        assert!(repo.find_bates(&miss, None).await.unwrap().is_empty());
+
+        let ranges = repo.get_source_bates_ranges("doj").await.unwrap();
+        assert_eq!(ranges.len(), 2);
+        let gaps = find_gaps(&ranges);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].from, "DOJ-OGR-000105");
+        assert_eq!(gaps[0].missing, 15);
+
+        // Re-extraction replaces earlier ranges
+        repo.replace_bates_ranges("a", 2, &[]).await.unwrap();
+        assert!(repo.get_bates_ranges("a").await.unwrap().is_empty());
+    }
+}
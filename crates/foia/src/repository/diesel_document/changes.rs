@@ -0,0 +1,177 @@
+//! The change journal: every creation, update and deletion of a document,
+//! numbered in order by triggers on `documents`, `document_versions` and
+//! `virtual_files`.
+//!
+//! Consumers remember the last sequence number they processed and ask for
+//! what came after it, which unlike comparing `updated_at` also catches
+//! deletions. Documents changed after a sequence number are streamed with
+//! [`StreamFilter::changed_since`](super::StreamFilter::changed_since).
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::{ChangeType, DocumentChange};
+use crate::repository::models::DocumentChangeRecord;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{document_changes, documents};
+use crate::with_conn;
+
+impl From<DocumentChangeRecord> for DocumentChange {
+    fn from(record: DocumentChangeRecord) -> Self {
+        Self {
+            seq: record.seq,
+            document_id: record.document_id,
+            source_id: record.source_id,
+            change_type: ChangeType::from_str(&record.change_type).unwrap_or(ChangeType::Updated),
+            changed_at: parse_datetime(&record.changed_at),
+        }
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Sequence number of the latest change, or 0 before the first.
+    pub async fn latest_change_seq(&self) -> Result<i64, DieselError> {
+        let seq: Option<i64> = with_conn!(self.pool, conn, {
+            document_changes::table
+                .select(diesel::dsl::max(document_changes::seq))
+                .first(&mut conn)
+                .await
+        })?;
+        Ok(seq.unwrap_or(0))
+    }
+
+    /// Up to `limit` changes after sequence number `seq`, oldest first.
+    pub async fn get_changes_since(
+        &self,
+        seq: i64,
+        limit: i64,
+    ) -> Result<Vec<DocumentChange>, DieselError> {
+        let records: Vec<DocumentChangeRecord> = with_conn!(self.pool, conn, {
+            document_changes::table
+                .filter(document_changes::seq.gt(seq))
+                .order(document_changes::seq.asc())
+                .limit(limit)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(DocumentChange::from).collect())
+    }
+
+    /// IDs of documents deleted after sequence number `seq` and not
+    /// re-created since, optionally only those of one source.
+    pub async fn get_deleted_documents_since(
+        &self,
+        seq: i64,
+        source_id: Option<&str>,
+    ) -> Result<Vec<String>, DieselError> {
+        with_conn!(self.pool, conn, {
+            let mut query = document_changes::table
+                .filter(document_changes::seq.gt(seq))
+                .filter(document_changes::change_type.eq(ChangeType::Deleted.as_str()))
+                .filter(diesel::dsl::not(diesel::dsl::exists(
+                    documents::table.filter(documents::id.eq(document_changes::document_id)),
+                )))
+                .select(document_changes::document_id)
+                .distinct()
+                .order(document_changes::document_id.asc())
+                .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(document_changes::source_id.eq(source_id));
+            }
+            query.load(&mut conn).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use crate::repository::diesel_document::StreamFilter;
+    use chrono::Utc;
+    use futures::TryStreamExt;
+
+    fn document(id: &str, source_id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: "Release".to_string(),
+            source_url: format!("https://agency.gov/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Pending,
+            metadata: serde_json::json!({}),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_journal_records_changes_and_deletions() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        assert_eq!(repo.latest_change_seq().await.unwrap(), 0);
+
+        repo.save(&document("doc-1", "fbi")).await.unwrap();
+        repo.save(&document("doc-2", "cia")).await.unwrap();
+        let start = repo.latest_change_seq().await.unwrap();
+
+        let mut doc = document("doc-1", "fbi");
+        doc.title = "Release, revised".to_string();
+        repo.save(&doc).await.unwrap();
+        repo.delete("doc-2").await.unwrap();
+
+        let changes = repo.get_changes_since(0, 100).await.unwrap();
+        let summary: Vec<(&str, ChangeType)> = changes
+            .iter()
+            .map(|c| (c.document_id.as_str(), c.change_type))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("doc-1", ChangeType::Created),
+                ("doc-2", ChangeType::Created),
+                ("doc-1", ChangeType::Updated),
+                ("doc-2", ChangeType::Deleted),
+            ]
+        );
+        assert!(changes.windows(2).all(|w| w[0].seq < w[1].seq));
+
+        let changed: Vec<String> = repo
+            .stream_documents(
+                StreamFilter {
+                    changed_since: Some(start),
+                    ..Default::default()
+                },
+                10,
+            )
+            .map_ok(|d| d.id)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(changed, vec!["doc-1"]);
+        assert_eq!(
+            repo.get_deleted_documents_since(start, None).await.unwrap(),
+            vec!["doc-2"]
+        );
+        assert!(repo
+            .get_deleted_documents_since(start, Some("fbi"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // A re-created document is no longer deleted
+        repo.save(&document("doc-2", "cia")).await.unwrap();
+        assert!(repo
+            .get_deleted_documents_since(start, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
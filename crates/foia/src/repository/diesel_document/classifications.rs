@@ -0,0 +1,182 @@
+//! Record-type classification of documents (letter, memo, photo, ...).
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::RecordType;
+use crate::repository::models::{DocumentClassificationRecord, NewDocumentClassification};
+use crate::repository::pool::DieselError;
+use crate::schema::document_classifications;
+use crate::{with_conn, with_conn_split};
+
+impl DieselDocumentRepository {
+    /// Store a document's record type, replacing any earlier classification.
+    ///
+    /// `confidence` is clamped to 0.0..=1.0; `classifier` names what produced
+    /// it (`heuristic`, or `llm:<model>`).
+    pub async fn set_classification(
+        &self,
+        doc_id: &str,
+        record_type: RecordType,
+        confidence: f32,
+        classifier: &str,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let confidence = confidence.clamp(0.0, 1.0);
+        let new = NewDocumentClassification {
+            document_id: doc_id,
+            record_type: record_type.as_str(),
+            confidence,
+            classifier,
+            classified_at: &now,
+        };
+
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(document_classifications::table)
+                    .values(&new)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(document_classifications::table)
+                    .values(&new)
+                    .on_conflict(document_classifications::document_id)
+                    .do_update()
+                    .set((
+                        document_classifications::record_type.eq(record_type.as_str()),
+                        document_classifications::confidence.eq(confidence),
+                        document_classifications::classifier.eq(classifier),
+                        document_classifications::classified_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// A document's classification, if it has been classified.
+    pub async fn get_classification(
+        &self,
+        doc_id: &str,
+    ) -> Result<Option<DocumentClassificationRecord>, DieselError> {
+        with_conn!(self.pool, conn, {
+            document_classifications::table
+                .find(doc_id)
+                .first(&mut conn)
+                .await
+                .optional()
+        })
+    }
+
+    /// Record types of the given documents; unclassified ones are absent.
+    pub async fn get_record_types(
+        &self,
+        doc_ids: &[String],
+    ) -> Result<HashMap<String, String>, DieselError> {
+        if doc_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows: Vec<(String, String)> = with_conn!(self.pool, conn, {
+            document_classifications::table
+                .filter(document_classifications::document_id.eq_any(doc_ids))
+                .select((
+                    document_classifications::document_id,
+                    document_classifications::record_type,
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Number of documents of each record type, most common first.
+    pub async fn get_record_type_counts(&self) -> Result<Vec<(String, u64)>, DieselError> {
+        use diesel::dsl::count_star;
+
+        let rows: Vec<(String, i64)> = with_conn!(self.pool, conn, {
+            document_classifications::table
+                .group_by(document_classifications::record_type)
+                .select((document_classifications::record_type, count_star()))
+                .load(&mut conn)
+                .await
+        })?;
+        let mut counts: Vec<(String, u64)> = rows
+            .into_iter()
+            .map(|(record_type, count)| (record_type, count as u64))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    fn doc(id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: "agency".to_string(),
+            title: id.to_string(),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_classification_replace_and_counts() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        for id in ["a", "b", "c"] {
+            repo.save(&doc(id)).await.unwrap();
+        }
+
+        repo.set_classification("a", RecordType::Memo, 0.6, "heuristic")
+            .await
+            .unwrap();
+        repo.set_classification("b", RecordType::Photo, 1.4, "heuristic")
+            .await
+            .unwrap();
+        repo.set_classification("c", RecordType::Photo, 0.9, "heuristic")
+            .await
+            .unwrap();
+        // Reclassifying replaces the earlier result
+        repo.set_classification("a", RecordType::Letter, 0.8, "llm:test")
+            .await
+            .unwrap();
+
+        let a = repo.get_classification("a").await.unwrap().unwrap();
+        assert_eq!(a.record_type, "letter");
+        assert_eq!(a.classifier, "llm:test");
+        let b = repo.get_classification("b").await.unwrap().unwrap();
+        assert_eq!(b.confidence, 1.0);
+
+        assert_eq!(
+            repo.get_record_type_counts().await.unwrap(),
+            vec![("photo".to_string(), 2), ("letter".to_string(), 1)]
+        );
+        let types = repo
+            .get_record_types(&["a".to_string(), "missing".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types["a"], "letter");
+    }
+}
@@ -0,0 +1,213 @@
+//! Column names of tabular documents (CSV files and spreadsheets).
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{DocumentColumnRecord, NewDocumentColumn};
+use crate::repository::pool::DieselError;
+use crate::schema::{document_columns, documents};
+use crate::with_conn;
+
+/// A column whose name matched a search.
+#[derive(Debug, Clone)]
+pub struct ColumnMatch {
+    pub document_id: String,
+    pub title: String,
+    pub source_id: String,
+    pub sheet: String,
+    pub position: i32,
+    pub name: String,
+}
+
+/// Normalized column name used for matching: lowercase, whitespace collapsed.
+pub fn column_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl DieselDocumentRepository {
+    /// Replace a document's indexed columns with those of `version_id`.
+    ///
+    /// `sheets` holds `(sheet name, header row)` pairs; empty names are skipped.
+    pub async fn replace_document_columns(
+        &self,
+        doc_id: &str,
+        version_id: i32,
+        sheets: &[(String, Vec<String>)],
+    ) -> Result<(), DieselError> {
+        let rows: Vec<NewDocumentColumn> = sheets
+            .iter()
+            .flat_map(|(sheet, names)| {
+                names
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, name)| !name.trim().is_empty())
+                    .map(move |(i, name)| NewDocumentColumn {
+                        document_id: doc_id,
+                        version_id,
+                        sheet,
+                        position: i as i32,
+                        name: name.trim(),
+                        name_key: column_key(name),
+                    })
+            })
+            .collect();
+
+        with_conn!(self.pool, conn, {
+            diesel::delete(
+                document_columns::table.filter(document_columns::document_id.eq(doc_id)),
+            )
+            .execute(&mut conn)
+            .await?;
+            // Stay under SQLite's bound-parameter limit on wide sheets
+            for chunk in rows.chunks(500) {
+                diesel::insert_into(document_columns::table)
+                    .values(chunk)
+                    .execute(&mut conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Indexed columns of a document, in sheet and column order.
+    pub async fn get_document_columns(
+        &self,
+        doc_id: &str,
+    ) -> Result<Vec<DocumentColumnRecord>, DieselError> {
+        with_conn!(self.pool, conn, {
+            document_columns::table
+                .filter(document_columns::document_id.eq(doc_id))
+                .order(document_columns::id.asc())
+                .load(&mut conn)
+                .await
+        })
+    }
+
+    /// Columns whose name contains `query` (case-insensitive).
+    pub async fn search_columns(
+        &self,
+        query: &str,
+        source_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ColumnMatch>, DieselError> {
+        let pattern = format!("%{}%", column_key(query));
+        let rows: Vec<(String, String, String, String, i32, String)> =
+            with_conn!(self.pool, conn, {
+                let mut q = document_columns::table
+                    .inner_join(documents::table)
+                    .filter(document_columns::name_key.like(pattern.clone()))
+                    .into_boxed();
+                if let Some(sid) = source_id {
+                    q = q.filter(documents::source_id.eq(sid));
+                }
+                q.select((
+                    document_columns::document_id,
+                    documents::title,
+                    documents::source_id,
+                    document_columns::sheet,
+                    document_columns::position,
+                    document_columns::name,
+                ))
+                .order((documents::title.asc(), document_columns::id.asc()))
+                .limit(limit)
+                .load(&mut conn)
+                .await
+            })?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(document_id, title, source_id, sheet, position, name)| ColumnMatch {
+                    document_id,
+                    title,
+                    source_id,
+                    sheet,
+                    position,
+                    name,
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use chrono::Utc;
+
+    fn doc(id: &str, source_id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: format!("{} data", id),
+            source_url: format!("https://example.com/{}.xlsx", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_columns_replace_and_search() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        repo.save(&doc("contracts", "city")).await.unwrap();
+        repo.save(&doc("overtime", "county")).await.unwrap();
+
+        let header =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|n| n.to_string()).collect() };
+        repo.replace_document_columns(
+            "contracts",
+            1,
+            &[(
+                "FY2023".to_string(),
+                header(&["Vendor  Name", "Amount", ""]),
+            )],
+        )
+        .await
+        .unwrap();
+        repo.replace_document_columns(
+            "overtime",
+            1,
+            &[("Sheet1".to_string(), header(&["Officer", "OT Amount"]))],
+        )
+        .await
+        .unwrap();
+
+        let matches = repo.search_columns("amount", None, 10).await.unwrap();
+        assert_eq!(matches.len(), 2);
+        let matches = repo.search_columns("VENDOR name", None, 10).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].name, "Vendor  Name");
+        assert_eq!(matches[0].sheet, "FY2023");
+        let matches = repo
+            .search_columns("amount", Some("county"), 10)
+            .await
+            .unwrap();
+        assert_eq!(matches[0].document_id, "overtime");
+
+        // Reprocessing replaces the previous version's columns
+        repo.replace_document_columns(
+            "contracts",
+            2,
+            &[("FY2024".to_string(), header(&["Payee"]))],
+        )
+        .await
+        .unwrap();
+        let columns = repo.get_document_columns("contracts").await.unwrap();
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns[0].version_id, 2);
+    }
+}
@@ -0,0 +1,289 @@
+//! Text layer coverage and OCR quality per source.
+//!
+//! A document has text if any of its pages does (final, OCR or PDF text)
+//! or it has whole-document extracted text. Quality is the backend's
+//! quality score where recorded, else its confidence, averaged over every
+//! OCR result that has one.
+
+use std::collections::BTreeMap;
+
+use diesel::sql_types::{BigInt, Double, Nullable, Text};
+use diesel_async::RunQueryDsl;
+use serde::Serialize;
+
+use super::DieselDocumentRepository;
+use crate::repository::pool::DieselError;
+use crate::with_conn;
+
+/// A page with any text.
+const PAGE_HAS_TEXT: &str = "COALESCE(p.final_text, p.ocr_text, p.pdf_text, '') <> ''";
+
+/// Score of one OCR result.
+const RESULT_QUALITY: &str = "COALESCE(r.quality_score, r.confidence)";
+
+/// Text coverage and OCR quality of one source.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SourceCoverage {
+    pub source_id: String,
+    pub documents: u64,
+    /// Documents with any text.
+    pub documents_with_text: u64,
+    pub pages: u64,
+    pub pages_failed: u64,
+    /// Pages not processed yet.
+    pub pages_pending: u64,
+    /// Average OCR quality (0-1) over scored results.
+    pub quality: Option<f64>,
+    /// OCR backends used, most results first.
+    pub backends: Vec<BackendUsage>,
+}
+
+impl SourceCoverage {
+    /// Fraction of documents with any text.
+    pub fn text_fraction(&self) -> Option<f64> {
+        (self.documents > 0).then(|| self.documents_with_text as f64 / self.documents as f64)
+    }
+}
+
+/// OCR results one backend produced for a source.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BackendUsage {
+    pub backend: String,
+    /// Pages it produced a result for.
+    pub pages: u64,
+    /// Of those, results that recorded an error.
+    pub errors: u64,
+    /// Average quality (0-1) of its scored results.
+    pub quality: Option<f64>,
+    /// Results with a score.
+    #[serde(skip)]
+    scored: u64,
+}
+
+/// What a coverage drill-down lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageIssue {
+    /// Documents with no text at all.
+    NoText,
+    /// Documents with failed pages, most failures first.
+    Failed,
+    /// Documents with scored OCR, lowest quality first.
+    LowQuality,
+}
+
+impl CoverageIssue {
+    pub const ALL: [CoverageIssue; 3] = [Self::NoText, Self::Failed, Self::LowQuality];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoText => "no_text",
+            Self::Failed => "failed",
+            Self::LowQuality => "low_quality",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "no_text" => Some(Self::NoText),
+            "failed" => Some(Self::Failed),
+            "low_quality" => Some(Self::LowQuality),
+            _ => None,
+        }
+    }
+}
+
+/// A document in a coverage drill-down.
+#[derive(Debug, Clone, PartialEq, Serialize, diesel::QueryableByName)]
+pub struct CoverageDocument {
+    #[diesel(sql_type = Text)]
+    pub id: String,
+    #[diesel(sql_type = Text)]
+    pub title: String,
+    #[diesel(sql_type = BigInt)]
+    pub pages: i64,
+    #[diesel(sql_type = BigInt)]
+    pub failed_pages: i64,
+    #[diesel(sql_type = BigInt)]
+    pub text_pages: i64,
+    #[diesel(sql_type = Nullable<Double>)]
+    pub quality: Option<f64>,
+}
+
+#[derive(diesel::QueryableByName)]
+struct DocumentRow {
+    #[diesel(sql_type = Text)]
+    source_id: String,
+    #[diesel(sql_type = BigInt)]
+    documents: i64,
+    #[diesel(sql_type = BigInt)]
+    with_text: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct PageRow {
+    #[diesel(sql_type = Text)]
+    source_id: String,
+    #[diesel(sql_type = BigInt)]
+    pages: i64,
+    #[diesel(sql_type = BigInt)]
+    failed: i64,
+    #[diesel(sql_type = BigInt)]
+    pending: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct BackendRow {
+    #[diesel(sql_type = Text)]
+    source_id: String,
+    #[diesel(sql_type = Text)]
+    backend: String,
+    #[diesel(sql_type = BigInt)]
+    pages: i64,
+    #[diesel(sql_type = BigInt)]
+    errors: i64,
+    #[diesel(sql_type = BigInt)]
+    scored: i64,
+    #[diesel(sql_type = Nullable<Double>)]
+    quality: Option<f64>,
+}
+
+impl DieselDocumentRepository {
+    /// Coverage of every source with documents, by source ID.
+    pub async fn ocr_coverage(&self) -> Result<Vec<SourceCoverage>, DieselError> {
+        let documents_sql = format!(
+            "SELECT d.source_id, CAST(COUNT(*) AS BIGINT) AS documents, \
+             CAST(SUM(CASE WHEN COALESCE(d.extracted_text, '') <> '' \
+             OR EXISTS (SELECT 1 FROM document_pages p WHERE p.document_id = d.id \
+             AND {PAGE_HAS_TEXT}) THEN 1 ELSE 0 END) AS BIGINT) AS with_text \
+             FROM documents d GROUP BY d.source_id"
+        );
+        let pages_sql = "SELECT d.source_id, CAST(COUNT(*) AS BIGINT) AS pages, \
+             CAST(SUM(CASE WHEN p.ocr_status = 'failed' THEN 1 ELSE 0 END) AS BIGINT) AS failed, \
+             CAST(SUM(CASE WHEN p.ocr_status = 'pending' THEN 1 ELSE 0 END) AS BIGINT) AS pending \
+             FROM document_pages p JOIN documents d ON d.id = p.document_id \
+             GROUP BY d.source_id";
+        let backends_sql = format!(
+            "SELECT d.source_id, r.backend, CAST(COUNT(*) AS BIGINT) AS pages, \
+             CAST(SUM(CASE WHEN r.error_message IS NOT NULL THEN 1 ELSE 0 END) AS BIGINT) AS errors, \
+             CAST(COUNT({RESULT_QUALITY}) AS BIGINT) AS scored, \
+             AVG({RESULT_QUALITY}) AS quality \
+             FROM page_ocr_results r \
+             JOIN document_pages p ON p.id = r.page_id \
+             JOIN documents d ON d.id = p.document_id \
+             GROUP BY d.source_id, r.backend"
+        );
+
+        let (documents, pages, backends) = with_conn!(self.pool, conn, {
+            let documents: Vec<DocumentRow> =
+                diesel::sql_query(&documents_sql).load(&mut conn).await?;
+            let pages: Vec<PageRow> = diesel::sql_query(pages_sql).load(&mut conn).await?;
+            let backends: Vec<BackendRow> =
+                diesel::sql_query(&backends_sql).load(&mut conn).await?;
+            Ok::<_, DieselError>((documents, pages, backends))
+        })?;
+
+        let mut coverage: BTreeMap<String, SourceCoverage> = documents
+            .into_iter()
+            .map(|row| {
+                let source = SourceCoverage {
+                    source_id: row.source_id.clone(),
+                    documents: row.documents as u64,
+                    documents_with_text: row.with_text as u64,
+                    ..Default::default()
+                };
+                (row.source_id, source)
+            })
+            .collect();
+        for row in pages {
+            if let Some(source) = coverage.get_mut(&row.source_id) {
+                source.pages = row.pages as u64;
+                source.pages_failed = row.failed as u64;
+                source.pages_pending = row.pending as u64;
+            }
+        }
+        for row in backends {
+            if let Some(source) = coverage.get_mut(&row.source_id) {
+                source.backends.push(BackendUsage {
+                    backend: row.backend,
+                    pages: row.pages as u64,
+                    errors: row.errors as u64,
+                    quality: row.quality,
+                    scored: row.scored as u64,
+                });
+            }
+        }
+        for source in coverage.values_mut() {
+            source.backends.sort_by(|a, b| b.pages.cmp(&a.pages));
+            source.quality = weighted_quality(&source.backends);
+        }
+        Ok(coverage.into_values().collect())
+    }
+
+    /// Documents of a source with a coverage issue, worst first.
+    pub async fn coverage_documents(
+        &self,
+        source_id: &str,
+        issue: CoverageIssue,
+        limit: u32,
+    ) -> Result<Vec<CoverageDocument>, DieselError> {
+        let (filter, order) = match issue {
+            CoverageIssue::NoText => ("text_pages = 0 AND has_extracted = 0", "pages DESC"),
+            CoverageIssue::Failed => ("failed_pages > 0", "failed_pages DESC"),
+            CoverageIssue::LowQuality => ("quality IS NOT NULL", "quality ASC"),
+        };
+        let sql = format!(
+            "SELECT id, title, pages, failed_pages, text_pages, quality FROM ( \
+             SELECT d.id, d.title, CAST(COUNT(p.id) AS BIGINT) AS pages, \
+             CAST(COALESCE(SUM(CASE WHEN p.ocr_status = 'failed' THEN 1 ELSE 0 END), 0) AS BIGINT) \
+             AS failed_pages, \
+             CAST(COALESCE(SUM(CASE WHEN {PAGE_HAS_TEXT} THEN 1 ELSE 0 END), 0) AS BIGINT) \
+             AS text_pages, \
+             MAX(CASE WHEN COALESCE(d.extracted_text, '') <> '' THEN 1 ELSE 0 END) AS has_extracted, \
+             (SELECT AVG({RESULT_QUALITY}) FROM page_ocr_results r \
+             JOIN document_pages rp ON rp.id = r.page_id WHERE rp.document_id = d.id) AS quality \
+             FROM documents d LEFT JOIN document_pages p ON p.document_id = d.id \
+             WHERE d.source_id = $1 GROUP BY d.id, d.title \
+             ) coverage WHERE {filter} ORDER BY {order}, id LIMIT $2"
+        );
+        with_conn!(self.pool, conn, {
+            diesel::sql_query(&sql)
+                .bind::<Text, _>(source_id)
+                .bind::<BigInt, _>(limit as i64)
+                .load(&mut conn)
+                .await
+        })
+    }
+}
+
+/// Average quality over every backend's scored results.
+fn weighted_quality(backends: &[BackendUsage]) -> Option<f64> {
+    let (sum, scored) = backends
+        .iter()
+        .filter_map(|b| b.quality.map(|q| (q * b.scored as f64, b.scored)))
+        .fold((0.0, 0), |(sum, n), (q, s)| (sum + q, n + s));
+    (scored > 0).then(|| sum / scored as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_quality() {
+        let backend = |quality: Option<f64>, scored: u64| BackendUsage {
+            quality,
+            scored,
+            ..Default::default()
+        };
+        assert_eq!(weighted_quality(&[]), None);
+        assert_eq!(weighted_quality(&[backend(None, 0)]), None);
+        // Three results at 0.9 outweigh one at 0.5
+        let quality = weighted_quality(&[
+            backend(Some(0.9), 3),
+            backend(Some(0.5), 1),
+            backend(None, 0),
+        ]);
+        assert!((quality.unwrap() - 0.8).abs() < 1e-9);
+    }
+}
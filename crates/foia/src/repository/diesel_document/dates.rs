@@ -0,0 +1,251 @@
+//! Manual publication dates and the queue of documents needing one.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::{CountRow, DieselDocumentRepository, DocIdRow};
+use crate::repository::pool::DieselError;
+use crate::schema::documents;
+use crate::{with_conn, with_conn_split};
+
+/// `date_source` recorded once a person has reviewed the date, whether or
+/// not they could set one.
+const MANUAL_DATE_SOURCE: &str = "manual";
+
+/// Documents without a reviewed date whose estimate is low-confidence or
+/// missing. The estimate's confidence is read from the column, falling back
+/// to the one the date detector stores in metadata.
+const SQLITE_REVIEW_FILTER: &str = r#"manual_date IS NULL
+    AND COALESCE(date_source, '') <> 'manual'
+    AND COALESCE(date_confidence, json_extract(metadata, '$.estimated_date.confidence'), 'low') = 'low'"#;
+
+const POSTGRES_REVIEW_FILTER: &str = r#"manual_date IS NULL
+    AND COALESCE(date_source, '') <> 'manual'
+    AND COALESCE(date_confidence, metadata::jsonb->'estimated_date'->>'confidence', 'low') = 'low'"#;
+
+impl DieselDocumentRepository {
+    /// IDs of documents awaiting date review, ordered by ID.
+    ///
+    /// Pass the last ID of the previous page as `after` to continue.
+    pub async fn get_date_review_queue(
+        &self,
+        source_id: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>, DieselError> {
+        let limit = limit as i64;
+        let rows: Vec<DocIdRow> = with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::sql_query(format!(
+                    "SELECT id FROM documents WHERE {SQLITE_REVIEW_FILTER}
+                     AND ($1 IS NULL OR source_id = $1)
+                     AND ($2 IS NULL OR id > $2)
+                     ORDER BY id LIMIT $3"
+                ))
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(after)
+                .bind::<diesel::sql_types::BigInt, _>(limit)
+                .load(&mut conn)
+                .await
+            },
+            postgres: conn => {
+                diesel::sql_query(format!(
+                    "SELECT id FROM documents WHERE {POSTGRES_REVIEW_FILTER}
+                     AND ($1::text IS NULL OR source_id = $1)
+                     AND ($2::text IS NULL OR id > $2)
+                     ORDER BY id LIMIT $3"
+                ))
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(after)
+                .bind::<diesel::sql_types::BigInt, _>(limit)
+                .load(&mut conn)
+                .await
+            }
+        )?;
+        Ok(rows.into_iter().map(|r| r.id).collect())
+    }
+
+    /// Count documents awaiting date review.
+    pub async fn count_date_review_queue(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<u64, DieselError> {
+        let rows: Vec<CountRow> = with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::sql_query(format!(
+                    "SELECT COUNT(*) AS count FROM documents WHERE {SQLITE_REVIEW_FILTER}
+                     AND ($1 IS NULL OR source_id = $1)"
+                ))
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .load(&mut conn)
+                .await
+            },
+            postgres: conn => {
+                diesel::sql_query(format!(
+                    "SELECT COUNT(*) AS count FROM documents WHERE {POSTGRES_REVIEW_FILTER}
+                     AND ($1::text IS NULL OR source_id = $1)"
+                ))
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .load(&mut conn)
+                .await
+            }
+        )?;
+        Ok(rows.first().map(|r| r.count as u64).unwrap_or(0))
+    }
+
+    /// Record a reviewed publication date, or `None` when the reviewer found
+    /// no date. Either way the document leaves the review queue.
+    /// Returns false if the document doesn't exist.
+    pub async fn set_manual_date(
+        &self,
+        id: &str,
+        date: Option<NaiveDate>,
+    ) -> Result<bool, DieselError> {
+        let date = date.map(|d| d.format("%Y-%m-%d").to_string());
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            let updated = diesel::update(documents::table.find(id))
+                .set((
+                    documents::manual_date.eq(date.as_deref()),
+                    documents::date_source.eq(MANUAL_DATE_SOURCE),
+                    documents::updated_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(updated > 0)
+        })
+    }
+
+    /// Undo a review, returning the document to the queue if its estimate
+    /// is still uncertain. Returns false if the document doesn't exist.
+    pub async fn clear_manual_date(&self, id: &str) -> Result<bool, DieselError> {
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            let updated = diesel::update(documents::table.find(id))
+                .set((
+                    documents::manual_date.eq(None::<String>),
+                    documents::date_source.eq(None::<String>),
+                    documents::updated_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(updated > 0)
+        })
+    }
+
+    /// The reviewed date of a document, if one was set.
+    pub async fn get_manual_date(&self, id: &str) -> Result<Option<String>, DieselError> {
+        let date: Option<Option<String>> = with_conn!(self.pool, conn, {
+            documents::table
+                .find(id)
+                .select(documents::manual_date)
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(date.flatten())
+    }
+
+    /// Publication dates of documents: the reviewed date if set, otherwise
+    /// the estimate. Documents with neither are left out.
+    pub async fn get_publication_dates(
+        &self,
+        ids: &[String],
+    ) -> Result<HashMap<String, NaiveDate>, DieselError> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows: Vec<(String, Option<String>, Option<String>)> = with_conn!(self.pool, conn, {
+            documents::table
+                .filter(documents::id.eq_any(ids))
+                .select((
+                    documents::id,
+                    documents::manual_date,
+                    documents::estimated_date,
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, manual, estimated)| {
+                let date = manual.or(estimated)?;
+                let date = NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()?;
+                Some((id, date))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use chrono::TimeZone;
+
+    fn doc(id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: "test-source".to_string(),
+            title: id.to_string(),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::OcrComplete,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_date_review_queue() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        for id in ["a-undated", "b-low", "c-high", "d-reviewed"] {
+            repo.save(&doc(id)).await.unwrap();
+        }
+        let date = Utc.with_ymd_and_hms(1963, 11, 22, 0, 0, 0).unwrap();
+        repo.update_estimated_date("b-low", date, "low", "content")
+            .await
+            .unwrap();
+        repo.update_estimated_date("c-high", date, "high", "server")
+            .await
+            .unwrap();
+        assert!(repo.set_manual_date("d-reviewed", None).await.unwrap());
+
+        let queue = repo.get_date_review_queue(None, None, 10).await.unwrap();
+        assert_eq!(queue, vec!["a-undated", "b-low"]);
+        assert_eq!(repo.count_date_review_queue(None).await.unwrap(), 2);
+        let page = repo
+            .get_date_review_queue(Some("test-source"), Some("a-undated"), 10)
+            .await
+            .unwrap();
+        assert_eq!(page, vec!["b-low"]);
+
+        let picked = NaiveDate::from_ymd_opt(1963, 11, 21);
+        assert!(repo.set_manual_date("b-low", picked).await.unwrap());
+        assert_eq!(
+            repo.get_manual_date("b-low").await.unwrap().as_deref(),
+            Some("1963-11-21")
+        );
+        assert_eq!(repo.count_date_review_queue(None).await.unwrap(), 1);
+        let dates = repo
+            .get_publication_dates(&["a-undated".to_string(), "b-low".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(dates.len(), 1);
+        assert_eq!(dates.get("b-low").copied(), picked);
+
+        assert!(repo.clear_manual_date("b-low").await.unwrap());
+        assert_eq!(repo.count_date_review_queue(None).await.unwrap(), 2);
+        assert!(!repo.set_manual_date("missing", picked).await.unwrap());
+    }
+}
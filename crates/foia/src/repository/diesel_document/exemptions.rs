@@ -0,0 +1,313 @@
+//! FOIA exemption markings of document pages and their statistics.
+
+use std::collections::{BTreeMap, HashSet};
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{DocumentExemptionRecord, NewDocumentExemption};
+use crate::repository::pool::DieselError;
+use crate::schema::{document_exemptions, documents};
+use crate::services::exemptions::PageExemption;
+use crate::with_conn;
+
+/// How exemption statistics are bucketed over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsInterval {
+    #[default]
+    Year,
+    Month,
+}
+
+impl StatsInterval {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "year" => Some(Self::Year),
+            "month" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    /// Period of an ISO date: `1998` or `1998-04`.
+    fn bucket(self, date: &str) -> Option<String> {
+        let len = match self {
+            Self::Year => 4,
+            Self::Month => 7,
+        };
+        date.get(..len).map(str::to_string)
+    }
+}
+
+/// How often an exemption is cited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExemptionStats {
+    pub code: String,
+    /// Documents citing it at least once.
+    pub documents: u64,
+    /// Pages citing it at least once.
+    pub pages: u64,
+    /// Total markings.
+    pub markings: u64,
+}
+
+/// Citations of an exemption in one period, by publication date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExemptionPeriod {
+    /// `1998` or `1998-04`.
+    pub period: String,
+    pub code: String,
+    pub documents: u64,
+    pub markings: u64,
+}
+
+impl DieselDocumentRepository {
+    /// Replace a document's exemption markings with those found in `version_id`.
+    pub async fn replace_exemptions(
+        &self,
+        doc_id: &str,
+        version_id: i32,
+        exemptions: &[PageExemption],
+    ) -> Result<(), DieselError> {
+        let rows: Vec<NewDocumentExemption> = exemptions
+            .iter()
+            .map(|e| NewDocumentExemption {
+                document_id: doc_id,
+                version_id,
+                page_number: e.page_number as i32,
+                code: &e.code,
+                count: e.count as i32,
+            })
+            .collect();
+
+        with_conn!(self.pool, conn, {
+            diesel::delete(
+                document_exemptions::table.filter(document_exemptions::document_id.eq(doc_id)),
+            )
+            .execute(&mut conn)
+            .await?;
+            for chunk in rows.chunks(500) {
+                diesel::insert_into(document_exemptions::table)
+                    .values(chunk)
+                    .execute(&mut conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Exemption markings of a document, in page order.
+    pub async fn get_exemptions(&self, doc_id: &str) -> Result<Vec<PageExemption>, DieselError> {
+        let records: Vec<DocumentExemptionRecord> = with_conn!(self.pool, conn, {
+            document_exemptions::table
+                .filter(document_exemptions::document_id.eq(doc_id))
+                .order((
+                    document_exemptions::page_number.asc(),
+                    document_exemptions::code.asc(),
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records
+            .into_iter()
+            .map(|r| PageExemption {
+                page_number: r.page_number as u32,
+                code: r.code,
+                count: r.count as u32,
+            })
+            .collect())
+    }
+
+    /// Citation counts per exemption, most cited first.
+    pub async fn get_exemption_stats(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<Vec<ExemptionStats>, DieselError> {
+        let rows = self.load_exemption_rows(source_id, None).await?;
+
+        let mut by_code: BTreeMap<String, (HashSet<String>, u64, u64)> = BTreeMap::new();
+        for (doc_id, code, count, _) in rows {
+            let entry = by_code.entry(code).or_default();
+            entry.0.insert(doc_id);
+            entry.1 += 1;
+            entry.2 += count as u64;
+        }
+        let mut stats: Vec<ExemptionStats> = by_code
+            .into_iter()
+            .map(|(code, (docs, pages, markings))| ExemptionStats {
+                code,
+                documents: docs.len() as u64,
+                pages,
+                markings,
+            })
+            .collect();
+        stats.sort_by(|a, b| b.documents.cmp(&a.documents).then(a.code.cmp(&b.code)));
+        Ok(stats)
+    }
+
+    /// Number of documents citing each exemption, most cited first.
+    pub async fn get_exemption_counts(&self) -> Result<Vec<(String, u64)>, DieselError> {
+        use diesel::dsl::count_distinct;
+
+        let rows: Vec<(String, i64)> = with_conn!(self.pool, conn, {
+            document_exemptions::table
+                .group_by(document_exemptions::code)
+                .select((
+                    document_exemptions::code,
+                    count_distinct(document_exemptions::document_id),
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+        let mut counts: Vec<(String, u64)> = rows
+            .into_iter()
+            .map(|(code, count)| (code, count as u64))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(counts)
+    }
+
+    /// Citations per period of publication date, oldest first.
+    ///
+    /// Documents without a publication date are left out.
+    pub async fn get_exemption_timeline(
+        &self,
+        source_id: Option<&str>,
+        code: Option<&str>,
+        interval: StatsInterval,
+    ) -> Result<Vec<ExemptionPeriod>, DieselError> {
+        let rows = self.load_exemption_rows(source_id, code).await?;
+
+        let mut by_period: BTreeMap<(String, String), (HashSet<String>, u64)> = BTreeMap::new();
+        for (doc_id, code, count, date) in rows {
+            let Some(period) = date.as_deref().and_then(|d| interval.bucket(d)) else {
+                continue;
+            };
+            let entry = by_period.entry((period, code)).or_default();
+            entry.0.insert(doc_id);
+            entry.1 += count as u64;
+        }
+        Ok(by_period
+            .into_iter()
+            .map(|((period, code), (docs, markings))| ExemptionPeriod {
+                period,
+                code,
+                documents: docs.len() as u64,
+                markings,
+            })
+            .collect())
+    }
+
+    /// `(document_id, code, count, publication date)` per page row.
+    async fn load_exemption_rows(
+        &self,
+        source_id: Option<&str>,
+        code: Option<&str>,
+    ) -> Result<Vec<(String, String, i32, Option<String>)>, DieselError> {
+        #[allow(clippy::type_complexity)]
+        let rows: Vec<(String, String, i32, Option<String>, Option<String>)> =
+            with_conn!(self.pool, conn, {
+                let mut q = document_exemptions::table
+                    .inner_join(documents::table)
+                    .select((
+                        document_exemptions::document_id,
+                        document_exemptions::code,
+                        document_exemptions::count,
+                        documents::manual_date,
+                        documents::estimated_date,
+                    ))
+                    .into_boxed();
+                if let Some(sid) = source_id {
+                    q = q.filter(documents::source_id.eq(sid));
+                }
+                if let Some(code) = code {
+                    q = q.filter(document_exemptions::code.eq(code));
+                }
+                q.load(&mut conn).await
+            })?;
+        Ok(rows
+            .into_iter()
+            .map(|(doc_id, code, count, manual, estimated)| {
+                (doc_id, code, count, manual.or(estimated))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use crate::services::exemptions::page_exemptions;
+    use chrono::{NaiveDate, Utc};
+
+    fn doc(id: &str, source_id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: id.to_string(),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exemption_stats_and_timeline() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        for (id, source) in [("a", "fbi"), ("b", "fbi"), ("c", "cia")] {
+            repo.save(&doc(id, source)).await.unwrap();
+        }
+        repo.set_manual_date("a", NaiveDate::from_ymd_opt(1998, 4, 2))
+            .await
+            .unwrap();
+        repo.set_manual_date("b", NaiveDate::from_ymd_opt(2004, 11, 30))
+            .await
+            .unwrap();
+
+        let a = page_exemptions([(1, "(b)(5) (b)(6)"), (2, "b6 b7C")]);
+        repo.replace_exemptions("a", 1, &a).await.unwrap();
+        repo.replace_exemptions("b", 1, &page_exemptions([(3, "(b)(6)")]))
+            .await
+            .unwrap();
+        repo.replace_exemptions("c", 1, &page_exemptions([(1, "(b)(1)")]))
+            .await
+            .unwrap();
+
+        assert_eq!(repo.get_exemptions("a").await.unwrap(), a);
+
+        let stats = repo.get_exemption_stats(Some("fbi")).await.unwrap();
+        assert_eq!(stats[0].code, "(b)(6)");
+        assert_eq!(
+            (stats[0].documents, stats[0].pages, stats[0].markings),
+            (2, 3, 3)
+        );
+        assert!(stats.iter().all(|s| s.code != "(b)(1)"));
+        let counts = repo.get_exemption_counts().await.unwrap();
+        assert_eq!(counts[0], ("(b)(6)".to_string(), 2));
+        assert_eq!(counts.len(), 4);
+
+        let timeline = repo
+            .get_exemption_timeline(None, Some("(b)(6)"), StatsInterval::Year)
+            .await
+            .unwrap();
+        let periods: Vec<(&str, u64)> = timeline
+            .iter()
+            .map(|p| (p.period.as_str(), p.markings))
+            .collect();
+        assert_eq!(periods, vec![("1998", 2), ("2004", 1)]);
+
+        // Re-extraction replaces earlier markings
+        repo.replace_exemptions("a", 2, &[]).await.unwrap();
+        assert!(repo.get_exemptions("a").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! History of scheduled export jobs.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{ExportRunRecord, NewExportRun};
+use crate::repository::pool::DieselError;
+use crate::repository::{parse_datetime, parse_datetime_opt};
+use crate::schema::export_runs;
+use crate::services::exports::{ExportRun, RunStatus};
+use crate::with_conn;
+
+impl From<ExportRunRecord> for ExportRun {
+    fn from(record: ExportRunRecord) -> Self {
+        Self {
+            id: record.id,
+            job: record.job,
+            format: record.format,
+            destination: record.destination,
+            started_at: parse_datetime(&record.started_at),
+            finished_at: parse_datetime_opt(record.finished_at),
+            status: RunStatus::from_str(&record.status).unwrap_or(RunStatus::Failed),
+            artifact: record.artifact,
+            documents: record.documents.max(0) as u64,
+            bytes: record.bytes.max(0) as u64,
+            error: record.error,
+            change_seq: record.change_seq,
+        }
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Record the start of an export run at change journal entry
+    /// `change_seq`, returning its ID.
+    pub async fn start_export_run(
+        &self,
+        job: &str,
+        format: &str,
+        destination: &str,
+        started_at: DateTime<Utc>,
+        change_seq: i64,
+    ) -> Result<i32, DieselError> {
+        let started_at = started_at.to_rfc3339();
+        let new = NewExportRun {
+            job,
+            format,
+            destination,
+            started_at: &started_at,
+            status: RunStatus::Running.as_str(),
+            change_seq: Some(change_seq),
+        };
+        with_conn!(self.pool, conn, {
+            diesel::insert_into(export_runs::table)
+                .values(&new)
+                .execute(&mut conn)
+                .await?;
+            export_runs::table
+                .filter(export_runs::job.eq(job))
+                .filter(export_runs::started_at.eq(&started_at))
+                .select(export_runs::id)
+                .order(export_runs::id.desc())
+                .first(&mut conn)
+                .await
+        })
+    }
+
+    /// Record how an export run ended.
+    pub async fn finish_export_run(
+        &self,
+        id: i32,
+        status: RunStatus,
+        artifact: Option<&str>,
+        documents: u64,
+        bytes: u64,
+        error: Option<&str>,
+    ) -> Result<ExportRun, DieselError> {
+        let finished_at = Utc::now().to_rfc3339();
+        let record: ExportRunRecord = with_conn!(self.pool, conn, {
+            diesel::update(export_runs::table.find(id))
+                .set((
+                    export_runs::finished_at.eq(&finished_at),
+                    export_runs::status.eq(status.as_str()),
+                    export_runs::artifact.eq(artifact),
+                    export_runs::documents.eq(documents as i32),
+                    export_runs::bytes.eq(bytes as i64),
+                    export_runs::error.eq(error),
+                ))
+                .execute(&mut conn)
+                .await?;
+            export_runs::table.find(id).first(&mut conn).await
+        })?;
+        Ok(record.into())
+    }
+
+    /// Most recent run of a job, optionally with a given status.
+    pub async fn last_export_run(
+        &self,
+        job: &str,
+        status: Option<RunStatus>,
+    ) -> Result<Option<ExportRun>, DieselError> {
+        let record: Option<ExportRunRecord> = with_conn!(self.pool, conn, {
+            let mut q = export_runs::table
+                .filter(export_runs::job.eq(job))
+                .into_boxed();
+            if let Some(status) = status {
+                q = q.filter(export_runs::status.eq(status.as_str()));
+            }
+            q.order((export_runs::started_at.desc(), export_runs::id.desc()))
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(record.map(ExportRun::from))
+    }
+
+    /// Runs, newest first, optionally for one job.
+    pub async fn list_export_runs(
+        &self,
+        job: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ExportRun>, DieselError> {
+        let records: Vec<ExportRunRecord> = with_conn!(self.pool, conn, {
+            let mut q = export_runs::table.into_boxed();
+            if let Some(job) = job {
+                q = q.filter(export_runs::job.eq(job));
+            }
+            q.order((export_runs::started_at.desc(), export_runs::id.desc()))
+                .limit(limit)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(records.into_iter().map(ExportRun::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_export_run_history() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        let first = repo
+            .start_export_run("daily", "jsonl", "/mnt/exports", Utc::now(), 7)
+            .await
+            .unwrap();
+        let run = repo
+            .finish_export_run(
+                first,
+                RunStatus::Succeeded,
+                Some("/mnt/exports/daily.jsonl"),
+                3,
+                1024,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(run.status, RunStatus::Succeeded);
+        assert_eq!((run.documents, run.bytes), (3, 1024));
+
+        let second = repo
+            .start_export_run(
+                "daily",
+                "jsonl",
+                "/mnt/exports",
+                Utc::now() + chrono::Duration::seconds(1),
+                9,
+            )
+            .await
+            .unwrap();
+        repo.finish_export_run(second, RunStatus::Failed, None, 0, 0, Some("disk full"))
+            .await
+            .unwrap();
+
+        let last = repo.last_export_run("daily", None).await.unwrap().unwrap();
+        assert_eq!(last.id, second);
+        assert_eq!(last.error.as_deref(), Some("disk full"));
+        let ok = repo
+            .last_export_run("daily", Some(RunStatus::Succeeded))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ok.id, first);
+        assert_eq!(ok.change_seq, Some(7));
+        assert!(repo
+            .last_export_run("weekly", None)
+            .await
+            .unwrap()
+            .is_none());
+
+        let runs = repo.list_export_runs(None, 10).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].id, second);
+    }
+}
@@ -0,0 +1,172 @@
+//! Archive growth over a period, for funder reports.
+//!
+//! Counts come from the timestamps already on each row: documents by
+//! `created_at`, versions by `acquired_at`. OCR coverage is the share of
+//! pages of documents acquired by the end of the period whose OCR is done
+//! or was skipped because the page already had text.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use diesel::sql_types::{BigInt, Nullable, Text};
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::pool::DieselError;
+use crate::services::report::{NotableRecord, SourceGrowth};
+use crate::with_conn;
+
+#[derive(diesel::QueryableByName)]
+struct DocumentCountRow {
+    #[diesel(sql_type = Text)]
+    source_id: String,
+    #[diesel(sql_type = BigInt)]
+    existing: i64,
+    #[diesel(sql_type = BigInt)]
+    added: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct VersionCountRow {
+    #[diesel(sql_type = Text)]
+    source_id: String,
+    #[diesel(sql_type = BigInt)]
+    versions: i64,
+    #[diesel(sql_type = BigInt)]
+    bytes: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct PageCountRow {
+    #[diesel(sql_type = Text)]
+    source_id: String,
+    #[diesel(sql_type = BigInt)]
+    pages: i64,
+    #[diesel(sql_type = BigInt)]
+    ocr_pages: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct NotableRow {
+    #[diesel(sql_type = Text)]
+    id: String,
+    #[diesel(sql_type = Text)]
+    source_id: String,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = Text)]
+    source_url: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    synopsis: Option<String>,
+    #[diesel(sql_type = Text)]
+    created_at: String,
+    #[diesel(sql_type = BigInt)]
+    pages: i64,
+}
+
+impl DieselDocumentRepository {
+    /// Growth of every source that had documents by `to`, by source ID.
+    pub async fn source_growth(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<SourceGrowth>, DieselError> {
+        let (from, to) = (from.to_rfc3339(), to.to_rfc3339());
+
+        let (documents, versions, pages) = with_conn!(self.pool, conn, {
+            let documents: Vec<DocumentCountRow> = diesel::sql_query(
+                "SELECT source_id, \
+                 CAST(SUM(CASE WHEN created_at < $1 THEN 1 ELSE 0 END) AS BIGINT) AS existing, \
+                 CAST(SUM(CASE WHEN created_at >= $1 THEN 1 ELSE 0 END) AS BIGINT) AS added \
+                 FROM documents WHERE created_at < $2 GROUP BY source_id",
+            )
+            .bind::<Text, _>(&from)
+            .bind::<Text, _>(&to)
+            .load(&mut conn)
+            .await?;
+
+            let versions: Vec<VersionCountRow> = diesel::sql_query(
+                "SELECT d.source_id, CAST(COUNT(*) AS BIGINT) AS versions, \
+                 CAST(COALESCE(SUM(v.file_size), 0) AS BIGINT) AS bytes \
+                 FROM document_versions v JOIN documents d ON d.id = v.document_id \
+                 WHERE v.acquired_at >= $1 AND v.acquired_at < $2 GROUP BY d.source_id",
+            )
+            .bind::<Text, _>(&from)
+            .bind::<Text, _>(&to)
+            .load(&mut conn)
+            .await?;
+
+            let pages: Vec<PageCountRow> = diesel::sql_query(
+                "SELECT d.source_id, CAST(COUNT(*) AS BIGINT) AS pages, \
+                 CAST(SUM(CASE WHEN p.ocr_status IN ('ocr_complete', 'skipped') \
+                 THEN 1 ELSE 0 END) AS BIGINT) AS ocr_pages \
+                 FROM document_pages p JOIN documents d ON d.id = p.document_id \
+                 WHERE d.created_at < $1 GROUP BY d.source_id",
+            )
+            .bind::<Text, _>(&to)
+            .load(&mut conn)
+            .await?;
+
+            Ok::<_, DieselError>((documents, versions, pages))
+        })?;
+
+        let mut growth: BTreeMap<String, SourceGrowth> = BTreeMap::new();
+        for row in documents {
+            let g = growth.entry(row.source_id.clone()).or_default();
+            g.source_id = row.source_id;
+            g.documents_before = row.existing as u64;
+            g.documents_added = row.added as u64;
+        }
+        for row in versions {
+            let g = growth.entry(row.source_id.clone()).or_default();
+            g.source_id = row.source_id;
+            g.versions_added = row.versions as u64;
+            g.bytes_added = row.bytes as u64;
+        }
+        for row in pages {
+            let g = growth.entry(row.source_id.clone()).or_default();
+            g.source_id = row.source_id;
+            g.pages = row.pages as u64;
+            g.ocr_pages = row.ocr_pages as u64;
+        }
+        Ok(growth.into_values().collect())
+    }
+
+    /// Documents first acquired in the period that stand out: summarized
+    /// ones first, then the longest.
+    pub async fn notable_new_documents(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<NotableRecord>, DieselError> {
+        let rows: Vec<NotableRow> = with_conn!(self.pool, conn, {
+            diesel::sql_query(
+                "SELECT d.id, d.source_id, d.title, d.source_url, d.synopsis, d.created_at, \
+                 CAST(COALESCE(MAX(v.page_count), 0) AS BIGINT) AS pages \
+                 FROM documents d LEFT JOIN document_versions v ON v.document_id = d.id \
+                 WHERE d.created_at >= $1 AND d.created_at < $2 \
+                 GROUP BY d.id, d.source_id, d.title, d.source_url, d.synopsis, d.created_at \
+                 ORDER BY CASE WHEN d.synopsis IS NULL THEN 1 ELSE 0 END, pages DESC, d.id \
+                 LIMIT $3",
+            )
+            .bind::<Text, _>(from.to_rfc3339())
+            .bind::<Text, _>(to.to_rfc3339())
+            .bind::<BigInt, _>(limit as i64)
+            .load(&mut conn)
+            .await
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|r| NotableRecord {
+                id: r.id,
+                source_id: r.source_id,
+                title: r.title,
+                url: r.source_url,
+                synopsis: r.synopsis,
+                acquired_at: r.created_at,
+                pages: r.pages as u64,
+            })
+            .collect())
+    }
+}
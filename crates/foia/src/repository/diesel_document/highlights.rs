@@ -0,0 +1,215 @@
+//! Page highlight (user-selected text range) operations.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::{DieselDocumentRepository, ReturningId};
+use crate::repository::models::{NewPageHighlight, PageHighlightRecord};
+use crate::repository::pool::DieselError;
+use crate::schema::page_highlights;
+use crate::with_conn;
+
+impl DieselDocumentRepository {
+    /// Save a page highlight. Returns the highlight ID.
+    pub async fn save_highlight(
+        &self,
+        highlight: &NewPageHighlight<'_>,
+    ) -> Result<i64, DieselError> {
+        use crate::repository::pool::build_sql;
+        use crate::repository::sea_tables::PageHighlights;
+        use sea_query::Query;
+
+        let stmt = Query::insert()
+            .into_table(PageHighlights::Table)
+            .columns([
+                PageHighlights::DocumentId,
+                PageHighlights::VersionId,
+                PageHighlights::PageNumber,
+                PageHighlights::StartOffset,
+                PageHighlights::EndOffset,
+                PageHighlights::SelectedText,
+                PageHighlights::Comment,
+                PageHighlights::Label,
+                PageHighlights::CreatedAt,
+            ])
+            .values_panic([
+                highlight.document_id.into(),
+                highlight.version_id.into(),
+                highlight.page_number.into(),
+                highlight.start_offset.into(),
+                highlight.end_offset.into(),
+                highlight.selected_text.into(),
+                highlight.comment.into(),
+                highlight.label.into(),
+                highlight.created_at.into(),
+            ])
+            .returning_col(PageHighlights::Id)
+            .to_owned();
+
+        let sql = build_sql(&self.pool, &stmt);
+
+        with_conn!(self.pool, conn, {
+            let result: ReturningId = diesel::sql_query(&sql)
+                .bind::<diesel::sql_types::Text, _>(highlight.document_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Integer>, _>(
+                    highlight.version_id,
+                )
+                .bind::<diesel::sql_types::Integer, _>(highlight.page_number)
+                .bind::<diesel::sql_types::Integer, _>(highlight.start_offset)
+                .bind::<diesel::sql_types::Integer, _>(highlight.end_offset)
+                .bind::<diesel::sql_types::Text, _>(highlight.selected_text)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(
+                    highlight.comment,
+                )
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(highlight.label)
+                .bind::<diesel::sql_types::Text, _>(highlight.created_at)
+                .get_result(&mut conn)
+                .await?;
+            Ok(result.id as i64)
+        })
+    }
+
+    /// Get all highlights for a document, ordered by page then position.
+    pub async fn get_highlights(
+        &self,
+        doc_id: &str,
+    ) -> Result<Vec<PageHighlightRecord>, DieselError> {
+        with_conn!(self.pool, conn, {
+            page_highlights::table
+                .filter(page_highlights::document_id.eq(doc_id))
+                .order((
+                    page_highlights::page_number.asc(),
+                    page_highlights::start_offset.asc(),
+                ))
+                .load(&mut conn)
+                .await
+        })
+    }
+
+    /// Get highlights for multiple documents in a single query.
+    pub async fn get_highlights_batch(
+        &self,
+        doc_ids: &[String],
+    ) -> Result<HashMap<String, Vec<PageHighlightRecord>>, DieselError> {
+        if doc_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let records: Vec<PageHighlightRecord> = with_conn!(self.pool, conn, {
+            page_highlights::table
+                .filter(page_highlights::document_id.eq_any(doc_ids))
+                .order((
+                    page_highlights::document_id.asc(),
+                    page_highlights::page_number.asc(),
+                    page_highlights::start_offset.asc(),
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+
+        let mut map: HashMap<String, Vec<PageHighlightRecord>> = HashMap::new();
+        for record in records {
+            map.entry(record.document_id.clone())
+                .or_default()
+                .push(record);
+        }
+        Ok(map)
+    }
+
+    /// Delete a single highlight belonging to a document.
+    /// Returns true if a row was removed.
+    pub async fn delete_highlight(&self, doc_id: &str, id: i32) -> Result<bool, DieselError> {
+        with_conn!(self.pool, conn, {
+            let deleted = diesel::delete(
+                page_highlights::table
+                    .filter(page_highlights::id.eq(id))
+                    .filter(page_highlights::document_id.eq(doc_id)),
+            )
+            .execute(&mut conn)
+            .await?;
+            Ok(deleted > 0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use chrono::Utc;
+
+    async fn create_highlight_table(repo: &DieselDocumentRepository) -> Result<(), DieselError> {
+        use diesel_async::SimpleAsyncConnection;
+        with_conn!(repo.pool, conn, {
+            conn.batch_execute(
+                r#"CREATE TABLE IF NOT EXISTS page_highlights (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    document_id TEXT NOT NULL,
+                    version_id INTEGER,
+                    page_number INTEGER NOT NULL,
+                    start_offset INTEGER NOT NULL,
+                    end_offset INTEGER NOT NULL,
+                    selected_text TEXT NOT NULL,
+                    comment TEXT,
+                    label TEXT,
+                    created_at TEXT NOT NULL
+                )"#,
+            )
+            .await
+            .unwrap();
+            Ok::<_, DieselError>(())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_highlight_crud() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        create_highlight_table(&repo).await.unwrap();
+
+        let now = Utc::now().to_rfc3339();
+        let second = NewPageHighlight {
+            document_id: "doc-hl-1",
+            version_id: Some(1),
+            page_number: 2,
+            start_offset: 10,
+            end_offset: 24,
+            selected_text: "redacted names",
+            comment: Some("check against index"),
+            label: Some("follow-up"),
+            created_at: &now,
+        };
+        let first = NewPageHighlight {
+            page_number: 1,
+            start_offset: 0,
+            end_offset: 5,
+            selected_text: "Dear ",
+            comment: None,
+            label: None,
+            ..second
+        };
+
+        let id_b = repo.save_highlight(&second).await.unwrap();
+        let id_a = repo.save_highlight(&first).await.unwrap();
+        assert_ne!(id_a, id_b);
+
+        let fetched = repo.get_highlights("doc-hl-1").await.unwrap();
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(fetched[0].page_number, 1);
+        assert_eq!(fetched[1].label.as_deref(), Some("follow-up"));
+
+        let batch = repo
+            .get_highlights_batch(&["doc-hl-1".to_string(), "other".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(batch.get("doc-hl-1").map(Vec::len), Some(2));
+        assert!(!batch.contains_key("other"));
+
+        // Deleting through the wrong document is a no-op
+        assert!(!repo.delete_highlight("other", id_a as i32).await.unwrap());
+        assert!(repo.delete_highlight("doc-hl-1", id_a as i32).await.unwrap());
+        assert_eq!(repo.get_highlights("doc-hl-1").await.unwrap().len(), 1);
+    }
+}
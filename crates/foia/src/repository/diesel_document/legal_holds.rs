@@ -0,0 +1,146 @@
+//! Legal holds: documents whose files and versions must not be deleted.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::LegalHold;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{documents, legal_holds};
+use crate::{with_conn, with_conn_split};
+
+impl DieselDocumentRepository {
+    /// Place a document under legal hold, or update the reason if it
+    /// already is.
+    pub async fn place_legal_hold(&self, doc_id: &str, reason: &str) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let values = (
+            legal_holds::document_id.eq(doc_id),
+            legal_holds::reason.eq(reason),
+            legal_holds::placed_at.eq(&now),
+        );
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(legal_holds::table)
+                    .values(values)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(legal_holds::table)
+                    .values(values)
+                    .on_conflict(legal_holds::document_id)
+                    .do_update()
+                    .set(legal_holds::reason.eq(reason))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// Release a document's legal hold. Returns false if it had none.
+    pub async fn release_legal_hold(&self, doc_id: &str) -> Result<bool, DieselError> {
+        with_conn!(self.pool, conn, {
+            diesel::delete(legal_holds::table.find(doc_id))
+                .execute(&mut conn)
+                .await
+                .map(|rows| rows > 0)
+        })
+    }
+
+    /// Legal holds, optionally limited to one source, oldest first.
+    pub async fn get_legal_holds(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<Vec<LegalHold>, DieselError> {
+        let rows: Vec<(String, String, String)> = with_conn!(self.pool, conn, {
+            let mut query = legal_holds::table
+                .inner_join(documents::table)
+                .select((
+                    legal_holds::document_id,
+                    legal_holds::reason,
+                    legal_holds::placed_at,
+                ))
+                .order(legal_holds::placed_at.asc())
+                .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(documents::source_id.eq(source_id));
+            }
+            query.load(&mut conn).await
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|(document_id, reason, placed_at)| LegalHold {
+                document_id,
+                reason,
+                placed_at: parse_datetime(&placed_at),
+            })
+            .collect())
+    }
+
+    /// IDs of held documents, optionally limited to one source.
+    pub async fn get_held_document_ids(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<HashSet<String>, DieselError> {
+        Ok(self
+            .get_legal_holds(source_id)
+            .await?
+            .into_iter()
+            .map(|hold| hold.document_id)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    fn doc(id: &str, source_id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: id.to_string(),
+            source_url: format!("https://agency.gov/{}", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "import".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_legal_holds() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        repo.save(&doc("a", "fbi")).await.unwrap();
+        repo.save(&doc("b", "cia")).await.unwrap();
+
+        repo.place_legal_hold("a", "Case 24-cv-0101").await.unwrap();
+        repo.place_legal_hold("b", "Request 2024-17").await.unwrap();
+        repo.place_legal_hold("a", "Case 24-cv-0102").await.unwrap();
+
+        let holds = repo.get_legal_holds(Some("fbi")).await.unwrap();
+        assert_eq!(holds.len(), 1);
+        assert_eq!(holds[0].reason, "Case 24-cv-0102");
+        assert_eq!(repo.get_held_document_ids(None).await.unwrap().len(), 2);
+
+        assert!(repo.release_legal_hold("a").await.unwrap());
+        assert!(!repo.release_legal_hold("a").await.unwrap());
+        let held = repo.get_held_document_ids(None).await.unwrap();
+        assert!(!held.contains("a") && held.contains("b"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Tracking of versions whose stored files are gone, either lost to a
+//! storage failure or deleted under text-only retention.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::LostFile;
+use crate::repository::models::LostFileRecord;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{document_versions, documents, lost_files};
+use crate::with_conn;
+
+impl From<LostFileRecord> for LostFile {
+    fn from(record: LostFileRecord) -> Self {
+        Self {
+            version_id: record.version_id as i64,
+            document_id: record.document_id,
+            reason: record.reason,
+            lost_at: parse_datetime(&record.lost_at),
+        }
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Mark a version's file as lost, or update the reason if it already is.
+    pub async fn mark_version_lost(
+        &self,
+        version_id: i64,
+        document_id: &str,
+        reason: &str,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+
+        with_conn!(self.pool, conn, {
+            // Try to update first
+            let updated = diesel::update(lost_files::table.find(version_id as i32))
+                .set((lost_files::reason.eq(reason), lost_files::lost_at.eq(&now)))
+                .execute(&mut conn)
+                .await?;
+
+            // If no row was updated, insert
+            if updated == 0 {
+                diesel::insert_into(lost_files::table)
+                    .values((
+                        lost_files::version_id.eq(version_id as i32),
+                        lost_files::document_id.eq(document_id),
+                        lost_files::reason.eq(reason),
+                        lost_files::lost_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Clear the lost mark after a version's file has been restored.
+    pub async fn clear_version_lost(&self, version_id: i64) -> Result<bool, DieselError> {
+        with_conn!(self.pool, conn, {
+            diesel::delete(lost_files::table.find(version_id as i32))
+                .execute(&mut conn)
+                .await
+                .map(|rows| rows > 0)
+        })
+    }
+
+    /// Lost versions of one document.
+    pub async fn get_lost_files(&self, document_id: &str) -> Result<Vec<LostFile>, DieselError> {
+        with_conn!(self.pool, conn, {
+            lost_files::table
+                .filter(lost_files::document_id.eq(document_id))
+                .select(LostFileRecord::as_select())
+                .load(&mut conn)
+                .await
+                .map(|records| records.into_iter().map(LostFile::from).collect())
+        })
+    }
+
+    /// IDs of every lost version, optionally limited to one source.
+    pub async fn get_lost_version_ids(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<HashSet<i64>, DieselError> {
+        let ids: Vec<i32> = with_conn!(self.pool, conn, {
+            match source_id {
+                Some(source_id) => {
+                    lost_files::table
+                        .inner_join(documents::table.on(documents::id.eq(lost_files::document_id)))
+                        .filter(documents::source_id.eq(source_id))
+                        .select(lost_files::version_id)
+                        .load(&mut conn)
+                        .await
+                }
+                None => {
+                    lost_files::table
+                        .select(lost_files::version_id)
+                        .load(&mut conn)
+                        .await
+                }
+            }
+        })?;
+        Ok(ids.into_iter().map(|id| id as i64).collect())
+    }
+
+    /// Count other versions with the same content that still expect their
+    /// file on disk. Deduplicated versions can share one file, so it may
+    /// only be deleted when this is zero.
+    pub async fn count_versions_needing_file(
+        &self,
+        content_hash: &str,
+        exclude_version_id: i64,
+    ) -> Result<u64, DieselError> {
+        with_conn!(self.pool, conn, {
+            let count: i64 = document_versions::table
+                .filter(document_versions::content_hash.eq(content_hash))
+                .filter(document_versions::id.ne(exclude_version_id as i32))
+                .filter(diesel::dsl::not(
+                    document_versions::id.eq_any(lost_files::table.select(lost_files::version_id)),
+                ))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            Ok(count as u64)
+        })
+    }
+}
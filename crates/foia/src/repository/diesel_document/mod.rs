@@ -8,14 +8,96 @@
 //! - `pages.rs`: Document page and OCR operations
 //! - `queries.rs`: Complex queries, browsing, statistics
 //! - `analysis.rs`: Analysis result operations
+//! - `highlights.rs`: User page highlights and comments
+//! - `relations.rs`: Typed links between documents
+//! - `lost_files.rs`: Versions whose stored file is lost or pruned
+//! - `page_compression.rs`: Bulk compression of stored page text
+//! - `stream.rs`: Batched streaming over large document sets
+//! - `projection.rs`: Column projection to skip `extracted_text`
+//! - `columns.rs`: Header columns of CSV files and spreadsheets
+//! - `classifications.rs`: Record types assigned by classification
+//! - `bates.rs`: Bates-stamped page ranges and lookup by Bates number
+//! - `exemptions.rs`: FOIA exemption markings per page and their statistics
+//! - `aliases.rs`: Alias dictionary for search query expansion
+//! - `export_runs.rs`: History of scheduled export jobs
+//! - `access.rs`: Visibility rules for documents and sources
+//! - `api_keys.rs`: Partner API keys and their usage
+//! - `storage.rs`: Aggregate storage usage by source, type and artifact
+//! - `search_sync.rs`: Attachment text for search indexes
+//! - `changes.rs`: Change journal for incremental exports and indexing
+
+/// Withhold the documents an [`AccessFilter`](crate::services::access::AccessFilter)
+/// hides from a boxed query that includes the `documents` table.
+macro_rules! restrict_access {
+    ($query:ident, $access:expr) => {
+        if let Some(access) = $access {
+            if !access.hidden_documents.is_empty() {
+                $query = $query.filter(documents::id.ne_all(&access.hidden_documents));
+            }
+            if !access.hidden_sources.is_empty() {
+                $query = $query.filter(
+                    documents::source_id
+                        .ne_all(&access.hidden_sources)
+                        .or(documents::id.eq_any(&access.released_documents)),
+                );
+            }
+            if let Some(only) = &access.only_sources {
+                $query = $query.filter(documents::source_id.eq_any(only));
+            }
+        }
+    };
+}
 
+mod access;
+mod aliases;
 mod analysis;
+mod api_keys;
+mod bates;
+mod changes;
+mod classifications;
+mod columns;
+mod coverage;
+mod dates;
 pub mod entities;
+mod exemptions;
+mod export_runs;
+mod growth;
+mod highlights;
+mod legal_holds;
+mod lost_files;
+mod original_paths;
+mod page_compression;
 mod pages;
+mod preprocessing;
+mod projection;
 mod queries;
+mod relations;
+mod search_sync;
+mod sections;
+mod splits;
+mod stats;
+mod storage;
+mod stream;
+mod summaries;
+mod takedowns;
+mod text_decisions;
+mod text_revisions;
+mod titles;
+mod transcription;
+mod uploads;
 mod versions;
-
-pub use queries::BrowseParams;
+mod virtual_files;
+
+pub use bates::BatesHit;
+pub use columns::{column_key, ColumnMatch};
+pub use coverage::{BackendUsage, CoverageDocument, CoverageIssue, SourceCoverage};
+pub use exemptions::{ExemptionPeriod, ExemptionStats, StatsInterval};
+pub use page_compression::PageCompressionBatch;
+pub use projection::Projection;
+pub use queries::{BrowseCursor, BrowseParams, Keyset, SourceScope};
+pub use relations::{RelatedDocument, RelationGraph, MAX_RELATION_DEPTH};
+pub use stream::{StreamFilter, DEFAULT_STREAM_BATCH};
+pub use summaries::SummarySelector;
 
 use std::path::PathBuf;
 
@@ -24,7 +106,7 @@ use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
 use super::models::{DocumentRecord, DocumentVersionRecord, VirtualFileRecord};
-use super::pool::{DbPool, DieselError};
+use super::pool::{retry_on_busy, DbPool, DieselError};
 use super::{parse_datetime, parse_datetime_opt};
 use crate::models::{Document, DocumentStatus, DocumentVersion, VirtualFile, VirtualFileStatus};
 use crate::schema::{document_versions, documents, virtual_files};
@@ -81,11 +163,21 @@ impl DieselDocumentRepository {
 
     /// Get multiple documents by IDs in a single batch query.
     pub async fn get_batch(&self, ids: &[String]) -> Result<Vec<Document>, DieselError> {
+        self.get_batch_projected(ids, Projection::Full).await
+    }
+
+    /// Get multiple documents by ID, loading only the columns in `projection`.
+    pub async fn get_batch_projected(
+        &self,
+        ids: &[String],
+        projection: Projection,
+    ) -> Result<Vec<Document>, DieselError> {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
         let records: Vec<DocumentRecord> = with_conn!(self.pool, conn, {
-            documents::table
+            projection
+                .documents_query()
                 .filter(documents::id.eq_any(ids))
                 .load(&mut conn)
                 .await
@@ -96,8 +188,22 @@ impl DieselDocumentRepository {
 
     /// Get a document by ID.
     pub async fn get(&self, id: &str) -> Result<Option<Document>, DieselError> {
+        self.get_projected(id, Projection::Full).await
+    }
+
+    /// Get a document by ID, loading only the columns in `projection`.
+    pub async fn get_projected(
+        &self,
+        id: &str,
+        projection: Projection,
+    ) -> Result<Option<Document>, DieselError> {
         let record: Option<DocumentRecord> = with_conn!(self.pool, conn, {
-            documents::table.find(id).first(&mut conn).await.optional()
+            projection
+                .documents_query()
+                .filter(documents::id.eq(id))
+                .first(&mut conn)
+                .await
+                .optional()
         })?;
 
         match record {
@@ -245,16 +351,79 @@ impl DieselDocumentRepository {
     }
 
     /// Delete a document.
-    #[allow(dead_code)]
     pub async fn delete(&self, id: &str) -> Result<bool, DieselError> {
-        use crate::schema::document_pages;
+        use crate::schema::{
+            document_analysis_results, document_bates, document_classifications, document_columns,
+            document_exemptions, document_pages, document_sections, document_splits, legal_holds,
+            lost_files, original_paths, page_preprocessing, page_text_decisions,
+            page_text_revisions, takedown_documents, title_suggestions, transcription_entries,
+            transcription_tasks, uploads,
+        };
         use diesel_async::AsyncConnection;
 
         with_conn!(self.pool, conn, {
             conn.transaction(|conn| {
                 Box::pin(async move {
+                    // Children first: pages and attachments reference versions
                     diesel::delete(
-                        document_versions::table.filter(document_versions::document_id.eq(id)),
+                        document_analysis_results::table
+                            .filter(document_analysis_results::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        page_text_decisions::table.filter(
+                            page_text_decisions::page_id.eq_any(
+                                document_pages::table
+                                    .filter(document_pages::document_id.eq(id))
+                                    .select(document_pages::id),
+                            ),
+                        ),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        page_text_revisions::table.filter(
+                            page_text_revisions::page_id.eq_any(
+                                document_pages::table
+                                    .filter(document_pages::document_id.eq(id))
+                                    .select(document_pages::id),
+                            ),
+                        ),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        page_preprocessing::table.filter(
+                            page_preprocessing::page_id.eq_any(
+                                document_pages::table
+                                    .filter(document_pages::document_id.eq(id))
+                                    .select(document_pages::id),
+                            ),
+                        ),
+                    )
+                    .execute(conn)
+                    .await?;
+                    let task_ids = || {
+                        transcription_tasks::table
+                            .filter(
+                                transcription_tasks::page_id.eq_any(
+                                    document_pages::table
+                                        .filter(document_pages::document_id.eq(id))
+                                        .select(document_pages::id),
+                                ),
+                            )
+                            .select(transcription_tasks::id)
+                    };
+                    diesel::delete(
+                        transcription_entries::table
+                            .filter(transcription_entries::task_id.eq_any(task_ids())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        transcription_tasks::table
+                            .filter(transcription_tasks::id.eq_any(task_ids())),
                     )
                     .execute(conn)
                     .await?;
@@ -266,6 +435,72 @@ impl DieselDocumentRepository {
                     diesel::delete(virtual_files::table.filter(virtual_files::document_id.eq(id)))
                         .execute(conn)
                         .await?;
+                    diesel::delete(lost_files::table.filter(lost_files::document_id.eq(id)))
+                        .execute(conn)
+                        .await?;
+                    diesel::delete(
+                        document_columns::table.filter(document_columns::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_classifications::table
+                            .filter(document_classifications::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_bates::table.filter(document_bates::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        title_suggestions::table.filter(title_suggestions::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        original_paths::table.filter(original_paths::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(legal_holds::table.filter(legal_holds::document_id.eq(id)))
+                        .execute(conn)
+                        .await?;
+                    diesel::delete(
+                        document_splits::table.filter(
+                            document_splits::document_id
+                                .eq(id)
+                                .or(document_splits::parent_id.eq(id)),
+                        ),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_sections::table.filter(document_sections::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(uploads::table.filter(uploads::document_id.eq(id)))
+                        .execute(conn)
+                        .await?;
+                    // The case stays on record without the document
+                    diesel::delete(
+                        takedown_documents::table.filter(takedown_documents::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_exemptions::table
+                            .filter(document_exemptions::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_versions::table.filter(document_versions::document_id.eq(id)),
+                    )
+                    .execute(conn)
+                    .await?;
                     let rows = diesel::delete(documents::table.find(id))
                         .execute(conn)
                         .await?;
@@ -277,20 +512,48 @@ impl DieselDocumentRepository {
     }
 
     /// Update document status.
+    ///
+    /// Only transitions allowed by [`DocumentStatus::can_transition_to`] are
+    /// applied; any other edge fails with a `QueryBuilderError`. Updating a
+    /// missing document is a no-op.
     pub async fn update_status(&self, id: &str, status: DocumentStatus) -> Result<(), DieselError> {
         let status_str = status.as_str().to_string();
+        let allowed_from: Vec<&str> = status.predecessors().iter().map(|s| s.as_str()).collect();
         let updated_at = Utc::now().to_rfc3339();
 
-        with_conn!(self.pool, conn, {
-            diesel::update(documents::table.find(id))
+        retry_on_busy(|| async {
+            with_conn!(self.pool, conn, {
+                // Guard on the current status so concurrent writers can't
+                // sneak a disallowed edge in between a read and the update.
+                let rows = diesel::update(
+                    documents::table
+                        .find(id)
+                        .filter(documents::status.eq_any(&allowed_from)),
+                )
                 .set((
                     documents::status.eq(&status_str),
                     documents::updated_at.eq(&updated_at),
                 ))
                 .execute(&mut conn)
                 .await?;
-            Ok(())
+
+                if rows > 0 {
+                    Ok(())
+                } else {
+                    let current: Option<String> = documents::table
+                        .find(id)
+                        .select(documents::status)
+                        .first(&mut conn)
+                        .await
+                        .optional()?;
+                    match current {
+                        Some(current) => Err(invalid_transition(id, &current, status)),
+                        None => Ok(()),
+                    }
+                }
+            })
         })
+        .await
     }
 
     /// Get all documents.
@@ -302,12 +565,7 @@ impl DieselDocumentRepository {
                 .await
         })?;
 
-        let mut docs = Vec::with_capacity(records.len());
-        for record in records {
-            let versions = self.load_versions(&record.id).await?;
-            docs.push(Self::record_to_document(record, versions)?);
-        }
-        Ok(docs)
+        self.records_to_documents(records).await
     }
 
     /// Get all document URLs as a HashSet.
@@ -700,14 +958,6 @@ impl DieselDocumentRepository {
 }
 
 // Helper structs for SQL queries
-#[derive(diesel::QueryableByName)]
-pub(crate) struct MimeCount {
-    #[diesel(sql_type = diesel::sql_types::Text)]
-    pub mime_type: String,
-    #[diesel(sql_type = diesel::sql_types::BigInt)]
-    pub count: i64,
-}
-
 #[derive(diesel::QueryableByName)]
 pub(crate) struct TagRow {
     #[diesel(sql_type = diesel::sql_types::Text)]
@@ -728,10 +978,19 @@ pub(crate) struct CountRow {
 
 /// Lightweight browse result that excludes large text fields.
 /// Used for document listing pages to avoid loading extracted_text.
+///
+/// Rows are documents or, when the `attachments` type is selected, archive
+/// members and email attachments; `kind` tells them apart.
 #[derive(diesel::QueryableByName, Debug, Clone)]
 pub struct BrowseRow {
     #[diesel(sql_type = diesel::sql_types::Text)]
     pub id: String,
+    /// [`BrowseRow::DOCUMENT`] or [`BrowseRow::ATTACHMENT`].
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub kind: String,
+    /// Document an attachment belongs to.
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub parent_id: Option<String>,
     #[diesel(sql_type = diesel::sql_types::Text)]
     pub title: String,
     #[diesel(sql_type = diesel::sql_types::Text)]
@@ -748,6 +1007,35 @@ pub struct BrowseRow {
     pub file_size: i32,
     #[diesel(sql_type = diesel::sql_types::Text)]
     pub acquired_at: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub updated_at: String,
+}
+
+impl BrowseRow {
+    pub const DOCUMENT: &'static str = "document";
+    pub const ATTACHMENT: &'static str = "attachment";
+
+    pub fn is_attachment(&self) -> bool {
+        self.kind == Self::ATTACHMENT
+    }
+
+    /// Keyset cursor pointing at this row.
+    pub fn cursor(&self) -> BrowseCursor {
+        BrowseCursor::new(&self.updated_at, &self.id)
+    }
+}
+
+/// Error for a status change the document state machine doesn't allow.
+fn invalid_transition(id: &str, current: &str, next: DocumentStatus) -> DieselError {
+    diesel::result::Error::QueryBuilderError(
+        format!(
+            "invalid status transition for document '{}': {} -> {}",
+            id,
+            current,
+            next.as_str()
+        )
+        .into(),
+    )
 }
 
 #[derive(diesel::QueryableByName)]
@@ -757,7 +1045,7 @@ pub(crate) struct ReturningId {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::super::pool::SqlitePool;
     use super::*;
     use diesel_async::SimpleAsyncConnection;
@@ -824,6 +1112,53 @@ mod tests {
                 UNIQUE(document_id, version_id, page_number)
             );
 
+            CREATE TABLE IF NOT EXISTS page_preprocessing (
+                page_id INTEGER PRIMARY KEY,
+                rotation INTEGER NOT NULL DEFAULT 0,
+                skew_angle REAL,
+                steps TEXT NOT NULL,
+                tool TEXT,
+                applied_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS page_text_decisions (
+                page_id INTEGER PRIMARY KEY,
+                method TEXT NOT NULL,
+                backend TEXT,
+                scores TEXT NOT NULL,
+                rationale TEXT NOT NULL,
+                decided_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS page_text_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                page_id INTEGER NOT NULL,
+                editor TEXT NOT NULL,
+                previous_text TEXT,
+                text TEXT NOT NULL,
+                diff TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS transcription_tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                page_id INTEGER NOT NULL UNIQUE,
+                status TEXT NOT NULL DEFAULT 'open',
+                accepted_text TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS transcription_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id INTEGER NOT NULL,
+                volunteer TEXT NOT NULL,
+                text TEXT,
+                assigned_at TEXT NOT NULL,
+                submitted_at TEXT,
+                UNIQUE (task_id, volunteer)
+            );
+
             CREATE TABLE IF NOT EXISTS virtual_files (
                 id TEXT PRIMARY KEY,
                 document_id TEXT NOT NULL,
@@ -839,6 +1174,266 @@ mod tests {
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             );
+
+            CREATE TABLE IF NOT EXISTS virtual_file_annotations (
+                virtual_file_id TEXT NOT NULL,
+                annotation_type TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                data TEXT,
+                error TEXT,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (virtual_file_id, annotation_type)
+            );
+
+            CREATE TABLE IF NOT EXISTS document_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id TEXT NOT NULL,
+                related_document_id TEXT NOT NULL,
+                relation_type TEXT NOT NULL,
+                virtual_file_id TEXT,
+                note TEXT,
+                created_at TEXT NOT NULL,
+                UNIQUE(document_id, related_document_id, relation_type)
+            );
+
+            CREATE TABLE IF NOT EXISTS lost_files (
+                version_id INTEGER PRIMARY KEY,
+                document_id TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                lost_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS document_columns (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id TEXT NOT NULL,
+                version_id INTEGER NOT NULL,
+                sheet TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                name_key TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS document_classifications (
+                document_id TEXT PRIMARY KEY,
+                record_type TEXT NOT NULL,
+                confidence REAL NOT NULL,
+                classifier TEXT NOT NULL,
+                classified_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS title_suggestions (
+                document_id TEXT PRIMARY KEY,
+                original_title TEXT NOT NULL,
+                suggested_title TEXT NOT NULL,
+                method TEXT NOT NULL,
+                status TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                reviewed_at TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS original_paths (
+                document_id TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                path TEXT NOT NULL,
+                PRIMARY KEY (document_id, content_hash)
+            );
+
+            CREATE TABLE IF NOT EXISTS legal_holds (
+                document_id TEXT PRIMARY KEY,
+                reason TEXT NOT NULL,
+                placed_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS document_splits (
+                document_id TEXT PRIMARY KEY,
+                parent_id TEXT NOT NULL,
+                version_id INTEGER NOT NULL,
+                first_page INTEGER NOT NULL,
+                last_page INTEGER NOT NULL,
+                boundary TEXT,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS document_sections (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id TEXT NOT NULL,
+                version_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                level INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                first_page INTEGER NOT NULL,
+                last_page INTEGER NOT NULL,
+                origin TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS takedown_cases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                requester_name TEXT NOT NULL,
+                requester_contact TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'open',
+                submitted_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS takedown_documents (
+                case_id INTEGER NOT NULL,
+                document_id TEXT NOT NULL,
+                PRIMARY KEY (case_id, document_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS takedown_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                case_id INTEGER NOT NULL,
+                action TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                note TEXT,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS uploads (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id TEXT NOT NULL,
+                contributor TEXT NOT NULL,
+                description TEXT,
+                attribution TEXT,
+                status TEXT NOT NULL DEFAULT 'pending',
+                submitted_at TEXT NOT NULL,
+                reviewed_at TEXT,
+                reviewed_by TEXT,
+                review_note TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS document_bates (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id TEXT NOT NULL,
+                version_id INTEGER NOT NULL,
+                prefix TEXT NOT NULL,
+                prefix_key TEXT NOT NULL,
+                start_number INTEGER NOT NULL,
+                end_number INTEGER NOT NULL,
+                digits INTEGER NOT NULL,
+                first_page INTEGER NOT NULL,
+                last_page INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS document_exemptions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id TEXT NOT NULL,
+                version_id INTEGER NOT NULL,
+                page_number INTEGER NOT NULL,
+                code TEXT NOT NULL,
+                count INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS search_aliases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                term TEXT NOT NULL,
+                expansion TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_search_aliases_pair
+                ON search_aliases(term, expansion);
+
+            CREATE TABLE IF NOT EXISTS export_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                job TEXT NOT NULL,
+                format TEXT NOT NULL,
+                destination TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                finished_at TEXT,
+                status TEXT NOT NULL,
+                artifact TEXT,
+                documents INTEGER NOT NULL DEFAULT 0,
+                bytes BIGINT NOT NULL DEFAULT 0,
+                error TEXT,
+                change_seq BIGINT
+            );
+
+            CREATE TABLE IF NOT EXISTS access_rules (
+                scope TEXT NOT NULL,
+                target TEXT NOT NULL,
+                visibility TEXT NOT NULL,
+                embargo_until TEXT,
+                note TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (scope, target)
+            );
+
+            CREATE TABLE IF NOT EXISTS api_keys (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                key_prefix TEXT NOT NULL,
+                key_hash TEXT NOT NULL UNIQUE,
+                read_only INTEGER NOT NULL DEFAULT 1,
+                sources TEXT,
+                rate_limit INTEGER,
+                requests BIGINT NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                last_used_at TEXT,
+                revoked_at TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS api_key_usage (
+                key_id INTEGER NOT NULL,
+                day TEXT NOT NULL,
+                requests BIGINT NOT NULL DEFAULT 0,
+                PRIMARY KEY (key_id, day)
+            );
+
+            CREATE TABLE IF NOT EXISTS document_changes (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                change_type TEXT NOT NULL,
+                changed_at TEXT NOT NULL
+            );
+
+            CREATE TRIGGER IF NOT EXISTS tr_documents_change_insert
+            AFTER INSERT ON documents
+            BEGIN
+                INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+                VALUES (NEW.id, NEW.source_id, 'created', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS tr_documents_change_update
+            AFTER UPDATE ON documents
+            BEGIN
+                INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+                VALUES (NEW.id, NEW.source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS tr_documents_change_delete
+            AFTER DELETE ON documents
+            BEGIN
+                INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+                VALUES (OLD.id, OLD.source_id, 'deleted', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'));
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS tr_document_versions_change
+            AFTER INSERT ON document_versions
+            BEGIN
+                INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+                SELECT id, source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                FROM documents WHERE id = NEW.document_id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS tr_virtual_files_change_insert
+            AFTER INSERT ON virtual_files
+            BEGIN
+                INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+                SELECT id, source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                FROM documents WHERE id = NEW.document_id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS tr_virtual_files_change_update
+            AFTER UPDATE OF extracted_text ON virtual_files
+            BEGIN
+                INSERT INTO document_changes (document_id, source_id, change_type, changed_at)
+                SELECT id, source_id, 'updated', strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                FROM documents WHERE id = NEW.document_id;
+            END;
             "#,
         )
         .await
@@ -953,4 +1548,384 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 0);
     }
+
+    fn bulk_doc(i: usize) -> Document {
+        Document {
+            id: format!("bulk-{:05}", i),
+            source_id: "test-source".to_string(),
+            title: format!("Bulk {}", i),
+            source_url: format!("https://example.com/bulk/{}.pdf", i),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Pending,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    fn bulk_version(hash: String) -> DocumentVersion {
+        DocumentVersion {
+            id: 0,
+            content_hash: hash,
+            content_hash_blake3: None,
+            file_path: None,
+            file_size: 10,
+            mime_type: "application/pdf".to_string(),
+            acquired_at: Utc::now(),
+            source_url: None,
+            original_filename: None,
+            server_date: None,
+            page_count: None,
+            archive_snapshot_id: None,
+            earliest_archived_at: None,
+            dedup_index: None,
+        }
+    }
+
+    async fn seed_bulk(repo: &DieselDocumentRepository, count: usize) {
+        for i in 0..count {
+            let doc = bulk_doc(i);
+            repo.save(&doc).await.unwrap();
+            repo.add_version(&doc.id, &bulk_version(format!("{}-a", i)))
+                .await
+                .unwrap();
+            repo.add_version(&doc.id, &bulk_version(format!("{}-b", i)))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_all_loads_versions_across_chunks() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        let count = versions::VERSION_BATCH_CHUNK + 5;
+        seed_bulk(&repo, count).await;
+
+        let docs = repo.get_all().await.unwrap();
+        assert_eq!(docs.len(), count);
+        for doc in &docs {
+            let i = doc.id.trim_start_matches("bulk-").parse::<usize>().unwrap();
+            assert_eq!(doc.versions.len(), 2);
+            // Newest version first, matching load_versions()
+            assert_eq!(doc.versions[0].content_hash, format!("{}-b", i));
+        }
+    }
+
+    #[test]
+    fn test_browse_cursor_roundtrip() {
+        let cursor = BrowseCursor::new("2024-01-02T03:04:05+00:00", "doc/with:odd chars");
+        assert_eq!(BrowseCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(BrowseCursor::decode("not base64!"), None);
+    }
+
+    #[tokio::test]
+    async fn test_browse_keyset_matches_offset() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 25).await;
+
+        let by_offset: Vec<String> = repo
+            .browse(BrowseParams {
+                limit: 100,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(by_offset.len(), 25);
+
+        // Walk forward with `after` cursors
+        let mut walked = Vec::new();
+        let mut cursor: Option<BrowseCursor> = None;
+        let mut pages = Vec::new();
+        loop {
+            let page = repo
+                .browse(BrowseParams {
+                    limit: 10,
+                    keyset: cursor.as_ref().map(Keyset::After),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+            if page.is_empty() {
+                break;
+            }
+            cursor = page.last().map(BrowseCursor::for_document);
+            walked.extend(page.iter().map(|d| d.id.clone()));
+            pages.push(page);
+        }
+        assert_eq!(walked, by_offset);
+
+        // `before` the last page returns the page preceding it, in order
+        let last_first = BrowseCursor::for_document(&pages[2][0]);
+        let previous: Vec<String> = repo
+            .browse(BrowseParams {
+                limit: 10,
+                keyset: Some(Keyset::Before(&last_first)),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(previous, by_offset[10..20]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_documents_across_batches() {
+        use futures::TryStreamExt;
+
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 23).await;
+
+        let streamed: Vec<Document> = repo
+            .stream_documents(StreamFilter::default(), 5)
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<String> = streamed.iter().map(|d| d.id.clone()).collect();
+        let expected: Vec<String> = (0..23).map(|i| bulk_doc(i).id).collect();
+        assert_eq!(ids, expected);
+        assert!(streamed.iter().all(|d| d.versions.len() == 2));
+        assert_eq!(repo.count_stream(&StreamFilter::default()).await.unwrap(), 23);
+
+        let none: Vec<Document> = repo
+            .stream_documents(StreamFilter::source(Some("missing")), 5)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+        assert_eq!(
+            repo.count_stream(&StreamFilter::source(Some("missing")))
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_documents_snapshot_ignores_concurrent_writes() {
+        use futures::TryStreamExt;
+
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 10).await;
+
+        let mut stream = repo.stream_documents_snapshot(StreamFilter::default(), 3);
+        let first = stream.try_next().await.unwrap().unwrap();
+
+        // Writes after the snapshot started are not seen
+        repo.delete(&bulk_doc(5).id).await.unwrap();
+        repo.save(&bulk_doc(20)).await.unwrap();
+        repo.add_version(&bulk_doc(7).id, &bulk_version("7-c".to_string()))
+            .await
+            .unwrap();
+
+        let mut streamed = vec![first];
+        streamed.extend(stream.try_collect::<Vec<Document>>().await.unwrap());
+        let ids: Vec<String> = streamed.iter().map(|d| d.id.clone()).collect();
+        let expected: Vec<String> = (0..10).map(|i| bulk_doc(i).id).collect();
+        assert_eq!(ids, expected);
+        assert!(streamed.iter().all(|d| d.versions.len() == 2));
+
+        // A new stream sees them
+        let after: Vec<Document> = repo
+            .stream_documents_snapshot(StreamFilter::default(), 3)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(after.len(), 10);
+        assert!(after.iter().all(|d| d.id != bulk_doc(5).id));
+    }
+
+    #[tokio::test]
+    async fn test_lost_files_mark_and_clear() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 1).await;
+        let doc = repo.get("bulk-00000").await.unwrap().unwrap();
+        let version_id = doc.versions[0].id;
+
+        repo.mark_version_lost(version_id, &doc.id, "HTTP 404")
+            .await
+            .unwrap();
+        repo.mark_version_lost(version_id, &doc.id, "HTTP 410")
+            .await
+            .unwrap();
+        let lost = repo.get_lost_files(&doc.id).await.unwrap();
+        assert_eq!(lost.len(), 1);
+        assert_eq!(lost[0].reason, "HTTP 410");
+        assert!(repo
+            .get_lost_version_ids(Some("test-source"))
+            .await
+            .unwrap()
+            .contains(&version_id));
+        assert!(repo
+            .get_lost_version_ids(Some("other"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(repo.clear_version_lost(version_id).await.unwrap());
+        assert!(repo.get_lost_files(&doc.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_storage_bytes_by_source() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 3).await;
+
+        assert_eq!(
+            repo.storage_bytes_by_source("test-source").await.unwrap(),
+            30
+        );
+        assert_eq!(repo.storage_bytes_by_source("other").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_projection_skips_text() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 2).await;
+        let id = bulk_doc(0).id;
+        repo.update_content_fields(&id, Some("page one text"), Some("summary"), &[])
+            .await
+            .unwrap();
+
+        let full = repo.get(&id).await.unwrap().unwrap();
+        assert_eq!(full.extracted_text.as_deref(), Some("page one text"));
+
+        let meta = repo
+            .get_projected(&id, Projection::Metadata)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(meta.extracted_text, None);
+        assert_eq!(meta.title, full.title);
+        assert_eq!(meta.synopsis.as_deref(), Some("summary"));
+        assert_eq!(meta.versions.len(), full.versions.len());
+
+        let listed = repo
+            .browse(BrowseParams {
+                limit: 10,
+                projection: Projection::Metadata,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|d| d.extracted_text.is_none()));
+
+        // Saving a metadata-only document leaves the stored text alone
+        repo.save(&meta).await.unwrap();
+        let reloaded = repo.get(&id).await.unwrap().unwrap();
+        assert_eq!(reloaded.extracted_text.as_deref(), Some("page one text"));
+    }
+
+    #[tokio::test]
+    async fn test_update_status_enforces_transitions() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 1).await;
+        let id = bulk_doc(0).id;
+
+        // Pending documents can't skip straight to indexed
+        assert!(repo
+            .update_status(&id, DocumentStatus::Indexed)
+            .await
+            .is_err());
+        let doc = repo.get(&id).await.unwrap().unwrap();
+        assert_eq!(doc.status, DocumentStatus::Pending);
+
+        for status in [
+            DocumentStatus::Downloaded,
+            DocumentStatus::TextExtracted,
+            DocumentStatus::OcrComplete,
+            DocumentStatus::Indexed,
+        ] {
+            repo.update_status(&id, status).await.unwrap();
+        }
+        let doc = repo.get(&id).await.unwrap().unwrap();
+        assert_eq!(doc.status, DocumentStatus::Indexed);
+
+        // Missing documents are a no-op, not an invalid transition
+        repo.update_status("missing", DocumentStatus::Failed)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_requeue_resets_along_allowed_edges() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        seed_bulk(&repo, 3).await;
+        for i in 0..3 {
+            let id = bulk_doc(i).id;
+            repo.update_status(&id, DocumentStatus::Downloaded)
+                .await
+                .unwrap();
+            repo.update_synopsis_and_tags(&id, Some("summary"), &["tag".to_string()])
+                .await
+                .unwrap();
+        }
+
+        assert!(repo
+            .requeue(DocumentStatus::Downloaded, DocumentStatus::Indexed, None)
+            .await
+            .is_err());
+
+        let moved = repo
+            .requeue(
+                DocumentStatus::Indexed,
+                DocumentStatus::OcrComplete,
+                Some("test-source"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(moved, 3);
+
+        let doc = repo.get(&bulk_doc(0).id).await.unwrap().unwrap();
+        assert_eq!(doc.status, DocumentStatus::OcrComplete);
+        assert_eq!(doc.synopsis, None);
+        assert!(doc.tags.is_empty());
+    }
+
+    /// Compare per-document version loading with the batched IN query.
+    ///
+    /// Run with: cargo test -p foia bench_version_loading -- --ignored --nocapture
+    #[tokio::test]
+    #[ignore]
+    async fn bench_version_loading() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        let count = 2000;
+        seed_bulk(&repo, count).await;
+        let ids: Vec<String> = (0..count).map(|i| bulk_doc(i).id).collect();
+
+        let start = std::time::Instant::now();
+        for id in &ids {
+            repo.load_versions(id).await.unwrap();
+        }
+        let per_document = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let batched = repo.load_versions_batch(&ids).await.unwrap();
+        let batch = start.elapsed();
+
+        assert_eq!(batched.len(), count);
+        println!(
+            "{} documents: per-document {:?}, batched {:?}",
+            count, per_document, batch
+        );
+    }
 }
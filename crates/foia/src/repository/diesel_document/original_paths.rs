@@ -0,0 +1,108 @@
+//! Where imported files sat in the production they came from.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::pool::DieselError;
+use crate::schema::original_paths;
+use crate::{with_conn, with_conn_split};
+
+impl DieselDocumentRepository {
+    /// Record the relative path a document's file had in its production,
+    /// replacing any path recorded for the same content.
+    pub async fn record_original_path(
+        &self,
+        doc_id: &str,
+        content_hash: &str,
+        path: &str,
+    ) -> Result<(), DieselError> {
+        let values = (
+            original_paths::document_id.eq(doc_id),
+            original_paths::content_hash.eq(content_hash),
+            original_paths::path.eq(path),
+        );
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(original_paths::table)
+                    .values(values)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(original_paths::table)
+                    .values(values)
+                    .on_conflict((original_paths::document_id, original_paths::content_hash))
+                    .do_update()
+                    .set(original_paths::path.eq(path))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// Original paths of a document's files, by content hash.
+    pub async fn get_original_paths(
+        &self,
+        doc_id: &str,
+    ) -> Result<HashMap<String, String>, DieselError> {
+        let rows: Vec<(String, String)> = with_conn!(self.pool, conn, {
+            original_paths::table
+                .filter(original_paths::document_id.eq(doc_id))
+                .select((original_paths::content_hash, original_paths::path))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn test_original_paths_replace() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        repo.save(&Document {
+            id: "a".to_string(),
+            source_id: "production".to_string(),
+            title: "ABC000001".to_string(),
+            source_url: "concordance://ABC000001..ABC000003".to_string(),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "import".to_string(),
+            versions: vec![],
+        })
+        .await
+        .unwrap();
+
+        repo.record_original_path("a", "h1", "IMAGES/001/ABC000001.tif")
+            .await
+            .unwrap();
+        repo.record_original_path("a", "h2", "NATIVES/ABC000001.xlsx")
+            .await
+            .unwrap();
+        repo.record_original_path("a", "h1", "IMAGES/002/ABC000001.tif")
+            .await
+            .unwrap();
+
+        let paths = repo.get_original_paths("a").await.unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths["h1"], "IMAGES/002/ABC000001.tif");
+        assert!(repo.get_original_paths("b").await.unwrap().is_empty());
+    }
+}
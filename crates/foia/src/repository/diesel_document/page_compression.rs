@@ -0,0 +1,98 @@
+//! Bulk compression of stored page text.
+//!
+//! New pages are compressed as they are saved; this brings rows written
+//! before compression (or before their `final_text` was set) up to date.
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::pool::DieselError;
+use crate::repository::text_codec;
+use crate::schema::document_pages;
+use crate::with_conn;
+
+/// Outcome of compressing one batch of pages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageCompressionBatch {
+    /// ID of the last page scanned; pass it back to continue. `None` when
+    /// no pages were left.
+    pub last_id: Option<i32>,
+    pub pages_scanned: u64,
+    pub pages_compressed: u64,
+    /// Stored size of the compressed columns before and after.
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl DieselDocumentRepository {
+    /// Compress `pdf_text` and `ocr_text` on the next `limit` pages after
+    /// `after_id` that have `final_text`.
+    ///
+    /// Pages without `final_text` are left alone: search falls back to their
+    /// intermediate text. With `dry_run`, savings are computed but nothing
+    /// is written.
+    pub async fn compress_page_text_batch(
+        &self,
+        after_id: i32,
+        limit: usize,
+        dry_run: bool,
+    ) -> Result<PageCompressionBatch, DieselError> {
+        let rows: Vec<(i32, Option<String>, Option<String>)> = with_conn!(self.pool, conn, {
+            document_pages::table
+                .filter(document_pages::id.gt(after_id))
+                .filter(document_pages::final_text.is_not_null())
+                .order(document_pages::id.asc())
+                .limit(limit as i64)
+                .select((
+                    document_pages::id,
+                    document_pages::pdf_text,
+                    document_pages::ocr_text,
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+
+        let mut batch = PageCompressionBatch {
+            last_id: rows.last().map(|(id, _, _)| *id),
+            pages_scanned: rows.len() as u64,
+            ..Default::default()
+        };
+
+        for (id, pdf_text, ocr_text) in rows {
+            let mut changed = false;
+            let mut compress = |text: Option<String>| match text {
+                Some(t) if !text_codec::is_compressed(&t) => {
+                    let stored = text_codec::compress(&t).into_owned();
+                    if stored.len() < t.len() {
+                        batch.bytes_before += t.len() as u64;
+                        batch.bytes_after += stored.len() as u64;
+                        changed = true;
+                    }
+                    Some(stored)
+                }
+                other => other,
+            };
+            let pdf_text = compress(pdf_text);
+            let ocr_text = compress(ocr_text);
+            if !changed {
+                continue;
+            }
+            batch.pages_compressed += 1;
+
+            if !dry_run {
+                with_conn!(self.pool, conn, {
+                    diesel::update(document_pages::table.find(id))
+                        .set((
+                            document_pages::pdf_text.eq(pdf_text),
+                            document_pages::ocr_text.eq(ocr_text),
+                        ))
+                        .execute(&mut conn)
+                        .await
+                })?;
+            }
+        }
+
+        Ok(batch)
+    }
+}
@@ -10,10 +10,19 @@ use super::{CountRow, DieselDocumentRepository, OcrResult, ReturningId};
 use crate::models::{DocumentPage, PageOcrStatus};
 use crate::repository::models::{DocumentPageRecord, PageOcrResultRecord};
 use crate::repository::parse_datetime;
-use crate::repository::pool::DieselError;
+use crate::repository::pool::{retry_on_busy, DieselError};
+use crate::repository::text_codec;
 use crate::schema::{document_pages, page_ocr_results};
 use crate::{with_conn, with_conn_split};
 
+/// CTE `q` with one column `tsq`: the search terms bound as a JSON array in
+/// `$1`, each parsed like `plainto_tsquery` and OR'd together.
+const PG_TERMS_QUERY: &str = r#"q AS (
+    SELECT string_agg('(' || plainto_tsquery('english', term)::text || ')', ' | ')::tsquery AS tsq
+    FROM jsonb_array_elements_text($1::jsonb) AS t(term)
+    WHERE plainto_tsquery('english', term)::text <> ''
+)"#;
+
 #[derive(diesel::QueryableByName, Debug)]
 pub struct PageSearchRow {
     #[diesel(sql_type = diesel::sql_types::Text)]
@@ -36,6 +45,44 @@ pub struct PageSearchRow {
     pub dedup_index: Option<i32>,
     #[diesel(sql_type = diesel::sql_types::Text)]
     pub source_url: String,
+    /// Set when the match is in an archive member or attachment of the document.
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub virtual_file_id: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub archive_path: Option<String>,
+}
+
+/// Backend name recorded for text taken from a PDF's embedded text layer.
+pub const TEXT_LAYER_BACKEND: &str = "pdftotext";
+
+/// Row returned by the Postgres multi-row page upsert.
+#[cfg(feature = "postgres")]
+#[derive(diesel::QueryableByName)]
+struct SavedPageRow {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    id: i32,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    document_id: String,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    version_id: i32,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    page_number: i32,
+}
+
+/// Non-empty text-layer content for a page, if any.
+fn text_layer(page: &DocumentPage) -> Option<&str> {
+    page.pdf_text.as_deref().filter(|t| !t.trim().is_empty())
+}
+
+/// `pdf_text` and `ocr_text` as stored. Once a page has `final_text`,
+/// search and display read that first, so the intermediate copies are
+/// compressed; until then they stay plain for search to match.
+pub(super) fn stored_intermediate_text(page: &DocumentPage) -> (Option<String>, Option<String>) {
+    let store = |text: &Option<String>| match (&page.final_text, text) {
+        (Some(_), Some(t)) => Some(text_codec::compress(t).into_owned()),
+        _ => text.clone(),
+    };
+    (store(&page.pdf_text), store(&page.ocr_text))
 }
 
 impl From<DocumentPageRecord> for DocumentPage {
@@ -45,9 +92,9 @@ impl From<DocumentPageRecord> for DocumentPage {
             document_id: r.document_id,
             version_id: r.version_id as i64,
             page_number: r.page_number as u32,
-            pdf_text: r.pdf_text,
-            ocr_text: r.ocr_text,
-            final_text: r.final_text,
+            pdf_text: r.pdf_text.map(text_codec::decompress),
+            ocr_text: r.ocr_text.map(text_codec::decompress),
+            final_text: r.final_text.map(text_codec::decompress),
             ocr_status: PageOcrStatus::from_str(&r.ocr_status).unwrap_or(PageOcrStatus::Pending),
             created_at: parse_datetime(&r.created_at),
             updated_at: parse_datetime(&r.updated_at),
@@ -80,6 +127,7 @@ impl DieselDocumentRepository {
         let version_id = page.version_id as i32;
         let page_number = page.page_number as i32;
         let ocr_status = page.ocr_status.as_str().to_string();
+        let (pdf_text, ocr_text) = stored_intermediate_text(page);
 
         let stmt = Query::insert()
             .into_table(DocumentPages::Table)
@@ -98,8 +146,8 @@ impl DieselDocumentRepository {
                 page.document_id.clone().into(),
                 version_id.into(),
                 page_number.into(),
-                page.pdf_text.clone().into(),
-                page.ocr_text.clone().into(),
+                pdf_text.clone().into(),
+                ocr_text.clone().into(),
                 page.final_text.clone().into(),
                 ocr_status.clone().into(),
                 now.clone().into(),
@@ -130,8 +178,8 @@ impl DieselDocumentRepository {
                 .bind::<diesel::sql_types::Text, _>(&page.document_id)
                 .bind::<diesel::sql_types::Integer, _>(version_id)
                 .bind::<diesel::sql_types::Integer, _>(page_number)
-                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&page.pdf_text)
-                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&page.ocr_text)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&pdf_text)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&ocr_text)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&page.final_text)
                 .bind::<diesel::sql_types::Text, _>(&ocr_status)
                 .bind::<diesel::sql_types::Text, _>(&now)
@@ -142,91 +190,170 @@ impl DieselDocumentRepository {
         })
     }
 
-    /// Save multiple document pages in a single bulk insert.
-    /// Much faster than calling save_page() in a loop.
-    pub async fn save_pages_batch(&self, pages: &[DocumentPage]) -> Result<(), DieselError> {
+    /// Save pages and their text-layer results in a single transaction.
+    ///
+    /// Pages are upserted like `save_page()`. Every page with non-empty
+    /// `pdf_text` also gets a `pdftotext` row in `page_ocr_results`, so the
+    /// text layer is compared alongside OCR backends. On a 1000-page PDF this
+    /// replaces thousands of auto-committed statements with one commit.
+    pub async fn save_pages(&self, pages: &[DocumentPage]) -> Result<(), DieselError> {
+        use diesel_async::AsyncConnection;
+
         if pages.is_empty() {
             return Ok(());
         }
 
         let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
 
-        with_conn_split!(self.pool,
-            sqlite: conn => {
-                for page in pages {
-                    let version_id = page.version_id as i32;
-                    let page_number = page.page_number as i32;
-                    let ocr_status = page.ocr_status.as_str().to_string();
-
-                    diesel::sql_query(
-                        "INSERT INTO document_pages (document_id, version_id, page_number, pdf_text, ocr_text, final_text, ocr_status, created_at, updated_at) \
-                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) \
-                         ON CONFLICT (document_id, version_id, page_number) \
-                         DO UPDATE SET pdf_text = excluded.pdf_text, ocr_text = excluded.ocr_text, \
-                         final_text = excluded.final_text, ocr_status = excluded.ocr_status, updated_at = excluded.updated_at"
-                    )
-                    .bind::<diesel::sql_types::Text, _>(&page.document_id)
-                    .bind::<diesel::sql_types::Integer, _>(version_id)
-                    .bind::<diesel::sql_types::Integer, _>(page_number)
-                    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&page.pdf_text)
-                    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&page.ocr_text)
-                    .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&page.final_text)
-                    .bind::<diesel::sql_types::Text, _>(&ocr_status)
-                    .bind::<diesel::sql_types::Text, _>(&now)
-                    .bind::<diesel::sql_types::Text, _>(&now)
-                    .execute(&mut conn)
-                    .await?;
-                }
-                Ok::<_, DieselError>(())
-            },
-            postgres: conn => {
-                // Build multi-row INSERT with numbered parameters
-                for chunk in pages.chunks(50) {
-                    let params_per_row = 9;
-                    let mut placeholders = Vec::with_capacity(chunk.len());
-                    for i in 0..chunk.len() {
-                        let base = i * params_per_row + 1;
-                        placeholders.push(format!(
-                            "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
-                            base, base + 1, base + 2, base + 3, base + 4,
-                            base + 5, base + 6, base + 7, base + 8
-                        ));
-                    }
-
-                    let sql = format!(
-                        "INSERT INTO document_pages (document_id, version_id, page_number, pdf_text, ocr_text, final_text, ocr_status, created_at, updated_at) \
-                         VALUES {} \
-                         ON CONFLICT (document_id, version_id, page_number) \
-                         DO UPDATE SET pdf_text = EXCLUDED.pdf_text, ocr_text = EXCLUDED.ocr_text, \
-                         final_text = EXCLUDED.final_text, ocr_status = EXCLUDED.ocr_status, updated_at = EXCLUDED.updated_at",
-                        placeholders.join(", ")
-                    );
-
-                    let mut query = diesel::sql_query(sql).into_boxed::<diesel::pg::Pg>();
-                    for page in chunk {
-                        let version_id = page.version_id as i32;
-                        let page_number = page.page_number as i32;
-                        let ocr_status = page.ocr_status.as_str().to_string();
-
-                        query = query
-                            .bind::<diesel::sql_types::Text, _>(page.document_id.clone())
-                            .bind::<diesel::sql_types::Integer, _>(version_id)
-                            .bind::<diesel::sql_types::Integer, _>(page_number)
-                            .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(page.pdf_text.clone())
-                            .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(page.ocr_text.clone())
-                            .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(page.final_text.clone())
-                            .bind::<diesel::sql_types::Text, _>(ocr_status)
-                            .bind::<diesel::sql_types::Text, _>(now.clone())
-                            .bind::<diesel::sql_types::Text, _>(now.clone());
-                    }
-
-                    query.execute(&mut conn).await?;
-                }
-                Ok::<_, DieselError>(())
-            }
-        )?;
+        retry_on_busy(|| async {
+            with_conn_split!(self.pool,
+                sqlite: conn => {
+                    conn.transaction(|conn| {
+                        Box::pin(async move {
+                            for page in pages {
+                                let (pdf_text, ocr_text) = stored_intermediate_text(page);
+                                let saved: ReturningId = diesel::sql_query(
+                                    "INSERT INTO document_pages (document_id, version_id, page_number, pdf_text, ocr_text, final_text, ocr_status, created_at, updated_at) \
+                                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) \
+                                     ON CONFLICT (document_id, version_id, page_number) \
+                                     DO UPDATE SET pdf_text = excluded.pdf_text, ocr_text = excluded.ocr_text, \
+                                     final_text = excluded.final_text, ocr_status = excluded.ocr_status, updated_at = excluded.updated_at \
+                                     RETURNING id"
+                                )
+                                .bind::<diesel::sql_types::Text, _>(&page.document_id)
+                                .bind::<diesel::sql_types::Integer, _>(page.version_id as i32)
+                                .bind::<diesel::sql_types::Integer, _>(page.page_number as i32)
+                                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(pdf_text)
+                                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(ocr_text)
+                                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(&page.final_text)
+                                .bind::<diesel::sql_types::Text, _>(page.ocr_status.as_str())
+                                .bind::<diesel::sql_types::Text, _>(now)
+                                .bind::<diesel::sql_types::Text, _>(now)
+                                .get_result(conn)
+                                .await?;
+
+                                if let Some(text) = text_layer(page) {
+                                    diesel::sql_query(
+                                        "INSERT INTO page_ocr_results (page_id, backend, text, char_count, word_count, created_at) \
+                                         VALUES (?, ?, ?, ?, ?, ?) \
+                                         ON CONFLICT (page_id, backend, COALESCE(model, '')) \
+                                         DO UPDATE SET text = excluded.text, char_count = excluded.char_count, \
+                                         word_count = excluded.word_count, created_at = excluded.created_at"
+                                    )
+                                    .bind::<diesel::sql_types::Integer, _>(saved.id)
+                                    .bind::<diesel::sql_types::Text, _>(TEXT_LAYER_BACKEND)
+                                    .bind::<diesel::sql_types::Text, _>(text)
+                                    .bind::<diesel::sql_types::Integer, _>(text.chars().count() as i32)
+                                    .bind::<diesel::sql_types::Integer, _>(text.split_whitespace().count() as i32)
+                                    .bind::<diesel::sql_types::Text, _>(now)
+                                    .execute(conn)
+                                    .await?;
+                                }
+                            }
+                            Ok::<_, DieselError>(())
+                        })
+                    })
+                    .await
+                },
+                postgres: conn => {
+                    conn.transaction(|conn| {
+                        Box::pin(async move {
+                            // Build multi-row INSERTs with numbered parameters
+                            for chunk in pages.chunks(50) {
+                                let params_per_row = 9;
+                                let placeholders: Vec<String> = (0..chunk.len())
+                                    .map(|i| {
+                                        let base = i * params_per_row + 1;
+                                        format!(
+                                            "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                                            base, base + 1, base + 2, base + 3, base + 4,
+                                            base + 5, base + 6, base + 7, base + 8
+                                        )
+                                    })
+                                    .collect();
+
+                                let sql = format!(
+                                    "INSERT INTO document_pages (document_id, version_id, page_number, pdf_text, ocr_text, final_text, ocr_status, created_at, updated_at) \
+                                     VALUES {} \
+                                     ON CONFLICT (document_id, version_id, page_number) \
+                                     DO UPDATE SET pdf_text = EXCLUDED.pdf_text, ocr_text = EXCLUDED.ocr_text, \
+                                     final_text = EXCLUDED.final_text, ocr_status = EXCLUDED.ocr_status, updated_at = EXCLUDED.updated_at \
+                                     RETURNING id, document_id, version_id, page_number",
+                                    placeholders.join(", ")
+                                );
+
+                                let mut query = diesel::sql_query(sql).into_boxed::<diesel::pg::Pg>();
+                                for page in chunk {
+                                    let (pdf_text, ocr_text) = stored_intermediate_text(page);
+                                    query = query
+                                        .bind::<diesel::sql_types::Text, _>(page.document_id.clone())
+                                        .bind::<diesel::sql_types::Integer, _>(page.version_id as i32)
+                                        .bind::<diesel::sql_types::Integer, _>(page.page_number as i32)
+                                        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(pdf_text)
+                                        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(ocr_text)
+                                        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(page.final_text.clone())
+                                        .bind::<diesel::sql_types::Text, _>(page.ocr_status.as_str().to_string())
+                                        .bind::<diesel::sql_types::Text, _>(now.to_string())
+                                        .bind::<diesel::sql_types::Text, _>(now.to_string());
+                                }
+                                let saved: Vec<SavedPageRow> = query.load(conn).await?;
+                                let ids: HashMap<(&str, i32, i32), i32> = saved
+                                    .iter()
+                                    .map(|r| ((r.document_id.as_str(), r.version_id, r.page_number), r.id))
+                                    .collect();
+
+                                let results: Vec<(i32, &str)> = chunk
+                                    .iter()
+                                    .filter_map(|page| {
+                                        let text = text_layer(page)?;
+                                        let key = (page.document_id.as_str(), page.version_id as i32, page.page_number as i32);
+                                        ids.get(&key).map(|&id| (id, text))
+                                    })
+                                    .collect();
+                                if results.is_empty() {
+                                    continue;
+                                }
+
+                                let params_per_row = 6;
+                                let placeholders: Vec<String> = (0..results.len())
+                                    .map(|i| {
+                                        let base = i * params_per_row + 1;
+                                        format!(
+                                            "(${}, ${}, ${}, ${}, ${}, ${})",
+                                            base, base + 1, base + 2, base + 3, base + 4, base + 5
+                                        )
+                                    })
+                                    .collect();
+                                let sql = format!(
+                                    "INSERT INTO page_ocr_results (page_id, backend, text, char_count, word_count, created_at) \
+                                     VALUES {} \
+                                     ON CONFLICT (page_id, backend, COALESCE(model, '')) \
+                                     DO UPDATE SET text = EXCLUDED.text, char_count = EXCLUDED.char_count, \
+                                     word_count = EXCLUDED.word_count, created_at = EXCLUDED.created_at",
+                                    placeholders.join(", ")
+                                );
 
-        Ok(())
+                                let mut query = diesel::sql_query(sql).into_boxed::<diesel::pg::Pg>();
+                                for (page_id, text) in results {
+                                    query = query
+                                        .bind::<diesel::sql_types::Integer, _>(page_id)
+                                        .bind::<diesel::sql_types::Text, _>(TEXT_LAYER_BACKEND)
+                                        .bind::<diesel::sql_types::Text, _>(text.to_string())
+                                        .bind::<diesel::sql_types::Integer, _>(text.chars().count() as i32)
+                                        .bind::<diesel::sql_types::Integer, _>(text.split_whitespace().count() as i32)
+                                        .bind::<diesel::sql_types::Text, _>(now.to_string());
+                                }
+                                query.execute(conn).await?;
+                            }
+                            Ok::<_, DieselError>(())
+                        })
+                    })
+                    .await
+                }
+            )
+        })
+        .await
     }
 
     /// Get document pages.
@@ -247,6 +374,26 @@ impl DieselDocumentRepository {
         Ok(records.into_iter().map(DocumentPage::from).collect())
     }
 
+    /// Get a single page of a document version.
+    pub async fn get_page(
+        &self,
+        document_id: &str,
+        version: i32,
+        page_number: u32,
+    ) -> Result<Option<DocumentPage>, DieselError> {
+        let record: Option<DocumentPageRecord> = with_conn!(self.pool, conn, {
+            document_pages::table
+                .filter(document_pages::document_id.eq(document_id))
+                .filter(document_pages::version_id.eq(version))
+                .filter(document_pages::page_number.eq(page_number as i32))
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+
+        Ok(record.map(DocumentPage::from))
+    }
+
     /// Get pages needing OCR.
     #[allow(dead_code)]
     pub async fn get_pages_needing_ocr(
@@ -568,23 +715,35 @@ impl DieselDocumentRepository {
         Ok(records.into_iter().map(DocumentPage::from).collect())
     }
 
-    /// Get combined page text for a document.
+    /// Get combined page text for a document: each page's final text,
+    /// falling back to its OCR text and then its text layer.
     pub async fn get_combined_page_text(
         &self,
         document_id: &str,
         version: i32,
     ) -> Result<Option<String>, DieselError> {
-        let texts: Vec<Option<String>> = with_conn!(self.pool, conn, {
-            document_pages::table
-                .filter(document_pages::document_id.eq(document_id))
-                .filter(document_pages::version_id.eq(version))
-                .order(document_pages::page_number.asc())
-                .select(document_pages::ocr_text)
-                .load(&mut conn)
-                .await
-        })?;
+        #[allow(clippy::type_complexity)]
+        let texts: Vec<(Option<String>, Option<String>, Option<String>)> =
+            with_conn!(self.pool, conn, {
+                document_pages::table
+                    .filter(document_pages::document_id.eq(document_id))
+                    .filter(document_pages::version_id.eq(version))
+                    .order(document_pages::page_number.asc())
+                    .select((
+                        document_pages::final_text,
+                        document_pages::ocr_text,
+                        document_pages::pdf_text,
+                    ))
+                    .load(&mut conn)
+                    .await
+            })?;
 
-        let combined: String = texts.into_iter().flatten().collect::<Vec<_>>().join("\n\n");
+        let combined: String = texts
+            .into_iter()
+            .filter_map(|(final_text, ocr_text, pdf_text)| final_text.or(ocr_text).or(pdf_text))
+            .map(text_codec::decompress)
+            .collect::<Vec<_>>()
+            .join("\n\n");
 
         if combined.is_empty() {
             Ok(None)
@@ -593,19 +752,23 @@ impl DieselDocumentRepository {
         }
     }
 
-    /// Full-text search on page content.
+    /// Full-text search on page content and the extracted text of the
+    /// document's archive members and attachments (`page_number` 0).
+    ///
+    /// Matches any of `terms`: a query and its alias expansions (see
+    /// [`expand_search_query`](Self::expand_search_query)).
     ///
     /// Postgres: uses `tsvector`/`tsquery` for ranked full-text search with headline snippets.
     /// SQLite: falls back to LIKE matching (no headlines).
     pub async fn search_page_content(
         &self,
-        query: &str,
+        terms: &[String],
         source_id: Option<&str>,
         document_id: Option<&str>,
         limit: usize,
         offset: usize,
     ) -> Result<Vec<PageSearchRow>, DieselError> {
-        let like_pattern = format!("%{query}%");
+        let terms_json = serde_json::to_string(terms).unwrap_or_else(|_| "[]".to_string());
 
         with_conn_split!(self.pool,
             sqlite: conn => {
@@ -613,17 +776,38 @@ impl DieselDocumentRepository {
                     r#"SELECT dp.document_id, d.title, d.source_id, dp.page_number,
                               '' AS headline,
                               dv.content_hash, dv.mime_type AS version_mime_type,
-                              dv.original_filename, dv.dedup_index, d.source_url
+                              dv.original_filename, dv.dedup_index, d.source_url,
+                              NULL AS virtual_file_id, NULL AS archive_path
                        FROM document_pages dp
                        JOIN documents d ON d.id = dp.document_id
                        JOIN document_versions dv ON dv.id = dp.version_id
-                       WHERE COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, '') LIKE ?
+                       WHERE EXISTS (SELECT 1 FROM json_each(?) t
+                                     WHERE COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, '')
+                                           LIKE '%' || t.value || '%')
                          AND (? IS NULL OR d.source_id = ?)
                          AND (? IS NULL OR dp.document_id = ?)
-                       ORDER BY dp.document_id, dp.page_number
+                       UNION ALL
+                       SELECT vf.document_id, d.title, d.source_id, 0 AS page_number,
+                              '' AS headline,
+                              dv.content_hash, dv.mime_type AS version_mime_type,
+                              dv.original_filename, dv.dedup_index, d.source_url,
+                              vf.id AS virtual_file_id, vf.archive_path
+                       FROM virtual_files vf
+                       JOIN documents d ON d.id = vf.document_id
+                       JOIN document_versions dv ON dv.id = vf.version_id
+                       WHERE EXISTS (SELECT 1 FROM json_each(?) t
+                                     WHERE vf.extracted_text LIKE '%' || t.value || '%')
+                         AND (? IS NULL OR d.source_id = ?)
+                         AND (? IS NULL OR vf.document_id = ?)
+                       ORDER BY document_id, page_number, archive_path
                        LIMIT {limit} OFFSET {offset}"#
                 ))
-                .bind::<diesel::sql_types::Text, _>(&like_pattern)
+                .bind::<diesel::sql_types::Text, _>(&terms_json)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
+                .bind::<diesel::sql_types::Text, _>(&terms_json)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
@@ -633,27 +817,45 @@ impl DieselDocumentRepository {
             },
             postgres: conn => {
                 diesel::sql_query(format!(
-                    r#"SELECT dp.document_id, d.title, d.source_id, dp.page_number,
+                    r#"WITH {PG_TERMS_QUERY}
+                       SELECT dp.document_id, d.title, d.source_id, dp.page_number,
                               ts_headline('english',
                                           COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, ''),
-                                          plainto_tsquery('english', $1),
+                                          q.tsq,
                                           'MaxFragments=3, MaxWords=30, MinWords=10') AS headline,
                               dv.content_hash, dv.mime_type AS version_mime_type,
-                              dv.original_filename, dv.dedup_index, d.source_url
+                              dv.original_filename, dv.dedup_index, d.source_url,
+                              NULL::text AS virtual_file_id, NULL::text AS archive_path,
+                              ts_rank(
+                                  to_tsvector('english', COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, '')),
+                                  q.tsq) AS rank
                        FROM document_pages dp
                        JOIN documents d ON d.id = dp.document_id
                        JOIN document_versions dv ON dv.id = dp.version_id
+                       CROSS JOIN q
                        WHERE to_tsvector('english', COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, ''))
-                             @@ plainto_tsquery('english', $1)
+                             @@ q.tsq
                          AND ($2::text IS NULL OR d.source_id = $2)
                          AND ($3::text IS NULL OR dp.document_id = $3)
-                       ORDER BY ts_rank(
-                                  to_tsvector('english', COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, '')),
-                                  plainto_tsquery('english', $1)) DESC,
-                                dp.document_id, dp.page_number
+                       UNION ALL
+                       SELECT vf.document_id, d.title, d.source_id, 0 AS page_number,
+                              ts_headline('english', vf.extracted_text, q.tsq,
+                                          'MaxFragments=3, MaxWords=30, MinWords=10') AS headline,
+                              dv.content_hash, dv.mime_type AS version_mime_type,
+                              dv.original_filename, dv.dedup_index, d.source_url,
+                              vf.id AS virtual_file_id, vf.archive_path,
+                              ts_rank(to_tsvector('english', vf.extracted_text), q.tsq) AS rank
+                       FROM virtual_files vf
+                       JOIN documents d ON d.id = vf.document_id
+                       JOIN document_versions dv ON dv.id = vf.version_id
+                       CROSS JOIN q
+                       WHERE to_tsvector('english', COALESCE(vf.extracted_text, '')) @@ q.tsq
+                         AND ($2::text IS NULL OR d.source_id = $2)
+                         AND ($3::text IS NULL OR vf.document_id = $3)
+                       ORDER BY rank DESC, document_id, page_number, archive_path
                        LIMIT {limit} OFFSET {offset}"#
                 ))
-                .bind::<diesel::sql_types::Text, _>(query)
+                .bind::<diesel::sql_types::Text, _>(&terms_json)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
                 .load::<PageSearchRow>(&mut conn)
@@ -662,26 +864,41 @@ impl DieselDocumentRepository {
         )
     }
 
-    /// Count full-text search matches on page content.
+    /// Count full-text search matches on page content for any of `terms`.
     pub async fn count_page_content_matches(
         &self,
-        query: &str,
+        terms: &[String],
         source_id: Option<&str>,
         document_id: Option<&str>,
     ) -> Result<u64, DieselError> {
-        let like_pattern = format!("%{query}%");
+        let terms_json = serde_json::to_string(terms).unwrap_or_else(|_| "[]".to_string());
 
         with_conn_split!(self.pool,
             sqlite: conn => {
                 let result: Vec<CountRow> = diesel::sql_query(
-                    r#"SELECT COUNT(*) AS count
-                       FROM document_pages dp
-                       JOIN documents d ON d.id = dp.document_id
-                       WHERE COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, '') LIKE ?
-                         AND (? IS NULL OR d.source_id = ?)
-                         AND (? IS NULL OR dp.document_id = ?)"#,
+                    r#"SELECT
+                         (SELECT COUNT(*)
+                          FROM document_pages dp
+                          JOIN documents d ON d.id = dp.document_id
+                          WHERE EXISTS (SELECT 1 FROM json_each(?) t
+                                        WHERE COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, '')
+                                              LIKE '%' || t.value || '%')
+                            AND (? IS NULL OR d.source_id = ?)
+                            AND (? IS NULL OR dp.document_id = ?))
+                       + (SELECT COUNT(*)
+                          FROM virtual_files vf
+                          JOIN documents d ON d.id = vf.document_id
+                          WHERE EXISTS (SELECT 1 FROM json_each(?) t
+                                        WHERE vf.extracted_text LIKE '%' || t.value || '%')
+                            AND (? IS NULL OR d.source_id = ?)
+                            AND (? IS NULL OR vf.document_id = ?)) AS count"#,
                 )
-                .bind::<diesel::sql_types::Text, _>(&like_pattern)
+                .bind::<diesel::sql_types::Text, _>(&terms_json)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
+                .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
+                .bind::<diesel::sql_types::Text, _>(&terms_json)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
@@ -692,16 +909,26 @@ impl DieselDocumentRepository {
                 Ok(result.get(0).map(|r| r.count as u64).unwrap_or(0))
             },
             postgres: conn => {
-                let result: Vec<CountRow> = diesel::sql_query(
-                    r#"SELECT COUNT(*) AS count
-                       FROM document_pages dp
-                       JOIN documents d ON d.id = dp.document_id
-                       WHERE to_tsvector('english', COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, ''))
-                             @@ plainto_tsquery('english', $1)
-                         AND ($2::text IS NULL OR d.source_id = $2)
-                         AND ($3::text IS NULL OR dp.document_id = $3)"#,
-                )
-                .bind::<diesel::sql_types::Text, _>(query)
+                let result: Vec<CountRow> = diesel::sql_query(format!(
+                    r#"WITH {PG_TERMS_QUERY}
+                       SELECT
+                         (SELECT COUNT(*)
+                          FROM document_pages dp
+                          JOIN documents d ON d.id = dp.document_id
+                          CROSS JOIN q
+                          WHERE to_tsvector('english', COALESCE(dp.final_text, dp.ocr_text, dp.pdf_text, ''))
+                                @@ q.tsq
+                            AND ($2::text IS NULL OR d.source_id = $2)
+                            AND ($3::text IS NULL OR dp.document_id = $3))
+                       + (SELECT COUNT(*)
+                          FROM virtual_files vf
+                          JOIN documents d ON d.id = vf.document_id
+                          CROSS JOIN q
+                          WHERE to_tsvector('english', COALESCE(vf.extracted_text, '')) @@ q.tsq
+                            AND ($2::text IS NULL OR d.source_id = $2)
+                            AND ($3::text IS NULL OR vf.document_id = $3)) AS count"#
+                ))
+                .bind::<diesel::sql_types::Text, _>(&terms_json)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(source_id)
                 .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(document_id)
                 .load(&mut conn)
@@ -729,3 +956,148 @@ impl DieselDocumentRepository {
         Ok(vec![])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    async fn create_ocr_results_table(repo: &DieselDocumentRepository) -> Result<(), DieselError> {
+        use diesel_async::SimpleAsyncConnection;
+        with_conn!(repo.pool, conn, {
+            conn.batch_execute(
+                r#"CREATE TABLE IF NOT EXISTS page_ocr_results (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    page_id INTEGER NOT NULL,
+                    backend TEXT NOT NULL,
+                    text TEXT,
+                    confidence REAL,
+                    quality_score REAL,
+                    char_count INTEGER,
+                    word_count INTEGER,
+                    processing_time_ms INTEGER,
+                    error_message TEXT,
+                    created_at TEXT NOT NULL,
+                    model TEXT,
+                    image_hash TEXT
+                );
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_page_ocr_results_unique
+                    ON page_ocr_results(page_id, backend, COALESCE(model, ''));"#,
+            )
+            .await
+            .unwrap();
+            Ok::<_, DieselError>(())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_save_pages_records_text_layer() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        create_ocr_results_table(&repo).await.unwrap();
+
+        let pages: Vec<DocumentPage> = (1..=3)
+            .map(|n| {
+                let mut page = DocumentPage::new("doc-pages".to_string(), 7, n);
+                page.pdf_text = (n != 2).then(|| format!("page {} text", n));
+                page.ocr_status = PageOcrStatus::TextExtracted;
+                page
+            })
+            .collect();
+        repo.save_pages(&pages).await.unwrap();
+        // Saving again updates in place
+        repo.save_pages(&pages).await.unwrap();
+
+        let saved = repo.get_pages("doc-pages", 7).await.unwrap();
+        assert_eq!(saved.len(), 3);
+        assert_eq!(saved[0].pdf_text.as_deref(), Some("page 1 text"));
+
+        let first = repo.get_page_ocr_results(saved[0].id).await.unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].backend, TEXT_LAYER_BACKEND);
+        assert_eq!(first[0].text.as_deref(), Some("page 1 text"));
+        assert_eq!(first[0].word_count, Some(3));
+
+        // Pages without a text layer get no result row
+        assert!(repo
+            .get_page_ocr_results(saved[1].id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_page_text_compressed_once_final() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        create_ocr_results_table(&repo).await.unwrap();
+
+        let long = "Responsive records follow. ".repeat(200);
+        let mut page = DocumentPage::new("doc-zstd".to_string(), 1, 1);
+        page.pdf_text = Some(long.clone());
+        page.ocr_status = PageOcrStatus::TextExtracted;
+        repo.save_page(&page).await.unwrap();
+
+        let stored_pdf_text = |repo: &DieselDocumentRepository| {
+            let pool = repo.pool.clone();
+            async move {
+                with_conn!(pool, conn, {
+                    document_pages::table
+                        .select(document_pages::pdf_text)
+                        .first::<Option<String>>(&mut conn)
+                        .await
+                })
+                .unwrap()
+                .unwrap()
+            }
+        };
+        // Without final_text the text stays plain so search can match it
+        assert_eq!(stored_pdf_text(&repo).await, long);
+
+        page.final_text = Some(long.clone());
+        repo.save_page(&page).await.unwrap();
+        assert!(text_codec::is_compressed(&stored_pdf_text(&repo).await));
+
+        let saved = repo.get_pages("doc-zstd", 1).await.unwrap();
+        assert_eq!(saved[0].pdf_text.as_deref(), Some(long.as_str()));
+        assert_eq!(saved[0].final_text.as_deref(), Some(long.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_compress_page_text_batch() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        create_ocr_results_table(&repo).await.unwrap();
+
+        let long = "Withheld in full under (b)(7)(C). ".repeat(200);
+        let pages: Vec<DocumentPage> = (1..=2)
+            .map(|n| {
+                let mut page = DocumentPage::new("doc-batch".to_string(), 1, n);
+                page.ocr_text = Some(long.clone());
+                page
+            })
+            .collect();
+        repo.save_pages(&pages).await.unwrap();
+        // Simulate a row written before compression existed
+        with_conn!(repo.pool, conn, {
+            diesel::update(document_pages::table)
+                .set(document_pages::final_text.eq(Some(long.as_str())))
+                .execute(&mut conn)
+                .await
+        })
+        .unwrap();
+
+        let dry = repo.compress_page_text_batch(0, 10, true).await.unwrap();
+        assert_eq!(dry.pages_scanned, 2);
+        assert_eq!(dry.pages_compressed, 2);
+        assert!(dry.bytes_after < dry.bytes_before);
+
+        let run = repo.compress_page_text_batch(0, 10, false).await.unwrap();
+        assert_eq!(run, dry);
+        let again = repo.compress_page_text_batch(0, 10, false).await.unwrap();
+        assert_eq!(again.pages_compressed, 0);
+
+        let saved = repo.get_pages("doc-batch", 1).await.unwrap();
+        assert_eq!(saved[1].ocr_text.as_deref(), Some(long.as_str()));
+    }
+}
@@ -0,0 +1,124 @@
+//! Image preprocessing records: what was done to each page's image before
+//! OCR.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::PagePreprocessing;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::page_preprocessing;
+use crate::{with_conn, with_conn_split};
+
+impl DieselDocumentRepository {
+    /// Record the preprocessing of a page's image, replacing any earlier
+    /// record for it.
+    pub async fn record_page_preprocessing(
+        &self,
+        record: &PagePreprocessing,
+    ) -> Result<(), DieselError> {
+        let page_id = record.page_id as i32;
+        let steps = serde_json::to_string(&record.steps).unwrap_or_else(|_| "[]".into());
+        let now = Utc::now().to_rfc3339();
+        let values = (
+            page_preprocessing::page_id.eq(page_id),
+            page_preprocessing::rotation.eq(record.rotation),
+            page_preprocessing::skew_angle.eq(record.skew_angle),
+            page_preprocessing::steps.eq(&steps),
+            page_preprocessing::tool.eq(record.tool.as_deref()),
+            page_preprocessing::applied_at.eq(&now),
+        );
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(page_preprocessing::table)
+                    .values(values)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(page_preprocessing::table)
+                    .values(values)
+                    .on_conflict(page_preprocessing::page_id)
+                    .do_update()
+                    .set((
+                        page_preprocessing::rotation.eq(record.rotation),
+                        page_preprocessing::skew_angle.eq(record.skew_angle),
+                        page_preprocessing::steps.eq(&steps),
+                        page_preprocessing::tool.eq(record.tool.as_deref()),
+                        page_preprocessing::applied_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// How a page's image was preprocessed before OCR, if it was.
+    pub async fn get_page_preprocessing(
+        &self,
+        page_id: i64,
+    ) -> Result<Option<PagePreprocessing>, DieselError> {
+        #[allow(clippy::type_complexity)]
+        let row: Option<(i32, i32, Option<f32>, String, Option<String>, String)> =
+            with_conn!(self.pool, conn, {
+                page_preprocessing::table
+                    .find(page_id as i32)
+                    .select((
+                        page_preprocessing::page_id,
+                        page_preprocessing::rotation,
+                        page_preprocessing::skew_angle,
+                        page_preprocessing::steps,
+                        page_preprocessing::tool,
+                        page_preprocessing::applied_at,
+                    ))
+                    .first(&mut conn)
+                    .await
+                    .optional()
+            })?;
+        Ok(row.map(
+            |(page_id, rotation, skew_angle, steps, tool, applied_at)| PagePreprocessing {
+                page_id: page_id as i64,
+                rotation,
+                skew_angle,
+                steps: serde_json::from_str(&steps).unwrap_or_default(),
+                tool,
+                applied_at: parse_datetime(&applied_at),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_page_preprocessing() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        assert!(repo.get_page_preprocessing(3).await.unwrap().is_none());
+
+        let mut record = PagePreprocessing {
+            page_id: 3,
+            rotation: 90,
+            skew_angle: Some(-1.5),
+            steps: vec!["rotate".to_string(), "deskew".to_string()],
+            tool: Some("ImageMagick 7.1.1-15".to_string()),
+            applied_at: Utc::now(),
+        };
+        repo.record_page_preprocessing(&record).await.unwrap();
+        record.rotation = 0;
+        record.steps = vec!["deskew".to_string()];
+        repo.record_page_preprocessing(&record).await.unwrap();
+
+        let saved = repo.get_page_preprocessing(3).await.unwrap().unwrap();
+        assert_eq!(saved.rotation, 0);
+        assert_eq!(saved.skew_angle, Some(-1.5));
+        assert_eq!(saved.steps, vec!["deskew"]);
+    }
+}
@@ -0,0 +1,88 @@
+//! Column projection for document queries.
+//!
+//! `extracted_text` can be several megabytes of OCR output per document.
+//! Listing pages, navigation and most background jobs only need metadata,
+//! so queries take a [`Projection`] and skip the column unless asked.
+
+use diesel::backend::Backend;
+use diesel::expression::SqlLiteral;
+use diesel::prelude::*;
+use diesel::query_dsl::methods::BoxedDsl;
+use diesel::sql_types::{Nullable, Text};
+
+use crate::schema::documents;
+
+/// Which document columns a query loads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Projection {
+    /// Every column, including `extracted_text`.
+    #[default]
+    Full,
+    /// Every column except `extracted_text`, which is returned as `None`.
+    ///
+    /// Safe to pass back to `save()`, which never writes `extracted_text`.
+    Metadata,
+}
+
+/// `documents` columns in [`DocumentRecord`](crate::repository::models::DocumentRecord)
+/// order, with `extracted_text` replaced by `NULL`.
+type MetadataColumns = (
+    documents::id,
+    documents::source_id,
+    documents::title,
+    documents::source_url,
+    SqlLiteral<Nullable<Text>>,
+    documents::status,
+    documents::metadata,
+    documents::created_at,
+    documents::updated_at,
+    documents::synopsis,
+    documents::tags,
+    documents::estimated_date,
+    documents::date_confidence,
+    documents::date_source,
+    documents::manual_date,
+    documents::discovery_method,
+    documents::category_id,
+);
+
+fn metadata_columns() -> MetadataColumns {
+    (
+        documents::id,
+        documents::source_id,
+        documents::title,
+        documents::source_url,
+        diesel::dsl::sql::<Nullable<Text>>("NULL"),
+        documents::status,
+        documents::metadata,
+        documents::created_at,
+        documents::updated_at,
+        documents::synopsis,
+        documents::tags,
+        documents::estimated_date,
+        documents::date_confidence,
+        documents::date_source,
+        documents::manual_date,
+        documents::discovery_method,
+        documents::category_id,
+    )
+}
+
+impl Projection {
+    /// Boxed `documents` query selecting this projection's columns.
+    ///
+    /// Both variants produce the same row shape, so callers load
+    /// `DocumentRecord` either way.
+    pub(crate) fn documents_query<'a, DB>(self) -> documents::BoxedQuery<'a, DB>
+    where
+        DB: Backend,
+        documents::table: BoxedDsl<'a, DB, Output = documents::BoxedQuery<'a, DB>>,
+        diesel::dsl::Select<documents::table, MetadataColumns>:
+            BoxedDsl<'a, DB, Output = documents::BoxedQuery<'a, DB>>,
+    {
+        match self {
+            Projection::Full => documents::table.into_boxed(),
+            Projection::Metadata => documents::table.select(metadata_columns()).into_boxed(),
+        }
+    }
+}
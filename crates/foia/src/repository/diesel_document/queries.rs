@@ -6,12 +6,17 @@ use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 
-use super::{CountRow, DieselDocumentRepository, DocIdRow, MimeCount, TagRow};
+use super::{CountRow, DieselDocumentRepository, DocIdRow, Projection, TagRow};
 use crate::models::{Document, DocumentStatus};
 use crate::repository::document::DocumentNavigation;
 use crate::repository::models::DocumentRecord;
-use crate::repository::pool::DieselError;
-use crate::schema::documents;
+use crate::repository::pool::{retry_on_busy, DieselError};
+use crate::schema::{
+    document_classifications, document_exemptions, documents, mime_type_counts,
+    source_status_counts, tag_counts,
+};
+use crate::services::access::AccessFilter;
+use crate::utils::ATTACHMENTS_CATEGORY;
 use crate::{with_conn, with_conn_split};
 
 /// Validate that a string only contains safe identifier characters (alphanumeric + underscore).
@@ -27,6 +32,92 @@ fn validate_identifier(s: &str) -> Result<(), DieselError> {
     Ok(())
 }
 
+/// Encode excluded source IDs as a JSON array bind parameter for raw queries.
+fn exclude_sources_json(sources: &[String]) -> String {
+    serde_json::to_string(sources).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Position in the default `(updated_at, id)` browse ordering.
+///
+/// Encoded as an opaque URL-safe token for use in page links and the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseCursor {
+    pub updated_at: String,
+    pub id: String,
+}
+
+impl BrowseCursor {
+    pub fn new(updated_at: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            updated_at: updated_at.into(),
+            id: id.into(),
+        }
+    }
+
+    /// Encode as an opaque token.
+    pub fn encode(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(format!("{}\n{}", self.updated_at, self.id))
+    }
+
+    /// Decode a token produced by [`encode`](Self::encode).
+    pub fn decode(token: &str) -> Option<Self> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token)
+            .ok()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let (updated_at, id) = text.split_once('\n')?;
+        Some(Self::new(updated_at, id))
+    }
+
+    /// Cursor pointing at a loaded document. Timestamps are stored with
+    /// `to_rfc3339()`, so this matches the column value.
+    pub fn for_document(doc: &Document) -> Self {
+        Self::new(doc.updated_at.to_rfc3339(), &doc.id)
+    }
+}
+
+/// Keyset boundary for cursor pagination.
+#[derive(Debug, Clone, Copy)]
+pub enum Keyset<'a> {
+    /// Rows that sort after the cursor (next page).
+    After(&'a BrowseCursor),
+    /// Rows that sort before the cursor (previous page).
+    Before(&'a BrowseCursor),
+}
+
+impl Keyset<'_> {
+    /// Whether rows are fetched in reverse and must be flipped back.
+    pub(super) fn is_backward(&self) -> bool {
+        matches!(self, Keyset::Before(_))
+    }
+
+    fn cursor(&self) -> &BrowseCursor {
+        match self {
+            Keyset::After(c) | Keyset::Before(c) => c,
+        }
+    }
+}
+
+/// Which sources a browse listing covers.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum SourceScope<'a> {
+    #[default]
+    All,
+    /// A single source.
+    One(&'a str),
+    /// Any of several sources, e.g. everything under an agency.
+    Any(&'a [String]),
+}
+
+impl<'a> From<Option<&'a str>> for SourceScope<'a> {
+    fn from(source_id: Option<&'a str>) -> Self {
+        source_id.map_or(Self::All, Self::One)
+    }
+}
+
 /// Parameters for browsing/filtering documents.
 #[derive(Debug, Default, Clone)]
 pub struct BrowseParams<'a> {
@@ -39,6 +130,13 @@ pub struct BrowseParams<'a> {
     pub sort_order: Option<&'a str>,
     pub limit: u32,
     pub offset: u32,
+    /// Keyset position; replaces `offset`. Only valid with the default
+    /// `updated_at` sort.
+    pub keyset: Option<Keyset<'a>>,
+    /// Columns to load; `Projection::Metadata` skips `extracted_text`.
+    pub projection: Projection,
+    /// Withhold documents restricted from the viewer.
+    pub access: Option<&'a AccessFilter>,
 }
 
 impl DieselDocumentRepository {
@@ -47,36 +145,51 @@ impl DieselDocumentRepository {
     // ========================================================================
 
     /// Count all documents.
+    ///
+    /// Reads the trigger-maintained `source_status_counts` table.
     pub async fn count(&self) -> Result<u64, DieselError> {
-        use diesel::dsl::count_star;
-        with_conn!(self.pool, conn, {
-            let count: i64 = documents::table
-                .select(count_star())
-                .get_result(&mut conn)
-                .await?;
-            Ok(count as u64)
-        })
+        Ok(self.get_all_source_counts().await?.values().sum())
     }
 
     /// Get document counts per source.
     pub async fn get_all_source_counts(&self) -> Result<HashMap<String, u64>, DieselError> {
-        use diesel::dsl::count_star;
-        with_conn!(self.pool, conn, {
-            let rows: Vec<(String, i64)> = documents::table
-                .group_by(documents::source_id)
-                .select((documents::source_id, count_star()))
-                .load(&mut conn)
-                .await?;
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for (source_id, _, count) in self.load_source_status_counts(None).await? {
+            *counts.entry(source_id).or_default() += count;
+        }
+        Ok(counts)
+    }
 
+    /// Load `(source_id, status, count)` rows from `source_status_counts`,
+    /// skipping empty ones.
+    async fn load_source_status_counts(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<Vec<(String, String, u64)>, DieselError> {
+        with_conn!(self.pool, conn, {
+            let mut query = source_status_counts::table
+                .filter(source_status_counts::count.gt(0))
+                .select((
+                    source_status_counts::source_id,
+                    source_status_counts::status,
+                    source_status_counts::count,
+                ))
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(source_status_counts::source_id.eq(sid));
+            }
+            let rows: Vec<(String, String, i64)> = query.load(&mut conn).await?;
             Ok(rows
                 .into_iter()
-                .map(|(id, count)| (id, count as u64))
+                .map(|(source, status, count)| (source, status, count as u64))
                 .collect())
         })
     }
 
     /// Count documents needing a specific analysis type.
     ///
+    /// `mime_type` may end in `*` to match a prefix (e.g. `audio/*`).
+    ///
     /// A document needs analysis when:
     /// - No `complete` result exists in `document_analysis_results` for the type
     /// - No `failed` result exists within the retry window
@@ -87,6 +200,7 @@ impl DieselDocumentRepository {
         source_id: Option<&str>,
         mime_type: Option<&str>,
         retry_interval_hours: u32,
+        exclude_sources: &[String],
     ) -> Result<u64, DieselError> {
         use crate::schema::{document_analysis_results as dar, document_versions};
         use diesel::dsl::{count_distinct, exists, not};
@@ -127,8 +241,17 @@ impl DieselDocumentRepository {
             if let Some(sid) = source_id {
                 query = query.filter(documents::source_id.eq(sid));
             }
+            if !exclude_sources.is_empty() {
+                query = query.filter(documents::source_id.ne_all(exclude_sources));
+            }
             if let Some(mime) = mime_type {
-                query = query.filter(document_versions::mime_type.eq(mime));
+                // "audio/*" matches every audio subtype
+                query = match mime.strip_suffix('*') {
+                    Some(prefix) => {
+                        query.filter(document_versions::mime_type.like(format!("{}%", prefix)))
+                    }
+                    None => query.filter(document_versions::mime_type.eq(mime)),
+                };
             }
 
             let count: i64 = query
@@ -142,7 +265,7 @@ impl DieselDocumentRepository {
     /// Count documents needing OCR.
     #[deprecated(note = "Use count_needing_analysis(\"ocr\", ...) instead")]
     pub async fn count_needing_ocr(&self, source_id: Option<&str>) -> Result<u64, DieselError> {
-        self.count_needing_analysis("ocr", source_id, None, 12)
+        self.count_needing_analysis("ocr", source_id, None, 12, &[])
             .await
     }
 
@@ -153,7 +276,7 @@ impl DieselDocumentRepository {
         source_id: Option<&str>,
         mime_type: Option<&str>,
     ) -> Result<u64, DieselError> {
-        self.count_needing_analysis("ocr", source_id, mime_type, 12)
+        self.count_needing_analysis("ocr", source_id, mime_type, 12, &[])
             .await
     }
 
@@ -162,6 +285,7 @@ impl DieselDocumentRepository {
     pub async fn count_needing_summarization(
         &self,
         source_id: Option<&str>,
+        exclude_sources: &[String],
     ) -> Result<u64, DieselError> {
         with_conn!(self.pool, conn, {
             let mut query = documents::table
@@ -170,50 +294,107 @@ impl DieselDocumentRepository {
             if let Some(sid) = source_id {
                 query = query.filter(documents::source_id.eq(sid));
             }
+            if !exclude_sources.is_empty() {
+                query = query.filter(documents::source_id.ne_all(exclude_sources));
+            }
             let count: i64 = query.count().get_result(&mut conn).await?;
             Ok(count as u64)
         })
     }
 
-    /// Count documents by source.
-    pub async fn count_by_source(&self, source_id: &str) -> Result<u64, DieselError> {
-        use diesel::dsl::count_star;
+    /// Count documents with an analysis of this type completed since `since`.
+    pub async fn count_analyzed_since(
+        &self,
+        analysis_type: &str,
+        source_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<u64, DieselError> {
+        use crate::schema::document_analysis_results as dar;
+        use diesel::dsl::count_distinct;
+
+        let since = since.to_rfc3339();
         with_conn!(self.pool, conn, {
-            let count: i64 = documents::table
-                .filter(documents::source_id.eq(source_id))
-                .select(count_star())
-                .get_result(&mut conn)
+            let mut query = dar::table
+                .inner_join(documents::table)
+                .filter(dar::analysis_type.eq(analysis_type))
+                .filter(dar::status.eq("complete"))
+                .filter(dar::created_at.ge(&since))
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(documents::source_id.eq(sid));
+            }
+            let count: i64 = query
+                .select(count_distinct(dar::document_id))
+                .first(&mut conn)
                 .await?;
             Ok(count as u64)
         })
     }
 
-    /// Count documents by status.
-    pub async fn count_by_status(
+    /// Count summarized documents last updated since `since`.
+    pub async fn count_summarized_since(
         &self,
         source_id: Option<&str>,
-    ) -> Result<HashMap<String, u64>, DieselError> {
-        use diesel::dsl::count_star;
-
+        since: DateTime<Utc>,
+    ) -> Result<u64, DieselError> {
+        let since = since.to_rfc3339();
         with_conn!(self.pool, conn, {
             let mut query = documents::table
-                .group_by(documents::status)
-                .select((documents::status, count_star()))
+                .filter(documents::status.eq("indexed"))
+                .filter(documents::synopsis.is_not_null())
+                .filter(documents::updated_at.ge(&since))
                 .into_boxed();
-
             if let Some(sid) = source_id {
                 query = query.filter(documents::source_id.eq(sid));
             }
+            let count: i64 = query.count().get_result(&mut conn).await?;
+            Ok(count as u64)
+        })
+    }
 
-            let rows: Vec<(String, i64)> = query.load(&mut conn).await?;
-            let mut counts = HashMap::new();
-            for (status, count) in rows {
-                counts.insert(status, count as u64);
-            }
-            Ok(counts)
+    /// Count documents by source.
+    pub async fn count_by_source(&self, source_id: &str) -> Result<u64, DieselError> {
+        Ok(self
+            .load_source_status_counts(Some(source_id))
+            .await?
+            .into_iter()
+            .map(|(_, _, count)| count)
+            .sum())
+    }
+
+    /// Total size in bytes of every stored version of a source's documents.
+    pub async fn storage_bytes_by_source(&self, source_id: &str) -> Result<u64, DieselError> {
+        #[derive(diesel::QueryableByName)]
+        struct Total {
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            total: i64,
+        }
+
+        with_conn!(self.pool, conn, {
+            let row: Total = diesel::sql_query(
+                "SELECT CAST(COALESCE(SUM(v.file_size), 0) AS BIGINT) AS total \
+                 FROM document_versions v JOIN documents d ON d.id = v.document_id \
+                 WHERE d.source_id = $1",
+            )
+            .bind::<diesel::sql_types::Text, _>(source_id)
+            .get_result(&mut conn)
+            .await?;
+            Ok(row.total as u64)
         })
     }
 
+    /// Count documents by status.
+    pub async fn count_by_status(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<HashMap<String, u64>, DieselError> {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for (_, status, count) in self.load_source_status_counts(source_id).await? {
+            *counts.entry(status).or_default() += count;
+        }
+        Ok(counts)
+    }
+
     /// Count all by status.
     pub async fn count_all_by_status(&self) -> Result<HashMap<String, u64>, DieselError> {
         self.count_by_status(None).await
@@ -224,32 +405,11 @@ impl DieselDocumentRepository {
     pub async fn get_source_status_counts(
         &self,
     ) -> Result<HashMap<String, HashMap<String, u64>>, DieselError> {
-        #[derive(diesel::QueryableByName)]
-        struct SourceStatusCount {
-            #[diesel(sql_type = diesel::sql_types::Text)]
-            source_id: String,
-            #[diesel(sql_type = diesel::sql_types::Text)]
-            status: String,
-            #[diesel(sql_type = diesel::sql_types::BigInt)]
-            count: i64,
+        let mut result: HashMap<String, HashMap<String, u64>> = HashMap::new();
+        for (source_id, status, count) in self.load_source_status_counts(None).await? {
+            result.entry(source_id).or_default().insert(status, count);
         }
-
-        with_conn!(self.pool, conn, {
-            let rows: Vec<SourceStatusCount> = diesel::sql_query(
-                "SELECT source_id, status, COUNT(*) as count FROM documents GROUP BY source_id, status",
-            )
-            .load(&mut conn)
-            .await?;
-
-            let mut result: HashMap<String, HashMap<String, u64>> = HashMap::new();
-            for row in rows {
-                result
-                    .entry(row.source_id)
-                    .or_default()
-                    .insert(row.status, row.count as u64);
-            }
-            Ok(result)
-        })
+        Ok(result)
     }
 
     /// Count documents needing date estimation.
@@ -257,7 +417,9 @@ impl DieselDocumentRepository {
     pub async fn count_documents_needing_date_estimation(
         &self,
         source_id: Option<&str>,
+        exclude_sources: &[String],
     ) -> Result<u64, DieselError> {
+        let excluded = exclude_sources_json(exclude_sources);
         with_conn_split!(self.pool,
             sqlite: conn => {
                 let result: Vec<CountRow> = if let Some(sid) = source_id {
@@ -265,9 +427,11 @@ impl DieselDocumentRepository {
                         diesel::sql_query(
                             r#"SELECT COUNT(*) as count FROM documents
                                WHERE json_extract(metadata, '$.estimated_date') IS NULL
-                               AND source_id = $1"#,
+                               AND source_id = $1
+                               AND source_id NOT IN (SELECT value FROM json_each($2))"#,
                         )
-                        .bind::<diesel::sql_types::Text, _>(sid),
+                        .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -276,8 +440,10 @@ impl DieselDocumentRepository {
                     diesel_async::RunQueryDsl::load(
                         diesel::sql_query(
                             r#"SELECT COUNT(*) as count FROM documents
-                               WHERE json_extract(metadata, '$.estimated_date') IS NULL"#,
-                        ),
+                               WHERE json_extract(metadata, '$.estimated_date') IS NULL
+                               AND source_id NOT IN (SELECT value FROM json_each($1))"#,
+                        )
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -292,9 +458,11 @@ impl DieselDocumentRepository {
                         diesel::sql_query(
                             r#"SELECT COUNT(*) as count FROM documents
                                WHERE metadata->>'estimated_date' IS NULL
-                               AND source_id = $1"#,
+                               AND source_id = $1
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($2::jsonb))"#,
                         )
-                        .bind::<diesel::sql_types::Text, _>(sid),
+                        .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -303,8 +471,10 @@ impl DieselDocumentRepository {
                     diesel_async::RunQueryDsl::load(
                         diesel::sql_query(
                             r#"SELECT COUNT(*) as count FROM documents
-                               WHERE metadata->>'estimated_date' IS NULL"#,
-                        ),
+                               WHERE metadata->>'estimated_date' IS NULL
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($1::jsonb))"#,
+                        )
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -330,22 +500,26 @@ impl DieselDocumentRepository {
         annotation_type: &str,
         version: i32,
         source_id: Option<&str>,
+        exclude_sources: &[String],
     ) -> Result<u64, DieselError> {
         // For llm_summary, delegate to existing specialized query
         if annotation_type == "llm_summary" {
-            return self.count_needing_summarization(source_id).await;
+            return self
+                .count_needing_summarization(source_id, exclude_sources)
+                .await;
         }
 
         // For date_detection, delegate to existing specialized query
         if annotation_type == "date_detection" {
             return self
-                .count_documents_needing_date_estimation(source_id)
+                .count_documents_needing_date_estimation(source_id, exclude_sources)
                 .await;
         }
 
         // annotation_type is interpolated into JSON path expressions where bind
         // params aren't supported — validate it only contains safe identifier chars
         validate_identifier(annotation_type)?;
+        let excluded = exclude_sources_json(exclude_sources);
 
         with_conn_split!(self.pool,
             sqlite: conn => {
@@ -357,10 +531,12 @@ impl DieselDocumentRepository {
                                    json_extract(metadata, '$.annotations.{annotation_type}.version') IS NULL
                                    OR json_extract(metadata, '$.annotations.{annotation_type}.version') < $1
                                )
-                               AND source_id = $2"#,
+                               AND source_id = $2
+                               AND source_id NOT IN (SELECT value FROM json_each($3))"#,
                         ))
                         .bind::<diesel::sql_types::Integer, _>(version)
-                        .bind::<diesel::sql_types::Text, _>(sid),
+                        .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -372,9 +548,11 @@ impl DieselDocumentRepository {
                                WHERE (
                                    json_extract(metadata, '$.annotations.{annotation_type}.version') IS NULL
                                    OR json_extract(metadata, '$.annotations.{annotation_type}.version') < $1
-                               )"#,
+                               )
+                               AND source_id NOT IN (SELECT value FROM json_each($2))"#,
                         ))
-                        .bind::<diesel::sql_types::Integer, _>(version),
+                        .bind::<diesel::sql_types::Integer, _>(version)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -392,10 +570,12 @@ impl DieselDocumentRepository {
                                    (metadata->'annotations'->'{annotation_type}'->>'version')::int IS NULL
                                    OR (metadata->'annotations'->'{annotation_type}'->>'version')::int < $1
                                )
-                               AND source_id = $2"#,
+                               AND source_id = $2
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($3::jsonb))"#,
                         ))
                         .bind::<diesel::sql_types::Integer, _>(version)
-                        .bind::<diesel::sql_types::Text, _>(sid),
+                        .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -407,9 +587,11 @@ impl DieselDocumentRepository {
                                WHERE (
                                    (metadata->'annotations'->'{annotation_type}'->>'version')::int IS NULL
                                    OR (metadata->'annotations'->'{annotation_type}'->>'version')::int < $1
-                               )"#,
+                               )
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($2::jsonb))"#,
                         ))
-                        .bind::<diesel::sql_types::Integer, _>(version),
+                        .bind::<diesel::sql_types::Integer, _>(version)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -427,21 +609,25 @@ impl DieselDocumentRepository {
         annotation_type: &str,
         version: i32,
         source_id: Option<&str>,
+        exclude_sources: &[String],
         limit: usize,
     ) -> Result<Vec<Document>, DieselError> {
         // For llm_summary, delegate to existing specialized query
         if annotation_type == "llm_summary" {
-            return self.get_needing_summarization(limit).await;
+            return self
+                .get_needing_summarization(source_id, exclude_sources, limit)
+                .await;
         }
 
         // For date_detection, delegate to existing specialized query
         if annotation_type == "date_detection" {
             return self
-                .get_documents_needing_date_estimation(source_id, limit)
+                .get_documents_needing_date_estimation(source_id, exclude_sources, limit)
                 .await;
         }
 
         validate_identifier(annotation_type)?;
+        let excluded = exclude_sources_json(exclude_sources);
         let limit_i64 = limit as i64;
 
         let ids: Vec<DocIdRow> = with_conn_split!(self.pool,
@@ -455,10 +641,12 @@ impl DieselDocumentRepository {
                                    OR json_extract(metadata, '$.annotations.{annotation_type}.version') < $1
                                )
                                AND source_id = $2
-                               LIMIT $3"#,
+                               AND source_id NOT IN (SELECT value FROM json_each($3))
+                               LIMIT $4"#,
                         ))
                         .bind::<diesel::sql_types::Integer, _>(version)
                         .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str())
                         .bind::<diesel::sql_types::BigInt, _>(limit_i64),
                         &mut conn,
                     )
@@ -472,9 +660,11 @@ impl DieselDocumentRepository {
                                    json_extract(metadata, '$.annotations.{annotation_type}.version') IS NULL
                                    OR json_extract(metadata, '$.annotations.{annotation_type}.version') < $1
                                )
-                               LIMIT $2"#,
+                               AND source_id NOT IN (SELECT value FROM json_each($2))
+                               LIMIT $3"#,
                         ))
                         .bind::<diesel::sql_types::Integer, _>(version)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str())
                         .bind::<diesel::sql_types::BigInt, _>(limit_i64),
                         &mut conn,
                     )
@@ -492,10 +682,12 @@ impl DieselDocumentRepository {
                                    OR (metadata->'annotations'->'{annotation_type}'->>'version')::int < $1
                                )
                                AND source_id = $2
-                               LIMIT $3"#,
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($3::jsonb))
+                               LIMIT $4"#,
                         ))
                         .bind::<diesel::sql_types::Integer, _>(version)
                         .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str())
                         .bind::<diesel::sql_types::BigInt, _>(limit_i64),
                         &mut conn,
                     )
@@ -509,9 +701,11 @@ impl DieselDocumentRepository {
                                    (metadata->'annotations'->'{annotation_type}'->>'version')::int IS NULL
                                    OR (metadata->'annotations'->'{annotation_type}'->>'version')::int < $1
                                )
-                               LIMIT $2"#,
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($2::jsonb))
+                               LIMIT $3"#,
                         ))
                         .bind::<diesel::sql_types::Integer, _>(version)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str())
                         .bind::<diesel::sql_types::BigInt, _>(limit_i64),
                         &mut conn,
                     )
@@ -529,27 +723,19 @@ impl DieselDocumentRepository {
     // Statistics Operations
     // ========================================================================
 
-    /// Get type statistics - count documents by MIME type.
+    /// Get type statistics - count documents by the MIME type of their
+    /// newest version, from the trigger-maintained `mime_type_counts` table.
     pub async fn get_type_stats(&self) -> Result<HashMap<String, u64>, DieselError> {
         with_conn!(self.pool, conn, {
-            let results: Vec<MimeCount> = diesel_async::RunQueryDsl::load(
-                diesel::sql_query(
-                    r#"SELECT COALESCE(dv.mime_type, 'unknown') as mime_type, COUNT(DISTINCT dv.document_id) as count
-                       FROM document_versions dv
-                       INNER JOIN (
-                           SELECT document_id, MAX(id) as max_id
-                           FROM document_versions
-                           GROUP BY document_id
-                       ) latest ON dv.document_id = latest.document_id AND dv.id = latest.max_id
-                       GROUP BY dv.mime_type"#
-                ),
-                &mut conn,
-            ).await?;
-            let mut stats = HashMap::new();
-            for row in results {
-                stats.insert(row.mime_type, row.count as u64);
-            }
-            Ok(stats)
+            let rows: Vec<(String, i64)> = mime_type_counts::table
+                .filter(mime_type_counts::count.gt(0))
+                .select((mime_type_counts::mime_type, mime_type_counts::count))
+                .load(&mut conn)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(mime_type, count)| (mime_type, count as u64))
+                .collect())
         })
     }
 
@@ -602,10 +788,15 @@ impl DieselDocumentRepository {
     // ========================================================================
 
     /// Get recent documents.
-    pub async fn get_recent(&self, limit: u32) -> Result<Vec<Document>, DieselError> {
+    pub async fn get_recent(
+        &self,
+        limit: u32,
+        projection: Projection,
+    ) -> Result<Vec<Document>, DieselError> {
         let limit = limit as i64;
         let records: Vec<DocumentRecord> = with_conn!(self.pool, conn, {
-            documents::table
+            projection
+                .documents_query()
                 .order(documents::updated_at.desc())
                 .limit(limit)
                 .load(&mut conn)
@@ -626,10 +817,19 @@ impl DieselDocumentRepository {
         let search_query = params.search_query;
         let sort_field = params.sort_field;
         let sort_order = params.sort_order;
+        let keyset = params.keyset;
+        let projection = params.projection;
+        let access = params.access;
+
+        if keyset.is_some() && matches!(sort_field, Some("created_at") | Some("title")) {
+            return Err(diesel::result::Error::QueryBuilderError(
+                "cursor pagination requires the updated_at sort".into(),
+            ));
+        }
 
-        let records: Vec<DocumentRecord> = with_conn!(self.pool, conn, {
+        let mut records: Vec<DocumentRecord> = with_conn!(self.pool, conn, {
             // Build query with filters first, then order and paginate
-            let mut query = documents::table.into_boxed();
+            let mut query = projection.documents_query();
 
             // Apply filters
             if let Some(sid) = source_id {
@@ -657,6 +857,7 @@ impl DieselDocumentRepository {
                     );
                 }
             }
+            restrict_access!(query, access);
 
             // Apply sorting
             let is_desc = sort_order
@@ -678,18 +879,46 @@ impl DieselDocumentRepository {
                     }
                 }
                 _ => {
-                    // Default: updated_at desc
-                    if is_desc {
-                        query = query.order(documents::updated_at.desc());
+                    // Default: updated_at desc, id breaks ties so keyset
+                    // cursors are stable
+                    if let Some(ks) = keyset {
+                        let c = ks.cursor();
+                        let below = is_desc != ks.is_backward();
+                        query = if below {
+                            query.filter(
+                                documents::updated_at.lt(c.updated_at.clone()).or(
+                                    documents::updated_at
+                                        .eq(c.updated_at.clone())
+                                        .and(documents::id.lt(c.id.clone())),
+                                ),
+                            )
+                        } else {
+                            query.filter(
+                                documents::updated_at.gt(c.updated_at.clone()).or(
+                                    documents::updated_at
+                                        .eq(c.updated_at.clone())
+                                        .and(documents::id.gt(c.id.clone())),
+                                ),
+                            )
+                        };
+                    }
+                    let reverse = keyset.is_some_and(|ks| ks.is_backward());
+                    if is_desc != reverse {
+                        query = query.order((documents::updated_at.desc(), documents::id.desc()));
                     } else {
-                        query = query.order(documents::updated_at.asc());
+                        query = query.order((documents::updated_at.asc(), documents::id.asc()));
                     }
                 }
             }
 
+            let offset = if keyset.is_some() { 0 } else { offset };
             query.limit(limit).offset(offset).load(&mut conn).await
         })?;
 
+        if keyset.is_some_and(|ks| ks.is_backward()) {
+            records.reverse();
+        }
+
         // Batch load all versions in a single query
         let doc_ids: Vec<String> = records.iter().map(|r| r.id.clone()).collect();
         let mut versions_map = self.load_versions_batch(&doc_ids).await?;
@@ -705,33 +934,68 @@ impl DieselDocumentRepository {
     }
 
     /// Browse count.
+    #[allow(clippy::too_many_arguments)]
     pub async fn browse_count(
         &self,
-        source_id: Option<&str>,
+        sources: SourceScope<'_>,
         status: Option<&str>,
         categories: &[String],
         tags: &[String],
+        record_types: &[String],
+        exemptions: &[String],
         search_query: Option<&str>,
+        access: Option<&AccessFilter>,
     ) -> Result<u64, DieselError> {
+        // Attachments have no status, record type or exemptions, so those
+        // filters exclude them
+        let doc_categories: Vec<String>;
+        let mut attachments = 0;
+        let categories = if categories.iter().any(|c| c == ATTACHMENTS_CATEGORY) {
+            if status.is_none() && record_types.is_empty() && exemptions.is_empty() {
+                attachments = self
+                    .count_browse_virtual_files(sources, tags, search_query, access)
+                    .await?;
+            }
+            doc_categories = categories
+                .iter()
+                .filter(|c| *c != ATTACHMENTS_CATEGORY)
+                .cloned()
+                .collect();
+            if doc_categories.is_empty() {
+                return Ok(attachments);
+            }
+            &doc_categories[..]
+        } else {
+            categories
+        };
+
         let has_filters = status.is_some()
             || !categories.is_empty()
             || !tags.is_empty()
-            || search_query.is_some_and(|q| !q.is_empty());
+            || !record_types.is_empty()
+            || !exemptions.is_empty()
+            || search_query.is_some_and(|q| !q.is_empty())
+            || access.is_some_and(|a| !a.is_empty());
 
         // Use pre-computed counts when no filters are active
         if !has_filters {
-            return if let Some(sid) = source_id {
-                self.count_by_source(sid).await
-            } else {
-                self.count().await
+            return match sources {
+                SourceScope::All => self.count().await,
+                SourceScope::One(sid) => self.count_by_source(sid).await,
+                SourceScope::Any(ids) => {
+                    let counts = self.get_all_source_counts().await?;
+                    Ok(ids.iter().filter_map(|id| counts.get(id)).sum())
+                }
             };
         }
 
         use diesel::dsl::count_star;
         with_conn!(self.pool, conn, {
             let mut query = documents::table.select(count_star()).into_boxed();
-            if let Some(sid) = source_id {
-                query = query.filter(documents::source_id.eq(sid));
+            match sources {
+                SourceScope::All => {}
+                SourceScope::One(sid) => query = query.filter(documents::source_id.eq(sid)),
+                SourceScope::Any(ids) => query = query.filter(documents::source_id.eq_any(ids)),
             }
             if let Some(st) = status {
                 query = query.filter(documents::status.eq(st));
@@ -743,6 +1007,24 @@ impl DieselDocumentRepository {
                 let pattern = format!("%{}%", tag);
                 query = query.filter(documents::tags.like(pattern));
             }
+            if !record_types.is_empty() {
+                query = query.filter(
+                    documents::id.eq_any(
+                        document_classifications::table
+                            .filter(document_classifications::record_type.eq_any(record_types))
+                            .select(document_classifications::document_id),
+                    ),
+                );
+            }
+            if !exemptions.is_empty() {
+                query = query.filter(
+                    documents::id.eq_any(
+                        document_exemptions::table
+                            .filter(document_exemptions::code.eq_any(exemptions))
+                            .select(document_exemptions::document_id),
+                    ),
+                );
+            }
             if let Some(q) = search_query {
                 if !q.is_empty() {
                     let pattern = format!("%{}%", q);
@@ -753,25 +1035,121 @@ impl DieselDocumentRepository {
                     );
                 }
             }
+            restrict_access!(query, access);
             let count: i64 = query.first(&mut conn).await?;
-            Ok(count as u64)
+            Ok(count as u64 + attachments)
         })
     }
 
     /// Optimized browse that only loads columns needed for listing.
     /// Avoids loading `extracted_text` which can be very large (OCR text).
-    /// Two-step query: fetch document page first, then batch-load latest versions.
+    ///
+    /// Ordered by `(updated_at, id)` descending; `keyset` replaces `offset`.
+    /// The [`ATTACHMENTS_CATEGORY`] type lists archive members and email
+    /// attachments, merged with documents of any other selected types.
+    /// Attachments are never classified or scanned for exemptions, so a
+    /// `record_types` or `exemptions` filter drops them.
+    #[allow(clippy::too_many_arguments)]
     pub async fn browse_fast(
         &self,
-        source_id: Option<&str>,
+        sources: SourceScope<'_>,
         _status: Option<&str>,
         categories: &[String],
         tags: &[String],
+        record_types: &[String],
+        exemptions: &[String],
+        search_query: Option<&str>,
         limit: u32,
         offset: u32,
+        keyset: Option<Keyset<'_>>,
+        access: Option<&AccessFilter>,
+    ) -> Result<Vec<super::BrowseRow>, DieselError> {
+        let doc_categories: Vec<String> = categories
+            .iter()
+            .filter(|c| *c != ATTACHMENTS_CATEGORY)
+            .cloned()
+            .collect();
+        if doc_categories.len() == categories.len()
+            || !record_types.is_empty()
+            || !exemptions.is_empty()
+        {
+            if doc_categories.is_empty() && !categories.is_empty() {
+                return Ok(Vec::new());
+            }
+            return self
+                .browse_document_rows(
+                    sources,
+                    &doc_categories,
+                    tags,
+                    record_types,
+                    exemptions,
+                    search_query,
+                    limit,
+                    offset,
+                    keyset,
+                    access,
+                )
+                .await;
+        }
+
+        // Both lists are sorted the same way, so the page is the first `limit`
+        // rows of their merge. Offset pages are merged from the top.
+        let (fetch, skip) = match keyset {
+            Some(_) => (limit, 0),
+            None => (limit + offset, offset as usize),
+        };
+        let mut rows = self
+            .browse_virtual_files(sources, tags, search_query, fetch, 0, keyset, access)
+            .await?;
+        if !doc_categories.is_empty() {
+            rows.extend(
+                self.browse_document_rows(
+                    sources,
+                    &doc_categories,
+                    tags,
+                    &[],
+                    &[],
+                    search_query,
+                    fetch,
+                    0,
+                    keyset,
+                    access,
+                )
+                .await?,
+            );
+        }
+        rows.sort_by(|a, b| (&b.updated_at, &b.id).cmp(&(&a.updated_at, &a.id)));
+
+        if keyset.is_some_and(|ks| ks.is_backward()) {
+            // Rows just before the cursor are the last ones in display order
+            let excess = rows.len().saturating_sub(limit as usize);
+            rows.drain(..excess);
+        } else {
+            rows = rows.into_iter().skip(skip).take(limit as usize).collect();
+        }
+        Ok(rows)
+    }
+
+    /// Document rows for [`browse_fast`](Self::browse_fast).
+    /// Two-step query: fetch document page first, then batch-load latest versions.
+    #[allow(clippy::too_many_arguments)]
+    async fn browse_document_rows(
+        &self,
+        sources: SourceScope<'_>,
+        categories: &[String],
+        tags: &[String],
+        record_types: &[String],
+        exemptions: &[String],
+        search_query: Option<&str>,
+        limit: u32,
+        offset: u32,
+        keyset: Option<Keyset<'_>>,
+        access: Option<&AccessFilter>,
     ) -> Result<Vec<super::BrowseRow>, DieselError> {
         use crate::schema::document_versions;
 
+        let backward = keyset.is_some_and(|ks| ks.is_backward());
+
         with_conn!(self.pool, conn, {
             // Step 1: fetch the page of documents that have at least one version
             // Use EXISTS subquery to filter out versionless documents
@@ -782,19 +1160,50 @@ impl DieselDocumentRepository {
                     documents::source_id,
                     documents::synopsis,
                     documents::tags,
+                    documents::updated_at,
                 ))
                 .filter(diesel::dsl::exists(
                     document_versions::table
                         .filter(document_versions::document_id.eq(documents::id))
                         .select(document_versions::id),
                 ))
-                .order(documents::updated_at.desc())
                 .limit(limit as i64)
-                .offset(offset as i64)
                 .into_boxed();
 
-            if let Some(sid) = source_id {
-                query = query.filter(documents::source_id.eq(sid));
+            match keyset {
+                Some(Keyset::After(c)) => {
+                    query = query
+                        .filter(
+                            documents::updated_at.lt(c.updated_at.clone()).or(
+                                documents::updated_at
+                                    .eq(c.updated_at.clone())
+                                    .and(documents::id.lt(c.id.clone())),
+                            ),
+                        )
+                        .order((documents::updated_at.desc(), documents::id.desc()));
+                }
+                Some(Keyset::Before(c)) => {
+                    query = query
+                        .filter(
+                            documents::updated_at.gt(c.updated_at.clone()).or(
+                                documents::updated_at
+                                    .eq(c.updated_at.clone())
+                                    .and(documents::id.gt(c.id.clone())),
+                            ),
+                        )
+                        .order((documents::updated_at.asc(), documents::id.asc()));
+                }
+                None => {
+                    query = query
+                        .order((documents::updated_at.desc(), documents::id.desc()))
+                        .offset(offset as i64);
+                }
+            }
+
+            match sources {
+                SourceScope::All => {}
+                SourceScope::One(sid) => query = query.filter(documents::source_id.eq(sid)),
+                SourceScope::Any(ids) => query = query.filter(documents::source_id.eq_any(ids)),
             }
             if !categories.is_empty() {
                 query = query.filter(documents::category_id.eq_any(categories));
@@ -803,15 +1212,46 @@ impl DieselDocumentRepository {
                 let pattern = format!("%{}%", tag);
                 query = query.filter(documents::tags.like(pattern));
             }
+            if !record_types.is_empty() {
+                query = query.filter(
+                    documents::id.eq_any(
+                        document_classifications::table
+                            .filter(document_classifications::record_type.eq_any(record_types))
+                            .select(document_classifications::document_id),
+                    ),
+                );
+            }
+            if !exemptions.is_empty() {
+                query = query.filter(
+                    documents::id.eq_any(
+                        document_exemptions::table
+                            .filter(document_exemptions::code.eq_any(exemptions))
+                            .select(document_exemptions::document_id),
+                    ),
+                );
+            }
+            if let Some(q) = search_query.filter(|q| !q.is_empty()) {
+                let pattern = format!("%{}%", q);
+                query = query.filter(
+                    documents::title
+                        .like(pattern.clone())
+                        .or(documents::synopsis.like(pattern)),
+                );
+            }
+            restrict_access!(query, access);
 
             #[allow(clippy::type_complexity)]
-            let doc_rows: Vec<(
+            let mut doc_rows: Vec<(
                 String,
                 String,
                 String,
                 Option<String>,
                 Option<String>,
+                String,
             )> = query.load(&mut conn).await?;
+            if backward {
+                doc_rows.reverse();
+            }
 
             if doc_rows.is_empty() {
                 return Ok(Vec::new());
@@ -846,10 +1286,12 @@ impl DieselDocumentRepository {
             // Combine in document order
             let results: Vec<super::BrowseRow> = doc_rows
                 .into_iter()
-                .filter_map(|(id, title, source_id, synopsis, tags)| {
+                .filter_map(|(id, title, source_id, synopsis, tags, updated_at)| {
                     let (filename, mime, size, acquired) = latest_versions.remove(id.as_str())?;
                     Some(super::BrowseRow {
                         id,
+                        kind: super::BrowseRow::DOCUMENT.to_string(),
+                        parent_id: None,
                         title,
                         source_id,
                         synopsis,
@@ -858,6 +1300,7 @@ impl DieselDocumentRepository {
                         mime_type: mime,
                         file_size: size,
                         acquired_at: acquired,
+                        updated_at,
                     })
                 })
                 .collect();
@@ -956,36 +1399,29 @@ impl DieselDocumentRepository {
 
     /// Get all unique tags from document metadata.
     pub async fn get_all_tags(&self) -> Result<Vec<String>, DieselError> {
-        with_conn_split!(self.pool,
-            sqlite: conn => {
-                let results: Vec<TagRow> = diesel_async::RunQueryDsl::load(
-                    diesel::sql_query(
-                        r#"SELECT DISTINCT value as tag
-                           FROM documents, json_each(documents.tags)
-                           WHERE documents.tags IS NOT NULL AND documents.tags != '[]'
-                           ORDER BY value"#,
-                    ),
-                    &mut conn,
-                )
-                .await
-                .unwrap_or_default();
-                Ok(results.into_iter().map(|r| r.tag).collect())
-            },
-            postgres: conn => {
-                let results: Vec<TagRow> = diesel_async::RunQueryDsl::load(
-                    diesel::sql_query(
-                        r#"SELECT DISTINCT tag
-                           FROM documents, jsonb_array_elements_text(documents.tags::jsonb) as tag
-                           WHERE documents.tags IS NOT NULL AND documents.tags != '[]'
-                           ORDER BY tag"#,
-                    ),
-                    &mut conn,
-                )
-                .await
-                .unwrap_or_default();
-                Ok(results.into_iter().map(|r| r.tag).collect())
-            }
-        )
+        Ok(self
+            .get_tag_counts()
+            .await?
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect())
+    }
+
+    /// Get every tag with the number of documents carrying it, ordered by
+    /// tag, from the trigger-maintained `tag_counts` table.
+    pub async fn get_tag_counts(&self) -> Result<Vec<(String, u64)>, DieselError> {
+        with_conn!(self.pool, conn, {
+            let rows: Vec<(String, i64)> = tag_counts::table
+                .filter(tag_counts::count.gt(0))
+                .order(tag_counts::tag.asc())
+                .select((tag_counts::tag, tag_counts::count))
+                .load(&mut conn)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(tag, count)| (tag, count as u64))
+                .collect())
+        })
     }
 
     /// Get documents by tag.
@@ -1063,7 +1499,8 @@ impl DieselDocumentRepository {
         );
 
         let doc_ids: Vec<String> = ids.into_iter().map(|r| r.id).collect();
-        self.get_batch(&doc_ids).await
+        self.get_batch_projected(&doc_ids, Projection::Metadata)
+            .await
     }
 
     /// Get documents by MIME type category.
@@ -1119,7 +1556,8 @@ impl DieselDocumentRepository {
         });
 
         let doc_ids: Vec<String> = ids.into_iter().map(|r| r.id).collect();
-        self.get_batch(&doc_ids).await
+        self.get_batch_projected(&doc_ids, Projection::Metadata)
+            .await
     }
 
     // ========================================================================
@@ -1259,8 +1697,10 @@ impl DieselDocumentRepository {
     pub async fn get_documents_needing_date_estimation(
         &self,
         source_id: Option<&str>,
+        exclude_sources: &[String],
         limit: usize,
     ) -> Result<Vec<Document>, DieselError> {
+        let excluded = exclude_sources_json(exclude_sources);
         let ids: Vec<DocIdRow> = with_conn_split!(self.pool,
             sqlite: conn => {
                 if let Some(sid) = source_id {
@@ -1269,10 +1709,12 @@ impl DieselDocumentRepository {
                             r#"SELECT id FROM documents
                                WHERE json_extract(metadata, '$.estimated_date') IS NULL
                                AND source_id = $1
+                               AND source_id NOT IN (SELECT value FROM json_each($2))
                                LIMIT {}"#,
                             limit
                         ))
-                        .bind::<diesel::sql_types::Text, _>(sid),
+                        .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -1282,9 +1724,11 @@ impl DieselDocumentRepository {
                         diesel::sql_query(format!(
                             r#"SELECT id FROM documents
                                WHERE json_extract(metadata, '$.estimated_date') IS NULL
+                               AND source_id NOT IN (SELECT value FROM json_each($1))
                                LIMIT {}"#,
                             limit
-                        )),
+                        ))
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -1298,10 +1742,12 @@ impl DieselDocumentRepository {
                             r#"SELECT id FROM documents
                                WHERE metadata->>'estimated_date' IS NULL
                                AND source_id = $1
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($2::jsonb))
                                LIMIT {}"#,
                             limit
                         ))
-                        .bind::<diesel::sql_types::Text, _>(sid),
+                        .bind::<diesel::sql_types::Text, _>(sid)
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -1311,9 +1757,11 @@ impl DieselDocumentRepository {
                         diesel::sql_query(format!(
                             r#"SELECT id FROM documents
                                WHERE metadata->>'estimated_date' IS NULL
+                               AND source_id NOT IN (SELECT jsonb_array_elements_text($1::jsonb))
                                LIMIT {}"#,
                             limit
-                        )),
+                        ))
+                        .bind::<diesel::sql_types::Text, _>(excluded.as_str()),
                         &mut conn,
                     )
                     .await
@@ -1335,7 +1783,12 @@ impl DieselDocumentRepository {
         source: &str,
     ) -> Result<(), DieselError> {
         let record: Option<DocumentRecord> = with_conn!(self.pool, conn, {
-            documents::table.find(id).first(&mut conn).await.optional()
+            Projection::Metadata
+                .documents_query()
+                .filter(documents::id.eq(id))
+                .first(&mut conn)
+                .await
+                .optional()
         })?;
 
         if let Some(record) = record {
@@ -1374,7 +1827,12 @@ impl DieselDocumentRepository {
         error: Option<&str>,
     ) -> Result<(), DieselError> {
         let record: Option<DocumentRecord> = with_conn!(self.pool, conn, {
-            documents::table.find(id).first(&mut conn).await.optional()
+            Projection::Metadata
+                .documents_query()
+                .filter(documents::id.eq(id))
+                .first(&mut conn)
+                .await
+                .optional()
         })?;
 
         if let Some(record) = record {
@@ -1413,11 +1871,21 @@ impl DieselDocumentRepository {
     /// Get documents needing summarization.
     pub async fn get_needing_summarization(
         &self,
+        source_id: Option<&str>,
+        exclude_sources: &[String],
         limit: usize,
     ) -> Result<Vec<Document>, DieselError> {
         let records: Vec<DocumentRecord> = with_conn!(self.pool, conn, {
-            documents::table
+            let mut query = documents::table
                 .filter(documents::status.eq("ocr_complete"))
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(documents::source_id.eq(sid));
+            }
+            if !exclude_sources.is_empty() {
+                query = query.filter(documents::source_id.ne_all(exclude_sources));
+            }
+            query
                 .order(documents::updated_at.asc())
                 .limit(limit as i64)
                 .load(&mut conn)
@@ -1431,7 +1899,7 @@ impl DieselDocumentRepository {
     #[allow(dead_code)]
     #[deprecated(note = "Use get_needing_analysis(\"ocr\", ...) instead")]
     pub async fn get_needing_ocr(&self, limit: usize) -> Result<Vec<Document>, DieselError> {
-        self.get_needing_analysis("ocr", limit, None, None, None, 12)
+        self.get_needing_analysis("ocr", limit, None, None, None, 12, &[])
             .await
     }
 
@@ -1449,6 +1917,7 @@ impl DieselDocumentRepository {
         mime_type: Option<&str>,
         after_id: Option<&str>,
         retry_interval_hours: u32,
+        exclude_sources: &[String],
     ) -> Result<Vec<Document>, DieselError> {
         use crate::schema::{document_analysis_results as dar, document_versions};
         use diesel::dsl::{exists, not};
@@ -1489,8 +1958,17 @@ impl DieselDocumentRepository {
             if let Some(sid) = source_id {
                 query = query.filter(documents::source_id.eq(sid));
             }
+            if !exclude_sources.is_empty() {
+                query = query.filter(documents::source_id.ne_all(exclude_sources));
+            }
             if let Some(mime) = mime_type {
-                query = query.filter(document_versions::mime_type.eq(mime));
+                // "audio/*" matches every audio subtype
+                query = match mime.strip_suffix('*') {
+                    Some(prefix) => {
+                        query.filter(document_versions::mime_type.like(format!("{}%", prefix)))
+                    }
+                    None => query.filter(document_versions::mime_type.eq(mime)),
+                };
             }
             if let Some(cursor) = after_id {
                 query = query.filter(documents::id.gt(cursor));
@@ -1508,8 +1986,10 @@ impl DieselDocumentRepository {
             return Ok(vec![]);
         }
 
+        // Analysis reads files and pages, never the stored text
         let records: Vec<DocumentRecord> = with_conn!(self.pool, conn, {
-            documents::table
+            Projection::Metadata
+                .documents_query()
                 .filter(documents::id.eq_any(&ids))
                 .order(documents::id.asc())
                 .load(&mut conn)
@@ -1528,7 +2008,7 @@ impl DieselDocumentRepository {
         mime_type: Option<&str>,
         after_id: Option<&str>,
     ) -> Result<Vec<Document>, DieselError> {
-        self.get_needing_analysis("ocr", limit, source_id, mime_type, after_id, 12)
+        self.get_needing_analysis("ocr", limit, source_id, mime_type, after_id, 12, &[])
             .await
     }
 
@@ -1578,6 +2058,55 @@ impl DieselDocumentRepository {
         Ok(count)
     }
 
+    /// Move documents in status `from` back to the earlier status `to`.
+    ///
+    /// Only requeue edges allowed by [`DocumentStatus::can_requeue_to`] are
+    /// accepted. Synopsis and tags are cleared since every requeue target
+    /// precedes annotation. Returns the number of documents moved.
+    pub async fn requeue(
+        &self,
+        from: DocumentStatus,
+        to: DocumentStatus,
+        source_id: Option<&str>,
+    ) -> Result<u64, DieselError> {
+        if !from.can_requeue_to(to) {
+            return Err(diesel::result::Error::QueryBuilderError(
+                format!(
+                    "cannot requeue documents from {} to {}",
+                    from.as_str(),
+                    to.as_str()
+                )
+                .into(),
+            ));
+        }
+        let now = Utc::now().to_rfc3339();
+
+        let count = retry_on_busy(|| async {
+            with_conn!(self.pool, conn, {
+                let mut query = diesel::update(documents::table)
+                    .filter(documents::status.eq(from.as_str()))
+                    .into_boxed();
+
+                if let Some(sid) = source_id {
+                    query = query.filter(documents::source_id.eq(sid));
+                }
+
+                query
+                    .set((
+                        documents::status.eq(to.as_str()),
+                        documents::synopsis.eq(None::<String>),
+                        documents::tags.eq(None::<String>),
+                        documents::updated_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await
+            })
+        })
+        .await?;
+
+        Ok(count as u64)
+    }
+
     /// Count documents that have been annotated (status = indexed).
     pub async fn count_annotated(&self, source_id: Option<&str>) -> Result<u64, DieselError> {
         with_conn!(self.pool, conn, {
@@ -1606,17 +2135,55 @@ impl DieselDocumentRepository {
     ) -> Result<(), DieselError> {
         let now = Utc::now().to_rfc3339();
         let tags_json = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+        let indexable: Vec<&str> = DocumentStatus::Indexed
+            .predecessors()
+            .iter()
+            .map(|s| s.as_str())
+            .collect();
 
         with_conn!(self.pool, conn, {
             diesel::update(documents::table.find(id))
                 .set((
                     documents::synopsis.eq(synopsis),
                     documents::tags.eq(&tags_json),
-                    documents::status.eq("indexed"),
                     documents::updated_at.eq(&now),
                 ))
                 .execute(&mut conn)
                 .await?;
+            // Pending or failed documents keep their status; the annotation
+            // is stored but doesn't mark them as processed.
+            diesel::update(
+                documents::table
+                    .find(id)
+                    .filter(documents::status.eq_any(&indexable)),
+            )
+            .set(documents::status.eq(DocumentStatus::Indexed.as_str()))
+            .execute(&mut conn)
+            .await?;
+            Ok(())
+        })
+    }
+
+    /// Overwrite extracted text, synopsis and tags without touching status or
+    /// `updated_at`. Used when copying a document from another instance.
+    pub async fn update_content_fields(
+        &self,
+        id: &str,
+        extracted_text: Option<&str>,
+        synopsis: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), DieselError> {
+        let tags_json = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+
+        with_conn!(self.pool, conn, {
+            diesel::update(documents::table.find(id))
+                .set((
+                    documents::extracted_text.eq(extracted_text),
+                    documents::synopsis.eq(synopsis),
+                    documents::tags.eq(&tags_json),
+                ))
+                .execute(&mut conn)
+                .await?;
             Ok(())
         })
     }
@@ -1659,8 +2226,56 @@ mod tests {
         let repo = DieselDocumentRepository::new(pool);
 
         let result = repo
-            .count_documents_needing_annotation("'; DROP TABLE documents; --", 1, None)
+            .count_documents_needing_annotation("'; DROP TABLE documents; --", 1, None, &[])
             .await;
         assert!(result.is_err());
     }
+
+    fn annotation_doc(id: &str, source_id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: id.to_string(),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Pending,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_annotation_queries_skip_excluded_sources() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        repo.save(&annotation_doc("doc-a", "docs")).await.unwrap();
+        repo.save(&annotation_doc("doc-b", "videos")).await.unwrap();
+        let excluded = vec!["videos".to_string()];
+
+        for annotation_type in ["ner_extraction", "date_detection"] {
+            let count = repo
+                .count_documents_needing_annotation(annotation_type, 1, None, &excluded)
+                .await
+                .unwrap();
+            assert_eq!(count, 1, "{}", annotation_type);
+
+            let docs = repo
+                .get_documents_needing_annotation(annotation_type, 1, None, &excluded, 10)
+                .await
+                .unwrap();
+            assert_eq!(docs.len(), 1, "{}", annotation_type);
+            assert_eq!(docs[0].id, "doc-a");
+        }
+
+        let count = repo
+            .count_documents_needing_annotation("ner_extraction", 1, None, &[])
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+    }
 }
@@ -0,0 +1,365 @@
+//! Typed relations between documents (attachments, exhibits, supersession).
+
+use std::collections::{HashMap, HashSet};
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::RelationType;
+use crate::repository::models::{DocumentRelationRecord, NewDocumentRelation};
+use crate::repository::pool::DieselError;
+use crate::schema::{document_relations, documents};
+use crate::{with_conn, with_conn_split};
+
+/// Deepest traversal allowed by [`DieselDocumentRepository::get_relation_graph`].
+pub const MAX_RELATION_DEPTH: u32 = 5;
+
+/// Traversal stops adding documents past this many.
+const MAX_GRAPH_NODES: usize = 500;
+
+/// One relation seen from a particular document.
+#[derive(Debug, Clone)]
+pub struct RelatedDocument {
+    pub relation_id: i32,
+    pub relation_type: RelationType,
+    /// True when the edge starts at the viewed document
+    /// ("this document is an attachment of ...").
+    pub outgoing: bool,
+    /// The document on the other end.
+    pub document_id: String,
+    pub title: String,
+    pub virtual_file_id: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+impl RelatedDocument {
+    /// Human-readable label from the viewed document's side.
+    pub fn label(&self) -> &'static str {
+        if self.outgoing {
+            self.relation_type.label()
+        } else {
+            self.relation_type.inverse_label()
+        }
+    }
+}
+
+/// Documents reachable from a starting document and the edges between them.
+#[derive(Debug, Clone, Default)]
+pub struct RelationGraph {
+    /// `(document_id, title)`, starting document first.
+    pub nodes: Vec<(String, String)>,
+    pub edges: Vec<DocumentRelationRecord>,
+    /// True when the node cap cut the traversal short.
+    pub truncated: bool,
+}
+
+impl DieselDocumentRepository {
+    /// Add a relation. Returns false if the same typed edge already exists.
+    pub async fn add_relation(
+        &self,
+        relation: &NewDocumentRelation<'_>,
+    ) -> Result<bool, DieselError> {
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                let inserted = diesel::insert_or_ignore_into(document_relations::table)
+                    .values(relation)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(inserted > 0)
+            },
+            postgres: conn => {
+                let inserted = diesel::insert_into(document_relations::table)
+                    .values(relation)
+                    .on_conflict_do_nothing()
+                    .execute(&mut conn)
+                    .await?;
+                Ok(inserted > 0)
+            }
+        )
+    }
+
+    /// Look up the edge of `relation_type` from `doc_id` to `related_id`.
+    pub async fn find_relation(
+        &self,
+        doc_id: &str,
+        related_id: &str,
+        relation_type: RelationType,
+    ) -> Result<Option<DocumentRelationRecord>, DieselError> {
+        with_conn!(self.pool, conn, {
+            document_relations::table
+                .filter(document_relations::document_id.eq(doc_id))
+                .filter(document_relations::related_document_id.eq(related_id))
+                .filter(document_relations::relation_type.eq(relation_type.as_str()))
+                .first(&mut conn)
+                .await
+                .optional()
+        })
+    }
+
+    /// Delete a relation touching `doc_id` on either end.
+    /// Returns true if a row was removed.
+    pub async fn delete_relation(&self, doc_id: &str, id: i32) -> Result<bool, DieselError> {
+        with_conn!(self.pool, conn, {
+            let deleted = diesel::delete(
+                document_relations::table
+                    .filter(document_relations::id.eq(id))
+                    .filter(
+                        document_relations::document_id
+                            .eq(doc_id)
+                            .or(document_relations::related_document_id.eq(doc_id)),
+                    ),
+            )
+            .execute(&mut conn)
+            .await?;
+            Ok(deleted > 0)
+        })
+    }
+
+    /// Relations in both directions for a document, with the other end's title.
+    pub async fn get_relations(&self, doc_id: &str) -> Result<Vec<RelatedDocument>, DieselError> {
+        let records = self.relations_touching(&[doc_id.to_string()]).await?;
+        let other_ids: Vec<String> = records
+            .iter()
+            .map(|r| {
+                if r.document_id == doc_id {
+                    r.related_document_id.clone()
+                } else {
+                    r.document_id.clone()
+                }
+            })
+            .collect();
+        let titles = self.titles_for(&other_ids).await?;
+
+        let mut related: Vec<RelatedDocument> = records
+            .into_iter()
+            .filter_map(|r| {
+                let relation_type = RelationType::from_str(&r.relation_type)?;
+                let outgoing = r.document_id == doc_id;
+                let other = if outgoing {
+                    r.related_document_id
+                } else {
+                    r.document_id
+                };
+                // Skip edges whose other end has been deleted
+                let title = titles.get(&other)?.clone();
+                Some(RelatedDocument {
+                    relation_id: r.id,
+                    relation_type,
+                    outgoing,
+                    document_id: other,
+                    title,
+                    virtual_file_id: r.virtual_file_id,
+                    note: r.note,
+                    created_at: r.created_at,
+                })
+            })
+            .collect();
+        related.sort_by(|a, b| {
+            (a.label(), &a.title, &a.document_id).cmp(&(b.label(), &b.title, &b.document_id))
+        });
+        Ok(related)
+    }
+
+    /// Breadth-first walk of relations from `doc_id`, following edges in both
+    /// directions up to `depth` hops. An empty `types` follows every type.
+    pub async fn get_relation_graph(
+        &self,
+        doc_id: &str,
+        depth: u32,
+        types: &[RelationType],
+    ) -> Result<RelationGraph, DieselError> {
+        let depth = depth.min(MAX_RELATION_DEPTH);
+        let mut order = vec![doc_id.to_string()];
+        let mut seen: HashSet<String> = order.iter().cloned().collect();
+        let mut edge_ids = HashSet::new();
+        let mut edges = Vec::new();
+        let mut frontier = order.clone();
+        let mut truncated = false;
+
+        for _ in 0..depth {
+            if frontier.is_empty() {
+                break;
+            }
+            let mut next = Vec::new();
+            for record in self.relations_touching(&frontier).await? {
+                let followed = match RelationType::from_str(&record.relation_type) {
+                    Some(t) => types.is_empty() || types.contains(&t),
+                    None => false,
+                };
+                if !followed || !edge_ids.insert(record.id) {
+                    continue;
+                }
+                for id in [&record.document_id, &record.related_document_id] {
+                    if seen.contains(id) {
+                        continue;
+                    }
+                    if seen.len() >= MAX_GRAPH_NODES {
+                        truncated = true;
+                        continue;
+                    }
+                    seen.insert(id.clone());
+                    order.push(id.clone());
+                    next.push(id.clone());
+                }
+                if seen.contains(&record.document_id) && seen.contains(&record.related_document_id)
+                {
+                    edges.push(record);
+                }
+            }
+            frontier = next;
+        }
+
+        let titles = self.titles_for(&order).await?;
+        let nodes: Vec<(String, String)> = order
+            .into_iter()
+            .filter_map(|id| titles.get(&id).cloned().map(|title| (id, title)))
+            .collect();
+        let present: HashSet<&str> = nodes.iter().map(|(id, _)| id.as_str()).collect();
+        edges.retain(|e| {
+            present.contains(e.document_id.as_str())
+                && present.contains(e.related_document_id.as_str())
+        });
+
+        Ok(RelationGraph {
+            nodes,
+            edges,
+            truncated,
+        })
+    }
+
+    async fn relations_touching(
+        &self,
+        doc_ids: &[String],
+    ) -> Result<Vec<DocumentRelationRecord>, DieselError> {
+        if doc_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        with_conn!(self.pool, conn, {
+            document_relations::table
+                .filter(
+                    document_relations::document_id
+                        .eq_any(doc_ids)
+                        .or(document_relations::related_document_id.eq_any(doc_ids)),
+                )
+                .order(document_relations::id.asc())
+                .load(&mut conn)
+                .await
+        })
+    }
+
+    async fn titles_for(&self, doc_ids: &[String]) -> Result<HashMap<String, String>, DieselError> {
+        if doc_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows: Vec<(String, String)> = with_conn!(self.pool, conn, {
+            documents::table
+                .filter(documents::id.eq_any(doc_ids))
+                .select((documents::id, documents::title))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use chrono::Utc;
+
+    fn doc(id: &str, title: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: "test-source".to_string(),
+            title: title.to_string(),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relation_crud_and_traversal() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        for (id, title) in [
+            ("email", "Response letter"),
+            ("memo", "Enclosed memo"),
+            ("report", "Final report"),
+            ("draft", "Draft report"),
+        ] {
+            repo.save(&doc(id, title)).await.unwrap();
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let attachment = NewDocumentRelation {
+            document_id: "memo",
+            related_document_id: "email",
+            relation_type: RelationType::AttachmentOf.as_str(),
+            virtual_file_id: None,
+            note: None,
+            created_at: &now,
+        };
+        assert!(repo.add_relation(&attachment).await.unwrap());
+        // Same typed edge again is ignored
+        assert!(!repo.add_relation(&attachment).await.unwrap());
+        assert!(repo
+            .add_relation(&NewDocumentRelation {
+                document_id: "memo",
+                related_document_id: "report",
+                relation_type: RelationType::References.as_str(),
+                ..attachment
+            })
+            .await
+            .unwrap());
+        assert!(repo
+            .add_relation(&NewDocumentRelation {
+                document_id: "report",
+                related_document_id: "draft",
+                relation_type: RelationType::Supersedes.as_str(),
+                note: Some("released after appeal"),
+                ..attachment
+            })
+            .await
+            .unwrap());
+
+        let related = repo.get_relations("email").await.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].document_id, "memo");
+        assert_eq!(related[0].title, "Enclosed memo");
+        assert!(!related[0].outgoing);
+        assert_eq!(related[0].label(), "Has attachment");
+
+        let graph = repo.get_relation_graph("email", 1, &[]).await.unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].0, "email");
+        assert_eq!(graph.edges.len(), 1);
+
+        let graph = repo.get_relation_graph("email", 3, &[]).await.unwrap();
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edges.len(), 3);
+
+        let graph = repo
+            .get_relation_graph("email", 3, &[RelationType::AttachmentOf])
+            .await
+            .unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+
+        // Deleting through an unrelated document is a no-op
+        let id = related[0].relation_id;
+        assert!(!repo.delete_relation("draft", id).await.unwrap());
+        assert!(repo.delete_relation("email", id).await.unwrap());
+        assert!(repo.get_relations("email").await.unwrap().is_empty());
+    }
+}
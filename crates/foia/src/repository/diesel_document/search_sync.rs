@@ -0,0 +1,112 @@
+//! Text search indexes store besides a document's own: what was extracted
+//! from its archive members and attachments.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::EMAIL_BODY_PLACEHOLDER;
+use crate::repository::pool::DieselError;
+use crate::schema::virtual_files;
+use crate::with_conn;
+
+impl DieselDocumentRepository {
+    /// Extracted text of each document's archive members and attachments,
+    /// headed by their file names.
+    pub async fn get_virtual_file_texts(
+        &self,
+        doc_ids: &[String],
+    ) -> Result<HashMap<String, Vec<String>>, DieselError> {
+        if doc_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows: Vec<(String, String, String)> = with_conn!(self.pool, conn, {
+            virtual_files::table
+                .filter(virtual_files::document_id.eq_any(doc_ids))
+                .filter(virtual_files::archive_path.ne(EMAIL_BODY_PLACEHOLDER))
+                .filter(virtual_files::extracted_text.is_not_null())
+                .order((
+                    virtual_files::document_id.asc(),
+                    virtual_files::archive_path.asc(),
+                ))
+                .select((
+                    virtual_files::document_id,
+                    virtual_files::filename,
+                    virtual_files::extracted_text.assume_not_null(),
+                ))
+                .load(&mut conn)
+                .await
+        })?;
+
+        let mut texts: HashMap<String, Vec<String>> = HashMap::new();
+        for (document_id, filename, text) in rows {
+            texts
+                .entry(document_id)
+                .or_default()
+                .push(format!("{}\n{}", filename, text));
+        }
+        Ok(texts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus, VirtualFile, VirtualFileStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use chrono::Utc;
+
+    fn document(id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: "agency".to_string(),
+            title: "Release bundle".to_string(),
+            source_url: format!("https://agency.gov/{}.zip", id),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Pending,
+            metadata: serde_json::json!({}),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_attachment_text() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        repo.save(&document("doc-1")).await.unwrap();
+        repo.save(&document("doc-2")).await.unwrap();
+        let before = repo.latest_change_seq().await.unwrap();
+
+        let mut vf = VirtualFile::new(
+            "doc-1".to_string(),
+            1,
+            "letters/a.pdf".to_string(),
+            "a.pdf".to_string(),
+            "application/pdf".to_string(),
+            1024,
+        );
+        vf.status = VirtualFileStatus::OcrComplete;
+        vf.extracted_text = Some("Dear requester".to_string());
+        repo.insert_virtual_file(&vf).await.unwrap();
+
+        let texts = repo
+            .get_virtual_file_texts(&["doc-1".to_string(), "doc-2".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(texts.len(), 1);
+        assert_eq!(texts["doc-1"], vec!["a.pdf\nDear requester"]);
+
+        // New attachments count as changes of their document
+        let changes = repo.get_changes_since(before, 10).await.unwrap();
+        assert!(!changes.is_empty());
+        assert!(changes.iter().all(|c| c.document_id == "doc-1"));
+    }
+}
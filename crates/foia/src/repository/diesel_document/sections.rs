@@ -0,0 +1,177 @@
+//! Table of contents of document versions.
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::DocumentSection;
+use crate::repository::pool::DieselError;
+use crate::schema::document_sections;
+use crate::with_conn;
+
+type SectionRow = (i32, String, i32, i32, String, i32, i32, String);
+
+fn from_row(row: SectionRow) -> DocumentSection {
+    let (id, document_id, version_id, level, title, first_page, last_page, origin) = row;
+    DocumentSection {
+        id: id as i64,
+        document_id,
+        version_id: version_id as i64,
+        level: level as u32,
+        title,
+        first_page: first_page as u32,
+        last_page: last_page as u32,
+        origin,
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Replace the table of contents of a document version.
+    pub async fn replace_document_sections(
+        &self,
+        doc_id: &str,
+        version_id: i64,
+        sections: &[DocumentSection],
+    ) -> Result<(), DieselError> {
+        with_conn!(self.pool, conn, {
+            diesel::delete(
+                document_sections::table
+                    .filter(document_sections::document_id.eq(doc_id))
+                    .filter(document_sections::version_id.eq(version_id as i32)),
+            )
+            .execute(&mut conn)
+            .await?;
+            for (position, section) in sections.iter().enumerate() {
+                diesel::insert_into(document_sections::table)
+                    .values((
+                        document_sections::document_id.eq(doc_id),
+                        document_sections::version_id.eq(version_id as i32),
+                        document_sections::position.eq(position as i32),
+                        document_sections::level.eq(section.level as i32),
+                        document_sections::title.eq(&section.title),
+                        document_sections::first_page.eq(section.first_page as i32),
+                        document_sections::last_page.eq(section.last_page as i32),
+                        document_sections::origin.eq(&section.origin),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Sections of a document version, in reading order.
+    pub async fn get_document_sections(
+        &self,
+        doc_id: &str,
+        version_id: i64,
+    ) -> Result<Vec<DocumentSection>, DieselError> {
+        let rows: Vec<SectionRow> = with_conn!(self.pool, conn, {
+            document_sections::table
+                .filter(document_sections::document_id.eq(doc_id))
+                .filter(document_sections::version_id.eq(version_id as i32))
+                .select((
+                    document_sections::id,
+                    document_sections::document_id,
+                    document_sections::version_id,
+                    document_sections::level,
+                    document_sections::title,
+                    document_sections::first_page,
+                    document_sections::last_page,
+                    document_sections::origin,
+                ))
+                .order(document_sections::position.asc())
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().map(from_row).collect())
+    }
+
+    /// A single section of a document, by id.
+    pub async fn get_document_section(
+        &self,
+        doc_id: &str,
+        section_id: i64,
+    ) -> Result<Option<DocumentSection>, DieselError> {
+        let row: Option<SectionRow> = with_conn!(self.pool, conn, {
+            document_sections::table
+                .filter(document_sections::id.eq(section_id as i32))
+                .filter(document_sections::document_id.eq(doc_id))
+                .select((
+                    document_sections::id,
+                    document_sections::document_id,
+                    document_sections::version_id,
+                    document_sections::level,
+                    document_sections::title,
+                    document_sections::first_page,
+                    document_sections::last_page,
+                    document_sections::origin,
+                ))
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(row.map(from_row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    fn section(level: u32, title: &str, first_page: u32, last_page: u32) -> DocumentSection {
+        DocumentSection {
+            id: 0,
+            document_id: "report".to_string(),
+            version_id: 1,
+            level,
+            title: title.to_string(),
+            first_page,
+            last_page,
+            origin: "bookmarks".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_document_sections() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        repo.replace_document_sections("report", 1, &[section(1, "Old", 1, 9)])
+            .await
+            .unwrap();
+        repo.replace_document_sections(
+            "report",
+            1,
+            &[
+                section(1, "Introduction", 1, 4),
+                section(1, "Findings", 5, 20),
+                section(2, "Methodology", 5, 8),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let sections = repo.get_document_sections("report", 1).await.unwrap();
+        let titles: Vec<&str> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Introduction", "Findings", "Methodology"]);
+        assert!(repo
+            .get_document_sections("report", 2)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let found = repo
+            .get_document_section("report", sections[2].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((found.level, found.first_page, found.last_page), (2, 5, 8));
+        assert!(repo
+            .get_document_section("other", sections[2].id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
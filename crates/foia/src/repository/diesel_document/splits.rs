@@ -0,0 +1,163 @@
+//! Documents split out of multi-document bundles.
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::DocumentSplit;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::document_splits;
+use crate::{with_conn, with_conn_split};
+
+type SplitRow = (String, String, i32, i32, i32, Option<String>, String);
+
+fn from_row(row: SplitRow) -> DocumentSplit {
+    let (document_id, parent_id, version_id, first_page, last_page, boundary, created_at) = row;
+    DocumentSplit {
+        document_id,
+        parent_id,
+        version_id: version_id as i64,
+        first_page: first_page as u32,
+        last_page: last_page as u32,
+        boundary,
+        created_at: parse_datetime(&created_at),
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Record that a document was cut out of a bundle, replacing any
+    /// earlier record for it.
+    pub async fn record_document_split(&self, split: &DocumentSplit) -> Result<(), DieselError> {
+        let created_at = split.created_at.to_rfc3339();
+        let values = (
+            document_splits::document_id.eq(&split.document_id),
+            document_splits::parent_id.eq(&split.parent_id),
+            document_splits::version_id.eq(split.version_id as i32),
+            document_splits::first_page.eq(split.first_page as i32),
+            document_splits::last_page.eq(split.last_page as i32),
+            document_splits::boundary.eq(split.boundary.as_deref()),
+            document_splits::created_at.eq(&created_at),
+        );
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(document_splits::table)
+                    .values(values)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(document_splits::table)
+                    .values(values)
+                    .on_conflict(document_splits::document_id)
+                    .do_update()
+                    .set((
+                        document_splits::parent_id.eq(&split.parent_id),
+                        document_splits::version_id.eq(split.version_id as i32),
+                        document_splits::first_page.eq(split.first_page as i32),
+                        document_splits::last_page.eq(split.last_page as i32),
+                        document_splits::boundary.eq(split.boundary.as_deref()),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// Documents split out of a bundle, in page order.
+    pub async fn get_document_splits(
+        &self,
+        parent_id: &str,
+    ) -> Result<Vec<DocumentSplit>, DieselError> {
+        let rows: Vec<SplitRow> = with_conn!(self.pool, conn, {
+            document_splits::table
+                .filter(document_splits::parent_id.eq(parent_id))
+                .select((
+                    document_splits::document_id,
+                    document_splits::parent_id,
+                    document_splits::version_id,
+                    document_splits::first_page,
+                    document_splits::last_page,
+                    document_splits::boundary,
+                    document_splits::created_at,
+                ))
+                .order(document_splits::first_page.asc())
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().map(from_row).collect())
+    }
+
+    /// Where a document was cut from, if it came out of a bundle.
+    pub async fn get_split_origin(
+        &self,
+        doc_id: &str,
+    ) -> Result<Option<DocumentSplit>, DieselError> {
+        let row: Option<SplitRow> = with_conn!(self.pool, conn, {
+            document_splits::table
+                .find(doc_id)
+                .select((
+                    document_splits::document_id,
+                    document_splits::parent_id,
+                    document_splits::version_id,
+                    document_splits::first_page,
+                    document_splits::last_page,
+                    document_splits::boundary,
+                    document_splits::created_at,
+                ))
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(row.map(from_row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use chrono::Utc;
+
+    fn split(document_id: &str, first_page: u32, last_page: u32) -> DocumentSplit {
+        DocumentSplit {
+            document_id: document_id.to_string(),
+            parent_id: "bundle".to_string(),
+            version_id: 1,
+            first_page,
+            last_page,
+            boundary: (first_page > 1).then(|| "letterhead".to_string()),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_document_splits() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        repo.record_document_split(&split("b", 13, 40))
+            .await
+            .unwrap();
+        repo.record_document_split(&split("a", 1, 12))
+            .await
+            .unwrap();
+        repo.record_document_split(&split("b", 13, 41))
+            .await
+            .unwrap();
+
+        let splits = repo.get_document_splits("bundle").await.unwrap();
+        let ranges: Vec<(&str, u32, u32)> = splits
+            .iter()
+            .map(|s| (s.document_id.as_str(), s.first_page, s.last_page))
+            .collect();
+        assert_eq!(ranges, vec![("a", 1, 12), ("b", 13, 41)]);
+
+        let origin = repo.get_split_origin("b").await.unwrap().unwrap();
+        assert_eq!(origin.parent_id, "bundle");
+        assert_eq!(origin.boundary.as_deref(), Some("letterhead"));
+        assert!(repo.get_split_origin("bundle").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,70 @@
+//! Rebuilding the trigger-maintained statistics tables.
+//!
+//! `source_status_counts`, `tag_counts` and `mime_type_counts` are kept
+//! current by triggers on `documents` and `document_versions`. Bulk changes
+//! that skip row triggers (`TRUNCATE`, restoring a dump taken without them)
+//! leave them stale; rebuilding recomputes them from the rows.
+
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+use super::DieselDocumentRepository;
+use crate::repository::pool::DieselError;
+use crate::with_conn;
+
+const CLEAR: [&str; 3] = [
+    "DELETE FROM source_status_counts",
+    "DELETE FROM tag_counts",
+    "DELETE FROM mime_type_counts",
+];
+
+const SOURCE_STATUS_COUNTS: &str = r#"INSERT INTO source_status_counts (source_id, status, count)
+SELECT source_id, status, COUNT(*) FROM documents
+GROUP BY source_id, status"#;
+
+const MIME_TYPE_COUNTS: &str = r#"INSERT INTO mime_type_counts (mime_type, count)
+SELECT dv.mime_type, COUNT(*)
+FROM document_versions dv
+INNER JOIN (
+    SELECT document_id, MAX(id) AS max_id
+    FROM document_versions
+    GROUP BY document_id
+) latest ON dv.id = latest.max_id
+GROUP BY dv.mime_type"#;
+
+const SQLITE_TAG_COUNTS: &str = r#"INSERT INTO tag_counts (tag, count)
+SELECT value, COUNT(DISTINCT documents.id)
+FROM documents, json_each(CASE WHEN json_valid(documents.tags) THEN documents.tags ELSE '[]' END)
+GROUP BY value"#;
+
+const POSTGRES_TAG_COUNTS: &str = r#"INSERT INTO tag_counts (tag, count)
+SELECT tag, COUNT(DISTINCT documents.id)
+FROM documents, jsonb_array_elements_text(documents.tags::jsonb) AS tag
+WHERE documents.tags IS NOT NULL AND documents.tags != '[]'
+GROUP BY tag"#;
+
+impl DieselDocumentRepository {
+    /// Recompute the statistics tables from `documents` and
+    /// `document_versions` in a single transaction.
+    pub async fn rebuild_stats(&self) -> Result<(), DieselError> {
+        let tag_counts = if self.pool.is_sqlite() {
+            SQLITE_TAG_COUNTS
+        } else {
+            POSTGRES_TAG_COUNTS
+        };
+
+        with_conn!(self.pool, conn, {
+            conn.transaction(|conn| {
+                Box::pin(async move {
+                    for sql in CLEAR {
+                        diesel::sql_query(sql).execute(conn).await?;
+                    }
+                    for sql in [SOURCE_STATUS_COUNTS, tag_counts, MIME_TYPE_COUNTS] {
+                        diesel::sql_query(sql).execute(conn).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .await
+        })
+    }
+}
@@ -0,0 +1,236 @@
+//! Aggregate storage usage for the storage report.
+//!
+//! Sizes come from the recorded `file_size` of each version, leaving out
+//! versions whose file is lost or pruned. Text sizes are the stored bytes
+//! of the text columns, compressed or not.
+
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::DocumentStatus;
+use crate::repository::pool::DieselError;
+use crate::schema::documents;
+use crate::services::storage_report::{ArtifactUsage, Usage};
+use crate::with_conn;
+
+/// Versions whose file is still expected on disk.
+const STORED_VERSIONS: &str = "v.id NOT IN (SELECT version_id FROM lost_files)";
+
+#[derive(diesel::QueryableByName)]
+struct UsageRow {
+    #[diesel(sql_type = Text)]
+    key: String,
+    #[diesel(sql_type = BigInt)]
+    files: i64,
+    #[diesel(sql_type = BigInt)]
+    bytes: i64,
+}
+
+impl From<UsageRow> for Usage {
+    fn from(row: UsageRow) -> Self {
+        Self {
+            key: row.key,
+            files: row.files as u64,
+            bytes: row.bytes as u64,
+        }
+    }
+}
+
+#[derive(diesel::QueryableByName)]
+struct Total {
+    #[diesel(sql_type = BigInt)]
+    total: i64,
+}
+
+impl DieselDocumentRepository {
+    /// Stored originals grouped by source, largest first.
+    pub async fn storage_by_source(&self) -> Result<Vec<Usage>, DieselError> {
+        self.grouped_storage("d.source_id").await
+    }
+
+    /// Stored originals grouped by MIME type, largest first.
+    pub async fn storage_by_mime_type(&self) -> Result<Vec<Usage>, DieselError> {
+        self.grouped_storage("v.mime_type").await
+    }
+
+    async fn grouped_storage(&self, column: &str) -> Result<Vec<Usage>, DieselError> {
+        let sql = format!(
+            "SELECT {column} AS key, CAST(COUNT(*) AS BIGINT) AS files, \
+             CAST(COALESCE(SUM(v.file_size), 0) AS BIGINT) AS bytes \
+             FROM document_versions v JOIN documents d ON d.id = v.document_id \
+             WHERE {STORED_VERSIONS} \
+             GROUP BY {column} ORDER BY bytes DESC, key"
+        );
+        let rows: Vec<UsageRow> = with_conn!(self.pool, conn, {
+            diesel::sql_query(&sql).load(&mut conn).await
+        })?;
+        Ok(rows.into_iter().map(Usage::from).collect())
+    }
+
+    /// Stored originals of documents whose processing failed.
+    pub async fn failed_document_storage(&self) -> Result<Usage, DieselError> {
+        let sql = format!(
+            "SELECT 'failed' AS key, CAST(COUNT(*) AS BIGINT) AS files, \
+             CAST(COALESCE(SUM(v.file_size), 0) AS BIGINT) AS bytes \
+             FROM document_versions v JOIN documents d ON d.id = v.document_id \
+             WHERE d.status = 'failed' AND {STORED_VERSIONS}"
+        );
+        let row: UsageRow = with_conn!(self.pool, conn, {
+            diesel::sql_query(&sql).get_result(&mut conn).await
+        })?;
+        Ok(row.into())
+    }
+
+    /// IDs of documents whose processing failed.
+    pub async fn failed_document_ids(&self) -> Result<Vec<String>, DieselError> {
+        with_conn!(self.pool, conn, {
+            documents::table
+                .filter(documents::status.eq(DocumentStatus::Failed.as_str()))
+                .select(documents::id)
+                .order(documents::id.asc())
+                .load(&mut conn)
+                .await
+        })
+    }
+
+    /// Extra copies of identical content: every stored version beyond the
+    /// first with the same content hash.
+    pub async fn duplicate_storage(&self) -> Result<Usage, DieselError> {
+        let sql = format!(
+            "SELECT 'duplicates' AS key, \
+             CAST(COALESCE(SUM(dup.copies - 1), 0) AS BIGINT) AS files, \
+             CAST(COALESCE(SUM(dup.total - dup.one), 0) AS BIGINT) AS bytes \
+             FROM (SELECT COUNT(*) AS copies, SUM(v.file_size) AS total, \
+                   MAX(v.file_size) AS one \
+                   FROM document_versions v WHERE {STORED_VERSIONS} \
+                   GROUP BY v.content_hash HAVING COUNT(*) > 1) dup"
+        );
+        let row: UsageRow = with_conn!(self.pool, conn, {
+            diesel::sql_query(&sql).get_result(&mut conn).await
+        })?;
+        Ok(row.into())
+    }
+
+    /// Bytes held in the database as extracted text and as intermediate
+    /// OCR and analysis output. `originals` is left for the caller.
+    pub async fn text_storage(&self) -> Result<ArtifactUsage, DieselError> {
+        let len = |column: &str| {
+            if self.pool.is_sqlite() {
+                format!("COALESCE(SUM(LENGTH(CAST({column} AS BLOB))), 0)")
+            } else {
+                format!("COALESCE(SUM(OCTET_LENGTH({column})), 0)")
+            }
+        };
+        let text = format!(
+            "SELECT CAST((SELECT {} FROM documents) + (SELECT {} FROM document_pages) \
+             AS BIGINT) AS total",
+            len("extracted_text"),
+            len("final_text"),
+        );
+        let derived = format!(
+            "SELECT CAST((SELECT {} + {} FROM document_pages) \
+             + (SELECT {} FROM page_ocr_results) \
+             + (SELECT {} FROM document_analysis_results) AS BIGINT) AS total",
+            len("pdf_text"),
+            len("ocr_text"),
+            len("text"),
+            len("result_text"),
+        );
+
+        with_conn!(self.pool, conn, {
+            let text: Total = diesel::sql_query(&text).get_result(&mut conn).await?;
+            let derived: Total = diesel::sql_query(&derived).get_result(&mut conn).await?;
+            Ok(ArtifactUsage {
+                originals: 0,
+                derived: derived.total as u64,
+                text: text.total as u64,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::models::{Document, DocumentVersion};
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    fn doc(id: &str, source_id: &str, status: DocumentStatus, text: Option<&str>) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: source_id.to_string(),
+            title: id.to_string(),
+            source_url: format!("https://example.com/{}", id),
+            extracted_text: text.map(String::from),
+            synopsis: None,
+            tags: vec![],
+            status,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    fn version(content: &[u8], mime: &str) -> DocumentVersion {
+        DocumentVersion::new(content, mime.to_string(), None)
+    }
+
+    #[tokio::test]
+    async fn test_storage_usage() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        repo.save(&doc("a", "fbi", DocumentStatus::Downloaded, Some("hello")))
+            .await
+            .unwrap();
+        repo.save(&doc("b", "fbi", DocumentStatus::Failed, None))
+            .await
+            .unwrap();
+        repo.save(&doc("c", "cia", DocumentStatus::Downloaded, None))
+            .await
+            .unwrap();
+        let report = vec![7u8; 100];
+        let a = repo
+            .add_version("a", &version(&report, "application/pdf"))
+            .await
+            .unwrap();
+        repo.add_version("b", &version(&[1u8; 40], "text/html"))
+            .await
+            .unwrap();
+        // Same content as "a"
+        repo.add_version("c", &version(&report, "application/pdf"))
+            .await
+            .unwrap();
+
+        let by_source = repo.storage_by_source().await.unwrap();
+        assert_eq!(by_source.len(), 2);
+        assert_eq!(by_source[0].key, "fbi");
+        assert_eq!((by_source[0].files, by_source[0].bytes), (2, 140));
+
+        let by_mime = repo.storage_by_mime_type().await.unwrap();
+        assert_eq!(by_mime[0].key, "application/pdf");
+        assert_eq!(by_mime[0].bytes, 200);
+
+        let failed = repo.failed_document_storage().await.unwrap();
+        assert_eq!((failed.files, failed.bytes), (1, 40));
+        assert_eq!(repo.failed_document_ids().await.unwrap(), ["b"]);
+
+        let dups = repo.duplicate_storage().await.unwrap();
+        assert_eq!((dups.files, dups.bytes), (1, 100));
+
+        // A lost file no longer counts
+        repo.mark_version_lost(a, "a", "test").await.unwrap();
+        let dups = repo.duplicate_storage().await.unwrap();
+        assert_eq!((dups.files, dups.bytes), (0, 0));
+
+        let text = repo.text_storage().await.unwrap();
+        assert_eq!(text.text, 5);
+        assert_eq!(text.derived, 0);
+    }
+}
@@ -0,0 +1,286 @@
+//! Streaming iteration over large document sets.
+//!
+//! `get_all()` and `get_by_source()` materialize every document at once,
+//! which does not scale to archives with hundreds of thousands of rows.
+//! The stream here walks documents in primary-key order with keyset
+//! pagination, loading one batch ahead of the consumer.
+//!
+//! [`DieselDocumentRepository::stream_documents_snapshot`] reads every batch
+//! inside one read transaction instead, so the stream sees the database as
+//! it was when it started even while crawls keep writing.
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use futures::stream::{BoxStream, StreamExt};
+use tokio::sync::mpsc;
+
+use super::versions::VERSION_BATCH_CHUNK;
+use super::{DieselDocumentRepository, Projection};
+use crate::models::{Document, DocumentVersion};
+use crate::repository::models::{DocumentRecord, DocumentVersionRecord};
+use crate::repository::pool::{DbPool, DieselError};
+use crate::schema::{document_changes, document_versions, documents};
+use crate::services::access::AccessFilter;
+use crate::with_conn;
+
+/// Default number of documents loaded per query.
+pub const DEFAULT_STREAM_BATCH: usize = 500;
+
+/// Batches buffered ahead of the consumer.
+const PREFETCH_BATCHES: usize = 1;
+
+/// Narrow a boxed `documents` query to what a [`StreamFilter`] selects.
+macro_rules! apply_stream_filter {
+    ($query:ident, $filter:expr) => {
+        let filter: &StreamFilter = $filter;
+        if let Some(sid) = filter.source_id.as_deref() {
+            $query = $query.filter(documents::source_id.eq(sid));
+        }
+        if !filter.categories.is_empty() {
+            $query = $query.filter(documents::category_id.eq_any(&filter.categories));
+        }
+        for tag in &filter.tags {
+            let pattern = format!("%{}%", tag);
+            $query = $query.filter(documents::tags.like(pattern));
+        }
+        if let Some(seq) = filter.changed_since {
+            $query = $query.filter(
+                documents::id.eq_any(
+                    document_changes::table
+                        .filter(document_changes::seq.gt(seq))
+                        .select(document_changes::document_id),
+                ),
+            );
+        }
+        restrict_access!($query, filter.access.as_ref());
+    };
+}
+
+/// Boxed query for the batch of documents matching a [`StreamFilter`]
+/// whose ID sorts after `$after`.
+macro_rules! batch_query {
+    ($filter:expr, $after:expr, $limit:expr) => {{
+        let filter: &StreamFilter = $filter;
+        let mut query = filter.projection.documents_query();
+        apply_stream_filter!(query, filter);
+        if let Some(id) = $after {
+            query = query.filter(documents::id.gt(id));
+        }
+        query.order(documents::id.asc()).limit($limit as i64)
+    }};
+}
+
+/// Filters for [`DieselDocumentRepository::stream_documents`].
+#[derive(Debug, Clone, Default)]
+pub struct StreamFilter {
+    pub source_id: Option<String>,
+    /// MIME categories (any of).
+    pub categories: Vec<String>,
+    /// Tags (all of).
+    pub tags: Vec<String>,
+    /// Only documents changed after this change journal sequence number.
+    pub changed_since: Option<i64>,
+    /// Columns to load; `Projection::Metadata` skips `extracted_text`.
+    pub projection: Projection,
+    /// Skip documents restricted from the audience.
+    pub access: Option<AccessFilter>,
+}
+
+impl StreamFilter {
+    /// Filter to a single source, or all documents when `None`.
+    pub fn source(source_id: Option<&str>) -> Self {
+        Self {
+            source_id: source_id.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// Load only the given columns.
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Skip documents the filter withholds.
+    pub fn with_access(mut self, access: Option<AccessFilter>) -> Self {
+        self.access = access;
+        self
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Number of documents a stream with this filter would yield, for
+    /// sizing progress displays. Counted separately from the stream, so
+    /// writes in between can make it differ.
+    pub async fn count_stream(&self, filter: &StreamFilter) -> Result<u64, DieselError> {
+        use diesel::dsl::count_star;
+        let count: i64 = with_conn!(self.pool, conn, {
+            let mut query = documents::table.select(count_star()).into_boxed();
+            apply_stream_filter!(query, filter);
+            query.first(&mut conn).await
+        })?;
+        Ok(count as u64)
+    }
+
+    /// Stream documents (with versions) in ID order, `batch_size` at a time.
+    ///
+    /// The next batch is loaded in the background while the current one is
+    /// consumed, so at most two batches are held in memory. Dropping the
+    /// stream stops the loader.
+    pub fn stream_documents(
+        &self,
+        filter: StreamFilter,
+        batch_size: usize,
+    ) -> BoxStream<'static, Result<Document, DieselError>> {
+        let repo = self.clone();
+        let batch_size = batch_size.max(1);
+        let (tx, rx) = mpsc::channel(PREFETCH_BATCHES);
+
+        tokio::spawn(async move {
+            let mut after: Option<String> = None;
+            loop {
+                let batch = repo
+                    .load_batch_after(&filter, after.as_deref(), batch_size)
+                    .await;
+                let done = match &batch {
+                    Ok(docs) => {
+                        after = docs.last().map(|d| d.id.clone());
+                        docs.len() < batch_size
+                    }
+                    Err(_) => true,
+                };
+                if tx.send(batch).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        flatten_batches(rx)
+    }
+
+    /// Stream documents (with versions) in ID order as they were when the
+    /// stream started.
+    ///
+    /// Unlike [`stream_documents`](Self::stream_documents), which takes a
+    /// fresh connection per batch, this holds one connection with a
+    /// read-only transaction open until the stream ends or is dropped.
+    /// Documents saved, updated or deleted meanwhile are not seen, and each
+    /// document comes with exactly the versions it had at the start. The
+    /// open transaction stops SQLite from checkpointing past it and
+    /// PostgreSQL from vacuuming the rows it can see, so use it for bounded
+    /// jobs like exports rather than long-lived consumers.
+    pub fn stream_documents_snapshot(
+        &self,
+        filter: StreamFilter,
+        batch_size: usize,
+    ) -> BoxStream<'static, Result<Document, DieselError>> {
+        let repo = self.clone();
+        let batch_size = batch_size.max(1);
+        let (tx, rx) = mpsc::channel(PREFETCH_BATCHES);
+
+        tokio::spawn(async move {
+            if let Err(e) = repo.send_snapshot_batches(&filter, batch_size, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        flatten_batches(rx)
+    }
+
+    /// Send batches read inside one transaction until they run out or the
+    /// receiver goes away.
+    async fn send_snapshot_batches(
+        &self,
+        filter: &StreamFilter,
+        batch_size: usize,
+        tx: &mpsc::Sender<Result<Vec<Document>, DieselError>>,
+    ) -> Result<(), DieselError> {
+        // SQLite pins a WAL snapshot at a deferred transaction's first read;
+        // PostgreSQL needs REPEATABLE READ for one snapshot across statements.
+        let begin = match &self.pool {
+            DbPool::Sqlite(_) => "BEGIN",
+            #[cfg(feature = "postgres")]
+            DbPool::Postgres(_) => "BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY",
+        };
+
+        with_conn!(self.pool, conn, {
+            conn.batch_execute(begin).await?;
+            let sent = async {
+                let mut after: Option<String> = None;
+                loop {
+                    let records: Vec<DocumentRecord> =
+                        batch_query!(filter, after.as_deref(), batch_size)
+                            .load(&mut conn)
+                            .await?;
+                    let done = records.len() < batch_size;
+                    after = records.last().map(|r| r.id.clone());
+
+                    let ids: Vec<String> = records.iter().map(|r| r.id.clone()).collect();
+                    let mut versions: HashMap<String, Vec<DocumentVersion>> = HashMap::new();
+                    for chunk in ids.chunks(VERSION_BATCH_CHUNK) {
+                        let rows: Vec<DocumentVersionRecord> = document_versions::table
+                            .filter(document_versions::document_id.eq_any(chunk))
+                            .order((document_versions::document_id, document_versions::id.desc()))
+                            .load(&mut conn)
+                            .await?;
+                        for row in rows {
+                            versions
+                                .entry(row.document_id.clone())
+                                .or_default()
+                                .push(Self::version_record_to_model(row));
+                        }
+                    }
+                    let docs = records
+                        .into_iter()
+                        .map(|record| {
+                            let versions = versions.remove(&record.id).unwrap_or_default();
+                            Self::record_to_document(record, versions)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    if tx.send(Ok(docs)).await.is_err() || done {
+                        return Ok::<(), DieselError>(());
+                    }
+                }
+            }
+            .await;
+            // Nothing was written; always end the transaction so a pooled
+            // PostgreSQL connection goes back clean.
+            let ended = conn.batch_execute("ROLLBACK").await;
+            sent.and(ended)
+        })
+    }
+
+    /// Load the batch of documents whose ID sorts after `after`.
+    async fn load_batch_after(
+        &self,
+        filter: &StreamFilter,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Document>, DieselError> {
+        let records: Vec<DocumentRecord> = with_conn!(self.pool, conn, {
+            batch_query!(filter, after, limit).load(&mut conn).await
+        })?;
+
+        self.records_to_documents(records).await
+    }
+}
+
+/// Turn a channel of document batches into a stream of documents.
+fn flatten_batches(
+    rx: mpsc::Receiver<Result<Vec<Document>, DieselError>>,
+) -> BoxStream<'static, Result<Document, DieselError>> {
+    futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|batch| (batch, rx))
+    })
+    .flat_map(|batch| {
+        let items: Vec<Result<Document, DieselError>> = match batch {
+            Ok(docs) => docs.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        };
+        futures::stream::iter(items)
+    })
+    .boxed()
+}
@@ -0,0 +1,217 @@
+//! Selecting LLM summaries for regeneration after a model or prompt change.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::pool::{retry_on_busy, DieselError};
+use crate::schema::documents;
+use crate::with_conn;
+
+/// Which existing summaries to regenerate. A summary must match every
+/// selector that is set; with none set, every summary matches.
+#[derive(Debug, Clone, Default)]
+pub struct SummarySelector {
+    /// Summaries written before this time.
+    pub older_than: Option<DateTime<Utc>>,
+    /// Summaries written with any prompt version other than this one.
+    pub prompt_version: Option<String>,
+}
+
+impl SummarySelector {
+    /// Check a document's metadata against the selectors. Summaries from
+    /// before model and prompt version were recorded count as outdated.
+    pub fn matches(&self, metadata: &serde_json::Value) -> bool {
+        let annotation = &metadata["annotations"]["llm_summary"];
+
+        if let Some(cutoff) = self.older_than {
+            let written = annotation["timestamp"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+            if written.is_some_and(|t| t >= cutoff) {
+                return false;
+            }
+        }
+
+        if let Some(ref wanted) = self.prompt_version {
+            let data: serde_json::Value = annotation["data"]
+                .as_str()
+                .and_then(|d| serde_json::from_str(d).ok())
+                .unwrap_or_default();
+            if data["prompt_version"].as_str() == Some(wanted.as_str()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl DieselDocumentRepository {
+    /// IDs of summarized documents matching `selector`, oldest first.
+    pub async fn find_summaries_to_refresh(
+        &self,
+        source_id: Option<&str>,
+        selector: &SummarySelector,
+        limit: usize,
+    ) -> Result<Vec<String>, DieselError> {
+        let rows: Vec<(String, String)> = with_conn!(self.pool, conn, {
+            let mut query = documents::table
+                .filter(documents::status.eq("indexed"))
+                .filter(documents::synopsis.is_not_null())
+                .select((documents::id, documents::metadata))
+                .order(documents::updated_at.asc())
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(documents::source_id.eq(sid));
+            }
+            query.load(&mut conn).await
+        })?;
+
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        Ok(rows
+            .into_iter()
+            .filter(|(_, metadata)| {
+                let metadata = serde_json::from_str(metadata).unwrap_or_default();
+                selector.matches(&metadata)
+            })
+            .map(|(id, _)| id)
+            .take(limit)
+            .collect())
+    }
+
+    /// Send indexed documents back to the summarization queue.
+    ///
+    /// Unlike [`Self::reset_annotations`], the current synopsis and tags stay
+    /// in place until the new summary replaces them.
+    pub async fn requeue_for_summary(&self, ids: &[String]) -> Result<u64, DieselError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let now = Utc::now().to_rfc3339();
+        let mut moved = 0u64;
+        for chunk in ids.chunks(500) {
+            let count = retry_on_busy(|| async {
+                with_conn!(self.pool, conn, {
+                    diesel::update(
+                        documents::table
+                            .filter(documents::id.eq_any(chunk))
+                            .filter(documents::status.eq("indexed")),
+                    )
+                    .set((
+                        documents::status.eq("ocr_complete"),
+                        documents::updated_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await
+                })
+            })
+            .await?;
+            moved += count as u64;
+        }
+        Ok(moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    fn summarized(id: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: "test-source".to_string(),
+            title: id.to_string(),
+            source_url: format!("https://example.com/{}.pdf", id),
+            extracted_text: Some("text".to_string()),
+            synopsis: Some("A synopsis.".to_string()),
+            tags: vec!["memo".to_string()],
+            status: DocumentStatus::Indexed,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_selector_matches() {
+        let metadata = serde_json::json!({
+            "annotations": {"llm_summary": {
+                "version": 1,
+                "data": r#"{"model":"llama3","prompt_version":"2"}"#,
+                "timestamp": "2026-03-01T00:00:00+00:00",
+            }}
+        });
+        let legacy = serde_json::json!({
+            "annotations": {"llm_summary": {"version": 1, "data": "{}"}}
+        });
+        let cutoff = DateTime::parse_from_rfc3339("2026-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert!(SummarySelector::default().matches(&metadata));
+
+        let older = SummarySelector {
+            older_than: Some(cutoff),
+            ..Default::default()
+        };
+        assert!(older.matches(&metadata));
+        assert!(older.matches(&legacy));
+        let newer = SummarySelector {
+            older_than: Some(cutoff - chrono::Duration::days(180)),
+            ..Default::default()
+        };
+        assert!(!newer.matches(&metadata));
+
+        let current = SummarySelector {
+            prompt_version: Some("2".to_string()),
+            ..Default::default()
+        };
+        assert!(!current.matches(&metadata));
+        assert!(current.matches(&legacy));
+
+        // Both selectors must match
+        let both = SummarySelector {
+            older_than: Some(cutoff),
+            prompt_version: Some("2".to_string()),
+        };
+        assert!(!both.matches(&metadata));
+    }
+
+    #[tokio::test]
+    async fn test_requeue_for_summary() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        for id in ["old", "new"] {
+            repo.save(&summarized(id)).await.unwrap();
+        }
+        let data = r#"{"prompt_version":"2"}"#;
+        repo.record_annotation("new", "llm_summary", 1, Some(data), None)
+            .await
+            .unwrap();
+
+        let selector = SummarySelector {
+            prompt_version: Some("2".to_string()),
+            ..Default::default()
+        };
+        let ids = repo
+            .find_summaries_to_refresh(None, &selector, 0)
+            .await
+            .unwrap();
+        assert_eq!(ids, vec!["old".to_string()]);
+
+        assert_eq!(repo.requeue_for_summary(&ids).await.unwrap(), 1);
+        let doc = repo.get("old").await.unwrap().unwrap();
+        assert_eq!(doc.status, DocumentStatus::OcrComplete);
+        assert_eq!(doc.synopsis.as_deref(), Some("A synopsis."));
+        assert_eq!(
+            repo.count_needing_summarization(None, &[]).await.unwrap(),
+            1
+        );
+    }
+}
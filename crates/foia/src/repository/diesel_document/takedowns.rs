@@ -0,0 +1,305 @@
+//! Takedown cases, the documents they name, and their history.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{takedown_cases, takedown_documents, takedown_events};
+use crate::services::takedowns::{TakedownCase, TakedownEvent, TakedownStatus, Tombstone};
+use crate::with_conn;
+
+type CaseRow = (i32, String, String, String, String, String, String);
+
+fn from_row(row: CaseRow, document_ids: Vec<String>) -> TakedownCase {
+    let (id, requester_name, requester_contact, reason, status, submitted_at, updated_at) = row;
+    TakedownCase {
+        id,
+        requester_name,
+        requester_contact,
+        reason,
+        status: TakedownStatus::from_str(&status).unwrap_or(TakedownStatus::Open),
+        document_ids,
+        submitted_at: parse_datetime(&submitted_at),
+        updated_at: parse_datetime(&updated_at),
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Record a new open case naming `document_ids`. Returns its id.
+    pub async fn create_takedown_case(
+        &self,
+        requester_name: &str,
+        requester_contact: &str,
+        reason: &str,
+        document_ids: &[String],
+    ) -> Result<i32, DieselError> {
+        use diesel_async::AsyncConnection;
+
+        let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
+        with_conn!(self.pool, conn, {
+            conn.transaction(|conn| {
+                Box::pin(async move {
+                    diesel::insert_into(takedown_cases::table)
+                        .values((
+                            takedown_cases::requester_name.eq(requester_name),
+                            takedown_cases::requester_contact.eq(requester_contact),
+                            takedown_cases::reason.eq(reason),
+                            takedown_cases::status.eq(TakedownStatus::Open.as_str()),
+                            takedown_cases::submitted_at.eq(now),
+                            takedown_cases::updated_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    let id: i32 = takedown_cases::table
+                        .select(takedown_cases::id)
+                        .order(takedown_cases::id.desc())
+                        .first(conn)
+                        .await?;
+                    for document_id in document_ids {
+                        diesel::insert_into(takedown_documents::table)
+                            .values((
+                                takedown_documents::case_id.eq(id),
+                                takedown_documents::document_id.eq(document_id),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+                    diesel::insert_into(takedown_events::table)
+                        .values((
+                            takedown_events::case_id.eq(id),
+                            takedown_events::action.eq("received"),
+                            takedown_events::actor.eq(requester_name),
+                            takedown_events::created_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    Ok(id)
+                })
+            })
+            .await
+        })
+    }
+
+    pub async fn get_takedown_case(&self, id: i32) -> Result<Option<TakedownCase>, DieselError> {
+        let row: Option<CaseRow> = with_conn!(self.pool, conn, {
+            takedown_cases::table
+                .find(id)
+                .select((
+                    takedown_cases::id,
+                    takedown_cases::requester_name,
+                    takedown_cases::requester_contact,
+                    takedown_cases::reason,
+                    takedown_cases::status,
+                    takedown_cases::submitted_at,
+                    takedown_cases::updated_at,
+                ))
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let document_ids = self.takedown_document_ids(id).await?;
+        Ok(Some(from_row(row, document_ids)))
+    }
+
+    /// Cases, oldest first; all of them when `status` is `None`.
+    pub async fn list_takedown_cases(
+        &self,
+        status: Option<TakedownStatus>,
+    ) -> Result<Vec<TakedownCase>, DieselError> {
+        let rows: Vec<CaseRow> = with_conn!(self.pool, conn, {
+            let mut query = takedown_cases::table
+                .select((
+                    takedown_cases::id,
+                    takedown_cases::requester_name,
+                    takedown_cases::requester_contact,
+                    takedown_cases::reason,
+                    takedown_cases::status,
+                    takedown_cases::submitted_at,
+                    takedown_cases::updated_at,
+                ))
+                .into_boxed();
+            if let Some(status) = status {
+                query = query.filter(takedown_cases::status.eq(status.as_str()));
+            }
+            query.order(takedown_cases::id.asc()).load(&mut conn).await
+        })?;
+        let mut cases = Vec::with_capacity(rows.len());
+        for row in rows {
+            let document_ids = self.takedown_document_ids(row.0).await?;
+            cases.push(from_row(row, document_ids));
+        }
+        Ok(cases)
+    }
+
+    async fn takedown_document_ids(&self, case_id: i32) -> Result<Vec<String>, DieselError> {
+        with_conn!(self.pool, conn, {
+            takedown_documents::table
+                .filter(takedown_documents::case_id.eq(case_id))
+                .select(takedown_documents::document_id)
+                .order(takedown_documents::document_id.asc())
+                .load(&mut conn)
+                .await
+        })
+    }
+
+    /// Move a case to `status`, logging who did it and why.
+    pub async fn update_takedown_case(
+        &self,
+        id: i32,
+        status: TakedownStatus,
+        actor: &str,
+        note: Option<&str>,
+    ) -> Result<(), DieselError> {
+        use diesel_async::AsyncConnection;
+
+        let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
+        with_conn!(self.pool, conn, {
+            conn.transaction(|conn| {
+                Box::pin(async move {
+                    diesel::update(takedown_cases::table.find(id))
+                        .set((
+                            takedown_cases::status.eq(status.as_str()),
+                            takedown_cases::updated_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    diesel::insert_into(takedown_events::table)
+                        .values((
+                            takedown_events::case_id.eq(id),
+                            takedown_events::action.eq(status.as_str()),
+                            takedown_events::actor.eq(actor),
+                            takedown_events::note.eq(note),
+                            takedown_events::created_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .await
+        })
+    }
+
+    /// History of a case, oldest first.
+    pub async fn takedown_events(&self, case_id: i32) -> Result<Vec<TakedownEvent>, DieselError> {
+        let rows: Vec<(String, String, Option<String>, String)> = with_conn!(self.pool, conn, {
+            takedown_events::table
+                .filter(takedown_events::case_id.eq(case_id))
+                .select((
+                    takedown_events::action,
+                    takedown_events::actor,
+                    takedown_events::note,
+                    takedown_events::created_at,
+                ))
+                .order(takedown_events::id.asc())
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|(action, actor, note, created_at)| TakedownEvent {
+                action,
+                actor,
+                note,
+                created_at: parse_datetime(&created_at),
+            })
+            .collect())
+    }
+
+    /// The latest suppressed or upheld case naming a document.
+    pub async fn takedown_tombstone(
+        &self,
+        document_id: &str,
+    ) -> Result<Option<Tombstone>, DieselError> {
+        let withholding = [
+            TakedownStatus::Suppressed.as_str(),
+            TakedownStatus::Upheld.as_str(),
+        ];
+        let row: Option<(i32, String, String)> = with_conn!(self.pool, conn, {
+            takedown_cases::table
+                .inner_join(takedown_documents::table)
+                .filter(takedown_documents::document_id.eq(document_id))
+                .filter(takedown_cases::status.eq_any(withholding))
+                .select((
+                    takedown_cases::id,
+                    takedown_cases::status,
+                    takedown_cases::updated_at,
+                ))
+                .order(takedown_cases::id.desc())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(row.and_then(|(case_id, status, updated_at)| {
+            Some(Tombstone {
+                case_id,
+                status: TakedownStatus::from_str(&status)?,
+                since: parse_datetime(&updated_at),
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_takedown_case_lifecycle() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        let docs = vec!["doc-1".to_string(), "doc-2".to_string()];
+        let id = repo
+            .create_takedown_case("Jane Doe", "jane@example.org", "Home address", &docs)
+            .await
+            .unwrap();
+        let case = repo.get_takedown_case(id).await.unwrap().unwrap();
+        assert_eq!(case.status, TakedownStatus::Open);
+        assert_eq!(case.document_ids, docs);
+        assert!(repo.takedown_tombstone("doc-1").await.unwrap().is_none());
+
+        repo.update_takedown_case(id, TakedownStatus::Suppressed, "editor", None)
+            .await
+            .unwrap();
+        let tombstone = repo.takedown_tombstone("doc-2").await.unwrap().unwrap();
+        assert_eq!(tombstone.case_id, id);
+        assert_eq!(tombstone.status, TakedownStatus::Suppressed);
+
+        repo.update_takedown_case(
+            id,
+            TakedownStatus::Rejected,
+            "editor",
+            Some("Public record"),
+        )
+        .await
+        .unwrap();
+        assert!(repo.takedown_tombstone("doc-2").await.unwrap().is_none());
+
+        let events = repo.takedown_events(id).await.unwrap();
+        let actions: Vec<&str> = events.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, vec!["received", "suppressed", "rejected"]);
+        assert_eq!(events[2].note.as_deref(), Some("Public record"));
+        assert_eq!(
+            repo.list_takedown_cases(Some(TakedownStatus::Rejected))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(repo
+            .list_takedown_cases(Some(TakedownStatus::Open))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
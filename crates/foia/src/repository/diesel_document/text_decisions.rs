@@ -0,0 +1,195 @@
+//! Arbitration decisions: how each page's final text was chosen from the
+//! readings of several OCR backends.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::models::{DocumentPage, PageTextDecision};
+use crate::repository::models::DocumentPageRecord;
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{
+    document_pages, documents, page_ocr_results, page_text_decisions, page_text_revisions,
+};
+use crate::{with_conn, with_conn_split};
+
+impl DieselDocumentRepository {
+    /// Record how a page's final text was chosen, replacing any earlier
+    /// decision for it.
+    pub async fn record_page_text_decision(
+        &self,
+        decision: &PageTextDecision,
+    ) -> Result<(), DieselError> {
+        let page_id = decision.page_id as i32;
+        let scores = serde_json::to_string(&decision.scores).unwrap_or_else(|_| "[]".into());
+        let now = Utc::now().to_rfc3339();
+        let values = (
+            page_text_decisions::page_id.eq(page_id),
+            page_text_decisions::method.eq(&decision.method),
+            page_text_decisions::backend.eq(decision.backend.as_deref()),
+            page_text_decisions::scores.eq(&scores),
+            page_text_decisions::rationale.eq(&decision.rationale),
+            page_text_decisions::decided_at.eq(&now),
+        );
+        with_conn_split!(self.pool,
+            sqlite: conn => {
+                diesel::replace_into(page_text_decisions::table)
+                    .values(values)
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            },
+            postgres: conn => {
+                diesel::insert_into(page_text_decisions::table)
+                    .values(values)
+                    .on_conflict(page_text_decisions::page_id)
+                    .do_update()
+                    .set((
+                        page_text_decisions::method.eq(&decision.method),
+                        page_text_decisions::backend.eq(decision.backend.as_deref()),
+                        page_text_decisions::scores.eq(&scores),
+                        page_text_decisions::rationale.eq(&decision.rationale),
+                        page_text_decisions::decided_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+                Ok(())
+            }
+        )
+    }
+
+    /// How a page's final text was chosen, if it was arbitrated.
+    pub async fn get_page_text_decision(
+        &self,
+        page_id: i64,
+    ) -> Result<Option<PageTextDecision>, DieselError> {
+        #[allow(clippy::type_complexity)]
+        let row: Option<(i32, String, Option<String>, String, String, String)> =
+            with_conn!(self.pool, conn, {
+                page_text_decisions::table
+                    .find(page_id as i32)
+                    .select((
+                        page_text_decisions::page_id,
+                        page_text_decisions::method,
+                        page_text_decisions::backend,
+                        page_text_decisions::scores,
+                        page_text_decisions::rationale,
+                        page_text_decisions::decided_at,
+                    ))
+                    .first(&mut conn)
+                    .await
+                    .optional()
+            })?;
+        Ok(row.map(
+            |(page_id, method, backend, scores, rationale, decided_at)| PageTextDecision {
+                page_id: page_id as i64,
+                method,
+                backend,
+                scores: serde_json::from_str(&scores).unwrap_or_default(),
+                rationale,
+                decided_at: parse_datetime(&decided_at),
+            },
+        ))
+    }
+
+    /// Pages with at least two OCR readings to arbitrate between, optionally
+    /// limited to one source or document, in page order (0 = no limit).
+    /// Pages a curator has corrected are left out.
+    pub async fn get_pages_with_readings(
+        &self,
+        source_id: Option<&str>,
+        document_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DocumentPage>, DieselError> {
+        let page_ids: Vec<i32> = with_conn!(self.pool, conn, {
+            let mut query = page_ocr_results::table
+                .inner_join(document_pages::table.inner_join(documents::table))
+                .filter(page_ocr_results::error_message.is_null())
+                .filter(page_ocr_results::text.is_not_null())
+                .filter(diesel::dsl::not(page_ocr_results::page_id.eq_any(
+                    page_text_revisions::table.select(page_text_revisions::page_id),
+                )))
+                .group_by(page_ocr_results::page_id)
+                .having(diesel::dsl::count_star().ge(2))
+                .select(page_ocr_results::page_id)
+                .order(page_ocr_results::page_id.asc())
+                .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(documents::source_id.eq(source_id));
+            }
+            if let Some(document_id) = document_id {
+                query = query.filter(document_pages::document_id.eq(document_id));
+            }
+            if limit > 0 {
+                query = query.limit(limit as i64);
+            }
+            query.load(&mut conn).await
+        })?;
+
+        let mut pages = Vec::with_capacity(page_ids.len());
+        for chunk in page_ids.chunks(500) {
+            let records: Vec<DocumentPageRecord> = with_conn!(self.pool, conn, {
+                document_pages::table
+                    .filter(document_pages::id.eq_any(chunk))
+                    .order(document_pages::id.asc())
+                    .load(&mut conn)
+                    .await
+            })?;
+            pages.extend(records.into_iter().map(DocumentPage::from));
+        }
+        Ok(pages)
+    }
+
+    /// Store the arbitration score of one OCR result as its quality score.
+    pub async fn set_ocr_result_quality(
+        &self,
+        result_id: i64,
+        score: f32,
+    ) -> Result<(), DieselError> {
+        with_conn!(self.pool, conn, {
+            diesel::update(page_ocr_results::table.find(result_id as i32))
+                .set(page_ocr_results::quality_score.eq(Some(score)))
+                .execute(&mut conn)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ReadingScore;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_page_text_decisions() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        assert!(repo.get_page_text_decision(7).await.unwrap().is_none());
+
+        let mut decision = PageTextDecision {
+            page_id: 7,
+            method: "agreement".to_string(),
+            backend: Some("tesseract".to_string()),
+            scores: vec![ReadingScore {
+                backend: "tesseract".to_string(),
+                agreement: Some(0.9),
+                dictionary: 0.95,
+                score: 0.92,
+            }],
+            rationale: "tesseract agrees best with the other readings".to_string(),
+            decided_at: Utc::now(),
+        };
+        repo.record_page_text_decision(&decision).await.unwrap();
+        decision.method = "fused".to_string();
+        repo.record_page_text_decision(&decision).await.unwrap();
+
+        let saved = repo.get_page_text_decision(7).await.unwrap().unwrap();
+        assert_eq!(saved.method, "fused");
+        assert_eq!(saved.backend.as_deref(), Some("tesseract"));
+        assert_eq!(saved.scores, decision.scores);
+    }
+}
@@ -0,0 +1,215 @@
+//! Curators' corrections of page text, and the human-verified pages they
+//! leave behind.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::pages::stored_intermediate_text;
+use super::DieselDocumentRepository;
+use crate::models::{DocumentPage, PageTextRevision};
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{document_pages, documents, page_text_revisions};
+use crate::with_conn;
+
+type RevisionRow = (i32, i32, String, Option<String>, String, String, String);
+
+fn from_row(row: RevisionRow) -> PageTextRevision {
+    let (id, page_id, editor, previous_text, text, diff, created_at) = row;
+    PageTextRevision {
+        id: id as i64,
+        page_id: page_id as i64,
+        editor,
+        previous_text,
+        text,
+        diff,
+        created_at: parse_datetime(&created_at),
+    }
+}
+
+macro_rules! revision_columns {
+    () => {
+        (
+            page_text_revisions::id,
+            page_text_revisions::page_id,
+            page_text_revisions::editor,
+            page_text_revisions::previous_text,
+            page_text_revisions::text,
+            page_text_revisions::diff,
+            page_text_revisions::created_at,
+        )
+    };
+}
+
+impl DieselDocumentRepository {
+    /// Store `page` with its corrected `final_text` and record the edit.
+    /// Returns the new revision.
+    pub async fn save_page_text_revision(
+        &self,
+        page: &DocumentPage,
+        editor: &str,
+        previous_text: Option<&str>,
+        diff: &str,
+    ) -> Result<PageTextRevision, DieselError> {
+        use diesel_async::AsyncConnection;
+
+        let page_id = page.id as i32;
+        let text = page.final_text.as_deref().unwrap_or_default();
+        let (pdf_text, ocr_text) = stored_intermediate_text(page);
+        let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
+        let row: RevisionRow = with_conn!(self.pool, conn, {
+            conn.transaction(|conn| {
+                Box::pin(async move {
+                    diesel::update(document_pages::table.find(page_id))
+                        .set((
+                            document_pages::pdf_text.eq(pdf_text),
+                            document_pages::ocr_text.eq(ocr_text),
+                            document_pages::final_text.eq(text),
+                            document_pages::updated_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    diesel::insert_into(page_text_revisions::table)
+                        .values((
+                            page_text_revisions::page_id.eq(page_id),
+                            page_text_revisions::editor.eq(editor),
+                            page_text_revisions::previous_text.eq(previous_text),
+                            page_text_revisions::text.eq(text),
+                            page_text_revisions::diff.eq(diff),
+                            page_text_revisions::created_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    page_text_revisions::table
+                        .filter(page_text_revisions::page_id.eq(page_id))
+                        .select(revision_columns!())
+                        .order(page_text_revisions::id.desc())
+                        .first(conn)
+                        .await
+                })
+            })
+            .await
+        })?;
+        Ok(from_row(row))
+    }
+
+    /// Revisions of a page, newest first.
+    pub async fn get_page_text_revisions(
+        &self,
+        page_id: i64,
+    ) -> Result<Vec<PageTextRevision>, DieselError> {
+        let rows: Vec<RevisionRow> = with_conn!(self.pool, conn, {
+            page_text_revisions::table
+                .filter(page_text_revisions::page_id.eq(page_id as i32))
+                .select(revision_columns!())
+                .order(page_text_revisions::id.desc())
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().map(from_row).collect())
+    }
+
+    /// Whether a curator has edited or confirmed the page's text.
+    pub async fn is_page_verified(&self, page_id: i64) -> Result<bool, DieselError> {
+        Ok(!self.verified_page_ids(&[page_id]).await?.is_empty())
+    }
+
+    /// Which of `page_ids` a curator has edited or confirmed.
+    pub async fn verified_page_ids(&self, page_ids: &[i64]) -> Result<HashSet<i64>, DieselError> {
+        if page_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let ids: Vec<i32> = page_ids.iter().map(|&id| id as i32).collect();
+        let verified: Vec<i32> = with_conn!(self.pool, conn, {
+            page_text_revisions::table
+                .filter(page_text_revisions::page_id.eq_any(&ids))
+                .select(page_text_revisions::page_id)
+                .distinct()
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(verified.into_iter().map(|id| id as i64).collect())
+    }
+
+    /// Rebuild a document's text from its pages after one of them changed,
+    /// and mark it updated so search indexes pick up the change. Documents
+    /// without text of their own are already indexed from their pages.
+    pub async fn refresh_text_from_pages(
+        &self,
+        document_id: &str,
+        version_id: i32,
+    ) -> Result<(), DieselError> {
+        let combined = self.get_combined_page_text(document_id, version_id).await?;
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            diesel::update(
+                documents::table
+                    .find(document_id)
+                    .filter(documents::extracted_text.is_not_null()),
+            )
+            .set(documents::extracted_text.eq(&combined))
+            .execute(&mut conn)
+            .await?;
+            diesel::update(documents::table.find(document_id))
+                .set(documents::updated_at.eq(&now))
+                .execute(&mut conn)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_page_text_revisions() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        let mut page = DocumentPage::new("doc-1".to_string(), 1, 1);
+        page.ocr_text = Some("Tbe quick fox".to_string());
+        page.final_text = page.ocr_text.clone();
+        page.id = repo.save_page(&page).await.unwrap();
+        assert!(!repo.is_page_verified(page.id).await.unwrap());
+
+        page.final_text = Some("The quick fox".to_string());
+        let revision = repo
+            .save_page_text_revision(&page, "editor", Some("Tbe quick fox"), "-Tbe\n+The\n")
+            .await
+            .unwrap();
+        assert_eq!(revision.page_id, page.id);
+        assert_eq!(revision.previous_text.as_deref(), Some("Tbe quick fox"));
+
+        let saved = repo.get_page("doc-1", 1, 1).await.unwrap().unwrap();
+        assert_eq!(saved.final_text.as_deref(), Some("The quick fox"));
+        assert_eq!(saved.ocr_text.as_deref(), Some("Tbe quick fox"));
+        assert_eq!(
+            repo.get_combined_page_text("doc-1", 1)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("The quick fox")
+        );
+
+        page.final_text = Some("The quick brown fox".to_string());
+        repo.save_page_text_revision(&page, "editor", Some("The quick fox"), "")
+            .await
+            .unwrap();
+        let revisions = repo.get_page_text_revisions(page.id).await.unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].text, "The quick brown fox");
+        assert_eq!(
+            repo.verified_page_ids(&[page.id, page.id + 1])
+                .await
+                .unwrap(),
+            HashSet::from([page.id])
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Suggested titles for documents, awaiting curator review.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::models::{NewTitleSuggestion, TitleSuggestionRecord};
+use crate::repository::pool::DieselError;
+use crate::schema::{documents, title_suggestions};
+use crate::services::titles::{TitleStatus, TitleSuggestion};
+use crate::with_conn;
+
+fn to_suggestion(record: TitleSuggestionRecord, source_id: String) -> TitleSuggestion {
+    TitleSuggestion {
+        document_id: record.document_id,
+        source_id,
+        original_title: record.original_title,
+        suggested_title: record.suggested_title,
+        method: record.method,
+        status: TitleStatus::from_str(&record.status).unwrap_or(TitleStatus::Suggested),
+        created_at: record.created_at,
+        reviewed_at: record.reviewed_at,
+    }
+}
+
+impl DieselDocumentRepository {
+    /// Record a suggested title, replacing the document's title if `apply`.
+    ///
+    /// The original title is kept from the first suggestion, so it survives
+    /// re-runs. Returns `None` without changes once a curator has accepted
+    /// or rejected a suggestion for the document.
+    pub async fn record_title_suggestion(
+        &self,
+        doc_id: &str,
+        current_title: &str,
+        suggested_title: &str,
+        method: &str,
+        apply: bool,
+    ) -> Result<Option<TitleStatus>, DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let status = if apply {
+            TitleStatus::Applied
+        } else {
+            TitleStatus::Suggested
+        };
+
+        with_conn!(self.pool, conn, {
+            let existing: Option<TitleSuggestionRecord> = title_suggestions::table
+                .find(doc_id)
+                .first(&mut conn)
+                .await
+                .optional()?;
+            if existing
+                .as_ref()
+                .and_then(|r| TitleStatus::from_str(&r.status))
+                .is_some_and(|s| !s.needs_review())
+            {
+                return Ok(None);
+            }
+            let original_title = existing
+                .as_ref()
+                .map_or(current_title, |r| r.original_title.as_str());
+
+            diesel::delete(title_suggestions::table.find(doc_id))
+                .execute(&mut conn)
+                .await?;
+            diesel::insert_into(title_suggestions::table)
+                .values(&NewTitleSuggestion {
+                    document_id: doc_id,
+                    original_title,
+                    suggested_title,
+                    method,
+                    status: status.as_str(),
+                    created_at: &now,
+                    reviewed_at: None,
+                })
+                .execute(&mut conn)
+                .await?;
+            if apply {
+                diesel::update(documents::table.find(doc_id))
+                    .set((
+                        documents::title.eq(suggested_title),
+                        documents::updated_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+            Ok(Some(status))
+        })
+    }
+
+    /// Title suggestions, newest first, optionally of one source or status.
+    pub async fn list_title_suggestions(
+        &self,
+        source_id: Option<&str>,
+        status: Option<TitleStatus>,
+        limit: u32,
+    ) -> Result<Vec<TitleSuggestion>, DieselError> {
+        let rows: Vec<(TitleSuggestionRecord, String)> = with_conn!(self.pool, conn, {
+            let mut q = title_suggestions::table
+                .inner_join(documents::table)
+                .into_boxed();
+            if let Some(sid) = source_id {
+                q = q.filter(documents::source_id.eq(sid));
+            }
+            if let Some(status) = status {
+                q = q.filter(title_suggestions::status.eq(status.as_str()));
+            }
+            q.select((TitleSuggestionRecord::as_select(), documents::source_id))
+                .order((
+                    title_suggestions::created_at.desc(),
+                    title_suggestions::document_id.asc(),
+                ))
+                .limit(limit as i64)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|(record, source_id)| to_suggestion(record, source_id))
+            .collect())
+    }
+
+    /// The title suggestion for a document, if there is one.
+    pub async fn get_title_suggestion(
+        &self,
+        doc_id: &str,
+    ) -> Result<Option<TitleSuggestion>, DieselError> {
+        let row: Option<(TitleSuggestionRecord, String)> = with_conn!(self.pool, conn, {
+            title_suggestions::table
+                .inner_join(documents::table)
+                .filter(title_suggestions::document_id.eq(doc_id))
+                .select((TitleSuggestionRecord::as_select(), documents::source_id))
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(row.map(|(record, source_id)| to_suggestion(record, source_id)))
+    }
+
+    /// A curator's verdict on a suggestion. Accepting gives the document the
+    /// suggested title; rejecting restores the original if the suggestion
+    /// had been applied. Returns the reviewed suggestion, or `None` if the
+    /// document has none.
+    pub async fn review_title_suggestion(
+        &self,
+        doc_id: &str,
+        accept: bool,
+    ) -> Result<Option<TitleSuggestion>, DieselError> {
+        let Some(suggestion) = self.get_title_suggestion(doc_id).await? else {
+            return Ok(None);
+        };
+        let now = Utc::now().to_rfc3339();
+        let status = if accept {
+            TitleStatus::Accepted
+        } else {
+            TitleStatus::Rejected
+        };
+
+        with_conn!(self.pool, conn, {
+            if accept {
+                diesel::update(documents::table.find(doc_id))
+                    .set((
+                        documents::title.eq(&suggestion.suggested_title),
+                        documents::updated_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            } else {
+                // Leave titles edited since the suggestion was applied alone
+                diesel::update(
+                    documents::table
+                        .filter(documents::id.eq(doc_id))
+                        .filter(documents::title.eq(&suggestion.suggested_title)),
+                )
+                .set((
+                    documents::title.eq(&suggestion.original_title),
+                    documents::updated_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            }
+            diesel::update(title_suggestions::table.find(doc_id))
+                .set((
+                    title_suggestions::status.eq(status.as_str()),
+                    title_suggestions::reviewed_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok::<_, DieselError>(())
+        })?;
+
+        Ok(Some(TitleSuggestion {
+            status,
+            reviewed_at: Some(now),
+            ..suggestion
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    fn doc(id: &str, title: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            source_id: "agency".to_string(),
+            title: title.to_string(),
+            source_url: format!("https://example.com/{}", title),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Downloaded,
+            metadata: serde_json::Value::Object(Default::default()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_title_suggestion_review() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        repo.save(&doc("a", "document.pdf")).await.unwrap();
+        repo.save(&doc("b", "download.aspx?id=1")).await.unwrap();
+
+        // Suggested only: the title stays until accepted
+        let status = repo
+            .record_title_suggestion("a", "document.pdf", "Budget Memo", "first_page", false)
+            .await
+            .unwrap();
+        assert_eq!(status, Some(TitleStatus::Suggested));
+        assert_eq!(repo.get("a").await.unwrap().unwrap().title, "document.pdf");
+        repo.review_title_suggestion("a", true).await.unwrap();
+        assert_eq!(repo.get("a").await.unwrap().unwrap().title, "Budget Memo");
+
+        // Applied, then rejected: the original comes back
+        repo.record_title_suggestion("b", "download.aspx?id=1", "Draft", "first_page", true)
+            .await
+            .unwrap();
+        // A re-run keeps the first original title
+        repo.record_title_suggestion("b", "Draft", "Final Report", "llm:test", true)
+            .await
+            .unwrap();
+        assert_eq!(repo.get("b").await.unwrap().unwrap().title, "Final Report");
+        let pending = repo
+            .list_title_suggestions(Some("agency"), Some(TitleStatus::Applied), 10)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].original_title, "download.aspx?id=1");
+
+        let reviewed = repo
+            .review_title_suggestion("b", false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reviewed.status, TitleStatus::Rejected);
+        assert_eq!(
+            repo.get("b").await.unwrap().unwrap().title,
+            "download.aspx?id=1"
+        );
+        // Reviewed documents aren't suggested again
+        let status = repo
+            .record_title_suggestion("b", "download.aspx?id=1", "Other", "first_page", true)
+            .await
+            .unwrap();
+        assert_eq!(status, None);
+        assert!(repo
+            .review_title_suggestion("missing", true)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,433 @@
+//! Transcription tasks and the volunteers' transcriptions of them.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Double, Nullable, Text};
+use diesel_async::RunQueryDsl;
+
+use super::{DieselDocumentRepository, ReturningId};
+use crate::repository::parse_datetime;
+use crate::repository::pool::DieselError;
+use crate::schema::{document_pages, transcription_entries, transcription_tasks};
+use crate::services::transcription::{TaskStatus, TranscriptionEntry, TranscriptionTask};
+use crate::with_conn;
+
+type TaskRow = (
+    i32,
+    i32,
+    String,
+    i32,
+    i32,
+    String,
+    Option<String>,
+    String,
+    String,
+);
+
+fn from_row(row: TaskRow) -> TranscriptionTask {
+    let (
+        id,
+        page_id,
+        document_id,
+        version_id,
+        page_number,
+        status,
+        accepted_text,
+        created_at,
+        updated_at,
+    ) = row;
+    TranscriptionTask {
+        id,
+        page_id: page_id as i64,
+        document_id,
+        version_id: version_id as i64,
+        page_number: page_number as u32,
+        status: TaskStatus::from_str(&status).unwrap_or(TaskStatus::Open),
+        accepted_text,
+        created_at: parse_datetime(&created_at),
+        updated_at: parse_datetime(&updated_at),
+    }
+}
+
+macro_rules! task_columns {
+    () => {
+        (
+            transcription_tasks::id,
+            transcription_tasks::page_id,
+            document_pages::document_id,
+            document_pages::version_id,
+            document_pages::page_number,
+            transcription_tasks::status,
+            transcription_tasks::accepted_text,
+            transcription_tasks::created_at,
+            transcription_tasks::updated_at,
+        )
+    };
+}
+
+/// Pages of current versions that OCR is done with, that have no text or
+/// only poorly scored OCR text, and that no curator or task has taken up.
+const CANDIDATES_SQL: &str = "SELECT p.id FROM document_pages p \
+     JOIN documents d ON d.id = p.document_id \
+     WHERE ($1 IS NULL OR d.source_id = $1) \
+     AND p.ocr_status <> 'pending' \
+     AND p.version_id = (SELECT MAX(v.id) FROM document_versions v WHERE v.document_id = p.document_id) \
+     AND NOT EXISTS (SELECT 1 FROM transcription_tasks t WHERE t.page_id = p.id) \
+     AND NOT EXISTS (SELECT 1 FROM page_text_revisions r WHERE r.page_id = p.id) \
+     AND (COALESCE(p.final_text, p.ocr_text, p.pdf_text, '') = '' \
+     OR (SELECT MAX(COALESCE(r.quality_score, r.confidence)) FROM page_ocr_results r \
+     WHERE r.page_id = p.id) < $2) \
+     ORDER BY p.document_id, p.page_number LIMIT $3";
+
+/// The oldest task needing a transcription from volunteer `$1`, disputed
+/// tasks first. `$2` and `$3` are how many volunteers open and disputed
+/// tasks take. Pages a curator corrected meanwhile are skipped.
+const NEXT_TASK_SQL: &str = "SELECT t.id FROM transcription_tasks t \
+     WHERE t.status IN ('open', 'disputed') \
+     AND NOT EXISTS (SELECT 1 FROM page_text_revisions r WHERE r.page_id = t.page_id) \
+     AND NOT EXISTS (SELECT 1 FROM transcription_entries e \
+     WHERE e.task_id = t.id AND e.volunteer = $1) \
+     AND (SELECT COUNT(*) FROM transcription_entries e WHERE e.task_id = t.id) \
+     < CASE WHEN t.status = 'open' THEN $2 ELSE $3 END \
+     ORDER BY CASE WHEN t.status = 'disputed' THEN 0 ELSE 1 END, t.id LIMIT 1";
+
+impl DieselDocumentRepository {
+    /// Pages that need a human transcription, in document order.
+    pub async fn transcription_candidates(
+        &self,
+        source_id: Option<&str>,
+        max_quality: f64,
+        limit: u32,
+    ) -> Result<Vec<i64>, DieselError> {
+        let rows: Vec<ReturningId> = with_conn!(self.pool, conn, {
+            diesel::sql_query(CANDIDATES_SQL)
+                .bind::<Nullable<Text>, _>(source_id)
+                .bind::<Double, _>(max_quality)
+                .bind::<BigInt, _>(limit as i64)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().map(|r| r.id as i64).collect())
+    }
+
+    /// Open a task for each page that doesn't have one. Returns how many
+    /// were opened.
+    pub async fn create_transcription_tasks(&self, page_ids: &[i64]) -> Result<usize, DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let mut created = 0;
+        for &page_id in page_ids {
+            let exists: i64 = with_conn!(self.pool, conn, {
+                transcription_tasks::table
+                    .filter(transcription_tasks::page_id.eq(page_id as i32))
+                    .count()
+                    .get_result(&mut conn)
+                    .await
+            })?;
+            if exists > 0 {
+                continue;
+            }
+            with_conn!(self.pool, conn, {
+                diesel::insert_into(transcription_tasks::table)
+                    .values((
+                        transcription_tasks::page_id.eq(page_id as i32),
+                        transcription_tasks::status.eq(TaskStatus::Open.as_str()),
+                        transcription_tasks::created_at.eq(&now),
+                        transcription_tasks::updated_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await
+            })?;
+            created += 1;
+        }
+        Ok(created)
+    }
+
+    pub async fn get_transcription_task(
+        &self,
+        id: i32,
+    ) -> Result<Option<TranscriptionTask>, DieselError> {
+        let row: Option<TaskRow> = with_conn!(self.pool, conn, {
+            transcription_tasks::table
+                .inner_join(document_pages::table)
+                .filter(transcription_tasks::id.eq(id))
+                .select(task_columns!())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(row.map(from_row))
+    }
+
+    /// Tasks, oldest first; all of them when `status` is `None`.
+    pub async fn list_transcription_tasks(
+        &self,
+        status: Option<TaskStatus>,
+        limit: u32,
+    ) -> Result<Vec<TranscriptionTask>, DieselError> {
+        let rows: Vec<TaskRow> = with_conn!(self.pool, conn, {
+            let mut query = transcription_tasks::table
+                .inner_join(document_pages::table)
+                .select(task_columns!())
+                .into_boxed();
+            if let Some(status) = status {
+                query = query.filter(transcription_tasks::status.eq(status.as_str()));
+            }
+            query
+                .order(transcription_tasks::id.asc())
+                .limit(limit as i64)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().map(from_row).collect())
+    }
+
+    /// Number of tasks in each status.
+    pub async fn count_transcription_tasks(&self) -> Result<Vec<(TaskStatus, u64)>, DieselError> {
+        let rows: Vec<(String, i64)> = with_conn!(self.pool, conn, {
+            transcription_tasks::table
+                .group_by(transcription_tasks::status)
+                .select((transcription_tasks::status, diesel::dsl::count_star()))
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(status, n)| Some((TaskStatus::from_str(&status)?, n as u64)))
+            .collect())
+    }
+
+    /// Volunteers' work on a task, in the order they were assigned.
+    pub async fn get_transcription_entries(
+        &self,
+        task_id: i32,
+    ) -> Result<Vec<TranscriptionEntry>, DieselError> {
+        let rows: Vec<(String, Option<String>, String, Option<String>)> =
+            with_conn!(self.pool, conn, {
+                transcription_entries::table
+                    .filter(transcription_entries::task_id.eq(task_id))
+                    .select((
+                        transcription_entries::volunteer,
+                        transcription_entries::text,
+                        transcription_entries::assigned_at,
+                        transcription_entries::submitted_at,
+                    ))
+                    .order(transcription_entries::id.asc())
+                    .load(&mut conn)
+                    .await
+            })?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(volunteer, text, assigned_at, submitted_at)| TranscriptionEntry {
+                    volunteer,
+                    text,
+                    assigned_at: parse_datetime(&assigned_at),
+                    submitted_at: submitted_at.as_deref().map(parse_datetime),
+                },
+            )
+            .collect())
+    }
+
+    /// Drop assignments made before `cutoff` that were never submitted.
+    pub async fn release_transcription_assignments(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, DieselError> {
+        let cutoff = cutoff.to_rfc3339();
+        with_conn!(self.pool, conn, {
+            diesel::delete(
+                transcription_entries::table
+                    .filter(transcription_entries::submitted_at.is_null())
+                    .filter(transcription_entries::assigned_at.lt(&cutoff)),
+            )
+            .execute(&mut conn)
+            .await
+        })
+    }
+
+    /// The task `volunteer` holds, or else assign them the next one that
+    /// needs them. Returns the task's id.
+    pub async fn assign_transcription_task(
+        &self,
+        volunteer: &str,
+    ) -> Result<Option<i32>, DieselError> {
+        use diesel_async::AsyncConnection;
+
+        let now = Utc::now().to_rfc3339();
+        let now = now.as_str();
+        with_conn!(self.pool, conn, {
+            conn.transaction(|conn| {
+                Box::pin(async move {
+                    let held: Option<i32> = transcription_entries::table
+                        .inner_join(transcription_tasks::table)
+                        .filter(transcription_entries::volunteer.eq(volunteer))
+                        .filter(transcription_entries::text.is_null())
+                        .filter(
+                            transcription_tasks::status
+                                .eq_any([TaskStatus::Open.as_str(), TaskStatus::Disputed.as_str()]),
+                        )
+                        .select(transcription_entries::task_id)
+                        .first(conn)
+                        .await
+                        .optional()?;
+                    if held.is_some() {
+                        return Ok(held);
+                    }
+                    let next: Option<ReturningId> = diesel::sql_query(NEXT_TASK_SQL)
+                        .bind::<Text, _>(volunteer)
+                        .bind::<BigInt, _>(TaskStatus::Open.volunteers_wanted() as i64)
+                        .bind::<BigInt, _>(TaskStatus::Disputed.volunteers_wanted() as i64)
+                        .get_result(conn)
+                        .await
+                        .optional()?;
+                    let Some(next) = next else {
+                        return Ok(None);
+                    };
+                    diesel::insert_into(transcription_entries::table)
+                        .values((
+                            transcription_entries::task_id.eq(next.id),
+                            transcription_entries::volunteer.eq(volunteer),
+                            transcription_entries::assigned_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    Ok(Some(next.id))
+                })
+            })
+            .await
+        })
+    }
+
+    /// Store `volunteer`'s transcription of a task, whether or not they
+    /// still hold the assignment.
+    pub async fn save_transcription(
+        &self,
+        task_id: i32,
+        volunteer: &str,
+        text: &str,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let updated = with_conn!(self.pool, conn, {
+            diesel::update(
+                transcription_entries::table
+                    .filter(transcription_entries::task_id.eq(task_id))
+                    .filter(transcription_entries::volunteer.eq(volunteer)),
+            )
+            .set((
+                transcription_entries::text.eq(text),
+                transcription_entries::submitted_at.eq(&now),
+            ))
+            .execute(&mut conn)
+            .await
+        })?;
+        if updated == 0 {
+            with_conn!(self.pool, conn, {
+                diesel::insert_into(transcription_entries::table)
+                    .values((
+                        transcription_entries::task_id.eq(task_id),
+                        transcription_entries::volunteer.eq(volunteer),
+                        transcription_entries::text.eq(text),
+                        transcription_entries::assigned_at.eq(&now),
+                        transcription_entries::submitted_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await
+            })?;
+        }
+        Ok(())
+    }
+
+    pub async fn set_transcription_task_status(
+        &self,
+        id: i32,
+        status: TaskStatus,
+        accepted_text: Option<&str>,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            diesel::update(transcription_tasks::table.find(id))
+                .set((
+                    transcription_tasks::status.eq(status.as_str()),
+                    transcription_tasks::accepted_text.eq(accepted_text),
+                    transcription_tasks::updated_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DocumentPage;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_transcription_assignment() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        let page = DocumentPage::new("doc-1".to_string(), 1, 1);
+        let page_id = repo.save_page(&page).await.unwrap();
+        assert_eq!(
+            repo.create_transcription_tasks(&[page_id]).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.create_transcription_tasks(&[page_id]).await.unwrap(),
+            0
+        );
+
+        // Two volunteers get the open task, a third doesn't
+        let task_id = repo
+            .assign_transcription_task("ann")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            repo.assign_transcription_task("ann").await.unwrap(),
+            Some(task_id)
+        );
+        assert_eq!(
+            repo.assign_transcription_task("bob").await.unwrap(),
+            Some(task_id)
+        );
+        assert_eq!(repo.assign_transcription_task("cy").await.unwrap(), None);
+
+        repo.save_transcription(task_id, "ann", "Dear Sir,")
+            .await
+            .unwrap();
+        let entries = repo.get_transcription_entries(task_id).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].text.as_deref(), Some("Dear Sir,"));
+        assert!(entries[1].submitted_at.is_none());
+
+        // A released assignment frees the place; a submitted one stays
+        let released = repo
+            .release_transcription_assignments(Utc::now() + chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(released, 1);
+        assert_eq!(
+            repo.assign_transcription_task("cy").await.unwrap(),
+            Some(task_id)
+        );
+        assert_eq!(repo.assign_transcription_task("ann").await.unwrap(), None);
+
+        repo.set_transcription_task_status(task_id, TaskStatus::Disputed, None)
+            .await
+            .unwrap();
+        let task = repo.get_transcription_task(task_id).await.unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Disputed);
+        assert_eq!((task.document_id.as_str(), task.page_number), ("doc-1", 1));
+        assert_eq!(
+            repo.list_transcription_tasks(Some(TaskStatus::Open), 10)
+                .await
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+}
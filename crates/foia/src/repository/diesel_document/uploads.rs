@@ -0,0 +1,227 @@
+//! Contributed uploads and their moderation.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::DieselDocumentRepository;
+use crate::repository::pool::DieselError;
+use crate::repository::{parse_datetime, parse_datetime_opt};
+use crate::schema::uploads;
+use crate::services::uploads::{Upload, UploadStatus};
+use crate::with_conn;
+
+type UploadRow = (
+    i32,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+fn from_row(row: UploadRow) -> Upload {
+    let (
+        id,
+        document_id,
+        contributor,
+        description,
+        attribution,
+        status,
+        submitted_at,
+        reviewed_at,
+        reviewed_by,
+        review_note,
+    ) = row;
+    Upload {
+        id,
+        document_id,
+        contributor,
+        description,
+        attribution,
+        status: UploadStatus::from_str(&status).unwrap_or(UploadStatus::Pending),
+        submitted_at: parse_datetime(&submitted_at),
+        reviewed_at: parse_datetime_opt(reviewed_at),
+        reviewed_by,
+        review_note,
+    }
+}
+
+macro_rules! upload_columns {
+    () => {
+        (
+            uploads::id,
+            uploads::document_id,
+            uploads::contributor,
+            uploads::description,
+            uploads::attribution,
+            uploads::status,
+            uploads::submitted_at,
+            uploads::reviewed_at,
+            uploads::reviewed_by,
+            uploads::review_note,
+        )
+    };
+}
+
+impl DieselDocumentRepository {
+    /// Record an upload. The `id` of `upload` is ignored; the stored
+    /// upload is returned.
+    pub async fn create_upload(&self, upload: &Upload) -> Result<Upload, DieselError> {
+        let submitted_at = upload.submitted_at.to_rfc3339();
+        let row: UploadRow = with_conn!(self.pool, conn, {
+            diesel::insert_into(uploads::table)
+                .values((
+                    uploads::document_id.eq(&upload.document_id),
+                    uploads::contributor.eq(&upload.contributor),
+                    uploads::description.eq(upload.description.as_deref()),
+                    uploads::attribution.eq(upload.attribution.as_deref()),
+                    uploads::status.eq(upload.status.as_str()),
+                    uploads::submitted_at.eq(&submitted_at),
+                ))
+                .execute(&mut conn)
+                .await?;
+            uploads::table
+                .filter(uploads::document_id.eq(&upload.document_id))
+                .select(upload_columns!())
+                .order(uploads::id.desc())
+                .first(&mut conn)
+                .await
+        })?;
+        Ok(from_row(row))
+    }
+
+    pub async fn get_upload(&self, id: i32) -> Result<Option<Upload>, DieselError> {
+        let row: Option<UploadRow> = with_conn!(self.pool, conn, {
+            uploads::table
+                .find(id)
+                .select(upload_columns!())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(row.map(from_row))
+    }
+
+    /// Uploads, oldest first so moderators work through the queue in
+    /// order; all of them when `status` is `None`.
+    pub async fn list_uploads(
+        &self,
+        status: Option<UploadStatus>,
+        limit: i64,
+    ) -> Result<Vec<Upload>, DieselError> {
+        let rows: Vec<UploadRow> = with_conn!(self.pool, conn, {
+            let mut query = uploads::table.select(upload_columns!()).into_boxed();
+            if let Some(status) = status {
+                query = query.filter(uploads::status.eq(status.as_str()));
+            }
+            query
+                .order((uploads::submitted_at.asc(), uploads::id.asc()))
+                .limit(limit)
+                .load(&mut conn)
+                .await
+        })?;
+        Ok(rows.into_iter().map(from_row).collect())
+    }
+
+    /// Record a moderator's decision on a pending upload. Returns false if
+    /// the upload was not pending.
+    pub async fn review_upload(
+        &self,
+        id: i32,
+        status: UploadStatus,
+        reviewer: &str,
+        note: Option<&str>,
+    ) -> Result<bool, DieselError> {
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            let rows = diesel::update(
+                uploads::table
+                    .filter(uploads::id.eq(id))
+                    .filter(uploads::status.eq(UploadStatus::Pending.as_str())),
+            )
+            .set((
+                uploads::status.eq(status.as_str()),
+                uploads::reviewed_at.eq(&now),
+                uploads::reviewed_by.eq(reviewer),
+                uploads::review_note.eq(note),
+            ))
+            .execute(&mut conn)
+            .await?;
+            Ok(rows > 0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    #[tokio::test]
+    async fn test_upload_moderation() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+
+        let upload = Upload {
+            id: 0,
+            document_id: "doc-1".to_string(),
+            contributor: "Newsroom".to_string(),
+            description: Some("Response to our request".to_string()),
+            attribution: Some("FOIA 2024-0042".to_string()),
+            status: UploadStatus::Pending,
+            submitted_at: Utc::now(),
+            reviewed_at: None,
+            reviewed_by: None,
+            review_note: None,
+        };
+        let first = repo.create_upload(&upload).await.unwrap();
+        let second = repo
+            .create_upload(&Upload {
+                document_id: "doc-2".to_string(),
+                ..upload.clone()
+            })
+            .await
+            .unwrap();
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.attribution.as_deref(), Some("FOIA 2024-0042"));
+
+        let pending = repo
+            .list_uploads(Some(UploadStatus::Pending), 10)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 2);
+
+        assert!(repo
+            .review_upload(
+                first.id,
+                UploadStatus::Rejected,
+                "editor",
+                Some("Duplicate")
+            )
+            .await
+            .unwrap());
+        assert!(!repo
+            .review_upload(first.id, UploadStatus::Approved, "editor", None)
+            .await
+            .unwrap());
+
+        let reviewed = repo.get_upload(first.id).await.unwrap().unwrap();
+        assert_eq!(reviewed.status, UploadStatus::Rejected);
+        assert_eq!(reviewed.reviewed_by.as_deref(), Some("editor"));
+        assert_eq!(reviewed.review_note.as_deref(), Some("Duplicate"));
+        assert!(reviewed.reviewed_at.is_some());
+
+        let pending = repo
+            .list_uploads(Some(UploadStatus::Pending), 10)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, second.id);
+        assert_eq!(repo.list_uploads(None, 10).await.unwrap().len(), 2);
+    }
+}
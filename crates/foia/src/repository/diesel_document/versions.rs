@@ -10,6 +10,10 @@ use crate::repository::pool::DieselError;
 use crate::schema::document_versions;
 use crate::with_conn;
 
+/// Document IDs bound per `IN (...)` query, kept under SQLite's
+/// historical 999-variable limit.
+pub(crate) const VERSION_BATCH_CHUNK: usize = 900;
+
 impl DieselDocumentRepository {
     /// Load versions for a document.
     pub(crate) async fn load_versions(
@@ -31,30 +35,29 @@ impl DieselDocumentRepository {
         })
     }
 
-    /// Load versions for multiple documents in a single query.
-    /// Returns a map of document_id -> versions.
+    /// Load versions for multiple documents with one `IN` query per
+    /// [`VERSION_BATCH_CHUNK`] IDs. Returns a map of document_id -> versions.
     pub(crate) async fn load_versions_batch(
         &self,
         document_ids: &[String],
     ) -> Result<std::collections::HashMap<String, Vec<DocumentVersion>>, DieselError> {
-        if document_ids.is_empty() {
-            return Ok(std::collections::HashMap::new());
-        }
-
-        let records: Vec<DocumentVersionRecord> = with_conn!(self.pool, conn, {
-            document_versions::table
-                .filter(document_versions::document_id.eq_any(document_ids))
-                .order((document_versions::document_id, document_versions::id.desc()))
-                .load(&mut conn)
-                .await
-        })?;
-
         let mut result: std::collections::HashMap<String, Vec<DocumentVersion>> =
             std::collections::HashMap::new();
-        for record in records {
-            let doc_id = record.document_id.clone();
-            let version = Self::version_record_to_model(record);
-            result.entry(doc_id).or_default().push(version);
+
+        for chunk in document_ids.chunks(VERSION_BATCH_CHUNK) {
+            let records: Vec<DocumentVersionRecord> = with_conn!(self.pool, conn, {
+                document_versions::table
+                    .filter(document_versions::document_id.eq_any(chunk))
+                    .order((document_versions::document_id, document_versions::id.desc()))
+                    .load(&mut conn)
+                    .await
+            })?;
+
+            for record in records {
+                let doc_id = record.document_id.clone();
+                let version = Self::version_record_to_model(record);
+                result.entry(doc_id).or_default().push(version);
+            }
         }
         Ok(result)
     }
@@ -414,6 +417,171 @@ impl DieselDocumentRepository {
             .map(|r| (r.source_id, r.document_id, r.title.unwrap_or_default()))
             .collect())
     }
+
+    /// Delete one version of a document with the pages, attachments and
+    /// analysis derived from it. Highlights made on it stay on the
+    /// document, unlinked from the version. The stored file is left to
+    /// the caller, since other versions may share it.
+    pub async fn delete_version(&self, doc_id: &str, version_id: i64) -> Result<bool, DieselError> {
+        use crate::schema::{
+            archive_checks, document_analysis_results, document_bates, document_columns,
+            document_exemptions, document_pages, document_sections, document_splits, lost_files,
+            page_highlights, page_ocr_results, page_preprocessing, page_text_decisions,
+            page_text_revisions, transcription_entries, transcription_tasks,
+            virtual_file_annotations, virtual_files,
+        };
+        use diesel_async::AsyncConnection;
+
+        let version_id = version_id as i32;
+        with_conn!(self.pool, conn, {
+            conn.transaction(|conn| {
+                Box::pin(async move {
+                    let page_ids = || {
+                        document_pages::table
+                            .filter(document_pages::document_id.eq(doc_id))
+                            .filter(document_pages::version_id.eq(version_id))
+                            .select(document_pages::id)
+                    };
+                    diesel::delete(
+                        page_ocr_results::table
+                            .filter(page_ocr_results::page_id.eq_any(page_ids())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        page_text_decisions::table
+                            .filter(page_text_decisions::page_id.eq_any(page_ids())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        page_text_revisions::table
+                            .filter(page_text_revisions::page_id.eq_any(page_ids())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        page_preprocessing::table
+                            .filter(page_preprocessing::page_id.eq_any(page_ids())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    let task_ids = || {
+                        transcription_tasks::table
+                            .filter(transcription_tasks::page_id.eq_any(page_ids()))
+                            .select(transcription_tasks::id)
+                    };
+                    diesel::delete(
+                        transcription_entries::table
+                            .filter(transcription_entries::task_id.eq_any(task_ids())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        transcription_tasks::table
+                            .filter(transcription_tasks::id.eq_any(task_ids())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_analysis_results::table
+                            .filter(document_analysis_results::document_id.eq(doc_id))
+                            .filter(document_analysis_results::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_pages::table
+                            .filter(document_pages::document_id.eq(doc_id))
+                            .filter(document_pages::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+
+                    let file_ids = virtual_files::table
+                        .filter(virtual_files::document_id.eq(doc_id))
+                        .filter(virtual_files::version_id.eq(version_id))
+                        .select(virtual_files::id);
+                    diesel::delete(
+                        virtual_file_annotations::table
+                            .filter(virtual_file_annotations::virtual_file_id.eq_any(file_ids)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        virtual_files::table
+                            .filter(virtual_files::document_id.eq(doc_id))
+                            .filter(virtual_files::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+
+                    diesel::delete(
+                        document_columns::table
+                            .filter(document_columns::document_id.eq(doc_id))
+                            .filter(document_columns::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_bates::table
+                            .filter(document_bates::document_id.eq(doc_id))
+                            .filter(document_bates::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_exemptions::table
+                            .filter(document_exemptions::document_id.eq(doc_id))
+                            .filter(document_exemptions::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_splits::table
+                            .filter(document_splits::parent_id.eq(doc_id))
+                            .filter(document_splits::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        document_sections::table
+                            .filter(document_sections::document_id.eq(doc_id))
+                            .filter(document_sections::version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(
+                        archive_checks::table
+                            .filter(archive_checks::document_version_id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    diesel::update(
+                        page_highlights::table
+                            .filter(page_highlights::document_id.eq(doc_id))
+                            .filter(page_highlights::version_id.eq(version_id)),
+                    )
+                    .set(page_highlights::version_id.eq(None::<i32>))
+                    .execute(conn)
+                    .await?;
+                    diesel::delete(lost_files::table.find(version_id))
+                        .execute(conn)
+                        .await?;
+
+                    let rows = diesel::delete(
+                        document_versions::table
+                            .filter(document_versions::document_id.eq(doc_id))
+                            .filter(document_versions::id.eq(version_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    Ok(rows > 0)
+                })
+            })
+            .await
+        })
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,673 @@
+//! Work selection and result storage for virtual files (archive members and
+//! email attachments), so they can go through the same text extraction and
+//! annotation passes as documents.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+
+use super::queries::{Keyset, SourceScope};
+use super::{BrowseRow, DieselDocumentRepository};
+use crate::models::{VirtualFile, VirtualFileStatus, EMAIL_BODY_PLACEHOLDER};
+use crate::repository::models::VirtualFileRecord;
+use crate::repository::pool::DieselError;
+use crate::schema::{documents, virtual_file_annotations, virtual_files};
+use crate::services::access::AccessFilter;
+use crate::with_conn;
+
+impl DieselDocumentRepository {
+    /// Get a virtual file by ID.
+    pub async fn get_virtual_file(&self, id: &str) -> Result<Option<VirtualFile>, DieselError> {
+        let record = with_conn!(self.pool, conn, {
+            virtual_files::table
+                .find(id)
+                .select(VirtualFileRecord::as_select())
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        record.map(Self::virtual_file_record_to_model).transpose()
+    }
+
+    /// Count virtual files whose text has not been extracted yet.
+    pub async fn count_virtual_files_needing_text(
+        &self,
+        source_id: Option<&str>,
+    ) -> Result<u64, DieselError> {
+        with_conn!(self.pool, conn, {
+            let mut query = virtual_files::table
+                .inner_join(documents::table)
+                .filter(virtual_files::status.eq(VirtualFileStatus::Pending.as_str()))
+                .count()
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(documents::source_id.eq(sid));
+            }
+            if !exclude_sources.is_empty() {
+                query = query.filter(documents::source_id.ne_all(exclude_sources));
+            }
+            query.get_result::<i64>(&mut conn).await.map(|c| c as u64)
+        })
+    }
+
+    /// Virtual files whose text has not been extracted yet, ordered by ID
+    /// and starting after `after`.
+    pub async fn get_virtual_files_needing_text(
+        &self,
+        source_id: Option<&str>,
+        limit: usize,
+        after: Option<&str>,
+    ) -> Result<Vec<VirtualFile>, DieselError> {
+        let records = with_conn!(self.pool, conn, {
+            let mut query = virtual_files::table
+                .inner_join(documents::table)
+                .filter(virtual_files::status.eq(VirtualFileStatus::Pending.as_str()))
+                .select(VirtualFileRecord::as_select())
+                .order(virtual_files::id.asc())
+                .limit(limit as i64)
+                .into_boxed();
+            if let Some(sid) = source_id {
+                query = query.filter(documents::source_id.eq(sid));
+            }
+            if let Some(after) = after {
+                query = query.filter(virtual_files::id.gt(after));
+            }
+            query.load::<VirtualFileRecord>(&mut conn).await
+        })?;
+        records
+            .into_iter()
+            .map(Self::virtual_file_record_to_model)
+            .collect()
+    }
+
+    /// Store the result of text extraction for a virtual file.
+    pub async fn update_virtual_file_text(
+        &self,
+        id: &str,
+        text: Option<&str>,
+        status: VirtualFileStatus,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            diesel::update(virtual_files::table.find(id))
+                .set((
+                    virtual_files::extracted_text.eq(text),
+                    virtual_files::status.eq(status.as_str()),
+                    virtual_files::updated_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(())
+        })
+    }
+
+    /// Store an LLM synopsis and tags for a virtual file.
+    pub async fn update_virtual_file_summary(
+        &self,
+        id: &str,
+        synopsis: &str,
+        tags: &[String],
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        let tags_json = serde_json::to_string(tags)
+            .map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))?;
+        with_conn!(self.pool, conn, {
+            diesel::update(virtual_files::table.find(id))
+                .set((
+                    virtual_files::synopsis.eq(synopsis),
+                    virtual_files::tags.eq(&tags_json),
+                    virtual_files::updated_at.eq(&now),
+                ))
+                .execute(&mut conn)
+                .await?;
+            Ok(())
+        })
+    }
+
+    /// Count virtual files listed by [`browse_virtual_files`](Self::browse_virtual_files).
+    pub async fn count_browse_virtual_files(
+        &self,
+        sources: SourceScope<'_>,
+        tags: &[String],
+        search_query: Option<&str>,
+        access: Option<&AccessFilter>,
+    ) -> Result<u64, DieselError> {
+        with_conn!(self.pool, conn, {
+            let mut query = virtual_files::table
+                .inner_join(documents::table)
+                .filter(virtual_files::archive_path.ne(EMAIL_BODY_PLACEHOLDER))
+                .count()
+                .into_boxed();
+            match sources {
+                SourceScope::All => {}
+                SourceScope::One(sid) => query = query.filter(documents::source_id.eq(sid)),
+                SourceScope::Any(ids) => query = query.filter(documents::source_id.eq_any(ids)),
+            }
+            for tag in tags {
+                query = query.filter(virtual_files::tags.like(format!("%{}%", tag)));
+            }
+            if let Some(q) = search_query.filter(|q| !q.is_empty()) {
+                let pattern = format!("%{}%", q);
+                query = query.filter(
+                    virtual_files::archive_path
+                        .like(pattern.clone())
+                        .or(virtual_files::synopsis.like(pattern.clone()))
+                        .or(virtual_files::extracted_text.like(pattern)),
+                );
+            }
+            restrict_access!(query, access);
+            query.get_result::<i64>(&mut conn).await.map(|c| c as u64)
+        })
+    }
+
+    /// Archive members and attachments as browse rows of the `attachment`
+    /// kind, ordered like documents by `(updated_at, id)` descending.
+    ///
+    /// The row title is the parent document's; `original_filename` holds the
+    /// path within it. `search_query` also matches extracted text.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn browse_virtual_files(
+        &self,
+        sources: SourceScope<'_>,
+        tags: &[String],
+        search_query: Option<&str>,
+        limit: u32,
+        offset: u32,
+        keyset: Option<Keyset<'_>>,
+        access: Option<&AccessFilter>,
+    ) -> Result<Vec<BrowseRow>, DieselError> {
+        let backward = keyset.is_some_and(|ks| ks.is_backward());
+
+        #[allow(clippy::type_complexity)]
+        let mut rows: Vec<(
+            String,
+            String,
+            String,
+            String,
+            Option<String>,
+            Option<String>,
+            String,
+            String,
+            i32,
+            String,
+            String,
+        )> = with_conn!(self.pool, conn, {
+            let mut query = virtual_files::table
+                .inner_join(documents::table)
+                .filter(virtual_files::archive_path.ne(EMAIL_BODY_PLACEHOLDER))
+                .select((
+                    virtual_files::id,
+                    virtual_files::document_id,
+                    documents::title,
+                    documents::source_id,
+                    virtual_files::synopsis,
+                    virtual_files::tags,
+                    virtual_files::archive_path,
+                    virtual_files::mime_type,
+                    virtual_files::file_size,
+                    virtual_files::created_at,
+                    virtual_files::updated_at,
+                ))
+                .limit(limit as i64)
+                .into_boxed();
+
+            match keyset {
+                Some(Keyset::After(c)) => {
+                    query = query
+                        .filter(
+                            virtual_files::updated_at.lt(c.updated_at.clone()).or(
+                                virtual_files::updated_at
+                                    .eq(c.updated_at.clone())
+                                    .and(virtual_files::id.lt(c.id.clone())),
+                            ),
+                        )
+                        .order((virtual_files::updated_at.desc(), virtual_files::id.desc()));
+                }
+                Some(Keyset::Before(c)) => {
+                    query = query
+                        .filter(
+                            virtual_files::updated_at.gt(c.updated_at.clone()).or(
+                                virtual_files::updated_at
+                                    .eq(c.updated_at.clone())
+                                    .and(virtual_files::id.gt(c.id.clone())),
+                            ),
+                        )
+                        .order((virtual_files::updated_at.asc(), virtual_files::id.asc()));
+                }
+                None => {
+                    query = query
+                        .order((virtual_files::updated_at.desc(), virtual_files::id.desc()))
+                        .offset(offset as i64);
+                }
+            }
+
+            match sources {
+                SourceScope::All => {}
+                SourceScope::One(sid) => query = query.filter(documents::source_id.eq(sid)),
+                SourceScope::Any(ids) => query = query.filter(documents::source_id.eq_any(ids)),
+            }
+            for tag in tags {
+                query = query.filter(virtual_files::tags.like(format!("%{}%", tag)));
+            }
+            if let Some(q) = search_query.filter(|q| !q.is_empty()) {
+                let pattern = format!("%{}%", q);
+                query = query.filter(
+                    virtual_files::archive_path
+                        .like(pattern.clone())
+                        .or(virtual_files::synopsis.like(pattern.clone()))
+                        .or(virtual_files::extracted_text.like(pattern)),
+                );
+            }
+            restrict_access!(query, access);
+            query.load(&mut conn).await
+        })?;
+        if backward {
+            rows.reverse();
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    id,
+                    document_id,
+                    title,
+                    source_id,
+                    synopsis,
+                    tags,
+                    archive_path,
+                    mime_type,
+                    file_size,
+                    created_at,
+                    updated_at,
+                )| BrowseRow {
+                    id,
+                    kind: BrowseRow::ATTACHMENT.to_string(),
+                    parent_id: Some(document_id),
+                    title,
+                    source_id,
+                    synopsis,
+                    tags,
+                    original_filename: Some(archive_path),
+                    mime_type,
+                    file_size,
+                    acquired_at: created_at,
+                    updated_at,
+                },
+            )
+            .collect())
+    }
+
+    /// Count virtual files without a current `annotation_type` result.
+    ///
+    /// With `requires_text`, only files with extracted text are counted.
+    pub async fn count_virtual_files_needing_annotation(
+        &self,
+        annotation_type: &str,
+        version: i32,
+        requires_text: bool,
+        source_id: Option<&str>,
+        exclude_sources: &[String],
+    ) -> Result<u64, DieselError> {
+        with_conn!(self.pool, conn, {
+            let mut query = virtual_files::table
+                .inner_join(documents::table)
+                .filter(diesel::dsl::not(
+                    virtual_files::id.eq_any(
+                        virtual_file_annotations::table
+                            .filter(virtual_file_annotations::annotation_type.eq(annotation_type))
+                            .filter(virtual_file_annotations::version.ge(version))
+                            .select(virtual_file_annotations::virtual_file_id),
+                    ),
+                ))
+                .count()
+                .into_boxed();
+            if requires_text {
+                query = query
+                    .filter(virtual_files::status.eq(VirtualFileStatus::OcrComplete.as_str()))
+                    .filter(virtual_files::extracted_text.is_not_null());
+            }
+            if let Some(sid) = source_id {
+                query = query.filter(documents::source_id.eq(sid));
+            }
+            if !exclude_sources.is_empty() {
+                query = query.filter(documents::source_id.ne_all(exclude_sources));
+            }
+            query.get_result::<i64>(&mut conn).await.map(|c| c as u64)
+        })
+    }
+
+    /// Virtual files without a current `annotation_type` result, ordered by
+    /// ID and starting after `after`.
+    pub async fn get_virtual_files_needing_annotation(
+        &self,
+        annotation_type: &str,
+        version: i32,
+        requires_text: bool,
+        source_id: Option<&str>,
+        exclude_sources: &[String],
+        limit: usize,
+        after: Option<&str>,
+    ) -> Result<Vec<VirtualFile>, DieselError> {
+        let records = with_conn!(self.pool, conn, {
+            let mut query = virtual_files::table
+                .inner_join(documents::table)
+                .filter(diesel::dsl::not(
+                    virtual_files::id.eq_any(
+                        virtual_file_annotations::table
+                            .filter(virtual_file_annotations::annotation_type.eq(annotation_type))
+                            .filter(virtual_file_annotations::version.ge(version))
+                            .select(virtual_file_annotations::virtual_file_id),
+                    ),
+                ))
+                .select(VirtualFileRecord::as_select())
+                .order(virtual_files::id.asc())
+                .limit(limit as i64)
+                .into_boxed();
+            if requires_text {
+                query = query
+                    .filter(virtual_files::status.eq(VirtualFileStatus::OcrComplete.as_str()))
+                    .filter(virtual_files::extracted_text.is_not_null());
+            }
+            if let Some(sid) = source_id {
+                query = query.filter(documents::source_id.eq(sid));
+            }
+            if !exclude_sources.is_empty() {
+                query = query.filter(documents::source_id.ne_all(exclude_sources));
+            }
+            if let Some(after) = after {
+                query = query.filter(virtual_files::id.gt(after));
+            }
+            query.load::<VirtualFileRecord>(&mut conn).await
+        })?;
+        records
+            .into_iter()
+            .map(Self::virtual_file_record_to_model)
+            .collect()
+    }
+
+    /// Record an annotation result for a virtual file, replacing any earlier
+    /// result of the same type.
+    pub async fn record_virtual_file_annotation(
+        &self,
+        id: &str,
+        annotation_type: &str,
+        version: i32,
+        data: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<(), DieselError> {
+        let now = Utc::now().to_rfc3339();
+        with_conn!(self.pool, conn, {
+            let updated =
+                diesel::update(virtual_file_annotations::table.find((id, annotation_type)))
+                    .set((
+                        virtual_file_annotations::version.eq(version),
+                        virtual_file_annotations::data.eq(data),
+                        virtual_file_annotations::error.eq(error),
+                        virtual_file_annotations::created_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            if updated == 0 {
+                diesel::insert_into(virtual_file_annotations::table)
+                    .values((
+                        virtual_file_annotations::virtual_file_id.eq(id),
+                        virtual_file_annotations::annotation_type.eq(annotation_type),
+                        virtual_file_annotations::version.eq(version),
+                        virtual_file_annotations::data.eq(data),
+                        virtual_file_annotations::error.eq(error),
+                        virtual_file_annotations::created_at.eq(&now),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Data recorded for one annotation of a virtual file, if it succeeded.
+    pub async fn get_virtual_file_annotation(
+        &self,
+        id: &str,
+        annotation_type: &str,
+    ) -> Result<Option<String>, DieselError> {
+        with_conn!(self.pool, conn, {
+            virtual_file_annotations::table
+                .find((id, annotation_type))
+                .select(virtual_file_annotations::data)
+                .first::<Option<String>>(&mut conn)
+                .await
+                .optional()
+                .map(Option::flatten)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Document, DocumentStatus};
+    use crate::repository::diesel_document::tests::setup_test_db;
+
+    async fn setup() -> (DieselDocumentRepository, tempfile::TempDir) {
+        let (pool, dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        let doc = Document {
+            id: "doc-1".to_string(),
+            source_id: "agency".to_string(),
+            title: "Release bundle".to_string(),
+            source_url: "https://agency.gov/release.zip".to_string(),
+            extracted_text: None,
+            synopsis: None,
+            tags: vec![],
+            status: DocumentStatus::Pending,
+            metadata: serde_json::json!({}),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            discovery_method: "seed".to_string(),
+            versions: vec![],
+        };
+        repo.save(&doc).await.unwrap();
+        (repo, dir)
+    }
+
+    fn member(id: &str, status: VirtualFileStatus) -> VirtualFile {
+        let mut vf = VirtualFile::new(
+            "doc-1".to_string(),
+            1,
+            format!("letters/{}.pdf", id),
+            format!("{}.pdf", id),
+            "application/pdf".to_string(),
+            1024,
+        );
+        vf.id = id.to_string();
+        vf.status = status;
+        vf
+    }
+
+    #[tokio::test]
+    async fn test_virtual_file_text_work() {
+        let (repo, _dir) = setup().await;
+        for (id, status) in [
+            ("a", VirtualFileStatus::Pending),
+            ("b", VirtualFileStatus::Pending),
+            ("c", VirtualFileStatus::Unsupported),
+        ] {
+            repo.insert_virtual_file(&member(id, status)).await.unwrap();
+        }
+
+        assert_eq!(
+            repo.count_virtual_files_needing_text(None).await.unwrap(),
+            2
+        );
+        assert_eq!(
+            repo.count_virtual_files_needing_text(Some("other"))
+                .await
+                .unwrap(),
+            0
+        );
+        let batch = repo
+            .get_virtual_files_needing_text(Some("agency"), 10, Some("a"))
+            .await
+            .unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].id, "b");
+
+        repo.update_virtual_file_text("a", Some("Dear requester"), VirtualFileStatus::OcrComplete)
+            .await
+            .unwrap();
+        let a = repo.get_virtual_file("a").await.unwrap().unwrap();
+        assert_eq!(a.extracted_text.as_deref(), Some("Dear requester"));
+        assert_eq!(
+            repo.count_virtual_files_needing_text(None).await.unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_virtual_file_annotation_work() {
+        let (repo, _dir) = setup().await;
+        let mut with_text = member("a", VirtualFileStatus::OcrComplete);
+        with_text.extracted_text = Some("Memo dated 2019-04-02".to_string());
+        repo.insert_virtual_file(&with_text).await.unwrap();
+        repo.insert_virtual_file(&member("b", VirtualFileStatus::Pending))
+            .await
+            .unwrap();
+
+        let needing = |requires_text| {
+            repo.count_virtual_files_needing_annotation("llm_summary", 1, requires_text, None, &[])
+        };
+        assert_eq!(needing(true).await.unwrap(), 1);
+        assert_eq!(needing(false).await.unwrap(), 2);
+
+        repo.record_virtual_file_annotation("a", "llm_summary", 1, Some("{}"), None)
+            .await
+            .unwrap();
+        assert_eq!(needing(true).await.unwrap(), 0);
+
+        // A newer annotator version makes the file eligible again
+        assert_eq!(
+            repo.count_virtual_files_needing_annotation("llm_summary", 2, true, None, &[])
+                .await
+                .unwrap(),
+            1
+        );
+        repo.record_virtual_file_annotation("a", "llm_summary", 2, Some("{\"v\":2}"), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_virtual_file_annotation("a", "llm_summary")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("{\"v\":2}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_browse_attachments() {
+        let (repo, _dir) = setup().await;
+        let mut a = member("a", VirtualFileStatus::OcrComplete);
+        a.extracted_text = Some("Budget memo for fiscal 2019".to_string());
+        repo.insert_virtual_file(&a).await.unwrap();
+        repo.insert_virtual_file(&member("b", VirtualFileStatus::Pending))
+            .await
+            .unwrap();
+        let mut body = member("body", VirtualFileStatus::Unsupported);
+        body.archive_path = EMAIL_BODY_PLACEHOLDER.to_string();
+        repo.insert_virtual_file(&body).await.unwrap();
+
+        let attachments = vec![crate::utils::ATTACHMENTS_CATEGORY.to_string()];
+        let rows = repo
+            .browse_fast(
+                SourceScope::All,
+                None,
+                &attachments,
+                &[],
+                &[],
+                &[],
+                None,
+                10,
+                0,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|r| r.is_attachment()));
+        assert_eq!(rows[0].parent_id.as_deref(), Some("doc-1"));
+        assert_eq!(rows[0].title, "Release bundle");
+        assert_eq!(
+            repo.browse_count(
+                SourceScope::All,
+                None,
+                &attachments,
+                &[],
+                &[],
+                &[],
+                None,
+                None
+            )
+            .await
+            .unwrap(),
+            2
+        );
+
+        // Search reaches the extracted text
+        let rows = repo
+            .browse_fast(
+                SourceScope::All,
+                None,
+                &attachments,
+                &[],
+                &[],
+                &[],
+                Some("fiscal"),
+                10,
+                0,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].original_filename.as_deref(), Some("letters/a.pdf"));
+        assert_eq!(
+            repo.browse_count(
+                SourceScope::One("other"),
+                None,
+                &attachments,
+                &[],
+                &[],
+                &[],
+                None,
+                None
+            )
+            .await
+            .unwrap(),
+            0
+        );
+
+        // Attachments of a restricted document are withheld with it
+        let hidden = AccessFilter {
+            hidden_documents: vec!["doc-1".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            repo.browse_count(
+                SourceScope::All,
+                None,
+                &attachments,
+                &[],
+                &[],
+                &[],
+                None,
+                Some(&hidden)
+            )
+            .await
+            .unwrap(),
+            0
+        );
+    }
+}
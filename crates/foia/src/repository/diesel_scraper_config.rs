@@ -3,6 +3,8 @@
 //! Stores per-source scraper configurations in the `scraper_configs` table.
 //! Uses diesel-async for async database support. Works with both SQLite and PostgreSQL.
 
+use std::collections::HashMap;
+
 use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
@@ -10,6 +12,7 @@ use diesel_async::RunQueryDsl;
 use super::models::{NewScraperConfig, ScraperConfigRecord};
 use super::pool::{DbPool, DieselError};
 use crate::config::ScraperConfig;
+use crate::models::SharedFrontier;
 use crate::schema::scraper_configs;
 use crate::{with_conn, with_conn_split};
 
@@ -62,6 +65,58 @@ impl DieselScraperConfigRepository {
         Ok(results)
     }
 
+    /// The frontier a source shares with other sources on its host, if
+    /// it opted in to one.
+    pub async fn shared_frontier(
+        &self,
+        source_id: &str,
+    ) -> Result<Option<SharedFrontier>, DieselError> {
+        let mut configs = self.get_all().await?;
+        // Deterministic member order, so pattern overlaps resolve the same way
+        configs.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(SharedFrontier::for_source(source_id, &configs))
+    }
+
+    /// Source IDs whose processing profile excludes `stage`.
+    pub async fn sources_skipping(&self, stage: &str) -> Result<Vec<String>, DieselError> {
+        Ok(self
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|(_, config)| !config.processing.allows(stage))
+            .map(|(source_id, _)| source_id)
+            .collect())
+    }
+
+    /// Per source, the record types whose documents skip `stage`.
+    /// Sources without such rules are absent.
+    pub async fn record_type_skips(
+        &self,
+        stage: &str,
+    ) -> Result<HashMap<String, Vec<String>>, DieselError> {
+        Ok(self
+            .get_all()
+            .await?
+            .into_iter()
+            .filter_map(|(source_id, config)| {
+                let types = config.processing.record_types_skipping(stage);
+                (!types.is_empty()).then_some((source_id, types))
+            })
+            .collect())
+    }
+
+    /// Source IDs whose processing profile lets suggested titles replace
+    /// generic ones.
+    pub async fn sources_applying_titles(&self) -> Result<Vec<String>, DieselError> {
+        Ok(self
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|(_, config)| config.processing.apply_titles)
+            .map(|(source_id, _)| source_id)
+            .collect())
+    }
+
     /// List all source IDs that have scraper configs.
     pub async fn list_source_ids(&self) -> Result<Vec<String>, DieselError> {
         with_conn!(self.pool, conn, {
@@ -30,6 +30,7 @@ impl TryFrom<SourceRecord> for Source {
             metadata,
             created_at: parse_datetime(&record.created_at),
             last_scraped: parse_datetime_opt(record.last_scraped),
+            paused: record.paused != 0,
         })
     }
 }
@@ -173,6 +174,42 @@ impl DieselSourceRepository {
         })
     }
 
+    /// Pause or resume a source. Returns false if there is no such source.
+    pub async fn set_paused(&self, id: &str, paused: bool) -> Result<bool, DieselError> {
+        with_conn!(self.pool, conn, {
+            let rows = diesel::update(sources::table.find(id))
+                .set(sources::paused.eq(i32::from(paused)))
+                .execute(&mut conn)
+                .await?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Whether a source is paused. Unregistered sources are not.
+    pub async fn is_paused(&self, id: &str) -> Result<bool, DieselError> {
+        let paused: Option<i32> = with_conn!(self.pool, conn, {
+            sources::table
+                .find(id)
+                .select(sources::paused)
+                .first(&mut conn)
+                .await
+                .optional()
+        })?;
+        Ok(paused.is_some_and(|p| p != 0))
+    }
+
+    /// IDs of paused sources.
+    pub async fn paused_ids(&self) -> Result<Vec<String>, DieselError> {
+        with_conn!(self.pool, conn, {
+            sources::table
+                .filter(sources::paused.ne(0))
+                .select(sources::id)
+                .order(sources::id.asc())
+                .load(&mut conn)
+                .await
+        })
+    }
+
     /// Rename a source ID, updating all related tables.
     /// Returns the number of documents and crawl URLs updated.
     pub async fn rename(&self, old_id: &str, new_id: &str) -> Result<(usize, usize), DieselError> {
@@ -243,7 +280,8 @@ mod tests {
                 base_url TEXT NOT NULL,
                 metadata TEXT NOT NULL DEFAULT '{}',
                 created_at TEXT NOT NULL,
-                last_scraped TEXT
+                last_scraped TEXT,
+                paused INTEGER NOT NULL DEFAULT 0
             )"#,
         )
         .await
@@ -346,4 +384,33 @@ mod tests {
         let result = repo.get_all().await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_pause_survives_save() {
+        let (pool, _dir) = setup_test_db().await;
+        let repo = DieselSourceRepository::new(pool);
+
+        let mut source = Source::new(
+            "fbi".to_string(),
+            SourceType::Custom,
+            "FBI Vault".to_string(),
+            "https://vault.fbi.gov".to_string(),
+        );
+        repo.save(&source).await.unwrap();
+        assert!(!repo.is_paused("fbi").await.unwrap());
+
+        assert!(repo.set_paused("fbi", true).await.unwrap());
+        assert!(!repo.set_paused("missing", true).await.unwrap());
+        assert!(repo.is_paused("fbi").await.unwrap());
+        assert!(!repo.is_paused("missing").await.unwrap());
+        assert_eq!(repo.paused_ids().await.unwrap(), vec!["fbi"]);
+
+        // A scrape updating last_scraped must not resume the source
+        source.last_scraped = Some(chrono::Utc::now());
+        repo.save(&source).await.unwrap();
+        assert!(repo.get("fbi").await.unwrap().unwrap().paused);
+
+        repo.set_paused("fbi", false).await.unwrap();
+        assert!(repo.paused_ids().await.unwrap().is_empty());
+    }
 }
@@ -13,6 +13,7 @@ pub mod sea_tables;
 pub mod source;
 
 // Legacy diesel-prefixed modules (to be removed)
+pub mod diesel_agency;
 pub mod diesel_config_history;
 pub mod diesel_crawl;
 pub mod diesel_document;
@@ -24,6 +25,8 @@ pub mod diesel_service_status;
 pub mod diesel_source;
 
 // Utilities
+pub mod diagnostics;
+pub mod text_codec;
 pub mod util;
 
 // Database migration (legacy - to be removed)
@@ -44,11 +47,12 @@ mod document;
 #[allow(unused_imports)]
 pub use context::DbContext;
 #[allow(unused_imports)]
-pub use pool::{DbError, DbPool};
+pub use pool::{retry_on_busy, DbError, DbPool, PoolOptions};
 #[allow(unused_imports)]
 pub use source::SourceRepository;
 
 // Legacy re-exports for backwards compatibility
+pub use diesel_agency::DieselAgencyRepository;
 #[allow(unused_imports)]
 pub use diesel_config_history::DieselConfigHistoryRepository;
 pub use diesel_crawl::DieselCrawlRepository;
@@ -62,16 +66,16 @@ pub use migration_sqlite::SqliteMigrator;
 pub use pool::DieselError;
 
 // Re-export helper types from document module
-pub use document::{extract_filename_parts, sanitize_filename};
+pub use document::{extract_filename_parts, sanitize_filename, DocumentNavigation};
 
 // Re-export models (public API)
 #[allow(unused_imports)]
 pub use models::{
-    ConfigHistoryRecord, CrawlConfigRecord, CrawlRequestRecord, CrawlUrlRecord, DocumentPageRecord,
-    DocumentRecord, DocumentVersionRecord, NewConfigHistory, NewCrawlRequest, NewCrawlUrl,
-    NewDocument, NewDocumentPage, NewDocumentVersion, NewRateLimitState, NewScraperConfig,
-    NewSource, NewVirtualFile, RateLimitStateRecord, ScraperConfigRecord, SourceRecord,
-    VirtualFileRecord,
+    AgencyRecord, ConfigHistoryRecord, CrawlConfigRecord, CrawlRequestRecord, CrawlUrlRecord,
+    DocumentPageRecord, DocumentRecord, DocumentVersionRecord, NewAgency, NewConfigHistory,
+    NewCrawlRequest, NewCrawlUrl, NewDocument, NewDocumentPage, NewDocumentVersion,
+    NewRateLimitState, NewScraperConfig, NewSource, NewVirtualFile, RateLimitStateRecord,
+    ScraperConfigRecord, SourceRecord, VirtualFileRecord,
 };
 
 use chrono::{DateTime, Utc};
@@ -83,6 +87,7 @@ use self::diesel_context::DieselDbContext;
 /// Constructed via [`crate::config::Settings::repositories()`] to eliminate
 /// repetitive `create_db_context()` boilerplate in CLI commands.
 pub struct Repositories {
+    pub agencies: DieselAgencyRepository,
     pub sources: DieselSourceRepository,
     pub crawl: DieselCrawlRepository,
     pub documents: DieselDocumentRepository,
@@ -95,6 +100,7 @@ pub struct Repositories {
 impl Repositories {
     pub fn new(ctx: DieselDbContext) -> Self {
         Self {
+            agencies: ctx.agencies(),
             sources: ctx.sources(),
             crawl: ctx.crawl(),
             documents: ctx.documents(),
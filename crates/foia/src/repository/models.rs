@@ -21,6 +21,7 @@ pub struct SourceRecord {
     pub metadata: String,
     pub created_at: String,
     pub last_scraped: Option<String>,
+    pub paused: i32,
 }
 
 /// New source for insertion.
@@ -107,6 +108,7 @@ pub struct CrawlRequestRecord {
     pub error: Option<String>,
     pub was_conditional: i32,
     pub was_not_modified: i32,
+    pub identified: i32,
 }
 
 /// New crawl request for insertion.
@@ -126,6 +128,69 @@ pub struct NewCrawlRequest<'a> {
     pub error: Option<&'a str>,
     pub was_conditional: i32,
     pub was_not_modified: i32,
+    pub identified: i32,
+}
+
+// =============================================================================
+// Crawl Challenges
+// =============================================================================
+
+/// Crawl challenge (CAPTCHA) record from the database.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::crawl_challenges)]
+pub struct CrawlChallengeRecord {
+    pub id: i32,
+    pub source_id: String,
+    pub domain: String,
+    pub url: String,
+    pub kind: String,
+    pub status: String,
+    pub detected_at: String,
+    pub resolved_at: Option<String>,
+    pub cookies: Option<String>,
+}
+
+/// Domain circuit breaker incident record from the database.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::domain_incidents)]
+pub struct DomainIncidentRecord {
+    pub id: i32,
+    pub source_id: String,
+    pub domain: String,
+    pub responses: i32,
+    pub blocked: i32,
+    pub statuses: String,
+    pub opened_at: String,
+    pub paused_until: String,
+}
+
+/// Listing page snapshot record from the database.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::listing_snapshots)]
+pub struct ListingSnapshotRecord {
+    pub id: i32,
+    pub source_id: String,
+    pub url: String,
+    pub content_hash: String,
+    pub content: String,
+    pub link_count: i32,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
+    pub fetch_count: i32,
+}
+
+/// API response schema record from the database.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::api_schemas)]
+pub struct ApiSchemaRecord {
+    pub source_id: String,
+    pub endpoint: String,
+    pub fingerprint: String,
+    pub paths: String,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
+    pub drift: Option<String>,
+    pub drift_at: Option<String>,
 }
 
 // =============================================================================
@@ -237,6 +302,17 @@ pub struct NewDocumentVersion<'a> {
     pub dedup_index: Option<i32>,
 }
 
+/// Lost file record from the database.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::lost_files)]
+#[diesel(primary_key(version_id))]
+pub struct LostFileRecord {
+    pub version_id: i32,
+    pub document_id: String,
+    pub reason: String,
+    pub lost_at: String,
+}
+
 // =============================================================================
 // Document Pages
 // =============================================================================
@@ -380,6 +456,34 @@ pub struct NewScraperConfig<'a> {
     pub updated_at: &'a str,
 }
 
+// =============================================================================
+// Agencies
+// =============================================================================
+
+/// Agency record from the database.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::agencies)]
+pub struct AgencyRecord {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    pub config: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// New agency for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::agencies)]
+pub struct NewAgency<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub parent_id: Option<&'a str>,
+    pub config: &'a str,
+    pub created_at: &'a str,
+    pub updated_at: &'a str,
+}
+
 // =============================================================================
 // Configuration History
 // =============================================================================
@@ -491,6 +595,313 @@ pub struct NewDocumentEntity<'a> {
     pub created_at: &'a str,
 }
 
+// =============================================================================
+// Page Highlights
+// =============================================================================
+
+/// Page highlight record from the database.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::page_highlights)]
+pub struct PageHighlightRecord {
+    pub id: i32,
+    pub document_id: String,
+    pub version_id: Option<i32>,
+    pub page_number: i32,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    pub selected_text: String,
+    pub comment: Option<String>,
+    pub label: Option<String>,
+    pub created_at: String,
+}
+
+/// New page highlight for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::page_highlights)]
+pub struct NewPageHighlight<'a> {
+    pub document_id: &'a str,
+    pub version_id: Option<i32>,
+    pub page_number: i32,
+    pub start_offset: i32,
+    pub end_offset: i32,
+    pub selected_text: &'a str,
+    pub comment: Option<&'a str>,
+    pub label: Option<&'a str>,
+    pub created_at: &'a str,
+}
+
+// =============================================================================
+// Document Relations
+// =============================================================================
+
+/// Typed edge between two documents.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::document_relations)]
+pub struct DocumentRelationRecord {
+    pub id: i32,
+    pub document_id: String,
+    pub related_document_id: String,
+    pub relation_type: String,
+    pub virtual_file_id: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+/// New document relation for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::document_relations)]
+pub struct NewDocumentRelation<'a> {
+    pub document_id: &'a str,
+    pub related_document_id: &'a str,
+    pub relation_type: &'a str,
+    pub virtual_file_id: Option<&'a str>,
+    pub note: Option<&'a str>,
+    pub created_at: &'a str,
+}
+
+/// Column name from the header row of a tabular document.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::document_columns)]
+pub struct DocumentColumnRecord {
+    pub id: i32,
+    pub document_id: String,
+    pub version_id: i32,
+    pub sheet: String,
+    pub position: i32,
+    pub name: String,
+    pub name_key: String,
+}
+
+/// New document column for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::document_columns)]
+pub struct NewDocumentColumn<'a> {
+    pub document_id: &'a str,
+    pub version_id: i32,
+    pub sheet: &'a str,
+    pub position: i32,
+    pub name: &'a str,
+    pub name_key: String,
+}
+
+/// Record type assigned to a document by classification.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::document_classifications)]
+pub struct DocumentClassificationRecord {
+    pub document_id: String,
+    pub record_type: String,
+    pub confidence: f32,
+    pub classifier: String,
+    pub classified_at: String,
+}
+
+/// New or replacement document classification.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::document_classifications)]
+pub struct NewDocumentClassification<'a> {
+    pub document_id: &'a str,
+    pub record_type: &'a str,
+    pub confidence: f32,
+    pub classifier: &'a str,
+    pub classified_at: &'a str,
+}
+
+/// Title suggested for a document, with the title it had before.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::title_suggestions)]
+pub struct TitleSuggestionRecord {
+    pub document_id: String,
+    pub original_title: String,
+    pub suggested_title: String,
+    pub method: String,
+    pub status: String,
+    pub created_at: String,
+    pub reviewed_at: Option<String>,
+}
+
+/// New or replacement title suggestion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::title_suggestions)]
+pub struct NewTitleSuggestion<'a> {
+    pub document_id: &'a str,
+    pub original_title: &'a str,
+    pub suggested_title: &'a str,
+    pub method: &'a str,
+    pub status: &'a str,
+    pub created_at: &'a str,
+    pub reviewed_at: Option<&'a str>,
+}
+
+/// Run of Bates-stamped pages within a document.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::document_bates)]
+pub struct DocumentBatesRecord {
+    pub id: i32,
+    pub document_id: String,
+    pub version_id: i32,
+    pub prefix: String,
+    pub prefix_key: String,
+    pub start_number: i64,
+    pub end_number: i64,
+    pub digits: i32,
+    pub first_page: i32,
+    pub last_page: i32,
+}
+
+/// New Bates range for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::document_bates)]
+pub struct NewDocumentBates<'a> {
+    pub document_id: &'a str,
+    pub version_id: i32,
+    pub prefix: &'a str,
+    pub prefix_key: String,
+    pub start_number: i64,
+    pub end_number: i64,
+    pub digits: i32,
+    pub first_page: i32,
+    pub last_page: i32,
+}
+
+/// Exemption markings of one code on a document page.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::document_exemptions)]
+pub struct DocumentExemptionRecord {
+    pub id: i32,
+    pub document_id: String,
+    pub version_id: i32,
+    pub page_number: i32,
+    pub code: String,
+    pub count: i32,
+}
+
+/// New page exemption row for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::document_exemptions)]
+pub struct NewDocumentExemption<'a> {
+    pub document_id: &'a str,
+    pub version_id: i32,
+    pub page_number: i32,
+    pub code: &'a str,
+    pub count: i32,
+}
+
+/// Search alias: a term and what it stands for.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::search_aliases)]
+pub struct SearchAliasRecord {
+    pub id: i32,
+    pub term: String,
+    pub expansion: String,
+    pub kind: String,
+    pub created_at: String,
+}
+
+/// New search alias for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::search_aliases)]
+pub struct NewSearchAlias<'a> {
+    pub term: &'a str,
+    pub expansion: &'a str,
+    pub kind: &'a str,
+    pub created_at: &'a str,
+}
+
+/// Run of a scheduled export job.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::export_runs)]
+pub struct ExportRunRecord {
+    pub id: i32,
+    pub job: String,
+    pub format: String,
+    pub destination: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub status: String,
+    pub artifact: Option<String>,
+    pub documents: i32,
+    pub bytes: i64,
+    pub error: Option<String>,
+    pub change_seq: Option<i64>,
+}
+
+/// New export run for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::export_runs)]
+pub struct NewExportRun<'a> {
+    pub job: &'a str,
+    pub format: &'a str,
+    pub destination: &'a str,
+    pub started_at: &'a str,
+    pub status: &'a str,
+    pub change_seq: Option<i64>,
+}
+
+/// Change journal entry, from the database.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::document_changes)]
+pub struct DocumentChangeRecord {
+    pub seq: i64,
+    pub document_id: String,
+    pub source_id: String,
+    pub change_type: String,
+    pub changed_at: String,
+}
+
+/// Visibility rule for a document or source, from the database.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = schema::access_rules)]
+pub struct AccessRuleRecord {
+    pub scope: String,
+    pub target: String,
+    pub visibility: String,
+    pub embargo_until: Option<String>,
+    pub note: Option<String>,
+    pub updated_at: String,
+}
+
+/// New or replaced visibility rule.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::access_rules)]
+pub struct NewAccessRule<'a> {
+    pub scope: &'a str,
+    pub target: &'a str,
+    pub visibility: &'a str,
+    pub embargo_until: Option<&'a str>,
+    pub note: Option<&'a str>,
+    pub updated_at: &'a str,
+}
+
+/// API key, from the database. The key itself is never stored.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone)]
+#[diesel(table_name = schema::api_keys)]
+pub struct ApiKeyRecord {
+    pub id: i32,
+    pub name: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub read_only: i32,
+    pub sources: Option<String>,
+    pub rate_limit: Option<i32>,
+    pub requests: i64,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+/// New API key for insertion.
+#[derive(Insertable, Debug)]
+#[diesel(table_name = schema::api_keys)]
+pub struct NewApiKey<'a> {
+    pub name: &'a str,
+    pub key_prefix: &'a str,
+    pub key_hash: &'a str,
+    pub read_only: i32,
+    pub sources: Option<&'a str>,
+    pub rate_limit: Option<i32>,
+    pub created_at: &'a str,
+}
+
 // =============================================================================
 // Document Analysis Results
 // =============================================================================
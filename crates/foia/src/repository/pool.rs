@@ -3,11 +3,15 @@
 //! This module provides a backend-agnostic interface for database connections.
 //! The actual backend is determined at runtime based on the database URL.
 
+use std::future::Future;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use diesel::sqlite::SqliteConnection;
 use diesel_async::sync_connection_wrapper::SyncConnectionWrapper;
-use diesel_async::AsyncConnection;
+use diesel_async::{AsyncConnection, SimpleAsyncConnection};
 
 #[cfg(feature = "postgres")]
 use diesel_async::pooled_connection::deadpool::Pool as DeadPool;
@@ -35,10 +39,58 @@ pub type SqliteConn = SyncConnectionWrapper<SqliteConnection>;
 #[cfg(feature = "postgres")]
 pub type PgConn = deadpool::managed::Object<AsyncDieselConnectionManager<AsyncPgConnection>>;
 
+/// Connection pool tuning shared by both backends.
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    /// Maximum pooled PostgreSQL connections. SQLite opens a connection per
+    /// operation and serializes writers with `busy_timeout` instead.
+    pub max_connections: usize,
+    /// How long a SQLite connection waits on a locked database before
+    /// failing with `SQLITE_BUSY`.
+    pub busy_timeout: Duration,
+    /// WAL size in pages at which SQLite runs an automatic passive checkpoint.
+    pub wal_autocheckpoint: u32,
+    /// How often long-running processes truncate the WAL (None = never).
+    pub checkpoint_interval: Option<Duration>,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            busy_timeout: Duration::from_secs(5),
+            wal_autocheckpoint: 1000,
+            checkpoint_interval: Some(Duration::from_secs(300)),
+        }
+    }
+}
+
+/// Result of `PRAGMA wal_checkpoint`.
+#[derive(Debug, Clone, Copy, diesel::QueryableByName)]
+pub struct WalCheckpoint {
+    /// 1 if another connection blocked the checkpoint from completing.
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub busy: i32,
+    /// Frames in the WAL before the checkpoint.
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub log: i32,
+    /// Frames copied back into the database file.
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pub checkpointed: i32,
+}
+
 /// SQLite connection pool (lightweight - creates connections on demand).
+///
+/// Because every operation opens a fresh connection, a replica file swapped
+/// in underneath the pool is picked up without any explicit refresh.
 #[derive(Clone)]
 pub struct SqlitePool {
     database_url: String,
+    read_only: bool,
+    options: PoolOptions,
+    /// Set once `journal_mode = WAL` has been applied; the mode is stored
+    /// in the database file, so one successful switch covers every connection.
+    wal_enabled: Arc<AtomicBool>,
 }
 
 #[allow(dead_code)]
@@ -49,9 +101,30 @@ impl SqlitePool {
         let url = database_url.strip_prefix("sqlite:").unwrap_or(database_url);
         Self {
             database_url: url.to_string(),
+            read_only: false,
+            options: PoolOptions::default(),
+            wal_enabled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Apply connection tuning.
+    pub fn with_options(self, options: PoolOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Open all connections with `PRAGMA query_only`, rejecting writes.
+    pub fn into_read_only(self) -> Self {
+        Self {
+            read_only: true,
+            ..self
         }
     }
 
+    /// Whether connections are opened read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Create pool from a file path.
     pub fn from_path(path: &Path) -> Self {
         Self::new(&path.display().to_string())
@@ -59,9 +132,56 @@ impl SqlitePool {
 
     /// Get a connection.
     pub async fn get(&self) -> Result<SqliteConn, DbError> {
-        SqliteConn::establish(&self.database_url)
+        let mut conn = SqliteConn::establish(&self.database_url)
+            .await
+            .map_err(to_diesel_error)?;
+        conn.batch_execute(&format!(
+            "PRAGMA busy_timeout = {}",
+            self.options.busy_timeout.as_millis()
+        ))
+        .await?;
+        if self.read_only {
+            conn.batch_execute("PRAGMA query_only = ON").await?;
+            return Ok(conn);
+        }
+
+        if !self.wal_enabled.load(Ordering::Relaxed) {
+            // Best effort: a failed switch leaves the default rollback
+            // journal, which still works, and is retried on the next connection.
+            match conn.batch_execute("PRAGMA journal_mode = WAL").await {
+                Ok(()) => self.wal_enabled.store(true, Ordering::Relaxed),
+                Err(e) => tracing::debug!("Could not enable WAL mode: {}", e),
+            }
+        }
+        conn.batch_execute(&format!(
+            "PRAGMA wal_autocheckpoint = {}",
+            self.options.wal_autocheckpoint
+        ))
+        .await?;
+        Ok(conn)
+    }
+
+    /// Copy the WAL back into the database file and truncate it.
+    ///
+    /// Auto-checkpoints are passive and never shrink the WAL file, which
+    /// grows without bound while readers keep it pinned.
+    pub async fn checkpoint(&self) -> Result<WalCheckpoint, DbError> {
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.get().await?;
+        diesel::sql_query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .get_result(&mut conn)
             .await
-            .map_err(to_diesel_error)
+    }
+
+    /// Check whether the database file can only be opened for reading
+    /// (e.g. a replica restored onto a read-only mount).
+    pub fn detect_read_only(&self) -> bool {
+        let path = Path::new(&self.database_url);
+        match std::fs::OpenOptions::new().write(true).open(path) {
+            Ok(_) => false,
+            Err(e) => e.kind() == std::io::ErrorKind::PermissionDenied,
+        }
     }
 
     /// Get the database URL.
@@ -108,6 +228,36 @@ impl PgPool {
     pub fn inner(&self) -> DeadPool<AsyncPgConnection> {
         self.pool.clone()
     }
+
+    /// Drop idle connections older than `max_age` so new ones are opened.
+    /// Returns the number of connections removed.
+    pub fn recycle_older_than(&self, max_age: Duration) -> usize {
+        self.pool
+            .retain(|_, metrics| metrics.age() < max_age)
+            .removed
+            .len()
+    }
+
+    /// Check whether the server only accepts reads (hot standby or
+    /// `default_transaction_read_only`).
+    pub async fn detect_read_only(&self) -> Result<bool, DbError> {
+        use diesel_async::RunQueryDsl;
+
+        #[derive(diesel::QueryableByName)]
+        struct ReadOnlyRow {
+            #[diesel(sql_type = diesel::sql_types::Bool)]
+            read_only: bool,
+        }
+
+        let mut conn = self.get().await?;
+        let row: ReadOnlyRow = diesel::sql_query(
+            "SELECT pg_is_in_recovery() \
+             OR current_setting('default_transaction_read_only') = 'on' AS read_only",
+        )
+        .get_result(&mut conn)
+        .await?;
+        Ok(row.read_only)
+    }
 }
 
 /// Unified database pool that supports both SQLite and PostgreSQL.
@@ -130,12 +280,25 @@ impl DbPool {
     /// - A PostgreSQL URL is provided but the `postgres` feature is not enabled
     /// - The URL format is not recognized
     pub fn from_url(url: &str, no_tls: bool) -> Result<Self, DbError> {
+        Self::from_url_with_options(url, no_tls, &PoolOptions::default())
+    }
+
+    /// Create a pool from a database URL with explicit tuning.
+    pub fn from_url_with_options(
+        url: &str,
+        no_tls: bool,
+        options: &PoolOptions,
+    ) -> Result<Self, DbError> {
         // Validate the URL is supported by this build
         validate_database_url(url)?;
 
         #[cfg(feature = "postgres")]
         if is_postgres_url(url) {
-            return Ok(DbPool::Postgres(PgPool::new(url, 10, no_tls)?));
+            return Ok(DbPool::Postgres(PgPool::new(
+                url,
+                options.max_connections,
+                no_tls,
+            )?));
         }
         let _ = no_tls;
 
@@ -149,7 +312,9 @@ impl DbPool {
             ));
         }
 
-        Ok(DbPool::Sqlite(SqlitePool::new(url)))
+        Ok(DbPool::Sqlite(
+            SqlitePool::new(url).with_options(options.clone()),
+        ))
     }
 
     /// Create a SQLite pool from a file path.
@@ -157,6 +322,82 @@ impl DbPool {
         DbPool::Sqlite(SqlitePool::from_path(path))
     }
 
+    /// Reject writes on every connection from this pool.
+    ///
+    /// For SQLite this sets `PRAGMA query_only`. PostgreSQL replicas already
+    /// refuse writes server-side, so the pool is returned unchanged.
+    pub fn into_read_only(self) -> Self {
+        match self {
+            DbPool::Sqlite(pool) => DbPool::Sqlite(pool.into_read_only()),
+            #[cfg(feature = "postgres")]
+            other => other,
+        }
+    }
+
+    /// Detect whether the database only permits reads.
+    pub async fn detect_read_only(&self) -> Result<bool, DbError> {
+        match self {
+            DbPool::Sqlite(pool) => Ok(pool.detect_read_only()),
+            #[cfg(feature = "postgres")]
+            DbPool::Postgres(pool) => pool.detect_read_only().await,
+        }
+    }
+
+    /// Replace pooled connections so a replica's latest state is visible.
+    ///
+    /// SQLite opens a connection per operation, so there is nothing to
+    /// refresh; PostgreSQL idle connections older than `max_age` are dropped.
+    /// Returns the number of connections removed.
+    pub fn refresh_connections(&self, max_age: std::time::Duration) -> usize {
+        match self {
+            DbPool::Sqlite(_) => {
+                let _ = max_age;
+                0
+            }
+            #[cfg(feature = "postgres")]
+            DbPool::Postgres(pool) => pool.recycle_older_than(max_age),
+        }
+    }
+
+    /// Truncate the SQLite WAL. Returns `None` for PostgreSQL and read-only
+    /// pools, which have nothing to checkpoint.
+    pub async fn checkpoint_wal(&self) -> Result<Option<WalCheckpoint>, DbError> {
+        match self {
+            DbPool::Sqlite(pool) if !pool.is_read_only() => pool.checkpoint().await.map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Periodically truncate the SQLite WAL in the background.
+    ///
+    /// Meant for long-running processes (server, daemons). Does nothing for
+    /// PostgreSQL, read-only pools, or when no interval is configured.
+    pub fn spawn_wal_checkpoint(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let pool = match self {
+            DbPool::Sqlite(pool) if !pool.is_read_only() => pool.clone(),
+            _ => return None,
+        };
+        let interval = pool.options.checkpoint_interval?;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match pool.checkpoint().await {
+                    Ok(result) if result.busy != 0 => tracing::debug!(
+                        "WAL checkpoint blocked by readers ({}/{} frames)",
+                        result.checkpointed,
+                        result.log
+                    ),
+                    Ok(result) => {
+                        tracing::debug!("WAL checkpoint: {} frames", result.checkpointed)
+                    }
+                    Err(e) => tracing::warn!("WAL checkpoint failed: {}", e),
+                }
+            }
+        }))
+    }
+
     /// Check if this is a SQLite backend.
     pub fn is_sqlite(&self) -> bool {
         matches!(self, DbPool::Sqlite(_))
@@ -169,6 +410,50 @@ impl DbPool {
     }
 }
 
+/// Attempts made by [`retry_on_busy`] after the first failure.
+const BUSY_RETRIES: u32 = 5;
+
+/// Delay before the first busy retry; doubles on each attempt.
+const BUSY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Whether an error means SQLite gave up waiting for a lock.
+pub fn is_busy_error(e: &DbError) -> bool {
+    match e {
+        diesel::result::Error::DatabaseError(_, info) => {
+            let message = info.message();
+            message.contains("database is locked") || message.contains("database table is locked")
+        }
+        _ => false,
+    }
+}
+
+/// Run `op`, retrying with exponential backoff while SQLite reports the
+/// database as locked.
+///
+/// `busy_timeout` absorbs most contention, but SQLite fails immediately when
+/// a transaction that started as a reader needs to write while another
+/// connection holds the write lock. Write paths shared by concurrent workers
+/// go through this wrapper.
+pub async fn retry_on_busy<T, F, Fut>(mut op: F) -> Result<T, DbError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DbError>>,
+{
+    let mut delay = BUSY_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(e) if attempt < BUSY_RETRIES && is_busy_error(&e) => {
+                attempt += 1;
+                tracing::debug!("Database busy, retry {} in {:?}", attempt, delay);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
 /// Macro for running database operations on either backend.
 ///
 /// This macro handles the connection dispatch, allowing the same Diesel DSL
@@ -279,4 +564,75 @@ mod tests {
                 .is_postgres());
         }
     }
+
+    #[tokio::test]
+    async fn test_read_only_sqlite_rejects_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("replica.db");
+
+        let pool = SqlitePool::from_path(&path);
+        let mut conn = pool.get().await.unwrap();
+        conn.batch_execute("CREATE TABLE t (id INTEGER)")
+            .await
+            .unwrap();
+        drop(conn);
+        assert!(!pool.detect_read_only());
+
+        let ro = pool.into_read_only();
+        assert!(ro.is_read_only());
+        let mut conn = ro.get().await.unwrap();
+        conn.batch_execute("SELECT * FROM t").await.unwrap();
+        assert!(conn
+            .batch_execute("INSERT INTO t (id) VALUES (1)")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_writable_sqlite_uses_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = SqlitePool::from_path(&dir.path().join("wal.db"));
+        let mut conn = pool.get().await.unwrap();
+        conn.batch_execute("CREATE TABLE t (id INTEGER); INSERT INTO t VALUES (1)")
+            .await
+            .unwrap();
+
+        // The WAL file only exists while a connection is open.
+        assert!(dir.path().join("wal.db-wal").exists());
+        let result = pool.checkpoint().await.unwrap();
+        assert_eq!(result.busy, 0);
+        drop(conn);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_busy_retries_locked_errors() {
+        use diesel::result::DatabaseErrorKind;
+
+        let mut calls = 0;
+        let result = retry_on_busy(|| {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt < 3 {
+                    Err(diesel::result::Error::DatabaseError(
+                        DatabaseErrorKind::Unknown,
+                        Box::new("database is locked".to_string()),
+                    ))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<(), _> = retry_on_busy(|| {
+            calls += 1;
+            async { Err(diesel::result::Error::NotFound) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
 }
@@ -146,3 +146,18 @@ pub enum CrawlConfig {
     Table,
     SourceId,
 }
+
+#[derive(Iden)]
+pub enum PageHighlights {
+    Table,
+    Id,
+    DocumentId,
+    VersionId,
+    PageNumber,
+    StartOffset,
+    EndOffset,
+    SelectedText,
+    Comment,
+    Label,
+    CreatedAt,
+}
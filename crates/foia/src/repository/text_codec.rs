@@ -0,0 +1,97 @@
+//! Transparent zstd compression for large text columns.
+//!
+//! Compressed values stay in their TEXT columns as a marker followed by
+//! base64-encoded zstd, so both backends and the SQLite/Postgres copy tools
+//! handle them unchanged. Anything without the marker is returned as-is,
+//! which keeps uncompressed rows from before compression readable.
+
+use std::borrow::Cow;
+
+use base64::Engine;
+
+/// Prefix marking a compressed value. Starts with a control character that
+/// extracted text does not contain.
+const MARKER: &str = "\u{1}zstd:";
+
+/// zstd compression level: fast, and most of the gain on prose.
+const LEVEL: i32 = 3;
+
+/// Values shorter than this are stored as-is; base64 and the frame header
+/// eat the savings on short pages.
+pub const COMPRESS_MIN_BYTES: usize = 1024;
+
+/// Whether a stored value is compressed.
+pub fn is_compressed(stored: &str) -> bool {
+    stored.starts_with(MARKER)
+}
+
+/// Compress `text` for storage if it is long enough and gets smaller.
+pub fn compress(text: &str) -> Cow<'_, str> {
+    if text.len() < COMPRESS_MIN_BYTES || is_compressed(text) {
+        return Cow::Borrowed(text);
+    }
+    let Ok(compressed) = zstd::bulk::compress(text.as_bytes(), LEVEL) else {
+        return Cow::Borrowed(text);
+    };
+    let encoded = format!(
+        "{}{}",
+        MARKER,
+        base64::engine::general_purpose::STANDARD.encode(compressed)
+    );
+    if encoded.len() < text.len() {
+        Cow::Owned(encoded)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Restore a stored value, decompressing it if needed.
+///
+/// A value that carries the marker but does not decode is returned as
+/// stored rather than dropped.
+pub fn decompress(stored: String) -> String {
+    let Some(encoded) = stored.strip_prefix(MARKER) else {
+        return stored;
+    };
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| zstd::stream::decode_all(bytes.as_slice()).ok())
+        .and_then(|bytes| String::from_utf8(bytes).ok());
+    match decoded {
+        Some(text) => text,
+        None => {
+            tracing::warn!("Failed to decompress stored text; returning it as stored");
+            stored
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let text = "The agency withheld the record under exemption (b)(5). ".repeat(100);
+        let stored = compress(&text);
+        assert!(is_compressed(&stored));
+        assert!(stored.len() < text.len() / 4);
+        assert_eq!(decompress(stored.into_owned()), text);
+    }
+
+    #[test]
+    fn test_short_and_plain_text_untouched() {
+        let short = "page one";
+        assert!(matches!(compress(short), Cow::Borrowed(_)));
+        assert_eq!(decompress(short.to_string()), short);
+
+        // Already compressed values are not compressed twice
+        let text = "x".repeat(4096);
+        let stored = compress(&text).into_owned();
+        assert_eq!(compress(&stored), stored);
+
+        let corrupt = format!("{}not base64!", MARKER);
+        assert_eq!(decompress(corrupt.clone()), corrupt);
+    }
+}
@@ -1,6 +1,24 @@
 // @generated automatically by Diesel CLI.
 // Manually corrected to match actual database schema.
 
+diesel::table! {
+    agencies (id) {
+        id -> Text,
+        name -> Text,
+        parent_id -> Nullable<Text>,
+        config -> Text,
+        created_at -> Text,
+        updated_at -> Text,
+    }
+}
+
+diesel::table! {
+    agency_sources (source_id) {
+        source_id -> Text,
+        agency_id -> Text,
+    }
+}
+
 diesel::table! {
     configuration_history (uuid) {
         uuid -> Text,
@@ -19,6 +37,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    crawl_challenges (id) {
+        id -> Integer,
+        source_id -> Text,
+        domain -> Text,
+        url -> Text,
+        kind -> Text,
+        status -> Text,
+        detected_at -> Text,
+        resolved_at -> Nullable<Text>,
+        cookies -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     crawl_requests (id) {
         id -> Integer,
@@ -35,6 +67,7 @@ diesel::table! {
         error -> Nullable<Text>,
         was_conditional -> Integer,
         was_not_modified -> Integer,
+        identified -> Integer,
     }
 }
 
@@ -60,6 +93,64 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    listing_snapshots (id) {
+        id -> Integer,
+        source_id -> Text,
+        url -> Text,
+        content_hash -> Text,
+        content -> Text,
+        link_count -> Integer,
+        first_seen_at -> Text,
+        last_seen_at -> Text,
+        fetch_count -> Integer,
+    }
+}
+
+diesel::table! {
+    lost_files (version_id) {
+        version_id -> Integer,
+        document_id -> Text,
+        reason -> Text,
+        lost_at -> Text,
+    }
+}
+
+diesel::table! {
+    source_status_counts (source_id, status) {
+        source_id -> Text,
+        status -> Text,
+        count -> BigInt,
+    }
+}
+
+diesel::table! {
+    tag_counts (tag) {
+        tag -> Text,
+        count -> BigInt,
+    }
+}
+
+diesel::table! {
+    mime_type_counts (mime_type) {
+        mime_type -> Text,
+        count -> BigInt,
+    }
+}
+
+diesel::table! {
+    api_schemas (source_id, endpoint) {
+        source_id -> Text,
+        endpoint -> Text,
+        fingerprint -> Text,
+        paths -> Text,
+        first_seen_at -> Text,
+        last_seen_at -> Text,
+        drift -> Nullable<Text>,
+        drift_at -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     document_entities (id) {
         id -> Integer,
@@ -73,6 +164,204 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    page_highlights (id) {
+        id -> Integer,
+        document_id -> Text,
+        version_id -> Nullable<Integer>,
+        page_number -> Integer,
+        start_offset -> Integer,
+        end_offset -> Integer,
+        selected_text -> Text,
+        comment -> Nullable<Text>,
+        label -> Nullable<Text>,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    document_relations (id) {
+        id -> Integer,
+        document_id -> Text,
+        related_document_id -> Text,
+        relation_type -> Text,
+        virtual_file_id -> Nullable<Text>,
+        note -> Nullable<Text>,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    document_classifications (document_id) {
+        document_id -> Text,
+        record_type -> Text,
+        confidence -> Float,
+        classifier -> Text,
+        classified_at -> Text,
+    }
+}
+
+diesel::table! {
+    title_suggestions (document_id) {
+        document_id -> Text,
+        original_title -> Text,
+        suggested_title -> Text,
+        method -> Text,
+        status -> Text,
+        created_at -> Text,
+        reviewed_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    fetch_claims (source_id, claim_key) {
+        source_id -> Text,
+        claim_key -> Text,
+        url -> Text,
+        claimed_at -> Text,
+        expires_at -> Text,
+    }
+}
+
+diesel::table! {
+    legal_holds (document_id) {
+        document_id -> Text,
+        reason -> Text,
+        placed_at -> Text,
+    }
+}
+
+diesel::table! {
+    original_paths (document_id, content_hash) {
+        document_id -> Text,
+        content_hash -> Text,
+        path -> Text,
+    }
+}
+
+diesel::table! {
+    document_bates (id) {
+        id -> Integer,
+        document_id -> Text,
+        version_id -> Integer,
+        prefix -> Text,
+        prefix_key -> Text,
+        start_number -> BigInt,
+        end_number -> BigInt,
+        digits -> Integer,
+        first_page -> Integer,
+        last_page -> Integer,
+    }
+}
+
+diesel::table! {
+    document_exemptions (id) {
+        id -> Integer,
+        document_id -> Text,
+        version_id -> Integer,
+        page_number -> Integer,
+        code -> Text,
+        count -> Integer,
+    }
+}
+
+diesel::table! {
+    search_aliases (id) {
+        id -> Integer,
+        term -> Text,
+        expansion -> Text,
+        kind -> Text,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    access_rules (scope, target) {
+        scope -> Text,
+        target -> Text,
+        visibility -> Text,
+        embargo_until -> Nullable<Text>,
+        note -> Nullable<Text>,
+        updated_at -> Text,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> Integer,
+        name -> Text,
+        key_prefix -> Text,
+        key_hash -> Text,
+        read_only -> Integer,
+        sources -> Nullable<Text>,
+        rate_limit -> Nullable<Integer>,
+        requests -> BigInt,
+        created_at -> Text,
+        last_used_at -> Nullable<Text>,
+        revoked_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    api_key_usage (key_id, day) {
+        key_id -> Integer,
+        day -> Text,
+        requests -> BigInt,
+    }
+}
+
+diesel::table! {
+    document_changes (seq) {
+        seq -> BigInt,
+        document_id -> Text,
+        source_id -> Text,
+        change_type -> Text,
+        changed_at -> Text,
+    }
+}
+
+diesel::table! {
+    export_runs (id) {
+        id -> Integer,
+        job -> Text,
+        format -> Text,
+        destination -> Text,
+        started_at -> Text,
+        finished_at -> Nullable<Text>,
+        status -> Text,
+        artifact -> Nullable<Text>,
+        documents -> Integer,
+        bytes -> BigInt,
+        error -> Nullable<Text>,
+        change_seq -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    document_columns (id) {
+        id -> Integer,
+        document_id -> Text,
+        version_id -> Integer,
+        sheet -> Text,
+        position -> Integer,
+        name -> Text,
+        name_key -> Text,
+    }
+}
+
+diesel::table! {
+    domain_incidents (id) {
+        id -> Integer,
+        source_id -> Text,
+        domain -> Text,
+        responses -> Integer,
+        blocked -> Integer,
+        statuses -> Text,
+        opened_at -> Text,
+        paused_until -> Text,
+    }
+}
+
 diesel::table! {
     document_analysis_results (id) {
         id -> Integer,
@@ -107,6 +396,40 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    page_preprocessing (page_id) {
+        page_id -> Integer,
+        rotation -> Integer,
+        skew_angle -> Nullable<Float>,
+        steps -> Text,
+        tool -> Nullable<Text>,
+        applied_at -> Text,
+    }
+}
+
+diesel::table! {
+    page_text_decisions (page_id) {
+        page_id -> Integer,
+        method -> Text,
+        backend -> Nullable<Text>,
+        scores -> Text,
+        rationale -> Text,
+        decided_at -> Text,
+    }
+}
+
+diesel::table! {
+    page_text_revisions (id) {
+        id -> Integer,
+        page_id -> Integer,
+        editor -> Text,
+        previous_text -> Nullable<Text>,
+        text -> Text,
+        diff -> Text,
+        created_at -> Text,
+    }
+}
+
 diesel::table! {
     page_ocr_results (id) {
         id -> Integer,
@@ -125,6 +448,99 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    document_sections (id) {
+        id -> Integer,
+        document_id -> Text,
+        version_id -> Integer,
+        position -> Integer,
+        level -> Integer,
+        title -> Text,
+        first_page -> Integer,
+        last_page -> Integer,
+        origin -> Text,
+    }
+}
+
+diesel::table! {
+    uploads (id) {
+        id -> Integer,
+        document_id -> Text,
+        contributor -> Text,
+        description -> Nullable<Text>,
+        attribution -> Nullable<Text>,
+        status -> Text,
+        submitted_at -> Text,
+        reviewed_at -> Nullable<Text>,
+        reviewed_by -> Nullable<Text>,
+        review_note -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    takedown_cases (id) {
+        id -> Integer,
+        requester_name -> Text,
+        requester_contact -> Text,
+        reason -> Text,
+        status -> Text,
+        submitted_at -> Text,
+        updated_at -> Text,
+    }
+}
+
+diesel::table! {
+    takedown_documents (case_id, document_id) {
+        case_id -> Integer,
+        document_id -> Text,
+    }
+}
+
+diesel::table! {
+    takedown_events (id) {
+        id -> Integer,
+        case_id -> Integer,
+        action -> Text,
+        actor -> Text,
+        note -> Nullable<Text>,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    transcription_tasks (id) {
+        id -> Integer,
+        page_id -> Integer,
+        status -> Text,
+        accepted_text -> Nullable<Text>,
+        created_at -> Text,
+        updated_at -> Text,
+    }
+}
+
+diesel::table! {
+    transcription_entries (id) {
+        id -> Integer,
+        task_id -> Integer,
+        volunteer -> Text,
+        text -> Nullable<Text>,
+        assigned_at -> Text,
+        submitted_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    document_splits (document_id) {
+        document_id -> Text,
+        parent_id -> Text,
+        version_id -> Integer,
+        first_page -> Integer,
+        last_page -> Integer,
+        boundary -> Nullable<Text>,
+        created_at -> Text,
+    }
+}
+
 diesel::table! {
     document_versions (id) {
         id -> Integer,
@@ -236,6 +652,7 @@ diesel::table! {
         metadata -> Text,
         created_at -> Text,
         last_scraped -> Nullable<Text>,
+        paused -> Integer,
     }
 }
 
@@ -266,13 +683,45 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    virtual_file_annotations (virtual_file_id, annotation_type) {
+        virtual_file_id -> Text,
+        annotation_type -> Text,
+        version -> Integer,
+        data -> Nullable<Text>,
+        error -> Nullable<Text>,
+        created_at -> Text,
+    }
+}
+
+diesel::joinable!(document_bates -> documents (document_id));
+diesel::joinable!(document_classifications -> documents (document_id));
+diesel::joinable!(title_suggestions -> documents (document_id));
+diesel::joinable!(original_paths -> documents (document_id));
+diesel::joinable!(legal_holds -> documents (document_id));
+diesel::joinable!(document_sections -> documents (document_id));
+diesel::joinable!(uploads -> documents (document_id));
+diesel::joinable!(takedown_documents -> takedown_cases (case_id));
+diesel::joinable!(takedown_events -> takedown_cases (case_id));
+diesel::joinable!(document_splits -> documents (document_id));
+diesel::joinable!(document_columns -> documents (document_id));
 diesel::joinable!(document_entities -> documents (document_id));
+diesel::joinable!(document_exemptions -> documents (document_id));
 diesel::joinable!(document_pages -> documents (document_id));
 diesel::joinable!(document_versions -> documents (document_id));
 diesel::joinable!(document_versions -> archive_snapshots (archive_snapshot_id));
 diesel::joinable!(documents -> sources (source_id));
+diesel::joinable!(agency_sources -> agencies (agency_id));
 diesel::joinable!(virtual_files -> documents (document_id));
+diesel::joinable!(virtual_file_annotations -> virtual_files (virtual_file_id));
+diesel::joinable!(page_highlights -> documents (document_id));
+diesel::joinable!(document_relations -> virtual_files (virtual_file_id));
 diesel::joinable!(page_ocr_results -> document_pages (page_id));
+diesel::joinable!(page_preprocessing -> document_pages (page_id));
+diesel::joinable!(page_text_decisions -> document_pages (page_id));
+diesel::joinable!(page_text_revisions -> document_pages (page_id));
+diesel::joinable!(transcription_tasks -> document_pages (page_id));
+diesel::joinable!(transcription_entries -> transcription_tasks (task_id));
 
 diesel::joinable!(document_analysis_results -> documents (document_id));
 diesel::joinable!(document_analysis_results -> document_pages (page_id));
@@ -281,21 +730,59 @@ diesel::joinable!(document_analysis_results -> document_versions (version_id));
 diesel::joinable!(archive_checks -> document_versions (document_version_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    access_rules,
+    agencies,
+    agency_sources,
+    api_key_usage,
+    api_keys,
+    api_schemas,
     archive_checks,
     archive_snapshots,
     configuration_history,
+    crawl_challenges,
     crawl_config,
     crawl_requests,
     crawl_urls,
     document_analysis_results,
+    document_bates,
+    document_changes,
+    document_classifications,
+    document_columns,
     document_entities,
+    document_exemptions,
     document_pages,
+    document_relations,
+    document_sections,
+    document_splits,
     document_versions,
     documents,
+    domain_incidents,
+    export_runs,
+    fetch_claims,
+    legal_holds,
+    listing_snapshots,
+    lost_files,
+    mime_type_counts,
+    page_highlights,
     page_ocr_results,
+    page_preprocessing,
+    page_text_decisions,
+    page_text_revisions,
     rate_limit_state,
     scraper_configs,
+    search_aliases,
     service_status,
+    source_status_counts,
     sources,
+    tag_counts,
+    takedown_cases,
+    takedown_documents,
+    takedown_events,
+    title_suggestions,
+    transcription_entries,
+    transcription_tasks,
+    original_paths,
+    uploads,
+    virtual_file_annotations,
     virtual_files,
 );
@@ -0,0 +1,372 @@
+//! Secret references for API keys and credentials.
+//!
+//! Config values and credential env vars may hold `secret://name` instead of
+//! the secret itself. References are resolved at the point of use, checking
+//! in order:
+//!
+//! 1. The `FOIA_SECRET_<NAME>` environment variable
+//! 2. The encrypted secrets file (`secrets.file`)
+//! 3. The OS keychain (`secrets.keychain`, service `foia-secrets`)
+//!
+//! The file and keychain stores are configured once at startup with
+//! [`install_store`]; without it only environment variables are consulted.
+//! Values that are not references pass through unchanged.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::Config;
+use crate::encryption::{ContentKey, EncryptionError};
+
+/// URI scheme marking a config value as a secret reference.
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Keychain service name for stored secrets.
+#[cfg_attr(not(feature = "keychain"), allow(dead_code))]
+const KEYCHAIN_SERVICE: &str = "foia-secrets";
+
+/// Secret store used for this process.
+static SECRET_STORE: OnceLock<SecretStore> = OnceLock::new();
+
+/// Errors from resolving secrets or reading the secrets file.
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("secret '{name}' not found (set {env_var} or add it with 'foia secrets set')")]
+    NotFound { name: String, env_var: String },
+    #[error("invalid secret name '{0}': use letters, digits, '-', '_' or '.'")]
+    InvalidName(String),
+    #[error("secrets file {0} requires a key (set secrets.key_file or encryption.key_file)")]
+    NoKey(String),
+    #[error("failed to access secrets file {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid secrets file {0}")]
+    Format(String),
+    #[error("keychain error: {0}")]
+    Keychain(String),
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
+}
+
+/// Extract the secret name from a `secret://name` reference.
+pub fn secret_name(value: &str) -> Option<&str> {
+    value.strip_prefix(SECRET_SCHEME)
+}
+
+/// Check that a secret name is usable in references and env var names.
+pub fn validate_name(name: &str) -> Result<(), SecretError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(SecretError::InvalidName(name.to_string()))
+    }
+}
+
+/// Environment variable that overrides a secret, e.g. `groq-key` ->
+/// `FOIA_SECRET_GROQ_KEY`.
+pub fn env_var_name(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("FOIA_SECRET_{}", suffix)
+}
+
+/// Resolve a value that may be a `secret://` reference.
+pub fn resolve(value: &str) -> Result<String, SecretError> {
+    match secret_name(value) {
+        Some(name) => match SECRET_STORE.get() {
+            Some(store) => store.get(name),
+            None => SecretStore::default().get(name),
+        },
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Resolve a value, logging and returning `None` if the secret is missing.
+pub fn resolve_or_warn(value: &str) -> Option<String> {
+    match resolve(value) {
+        Ok(resolved) => Some(resolved),
+        Err(e) => {
+            tracing::warn!("{}", e);
+            None
+        }
+    }
+}
+
+/// Read a credential env var, resolving it if it holds a secret reference.
+pub fn env_var(name: &str) -> Option<String> {
+    let value = std::env::var(name).ok().filter(|v| !v.is_empty())?;
+    resolve_or_warn(&value)
+}
+
+/// Install the process-wide secret store. Returns `false` if one was
+/// already installed.
+pub fn install_store(store: SecretStore) -> bool {
+    SECRET_STORE.set(store).is_ok()
+}
+
+/// Resolves secret names against the configured stores.
+#[derive(Debug, Default)]
+pub struct SecretStore {
+    file: BTreeMap<String, String>,
+    keychain: bool,
+    /// Keychain lookups can prompt or hit IPC, so hits are cached.
+    keychain_cache: Mutex<HashMap<String, String>>,
+}
+
+impl SecretStore {
+    /// Build the store described by the `secrets` config section.
+    pub fn from_config(config: &Config) -> Result<Self, SecretError> {
+        let file = match SecretsFile::from_config(config)? {
+            Some(file) => file.load()?,
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            file,
+            keychain: config.secrets.keychain,
+            keychain_cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Look up a secret by name.
+    pub fn get(&self, name: &str) -> Result<String, SecretError> {
+        let env_name = env_var_name(name);
+        if let Some(value) = std::env::var(&env_name).ok().filter(|v| !v.is_empty()) {
+            return Ok(value);
+        }
+        if let Some(value) = self.file.get(name) {
+            return Ok(value.clone());
+        }
+        if self.keychain {
+            let mut cache = self.keychain_cache.lock().unwrap();
+            if let Some(value) = cache.get(name) {
+                return Ok(value.clone());
+            }
+            if let Some(value) = keychain_get(name)? {
+                cache.insert(name.to_string(), value.clone());
+                return Ok(value);
+            }
+        }
+        Err(SecretError::NotFound {
+            name: name.to_string(),
+            env_var: env_name,
+        })
+    }
+}
+
+/// Read a secret from the OS keychain. `Ok(None)` if the entry is missing.
+#[cfg(feature = "keychain")]
+pub fn keychain_get(name: &str) -> Result<Option<String>, SecretError> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, name)
+        .map_err(|e| SecretError::Keychain(e.to_string()))?;
+    match entry.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(SecretError::Keychain(e.to_string())),
+    }
+}
+
+/// Read a secret from the OS keychain. `Ok(None)` if the entry is missing.
+#[cfg(not(feature = "keychain"))]
+pub fn keychain_get(_name: &str) -> Result<Option<String>, SecretError> {
+    Err(keychain_unsupported())
+}
+
+/// Store a secret in the OS keychain.
+#[cfg(feature = "keychain")]
+pub fn keychain_set(name: &str, value: &str) -> Result<(), SecretError> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, name)
+        .and_then(|e| e.set_password(value))
+        .map_err(|e| SecretError::Keychain(e.to_string()))
+}
+
+/// Store a secret in the OS keychain.
+#[cfg(not(feature = "keychain"))]
+pub fn keychain_set(_name: &str, _value: &str) -> Result<(), SecretError> {
+    Err(keychain_unsupported())
+}
+
+/// Remove a secret from the OS keychain. Returns `false` if it was missing.
+#[cfg(feature = "keychain")]
+pub fn keychain_remove(name: &str) -> Result<bool, SecretError> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, name)
+        .map_err(|e| SecretError::Keychain(e.to_string()))?;
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(SecretError::Keychain(e.to_string())),
+    }
+}
+
+/// Remove a secret from the OS keychain. Returns `false` if it was missing.
+#[cfg(not(feature = "keychain"))]
+pub fn keychain_remove(_name: &str) -> Result<bool, SecretError> {
+    Err(keychain_unsupported())
+}
+
+#[cfg(not(feature = "keychain"))]
+fn keychain_unsupported() -> SecretError {
+    SecretError::Keychain("built without keychain support (enable the `keychain` feature)".into())
+}
+
+/// The encrypted secrets file: a JSON object of name to value, sealed with
+/// the same format as encrypted document files.
+pub struct SecretsFile {
+    path: PathBuf,
+    key: ContentKey,
+}
+
+impl SecretsFile {
+    pub fn new(path: PathBuf, key: ContentKey) -> Self {
+        Self { path, key }
+    }
+
+    /// The secrets file configured under `secrets.file`, if any.
+    ///
+    /// Uses `secrets.key_file` when set, otherwise the document encryption
+    /// key.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, SecretError> {
+        let Some(ref file) = config.secrets.file else {
+            return Ok(None);
+        };
+        let base_dir = config
+            .base_dir()
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        let path = config.resolve_path(file, &base_dir);
+
+        let key = match config.secrets.key_file {
+            Some(ref key_file) => Some(ContentKey::load_file(
+                &config.resolve_path(key_file, &base_dir),
+            )?),
+            None => ContentKey::from_config(config)?,
+        };
+        let key = key.ok_or_else(|| SecretError::NoKey(path.display().to_string()))?;
+        Ok(Some(Self::new(path, key)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read all secrets. A missing file is treated as empty.
+    pub fn load(&self) -> Result<BTreeMap<String, String>, SecretError> {
+        let data = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(source) => return Err(self.io_error(source)),
+        };
+        let plaintext = self.key.decrypt(&data)?;
+        serde_json::from_slice(&plaintext)
+            .map_err(|_| SecretError::Format(self.path.display().to_string()))
+    }
+
+    /// Replace the file contents with `secrets`.
+    pub fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), SecretError> {
+        let json = serde_json::to_vec(secrets)
+            .map_err(|_| SecretError::Format(self.path.display().to_string()))?;
+        let sealed = self.key.encrypt(&json);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| self.io_error(e))?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        write_private(&tmp, &sealed).map_err(|e| self.io_error(e))?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| self.io_error(e))
+    }
+
+    fn io_error(&self, source: std::io::Error) -> SecretError {
+        SecretError::Io {
+            path: self.path.display().to_string(),
+            source,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_values_pass_through() {
+        assert_eq!(resolve("sk-literal").unwrap(), "sk-literal");
+        assert_eq!(secret_name("secret://groq"), Some("groq"));
+        assert_eq!(secret_name("socks5://host:1080"), None);
+    }
+
+    #[test]
+    fn test_env_var_name() {
+        assert_eq!(env_var_name("groq-key"), "FOIA_SECRET_GROQ_KEY");
+        assert_eq!(env_var_name("proxy.tor"), "FOIA_SECRET_PROXY_TOR");
+        assert!(validate_name("groq-key").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("a/b").is_err());
+    }
+
+    #[test]
+    fn test_store_lookup_order() {
+        let store = SecretStore {
+            file: BTreeMap::from([
+                ("from-file".to_string(), "file-value".to_string()),
+                ("shadowed-test".to_string(), "file-value".to_string()),
+            ]),
+            ..Default::default()
+        };
+        std::env::set_var("FOIA_SECRET_SHADOWED_TEST", "env-value");
+
+        assert_eq!(store.get("from-file").unwrap(), "file-value");
+        assert_eq!(store.get("shadowed-test").unwrap(), "env-value");
+        assert!(matches!(
+            store.get("missing-test"),
+            Err(SecretError::NotFound { .. })
+        ));
+
+        std::env::remove_var("FOIA_SECRET_SHADOWED_TEST");
+    }
+
+    #[test]
+    fn test_secrets_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = SecretsFile::new(dir.path().join("secrets.enc"), ContentKey::generate());
+
+        assert!(file.load().unwrap().is_empty());
+        let secrets = BTreeMap::from([("openai".to_string(), "sk-test".to_string())]);
+        file.save(&secrets).unwrap();
+
+        let raw = std::fs::read(file.path()).unwrap();
+        assert!(crate::encryption::is_encrypted(&raw));
+        assert_eq!(file.load().unwrap(), secrets);
+    }
+}
@@ -0,0 +1,311 @@
+//! Access restrictions: which documents the public may see.
+//!
+//! Sensitive records often live next to published ones: a release still
+//! under review, a source kept for internal research, a document promised
+//! to a reporter until a publication date. A rule marks a document or a
+//! whole source as internal-only or embargoed until a date; a document
+//! rule overrides its source's, so one record can be released from a
+//! restricted source or held back from a public one.
+//!
+//! Everything without a rule is public. Restricted documents are withheld
+//! from the web server's anonymous visitors and from export jobs unless
+//! those are configured to include them.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Who may see a document or source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    /// Only visible to operators presenting the access token.
+    Internal,
+    /// Internal until the given date, public from then on.
+    Embargoed(NaiveDate),
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Internal => "internal",
+            Self::Embargoed(_) => "embargo",
+        }
+    }
+
+    /// Parse a stored or user-supplied visibility. An embargo needs a date.
+    pub fn parse(visibility: &str, until: Option<NaiveDate>) -> Option<Self> {
+        match (visibility, until) {
+            ("public", _) => Some(Self::Public),
+            ("internal", _) => Some(Self::Internal),
+            ("embargo" | "embargoed", Some(date)) => Some(Self::Embargoed(date)),
+            _ => None,
+        }
+    }
+
+    /// The embargo end date, if embargoed.
+    pub fn until(&self) -> Option<NaiveDate> {
+        match self {
+            Self::Embargoed(date) => Some(*date),
+            _ => None,
+        }
+    }
+
+    /// Whether the public is kept out on `today`.
+    pub fn is_restricted(&self, today: NaiveDate) -> bool {
+        match self {
+            Self::Public => false,
+            Self::Internal => true,
+            Self::Embargoed(until) => today < *until,
+        }
+    }
+}
+
+/// What a rule applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessScope {
+    Document,
+    /// Every document of a source.
+    Source,
+}
+
+impl AccessScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Document => "document",
+            Self::Source => "source",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "document" => Some(Self::Document),
+            "source" => Some(Self::Source),
+            _ => None,
+        }
+    }
+}
+
+/// Visibility of one document or source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessRule {
+    pub scope: AccessScope,
+    pub target: String,
+    pub visibility: Visibility,
+    /// Why it is restricted, for the operators.
+    pub note: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// All access rules, resolved per document.
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    documents: HashMap<String, Visibility>,
+    sources: HashMap<String, Visibility>,
+}
+
+impl AccessPolicy {
+    pub fn new(rules: impl IntoIterator<Item = AccessRule>) -> Self {
+        let mut policy = Self::default();
+        for rule in rules {
+            let map = match rule.scope {
+                AccessScope::Document => &mut policy.documents,
+                AccessScope::Source => &mut policy.sources,
+            };
+            map.insert(rule.target, rule.visibility);
+        }
+        policy
+    }
+
+    /// Whether no rule is set, so everything is public.
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty() && self.sources.is_empty()
+    }
+
+    /// Effective visibility of a document: its own rule, else its source's.
+    pub fn visibility(&self, doc_id: &str, source_id: &str) -> Visibility {
+        self.documents
+            .get(doc_id)
+            .or_else(|| self.sources.get(source_id))
+            .copied()
+            .unwrap_or(Visibility::Public)
+    }
+
+    /// What the public may not see on `today`.
+    pub fn filter(&self, today: NaiveDate) -> AccessFilter {
+        let mut filter = AccessFilter::default();
+        for (id, visibility) in &self.documents {
+            if visibility.is_restricted(today) {
+                filter.hidden_documents.push(id.clone());
+            } else {
+                filter.released_documents.push(id.clone());
+            }
+        }
+        filter.hidden_sources = self
+            .sources
+            .iter()
+            .filter(|(_, v)| v.is_restricted(today))
+            .map(|(id, _)| id.clone())
+            .collect();
+        if filter.hidden_sources.is_empty() {
+            // Only needed to override a hidden source
+            filter.released_documents.clear();
+        }
+        filter.hidden_documents.sort();
+        filter.hidden_sources.sort();
+        filter.released_documents.sort();
+        filter
+    }
+}
+
+/// Documents withheld from the public at one point in time, as sorted ID
+/// lists that queries can filter on.
+///
+/// A document is hidden when it is in `hidden_documents`, or when its
+/// source is in `hidden_sources` and it is not in `released_documents`, or
+/// when `only_sources` is set and does not list its source.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessFilter {
+    pub hidden_documents: Vec<String>,
+    pub hidden_sources: Vec<String>,
+    /// Documents of hidden sources that their own rule makes public.
+    pub released_documents: Vec<String>,
+    /// The only sources that may be seen, for API keys scoped to sources.
+    pub only_sources: Option<Vec<String>>,
+}
+
+impl AccessFilter {
+    /// Whether nothing is hidden.
+    pub fn is_empty(&self) -> bool {
+        self.hidden_documents.is_empty()
+            && self.hidden_sources.is_empty()
+            && self.only_sources.is_none()
+    }
+
+    /// Hide every source but `sources`, on top of the rules.
+    pub fn limit_to_sources(&mut self, sources: &[String]) {
+        let mut sources = sources.to_vec();
+        sources.sort();
+        self.only_sources = Some(sources);
+    }
+
+    /// Whether documents of a source may be seen at all.
+    pub fn shows_source(&self, source_id: &str) -> bool {
+        !contains(&self.hidden_sources, source_id)
+            && self
+                .only_sources
+                .as_ref()
+                .is_none_or(|only| contains(only, source_id))
+    }
+
+    /// Whether the public may see a document.
+    pub fn allows(&self, doc_id: &str, source_id: &str) -> bool {
+        if contains(&self.hidden_documents, doc_id) {
+            return false;
+        }
+        if self
+            .only_sources
+            .as_ref()
+            .is_some_and(|only| !contains(only, source_id))
+        {
+            return false;
+        }
+        !contains(&self.hidden_sources, source_id) || contains(&self.released_documents, doc_id)
+    }
+}
+
+/// Look up an ID in one of the sorted [`AccessFilter`] lists.
+fn contains(sorted: &[String], id: &str) -> bool {
+    sorted.binary_search_by(|s| s.as_str().cmp(id)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn rule(scope: AccessScope, target: &str, visibility: Visibility) -> AccessRule {
+        AccessRule {
+            scope,
+            target: target.to_string(),
+            visibility,
+            note: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_visibility_parse() {
+        assert_eq!(Visibility::parse("public", None), Some(Visibility::Public));
+        assert_eq!(
+            Visibility::parse("embargo", Some(date("2027-01-01"))),
+            Some(Visibility::Embargoed(date("2027-01-01")))
+        );
+        assert_eq!(Visibility::parse("embargo", None), None);
+        assert_eq!(Visibility::parse("secret", None), None);
+    }
+
+    #[test]
+    fn test_embargo_lifts_on_date() {
+        let embargo = Visibility::Embargoed(date("2026-11-01"));
+        assert!(embargo.is_restricted(date("2026-10-31")));
+        assert!(!embargo.is_restricted(date("2026-11-01")));
+        assert!(Visibility::Internal.is_restricted(date("2099-01-01")));
+    }
+
+    #[test]
+    fn test_document_rule_overrides_source() {
+        let policy = AccessPolicy::new([
+            rule(AccessScope::Source, "fbi", Visibility::Internal),
+            rule(AccessScope::Document, "doc-released", Visibility::Public),
+            rule(AccessScope::Document, "doc-held", Visibility::Internal),
+            rule(
+                AccessScope::Document,
+                "doc-embargoed",
+                Visibility::Embargoed(date("2026-11-01")),
+            ),
+        ]);
+        assert_eq!(policy.visibility("doc-released", "fbi"), Visibility::Public);
+        assert_eq!(policy.visibility("doc-other", "fbi"), Visibility::Internal);
+        assert_eq!(policy.visibility("doc-other", "cia"), Visibility::Public);
+
+        let filter = policy.filter(date("2026-10-16"));
+        assert!(filter.allows("doc-released", "fbi"));
+        assert!(!filter.allows("doc-other", "fbi"));
+        assert!(!filter.allows("doc-held", "cia"));
+        assert!(!filter.allows("doc-embargoed", "cia"));
+        assert!(filter.allows("doc-other", "cia"));
+
+        let later = policy.filter(date("2026-11-01"));
+        assert!(later.allows("doc-embargoed", "cia"));
+    }
+
+    #[test]
+    fn test_limit_to_sources() {
+        let policy = AccessPolicy::new([rule(
+            AccessScope::Document,
+            "doc-held",
+            Visibility::Internal,
+        )]);
+        let mut filter = policy.filter(date("2026-10-16"));
+        filter.limit_to_sources(&["fbi".to_string()]);
+        assert!(filter.allows("doc-1", "fbi"));
+        assert!(!filter.allows("doc-held", "fbi"));
+        assert!(!filter.allows("doc-2", "cia"));
+        assert!(filter.shows_source("fbi"));
+        assert!(!filter.shows_source("cia"));
+    }
+
+    #[test]
+    fn test_empty_policy_hides_nothing() {
+        let policy = AccessPolicy::new([rule(AccessScope::Document, "doc-1", Visibility::Public)]);
+        let filter = policy.filter(date("2026-10-16"));
+        assert!(filter.is_empty());
+        assert!(filter.released_documents.is_empty());
+        assert!(filter.allows("doc-1", "any"));
+    }
+}
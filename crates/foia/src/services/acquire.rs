@@ -0,0 +1,420 @@
+//! On-demand acquisition of a single URL.
+//!
+//! Fetches one URL outside any crawl and stores it like a scraped document,
+//! for "archive this page now" requests from the CLI and the HTTP API.
+//! Pages the server can't fetch itself (login-gated portals) can instead be
+//! captured in the browser and submitted as HTML or MHTML.
+//! Text extraction is left to the caller, which owns the analysis backends.
+
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::http_client::HttpClient;
+use crate::models::{Document, DocumentVersion, Source, SourceType};
+use crate::repository::{DieselDocumentRepository, DieselSourceRepository};
+use crate::storage::{save_document_async, DocumentInput};
+use crate::utils::extract_title_from_url;
+
+/// Source that ad-hoc URLs are filed under when none is given.
+pub const ADHOC_SOURCE_ID: &str = "adhoc";
+
+/// Timeout for fetching an ad-hoc URL.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A URL to acquire.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AcquireRequest {
+    pub url: String,
+    /// Source to file the document under (default: `adhoc`). Created if it
+    /// does not exist.
+    #[serde(default)]
+    pub source_id: Option<String>,
+    /// Tags added to the document.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl AcquireRequest {
+    pub fn source_id(&self) -> &str {
+        self.source_id
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or(ADHOC_SOURCE_ID)
+    }
+
+    /// Check the URL is absolute http(s), returning a message for the user.
+    pub fn validate(&self) -> Result<url::Url, String> {
+        let parsed = url::Url::parse(self.url.trim()).map_err(|e| format!("invalid URL: {}", e))?;
+        match parsed.scheme() {
+            "http" | "https" => Ok(parsed),
+            other => Err(format!("unsupported URL scheme: {}", other)),
+        }
+    }
+}
+
+/// A page captured client-side, e.g. by a bookmarklet.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CaptureRequest {
+    /// Address of the captured page; becomes the document URL.
+    pub url: String,
+    /// Page content as HTML or MHTML.
+    pub content: String,
+    /// `text/html` or `multipart/related` (MHTML). Sniffed when omitted.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Page title as seen in the browser.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub source_id: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Who submitted the capture, recorded in the version metadata.
+    #[serde(default)]
+    pub submitted_by: Option<String>,
+    /// When the browser captured the page (default: now).
+    #[serde(default)]
+    pub captured_at: Option<DateTime<Utc>>,
+}
+
+impl CaptureRequest {
+    /// The URL and source rules are the same as for fetched URLs.
+    fn as_acquire(&self) -> AcquireRequest {
+        AcquireRequest {
+            url: self.url.clone(),
+            source_id: self.source_id.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    pub fn source_id(&self) -> &str {
+        self.source_id
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or(ADHOC_SOURCE_ID)
+    }
+
+    /// Check the URL and content, returning a message for the user.
+    pub fn validate(&self) -> Result<url::Url, String> {
+        let parsed = self.as_acquire().validate()?;
+        if self.content.trim().is_empty() {
+            return Err("captured content is empty".to_string());
+        }
+        match self.mime_type().as_str() {
+            "text/html" | "multipart/related" => Ok(parsed),
+            other => Err(format!("unsupported capture type: {}", other)),
+        }
+    }
+
+    /// Normalized MIME type of the content, without parameters.
+    pub fn mime_type(&self) -> String {
+        match self.content_type.as_deref() {
+            Some(ct) => {
+                let ct = ct.split(';').next().unwrap_or("").trim().to_lowercase();
+                match ct.as_str() {
+                    "application/x-mimearchive" => "multipart/related".to_string(),
+                    "application/xhtml+xml" => "text/html".to_string(),
+                    _ => ct,
+                }
+            }
+            None if is_mhtml(&self.content) => "multipart/related".to_string(),
+            None => "text/html".to_string(),
+        }
+    }
+}
+
+/// Whether `content` looks like an MHTML archive rather than bare HTML.
+fn is_mhtml(content: &str) -> bool {
+    let head: String = content
+        .trim_start()
+        .chars()
+        .take(2048)
+        .collect::<String>()
+        .to_lowercase();
+    !head.starts_with('<') && head.contains("mime-version:") && head.contains("multipart/related")
+}
+
+/// A stored ad-hoc document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AcquireOutcome {
+    pub document_id: String,
+    pub source_id: String,
+    /// False when the URL was already archived; a changed page still adds
+    /// a new version.
+    pub created: bool,
+    pub mime_type: String,
+    pub bytes: u64,
+}
+
+/// Fetch `request.url` and store it as a document.
+///
+/// Requests use the privacy settings and, when the source has a scraper
+/// config, its user agent, credentials and privacy overrides.
+pub async fn fetch_and_store(
+    config: &Config,
+    doc_repo: &DieselDocumentRepository,
+    source_repo: &DieselSourceRepository,
+    documents_dir: &Path,
+    request: &AcquireRequest,
+) -> anyhow::Result<AcquireOutcome> {
+    let parsed = request.validate().map_err(anyhow::Error::msg)?;
+    let url = parsed.as_str();
+    let source_id = request.source_id();
+    ensure_source(source_repo, source_id, &parsed).await?;
+
+    let scraper = config.scrapers.get(source_id);
+    let privacy = match scraper {
+        Some(s) => s.privacy.apply_to(&config.privacy),
+        None => config.privacy.clone(),
+    };
+    let mut builder =
+        HttpClient::builder(source_id, FETCH_TIMEOUT, Duration::ZERO).privacy(&privacy);
+    if let Some(scraper) = scraper {
+        if let Some(ua) = scraper.user_agent.as_deref() {
+            builder = builder.user_agent(ua);
+        }
+        if !scraper.auth.is_default() {
+            builder = builder.default_headers(scraper.auth.to_headers()?);
+        }
+    }
+    let client = builder.build().map_err(anyhow::Error::msg)?;
+
+    let response = client.get(url, None, None).await?;
+    if response.is_challenge_paused() {
+        anyhow::bail!("{} is paused behind an unsolved challenge", url);
+    }
+    if !response.is_success() {
+        anyhow::bail!("HTTP {} fetching {}", response.status, url);
+    }
+
+    let mime_type = response
+        .content_type()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let original_filename = response.content_disposition_filename();
+    let server_date = response.last_modified().and_then(|lm| {
+        chrono::DateTime::parse_from_rfc2822(lm)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    });
+    let content = response.bytes().await?;
+    if content.is_empty() {
+        anyhow::bail!("{} returned an empty body", url);
+    }
+
+    let input = DocumentInput {
+        url: url.to_string(),
+        title: extract_title_from_url(url),
+        mime_type: mime_type.clone(),
+        metadata: serde_json::json!({
+            "fetched_at": Utc::now().to_rfc3339(),
+            "acquired": "adhoc",
+        }),
+        original_filename,
+        server_date,
+    };
+    let created = save_document_async(doc_repo, &content, &input, source_id, documents_dir).await?;
+    let doc = tag_saved_document(doc_repo, url, &request.tags).await?;
+
+    Ok(AcquireOutcome {
+        document_id: doc.id,
+        source_id: doc.source_id,
+        created,
+        mime_type,
+        bytes: content.len() as u64,
+    })
+}
+
+/// Store a client-side page capture as a document.
+///
+/// A capture of an already archived URL adds a new version when the content
+/// differs. Each capture's submitter and time are appended to the document's
+/// `captures` metadata.
+pub async fn store_capture(
+    doc_repo: &DieselDocumentRepository,
+    source_repo: &DieselSourceRepository,
+    documents_dir: &Path,
+    request: &CaptureRequest,
+) -> anyhow::Result<AcquireOutcome> {
+    let parsed = request.validate().map_err(anyhow::Error::msg)?;
+    let url = parsed.as_str();
+    let source_id = request.source_id();
+    ensure_source(source_repo, source_id, &parsed).await?;
+
+    let mime_type = request.mime_type();
+    let now = Utc::now();
+    let captured_at = request.captured_at.unwrap_or(now);
+    let title = request
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| extract_title_from_url(url));
+    let submitted_by = request
+        .submitted_by
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+
+    let input = DocumentInput {
+        url: url.to_string(),
+        title,
+        mime_type: mime_type.clone(),
+        metadata: serde_json::json!({ "acquired": "capture" }),
+        original_filename: None,
+        server_date: Some(captured_at),
+    };
+    let content = request.content.as_bytes();
+    let created = save_document_async(doc_repo, content, &input, source_id, documents_dir).await?;
+
+    // Versions have no metadata of their own, so captures are logged on the
+    // document keyed by content hash.
+    let mut doc = load_saved_document(doc_repo, url).await?;
+    add_tags(&mut doc, &request.tags);
+    let entry = serde_json::json!({
+        "content_hash": DocumentVersion::compute_hash(content),
+        "captured_at": captured_at.to_rfc3339(),
+        "submitted_at": now.to_rfc3339(),
+        "submitted_by": submitted_by,
+    });
+    if !doc.metadata.is_object() {
+        doc.metadata = serde_json::json!({});
+    }
+    match doc.metadata.get_mut("captures") {
+        Some(serde_json::Value::Array(captures)) => captures.push(entry),
+        _ => doc.metadata["captures"] = serde_json::json!([entry]),
+    }
+    doc_repo.save(&doc).await?;
+
+    Ok(AcquireOutcome {
+        document_id: doc.id,
+        source_id: doc.source_id,
+        created,
+        mime_type,
+        bytes: content.len() as u64,
+    })
+}
+
+/// Create `source_id` as a custom source if it does not exist yet.
+async fn ensure_source(
+    source_repo: &DieselSourceRepository,
+    source_id: &str,
+    url: &url::Url,
+) -> anyhow::Result<()> {
+    if source_repo.get(source_id).await?.is_some() {
+        return Ok(());
+    }
+    let source = Source {
+        id: source_id.to_string(),
+        name: if source_id == ADHOC_SOURCE_ID {
+            "Ad-hoc submissions".to_string()
+        } else {
+            source_id.to_string()
+        },
+        source_type: SourceType::Custom,
+        base_url: format!("{}://{}", url.scheme(), url.host_str().unwrap_or("unknown")),
+        metadata: serde_json::json!({}),
+        created_at: Utc::now(),
+        last_scraped: None,
+        paused: false,
+    };
+    source_repo.save(&source).await?;
+    Ok(())
+}
+
+/// Load the document just saved for `url` and add any missing `tags`.
+async fn tag_saved_document(
+    doc_repo: &DieselDocumentRepository,
+    url: &str,
+    tags: &[String],
+) -> anyhow::Result<Document> {
+    let mut doc = load_saved_document(doc_repo, url).await?;
+    if add_tags(&mut doc, tags) {
+        doc_repo.save(&doc).await?;
+    }
+    Ok(doc)
+}
+
+pub(crate) async fn load_saved_document(
+    doc_repo: &DieselDocumentRepository,
+    url: &str,
+) -> anyhow::Result<Document> {
+    doc_repo
+        .get_by_url(url)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Document for {} missing after save", url))
+}
+
+/// Add the non-blank `tags` the document lacks; true if any were added.
+pub(crate) fn add_tags(doc: &mut Document, tags: &[String]) -> bool {
+    let before = doc.tags.len();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !doc.tags.iter().any(|t| t == tag) {
+            doc.tags.push(tag.to_string());
+        }
+    }
+    doc.tags.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_validation() {
+        let request = AcquireRequest {
+            url: " https://agency.gov/memo.pdf ".to_string(),
+            ..Default::default()
+        };
+        assert!(request.validate().is_ok());
+        assert_eq!(request.source_id(), ADHOC_SOURCE_ID);
+
+        let request = AcquireRequest {
+            url: "file:///etc/passwd".to_string(),
+            source_id: Some("fbi".to_string()),
+            ..Default::default()
+        };
+        assert!(request.validate().unwrap_err().contains("scheme"));
+        assert_eq!(request.source_id(), "fbi");
+
+        let request = AcquireRequest {
+            url: "memo.pdf".to_string(),
+            ..Default::default()
+        };
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_capture_mime_type() {
+        let mut capture = CaptureRequest {
+            url: "https://portal.agency.gov/case/42".to_string(),
+            content: "<html><body>Closed</body></html>".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(capture.mime_type(), "text/html");
+        assert!(capture.validate().is_ok());
+
+        capture.content = "From: <Saved by Blink>\r\nMIME-Version: 1.0\r\n\
+                           Content-Type: multipart/related; boundary=\"b\"\r\n"
+            .to_string();
+        assert_eq!(capture.mime_type(), "multipart/related");
+
+        capture.content_type = Some("application/x-mimearchive".to_string());
+        assert_eq!(capture.mime_type(), "multipart/related");
+
+        capture.content_type = Some("application/pdf".to_string());
+        assert!(capture.validate().unwrap_err().contains("unsupported"));
+
+        capture.content_type = None;
+        capture.content = "  ".to_string();
+        assert!(capture.validate().unwrap_err().contains("empty"));
+    }
+}
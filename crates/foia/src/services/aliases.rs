@@ -0,0 +1,158 @@
+//! Search aliases: acronyms, code names and other names for the same thing.
+//!
+//! Released records rarely spell things the same way twice: a memo says
+//! "OIG", the cover letter "Office of Inspector General", and a source is
+//! mentioned by code name in one file and by name in the next. An alias
+//! links a term to what it stands for, and a search for either also finds
+//! the other.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+/// Most query variants [`expand_query`] returns, the query itself included.
+pub const MAX_VARIANTS: usize = 8;
+
+/// What an alias is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasKind {
+    /// Acronym or abbreviation: `OIG`.
+    Acronym,
+    /// Program or operation code name: `MKULTRA`.
+    CodeName,
+    /// Another name of a person: maiden name, alias, transliteration.
+    Person,
+}
+
+impl AliasKind {
+    pub const ALL: [AliasKind; 3] = [Self::Acronym, Self::CodeName, Self::Person];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Acronym => "acronym",
+            Self::CodeName => "code_name",
+            Self::Person => "person",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Acronym => "Acronym",
+            Self::CodeName => "Code name",
+            Self::Person => "Person",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "acronym" => Some(Self::Acronym),
+            "code_name" | "codename" => Some(Self::CodeName),
+            "person" => Some(Self::Person),
+            _ => None,
+        }
+    }
+}
+
+/// A term and what it stands for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchAlias {
+    pub id: i32,
+    pub term: String,
+    pub expansion: String,
+    pub kind: AliasKind,
+}
+
+/// Replace whole-word occurrences of `phrase` in `text`, ignoring case.
+fn replace_phrase(text: &str, phrase: &str, with: &str) -> Option<String> {
+    let pattern = format!(
+        r"(?i)(^|[^\p{{Alphabetic}}\p{{N}}]){}($|[^\p{{Alphabetic}}\p{{N}}])",
+        regex::escape(phrase.trim())
+    );
+    let re = Regex::new(&pattern).ok()?;
+    if !re.is_match(text) {
+        return None;
+    }
+    Some(
+        re.replace_all(text, |c: &Captures| format!("{}{}{}", &c[1], with, &c[2]))
+            .into_owned(),
+    )
+}
+
+/// The query followed by its variants with aliased phrases swapped for
+/// their counterparts, in both directions.
+///
+/// `OIG report` expands to `Office of Inspector General report`, and the
+/// expansion back to the acronym. Variants of variants are included up to
+/// [`MAX_VARIANTS`], so a query naming two aliased things also finds both
+/// spelled out.
+pub fn expand_query(query: &str, aliases: &[SearchAlias]) -> Vec<String> {
+    let mut variants = vec![query.trim().to_string()];
+    for alias in aliases {
+        for (from, to) in [
+            (&alias.term, &alias.expansion),
+            (&alias.expansion, &alias.term),
+        ] {
+            if from.trim().is_empty() {
+                continue;
+            }
+            for i in 0..variants.len() {
+                if variants.len() >= MAX_VARIANTS {
+                    return variants;
+                }
+                let Some(variant) = replace_phrase(&variants[i], from, to) else {
+                    continue;
+                };
+                if !variants.iter().any(|v| v.eq_ignore_ascii_case(&variant)) {
+                    variants.push(variant);
+                }
+            }
+        }
+    }
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(term: &str, expansion: &str, kind: AliasKind) -> SearchAlias {
+        SearchAlias {
+            id: 0,
+            term: term.to_string(),
+            expansion: expansion.to_string(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_expand_query() {
+        let aliases = vec![
+            alias("OIG", "Office of Inspector General", AliasKind::Acronym),
+            alias("MKULTRA", "Project Artichoke", AliasKind::CodeName),
+        ];
+
+        assert_eq!(
+            expand_query("oig report", &aliases),
+            vec!["oig report", "Office of Inspector General report"]
+        );
+        assert_eq!(
+            expand_query("office of inspector general", &aliases),
+            vec!["office of inspector general", "OIG"]
+        );
+        // Whole words only
+        assert_eq!(expand_query("foigle", &aliases), vec!["foigle"]);
+
+        let both = expand_query("OIG on MKULTRA", &aliases);
+        assert_eq!(both.len(), 4);
+        assert!(both.contains(&"Office of Inspector General on Project Artichoke".to_string()));
+    }
+
+    #[test]
+    fn test_alias_kind_round_trip() {
+        for kind in AliasKind::ALL {
+            assert_eq!(AliasKind::from_str(kind.as_str()), Some(kind));
+        }
+        assert_eq!(AliasKind::from_str("codename"), Some(AliasKind::CodeName));
+        assert!(AliasKind::from_str("place").is_none());
+    }
+}
@@ -0,0 +1,141 @@
+//! API keys for partner access to the JSON API.
+//!
+//! Partner organizations get a key instead of the operator's access token:
+//! it can be limited to some sources and to read-only requests, carries its
+//! own rate limit, and its requests are counted per day. Keys never unlock
+//! restricted documents.
+//!
+//! Only a SHA-256 hash of each key is stored, so a key is shown once, when
+//! it is created. The first characters are kept to tell keys apart.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+
+/// Start of every key, so leaked keys are easy to recognize.
+pub const KEY_PREFIX: &str = "foia_";
+
+/// Characters of a key kept for listings.
+const DISPLAY_PREFIX_LEN: usize = KEY_PREFIX.len() + 8;
+
+/// An API key's permissions and bookkeeping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    pub id: i32,
+    /// Who the key was issued to.
+    pub name: String,
+    /// Start of the key, e.g. `foia_3f9a12c0`.
+    pub prefix: String,
+    /// Only safe methods (GET, HEAD, OPTIONS) are allowed.
+    pub read_only: bool,
+    /// Sources the key may see; `None` for all.
+    pub sources: Option<Vec<String>>,
+    /// Requests per minute; `None` for the configured default.
+    pub rate_limit: Option<u32>,
+    /// Requests made with the key, as of the last usage flush.
+    pub requests: i64,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    /// Whether the key has not been revoked.
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
+
+    /// Whether the key may see documents of a source.
+    pub fn allows_source(&self, source_id: &str) -> bool {
+        self.sources
+            .as_ref()
+            .is_none_or(|sources| sources.iter().any(|s| s == source_id))
+    }
+
+    /// Short description of the scope, for listings.
+    pub fn scope_label(&self) -> String {
+        let access = if self.read_only {
+            "read-only"
+        } else {
+            "read-write"
+        };
+        match &self.sources {
+            Some(sources) => format!("{}, {}", access, sources.join(", ")),
+            None => format!("{}, all sources", access),
+        }
+    }
+}
+
+/// What a new key may do.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeySpec {
+    pub name: String,
+    pub read_only: bool,
+    pub sources: Option<Vec<String>>,
+    pub rate_limit: Option<u32>,
+}
+
+/// Requests made with a key on one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyUsage {
+    pub day: NaiveDate,
+    pub requests: i64,
+}
+
+/// A new random key.
+pub fn generate_key() -> String {
+    format!(
+        "{}{}{}",
+        KEY_PREFIX,
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Hash under which a key is stored and looked up.
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Start of a key shown in listings.
+pub fn display_prefix(key: &str) -> String {
+    key.chars().take(DISPLAY_PREFIX_LEN).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys() {
+        let key = generate_key();
+        assert!(key.starts_with(KEY_PREFIX));
+        assert_eq!(key.len(), KEY_PREFIX.len() + 64);
+        assert_ne!(key, generate_key());
+        assert_eq!(display_prefix(&key).len(), DISPLAY_PREFIX_LEN);
+        assert_eq!(hash_key(&key), hash_key(&key));
+        assert_ne!(hash_key(&key), hash_key(&generate_key()));
+    }
+
+    #[test]
+    fn test_source_scope() {
+        let mut key = ApiKey {
+            id: 1,
+            name: "partner".to_string(),
+            prefix: "foia_12345678".to_string(),
+            read_only: true,
+            sources: None,
+            rate_limit: None,
+            requests: 0,
+            created_at: Utc::now(),
+            last_used_at: None,
+            revoked_at: None,
+        };
+        assert!(key.allows_source("fbi"));
+        assert_eq!(key.scope_label(), "read-only, all sources");
+
+        key.sources = Some(vec!["fbi".to_string(), "cia".to_string()]);
+        assert!(key.allows_source("cia"));
+        assert!(!key.allows_source("nsa"));
+        assert_eq!(key.scope_label(), "read-only, fbi, cia");
+    }
+}
@@ -0,0 +1,321 @@
+//! Bates number extraction and sequence analysis.
+//!
+//! Productions are stamped with a prefix and a zero-padded page counter
+//! (`DOJ-OGR-00012345`, `FBI 0001234`) in a page margin, usually the bottom
+//! right. Stamps are looked for in the first and last lines of each page's
+//! text, consecutive stamped pages are folded into ranges, and numbers
+//! missing between the ranges of a source point at documents that were
+//! withheld or never published.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Lines at the top of a page searched for a stamp.
+const HEAD_LINES: usize = 3;
+/// Lines at the bottom of a page searched for a stamp.
+const TAIL_LINES: usize = 5;
+
+/// Fewest digits in a stamp's counter; shorter numbers are too often
+/// years, page numbers or form numbers.
+const MIN_DIGITS: usize = 5;
+
+/// Prefixes that look like stamps but label something else.
+const NOT_PREFIXES: &[&str] = &[
+    "PAGE", "PG", "NO", "NUMBER", "FORM", "CASE", "FILE", "FOIA", "TEL", "PHONE", "FAX", "ROOM",
+    "SUITE", "BOX", "PO", "ZIP", "REV", "OMB", "EXT", "ID", "REF", "DOC",
+];
+
+static STAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b([A-Z]{1,12}(?:[-_][A-Z]{1,12}){0,3})[-_ ]?(\d{5,10})\b").unwrap()
+});
+
+/// A single Bates number: prefix plus page counter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BatesNumber {
+    /// Prefix as stamped, e.g. `DOJ-OGR-`; compared via [`prefix_key`].
+    pub prefix: String,
+    pub number: u64,
+    /// Zero-padded width of the counter.
+    pub digits: usize,
+}
+
+impl BatesNumber {
+    /// Parse a Bates number typed by a user or found on a page, e.g.
+    /// `DOJ-OGR-00012345` or `fbi 0001234`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_uppercase();
+        let caps = STAMP.captures(&s)?;
+        if caps.get(0)?.as_str().len() != s.len() {
+            return None;
+        }
+        Self::from_parts(&s[caps.get(1)?.start()..caps.get(2)?.start()], &caps[2])
+    }
+
+    fn from_parts(prefix: &str, digits: &str) -> Option<Self> {
+        let key = prefix_key(prefix);
+        if key.is_empty() || NOT_PREFIXES.contains(&key.as_str()) || digits.len() < MIN_DIGITS {
+            return None;
+        }
+        Some(Self {
+            prefix: prefix.to_string(),
+            number: digits.parse().ok()?,
+            digits: digits.len(),
+        })
+    }
+
+    /// Normalized prefix used for matching.
+    pub fn key(&self) -> String {
+        prefix_key(&self.prefix)
+    }
+}
+
+impl fmt::Display for BatesNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{:0width$}",
+            self.prefix,
+            self.number,
+            width = self.digits
+        )
+    }
+}
+
+/// Prefix with separators dropped, so `DOJ-OGR-`, `DOJ_OGR` and `DOJOGR`
+/// match each other.
+pub fn prefix_key(prefix: &str) -> String {
+    prefix
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Find the Bates stamp on a page: the last candidate in the bottom
+/// margin, else the first in the top margin.
+pub fn find_page_stamp(text: &str) -> Option<BatesNumber> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let tail_start = lines.len().saturating_sub(TAIL_LINES);
+    let head_end = HEAD_LINES.min(tail_start);
+
+    let in_line = |line: &str| -> Vec<BatesNumber> {
+        STAMP
+            .captures_iter(line)
+            .filter_map(|c| {
+                let prefix = &line[c.get(1)?.start()..c.get(2)?.start()];
+                BatesNumber::from_parts(prefix, &c[2])
+            })
+            .collect()
+    };
+
+    lines[tail_start..]
+        .iter()
+        .rev()
+        .find_map(|line| in_line(line).pop())
+        .or_else(|| {
+            lines[..head_end]
+                .iter()
+                .find_map(|line| in_line(line).into_iter().next())
+        })
+}
+
+/// A run of consecutively numbered pages within one document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatesRange {
+    pub prefix: String,
+    pub start: u64,
+    pub end: u64,
+    pub digits: usize,
+    pub first_page: u32,
+    pub last_page: u32,
+}
+
+impl BatesRange {
+    pub fn key(&self) -> String {
+        prefix_key(&self.prefix)
+    }
+
+    /// First number of the range, formatted as stamped.
+    pub fn first(&self) -> BatesNumber {
+        self.number(self.start)
+    }
+
+    /// Last number of the range, formatted as stamped.
+    pub fn last(&self) -> BatesNumber {
+        self.number(self.end)
+    }
+
+    fn number(&self, number: u64) -> BatesNumber {
+        BatesNumber {
+            prefix: self.prefix.clone(),
+            number,
+            digits: self.digits,
+        }
+    }
+
+    /// Page carrying `number`, if the range covers it.
+    pub fn page_of(&self, number: u64) -> Option<u32> {
+        if number < self.start || number > self.end {
+            return None;
+        }
+        let page = self.first_page as u64 + (number - self.start);
+        Some(page.min(self.last_page as u64) as u32)
+    }
+}
+
+/// Fold per-page stamps into ranges. A page continues the current range
+/// when its prefix matches and its number is the previous stamp's plus the
+/// pages between them; unstamped pages in between are absorbed.
+pub fn page_ranges(stamps: &[(u32, BatesNumber)]) -> Vec<BatesRange> {
+    let mut ranges: Vec<BatesRange> = Vec::new();
+    for (page, stamp) in stamps {
+        if let Some(last) = ranges.last_mut() {
+            let step = page.saturating_sub(last.last_page) as u64;
+            if last.key() == stamp.key() && step > 0 && stamp.number == last.end + step {
+                last.end = stamp.number;
+                last.last_page = *page;
+                continue;
+            }
+        }
+        ranges.push(BatesRange {
+            prefix: stamp.prefix.clone(),
+            start: stamp.number,
+            end: stamp.number,
+            digits: stamp.digits,
+            first_page: *page,
+            last_page: *page,
+        });
+    }
+    ranges
+}
+
+/// Numbers missing from a Bates sequence between two documents' ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BatesGap {
+    /// First missing number, formatted as stamped.
+    pub from: String,
+    /// Last missing number, formatted as stamped.
+    pub to: String,
+    /// Count of missing numbers (pages).
+    pub missing: u64,
+    /// Document whose range ends just before the gap.
+    pub after_document: String,
+    /// Document whose range starts just after the gap.
+    pub before_document: String,
+}
+
+/// Gaps between the ranges of a source, per prefix, in sequence order.
+///
+/// Overlapping ranges (duplicate productions) are merged, not reported.
+pub fn find_gaps(ranges: &[(String, BatesRange)]) -> Vec<BatesGap> {
+    let mut by_prefix: BTreeMap<String, Vec<&(String, BatesRange)>> = BTreeMap::new();
+    for entry in ranges {
+        by_prefix.entry(entry.1.key()).or_default().push(entry);
+    }
+
+    let mut gaps = Vec::new();
+    for (_, mut entries) in by_prefix {
+        entries.sort_by_key(|(_, r)| (r.start, r.end));
+        let mut reach: Option<(u64, &String)> = None;
+        for (doc_id, range) in entries {
+            if let Some((end, prev_doc)) = reach {
+                if range.start > end + 1 {
+                    gaps.push(BatesGap {
+                        from: range.number(end + 1).to_string(),
+                        to: range.number(range.start - 1).to_string(),
+                        missing: range.start - end - 1,
+                        after_document: prev_doc.clone(),
+                        before_document: doc_id.clone(),
+                    });
+                }
+                if range.end > end {
+                    reach = Some((range.end, doc_id));
+                }
+            } else {
+                reach = Some((range.end, doc_id));
+            }
+        }
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp(s: &str) -> BatesNumber {
+        BatesNumber::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_bates_number() {
+        let n = stamp("doj-ogr-00012345");
+        assert_eq!(n.key(), "DOJOGR");
+        assert_eq!(n.number, 12345);
+        assert_eq!(n.to_string(), "DOJ-OGR-00012345");
+        assert_eq!(stamp("FBI 0001234").key(), "FBI");
+
+        assert!(BatesNumber::parse("Page 00012").is_none());
+        assert!(BatesNumber::parse("ABC 2019").is_none());
+        assert!(BatesNumber::parse("not a stamp").is_none());
+    }
+
+    #[test]
+    fn test_find_page_stamp() {
+        let page = "MEMORANDUM FOR THE RECORD\nSubject: Form 12345 review\n\n\
+                    Body text mentioning case 2019-00123.\n\nPage 2 of 7\nCIA-RDP00001234\n";
+        assert_eq!(
+            find_page_stamp(page).unwrap().to_string(),
+            "CIA-RDP00001234"
+        );
+
+        let top = "HOUSE-OVERSIGHT-012001\nDear Chairman,\n1\n2\n3\n4\n5\n6";
+        assert_eq!(find_page_stamp(top).unwrap().number, 12001);
+        assert!(find_page_stamp("Nothing stamped here\nPage 3").is_none());
+    }
+
+    #[test]
+    fn test_page_ranges_and_gaps() {
+        let stamps = vec![
+            (1, stamp("ABC000100")),
+            (2, stamp("ABC000101")),
+            // Page 3 unreadable; page 4 continues the run
+            (4, stamp("ABC 000103")),
+            (5, stamp("XYZ000001")),
+        ];
+        let ranges = page_ranges(&stamps);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!((ranges[0].start, ranges[0].end), (100, 103));
+        assert_eq!((ranges[0].first_page, ranges[0].last_page), (1, 4));
+        assert_eq!(ranges[0].page_of(102), Some(3));
+        assert_eq!(ranges[0].page_of(104), None);
+
+        let source = vec![
+            (
+                "b".to_string(),
+                page_ranges(&[(1, stamp("ABC000110"))])[0].clone(),
+            ),
+            ("a".to_string(), ranges[0].clone()),
+            (
+                "c".to_string(),
+                page_ranges(&[(1, stamp("ABC000111"))])[0].clone(),
+            ),
+            ("x".to_string(), ranges[1].clone()),
+        ];
+        let gaps = find_gaps(&source);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].from, "ABC000104");
+        assert_eq!(gaps[0].to, "ABC000109");
+        assert_eq!(gaps[0].missing, 6);
+        assert_eq!(gaps[0].after_document, "a");
+        assert_eq!(gaps[0].before_document, "b");
+    }
+}
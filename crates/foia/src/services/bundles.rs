@@ -0,0 +1,665 @@
+//! Splitting multi-document PDF bundles into logical documents.
+//!
+//! Agencies often release hundreds of records as one omnibus PDF. Each
+//! page's text is checked for signals that a new record starts on it:
+//!
+//! - `bates`: the Bates counter restarts or the prefix changes
+//! - `numbering`: a "Page 1 of N" marker
+//! - `blank`: the page follows blank separator sheets
+//! - `letterhead`: an agency letterhead or a memo, email or form header
+//! - `llm`: the model reads the page break and says a new record starts
+//!
+//! Each range between starts becomes a child document with its own PDF
+//! and the bundle's page text, linked to the bundle by a `part-of`
+//! relation, so it is annotated and searched on its own.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use chrono::Utc;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::llm::LlmClient;
+use crate::models::{Document, DocumentPage, DocumentSplit, PageOcrStatus, RelationType};
+use crate::repository::models::NewDocumentRelation;
+use crate::repository::DieselDocumentRepository;
+use crate::services::bates::{find_page_stamp, BatesNumber};
+use crate::storage::{self, DocumentInput};
+
+/// Lines at the top of a page checked for a letterhead or header.
+const HEAD_LINES: usize = 6;
+/// Lines at the bottom of a page checked for a page number.
+const TAIL_LINES: usize = 3;
+
+/// Fewest words of three or more letters on a page that is not blank;
+/// a separator sheet may still carry a Bates stamp or a scanner mark.
+const MIN_WORDS: usize = 3;
+
+/// Past this share of blank pages, blank pages are the backs of a duplex
+/// scan rather than separator sheets.
+const MAX_SEPARATOR_SHARE: f32 = 1.0 / 3.0;
+
+/// Memo, email and form header fields; two at the top of a page start a
+/// record.
+const HEADER_FIELDS: &[&str] = &[
+    "TO",
+    "FROM",
+    "SUBJECT",
+    "SUBJ",
+    "RE",
+    "DATE",
+    "SENT",
+    "CC",
+    "MEMORANDUM FOR",
+];
+
+/// Letterhead openings of the agencies whose releases come bundled.
+const LETTERHEADS: &[&str] = &[
+    "U.S. DEPARTMENT OF",
+    "UNITED STATES DEPARTMENT OF",
+    "DEPARTMENT OF",
+    "FEDERAL BUREAU OF INVESTIGATION",
+    "CENTRAL INTELLIGENCE AGENCY",
+    "NATIONAL SECURITY AGENCY",
+    "OFFICE OF THE",
+    "THE WHITE HOUSE",
+    "UNITED STATES GOVERNMENT",
+    "MEMORANDUM",
+];
+
+static PAGE_NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:page\s+(\d{1,4})(?:\s+of\s+\d{1,4})?|-\s*(\d{1,4})\s*-)$").unwrap()
+});
+
+/// A signal that a new document starts on a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Signal {
+    Bates,
+    Numbering,
+    Blank,
+    Letterhead,
+    Llm,
+}
+
+impl Signal {
+    pub const ALL: [Signal; 5] = [
+        Self::Bates,
+        Self::Numbering,
+        Self::Blank,
+        Self::Letterhead,
+        Self::Llm,
+    ];
+
+    /// Signals used unless others are asked for; the LLM is opt-in since
+    /// it takes a request per page.
+    pub const DEFAULT: [Signal; 4] = [Self::Bates, Self::Numbering, Self::Blank, Self::Letterhead];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bates => "bates",
+            Self::Numbering => "numbering",
+            Self::Blank => "blank",
+            Self::Letterhead => "letterhead",
+            Self::Llm => "llm",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "bates" => Some(Self::Bates),
+            "numbering" | "page-numbers" => Some(Self::Numbering),
+            "blank" | "blank-pages" => Some(Self::Blank),
+            "letterhead" | "headers" => Some(Self::Letterhead),
+            "llm" => Some(Self::Llm),
+            _ => None,
+        }
+    }
+}
+
+/// A page range that becomes one document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Segment {
+    pub first_page: u32,
+    pub last_page: u32,
+    /// What marked the start; `None` for the bundle's first document.
+    pub boundary: Option<Signal>,
+}
+
+impl Segment {
+    pub fn page_count(&self) -> u32 {
+        self.last_page - self.first_page + 1
+    }
+}
+
+/// What the text of one page says about where it falls in a record.
+#[derive(Debug, Default)]
+struct PageFeatures {
+    blank: bool,
+    letterhead: bool,
+    /// Page number from a "Page N of M" or "- N -" marker.
+    page_number: Option<u32>,
+    stamp: Option<BatesNumber>,
+}
+
+impl PageFeatures {
+    fn of(text: &str) -> Self {
+        let words = text
+            .split(|c: char| !c.is_alphabetic())
+            .filter(|w| w.chars().count() >= 3)
+            .count();
+        let blank = words < MIN_WORDS || text.to_lowercase().contains("intentionally left blank");
+        if blank {
+            return Self {
+                blank,
+                stamp: find_page_stamp(text),
+                ..Default::default()
+            };
+        }
+
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        let head = &lines[..HEAD_LINES.min(lines.len())];
+        let tail = &lines[lines.len().saturating_sub(TAIL_LINES)..];
+        let page_number = head.iter().chain(tail).find_map(|line| {
+            let caps = PAGE_NUMBER.captures(line)?;
+            caps.get(1).or(caps.get(2))?.as_str().parse().ok()
+        });
+
+        Self {
+            blank,
+            letterhead: has_letterhead(head),
+            page_number,
+            stamp: find_page_stamp(text),
+        }
+    }
+
+    /// Whether the page is numbered as the second or later page of a record.
+    fn continues(&self) -> bool {
+        self.page_number.is_some_and(|n| n > 1)
+    }
+}
+
+/// Whether the top lines of a page open a record: a letterhead on the
+/// first lines, or two or more memo, email or form header fields.
+fn has_letterhead(head: &[&str]) -> bool {
+    let upper: Vec<String> = head.iter().map(|l| l.to_uppercase()).collect();
+    let letterhead = upper
+        .iter()
+        .take(3)
+        .any(|line| LETTERHEADS.iter().any(|l| line.starts_with(l)));
+    let fields = upper
+        .iter()
+        .filter(|line| {
+            HEADER_FIELDS.iter().any(|field| {
+                line.strip_prefix(field)
+                    .is_some_and(|rest| rest.trim_start().starts_with(':'))
+            })
+        })
+        .count();
+    letterhead || fields >= 2
+}
+
+/// How to split a bundle.
+#[derive(Debug, Clone)]
+pub struct SplitOptions {
+    pub signals: Vec<Signal>,
+    /// Shorter documents are folded into the one before them.
+    pub min_pages: u32,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        Self {
+            signals: Signal::DEFAULT.to_vec(),
+            min_pages: 1,
+        }
+    }
+}
+
+/// Finds the documents in a bundle.
+#[derive(Default)]
+pub struct BundleSplitter {
+    options: SplitOptions,
+    llm: Option<LlmClient>,
+}
+
+impl BundleSplitter {
+    pub fn new(options: SplitOptions) -> Self {
+        Self { options, llm: None }
+    }
+
+    /// Ask `client` about page breaks no heuristic decided, if the `llm`
+    /// signal is on.
+    pub fn with_llm(mut self, client: LlmClient) -> Self {
+        if self.options.signals.contains(&Signal::Llm) {
+            self.llm = Some(client);
+        }
+        self
+    }
+
+    /// Split a bundle given its pages' text in page order.
+    pub async fn segments(&self, pages: &[String]) -> Vec<Segment> {
+        let features: Vec<PageFeatures> = pages.iter().map(|t| PageFeatures::of(t)).collect();
+        let separators = self.uses_separators(&features);
+        let mut starts = self.find_starts(&features, separators);
+
+        if let Some(llm) = &self.llm {
+            let mut previous: Option<usize> = None;
+            for (i, page) in features.iter().enumerate() {
+                if page.blank {
+                    continue;
+                }
+                let number = i as u32 + 1;
+                if let Some(prev) = previous {
+                    if !starts.contains_key(&number) && !page.continues() {
+                        match llm.starts_new_document(&pages[prev], &pages[i]).await {
+                            Ok(true) => {
+                                starts.insert(number, Signal::Llm);
+                            }
+                            Ok(false) => {}
+                            Err(e) => {
+                                tracing::warn!("Boundary check of page {} failed: {}", number, e)
+                            }
+                        }
+                    }
+                }
+                previous = Some(i);
+            }
+        }
+
+        self.build_segments(&features, &starts, separators)
+    }
+
+    /// Whether blank pages separate documents in this bundle.
+    fn uses_separators(&self, features: &[PageFeatures]) -> bool {
+        let blank = features.iter().filter(|f| f.blank).count();
+        self.options.signals.contains(&Signal::Blank)
+            && (blank as f32) <= features.len() as f32 * MAX_SEPARATOR_SHARE
+    }
+
+    /// Pages the heuristics say start a document, with the strongest signal.
+    fn find_starts(&self, features: &[PageFeatures], separators: bool) -> BTreeMap<u32, Signal> {
+        let on = |signal| self.options.signals.contains(&signal);
+        let mut starts = BTreeMap::new();
+        let mut last_stamp: Option<&BatesNumber> = None;
+        for (i, page) in features.iter().enumerate() {
+            let after_blank = i > 0 && features[i - 1].blank;
+            let mut signals = Vec::new();
+            if let Some(stamp) = &page.stamp {
+                if let Some(last) = last_stamp {
+                    if on(Signal::Bates)
+                        && (last.key() != stamp.key() || stamp.number <= last.number)
+                    {
+                        signals.push(Signal::Bates);
+                    }
+                }
+                last_stamp = Some(stamp);
+            }
+            if !page.blank && !page.continues() {
+                if on(Signal::Numbering) && page.page_number == Some(1) {
+                    signals.push(Signal::Numbering);
+                }
+                if separators && after_blank {
+                    signals.push(Signal::Blank);
+                }
+                if on(Signal::Letterhead) && page.letterhead {
+                    signals.push(Signal::Letterhead);
+                }
+            }
+            if i > 0 {
+                if let Some(signal) = signals.into_iter().min() {
+                    starts.insert(i as u32 + 1, signal);
+                }
+            }
+        }
+        starts
+    }
+
+    fn build_segments(
+        &self,
+        features: &[PageFeatures],
+        starts: &BTreeMap<u32, Signal>,
+        separators: bool,
+    ) -> Vec<Segment> {
+        let page_count = features.len() as u32;
+        let blank = |page: u32| separators && features[page as usize - 1].blank;
+
+        let mut bounds: Vec<(u32, Option<Signal>)> = vec![(1, None)];
+        bounds.extend(starts.iter().map(|(&page, &signal)| (page, Some(signal))));
+
+        let mut segments: Vec<Segment> = Vec::new();
+        for (i, &(first, boundary)) in bounds.iter().enumerate() {
+            let last = bounds.get(i + 1).map_or(page_count, |&(next, _)| next - 1);
+            // Separator sheets belong to neither neighbour
+            let Some(first) = (first..=last).find(|&p| !blank(p)) else {
+                continue;
+            };
+            let last = (first..=last).rev().find(|&p| !blank(p)).unwrap_or(first);
+            let segment = Segment {
+                first_page: first,
+                last_page: last,
+                boundary: if segments.is_empty() { None } else { boundary },
+            };
+            match segments.last_mut() {
+                Some(prev) if segment.page_count() < self.options.min_pages => {
+                    prev.last_page = segment.last_page;
+                }
+                _ => segments.push(segment),
+            }
+        }
+        segments
+    }
+}
+
+/// A document split out of a bundle.
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitDocument {
+    pub document_id: String,
+    pub title: String,
+    pub segment: Segment,
+}
+
+/// Cut a bundle into the documents in `segments`: each gets its pages as
+/// a PDF of its own and the bundle's page text, so it needs no OCR, and is
+/// linked to the bundle as `part-of`. Splitting again with the same ranges
+/// reuses the documents already cut.
+///
+/// Needs poppler's `pdfseparate` and `pdfunite`.
+pub async fn split_bundle(
+    doc_repo: &DieselDocumentRepository,
+    doc: &Document,
+    segments: &[Segment],
+    documents_dir: &Path,
+) -> anyhow::Result<Vec<SplitDocument>> {
+    let version = doc
+        .current_version()
+        .ok_or_else(|| anyhow::anyhow!("Document {} has no versions", doc.id))?;
+    if version.mime_type != "application/pdf" {
+        anyhow::bail!(
+            "Only PDFs can be split; {} is {}",
+            doc.id,
+            version.mime_type
+        );
+    }
+    for tool in ["pdfseparate", "pdfunite"] {
+        if which::which(tool).is_err() {
+            anyhow::bail!("Splitting bundles needs poppler ({} not found)", tool);
+        }
+    }
+
+    let stored = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+    let pdf = storage::plaintext_path(&stored)?;
+    let pages = doc_repo.get_pages(&doc.id, version.id as i32).await?;
+    let stem = version
+        .original_filename
+        .as_deref()
+        .and_then(|name| Path::new(name).file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| doc.id.clone());
+
+    let mut split = Vec::with_capacity(segments.len());
+    for segment in segments {
+        let (first, last) = (segment.first_page, segment.last_page);
+        let content = extract_pages(pdf.path(), first, last).await?;
+        let url = format!("{}#pages={}-{}", doc.source_url, first, last);
+        let title = format!("{} (pages {}-{})", doc.title, first, last);
+        let input = DocumentInput {
+            url: url.clone(),
+            title: title.clone(),
+            mime_type: version.mime_type.clone(),
+            metadata: doc.metadata.clone(),
+            original_filename: Some(format!("{}-pages-{}-{}.pdf", stem, first, last)),
+            server_date: version.server_date,
+        };
+        storage::save_document_async(doc_repo, &content, &input, &doc.source_id, documents_dir)
+            .await?;
+        let child = doc_repo
+            .get_by_url(&url)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Split document {} was not saved", url))?;
+        let child_version = child
+            .current_version()
+            .ok_or_else(|| anyhow::anyhow!("Split document {} has no versions", child.id))?;
+
+        // Carry the bundle's page text over so the pages aren't read again
+        if doc_repo
+            .count_pages(&child.id, child_version.id as i32)
+            .await?
+            == 0
+        {
+            let copied: Vec<DocumentPage> = pages
+                .iter()
+                .filter(|p| (first..=last).contains(&p.page_number))
+                .map(|p| {
+                    let mut page = DocumentPage::new(
+                        child.id.clone(),
+                        child_version.id,
+                        p.page_number - first + 1,
+                    );
+                    page.pdf_text = p.pdf_text.clone();
+                    page.ocr_text = p.ocr_text.clone();
+                    page.final_text = p.final_text.clone();
+                    page.ocr_status = p.ocr_status;
+                    page
+                })
+                .collect();
+            let complete = copied.len() as u32 == segment.page_count()
+                && copied
+                    .iter()
+                    .all(|p| p.ocr_status == PageOcrStatus::OcrComplete);
+            doc_repo.save_pages(&copied).await?;
+            if complete {
+                doc_repo.finalize_document(&child.id).await?;
+                doc_repo
+                    .store_analysis_result_for_document(
+                        &child.id,
+                        child_version.id as i32,
+                        "ocr",
+                        "split",
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                    )
+                    .await?;
+            }
+        }
+
+        let now = Utc::now().to_rfc3339();
+        doc_repo
+            .add_relation(&NewDocumentRelation {
+                document_id: &child.id,
+                related_document_id: &doc.id,
+                relation_type: RelationType::PartOf.as_str(),
+                virtual_file_id: None,
+                note: None,
+                created_at: &now,
+            })
+            .await?;
+        doc_repo
+            .record_document_split(&DocumentSplit {
+                document_id: child.id.clone(),
+                parent_id: doc.id.clone(),
+                version_id: version.id,
+                first_page: first,
+                last_page: last,
+                boundary: segment.boundary.map(|s| s.as_str().to_string()),
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        split.push(SplitDocument {
+            document_id: child.id,
+            title,
+            segment: segment.clone(),
+        });
+    }
+    Ok(split)
+}
+
+/// Pages `first..=last` of a PDF as a PDF of their own.
+pub async fn extract_pages(pdf: &Path, first: u32, last: u32) -> anyhow::Result<Vec<u8>> {
+    let dir = tempfile::tempdir()?;
+    let pattern = dir.path().join("page-%d.pdf");
+    let result = tokio::process::Command::new("pdfseparate")
+        .args(["-f", &first.to_string(), "-l", &last.to_string()])
+        .arg(pdf)
+        .arg(&pattern)
+        .output()
+        .await?;
+    if !result.status.success() {
+        anyhow::bail!(
+            "pdfseparate failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    let single: Vec<_> = (first..=last)
+        .map(|page| dir.path().join(format!("page-{}.pdf", page)))
+        .collect();
+    if let [only] = single.as_slice() {
+        return Ok(tokio::fs::read(only).await?);
+    }
+    let output = dir.path().join("pages.pdf");
+    let result = tokio::process::Command::new("pdfunite")
+        .args(&single)
+        .arg(&output)
+        .output()
+        .await?;
+    if !result.status.success() {
+        anyhow::bail!(
+            "pdfunite failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+    Ok(tokio::fs::read(&output).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter(subject: &str) -> String {
+        format!(
+            "U.S. DEPARTMENT OF JUSTICE\nFederal Bureau of Investigation\n\n\
+             Dear Requester,\nThis letter concerns {} and the records requested.",
+            subject
+        )
+    }
+
+    fn body(text: &str) -> String {
+        format!("continued discussion of the matter regarding {}", text)
+    }
+
+    async fn split(pages: &[String], options: SplitOptions) -> Vec<(u32, u32, Option<Signal>)> {
+        BundleSplitter::new(options)
+            .segments(pages)
+            .await
+            .into_iter()
+            .map(|s| (s.first_page, s.last_page, s.boundary))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_letterheads_and_separators() {
+        let pages = vec![
+            letter("the first request"),
+            body("the first request"),
+            "".to_string(),
+            body("a memo without a header"),
+            letter("the second request"),
+            format!("{}\n\nPage 2 of 2", body("the second request")),
+        ];
+        assert_eq!(
+            split(&pages, SplitOptions::default()).await,
+            vec![
+                (1, 2, None),
+                (4, 4, Some(Signal::Blank)),
+                (5, 6, Some(Signal::Letterhead)),
+            ]
+        );
+
+        // Too short documents fold into the one before
+        let options = SplitOptions {
+            min_pages: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            split(&pages, options).await,
+            vec![(1, 4, None), (5, 6, Some(Signal::Letterhead))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bates_resets_and_numbering() {
+        let stamped = |text: String, stamp: &str| format!("{}\n{}", text, stamp);
+        let pages = vec![
+            stamped(body("one"), "FBI-0000101"),
+            stamped(body("one"), "FBI-0000102"),
+            stamped(body("two"), "FBI-0000001"),
+            stamped(body("two"), "FBI-0000002"),
+            format!("Page 1 of 2\n{}", body("three")),
+            format!("Page 2 of 2\n{}", letter("a quoted letter")),
+        ];
+        assert_eq!(
+            split(&pages, SplitOptions::default()).await,
+            vec![
+                (1, 2, None),
+                (3, 4, Some(Signal::Bates)),
+                (5, 6, Some(Signal::Numbering)),
+            ]
+        );
+
+        let only_bates = SplitOptions {
+            signals: vec![Signal::Bates],
+            ..Default::default()
+        };
+        assert_eq!(
+            split(&pages, only_bates).await,
+            vec![(1, 2, None), (3, 6, Some(Signal::Bates))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplex_blanks_are_not_separators() {
+        let pages = vec![
+            body("one"),
+            String::new(),
+            body("one"),
+            String::new(),
+            letter("two"),
+            String::new(),
+        ];
+        assert_eq!(
+            split(&pages, SplitOptions::default()).await,
+            vec![(1, 4, None), (5, 6, Some(Signal::Letterhead))]
+        );
+    }
+
+    #[test]
+    fn test_page_features() {
+        let memo = "MEMORANDUM FOR THE RECORD\nTO: Director\nFROM: SAC, Boston\nSUBJECT: Review";
+        assert!(PageFeatures::of(memo).letterhead);
+        assert!(!PageFeatures::of(&body("nothing at the top")).letterhead);
+
+        let stamped_blank = PageFeatures::of("\n\nDOJ-OGR-00012345\n");
+        assert!(stamped_blank.blank);
+        assert_eq!(stamped_blank.stamp.unwrap().number, 12345);
+        assert!(PageFeatures::of("This page intentionally left blank").blank);
+
+        assert_eq!(
+            PageFeatures::of(&format!("{}\n- 3 -", body("x"))).page_number,
+            Some(3)
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! History of the scraper configs stored in the database.
+//!
+//! Per-source scraper configs live in the `scraper_configs` table. Each
+//! change made through `foia config` or the web admin pages saves a
+//! snapshot of the whole table to `configuration_history`, so earlier
+//! states can be listed, compared and restored. A rollback is itself a
+//! change and is recorded the same way, so it can be undone. Daemons and
+//! the server reload their config when the latest entry changes.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::{ScraperConfig, ScraperDiff, SourcesConfig};
+use crate::repository::diesel_config_history::DieselConfigHistoryEntry;
+use crate::repository::{
+    DieselConfigHistoryRepository, DieselError, DieselScraperConfigRepository,
+};
+use crate::services::page_text::line_diff;
+
+/// Format of the snapshots this module records.
+pub const FORMAT: &str = "json";
+
+/// Shortest hash prefix accepted when naming an entry.
+pub const MIN_PREFIX: usize = 4;
+
+/// Scraper configs by source ID.
+pub type Scrapers = HashMap<String, ScraperConfig>;
+
+/// Errors from looking up, editing or restoring config history.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigHistoryError {
+    #[error("no config history entry matches '{0}'")]
+    NotFound(String),
+    #[error("'{0}' matches more than one config history entry; give more of the hash")]
+    Ambiguous(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Database(#[from] DieselError),
+}
+
+/// The scraper configs serialized for the history table, and their hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub data: String,
+    pub hash: String,
+}
+
+impl Snapshot {
+    /// Serialize with sources and keys in sorted order, so equal configs
+    /// always hash the same.
+    pub fn of(scrapers: &Scrapers) -> Self {
+        let sources = SourcesConfig {
+            scrapers: scrapers.clone(),
+            ..SourcesConfig::default()
+        };
+        let value = serde_json::to_value(&sources).unwrap_or_default();
+        let data = serde_json::to_string_pretty(&value).unwrap_or_default();
+        let hash = hex::encode(Sha256::digest(data.as_bytes()));
+        Self { data, hash }
+    }
+}
+
+/// One history entry as listed.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryItem {
+    pub hash: String,
+    pub created_at: DateTime<Utc>,
+    /// Scraper configs in the entry (`None` if it can't be read).
+    pub sources: Option<usize>,
+    /// Whether the entry matches the configs in use now.
+    pub current: bool,
+}
+
+/// What changed between two states of the scraper configs.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    /// Line diff of the two snapshots; empty when they are the same.
+    pub lines: String,
+}
+
+impl ConfigDiff {
+    pub fn between(old: &Scrapers, new: &Scrapers) -> Self {
+        let sources = ScraperDiff::between(old, new);
+        Self {
+            added: sources.added,
+            removed: sources.removed,
+            changed: sources.changed,
+            lines: line_diff(&Snapshot::of(old).data, &Snapshot::of(new).data),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The scraper configs in use now.
+pub async fn current(
+    scraper_configs: &DieselScraperConfigRepository,
+) -> Result<Scrapers, DieselError> {
+    Ok(scraper_configs.get_all().await?.into_iter().collect())
+}
+
+/// The scraper configs an entry holds.
+pub fn parse(entry: &DieselConfigHistoryEntry) -> Result<Scrapers, ConfigHistoryError> {
+    if entry.format != FORMAT {
+        return Err(ConfigHistoryError::Invalid(format!(
+            "entry {} is in {} format, not {}",
+            short_hash(&entry.hash),
+            entry.format,
+            FORMAT
+        )));
+    }
+    serde_json::from_str::<SourcesConfig>(&entry.data)
+        .map(|sources| sources.scrapers)
+        .map_err(|e| {
+            ConfigHistoryError::Invalid(format!(
+                "entry {} can't be read: {}",
+                short_hash(&entry.hash),
+                e
+            ))
+        })
+}
+
+/// History entries, most recent first.
+pub async fn list(
+    scraper_configs: &DieselScraperConfigRepository,
+    history: &DieselConfigHistoryRepository,
+) -> Result<Vec<HistoryItem>, DieselError> {
+    let now = Snapshot::of(&current(scraper_configs).await?);
+    Ok(history
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|entry| HistoryItem {
+            sources: parse(&entry).ok().map(|s| s.len()),
+            current: entry.hash == now.hash,
+            hash: entry.hash,
+            created_at: entry.created_at,
+        })
+        .collect())
+}
+
+/// The entry a hash, or a prefix of at least [`MIN_PREFIX`] hex digits,
+/// names.
+pub async fn find(
+    history: &DieselConfigHistoryRepository,
+    hash: &str,
+) -> Result<DieselConfigHistoryEntry, ConfigHistoryError> {
+    let prefix = hash.trim().to_ascii_lowercase();
+    if prefix.len() < MIN_PREFIX || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ConfigHistoryError::Invalid(format!(
+            "'{}' is not a config hash (at least {} hex digits)",
+            hash, MIN_PREFIX
+        )));
+    }
+    let mut entries = history.find_by_hash_prefix(&prefix).await?;
+    if entries.is_empty() {
+        return Err(ConfigHistoryError::NotFound(hash.to_string()));
+    }
+    // The same config may be recorded more than once
+    if entries.iter().any(|e| e.hash != entries[0].hash) {
+        return Err(ConfigHistoryError::Ambiguous(hash.to_string()));
+    }
+    Ok(entries.swap_remove(0))
+}
+
+/// Save the scraper configs in use now as a history entry, unless the
+/// latest entry already matches. Returns the hash of a new entry.
+pub async fn record(
+    scraper_configs: &DieselScraperConfigRepository,
+    history: &DieselConfigHistoryRepository,
+) -> Result<Option<String>, DieselError> {
+    let snapshot = Snapshot::of(&current(scraper_configs).await?);
+    let inserted = history
+        .insert_if_changed(&snapshot.data, FORMAT, &snapshot.hash)
+        .await?;
+    Ok(inserted.then_some(snapshot.hash))
+}
+
+/// Check an edited scraper config before it is saved: the source ID must
+/// be usable, and the JSON an object that reads as a scraper config.
+pub fn validate_scraper(
+    source_id: &str,
+    value: serde_json::Value,
+) -> Result<ScraperConfig, ConfigHistoryError> {
+    let invalid = |msg: String| Err(ConfigHistoryError::Invalid(msg));
+    if source_id.is_empty()
+        || !source_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return invalid(format!(
+            "invalid source ID '{}': use letters, digits, '-', '_' or '.'",
+            source_id
+        ));
+    }
+    if !value.is_object() {
+        return invalid("a scraper config must be a JSON object".to_string());
+    }
+    let config: ScraperConfig = match serde_json::from_value(value) {
+        Ok(config) => config,
+        Err(e) => return invalid(format!("invalid scraper config: {}", e)),
+    };
+    if let Some(ref base_url) = config.base_url {
+        match url::Url::parse(base_url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            _ => return invalid(format!("base_url '{}' is not an http(s) URL", base_url)),
+        }
+    }
+    Ok(config)
+}
+
+/// Save one source's scraper config and record the change. The state
+/// before it is recorded first if history doesn't have it yet, so the
+/// edit can be rolled back. Returns the hash of the new entry, `None` if
+/// nothing changed.
+pub async fn save_scraper(
+    scraper_configs: &DieselScraperConfigRepository,
+    history: &DieselConfigHistoryRepository,
+    source_id: &str,
+    config: &ScraperConfig,
+) -> Result<Option<String>, DieselError> {
+    record(scraper_configs, history).await?;
+    scraper_configs.upsert(source_id, config).await?;
+    record(scraper_configs, history).await
+}
+
+/// Result of a rollback.
+#[derive(Debug, Clone, Serialize)]
+pub struct Rollback {
+    /// Hash of the entry restored.
+    pub hash: String,
+    /// What the rollback changed.
+    pub diff: ConfigDiff,
+}
+
+/// Restore the scraper configs an entry holds: sources it has are
+/// written back, sources added since are removed.
+pub async fn rollback(
+    scraper_configs: &DieselScraperConfigRepository,
+    history: &DieselConfigHistoryRepository,
+    hash: &str,
+) -> Result<Rollback, ConfigHistoryError> {
+    let entry = find(history, hash).await?;
+    let target = parse(&entry)?;
+    record(scraper_configs, history).await?;
+
+    let now = current(scraper_configs).await?;
+    let diff = ConfigDiff::between(&now, &target);
+    for source_id in diff.added.iter().chain(&diff.changed) {
+        scraper_configs
+            .upsert(source_id, &target[source_id])
+            .await?;
+    }
+    for source_id in &diff.removed {
+        scraper_configs.delete(source_id).await?;
+    }
+    record(scraper_configs, history).await?;
+    Ok(Rollback {
+        hash: entry.hash,
+        diff,
+    })
+}
+
+/// First 12 hex digits of a hash, for display.
+pub fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn scrapers(entries: &[(&str, &str)]) -> Scrapers {
+        entries
+            .iter()
+            .map(|(id, base_url)| {
+                let config = ScraperConfig {
+                    base_url: Some(base_url.to_string()),
+                    ..Default::default()
+                };
+                (id.to_string(), config)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_snapshot_is_stable() {
+        let a = scrapers(&[("fbi", "https://vault.fbi.gov"), ("cia", "https://cia.gov")]);
+        let b = scrapers(&[("cia", "https://cia.gov"), ("fbi", "https://vault.fbi.gov")]);
+        assert_eq!(Snapshot::of(&a), Snapshot::of(&b));
+
+        let entry = DieselConfigHistoryEntry {
+            uuid: String::new(),
+            created_at: Utc::now(),
+            data: Snapshot::of(&a).data,
+            format: FORMAT.to_string(),
+            hash: Snapshot::of(&a).hash,
+        };
+        assert_eq!(parse(&entry).unwrap(), a);
+    }
+
+    #[test]
+    fn test_config_diff() {
+        let old = scrapers(&[("fbi", "https://vault.fbi.gov"), ("cia", "https://cia.gov")]);
+        let new = scrapers(&[
+            ("fbi", "https://vault.fbi.gov/new"),
+            ("nsa", "https://nsa.gov"),
+        ]);
+        let diff = ConfigDiff::between(&old, &new);
+        assert_eq!(diff.added, vec!["nsa"]);
+        assert_eq!(diff.removed, vec!["cia"]);
+        assert_eq!(diff.changed, vec!["fbi"]);
+        assert!(diff
+            .lines
+            .contains("+      \"base_url\": \"https://vault.fbi.gov/new\""));
+        assert!(ConfigDiff::between(&old, &old).lines.is_empty());
+    }
+
+    #[test]
+    fn test_validate_scraper() {
+        let config = validate_scraper("fbi", json!({"base_url": "https://vault.fbi.gov"}));
+        assert_eq!(
+            config.unwrap().base_url.as_deref(),
+            Some("https://vault.fbi.gov")
+        );
+        assert!(validate_scraper("fbi vault", json!({})).is_err());
+        assert!(validate_scraper("fbi", json!([])).is_err());
+        assert!(validate_scraper("fbi", json!({"request_delay_ms": "soon"})).is_err());
+        assert!(validate_scraper("fbi", json!({"base_url": "ftp://vault.fbi.gov"})).is_err());
+    }
+}
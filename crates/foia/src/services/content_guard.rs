@@ -0,0 +1,331 @@
+//! Post-download validation of fetched content.
+//!
+//! Catches the 200 OK responses that aren't the document: HTML error and
+//! login pages served where a PDF was linked, empty bodies, and pages a
+//! source's own patterns mark as errors. Callers mark rejected fetches
+//! failed so they are retried instead of archived.
+
+use std::fmt;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::config::ValidationConfig;
+
+/// How much of a text response error patterns look at.
+const PATTERN_SCAN_BYTES: usize = 64 * 1024;
+
+/// Titles of the error and login pages portals serve with a 200 OK: the
+/// whole title, or the part before a site-name separator.
+const ERROR_TITLE_PATTERN: &str = concat!(
+    r"^\s*(access denied|403 forbidden|forbidden|404 not found|page not found|not found|",
+    r"unauthorized|session (has )?expired|sign[ -]?in|log[ -]?in|error|server error|",
+    r"service unavailable|temporarily unavailable|under maintenance)\s*([-|:\x{2013}]|$)",
+);
+
+/// MIME types expected from a URL's file extension.
+const EXTENSION_TYPES: &[(&str, &str)] = &[
+    ("pdf", "application/pdf"),
+    ("doc", "application/msword"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    ("xls", "application/vnd.ms-excel"),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    ("ppt", "application/vnd.ms-powerpoint"),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    ("zip", "application/zip"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("png", "image/png"),
+    ("gif", "image/gif"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/x-wav"),
+    ("mp4", "video/mp4"),
+];
+
+/// Why fetched content was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// Fewer bytes than the source's minimum.
+    TooSmall { size: usize, min: u64 },
+    /// The content sniffs as a different type than expected.
+    WrongType { expected: String, found: String },
+    /// An error or login page.
+    ErrorPage(String),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooSmall { size, min } => {
+                write!(f, "Rejected: {} bytes, expected at least {}", size, min)
+            }
+            Self::WrongType { expected, found } => {
+                write!(f, "Rejected: expected {}, got {}", expected, found)
+            }
+            Self::ErrorPage(reason) => write!(f, "Rejected: error page ({})", reason),
+        }
+    }
+}
+
+/// Compiled validation checks for one source.
+#[derive(Debug, Clone)]
+pub struct ContentGuard {
+    disabled: bool,
+    min_bytes: u64,
+    expected_types: Vec<String>,
+    error_title: Regex,
+    error_patterns: Vec<Regex>,
+}
+
+impl Default for ContentGuard {
+    fn default() -> Self {
+        Self::from_config(&ValidationConfig::default()).expect("built-in patterns compile")
+    }
+}
+
+impl ContentGuard {
+    /// Compile a source's validation config. Fails on an invalid pattern.
+    pub fn from_config(config: &ValidationConfig) -> Result<Self, regex::Error> {
+        let compile = |pattern: &str| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .size_limit(1 << 20)
+                .build()
+        };
+        Ok(Self {
+            disabled: config.disabled,
+            min_bytes: config.min_bytes(),
+            expected_types: config
+                .expected_types
+                .iter()
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            error_title: compile(ERROR_TITLE_PATTERN)?,
+            error_patterns: config
+                .error_patterns
+                .iter()
+                .map(|p| compile(p))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Check content fetched from `url`.
+    ///
+    /// Sniffs the bytes rather than trusting the Content-Type header, which
+    /// error pages often get wrong.
+    pub fn check(&self, url: &str, content: &[u8]) -> Result<(), Rejection> {
+        if self.disabled {
+            return Ok(());
+        }
+        if (content.len() as u64) < self.min_bytes {
+            return Err(Rejection::TooSmall {
+                size: content.len(),
+                min: self.min_bytes,
+            });
+        }
+
+        let html = looks_like_html(content);
+        let found = if html {
+            Some("text/html")
+        } else {
+            infer::get(content).map(|t| t.mime_type())
+        };
+
+        if self.expected_types.is_empty() {
+            // Only an HTML page in place of a linked document is certain;
+            // servers mislabel extensions of real files often enough
+            if let Some(expected) = expected_type(url) {
+                if html {
+                    return Err(Rejection::WrongType {
+                        expected: expected.to_string(),
+                        found: "text/html".to_string(),
+                    });
+                }
+            }
+        } else if let Some(found) = found {
+            let matches = |t: &String| {
+                found == t.as_str() || (t.ends_with('/') && found.starts_with(t.as_str()))
+            };
+            if !self.expected_types.iter().any(matches) {
+                return Err(Rejection::WrongType {
+                    expected: self.expected_types.join(" or "),
+                    found: found.to_string(),
+                });
+            }
+        }
+
+        // Binary formats can't be error pages
+        if found.is_some() && !html {
+            return Ok(());
+        }
+        let scanned = &content[..content.len().min(PATTERN_SCAN_BYTES)];
+        let text = String::from_utf8_lossy(scanned);
+        if html {
+            if has_password_field(&text) {
+                return Err(Rejection::ErrorPage("login form".to_string()));
+            }
+            if let Some(title) = html_title(&text) {
+                if self.error_title.is_match(&title) {
+                    return Err(Rejection::ErrorPage(format!("title \"{}\"", title)));
+                }
+            }
+        }
+        if let Some(pattern) = self.error_patterns.iter().find(|p| p.is_match(&text)) {
+            return Err(Rejection::ErrorPage(format!(
+                "matches /{}/",
+                pattern.as_str()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The MIME type a URL's file extension promises, if it names a document.
+pub fn expected_type(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let name = path.rsplit('/').next()?;
+    let (_, ext) = name.rsplit_once('.')?;
+    let ext = ext.to_ascii_lowercase();
+    EXTENSION_TYPES
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, mime)| *mime)
+}
+
+/// Whether content starts like an HTML document.
+fn looks_like_html(content: &[u8]) -> bool {
+    let head = &content[..content.len().min(1024)];
+    let head = String::from_utf8_lossy(head).to_lowercase();
+    let start = head.trim_start_matches('\u{feff}').trim_start();
+    start.starts_with("<!doctype html")
+        || start.starts_with("<html")
+        || start.starts_with("<head")
+        || start.starts_with("<body")
+        || (start.starts_with('<') && head.contains("<html"))
+}
+
+fn has_password_field(html: &str) -> bool {
+    let lower = html.to_lowercase();
+    lower.contains("type=\"password\"")
+        || lower.contains("type='password'")
+        || lower.contains("type=password")
+}
+
+/// Text of an HTML page's `<title>`, with whitespace collapsed.
+fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = html.get(start..end)?;
+    Some(title.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PDF: &[u8] = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog >>\nendobj\n";
+
+    #[test]
+    fn test_html_in_place_of_pdf() {
+        let guard = ContentGuard::default();
+        assert!(guard.check("https://agency.gov/doc.pdf", PDF).is_ok());
+
+        let page = b"<!DOCTYPE html><html><head><title>Records</title></head></html>";
+        let rejection = guard
+            .check("https://agency.gov/doc.pdf?v=2", page)
+            .unwrap_err();
+        assert_eq!(
+            rejection,
+            Rejection::WrongType {
+                expected: "application/pdf".to_string(),
+                found: "text/html".to_string(),
+            }
+        );
+        // HTML pages at HTML URLs are fine unless they look like errors
+        assert!(guard.check("https://agency.gov/reading-room", page).is_ok());
+    }
+
+    #[test]
+    fn test_error_and_login_pages() {
+        let guard = ContentGuard::default();
+        let url = "https://agency.gov/view";
+        let login = b"<html><body><form><input type=\"password\" name=\"pw\"></form></body></html>";
+        assert!(matches!(
+            guard.check(url, login),
+            Err(Rejection::ErrorPage(_))
+        ));
+        let expired = b"<html><head><title>\n  Session Expired </title></head></html>";
+        assert_eq!(
+            guard.check(url, expired).unwrap_err().to_string(),
+            "Rejected: error page (title \"Session Expired\")"
+        );
+        let portal = b"<html><head><title>Sign In | Records Portal</title></head></html>";
+        assert!(guard.check(url, portal).is_err());
+        // Titles merely starting with an error word are real pages
+        let report = b"<html><head><title>Error rates in 2019 audits</title></head></html>";
+        assert!(guard.check(url, report).is_ok());
+        assert!(matches!(
+            guard.check(url, b""),
+            Err(Rejection::TooSmall { size: 0, min: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_configured_checks() {
+        let guard = ContentGuard::from_config(&ValidationConfig {
+            min_bytes: Some(16),
+            expected_types: vec!["application/pdf".to_string(), "image/".to_string()],
+            error_patterns: vec!["request could not be processed".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let url = "https://portal.example.com/GetDocument?id=7";
+        assert!(guard.check(url, PDF).is_ok());
+        assert!(guard.check(url, b"%PDF-1.7").is_err());
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01";
+        assert!(guard.check(url, png).is_ok());
+        assert!(matches!(
+            guard.check(url, b"<html><body>Welcome to the portal</body></html>"),
+            Err(Rejection::WrongType { .. })
+        ));
+        assert!(matches!(
+            guard.check(url, b"Your Request Could Not Be Processed, try later"),
+            Err(Rejection::ErrorPage(_))
+        ));
+
+        let off = ContentGuard::from_config(&ValidationConfig {
+            disabled: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(off.check(url, b"").is_ok());
+        assert!(ContentGuard::from_config(&ValidationConfig {
+            error_patterns: vec!["(".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_expected_type() {
+        assert_eq!(
+            expected_type("https://a.gov/files/Report.PDF#page=2"),
+            Some("application/pdf")
+        );
+        assert_eq!(expected_type("https://a.gov/files/"), None);
+        assert_eq!(expected_type("https://a.gov/index.html"), None);
+    }
+}
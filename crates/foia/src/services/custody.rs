@@ -0,0 +1,646 @@
+//! Chain-of-custody certificates for documents.
+//!
+//! A certificate collects everything needed to establish provenance for a
+//! single document: where it was fetched from and when (from `crawl_requests`),
+//! the content hashes of every stored version, a fresh fixity check of the
+//! files on disk, and the processing history. The certificate body is
+//! serialized to JSON and signed with an ed25519 key so third parties can
+//! verify it was issued by the holder of that key and has not been altered.
+
+use std::path::Path;
+
+use chrono::Utc;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::models::DocumentVersion;
+use crate::repository::{DieselCrawlRepository, DieselDocumentRepository, DieselError};
+
+/// Format identifier embedded in every certificate.
+pub const CERTIFICATE_FORMAT: &str = "foia-custody-certificate/1";
+
+/// Signature algorithm identifier.
+const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Errors that can occur while building, signing, or verifying certificates.
+#[derive(Debug, thiserror::Error)]
+pub enum CustodyError {
+    #[error("database error: {0}")]
+    Database(#[from] DieselError),
+    #[error("failed to read signing key {path}: {source}")]
+    KeyRead {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid signing key: expected 32-byte hex-encoded seed")]
+    InvalidKey,
+    #[error("invalid signature encoding")]
+    InvalidSignature,
+    #[error("unsupported signature algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Document identity as recorded in the certificate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertifiedDocument {
+    pub id: String,
+    pub title: String,
+    pub source_id: String,
+    pub source_url: String,
+    pub status: String,
+    pub discovery_method: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Result of re-hashing a stored file at certificate time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixityStatus {
+    /// File exists and its SHA-256 matches the recorded hash.
+    Verified,
+    /// File exists but its SHA-256 differs from the recorded hash.
+    Mismatch,
+    /// File is not present in the documents directory.
+    Missing,
+}
+
+impl FixityStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Verified => "verified",
+            Self::Mismatch => "mismatch",
+            Self::Missing => "missing",
+        }
+    }
+}
+
+/// Fixity check for a single version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixityCheck {
+    pub status: FixityStatus,
+    pub checked_at: String,
+    /// SHA-256 observed on disk (present unless the file is missing).
+    pub observed_sha256: Option<String>,
+    pub observed_size: Option<u64>,
+}
+
+/// A stored version of the document with its recorded hashes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertifiedVersion {
+    pub id: i64,
+    pub source_url: Option<String>,
+    pub original_filename: Option<String>,
+    pub mime_type: String,
+    pub file_size: u64,
+    pub acquired_at: String,
+    pub server_date: Option<String>,
+    pub earliest_archived_at: Option<String>,
+    pub sha256: String,
+    pub blake3: Option<String>,
+    pub fixity: FixityCheck,
+}
+
+/// A logged HTTP request for one of the document's URLs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchRecord {
+    pub url: String,
+    pub method: String,
+    pub request_at: String,
+    pub response_status: Option<u16>,
+    pub response_at: Option<String>,
+    pub response_size: Option<u64>,
+    pub was_not_modified: bool,
+    pub error: Option<String>,
+}
+
+/// A processing step (OCR, transcription, etc.) applied to the document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingRecord {
+    pub version_id: i64,
+    pub page_id: Option<i64>,
+    pub analysis_type: String,
+    pub backend: String,
+    pub status: String,
+    pub created_at: String,
+}
+
+/// The certificate body. This is the exact structure that gets signed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustodyCertificate {
+    pub format: String,
+    pub issued_at: String,
+    pub signer: Option<String>,
+    pub document: CertifiedDocument,
+    pub versions: Vec<CertifiedVersion>,
+    pub fetches: Vec<FetchRecord>,
+    pub processing: Vec<ProcessingRecord>,
+}
+
+/// Detached signature over the serialized certificate body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateSignature {
+    pub algorithm: String,
+    /// Hex-encoded ed25519 public key.
+    pub public_key: String,
+    /// Hex-encoded signature.
+    pub value: String,
+}
+
+/// A certificate together with its signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedCertificate {
+    pub certificate: CustodyCertificate,
+    pub signature: CertificateSignature,
+}
+
+/// Load an ed25519 signing key from a file containing a hex-encoded 32-byte seed.
+pub fn load_signing_key(path: &Path) -> Result<SigningKey, CustodyError> {
+    let contents = std::fs::read_to_string(path).map_err(|source| CustodyError::KeyRead {
+        path: path.display().to_string(),
+        source,
+    })?;
+    parse_signing_key(contents.trim())
+}
+
+/// Parse a hex-encoded 32-byte ed25519 seed.
+pub fn parse_signing_key(hex_seed: &str) -> Result<SigningKey, CustodyError> {
+    let bytes = hex::decode(hex_seed).map_err(|_| CustodyError::InvalidKey)?;
+    let seed: [u8; 32] = bytes.try_into().map_err(|_| CustodyError::InvalidKey)?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Gather provenance data for a document into an unsigned certificate.
+///
+/// Returns `None` if the document does not exist.
+pub async fn build_certificate(
+    doc_repo: &DieselDocumentRepository,
+    crawl_repo: &DieselCrawlRepository,
+    documents_dir: &Path,
+    doc_id: &str,
+    signer: Option<&str>,
+) -> Result<Option<CustodyCertificate>, CustodyError> {
+    let Some(doc) = doc_repo.get(doc_id).await? else {
+        return Ok(None);
+    };
+
+    let mut versions = Vec::with_capacity(doc.versions.len());
+    let mut processing = Vec::new();
+    for version in &doc.versions {
+        let path = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+        let fixity = check_fixity(version, &path).await;
+        versions.push(CertifiedVersion {
+            id: version.id,
+            source_url: version.source_url.clone(),
+            original_filename: version.original_filename.clone(),
+            mime_type: version.mime_type.clone(),
+            file_size: version.file_size,
+            acquired_at: version.acquired_at.to_rfc3339(),
+            server_date: version.server_date.map(|d| d.to_rfc3339()),
+            earliest_archived_at: version.earliest_archived_at.map(|d| d.to_rfc3339()),
+            sha256: version.content_hash.clone(),
+            blake3: version.content_hash_blake3.clone(),
+            fixity,
+        });
+
+        let results = doc_repo
+            .get_analysis_results(&doc.id, version.id as i32)
+            .await?;
+        processing.extend(results.into_iter().map(|r| ProcessingRecord {
+            version_id: r.version_id,
+            page_id: r.page_id,
+            analysis_type: r.analysis_type,
+            backend: r.backend,
+            status: r.status.as_str().to_string(),
+            created_at: r.created_at,
+        }));
+    }
+    processing.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+    let mut urls = vec![doc.source_url.clone()];
+    for url in doc.versions.iter().filter_map(|v| v.source_url.as_ref()) {
+        if !urls.contains(url) {
+            urls.push(url.clone());
+        }
+    }
+    let fetches = crawl_repo
+        .get_requests_for_urls(&urls)
+        .await?
+        .into_iter()
+        .map(|r| FetchRecord {
+            url: r.url,
+            method: r.method,
+            request_at: r.request_at.to_rfc3339(),
+            response_status: r.response_status,
+            response_at: r.response_at.map(|d| d.to_rfc3339()),
+            response_size: r.response_size,
+            was_not_modified: r.was_not_modified,
+            error: r.error,
+        })
+        .collect();
+
+    Ok(Some(CustodyCertificate {
+        format: CERTIFICATE_FORMAT.to_string(),
+        issued_at: Utc::now().to_rfc3339(),
+        signer: signer.map(String::from),
+        document: CertifiedDocument {
+            id: doc.id,
+            title: doc.title,
+            source_id: doc.source_id,
+            source_url: doc.source_url,
+            status: doc.status.as_str().to_string(),
+            discovery_method: doc.discovery_method,
+            created_at: doc.created_at.to_rfc3339(),
+            updated_at: doc.updated_at.to_rfc3339(),
+        },
+        versions,
+        fetches,
+        processing,
+    }))
+}
+
+async fn check_fixity(version: &DocumentVersion, path: &Path) -> FixityCheck {
+    let checked_at = Utc::now().to_rfc3339();
+    match crate::storage::read_content_async(path).await {
+        Ok(content) => {
+            let observed = DocumentVersion::compute_hash(&content);
+            let status = if observed == version.content_hash {
+                FixityStatus::Verified
+            } else {
+                FixityStatus::Mismatch
+            };
+            FixityCheck {
+                status,
+                checked_at,
+                observed_sha256: Some(observed),
+                observed_size: Some(content.len() as u64),
+            }
+        }
+        Err(_) => FixityCheck {
+            status: FixityStatus::Missing,
+            checked_at,
+            observed_sha256: None,
+            observed_size: None,
+        },
+    }
+}
+
+impl CustodyCertificate {
+    /// Sign the serialized certificate body.
+    pub fn sign(self, key: &SigningKey) -> Result<SignedCertificate, CustodyError> {
+        let payload = serde_json::to_vec(&self)?;
+        let signature = key.sign(&payload);
+        Ok(SignedCertificate {
+            certificate: self,
+            signature: CertificateSignature {
+                algorithm: SIGNATURE_ALGORITHM.to_string(),
+                public_key: hex::encode(key.verifying_key().to_bytes()),
+                value: hex::encode(signature.to_bytes()),
+            },
+        })
+    }
+}
+
+impl SignedCertificate {
+    /// Verify the signature against the embedded public key.
+    ///
+    /// Returns `Ok(false)` if the certificate was altered after signing.
+    /// Callers should additionally check that `signature.public_key` is a
+    /// key they trust.
+    pub fn verify(&self) -> Result<bool, CustodyError> {
+        if self.signature.algorithm != SIGNATURE_ALGORITHM {
+            return Err(CustodyError::UnsupportedAlgorithm(
+                self.signature.algorithm.clone(),
+            ));
+        }
+
+        let key_bytes: [u8; 32] = hex::decode(&self.signature.public_key)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or(CustodyError::InvalidKey)?;
+        let verifying_key =
+            VerifyingKey::from_bytes(&key_bytes).map_err(|_| CustodyError::InvalidKey)?;
+
+        let sig_bytes: [u8; 64] = hex::decode(&self.signature.value)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or(CustodyError::InvalidSignature)?;
+        let signature = Signature::from_bytes(&sig_bytes);
+
+        let payload = serde_json::to_vec(&self.certificate)?;
+        Ok(verifying_key.verify(&payload, &signature).is_ok())
+    }
+
+    /// Render a human-readable text summary of the certificate.
+    pub fn to_text_lines(&self) -> Vec<String> {
+        let c = &self.certificate;
+        let mut lines = vec![
+            "CHAIN OF CUSTODY CERTIFICATE".to_string(),
+            String::new(),
+            format!("Format:        {}", c.format),
+            format!("Issued:        {}", c.issued_at),
+            format!("Issued by:     {}", c.signer.as_deref().unwrap_or("(unspecified)")),
+            String::new(),
+            "DOCUMENT".to_string(),
+            format!("ID:            {}", c.document.id),
+            format!("Title:         {}", c.document.title),
+            format!("Source:        {}", c.document.source_id),
+            format!("URL:           {}", c.document.source_url),
+            format!("Status:        {}", c.document.status),
+            format!("Discovered:    {}", c.document.discovery_method),
+            format!("Created:       {}", c.document.created_at),
+            format!("Updated:       {}", c.document.updated_at),
+        ];
+
+        lines.push(String::new());
+        lines.push(format!("VERSIONS ({})", c.versions.len()));
+        for v in &c.versions {
+            lines.push(format!("- Version {} acquired {}", v.id, v.acquired_at));
+            if let Some(url) = &v.source_url {
+                lines.push(format!("  URL:         {}", url));
+            }
+            if let Some(name) = &v.original_filename {
+                lines.push(format!("  Filename:    {}", name));
+            }
+            lines.push(format!("  Type/size:   {} / {} bytes", v.mime_type, v.file_size));
+            if let Some(date) = &v.server_date {
+                lines.push(format!("  Server date: {}", date));
+            }
+            lines.push(format!("  SHA-256:     {}", v.sha256));
+            if let Some(b3) = &v.blake3 {
+                lines.push(format!("  BLAKE3:      {}", b3));
+            }
+            lines.push(format!(
+                "  Fixity:      {} at {}",
+                v.fixity.status.as_str(),
+                v.fixity.checked_at
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("FETCH LOG ({})", c.fetches.len()));
+        for f in &c.fetches {
+            let status = f
+                .response_status
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let mut line = format!("- {} {} {} -> {}", f.request_at, f.method, f.url, status);
+            if f.was_not_modified {
+                line.push_str(" (not modified)");
+            }
+            if let Some(err) = &f.error {
+                line.push_str(&format!(" error: {}", err));
+            }
+            lines.push(line);
+        }
+
+        lines.push(String::new());
+        lines.push(format!("PROCESSING HISTORY ({})", c.processing.len()));
+        for p in &c.processing {
+            let scope = match p.page_id {
+                Some(page) => format!("page {}", page),
+                None => "document".to_string(),
+            };
+            lines.push(format!(
+                "- {} {}/{} on version {} {}: {}",
+                p.created_at, p.analysis_type, p.backend, p.version_id, scope, p.status
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push("SIGNATURE".to_string());
+        lines.push(format!("Algorithm:     {}", self.signature.algorithm));
+        lines.push(format!("Public key:    {}", self.signature.public_key));
+        lines.push(format!("Signature:     {}", self.signature.value));
+        lines.push(String::new());
+        lines.push(
+            "The signature covers the JSON certificate body; verify it with the JSON export."
+                .to_string(),
+        );
+        lines
+    }
+
+    /// Render the certificate as a plain, self-contained PDF document.
+    pub fn to_pdf(&self) -> Vec<u8> {
+        render_text_pdf(&self.to_text_lines())
+    }
+}
+
+/// Page geometry for the PDF renderer (US Letter, points).
+const PDF_PAGE_WIDTH: u32 = 612;
+const PDF_PAGE_HEIGHT: u32 = 792;
+const PDF_MARGIN: u32 = 50;
+const PDF_FONT_SIZE: u32 = 8;
+const PDF_LEADING: u32 = 11;
+/// Courier glyphs are 0.6em wide, so this fits within the margins.
+const PDF_WRAP_COLUMNS: usize = 105;
+
+/// Write lines of text into a minimal multi-page PDF using the built-in
+/// Courier font. Non-ASCII characters are replaced with `?`.
+fn render_text_pdf(lines: &[String]) -> Vec<u8> {
+    let wrapped: Vec<String> = lines
+        .iter()
+        .flat_map(|line| wrap_line(line, PDF_WRAP_COLUMNS))
+        .collect();
+    let lines_per_page = ((PDF_PAGE_HEIGHT - 2 * PDF_MARGIN) / PDF_LEADING) as usize;
+    let pages: Vec<&[String]> = if wrapped.is_empty() {
+        vec![&[]]
+    } else {
+        wrapped.chunks(lines_per_page).collect()
+    };
+
+    // Object layout: 1 = catalog, 2 = page tree, 3 = font,
+    // then (page, content stream) pairs starting at 4.
+    let page_obj = |i: usize| 4 + 2 * i;
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", page_obj(i)))
+        .collect();
+    objects.push(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+    );
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_vec());
+
+    for (i, page_lines) in pages.iter().enumerate() {
+        let mut content = format!(
+            "BT /F1 {} Tf {} TL {} {} Td\n",
+            PDF_FONT_SIZE,
+            PDF_LEADING,
+            PDF_MARGIN,
+            PDF_PAGE_HEIGHT - PDF_MARGIN
+        );
+        for line in page_lines.iter() {
+            content.push('(');
+            content.push_str(&escape_pdf_text(line));
+            content.push_str(") Tj T*\n");
+        }
+        content.push_str("ET");
+
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                PDF_PAGE_WIDTH,
+                PDF_PAGE_HEIGHT,
+                page_obj(i) + 1
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                content.len(),
+                content
+            )
+            .into_bytes(),
+        );
+    }
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, body) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(body);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref_offset = out.len();
+    out.extend_from_slice(format!("xref\n0 {}\n", objects.len() + 1).as_bytes());
+    out.extend_from_slice(b"0000000000 65535 f \n");
+    for offset in offsets {
+        out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    out
+}
+
+/// Hard-wrap a line at `width` characters (hashes and URLs have no spaces).
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.len() <= width {
+        return vec![line.to_string()];
+    }
+    chars
+        .chunks(width)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+fn escape_pdf_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_certificate() -> CustodyCertificate {
+        CustodyCertificate {
+            format: CERTIFICATE_FORMAT.to_string(),
+            issued_at: "2024-01-01T00:00:00+00:00".to_string(),
+            signer: Some("Records Desk".to_string()),
+            document: CertifiedDocument {
+                id: "doc-1".to_string(),
+                title: "Memo (draft)".to_string(),
+                source_id: "agency".to_string(),
+                source_url: "https://example.gov/memo.pdf".to_string(),
+                status: "indexed".to_string(),
+                discovery_method: "crawl".to_string(),
+                created_at: "2023-12-01T00:00:00+00:00".to_string(),
+                updated_at: "2023-12-02T00:00:00+00:00".to_string(),
+            },
+            versions: vec![CertifiedVersion {
+                id: 1,
+                source_url: None,
+                original_filename: Some("memo.pdf".to_string()),
+                mime_type: "application/pdf".to_string(),
+                file_size: 4,
+                acquired_at: "2023-12-01T00:00:00+00:00".to_string(),
+                server_date: None,
+                earliest_archived_at: None,
+                sha256: DocumentVersion::compute_hash(b"test"),
+                blake3: None,
+                fixity: FixityCheck {
+                    status: FixityStatus::Verified,
+                    checked_at: "2024-01-01T00:00:00+00:00".to_string(),
+                    observed_sha256: Some(DocumentVersion::compute_hash(b"test")),
+                    observed_size: Some(4),
+                },
+            }],
+            fetches: vec![],
+            processing: vec![],
+        }
+    }
+
+    fn test_key() -> SigningKey {
+        parse_signing_key(&"07".repeat(32)).unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let signed = sample_certificate().sign(&test_key()).unwrap();
+        assert!(signed.verify().unwrap());
+
+        // Survives a JSON round trip, which is how certificates are shared
+        let json = serde_json::to_string_pretty(&signed).unwrap();
+        let parsed: SignedCertificate = serde_json::from_str(&json).unwrap();
+        assert!(parsed.verify().unwrap());
+    }
+
+    #[test]
+    fn test_tampered_certificate_fails_verification() {
+        let mut signed = sample_certificate().sign(&test_key()).unwrap();
+        signed.certificate.versions[0].sha256 = DocumentVersion::compute_hash(b"other");
+        assert!(!signed.verify().unwrap());
+    }
+
+    #[test]
+    fn test_parse_signing_key_rejects_bad_input() {
+        assert!(parse_signing_key("not hex").is_err());
+        assert!(parse_signing_key("abcd").is_err());
+    }
+
+    #[test]
+    fn test_pdf_rendering() {
+        let signed = sample_certificate().sign(&test_key()).unwrap();
+        let pdf = signed.to_pdf();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        // Parentheses in the title are escaped inside string literals
+        assert!(text.contains("Memo \\(draft\\)"));
+    }
+
+    #[test]
+    fn test_wrap_line() {
+        assert_eq!(wrap_line("abc", 5), vec!["abc"]);
+        assert_eq!(wrap_line("abcdefg", 3), vec!["abc", "def", "g"]);
+    }
+}
@@ -0,0 +1,451 @@
+//! Analytical export of the archive for researchers.
+//!
+//! Documents, versions, pages, crawl requests and entities are written as
+//! separate CSV tables next to a `load.sql` that creates typed DuckDB
+//! tables from them. With the `duckdb` CLI on the PATH the script is run
+//! to build [`DATABASE`], and optionally one Parquet file per table, so
+//! heavy aggregate queries run against a copy instead of the production
+//! database. Without it the directory holds everything needed to build
+//! the database elsewhere.
+//!
+//! Internal and embargoed documents are left out unless asked for, along
+//! with their pages, entities and the requests that fetched them. Request
+//! headers are never exported; they can carry cookies and credentials.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::Serialize;
+
+use crate::models::{CrawlRequest, Document};
+use crate::repository::diesel_document::{Projection, StreamFilter, DEFAULT_STREAM_BATCH};
+use crate::repository::{DieselCrawlRepository, DieselDocumentRepository};
+
+/// Database file built in the export directory.
+pub const DATABASE: &str = "archive.duckdb";
+
+/// Script creating and loading the tables, relative to the export directory.
+pub const LOAD_SCRIPT: &str = "load.sql";
+
+/// A table of the export and its DuckDB column types.
+struct Table {
+    name: &'static str,
+    columns: &'static [(&'static str, &'static str)],
+}
+
+const DOCUMENTS: Table = Table {
+    name: "documents",
+    columns: &[
+        ("id", "VARCHAR PRIMARY KEY"),
+        ("source_id", "VARCHAR NOT NULL"),
+        ("title", "VARCHAR"),
+        ("source_url", "VARCHAR"),
+        ("status", "VARCHAR"),
+        ("discovery_method", "VARCHAR"),
+        ("tags", "JSON"),
+        ("synopsis", "VARCHAR"),
+        ("metadata", "JSON"),
+        ("extracted_text", "VARCHAR"),
+        ("created_at", "TIMESTAMPTZ"),
+        ("updated_at", "TIMESTAMPTZ"),
+    ],
+};
+
+const VERSIONS: Table = Table {
+    name: "versions",
+    columns: &[
+        ("id", "BIGINT PRIMARY KEY"),
+        ("document_id", "VARCHAR NOT NULL"),
+        ("content_hash", "VARCHAR"),
+        ("mime_type", "VARCHAR"),
+        ("file_size", "BIGINT"),
+        ("page_count", "INTEGER"),
+        ("source_url", "VARCHAR"),
+        ("original_filename", "VARCHAR"),
+        ("acquired_at", "TIMESTAMPTZ"),
+        ("server_date", "TIMESTAMPTZ"),
+        ("earliest_archived_at", "TIMESTAMPTZ"),
+    ],
+};
+
+const PAGES: Table = Table {
+    name: "pages",
+    columns: &[
+        ("id", "BIGINT PRIMARY KEY"),
+        ("document_id", "VARCHAR NOT NULL"),
+        ("version_id", "BIGINT NOT NULL"),
+        ("page_number", "INTEGER NOT NULL"),
+        ("ocr_status", "VARCHAR"),
+        ("pdf_text", "VARCHAR"),
+        ("ocr_text", "VARCHAR"),
+        ("final_text", "VARCHAR"),
+        ("updated_at", "TIMESTAMPTZ"),
+    ],
+};
+
+const REQUESTS: Table = Table {
+    name: "requests",
+    columns: &[
+        ("id", "BIGINT PRIMARY KEY"),
+        ("source_id", "VARCHAR NOT NULL"),
+        ("method", "VARCHAR"),
+        ("url", "VARCHAR"),
+        ("request_at", "TIMESTAMPTZ"),
+        ("response_status", "INTEGER"),
+        ("response_at", "TIMESTAMPTZ"),
+        ("response_size", "BIGINT"),
+        ("duration_ms", "BIGINT"),
+        ("was_conditional", "BOOLEAN"),
+        ("was_not_modified", "BOOLEAN"),
+        ("identified", "BOOLEAN"),
+        ("error", "VARCHAR"),
+    ],
+};
+
+const ENTITIES: Table = Table {
+    name: "entities",
+    columns: &[
+        ("id", "BIGINT PRIMARY KEY"),
+        ("document_id", "VARCHAR NOT NULL"),
+        ("entity_type", "VARCHAR"),
+        ("entity_text", "VARCHAR"),
+        ("normalized_text", "VARCHAR"),
+        ("latitude", "DOUBLE"),
+        ("longitude", "DOUBLE"),
+        ("created_at", "TIMESTAMPTZ"),
+    ],
+};
+
+const TABLES: [&Table; 5] = [&DOCUMENTS, &VERSIONS, &PAGES, &REQUESTS, &ENTITIES];
+
+/// What [`write_duckdb`] wrote.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DuckdbExport {
+    pub documents: u64,
+    pub versions: u64,
+    pub pages: u64,
+    pub requests: u64,
+    pub entities: u64,
+    /// The built database, or `None` when `duckdb` isn't installed.
+    pub database: Option<PathBuf>,
+    /// Whether Parquet files were written.
+    pub parquet: bool,
+}
+
+/// Write the archive, or one source of it, into `dir` as typed tables for
+/// DuckDB, and build the database when the `duckdb` CLI is available.
+/// `dir` must be empty or not exist yet.
+pub async fn write_duckdb(
+    doc_repo: &DieselDocumentRepository,
+    crawl_repo: &DieselCrawlRepository,
+    source_id: Option<&str>,
+    include_restricted: bool,
+    parquet: bool,
+    dir: &Path,
+) -> anyhow::Result<DuckdbExport> {
+    if dir.exists() && std::fs::read_dir(dir)?.next().is_some() {
+        anyhow::bail!("{} is not empty", dir.display());
+    }
+    std::fs::create_dir_all(dir.join("csv"))?;
+
+    let mut filter = StreamFilter::source(source_id);
+    if !include_restricted {
+        let access = doc_repo
+            .access_policy()
+            .await?
+            .filter(Utc::now().date_naive());
+        filter = filter.with_access(Some(access).filter(|a| !a.is_empty()));
+    }
+    let restricted = filter.access.is_some();
+
+    let mut summary = DuckdbExport::default();
+    let mut documents = CsvTable::create(dir, &DOCUMENTS)?;
+    let mut versions = CsvTable::create(dir, &VERSIONS)?;
+    let mut pages = CsvTable::create(dir, &PAGES)?;
+    let mut entities = CsvTable::create(dir, &ENTITIES)?;
+    let mut exported = HashSet::new();
+    let mut batch = Vec::new();
+
+    let mut stream = doc_repo.stream_documents(filter, DEFAULT_STREAM_BATCH);
+    while let Some(doc) = stream.next().await {
+        let doc = doc?;
+        documents.row(document_row(&doc))?;
+        summary.documents += 1;
+        for version in &doc.versions {
+            versions.row(vec![
+                value(Some(version.id)),
+                text(&doc.id),
+                text(&version.content_hash),
+                text(&version.mime_type),
+                value(Some(version.file_size)),
+                value(version.page_count),
+                opt_text(version.source_url.as_deref()),
+                opt_text(version.original_filename.as_deref()),
+                timestamp(Some(version.acquired_at)),
+                timestamp(version.server_date),
+                timestamp(version.earliest_archived_at),
+            ])?;
+            summary.versions += 1;
+            for page in doc_repo.get_pages(&doc.id, version.id as i32).await? {
+                pages.row(vec![
+                    value(Some(page.id)),
+                    text(&page.document_id),
+                    value(Some(page.version_id)),
+                    value(Some(page.page_number)),
+                    text(page.ocr_status.as_str()),
+                    opt_text(page.pdf_text.as_deref()),
+                    opt_text(page.ocr_text.as_deref()),
+                    opt_text(page.final_text.as_deref()),
+                    timestamp(Some(page.updated_at)),
+                ])?;
+                summary.pages += 1;
+            }
+        }
+        if restricted {
+            exported.insert(doc.id.clone());
+        }
+        batch.push(doc.id);
+        if batch.len() >= DEFAULT_STREAM_BATCH {
+            summary.entities += write_entities(doc_repo, &batch, &mut entities).await?;
+            batch.clear();
+        }
+    }
+    summary.entities += write_entities(doc_repo, &batch, &mut entities).await?;
+
+    let withheld = if restricted {
+        withheld_urls(doc_repo, source_id, &exported).await?
+    } else {
+        HashSet::new()
+    };
+    let mut requests = CsvTable::create(dir, &REQUESTS)?;
+    let mut after = 0;
+    loop {
+        let rows = crawl_repo
+            .get_requests_after(source_id, after, DEFAULT_STREAM_BATCH as i64)
+            .await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after = last.id.unwrap_or_default();
+        for request in rows.iter().filter(|r| !withheld.contains(&r.url)) {
+            requests.row(request_row(request))?;
+            summary.requests += 1;
+        }
+    }
+
+    for table in [documents, versions, pages, entities, requests] {
+        table.finish()?;
+    }
+    if parquet {
+        std::fs::create_dir_all(dir.join("parquet"))?;
+    }
+    std::fs::write(dir.join(LOAD_SCRIPT), load_sql(parquet))?;
+
+    if which::which("duckdb").is_ok() {
+        let output = tokio::process::Command::new("duckdb")
+            .arg("-bail")
+            .arg(DATABASE)
+            .arg(format!(".read {}", LOAD_SCRIPT))
+            .current_dir(dir)
+            .output()
+            .await?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "duckdb could not load the export: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        summary.database = Some(dir.join(DATABASE));
+        summary.parquet = parquet;
+    }
+    Ok(summary)
+}
+
+/// The DuckDB script creating each table and loading its CSV, then
+/// copying the tables out to Parquet if asked.
+fn load_sql(parquet: bool) -> String {
+    let mut sql = String::from("-- Build with: duckdb archive.duckdb < load.sql\n");
+    for table in TABLES {
+        let columns: Vec<String> = table
+            .columns
+            .iter()
+            .map(|(name, ty)| format!("    {} {}", name, ty))
+            .collect();
+        sql.push_str(&format!(
+            "\nCREATE TABLE {} (\n{}\n);\nCOPY {} FROM 'csv/{}.csv' (HEADER);\n",
+            table.name,
+            columns.join(",\n"),
+            table.name,
+            table.name
+        ));
+    }
+    if parquet {
+        sql.push('\n');
+        for table in TABLES {
+            sql.push_str(&format!(
+                "COPY {} TO 'parquet/{}.parquet' (FORMAT PARQUET);\n",
+                table.name, table.name
+            ));
+        }
+    }
+    sql
+}
+
+/// Entities of a batch of exported documents.
+async fn write_entities(
+    doc_repo: &DieselDocumentRepository,
+    doc_ids: &[String],
+    out: &mut CsvTable,
+) -> anyhow::Result<u64> {
+    let mut count = 0;
+    for records in doc_repo.get_entities_batch(doc_ids).await?.into_values() {
+        for entity in records {
+            out.row(vec![
+                value(Some(entity.id)),
+                text(&entity.document_id),
+                text(&entity.entity_type),
+                text(&entity.entity_text),
+                text(&entity.normalized_text),
+                value(entity.latitude),
+                value(entity.longitude),
+                text(&entity.created_at),
+            ])?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// URLs of documents the export leaves out, so the request log doesn't
+/// name them.
+async fn withheld_urls(
+    doc_repo: &DieselDocumentRepository,
+    source_id: Option<&str>,
+    exported: &HashSet<String>,
+) -> anyhow::Result<HashSet<String>> {
+    let filter = StreamFilter::source(source_id).with_projection(Projection::Metadata);
+    let mut stream = doc_repo.stream_documents(filter, DEFAULT_STREAM_BATCH);
+    let mut urls = HashSet::new();
+    while let Some(doc) = stream.next().await {
+        let doc = doc?;
+        if exported.contains(&doc.id) {
+            continue;
+        }
+        urls.extend(doc.versions.into_iter().filter_map(|v| v.source_url));
+        urls.insert(doc.source_url);
+    }
+    Ok(urls)
+}
+
+fn document_row(doc: &Document) -> Vec<String> {
+    vec![
+        text(&doc.id),
+        text(&doc.source_id),
+        text(&doc.title),
+        text(&doc.source_url),
+        text(doc.status.as_str()),
+        text(&doc.discovery_method),
+        text(&serde_json::to_string(&doc.tags).unwrap_or_else(|_| "[]".to_string())),
+        opt_text(doc.synopsis.as_deref()),
+        text(&doc.metadata.to_string()),
+        opt_text(doc.extracted_text.as_deref()),
+        timestamp(Some(doc.created_at)),
+        timestamp(Some(doc.updated_at)),
+    ]
+}
+
+fn request_row(request: &CrawlRequest) -> Vec<String> {
+    vec![
+        value(request.id),
+        text(&request.source_id),
+        text(&request.method),
+        text(&request.url),
+        timestamp(Some(request.request_at)),
+        value(request.response_status),
+        timestamp(request.response_at),
+        value(request.response_size),
+        value(request.duration_ms),
+        value(Some(request.was_conditional)),
+        value(Some(request.was_not_modified)),
+        value(Some(request.identified)),
+        opt_text(request.error.as_deref()),
+    ]
+}
+
+/// One table's CSV file.
+struct CsvTable {
+    out: BufWriter<std::fs::File>,
+    columns: usize,
+}
+
+impl CsvTable {
+    fn create(dir: &Path, table: &Table) -> std::io::Result<Self> {
+        let path = dir.join("csv").join(format!("{}.csv", table.name));
+        let mut out = BufWriter::new(std::fs::File::create(path)?);
+        let header: Vec<&str> = table.columns.iter().map(|(name, _)| *name).collect();
+        writeln!(out, "{}", header.join(","))?;
+        Ok(Self {
+            out,
+            columns: table.columns.len(),
+        })
+    }
+
+    fn row(&mut self, fields: Vec<String>) -> std::io::Result<()> {
+        debug_assert_eq!(fields.len(), self.columns);
+        writeln!(self.out, "{}", fields.join(","))
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// A text field, always quoted: DuckDB reads an empty unquoted field as
+/// NULL and `""` as an empty string.
+fn text(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+fn opt_text(s: Option<&str>) -> String {
+    s.map(text).unwrap_or_default()
+}
+
+fn value<T: Display>(v: Option<T>) -> String {
+    v.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn timestamp(t: Option<DateTime<Utc>>) -> String {
+    t.map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(text("say \"hi\",\nthen go"), "\"say \"\"hi\"\",\nthen go\"");
+        assert_eq!(opt_text(Some("")), "\"\"");
+        assert_eq!(opt_text(None), "");
+        assert_eq!(value(Some(true)), "true");
+        assert_eq!(value::<i64>(None), "");
+    }
+
+    #[test]
+    fn test_load_sql() {
+        let sql = load_sql(false);
+        for table in TABLES {
+            assert!(sql.contains(&format!("CREATE TABLE {} (", table.name)));
+            assert!(sql.contains(&format!(
+                "COPY {} FROM 'csv/{}.csv'",
+                table.name, table.name
+            )));
+        }
+        assert!(!sql.contains("PARQUET"));
+        assert!(load_sql(true).contains("COPY pages TO 'parquet/pages.parquet' (FORMAT PARQUET);"));
+    }
+}
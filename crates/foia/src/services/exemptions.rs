@@ -0,0 +1,151 @@
+//! FOIA exemption markings in released pages.
+//!
+//! Redacted releases cite the exemption justifying each withholding, either
+//! in full (`(b)(5)`, `(b)(7)(C)`) or in the short form redaction software
+//! stamps on the blacked-out area (`b6`, `b7C`). Codes are normalized to the
+//! full form so `b7c`, `(b) (7) (c)` and `(b)(7)(C)` count as one.
+
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// `(b)(5)`, `(b) (7) (C)`, `b(6)`.
+static FULL_FORM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:\(\s*b\s*\)|\bb)\s*\(\s*([1-9])\s*\)(?:\s*\(\s*([a-f])\s*\))?").unwrap()
+});
+/// `b5`, `b7C`, `B6`.
+static SHORT_FORM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[bB]([1-9])([A-Fa-f])?\b").unwrap());
+/// `Exemption 5`, `exemption 7(C)` in cover letters.
+static PHRASE_FORM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bexemptions?\s+([1-9])\b(?:\s*\(\s*([a-f])\s*\))?").unwrap()
+});
+
+/// Number of exemption markings of one code on a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageExemption {
+    pub page_number: u32,
+    /// Normalized code, e.g. `(b)(7)(C)`.
+    pub code: String,
+    pub count: u32,
+}
+
+/// Canonical form of an exemption number and optional (b)(7) subsection.
+fn code(number: &str, sub: Option<&str>) -> String {
+    match sub {
+        Some(sub) if number == "7" => format!("(b)(7)({})", sub.to_uppercase()),
+        _ => format!("(b)({})", number),
+    }
+}
+
+/// Normalize an exemption code typed by a user: `b5`, `(b)(7)(c)`, `7C`.
+pub fn normalize_code(s: &str) -> Option<String> {
+    let compact: String = s
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    let rest = compact.strip_prefix('b').unwrap_or(&compact);
+    let mut chars = rest.chars();
+    let number = chars.next().filter(|c| ('1'..='9').contains(c))?;
+    let sub = chars.next();
+    if chars.next().is_some() {
+        return None;
+    }
+    match sub {
+        None => Some(code(&number.to_string(), None)),
+        Some(sub @ 'a'..='f') if number == '7' => Some(code("7", Some(&sub.to_string()))),
+        Some(_) => None,
+    }
+}
+
+/// Count exemption markings in a page's text, by normalized code.
+///
+/// Short forms like `b6` are also aircraft, vitamins and grid squares, so
+/// they only count on lines citing at least one other exemption.
+pub fn find_exemptions(text: &str) -> BTreeMap<String, u32> {
+    let mut counts = BTreeMap::new();
+    for line in text.lines() {
+        let mut found: Vec<String> = FULL_FORM
+            .captures_iter(line)
+            .chain(PHRASE_FORM.captures_iter(line))
+            .map(|c| code(&c[1], c.get(2).map(|m| m.as_str())))
+            .collect();
+        let short: Vec<String> = SHORT_FORM
+            .captures_iter(line)
+            .map(|c| code(&c[1], c.get(2).map(|m| m.as_str())))
+            .collect();
+        if short.len() >= 2 || (!short.is_empty() && !found.is_empty()) {
+            found.extend(short);
+        }
+        for exemption in found {
+            *counts.entry(exemption).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Exemption markings of every page, in page order.
+pub fn page_exemptions<'a>(pages: impl IntoIterator<Item = (u32, &'a str)>) -> Vec<PageExemption> {
+    pages
+        .into_iter()
+        .flat_map(|(page_number, text)| {
+            find_exemptions(text)
+                .into_iter()
+                .map(move |(code, count)| PageExemption {
+                    page_number,
+                    code,
+                    count,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_code() {
+        assert_eq!(normalize_code("b5").as_deref(), Some("(b)(5)"));
+        assert_eq!(normalize_code("(b)(7)(c)").as_deref(), Some("(b)(7)(C)"));
+        assert_eq!(normalize_code("7C").as_deref(), Some("(b)(7)(C)"));
+        assert_eq!(normalize_code("(b) (6)").as_deref(), Some("(b)(6)"));
+        assert!(normalize_code("b5c").is_none());
+        assert!(normalize_code("b10").is_none());
+        assert!(normalize_code("memo").is_none());
+    }
+
+    #[test]
+    fn test_find_exemptions() {
+        let page = "Withheld pursuant to (b)(5) and (b) (7) (c).\n\
+                    [REDACTED] b6 b7C [REDACTED]\n\
+                    The B2 bomber flew overhead.\n\
+                    Exemption 7(E) applies to techniques. b(6) again.";
+        let found = find_exemptions(page);
+        assert_eq!(found.get("(b)(5)"), Some(&1));
+        assert_eq!(found.get("(b)(6)"), Some(&2));
+        assert_eq!(found.get("(b)(7)(C)"), Some(&2));
+        assert_eq!(found.get("(b)(7)(E)"), Some(&1));
+        assert!(!found.contains_key("(b)(2)"));
+
+        let pages = page_exemptions([(1, "nothing here"), (2, "b6 b6 b7c")]);
+        assert_eq!(
+            pages,
+            vec![
+                PageExemption {
+                    page_number: 2,
+                    code: "(b)(6)".to_string(),
+                    count: 2
+                },
+                PageExemption {
+                    page_number: 2,
+                    code: "(b)(7)(C)".to_string(),
+                    count: 1
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,1460 @@
+//! Scheduled exports that feed downstream data pipelines.
+//!
+//! An export job writes the archive to a file and pushes it to a local
+//! directory, an S3 bucket or an SFTP server:
+//!
+//! - `jsonl`: metadata and text of documents changed since the job's last
+//!   successful run, one JSON object per line, followed by a
+//!   `{"id": ..., "deleted": true}` line per document deleted since.
+//! - `warc`: every stored file as a WARC/1.1 `resource` record.
+//! - `bag`: every stored file in a zipped BagIt bag with SHA-256 manifest,
+//!   named by content hash, original production path or Bates range.
+//!
+//! [`write_directory`] writes one source's files as a plain folder tree in
+//! the same layouts, for handing a production onward.
+//!
+//! Runs are recorded in `export_runs` with the change journal entry they
+//! started at, which is where incremental exports pick up from; failed
+//! runs are posted to the configured alert webhook.
+//! Internal and embargoed documents are left out unless the job includes
+//! restricted documents.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::{error, warn};
+
+use crate::config::{ExportJobConfig, S3Config};
+use crate::models::{Document, DocumentVersion};
+use crate::repository::diesel_document::{Projection, StreamFilter, DEFAULT_STREAM_BATCH};
+use crate::repository::{DieselDocumentRepository, DieselError};
+use crate::secrets::{self, SecretError};
+use crate::services::politeness::escape_csv;
+use crate::storage;
+
+/// Region used for S3 when none is configured.
+const DEFAULT_S3_REGION: &str = "us-east-1";
+
+/// Longest wait before a failed job is retried.
+const RETRY_AFTER_HOURS: i64 = 1;
+
+/// Errors from running an export job.
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("export job '{job}': {message}")]
+    Config { job: String, message: String },
+    #[error("database error: {0}")]
+    Database(#[from] DieselError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Secret(#[from] SecretError),
+    #[error("zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("upload failed: {0}")]
+    Upload(String),
+}
+
+/// What an export job writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Documents changed since the last successful run, as JSON lines.
+    Jsonl,
+    /// Every stored file as WARC resource records.
+    Warc,
+    /// Every stored file in a zipped BagIt bag.
+    Bag,
+}
+
+impl ExportFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "jsonl" => Some(Self::Jsonl),
+            "warc" => Some(Self::Warc),
+            "bag" | "bagit" => Some(Self::Bag),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "jsonl",
+            Self::Warc => "warc",
+            Self::Bag => "bag",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "jsonl",
+            Self::Warc => "warc",
+            Self::Bag => "zip",
+        }
+    }
+
+    /// Whether each run only covers changes since the last successful one.
+    pub fn is_incremental(&self) -> bool {
+        matches!(self, Self::Jsonl)
+    }
+}
+
+/// How stored files are named in a bag or directory export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportLayout {
+    /// `<hash>.<ext>`, each distinct file once.
+    #[default]
+    Hash,
+    /// The path the file had in the production it was imported from.
+    Original,
+    /// `<begin>_<end>.<ext>` by Bates range, so files sort in Bates order.
+    Bates,
+}
+
+impl ExportLayout {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "hash" => Some(Self::Hash),
+            "original" => Some(Self::Original),
+            "bates" => Some(Self::Bates),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hash => "hash",
+            Self::Original => "original",
+            Self::Bates => "bates",
+        }
+    }
+}
+
+/// How often an export job runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+impl Schedule {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "hourly" => Some(Self::Hourly),
+            "daily" => Some(Self::Daily),
+            "weekly" => Some(Self::Weekly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        match self {
+            Self::Hourly => Duration::hours(1),
+            Self::Daily => Duration::days(1),
+            Self::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// Where an export is pushed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    /// A local (or mounted) directory.
+    Local(PathBuf),
+    /// `s3://bucket/prefix`
+    S3 { bucket: String, prefix: String },
+    /// `sftp://user@host/path`; `/~/path` is relative to the login directory.
+    Sftp { host: String, path: String },
+}
+
+impl Destination {
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Some(rest) = s.strip_prefix("s3://") {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                return None;
+            }
+            return Some(Self::S3 {
+                bucket: bucket.to_string(),
+                prefix: prefix.trim_matches('/').to_string(),
+            });
+        }
+        if let Some(rest) = s.strip_prefix("sftp://") {
+            let (host, path) = match rest.find('/') {
+                Some(i) => rest.split_at(i),
+                None => (rest, "/~"),
+            };
+            if host.is_empty() {
+                return None;
+            }
+            return Some(Self::Sftp {
+                host: host.to_string(),
+                path: path.trim_end_matches('/').to_string(),
+            });
+        }
+        let path = s.strip_prefix("file://").unwrap_or(s);
+        if path.is_empty() {
+            return None;
+        }
+        Some(Self::Local(PathBuf::from(path)))
+    }
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(path) => write!(f, "{}", path.display()),
+            Self::S3 { bucket, prefix } if prefix.is_empty() => write!(f, "s3://{}", bucket),
+            Self::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+            Self::Sftp { host, path } => write!(f, "sftp://{}{}", host, path),
+        }
+    }
+}
+
+/// A configured export job.
+#[derive(Debug, Clone)]
+pub struct ExportJob {
+    pub name: String,
+    pub format: ExportFormat,
+    pub schedule: Schedule,
+    pub destination: Destination,
+    pub source: Option<String>,
+    /// File layout of `bag` exports.
+    pub layout: ExportLayout,
+    /// Also export internal and embargoed documents.
+    pub include_restricted: bool,
+    /// Read every document from one database snapshot.
+    pub snapshot: bool,
+    pub s3: S3Config,
+}
+
+impl ExportJob {
+    pub fn from_config(name: &str, config: &ExportJobConfig) -> Result<Self, ExportError> {
+        let invalid = |message: String| ExportError::Config {
+            job: name.to_string(),
+            message,
+        };
+        let format = ExportFormat::from_str(&config.format).ok_or_else(|| {
+            invalid(format!(
+                "unknown format '{}' (expected jsonl, warc or bag)",
+                config.format
+            ))
+        })?;
+        let schedule = Schedule::from_str(&config.schedule).ok_or_else(|| {
+            invalid(format!(
+                "unknown schedule '{}' (expected hourly, daily or weekly)",
+                config.schedule
+            ))
+        })?;
+        let destination = Destination::parse(&config.destination)
+            .ok_or_else(|| invalid(format!("invalid destination '{}'", config.destination)))?;
+        let layout = match config.layout.as_deref() {
+            None => ExportLayout::default(),
+            Some(_) if format != ExportFormat::Bag => {
+                return Err(invalid("layout only applies to bag exports".to_string()))
+            }
+            Some(layout) => ExportLayout::from_str(layout).ok_or_else(|| {
+                invalid(format!(
+                    "unknown layout '{}' (expected hash, original or bates)",
+                    layout
+                ))
+            })?,
+        };
+        Ok(Self {
+            name: name.to_string(),
+            format,
+            schedule,
+            destination,
+            source: config.source.clone(),
+            layout,
+            include_restricted: config.include_restricted,
+            snapshot: config.snapshot,
+            s3: config.s3.clone(),
+        })
+    }
+
+    /// Whether the job should run at `now`, given when it last succeeded
+    /// and when it last failed (if after that).
+    ///
+    /// A failed job is retried after an hour, or sooner if its schedule is.
+    pub fn is_due(
+        &self,
+        last_success: Option<DateTime<Utc>>,
+        last_failure: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let interval = self.schedule.interval();
+        if let Some(failed) = last_failure {
+            if now - failed < interval.min(Duration::hours(RETRY_AFTER_HOURS)) {
+                return false;
+            }
+        }
+        last_success.is_none_or(|t| now - t >= interval)
+    }
+
+    /// File name of the run started at `started_at`.
+    fn artifact_name(&self, started_at: DateTime<Utc>) -> String {
+        format!(
+            "{}-{}.{}",
+            self.name,
+            started_at.format("%Y%m%dT%H%M%SZ"),
+            self.format.extension()
+        )
+    }
+}
+
+/// Outcome of an export run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "running" => Some(Self::Running),
+            "succeeded" => Some(Self::Succeeded),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A recorded run of an export job.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportRun {
+    pub id: i32,
+    pub job: String,
+    pub format: String,
+    pub destination: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub status: RunStatus,
+    /// Where the export was written.
+    pub artifact: Option<String>,
+    pub documents: u64,
+    pub bytes: u64,
+    pub error: Option<String>,
+    /// Latest change journal entry when the run started.
+    pub change_seq: Option<i64>,
+}
+
+/// Progress of an export, reported to the callback given to
+/// [`ExportRunner::with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportProgress {
+    /// Writing started, with the number of documents to write.
+    Started { total: u64 },
+    /// Another document was written.
+    Exported,
+}
+
+/// Callback receiving [`ExportProgress`].
+pub type ExportProgressCallback = Arc<dyn Fn(ExportProgress) + Send + Sync>;
+
+/// What a successful export produced.
+struct Exported {
+    artifact: String,
+    documents: u64,
+    bytes: u64,
+}
+
+/// Runs export jobs and records their history.
+pub struct ExportRunner<'a> {
+    repo: &'a DieselDocumentRepository,
+    documents_dir: &'a Path,
+    /// Directory for artifacts before upload.
+    work_dir: &'a Path,
+    client: reqwest::Client,
+    alert_webhook: Option<String>,
+    progress: Option<ExportProgressCallback>,
+}
+
+impl<'a> ExportRunner<'a> {
+    pub fn new(
+        repo: &'a DieselDocumentRepository,
+        documents_dir: &'a Path,
+        work_dir: &'a Path,
+        client: reqwest::Client,
+        alert_webhook: Option<String>,
+    ) -> Self {
+        Self {
+            repo,
+            documents_dir,
+            work_dir,
+            client,
+            alert_webhook,
+            progress: None,
+        }
+    }
+
+    /// Report the progress of each export to `callback`.
+    pub fn with_progress(mut self, callback: ExportProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
+
+    /// Whether a job should run now, according to its history.
+    pub async fn is_due(&self, job: &ExportJob) -> Result<bool, ExportError> {
+        let last_success = self
+            .repo
+            .last_export_run(&job.name, Some(RunStatus::Succeeded))
+            .await?
+            .map(|r| r.started_at);
+        let last_failure = self
+            .repo
+            .last_export_run(&job.name, Some(RunStatus::Failed))
+            .await?
+            .map(|r| r.started_at)
+            .filter(|failed| last_success.is_none_or(|ok| *failed > ok));
+        Ok(job.is_due(last_success, last_failure, Utc::now()))
+    }
+
+    /// Run a job and record it. A failed export is recorded and alerted,
+    /// not returned as an error; errors are for failing to record the run.
+    pub async fn run(&self, job: &ExportJob) -> Result<ExportRun, ExportError> {
+        let since = if job.format.is_incremental() {
+            self.repo
+                .last_export_run(&job.name, Some(RunStatus::Succeeded))
+                .await?
+                .and_then(|r| r.change_seq)
+        } else {
+            None
+        };
+
+        let started_at = Utc::now();
+        let change_seq = self.repo.latest_change_seq().await?;
+        let destination = job.destination.to_string();
+        let id = self
+            .repo
+            .start_export_run(
+                &job.name,
+                job.format.as_str(),
+                &destination,
+                started_at,
+                change_seq,
+            )
+            .await?;
+
+        match self.export(job, since, started_at).await {
+            Ok(exported) => Ok(self
+                .repo
+                .finish_export_run(
+                    id,
+                    RunStatus::Succeeded,
+                    Some(&exported.artifact),
+                    exported.documents,
+                    exported.bytes,
+                    None,
+                )
+                .await?),
+            Err(e) => {
+                error!("Export job '{}' failed: {}", job.name, e);
+                let run = self
+                    .repo
+                    .finish_export_run(id, RunStatus::Failed, None, 0, 0, Some(&e.to_string()))
+                    .await?;
+                self.alert(&run).await;
+                Ok(run)
+            }
+        }
+    }
+
+    async fn export(
+        &self,
+        job: &ExportJob,
+        since: Option<i64>,
+        started_at: DateTime<Utc>,
+    ) -> Result<Exported, ExportError> {
+        tokio::fs::create_dir_all(self.work_dir).await?;
+        let dir = tempfile::Builder::new()
+            .prefix(".export-")
+            .tempdir_in(self.work_dir)?;
+        let name = job.artifact_name(started_at);
+        let local = dir.path().join(&name);
+
+        let mut filter = StreamFilter::source(job.source.as_deref());
+        if !job.include_restricted {
+            let access = self
+                .repo
+                .access_policy()
+                .await?
+                .filter(started_at.date_naive());
+            filter = filter.with_access(Some(access).filter(|a| !a.is_empty()));
+        }
+        let documents = match job.format {
+            ExportFormat::Jsonl => {
+                let deleted = match since {
+                    Some(seq) => {
+                        self.repo
+                            .get_deleted_documents_since(seq, job.source.as_deref())
+                            .await?
+                    }
+                    None => Vec::new(),
+                };
+                filter.changed_since = since;
+                let docs = self.documents(filter, job.snapshot).await?;
+                write_jsonl(docs, &deleted, &local).await?
+            }
+            ExportFormat::Warc => {
+                let filter = filter.with_projection(Projection::Metadata);
+                let docs = self.documents(filter, job.snapshot).await?;
+                write_warc(docs, self.documents_dir, &local).await?
+            }
+            ExportFormat::Bag => {
+                let docs = self.documents(filter, job.snapshot).await?;
+                write_bag(
+                    docs,
+                    self.repo,
+                    self.documents_dir,
+                    job.layout,
+                    &job.name,
+                    &local,
+                )
+                .await?
+            }
+        };
+        let bytes = tokio::fs::metadata(&local).await?.len();
+        let artifact = self.upload(job, &local, &name).await?;
+        Ok(Exported {
+            artifact,
+            documents,
+            bytes,
+        })
+    }
+
+    /// Documents to export, counted first and each reported as it is read
+    /// when there is a progress callback.
+    async fn documents(
+        &self,
+        filter: StreamFilter,
+        snapshot: bool,
+    ) -> Result<DocumentStream, ExportError> {
+        let Some(progress) = self.progress.clone() else {
+            return Ok(export_stream(self.repo, filter, snapshot));
+        };
+        let total = self.repo.count_stream(&filter).await?;
+        progress(ExportProgress::Started { total });
+        Ok(export_stream(self.repo, filter, snapshot)
+            .inspect(move |doc| {
+                if doc.is_ok() {
+                    progress(ExportProgress::Exported);
+                }
+            })
+            .boxed())
+    }
+
+    /// Push the artifact to the job's destination, returning its location.
+    async fn upload(
+        &self,
+        job: &ExportJob,
+        local: &Path,
+        name: &str,
+    ) -> Result<String, ExportError> {
+        match &job.destination {
+            Destination::Local(dir) => {
+                tokio::fs::create_dir_all(dir).await?;
+                let target = dir.join(name);
+                // Copy beside the target and rename, so readers never see
+                // a partial file
+                let partial = dir.join(format!(".{}.part", name));
+                tokio::fs::copy(local, &partial).await?;
+                tokio::fs::rename(&partial, &target).await?;
+                Ok(target.display().to_string())
+            }
+            Destination::S3 { bucket, prefix } => {
+                let key = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+                let body = tokio::fs::read(local).await?;
+                put_s3(&self.client, &job.s3, bucket, &key, body).await?;
+                Ok(format!("s3://{}/{}", bucket, key))
+            }
+            Destination::Sftp { host, path } => {
+                let remote = put_sftp(host, path, local, name).await?;
+                if remote.starts_with('/') {
+                    Ok(format!("sftp://{}{}", host, remote))
+                } else {
+                    Ok(format!("sftp://{}/~/{}", host, remote))
+                }
+            }
+        }
+    }
+
+    /// Post a failed run to the alert webhook, if one is configured.
+    async fn alert(&self, run: &ExportRun) {
+        let Some(ref url) = self.alert_webhook else {
+            return;
+        };
+        let url = match secrets::resolve(url) {
+            Ok(url) => url,
+            Err(e) => {
+                warn!("Export alert webhook: {}", e);
+                return;
+            }
+        };
+        let payload = serde_json::json!({
+            "event": "export_failed",
+            "run": run,
+        });
+        let result = self
+            .client
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        if let Err(e) = result {
+            warn!("Failed to deliver export alert for '{}': {}", run.job, e);
+        }
+    }
+}
+
+type DocumentStream = BoxStream<'static, Result<Document, DieselError>>;
+
+/// Documents to export, read from one database snapshot if `snapshot` is
+/// set so a crawl writing meanwhile can't leave the artifact half old, half
+/// new.
+fn export_stream(
+    repo: &DieselDocumentRepository,
+    filter: StreamFilter,
+    snapshot: bool,
+) -> DocumentStream {
+    if snapshot {
+        repo.stream_documents_snapshot(filter, DEFAULT_STREAM_BATCH)
+    } else {
+        repo.stream_documents(filter, DEFAULT_STREAM_BATCH)
+    }
+}
+
+/// Write documents as JSON lines, then a line per deleted document.
+async fn write_jsonl(
+    mut stream: DocumentStream,
+    deleted: &[String],
+    path: &Path,
+) -> Result<u64, ExportError> {
+    let mut out = BufWriter::new(std::fs::File::create(path)?);
+    let mut count = 0;
+    while let Some(doc) = stream.next().await {
+        serde_json::to_writer(&mut out, &doc?).map_err(std::io::Error::from)?;
+        out.write_all(b"\n")?;
+        count += 1;
+    }
+    for id in deleted {
+        serde_json::to_writer(&mut out, &serde_json::json!({ "id": id, "deleted": true }))
+            .map_err(std::io::Error::from)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(count)
+}
+
+/// Read a stored version's content, or `None` if its file is gone.
+async fn read_version(
+    documents_dir: &Path,
+    doc_url: &str,
+    doc_title: &str,
+    version: &DocumentVersion,
+) -> Result<Option<Vec<u8>>, ExportError> {
+    let path = version.resolve_path(documents_dir, doc_url, doc_title);
+    match storage::read_content_async(&path).await {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            warn!("Skipping missing file {}", path.display());
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write one WARC/1.1 record.
+fn write_warc_record(
+    out: &mut impl Write,
+    headers: &[(&str, String)],
+    block: &[u8],
+) -> std::io::Result<()> {
+    out.write_all(b"WARC/1.1\r\n")?;
+    for (name, value) in headers {
+        write!(out, "{}: {}\r\n", name, value)?;
+    }
+    write!(out, "Content-Length: {}\r\n\r\n", block.len())?;
+    out.write_all(block)?;
+    out.write_all(b"\r\n\r\n")
+}
+
+fn warc_record_id() -> String {
+    format!("<urn:uuid:{}>", uuid::Uuid::new_v4())
+}
+
+/// Write every stored version as a WARC resource record.
+async fn write_warc(
+    mut stream: DocumentStream,
+    documents_dir: &Path,
+    path: &Path,
+) -> Result<u64, ExportError> {
+    let mut out = BufWriter::new(std::fs::File::create(path)?);
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    write_warc_record(
+        &mut out,
+        &[
+            ("WARC-Type", "warcinfo".to_string()),
+            ("WARC-Record-ID", warc_record_id()),
+            (
+                "WARC-Date",
+                Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            ),
+            ("WARC-Filename", filename),
+            ("Content-Type", "application/warc-fields".to_string()),
+        ],
+        format!("software: foia/{}\r\n", env!("CARGO_PKG_VERSION")).as_bytes(),
+    )?;
+
+    let mut count = 0;
+    while let Some(doc) = stream.next().await {
+        let doc = doc?;
+        let mut written = false;
+        for version in &doc.versions {
+            let Some(content) =
+                read_version(documents_dir, &doc.source_url, &doc.title, version).await?
+            else {
+                continue;
+            };
+            let target = version.source_url.as_deref().unwrap_or(&doc.source_url);
+            write_warc_record(
+                &mut out,
+                &[
+                    ("WARC-Type", "resource".to_string()),
+                    ("WARC-Record-ID", warc_record_id()),
+                    (
+                        "WARC-Date",
+                        version.acquired_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                    ),
+                    ("WARC-Target-URI", target.to_string()),
+                    (
+                        "WARC-Block-Digest",
+                        format!("sha256:{}", DocumentVersion::compute_hash(&content)),
+                    ),
+                    ("Content-Type", version.mime_type.clone()),
+                ],
+                &content,
+            )?;
+            written = true;
+        }
+        if written {
+            count += 1;
+        }
+    }
+    out.flush()?;
+    Ok(count)
+}
+
+/// Write every stored version into a zipped BagIt bag.
+///
+/// Files go under `data/<source>/`, named by `layout`, with document
+/// metadata in `data/documents.jsonl`.
+async fn write_bag(
+    mut stream: DocumentStream,
+    repo: &DieselDocumentRepository,
+    documents_dir: &Path,
+    layout: ExportLayout,
+    job: &str,
+    path: &Path,
+) -> Result<u64, ExportError> {
+    use zip::write::SimpleFileOptions;
+
+    let options = SimpleFileOptions::default();
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+    let mut manifest = String::new();
+    let mut entries = Entries::default();
+    // Metadata (with text) is staged on disk rather than held in memory
+    let metadata_path = path.with_extension("documents.jsonl");
+    let mut metadata = BufWriter::new(std::fs::File::create(&metadata_path)?);
+    let mut payload_bytes = 0u64;
+    let mut payload_files = 0u64;
+
+    let mut count = 0;
+    while let Some(doc) = stream.next().await {
+        let doc = doc?;
+        let names = LayoutNames::load(repo, &doc, layout, false).await?;
+        let mut written = false;
+        for version in &doc.versions {
+            let Some(content) =
+                read_version(documents_dir, &doc.source_url, &doc.title, version).await?
+            else {
+                continue;
+            };
+            let hash = DocumentVersion::compute_hash(&content);
+            let (name, _) = names.path(version, &hash);
+            let Some(entry) = entries.claim(format!("data/{}/{}", doc.source_id, name), &hash)
+            else {
+                // The same file in two documents is stored once
+                continue;
+            };
+            zip.start_file(entry.as_str(), options)?;
+            zip.write_all(&content)?;
+            manifest.push_str(&format!("{}  {}\n", hash, entry));
+            payload_bytes += content.len() as u64;
+            payload_files += 1;
+            written = true;
+        }
+        if written {
+            serde_json::to_writer(&mut metadata, &doc).map_err(std::io::Error::from)?;
+            metadata.write_all(b"\n")?;
+            count += 1;
+        }
+    }
+
+    metadata.flush()?;
+    drop(metadata);
+    let entry = "data/documents.jsonl";
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(&metadata_path)?, &mut hasher)?;
+    zip.start_file(entry, options)?;
+    payload_bytes += std::io::copy(&mut std::fs::File::open(&metadata_path)?, &mut zip)?;
+    payload_files += 1;
+    manifest.push_str(&format!("{}  {}\n", hex::encode(hasher.finalize()), entry));
+    std::fs::remove_file(&metadata_path)?;
+
+    zip.start_file("bagit.txt", options)?;
+    zip.write_all(b"BagIt-Version: 1.0\nTag-File-Character-Encoding: UTF-8\n")?;
+    zip.start_file("manifest-sha256.txt", options)?;
+    zip.write_all(manifest.as_bytes())?;
+    zip.start_file("bag-info.txt", options)?;
+    zip.write_all(
+        format!(
+            "Bagging-Date: {}\nPayload-Oxum: {}.{}\nExternal-Description: foia export '{}'\n",
+            Utc::now().format("%Y-%m-%d"),
+            payload_bytes,
+            payload_files,
+            job
+        )
+        .as_bytes(),
+    )?;
+    zip.finish()?;
+    Ok(count)
+}
+
+/// HMAC-SHA256 (RFC 2104).
+/// Name of the file map written at the root of a directory export.
+pub const FILE_MAP: &str = "filemap.csv";
+
+/// What [`write_directory`] wrote.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DirectoryExport {
+    pub documents: u64,
+    pub files: u64,
+    pub bytes: u64,
+    /// Files named by hash because the layout had no name for them.
+    pub unnamed: u64,
+}
+
+/// Write a source's stored files into `dir`, named by `layout`, so a
+/// production can be handed onward as the folder tree it arrived in.
+///
+/// [`FILE_MAP`] lists every file with its document, content hash, Bates
+/// range and original path. `dir` must be empty or not exist yet.
+pub async fn write_directory(
+    repo: &DieselDocumentRepository,
+    documents_dir: &Path,
+    source_id: &str,
+    layout: ExportLayout,
+    include_restricted: bool,
+    dir: &Path,
+) -> Result<DirectoryExport, ExportError> {
+    if dir.exists() && std::fs::read_dir(dir)?.next().is_some() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} is not empty", dir.display()),
+        )
+        .into());
+    }
+    tokio::fs::create_dir_all(dir).await?;
+
+    let mut filter = StreamFilter::source(Some(source_id));
+    if !include_restricted {
+        let access = repo.access_policy().await?.filter(Utc::now().date_naive());
+        filter = filter.with_access(Some(access).filter(|a| !a.is_empty()));
+    }
+    let mut stream = export_stream(repo, filter, false);
+
+    let mut map =
+        String::from("path,document_id,content_hash,begin_bates,end_bates,original_path,title\n");
+    let mut entries = Entries::default();
+    let mut summary = DirectoryExport::default();
+    while let Some(doc) = stream.next().await {
+        let doc = doc?;
+        let names = LayoutNames::load(repo, &doc, layout, true).await?;
+        let mut written = false;
+        for version in &doc.versions {
+            let Some(content) =
+                read_version(documents_dir, &doc.source_url, &doc.title, version).await?
+            else {
+                continue;
+            };
+            let hash = DocumentVersion::compute_hash(&content);
+            let (name, named) = names.path(version, &hash);
+            let Some(entry) = entries.claim(name, &hash) else {
+                continue;
+            };
+            let target = dir.join(&entry);
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&target, &content).await?;
+
+            let (begin, end) = names.bates.clone().unwrap_or_default();
+            map.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                escape_csv(&entry),
+                escape_csv(&doc.id),
+                hash,
+                escape_csv(&begin),
+                escape_csv(&end),
+                escape_csv(names.original(version).unwrap_or("")),
+                escape_csv(&doc.title)
+            ));
+            summary.files += 1;
+            summary.bytes += content.len() as u64;
+            if !named {
+                summary.unnamed += 1;
+            }
+            written = true;
+        }
+        if written {
+            summary.documents += 1;
+        }
+    }
+    tokio::fs::write(dir.join(FILE_MAP), map).await?;
+    Ok(summary)
+}
+
+/// A path from a production made safe to write under an export root:
+/// forward slashes, no drive letter, no `.` components. `None` if it
+/// climbs out with `..` or nothing is left.
+pub fn normalize_original_path(path: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for (i, part) in path.split(['/', '\\']).enumerate() {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            drive if i == 0 && drive.ends_with(':') => {}
+            part => parts.push(part),
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// File name stem of a document under the Bates layout.
+fn bates_stem(begin: &str, end: &str) -> String {
+    let clean = |s: &str| s.replace(['/', '\\'], "_");
+    if begin == end {
+        clean(begin)
+    } else {
+        format!("{}_{}", clean(begin), clean(end))
+    }
+}
+
+/// Names for one document's files under an export layout.
+struct LayoutNames {
+    layout: ExportLayout,
+    /// Original paths by content hash.
+    original: HashMap<String, String>,
+    /// First and last Bates number.
+    bates: Option<(String, String)>,
+}
+
+impl LayoutNames {
+    /// Look up what `layout` names files by, or everything if `all`.
+    async fn load(
+        repo: &DieselDocumentRepository,
+        doc: &Document,
+        layout: ExportLayout,
+        all: bool,
+    ) -> Result<Self, DieselError> {
+        let original = if all || layout == ExportLayout::Original {
+            repo.get_original_paths(&doc.id).await?
+        } else {
+            HashMap::new()
+        };
+        let bates = if all || layout == ExportLayout::Bates {
+            Self::bates_range(repo, doc).await?
+        } else {
+            None
+        };
+        Ok(Self {
+            layout,
+            original,
+            bates,
+        })
+    }
+
+    /// Bates range from the import load file, else from extracted stamps.
+    async fn bates_range(
+        repo: &DieselDocumentRepository,
+        doc: &Document,
+    ) -> Result<Option<(String, String)>, DieselError> {
+        let field = |key: &str| {
+            doc.metadata
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        if let Some(begin) = field("begin_bates") {
+            let end = field("end_bates").unwrap_or_else(|| begin.clone());
+            return Ok(Some((begin, end)));
+        }
+        let ranges = repo.get_bates_ranges(&doc.id).await?;
+        Ok(ranges
+            .first()
+            .zip(ranges.last())
+            .map(|(first, last)| (first.first().to_string(), last.last().to_string())))
+    }
+
+    fn original(&self, version: &DocumentVersion) -> Option<&str> {
+        self.original.get(&version.content_hash).map(String::as_str)
+    }
+
+    /// Relative path of a version's file, and whether the layout named it
+    /// (rather than falling back to its hash).
+    fn path(&self, version: &DocumentVersion, hash: &str) -> (String, bool) {
+        let extension = storage::mime_to_extension(&version.mime_type);
+        let named = match self.layout {
+            ExportLayout::Hash => None,
+            ExportLayout::Original => self.original(version).and_then(normalize_original_path),
+            ExportLayout::Bates => self
+                .bates
+                .as_ref()
+                .map(|(begin, end)| format!("{}.{}", bates_stem(begin, end), extension)),
+        };
+        match named {
+            Some(path) => (path, true),
+            None => (format!("{}.{}", hash, extension), false),
+        }
+    }
+}
+
+/// Paths written so far with their content, so the same file in two
+/// documents is written once and different files never overwrite each other.
+#[derive(Default)]
+struct Entries(HashMap<String, String>);
+
+impl Entries {
+    /// Where to write content `hash` wanted at `path`: there, or beside it
+    /// with the hash in its name if other content took it. `None` if the
+    /// same content is already there.
+    fn claim(&mut self, path: String, hash: &str) -> Option<String> {
+        match self.0.get(&path) {
+            None => {
+                self.0.insert(path.clone(), hash.to_string());
+                Some(path)
+            }
+            Some(existing) if existing == hash => None,
+            Some(_) => {
+                let short = &hash[..hash.len().min(8)];
+                let name_start = path.rfind('/').map_or(0, |i| i + 1);
+                let alternative = match path[name_start..].rfind('.') {
+                    Some(dot) => {
+                        let (stem, ext) = path.split_at(name_start + dot);
+                        format!("{}~{}{}", stem, short, ext)
+                    }
+                    None => format!("{}~{}", path, short),
+                };
+                self.claim(alternative, hash)
+            }
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut padded = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        padded[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        padded[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(padded.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(padded.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// AWS Signature Version 4 signing key.
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> [u8; 32] {
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// URI-encode per SigV4: everything but unreserved characters (and `/`
+/// in object keys).
+fn aws_uri_encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// An S3 request to be signed.
+struct S3Request<'r> {
+    method: &'r str,
+    host: &'r str,
+    /// URI-encoded path.
+    path: &'r str,
+    payload_hash: &'r str,
+    /// `YYYYMMDDTHHMMSSZ`
+    amz_date: &'r str,
+}
+
+impl S3Request<'_> {
+    const SIGNED_HEADERS: &'static str = "host;x-amz-content-sha256;x-amz-date";
+
+    /// `Authorization` header value.
+    fn authorization(&self, access_key: &str, secret_key: &str, region: &str) -> String {
+        let date = &self.amz_date[..8];
+        let canonical = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            self.method,
+            self.path,
+            self.host,
+            self.payload_hash,
+            self.amz_date,
+            Self::SIGNED_HEADERS,
+            self.payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            self.amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical.as_bytes()))
+        );
+        let key = signing_key(secret_key, date, region, "s3");
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            access_key,
+            scope,
+            Self::SIGNED_HEADERS,
+            hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()))
+        )
+    }
+}
+
+/// Upload an object with a path-style, SigV4-signed `PUT`.
+async fn put_s3(
+    client: &reqwest::Client,
+    s3: &S3Config,
+    bucket: &str,
+    key: &str,
+    body: Vec<u8>,
+) -> Result<(), ExportError> {
+    let (Some(access_key), Some(secret_key)) = (&s3.access_key, &s3.secret_key) else {
+        return Err(ExportError::Upload(
+            "s3.access_key and s3.secret_key are required".to_string(),
+        ));
+    };
+    let access_key = secrets::resolve(access_key)?;
+    let secret_key = secrets::resolve(secret_key)?;
+    let region = s3.region.as_deref().unwrap_or(DEFAULT_S3_REGION);
+    let endpoint = match s3.endpoint {
+        Some(ref endpoint) => endpoint.trim_end_matches('/').to_string(),
+        None => format!("https://s3.{}.amazonaws.com", region),
+    };
+
+    let url = url::Url::parse(&format!(
+        "{}/{}/{}",
+        endpoint,
+        aws_uri_encode(bucket, false),
+        aws_uri_encode(key, true)
+    ))
+    .map_err(|e| ExportError::Upload(format!("invalid S3 endpoint: {}", e)))?;
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(ExportError::Upload("S3 endpoint has no host".to_string())),
+    };
+    let payload_hash = hex::encode(Sha256::digest(&body));
+    let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let authorization = S3Request {
+        method: "PUT",
+        host: &host,
+        path: url.path(),
+        payload_hash: &payload_hash,
+        amz_date: &amz_date,
+    }
+    .authorization(&access_key, &secret_key, region);
+
+    let response = client
+        .put(url.clone())
+        .header("x-amz-date", &amz_date)
+        .header("x-amz-content-sha256", &payload_hash)
+        .header("authorization", authorization)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| ExportError::Upload(e.to_string()))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(ExportError::Upload(format!(
+            "S3 returned {}: {}",
+            status,
+            text.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Upload a file with the `sftp` client in batch mode, returning the
+/// remote path. Authentication uses the user's SSH keys and config.
+async fn put_sftp(host: &str, dir: &str, local: &Path, name: &str) -> Result<String, ExportError> {
+    // `/~/path` is relative to the login directory
+    let dir = match dir.strip_prefix("/~") {
+        Some(rest) => rest.trim_start_matches('/'),
+        None => dir,
+    };
+    let remote = if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    };
+    let mut batch = String::new();
+    if !dir.is_empty() {
+        // Leading '-' ignores the error when the directory exists
+        batch.push_str(&format!("-mkdir \"{}\"\n", dir));
+    }
+    batch.push_str(&format!(
+        "put \"{}\" \"{}.part\"\nrename \"{}.part\" \"{}\"\n",
+        local.display(),
+        remote,
+        remote,
+        remote
+    ));
+
+    let batch_file = local.with_extension("sftp");
+    tokio::fs::write(&batch_file, batch).await?;
+    let output = tokio::process::Command::new("sftp")
+        .arg("-b")
+        .arg(&batch_file)
+        .arg(host)
+        .output()
+        .await
+        .map_err(|e| ExportError::Upload(format!("could not run sftp: {}", e)))?;
+    if !output.status.success() {
+        return Err(ExportError::Upload(format!(
+            "sftp exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(remote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(format: &str, schedule: &str, destination: &str) -> ExportJobConfig {
+        ExportJobConfig {
+            format: format.to_string(),
+            schedule: schedule.to_string(),
+            destination: destination.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_destination_parse() {
+        assert_eq!(
+            Destination::parse("s3://archive/foia/daily/"),
+            Some(Destination::S3 {
+                bucket: "archive".to_string(),
+                prefix: "foia/daily".to_string()
+            })
+        );
+        assert_eq!(
+            Destination::parse("sftp://etl@data.example.org/srv/drop"),
+            Some(Destination::Sftp {
+                host: "etl@data.example.org".to_string(),
+                path: "/srv/drop".to_string()
+            })
+        );
+        assert_eq!(
+            Destination::parse("/mnt/exports"),
+            Some(Destination::Local(PathBuf::from("/mnt/exports")))
+        );
+        assert!(Destination::parse("s3://").is_none());
+
+        for s in [
+            "s3://archive/foia",
+            "sftp://etl@host/~/drop",
+            "/mnt/exports",
+        ] {
+            assert_eq!(Destination::parse(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_job_from_config() {
+        let daily = ExportJob::from_config("daily", &job("jsonl", "daily", "/tmp/x")).unwrap();
+        assert_eq!(daily.format, ExportFormat::Jsonl);
+        assert_eq!(daily.schedule, Schedule::Daily);
+        assert!(ExportJob::from_config("x", &job("parquet", "daily", "/tmp/x")).is_err());
+        assert!(ExportJob::from_config("x", &job("warc", "monthly", "/tmp/x")).is_err());
+
+        let started = "2024-03-01T02:00:00Z".parse().unwrap();
+        assert_eq!(daily.artifact_name(started), "daily-20240301T020000Z.jsonl");
+
+        let mut bag = job("bag", "daily", "/tmp/x");
+        bag.layout = Some("bates".to_string());
+        assert_eq!(
+            ExportJob::from_config("b", &bag).unwrap().layout,
+            ExportLayout::Bates
+        );
+        bag.format = "warc".to_string();
+        assert!(ExportJob::from_config("b", &bag).is_err());
+    }
+
+    #[test]
+    fn test_normalize_original_path() {
+        assert_eq!(
+            normalize_original_path(r"IMAGES\001\ABC000001.tif").as_deref(),
+            Some("IMAGES/001/ABC000001.tif")
+        );
+        assert_eq!(
+            normalize_original_path(r"D:\VOL001\.\NATIVES\a.xlsx").as_deref(),
+            Some("VOL001/NATIVES/a.xlsx")
+        );
+        assert_eq!(
+            normalize_original_path("/abs/./x.pdf").as_deref(),
+            Some("abs/x.pdf")
+        );
+        assert_eq!(normalize_original_path("IMAGES/../../etc/passwd"), None);
+        assert_eq!(normalize_original_path(r"\\"), None);
+    }
+
+    #[test]
+    fn test_entries_claim() {
+        let mut entries = Entries::default();
+        let path = "IMAGES/001/ABC1.tif".to_string();
+        assert_eq!(
+            entries.claim(path.clone(), "aaaaaaaaaa").as_deref(),
+            Some("IMAGES/001/ABC1.tif")
+        );
+        // Same content again is written once
+        assert_eq!(entries.claim(path.clone(), "aaaaaaaaaa"), None);
+        // Other content at the same path goes beside it
+        assert_eq!(
+            entries.claim(path, "bbbbbbbbbb").as_deref(),
+            Some("IMAGES/001/ABC1~bbbbbbbb.tif")
+        );
+        assert_eq!(
+            entries.claim("v1.2/README".to_string(), "cc").as_deref(),
+            Some("v1.2/README")
+        );
+        assert_eq!(
+            entries.claim("v1.2/README".to_string(), "dd").as_deref(),
+            Some("v1.2/README~dd")
+        );
+        assert_eq!(bates_stem("ABC-0001", "ABC-0003"), "ABC-0001_ABC-0003");
+        assert_eq!(bates_stem("A/1", "A/1"), "A_1");
+    }
+
+    #[test]
+    fn test_is_due() {
+        let weekly = ExportJob::from_config("w", &job("bag", "weekly", "/tmp/x")).unwrap();
+        let now: DateTime<Utc> = "2024-03-10T00:00:00Z".parse().unwrap();
+
+        assert!(weekly.is_due(None, None, now));
+        assert!(!weekly.is_due(Some(now - Duration::days(6)), None, now));
+        assert!(weekly.is_due(Some(now - Duration::days(7)), None, now));
+        // A failure is retried an hour later
+        let last = Some(now - Duration::days(8));
+        assert!(!weekly.is_due(last, Some(now - Duration::minutes(30)), now));
+        assert!(weekly.is_due(last, Some(now - Duration::minutes(90)), now));
+    }
+
+    #[test]
+    fn test_warc_record() {
+        let mut out = Vec::new();
+        write_warc_record(&mut out, &[("WARC-Type", "resource".to_string())], b"hello").unwrap();
+        assert_eq!(
+            out,
+            b"WARC/1.1\r\nWARC-Type: resource\r\nContent-Length: 5\r\n\r\nhello\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+        assert_eq!(
+            aws_uri_encode("exports/daily 2024.jsonl", true),
+            "exports/daily%202024.jsonl"
+        );
+        assert_eq!(aws_uri_encode("a/b", false), "a%2Fb");
+    }
+}
@@ -0,0 +1,141 @@
+//! Link-level comparison of listing page snapshots.
+//!
+//! Reading-room indexes are mostly navigation chrome around a list of links,
+//! so the useful question between two crawls is which links appeared and
+//! which disappeared, not which bytes of markup moved.
+
+use std::collections::{BTreeMap, HashSet};
+
+use scraper::{Html, Selector};
+use serde::Serialize;
+use url::Url;
+
+/// A link found on a listing page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListingLink {
+    /// Absolute link target, without fragment.
+    pub url: String,
+    /// Visible link text, whitespace-collapsed.
+    pub text: String,
+}
+
+/// Links added and removed between two snapshots of the same page.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListingDiff {
+    pub added: Vec<ListingLink>,
+    pub removed: Vec<ListingLink>,
+    /// Links present in both snapshots.
+    pub unchanged: usize,
+}
+
+impl ListingDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Extract the distinct links on a page, in document order.
+pub fn extract_links(html: &str, page_url: &str) -> Vec<ListingLink> {
+    let base = Url::parse(page_url).ok();
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").expect("valid selector");
+
+    let mut seen = HashSet::new();
+    let mut links = Vec::new();
+    for element in document.select(&selector) {
+        let Some(href) = element.value().attr("href") else {
+            continue;
+        };
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            continue;
+        }
+        let mut url = match base.as_ref().map(|b| b.join(href)) {
+            Some(Ok(url)) => url,
+            _ => match Url::parse(href) {
+                Ok(url) => url,
+                Err(_) => continue,
+            },
+        };
+        url.set_fragment(None);
+        let url = url.to_string();
+        if !seen.insert(url.clone()) {
+            continue;
+        }
+        let text = element.text().collect::<Vec<_>>().join(" ");
+        links.push(ListingLink {
+            url,
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+        });
+    }
+    links
+}
+
+/// Compare two snapshots of the same listing page by their links.
+pub fn diff_listings(old_html: &str, new_html: &str, page_url: &str) -> ListingDiff {
+    let old: BTreeMap<String, ListingLink> = extract_links(old_html, page_url)
+        .into_iter()
+        .map(|l| (l.url.clone(), l))
+        .collect();
+    let new = extract_links(new_html, page_url);
+    let new_urls: HashSet<&str> = new.iter().map(|l| l.url.as_str()).collect();
+
+    let mut diff = ListingDiff::default();
+    for link in &new {
+        if old.contains_key(&link.url) {
+            diff.unchanged += 1;
+        } else {
+            diff.added.push(link.clone());
+        }
+    }
+    diff.removed = old
+        .into_values()
+        .filter(|l| !new_urls.contains(l.url.as_str()))
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "https://agency.gov/foia/reading-room";
+
+    #[test]
+    fn test_extract_links_resolves_and_dedupes() {
+        let html = r##"
+            <a href="/docs/a.pdf">Report  A</a>
+            <a href="/docs/a.pdf#page=2">Report A again</a>
+            <a href="#top">Top</a>
+            <a href="javascript:void(0)">Menu</a>
+            <a href="b.pdf">B</a>
+        "##;
+        let links = extract_links(html, PAGE);
+        assert_eq!(
+            links,
+            vec![
+                ListingLink {
+                    url: "https://agency.gov/docs/a.pdf".to_string(),
+                    text: "Report A".to_string(),
+                },
+                ListingLink {
+                    url: "https://agency.gov/foia/b.pdf".to_string(),
+                    text: "B".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_listings() {
+        let old = r#"<a href="/a.pdf">A</a><a href="/b.pdf">B</a>"#;
+        let new = r#"<nav>changed chrome</nav><a href="/b.pdf">B</a><a href="/c.pdf">C</a>"#;
+        let diff = diff_listings(old, new, PAGE);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].url, "https://agency.gov/c.pdf");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].url, "https://agency.gov/a.pdf");
+        assert!(diff_listings(old, old, PAGE).is_empty());
+    }
+}
@@ -3,5 +3,33 @@
 //! This module contains domain logic separated from UI concerns.
 //! Services can be used by CLI, web server, or other interfaces.
 
+pub mod access;
+pub mod acquire;
+pub mod aliases;
+pub mod api_keys;
+pub mod bates;
+pub mod bundles;
+pub mod config_history;
+pub mod content_guard;
+pub mod custody;
+pub mod duckdb_export;
+pub mod exemptions;
+pub mod exports;
 #[cfg(feature = "gis")]
 pub mod geolookup;
+pub mod listing_diff;
+pub mod overlap;
+pub mod page_text;
+pub mod pipeline_eta;
+pub mod politeness;
+pub mod report;
+pub mod retention;
+pub mod schema_drift;
+pub mod search;
+pub mod sections;
+pub mod storage_report;
+pub mod sync;
+pub mod takedowns;
+pub mod titles;
+pub mod transcription;
+pub mod uploads;
@@ -0,0 +1,356 @@
+//! Overlap between two productions of the same records.
+//!
+//! Agencies often release the same records to several requesters, with
+//! different redactions, scans or bundling. Comparing two sources (or two
+//! tagged collections) shows which documents both sides share byte for byte,
+//! which share nearly the same text, and which only one side received.
+//!
+//! Near-duplicate text is found with MinHash signatures over word shingles;
+//! locality-sensitive banding keeps the comparison from being quadratic.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use futures::StreamExt;
+use serde::Serialize;
+
+use crate::repository::diesel_document::{StreamFilter, DEFAULT_STREAM_BATCH};
+use crate::repository::{DieselDocumentRepository, DieselError};
+
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 5;
+/// MinHash values per signature.
+const SIGNATURE_LEN: usize = 64;
+/// LSH bands; `SIGNATURE_LEN / BANDS` values per band.
+const BANDS: usize = 16;
+
+/// Default similarity above which two texts are near-duplicates.
+pub const DEFAULT_THRESHOLD: f32 = 0.8;
+
+/// MinHash signature of a text's word shingles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature([u64; SIGNATURE_LEN]);
+
+impl Signature {
+    /// Signature of `text`, or `None` when it has too few words to compare.
+    pub fn of(text: &str) -> Option<Self> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if words.len() < SHINGLE_WORDS {
+            return None;
+        }
+
+        let mut mins = [u64::MAX; SIGNATURE_LEN];
+        for shingle in words.windows(SHINGLE_WORDS) {
+            let mut hasher = DefaultHasher::new();
+            shingle.hash(&mut hasher);
+            let base = hasher.finish();
+            for (i, min) in mins.iter_mut().enumerate() {
+                let h = mix(base ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                if h < *min {
+                    *min = h;
+                }
+            }
+        }
+        Some(Self(mins))
+    }
+
+    /// Estimated Jaccard similarity of the two texts' shingle sets.
+    pub fn similarity(&self, other: &Self) -> f32 {
+        let same = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        same as f32 / SIGNATURE_LEN as f32
+    }
+
+    fn bands(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.0
+            .chunks(SIGNATURE_LEN / BANDS)
+            .enumerate()
+            .map(|(band, rows)| {
+                let mut hasher = DefaultHasher::new();
+                rows.hash(&mut hasher);
+                (band, hasher.finish())
+            })
+    }
+}
+
+/// splitmix64 finalizer, to derive independent hash functions from one.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// What is compared of one document.
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    pub document_id: String,
+    pub title: String,
+    /// SHA-256 of the current version's file.
+    pub content_hash: Option<String>,
+    pub signature: Option<Signature>,
+}
+
+/// A document in a comparison report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocRef {
+    pub document_id: String,
+    pub title: String,
+}
+
+impl From<&Fingerprint> for DocRef {
+    fn from(f: &Fingerprint) -> Self {
+        Self {
+            document_id: f.document_id.clone(),
+            title: f.title.clone(),
+        }
+    }
+}
+
+/// A pair of documents, one from each side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Match {
+    pub left: DocRef,
+    pub right: DocRef,
+    /// 1.0 for identical files; estimated text similarity otherwise.
+    pub similarity: f32,
+}
+
+/// Overlap between two sets of documents.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OverlapReport {
+    pub left_total: usize,
+    pub right_total: usize,
+    /// Identical files on both sides.
+    pub exact: Vec<Match>,
+    /// Different files with nearly the same text.
+    pub near: Vec<Match>,
+    /// Documents with no counterpart on the right.
+    pub only_left: Vec<DocRef>,
+    /// Documents with no counterpart on the left.
+    pub only_right: Vec<DocRef>,
+}
+
+/// Compare two sets of documents.
+///
+/// Files are matched by hash first; the rest are paired with their most
+/// similar unmatched counterpart at or above `threshold`.
+pub fn compare(left: &[Fingerprint], right: &[Fingerprint], threshold: f32) -> OverlapReport {
+    let mut report = OverlapReport {
+        left_total: left.len(),
+        right_total: right.len(),
+        ..Default::default()
+    };
+    let mut matched_left = HashSet::new();
+    let mut matched_right = HashSet::new();
+
+    let mut right_by_hash: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, r) in right.iter().enumerate() {
+        if let Some(hash) = r.content_hash.as_deref() {
+            right_by_hash.entry(hash).or_default().push(i);
+        }
+    }
+    for (i, l) in left.iter().enumerate() {
+        let Some(candidates) = l.content_hash.as_deref().and_then(|h| right_by_hash.get(h)) else {
+            continue;
+        };
+        if let Some(&j) = candidates.iter().find(|j| !matched_right.contains(*j)) {
+            matched_left.insert(i);
+            matched_right.insert(j);
+            report.exact.push(Match {
+                left: l.into(),
+                right: (&right[j]).into(),
+                similarity: 1.0,
+            });
+        }
+    }
+
+    // Band buckets of the right side's unmatched signatures
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (j, r) in right.iter().enumerate() {
+        if matched_right.contains(&j) {
+            continue;
+        }
+        if let Some(sig) = &r.signature {
+            for key in sig.bands() {
+                buckets.entry(key).or_default().push(j);
+            }
+        }
+    }
+
+    let mut pairs: Vec<(f32, usize, usize)> = Vec::new();
+    for (i, l) in left.iter().enumerate() {
+        if matched_left.contains(&i) {
+            continue;
+        }
+        let Some(sig) = &l.signature else {
+            continue;
+        };
+        let candidates: HashSet<usize> = sig
+            .bands()
+            .filter_map(|key| buckets.get(&key))
+            .flatten()
+            .copied()
+            .collect();
+        for j in candidates {
+            if let Some(other) = &right[j].signature {
+                let similarity = sig.similarity(other);
+                if similarity >= threshold {
+                    pairs.push((similarity, i, j));
+                }
+            }
+        }
+    }
+    // Greedy best-first pairing
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    for (similarity, i, j) in pairs {
+        if matched_left.contains(&i) || matched_right.contains(&j) {
+            continue;
+        }
+        matched_left.insert(i);
+        matched_right.insert(j);
+        report.near.push(Match {
+            left: (&left[i]).into(),
+            right: (&right[j]).into(),
+            similarity,
+        });
+    }
+
+    report.only_left = left
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matched_left.contains(i))
+        .map(|(_, f)| f.into())
+        .collect();
+    report.only_right = right
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| !matched_right.contains(j))
+        .map(|(_, f)| f.into())
+        .collect();
+    report
+}
+
+/// One side of a comparison: a source, or documents with a tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Production {
+    Source(String),
+    Tag(String),
+}
+
+impl Production {
+    /// Parse `<source_id>` or `tag:<name>`.
+    pub fn parse(s: &str) -> Self {
+        match s.strip_prefix("tag:") {
+            Some(tag) => Self::Tag(tag.to_string()),
+            None => Self::Source(s.to_string()),
+        }
+    }
+
+    fn filter(&self) -> StreamFilter {
+        match self {
+            Self::Source(id) => StreamFilter::source(Some(id)),
+            Self::Tag(tag) => StreamFilter {
+                tags: vec![tag.clone()],
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Fingerprint every document of a production.
+pub async fn fingerprints(
+    repo: &DieselDocumentRepository,
+    production: &Production,
+) -> Result<Vec<Fingerprint>, DieselError> {
+    let mut stream = repo.stream_documents(production.filter(), DEFAULT_STREAM_BATCH);
+    let mut prints = Vec::new();
+    while let Some(doc) = stream.next().await {
+        let doc = doc?;
+        prints.push(Fingerprint {
+            content_hash: doc.current_version().map(|v| v.content_hash.clone()),
+            signature: doc.extracted_text.as_deref().and_then(Signature::of),
+            document_id: doc.id,
+            title: doc.title,
+        });
+    }
+    Ok(prints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMO: &str = "The director met with the committee on March 3 to discuss the \
+                        budget request for the coming fiscal year and agreed to provide \
+                        the supporting documents by the end of the month.";
+    const UNRELATED: &str = "An unrelated letter about road repairs on Main Street and the \
+                             schedule for resurfacing the parking lot next summer.";
+
+    fn print(id: &str, hash: &str, text: &str) -> Fingerprint {
+        Fingerprint {
+            document_id: id.to_string(),
+            title: id.to_string(),
+            content_hash: Some(hash.to_string()),
+            signature: Signature::of(text),
+        }
+    }
+
+    #[test]
+    fn test_signature_similarity() {
+        let a = Signature::of(MEMO).unwrap();
+        let rescanned = MEMO.replace("March 3", "March 3,").to_uppercase();
+        assert_eq!(a.similarity(&Signature::of(&rescanned).unwrap()), 1.0);
+
+        let redacted = MEMO.replace("the committee", "[REDACTED]");
+        let s = a.similarity(&Signature::of(&redacted).unwrap());
+        assert!(s > 0.4 && s < 1.0, "similarity {}", s);
+
+        let other = Signature::of(UNRELATED).unwrap();
+        assert!(a.similarity(&other) < 0.2);
+        assert!(Signature::of("too short").is_none());
+    }
+
+    #[test]
+    fn test_compare_productions() {
+        let rescan = MEMO.replace("fiscal year", "fiscal year,");
+        let left = vec![
+            print(
+                "l1",
+                "h1",
+                "Identical file contents with enough words in it",
+            ),
+            print("l2", "h2", MEMO),
+            print(
+                "l3",
+                "h3",
+                "A document only the first requester received in full",
+            ),
+        ];
+        let right = vec![
+            print(
+                "r1",
+                "h1",
+                "Identical file contents with enough words in it",
+            ),
+            print("r2", "h9", &rescan),
+            print(
+                "r3",
+                "h8",
+                "Something released only to the second requester today",
+            ),
+        ];
+
+        let report = compare(&left, &right, DEFAULT_THRESHOLD);
+        assert_eq!(report.exact.len(), 1);
+        assert_eq!(report.exact[0].right.document_id, "r1");
+        assert_eq!(report.near.len(), 1);
+        assert_eq!(report.near[0].left.document_id, "l2");
+        assert_eq!(report.near[0].right.document_id, "r2");
+        assert_eq!(report.only_left[0].document_id, "l3");
+        assert_eq!(report.only_right[0].document_id, "r3");
+    }
+}
@@ -0,0 +1,162 @@
+//! Corrections of page text by curators.
+//!
+//! A curator replaces the final text of a page, or confirms it as is. The
+//! edit is kept as a revision with the text it replaced and a line diff,
+//! and the page counts as human-verified from then on: OCR and arbitration
+//! no longer change its text. The document's combined text is rebuilt so
+//! search indexes pick the correction up on their next update.
+
+use chrono::Utc;
+
+use crate::models::{DocumentPage, PageTextDecision, PageTextRevision};
+use crate::repository::diesel_document::Projection;
+use crate::repository::DieselDocumentRepository;
+
+/// Arbitration method recorded for pages whose text a curator set.
+pub const HUMAN_METHOD: &str = "human";
+
+/// Longest accepted page text, in characters.
+pub const MAX_PAGE_CHARS: usize = 200_000;
+
+/// Above this many line pairs the diff gives up aligning lines and shows
+/// the whole text as replaced.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Page text as stored: Unix line endings, no trailing whitespace.
+pub fn normalize_text(text: &str) -> String {
+    text.replace("\r\n", "\n").trim_end().to_string()
+}
+
+/// The text a page currently shows: its final text, or failing that its
+/// OCR text or text layer.
+pub fn current_text(page: &DocumentPage) -> Option<&str> {
+    page.final_text
+        .as_deref()
+        .or(page.ocr_text.as_deref())
+        .or(page.pdf_text.as_deref())
+}
+
+/// Changed lines between `old` and `new`: each run of changes starts with
+/// `@@ -old_line +new_line @@`, then `-` removed and `+` added lines.
+/// Empty when nothing changed.
+pub fn line_diff(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence of lines, from the end
+    let (n, m) = (a.len(), b.len());
+    let cells = if n * m > MAX_DIFF_CELLS {
+        0
+    } else {
+        (n + 1) * (m + 1)
+    };
+    let mut lcs = vec![0u32; cells];
+    if !lcs.is_empty() {
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * (m + 1) + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+                };
+            }
+        }
+    }
+    let common = |i: usize, j: usize| lcs.get(i * (m + 1) + j).copied().unwrap_or(0);
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    let mut in_hunk = false;
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] && !lcs.is_empty() {
+            i += 1;
+            j += 1;
+            in_hunk = false;
+            continue;
+        }
+        if !in_hunk {
+            out.push_str(&format!("@@ -{} +{} @@\n", i + 1, j + 1));
+            in_hunk = true;
+        }
+        if j >= m || (i < n && common(i + 1, j) >= common(i, j + 1)) {
+            out.push_str(&format!("-{}\n", a[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+{}\n", b[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Set a page's text to a curator's `text`, or confirm its current text
+/// when it is unchanged, and record the revision.
+pub async fn edit_page_text(
+    doc_repo: &DieselDocumentRepository,
+    page: &DocumentPage,
+    text: &str,
+    editor: &str,
+) -> anyhow::Result<PageTextRevision> {
+    let editor = editor.trim();
+    if editor.is_empty() {
+        anyhow::bail!("editor is required");
+    }
+    let text = normalize_text(text);
+    if text.chars().count() > MAX_PAGE_CHARS {
+        anyhow::bail!("text is longer than {} characters", MAX_PAGE_CHARS);
+    }
+
+    let previous = current_text(page).map(normalize_text);
+    let diff = line_diff(previous.as_deref().unwrap_or_default(), &text);
+    let mut edited = page.clone();
+    edited.final_text = Some(text);
+    let revision = doc_repo
+        .save_page_text_revision(&edited, editor, previous.as_deref(), &diff)
+        .await?;
+
+    doc_repo
+        .record_page_text_decision(&PageTextDecision {
+            page_id: page.id,
+            method: HUMAN_METHOD.to_string(),
+            backend: None,
+            scores: Vec::new(),
+            rationale: if diff.is_empty() {
+                format!("Confirmed by {}", editor)
+            } else {
+                format!("Corrected by {}", editor)
+            },
+            decided_at: Utc::now(),
+        })
+        .await?;
+    // The document's own text belongs to its current version
+    let current = doc_repo
+        .get_projected(&page.document_id, Projection::Metadata)
+        .await?
+        .and_then(|doc| doc.current_version().map(|v| v.id));
+    if current == Some(page.version_id) {
+        doc_repo
+            .refresh_text_from_pages(&page.document_id, page.version_id as i32)
+            .await?;
+    }
+    Ok(revision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb\nc", "a\nb\nc"), "");
+        assert_eq!(
+            line_diff(
+                "Tbe quick\nbrown fox\njumps",
+                "The quick\nbrown fox\njumps over"
+            ),
+            "@@ -1 +1 @@\n-Tbe quick\n+The quick\n@@ -3 +3 @@\n-jumps\n+jumps over\n"
+        );
+        assert_eq!(line_diff("", "new"), "@@ -1 +1 @@\n+new\n");
+        assert_eq!(line_diff("a\nb", "b"), "@@ -1 +1 @@\n-a\n");
+        assert_eq!(normalize_text("one\r\ntwo  \n\n"), "one\ntwo");
+    }
+}
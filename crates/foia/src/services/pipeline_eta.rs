@@ -0,0 +1,215 @@
+//! Estimated time to clear each pipeline stage's backlog.
+//!
+//! Throughput comes from completion timestamps already in the database
+//! (URLs fetched, OCR results stored, summaries written) over a recent
+//! window, so every process reports the same rate without tracking
+//! anything itself. Estimates assume a stage keeps its recent pace; a
+//! stage that completed nothing in the window has no estimate.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::repository::{DieselCrawlRepository, DieselDocumentRepository, DieselError};
+
+/// How far back throughput is measured by default.
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(3600);
+
+/// A pipeline stage with a backlog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Download,
+    Ocr,
+    Summarize,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 3] = [Stage::Download, Stage::Ocr, Stage::Summarize];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Download => "download",
+            Self::Ocr => "ocr",
+            Self::Summarize => "summarize",
+        }
+    }
+
+    /// Name shown to people.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Download => "Download",
+            Self::Ocr => "OCR",
+            Self::Summarize => "Summarize",
+        }
+    }
+}
+
+/// Backlog, recent throughput and estimated completion of one stage.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageEta {
+    pub stage: Stage,
+    /// Items waiting for this stage.
+    pub pending: u64,
+    /// Items the stage completed in the window.
+    pub completed: u64,
+    /// Length of the window, in seconds.
+    pub window_secs: u64,
+    /// Items completed per hour over the window.
+    pub per_hour: f64,
+    /// Seconds until the backlog clears at that rate; 0 with no backlog,
+    /// `None` if the stage made no progress in the window.
+    pub eta_secs: Option<u64>,
+    /// When the backlog clears at that rate.
+    pub completes_at: Option<DateTime<Utc>>,
+}
+
+impl StageEta {
+    pub fn new(
+        stage: Stage,
+        pending: u64,
+        completed: u64,
+        window: Duration,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let window_secs = window.as_secs().max(1);
+        let per_sec = completed as f64 / window_secs as f64;
+        let eta_secs = if pending == 0 {
+            Some(0)
+        } else if completed == 0 {
+            None
+        } else {
+            Some((pending as f64 / per_sec).ceil() as u64)
+        };
+        let completes_at = eta_secs
+            .and_then(|secs| chrono::Duration::try_seconds(secs as i64))
+            .map(|eta| now + eta);
+        Self {
+            stage,
+            pending,
+            completed,
+            window_secs,
+            per_hour: per_sec * 3600.0,
+            eta_secs,
+            completes_at,
+        }
+    }
+}
+
+/// Estimate every stage, for one source or all of them.
+pub async fn estimate(
+    docs: &DieselDocumentRepository,
+    crawl: &DieselCrawlRepository,
+    source_id: Option<&str>,
+    window: Duration,
+) -> Result<Vec<StageEta>, DieselError> {
+    let now = Utc::now();
+    let since = now - chrono::Duration::from_std(window).unwrap_or(chrono::Duration::hours(1));
+
+    let mut stages = Vec::with_capacity(Stage::ALL.len());
+    for stage in Stage::ALL {
+        let (pending, completed) = match stage {
+            Stage::Download => {
+                let pending = match source_id {
+                    Some(sid) => crawl.get_crawl_state(sid).await?.urls_pending,
+                    None => crawl.count_pending_downloads().await? as u64,
+                };
+                (pending, crawl.count_fetched_since(source_id, since).await?)
+            }
+            Stage::Ocr => (
+                docs.count_needing_ocr(source_id).await?,
+                docs.count_analyzed_since("ocr", source_id, since).await?,
+            ),
+            Stage::Summarize => (
+                docs.count_needing_summarization(source_id, &[]).await?,
+                docs.count_summarized_since(source_id, since).await?,
+            ),
+        };
+        stages.push(StageEta::new(stage, pending, completed, window, now));
+    }
+    Ok(stages)
+}
+
+/// Render estimates in the Prometheus text exposition format. Stages
+/// without an estimate have no `foia_stage_eta_seconds` sample.
+pub fn to_prometheus(stages: &[StageEta]) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, value: &dyn Fn(&StageEta) -> Option<f64>| {
+        out.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for stage in stages {
+            if let Some(value) = value(stage) {
+                out.push_str(&format!(
+                    "{}{{stage=\"{}\"}} {}\n",
+                    name,
+                    stage.stage.as_str(),
+                    value
+                ));
+            }
+        }
+    };
+    gauge(
+        "foia_stage_pending",
+        "Items waiting for the pipeline stage.",
+        &|s| Some(s.pending as f64),
+    );
+    gauge(
+        "foia_stage_throughput_per_hour",
+        "Items the stage completed per hour over the recent window.",
+        &|s| Some(s.per_hour),
+    );
+    gauge(
+        "foia_stage_eta_seconds",
+        "Estimated seconds until the stage's backlog clears.",
+        &|s| s.eta_secs.map(|secs| secs as f64),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_eta() {
+        let now = Utc::now();
+        let hour = Duration::from_secs(3600);
+
+        // 120 an hour clears 300 in two and a half hours
+        let eta = StageEta::new(Stage::Ocr, 300, 120, hour, now);
+        assert_eq!(eta.per_hour, 120.0);
+        assert_eq!(eta.eta_secs, Some(9000));
+        assert_eq!(
+            eta.completes_at,
+            Some(now + chrono::Duration::seconds(9000))
+        );
+
+        // Nothing left to do
+        let eta = StageEta::new(Stage::Download, 0, 0, hour, now);
+        assert_eq!(eta.eta_secs, Some(0));
+
+        // A backlog with no recent progress has no estimate
+        let eta = StageEta::new(Stage::Summarize, 50, 0, hour, now);
+        assert_eq!(eta.eta_secs, None);
+        assert_eq!(eta.completes_at, None);
+    }
+
+    #[test]
+    fn test_to_prometheus() {
+        let now = Utc::now();
+        let hour = Duration::from_secs(3600);
+        let metrics = to_prometheus(&[
+            StageEta::new(Stage::Download, 30, 60, hour, now),
+            StageEta::new(Stage::Ocr, 5, 0, hour, now),
+        ]);
+        assert!(metrics.contains("# TYPE foia_stage_pending gauge\n"));
+        assert!(metrics.contains("foia_stage_pending{stage=\"ocr\"} 5\n"));
+        assert!(metrics.contains("foia_stage_throughput_per_hour{stage=\"download\"} 60\n"));
+        assert!(metrics.contains("foia_stage_eta_seconds{stage=\"download\"} 1800\n"));
+        // A stalled stage has no estimate
+        assert!(!metrics.contains("foia_stage_eta_seconds{stage=\"ocr\"}"));
+    }
+}
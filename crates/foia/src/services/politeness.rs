@@ -0,0 +1,163 @@
+//! Crawl politeness reports.
+//!
+//! Summarizes the traffic we sent to a single domain, from the audit trail in
+//! `crawl_requests`, so operators can show a records office exactly what was
+//! requested, how often, and what came back.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::models::CrawlRequest;
+
+/// Column header for the per-request CSV export.
+const CSV_HEADER: &str = "request_at,source_id,method,url,response_status,response_size,duration_ms,was_conditional,was_not_modified,identified,error\n";
+
+/// Aggregate figures for a set of requests to one domain.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PolitenessTotals {
+    pub requests: u64,
+    pub bytes: u64,
+    /// Requests that got no response at all (connection errors, timeouts).
+    pub failed: u64,
+    /// Requests sent with transparency mode's identification headers.
+    pub identified: u64,
+    /// Response counts keyed by HTTP status, for 4xx and 5xx responses.
+    pub error_statuses: BTreeMap<u16, u64>,
+    pub first_request: Option<DateTime<Utc>>,
+    pub last_request: Option<DateTime<Utc>>,
+    /// Mean gap between consecutive requests, in seconds.
+    pub avg_interval_secs: Option<f64>,
+    /// Smallest gap between consecutive requests, in seconds.
+    pub min_interval_secs: Option<f64>,
+}
+
+impl PolitenessTotals {
+    /// Compute totals from requests sorted oldest first.
+    pub fn from_requests(requests: &[CrawlRequest]) -> Self {
+        let mut totals = Self {
+            requests: requests.len() as u64,
+            first_request: requests.first().map(|r| r.request_at),
+            last_request: requests.last().map(|r| r.request_at),
+            ..Default::default()
+        };
+
+        for request in requests {
+            totals.bytes += request.response_size.unwrap_or(0);
+            if request.identified {
+                totals.identified += 1;
+            }
+            match request.response_status {
+                Some(status) if status >= 400 => {
+                    *totals.error_statuses.entry(status).or_insert(0) += 1;
+                }
+                Some(_) => {}
+                None => totals.failed += 1,
+            }
+        }
+
+        let gaps: Vec<f64> = requests
+            .windows(2)
+            .map(|pair| {
+                (pair[1].request_at - pair[0].request_at).num_milliseconds() as f64 / 1000.0
+            })
+            .collect();
+        if !gaps.is_empty() {
+            totals.avg_interval_secs = Some(gaps.iter().sum::<f64>() / gaps.len() as f64);
+            totals.min_interval_secs = gaps.iter().copied().reduce(f64::min);
+        }
+
+        totals
+    }
+}
+
+/// Render requests as CSV, one row per request.
+pub fn requests_to_csv(requests: &[CrawlRequest]) -> String {
+    let mut out = String::from(CSV_HEADER);
+    for request in requests {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{}\n",
+            request.request_at.to_rfc3339(),
+            escape_csv(&request.source_id),
+            escape_csv(&request.method),
+            escape_csv(&request.url),
+            request
+                .response_status
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            request
+                .response_size
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            request
+                .duration_ms
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            request.was_conditional,
+            request.was_not_modified,
+            request.identified,
+            escape_csv(request.error.as_deref().unwrap_or("")),
+        ));
+    }
+    out
+}
+
+pub(crate) fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn request(secs: i64, status: Option<u16>, size: Option<u64>) -> CrawlRequest {
+        let mut r = CrawlRequest::new(
+            "fbi".to_string(),
+            "https://vault.fbi.gov/a,b.pdf".to_string(),
+            "GET".to_string(),
+        );
+        r.request_at = Utc.timestamp_opt(1_700_000_000 + secs, 0).unwrap();
+        r.response_status = status;
+        r.response_size = size;
+        r
+    }
+
+    #[test]
+    fn test_totals() {
+        let requests = vec![
+            request(0, Some(200), Some(100)),
+            request(10, Some(404), Some(20)),
+            request(30, None, None),
+            request(60, Some(429), None),
+        ];
+        let totals = PolitenessTotals::from_requests(&requests);
+        assert_eq!(totals.requests, 4);
+        assert_eq!(totals.bytes, 120);
+        assert_eq!(totals.failed, 1);
+        assert_eq!(totals.error_statuses.get(&404), Some(&1));
+        assert_eq!(totals.error_statuses.get(&429), Some(&1));
+        assert_eq!(totals.avg_interval_secs, Some(20.0));
+        assert_eq!(totals.min_interval_secs, Some(10.0));
+    }
+
+    #[test]
+    fn test_totals_empty() {
+        let totals = PolitenessTotals::from_requests(&[]);
+        assert_eq!(totals.requests, 0);
+        assert!(totals.avg_interval_secs.is_none());
+    }
+
+    #[test]
+    fn test_csv_escapes_url() {
+        let csv = requests_to_csv(&[request(0, Some(200), Some(5))]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.contains("\"https://vault.fbi.gov/a,b.pdf\""));
+        assert!(row.ends_with(",200,5,,false,false,false,"));
+    }
+}
@@ -0,0 +1,398 @@
+//! Periodic archive reports for funders.
+//!
+//! A report covers one period: documents and bytes acquired, how each
+//! source grew, OCR coverage at the end of the period and the most notable
+//! new records. It renders to HTML through a template with `{{name}}`
+//! placeholders; OpenDocument and PDF are converted from that HTML by
+//! LibreOffice.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::repository::DieselDocumentRepository;
+
+/// Template used when none is given.
+pub const DEFAULT_TEMPLATE: &str = include_str!("report_template.html");
+
+/// Notable records listed by default.
+pub const DEFAULT_NOTABLE: u32 = 10;
+
+/// Output format of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Html,
+    Odt,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "html" => Some(Self::Html),
+            "odt" => Some(Self::Odt),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Odt => "odt",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// LibreOffice filter converting HTML to this format.
+    fn soffice_filter(&self) -> Option<&'static str> {
+        match self {
+            Self::Html => None,
+            Self::Odt => Some("odt:writer8"),
+            Self::Pdf => Some("pdf:writer_pdf_Export"),
+        }
+    }
+}
+
+/// How one source grew over the period.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SourceGrowth {
+    pub source_id: String,
+    /// Documents acquired before the period.
+    pub documents_before: u64,
+    /// Documents first acquired in the period.
+    pub documents_added: u64,
+    /// Versions acquired in the period, new documents' included.
+    pub versions_added: u64,
+    /// Bytes of the versions acquired in the period.
+    pub bytes_added: u64,
+    /// Pages of documents acquired by the end of the period.
+    pub pages: u64,
+    /// Of those, pages whose OCR is done or not needed.
+    pub ocr_pages: u64,
+}
+
+impl SourceGrowth {
+    pub fn documents_after(&self) -> u64 {
+        self.documents_before + self.documents_added
+    }
+
+    /// Percentage growth in documents; `None` for a source that is new.
+    pub fn growth_percent(&self) -> Option<f64> {
+        (self.documents_before > 0)
+            .then(|| self.documents_added as f64 * 100.0 / self.documents_before as f64)
+    }
+
+    /// Percentage of pages with OCR coverage; `None` without pages.
+    pub fn ocr_coverage(&self) -> Option<f64> {
+        (self.pages > 0).then(|| self.ocr_pages as f64 * 100.0 / self.pages as f64)
+    }
+}
+
+/// A document first acquired in the period worth calling out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NotableRecord {
+    pub id: String,
+    pub source_id: String,
+    pub title: String,
+    pub url: String,
+    pub synopsis: Option<String>,
+    pub acquired_at: String,
+    pub pages: u64,
+}
+
+/// Archive statistics for one period.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    /// Sources with documents by the end of the period, by ID.
+    pub sources: Vec<SourceGrowth>,
+    pub notable: Vec<NotableRecord>,
+}
+
+impl ArchiveReport {
+    /// Gather statistics for `[from, to)` from the database.
+    pub async fn build(
+        repo: &DieselDocumentRepository,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        notable: u32,
+    ) -> anyhow::Result<Self> {
+        if from >= to {
+            anyhow::bail!("Report period is empty: --from must be before --to");
+        }
+        Ok(Self {
+            from,
+            to,
+            generated_at: Utc::now(),
+            sources: repo.source_growth(from, to).await?,
+            notable: repo.notable_new_documents(from, to, notable).await?,
+        })
+    }
+
+    /// Totals over every source.
+    pub fn totals(&self) -> SourceGrowth {
+        let mut totals = SourceGrowth {
+            source_id: "total".to_string(),
+            ..Default::default()
+        };
+        for s in &self.sources {
+            totals.documents_before += s.documents_before;
+            totals.documents_added += s.documents_added;
+            totals.versions_added += s.versions_added;
+            totals.bytes_added += s.bytes_added;
+            totals.pages += s.pages;
+            totals.ocr_pages += s.ocr_pages;
+        }
+        totals
+    }
+
+    /// Render as HTML, through `template` or the default one.
+    ///
+    /// Placeholders: `{{title}}`, `{{from}}`, `{{to}}`, `{{generated_at}}`,
+    /// `{{documents_acquired}}`, `{{documents_total}}`, `{{versions_acquired}}`,
+    /// `{{bytes_acquired}}`, `{{ocr_coverage}}`, `{{sources_table}}` and
+    /// `{{notable_records}}`. Unknown placeholders are left as they are.
+    pub fn render_html(&self, title: &str, template: Option<&str>) -> String {
+        let totals = self.totals();
+        let values = [
+            ("title", escape(title)),
+            ("from", self.from.format("%Y-%m-%d").to_string()),
+            ("to", self.to.format("%Y-%m-%d").to_string()),
+            (
+                "generated_at",
+                self.generated_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            ),
+            ("documents_acquired", group_digits(totals.documents_added)),
+            ("documents_total", group_digits(totals.documents_after())),
+            ("versions_acquired", group_digits(totals.versions_added)),
+            ("bytes_acquired", human_bytes(totals.bytes_added)),
+            ("ocr_coverage", percent(totals.ocr_coverage())),
+            ("sources_table", self.sources_table(&totals)),
+            ("notable_records", self.notable_list()),
+        ];
+
+        let mut html = template.unwrap_or(DEFAULT_TEMPLATE).to_string();
+        for (name, value) in values {
+            html = html.replace(&format!("{{{{{}}}}}", name), &value);
+        }
+        html
+    }
+
+    fn sources_table(&self, totals: &SourceGrowth) -> String {
+        let mut rows = String::new();
+        for s in self.sources.iter().chain(std::iter::once(totals)) {
+            let growth = match s.growth_percent() {
+                Some(pct) => format!("+{:.1}%", pct),
+                None if s.documents_added > 0 => "new".to_string(),
+                None => "-".to_string(),
+            };
+            rows.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&s.source_id),
+                group_digits(s.documents_added),
+                group_digits(s.documents_after()),
+                growth,
+                human_bytes(s.bytes_added),
+                percent(s.ocr_coverage()),
+            ));
+        }
+        format!(
+            "<table>\n<thead><tr><th>Source</th><th>Acquired</th><th>Total</th>\
+             <th>Growth</th><th>Bytes</th><th>OCR coverage</th></tr></thead>\n\
+             <tbody>\n{}</tbody>\n</table>",
+            rows
+        )
+    }
+
+    fn notable_list(&self) -> String {
+        if self.notable.is_empty() {
+            return "<p>No new records in this period.</p>".to_string();
+        }
+        let mut items = String::new();
+        for record in &self.notable {
+            items.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> <span class=\"meta\">{} &middot; {}</span>",
+                escape(&record.url),
+                escape(&record.title),
+                escape(&record.source_id),
+                match record.pages {
+                    0 => record.acquired_at.get(..10).unwrap_or("").to_string(),
+                    1 => "1 page".to_string(),
+                    n => format!("{} pages", n),
+                },
+            ));
+            if let Some(synopsis) = &record.synopsis {
+                items.push_str(&format!("<p>{}</p>", escape(synopsis)));
+            }
+            items.push_str("</li>\n");
+        }
+        format!("<ol>\n{}</ol>", items)
+    }
+}
+
+/// Write a rendered report to `output` in `format`. OpenDocument and PDF
+/// need LibreOffice (`soffice`) on the PATH.
+pub async fn write_report(html: &str, format: ReportFormat, output: &Path) -> anyhow::Result<()> {
+    let Some(filter) = format.soffice_filter() else {
+        tokio::fs::write(output, html).await?;
+        return Ok(());
+    };
+    if which::which("soffice").is_err() {
+        anyhow::bail!(
+            "Writing {} reports needs LibreOffice (soffice); install it or use --format html",
+            format.extension()
+        );
+    }
+
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("report.html");
+    tokio::fs::write(&input, html).await?;
+    // A private profile keeps this from clashing with a running LibreOffice
+    let profile = format!(
+        "-env:UserInstallation=file://{}",
+        dir.path().join("profile").display()
+    );
+    let result = tokio::process::Command::new("soffice")
+        .arg(&profile)
+        .args(["--headless", "--infilter=HTML (StarWriter)", "--convert-to"])
+        .arg(filter)
+        .arg("--outdir")
+        .arg(dir.path())
+        .arg(&input)
+        .output()
+        .await?;
+    let converted: PathBuf = dir.path().join(format!("report.{}", format.extension()));
+    if !result.status.success() || !converted.exists() {
+        anyhow::bail!(
+            "LibreOffice could not convert the report: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+    tokio::fs::copy(&converted, output).await?;
+    Ok(())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn percent(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.1}%", v))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} bytes", bytes)
+    } else {
+        format!("{:.2} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn report() -> ArchiveReport {
+        ArchiveReport {
+            from: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            to: Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap(),
+            generated_at: Utc.with_ymd_and_hms(2026, 4, 2, 9, 30, 0).unwrap(),
+            sources: vec![
+                SourceGrowth {
+                    source_id: "fbi_vault".to_string(),
+                    documents_before: 1000,
+                    documents_added: 250,
+                    versions_added: 260,
+                    bytes_added: 3_500_000,
+                    pages: 8000,
+                    ocr_pages: 6000,
+                },
+                SourceGrowth {
+                    source_id: "city_<council>".to_string(),
+                    documents_added: 40,
+                    versions_added: 40,
+                    bytes_added: 500_000,
+                    pages: 2000,
+                    ocr_pages: 2000,
+                    ..Default::default()
+                },
+            ],
+            notable: vec![NotableRecord {
+                id: "doc-1".to_string(),
+                source_id: "fbi_vault".to_string(),
+                title: "Field office memo".to_string(),
+                url: "https://vault.fbi.gov/memo.pdf".to_string(),
+                synopsis: Some("Memo on A & B".to_string()),
+                acquired_at: "2026-02-03T00:00:00+00:00".to_string(),
+                pages: 12,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_totals() {
+        let totals = report().totals();
+        assert_eq!(totals.documents_added, 290);
+        assert_eq!(totals.documents_after(), 1290);
+        assert_eq!(totals.bytes_added, 4_000_000);
+        assert_eq!(totals.ocr_coverage(), Some(80.0));
+        assert_eq!(totals.growth_percent(), Some(29.0));
+    }
+
+    #[test]
+    fn test_render_html() {
+        let report = report();
+        let html = report.render_html("Q1 report", None);
+        assert!(html.contains("<title>Q1 report</title>"));
+        assert!(html.contains("2026-01-01"));
+        assert!(html.contains("<td>fbi_vault</td><td>250</td><td>1,250</td><td>+25.0%</td>"));
+        // A source with nothing before the period is new, and escaped
+        assert!(html.contains("<td>city_&lt;council&gt;</td><td>40</td><td>40</td><td>new</td>"));
+        assert!(html.contains("<p>Memo on A &amp; B</p>"));
+        assert!(!html.contains("{{"));
+
+        let custom = report.render_html(
+            "Q1",
+            Some("{{documents_acquired}} / {{bytes_acquired}} / {{unknown}}"),
+        );
+        assert_eq!(custom, "290 / 4.00 MB / {{unknown}}");
+    }
+
+    #[test]
+    fn test_group_digits() {
+        assert_eq!(group_digits(0), "0");
+        assert_eq!(group_digits(999), "999");
+        assert_eq!(group_digits(1234567), "1,234,567");
+    }
+}
@@ -0,0 +1,229 @@
+//! Text-only retention eligibility and version thinning.
+//!
+//! Deleting an original is only safe once everything worth keeping from it
+//! lives in the database. These checks cover what the document row can tell
+//! us; callers still verify the file on disk against the recorded hash and
+//! that no other version relies on the same file before deleting it.
+//!
+//! Pages re-fetched on every refresh pile up near-identical versions.
+//! [`select_versions_to_prune`] picks the ones that add nothing: a document
+//! keeps its first and last version and the most recent significant ones,
+//! those whose text differs enough from the version kept before them.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::RetentionConfig;
+use crate::models::{Document, DocumentStatus, DocumentVersion};
+use crate::services::overlap::Signature;
+
+/// Why a version's original must be kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepReason {
+    /// The document has not finished text extraction and OCR.
+    NotProcessed,
+    /// Less extracted text than `min_text_chars`.
+    TooLittleText,
+    /// Downloaded less than `min_age_days` ago.
+    TooRecent,
+    /// No http(s) URL to re-download from.
+    NoSourceUrl,
+}
+
+impl KeepReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotProcessed => "not processed",
+            Self::TooLittleText => "too little text",
+            Self::TooRecent => "too recent",
+            Self::NoSourceUrl => "no source URL",
+        }
+    }
+}
+
+/// URL an original can be re-downloaded from, if any.
+pub fn redownload_url<'a>(doc: &'a Document, version: &'a DocumentVersion) -> Option<&'a str> {
+    let url = version.source_url.as_deref().unwrap_or(&doc.source_url);
+    (url.starts_with("http://") || url.starts_with("https://")).then_some(url)
+}
+
+/// Check whether a version's original may be deleted under `config`.
+///
+/// Only the current version qualifies: extracted text belongs to it, so
+/// deleting an older version's file would lose content.
+pub fn check_prunable(
+    doc: &Document,
+    version: &DocumentVersion,
+    config: &RetentionConfig,
+    now: DateTime<Utc>,
+) -> Result<(), KeepReason> {
+    let is_current = doc.current_version().map(|v| v.id) == Some(version.id);
+    let processed = matches!(
+        doc.status,
+        DocumentStatus::OcrComplete | DocumentStatus::Indexed
+    );
+    if !is_current || !processed {
+        return Err(KeepReason::NotProcessed);
+    }
+    let text_chars = doc
+        .extracted_text
+        .as_deref()
+        .map(|t| t.trim().chars().count())
+        .unwrap_or(0);
+    if text_chars == 0 || text_chars < config.min_text_chars() {
+        return Err(KeepReason::TooLittleText);
+    }
+    if now - version.acquired_at < Duration::days(config.min_age_days() as i64) {
+        return Err(KeepReason::TooRecent);
+    }
+    if redownload_url(doc, version).is_none() {
+        return Err(KeepReason::NoSourceUrl);
+    }
+    Ok(())
+}
+
+/// What version thinning compares of one version.
+#[derive(Debug, Clone)]
+pub struct VersionText {
+    pub version_id: i64,
+    /// `None` when the version has too little text to compare; such
+    /// versions always count as significant.
+    pub signature: Option<Signature>,
+}
+
+/// IDs of the versions to prune, given a document's versions oldest first.
+///
+/// The first and last are always kept. In between, a version is
+/// significant when its text similarity to the previous significant one
+/// (or the first) is below `threshold`; only the `keep` most recent of
+/// those are kept.
+pub fn select_versions_to_prune(versions: &[VersionText], keep: usize, threshold: f64) -> Vec<i64> {
+    if versions.len() <= 2 {
+        return Vec::new();
+    }
+    let middle = &versions[1..versions.len() - 1];
+
+    let mut previous = &versions[0].signature;
+    let mut significant = Vec::new();
+    let mut prune = Vec::new();
+    for version in middle {
+        let similar = match (previous, &version.signature) {
+            (Some(a), Some(b)) => f64::from(a.similarity(b)) >= threshold,
+            _ => false,
+        };
+        if similar {
+            prune.push(version.version_id);
+        } else {
+            significant.push(version.version_id);
+            previous = &version.signature;
+        }
+    }
+
+    let excess = significant.len().saturating_sub(keep);
+    prune.extend_from_slice(&significant[..excess]);
+    prune.sort_unstable();
+    prune
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn processed_doc(text: &str, age_days: i64) -> Document {
+        let mut version = DocumentVersion::new(
+            b"%PDF-1.4 scanned",
+            "application/pdf".to_string(),
+            Some("https://agency.gov/doc.pdf".to_string()),
+        );
+        version.acquired_at = Utc::now() - Duration::days(age_days);
+        let mut doc = Document::new(
+            "doc-1".to_string(),
+            "test-source".to_string(),
+            "Doc".to_string(),
+            "https://agency.gov/doc.pdf".to_string(),
+            version,
+            serde_json::json!({}),
+        );
+        doc.status = DocumentStatus::OcrComplete;
+        doc.extracted_text = Some(text.to_string());
+        doc
+    }
+
+    #[test]
+    fn test_check_prunable() {
+        let config = RetentionConfig {
+            text_only: true,
+            min_text_chars: Some(10),
+            min_age_days: Some(7),
+            ..Default::default()
+        };
+        let now = Utc::now();
+
+        let doc = processed_doc("plenty of extracted text", 30);
+        assert_eq!(check_prunable(&doc, &doc.versions[0], &config, now), Ok(()));
+
+        let doc = processed_doc("short", 30);
+        assert_eq!(
+            check_prunable(&doc, &doc.versions[0], &config, now),
+            Err(KeepReason::TooLittleText)
+        );
+
+        let doc = processed_doc("plenty of extracted text", 1);
+        assert_eq!(
+            check_prunable(&doc, &doc.versions[0], &config, now),
+            Err(KeepReason::TooRecent)
+        );
+
+        let mut doc = processed_doc("plenty of extracted text", 30);
+        doc.status = DocumentStatus::Downloaded;
+        assert_eq!(
+            check_prunable(&doc, &doc.versions[0], &config, now),
+            Err(KeepReason::NotProcessed)
+        );
+
+        let mut doc = processed_doc("plenty of extracted text", 30);
+        doc.source_url = "file:///imports/doc.pdf".to_string();
+        doc.versions[0].source_url = None;
+        assert_eq!(
+            check_prunable(&doc, &doc.versions[0], &config, now),
+            Err(KeepReason::NoSourceUrl)
+        );
+    }
+
+    fn version(id: i64, text: &str) -> VersionText {
+        VersionText {
+            version_id: id,
+            signature: Signature::of(text),
+        }
+    }
+
+    #[test]
+    fn test_select_versions_to_prune() {
+        let base = "Freedom of Information Act reading room with the latest releases \
+                    from the field office listed by date and subject";
+        let changed = "Notice: the reading room has moved and all records are now \
+                       published on the new portal under the records section";
+        let versions = vec![
+            version(1, base),
+            version(2, base),
+            version(3, changed),
+            version(4, changed),
+            version(5, base),
+            version(6, "too short"),
+            version(7, base),
+        ];
+
+        // 3, 5 and 6 differ from the version kept before them
+        assert_eq!(select_versions_to_prune(&versions, 3, 0.9), vec![2, 4]);
+        // Only the most recent significant versions are kept
+        assert_eq!(
+            select_versions_to_prune(&versions, 1, 0.9),
+            vec![2, 3, 4, 5]
+        );
+        assert_eq!(
+            select_versions_to_prune(&versions, 0, 0.9),
+            vec![2, 3, 4, 5, 6]
+        );
+        // The first and last are never pruned
+        assert!(select_versions_to_prune(&versions[..2], 0, 0.9).is_empty());
+    }
+}
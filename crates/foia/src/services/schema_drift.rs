@@ -0,0 +1,187 @@
+//! JSON response schema fingerprinting.
+//!
+//! When an agency reshapes its API, `results_path` and the URL fields stop
+//! matching and discovery quietly finds nothing. We reduce each response to
+//! the set of JSON paths it contains (`results[].file.url`), hash that set,
+//! and compare against the last crawl so a change is reported instead.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Array elements sampled per array; later items rarely add new keys.
+const MAX_ITEMS: usize = 50;
+
+/// Nesting depth below which paths are not recorded.
+const MAX_DEPTH: usize = 12;
+
+/// Paths listed per direction in a drift summary.
+const SUMMARY_PATHS: usize = 5;
+
+/// Collect the structural paths of a JSON value.
+///
+/// Object keys are joined with `.`, array elements collapse into `[]`, and
+/// the union over sampled elements is taken so optional keys on some items
+/// still count. Values themselves are ignored.
+pub fn json_paths(value: &Value) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+    collect(value, String::new(), 0, &mut paths);
+    paths
+}
+
+fn collect(value: &Value, prefix: String, depth: usize, paths: &mut BTreeSet<String>) {
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                paths.insert(path.clone());
+                collect(child, path, depth + 1, paths);
+            }
+        }
+        Value::Array(items) => {
+            let path = format!("{}[]", prefix);
+            for item in items.iter().take(MAX_ITEMS) {
+                if item.is_object() || item.is_array() {
+                    paths.insert(path.clone());
+                    collect(item, path.clone(), depth + 1, paths);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Short stable hash of a path set.
+pub fn fingerprint(paths: &BTreeSet<String>) -> String {
+    let mut hasher = Sha256::new();
+    for path in paths {
+        hasher.update(path.as_bytes());
+        hasher.update(b"\n");
+    }
+    hex::encode(hasher.finalize())[..16].to_string()
+}
+
+/// Paths that appeared and disappeared between two responses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// One-line description for logs and the stored drift message.
+    pub fn summary(&self) -> String {
+        let list = |paths: &[String]| {
+            let mut s = paths
+                .iter()
+                .take(SUMMARY_PATHS)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ");
+            if paths.len() > SUMMARY_PATHS {
+                s.push_str(&format!(" (+{} more)", paths.len() - SUMMARY_PATHS));
+            }
+            s
+        };
+        let mut parts = Vec::new();
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", list(&self.removed)));
+        }
+        if !self.added.is_empty() {
+            parts.push(format!("added {}", list(&self.added)));
+        }
+        if parts.is_empty() {
+            "no path changes".to_string()
+        } else {
+            format!("response shape changed: {}", parts.join("; "))
+        }
+    }
+}
+
+/// Compare two path sets.
+pub fn diff_paths<'a>(
+    old: impl IntoIterator<Item = &'a String>,
+    new: &BTreeSet<String>,
+) -> SchemaDiff {
+    let old: BTreeSet<&String> = old.into_iter().collect();
+    SchemaDiff {
+        added: new.iter().filter(|p| !old.contains(p)).cloned().collect(),
+        removed: old
+            .into_iter()
+            .filter(|p| !new.contains(*p))
+            .cloned()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_paths_unions_array_items() {
+        let value = json!({
+            "total": 2,
+            "results": [
+                {"title": "A", "file": {"url": "/a.pdf"}},
+                {"title": "B", "file": {"url": "/b.pdf", "size": 10}},
+            ],
+            "tags": ["x", "y"],
+        });
+        let paths: Vec<String> = json_paths(&value).into_iter().collect();
+        assert_eq!(
+            paths,
+            vec![
+                "results",
+                "results[]",
+                "results[].file",
+                "results[].file.size",
+                "results[].file.url",
+                "results[].title",
+                "tags",
+                "total",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_values() {
+        let a = json!({"results": [{"url": "/a.pdf"}], "count": 1});
+        let b = json!({"results": [{"url": "/b.pdf"}, {"url": "/c.pdf"}], "count": 2});
+        let c = json!({"data": {"items": [{"link": "/a.pdf"}]}});
+        assert_eq!(fingerprint(&json_paths(&a)), fingerprint(&json_paths(&b)));
+        assert_ne!(fingerprint(&json_paths(&a)), fingerprint(&json_paths(&c)));
+    }
+
+    #[test]
+    fn test_diff_paths() {
+        let old = json_paths(&json!({"results": [{"url": "/a.pdf", "title": "A"}]}));
+        let new = json_paths(&json!({"data": [{"url": "/a.pdf", "title": "A"}]}));
+        let diff = diff_paths(&old, &new);
+        assert_eq!(
+            diff.removed,
+            vec!["results", "results[]", "results[].title", "results[].url"]
+        );
+        assert_eq!(
+            diff.added,
+            vec!["data", "data[]", "data[].title", "data[].url"]
+        );
+        assert!(diff
+            .summary()
+            .starts_with("response shape changed: removed results"));
+        assert!(diff_paths(&old, &old).is_empty());
+    }
+}
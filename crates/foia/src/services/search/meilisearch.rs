@@ -0,0 +1,478 @@
+//! Index on a Meilisearch server.
+//!
+//! One record per archive document. Meilisearch only accepts short
+//! alphanumeric primary keys, so records are keyed by a `key` derived from
+//! the document ID, which is kept in `id`. Writes are queued as tasks; an
+//! update waits for each before moving on. Meilisearch can't match
+//! alternative queries, so aliases are kept in its synonyms instead.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Method, RequestBuilder};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::sync::{deleted_since, ChangeFeed, SyncStateFile};
+use super::{
+    service_json, top_facets, FacetCount, IndexStats, IndexStatus, SearchError, SearchFacets,
+    SearchHit, SearchIndex, SearchRequest, SearchResults, SERVICE_TIMEOUT_SECS,
+};
+use crate::config::{SearchBackend, SearchConfig};
+use crate::repository::DieselDocumentRepository;
+use crate::secrets;
+use crate::services::access::AccessFilter;
+
+/// Interval between checks on a queued task.
+const TASK_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Longest wait for a queued task.
+const TASK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Words of context in a snippet.
+const CROP_WORDS: usize = 40;
+
+/// Attributes searched, most important first (Meilisearch ranks matches in
+/// earlier attributes higher).
+const SEARCHABLE: [&str; 4] = ["title", "tags", "entities", "body"];
+
+/// Attributes filters and facets may use.
+const FILTERABLE: [&str; 7] = [
+    "id",
+    "source_id",
+    "tags",
+    "year",
+    "entity_types",
+    "date_number",
+    "indexed_at",
+];
+
+/// Attribute behind each facet.
+const FACETS: [&str; 4] = ["source_id", "tags", "year", "entity_types"];
+
+/// A Meilisearch index.
+pub struct MeilisearchIndex {
+    client: reqwest::Client,
+    url: String,
+    index: String,
+    api_key: Option<String>,
+    state: SyncStateFile,
+}
+
+impl MeilisearchIndex {
+    pub fn new(config: &SearchConfig, data_dir: &Path) -> Result<Self, SearchError> {
+        let url = config
+            .url
+            .as_deref()
+            .ok_or_else(|| {
+                SearchError::Service("search.url is required for meilisearch".to_string())
+            })?
+            .trim_end_matches('/')
+            .to_string();
+        let api_key = config
+            .api_key
+            .as_deref()
+            .map(secrets::resolve)
+            .transpose()?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(SERVICE_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self {
+            client,
+            url,
+            index: config.index_name().to_string(),
+            api_key,
+            state: SyncStateFile::new(data_dir, "meilisearch", config.index_name()),
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.client.request(method, format!("{}{}", self.url, path));
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    fn index_path(&self, rest: &str) -> String {
+        format!("/indexes/{}{}", self.index, rest)
+    }
+
+    /// Send a write and wait for the task it queues.
+    async fn write(&self, request: RequestBuilder) -> Result<(), SearchError> {
+        let task = service_json(request.send().await?).await?;
+        let uid = task
+            .get("taskUid")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| SearchError::Service("response has no taskUid".to_string()))?;
+
+        let started = Instant::now();
+        loop {
+            let task = service_json(
+                self.request(Method::GET, &format!("/tasks/{}", uid))
+                    .send()
+                    .await?,
+            )
+            .await?;
+            match task.get("status").and_then(Value::as_str) {
+                Some("succeeded") => return Ok(()),
+                Some("failed") | Some("canceled") => {
+                    let message = task
+                        .pointer("/error/message")
+                        .and_then(Value::as_str)
+                        .unwrap_or("task failed");
+                    return Err(SearchError::Service(message.to_string()));
+                }
+                _ if started.elapsed() > TASK_TIMEOUT => {
+                    return Err(SearchError::Service(format!(
+                        "task {} did not finish in {}s",
+                        uid,
+                        TASK_TIMEOUT.as_secs()
+                    )));
+                }
+                _ => tokio::time::sleep(TASK_POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Create the index if needed and apply its settings.
+    async fn ensure_index(&self) -> Result<(), SearchError> {
+        let exists = self
+            .request(Method::GET, &self.index_path(""))
+            .send()
+            .await?
+            .status()
+            .is_success();
+        if !exists {
+            self.write(
+                self.request(Method::POST, "/indexes")
+                    .json(&json!({ "uid": self.index, "primaryKey": "key" })),
+            )
+            .await?;
+        }
+        self.write(
+            self.request(Method::PATCH, &self.index_path("/settings"))
+                .json(&json!({
+                    "searchableAttributes": SEARCHABLE,
+                    "filterableAttributes": FILTERABLE,
+                    "faceting": { "maxValuesPerFacet": 100 },
+                })),
+        )
+        .await
+    }
+
+    /// Make every alias a two-way synonym.
+    async fn sync_synonyms(&self, repo: &DieselDocumentRepository) -> Result<(), SearchError> {
+        let mut synonyms: HashMap<String, Vec<String>> = HashMap::new();
+        for alias in repo.list_aliases().await? {
+            synonyms
+                .entry(alias.term.clone())
+                .or_default()
+                .push(alias.expansion.clone());
+            synonyms
+                .entry(alias.expansion)
+                .or_default()
+                .push(alias.term);
+        }
+        self.write(
+            self.request(Method::PUT, &self.index_path("/settings/synonyms"))
+                .json(&synonyms),
+        )
+        .await
+    }
+
+    async fn document_count(&self) -> Result<u64, SearchError> {
+        let response = self
+            .request(Method::GET, &self.index_path("/stats"))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(0);
+        }
+        let stats = service_json(response).await?;
+        Ok(stats
+            .get("numberOfDocuments")
+            .and_then(Value::as_u64)
+            .unwrap_or(0))
+    }
+}
+
+#[async_trait]
+impl SearchIndex for MeilisearchIndex {
+    fn backend(&self) -> SearchBackend {
+        SearchBackend::Meilisearch
+    }
+
+    fn location(&self) -> String {
+        format!("{} (index {})", self.url, self.index)
+    }
+
+    async fn update(
+        &self,
+        repo: &DieselDocumentRepository,
+        full: bool,
+    ) -> Result<IndexStats, SearchError> {
+        let started = Utc::now();
+        // An index without a journal position needs a full update
+        let since = if full { None } else { self.state.change_seq() };
+        let full = since.is_none();
+        let through = repo.latest_change_seq().await?;
+        self.ensure_index().await?;
+        self.sync_synonyms(repo).await?;
+
+        // Deletions first, so a document deleted and re-created since is
+        // indexed again
+        let deleted = deleted_since(repo, since).await?;
+        if !deleted.is_empty() {
+            let keys: Vec<String> = deleted.iter().map(|id| record_key(id)).collect();
+            self.write(
+                self.request(Method::POST, &self.index_path("/documents/delete-batch"))
+                    .json(&keys),
+            )
+            .await?;
+        }
+
+        let mut feed = ChangeFeed::new(repo, since);
+        let mut indexed = 0u64;
+        while let Some(docs) = feed.next_batch().await? {
+            if docs.is_empty() {
+                continue;
+            }
+            let records: Vec<Value> = docs
+                .iter()
+                .map(|doc| {
+                    let mut record = doc.to_record(started.timestamp_millis());
+                    record["key"] = Value::String(record_key(&doc.id));
+                    record
+                })
+                .collect();
+            self.write(
+                self.request(Method::POST, &self.index_path("/documents"))
+                    .json(&records),
+            )
+            .await?;
+            indexed += docs.len() as u64;
+        }
+
+        // Records not rewritten by a full update are of deleted documents
+        if full {
+            self.write(
+                self.request(Method::POST, &self.index_path("/documents/delete"))
+                    .json(&json!({
+                        "filter": format!("indexed_at < {}", started.timestamp_millis())
+                    })),
+            )
+            .await?;
+        }
+
+        self.state.record(started, through)?;
+        Ok(IndexStats {
+            indexed,
+            total: self.document_count().await?,
+            deleted: deleted.len() as u64,
+        })
+    }
+
+    async fn search(&self, request: &SearchRequest) -> Result<SearchResults, SearchError> {
+        let body = json!({
+            "q": request.query,
+            "offset": request.offset,
+            "limit": request.limit,
+            "filter": filters(request),
+            "facets": FACETS,
+            "matchingStrategy": "all",
+            "attributesToRetrieve": ["id", "source_id", "title"],
+            "attributesToCrop": ["body"],
+            "cropLength": CROP_WORDS,
+            "attributesToHighlight": ["body"],
+            "highlightPreTag": "<b>",
+            "highlightPostTag": "</b>",
+            "showRankingScore": true,
+        });
+        let response = service_json(
+            self.request(Method::POST, &self.index_path("/search"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await?;
+
+        let hits = response
+            .get("hits")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|hit| {
+                let text = |key: &str| hit.get(key).and_then(Value::as_str).unwrap_or_default();
+                let snippet = hit
+                    .pointer("/_formatted/body")
+                    .and_then(Value::as_str)
+                    .filter(|body| body.contains("<b>"))
+                    .map(str::to_string);
+                SearchHit {
+                    document_id: text("id").to_string(),
+                    source_id: text("source_id").to_string(),
+                    title: text("title").to_string(),
+                    score: hit
+                        .get("_rankingScore")
+                        .and_then(Value::as_f64)
+                        .unwrap_or_default() as f32,
+                    snippet,
+                }
+            })
+            .collect();
+
+        let facet = |name: &str| -> Vec<FacetCount> {
+            let counts = response
+                .pointer(&format!("/facetDistribution/{}", name))
+                .and_then(Value::as_object)
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|(value, count)| FacetCount {
+                            value: value.clone(),
+                            count: count.as_u64().unwrap_or_default(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            top_facets(counts)
+        };
+
+        Ok(SearchResults {
+            total: response
+                .get("estimatedTotalHits")
+                .and_then(Value::as_u64)
+                .unwrap_or_default(),
+            hits,
+            facets: SearchFacets {
+                sources: facet("source_id"),
+                tags: facet("tags"),
+                years: facet("year"),
+                entity_types: facet("entity_types"),
+            },
+        })
+    }
+
+    async fn status(&self) -> Result<IndexStatus, SearchError> {
+        Ok(IndexStatus {
+            documents: self.document_count().await?,
+            indexed_through: self.state.indexed_through(),
+        })
+    }
+}
+
+/// Primary key of a document's record: the ID itself when Meilisearch
+/// accepts it, otherwise a hash of it.
+fn record_key(id: &str) -> String {
+    let accepted = !id.is_empty()
+        && id.len() <= 511
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if accepted {
+        id.to_string()
+    } else {
+        format!("h_{}", hex::encode(Sha256::digest(id.as_bytes())))
+    }
+}
+
+/// Quoted string for a filter expression.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn quote_list(values: &[String]) -> String {
+    let quoted: Vec<String> = values.iter().map(|v| quote(v)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+/// Filter expressions, all of which must hold.
+fn filters(request: &SearchRequest) -> Vec<String> {
+    let mut filters = Vec::new();
+    if let Some(source) = &request.source_id {
+        filters.push(format!("source_id = {}", quote(source)));
+    }
+    for tag in &request.tags {
+        filters.push(format!("tags = {}", quote(tag)));
+    }
+    let number = |d: chrono::NaiveDate| d.format("%Y%m%d").to_string();
+    if let Some(from) = request.date_from {
+        filters.push(format!("date_number >= {}", number(from)));
+    }
+    if let Some(to) = request.date_to {
+        filters.push(format!("date_number <= {}", number(to)));
+    }
+    if let Some(access) = &request.access {
+        filters.extend(access_filters(access));
+    }
+    filters
+}
+
+/// Filter expressions leaving out what an [`AccessFilter`] hides.
+fn access_filters(access: &AccessFilter) -> Vec<String> {
+    let mut filters = Vec::new();
+    if !access.hidden_documents.is_empty() {
+        filters.push(format!(
+            "NOT id IN {}",
+            quote_list(&access.hidden_documents)
+        ));
+    }
+    if !access.hidden_sources.is_empty() {
+        let hidden = format!("source_id IN {}", quote_list(&access.hidden_sources));
+        filters.push(if access.released_documents.is_empty() {
+            format!("NOT {}", hidden)
+        } else {
+            format!(
+                "NOT ({} AND NOT id IN {})",
+                hidden,
+                quote_list(&access.released_documents)
+            )
+        });
+    }
+    if let Some(only) = &access.only_sources {
+        filters.push(format!("source_id IN {}", quote_list(only)));
+    }
+    filters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_key() {
+        assert_eq!(record_key("doc-1_a"), "doc-1_a");
+        let hashed = record_key("agency:doc/1");
+        assert!(hashed.starts_with("h_"));
+        assert_eq!(hashed, record_key("agency:doc/1"));
+    }
+
+    #[test]
+    fn test_filters() {
+        let request = SearchRequest {
+            source_id: Some("fbi".to_string()),
+            tags: vec!["say \"cheese\"".to_string()],
+            date_from: chrono::NaiveDate::from_ymd_opt(1998, 4, 2),
+            access: Some(AccessFilter {
+                hidden_sources: vec!["cia".to_string()],
+                released_documents: vec!["doc-1".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            filters(&request),
+            vec![
+                "source_id = \"fbi\"",
+                "tags = \"say \\\"cheese\\\"\"",
+                "date_number >= 19980402",
+                "NOT (source_id IN [\"cia\"] AND NOT id IN [\"doc-1\"])",
+            ]
+        );
+    }
+}
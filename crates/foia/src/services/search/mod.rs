@@ -0,0 +1,319 @@
+//! Document search through a dedicated index.
+//!
+//! The database answers searches by default. An index kept alongside it
+//! ranks results by relevance and counts facets (source, tag, year, entity
+//! type) in the same query. The index is either embedded (tantivy) or lives
+//! on a search service the team already runs (Meilisearch, OpenSearch).
+//! Either way it is derived data: it can be rebuilt from the database at any
+//! time, and results are checked against the database before they are
+//! shown.
+
+mod meilisearch;
+mod opensearch;
+mod sync;
+mod tantivy_index;
+
+pub use meilisearch::MeilisearchIndex;
+pub use opensearch::OpenSearchIndex;
+pub use tantivy_index::TantivyIndex;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::config::{SearchBackend, SearchConfig};
+use crate::models::Document;
+use crate::repository::models::DocumentEntityRecord;
+use crate::repository::{DieselDocumentRepository, DieselError};
+use crate::secrets::SecretError;
+use crate::services::access::AccessFilter;
+
+/// Errors from building or querying a search index.
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    #[error("database error: {0}")]
+    Database(#[from] DieselError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("index error: {0}")]
+    Index(String),
+    #[error("search service error: {0}")]
+    Service(String),
+    #[error("search service credentials: {0}")]
+    Secret(#[from] SecretError),
+    #[error(
+        "the search index at {0} was built by another version; run 'foia search-index rebuild'"
+    )]
+    SchemaMismatch(String),
+}
+
+impl From<reqwest::Error> for SearchError {
+    fn from(e: reqwest::Error) -> Self {
+        SearchError::Service(e.to_string())
+    }
+}
+
+/// Longest snippet returned with a hit, in characters.
+const SNIPPET_CHARS: usize = 240;
+
+/// Values returned per facet.
+const FACET_VALUES: usize = 20;
+
+/// Seconds a search service may take to answer.
+const SERVICE_TIMEOUT_SECS: u64 = 60;
+
+/// An index answering document searches.
+#[async_trait]
+pub trait SearchIndex: Send + Sync {
+    /// Engine behind the index.
+    fn backend(&self) -> SearchBackend;
+
+    /// Where the index lives: a directory or a service URL.
+    fn location(&self) -> String;
+
+    /// Index documents changed since the last update and drop deleted
+    /// ones, or replace the contents with every document when `full` is
+    /// set.
+    async fn update(
+        &self,
+        repo: &DieselDocumentRepository,
+        full: bool,
+    ) -> Result<IndexStats, SearchError>;
+
+    /// Run a search, returning one page of hits and facet counts over all
+    /// matches.
+    async fn search(&self, request: &SearchRequest) -> Result<SearchResults, SearchError>;
+
+    /// Size of the index and how current it is.
+    async fn status(&self) -> Result<IndexStatus, SearchError>;
+}
+
+/// Shared handle to any search index.
+pub type BoxedSearchIndex = Arc<dyn SearchIndex>;
+
+/// Open the index `config` selects. For the `database` backend this is the
+/// tantivy index, so it can be built before switching to it.
+pub fn open_index(config: &SearchConfig, data_dir: &Path) -> Result<BoxedSearchIndex, SearchError> {
+    let index: BoxedSearchIndex = match config.backend {
+        SearchBackend::Database | SearchBackend::Tantivy => {
+            Arc::new(TantivyIndex::open(&config.index_dir(data_dir))?)
+        }
+        SearchBackend::Meilisearch => Arc::new(MeilisearchIndex::new(config, data_dir)?),
+        SearchBackend::OpenSearch => Arc::new(OpenSearchIndex::new(config, data_dir)?),
+    };
+    Ok(index)
+}
+
+/// A search against the index.
+#[derive(Debug, Clone, Default)]
+pub struct SearchRequest {
+    /// Query text; empty matches every document (for browsing facets).
+    pub query: String,
+    /// Alternative queries also matched, such as alias expansions from
+    /// [`expand_search_query`](crate::repository::DieselDocumentRepository::expand_search_query).
+    /// Meilisearch matches aliases through its synonyms instead.
+    pub expansions: Vec<String>,
+    pub source_id: Option<String>,
+    /// Tags (all of).
+    pub tags: Vec<String>,
+    /// Publication dates, inclusive.
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    pub limit: usize,
+    pub offset: usize,
+    /// Leave out documents the viewer may not see, from hits and counts.
+    pub access: Option<AccessFilter>,
+}
+
+/// A matching document.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub document_id: String,
+    pub source_id: String,
+    pub title: String,
+    pub score: f32,
+    /// Text around the match with terms in `<b>`, when the text matched.
+    pub snippet: Option<String>,
+}
+
+/// Number of matching documents with one facet value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: u64,
+}
+
+/// Facet counts over every matching document, not just the returned page.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchFacets {
+    pub sources: Vec<FacetCount>,
+    pub tags: Vec<FacetCount>,
+    pub years: Vec<FacetCount>,
+    pub entity_types: Vec<FacetCount>,
+}
+
+/// One page of results.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchResults {
+    /// Matching documents in total.
+    pub total: u64,
+    pub hits: Vec<SearchHit>,
+    pub facets: SearchFacets,
+}
+
+/// Progress of an index update.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IndexStats {
+    /// Documents (re)indexed.
+    pub indexed: u64,
+    /// Documents in the index afterwards.
+    pub total: u64,
+    /// Documents dropped because they were deleted.
+    pub deleted: u64,
+}
+
+/// Size of an index and how current it is.
+#[derive(Debug, Clone, Default)]
+pub struct IndexStatus {
+    pub documents: u64,
+    /// When the last completed update started; documents updated since may
+    /// be missing or stale.
+    pub indexed_through: Option<DateTime<Utc>>,
+}
+
+/// What the index stores about a document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexedDocument {
+    pub id: String,
+    pub source_id: String,
+    pub title: String,
+    /// Synopsis, extracted text (or page text), and the text of archive
+    /// members and attachments.
+    pub body: String,
+    pub tags: Vec<String>,
+    pub date: Option<NaiveDate>,
+    /// Entity texts, deduplicated.
+    pub entities: Vec<String>,
+    pub entity_types: Vec<String>,
+}
+
+impl IndexedDocument {
+    pub fn new(
+        doc: &Document,
+        date: Option<NaiveDate>,
+        entities: &[DocumentEntityRecord],
+        attachments: &[String],
+    ) -> Self {
+        let body = [doc.synopsis.as_deref(), doc.extracted_text.as_deref()]
+            .into_iter()
+            .flatten()
+            .chain(attachments.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let mut texts: Vec<String> = entities.iter().map(|e| e.entity_text.clone()).collect();
+        texts.sort();
+        texts.dedup();
+        let mut types: Vec<String> = entities.iter().map(|e| e.entity_type.clone()).collect();
+        types.sort();
+        types.dedup();
+
+        Self {
+            id: doc.id.clone(),
+            source_id: doc.source_id.clone(),
+            title: doc.title.clone(),
+            body,
+            tags: doc.tags.clone(),
+            date,
+            entities: texts,
+            entity_types: types,
+        }
+    }
+
+    /// Year facet value.
+    pub fn year(&self) -> Option<String> {
+        self.date.map(|d| d.year().to_string())
+    }
+
+    /// Flat JSON record stored on a search service. `indexed_at` marks the
+    /// update that wrote it, so a full update can drop records it didn't.
+    pub(crate) fn to_record(&self, indexed_at: i64) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "source_id": self.source_id,
+            "title": self.title,
+            "body": self.body,
+            "tags": self.tags,
+            "entities": self.entities,
+            "entity_types": self.entity_types,
+            "year": self.year(),
+            "date": self.date.map(|d| d.format("%Y-%m-%d").to_string()),
+            // Sortable number for services that filter ranges on numbers only
+            "date_number": self
+                .date
+                .map(|d| d.year() * 10_000 + d.month() as i32 * 100 + d.day() as i32),
+            "indexed_at": indexed_at,
+        })
+    }
+}
+
+/// Most frequent facet values first.
+fn top_facets(mut counts: Vec<FacetCount>) -> Vec<FacetCount> {
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts.truncate(FACET_VALUES);
+    counts
+}
+
+/// Body of a successful search service response, or its error message.
+async fn service_json(response: reqwest::Response) -> Result<serde_json::Value, SearchError> {
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|v| {
+                // Meilisearch: {"message"}; OpenSearch: {"error": {"reason"}}
+                v.get("message")
+                    .or_else(|| v.pointer("/error/reason"))
+                    .and_then(|m| m.as_str())
+                    .map(str::to_string)
+            })
+            .unwrap_or(text);
+        return Err(SearchError::Service(format!(
+            "{}: {}",
+            status,
+            message.trim()
+        )));
+    }
+    if text.is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    serde_json::from_str(&text).map_err(|e| SearchError::Service(e.to_string()))
+}
+
+/// Documents of a batch with their dates, entities and attachment text,
+/// ready to index.
+pub(crate) fn prepare_batch(
+    docs: &[Document],
+    dates: &HashMap<String, NaiveDate>,
+    entities: &HashMap<String, Vec<DocumentEntityRecord>>,
+    attachments: &HashMap<String, Vec<String>>,
+) -> Vec<IndexedDocument> {
+    docs.iter()
+        .map(|doc| {
+            IndexedDocument::new(
+                doc,
+                dates.get(&doc.id).copied(),
+                entities.get(&doc.id).map(Vec::as_slice).unwrap_or_default(),
+                attachments
+                    .get(&doc.id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+            )
+        })
+        .collect()
+}
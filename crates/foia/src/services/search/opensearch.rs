@@ -0,0 +1,462 @@
+//! Index on an OpenSearch cluster (Elasticsearch-compatible APIs).
+//!
+//! One record per archive document, with the document ID as `_id`. Writes
+//! go through the bulk API; the index is refreshed once an update is done
+//! rather than after every batch.
+
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Method, RequestBuilder};
+use serde_json::{json, Value};
+
+use super::sync::{deleted_since, ChangeFeed, SyncStateFile};
+use super::{
+    service_json, top_facets, FacetCount, IndexStats, IndexStatus, IndexedDocument, SearchError,
+    SearchFacets, SearchHit, SearchIndex, SearchRequest, SearchResults, FACET_VALUES,
+    SERVICE_TIMEOUT_SECS, SNIPPET_CHARS,
+};
+use crate::config::{SearchBackend, SearchConfig};
+use crate::repository::DieselDocumentRepository;
+use crate::secrets;
+use crate::services::access::AccessFilter;
+
+/// Fields searched, with boosts.
+const SEARCH_FIELDS: [&str; 4] = ["title^3", "tags.text^2", "entities", "body"];
+
+/// An OpenSearch index.
+pub struct OpenSearchIndex {
+    client: reqwest::Client,
+    url: String,
+    index: String,
+    credentials: Option<(String, String)>,
+    state: SyncStateFile,
+}
+
+impl OpenSearchIndex {
+    pub fn new(config: &SearchConfig, data_dir: &Path) -> Result<Self, SearchError> {
+        let url = config
+            .url
+            .as_deref()
+            .ok_or_else(|| {
+                SearchError::Service("search.url is required for opensearch".to_string())
+            })?
+            .trim_end_matches('/')
+            .to_string();
+        let credentials = match (&config.username, &config.password) {
+            (Some(user), Some(password)) => Some((user.clone(), secrets::resolve(password)?)),
+            _ => None,
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(SERVICE_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self {
+            client,
+            url,
+            index: config.index_name().to_string(),
+            credentials,
+            state: SyncStateFile::new(data_dir, "opensearch", config.index_name()),
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}/{}{}", self.url, self.index, path));
+        match &self.credentials {
+            Some((user, password)) => request.basic_auth(user, Some(password)),
+            None => request,
+        }
+    }
+
+    /// Create the index with its mapping if it doesn't exist.
+    async fn ensure_index(&self) -> Result<(), SearchError> {
+        let response = self.request(Method::HEAD, "").send().await?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+        let keyword = json!({ "type": "keyword" });
+        let text = json!({ "type": "text" });
+        service_json(
+            self.request(Method::PUT, "")
+                .json(&json!({
+                    "mappings": {
+                        "properties": {
+                            "id": keyword,
+                            "source_id": keyword,
+                            "title": text,
+                            "body": text,
+                            "tags": { "type": "keyword", "fields": { "text": text } },
+                            "entities": text,
+                            "entity_types": keyword,
+                            "year": keyword,
+                            "date": { "type": "date", "format": "yyyy-MM-dd" },
+                            "date_number": { "type": "integer" },
+                            "indexed_at": { "type": "long" },
+                        }
+                    }
+                }))
+                .send()
+                .await?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Send newline-delimited bulk actions, failing on the first rejected
+    /// item (deleting a record that isn't there is fine).
+    async fn bulk(&self, lines: Vec<Value>) -> Result<(), SearchError> {
+        let mut body = String::new();
+        for line in lines {
+            body.push_str(&line.to_string());
+            body.push('\n');
+        }
+        let response = service_json(
+            self.request(Method::POST, "/_bulk")
+                .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+                .body(body)
+                .send()
+                .await?,
+        )
+        .await?;
+        if response.get("errors").and_then(Value::as_bool) != Some(true) {
+            return Ok(());
+        }
+        let failure = response
+            .get("items")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item.as_object()?.values().next())
+            .find(|result| {
+                result.get("error").is_some()
+                    && result.get("result").and_then(Value::as_str) != Some("not_found")
+            });
+        match failure {
+            Some(result) => Err(SearchError::Service(
+                result
+                    .pointer("/error/reason")
+                    .and_then(Value::as_str)
+                    .unwrap_or("bulk request failed")
+                    .to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    async fn index_batch(
+        &self,
+        docs: &[IndexedDocument],
+        indexed_at: i64,
+    ) -> Result<(), SearchError> {
+        let mut lines = Vec::with_capacity(docs.len() * 2);
+        for doc in docs {
+            lines.push(json!({ "index": { "_id": doc.id } }));
+            lines.push(doc.to_record(indexed_at));
+        }
+        self.bulk(lines).await
+    }
+
+    async fn document_count(&self) -> Result<u64, SearchError> {
+        let response = self.request(Method::GET, "/_count").send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(0);
+        }
+        let count = service_json(response).await?;
+        Ok(count.get("count").and_then(Value::as_u64).unwrap_or(0))
+    }
+}
+
+#[async_trait]
+impl SearchIndex for OpenSearchIndex {
+    fn backend(&self) -> SearchBackend {
+        SearchBackend::OpenSearch
+    }
+
+    fn location(&self) -> String {
+        format!("{} (index {})", self.url, self.index)
+    }
+
+    async fn update(
+        &self,
+        repo: &DieselDocumentRepository,
+        full: bool,
+    ) -> Result<IndexStats, SearchError> {
+        let started = Utc::now();
+        // An index without a journal position needs a full update
+        let since = if full { None } else { self.state.change_seq() };
+        let full = since.is_none();
+        let through = repo.latest_change_seq().await?;
+        self.ensure_index().await?;
+
+        // Deletions first, so a document deleted and re-created since is
+        // indexed again
+        let deleted = deleted_since(repo, since).await?;
+        for ids in deleted.chunks(1000) {
+            let lines = ids
+                .iter()
+                .map(|id| json!({ "delete": { "_id": id } }))
+                .collect();
+            self.bulk(lines).await?;
+        }
+
+        let mut feed = ChangeFeed::new(repo, since);
+        let mut indexed = 0u64;
+        while let Some(docs) = feed.next_batch().await? {
+            if docs.is_empty() {
+                continue;
+            }
+            self.index_batch(&docs, started.timestamp_millis()).await?;
+            indexed += docs.len() as u64;
+        }
+
+        service_json(self.request(Method::POST, "/_refresh").send().await?).await?;
+        // Records not rewritten by a full update are of deleted documents
+        if full {
+            service_json(
+                self.request(Method::POST, "/_delete_by_query?refresh=true")
+                    .json(&json!({
+                        "query": {
+                            "range": { "indexed_at": { "lt": started.timestamp_millis() } }
+                        }
+                    }))
+                    .send()
+                    .await?,
+            )
+            .await?;
+        }
+
+        self.state.record(started, through)?;
+        Ok(IndexStats {
+            indexed,
+            total: self.document_count().await?,
+            deleted: deleted.len() as u64,
+        })
+    }
+
+    async fn search(&self, request: &SearchRequest) -> Result<SearchResults, SearchError> {
+        let terms = |field: &str| json!({ "terms": { "field": field, "size": FACET_VALUES } });
+        let body = json!({
+            "from": request.offset,
+            "size": request.limit,
+            "track_total_hits": true,
+            "_source": ["id", "source_id", "title"],
+            "query": query(request),
+            "aggs": {
+                "sources": terms("source_id"),
+                "tags": terms("tags"),
+                "years": terms("year"),
+                "entity_types": terms("entity_types"),
+            },
+            "highlight": {
+                "pre_tags": ["<b>"],
+                "post_tags": ["</b>"],
+                "fields": {
+                    "body": { "fragment_size": SNIPPET_CHARS, "number_of_fragments": 1 }
+                }
+            },
+        });
+        let response = service_json(
+            self.request(Method::POST, "/_search")
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await?;
+
+        let hits = response
+            .pointer("/hits/hits")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|hit| {
+                let text = |key: &str| {
+                    hit.pointer(&format!("/_source/{}", key))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                SearchHit {
+                    document_id: text("id"),
+                    source_id: text("source_id"),
+                    title: text("title"),
+                    score: hit
+                        .get("_score")
+                        .and_then(Value::as_f64)
+                        .unwrap_or_default() as f32,
+                    snippet: hit
+                        .pointer("/highlight/body/0")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                }
+            })
+            .collect();
+
+        let facet = |name: &str| -> Vec<FacetCount> {
+            let counts = response
+                .pointer(&format!("/aggregations/{}/buckets", name))
+                .and_then(Value::as_array)
+                .map(|buckets| {
+                    buckets
+                        .iter()
+                        .filter_map(|bucket| {
+                            Some(FacetCount {
+                                value: bucket.get("key")?.as_str()?.to_string(),
+                                count: bucket.get("doc_count")?.as_u64()?,
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            top_facets(counts)
+        };
+
+        Ok(SearchResults {
+            total: response
+                .pointer("/hits/total/value")
+                .and_then(Value::as_u64)
+                .unwrap_or_default(),
+            hits,
+            facets: SearchFacets {
+                sources: facet("sources"),
+                tags: facet("tags"),
+                years: facet("years"),
+                entity_types: facet("entity_types"),
+            },
+        })
+    }
+
+    async fn status(&self) -> Result<IndexStatus, SearchError> {
+        Ok(IndexStatus {
+            documents: self.document_count().await?,
+            indexed_through: self.state.indexed_through(),
+        })
+    }
+}
+
+/// Bool query for a search: text (any of the query and its expansions,
+/// all words required) plus filters.
+fn query(request: &SearchRequest) -> Value {
+    let text = |query: &str| {
+        json!({
+            "multi_match": { "query": query, "fields": SEARCH_FIELDS, "operator": "and" }
+        })
+    };
+    let must = if request.query.trim().is_empty() {
+        json!({ "match_all": {} })
+    } else if request.expansions.is_empty() {
+        text(&request.query)
+    } else {
+        let any: Vec<Value> = std::iter::once(request.query.as_str())
+            .chain(request.expansions.iter().map(String::as_str))
+            .map(text)
+            .collect();
+        json!({ "bool": { "should": any, "minimum_should_match": 1 } })
+    };
+
+    let mut filter = Vec::new();
+    if let Some(source) = &request.source_id {
+        filter.push(json!({ "term": { "source_id": source } }));
+    }
+    for tag in &request.tags {
+        filter.push(json!({ "term": { "tags": tag } }));
+    }
+    let mut dates = serde_json::Map::new();
+    if let Some(from) = request.date_from {
+        dates.insert(
+            "gte".to_string(),
+            json!(from.format("%Y-%m-%d").to_string()),
+        );
+    }
+    if let Some(to) = request.date_to {
+        dates.insert("lte".to_string(), json!(to.format("%Y-%m-%d").to_string()));
+    }
+    if !dates.is_empty() {
+        filter.push(json!({ "range": { "date": dates } }));
+    }
+    let mut must_not = Vec::new();
+    if let Some(access) = &request.access {
+        access_clauses(access, &mut filter, &mut must_not);
+    }
+
+    json!({ "bool": { "must": [must], "filter": filter, "must_not": must_not } })
+}
+
+/// Clauses leaving out what an [`AccessFilter`] hides.
+fn access_clauses(access: &AccessFilter, filter: &mut Vec<Value>, must_not: &mut Vec<Value>) {
+    if !access.hidden_documents.is_empty() {
+        must_not.push(json!({ "terms": { "id": access.hidden_documents } }));
+    }
+    if !access.hidden_sources.is_empty() {
+        let hidden = json!({ "terms": { "source_id": access.hidden_sources } });
+        must_not.push(if access.released_documents.is_empty() {
+            hidden
+        } else {
+            json!({
+                "bool": {
+                    "filter": [hidden],
+                    "must_not": [{ "terms": { "id": access.released_documents } }]
+                }
+            })
+        });
+    }
+    if let Some(only) = &access.only_sources {
+        filter.push(json!({ "terms": { "source_id": only } }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_combines_expansions_and_filters() {
+        let request = SearchRequest {
+            query: "OIG audit".to_string(),
+            expansions: vec!["Office of Inspector General audit".to_string()],
+            tags: vec!["budget".to_string()],
+            date_to: chrono::NaiveDate::from_ymd_opt(2001, 12, 31),
+            access: Some(AccessFilter {
+                hidden_documents: vec!["doc-9".to_string()],
+                only_sources: Some(vec!["fbi".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let query = query(&request);
+
+        let should = query
+            .pointer("/bool/must/0/bool/should")
+            .and_then(Value::as_array)
+            .unwrap();
+        assert_eq!(should.len(), 2);
+        assert_eq!(
+            should[1].pointer("/multi_match/query"),
+            Some(&json!("Office of Inspector General audit"))
+        );
+        assert_eq!(
+            query.pointer("/bool/filter"),
+            Some(&json!([
+                { "term": { "tags": "budget" } },
+                { "range": { "date": { "lte": "2001-12-31" } } },
+                { "terms": { "source_id": ["fbi"] } },
+            ]))
+        );
+        assert_eq!(
+            query.pointer("/bool/must_not"),
+            Some(&json!([{ "terms": { "id": ["doc-9"] } }]))
+        );
+    }
+
+    #[test]
+    fn test_empty_query_matches_all() {
+        let query = query(&SearchRequest::default());
+        assert_eq!(
+            query.pointer("/bool/must/0"),
+            Some(&json!({ "match_all": {} }))
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! What an index update reads from the database.
+//!
+//! Every backend indexes the same thing: documents changed since the change
+//! journal entry its last update read through (their attachment text
+//! included), and the IDs of documents deleted since.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::{prepare_batch, IndexedDocument, SearchError};
+use crate::models::Document;
+use crate::repository::diesel_document::{StreamFilter, DEFAULT_STREAM_BATCH};
+use crate::repository::{DieselDocumentRepository, DieselError};
+
+/// Documents to (re)index, a batch at a time.
+pub(crate) struct ChangeFeed<'a> {
+    repo: &'a DieselDocumentRepository,
+    stream: BoxStream<'static, Result<Document, DieselError>>,
+}
+
+impl<'a> ChangeFeed<'a> {
+    /// Documents changed after journal entry `since`, or every document
+    /// when `None`.
+    pub fn new(repo: &'a DieselDocumentRepository, since: Option<i64>) -> Self {
+        let filter = StreamFilter {
+            changed_since: since,
+            ..Default::default()
+        };
+        Self {
+            repo,
+            stream: repo.stream_documents(filter, DEFAULT_STREAM_BATCH),
+        }
+    }
+
+    /// The next batch, ready to index; `None` when there are no more.
+    pub async fn next_batch(&mut self) -> Result<Option<Vec<IndexedDocument>>, DieselError> {
+        let mut docs = Vec::with_capacity(DEFAULT_STREAM_BATCH);
+        while docs.len() < DEFAULT_STREAM_BATCH {
+            match self.stream.try_next().await? {
+                Some(doc) => docs.push(doc),
+                None => break,
+            }
+        }
+        if docs.is_empty() {
+            return Ok(None);
+        }
+        load_batch(self.repo, docs).await.map(Some)
+    }
+}
+
+/// Gather what the index stores about each document: its publication date,
+/// entities, attachment text, and page text when the document has no
+/// extracted text of its own.
+async fn load_batch(
+    repo: &DieselDocumentRepository,
+    mut docs: Vec<Document>,
+) -> Result<Vec<IndexedDocument>, DieselError> {
+    let ids: Vec<String> = docs.iter().map(|d| d.id.clone()).collect();
+    let dates = repo.get_publication_dates(&ids).await?;
+    let entities = repo.get_entities_batch(&ids).await?;
+    let attachments = repo.get_virtual_file_texts(&ids).await?;
+    for doc in docs.iter_mut().filter(|d| d.extracted_text.is_none()) {
+        if let Some(version_id) = doc.current_version().map(|v| v.id) {
+            doc.extracted_text = repo
+                .get_combined_page_text(&doc.id, version_id as i32)
+                .await?;
+        }
+    }
+    Ok(prepare_batch(&docs, &dates, &entities, &attachments))
+}
+
+/// Documents deleted after journal entry `since`. A full update has
+/// nothing to delete.
+pub(crate) async fn deleted_since(
+    repo: &DieselDocumentRepository,
+    since: Option<i64>,
+) -> Result<Vec<String>, DieselError> {
+    match since {
+        Some(since) => repo.get_deleted_documents_since(since, None).await,
+        None => Ok(Vec::new()),
+    }
+}
+
+/// How far a search service's index has been brought up to date, kept in
+/// the data directory since the service has nowhere to put it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncState {
+    /// When the last completed update started.
+    indexed_through: DateTime<Utc>,
+    /// Change journal entry it read through. Missing from state written
+    /// before the journal existed.
+    #[serde(default)]
+    change_seq: Option<i64>,
+}
+
+/// File recording a [`SyncState`].
+#[derive(Debug, Clone)]
+pub(crate) struct SyncStateFile {
+    path: PathBuf,
+}
+
+impl SyncStateFile {
+    /// State of `index` on `backend`, under `data_dir`.
+    pub fn new(data_dir: &Path, backend: &str, index: &str) -> Self {
+        Self {
+            path: data_dir
+                .join("search-sync")
+                .join(format!("{}-{}.json", backend, index)),
+        }
+    }
+
+    fn load(&self) -> Option<SyncState> {
+        let data = std::fs::read(&self.path).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// When the last completed update started.
+    pub fn indexed_through(&self) -> Option<DateTime<Utc>> {
+        self.load().map(|s| s.indexed_through)
+    }
+
+    /// Change journal entry the last completed update read through.
+    pub fn change_seq(&self) -> Option<i64> {
+        self.load().and_then(|s| s.change_seq)
+    }
+
+    pub fn record(
+        &self,
+        indexed_through: DateTime<Utc>,
+        change_seq: i64,
+    ) -> Result<(), SearchError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(&SyncState {
+            indexed_through,
+            change_seq: Some(change_seq),
+        })
+        .map_err(|e| SearchError::Index(e.to_string()))?;
+        std::fs::write(&self.path, data)?;
+        Ok(())
+    }
+}
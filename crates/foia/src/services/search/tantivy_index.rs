@@ -0,0 +1,682 @@
+//! Embedded search index built with tantivy.
+//!
+//! One index document per archive document, keyed by ID. Updates are
+//! incremental: the commit payload records when the last update started,
+//! and the next one re-indexes documents changed since and drops those
+//! deleted since.
+
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tantivy::collector::{Count, FacetCollector, FacetCounts, TopDocs};
+use tantivy::query::{
+    AllQuery, BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery, TermSetQuery,
+};
+use tantivy::schema::{
+    Facet, FacetOptions, Field, IndexRecordOption, Schema, Value, FAST, INDEXED, STORED, STRING,
+    TEXT,
+};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+use super::sync::{deleted_since, ChangeFeed};
+use super::{
+    FacetCount, IndexStats, IndexStatus, IndexedDocument, SearchError, SearchFacets, SearchHit,
+    SearchIndex, SearchRequest, SearchResults, FACET_VALUES, SNIPPET_CHARS,
+};
+use crate::config::SearchBackend;
+use crate::repository::DieselDocumentRepository;
+use crate::services::access::AccessFilter;
+
+/// Memory the index writer may use before flushing a segment.
+const WRITER_HEAP_BYTES: usize = 100_000_000;
+
+impl From<tantivy::TantivyError> for SearchError {
+    fn from(e: tantivy::TantivyError) -> Self {
+        SearchError::Index(e.to_string())
+    }
+}
+
+/// Stored in each commit.
+#[derive(Debug, Serialize, Deserialize)]
+struct CommitPayload {
+    /// When the update started.
+    indexed_through: DateTime<Utc>,
+    /// Change journal entry the update read through. Missing from indexes
+    /// committed before the journal existed.
+    #[serde(default)]
+    change_seq: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fields {
+    id: Field,
+    source_id: Field,
+    title: Field,
+    body: Field,
+    tags: Field,
+    entities: Field,
+    date: Field,
+    source_facet: Field,
+    tag_facet: Field,
+    year_facet: Field,
+    entity_type_facet: Field,
+}
+
+fn build_schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        id: builder.add_text_field("id", STRING | STORED),
+        source_id: builder.add_text_field("source_id", STRING | STORED),
+        title: builder.add_text_field("title", TEXT | STORED),
+        // Stored for snippets
+        body: builder.add_text_field("body", TEXT | STORED),
+        tags: builder.add_text_field("tags", TEXT),
+        entities: builder.add_text_field("entities", TEXT),
+        date: builder.add_date_field("date", INDEXED | FAST),
+        source_facet: builder.add_facet_field("source", FacetOptions::default()),
+        tag_facet: builder.add_facet_field("tag", FacetOptions::default()),
+        year_facet: builder.add_facet_field("year", FacetOptions::default()),
+        entity_type_facet: builder.add_facet_field("entity_type", FacetOptions::default()),
+    };
+    (builder.build(), fields)
+}
+
+fn facet(value: &str) -> Facet {
+    Facet::from_path([value])
+}
+
+fn date_value(date: NaiveDate) -> tantivy::DateTime {
+    let secs = date
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc()
+        .timestamp();
+    tantivy::DateTime::from_timestamp_secs(secs)
+}
+
+/// A tantivy index in a directory of its own.
+pub struct TantivyIndex {
+    dir: PathBuf,
+    index: Index,
+    reader: IndexReader,
+    fields: Fields,
+}
+
+impl TantivyIndex {
+    /// Open the index in `dir`, creating an empty one if there is none.
+    pub fn open(dir: &Path) -> Result<Self, SearchError> {
+        let (schema, fields) = build_schema();
+        let index = if dir.join("meta.json").exists() {
+            let index = Index::open_in_dir(dir)?;
+            if serde_json::to_string(&index.schema()).ok() != serde_json::to_string(&schema).ok() {
+                return Err(SearchError::SchemaMismatch(dir.display().to_string()));
+            }
+            index
+        } else {
+            std::fs::create_dir_all(dir)?;
+            Index::create_in_dir(dir, schema)?
+        };
+        Self::with_index(dir, index, fields)
+    }
+
+    /// Delete any index in `dir` and create an empty one.
+    pub fn recreate(dir: &Path) -> Result<Self, SearchError> {
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)?;
+        }
+        Self::open(dir)
+    }
+
+    fn with_index(dir: &Path, index: Index, fields: Fields) -> Result<Self, SearchError> {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            index,
+            reader,
+            fields,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Documents in the index.
+    pub fn num_docs(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    /// When the last completed update started; documents updated since
+    /// may be missing or stale.
+    pub fn indexed_through(&self) -> Result<Option<DateTime<Utc>>, SearchError> {
+        Ok(self.payload()?.map(|p| p.indexed_through))
+    }
+
+    /// Change journal entry the last completed update read through.
+    fn change_seq(&self) -> Result<Option<i64>, SearchError> {
+        Ok(self.payload()?.and_then(|p| p.change_seq))
+    }
+
+    fn payload(&self) -> Result<Option<CommitPayload>, SearchError> {
+        let metas = self.index.load_metas()?;
+        Ok(metas
+            .payload
+            .and_then(|p| serde_json::from_str::<CommitPayload>(&p).ok()))
+    }
+
+    /// Index documents changed since the last update and drop deleted
+    /// ones, or every document when `full` is set.
+    pub async fn update(
+        &self,
+        repo: &DieselDocumentRepository,
+        full: bool,
+    ) -> Result<IndexStats, SearchError> {
+        let started = Utc::now();
+        // An index without a journal position needs a full update
+        let since = if full { None } else { self.change_seq()? };
+        let full = since.is_none();
+        let through = repo.latest_change_seq().await?;
+        let writer: IndexWriter = self.index.writer(WRITER_HEAP_BYTES)?;
+        if full {
+            writer.delete_all_documents()?;
+        }
+
+        // Deletions first, so a document deleted and re-created since is
+        // indexed again
+        let deleted = deleted_since(repo, since).await?;
+        for id in &deleted {
+            writer.delete_term(Term::from_field_text(self.fields.id, id));
+        }
+        let mut feed = ChangeFeed::new(repo, since);
+        let mut indexed = 0u64;
+        while let Some(docs) = feed.next_batch().await? {
+            self.write(&writer, &docs)?;
+            indexed += docs.len() as u64;
+        }
+
+        self.commit(writer, started, through)?;
+        Ok(IndexStats {
+            indexed,
+            total: self.num_docs(),
+            deleted: deleted.len() as u64,
+        })
+    }
+
+    /// Add or replace documents.
+    fn write(&self, writer: &IndexWriter, docs: &[IndexedDocument]) -> Result<(), SearchError> {
+        let f = &self.fields;
+        for doc in docs {
+            writer.delete_term(Term::from_field_text(f.id, &doc.id));
+            let mut indexed = TantivyDocument::default();
+            indexed.add_text(f.id, &doc.id);
+            indexed.add_text(f.source_id, &doc.source_id);
+            indexed.add_text(f.title, &doc.title);
+            indexed.add_text(f.body, &doc.body);
+            indexed.add_facet(f.source_facet, facet(&doc.source_id));
+            for tag in &doc.tags {
+                indexed.add_text(f.tags, tag);
+                indexed.add_facet(f.tag_facet, facet(tag));
+            }
+            for entity in &doc.entities {
+                indexed.add_text(f.entities, entity);
+            }
+            for entity_type in &doc.entity_types {
+                indexed.add_facet(f.entity_type_facet, facet(entity_type));
+            }
+            if let (Some(date), Some(year)) = (doc.date, doc.year()) {
+                indexed.add_date(f.date, date_value(date));
+                indexed.add_facet(f.year_facet, facet(&year));
+            }
+            writer.add_document(indexed)?;
+        }
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        mut writer: IndexWriter,
+        started: DateTime<Utc>,
+        change_seq: i64,
+    ) -> Result<(), SearchError> {
+        let payload = serde_json::to_string(&CommitPayload {
+            indexed_through: started,
+            change_seq: Some(change_seq),
+        })
+        .unwrap_or_default();
+        let mut prepared = writer.prepare_commit()?;
+        prepared.set_payload(&payload);
+        prepared.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    /// Run a search, returning one page of hits and facet counts over all
+    /// matches.
+    pub fn search(&self, request: &SearchRequest) -> Result<SearchResults, SearchError> {
+        let f = &self.fields;
+        let searcher = self.reader.searcher();
+
+        let text_query: Option<Box<dyn Query>> = if request.query.trim().is_empty() {
+            None
+        } else {
+            let mut parser =
+                QueryParser::for_index(&self.index, vec![f.title, f.body, f.tags, f.entities]);
+            parser.set_conjunction_by_default();
+            parser.set_field_boost(f.title, 3.0);
+            parser.set_field_boost(f.tags, 2.0);
+            // Stray syntax is searched as plain words rather than rejected
+            let (query, _errors) = parser.parse_query_lenient(&request.query);
+            if request.expansions.is_empty() {
+                Some(query)
+            } else {
+                let mut any: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Should, query)];
+                for expansion in &request.expansions {
+                    any.push((Occur::Should, parser.parse_query_lenient(expansion).0));
+                }
+                Some(Box::new(BooleanQuery::new(any)))
+            }
+        };
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(
+            Occur::Must,
+            text_query
+                .as_ref()
+                .map_or_else(|| Box::new(AllQuery) as Box<dyn Query>, |q| q.box_clone()),
+        )];
+        if let Some(source) = &request.source_id {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(f.source_id, source),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+        for tag in &request.tags {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_facet(f.tag_facet, &facet(tag)),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+        if request.date_from.is_some() || request.date_to.is_some() {
+            let lower = request
+                .date_from
+                .map_or(Bound::Unbounded, |d| Bound::Included(date_value(d)));
+            let upper = request
+                .date_to
+                .map_or(Bound::Unbounded, |d| Bound::Included(date_value(d)));
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_date_bounds(
+                    "date".to_string(),
+                    lower,
+                    upper,
+                )),
+            ));
+        }
+        if let Some(access) = &request.access {
+            clauses.extend(self.access_clauses(access));
+        }
+        let query = BooleanQuery::new(clauses);
+
+        let mut source_facets = FacetCollector::for_field("source");
+        source_facets.add_facet("/");
+        let mut tag_facets = FacetCollector::for_field("tag");
+        tag_facets.add_facet("/");
+        let mut year_facets = FacetCollector::for_field("year");
+        year_facets.add_facet("/");
+        let mut entity_type_facets = FacetCollector::for_field("entity_type");
+        entity_type_facets.add_facet("/");
+
+        let top = TopDocs::with_limit(request.limit.max(1)).and_offset(request.offset);
+        let ((top_docs, total), (sources, tags, years, entity_types)) = searcher.search(
+            &query,
+            &(
+                (top, Count),
+                (source_facets, tag_facets, year_facets, entity_type_facets),
+            ),
+        )?;
+
+        let snippets = match &text_query {
+            Some(q) => {
+                let mut generator = SnippetGenerator::create(&searcher, q.as_ref(), f.body)?;
+                generator.set_max_num_chars(SNIPPET_CHARS);
+                Some(generator)
+            }
+            None => None,
+        };
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, address) in top_docs.into_iter().take(request.limit) {
+            let doc: TantivyDocument = searcher.doc(address)?;
+            let text = |field: Field| {
+                doc.get_first(field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let snippet = snippets
+                .as_ref()
+                .map(|g| g.snippet_from_doc(&doc))
+                .filter(|s| !s.highlighted().is_empty())
+                .map(|s| s.to_html());
+            hits.push(SearchHit {
+                document_id: text(f.id),
+                source_id: text(f.source_id),
+                title: text(f.title),
+                score,
+                snippet,
+            });
+        }
+
+        Ok(SearchResults {
+            total: total as u64,
+            hits,
+            facets: SearchFacets {
+                sources: facet_counts(&sources),
+                tags: facet_counts(&tags),
+                years: facet_counts(&years),
+                entity_types: facet_counts(&entity_types),
+            },
+        })
+    }
+
+    /// Clauses leaving out what an [`AccessFilter`] hides.
+    fn access_clauses(&self, access: &AccessFilter) -> Vec<(Occur, Box<dyn Query>)> {
+        let f = &self.fields;
+        let ids = |ids: &[String]| -> Box<dyn Query> {
+            Box::new(TermSetQuery::new(
+                ids.iter().map(|id| Term::from_field_text(f.id, id)),
+            ))
+        };
+        let sources = |sources: &[String]| -> Box<dyn Query> {
+            Box::new(TermSetQuery::new(
+                sources
+                    .iter()
+                    .map(|s| Term::from_field_text(f.source_id, s)),
+            ))
+        };
+
+        let mut clauses = Vec::new();
+        if !access.hidden_documents.is_empty() {
+            clauses.push((Occur::MustNot, ids(&access.hidden_documents)));
+        }
+        if !access.hidden_sources.is_empty() {
+            let hidden = if access.released_documents.is_empty() {
+                sources(&access.hidden_sources)
+            } else {
+                Box::new(BooleanQuery::new(vec![
+                    (Occur::Must, sources(&access.hidden_sources)),
+                    (Occur::MustNot, ids(&access.released_documents)),
+                ]))
+            };
+            clauses.push((Occur::MustNot, hidden));
+        }
+        if let Some(only) = &access.only_sources {
+            clauses.push((Occur::Must, sources(only)));
+        }
+        clauses
+    }
+}
+
+#[async_trait]
+impl SearchIndex for TantivyIndex {
+    fn backend(&self) -> SearchBackend {
+        SearchBackend::Tantivy
+    }
+
+    fn location(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    async fn update(
+        &self,
+        repo: &DieselDocumentRepository,
+        full: bool,
+    ) -> Result<IndexStats, SearchError> {
+        TantivyIndex::update(self, repo, full).await
+    }
+
+    async fn search(&self, request: &SearchRequest) -> Result<SearchResults, SearchError> {
+        TantivyIndex::search(self, request)
+    }
+
+    async fn status(&self) -> Result<IndexStatus, SearchError> {
+        Ok(IndexStatus {
+            documents: self.num_docs(),
+            indexed_through: self.indexed_through()?,
+        })
+    }
+}
+
+fn facet_counts(counts: &FacetCounts) -> Vec<FacetCount> {
+    counts
+        .top_k("/", FACET_VALUES)
+        .into_iter()
+        .map(|(facet, count)| FacetCount {
+            value: facet
+                .to_path()
+                .last()
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+            count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexed(id: &str, source: &str, title: &str, body: &str, tags: &[&str]) -> IndexedDocument {
+        IndexedDocument {
+            id: id.to_string(),
+            source_id: source.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            date: NaiveDate::from_ymd_opt(1998, 4, 2),
+            entities: vec!["Langley".to_string()],
+            entity_types: vec!["location".to_string()],
+        }
+    }
+
+    fn index_with(docs: &[IndexedDocument]) -> (TantivyIndex, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let index = TantivyIndex::open(dir.path()).unwrap();
+        let writer = index.index.writer(WRITER_HEAP_BYTES).unwrap();
+        index.write(&writer, docs).unwrap();
+        index.commit(writer, Utc::now(), 0).unwrap();
+        (index, dir)
+    }
+
+    fn search(index: &TantivyIndex, query: &str) -> SearchResults {
+        index
+            .search(&SearchRequest {
+                query: query.to_string(),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_search_ranks_and_counts_facets() {
+        let (index, _dir) = index_with(&[
+            indexed(
+                "a",
+                "fbi",
+                "Surveillance memo",
+                "memo on wiretaps",
+                &["wiretap"],
+            ),
+            indexed("b", "cia", "Budget", "the wiretap budget", &["budget"]),
+            indexed("c", "cia", "Travel", "travel vouchers", &["budget"]),
+        ]);
+        assert_eq!(index.num_docs(), 3);
+        assert!(index.indexed_through().unwrap().is_some());
+
+        let results = search(&index, "wiretap");
+        assert_eq!(results.total, 2);
+        // The tag match outranks the body-only match
+        assert_eq!(results.hits[0].document_id, "a");
+        assert!(results.hits[1]
+            .snippet
+            .as_deref()
+            .unwrap()
+            .contains("<b>wiretap</b>"));
+        let mut sources: Vec<_> = results
+            .facets
+            .sources
+            .iter()
+            .map(|f| (f.value.as_str(), f.count))
+            .collect();
+        sources.sort();
+        assert_eq!(sources, [("cia", 1), ("fbi", 1)]);
+
+        // An empty query browses everything
+        let all = search(&index, "");
+        assert_eq!(all.total, 3);
+        assert_eq!(all.facets.tags[0].value, "budget");
+        assert_eq!(all.facets.tags[0].count, 2);
+        assert_eq!(all.facets.years[0].value, "1998");
+
+        let filtered = index
+            .search(&SearchRequest {
+                tags: vec!["budget".to_string()],
+                source_id: Some("cia".to_string()),
+                query: "travel".to_string(),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(filtered.total, 1);
+        assert_eq!(filtered.hits[0].document_id, "c");
+
+        // Alias expansions widen the match
+        let expanded = index
+            .search(&SearchRequest {
+                query: "vouchers".to_string(),
+                expansions: vec!["surveillance memo".to_string()],
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(expanded.total, 2);
+    }
+
+    #[test]
+    fn test_search_respects_access() {
+        let (index, _dir) = index_with(&[
+            indexed("a", "fbi", "Memo", "memo", &[]),
+            indexed("b", "fbi", "Released memo", "memo", &[]),
+            indexed("c", "cia", "Memo", "memo", &[]),
+        ]);
+        let request = |access: AccessFilter| SearchRequest {
+            query: "memo".to_string(),
+            limit: 10,
+            access: Some(access),
+            ..Default::default()
+        };
+
+        let results = index
+            .search(&request(AccessFilter {
+                hidden_sources: vec!["fbi".to_string()],
+                released_documents: vec!["b".to_string()],
+                ..Default::default()
+            }))
+            .unwrap();
+        let mut ids: Vec<_> = results
+            .hits
+            .iter()
+            .map(|h| h.document_id.as_str())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["b", "c"]);
+        assert_eq!(results.facets.sources.len(), 2);
+
+        let results = index
+            .search(&request(AccessFilter {
+                hidden_documents: vec!["a".to_string()],
+                only_sources: Some(vec!["fbi".to_string()]),
+                ..Default::default()
+            }))
+            .unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].document_id, "b");
+    }
+
+    #[tokio::test]
+    async fn test_update_indexes_attachments_and_drops_deleted() {
+        use crate::models::{Document, DocumentStatus, VirtualFile, VirtualFileStatus};
+        use crate::repository::diesel_document::tests::setup_test_db;
+
+        let (pool, _db_dir) = setup_test_db().await;
+        let repo = DieselDocumentRepository::new(pool);
+        for (id, title) in [("doc-1", "Release bundle"), ("doc-2", "Cover letter")] {
+            repo.save(&Document {
+                id: id.to_string(),
+                source_id: "agency".to_string(),
+                title: title.to_string(),
+                source_url: format!("https://agency.gov/{}.zip", id),
+                extracted_text: None,
+                synopsis: None,
+                tags: vec![],
+                status: DocumentStatus::Pending,
+                metadata: serde_json::json!({}),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                discovery_method: "seed".to_string(),
+                versions: vec![],
+            })
+            .await
+            .unwrap();
+        }
+        let mut vf = VirtualFile::new(
+            "doc-1".to_string(),
+            1,
+            "letters/a.pdf".to_string(),
+            "a.pdf".to_string(),
+            "application/pdf".to_string(),
+            1024,
+        );
+        vf.status = VirtualFileStatus::OcrComplete;
+        vf.extracted_text = Some("Surveillance of Langley".to_string());
+        repo.insert_virtual_file(&vf).await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let index = TantivyIndex::open(dir.path()).unwrap();
+        let stats = index.update(&repo, true).await.unwrap();
+        assert_eq!((stats.indexed, stats.total), (2, 2));
+        let results = search(&index, "surveillance");
+        assert_eq!(results.hits.len(), 1);
+        assert_eq!(results.hits[0].document_id, "doc-1");
+
+        repo.delete("doc-2").await.unwrap();
+        let mut vf = VirtualFile::new(
+            "doc-1".to_string(),
+            1,
+            "letters/b.pdf".to_string(),
+            "b.pdf".to_string(),
+            "application/pdf".to_string(),
+            1024,
+        );
+        vf.extracted_text = Some("Travel vouchers".to_string());
+        repo.insert_virtual_file(&vf).await.unwrap();
+        let stats = index.update(&repo, false).await.unwrap();
+        assert_eq!((stats.indexed, stats.deleted, stats.total), (1, 1, 1));
+        assert!(search(&index, "cover").hits.is_empty());
+        assert_eq!(search(&index, "vouchers").hits.len(), 1);
+    }
+}
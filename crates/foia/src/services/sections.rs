@@ -0,0 +1,671 @@
+//! Chapter and section structure of long reports.
+//!
+//! A document's table of contents comes from its PDF bookmarks when it
+//! has them. Reports without bookmarks get one from headings found in
+//! the page text:
+//!
+//! - `Chapter 3`, `Part II`, `Appendix A` lines
+//! - numbered headings (`4.`, `4.2`) that count up through the report
+//! - short ALL-CAPS lines standing on their own, when nothing else is found
+//!
+//! Each section runs to the page before the next section at the same or
+//! a higher level, so the reader can list it, search within it and export
+//! its pages.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::models::{Document, DocumentPage, DocumentSection};
+use crate::repository::DieselDocumentRepository;
+use crate::storage;
+
+/// Fewest pages for headings to be looked for; shorter documents read
+/// fine without a table of contents.
+pub const MIN_PAGES: usize = 10;
+
+/// Fewest sections worth a table of contents.
+const MIN_SECTIONS: usize = 2;
+
+/// Longest line taken for a heading.
+const MAX_HEADING_CHARS: usize = 80;
+
+/// Most words in a numbered heading; longer lines are list items.
+const MAX_HEADING_WORDS: usize = 12;
+
+/// A line on more than this share of pages is a running header or footer.
+const MAX_REPEAT_SHARE: f32 = 0.2;
+
+/// Characters of context on each side of a search match.
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Where a document's table of contents came from.
+pub const ORIGIN_BOOKMARKS: &str = "bookmarks";
+pub const ORIGIN_HEADINGS: &str = "headings";
+
+static CHAPTER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?i:chapter|part|appendix|annex)\s+([0-9]{1,3}|[IVXLC]{1,7}|[A-Z])\b[\s.:\-–—]*(.*)$",
+    )
+    .unwrap()
+});
+
+static NUMBERED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{1,2})(?:\.(\d{1,2}))?\.?\s+(\p{Lu}.*)$").unwrap());
+
+/// A table of contents line: dot leaders or a wide gap before a page number.
+static TOC_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\.{4,}|\s{3,}|…+)\s*\d{1,4}$").unwrap());
+
+static OUTLINE_TOKEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<outline>|</outline>|<item(?:\s+page="(\d+)")?\s*>(.*?)</item>"#).unwrap()
+});
+
+/// An entry of a table of contents before its page range is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// Nesting depth, 1 for top-level entries.
+    pub level: u32,
+    pub title: String,
+    pub page: u32,
+}
+
+/// Read the bookmarks from the XML written by `pdftohtml -xml`.
+///
+/// Bookmarks that don't point at a page are skipped.
+pub fn parse_outline(xml: &str) -> Vec<OutlineEntry> {
+    let mut entries = Vec::new();
+    let mut depth = 0u32;
+    for token in OUTLINE_TOKEN.captures_iter(xml) {
+        match &token[0] {
+            "<outline>" => depth += 1,
+            "</outline>" => depth = depth.saturating_sub(1),
+            _ => {
+                let Some(page) = token.get(1).and_then(|p| p.as_str().parse().ok()) else {
+                    continue;
+                };
+                let title = unescape(&token[2]);
+                let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+                if page > 0 && !title.is_empty() {
+                    entries.push(OutlineEntry {
+                        level: depth.max(1),
+                        title,
+                        page,
+                    });
+                }
+            }
+        }
+    }
+    entries
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';').filter(|&end| end <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let decoded = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            entity => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16).ok())
+                .unwrap_or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Find chapter and section headings in the text of a long report, one
+/// string per page in page order.
+pub fn detect_headings(pages: &[String]) -> Vec<OutlineEntry> {
+    if pages.len() < MIN_PAGES {
+        return Vec::new();
+    }
+    let repeated = repeated_lines(pages);
+    let lines: Vec<Vec<&str>> = pages
+        .iter()
+        .map(|text| text.lines().map(str::trim).collect())
+        .collect();
+
+    let mut structured = Vec::new();
+    let mut caps = Vec::new();
+    let mut chapter = 0u32;
+    let mut numbered: Option<(u32, u32)> = None;
+    for (index, page_lines) in lines.iter().enumerate() {
+        let page = index as u32 + 1;
+        // The report's own contents page lists every heading; skip it
+        let toc_lines = page_lines.iter().filter(|l| TOC_LINE.is_match(l)).count();
+        if toc_lines >= 3 {
+            continue;
+        }
+
+        for (i, line) in page_lines.iter().enumerate() {
+            if line.is_empty()
+                || line.chars().count() > MAX_HEADING_CHARS
+                || repeated.contains_key(*line)
+                || TOC_LINE.is_match(line)
+            {
+                continue;
+            }
+
+            if let Some(m) = CHAPTER.captures(line) {
+                let mut title = m[2].trim().to_string();
+                if title.is_empty() {
+                    // "CHAPTER 3" with the title on the next line
+                    if let Some(next) = page_lines[i + 1..].iter().find(|l| !l.is_empty()) {
+                        if next.chars().count() <= MAX_HEADING_CHARS && !is_sentence(next) {
+                            title = next.to_string();
+                        }
+                    }
+                }
+                let label = line[..m.get(1).unwrap().end()].trim().to_string();
+                chapter += 1;
+                structured.push(OutlineEntry {
+                    level: 1,
+                    title: if title.is_empty() {
+                        label
+                    } else {
+                        format!("{}: {}", label, title)
+                    },
+                    page,
+                });
+                continue;
+            }
+
+            if let Some(m) = NUMBERED.captures(line) {
+                let title = &m[3];
+                let major: u32 = m[1].parse().unwrap_or(0);
+                let minor: Option<u32> = m.get(2).and_then(|m| m.as_str().parse().ok());
+                let next = match (numbered, minor) {
+                    (None, None) => major == 1,
+                    (Some((m, _)), None) => major == m + 1,
+                    (Some((m, n)), Some(sub)) => major == m && sub == n + 1,
+                    (None, Some(_)) => false,
+                };
+                if next
+                    && title.split_whitespace().count() <= MAX_HEADING_WORDS
+                    && !is_sentence(title)
+                {
+                    numbered = Some((major, minor.unwrap_or(0)));
+                    structured.push(OutlineEntry {
+                        level: if minor.is_some() { 2 } else { 1 },
+                        title: line.to_string(),
+                        page,
+                    });
+                    continue;
+                }
+            }
+
+            let isolated = (i == 0 || page_lines[i - 1].is_empty())
+                && page_lines.get(i + 1).is_none_or(|next| next.is_empty());
+            if isolated && is_caps_heading(line) {
+                caps.push(OutlineEntry {
+                    level: 1,
+                    title: line.to_string(),
+                    page,
+                });
+            }
+        }
+    }
+
+    // A chapter heading also numbered "1." is one entry, not two
+    if chapter > 0 {
+        structured.dedup_by(|b, a| a.page == b.page && a.level == b.level);
+    }
+    let mut found = if structured.len() >= MIN_SECTIONS {
+        structured
+    } else {
+        caps
+    };
+    found.dedup_by(|b, a| a.page == b.page && a.title == b.title);
+    found
+}
+
+/// Lines that appear on many pages: running headers, footers and markings.
+fn repeated_lines(pages: &[String]) -> HashMap<&str, usize> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for text in pages {
+        let mut seen: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        seen.sort_unstable();
+        seen.dedup();
+        for line in seen {
+            *counts.entry(line).or_default() += 1;
+        }
+    }
+    let limit = ((pages.len() as f32 * MAX_REPEAT_SHARE) as usize).max(2);
+    counts.retain(|_, count| *count > limit);
+    counts
+}
+
+fn is_sentence(line: &str) -> bool {
+    line.ends_with(['.', ',', ';', ':'])
+}
+
+fn is_caps_heading(line: &str) -> bool {
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    let visible = line.chars().filter(|c| !c.is_whitespace()).count();
+    let upper = letters.iter().filter(|c| c.is_uppercase()).count();
+    letters.len() >= 4
+        && line.split_whitespace().count() <= MAX_HEADING_WORDS
+        && letters.len() * 2 >= visible
+        && upper == letters.len()
+        && !is_sentence(line)
+}
+
+/// Give each entry the pages up to the next entry at its level or above.
+///
+/// Entries pointing past the last page are dropped.
+pub fn build_sections(
+    entries: &[OutlineEntry],
+    page_count: u32,
+    document_id: &str,
+    version_id: i64,
+    origin: &str,
+) -> Vec<DocumentSection> {
+    let entries: Vec<&OutlineEntry> = entries
+        .iter()
+        .filter(|e| e.page >= 1 && e.page <= page_count)
+        .collect();
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let end = entries[i + 1..]
+                .iter()
+                .find(|next| next.level <= entry.level)
+                .map(|next| next.page.saturating_sub(1))
+                .unwrap_or(page_count);
+            DocumentSection {
+                id: 0,
+                document_id: document_id.to_string(),
+                version_id,
+                level: entry.level,
+                title: entry.title.clone(),
+                first_page: entry.page,
+                last_page: end.max(entry.page),
+                origin: origin.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// A page of a section containing a search term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionMatch {
+    pub page_number: u32,
+    /// Text around the first match on the page.
+    pub snippet: String,
+}
+
+fn page_text(page: &DocumentPage) -> &str {
+    page.final_text
+        .as_deref()
+        .or(page.ocr_text.as_deref())
+        .or(page.pdf_text.as_deref())
+        .unwrap_or_default()
+}
+
+/// Pages of a section containing any of `terms`, ignoring case, in page
+/// order.
+pub fn search_section(
+    pages: &[DocumentPage],
+    section: &DocumentSection,
+    terms: &[String],
+) -> Vec<SectionMatch> {
+    let alternatives: Vec<String> = terms
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .map(regex::escape)
+        .collect();
+    if alternatives.is_empty() {
+        return Vec::new();
+    }
+    let Ok(pattern) = Regex::new(&format!("(?i){}", alternatives.join("|"))) else {
+        return Vec::new();
+    };
+
+    pages
+        .iter()
+        .filter(|p| (section.first_page..=section.last_page).contains(&p.page_number))
+        .filter_map(|page| {
+            let text = page_text(page);
+            let found = pattern.find(text)?;
+            Some(SectionMatch {
+                page_number: page.page_number,
+                snippet: snippet(text, found.start(), found.end()),
+            })
+        })
+        .collect()
+}
+
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let to = text[end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map(|(i, _)| end + i)
+        .unwrap_or(text.len());
+    let mut out = text[from..to]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if from > 0 {
+        out.insert(0, '…');
+    }
+    if to < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+/// The text of a section's pages, headed by its title and page markers.
+pub fn section_text(pages: &[DocumentPage], section: &DocumentSection) -> String {
+    let mut out = format!("{}\n", section.title);
+    for page in pages
+        .iter()
+        .filter(|p| (section.first_page..=section.last_page).contains(&p.page_number))
+    {
+        out.push_str(&format!("\n--- Page {} ---\n\n", page.page_number));
+        out.push_str(page_text(page).trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Find the table of contents of a document's current version.
+///
+/// PDF bookmarks are used when poppler's `pdftohtml` is installed and the
+/// PDF has them; otherwise headings are looked for in the page text.
+/// Empty for documents without a usable structure.
+pub async fn find_sections(
+    doc_repo: &DieselDocumentRepository,
+    doc: &Document,
+    documents_dir: &Path,
+) -> anyhow::Result<Vec<DocumentSection>> {
+    let version = doc
+        .current_version()
+        .ok_or_else(|| anyhow::anyhow!("Document {} has no versions", doc.id))?;
+    let pages = doc_repo.get_pages(&doc.id, version.id as i32).await?;
+    let page_count = pages.iter().map(|p| p.page_number).max().unwrap_or(0);
+
+    let mut entries = Vec::new();
+    let mut origin = ORIGIN_BOOKMARKS;
+    if version.mime_type == "application/pdf" && which::which("pdftohtml").is_ok() {
+        let stored = version.resolve_path(documents_dir, &doc.source_url, &doc.title);
+        let pdf = storage::plaintext_path(&stored)?;
+        entries = read_bookmarks(pdf.path()).await?;
+    }
+    if entries.len() < MIN_SECTIONS {
+        let mut texts = vec![String::new(); page_count as usize];
+        for page in &pages {
+            texts[page.page_number as usize - 1] = page_text(page).to_string();
+        }
+        entries = detect_headings(&texts);
+        origin = ORIGIN_HEADINGS;
+    }
+
+    let sections = build_sections(&entries, page_count, &doc.id, version.id, origin);
+    if sections.len() < MIN_SECTIONS {
+        return Ok(Vec::new());
+    }
+    Ok(sections)
+}
+
+/// Find and store the table of contents of a document's current version,
+/// replacing any earlier one. Returns the stored sections.
+pub async fn derive_sections(
+    doc_repo: &DieselDocumentRepository,
+    doc: &Document,
+    documents_dir: &Path,
+) -> anyhow::Result<Vec<DocumentSection>> {
+    let sections = find_sections(doc_repo, doc, documents_dir).await?;
+    let Some(version) = doc.current_version() else {
+        return Ok(Vec::new());
+    };
+    doc_repo
+        .replace_document_sections(&doc.id, version.id, &sections)
+        .await?;
+    Ok(doc_repo.get_document_sections(&doc.id, version.id).await?)
+}
+
+/// The PDF's bookmarks; page text is limited to the first page since only
+/// the outline is wanted.
+async fn read_bookmarks(pdf: &Path) -> anyhow::Result<Vec<OutlineEntry>> {
+    let result = tokio::process::Command::new("pdftohtml")
+        .args(["-xml", "-i", "-q", "-stdout", "-f", "1", "-l", "1"])
+        .arg(pdf)
+        .output()
+        .await?;
+    if !result.status.success() {
+        anyhow::bail!(
+            "pdftohtml failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+    Ok(parse_outline(&String::from_utf8_lossy(&result.stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: u32, title: &str, page: u32) -> OutlineEntry {
+        OutlineEntry {
+            level,
+            title: title.to_string(),
+            page,
+        }
+    }
+
+    fn report(pages: &[(u32, &str)], count: usize) -> Vec<String> {
+        let mut texts: Vec<String> = (1..=count)
+            .map(|n| {
+                format!(
+                    "OFFICE OF THE INSPECTOR GENERAL\n\nBody text on page {} runs on for a while.\n\n{}",
+                    n, n
+                )
+            })
+            .collect();
+        for (page, heading) in pages {
+            let text = &mut texts[*page as usize - 1];
+            *text = format!("{}\n\n{}", heading, text);
+        }
+        texts
+    }
+
+    #[test]
+    fn test_parse_outline() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<pdf2xml producer="poppler" version="23.02.0">
+<page number="1" position="absolute" top="0" left="0" height="1188" width="918">
+<text top="81" left="108" width="200" height="17" font="0">Executive &amp; Summary</text>
+</page>
+<outline>
+<item page="1">Executive Summary</item>
+<item page="3">Chapter 1: Background</item>
+<outline>
+<item page="3">1.1 Scope &amp; Method</item>
+<item>Unlinked</item>
+<item page="5">1.2 Findings&#x2019; Basis</item>
+</outline>
+<item page="9">Appendix   A</item>
+</outline>
+</pdf2xml>"#;
+        assert_eq!(
+            parse_outline(xml),
+            vec![
+                entry(1, "Executive Summary", 1),
+                entry(1, "Chapter 1: Background", 3),
+                entry(2, "1.1 Scope & Method", 3),
+                entry(2, "1.2 Findings\u{2019} Basis", 5),
+                entry(1, "Appendix A", 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_sections() {
+        let entries = [
+            entry(1, "Summary", 1),
+            entry(1, "Background", 3),
+            entry(2, "Scope", 3),
+            entry(2, "Findings", 5),
+            entry(1, "Appendix", 9),
+            entry(1, "Broken", 40),
+        ];
+        let sections = build_sections(&entries, 12, "doc", 1, ORIGIN_BOOKMARKS);
+        let ranges: Vec<(&str, u32, u32)> = sections
+            .iter()
+            .map(|s| (s.title.as_str(), s.first_page, s.last_page))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ("Summary", 1, 2),
+                ("Background", 3, 8),
+                ("Scope", 3, 4),
+                ("Findings", 5, 8),
+                ("Appendix", 9, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_chapters_and_numbered_headings() {
+        let mut pages = report(
+            &[
+                (3, "CHAPTER 1\nIntroduction"),
+                (4, "1.1 Purpose of the Review"),
+                (7, "1.2 Scope and Methodology"),
+                (10, "Chapter 2 - Findings"),
+                (14, "Appendix A: Agency Comments"),
+            ],
+            16,
+        );
+        // The report's contents page and a numbered list in the body
+        pages[1] = "CONTENTS\n\nIntroduction ........ 3\nFindings ........ 10\nAgency Comments ........ 14"
+            .to_string();
+        pages[11].push_str("\n\n1. The agency should revise its policy.\n2. Train staff.");
+
+        let titles: Vec<(u32, String, u32)> = detect_headings(&pages)
+            .into_iter()
+            .map(|e| (e.level, e.title, e.page))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                (1, "CHAPTER 1: Introduction".to_string(), 3),
+                (1, "Chapter 2: Findings".to_string(), 10),
+                (1, "Appendix A: Agency Comments".to_string(), 14),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_numbered_headings() {
+        let pages = report(
+            &[
+                (2, "1. Background"),
+                (5, "2. Findings"),
+                (6, "2.1 Contract Oversight"),
+                (8, "2.2 Payments"),
+                (9, "4. Not the next heading"),
+                (11, "3. Recommendations"),
+            ],
+            12,
+        );
+        let found: Vec<(u32, u32)> = detect_headings(&pages)
+            .iter()
+            .map(|e| (e.level, e.page))
+            .collect();
+        assert_eq!(found, vec![(1, 2), (1, 5), (2, 6), (2, 8), (1, 11)]);
+    }
+
+    #[test]
+    fn test_caps_headings_skip_running_headers() {
+        let pages = report(&[(1, "EXECUTIVE SUMMARY"), (6, "RESULTS OF REVIEW")], 12);
+        let found: Vec<String> = detect_headings(&pages)
+            .into_iter()
+            .map(|e| e.title)
+            .collect();
+        assert_eq!(found, vec!["EXECUTIVE SUMMARY", "RESULTS OF REVIEW"]);
+
+        // Short documents get no detected structure
+        assert!(detect_headings(&pages[..5]).is_empty());
+    }
+
+    fn page(number: u32, text: &str) -> DocumentPage {
+        let mut page = DocumentPage::new("doc".to_string(), 1, number);
+        page.final_text = Some(text.to_string());
+        page
+    }
+
+    #[test]
+    fn test_search_and_export_section() {
+        let section = build_sections(
+            &[entry(1, "A", 1), entry(1, "Findings", 2)],
+            3,
+            "doc",
+            1,
+            ORIGIN_HEADINGS,
+        )
+        .remove(1);
+        let filler = "word ".repeat(20);
+        let pages = vec![
+            page(1, "The contractor was paid twice."),
+            page(
+                2,
+                &format!("{}The CONTRACTOR   billed\nlate. {}", filler, filler),
+            ),
+            page(3, "No matches here."),
+        ];
+
+        let found = search_section(&pages, &section, &["contractor".to_string()]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].page_number, 2);
+        assert!(found[0].snippet.starts_with('…'));
+        assert!(found[0].snippet.contains("The CONTRACTOR billed late."));
+        assert!(found[0].snippet.ends_with('…'));
+        assert!(search_section(&pages, &section, &[" ".to_string()]).is_empty());
+
+        let text = section_text(&pages, &section);
+        assert!(text.starts_with("Findings\n\n--- Page 2 ---\n"));
+        assert!(text.contains("--- Page 3 ---\n\nNo matches here.\n"));
+        assert!(!text.contains("paid twice"));
+    }
+}
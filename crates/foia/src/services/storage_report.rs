@@ -0,0 +1,370 @@
+//! Where the workspace's disk space goes, and what can be reclaimed.
+//!
+//! Usage is broken down by source, by MIME category, and into originals
+//! (downloaded files), derived artifacts (intermediate OCR and analysis
+//! output) and final text. Three kinds of waste are measured separately,
+//! each with the command that reclaims it: duplicate copies of identical
+//! content, documents whose processing failed, and files in the documents
+//! directory that no version refers to.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use futures::StreamExt;
+use serde::Serialize;
+
+use crate::repository::diesel_document::{Projection, StreamFilter, DEFAULT_STREAM_BATCH};
+use crate::repository::{DieselDocumentRepository, DieselError};
+use crate::utils::mime_type_category;
+
+/// Files modified more recently than this are never orphans: a download
+/// writes its file before recording the version.
+const ORPHAN_MIN_AGE: Duration = Duration::from_secs(3600);
+
+/// Stored files and bytes under one key (a source, MIME category, ...).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub key: String,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Bytes by kind of artifact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ArtifactUsage {
+    /// Downloaded files on disk.
+    pub originals: u64,
+    /// Per-page PDF and OCR text, alternative OCR results and analysis
+    /// output kept in the database.
+    pub derived: u64,
+    /// Extracted document text and final page text.
+    pub text: u64,
+}
+
+/// A kind of reclaimable storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reclaim {
+    /// Extra copies of content stored more than once.
+    Duplicates,
+    /// Documents whose processing failed.
+    Failed,
+    /// Files no document version refers to.
+    Orphans,
+}
+
+impl Reclaim {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Duplicates => "duplicates",
+            Self::Failed => "failed",
+            Self::Orphans => "orphans",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "duplicates" => Some(Self::Duplicates),
+            "failed" => Some(Self::Failed),
+            "orphans" => Some(Self::Orphans),
+            _ => None,
+        }
+    }
+
+    /// Command that reclaims the space, or previews doing so.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Self::Duplicates => "foia db deduplicate --dry-run",
+            Self::Failed => "foia storage clean failed",
+            Self::Orphans => "foia storage clean orphans",
+        }
+    }
+
+    /// What the command does, for someone deciding whether to run it.
+    pub fn advice(&self) -> &'static str {
+        match self {
+            Self::Duplicates => "merge documents with identical content into one",
+            Self::Failed => {
+                "delete failed documents and their files \
+                 (or retry them with 'foia docs requeue --from-status failed')"
+            }
+            Self::Orphans => "delete files no document refers to",
+        }
+    }
+}
+
+/// Space one kind of waste takes up.
+#[derive(Debug, Clone, Serialize)]
+pub struct Reclaimable {
+    pub kind: Reclaim,
+    pub files: u64,
+    pub bytes: u64,
+    pub command: &'static str,
+    pub advice: &'static str,
+}
+
+/// A file in the documents directory that no version refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrphanFile {
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// Storage usage of the whole workspace.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageReport {
+    pub by_source: Vec<Usage>,
+    pub by_category: Vec<Usage>,
+    pub artifacts: ArtifactUsage,
+    /// Largest first.
+    pub reclaimable: Vec<Reclaimable>,
+    #[serde(skip)]
+    pub orphans: Vec<OrphanFile>,
+}
+
+impl StorageReport {
+    /// Measure usage from the database, then scan `documents_dir` for
+    /// orphaned files.
+    pub async fn build(
+        repo: &DieselDocumentRepository,
+        documents_dir: &Path,
+    ) -> anyhow::Result<Self> {
+        let by_source = repo.storage_by_source().await?;
+        let by_category = categorize(repo.storage_by_mime_type().await?);
+        let mut artifacts = repo.text_storage().await?;
+        artifacts.originals = by_source.iter().map(|u| u.bytes).sum();
+
+        let known = stored_paths(repo, documents_dir).await?;
+        let dir = documents_dir.to_path_buf();
+        let orphans = tokio::task::spawn_blocking(move || find_orphans(&dir, &known)).await??;
+
+        let duplicates = repo.duplicate_storage().await?;
+        let failed = repo.failed_document_storage().await?;
+        let mut reclaimable: Vec<Reclaimable> = [
+            (Reclaim::Duplicates, duplicates.files, duplicates.bytes),
+            (Reclaim::Failed, failed.files, failed.bytes),
+            (
+                Reclaim::Orphans,
+                orphans.len() as u64,
+                orphans.iter().map(|o| o.bytes).sum(),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, files, _)| *files > 0)
+        .map(|(kind, files, bytes)| Reclaimable {
+            kind,
+            files,
+            bytes,
+            command: kind.command(),
+            advice: kind.advice(),
+        })
+        .collect();
+        reclaimable.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+        Ok(Self {
+            by_source,
+            by_category,
+            artifacts,
+            reclaimable,
+            orphans,
+        })
+    }
+
+    /// Total bytes that could be reclaimed.
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.reclaimable.iter().map(|r| r.bytes).sum()
+    }
+}
+
+/// Group usage by MIME type into MIME categories, largest first.
+pub fn categorize(by_mime_type: Vec<Usage>) -> Vec<Usage> {
+    let mut categories: BTreeMap<&'static str, Usage> = BTreeMap::new();
+    for usage in by_mime_type {
+        let id = mime_type_category(&usage.key).id();
+        let entry = categories.entry(id).or_insert_with(|| Usage {
+            key: id.to_string(),
+            ..Default::default()
+        });
+        entry.files += usage.files;
+        entry.bytes += usage.bytes;
+    }
+    let mut categories: Vec<Usage> = categories.into_values().collect();
+    categories.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+    categories
+}
+
+/// Where every recorded version's file lives, lost ones included.
+pub async fn stored_paths(
+    repo: &DieselDocumentRepository,
+    documents_dir: &Path,
+) -> Result<HashSet<PathBuf>, DieselError> {
+    let mut paths = HashSet::new();
+    let mut docs = repo.stream_documents(
+        StreamFilter::default().with_projection(Projection::Metadata),
+        DEFAULT_STREAM_BATCH,
+    );
+    while let Some(doc) = docs.next().await {
+        let doc = doc?;
+        for version in &doc.versions {
+            paths.insert(version.resolve_path(documents_dir, &doc.source_url, &doc.title));
+        }
+    }
+    Ok(paths)
+}
+
+/// Files under `documents_dir` that are not in `known`, skipping anything
+/// modified within the last hour.
+pub fn find_orphans(
+    documents_dir: &Path,
+    known: &HashSet<PathBuf>,
+) -> std::io::Result<Vec<OrphanFile>> {
+    let mut orphans = Vec::new();
+    if !documents_dir.exists() {
+        return Ok(orphans);
+    }
+    let cutoff = SystemTime::now() - ORPHAN_MIN_AGE;
+    let mut pending = vec![documents_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            let path = entry.path();
+            if meta.is_dir() {
+                pending.push(path);
+            } else if meta.is_file()
+                && !known.contains(&path)
+                && meta.modified().map_or(true, |m| m < cutoff)
+            {
+                orphans.push(OrphanFile {
+                    path,
+                    bytes: meta.len(),
+                });
+            }
+        }
+    }
+    orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(orphans)
+}
+
+/// Delete orphaned files, returning how many bytes were freed. Files that
+/// have since disappeared are skipped.
+pub fn remove_orphans(orphans: &[OrphanFile]) -> std::io::Result<u64> {
+    let mut freed = 0;
+    for orphan in orphans {
+        match std::fs::remove_file(&orphan.path) {
+            Ok(()) => freed += orphan.bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(freed)
+}
+
+/// Delete a failed document and every file of its that no other version
+/// shares, returning the bytes freed.
+pub async fn remove_failed_document(
+    repo: &DieselDocumentRepository,
+    documents_dir: &Path,
+    document_id: &str,
+) -> anyhow::Result<u64> {
+    let Some(doc) = repo.get(document_id).await? else {
+        return Ok(0);
+    };
+    let mut files = Vec::new();
+    for version in &doc.versions {
+        if repo
+            .count_versions_needing_file(&version.content_hash, version.id)
+            .await?
+            == 0
+        {
+            files.push(version.resolve_path(documents_dir, &doc.source_url, &doc.title));
+        }
+    }
+    // Drop the rows first: a leftover file is only an orphan
+    repo.delete(&doc.id).await?;
+
+    let mut freed = 0;
+    for path in files {
+        if let Ok(meta) = std::fs::metadata(&path) {
+            std::fs::remove_file(&path)?;
+            freed += meta.len();
+        }
+    }
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(key: &str, files: u64, bytes: u64) -> Usage {
+        Usage {
+            key: key.to_string(),
+            files,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_categorize() {
+        let categories = categorize(vec![
+            usage("application/pdf", 3, 300),
+            usage("image/png", 1, 500),
+            usage("application/msword", 2, 100),
+            usage("text/html", 4, 40),
+        ]);
+        assert_eq!(
+            categories,
+            vec![
+                usage("images", 1, 500),
+                usage("documents", 5, 400),
+                usage("markup", 4, 40),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_orphans_skips_known_and_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("ab");
+        std::fs::create_dir_all(&sub).unwrap();
+        let known_file = sub.join("report-abcdef12.pdf");
+        let stray = sub.join("stray-12345678.pdf");
+        std::fs::write(&known_file, b"known").unwrap();
+        std::fs::write(&stray, b"stray").unwrap();
+
+        let known: HashSet<PathBuf> = [known_file].into_iter().collect();
+        // Both were just written, so neither is old enough
+        assert!(find_orphans(dir.path(), &known).unwrap().is_empty());
+
+        let old = SystemTime::now() - 2 * ORPHAN_MIN_AGE;
+        std::fs::File::options()
+            .write(true)
+            .open(&stray)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let orphans = find_orphans(dir.path(), &known).unwrap();
+        assert_eq!(
+            orphans,
+            vec![OrphanFile {
+                path: stray.clone(),
+                bytes: 5
+            }]
+        );
+
+        assert_eq!(remove_orphans(&orphans).unwrap(), 5);
+        assert!(!stray.exists());
+        // Already gone
+        assert_eq!(remove_orphans(&orphans).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_reclaim_round_trip() {
+        for kind in [Reclaim::Duplicates, Reclaim::Failed, Reclaim::Orphans] {
+            assert_eq!(Reclaim::from_str(kind.as_str()), Some(kind));
+        }
+        assert_eq!(Reclaim::from_str("everything"), None);
+    }
+}
@@ -0,0 +1,706 @@
+//! Differential sync between foia instances.
+//!
+//! Each instance summarizes its contents as a [`SyncManifest`]: a digest per
+//! document (covering metadata, annotations, versions and pages) and a digest
+//! per source's crawl state. Comparing two manifests yields the documents and
+//! sources that differ, so only those are transferred.
+//!
+//! Merging is conservative and order-independent:
+//! - document metadata and annotations: the side with the newer `updated_at` wins
+//! - versions: union by content hash (files are transferred separately as blobs)
+//! - pages: written when missing locally, when they add text the local copy
+//!   lacks, or when the incoming document is newer
+//! - crawl URLs: inserted when missing, replaced when fetched more recently
+//!
+//! Local row IDs never cross the wire; versions are matched by content hash
+//! and pages by `(version hash, page number)`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::models::{
+    CrawlUrl, Document, DocumentPage, DocumentStatus, DocumentVersion, PageOcrStatus, Source,
+};
+use crate::repository::diesel_document::{StreamFilter, DEFAULT_STREAM_BATCH};
+use crate::repository::{
+    DieselCrawlRepository, DieselDocumentRepository, DieselError, DieselSourceRepository,
+};
+
+/// Wire protocol version. Bumped on incompatible changes.
+pub const SYNC_PROTOCOL_VERSION: u32 = 1;
+
+/// Errors that can occur during sync.
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error("database error: {0}")]
+    Database(#[from] DieselError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("content hash mismatch: expected {expected}, got {actual}")]
+    HashMismatch { expected: String, actual: String },
+    #[error("unknown version {content_hash} for document {document_id}")]
+    UnknownVersion {
+        document_id: String,
+        content_hash: String,
+    },
+}
+
+/// Digests summarizing an instance's contents.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncManifest {
+    pub protocol: u32,
+    /// Document ID -> digest.
+    pub documents: BTreeMap<String, String>,
+    /// Source ID -> crawl state digest.
+    pub crawl: BTreeMap<String, String>,
+}
+
+/// Entries of one manifest that are missing or different in another.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    pub documents: Vec<String>,
+    pub sources: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty() && self.sources.is_empty()
+    }
+}
+
+/// Compute what `from` has that `to` lacks or holds a different copy of.
+pub fn diff_manifests(from: &SyncManifest, to: &SyncManifest) -> ManifestDiff {
+    fn differing(from: &BTreeMap<String, String>, to: &BTreeMap<String, String>) -> Vec<String> {
+        from.iter()
+            .filter(|(id, digest)| to.get(*id) != Some(*digest))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    ManifestDiff {
+        documents: differing(&from.documents, &to.documents),
+        sources: differing(&from.crawl, &to.crawl),
+    }
+}
+
+/// A document version without instance-local fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncVersion {
+    pub content_hash: String,
+    pub content_hash_blake3: Option<String>,
+    pub file_size: u64,
+    pub mime_type: String,
+    pub acquired_at: DateTime<Utc>,
+    pub source_url: Option<String>,
+    pub original_filename: Option<String>,
+    pub server_date: Option<DateTime<Utc>>,
+    pub page_count: Option<u32>,
+    pub earliest_archived_at: Option<DateTime<Utc>>,
+    pub dedup_index: Option<u32>,
+}
+
+impl From<&DocumentVersion> for SyncVersion {
+    fn from(v: &DocumentVersion) -> Self {
+        Self {
+            content_hash: v.content_hash.clone(),
+            content_hash_blake3: v.content_hash_blake3.clone(),
+            file_size: v.file_size,
+            mime_type: v.mime_type.clone(),
+            acquired_at: v.acquired_at,
+            source_url: v.source_url.clone(),
+            original_filename: v.original_filename.clone(),
+            server_date: v.server_date,
+            page_count: v.page_count,
+            earliest_archived_at: v.earliest_archived_at,
+            dedup_index: v.dedup_index,
+        }
+    }
+}
+
+impl SyncVersion {
+    /// Convert to an unsaved version stored at its deterministic path.
+    fn to_version(&self) -> DocumentVersion {
+        DocumentVersion {
+            id: 0,
+            content_hash: self.content_hash.clone(),
+            content_hash_blake3: self.content_hash_blake3.clone(),
+            file_path: None,
+            file_size: self.file_size,
+            mime_type: self.mime_type.clone(),
+            acquired_at: self.acquired_at,
+            source_url: self.source_url.clone(),
+            original_filename: self.original_filename.clone(),
+            server_date: self.server_date,
+            page_count: self.page_count,
+            archive_snapshot_id: None,
+            earliest_archived_at: self.earliest_archived_at,
+            dedup_index: self.dedup_index,
+        }
+    }
+}
+
+/// A page keyed by its version's content hash instead of the local version ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPage {
+    pub version_hash: String,
+    pub page_number: u32,
+    pub pdf_text: Option<String>,
+    pub ocr_text: Option<String>,
+    pub final_text: Option<String>,
+    pub ocr_status: PageOcrStatus,
+}
+
+/// A document with its versions and pages in transferable form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncDocument {
+    pub id: String,
+    pub source_id: String,
+    pub title: String,
+    pub source_url: String,
+    pub extracted_text: Option<String>,
+    pub synopsis: Option<String>,
+    pub tags: Vec<String>,
+    pub status: DocumentStatus,
+    pub metadata: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub discovery_method: String,
+    /// Sorted by content hash.
+    pub versions: Vec<SyncVersion>,
+    /// Sorted by version hash, then page number.
+    pub pages: Vec<SyncPage>,
+}
+
+impl SyncDocument {
+    fn from_parts(doc: Document, pages: Vec<SyncPage>) -> Self {
+        let mut versions: Vec<SyncVersion> = doc.versions.iter().map(SyncVersion::from).collect();
+        versions.sort_by(|a, b| a.content_hash.cmp(&b.content_hash));
+        let mut pages = pages;
+        pages.sort_by(|a, b| {
+            (a.version_hash.as_str(), a.page_number).cmp(&(b.version_hash.as_str(), b.page_number))
+        });
+
+        Self {
+            id: doc.id,
+            source_id: doc.source_id,
+            title: doc.title,
+            source_url: doc.source_url,
+            extracted_text: doc.extracted_text,
+            synopsis: doc.synopsis,
+            tags: doc.tags,
+            status: doc.status,
+            metadata: doc.metadata,
+            created_at: doc.created_at,
+            updated_at: doc.updated_at,
+            discovery_method: doc.discovery_method,
+            versions,
+            pages,
+        }
+    }
+
+    /// SHA-256 over the canonical JSON encoding.
+    pub fn digest(&self) -> String {
+        let bytes = serde_json::to_vec(self).unwrap_or_default();
+        hex::encode(Sha256::digest(&bytes))
+    }
+
+    /// Build the document model (with unsaved versions) for persisting.
+    fn to_document(&self) -> Document {
+        Document {
+            id: self.id.clone(),
+            source_id: self.source_id.clone(),
+            title: self.title.clone(),
+            source_url: self.source_url.clone(),
+            versions: self.versions.iter().map(SyncVersion::to_version).collect(),
+            extracted_text: self.extracted_text.clone(),
+            synopsis: self.synopsis.clone(),
+            tags: self.tags.clone(),
+            status: self.status,
+            metadata: self.metadata.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            discovery_method: self.discovery_method.clone(),
+        }
+    }
+}
+
+/// Documents plus the sources they belong to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncBatch {
+    pub sources: Vec<Source>,
+    pub documents: Vec<SyncDocument>,
+}
+
+/// Crawl state for one source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncCrawlState {
+    pub source: Option<Source>,
+    pub urls: Vec<CrawlUrl>,
+}
+
+/// Identifies a stored file by document and content hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobRef {
+    pub document_id: String,
+    pub content_hash: String,
+}
+
+/// Outcome of applying a [`SyncBatch`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyReport {
+    pub documents_created: usize,
+    pub documents_updated: usize,
+    pub versions_added: usize,
+    pub pages_written: usize,
+    /// Versions whose files are not yet present in the documents directory.
+    pub missing_blobs: Vec<BlobRef>,
+}
+
+/// Outcome of applying a [`SyncCrawlState`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrawlApplyReport {
+    pub added: usize,
+    pub updated: usize,
+}
+
+/// Digest of a source's crawl URLs (expects URLs ordered by URL).
+fn crawl_digest(urls: &[CrawlUrl]) -> String {
+    let mut hasher = Sha256::new();
+    for u in urls {
+        hasher.update(u.url.as_bytes());
+        hasher.update(b"\t");
+        hasher.update(u.status.as_str().as_bytes());
+        hasher.update(b"\t");
+        if let Some(fetched) = u.fetched_at {
+            hasher.update(fetched.to_rfc3339().as_bytes());
+        }
+        hasher.update(b"\t");
+        hasher.update(u.content_hash.as_deref().unwrap_or("").as_bytes());
+        hasher.update(b"\t");
+        hasher.update(u.document_id.as_deref().unwrap_or("").as_bytes());
+        hasher.update(b"\n");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Sync operations over an instance's repositories.
+pub struct SyncService<'a> {
+    documents: &'a DieselDocumentRepository,
+    sources: &'a DieselSourceRepository,
+    crawl: &'a DieselCrawlRepository,
+    documents_dir: &'a Path,
+}
+
+impl<'a> SyncService<'a> {
+    pub fn new(
+        documents: &'a DieselDocumentRepository,
+        sources: &'a DieselSourceRepository,
+        crawl: &'a DieselCrawlRepository,
+        documents_dir: &'a Path,
+    ) -> Self {
+        Self {
+            documents,
+            sources,
+            crawl,
+            documents_dir,
+        }
+    }
+
+    /// Compute digests for every document and every source's crawl state.
+    pub async fn manifest(&self) -> Result<SyncManifest, SyncError> {
+        let mut manifest = SyncManifest {
+            protocol: SYNC_PROTOCOL_VERSION,
+            ..Default::default()
+        };
+
+        let mut docs = self
+            .documents
+            .stream_documents(StreamFilter::default(), DEFAULT_STREAM_BATCH);
+        while let Some(doc) = docs.try_next().await? {
+            let id = doc.id.clone();
+            let sync_doc = self.load(doc).await?;
+            manifest.documents.insert(id, sync_doc.digest());
+        }
+
+        for source in self.sources.get_all().await? {
+            let urls = self.crawl.get_urls_by_source(&source.id).await?;
+            if !urls.is_empty() {
+                manifest.crawl.insert(source.id, crawl_digest(&urls));
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    async fn load(&self, doc: Document) -> Result<SyncDocument, SyncError> {
+        let mut pages = Vec::new();
+        for version in &doc.versions {
+            for page in self.documents.get_pages(&doc.id, version.id as i32).await? {
+                pages.push(SyncPage {
+                    version_hash: version.content_hash.clone(),
+                    page_number: page.page_number,
+                    pdf_text: page.pdf_text,
+                    ocr_text: page.ocr_text,
+                    final_text: page.final_text,
+                    ocr_status: page.ocr_status,
+                });
+            }
+        }
+        Ok(SyncDocument::from_parts(doc, pages))
+    }
+
+    /// Export the given documents with their sources.
+    pub async fn export(&self, ids: &[String]) -> Result<SyncBatch, SyncError> {
+        let mut batch = SyncBatch::default();
+        let mut source_ids = HashSet::new();
+
+        for doc in self.documents.get_batch(ids).await? {
+            if source_ids.insert(doc.source_id.clone()) {
+                if let Some(source) = self.sources.get(&doc.source_id).await? {
+                    batch.sources.push(source);
+                }
+            }
+            batch.documents.push(self.load(doc).await?);
+        }
+
+        Ok(batch)
+    }
+
+    /// Merge a batch into this instance.
+    pub async fn apply(&self, batch: &SyncBatch) -> Result<ApplyReport, SyncError> {
+        let mut report = ApplyReport::default();
+
+        for source in &batch.sources {
+            if !self.sources.exists(&source.id).await? {
+                self.sources.save(source).await?;
+            }
+        }
+
+        for incoming in &batch.documents {
+            self.apply_document(incoming, &mut report).await?;
+        }
+
+        Ok(report)
+    }
+
+    async fn apply_document(
+        &self,
+        incoming: &SyncDocument,
+        report: &mut ApplyReport,
+    ) -> Result<(), SyncError> {
+        let incoming_newer = match self.documents.get(&incoming.id).await? {
+            None => {
+                self.documents
+                    .save_with_versions(&incoming.to_document())
+                    .await?;
+                report.documents_created += 1;
+                report.versions_added += incoming.versions.len();
+                self.write_content_fields(incoming).await?;
+                true
+            }
+            Some(local) => {
+                let newer = incoming.updated_at > local.updated_at;
+                if newer {
+                    let mut doc = incoming.to_document();
+                    // Keep local versions so the category is derived from real rows
+                    doc.versions = local.versions.clone();
+                    self.documents.save(&doc).await?;
+                    self.write_content_fields(incoming).await?;
+                    report.documents_updated += 1;
+                }
+
+                let known: HashSet<&str> = local
+                    .versions
+                    .iter()
+                    .map(|v| v.content_hash.as_str())
+                    .collect();
+                for version in &incoming.versions {
+                    if !known.contains(version.content_hash.as_str()) {
+                        self.documents
+                            .add_version(&incoming.id, &version.to_version())
+                            .await?;
+                        report.versions_added += 1;
+                    }
+                }
+                newer
+            }
+        };
+
+        let Some(doc) = self.documents.get(&incoming.id).await? else {
+            return Ok(());
+        };
+        let version_ids: HashMap<&str, i64> = doc
+            .versions
+            .iter()
+            .map(|v| (v.content_hash.as_str(), v.id))
+            .collect();
+
+        let mut local_pages: HashMap<(i64, u32), DocumentPage> = HashMap::new();
+        for version in &doc.versions {
+            for page in self.documents.get_pages(&doc.id, version.id as i32).await? {
+                local_pages.insert((version.id, page.page_number), page);
+            }
+        }
+
+        for page in &incoming.pages {
+            let Some(&version_id) = version_ids.get(page.version_hash.as_str()) else {
+                continue;
+            };
+            let write = match local_pages.get(&(version_id, page.page_number)) {
+                None => true,
+                Some(existing) => {
+                    incoming_newer || (existing.final_text.is_none() && page.final_text.is_some())
+                }
+            };
+            if write {
+                let mut record = DocumentPage::new(doc.id.clone(), version_id, page.page_number);
+                record.pdf_text = page.pdf_text.clone();
+                record.ocr_text = page.ocr_text.clone();
+                record.final_text = page.final_text.clone();
+                record.ocr_status = page.ocr_status;
+                self.documents.save_page(&record).await?;
+                report.pages_written += 1;
+            }
+        }
+
+        for version in &doc.versions {
+            let path = version.resolve_path(self.documents_dir, &doc.source_url, &doc.title);
+            if !path.exists() {
+                report.missing_blobs.push(BlobRef {
+                    document_id: doc.id.clone(),
+                    content_hash: version.content_hash.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn write_content_fields(&self, doc: &SyncDocument) -> Result<(), SyncError> {
+        self.documents
+            .update_content_fields(
+                &doc.id,
+                doc.extracted_text.as_deref(),
+                doc.synopsis.as_deref(),
+                &doc.tags,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Local path of a version's file, if the document has that version.
+    pub async fn blob_path(
+        &self,
+        document_id: &str,
+        content_hash: &str,
+    ) -> Result<Option<PathBuf>, SyncError> {
+        let Some(doc) = self.documents.get(document_id).await? else {
+            return Ok(None);
+        };
+        Ok(doc
+            .versions
+            .iter()
+            .find(|v| v.content_hash == content_hash)
+            .map(|v| v.resolve_path(self.documents_dir, &doc.source_url, &doc.title)))
+    }
+
+    /// Verify and store a version's file content.
+    pub async fn store_blob(
+        &self,
+        document_id: &str,
+        content_hash: &str,
+        content: &[u8],
+    ) -> Result<PathBuf, SyncError> {
+        let actual = DocumentVersion::compute_hash(content);
+        if actual != content_hash {
+            return Err(SyncError::HashMismatch {
+                expected: content_hash.to_string(),
+                actual,
+            });
+        }
+
+        let path = self
+            .blob_path(document_id, content_hash)
+            .await?
+            .ok_or_else(|| SyncError::UnknownVersion {
+                document_id: document_id.to_string(),
+                content_hash: content_hash.to_string(),
+            })?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        crate::storage::write_content_async(&path, content).await?;
+        Ok(path)
+    }
+
+    /// Export a source's crawl state.
+    pub async fn export_crawl(&self, source_id: &str) -> Result<SyncCrawlState, SyncError> {
+        Ok(SyncCrawlState {
+            source: self.sources.get(source_id).await?,
+            urls: self.crawl.get_urls_by_source(source_id).await?,
+        })
+    }
+
+    /// Merge a source's crawl state into this instance.
+    pub async fn apply_crawl(&self, state: &SyncCrawlState) -> Result<CrawlApplyReport, SyncError> {
+        let mut report = CrawlApplyReport::default();
+
+        if let Some(source) = &state.source {
+            if !self.sources.exists(&source.id).await? {
+                self.sources.save(source).await?;
+            }
+        }
+
+        for url in &state.urls {
+            match self.crawl.get_url(&url.source_id, &url.url).await? {
+                None => {
+                    if self.crawl.add_url(url).await? {
+                        report.added += 1;
+                    }
+                }
+                Some(local) if url.fetched_at > local.fetched_at => {
+                    self.crawl.update_url(url).await?;
+                    report.updated += 1;
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::diesel_document::tests::setup_test_db;
+    use crate::repository::DbPool;
+    use diesel_async::SimpleAsyncConnection;
+
+    async fn setup() -> (DbPool, tempfile::TempDir) {
+        let (pool, dir) = setup_test_db().await;
+        match pool {
+            DbPool::Sqlite(ref sqlite_pool) => {
+                let mut conn = sqlite_pool.get().await.unwrap();
+                conn.batch_execute(
+                    r#"CREATE TABLE IF NOT EXISTS sources (
+                        id TEXT PRIMARY KEY,
+                        source_type TEXT NOT NULL,
+                        name TEXT NOT NULL,
+                        base_url TEXT NOT NULL,
+                        metadata TEXT NOT NULL DEFAULT '{}',
+                        created_at TEXT NOT NULL,
+                        last_scraped TEXT,
+                        paused INTEGER NOT NULL DEFAULT 0
+                    )"#,
+                )
+                .await
+                .unwrap();
+            }
+            #[cfg(feature = "postgres")]
+            DbPool::Postgres(_) => unreachable!("test uses sqlite"),
+        }
+        (pool, dir)
+    }
+
+    fn sample_document() -> Document {
+        let content = b"hello";
+        let version = DocumentVersion::new(
+            content,
+            "application/pdf".to_string(),
+            Some("https://example.gov/a.pdf".to_string()),
+        );
+        let mut doc = Document::new(
+            "doc-1".to_string(),
+            "agency".to_string(),
+            "A Memo".to_string(),
+            "https://example.gov/a.pdf".to_string(),
+            version,
+            serde_json::json!({}),
+        );
+        doc.synopsis = Some("About things".to_string());
+        doc.tags = vec!["memo".to_string()];
+        doc
+    }
+
+    #[test]
+    fn test_diff_manifests() {
+        let mut a = SyncManifest::default();
+        let mut b = SyncManifest::default();
+        a.documents.insert("same".into(), "1".into());
+        b.documents.insert("same".into(), "1".into());
+        a.documents.insert("changed".into(), "2".into());
+        b.documents.insert("changed".into(), "3".into());
+        a.documents.insert("new".into(), "4".into());
+        b.documents.insert("theirs".into(), "5".into());
+        a.crawl.insert("agency".into(), "x".into());
+
+        let diff = diff_manifests(&a, &b);
+        assert_eq!(
+            diff.documents,
+            vec!["changed".to_string(), "new".to_string()]
+        );
+        assert_eq!(diff.sources, vec!["agency".to_string()]);
+        assert!(diff_manifests(&a, &a).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_apply_converges() {
+        let (pool_a, dir_a) = setup().await;
+        let (pool_b, dir_b) = setup().await;
+        let docs_a = DieselDocumentRepository::new(pool_a.clone());
+        let sources_a = DieselSourceRepository::new(pool_a.clone());
+        let crawl_a = DieselCrawlRepository::new(pool_a);
+        let docs_b = DieselDocumentRepository::new(pool_b.clone());
+        let sources_b = DieselSourceRepository::new(pool_b.clone());
+        let crawl_b = DieselCrawlRepository::new(pool_b);
+
+        let doc = sample_document();
+        docs_a.save_with_versions(&doc).await.unwrap();
+        docs_a
+            .update_content_fields(&doc.id, None, doc.synopsis.as_deref(), &doc.tags)
+            .await
+            .unwrap();
+        let version_id = docs_a
+            .get_current_version_id(&doc.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut page = DocumentPage::new(doc.id.clone(), version_id, 1);
+        page.final_text = Some("page one".to_string());
+        docs_a.save_page(&page).await.unwrap();
+
+        let a = SyncService::new(&docs_a, &sources_a, &crawl_a, dir_a.path());
+        let b = SyncService::new(&docs_b, &sources_b, &crawl_b, dir_b.path());
+
+        let batch = a.export(&[doc.id.clone()]).await.unwrap();
+        let report = b.apply(&batch).await.unwrap();
+        assert_eq!(report.documents_created, 1);
+        assert_eq!(report.versions_added, 1);
+        assert_eq!(report.pages_written, 1);
+        assert_eq!(report.missing_blobs.len(), 1);
+
+        // Digests match once applied, despite different local row IDs
+        let exported = b.export(&[doc.id.clone()]).await.unwrap();
+        assert_eq!(batch.documents[0].digest(), exported.documents[0].digest());
+
+        // Applying again is a no-op
+        let again = b.apply(&batch).await.unwrap();
+        assert_eq!(again.documents_updated, 0);
+        assert_eq!(again.versions_added, 0);
+        assert_eq!(again.pages_written, 0);
+
+        // Blob storage verifies content
+        let hash = &batch.documents[0].versions[0].content_hash;
+        assert!(b.store_blob(&doc.id, hash, b"tampered").await.is_err());
+        let path = b.store_blob(&doc.id, hash, b"hello").await.unwrap();
+        assert!(path.starts_with(dir_b.path()));
+        let after = b.apply(&batch).await.unwrap();
+        assert!(after.missing_blobs.is_empty());
+    }
+}
@@ -0,0 +1,355 @@
+//! Removal requests and the review of them.
+//!
+//! Anyone can ask for documents to be taken down through the public form.
+//! The request becomes a case naming the documents. While it is reviewed,
+//! an operator can suppress them: they are withheld from the public by a
+//! document access rule, and their pages explain why instead of showing
+//! them. The case is then upheld, leaving the documents withheld, or
+//! rejected, restoring them. Every step is logged with who took it and why.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::repository::{DieselDocumentRepository, DieselError};
+use crate::services::access::{AccessScope, Visibility};
+
+/// Most documents one request may name.
+pub const MAX_DOCUMENTS: usize = 50;
+
+/// Longest accepted reason, in characters.
+const MAX_REASON_CHARS: usize = 10_000;
+
+/// Start of the note on access rules set by takedown cases, so rejecting
+/// a case only lifts restrictions it set itself.
+const RULE_NOTE_PREFIX: &str = "Takedown case #";
+
+/// Where a case is in review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TakedownStatus {
+    /// Received, documents still public.
+    Open,
+    /// Documents withheld while the request is reviewed.
+    Suppressed,
+    /// Request granted; documents stay withheld.
+    Upheld,
+    /// Request denied; documents restored.
+    Rejected,
+}
+
+impl TakedownStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Suppressed => "suppressed",
+            Self::Upheld => "upheld",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(Self::Open),
+            "suppressed" => Some(Self::Suppressed),
+            "upheld" => Some(Self::Upheld),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    /// Whether the case has been decided.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Self::Upheld | Self::Rejected)
+    }
+
+    /// Whether the case's documents are withheld.
+    pub fn withholds(&self) -> bool {
+        matches!(self, Self::Suppressed | Self::Upheld)
+    }
+}
+
+/// A removal request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TakedownCase {
+    pub id: i32,
+    pub requester_name: String,
+    /// How to reach the requester, usually an email address.
+    pub requester_contact: String,
+    pub reason: String,
+    pub status: TakedownStatus,
+    pub document_ids: Vec<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One step in a case: `received`, `suppressed`, `upheld` or `rejected`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TakedownEvent {
+    pub action: String,
+    pub actor: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What the public is told about a withheld document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tombstone {
+    pub case_id: i32,
+    pub status: TakedownStatus,
+    pub since: DateTime<Utc>,
+}
+
+/// A removal request as submitted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TakedownRequest {
+    pub requester_name: String,
+    pub requester_contact: String,
+    pub reason: String,
+    /// Document IDs or links to their pages.
+    pub documents: Vec<String>,
+}
+
+impl TakedownRequest {
+    /// Check the request, returning a message for the requester.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.requester_name.trim().is_empty() {
+            return Err("name is required".to_string());
+        }
+        if self.requester_contact.trim().is_empty() {
+            return Err("contact is required".to_string());
+        }
+        let reason = self.reason.trim();
+        if reason.is_empty() {
+            return Err("reason is required".to_string());
+        }
+        if reason.chars().count() > MAX_REASON_CHARS {
+            return Err(format!(
+                "reason is longer than {} characters",
+                MAX_REASON_CHARS
+            ));
+        }
+        let ids = self.document_ids();
+        if ids.is_empty() {
+            return Err("name at least one document".to_string());
+        }
+        if ids.len() > MAX_DOCUMENTS {
+            return Err(format!("at most {} documents per request", MAX_DOCUMENTS));
+        }
+        Ok(())
+    }
+
+    /// Distinct document IDs named by the request.
+    pub fn document_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for id in self
+            .documents
+            .iter()
+            .filter_map(|d| document_id_from_reference(d))
+        {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+}
+
+/// The document ID in a link to a document page, or the reference itself
+/// when it is a bare ID.
+pub fn document_id_from_reference(reference: &str) -> Option<String> {
+    let reference = reference.trim();
+    let path = reference
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(reference)
+        .trim_end_matches('/');
+    let id = ["/documents/", "/doc/"]
+        .iter()
+        .find_map(|marker| {
+            path.find(marker)
+                .map(|at| &path[at + marker.len()..])
+                .and_then(|rest| rest.split('/').next())
+        })
+        .unwrap_or(path);
+    let id = urlencoding::decode(id)
+        .map(|id| id.into_owned())
+        .unwrap_or_else(|_| id.to_string());
+    (!id.is_empty() && !id.contains(char::is_whitespace)).then_some(id)
+}
+
+/// Record a removal request. Documents that don't exist are refused so
+/// the requester can correct the links.
+pub async fn open_case(
+    doc_repo: &DieselDocumentRepository,
+    request: &TakedownRequest,
+) -> anyhow::Result<TakedownCase> {
+    request.validate().map_err(anyhow::Error::msg)?;
+    let ids = request.document_ids();
+    for id in &ids {
+        if doc_repo.document_owner(id).await?.is_none() {
+            anyhow::bail!("Unknown document: {}", id);
+        }
+    }
+    let id = doc_repo
+        .create_takedown_case(
+            request.requester_name.trim(),
+            request.requester_contact.trim(),
+            request.reason.trim(),
+            &ids,
+        )
+        .await?;
+    load_case(doc_repo, id).await
+}
+
+/// Withhold a case's documents while it is reviewed.
+pub async fn suppress_case(
+    doc_repo: &DieselDocumentRepository,
+    id: i32,
+    actor: &str,
+    note: Option<&str>,
+) -> anyhow::Result<TakedownCase> {
+    let case = load_case(doc_repo, id).await?;
+    if case.status != TakedownStatus::Open {
+        anyhow::bail!("Case {} is already {}", id, case.status.as_str());
+    }
+    withhold(doc_repo, &case).await?;
+    doc_repo
+        .update_takedown_case(id, TakedownStatus::Suppressed, actor, note)
+        .await?;
+    load_case(doc_repo, id).await
+}
+
+/// Decide a case: `Upheld` keeps its documents withheld, `Rejected`
+/// restores them. The reason is required, as it is owed to the requester.
+pub async fn decide_case(
+    doc_repo: &DieselDocumentRepository,
+    id: i32,
+    decision: TakedownStatus,
+    actor: &str,
+    reason: &str,
+) -> anyhow::Result<TakedownCase> {
+    if !decision.is_closed() {
+        anyhow::bail!("a case can only be upheld or rejected");
+    }
+    if reason.trim().is_empty() {
+        anyhow::bail!("a decision needs a reason");
+    }
+    let case = load_case(doc_repo, id).await?;
+    if case.status.is_closed() {
+        anyhow::bail!("Case {} is already {}", id, case.status.as_str());
+    }
+
+    if decision == TakedownStatus::Upheld {
+        withhold(doc_repo, &case).await?;
+    } else {
+        let marker = rule_note(id);
+        for doc_id in &case.document_ids {
+            let rule = doc_repo
+                .get_access_rule(AccessScope::Document, doc_id)
+                .await?;
+            if rule.is_some_and(|r| r.note.as_deref() == Some(marker.as_str())) {
+                doc_repo
+                    .clear_access_rule(AccessScope::Document, doc_id)
+                    .await?;
+            }
+        }
+    }
+    doc_repo
+        .update_takedown_case(id, decision, actor, Some(reason.trim()))
+        .await?;
+    load_case(doc_repo, id).await
+}
+
+/// Restrict each document of a case, unless another rule already keeps
+/// it internal.
+async fn withhold(doc_repo: &DieselDocumentRepository, case: &TakedownCase) -> anyhow::Result<()> {
+    let note = rule_note(case.id);
+    for doc_id in &case.document_ids {
+        let rule = doc_repo
+            .get_access_rule(AccessScope::Document, doc_id)
+            .await?;
+        let internal = rule.as_ref().is_some_and(|r| {
+            r.visibility == Visibility::Internal
+                && !r
+                    .note
+                    .as_deref()
+                    .is_some_and(|n| n.starts_with(RULE_NOTE_PREFIX))
+        });
+        if !internal {
+            doc_repo
+                .set_access_rule(
+                    AccessScope::Document,
+                    doc_id,
+                    Visibility::Internal,
+                    Some(&note),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn load_case(doc_repo: &DieselDocumentRepository, id: i32) -> anyhow::Result<TakedownCase> {
+    doc_repo
+        .get_takedown_case(id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Takedown case {} not found", id))
+}
+
+fn rule_note(case_id: i32) -> String {
+    format!("{}{}", RULE_NOTE_PREFIX, case_id)
+}
+
+/// Why a document is withheld, if a takedown case is the reason.
+pub async fn tombstone(
+    doc_repo: &DieselDocumentRepository,
+    document_id: &str,
+) -> Result<Option<Tombstone>, DieselError> {
+    doc_repo.takedown_tombstone(document_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_id_from_reference() {
+        let id = |r: &str| document_id_from_reference(r);
+        assert_eq!(id("doc-1").as_deref(), Some("doc-1"));
+        assert_eq!(
+            id("https://archive.example.org/documents/doc-1?page=2").as_deref(),
+            Some("doc-1")
+        );
+        assert_eq!(
+            id("https://archive.example.org/documents/doc-1/read#page-3").as_deref(),
+            Some("doc-1")
+        );
+        assert_eq!(id("/doc/a%2Fb/v/3/p/2").as_deref(), Some("a/b"));
+        assert_eq!(id("  "), None);
+        assert_eq!(id("not an id"), None);
+    }
+
+    #[test]
+    fn test_request_validation() {
+        let mut request = TakedownRequest {
+            requester_name: "Jane Doe".to_string(),
+            requester_contact: "jane@example.org".to_string(),
+            reason: "My home address is on page 4.".to_string(),
+            documents: vec![
+                "doc-1".to_string(),
+                "https://archive.example.org/documents/doc-1".to_string(),
+                "doc-2".to_string(),
+            ],
+        };
+        assert!(request.validate().is_ok());
+        assert_eq!(request.document_ids(), vec!["doc-1", "doc-2"]);
+
+        request.documents = vec!["".to_string()];
+        assert!(request.validate().unwrap_err().contains("at least one"));
+        request.documents = (0..=MAX_DOCUMENTS).map(|i| format!("doc-{}", i)).collect();
+        assert!(request.validate().unwrap_err().contains("at most"));
+        request.documents = vec!["doc-1".to_string()];
+        request.requester_contact = " ".to_string();
+        assert!(request.validate().unwrap_err().contains("contact"));
+    }
+}
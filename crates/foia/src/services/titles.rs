@@ -0,0 +1,290 @@
+//! Better titles for documents whose title came from their URL.
+//!
+//! Scrapers fall back to the file name when a listing gives no title,
+//! leaving titles like `document.pdf` or `download.aspx?id=123`. These are
+//! recognised here and replaced with a title taken from the first page: a
+//! subject line if there is one, else the first line that reads like a
+//! heading. Suggestions are recorded with the original title so curators
+//! can review them, and only replace the title for sources that opt in.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+/// Longest title suggested, in characters.
+pub const MAX_TITLE_LEN: usize = 120;
+
+/// Method recorded for titles taken from the first page.
+pub const FIRST_PAGE_METHOD: &str = "first_page";
+
+/// Review state of a suggested title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleStatus {
+    /// Suggested only; the document keeps its original title.
+    Suggested,
+    /// Applied automatically, waiting for a curator.
+    Applied,
+    /// Applied and confirmed by a curator.
+    Accepted,
+    /// Turned down by a curator; the original title stays.
+    Rejected,
+}
+
+impl TitleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Suggested => "suggested",
+            Self::Applied => "applied",
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "suggested" => Some(Self::Suggested),
+            "applied" => Some(Self::Applied),
+            "accepted" => Some(Self::Accepted),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    /// Whether a curator still has to look at it.
+    pub fn needs_review(&self) -> bool {
+        matches!(self, Self::Suggested | Self::Applied)
+    }
+}
+
+/// A title suggested for a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TitleSuggestion {
+    pub document_id: String,
+    pub source_id: String,
+    /// Title before any suggestion was applied.
+    pub original_title: String,
+    pub suggested_title: String,
+    /// `first_page` or `llm:<model>`.
+    pub method: String,
+    pub status: TitleStatus,
+    pub created_at: String,
+    pub reviewed_at: Option<String>,
+}
+
+/// Words that say nothing about a document when they make up its title.
+const GENERIC_WORDS: &[&str] = &[
+    "attachment",
+    "content",
+    "doc",
+    "document",
+    "documents",
+    "download",
+    "fetch",
+    "file",
+    "files",
+    "getfile",
+    "getdocument",
+    "image",
+    "img",
+    "index",
+    "item",
+    "page",
+    "pdf",
+    "record",
+    "scan",
+    "showdocument",
+    "untitled",
+    "view",
+    "viewdocument",
+    "viewer",
+];
+
+/// Extensions of server scripts that serve documents by ID.
+const SCRIPT_EXTENSIONS: &[&str] = &["asp", "aspx", "cfm", "cgi", "jsp", "php"];
+
+static EXTENSION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\.([A-Za-z0-9]{2,5})$").unwrap());
+
+static SUBJECT_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^\s*(?:subject|subj|re|title)\s*[:.]\s*(.+)$").unwrap());
+
+static BOILERPLATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)^(
+            page\s+\d+ | \d+\s+of\s+\d+ | (top\s+)?secret\b | (un)?classified\b
+            | confidential\b | for\s+official\s+use | fouo\b | declassified\b
+            | approved\s+for\s+release | released\s+under | sanitized\s+copy
+            | (to|from|cc|date|sent|file|ref)\s*: | https?:// | www\.
+        )",
+    )
+    .unwrap()
+});
+
+/// Whether a title looks derived from a URL or file name rather than
+/// describing the document.
+pub fn is_generic_title(title: &str) -> bool {
+    let title = title.trim();
+    if title.chars().filter(|c| c.is_alphanumeric()).count() < 3 {
+        return true;
+    }
+    // Query strings: `download.aspx?id=123`
+    let (path, query) = match title.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (title, None),
+    };
+    let extension = EXTENSION.captures(path).map(|c| c[1].to_ascii_lowercase());
+    if query.is_some_and(|q| q.contains('='))
+        || extension
+            .as_deref()
+            .is_some_and(|ext| SCRIPT_EXTENSIONS.contains(&ext))
+    {
+        return true;
+    }
+    // A title with spaces and no extension was written by someone
+    if extension.is_none() && title.contains(' ') {
+        return false;
+    }
+
+    let stem = match &extension {
+        Some(ext) => &path[..path.len() - ext.len() - 1],
+        None => path,
+    };
+    !stem
+        .split(|c: char| !c.is_alphanumeric())
+        .any(is_meaningful_word)
+}
+
+/// A word that carries meaning: not a generic term, number or ID.
+fn is_meaningful_word(word: &str) -> bool {
+    let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+    let digits = word.chars().filter(|c| c.is_ascii_digit()).count();
+    if letters < 2 || digits * 2 >= word.len() {
+        return false;
+    }
+    // Hex IDs and hashes
+    if word.len() >= 8 && word.chars().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+    !GENERIC_WORDS.contains(&word.to_ascii_lowercase().as_str())
+}
+
+/// A title taken from a document's first page: the subject line if it has
+/// one, else the first line that reads like a heading.
+pub fn title_from_text(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .take(60)
+        .collect();
+
+    let subject = lines.iter().find_map(|line| {
+        SUBJECT_LINE
+            .captures(line)
+            .map(|c| clean_title(&c[1]))
+            .filter(|t| reads_like_title(t))
+    });
+    subject.or_else(|| {
+        lines
+            .iter()
+            .filter(|line| !BOILERPLATE.is_match(line))
+            .map(|line| clean_title(line))
+            .find(|t| reads_like_title(t))
+    })
+}
+
+/// Whether a line could be a title: a few words, mostly letters.
+fn reads_like_title(line: &str) -> bool {
+    let len = line.chars().count();
+    if !(8..=200).contains(&len) || line.split_whitespace().count() < 2 {
+        return false;
+    }
+    let visible = line.chars().filter(|c| !c.is_whitespace()).count();
+    let letters = line.chars().filter(|c| c.is_alphabetic()).count();
+    letters * 10 >= visible * 6
+}
+
+/// Collapse whitespace, drop trailing punctuation and cut at a word
+/// boundary to [`MAX_TITLE_LEN`].
+pub fn clean_title(raw: &str) -> String {
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed
+        .trim_matches(|c: char| matches!(c, '"' | '\'' | '*' | '#' | '`'))
+        .trim_end_matches([':', ';', ',', '-', '.'])
+        .trim();
+    if trimmed.chars().count() <= MAX_TITLE_LEN {
+        return trimmed.to_string();
+    }
+    let mut title = String::new();
+    for word in trimmed.split(' ') {
+        if title.chars().count() + word.chars().count() + 1 > MAX_TITLE_LEN {
+            break;
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_generic_title() {
+        for title in [
+            "document.pdf",
+            "download.aspx?id=123",
+            "GetFile.php",
+            "12345.pdf",
+            "DOC_0001234.pdf",
+            "scan0001.PDF",
+            "3f9a2c71bd04e8aa.pdf",
+            "file-2019-04-01.pdf",
+            "",
+        ] {
+            assert!(is_generic_title(title), "{:?} should be generic", title);
+        }
+        for title in [
+            "Annual_Report_2019.pdf",
+            "Budget Hearing Minutes",
+            "use-of-force-policy.pdf",
+            "Memo on 1998 audit",
+        ] {
+            assert!(
+                !is_generic_title(title),
+                "{:?} should not be generic",
+                title
+            );
+        }
+    }
+
+    #[test]
+    fn test_title_from_text() {
+        let memo = "UNCLASSIFIED\nPage 1 of 3\nMEMORANDUM\nTO: Director\nFROM: Field Office\n\
+                    SUBJECT: Surveillance of  the 1968 convention:\n\nBody text follows.";
+        assert_eq!(
+            title_from_text(memo).as_deref(),
+            Some("Surveillance of the 1968 convention")
+        );
+
+        let report = "12\n\n- 3 -\nDepartment of Public Works\nAnnual Report 2019\n";
+        assert_eq!(
+            title_from_text(report).as_deref(),
+            Some("Department of Public Works")
+        );
+
+        assert_eq!(title_from_text("1234 5678\n---\n"), None);
+    }
+
+    #[test]
+    fn test_clean_title_truncates_at_word() {
+        let long = "word ".repeat(40);
+        let title = clean_title(&long);
+        assert!(title.chars().count() <= MAX_TITLE_LEN);
+        assert!(title.ends_with("word"));
+    }
+}
@@ -0,0 +1,324 @@
+//! Crowdsourced transcription of pages OCR can't read.
+//!
+//! Pages without usable text, typically handwriting, are packaged into
+//! tasks. Volunteers are handed one task at a time and type what they see
+//! on the page image, without seeing each other's work. Two matching
+//! transcriptions are accepted as the page's text, which then goes through
+//! the same path as a curator's correction. When the first two differ a
+//! third volunteer breaks the tie; if all three differ the task waits for
+//! a curator.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::repository::DieselDocumentRepository;
+use crate::services::page_text;
+
+/// Pages whose best OCR quality is below this are queued by default.
+pub const DEFAULT_MAX_QUALITY: f64 = 0.6;
+
+/// How long a volunteer keeps a task before it is handed to someone else.
+pub const ASSIGNMENT_MINUTES: i64 = 60;
+
+/// Transcriptions collected before a task goes to a curator.
+const MAX_ENTRIES: usize = 3;
+
+/// Where a task is in transcription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    /// Waiting for its first two transcriptions.
+    Open,
+    /// The first two differ; waiting for a third.
+    Disputed,
+    /// No two transcriptions agree; a curator decides.
+    Review,
+    /// Text accepted into the page.
+    Accepted,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Disputed => "disputed",
+            Self::Review => "review",
+            Self::Accepted => "accepted",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(Self::Open),
+            "disputed" => Some(Self::Disputed),
+            "review" => Some(Self::Review),
+            "accepted" => Some(Self::Accepted),
+            _ => None,
+        }
+    }
+
+    /// How many volunteers the task needs in this state, counting those
+    /// already done.
+    pub fn volunteers_wanted(&self) -> usize {
+        match self {
+            Self::Open => 2,
+            Self::Disputed => MAX_ENTRIES,
+            Self::Review | Self::Accepted => 0,
+        }
+    }
+}
+
+/// A page packaged for transcription.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionTask {
+    pub id: i32,
+    pub page_id: i64,
+    pub document_id: String,
+    pub version_id: i64,
+    pub page_number: u32,
+    pub status: TaskStatus,
+    pub accepted_text: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One volunteer's work on a task; `text` is `None` until submitted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionEntry {
+    pub volunteer: String,
+    pub text: Option<String>,
+    pub assigned_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+/// What the transcriptions of a task add up to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reconciliation {
+    /// Fewer than two transcriptions so far.
+    Waiting,
+    /// Two volunteers typed the same text.
+    Agreed {
+        text: String,
+        volunteers: [String; 2],
+    },
+    /// They differ; another volunteer is needed.
+    Disputed,
+    /// None agree and no more volunteers are asked.
+    Review,
+}
+
+/// Text as compared between transcriptions: runs of whitespace, including
+/// line breaks, count as one space.
+pub fn comparable_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Compare submitted transcriptions, given as `(volunteer, text)` in the
+/// order they came in. The earlier of two agreeing transcriptions is
+/// accepted, keeping its line breaks.
+pub fn reconcile(entries: &[(&str, &str)]) -> Reconciliation {
+    let comparable: Vec<String> = entries.iter().map(|(_, t)| comparable_text(t)).collect();
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            if comparable[i] == comparable[j] {
+                return Reconciliation::Agreed {
+                    text: page_text::normalize_text(entries[i].1),
+                    volunteers: [entries[i].0.to_string(), entries[j].0.to_string()],
+                };
+            }
+        }
+    }
+    match entries.len() {
+        0 | 1 => Reconciliation::Waiting,
+        n if n < MAX_ENTRIES => Reconciliation::Disputed,
+        _ => Reconciliation::Review,
+    }
+}
+
+/// Package pages for transcription: pages with no text, or whose best OCR
+/// result scores below `max_quality`, that no curator has verified and
+/// that aren't queued already. Returns how many were queued.
+pub async fn queue_pages(
+    doc_repo: &DieselDocumentRepository,
+    source_id: Option<&str>,
+    max_quality: f64,
+    limit: u32,
+) -> anyhow::Result<usize> {
+    let page_ids = doc_repo
+        .transcription_candidates(source_id, max_quality, limit)
+        .await?;
+    Ok(doc_repo.create_transcription_tasks(&page_ids).await?)
+}
+
+/// Hand `volunteer` a task: the one they already hold, or the oldest that
+/// needs another transcription they haven't given. Assignments older than
+/// [`ASSIGNMENT_MINUTES`] are released first.
+pub async fn next_task(
+    doc_repo: &DieselDocumentRepository,
+    volunteer: &str,
+) -> anyhow::Result<Option<TranscriptionTask>> {
+    let volunteer = volunteer.trim();
+    if volunteer.is_empty() {
+        anyhow::bail!("volunteer is required");
+    }
+    let cutoff = Utc::now() - Duration::minutes(ASSIGNMENT_MINUTES);
+    doc_repo.release_transcription_assignments(cutoff).await?;
+    let Some(id) = doc_repo.assign_transcription_task(volunteer).await? else {
+        return Ok(None);
+    };
+    Ok(doc_repo.get_transcription_task(id).await?)
+}
+
+/// Record `volunteer`'s transcription of a task and reconcile it with the
+/// others. Matching transcriptions become the page's text.
+pub async fn submit(
+    doc_repo: &DieselDocumentRepository,
+    task_id: i32,
+    volunteer: &str,
+    text: &str,
+) -> anyhow::Result<TranscriptionTask> {
+    let volunteer = volunteer.trim();
+    if volunteer.is_empty() {
+        anyhow::bail!("volunteer is required");
+    }
+    let text = page_text::normalize_text(text);
+    if text.is_empty() {
+        anyhow::bail!("text is required");
+    }
+    if text.chars().count() > page_text::MAX_PAGE_CHARS {
+        anyhow::bail!(
+            "text is longer than {} characters",
+            page_text::MAX_PAGE_CHARS
+        );
+    }
+
+    let task = load_task(doc_repo, task_id).await?;
+    if task.status.volunteers_wanted() == 0 {
+        anyhow::bail!("Task {} is already {}", task_id, task.status.as_str());
+    }
+    let entries = doc_repo.get_transcription_entries(task_id).await?;
+    match entries.iter().find(|e| e.volunteer == volunteer) {
+        Some(entry) if entry.text.is_some() => {
+            anyhow::bail!("You already transcribed task {}", task_id)
+        }
+        Some(_) => {}
+        None if entries.len() >= task.status.volunteers_wanted() => {
+            anyhow::bail!("Task {} doesn't need another transcription", task_id)
+        }
+        None => {}
+    }
+    doc_repo
+        .save_transcription(task_id, volunteer, &text)
+        .await?;
+
+    let entries = doc_repo.get_transcription_entries(task_id).await?;
+    let submitted: Vec<(&str, &str)> = entries
+        .iter()
+        .filter_map(|e| Some((e.volunteer.as_str(), e.text.as_deref()?)))
+        .collect();
+    match reconcile(&submitted) {
+        Reconciliation::Waiting => {}
+        Reconciliation::Agreed { text, volunteers } => {
+            accept(doc_repo, &task, &text, &volunteers.join(" & ")).await?;
+        }
+        Reconciliation::Disputed => {
+            doc_repo
+                .set_transcription_task_status(task_id, TaskStatus::Disputed, None)
+                .await?;
+        }
+        Reconciliation::Review => {
+            doc_repo
+                .set_transcription_task_status(task_id, TaskStatus::Review, None)
+                .await?;
+        }
+    }
+    load_task(doc_repo, task_id).await
+}
+
+/// Settle a task with a curator's text, whatever the volunteers typed.
+pub async fn resolve(
+    doc_repo: &DieselDocumentRepository,
+    task_id: i32,
+    text: &str,
+    curator: &str,
+) -> anyhow::Result<TranscriptionTask> {
+    let task = load_task(doc_repo, task_id).await?;
+    if task.status == TaskStatus::Accepted {
+        anyhow::bail!("Task {} is already accepted", task_id);
+    }
+    accept(doc_repo, &task, &page_text::normalize_text(text), curator).await?;
+    load_task(doc_repo, task_id).await
+}
+
+/// Make `text` the page's text, as a correction by `editor`.
+async fn accept(
+    doc_repo: &DieselDocumentRepository,
+    task: &TranscriptionTask,
+    text: &str,
+    editor: &str,
+) -> anyhow::Result<()> {
+    let page = doc_repo
+        .get_page(&task.document_id, task.version_id as i32, task.page_number)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Page of task {} not found", task.id))?;
+    page_text::edit_page_text(doc_repo, &page, text, editor).await?;
+    doc_repo
+        .set_transcription_task_status(task.id, TaskStatus::Accepted, Some(text))
+        .await?;
+    Ok(())
+}
+
+async fn load_task(
+    doc_repo: &DieselDocumentRepository,
+    id: i32,
+) -> anyhow::Result<TranscriptionTask> {
+    doc_repo
+        .get_transcription_task(id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Transcription task {} not found", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile() {
+        assert_eq!(reconcile(&[]), Reconciliation::Waiting);
+        assert_eq!(reconcile(&[("ann", "Dear Sir,")]), Reconciliation::Waiting);
+        assert_eq!(
+            reconcile(&[
+                ("ann", "Dear Sir,\nI write"),
+                ("bob", "Dear  Sir, I write ")
+            ]),
+            Reconciliation::Agreed {
+                text: "Dear Sir,\nI write".to_string(),
+                volunteers: ["ann".to_string(), "bob".to_string()],
+            }
+        );
+        assert_eq!(
+            reconcile(&[("ann", "Dear Sir,"), ("bob", "Dear Sin,")]),
+            Reconciliation::Disputed
+        );
+        // A third volunteer siding with either settles it
+        assert_eq!(
+            reconcile(&[
+                ("ann", "Dear Sir,"),
+                ("bob", "Dear Sin,"),
+                ("cy", "Dear Sin,")
+            ]),
+            Reconciliation::Agreed {
+                text: "Dear Sin,".to_string(),
+                volunteers: ["bob".to_string(), "cy".to_string()],
+            }
+        );
+        assert_eq!(
+            reconcile(&[
+                ("ann", "Dear Sir,"),
+                ("bob", "Dear Sin,"),
+                ("cy", "Dean Sir,")
+            ]),
+            Reconciliation::Review
+        );
+    }
+}
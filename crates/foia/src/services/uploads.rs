@@ -0,0 +1,327 @@
+//! Documents contributed by users, and their moderation.
+//!
+//! Trusted contributors (holders of a read-write API key) can submit a file
+//! with a description and where it came from. It is stored and processed
+//! like an imported file, but kept internal behind a document access rule
+//! until a moderator approves it. Rejected uploads stay internal, so the
+//! decision can be revisited; deleting the document removes the upload.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DocumentVersion, Source, SourceType};
+use crate::repository::{DieselDocumentRepository, DieselSourceRepository};
+use crate::services::access::{AccessScope, Visibility};
+use crate::storage::{save_document_async, DocumentInput};
+use crate::utils::guess_mime_from_filename;
+
+/// Source that uploads are filed under when none is given.
+pub const UPLOADS_SOURCE_ID: &str = "uploads";
+
+/// Note on the access rule hiding an upload until it is reviewed.
+const PENDING_NOTE: &str = "Upload pending moderation";
+
+/// Note on the access rule of a rejected upload.
+const REJECTED_NOTE: &str = "Upload rejected in moderation";
+
+/// Where an upload is in moderation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl UploadStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// A contributed document and its review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Upload {
+    pub id: i32,
+    pub document_id: String,
+    /// Who submitted it: the API key's name, or the operator.
+    pub contributor: String,
+    pub description: Option<String>,
+    /// Where the contributor got the document, e.g. a records request.
+    pub attribution: Option<String>,
+    pub status: UploadStatus,
+    pub submitted_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reviewed_by: Option<String>,
+    /// Moderator's reason, shown to the contributor.
+    pub review_note: Option<String>,
+}
+
+/// A file submitted for upload.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UploadRequest {
+    /// Name of the file on the contributor's side.
+    pub filename: String,
+    pub content: Vec<u8>,
+    /// Declared MIME type; the content is sniffed first.
+    pub content_type: Option<String>,
+    /// Document title (default: the file name without extension).
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub attribution: Option<String>,
+    /// Source to file the document under (default: `uploads`). Created if
+    /// it does not exist.
+    pub source_id: Option<String>,
+    pub tags: Vec<String>,
+    pub contributor: String,
+}
+
+impl UploadRequest {
+    pub fn source_id(&self) -> &str {
+        self.source_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(UPLOADS_SOURCE_ID)
+    }
+
+    /// Check the file is usable, returning a message for the contributor.
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.filename.trim();
+        if name.is_empty() {
+            return Err("filename is required".to_string());
+        }
+        if name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(format!("invalid filename: {}", name));
+        }
+        if self.content.is_empty() {
+            return Err("uploaded file is empty".to_string());
+        }
+        if self.contributor.trim().is_empty() {
+            return Err("contributor is required".to_string());
+        }
+        Ok(())
+    }
+
+    /// MIME type of the content: sniffed, then declared, then guessed from
+    /// the file name.
+    pub fn mime_type(&self) -> String {
+        if let Some(kind) = infer::get(&self.content) {
+            return kind.mime_type().to_string();
+        }
+        self.content_type
+            .as_deref()
+            .map(|ct| ct.split(';').next().unwrap_or("").trim().to_lowercase())
+            .filter(|ct| !ct.is_empty() && ct != "application/octet-stream")
+            .unwrap_or_else(|| guess_mime_from_filename(&self.filename).to_string())
+    }
+
+    fn title(&self) -> String {
+        self.title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                let name = self.filename.trim();
+                name.rsplit_once('.')
+                    .map_or(name, |(stem, _)| stem)
+                    .to_string()
+            })
+    }
+}
+
+/// Store an uploaded file as an internal document awaiting moderation.
+///
+/// Uploads are addressed by content hash, so the same file submitted twice
+/// is refused rather than stored again.
+pub async fn store_upload(
+    doc_repo: &DieselDocumentRepository,
+    source_repo: &DieselSourceRepository,
+    documents_dir: &Path,
+    request: &UploadRequest,
+) -> anyhow::Result<Upload> {
+    request.validate().map_err(anyhow::Error::msg)?;
+    let source_id = request.source_id();
+    ensure_source(source_repo, source_id).await?;
+
+    let filename = request.filename.trim();
+    let content_hash = DocumentVersion::compute_hash(&request.content);
+    let url = format!(
+        "upload://{}/{}/{}",
+        source_id,
+        &content_hash[..16],
+        filename
+    );
+    if let Some(existing) = doc_repo.get_by_url(&url).await?.into_iter().next() {
+        anyhow::bail!("This file was already uploaded as document {}", existing.id);
+    }
+
+    let now = Utc::now();
+    let input = DocumentInput {
+        url: url.clone(),
+        title: request.title(),
+        mime_type: request.mime_type(),
+        metadata: serde_json::json!({
+            "acquired": "upload",
+            "contributor": request.contributor.trim(),
+            "attribution": request.attribution,
+            "description": request.description,
+        }),
+        original_filename: Some(filename.to_string()),
+        server_date: None,
+    };
+    save_document_async(doc_repo, &request.content, &input, source_id, documents_dir).await?;
+    let mut doc = super::acquire::load_saved_document(doc_repo, &url).await?;
+    if super::acquire::add_tags(&mut doc, &request.tags) {
+        doc_repo.save(&doc).await?;
+    }
+
+    doc_repo
+        .set_access_rule(
+            AccessScope::Document,
+            &doc.id,
+            Visibility::Internal,
+            Some(PENDING_NOTE),
+        )
+        .await?;
+    let upload = Upload {
+        id: 0,
+        document_id: doc.id,
+        contributor: request.contributor.trim().to_string(),
+        description: clean(request.description.as_deref()),
+        attribution: clean(request.attribution.as_deref()),
+        status: UploadStatus::Pending,
+        submitted_at: now,
+        reviewed_at: None,
+        reviewed_by: None,
+        review_note: None,
+    };
+    Ok(doc_repo.create_upload(&upload).await?)
+}
+
+/// Approve or reject a pending upload. Approval lifts the hold, so the
+/// document follows its source's visibility.
+pub async fn review_upload(
+    doc_repo: &DieselDocumentRepository,
+    id: i32,
+    decision: UploadStatus,
+    reviewer: &str,
+    note: Option<&str>,
+) -> anyhow::Result<Upload> {
+    if decision == UploadStatus::Pending {
+        anyhow::bail!("an upload can only be approved or rejected");
+    }
+    let Some(upload) = doc_repo.get_upload(id).await? else {
+        anyhow::bail!("Upload {} not found", id);
+    };
+    if upload.status != UploadStatus::Pending {
+        anyhow::bail!("Upload {} was already {}", id, upload.status.as_str());
+    }
+
+    match decision {
+        UploadStatus::Approved => {
+            doc_repo
+                .clear_access_rule(AccessScope::Document, &upload.document_id)
+                .await?;
+        }
+        _ => {
+            doc_repo
+                .set_access_rule(
+                    AccessScope::Document,
+                    &upload.document_id,
+                    Visibility::Internal,
+                    Some(REJECTED_NOTE),
+                )
+                .await?;
+        }
+    }
+    let note = clean(note);
+    doc_repo
+        .review_upload(id, decision, reviewer, note.as_deref())
+        .await?;
+    doc_repo
+        .get_upload(id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Upload {} missing after review", id))
+}
+
+/// Create `source_id` as a custom source if it does not exist yet.
+async fn ensure_source(
+    source_repo: &DieselSourceRepository,
+    source_id: &str,
+) -> anyhow::Result<()> {
+    if source_repo.get(source_id).await?.is_some() {
+        return Ok(());
+    }
+    let source = Source {
+        id: source_id.to_string(),
+        name: if source_id == UPLOADS_SOURCE_ID {
+            "Contributed uploads".to_string()
+        } else {
+            source_id.to_string()
+        },
+        source_type: SourceType::Custom,
+        base_url: format!("upload://{}", source_id),
+        metadata: serde_json::json!({}),
+        created_at: Utc::now(),
+        last_scraped: None,
+        paused: false,
+    };
+    source_repo.save(&source).await?;
+    Ok(())
+}
+
+/// Trimmed text, `None` when blank.
+fn clean(text: Option<&str>) -> Option<String> {
+    text.map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_request_validation_and_mime() {
+        let mut request = UploadRequest {
+            filename: "response letter.pdf".to_string(),
+            content: b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec(),
+            contributor: "Newsroom".to_string(),
+            ..Default::default()
+        };
+        assert!(request.validate().is_ok());
+        assert_eq!(request.mime_type(), "application/pdf");
+        assert_eq!(request.title(), "response letter");
+        assert_eq!(request.source_id(), UPLOADS_SOURCE_ID);
+
+        request.content = b"plain notes".to_vec();
+        request.content_type = Some("text/plain; charset=utf-8".to_string());
+        assert_eq!(request.mime_type(), "text/plain");
+        request.content_type = Some("application/octet-stream".to_string());
+        request.filename = "notes.csv".to_string();
+        assert_eq!(request.mime_type(), "text/csv");
+
+        request.filename = "../etc/passwd".to_string();
+        assert!(request.validate().unwrap_err().contains("invalid filename"));
+        request.filename = "notes.csv".to_string();
+        request.content.clear();
+        assert!(request.validate().unwrap_err().contains("empty"));
+    }
+}
@@ -1,9 +1,11 @@
 //! Storage helpers for document content on disk.
 
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 
+use crate::encryption::{self, EncryptionError};
 use crate::models::{Document, DocumentVersion};
 use crate::repository::{extract_filename_parts, sanitize_filename, DieselDocumentRepository};
 
@@ -105,7 +107,7 @@ pub fn compute_storage_path_with_dedup(
         }
 
         // File exists - check if same content
-        if let Ok(existing) = std::fs::read(&abs) {
+        if let Ok(existing) = read_content(&abs) {
             if DocumentVersion::compute_hash(&existing) == content_hash {
                 let idx = if dedup_index == 0 {
                     None
@@ -150,7 +152,7 @@ pub async fn save_document_async(
     if let Some(parent) = abs_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    std::fs::write(&abs_path, content)?;
+    write_content(&abs_path, content)?;
 
     let mut version = DocumentVersion::new_with_metadata(
         content,
@@ -188,7 +190,9 @@ pub fn mime_to_extension(mime: &str) -> &'static str {
     match mime {
         "application/pdf" => "pdf",
         "text/html" => "html",
+        "multipart/related" | "application/x-mimearchive" => "mhtml",
         "text/plain" => "txt",
+        "text/csv" => "csv",
         "application/json" => "json",
         "application/xml" | "text/xml" => "xml",
         "image/jpeg" => "jpg",
@@ -198,6 +202,12 @@ pub fn mime_to_extension(mime: &str) -> &'static str {
         "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => "docx",
         "application/vnd.ms-excel" => "xls",
         "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => "xlsx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation" => "pptx",
+        "application/vnd.oasis.opendocument.text" => "odt",
+        "application/vnd.oasis.opendocument.spreadsheet" => "ods",
+        "application/vnd.oasis.opendocument.presentation" => "odp",
+        "application/rtf" | "text/rtf" => "rtf",
+        "application/vnd.wordperfect" => "wpd",
         "application/zip" => "zip",
         "application/gzip" => "gz",
         _ => "bin",
@@ -220,11 +230,144 @@ pub fn save_version_content(
     if let Some(parent) = content_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    std::fs::write(&content_path, content)?;
+    write_content(&content_path, content)?;
 
     Ok(content_path)
 }
 
+/// Write document content to disk, encrypting it when a content key is
+/// installed (see [`crate::encryption`]).
+pub fn write_content(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    match encryption::content_key() {
+        Some(key) => std::fs::write(path, key.encrypt(content)),
+        None => std::fs::write(path, content),
+    }
+}
+
+/// Async variant of [`write_content`].
+pub async fn write_content_async(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    match encryption::content_key() {
+        Some(key) => tokio::fs::write(path, key.encrypt(content)).await,
+        None => tokio::fs::write(path, content).await,
+    }
+}
+
+/// Read document content from disk, decrypting encrypted files.
+///
+/// Plain files are returned as-is whether or not encryption is enabled, so
+/// a partially migrated documents directory stays readable.
+pub fn read_content(path: &Path) -> std::io::Result<Vec<u8>> {
+    decode_content(path, std::fs::read(path)?)
+}
+
+/// Async variant of [`read_content`].
+pub async fn read_content_async(path: &Path) -> std::io::Result<Vec<u8>> {
+    decode_content(path, tokio::fs::read(path).await?)
+}
+
+fn decode_content(path: &Path, data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+    if !encryption::is_encrypted(&data) {
+        return Ok(data);
+    }
+    let key = encryption::content_key().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            EncryptionError::NoKey(path.display().to_string()),
+        )
+    })?;
+    key.decrypt(&data)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Check whether a stored file is encrypted (without reading all of it).
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut header = [0u8; encryption::MAGIC.len()];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .is_ok_and(|_| encryption::is_encrypted(&header))
+}
+
+/// Rewrite a stored file encrypted (`encrypt = true`) or as plaintext.
+///
+/// Returns `false` if the file was already in the requested form. The new
+/// content is written beside the original and renamed over it, so an
+/// interrupted migration never leaves a half-written document.
+pub fn migrate_file(
+    path: &Path,
+    key: &encryption::ContentKey,
+    encrypt: bool,
+) -> std::io::Result<bool> {
+    let data = std::fs::read(path)?;
+    if encryption::is_encrypted(&data) == encrypt {
+        return Ok(false);
+    }
+    let rewritten = if encrypt {
+        key.encrypt(&data)
+    } else {
+        key.decrypt(&data)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+    };
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    std::fs::write(&tmp_path, rewritten)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(true)
+}
+
+/// A path to document content that external tools (pdftotext, tesseract,
+/// whisper, ...) can read directly.
+///
+/// Plain files are used in place. Encrypted files are decrypted to a
+/// temporary file with the same extension, removed when this is dropped.
+pub enum PlaintextPath {
+    Original(PathBuf),
+    Decrypted(tempfile::TempPath),
+}
+
+impl PlaintextPath {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Original(path) => path,
+            Self::Decrypted(temp) => temp,
+        }
+    }
+}
+
+impl std::ops::Deref for PlaintextPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl AsRef<Path> for PlaintextPath {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+/// Resolve a stored file to a [`PlaintextPath`].
+pub fn plaintext_path(path: &Path) -> std::io::Result<PlaintextPath> {
+    if !is_encrypted_file(path) {
+        return Ok(PlaintextPath::Original(path.to_path_buf()));
+    }
+    let content = read_content(path)?;
+    let suffix = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let mut file = tempfile::Builder::new()
+        .prefix("foia-")
+        .suffix(&suffix)
+        .tempfile()?;
+    file.write_all(&content)?;
+    file.flush()?;
+    Ok(PlaintextPath::Decrypted(file.into_temp_path()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,6 +476,46 @@ mod tests {
         assert_eq!(parent_name.len(), 2);
     }
 
+    #[test]
+    fn test_plain_content_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("doc.pdf");
+
+        write_content(&path, b"plain content").unwrap();
+
+        assert!(!is_encrypted_file(&path));
+        assert_eq!(read_content(&path).unwrap(), b"plain content");
+        assert_eq!(plaintext_path(&path).unwrap().path(), path);
+    }
+
+    #[test]
+    fn test_read_encrypted_content_without_key_fails() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("doc.pdf");
+        let sealed = encryption::ContentKey::generate().encrypt(b"secret");
+        std::fs::write(&path, sealed).unwrap();
+
+        assert!(is_encrypted_file(&path));
+        let err = read_content(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn test_migrate_file_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("doc.pdf");
+        std::fs::write(&path, b"original").unwrap();
+        let key = encryption::ContentKey::generate();
+
+        assert!(migrate_file(&path, &key, true).unwrap());
+        assert!(is_encrypted_file(&path));
+        assert!(!migrate_file(&path, &key, true).unwrap());
+
+        assert!(migrate_file(&path, &key, false).unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), b"original");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
     #[test]
     fn test_compute_storage_path_with_dedup_no_collision() {
         let dir = tempdir().unwrap();
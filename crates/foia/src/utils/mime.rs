@@ -1,12 +1,14 @@
 //! MIME type categorization and display utilities.
 
 /// Known document file extensions (PDF, Office documents).
-const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx"];
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "rtf", "wpd",
+];
 
 /// Known file extensions (documents + images + archives).
 const FILE_EXTENSIONS: &[&str] = &[
-    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "jpg", "jpeg", "png", "gif", "tif", "tiff",
-    "bmp", "zip",
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "rtf", "wpd", "jpg",
+    "jpeg", "png", "gif", "tif", "tiff", "bmp", "zip",
 ];
 
 /// Guess MIME type from a filename's extension.
@@ -25,8 +27,16 @@ pub fn guess_mime_from_filename(name: &str) -> &'static str {
         "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
         "ppt" => "application/vnd.ms-powerpoint",
         "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "odp" => "application/vnd.oasis.opendocument.presentation",
+        "rtf" => "application/rtf",
+        "wpd" => "application/vnd.wordperfect",
         "txt" => "text/plain",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
         "html" | "htm" => "text/html",
+        "mht" | "mhtml" => "multipart/related",
         "jpg" | "jpeg" => "image/jpeg",
         "png" => "image/png",
         "gif" => "image/gif",
@@ -80,6 +90,22 @@ pub fn is_extractable_mimetype(mime_type: &str) -> bool {
             | "image/bmp"
             | "text/plain"
             | "text/html"
+            | "application/json"
+            | "multipart/related"
+            | "application/x-mimearchive"
+            | "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            | "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            | "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+            | "application/vnd.oasis.opendocument.text"
+            | "application/vnd.oasis.opendocument.spreadsheet"
+            | "application/vnd.oasis.opendocument.presentation"
+            | "application/msword"
+            | "application/rtf"
+            | "text/rtf"
+            | "application/vnd.wordperfect"
+            | "application/wordperfect"
+            | "text/csv"
+            | "text/tab-separated-values"
     )
 }
 
@@ -94,11 +120,20 @@ pub fn is_document_mimetype(mimetype: &str) -> bool {
             | "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
             | "application/vnd.ms-powerpoint"
             | "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+            | "application/vnd.oasis.opendocument.text"
+            | "application/vnd.oasis.opendocument.spreadsheet"
+            | "application/vnd.oasis.opendocument.presentation"
+            | "application/rtf"
+            | "application/vnd.wordperfect"
             | "text/html"
             | "application/xhtml+xml"
     )
 }
 
+/// Browse type that selects archive members and email attachments rather
+/// than a document category.
+pub const ATTACHMENTS_CATEGORY: &str = "attachments";
+
 /// MIME type categories for document classification.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MimeCategory {
@@ -175,6 +210,9 @@ pub fn mime_type_category(mime: &str) -> MimeCategory {
     } else if mime_lower == "application/pdf"
         || mime_lower.contains("word")
         || mime_lower == "application/msword"
+        || mime_lower == "application/vnd.oasis.opendocument.text"
+        || mime_lower.contains("rtf")
+        || mime_lower.contains("wordperfect")
         || mime_lower.contains("rfc822")
         || mime_lower.starts_with("message/")
         || (mime_lower.starts_with("text/") && mime_lower != "text/csv")
@@ -386,6 +424,7 @@ mod tests {
         assert_eq!(guess_mime_from_filename("notes.txt"), "text/plain");
         assert_eq!(guess_mime_from_filename("page.html"), "text/html");
         assert_eq!(guess_mime_from_filename("page.htm"), "text/html");
+        assert_eq!(guess_mime_from_filename("page.mhtml"), "multipart/related");
         assert_eq!(guess_mime_from_filename("photo.jpg"), "image/jpeg");
         assert_eq!(guess_mime_from_filename("photo.jpeg"), "image/jpeg");
         assert_eq!(guess_mime_from_filename("image.png"), "image/png");
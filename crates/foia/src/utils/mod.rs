@@ -13,7 +13,7 @@ pub use format::format_size;
 pub use mime::{
     category_to_mime_patterns, guess_mime_from_filename, guess_mime_from_url,
     has_document_extension, has_file_extension, is_document_mimetype, is_extractable_mimetype,
-    mime_icon, mime_to_category, mime_type_category, MimeCategory,
+    mime_icon, mime_to_category, mime_type_category, MimeCategory, ATTACHMENTS_CATEGORY,
 };
 pub use url_finder::UrlFinder;
 
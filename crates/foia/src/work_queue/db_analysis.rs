@@ -37,6 +37,7 @@ impl WorkQueue for DbAnalysisQueue {
                 filter.source_id.as_deref(),
                 filter.mime_type.as_deref(),
                 retry_hours,
+                &filter.exclude_sources,
             )
             .await?)
     }
@@ -57,6 +58,7 @@ impl WorkQueue for DbAnalysisQueue {
                 filter.mime_type.as_deref(),
                 cursor,
                 retry_hours,
+                &filter.exclude_sources,
             )
             .await?)
     }
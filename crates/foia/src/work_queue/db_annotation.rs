@@ -42,6 +42,7 @@ impl WorkQueue for DbAnnotationQueue {
                 &filter.work_type,
                 version,
                 filter.source_id.as_deref(),
+                &filter.exclude_sources,
             )
             .await?)
     }
@@ -59,6 +60,7 @@ impl WorkQueue for DbAnnotationQueue {
                 &filter.work_type,
                 version,
                 filter.source_id.as_deref(),
+                &filter.exclude_sources,
                 limit,
             )
             .await?)
@@ -27,6 +27,8 @@ pub struct WorkFilter {
     pub work_type: String,
     /// Restrict to a specific source.
     pub source_id: Option<String>,
+    /// Sources whose processing profile skips this work type.
+    pub exclude_sources: Vec<String>,
     /// Restrict to a specific MIME type.
     pub mime_type: Option<String>,
     /// Annotation schema version (used by annotation queues).
@@ -59,7 +59,7 @@ async fn count_needing_analysis_finds_documents_without_results() {
     create_test_doc(&repo, "doc-002", "test-source", "application/pdf").await;
 
     let count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 2);
@@ -92,7 +92,7 @@ async fn count_needing_analysis_skips_completed_documents() {
     .unwrap();
 
     let count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 1);
@@ -123,7 +123,7 @@ async fn count_needing_analysis_skips_recent_failures() {
     .unwrap();
 
     let count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 0, "Recent failure should be skipped");
@@ -155,7 +155,7 @@ async fn count_needing_analysis_retries_old_failures() {
 
     // Use a retry interval of 0 hours — all failures are eligible for retry
     let count = repo
-        .count_needing_analysis("ocr", None, None, 0)
+        .count_needing_analysis("ocr", None, None, 0, &[])
         .await
         .unwrap();
     assert_eq!(count, 1, "Old failure should be retried");
@@ -169,18 +169,39 @@ async fn count_needing_analysis_filters_by_source() {
     create_test_doc(&repo, "doc-003", "doj", "application/pdf").await;
 
     let count = repo
-        .count_needing_analysis("ocr", Some("doj"), None, 12)
+        .count_needing_analysis("ocr", Some("doj"), None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 2);
 
     let count = repo
-        .count_needing_analysis("ocr", Some("cia"), None, 12)
+        .count_needing_analysis("ocr", Some("cia"), None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 1);
 }
 
+#[tokio::test]
+async fn needing_analysis_skips_excluded_sources() {
+    let (repo, _dir) = setup_test_db().await;
+    create_test_doc(&repo, "doc-001", "doj", "application/pdf").await;
+    create_test_doc(&repo, "doc-002", "youtube", "video/mp4").await;
+
+    let excluded = vec!["youtube".to_string()];
+    let count = repo
+        .count_needing_analysis("ocr", None, None, 12, &excluded)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let docs = repo
+        .get_needing_analysis("ocr", 10, None, None, None, 12, &excluded)
+        .await
+        .unwrap();
+    assert_eq!(docs.len(), 1);
+    assert_eq!(docs[0].id, "doc-001");
+}
+
 #[tokio::test]
 async fn count_needing_analysis_filters_by_mime_type() {
     let (repo, _dir) = setup_test_db().await;
@@ -188,7 +209,7 @@ async fn count_needing_analysis_filters_by_mime_type() {
     create_test_doc(&repo, "doc-002", "test", "text/html").await;
 
     let count = repo
-        .count_needing_analysis("ocr", None, Some("application/pdf"), 12)
+        .count_needing_analysis("ocr", None, Some("application/pdf"), 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 1);
@@ -205,7 +226,7 @@ async fn count_needing_analysis_skips_failed_status_documents() {
         .unwrap();
 
     let count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 0, "Failed documents should be skipped");
@@ -222,7 +243,7 @@ async fn count_needing_analysis_includes_indexed_documents() {
         .unwrap();
 
     let count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(
@@ -256,13 +277,13 @@ async fn count_needing_analysis_different_types_are_independent() {
     .unwrap();
 
     let ocr_count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(ocr_count, 0, "OCR is complete");
 
     let whisper_count = repo
-        .count_needing_analysis("whisper", None, None, 12)
+        .count_needing_analysis("whisper", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(whisper_count, 1, "Whisper not done yet");
@@ -279,7 +300,7 @@ async fn get_needing_analysis_returns_eligible_documents() {
     create_test_doc(&repo, "doc-002", "test", "application/pdf").await;
 
     let docs = repo
-        .get_needing_analysis("ocr", 10, None, None, None, 12)
+        .get_needing_analysis("ocr", 10, None, None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(docs.len(), 2);
@@ -293,7 +314,7 @@ async fn get_needing_analysis_respects_limit() {
     create_test_doc(&repo, "doc-003", "test", "application/pdf").await;
 
     let docs = repo
-        .get_needing_analysis("ocr", 2, None, None, None, 12)
+        .get_needing_analysis("ocr", 2, None, None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(docs.len(), 2);
@@ -308,7 +329,7 @@ async fn get_needing_analysis_cursor_pagination() {
 
     // First page
     let page1 = repo
-        .get_needing_analysis("ocr", 2, None, None, None, 12)
+        .get_needing_analysis("ocr", 2, None, None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(page1.len(), 2);
@@ -316,7 +337,7 @@ async fn get_needing_analysis_cursor_pagination() {
     // Second page using cursor
     let last_id = &page1.last().unwrap().id;
     let page2 = repo
-        .get_needing_analysis("ocr", 2, None, None, Some(last_id), 12)
+        .get_needing_analysis("ocr", 2, None, None, Some(last_id), 12, &[])
         .await
         .unwrap();
     assert_eq!(page2.len(), 1);
@@ -348,7 +369,7 @@ async fn get_needing_analysis_excludes_completed() {
     .unwrap();
 
     let docs = repo
-        .get_needing_analysis("ocr", 10, None, None, None, 12)
+        .get_needing_analysis("ocr", 10, None, None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(docs.len(), 1);
@@ -391,13 +412,13 @@ async fn claim_analysis_locks_out_other_workers() {
 
     // Another worker should not see this document
     let count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 0, "Claimed document should be locked out");
 
     let docs = repo
-        .get_needing_analysis("ocr", 10, None, None, None, 12)
+        .get_needing_analysis("ocr", 10, None, None, None, 12, &[])
         .await
         .unwrap();
     assert!(
@@ -439,7 +460,7 @@ async fn completion_overwrites_pending_claim() {
     assert_eq!(pending, 0, "Pending should be overwritten by completion");
 
     let count = repo
-        .count_needing_analysis("ocr", None, None, 12)
+        .count_needing_analysis("ocr", None, None, 12, &[])
         .await
         .unwrap();
     assert_eq!(count, 0, "Completed document should not need analysis");
@@ -0,0 +1,144 @@
+//! Tests for the trigger-maintained statistics tables.
+//!
+//! Runs the real migrations so the SQLite triggers behind `count`,
+//! `get_tag_counts` and `get_type_stats` are exercised, and checks that
+//! `rebuild_stats` arrives at the same numbers.
+
+use std::collections::HashMap;
+
+use foia::models::{Document, DocumentStatus, DocumentVersion};
+use foia::repository::diesel_document::DieselDocumentRepository;
+use foia::repository::migrations;
+use foia::repository::pool::DbPool;
+
+/// Create a temporary SQLite database with all migrations applied.
+async fn setup_test_db() -> (DieselDocumentRepository, tempfile::TempDir) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = dir.path().join("test.db");
+    let db_url = db_path.display().to_string();
+
+    migrations::run_migrations(&db_url, false)
+        .await
+        .expect("Failed to run migrations");
+
+    let pool = DbPool::sqlite_from_path(&db_path);
+    let repo = DieselDocumentRepository::new(pool);
+    (repo, dir)
+}
+
+/// Create a test document with a version and save it.
+async fn create_test_doc(
+    repo: &DieselDocumentRepository,
+    id: &str,
+    source_id: &str,
+    mime_type: &str,
+) {
+    let version = DocumentVersion::new(
+        id.as_bytes(),
+        mime_type.to_string(),
+        Some(format!("https://example.com/{id}")),
+    );
+    let doc = Document::new(
+        id.to_string(),
+        source_id.to_string(),
+        format!("Test Document {id}"),
+        format!("https://example.com/{id}"),
+        version,
+        serde_json::json!({}),
+    );
+    repo.save_with_versions(&doc)
+        .await
+        .expect("Failed to save document");
+}
+
+fn tags(list: &[&str]) -> Vec<String> {
+    list.iter().map(|t| t.to_string()).collect()
+}
+
+#[tokio::test]
+async fn stats_follow_document_changes() {
+    let (repo, _dir) = setup_test_db().await;
+    create_test_doc(&repo, "doc-001", "source-a", "application/pdf").await;
+    create_test_doc(&repo, "doc-002", "source-a", "application/pdf").await;
+    create_test_doc(&repo, "doc-003", "source-b", "text/html").await;
+
+    assert_eq!(repo.count().await.unwrap(), 3);
+    assert_eq!(repo.count_by_source("source-a").await.unwrap(), 2);
+    let types = repo.get_type_stats().await.unwrap();
+    assert_eq!(types.get("application/pdf"), Some(&2));
+    assert_eq!(types.get("text/html"), Some(&1));
+
+    // Tags: duplicates within a document count once
+    repo.update_synopsis_and_tags("doc-001", None, &tags(&["budget", "budget", "fbi"]))
+        .await
+        .unwrap();
+    repo.update_synopsis_and_tags("doc-002", None, &tags(&["budget"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.get_tag_counts().await.unwrap(),
+        vec![("budget".to_string(), 2), ("fbi".to_string(), 1)]
+    );
+
+    // Retagging moves counts and drops tags nobody carries
+    repo.update_synopsis_and_tags("doc-001", None, &tags(&["cia"]))
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.get_all_tags().await.unwrap(),
+        vec!["budget".to_string(), "cia".to_string()]
+    );
+
+    // A new version changes the document's type
+    let version = DocumentVersion::new(
+        b"converted",
+        "text/plain".to_string(),
+        Some("https://example.com/doc-003".to_string()),
+    );
+    repo.add_version("doc-003", &version).await.unwrap();
+    let types = repo.get_type_stats().await.unwrap();
+    assert_eq!(types.get("text/html"), None);
+    assert_eq!(types.get("text/plain"), Some(&1));
+
+    repo.update_status("doc-003", DocumentStatus::Indexed)
+        .await
+        .unwrap();
+    let statuses = repo.get_source_status_counts().await.unwrap();
+    assert_eq!(statuses["source-b"].get("indexed"), Some(&1));
+    assert_eq!(statuses["source-b"].get("pending"), None);
+
+    repo.delete("doc-001").await.unwrap();
+    assert_eq!(repo.count().await.unwrap(), 2);
+    assert_eq!(
+        repo.get_tag_counts().await.unwrap(),
+        vec![("budget".to_string(), 1)]
+    );
+    assert_eq!(
+        repo.get_type_stats().await.unwrap().get("application/pdf"),
+        Some(&1)
+    );
+}
+
+#[tokio::test]
+async fn rebuild_stats_matches_triggers() {
+    let (repo, _dir) = setup_test_db().await;
+    create_test_doc(&repo, "doc-001", "source-a", "application/pdf").await;
+    create_test_doc(&repo, "doc-002", "source-b", "image/png").await;
+    repo.update_synopsis_and_tags("doc-002", None, &tags(&["photo", "photo"]))
+        .await
+        .unwrap();
+
+    let source_counts = repo.get_all_source_counts().await.unwrap();
+    let type_stats = repo.get_type_stats().await.unwrap();
+    let tag_counts = repo.get_tag_counts().await.unwrap();
+
+    repo.rebuild_stats().await.unwrap();
+
+    assert_eq!(repo.get_all_source_counts().await.unwrap(), source_counts);
+    assert_eq!(repo.get_type_stats().await.unwrap(), type_stats);
+    assert_eq!(repo.get_tag_counts().await.unwrap(), tag_counts);
+    assert_eq!(
+        source_counts,
+        HashMap::from([("source-a".to_string(), 1), ("source-b".to_string(), 1)])
+    );
+}